@@ -1,6 +1,7 @@
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
 import type { SavedSearch } from '../types';
+import { errorMessage } from '../utils/errors';
 
 function bareId(id: any): string {
   if (typeof id === 'string') {
@@ -38,7 +39,7 @@ export function useSavedSearches(pageId?: string) {
         new Date(b.data.createdAt).getTime() - new Date(a.data.createdAt).getTime()
       );
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e);
+      error.value = errorMessage(e);
       savedSearches.value = [];
     } finally {
       loading.value = false;
@@ -68,7 +69,7 @@ export function useSavedSearches(pageId?: string) {
       await invoke('upsert_record', { record });
       await loadSavedSearches();
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e);
+      error.value = errorMessage(e);
       throw e;
     } finally {
       loading.value = false;
@@ -83,7 +84,7 @@ export function useSavedSearches(pageId?: string) {
       await invoke('delete_record', { id });
       await loadSavedSearches();
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e);
+      error.value = errorMessage(e);
       throw e;
     } finally {
       loading.value = false;
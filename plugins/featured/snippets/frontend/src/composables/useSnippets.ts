@@ -1,6 +1,7 @@
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
 import type { Snippet, SnippetInput } from '../types';
+import { errorMessage } from '../utils/errors';
 
 function extractId(id: any): string {
   if (typeof id === 'string') return id;
@@ -43,7 +44,7 @@ export function useSnippets(pageId?: string) {
 
       snippets.value = filteredRecords.sort((a, b) => new Date(b.data.updatedAt).getTime() - new Date(a.data.updatedAt).getTime());
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e);
+      error.value = errorMessage(e);
       snippets.value = [];
     } finally {
       loading.value = false;
@@ -74,7 +75,7 @@ export function useSnippets(pageId?: string) {
       await invoke('upsert_record', { record });
       await loadSnippets();
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e);
+      error.value = errorMessage(e);
       throw e;
     } finally {
       loading.value = false;
@@ -106,7 +107,7 @@ export function useSnippets(pageId?: string) {
       await invoke('update_record', { id, record });
       await loadSnippets();
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e);
+      error.value = errorMessage(e);
       throw e;
     } finally {
       loading.value = false;
@@ -121,7 +122,7 @@ export function useSnippets(pageId?: string) {
       await invoke('delete_record', { id });
       await loadSnippets();
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e);
+      error.value = errorMessage(e);
       throw e;
     } finally {
       loading.value = false;
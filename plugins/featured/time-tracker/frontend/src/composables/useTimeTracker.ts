@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { calculateDuration } from '../utils/duration';
 import { calculateSummary, getTodayEntries, getThisWeekEntries } from '../utils/aggregation';
 import { generateCSV, downloadCSV } from '../utils/csv';
+import { errorMessage } from '../utils/errors';
 
 export interface RecordMetadata {
   tags?: string[];
@@ -141,7 +142,7 @@ export function useTimeTracker() {
         new Date(b.timestamp).getTime() - new Date(a.timestamp).getTime()
       );
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err);
+      error.value = errorMessage(err);
       console.error('Failed to load time entries:', err);
     } finally {
       loading.value = false;
@@ -191,7 +192,7 @@ export function useTimeTracker() {
       await invoke('upsert_record', { record: entry });
       await loadEntries();
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err);
+      error.value = errorMessage(err);
       console.error('Failed to start timer:', err);
       throw err;
     } finally {
@@ -230,7 +231,7 @@ export function useTimeTracker() {
       await invoke('upsert_record', { record: updated });
       await loadEntries();
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err);
+      error.value = errorMessage(err);
       console.error('Failed to stop timer:', err);
       throw err;
     } finally {
@@ -286,7 +287,7 @@ export function useTimeTracker() {
       await invoke('upsert_record', { record: entry });
       await loadEntries();
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err);
+      error.value = errorMessage(err);
       console.error('Failed to create entry:', err);
       throw err;
     } finally {
@@ -353,7 +354,7 @@ export function useTimeTracker() {
       await invoke('update_record', { id: recordId, record: updatedRecord });
       await loadEntries();
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err);
+      error.value = errorMessage(err);
       console.error('Failed to update entry:', err);
       throw err;
     } finally {
@@ -388,7 +389,7 @@ export function useTimeTracker() {
       await loadEntries();
       console.log('🗑️ Entries reloaded, count:', entries.value.length);
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err);
+      error.value = errorMessage(err);
       console.error('Failed to delete entry:', err);
       throw err;
     } finally {
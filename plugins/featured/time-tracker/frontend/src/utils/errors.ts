@@ -0,0 +1,11 @@
+/**
+ * Message of a rejected invoke() call; commands reject with an
+ * `{ code, message }` object rather than an Error
+ */
+export function errorMessage(error: unknown): string {
+  if (error instanceof Error) return error.message;
+  if (error && typeof error === 'object' && 'message' in error) {
+    return String((error as { message: unknown }).message);
+  }
+  return String(error);
+}
@@ -200,7 +200,10 @@ pub extern "C" fn fetch(config_ptr: *const c_char) -> *mut c_char {
 
     // Check status
     if response.status != 200 {
-        return create_error_response(&format!("HTTP error: {}", response.status));
+        return create_status_error_response(
+            response.status,
+            &format!("HTTP error: {}", response.status),
+        );
     }
 
     // TODO: Parse response.body and convert to StagedRecord format
@@ -269,3 +272,16 @@ fn create_error_response(message: &str) -> *mut c_char {
         .into_raw()
 }
 
+/// Error of a request the API answered with an error status
+/// The host retries deep syncs that fail with status 429 after a delay.
+fn create_status_error_response(status: u16, message: &str) -> *mut c_char {
+    let error = serde_json::json!({
+        "error": message,
+        "status": status
+    });
+
+    CString::new(error.to_string())
+        .unwrap_or_else(|_| CString::new("Unknown error").unwrap())
+        .into_raw()
+}
+
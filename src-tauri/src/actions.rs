@@ -0,0 +1,411 @@
+// Actions
+//
+// Invokable actions for the command palette. Core modules register theirs in
+// `ActionRegistry::new`, plugins declare them in the `actions` array of their
+// `manifest.json` and get them as `plugins.<name>.<id>`, run by the plugin's
+// `plugin_run_action` export. Every action carries a schema of its arguments,
+// so the palette can ask for them and `run_action` can check them before the
+// handler runs.
+
+use crate::backup;
+use crate::error::AppError;
+use crate::fetch_queue::FetchPriority;
+use crate::settings::SettingType;
+use crate::window;
+use crate::AppState;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+/// Owner of the actions registered by the app itself
+pub const CORE_OWNER: &str = "core";
+
+/// An argument of an action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionArg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub arg_type: SettingType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Allowed values (any when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<Value>,
+}
+
+impl ActionArg {
+    pub fn new(name: &str, arg_type: SettingType, label: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            arg_type,
+            label: Some(label.to_string()),
+            description: None,
+            required: false,
+            options: Vec::new(),
+        }
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+}
+
+/// A declared action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionDefinition {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<ActionArg>,
+    /// "core" or the plugin that declared the action
+    #[serde(default)]
+    pub owner: String,
+}
+
+impl ActionDefinition {
+    pub fn new(id: &str, name: &str, category: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: None,
+            category: Some(category.to_string()),
+            args: Vec::new(),
+            owner: CORE_OWNER.to_string(),
+        }
+    }
+
+    pub fn describe(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn arg(mut self, arg: ActionArg) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    /// Check arguments (an object, or null for none) against the schema
+    pub fn validate_args(&self, args: &Value) -> Result<(), AppError> {
+        let empty = serde_json::Map::new();
+        let values = match args {
+            Value::Null => &empty,
+            Value::Object(values) => values,
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Arguments of action {} must be an object, got {}",
+                    self.id, other
+                )))
+            }
+        };
+
+        if let Some(unknown) = values
+            .keys()
+            .find(|name| !self.args.iter().any(|arg| &arg.name == *name))
+        {
+            return Err(AppError::Validation(format!(
+                "Action {} has no argument {}",
+                self.id, unknown
+            )));
+        }
+        for arg in &self.args {
+            match values.get(&arg.name).filter(|value| !value.is_null()) {
+                None if arg.required => {
+                    return Err(AppError::Validation(format!(
+                        "Action {} needs the argument {}",
+                        self.id, arg.name
+                    )))
+                }
+                None => {}
+                Some(value) if !arg.arg_type.matches(value) => {
+                    return Err(AppError::Validation(format!(
+                        "Argument {} of action {} must be a {}, got {}",
+                        arg.name,
+                        self.id,
+                        arg.arg_type.as_str(),
+                        value
+                    )))
+                }
+                Some(value) if !arg.options.is_empty() && !arg.options.contains(value) => {
+                    return Err(AppError::Validation(format!(
+                        "Argument {} of action {} has no option {}",
+                        arg.name, self.id, value
+                    )))
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs an action with its (checked) arguments
+pub type ActionHandler =
+    Arc<dyn Fn(AppHandle, Value) -> BoxFuture<'static, Result<Value, AppError>> + Send + Sync>;
+
+/// Wrap an async function as an action handler
+pub fn handler<F, Fut>(f: F) -> ActionHandler
+where
+    F: Fn(AppHandle, Value) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Value, AppError>> + Send + 'static,
+{
+    Arc::new(move |app, args| Box::pin(f(app, args)))
+}
+
+pub struct ActionRegistry {
+    actions: BTreeMap<String, (ActionDefinition, ActionHandler)>,
+}
+
+impl ActionRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            actions: BTreeMap::new(),
+        };
+        for (definition, handler) in core_actions() {
+            if let Err(e) = registry.register(definition, handler) {
+                tracing::error!("Invalid core action: {}", e);
+            }
+        }
+        registry
+    }
+
+    /// Declare an action; ids are unique across owners
+    pub fn register(
+        &mut self,
+        definition: ActionDefinition,
+        handler: ActionHandler,
+    ) -> Result<(), AppError> {
+        if definition.id.trim().is_empty() || definition.name.trim().is_empty() {
+            return Err(AppError::Validation(
+                "Actions need an id and a name".to_string(),
+            ));
+        }
+        if let Some((existing, _)) = self.actions.get(&definition.id) {
+            if existing.owner != definition.owner {
+                return Err(AppError::Validation(format!(
+                    "Action {} is already declared by {}",
+                    definition.id, existing.owner
+                )));
+            }
+        }
+        self.actions
+            .insert(definition.id.clone(), (definition, handler));
+        Ok(())
+    }
+
+    /// Declare a plugin's actions under `plugins.<plugin>.`, replacing the
+    /// ones it declared before; they run through the plugin's backend
+    pub fn register_plugin_actions(
+        &mut self,
+        plugin: &str,
+        definitions: &[ActionDefinition],
+    ) -> Result<(), AppError> {
+        self.actions
+            .retain(|_, (definition, _)| definition.owner != plugin);
+        for definition in definitions {
+            let action = definition.id.clone();
+            let owner = plugin.to_string();
+            self.register(
+                ActionDefinition {
+                    id: format!("plugins.{}.{}", plugin, definition.id),
+                    category: definition
+                        .category
+                        .clone()
+                        .or_else(|| Some(format!("plugins.{}", plugin))),
+                    owner: plugin.to_string(),
+                    ..definition.clone()
+                },
+                handler(move |app, args| {
+                    let action = action.clone();
+                    let owner = owner.clone();
+                    async move {
+                        let state = app.state::<AppState>();
+                        let plugin = state.plugin_manager.lock().await.get_plugin(&owner);
+                        let plugin = plugin.ok_or_else(|| {
+                            AppError::Plugin(format!("Plugin {} has no loaded backend", owner))
+                        })?;
+                        plugin.run_action(&action, &args).await
+                    }
+                }),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Drop the actions of all plugins (before registering the loaded ones)
+    pub fn clear_plugin_actions(&mut self) {
+        self.actions
+            .retain(|_, (definition, _)| definition.owner == CORE_OWNER);
+    }
+
+    /// Declared actions, ordered by id
+    pub fn list(&self) -> Vec<ActionDefinition> {
+        self.actions
+            .values()
+            .map(|(definition, _)| definition.clone())
+            .collect()
+    }
+
+    /// An action and its handler; run it after releasing the registry, since
+    /// actions may change it (e.g. by reloading plugins)
+    pub fn get(&self, id: &str) -> Result<(ActionDefinition, ActionHandler), AppError> {
+        self.actions
+            .get(id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Action {}", id)))
+    }
+}
+
+/// Actions of the app's own modules
+fn core_actions() -> Vec<(ActionDefinition, ActionHandler)> {
+    vec![
+        (
+            ActionDefinition::new("fetch.all", "Fetch all data sources", "data")
+                .describe("Fetch every enabled data source now"),
+            handler(|app, _| async move {
+                let state = app.state::<AppState>();
+                let sources = state
+                    .data_source_service
+                    .read()
+                    .await
+                    .get_enabled_data_sources()
+                    .await?;
+                let summaries = futures::future::join_all(sources.iter().map(|s| {
+                    state
+                        .fetch_queue
+                        .run(s.to_adapter_config(), FetchPriority::Manual)
+                }))
+                .await;
+                Ok(json!(summaries))
+            }),
+        ),
+        (
+            ActionDefinition::new("fetch.source", "Fetch data source", "data")
+                .describe("Fetch one data source now, by source name or id")
+                .arg(ActionArg::new("source", SettingType::String, "Source").required()),
+            handler(|app, args| async move {
+                let source = args["source"].as_str().unwrap_or_default().to_string();
+                let state = app.state::<AppState>();
+                let data_source = state
+                    .data_source_service
+                    .read()
+                    .await
+                    .get_all_data_sources()
+                    .await?
+                    .into_iter()
+                    .find(|s| s.source == source || s.id == source)
+                    .ok_or_else(|| AppError::NotFound(format!("Data source {}", source)))?;
+                let summary = state
+                    .fetch_queue
+                    .run(data_source.to_adapter_config(), FetchPriority::Manual)
+                    .await;
+                Ok(json!(summary))
+            }),
+        ),
+        (
+            ActionDefinition::new("backup.create", "Create backup", "backup")
+                .describe("Write a backup now, using the configured folder and rotation"),
+            handler(|app, _| async move {
+                let state = app.state::<AppState>();
+                let config = {
+                    let settings = state.settings_service.read().await;
+                    backup::BackupConfig::load(&settings).await?
+                };
+                let backup =
+                    backup::write_backup(&state.database, &config.folder, config.max_keep).await?;
+                Ok(json!(backup))
+            }),
+        ),
+        (
+            ActionDefinition::new(
+                "dashboard.open_window",
+                "Open dashboard in window",
+                "dashboards",
+            )
+            .describe("Open a dashboard in a window of its own")
+            .arg(ActionArg::new("id", SettingType::String, "Dashboard").required()),
+            handler(|app, args| async move {
+                let id = args["id"].as_str().unwrap_or_default();
+                let dashboard = {
+                    let state = app.state::<AppState>();
+                    let db = state.database.lock().await;
+                    db.get_dashboard(id).await?
+                };
+                window::open_dashboard_window(&app, &dashboard.id, &dashboard.name)?;
+                Ok(Value::Null)
+            }),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop() -> ActionHandler {
+        handler(|_, _| async { Ok(Value::Null) })
+    }
+
+    #[test]
+    fn test_validate_args() {
+        let action = ActionDefinition::new("fetch.source", "Fetch data source", "data")
+            .arg(ActionArg::new("source", SettingType::String, "Source").required())
+            .arg(ActionArg {
+                options: vec![json!("manual"), json!("scheduled")],
+                ..ActionArg::new("priority", SettingType::String, "Priority")
+            });
+
+        assert!(action.validate_args(&json!({ "source": "gitlab" })).is_ok());
+        assert!(action
+            .validate_args(&json!({ "source": "gitlab", "priority": "manual" }))
+            .is_ok());
+        assert!(action.validate_args(&Value::Null).is_err());
+        assert!(action.validate_args(&json!({ "source": 1 })).is_err());
+        assert!(action
+            .validate_args(&json!({ "source": "gitlab", "priority": "urgent" }))
+            .is_err());
+        assert!(action
+            .validate_args(&json!({ "source": "gitlab", "limit": 5 }))
+            .is_err());
+        assert!(action.validate_args(&json!(["gitlab"])).is_err());
+    }
+
+    #[test]
+    fn test_plugin_actions() {
+        let mut registry = ActionRegistry::new();
+        assert!(registry.get("fetch.all").is_ok());
+
+        let declared = vec![ActionDefinition {
+            owner: String::new(),
+            category: None,
+            ..ActionDefinition::new("sync", "Sync issues", "")
+        }];
+        registry.register_plugin_actions("jira", &declared).unwrap();
+        let (action, _) = registry.get("plugins.jira.sync").unwrap();
+        assert_eq!(action.owner, "jira");
+        assert_eq!(action.category.as_deref(), Some("plugins.jira"));
+
+        // Ids stay with their owner
+        let taken = ActionDefinition {
+            owner: "other".to_string(),
+            ..action.clone()
+        };
+        assert!(registry.register(taken, noop()).is_err());
+
+        registry.clear_plugin_actions();
+        assert!(registry.get("plugins.jira.sync").is_err());
+        assert!(registry.get("fetch.all").is_ok());
+    }
+}
@@ -6,10 +6,17 @@
 
 use crate::db::StagedRecord;
 use crate::error::AppError;
+use crate::pipeline::Pipeline;
+use crate::redaction::{RedactionRules, REDACTED};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 pub mod rest;
+pub mod rss;
+pub mod s3;
+pub mod slack;
+pub mod ssh;
+pub mod weather;
 // gitlab module removed - functionality provided by gitlab-adapter plugin
 
 // ============================================================================
@@ -63,6 +70,18 @@ pub struct AdapterConfig {
 
     /// Whether this adapter is enabled
     pub enabled: bool,
+
+    /// Optional transformation pipeline applied before records are stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<Pipeline>,
+
+    /// Unique-key expression used to dedup records (e.g. "data.url"); defaults to data.id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_key: Option<String>,
+
+    /// Redaction rules applied to record data before it is stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<RedactionRules>,
 }
 
 impl AdapterConfig {
@@ -75,6 +94,9 @@ impl AdapterConfig {
             parameters: serde_json::json!({}),
             polling_interval: None,
             enabled: true,
+            pipeline: None,
+            unique_key: None,
+            redaction: None,
         }
     }
 }
@@ -83,7 +105,8 @@ impl AdapterConfig {
 // Authentication Configuration
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Debug output masks the secrets, so configs can be logged
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum AuthConfig {
     /// No authentication
@@ -118,6 +141,70 @@ pub enum AuthConfig {
 
     /// GitLab Personal Access Token
     GitLabToken { token: String },
+
+    /// AWS Signature V4 (S3 and S3-compatible storage)
+    AwsSigV4 {
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+    },
+
+    /// SSH private key (file path or PEM content), with optional passphrase
+    SshKey {
+        username: String,
+        private_key: String,
+        passphrase: Option<String>,
+    },
+
+    /// Named credential profile, swapped for the stored config before a fetch
+    CredentialRef { name: String },
+}
+
+impl AuthConfig {
+    /// The secret values of the config (tokens, passwords, keys)
+    pub fn secrets(&self) -> Vec<&str> {
+        match self {
+            AuthConfig::None | AuthConfig::CredentialRef { .. } => Vec::new(),
+            AuthConfig::Bearer { token } | AuthConfig::GitLabToken { token } => {
+                vec![token.as_str()]
+            }
+            AuthConfig::OAuth2ClientCredentials { client_secret, .. }
+            | AuthConfig::OAuth2AuthorizationCode { client_secret, .. } => {
+                vec![client_secret.as_str()]
+            }
+            AuthConfig::Basic { password, .. } => vec![password.as_str()],
+            AuthConfig::ApiKey { key, .. } => vec![key.as_str()],
+            AuthConfig::AwsSigV4 {
+                secret_access_key,
+                session_token,
+                ..
+            } => std::iter::once(secret_access_key.as_str())
+                .chain(session_token.as_deref())
+                .collect(),
+            AuthConfig::SshKey {
+                private_key,
+                passphrase,
+                ..
+            } => std::iter::once(private_key.as_str())
+                .chain(passphrase.as_deref())
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secrets = self.secrets();
+        let mut value = serde_json::to_value(self).map_err(|_| std::fmt::Error)?;
+        if let Some(fields) = value.as_object_mut() {
+            for field in fields.values_mut() {
+                if field.as_str().is_some_and(|s| secrets.contains(&s)) {
+                    *field = serde_json::Value::String(REDACTED.to_string());
+                }
+            }
+        }
+        write!(f, "AuthConfig({})", value)
+    }
 }
 
 // ============================================================================
@@ -137,6 +224,11 @@ impl AdapterRegistry {
 
         // Register built-in adapters
         registry.register(Box::new(rest::RestAdapter::new()));
+        registry.register(Box::new(s3::S3Adapter::new()));
+        registry.register(Box::new(ssh::SshAdapter::new()));
+        registry.register(Box::new(slack::SlackAdapter::new()));
+        registry.register(Box::new(weather::WeatherAdapter::new()));
+        registry.register(Box::new(rss::RssAdapter::new()));
         // GitLab adapter removed - functionality provided by gitlab-adapter plugin
 
         registry
@@ -191,12 +283,10 @@ impl Default for AdapterRegistry {
 pub struct HttpClient;
 
 impl HttpClient {
-    /// Create a new reqwest client with timeout
+    /// Create a new reqwest client following the network settings
+    /// (proxy, timeout, User-Agent)
     pub fn new_client() -> reqwest::Client {
-        reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client")
+        crate::network::client()
     }
 
     /// Add authentication headers to a request builder
@@ -223,6 +313,18 @@ impl HttpClient {
                 // Same as above
                 builder
             }
+            Some(AuthConfig::AwsSigV4 { .. }) => {
+                // Requests are signed per request by the S3 adapter
+                builder
+            }
+            Some(AuthConfig::SshKey { .. }) => {
+                // Not applicable to HTTP requests
+                builder
+            }
+            Some(AuthConfig::CredentialRef { .. }) => {
+                // Resolved from the credential store before the adapter runs
+                builder
+            }
         }
     }
 
@@ -250,24 +352,26 @@ impl HttpClient {
             .form(&params)
             .send()
             .await
-            .map_err(|e| AppError::Http(format!("OAuth2 token request failed: {}", e)))?;
+            .map_err(|e| AppError::request(&e, format!("OAuth2 token request failed: {}", e)))?;
 
         if !response.status().is_success() {
-            return Err(AppError::Http(format!(
-                "OAuth2 token request failed with status: {}",
-                response.status()
-            )));
+            return Err(AppError::http_status(
+                response.status().as_u16(),
+                format!(
+                    "OAuth2 token request failed with status: {}",
+                    response.status()
+                ),
+            ));
         }
 
-        let token_response: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| AppError::Http(format!("Failed to parse OAuth2 response: {}", e)))?;
+        let token_response: serde_json::Value = response.json().await.map_err(|e| {
+            AppError::request(&e, format!("Failed to parse OAuth2 response: {}", e))
+        })?;
 
         token_response["access_token"]
             .as_str()
             .map(|s| s.to_string())
-            .ok_or_else(|| AppError::Http("OAuth2 response missing access_token".to_string()))
+            .ok_or_else(|| AppError::http("OAuth2 response missing access_token"))
     }
 }
 
@@ -282,6 +386,11 @@ mod tests {
         // Check that built-in adapters are registered
         let types = registry.list_types();
         assert!(types.contains(&"rest_api".to_string()));
+        assert!(types.contains(&"s3".to_string()));
+        assert!(types.contains(&"ssh".to_string()));
+        assert!(types.contains(&"slack".to_string()));
+        assert!(types.contains(&"weather".to_string()));
+        assert!(types.contains(&"rss".to_string()));
 
         // GitLab is provided by a plugin in this repo, not a built-in adapter.
         assert!(!types.contains(&"gitlab".to_string()));
@@ -297,4 +406,20 @@ mod tests {
         assert!(config.enabled);
         assert!(config.auth.is_none());
     }
+
+    #[test]
+    fn test_auth_config_debug_masks_secrets() {
+        let mut config = AdapterConfig::new("s3", "backups", "https://s3.example.com");
+        config.auth = Some(AuthConfig::AwsSigV4 {
+            access_key_id: "AKID-visible".to_string(),
+            secret_access_key: "wJalrXUtnFEMI".to_string(),
+            session_token: Some("session-xyz".to_string()),
+        });
+
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("AKID-visible"));
+        assert!(!debug.contains("wJalrXUtnFEMI"));
+        assert!(!debug.contains("session-xyz"));
+        assert!(debug.contains(REDACTED));
+    }
 }
@@ -158,21 +158,21 @@ impl Adapter for RestAdapter {
         let response = request
             .send()
             .await
-            .map_err(|e| AppError::Http(format!("REST request failed: {}", e)))?;
+            .map_err(|e| AppError::request(&e, format!("REST request failed: {}", e)))?;
 
         // Check status
         if !response.status().is_success() {
-            return Err(AppError::Http(format!(
-                "REST API returned error status: {}",
-                response.status()
-            )));
+            return Err(AppError::http_status(
+                response.status().as_u16(),
+                format!("REST API returned error status: {}", response.status()),
+            ));
         }
 
         // Parse JSON response
         let json: Value = response
             .json()
             .await
-            .map_err(|e| AppError::Http(format!("Failed to parse JSON response: {}", e)))?;
+            .map_err(|e| AppError::request(&e, format!("Failed to parse JSON response: {}", e)))?;
 
         tracing::debug!("REST API response: {:?}", json);
 
@@ -205,7 +205,7 @@ impl Adapter for RestAdapter {
         let response = request
             .send()
             .await
-            .map_err(|e| AppError::Http(format!("Connection test failed: {}", e)))?;
+            .map_err(|e| AppError::request(&e, format!("Connection test failed: {}", e)))?;
 
         Ok(response.status().is_success())
     }
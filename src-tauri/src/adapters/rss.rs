@@ -0,0 +1,341 @@
+// RSS Adapter
+//
+// Fetches an RSS 2.0 or Atom feed from the endpoint and stages its items as
+// `rss_item` records (title, link, author, summary, publication date,
+// categories and enclosures). Items are keyed by their guid/id, so refetching
+// a feed updates them in place.
+//
+// Feeds are parsed with feed-rs into the format-independent `Feed`, which is
+// also what `fetch_rss_feed` returns to the feed reader plugin.
+
+use crate::adapters::{Adapter, AdapterConfig, HttpClient};
+use crate::db::{RecordMetadata, StagedRecord};
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Default number of items kept per fetch
+const DEFAULT_MAX_ITEMS: u64 = 50;
+
+pub struct RssAdapter;
+
+/// A feed independent of its format
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Feed {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub description: Option<String>,
+    pub items: Vec<FeedItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeedItem {
+    /// The guid/id, or an id feed-rs derives from the link and title
+    pub id: String,
+    pub title: String,
+    pub link: Option<String>,
+    /// Author names, comma separated
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    /// Full content (HTML) where the feed has it
+    pub content: Option<String>,
+    /// Publication date, the last update for Atom entries without one
+    pub published: Option<DateTime<Utc>>,
+    pub categories: Vec<String>,
+    pub enclosures: Vec<Enclosure>,
+}
+
+/// Media attached to an item (podcast audio, images)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Enclosure {
+    pub url: String,
+    pub media_type: Option<String>,
+    /// Size in bytes
+    pub length: Option<u64>,
+}
+
+impl RssAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Adapter for RssAdapter {
+    fn adapter_type(&self) -> &str {
+        "rss"
+    }
+
+    fn name(&self) -> &str {
+        "RSS / Atom Feed"
+    }
+
+    async fn fetch(&self, config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError> {
+        let client = HttpClient::new_client();
+        let feed = read_feed(HttpClient::add_auth(
+            client.get(&config.endpoint),
+            &config.auth,
+        ))
+        .await?;
+        let max_items = config.parameters["max_items"]
+            .as_u64()
+            .unwrap_or(DEFAULT_MAX_ITEMS) as usize;
+
+        let records: Vec<StagedRecord> = feed
+            .items
+            .into_iter()
+            .take(max_items)
+            .map(|item| item_record(config, feed.title.as_deref(), item))
+            .collect();
+
+        tracing::info!(
+            "Fetched {} feed items from {}",
+            records.len(),
+            config.endpoint
+        );
+        Ok(records)
+    }
+
+    async fn test_connection(&self, config: &AdapterConfig) -> Result<bool, AppError> {
+        match self.fetch(config).await {
+            Ok(_) => Ok(true),
+            Err(AppError::Http { message, .. }) => {
+                tracing::warn!("Feed connection test failed: {}", message);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn default_config(&self) -> AdapterConfig {
+        let mut config = AdapterConfig::new(
+            self.adapter_type(),
+            "rss",
+            "https://blog.rust-lang.org/feed.xml",
+        );
+
+        config.parameters = json!({
+            "max_items": DEFAULT_MAX_ITEMS,
+        });
+
+        config.polling_interval = Some(3600); // 1 hour
+
+        config
+    }
+}
+
+// ============================================================================
+// Feed Parsing
+// ============================================================================
+
+/// Send the feed request and parse the response
+pub async fn read_feed(request: reqwest::RequestBuilder) -> Result<Feed, AppError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::request(&e, format!("Feed request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::http_status(
+            response.status().as_u16(),
+            format!("Feed returned error status: {}", response.status()),
+        ));
+    }
+
+    let content = response
+        .bytes()
+        .await
+        .map_err(|e| AppError::request(&e, format!("Failed to read feed: {}", e)))?;
+    parse_feed(&content)
+}
+
+/// Parse an RSS 0.9x/1.0/2.0, Atom or JSON Feed document
+pub fn parse_feed(content: &[u8]) -> Result<Feed, AppError> {
+    let feed = feed_rs::parser::parse(content)
+        .map_err(|e| AppError::Adapter(format!("Not a valid RSS or Atom feed: {}", e)))?;
+
+    let text = |text: Option<feed_rs::model::Text>| {
+        text.map(|t| t.content.trim().to_string())
+            .filter(|t| !t.is_empty())
+    };
+    // The alternate link is the web page; Atom entries also link to
+    // themselves, their replies and their enclosures
+    let link = |links: &[feed_rs::model::Link]| {
+        links
+            .iter()
+            .find(|link| link.rel.as_deref().unwrap_or("alternate") == "alternate")
+            .or(links.first())
+            .map(|link| link.href.clone())
+    };
+
+    let items = feed
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let authors: Vec<String> = entry
+                .authors
+                .iter()
+                .map(|person| person.name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect();
+            let mut enclosures: Vec<Enclosure> = entry
+                .media
+                .iter()
+                .flat_map(|media| &media.content)
+                .filter_map(|content| {
+                    Some(Enclosure {
+                        url: content.url.as_ref()?.to_string(),
+                        media_type: content.content_type.as_ref().map(|t| t.to_string()),
+                        length: content.size,
+                    })
+                })
+                .collect();
+            let linked: Vec<Enclosure> = entry
+                .links
+                .iter()
+                .filter(|link| link.rel.as_deref() == Some("enclosure"))
+                .filter(|link| !enclosures.iter().any(|e| e.url == link.href))
+                .map(|link| Enclosure {
+                    url: link.href.clone(),
+                    media_type: link.media_type.clone(),
+                    length: link.length,
+                })
+                .collect();
+            enclosures.extend(linked);
+
+            FeedItem {
+                title: text(entry.title).unwrap_or_default(),
+                link: link(&entry.links),
+                author: (!authors.is_empty()).then(|| authors.join(", ")),
+                summary: text(entry.summary),
+                content: entry
+                    .content
+                    .and_then(|content| content.body)
+                    .filter(|body| !body.trim().is_empty()),
+                published: entry.published.or(entry.updated),
+                categories: entry
+                    .categories
+                    .into_iter()
+                    .map(|category| category.label.unwrap_or(category.term))
+                    .collect(),
+                enclosures,
+                id: entry.id,
+            }
+        })
+        .collect();
+
+    Ok(Feed {
+        title: text(feed.title),
+        link: link(&feed.links),
+        description: text(feed.description),
+        items,
+    })
+}
+
+fn item_record(config: &AdapterConfig, feed_title: Option<&str>, item: FeedItem) -> StagedRecord {
+    StagedRecord {
+        id: None, // Will be set by SurrealDB
+        record_type: "rss_item".to_string(),
+        source: config.source.clone(),
+        timestamp: item.published.unwrap_or_else(Utc::now),
+        data: json!({
+            "id": item.id,
+            "title": item.title,
+            "link": item.link,
+            "author": item.author,
+            "summary": item.summary,
+            "categories": item.categories,
+            "enclosures": item.enclosures,
+            "feed": feed_title,
+            "published": item.published,
+        }),
+        metadata: RecordMetadata {
+            tags: vec!["rss".to_string()],
+            status: None,
+            title: Some(item.title),
+            description: item.summary,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feed() {
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+              <channel>
+                <title>Example News</title>
+                <link>https://example.com</link>
+                <item>
+                  <title>First</title>
+                  <link>https://example.com/1</link>
+                  <guid isPermaLink="false">item-1</guid>
+                  <pubDate>Tue, 05 Mar 2024 10:00:00 +0000</pubDate>
+                  <dc:creator>Jane Doe</dc:creator>
+                  <category>releases</category>
+                  <description>One</description>
+                  <enclosure url="https://example.com/1.mp3" type="audio/mpeg" length="1024"/>
+                </item>
+                <item>
+                  <title>Second</title>
+                  <link>https://example.com/2</link>
+                </item>
+              </channel>
+            </rss>"#;
+        let feed = parse_feed(rss.as_bytes()).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Example News"));
+        assert_eq!(feed.items.len(), 2);
+        let first = &feed.items[0];
+        assert_eq!(first.id, "item-1");
+        assert_eq!(
+            first.published.unwrap().to_rfc3339(),
+            "2024-03-05T10:00:00+00:00"
+        );
+        assert_eq!(first.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(first.categories, vec!["releases"]);
+        assert_eq!(
+            first.enclosures,
+            vec![Enclosure {
+                url: "https://example.com/1.mp3".to_string(),
+                media_type: Some("audio/mpeg".to_string()),
+                length: Some(1024),
+            }]
+        );
+        // Without a guid the id is derived, the same on every fetch
+        let again = parse_feed(rss.as_bytes()).unwrap();
+        assert!(!feed.items[1].id.is_empty());
+        assert_eq!(feed.items[1].id, again.items[1].id);
+
+        let atom = r#"<?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+              <title type="text">Example Blog</title>
+              <entry>
+                <title type="html">Hello</title>
+                <link rel="self" href="https://example.com/api/hello"/>
+                <link rel="alternate" href="https://example.com/hello"/>
+                <id>urn:uuid:1</id>
+                <updated>2024-03-05T10:00:00Z</updated>
+                <author><name>Jane Doe</name></author>
+                <summary>Greeting</summary>
+              </entry>
+            </feed>"#;
+        let feed = parse_feed(atom.as_bytes()).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Example Blog"));
+        let entry = &feed.items[0];
+        assert_eq!(entry.id, "urn:uuid:1");
+        assert_eq!(entry.title, "Hello");
+        assert_eq!(entry.link.as_deref(), Some("https://example.com/hello"));
+        assert_eq!(entry.summary.as_deref(), Some("Greeting"));
+        assert_eq!(
+            entry.published,
+            Some("2024-03-05T10:00:00Z".parse().unwrap())
+        );
+
+        assert!(parse_feed(b"<html><body>Not a feed</body></html>").is_err());
+    }
+}
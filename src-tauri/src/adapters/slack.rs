@@ -0,0 +1,362 @@
+// Slack Adapter
+//
+// Pulls channel messages or reminders via the Slack Web API using a bot token
+// (Bearer auth) and cursor pagination. Messages are staged as `slack_message`
+// records with their thread and user metadata for team-activity widgets.
+
+use crate::adapters::{Adapter, AdapterConfig, AuthConfig, HttpClient};
+use crate::db::{RecordMetadata, StagedRecord};
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Page size for cursor-paginated requests (Slack allows up to 1000)
+const PAGE_SIZE: u64 = 200;
+
+/// Default cap on the number of messages fetched per run
+const DEFAULT_MAX_MESSAGES: usize = 1000;
+
+pub struct SlackAdapter;
+
+impl SlackAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Call a Slack Web API method and return the JSON body
+    /// Slack reports most errors as `{"ok": false, "error": "..."}` with HTTP 200
+    async fn call(
+        &self,
+        client: &reqwest::Client,
+        config: &AdapterConfig,
+        method: &str,
+        query: &[(&str, String)],
+    ) -> Result<Value, AppError> {
+        let url = format!("{}/{}", config.endpoint.trim_end_matches('/'), method);
+        let request = HttpClient::add_auth(client.get(&url).query(query), &config.auth);
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::request(&e, format!("Slack request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::http_status(
+                response.status().as_u16(),
+                format!("Slack API returned error status: {}", response.status()),
+            ));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::request(&e, format!("Failed to parse Slack response: {}", e)))?;
+
+        if body["ok"].as_bool() != Some(true) {
+            return Err(AppError::Adapter(format!(
+                "Slack API {} failed: {}",
+                method,
+                body["error"].as_str().unwrap_or("unknown error")
+            )));
+        }
+
+        Ok(body)
+    }
+
+    /// Fetch channel history (and optionally thread replies), following cursors
+    async fn fetch_messages(
+        &self,
+        client: &reqwest::Client,
+        config: &AdapterConfig,
+    ) -> Result<Vec<StagedRecord>, AppError> {
+        let channel = config.parameters["channel"]
+            .as_str()
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| {
+                AppError::Config("Slack adapter requires a 'channel' parameter".to_string())
+            })?;
+        let max_messages = config.parameters["max_messages"]
+            .as_u64()
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_MAX_MESSAGES);
+        let include_threads = config.parameters["include_threads"]
+            .as_bool()
+            .unwrap_or(false);
+
+        let mut query = vec![
+            ("channel", channel.to_string()),
+            ("limit", PAGE_SIZE.to_string()),
+        ];
+        if let Some(hours) = config.parameters["lookback_hours"].as_u64() {
+            let oldest = Utc::now() - chrono::Duration::hours(hours as i64);
+            query.push(("oldest", oldest.timestamp().to_string()));
+        }
+
+        let messages = self
+            .paginate(client, config, "conversations.history", query, max_messages)
+            .await?;
+
+        let mut records = Vec::with_capacity(messages.len());
+        for message in messages {
+            let has_replies = message["reply_count"].as_u64().unwrap_or(0) > 0;
+            let thread_ts = message["thread_ts"].as_str().map(String::from);
+
+            records.push(message_record(message, channel, config));
+
+            if include_threads && has_replies {
+                if let Some(thread_ts) = thread_ts {
+                    let query = vec![
+                        ("channel", channel.to_string()),
+                        ("ts", thread_ts.clone()),
+                        ("limit", PAGE_SIZE.to_string()),
+                    ];
+                    let replies = self
+                        .paginate(client, config, "conversations.replies", query, max_messages)
+                        .await?;
+
+                    // The first entry of a replies page is the parent message itself
+                    records.extend(
+                        replies
+                            .into_iter()
+                            .filter(|reply| reply["ts"].as_str() != Some(thread_ts.as_str()))
+                            .map(|reply| message_record(reply, channel, config)),
+                    );
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Collect `messages` from a cursor-paginated method
+    async fn paginate(
+        &self,
+        client: &reqwest::Client,
+        config: &AdapterConfig,
+        method: &str,
+        query: Vec<(&str, String)>,
+        max_items: usize,
+    ) -> Result<Vec<Value>, AppError> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut page_query = query.clone();
+            if let Some(cursor) = &cursor {
+                page_query.push(("cursor", cursor.clone()));
+            }
+
+            let mut body = self.call(client, config, method, &page_query).await?;
+            if let Some(messages) = body["messages"].as_array_mut() {
+                items.append(messages);
+            }
+
+            let next = body["response_metadata"]["next_cursor"]
+                .as_str()
+                .filter(|c| !c.is_empty());
+
+            match next {
+                Some(next) if items.len() < max_items => cursor = Some(next.to_string()),
+                _ => break,
+            }
+        }
+
+        items.truncate(max_items);
+        Ok(items)
+    }
+
+    async fn fetch_reminders(
+        &self,
+        client: &reqwest::Client,
+        config: &AdapterConfig,
+    ) -> Result<Vec<StagedRecord>, AppError> {
+        let body = self.call(client, config, "reminders.list", &[]).await?;
+
+        Ok(body["reminders"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|reminder| reminder_record(reminder, config))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Adapter for SlackAdapter {
+    fn adapter_type(&self) -> &str {
+        "slack"
+    }
+
+    fn name(&self) -> &str {
+        "Slack"
+    }
+
+    async fn fetch(&self, config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError> {
+        if !matches!(config.auth, Some(AuthConfig::Bearer { .. })) {
+            return Err(AppError::Config(
+                "Slack adapter requires a bot token (bearer auth)".to_string(),
+            ));
+        }
+
+        let client = HttpClient::new_client();
+        let mode = config.parameters["mode"].as_str().unwrap_or("messages");
+
+        let records = match mode {
+            "messages" => self.fetch_messages(&client, config).await?,
+            "reminders" => self.fetch_reminders(&client, config).await?,
+            other => {
+                return Err(AppError::Config(format!(
+                    "Unknown Slack adapter mode: {} (expected 'messages' or 'reminders')",
+                    other
+                )))
+            }
+        };
+
+        tracing::info!("Fetched {} records from Slack ({})", records.len(), mode);
+        Ok(records)
+    }
+
+    async fn test_connection(&self, config: &AdapterConfig) -> Result<bool, AppError> {
+        let client = HttpClient::new_client();
+
+        match self.call(&client, config, "auth.test", &[]).await {
+            Ok(_) => Ok(true),
+            Err(AppError::Adapter(e)) => {
+                tracing::warn!("Slack connection test failed: {}", e);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn default_config(&self) -> AdapterConfig {
+        let mut config = AdapterConfig::new(
+            self.adapter_type(),
+            "slack-channel",
+            "https://slack.com/api",
+        );
+
+        config.parameters = serde_json::json!({
+            "mode": "messages",
+            "channel": "C0123456789",
+            "lookback_hours": 24,
+            "include_threads": false,
+            "max_messages": DEFAULT_MAX_MESSAGES,
+        });
+
+        config.polling_interval = Some(300); // 5 minutes
+
+        config
+    }
+}
+
+// ============================================================================
+// Record Conversion
+// ============================================================================
+
+/// Parse a Slack timestamp ("1700000000.000100") into a UTC datetime
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    let (secs, micros) = ts.split_once('.').unwrap_or((ts, "0"));
+    DateTime::from_timestamp(secs.parse().ok()?, micros.parse::<u32>().ok()? * 1000)
+}
+
+fn message_record(message: Value, channel: &str, config: &AdapterConfig) -> StagedRecord {
+    let ts = message["ts"].as_str().unwrap_or_default().to_string();
+    let thread_ts = message["thread_ts"].as_str().map(String::from);
+    let is_reply = thread_ts.as_deref().is_some_and(|t| t != ts);
+    let text = message["text"].as_str().unwrap_or_default().to_string();
+
+    let data = serde_json::json!({
+        "id": format!("{}:{}", channel, ts),
+        "channel": channel,
+        "ts": ts,
+        "user": message["user"].as_str().or_else(|| message["bot_id"].as_str()),
+        "text": text,
+        "subtype": message["subtype"],
+        "thread_ts": thread_ts,
+        "is_thread_reply": is_reply,
+        "reply_count": message["reply_count"].as_u64().unwrap_or(0),
+        "reply_users": message["reply_users"],
+        "latest_reply": message["latest_reply"],
+        "reactions": message["reactions"],
+        "raw": message,
+    });
+
+    let mut tags = vec!["slack".to_string(), channel.to_string()];
+    if is_reply {
+        tags.push("thread-reply".to_string());
+    }
+
+    StagedRecord {
+        id: None, // Will be set by SurrealDB
+        record_type: "slack_message".to_string(),
+        source: config.source.clone(),
+        timestamp: parse_ts(&ts).unwrap_or_else(Utc::now),
+        data,
+        metadata: RecordMetadata {
+            tags,
+            status: None,
+            title: Some(text.chars().take(80).collect()),
+            description: None,
+        },
+    }
+}
+
+fn reminder_record(reminder: Value, config: &AdapterConfig) -> StagedRecord {
+    let complete = reminder["complete_ts"].as_u64().unwrap_or(0) > 0;
+    let timestamp = reminder["time"]
+        .as_i64()
+        .and_then(|t| DateTime::from_timestamp(t, 0))
+        .unwrap_or_else(Utc::now);
+
+    StagedRecord {
+        id: None, // Will be set by SurrealDB
+        record_type: "slack_reminder".to_string(),
+        source: config.source.clone(),
+        timestamp,
+        metadata: RecordMetadata {
+            tags: vec!["slack".to_string(), "reminder".to_string()],
+            status: Some(if complete { "complete" } else { "pending" }.to_string()),
+            title: reminder["text"].as_str().map(String::from),
+            description: None,
+        },
+        data: reminder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_record() {
+        let config = AdapterConfig::new("slack", "team", "https://slack.com/api");
+
+        let parent = message_record(
+            json!({
+                "ts": "1700000000.000100",
+                "thread_ts": "1700000000.000100",
+                "user": "U123",
+                "text": "Deploy finished",
+                "reply_count": 2
+            }),
+            "C1",
+            &config,
+        );
+        assert_eq!(parent.record_type, "slack_message");
+        assert_eq!(parent.data["id"], "C1:1700000000.000100");
+        assert_eq!(parent.data["is_thread_reply"], false);
+        assert_eq!(parent.timestamp.timestamp(), 1_700_000_000);
+
+        let reply = message_record(
+            json!({ "ts": "1700000100.000200", "thread_ts": "1700000000.000100", "user": "U456", "text": "thanks" }),
+            "C1",
+            &config,
+        );
+        assert_eq!(reply.data["is_thread_reply"], true);
+        assert!(reply.metadata.tags.contains(&"thread-reply".to_string()));
+    }
+}
@@ -0,0 +1,507 @@
+// SSH Command / Directory Adapter
+//
+// Runs a read-only command or lists a remote directory over SSH and stages the
+// output, for monitoring servers without installing agents. Uses the system
+// OpenSSH client, so host keys, agents and ~/.ssh/config work as usual.
+//
+// Authentication:
+// - No auth: default keys / ssh-agent of the current user
+// - Basic { username, password }: password auth (via SSH_ASKPASS)
+// - SshKey { username, private_key, passphrase }: key path or PEM key content
+
+use crate::adapters::{Adapter, AdapterConfig, AuthConfig};
+use crate::db::{RecordMetadata, StagedRecord};
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Default timeout for a remote command
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Commands that modify the remote system and are never run
+const BLOCKED_COMMANDS: &[&str] = &[
+    "rm",
+    "rmdir",
+    "mv",
+    "cp",
+    "dd",
+    "mkfs",
+    "mkdir",
+    "touch",
+    "truncate",
+    "shred",
+    "chmod",
+    "chown",
+    "chgrp",
+    "ln",
+    "kill",
+    "killall",
+    "pkill",
+    "reboot",
+    "shutdown",
+    "halt",
+    "poweroff",
+    "systemctl",
+    "service",
+    "sudo",
+    "su",
+    "tee",
+    "sed -i",
+    "crontab",
+];
+
+pub struct SshAdapter;
+
+impl SshAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Run a command on the remote host and return its stdout
+    async fn run(&self, config: &AdapterConfig, remote_command: &str) -> Result<String, AppError> {
+        let target = SshTarget::parse(&config.endpoint)?;
+        let timeout = config.parameters["timeout_secs"]
+            .as_u64()
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let host_key_checking = config.parameters["strict_host_key_checking"]
+            .as_str()
+            .unwrap_or("accept-new");
+
+        let mut command = Command::new("ssh");
+        command
+            .arg("-T")
+            .arg("-o")
+            .arg(format!("ConnectTimeout={}", timeout))
+            .arg("-o")
+            .arg(format!("StrictHostKeyChecking={}", host_key_checking))
+            .arg("-p")
+            .arg(target.port.to_string())
+            .kill_on_drop(true);
+
+        // Temporary files (key, askpass helper) live until the command has finished
+        let mut secrets = TempFiles::default();
+        let mut username = target.username.clone();
+
+        match &config.auth {
+            None | Some(AuthConfig::None) => {
+                command.arg("-o").arg("BatchMode=yes");
+            }
+            Some(AuthConfig::Basic {
+                username: user,
+                password,
+            }) => {
+                if !user.is_empty() {
+                    username = Some(user.clone());
+                }
+                command
+                    .arg("-o")
+                    .arg("PreferredAuthentications=password,keyboard-interactive")
+                    .arg("-o")
+                    .arg("NumberOfPasswordPrompts=1");
+                secrets.askpass(&mut command, password)?;
+            }
+            Some(AuthConfig::SshKey {
+                username: user,
+                private_key,
+                passphrase,
+            }) => {
+                if !user.is_empty() {
+                    username = Some(user.clone());
+                }
+                let key_path = if private_key.contains("-----BEGIN") {
+                    secrets.write("key", private_key)?
+                } else {
+                    PathBuf::from(private_key)
+                };
+                command
+                    .arg("-i")
+                    .arg(key_path)
+                    .arg("-o")
+                    .arg("IdentitiesOnly=yes");
+                match passphrase {
+                    Some(passphrase) => secrets.askpass(&mut command, passphrase)?,
+                    None => {
+                        command.arg("-o").arg("BatchMode=yes");
+                    }
+                }
+            }
+            Some(_) => {
+                return Err(AppError::Config(
+                    "SSH adapter supports password or key authentication only".to_string(),
+                ))
+            }
+        }
+
+        let destination = match username {
+            Some(user) => format!("{}@{}", user, target.host),
+            None => target.host.clone(),
+        };
+        command.arg("--").arg(destination).arg(remote_command);
+
+        let output = tokio::time::timeout(Duration::from_secs(timeout), command.output())
+            .await
+            .map_err(|_| AppError::Adapter(format!("SSH command timed out after {}s", timeout)))?
+            .map_err(|e| AppError::Adapter(format!("Failed to run ssh: {}", e)))?;
+
+        drop(secrets);
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(AppError::Adapter(format!(
+                "SSH command failed ({}): {}",
+                output.status,
+                stderr.trim()
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn record(&self, config: &AdapterConfig, record_type: &str, data: Value) -> StagedRecord {
+        let title = data
+            .get("name")
+            .or_else(|| data.get("title"))
+            .or_else(|| data.get("line"))
+            .and_then(|v| v.as_str())
+            .map(String::from);
+
+        StagedRecord {
+            id: None, // Will be set by SurrealDB
+            record_type: record_type.to_string(),
+            source: config.source.clone(),
+            timestamp: Utc::now(),
+            data,
+            metadata: RecordMetadata {
+                tags: vec!["ssh".to_string()],
+                status: None,
+                title,
+                description: None,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Adapter for SshAdapter {
+    fn adapter_type(&self) -> &str {
+        "ssh"
+    }
+
+    fn name(&self) -> &str {
+        "SSH Command / Directory"
+    }
+
+    async fn fetch(&self, config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError> {
+        let host = SshTarget::parse(&config.endpoint)?.host;
+        let mode = config.parameters["mode"].as_str().unwrap_or("command");
+
+        match mode {
+            "list" => {
+                let path = config.parameters["path"].as_str().unwrap_or(".");
+                let output = self.run(config, &list_command(path)).await?;
+
+                Ok(parse_listing(&output, path)
+                    .into_iter()
+                    .map(|data| self.record(config, "ssh_file", data))
+                    .collect())
+            }
+            "command" => {
+                let remote_command = config.parameters["command"]
+                    .as_str()
+                    .filter(|c| !c.trim().is_empty())
+                    .ok_or_else(|| {
+                        AppError::Config("SSH adapter requires a 'command' parameter".to_string())
+                    })?;
+                ensure_read_only(remote_command)?;
+
+                let output = self.run(config, remote_command).await?;
+                let format = config.parameters["output"].as_str().unwrap_or("auto");
+
+                Ok(parse_output(&output, format, &host)?
+                    .into_iter()
+                    .map(|data| self.record(config, "ssh_command", data))
+                    .collect())
+            }
+            other => Err(AppError::Config(format!(
+                "Unknown SSH adapter mode: {} (expected 'command' or 'list')",
+                other
+            ))),
+        }
+    }
+
+    async fn test_connection(&self, config: &AdapterConfig) -> Result<bool, AppError> {
+        match self.run(config, "true").await {
+            Ok(_) => Ok(true),
+            Err(AppError::Adapter(e)) => {
+                tracing::warn!("SSH connection test failed: {}", e);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn default_config(&self) -> AdapterConfig {
+        let mut config = AdapterConfig::new(self.adapter_type(), "ssh-server", "user@example.com");
+
+        config.parameters = serde_json::json!({
+            "mode": "command",
+            "command": "df -P",
+            "path": "/var/log",
+            "output": "auto",
+            "timeout_secs": DEFAULT_TIMEOUT_SECS,
+            "strict_host_key_checking": "accept-new",
+        });
+
+        config.polling_interval = Some(300); // 5 minutes
+
+        config
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Parsed `[ssh://][user@]host[:port]` endpoint
+#[derive(Debug, PartialEq)]
+struct SshTarget {
+    username: Option<String>,
+    host: String,
+    port: u16,
+}
+
+impl SshTarget {
+    fn parse(endpoint: &str) -> Result<Self, AppError> {
+        let rest = endpoint.trim().trim_start_matches("ssh://");
+        let rest = rest.trim_end_matches('/');
+
+        let (username, host_port) = match rest.rsplit_once('@') {
+            Some((user, host)) => (Some(user.to_string()), host),
+            None => (None, rest),
+        };
+
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| AppError::Config(format!("Invalid SSH port: {}", port)))?,
+            ),
+            None => (host_port, 22),
+        };
+
+        if host.is_empty() {
+            return Err(AppError::Config(format!(
+                "Invalid SSH endpoint: {}",
+                endpoint
+            )));
+        }
+
+        Ok(Self {
+            username,
+            host: host.to_string(),
+            port,
+        })
+    }
+}
+
+/// Reject commands that would modify the remote host
+fn ensure_read_only(command: &str) -> Result<(), AppError> {
+    if command.contains('>') || command.contains('`') || command.contains("$(") {
+        return Err(AppError::Config(
+            "SSH commands must be read-only (no redirection or command substitution)".to_string(),
+        ));
+    }
+
+    for segment in command.split(['|', ';', '&', '\n']) {
+        let segment = segment.trim();
+        let blocked = BLOCKED_COMMANDS.iter().find(|blocked| {
+            segment == **blocked
+                || segment
+                    .strip_prefix(**blocked)
+                    .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+        });
+
+        if let Some(blocked) = blocked {
+            return Err(AppError::Config(format!(
+                "SSH commands must be read-only ('{}' is not allowed)",
+                blocked
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Remote command listing a directory as tab-separated name, size, mtime, type
+fn list_command(path: &str) -> String {
+    format!(
+        "find '{}' -mindepth 1 -maxdepth 1 -printf '%f\\t%s\\t%T@\\t%y\\n'",
+        path.replace('\'', "'\\''")
+    )
+}
+
+fn parse_listing(output: &str, path: &str) -> Vec<Value> {
+    let base = path.trim_end_matches('/');
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next().filter(|n| !n.is_empty())?;
+            let size = fields.next().and_then(|s| s.parse::<u64>().ok());
+            let modified = fields
+                .next()
+                .and_then(|s| s.parse::<f64>().ok())
+                .and_then(|secs| Utc.timestamp_opt(secs as i64, 0).single())
+                .map(|t| t.to_rfc3339());
+            let file_type = match fields.next() {
+                Some("d") => "directory",
+                Some("l") => "symlink",
+                Some("f") => "file",
+                _ => "other",
+            };
+            let full_path = format!("{}/{}", base, name);
+
+            Some(serde_json::json!({
+                "id": full_path,
+                "name": name,
+                "path": full_path,
+                "size": size,
+                "modified": modified,
+                "type": file_type,
+            }))
+        })
+        .collect()
+}
+
+/// Parse command output as JSON (array or object) or as one record per line
+fn parse_output(output: &str, format: &str, host: &str) -> Result<Vec<Value>, AppError> {
+    if format != "lines" {
+        match serde_json::from_str::<Value>(output.trim()) {
+            Ok(Value::Array(items)) => return Ok(items),
+            Ok(value @ Value::Object(_)) => return Ok(vec![value]),
+            Ok(_) | Err(_) if format == "json" => {
+                return Err(AppError::Adapter(
+                    "SSH command output is not a JSON object or array".to_string(),
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    // Line records are keyed by position so each fetch replaces the previous snapshot
+    Ok(output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::json!({
+                "id": format!("{}:{}", host, index),
+                "host": host,
+                "index": index,
+                "line": line,
+            })
+        })
+        .collect())
+}
+
+/// Secret files written for a single ssh invocation, removed on drop
+#[derive(Default)]
+struct TempFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl TempFiles {
+    /// Write a private file to the temp directory
+    fn write(&mut self, name: &str, contents: &str) -> Result<PathBuf, AppError> {
+        let path =
+            std::env::temp_dir().join(format!("modulaur-ssh-{}-{}", uuid::Uuid::new_v4(), name));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o700);
+        }
+
+        let mut file = options.open(&path).map_err(AppError::Io)?;
+        std::io::Write::write_all(&mut file, contents.as_bytes()).map_err(AppError::Io)?;
+        self.paths.push(path.clone());
+        Ok(path)
+    }
+
+    /// Provide a password/passphrase to ssh through an askpass helper
+    /// The secret itself is only passed through the child's environment
+    fn askpass(&mut self, command: &mut Command, secret: &str) -> Result<(), AppError> {
+        let script = self.write(
+            "askpass.sh",
+            "#!/bin/sh\nprintf '%s\\n' \"$MODULAUR_SSH_SECRET\"\n",
+        )?;
+
+        command
+            .env("SSH_ASKPASS", script)
+            .env("SSH_ASKPASS_REQUIRE", "force")
+            .env("MODULAUR_SSH_SECRET", secret)
+            .stdin(std::process::Stdio::null());
+        Ok(())
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            SshTarget::parse("ssh://deploy@build.local:2222").unwrap(),
+            SshTarget {
+                username: Some("deploy".to_string()),
+                host: "build.local".to_string(),
+                port: 2222,
+            }
+        );
+        assert_eq!(SshTarget::parse("example.com").unwrap().port, 22);
+        assert!(SshTarget::parse("user@").is_err());
+    }
+
+    #[test]
+    fn test_read_only_guard() {
+        assert!(ensure_read_only("df -P | tail -n +2").is_ok());
+        assert!(ensure_read_only("cat /var/log/syslog && uptime").is_ok());
+        assert!(ensure_read_only("echo hi > /tmp/x").is_err());
+        assert!(ensure_read_only("uptime; rm -rf /tmp/x").is_err());
+        assert!(ensure_read_only("sudo cat /etc/shadow").is_err());
+        assert!(ensure_read_only("rmdir").is_err());
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let json = parse_output(r#"[{"id": 1}, {"id": 2}]"#, "auto", "host").unwrap();
+        assert_eq!(json.len(), 2);
+
+        let lines = parse_output("first\n\nsecond\n", "auto", "host").unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["line"], "second");
+        assert_eq!(lines[1]["id"], "host:1");
+
+        assert!(parse_output("not json", "json", "host").is_err());
+
+        let files = parse_listing("app.log\t1024\t1700000000.5\tf\n", "/var/log/");
+        assert_eq!(files[0]["path"], "/var/log/app.log");
+        assert_eq!(files[0]["type"], "file");
+    }
+}
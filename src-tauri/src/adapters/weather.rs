@@ -0,0 +1,540 @@
+// Weather Adapter
+//
+// Fetches current conditions and a daily forecast for configured coordinates
+// and stages them as `weather` records. Supports Open-Meteo (no API key) and
+// OpenWeather (API key via ApiKey/Bearer auth or the `api_key` parameter).
+
+use crate::adapters::{Adapter, AdapterConfig, AuthConfig, HttpClient};
+use crate::db::{RecordMetadata, StagedRecord};
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::{json, Value};
+
+const OPEN_METEO_ENDPOINT: &str = "https://api.open-meteo.com/v1/forecast";
+const OPENWEATHER_ENDPOINT: &str = "https://api.openweathermap.org/data/2.5";
+
+/// Default number of forecast days
+const DEFAULT_FORECAST_DAYS: u64 = 3;
+
+pub struct WeatherAdapter;
+
+/// Location and unit settings resolved from the adapter parameters
+struct WeatherQuery {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    imperial: bool,
+    forecast_days: u64,
+}
+
+impl WeatherQuery {
+    fn from_config(config: &AdapterConfig) -> Result<Self, AppError> {
+        let coordinate = |key: &str| {
+            config.parameters[key].as_f64().ok_or_else(|| {
+                AppError::Config(format!("Weather adapter requires a numeric '{}'", key))
+            })
+        };
+        let latitude = coordinate("latitude")?;
+        let longitude = coordinate("longitude")?;
+
+        Ok(Self {
+            name: config.parameters["location_name"]
+                .as_str()
+                .map(String::from)
+                .unwrap_or_else(|| format!("{:.2},{:.2}", latitude, longitude)),
+            latitude,
+            longitude,
+            imperial: config.parameters["units"].as_str() == Some("imperial"),
+            forecast_days: config.parameters["forecast_days"]
+                .as_u64()
+                .unwrap_or(DEFAULT_FORECAST_DAYS)
+                .clamp(1, 16),
+        })
+    }
+
+    fn temperature_unit(&self) -> &'static str {
+        if self.imperial {
+            "°F"
+        } else {
+            "°C"
+        }
+    }
+
+    fn wind_unit(&self) -> &'static str {
+        if self.imperial {
+            "mph"
+        } else {
+            "km/h"
+        }
+    }
+}
+
+impl WeatherAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn get_json(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<Value, AppError> {
+        let response = client
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| AppError::request(&e, format!("Weather request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::http_status(
+                response.status().as_u16(),
+                format!("Weather API returned error status: {}", response.status()),
+            ));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::request(&e, format!("Failed to parse weather response: {}", e)))
+    }
+
+    async fn fetch_open_meteo(
+        &self,
+        client: &reqwest::Client,
+        config: &AdapterConfig,
+        query: &WeatherQuery,
+    ) -> Result<Vec<StagedRecord>, AppError> {
+        let endpoint = endpoint_or(config, OPEN_METEO_ENDPOINT);
+        let mut params = vec![
+            ("latitude", query.latitude.to_string()),
+            ("longitude", query.longitude.to_string()),
+            (
+                "current",
+                "temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,wind_speed_10m"
+                    .to_string(),
+            ),
+            (
+                "daily",
+                "weather_code,temperature_2m_max,temperature_2m_min,precipitation_sum".to_string(),
+            ),
+            ("forecast_days", query.forecast_days.to_string()),
+            ("timezone", "auto".to_string()),
+        ];
+        if query.imperial {
+            params.push(("temperature_unit", "fahrenheit".to_string()));
+            params.push(("wind_speed_unit", "mph".to_string()));
+            params.push(("precipitation_unit", "inch".to_string()));
+        }
+
+        let body = self.get_json(client, endpoint, &params).await?;
+        Ok(open_meteo_records(&body, query, config))
+    }
+
+    async fn fetch_openweather(
+        &self,
+        client: &reqwest::Client,
+        config: &AdapterConfig,
+        query: &WeatherQuery,
+    ) -> Result<Vec<StagedRecord>, AppError> {
+        let api_key = match &config.auth {
+            Some(AuthConfig::ApiKey { key, .. }) => Some(key.clone()),
+            Some(AuthConfig::Bearer { token }) => Some(token.clone()),
+            _ => config.parameters["api_key"].as_str().map(String::from),
+        }
+        .ok_or_else(|| AppError::Config("OpenWeather requires an API key".to_string()))?;
+
+        let endpoint = endpoint_or(config, OPENWEATHER_ENDPOINT).trim_end_matches('/');
+        let params = vec![
+            ("lat", query.latitude.to_string()),
+            ("lon", query.longitude.to_string()),
+            ("appid", api_key),
+            (
+                "units",
+                if query.imperial { "imperial" } else { "metric" }.to_string(),
+            ),
+        ];
+
+        let current = self
+            .get_json(client, &format!("{}/weather", endpoint), &params)
+            .await?;
+        let forecast = self
+            .get_json(client, &format!("{}/forecast", endpoint), &params)
+            .await?;
+
+        Ok(openweather_records(&current, &forecast, query, config))
+    }
+}
+
+#[async_trait]
+impl Adapter for WeatherAdapter {
+    fn adapter_type(&self) -> &str {
+        "weather"
+    }
+
+    fn name(&self) -> &str {
+        "Weather (Open-Meteo / OpenWeather)"
+    }
+
+    async fn fetch(&self, config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError> {
+        let query = WeatherQuery::from_config(config)?;
+        let client = HttpClient::new_client();
+        let provider = config.parameters["provider"]
+            .as_str()
+            .unwrap_or("open-meteo");
+
+        let records = match provider {
+            "open-meteo" => self.fetch_open_meteo(&client, config, &query).await?,
+            "openweather" => self.fetch_openweather(&client, config, &query).await?,
+            other => {
+                return Err(AppError::Config(format!(
+                    "Unknown weather provider: {} (expected 'open-meteo' or 'openweather')",
+                    other
+                )))
+            }
+        };
+
+        tracing::info!(
+            "Fetched {} weather records for {} ({})",
+            records.len(),
+            query.name,
+            provider
+        );
+        Ok(records)
+    }
+
+    async fn test_connection(&self, config: &AdapterConfig) -> Result<bool, AppError> {
+        match self.fetch(config).await {
+            Ok(_) => Ok(true),
+            Err(AppError::Http { message, .. }) => {
+                tracing::warn!("Weather connection test failed: {}", message);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn default_config(&self) -> AdapterConfig {
+        let mut config = AdapterConfig::new(self.adapter_type(), "weather", OPEN_METEO_ENDPOINT);
+
+        config.parameters = json!({
+            "provider": "open-meteo",
+            "location_name": "Berlin",
+            "latitude": 52.52,
+            "longitude": 13.41,
+            "units": "metric",
+            "forecast_days": DEFAULT_FORECAST_DAYS,
+        });
+
+        config.polling_interval = Some(1800); // 30 minutes
+
+        config
+    }
+}
+
+// ============================================================================
+// Response Normalization
+// ============================================================================
+
+/// Use the configured endpoint unless it belongs to the other provider
+fn endpoint_or<'a>(config: &'a AdapterConfig, default: &'a str) -> &'a str {
+    let endpoint = config.endpoint.trim();
+    let other_provider = if default == OPEN_METEO_ENDPOINT {
+        "openweathermap.org"
+    } else {
+        "open-meteo.com"
+    };
+
+    if endpoint.is_empty() || endpoint.contains(other_provider) {
+        default
+    } else {
+        endpoint
+    }
+}
+
+/// Describe a WMO weather interpretation code (used by Open-Meteo)
+fn wmo_condition(code: i64) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 | 53 | 55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 | 63 | 65 => "Rain",
+        66 | 67 => "Freezing rain",
+        71 | 73 | 75 | 77 => "Snow",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown",
+    }
+}
+
+fn current_record(
+    query: &WeatherQuery,
+    config: &AdapterConfig,
+    timestamp: DateTime<Utc>,
+    mut data: Value,
+) -> StagedRecord {
+    let condition = data["condition"].as_str().unwrap_or("Unknown").to_string();
+    let title = match data["temperature"].as_f64() {
+        Some(t) => format!(
+            "{}: {:.1}{}, {}",
+            query.name,
+            t,
+            query.temperature_unit(),
+            condition
+        ),
+        None => format!("{}: {}", query.name, condition),
+    };
+
+    data["id"] = json!(format!("{}:current", query.name));
+    data["kind"] = json!("current");
+    add_location(&mut data, query);
+
+    weather_record(config, timestamp, data, title, condition, "current")
+}
+
+fn forecast_record(
+    query: &WeatherQuery,
+    config: &AdapterConfig,
+    date: &str,
+    mut data: Value,
+) -> StagedRecord {
+    let condition = data["condition"].as_str().unwrap_or("Unknown").to_string();
+    let title = format!(
+        "{} {}: {}/{}{}, {}",
+        query.name,
+        date,
+        data["temperature_min"]
+            .as_f64()
+            .map_or("?".to_string(), |t| format!("{:.0}", t)),
+        data["temperature_max"]
+            .as_f64()
+            .map_or("?".to_string(), |t| format!("{:.0}", t)),
+        query.temperature_unit(),
+        condition
+    );
+
+    data["id"] = json!(format!("{}:forecast:{}", query.name, date));
+    data["kind"] = json!("forecast");
+    data["date"] = json!(date);
+    add_location(&mut data, query);
+
+    let timestamp = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(12, 0, 0))
+        .map(|d| d.and_utc())
+        .unwrap_or_else(Utc::now);
+
+    weather_record(config, timestamp, data, title, condition, "forecast")
+}
+
+fn add_location(data: &mut Value, query: &WeatherQuery) {
+    data["location"] = json!(query.name);
+    data["latitude"] = json!(query.latitude);
+    data["longitude"] = json!(query.longitude);
+    data["temperature_unit"] = json!(query.temperature_unit());
+    data["wind_speed_unit"] = json!(query.wind_unit());
+}
+
+fn weather_record(
+    config: &AdapterConfig,
+    timestamp: DateTime<Utc>,
+    data: Value,
+    title: String,
+    condition: String,
+    kind: &str,
+) -> StagedRecord {
+    StagedRecord {
+        id: None, // Will be set by SurrealDB
+        record_type: "weather".to_string(),
+        source: config.source.clone(),
+        timestamp,
+        data,
+        metadata: RecordMetadata {
+            tags: vec!["weather".to_string(), kind.to_string()],
+            status: Some(condition),
+            title: Some(title),
+            description: None,
+        },
+    }
+}
+
+fn open_meteo_records(
+    body: &Value,
+    query: &WeatherQuery,
+    config: &AdapterConfig,
+) -> Vec<StagedRecord> {
+    let mut records = Vec::new();
+
+    let current = &body["current"];
+    if current.is_object() {
+        let code = current["weather_code"].as_i64();
+        records.push(current_record(
+            query,
+            config,
+            Utc::now(),
+            json!({
+                "time": current["time"],
+                "temperature": current["temperature_2m"],
+                "apparent_temperature": current["apparent_temperature"],
+                "humidity": current["relative_humidity_2m"],
+                "precipitation": current["precipitation"],
+                "wind_speed": current["wind_speed_10m"],
+                "weather_code": code,
+                "condition": code.map(wmo_condition),
+            }),
+        ));
+    }
+
+    let daily = &body["daily"];
+    let dates = daily["time"].as_array().cloned().unwrap_or_default();
+    for (index, date) in dates.iter().enumerate() {
+        let Some(date) = date.as_str() else {
+            continue;
+        };
+        let code = daily["weather_code"][index].as_i64();
+        records.push(forecast_record(
+            query,
+            config,
+            date,
+            json!({
+                "temperature_max": daily["temperature_2m_max"][index],
+                "temperature_min": daily["temperature_2m_min"][index],
+                "precipitation": daily["precipitation_sum"][index],
+                "weather_code": code,
+                "condition": code.map(wmo_condition),
+            }),
+        ));
+    }
+
+    records
+}
+
+fn openweather_records(
+    current: &Value,
+    forecast: &Value,
+    query: &WeatherQuery,
+    config: &AdapterConfig,
+) -> Vec<StagedRecord> {
+    let mut records = Vec::new();
+
+    let timestamp = current["dt"]
+        .as_i64()
+        .and_then(|t| DateTime::from_timestamp(t, 0))
+        .unwrap_or_else(Utc::now);
+    records.push(current_record(
+        query,
+        config,
+        timestamp,
+        json!({
+            "time": timestamp.to_rfc3339(),
+            "temperature": current["main"]["temp"],
+            "apparent_temperature": current["main"]["feels_like"],
+            "humidity": current["main"]["humidity"],
+            "precipitation": current["rain"]["1h"].as_f64().unwrap_or(0.0),
+            "wind_speed": current["wind"]["speed"],
+            "weather_code": current["weather"][0]["id"],
+            "condition": current["weather"][0]["description"],
+        }),
+    ));
+
+    // The 5-day forecast is 3-hourly; fold it into daily min/max
+    let mut days: Vec<(String, Value)> = Vec::new();
+    for entry in forecast["list"].as_array().cloned().unwrap_or_default() {
+        let Some(date) = entry["dt_txt"].as_str().and_then(|t| t.get(..10)) else {
+            continue;
+        };
+        let temp_min = entry["main"]["temp_min"].as_f64();
+        let temp_max = entry["main"]["temp_max"].as_f64();
+        let rain = entry["rain"]["3h"].as_f64().unwrap_or(0.0);
+
+        match days.iter_mut().find(|(d, _)| d == date) {
+            Some((_, day)) => {
+                if let (Some(t), Some(current)) = (temp_min, day["temperature_min"].as_f64()) {
+                    day["temperature_min"] = json!(t.min(current));
+                }
+                if let (Some(t), Some(current)) = (temp_max, day["temperature_max"].as_f64()) {
+                    day["temperature_max"] = json!(t.max(current));
+                }
+                let total = day["precipitation"].as_f64().unwrap_or(0.0) + rain;
+                day["precipitation"] = json!(total);
+                // Prefer the midday condition as the day's summary
+                if entry["dt_txt"]
+                    .as_str()
+                    .is_some_and(|t| t.contains("12:00"))
+                {
+                    day["weather_code"] = entry["weather"][0]["id"].clone();
+                    day["condition"] = entry["weather"][0]["description"].clone();
+                }
+            }
+            None => days.push((
+                date.to_string(),
+                json!({
+                    "temperature_min": temp_min,
+                    "temperature_max": temp_max,
+                    "precipitation": rain,
+                    "weather_code": entry["weather"][0]["id"],
+                    "condition": entry["weather"][0]["description"],
+                }),
+            )),
+        }
+    }
+
+    for (date, data) in days.into_iter().take(query.forecast_days as usize) {
+        records.push(forecast_record(query, config, &date, data));
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_meteo_records() {
+        let adapter = WeatherAdapter::new();
+        let config = adapter.default_config();
+        let query = WeatherQuery::from_config(&config).unwrap();
+
+        let body = json!({
+            "current": {
+                "time": "2024-05-01T12:00",
+                "temperature_2m": 18.4,
+                "apparent_temperature": 17.0,
+                "relative_humidity_2m": 60,
+                "precipitation": 0.0,
+                "weather_code": 2,
+                "wind_speed_10m": 11.2
+            },
+            "daily": {
+                "time": ["2024-05-01", "2024-05-02"],
+                "weather_code": [2, 61],
+                "temperature_2m_max": [20.1, 16.0],
+                "temperature_2m_min": [9.3, 8.7],
+                "precipitation_sum": [0.0, 4.2]
+            }
+        });
+
+        let records = open_meteo_records(&body, &query, &config);
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|r| r.record_type == "weather"));
+
+        assert_eq!(records[0].data["kind"], "current");
+        assert_eq!(records[0].data["id"], "Berlin:current");
+        assert_eq!(
+            records[0].metadata.title.as_deref(),
+            Some("Berlin: 18.4°C, Partly cloudy")
+        );
+
+        assert_eq!(records[2].data["id"], "Berlin:forecast:2024-05-02");
+        assert_eq!(records[2].metadata.status.as_deref(), Some("Rain"));
+    }
+}
@@ -0,0 +1,530 @@
+// Alert rules
+//
+// Persisted rules in the `alert_rules` table that compute a metric over a
+// record query (e.g. the count of gitlab_pipeline records with status
+// "failed") and compare it with a threshold. Rules are evaluated after every
+// completed fetch and by the `alerts` job (every five minutes). A rule fires
+// when its condition becomes true: the alert is written as a record (type
+// "alert", source "alerts"), so widgets can list and count alerts with a
+// normal widget query, it's published on the event bus (`alerts://fired` for
+// the frontend, also picked up by `webhooks`), and the user is notified (see
+// `notifications`). A rule fires again only after its condition was false in
+// between.
+
+use crate::audit::Actor;
+use crate::db::aggregate::Metric;
+use crate::db::query::RecordQuery;
+use crate::db::{Database, StagedRecord};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::notifications::{notify, NotificationCategory};
+use crate::scheduler::{Job, Schedule};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+
+/// How often rules are evaluated without fetches
+const ALERT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Record type and source of the records written for fired alerts
+pub const ALERT_RECORD_TYPE: &str = "alert";
+pub const ALERT_SOURCE: &str = "alerts";
+
+// ============================================================================
+// Alert Rule Model
+// ============================================================================
+
+/// How the metric is compared with the threshold
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    #[default]
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+    Ne,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Gt => value > threshold,
+            Comparison::Gte => value >= threshold,
+            Comparison::Lt => value < threshold,
+            Comparison::Lte => value <= threshold,
+            Comparison::Eq => value == threshold,
+            Comparison::Ne => value != threshold,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Gt => ">",
+            Comparison::Gte => ">=",
+            Comparison::Lt => "<",
+            Comparison::Lte => "<=",
+            Comparison::Eq => "=",
+            Comparison::Ne => "!=",
+        }
+    }
+}
+
+/// Alert rule as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AlertRuleRecord {
+    pub id: Thing,
+    pub name: String,
+    pub query: RecordQuery,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub enabled: bool,
+    #[serde(default)]
+    pub triggered: bool,
+    #[serde(default)]
+    pub last_value: Option<f64>,
+    #[serde(default)]
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_fired_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// User-facing alert rule with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    /// Records the metric is computed over
+    pub query: RecordQuery,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub enabled: bool,
+    /// Whether the condition held at the last evaluation
+    pub triggered: bool,
+    pub last_value: Option<f64>,
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    pub last_fired_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<AlertRuleRecord> for AlertRule {
+    fn from(record: AlertRuleRecord) -> Self {
+        AlertRule {
+            id: record.id.id.to_raw(),
+            name: record.name,
+            query: record.query,
+            metric: record.metric,
+            comparison: record.comparison,
+            threshold: record.threshold,
+            enabled: record.enabled,
+            triggered: record.triggered,
+            last_value: record.last_value,
+            last_evaluated_at: record.last_evaluated_at,
+            last_fired_at: record.last_fired_at,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+/// Create (no id) or update (with id) a rule
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveAlertRuleRequest {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub query: RecordQuery,
+    #[serde(default)]
+    pub metric: Metric,
+    #[serde(default)]
+    pub comparison: Comparison,
+    pub threshold: f64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A rule that fired
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FiredAlert {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub value: f64,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub message: String,
+    pub fired_at: DateTime<Utc>,
+}
+
+impl FiredAlert {
+    fn to_record(&self) -> StagedRecord {
+        let mut record = StagedRecord::new(
+            ALERT_RECORD_TYPE.to_string(),
+            ALERT_SOURCE.to_string(),
+            serde_json::json!({
+                "id": uuid::Uuid::new_v4().to_string(),
+                "rule_id": self.rule_id,
+                "rule_name": self.rule_name,
+                "value": self.value,
+                "comparison": self.comparison,
+                "threshold": self.threshold,
+            }),
+        );
+        record.timestamp = self.fired_at;
+        record.metadata.title = Some(self.message.clone());
+        record.metadata.status = Some("fired".to_string());
+        record.metadata.tags = vec![ALERT_RECORD_TYPE.to_string()];
+        record
+    }
+}
+
+/// The alert for a rule whose condition just became true, if it did
+fn check_rule(rule: &AlertRule, value: Option<f64>, now: DateTime<Utc>) -> Option<FiredAlert> {
+    let value = value?;
+    if !rule.comparison.holds(value, rule.threshold) || rule.triggered {
+        return None;
+    }
+    Some(FiredAlert {
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        value,
+        comparison: rule.comparison,
+        threshold: rule.threshold,
+        message: format!(
+            "{}: {} {} {}",
+            rule.name,
+            value,
+            rule.comparison.symbol(),
+            rule.threshold
+        ),
+        fired_at: now,
+    })
+}
+
+// ============================================================================
+// Alert Operations
+// ============================================================================
+
+impl Database {
+    pub async fn list_alert_rules(&self) -> Result<Vec<AlertRule>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM alert_rules ORDER BY created_at ASC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query alert rules: {}", e)))?;
+
+        let rules: Vec<AlertRuleRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse alert rules: {}", e)))?;
+
+        Ok(rules.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Create or update a rule; an updated rule is evaluated from scratch
+    pub async fn save_alert_rule(&self, req: SaveAlertRuleRequest) -> Result<AlertRule, AppError> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::Validation(
+                "An alert rule needs a name".to_string(),
+            ));
+        }
+        if !req.threshold.is_finite() {
+            return Err(AppError::Validation(
+                "The threshold must be a number".to_string(),
+            ));
+        }
+        // Reject invalid field paths now rather than on every evaluation
+        req.query.validate()?;
+
+        let now = Utc::now();
+        let (id, created_at) = match &req.id {
+            Some(id) => {
+                let key = id.strip_prefix("alert_rules:").unwrap_or(id).to_string();
+                let existing: Option<AlertRuleRecord> = self
+                    .db
+                    .select(("alert_rules", key.as_str()))
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to get alert rule: {}", e)))?;
+                let existing =
+                    existing.ok_or_else(|| AppError::NotFound(format!("Alert rule {}", id)))?;
+                (key, existing.created_at)
+            }
+            None => (uuid::Uuid::new_v4().to_string(), now),
+        };
+
+        let record = AlertRuleRecord {
+            id: Thing::from(("alert_rules", id.as_str())),
+            name: req.name.trim().to_string(),
+            query: req.query,
+            metric: req.metric,
+            comparison: req.comparison,
+            threshold: req.threshold,
+            enabled: req.enabled,
+            triggered: false,
+            last_value: None,
+            last_evaluated_at: None,
+            last_fired_at: None,
+            created_at,
+            updated_at: now,
+        };
+
+        let saved: Option<AlertRuleRecord> = self
+            .db
+            .upsert(("alert_rules", id.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save alert rule: {}", e)))?;
+
+        saved
+            .map(|r| r.into())
+            .ok_or_else(|| AppError::Database("Failed to save alert rule".to_string()))
+    }
+
+    pub async fn delete_alert_rule(&self, id: &str) -> Result<(), AppError> {
+        let key = id.strip_prefix("alert_rules:").unwrap_or(id);
+        let _: Option<AlertRuleRecord> = self
+            .db
+            .delete(("alert_rules", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete alert rule: {}", e)))?;
+        Ok(())
+    }
+
+    /// Evaluate the enabled rules, recording the alerts of rules that fired
+    pub async fn evaluate_alert_rules(&self) -> Result<Vec<FiredAlert>, AppError> {
+        let now = Utc::now();
+        let mut fired = Vec::new();
+
+        for rule in self.list_alert_rules().await? {
+            if !rule.enabled {
+                continue;
+            }
+
+            let value = match self.metric_value(&rule.metric, rule.query.clone()).await {
+                Ok(value) => value,
+                Err(e) => {
+                    tracing::warn!("Failed to evaluate alert rule {}: {}", rule.id, e);
+                    continue;
+                }
+            };
+            let alert = check_rule(&rule, value, now);
+            let triggered = value.is_some_and(|v| rule.comparison.holds(v, rule.threshold));
+
+            if let Some(alert) = &alert {
+                self.upsert_record(alert.to_record()).await?;
+                tracing::info!("Alert rule {} fired: {}", rule.id, alert.message);
+            }
+
+            self.query(
+                "UPDATE type::thing('alert_rules', $key) SET
+                         triggered = $triggered,
+                         last_value = $value,
+                         last_evaluated_at = $now,
+                         last_fired_at = IF $fired THEN $now ELSE last_fired_at END
+                     RETURN NONE",
+            )
+            .bind(("key", rule.id.clone()))
+            .bind(("triggered", triggered))
+            .bind(("value", value))
+            .bind(("now", now))
+            .bind(("fired", alert.is_some()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update alert rule: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to update alert rule: {}", e)))?;
+
+            fired.extend(alert);
+        }
+
+        Ok(fired)
+    }
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+/// Evaluate the alert rules and announce the ones that fired
+async fn evaluate_and_announce(database: &Mutex<Database>) -> Result<usize, AppError> {
+    let db = database.lock().await;
+    let fired = db
+        .as_actor(Actor::job("alerts"), db.evaluate_alert_rules())
+        .await?;
+    drop(db);
+    let count = fired.len();
+    for alert in fired {
+        notify(
+            NotificationCategory::Alerts,
+            &alert.rule_name,
+            &alert.message,
+        );
+        events::publish(AppEvent::AlertFired(alert));
+    }
+    Ok(count)
+}
+
+/// Evaluate alert rules after every completed fetch
+pub fn start(database: Arc<Mutex<Database>>) {
+    let mut events = events::subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                // Only fetches that stored something can change a rule's value
+                Ok(AppEvent::FetchFinished(summary)) if summary.records > 0 => {}
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+
+            if let Err(e) = evaluate_and_announce(&database).await {
+                tracing::warn!("Failed to evaluate alert rules: {}", e);
+            }
+        }
+    });
+}
+
+/// Job evaluating the alert rules without fetches, every five minutes by default
+pub fn job(database: Arc<Mutex<Database>>) -> Job {
+    Job::new(
+        "alerts",
+        "Evaluate alert rules",
+        Schedule::every(ALERT_INTERVAL),
+        move || {
+            let database = database.clone();
+            async move {
+                let fired = evaluate_and_announce(&database).await?;
+                Ok(format!("{} alerts fired", fired))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_alert_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let rule = db
+            .save_alert_rule(SaveAlertRuleRequest {
+                id: None,
+                name: "Failed pipelines".to_string(),
+                query: RecordQuery {
+                    types: vec!["gitlab_pipeline".to_string()],
+                    status: vec!["failed".to_string()],
+                    ..Default::default()
+                },
+                metric: Metric::Count,
+                comparison: Comparison::Gt,
+                threshold: 0.0,
+                enabled: true,
+            })
+            .await
+            .unwrap();
+        assert!(db.evaluate_alert_rules().await.unwrap().is_empty());
+
+        let pipeline = |id: u32, status: &str| {
+            let mut record = StagedRecord::new(
+                "gitlab_pipeline".to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "id": id }),
+            );
+            record.metadata.status = Some(status.to_string());
+            record
+        };
+        db.upsert_record(pipeline(1, "failed")).await.unwrap();
+
+        let fired = db.evaluate_alert_rules().await.unwrap();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule_id, rule.id);
+        assert_eq!(fired[0].message, "Failed pipelines: 1 > 0");
+
+        // Still failing: no new alert
+        db.upsert_record(pipeline(2, "failed")).await.unwrap();
+        assert!(db.evaluate_alert_rules().await.unwrap().is_empty());
+        let rule = db.list_alert_rules().await.unwrap().remove(0);
+        assert!(rule.triggered);
+        assert_eq!(rule.last_value, Some(2.0));
+        assert!(rule.last_fired_at.is_some());
+
+        // Recovered, then failing again
+        db.upsert_record(pipeline(1, "success")).await.unwrap();
+        db.upsert_record(pipeline(2, "success")).await.unwrap();
+        assert!(db.evaluate_alert_rules().await.unwrap().is_empty());
+        db.upsert_record(pipeline(3, "failed")).await.unwrap();
+        assert_eq!(db.evaluate_alert_rules().await.unwrap().len(), 1);
+
+        // Fired alerts are records widgets can query
+        let alerts = db
+            .query_records(RecordQuery {
+                types: vec![ALERT_RECORD_TYPE.to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(alerts.total, 2);
+
+        assert!(db
+            .save_alert_rule(SaveAlertRuleRequest {
+                id: Some("missing".to_string()),
+                name: "Missing".to_string(),
+                query: RecordQuery::default(),
+                metric: Metric::Count,
+                comparison: Comparison::Gt,
+                threshold: 0.0,
+                enabled: true,
+            })
+            .await
+            .is_err());
+        db.delete_alert_rule(&rule.id).await.unwrap();
+        assert!(db.list_alert_rules().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_rule() {
+        let now = Utc::now();
+        let rule = AlertRule {
+            id: "r1".to_string(),
+            name: "Slow builds".to_string(),
+            query: RecordQuery::default(),
+            metric: Metric::Avg {
+                field: "data.duration".to_string(),
+            },
+            comparison: Comparison::Gte,
+            threshold: 600.0,
+            enabled: true,
+            triggered: false,
+            last_value: None,
+            last_evaluated_at: None,
+            last_fired_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert!(check_rule(&rule, None, now).is_none());
+        assert!(check_rule(&rule, Some(599.0), now).is_none());
+        assert_eq!(
+            check_rule(&rule, Some(600.0), now).unwrap().message,
+            "Slow builds: 600 >= 600"
+        );
+        let triggered = AlertRule {
+            triggered: true,
+            ..rule
+        };
+        assert!(check_rule(&triggered, Some(700.0), now).is_none());
+    }
+}
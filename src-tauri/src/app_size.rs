@@ -0,0 +1,241 @@
+// App size
+//
+// Disk usage of the app: the executable, the plugin directory, the database
+// directory of every workspace and the logs, plus the largest tables of the
+// open database. Parts above a threshold come with a suggestion of what to
+// do about them, e.g. "records table: 1.2 GB — consider retention rules".
+
+use crate::db::{Database, TableStats};
+use crate::error::AppError;
+use crate::workspaces::WorkspaceRegistry;
+use serde::Serialize;
+use std::path::Path;
+
+const MB: u64 = 1024 * 1024;
+
+/// Release builds are expected to stay below this size
+pub const EXECUTABLE_THRESHOLD_BYTES: u64 = 20 * MB;
+
+/// Tables, workspaces and the plugin directory above this get a suggestion
+const LARGE_BYTES: u64 = 100 * MB;
+
+/// Logs above this get a suggestion
+const LARGE_LOGS_BYTES: u64 = 50 * MB;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceSize {
+    pub name: String,
+    pub data_bytes: u64,
+    pub active: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppSize {
+    /// Sum of the executable, plugins, workspace data and logs
+    pub total_bytes: u64,
+    pub executable_bytes: u64,
+    pub plugins_bytes: u64,
+    pub logs_bytes: u64,
+    pub workspaces: Vec<WorkspaceSize>,
+    /// Tables of the open database, largest first
+    pub tables: Vec<TableStats>,
+    pub suggestions: Vec<String>,
+}
+
+/// Total size of the files below `path` (0 if it does not exist)
+pub fn dir_size(path: &Path) -> u64 {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| dir_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Measure the app's disk usage
+pub async fn measure(
+    db: &Database,
+    registry: &WorkspaceRegistry,
+    plugin_dir: &Path,
+) -> Result<AppSize, AppError> {
+    let executable_bytes = std::env::current_exe()
+        .map(|path| dir_size(&path))
+        .unwrap_or(0);
+    let plugins_bytes = dir_size(plugin_dir);
+    let logs_bytes = crate::logging::log_dir()
+        .map(|dir| dir_size(&dir))
+        .unwrap_or(0);
+    let workspaces: Vec<WorkspaceSize> = registry
+        .workspaces
+        .iter()
+        .map(|workspace| WorkspaceSize {
+            name: workspace.name.clone(),
+            data_bytes: dir_size(&workspace.data_dir),
+            active: workspace.name == registry.active,
+        })
+        .collect();
+    let tables = db.table_stats().await?;
+
+    let total_bytes = executable_bytes
+        + plugins_bytes
+        + logs_bytes
+        + workspaces.iter().map(|w| w.data_bytes).sum::<u64>();
+    let mut size = AppSize {
+        total_bytes,
+        executable_bytes,
+        plugins_bytes,
+        logs_bytes,
+        workspaces,
+        tables,
+        suggestions: Vec::new(),
+    };
+    size.suggestions = suggestions(&size);
+    Ok(size)
+}
+
+/// What could be done about the parts that take up a lot of space
+pub fn suggestions(size: &AppSize) -> Vec<String> {
+    let mut suggestions = Vec::new();
+
+    for table in size.tables.iter().filter(|t| t.size_bytes > LARGE_BYTES) {
+        let advice = match table.table.as_str() {
+            "records" => "consider retention rules",
+            "fetch_runs"
+            | "notifications"
+            | "prompt_renders"
+            | "dashboard_versions"
+            | "dashboard_snapshots"
+            | "undo_journal" => "consider deleting old entries",
+            "attachments" => "consider removing unused attachments",
+            _ => "consider deleting unused data",
+        };
+        suggestions.push(format!(
+            "{} table: {} — {}",
+            table.table,
+            format_bytes(table.size_bytes),
+            advice
+        ));
+    }
+
+    for workspace in size
+        .workspaces
+        .iter()
+        .filter(|w| !w.active && w.data_bytes > LARGE_BYTES)
+    {
+        suggestions.push(format!(
+            "workspace {}: {} — delete it if it is no longer needed",
+            workspace.name,
+            format_bytes(workspace.data_bytes)
+        ));
+    }
+
+    if size.plugins_bytes > LARGE_BYTES {
+        suggestions.push(format!(
+            "plugins: {} — consider uninstalling unused plugins",
+            format_bytes(size.plugins_bytes)
+        ));
+    }
+    if size.logs_bytes > LARGE_LOGS_BYTES {
+        suggestions.push(format!(
+            "logs: {} — consider deleting old log files",
+            format_bytes(size.logs_bytes)
+        ));
+    }
+    if size.executable_bytes > EXECUTABLE_THRESHOLD_BYTES {
+        suggestions.push(format!(
+            "executable: {} — above the {} target, check that it is a release build",
+            format_bytes(size.executable_bytes),
+            format_bytes(EXECUTABLE_THRESHOLD_BYTES)
+        ));
+    }
+
+    suggestions
+}
+
+/// Size with a binary unit, e.g. "1.2 GB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dir_size() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a"), [0u8; 100]).unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        std::fs::write(temp_dir.path().join("sub").join("b"), [0u8; 50]).unwrap();
+
+        assert_eq!(dir_size(temp_dir.path()), 150);
+        assert_eq!(dir_size(&temp_dir.path().join("missing")), 0);
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1288 * MB), "1.3 GB");
+
+        let size = AppSize {
+            total_bytes: 0,
+            executable_bytes: 15 * MB,
+            plugins_bytes: 10 * MB,
+            logs_bytes: 60 * MB,
+            workspaces: vec![
+                WorkspaceSize {
+                    name: "default".to_string(),
+                    data_bytes: 2048 * MB,
+                    active: true,
+                },
+                WorkspaceSize {
+                    name: "old".to_string(),
+                    data_bytes: 300 * MB,
+                    active: false,
+                },
+            ],
+            tables: vec![
+                TableStats {
+                    table: "records".to_string(),
+                    rows: 1_000_000,
+                    size_bytes: 1229 * MB,
+                },
+                TableStats {
+                    table: "pages".to_string(),
+                    rows: 10,
+                    size_bytes: MB,
+                },
+            ],
+            suggestions: Vec::new(),
+        };
+
+        assert_eq!(
+            suggestions(&size),
+            vec![
+                "records table: 1.2 GB — consider retention rules",
+                "workspace old: 300.0 MB — delete it if it is no longer needed",
+                "logs: 60.0 MB — consider deleting old log files",
+            ]
+        );
+    }
+}
@@ -0,0 +1,406 @@
+// Attachments module
+//
+// Stores files attached to tickets, pages or records on disk (under the
+// workspace data dir) with a metadata row per file in the `attachments`
+// table. Files are copied in, so the original can be moved or deleted.
+// Files attached to a ticket comment belong to the ticket and carry the
+// comment's ID. Database exports include the metadata rows; the files stay in
+// the workspace's attachments directory.
+
+use crate::db::Database;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use surrealdb::sql::Thing;
+
+/// Tables files can be attached to
+pub const OWNER_TABLES: &[&str] = &["tickets", "pages", "records"];
+
+/// Directory attachments are stored in for a workspace data dir
+pub fn storage_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("attachments")
+}
+
+// ============================================================================
+// Attachment Model
+// ============================================================================
+
+/// Attachment as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AttachmentRecord {
+    pub id: Thing,
+    pub owner: Thing,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment_id: Option<String>,
+    pub file_name: String,
+    /// File name inside the storage directory
+    pub stored_name: String,
+    pub content_type: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// User-facing attachment with String IDs and the absolute file path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: String,
+    pub owner_type: String,
+    pub owner_id: String,
+    /// Ticket comment the file belongs to
+    pub comment_id: Option<String>,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+    pub path: String,
+}
+
+impl AttachmentRecord {
+    fn into_attachment(self, storage_dir: &Path) -> Attachment {
+        Attachment {
+            id: self.id.id.to_raw(),
+            owner_type: self.owner.tb.clone(),
+            owner_id: self.owner.id.to_raw(),
+            comment_id: self.comment_id,
+            file_name: self.file_name,
+            content_type: self.content_type,
+            size_bytes: self.size_bytes,
+            sha256: self.sha256,
+            created_at: self.created_at,
+            path: storage_dir
+                .join(&self.stored_name)
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+}
+
+/// Resolve an owner reference ("tickets", "abc" or "tickets", "tickets:abc")
+fn owner_thing(owner_type: &str, owner_id: &str) -> Result<Thing, AppError> {
+    if !OWNER_TABLES.contains(&owner_type) {
+        return Err(AppError::Validation(format!(
+            "Cannot attach files to '{}' (expected one of: {})",
+            owner_type,
+            OWNER_TABLES.join(", ")
+        )));
+    }
+
+    let key = owner_id
+        .strip_prefix(owner_type)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .unwrap_or(owner_id)
+        .trim_start_matches('⟨')
+        .trim_end_matches('⟩');
+    if key.is_empty() {
+        return Err(AppError::Validation("Owner ID cannot be empty".to_string()));
+    }
+
+    Ok(Thing::from((owner_type, key)))
+}
+
+/// Best-effort MIME type from the file extension
+fn guess_content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        _ => "application/octet-stream",
+    }
+}
+
+// ============================================================================
+// Attachment Operations
+// ============================================================================
+
+impl Database {
+    /// Copy a file into the storage directory and attach it to a ticket, page or record
+    pub async fn add_attachment(
+        &self,
+        storage_dir: &Path,
+        owner_type: &str,
+        owner_id: &str,
+        source: &Path,
+    ) -> Result<Attachment, AppError> {
+        let owner = owner_thing(owner_type, owner_id)?;
+        self.attach(storage_dir, owner, None, source).await
+    }
+
+    /// Attach a file to a comment of a ticket
+    pub async fn add_comment_attachment(
+        &self,
+        storage_dir: &Path,
+        ticket_id: &str,
+        comment_id: &str,
+        source: &Path,
+    ) -> Result<Attachment, AppError> {
+        let owner = owner_thing("tickets", ticket_id)?;
+        if !self.ticket_has_comment(ticket_id, comment_id).await? {
+            return Err(AppError::NotFound(format!(
+                "Comment {} not found on {}",
+                comment_id, owner
+            )));
+        }
+        self.attach(storage_dir, owner, Some(comment_id.to_string()), source)
+            .await
+    }
+
+    async fn attach(
+        &self,
+        storage_dir: &Path,
+        owner: Thing,
+        comment_id: Option<String>,
+        source: &Path,
+    ) -> Result<Attachment, AppError> {
+        let mut result = self
+            .query("RETURN record::exists($owner)")
+            .bind(("owner", owner.clone()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check owner: {}", e)))?;
+        let exists: Option<bool> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to check owner: {}", e)))?;
+        if exists != Some(true) {
+            return Err(AppError::NotFound(format!("{} not found", owner)));
+        }
+
+        if !source.is_file() {
+            return Err(AppError::Validation(format!(
+                "Not a file: {}",
+                source.display()
+            )));
+        }
+        let file_name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "attachment".to_string());
+
+        let content = std::fs::read(source)?;
+        let sha256 = hex::encode(Sha256::digest(&content));
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let stored_name = match source.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                format!("{}.{}", id, ext.to_ascii_lowercase())
+            }
+            _ => id.clone(),
+        };
+
+        std::fs::create_dir_all(storage_dir)?;
+        std::fs::write(storage_dir.join(&stored_name), &content)?;
+
+        let record = AttachmentRecord {
+            id: Thing::from(("attachments", id.as_str())),
+            owner,
+            comment_id,
+            file_name,
+            stored_name: stored_name.clone(),
+            content_type: guess_content_type(source).to_string(),
+            size_bytes: content.len() as u64,
+            sha256,
+            created_at: Utc::now(),
+        };
+
+        let created: Option<AttachmentRecord> = match self
+            .db
+            .create(("attachments", id.as_str()))
+            .content(record)
+            .await
+        {
+            Ok(created) => created,
+            Err(e) => {
+                // Don't leave orphaned files behind
+                let _ = std::fs::remove_file(storage_dir.join(&stored_name));
+                return Err(AppError::Database(format!(
+                    "Failed to create attachment: {}",
+                    e
+                )));
+            }
+        };
+
+        let attachment = created
+            .ok_or_else(|| AppError::Database("Failed to create attachment".to_string()))?
+            .into_attachment(storage_dir);
+        tracing::info!(
+            "Attached '{}' ({} bytes) to {}:{}",
+            attachment.file_name,
+            attachment.size_bytes,
+            attachment.owner_type,
+            attachment.owner_id
+        );
+        Ok(attachment)
+    }
+
+    /// Get an attachment by ID ("attachments:<id>" or bare id)
+    pub async fn get_attachment(
+        &self,
+        storage_dir: &Path,
+        id: &str,
+    ) -> Result<Attachment, AppError> {
+        let key = id.strip_prefix("attachments:").unwrap_or(id);
+        let record: Option<AttachmentRecord> = self
+            .db
+            .select(("attachments", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get attachment: {}", e)))?;
+
+        record
+            .map(|r| r.into_attachment(storage_dir))
+            .ok_or_else(|| AppError::NotFound(format!("Attachment {} not found", id)))
+    }
+
+    /// List the attachments of a ticket, page or record, oldest first
+    pub async fn list_attachments(
+        &self,
+        storage_dir: &Path,
+        owner_type: &str,
+        owner_id: &str,
+    ) -> Result<Vec<Attachment>, AppError> {
+        let owner = owner_thing(owner_type, owner_id)?;
+
+        let mut result = self
+            .query("SELECT * FROM attachments WHERE owner = $owner ORDER BY created_at ASC")
+            .bind(("owner", owner))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query attachments: {}", e)))?;
+
+        let records: Vec<AttachmentRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse attachments: {}", e)))?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| r.into_attachment(storage_dir))
+            .collect())
+    }
+
+    /// Delete an attachment and its file
+    pub async fn delete_attachment(&self, storage_dir: &Path, id: &str) -> Result<(), AppError> {
+        let attachment = self.get_attachment(storage_dir, id).await?;
+
+        let _: Option<AttachmentRecord> = self
+            .db
+            .delete(("attachments", attachment.id.as_str()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete attachment: {}", e)))?;
+
+        if let Err(e) = std::fs::remove_file(&attachment.path) {
+            tracing::warn!(
+                "Failed to remove attachment file {}: {}",
+                attachment.path,
+                e
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_attachment_lifecycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("data")).await.unwrap();
+        let storage = storage_dir(&temp_dir.path().join("data"));
+
+        let ticket = db
+            .create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": "Broken build",
+                    "ticket_type": "bug"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let source = temp_dir.path().join("Screenshot.PNG");
+        std::fs::write(&source, b"not really a png").unwrap();
+
+        assert!(db
+            .add_attachment(&storage, "tickets", "missing", &source)
+            .await
+            .is_err());
+        assert!(db
+            .add_attachment(&storage, "dashboards", &ticket.id, &source)
+            .await
+            .is_err());
+
+        let attachment = db
+            .add_attachment(&storage, "tickets", &ticket.id, &source)
+            .await
+            .unwrap();
+        assert_eq!(attachment.file_name, "Screenshot.PNG");
+        assert_eq!(attachment.content_type, "image/png");
+        assert_eq!(attachment.size_bytes, 16);
+        assert_eq!(
+            std::fs::read(&attachment.path).unwrap(),
+            b"not really a png"
+        );
+
+        let listed = db
+            .list_attachments(&storage, "tickets", &ticket.id)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(
+            db.get_attachment(&storage, &attachment.id)
+                .await
+                .unwrap()
+                .sha256,
+            attachment.sha256
+        );
+
+        db.delete_attachment(&storage, &attachment.id)
+            .await
+            .unwrap();
+        assert!(!Path::new(&attachment.path).exists());
+        assert!(db.get_attachment(&storage, &attachment.id).await.is_err());
+
+        let comment = db
+            .add_comment(
+                &ticket.id,
+                crate::tickets::CreateCommentRequest {
+                    author: "User".to_string(),
+                    text: "See screenshot".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(db
+            .add_comment_attachment(&storage, &ticket.id, "missing", &source)
+            .await
+            .is_err());
+        let attachment = db
+            .add_comment_attachment(&storage, &ticket.id, &comment.id, &source)
+            .await
+            .unwrap();
+        assert_eq!(attachment.comment_id.as_deref(), Some(comment.id.as_str()));
+        let listed = db
+            .list_attachments(&storage, "tickets", &ticket.id)
+            .await
+            .unwrap();
+        assert_eq!(listed[0].comment_id, Some(comment.id));
+    }
+}
@@ -0,0 +1,507 @@
+// Change audit log
+//
+// Table events append an `audit_log` row for every create, update and delete
+// on records, tickets, pages and the prompt library, so "what deleted my
+// records last Tuesday?" has an answer. Writers say who they are by running
+// the write through `Database::as_actor`: a command, plugin, adapter, job or
+// the local API. The actor is kept task-locally and bound as `$actor` to every
+// statement `Database::query` runs, so concurrent writers never see each
+// other's actor. Writes without an actor are logged without one.
+//
+// Entries are never edited; the `audit` job deletes those older than
+// `audit.retention_days` and the oldest beyond `audit.max_entries`.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::scheduler::{Job, Schedule};
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::IntoFuture;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::{Mutex, RwLock};
+
+pub const RETENTION_DAYS_SETTING: &str = "audit.retention_days";
+pub const MAX_ENTRIES_SETTING: &str = "audit.max_entries";
+
+const DEFAULT_RETENTION_DAYS: u64 = 90;
+const DEFAULT_MAX_ENTRIES: u64 = 100_000;
+
+/// Entries returned by `get_audit_log` unless the filter asks for fewer
+const DEFAULT_LIMIT: usize = 200;
+
+/// Query parameter the table events read the actor from
+pub(crate) const ACTOR_PARAM: &str = "actor";
+
+/// How often old entries are pruned
+const PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Audited tables and the expression labelling their rows in the log
+const AUDITED_TABLES: &[(&str, &str)] = &[
+    ("records", "$value.metadata.title ?? $value.record_type"),
+    ("tickets", "$value.title"),
+    ("pages", "$value.title"),
+    ("prompt_packages", "$value.name"),
+    ("prompt_sections", "$value.name"),
+    ("prompt_templates", "$value.name"),
+    ("prompt_separator_sets", "$value.name"),
+    ("prompt_data_types", "$value.name"),
+    ("prompt_tags", "$value.name"),
+    ("prompt_presets", "$value.name"),
+    ("prompt_chains", "$value.name"),
+];
+
+tokio::task_local! {
+    /// Actor of the writes made inside `Database::as_actor`
+    static ACTOR: Actor;
+}
+
+/// Actor of the `Database::as_actor` call the current code runs in, if any
+pub fn current_actor() -> Option<Actor> {
+    ACTOR.try_with(Actor::clone).ok()
+}
+
+/// What kind of code made a change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorKind {
+    /// A Tauri command, named after the command
+    Command,
+    /// A plugin, named after its adapter type
+    Plugin,
+    /// A built-in adapter, named `<adapter type>:<source>`
+    Adapter,
+    /// A scheduled job, named after the job
+    Job,
+    /// The local HTTP API, named after the endpoint
+    Api,
+}
+
+/// Who made a change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub kind: ActorKind,
+    pub name: String,
+}
+
+impl Actor {
+    pub fn new(kind: ActorKind, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+        }
+    }
+
+    pub fn command(name: &str) -> Self {
+        Self::new(ActorKind::Command, name)
+    }
+
+    pub fn plugin(adapter_type: &str) -> Self {
+        Self::new(ActorKind::Plugin, adapter_type)
+    }
+
+    pub fn adapter(adapter_type: &str, source: &str) -> Self {
+        Self::new(ActorKind::Adapter, format!("{}:{}", adapter_type, source))
+    }
+
+    pub fn job(name: &str) -> Self {
+        Self::new(ActorKind::Job, name)
+    }
+
+    pub fn api(endpoint: &str) -> Self {
+        Self::new(ActorKind::Api, endpoint)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AuditEntryRecord {
+    id: Thing,
+    entity: String,
+    action: AuditAction,
+    record_id: String,
+    label: Option<String>,
+    actor: Option<Actor>,
+    at: DateTime<Utc>,
+}
+
+/// A logged change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: String,
+    /// Table of the changed row, e.g. "records" or "prompt_sections"
+    pub entity: String,
+    pub action: AuditAction,
+    /// Full ID of the changed row, e.g. "tickets:abc123"
+    pub record_id: String,
+    /// Title or name of the row when it was changed
+    pub label: Option<String>,
+    /// None for writes that didn't say who they are
+    pub actor: Option<Actor>,
+    pub at: DateTime<Utc>,
+}
+
+impl From<AuditEntryRecord> for AuditEntry {
+    fn from(record: AuditEntryRecord) -> Self {
+        Self {
+            id: record.id.id.to_raw(),
+            entity: record.entity,
+            action: record.action,
+            record_id: record.record_id,
+            label: record.label,
+            actor: record.actor,
+            at: record.at,
+        }
+    }
+}
+
+/// Which entries `get_audit_log` returns; every field is optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditFilter {
+    pub entity: Option<String>,
+    pub action: Option<AuditAction>,
+    pub actor_kind: Option<ActorKind>,
+    pub actor_name: Option<String>,
+    pub record_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Newest entries first; 200 by default
+    pub limit: Option<usize>,
+}
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new(
+            RETENTION_DAYS_SETTING,
+            SettingType::Number,
+            DEFAULT_RETENTION_DAYS.into(),
+            "audit",
+        )
+        .describe(
+            "Audit log retention",
+            "Days changes are kept in the audit log",
+        )
+        .range(Some(1.0), Some(3650.0)),
+        SettingDefinition::new(
+            MAX_ENTRIES_SETTING,
+            SettingType::Number,
+            DEFAULT_MAX_ENTRIES.into(),
+            "audit",
+        )
+        .describe(
+            "Audit log size",
+            "Older entries beyond this count are deleted",
+        )
+        .range(Some(1000.0), None),
+    ]
+}
+
+/// Index and table event definitions, applied by a schema migration
+pub fn audit_schema() -> String {
+    let mut statements = "
+        DEFINE INDEX OVERWRITE audit_log_at ON audit_log FIELDS at;
+        DEFINE INDEX OVERWRITE audit_log_record ON audit_log FIELDS record_id;
+    "
+    .to_string();
+    for (table, label) in AUDITED_TABLES {
+        // Fetches re-upsert records; only log those whose content changed
+        let when = match *table {
+            "records" => {
+                "$event != 'UPDATE' OR $before.data != $after.data
+                 OR $before.metadata != $after.metadata"
+            }
+            _ => "true",
+        };
+        statements.push_str(&format!(
+            "DEFINE EVENT OVERWRITE audit ON TABLE {table}
+                 WHEN {when}
+                 THEN (CREATE audit_log CONTENT {{
+                     entity: '{table}',
+                     action: string::lowercase($event),
+                     record_id: <string> $value.id,
+                     label: {label},
+                     actor: ${ACTOR_PARAM},
+                     at: time::now(),
+                 }});\n"
+        ));
+    }
+    statements
+}
+
+// ============================================================================
+// Audit Log Operations
+// ============================================================================
+
+impl Database {
+    /// Run a write with the audit log entries it causes attributed to `actor`
+    /// Statements `operation` starts through `Database::query` (and the row
+    /// helpers built on it) carry the actor. They have to be started while it
+    /// runs, so pass an async fn call or block rather than a built query.
+    pub async fn as_actor<F: IntoFuture>(&self, actor: Actor, operation: F) -> F::Output {
+        ACTOR.scope(actor, operation.into_future()).await
+    }
+
+    /// Logged changes matching the filter, newest first
+    pub async fn get_audit_log(&self, filter: AuditFilter) -> Result<Vec<AuditEntry>, AppError> {
+        let mut result = self
+            .query(
+                "SELECT * FROM audit_log
+                 WHERE ($entity = NONE OR entity = $entity)
+                     AND ($action = NONE OR action = $action)
+                     AND ($actor_kind = NONE OR actor.kind = $actor_kind)
+                     AND ($actor_name = NONE OR actor.name = $actor_name)
+                     AND ($record_id = NONE OR record_id = $record_id)
+                     AND ($since = NONE OR at >= $since)
+                     AND ($until = NONE OR at <= $until)
+                 ORDER BY at DESC LIMIT $limit",
+            )
+            .bind(("entity", filter.entity))
+            .bind(("action", filter.action))
+            .bind(("actor_kind", filter.actor_kind))
+            .bind(("actor_name", filter.actor_name))
+            .bind(("record_id", filter.record_id))
+            .bind(("since", filter.since))
+            .bind(("until", filter.until))
+            .bind(("limit", filter.limit.unwrap_or(DEFAULT_LIMIT)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query the audit log: {}", e)))?;
+
+        let entries: Vec<AuditEntryRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse the audit log: {}", e)))?;
+        Ok(entries.into_iter().map(AuditEntry::from).collect())
+    }
+
+    /// Delete entries older than `max_age` and the oldest beyond `max_entries`
+    pub async fn prune_audit_log(
+        &self,
+        max_age: Duration,
+        max_entries: usize,
+    ) -> Result<usize, AppError> {
+        let mut result = self
+            .query(
+                "LET $total = count(SELECT VALUE id FROM audit_log);
+                 DELETE audit_log WHERE at < $cutoff;
+                 LET $overflow = (SELECT at FROM audit_log
+                     ORDER BY at DESC LIMIT 1 START $max_entries)[0].at;
+                 IF $overflow != NONE { DELETE audit_log WHERE at <= $overflow; };
+                 RETURN $total - count(SELECT VALUE id FROM audit_log);",
+            )
+            .bind(("cutoff", Utc::now() - max_age))
+            .bind(("max_entries", max_entries))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to prune the audit log: {}", e)))?;
+
+        let pruned: Option<usize> = result
+            .take(4)
+            .map_err(|e| AppError::Database(format!("Failed to prune the audit log: {}", e)))?;
+        Ok(pruned.unwrap_or(0))
+    }
+}
+
+/// Retention limits from the "audit.*" settings
+pub async fn limits(settings: &SettingsService) -> Result<(Duration, usize), AppError> {
+    let days = settings
+        .get_value(RETENTION_DAYS_SETTING)
+        .await?
+        .as_u64()
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    let max_entries = settings
+        .get_value(MAX_ENTRIES_SETTING)
+        .await?
+        .as_u64()
+        .unwrap_or(DEFAULT_MAX_ENTRIES);
+    Ok((Duration::days(days as i64), max_entries as usize))
+}
+
+/// Job pruning the audit log hourly
+pub fn job(settings_service: Arc<RwLock<SettingsService>>, database: Arc<Mutex<Database>>) -> Job {
+    Job::new(
+        "audit",
+        "Prune the change audit log",
+        Schedule::every(PRUNE_INTERVAL),
+        move || {
+            let settings_service = settings_service.clone();
+            let database = database.clone();
+            async move {
+                let (max_age, max_entries) = limits(&*settings_service.read().await).await?;
+                let pruned = database
+                    .lock()
+                    .await
+                    .prune_audit_log(max_age, max_entries)
+                    .await?;
+                Ok(format!("{} audit log entries pruned", pruned))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    fn record(status: &str) -> StagedRecord {
+        StagedRecord::new(
+            "gitlab_pipeline".to_string(),
+            "ci".to_string(),
+            serde_json::json!({ "id": 1, "status": status }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_attribution() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let fetcher = Actor::adapter("gitlab", "ci");
+        db.as_actor(fetcher.clone(), db.upsert_record(record("running")))
+            .await
+            .unwrap();
+        // Unchanged re-fetches aren't logged
+        db.as_actor(fetcher.clone(), db.upsert_record(record("running")))
+            .await
+            .unwrap();
+        db.as_actor(fetcher.clone(), db.upsert_record(record("success")))
+            .await
+            .unwrap();
+        let deleted = db
+            .as_actor(
+                Actor::command("delete_records_by_type"),
+                db.delete_records_by_type("gitlab_pipeline"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+
+        // Writes without an actor are logged without one
+        let ticket = db
+            .create_ticket(
+                serde_json::from_value(
+                    serde_json::json!({ "title": "Fix login", "ticket_type": "task" }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let records = db
+            .get_audit_log(AuditFilter {
+                entity: Some("records".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let actions: Vec<AuditAction> = records.iter().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::Delete,
+                AuditAction::Update,
+                AuditAction::Create
+            ]
+        );
+        assert_eq!(records[2].actor.as_ref(), Some(&fetcher));
+
+        let deletes = db
+            .get_audit_log(AuditFilter {
+                action: Some(AuditAction::Delete),
+                actor_kind: Some(ActorKind::Command),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(deletes.len(), 1);
+        assert_eq!(
+            deletes[0].actor.as_ref().unwrap().name,
+            "delete_records_by_type"
+        );
+
+        let tickets = db
+            .get_audit_log(AuditFilter {
+                record_id: Some(ticket.id.clone()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].label.as_deref(), Some("Fix login"));
+        assert_eq!(tickets[0].actor, None);
+    }
+
+    /// Upsert ten records of type `name` as the `name` command, yielding between writes
+    async fn write_records_as(db: &Database, name: &str) -> Result<(), AppError> {
+        db.as_actor(Actor::command(name), async {
+            for id in 0..10 {
+                let record = StagedRecord::new(
+                    name.to_string(),
+                    "ci".to_string(),
+                    serde_json::json!({ "id": id }),
+                );
+                db.upsert_record(record).await?;
+                tokio::task::yield_now().await;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_actors() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        // Interleave two writers on the same connection; each entry must keep
+        // the actor of the writer that caused it
+        let (first, second) = tokio::join!(
+            write_records_as(&db, "first"),
+            write_records_as(&db, "second")
+        );
+        first.unwrap();
+        second.unwrap();
+
+        let entries = db.get_audit_log(AuditFilter::default()).await.unwrap();
+        assert_eq!(entries.len(), 20);
+        for entry in entries {
+            assert_eq!(entry.actor.map(|actor| actor.name), entry.label);
+        }
+
+        // Nothing is left behind for writes outside `as_actor`
+        db.upsert_record(record("running")).await.unwrap();
+        let latest = db
+            .get_audit_log(AuditFilter {
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(latest[0].actor, None);
+    }
+
+    #[tokio::test]
+    async fn test_prune_audit_log() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        for status in ["running", "failed", "success"] {
+            db.upsert_record(record(status)).await.unwrap();
+        }
+
+        let pruned = db.prune_audit_log(Duration::days(90), 2).await.unwrap();
+        assert_eq!(pruned, 1);
+        let remaining = db.get_audit_log(AuditFilter::default()).await.unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[1].action, AuditAction::Update);
+
+        assert_eq!(db.prune_audit_log(Duration::zero(), 10).await.unwrap(), 2);
+    }
+}
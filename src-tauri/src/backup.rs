@@ -0,0 +1,642 @@
+// Database backups
+//
+// Shared export/import of the full database, a streaming file export for
+// large databases, and a scheduled job that periodically writes timestamped
+// backups to a configurable folder, keeping only the newest `max_keep` files.
+// When a remote target is configured, the job also uploads an encrypted copy
+// of each backup (see `remote_backup`).
+
+use crate::audit::Actor;
+use crate::db::{Database, ImportStats, StagedRecord};
+use crate::error::{AppError, HttpFailure};
+use crate::remote_backup::{upload_backup, RemoteTarget};
+use crate::scheduler::{Job, Schedule};
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+
+/// How often the backup job checks whether a backup is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Backup file name prefix; files are named `modulaur-backup-YYYYMMDD-HHMMSS.json`
+pub const FILE_PREFIX: &str = "modulaur-backup-";
+
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+const DEFAULT_MAX_KEEP: usize = 7;
+
+/// Tables included in snapshots (same as `Database::export_all_data`)
+const SNAPSHOT_TABLES: &[&str] = &[
+    "records",
+    "pages",
+    "data_sources",
+    "settings",
+    "plugin_data",
+    "tickets",
+    "attachments",
+    "sprints",
+    "boards",
+    "ticket_sequences",
+    "ticket_events",
+    "dashboards",
+    "dashboard_versions",
+    "dashboard_snapshots",
+];
+
+/// Rows read per query when streaming a snapshot to a file
+const EXPORT_CHUNK_SIZE: usize = 1000;
+
+// ============================================================================
+// Snapshot Export / Import
+// ============================================================================
+
+/// Export all database data
+pub async fn export_snapshot(database: &Mutex<Database>) -> Result<serde_json::Value, AppError> {
+    let export = database.lock().await.export_all_data().await?;
+    tracing::info!("Database export complete");
+    Ok(export)
+}
+
+/// Import database data
+/// merge_strategy options:
+/// - "replace": Clear existing data first, then import
+/// - "merge": Imported rows overwrite existing rows with the same ID
+/// - "skip": Keep existing rows on conflict
+/// The changes are attributed to `actor` in the audit log.
+pub async fn import_snapshot(
+    database: &Mutex<Database>,
+    import_data: serde_json::Value,
+    merge_strategy: &str,
+    actor: Actor,
+) -> Result<ImportStats, AppError> {
+    let db = database.lock().await;
+    let stats = db
+        .as_actor(actor, db.import_data(import_data, merge_strategy))
+        .await?;
+
+    tracing::info!("Database import complete");
+    Ok(stats)
+}
+
+// ============================================================================
+// Streaming File Export
+// ============================================================================
+
+/// File layout of a streamed snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotFormat {
+    /// Same document as `export_database` (importable with `import_database`)
+    #[default]
+    Json,
+    /// A header line, then one `{"table": ..., "row": ...}` object per line
+    Ndjson,
+}
+
+/// Progress of a streamed export, reported after every chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub table: String,
+    pub exported: usize,
+    pub total: usize,
+}
+
+/// Result of a streamed export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileExportSummary {
+    pub path: String,
+    pub format: SnapshotFormat,
+    /// Rows written per table
+    pub tables: Vec<(String, usize)>,
+    pub size_bytes: u64,
+}
+
+/// Writes rows table by table in either snapshot format
+struct SnapshotWriter<W: Write> {
+    out: W,
+    format: SnapshotFormat,
+    table: String,
+    tables_written: usize,
+    rows_in_table: usize,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    fn begin(mut out: W, format: SnapshotFormat) -> Result<Self, AppError> {
+        let exported_at = serde_json::to_string(&Utc::now().to_rfc3339())?;
+        match format {
+            SnapshotFormat::Json => write!(
+                out,
+                "{{\"version\":\"1.0\",\"exported_at\":{},\"data\":{{",
+                exported_at
+            )?,
+            SnapshotFormat::Ndjson => writeln!(
+                out,
+                "{{\"version\":\"1.0\",\"exported_at\":{},\"format\":\"ndjson\"}}",
+                exported_at
+            )?,
+        }
+        Ok(Self {
+            out,
+            format,
+            table: String::new(),
+            tables_written: 0,
+            rows_in_table: 0,
+        })
+    }
+
+    fn begin_table(&mut self, table: &str) -> Result<(), AppError> {
+        self.table = table.to_string();
+        self.rows_in_table = 0;
+        if self.format == SnapshotFormat::Json {
+            if self.tables_written > 0 {
+                self.out.write_all(b",")?;
+            }
+            write!(self.out, "{}:[", serde_json::to_string(table)?)?;
+        }
+        Ok(())
+    }
+
+    fn write_row<T: Serialize>(&mut self, row: &T) -> Result<(), AppError> {
+        match self.format {
+            SnapshotFormat::Json => {
+                if self.rows_in_table > 0 {
+                    self.out.write_all(b",")?;
+                }
+                serde_json::to_writer(&mut self.out, row)?;
+            }
+            SnapshotFormat::Ndjson => {
+                serde_json::to_writer(
+                    &mut self.out,
+                    &serde_json::json!({ "table": self.table, "row": row }),
+                )?;
+                self.out.write_all(b"\n")?;
+            }
+        }
+        self.rows_in_table += 1;
+        Ok(())
+    }
+
+    fn end_table(&mut self) -> Result<usize, AppError> {
+        if self.format == SnapshotFormat::Json {
+            self.out.write_all(b"]")?;
+        }
+        self.tables_written += 1;
+        Ok(self.rows_in_table)
+    }
+
+    fn finish(mut self) -> Result<(), AppError> {
+        if self.format == SnapshotFormat::Json {
+            self.out.write_all(b"}}")?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Stream all tables to `path` in chunks,
+/// so large databases are never held in memory as a whole
+pub async fn export_snapshot_to_file(
+    database: &Mutex<Database>,
+    path: &Path,
+    format: SnapshotFormat,
+    mut on_progress: impl FnMut(&ExportProgress),
+) -> Result<FileExportSummary, AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(path)?;
+    let mut writer = SnapshotWriter::begin(BufWriter::new(file), format)?;
+    let mut tables = Vec::new();
+
+    for table in SNAPSHOT_TABLES {
+        let written = stream_table(database, &mut writer, table, &mut on_progress).await?;
+        tables.push((table.to_string(), written));
+    }
+
+    writer.finish()?;
+
+    let size_bytes = std::fs::metadata(path)?.len();
+    tracing::info!(
+        "Streamed database export to {:?} ({} bytes): {:?}",
+        path,
+        size_bytes,
+        tables
+    );
+
+    Ok(FileExportSummary {
+        path: path.to_string_lossy().to_string(),
+        format,
+        tables,
+        size_bytes,
+    })
+}
+
+async fn stream_table<W: Write>(
+    database: &Mutex<Database>,
+    writer: &mut SnapshotWriter<W>,
+    table: &str,
+    on_progress: &mut impl FnMut(&ExportProgress),
+) -> Result<usize, AppError> {
+    let total = database.lock().await.count_table(table).await?;
+    writer.begin_table(table)?;
+
+    let mut exported = 0;
+    loop {
+        // Lock per chunk so other commands can run during long exports
+        let db = database.lock().await;
+        let written = if table == "records" {
+            // Records keep their typed shape, matching `export_all_data`
+            let rows: Vec<StagedRecord> = db
+                .export_table_chunk(table, exported, EXPORT_CHUNK_SIZE)
+                .await?;
+            rows.iter().try_for_each(|row| writer.write_row(row))?;
+            rows.len()
+        } else {
+            let rows = db
+                .export_table_chunk_json(table, exported, EXPORT_CHUNK_SIZE)
+                .await?;
+            rows.iter().try_for_each(|row| writer.write_row(row))?;
+            rows.len()
+        };
+        drop(db);
+        exported += written;
+
+        on_progress(&ExportProgress {
+            table: table.to_string(),
+            exported,
+            total: total.max(exported),
+        });
+
+        if written < EXPORT_CHUNK_SIZE {
+            break;
+        }
+    }
+
+    writer.end_table()
+}
+
+// ============================================================================
+// Backup Files
+// ============================================================================
+
+/// Backup settings, stored in the `backup` settings category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    pub folder: PathBuf,
+    pub max_keep: usize,
+}
+
+/// The `backup.*` settings
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new(
+            "backup.enabled",
+            SettingType::Boolean,
+            false.into(),
+            "backup",
+        )
+        .describe("Scheduled backups", "Back up the database periodically"),
+        SettingDefinition::new(
+            "backup.interval_hours",
+            SettingType::Number,
+            DEFAULT_INTERVAL_HOURS.into(),
+            "backup",
+        )
+        .describe("Backup interval", "Hours between backups")
+        .range(Some(1.0), None),
+        SettingDefinition::new("backup.folder", SettingType::String, "".into(), "backup").describe(
+            "Backup folder",
+            "Where backups are written (empty: next to the database)",
+        ),
+        SettingDefinition::new(
+            "backup.max_keep",
+            SettingType::Number,
+            DEFAULT_MAX_KEEP.into(),
+            "backup",
+        )
+        .describe("Backups kept", "Older backups are deleted")
+        .range(Some(1.0), None),
+    ]
+}
+
+impl BackupConfig {
+    /// Load the backup settings, falling back to defaults
+    pub async fn load(settings: &SettingsService) -> Result<Self, AppError> {
+        let enabled = settings.get_setting("backup.enabled").await?;
+        let interval = settings.get_setting("backup.interval_hours").await?;
+        let folder = settings.get_setting("backup.folder").await?;
+        let max_keep = settings.get_setting("backup.max_keep").await?;
+
+        Ok(Self {
+            enabled: enabled.as_deref() == Some("true"),
+            interval_hours: interval
+                .and_then(|v| v.parse().ok())
+                .filter(|h| *h > 0)
+                .unwrap_or(DEFAULT_INTERVAL_HOURS),
+            folder: folder
+                .filter(|f| !f.trim().is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(default_folder),
+            max_keep: max_keep
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(DEFAULT_MAX_KEEP),
+        })
+    }
+}
+
+/// Default backup folder next to the database
+pub fn default_folder() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("modulaur")
+        .join("backups")
+}
+
+/// A backup file on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Write a timestamped backup and rotate old ones
+pub async fn write_backup(
+    database: &Mutex<Database>,
+    folder: &Path,
+    max_keep: usize,
+) -> Result<BackupInfo, AppError> {
+    let created_at = Utc::now();
+    let file_name = format!("{}{}.json", FILE_PREFIX, created_at.format("%Y%m%d-%H%M%S"));
+    let path = folder.join(&file_name);
+
+    let summary = export_snapshot_to_file(database, &path, SnapshotFormat::Json, |_| {}).await?;
+    tracing::info!("Wrote database backup: {:?}", path);
+
+    rotate_backups(folder, max_keep)?;
+
+    Ok(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        file_name,
+        size_bytes: summary.size_bytes,
+        created_at,
+    })
+}
+
+/// List backups in a folder, newest first
+pub fn list_backups(folder: &Path) -> Result<Vec<BackupInfo>, AppError> {
+    if !folder.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !file_name.starts_with(FILE_PREFIX) || !file_name.ends_with(".json") {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        let created_at = metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+
+        backups.push(BackupInfo {
+            path: entry.path().to_string_lossy().to_string(),
+            file_name,
+            size_bytes: metadata.len(),
+            created_at,
+        });
+    }
+
+    // Timestamped names sort chronologically
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(backups)
+}
+
+/// Delete all but the newest `max_keep` backups
+fn rotate_backups(folder: &Path, max_keep: usize) -> Result<(), AppError> {
+    for old in list_backups(folder)?.into_iter().skip(max_keep) {
+        tracing::info!("Removing old backup: {}", old.path);
+        std::fs::remove_file(&old.path)?;
+    }
+    Ok(())
+}
+
+/// Restore a backup file, replacing the current data
+pub async fn restore_backup(
+    database: &Mutex<Database>,
+    path: &Path,
+) -> Result<ImportStats, AppError> {
+    let contents = std::fs::read(path)?;
+    let snapshot: serde_json::Value = serde_json::from_slice(&contents)?;
+    if snapshot.get("data").is_none() {
+        return Err(AppError::Validation(format!(
+            "Not a Modulaur backup file: {:?}",
+            path
+        )));
+    }
+
+    tracing::info!("Restoring database backup: {:?}", path);
+    import_snapshot(
+        database,
+        snapshot,
+        "replace",
+        Actor::command("restore_backup"),
+    )
+    .await
+}
+
+// ============================================================================
+// Scheduled Job
+// ============================================================================
+
+/// Job writing a backup when one is due by the "backup.*" settings; checks
+/// every five minutes by default
+pub fn job(settings_service: Arc<RwLock<SettingsService>>, database: Arc<Mutex<Database>>) -> Job {
+    Job::new(
+        "backup",
+        "Write scheduled database backups",
+        Schedule::every(CHECK_INTERVAL),
+        move || {
+            let settings_service = settings_service.clone();
+            let database = database.clone();
+            async move {
+                // A broken remote config must not stop the local backup
+                let (config, remote) = {
+                    let settings = settings_service.read().await;
+                    (
+                        BackupConfig::load(&settings).await?,
+                        RemoteTarget::load(&settings).await,
+                    )
+                };
+                if !config.enabled {
+                    return Ok("Scheduled backups are off".to_string());
+                }
+
+                let last_backup = list_backups(&config.folder)
+                    .ok()
+                    .and_then(|backups| backups.first().map(|b| b.created_at));
+                let due = last_backup.is_none_or(|last| {
+                    Utc::now() - last >= chrono::Duration::hours(config.interval_hours as i64)
+                });
+                if !due {
+                    return Ok("No backup due".to_string());
+                }
+
+                let backup = write_backup(&database, &config.folder, config.max_keep).await?;
+                let remote = match remote {
+                    Ok(Some(remote)) => remote,
+                    Ok(None) => return Ok(format!("Wrote {}", backup.file_name)),
+                    Err(e) => {
+                        return Err(AppError::Config(format!(
+                            "Wrote {}, but remote backups are misconfigured: {}",
+                            backup.file_name, e
+                        )))
+                    }
+                };
+                let uploaded = upload_backup(&remote, &backup, config.max_keep)
+                    .await
+                    .map_err(|e| {
+                        let failure = match &e {
+                            AppError::Http { failure, .. } => *failure,
+                            _ => HttpFailure::Request,
+                        };
+                        AppError::Http {
+                            failure,
+                            message: format!(
+                                "Wrote {}, but the upload to {} failed: {}",
+                                backup.file_name,
+                                remote.kind(),
+                                e
+                            ),
+                        }
+                    })?;
+                Ok(format!(
+                    "Wrote {} and uploaded {} to {}",
+                    backup.file_name,
+                    uploaded.id,
+                    remote.kind()
+                ))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rotate_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        for day in 1..=5 {
+            let name = format!("{}2024010{}-120000.json", FILE_PREFIX, day);
+            std::fs::write(temp_dir.path().join(name), "{}").unwrap();
+        }
+        std::fs::write(temp_dir.path().join("notes.json"), "{}").unwrap();
+
+        rotate_backups(temp_dir.path(), 2).unwrap();
+
+        let names: Vec<String> = list_backups(temp_dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|b| b.file_name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                format!("{}20240105-120000.json", FILE_PREFIX),
+                format!("{}20240104-120000.json", FILE_PREFIX),
+            ]
+        );
+        assert!(temp_dir.path().join("notes.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_streamed_export_restores() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Mutex::new(Database::new(temp_dir.path().join("a")).await.unwrap());
+
+        for i in 0..3 {
+            let record = StagedRecord::new(
+                "rest_api".to_string(),
+                "stream".to_string(),
+                serde_json::json!({ "id": i }),
+            );
+            database.lock().await.upsert_record(record).await.unwrap();
+        }
+
+        let ticket = database
+            .lock()
+            .await
+            .create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": "Exported ticket",
+                    "ticket_type": "task"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let screenshot = temp_dir.path().join("screenshot.png");
+        std::fs::write(&screenshot, b"png").unwrap();
+        let storage = temp_dir.path().join("files");
+        database
+            .lock()
+            .await
+            .add_attachment(&storage, "tickets", &ticket.id, &screenshot)
+            .await
+            .unwrap();
+
+        let ndjson_path = temp_dir.path().join("export.ndjson");
+        let mut progress = Vec::new();
+        let summary =
+            export_snapshot_to_file(&database, &ndjson_path, SnapshotFormat::Ndjson, |p| {
+                progress.push(p.clone())
+            })
+            .await
+            .unwrap();
+        assert_eq!(summary.tables[0], ("records".to_string(), 3));
+        assert_eq!(progress[0].exported, 3);
+        let lines = std::fs::read_to_string(&ndjson_path).unwrap();
+        assert_eq!(
+            lines
+                .lines()
+                .filter(|l| l.contains("\"table\":\"records\""))
+                .count(),
+            3
+        );
+
+        let backup = write_backup(&database, &temp_dir.path().join("backups"), 3)
+            .await
+            .unwrap();
+        let restored = Mutex::new(Database::new(temp_dir.path().join("b")).await.unwrap());
+        let stats = restore_backup(&restored, Path::new(&backup.path))
+            .await
+            .unwrap();
+        assert_eq!(stats.records_imported, 3);
+        assert!(stats.errors.is_empty(), "{:?}", stats.errors);
+        assert_eq!(stats.tickets_imported, 1);
+        assert_eq!(stats.attachments_imported, 1);
+        let attachments = restored
+            .lock()
+            .await
+            .list_attachments(&storage, "tickets", &ticket.id)
+            .await
+            .unwrap();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].file_name, "screenshot.png");
+    }
+}
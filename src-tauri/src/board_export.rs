@@ -0,0 +1,326 @@
+// Board export and import
+//
+// Bundles one board into a self-contained JSON document: its column
+// configuration, its tickets (with their comments and checklists), the
+// relations between them and the time tracked on them. Unlike the full
+// database export this can be shared or archived per project and imported
+// into another board or workspace. Imported tickets get new IDs; parent,
+// linked-ticket and relation references inside the board follow them, and
+// references to tickets outside the board are dropped. Sprints belong to the
+// workspace, so imported tickets start outside any sprint.
+
+use crate::boards::{BoardConfig, DEFAULT_BOARD};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::tickets::{ticket_key, ArchiveFilter, Ticket, TicketFilters, TicketRelationKind};
+use crate::time_entries::{CreateTimeEntryRequest, TimeEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use surrealdb::sql::Thing;
+
+/// Format version of board documents
+const BOARD_EXPORT_VERSION: &str = "1.0";
+
+/// Relation between two tickets of an exported board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardRelation {
+    pub from: String,
+    pub to: String,
+    pub kind: TicketRelationKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardExport {
+    pub version: String,
+    pub exported_at: String,
+    pub board: BoardConfig,
+    pub tickets: Vec<Ticket>,
+    #[serde(default)]
+    pub relations: Vec<BoardRelation>,
+    /// Finished time entries of the board's tickets
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardImport {
+    pub board_id: String,
+    pub tickets: usize,
+    pub relations: usize,
+    pub time_entries: usize,
+}
+
+impl Database {
+    /// Export a board with its tickets, relations and tracked time
+    pub async fn export_board(&self, board_id: &str) -> Result<BoardExport, AppError> {
+        let board = self.get_board_config(board_id).await?;
+        let tickets = self
+            .get_tickets(Some(TicketFilters {
+                board_id: Some(board_id.to_string()),
+                archived: ArchiveFilter::All,
+                ..Default::default()
+            }))
+            .await?;
+
+        let on_board = |id: &str| tickets.iter().any(|ticket| ticket.id == id);
+        let mut relations = Vec::new();
+        let mut time_entries = Vec::new();
+        for ticket in &tickets {
+            relations.extend(
+                ticket
+                    .relations
+                    .iter()
+                    .filter(|relation| relation.outgoing && on_board(&relation.ticket_id))
+                    .map(|relation| BoardRelation {
+                        from: ticket.id.clone(),
+                        to: relation.ticket_id.clone(),
+                        kind: relation.kind,
+                    }),
+            );
+            time_entries.extend(
+                self.get_ticket_time_entries(&ticket.id)
+                    .await?
+                    .into_iter()
+                    .filter(|entry| entry.end_time.is_some()),
+            );
+        }
+
+        tracing::info!(
+            "Exported board '{}': {} tickets, {} relations, {} time entries",
+            board_id,
+            tickets.len(),
+            relations.len(),
+            time_entries.len()
+        );
+        Ok(BoardExport {
+            version: BOARD_EXPORT_VERSION.to_string(),
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            board,
+            tickets,
+            relations,
+            time_entries,
+        })
+    }
+
+    /// Import a board document into `board_id` (the exported board's ID
+    /// without it); the target board must not have tickets yet
+    pub async fn import_board(
+        &self,
+        export: BoardExport,
+        board_id: Option<&str>,
+    ) -> Result<BoardImport, AppError> {
+        if export.version.split('.').next() != BOARD_EXPORT_VERSION.split('.').next() {
+            return Err(AppError::Validation(format!(
+                "Unsupported board export version {}",
+                export.version
+            )));
+        }
+        let board_id = board_id.unwrap_or(&export.board.board_id).to_string();
+        let existing: usize = self.board_status_counts(&board_id).await?.values().sum();
+        if existing > 0 {
+            return Err(AppError::Validation(format!(
+                "Board '{}' already has {} tickets; import into an empty board",
+                board_id, existing
+            )));
+        }
+        self.save_board_columns(&board_id, export.board.columns)
+            .await?;
+        let board = self
+            .set_board_key_prefix(&board_id, &export.board.key_prefix)
+            .await?;
+
+        // Create the tickets first so references can point at the new IDs;
+        // they get new keys too, as the old numbers may be taken here
+        let keys = self
+            .reserve_ticket_keys(&board_id, export.tickets.len())
+            .await?;
+        let mut ids: HashMap<String, String> = HashMap::new();
+        for (ticket, key) in export.tickets.iter().zip(keys) {
+            let mut content = serde_json::to_value(ticket)?;
+            let Some(object) = content.as_object_mut() else {
+                continue;
+            };
+            for field in ["id", "checklist_progress", "relations"] {
+                object.remove(field);
+            }
+            object.insert("key".to_string(), key.into());
+            object.insert("sprint_id".to_string(), serde_json::Value::Null);
+            object.insert("parent_id".to_string(), serde_json::Value::Null);
+            object.insert("linked_tickets".to_string(), serde_json::json!([]));
+            if board.column(&ticket.status).is_none() {
+                object.insert("status".to_string(), board.start_column().id.clone().into());
+            }
+            let metadata = object
+                .entry("metadata")
+                .or_insert_with(|| serde_json::json!({}));
+            if !metadata.is_object() {
+                *metadata = serde_json::json!({});
+            }
+            if board_id == DEFAULT_BOARD {
+                if let Some(metadata) = metadata.as_object_mut() {
+                    metadata.remove("board_id");
+                }
+            } else {
+                metadata["board_id"] = board_id.clone().into();
+            }
+
+            let mut result = self
+                .query("CREATE tickets CONTENT $content RETURN VALUE id")
+                .bind(("content", content))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to import ticket: {}", e)))?;
+            let created: Option<Thing> = result
+                .take(0)
+                .map_err(|e| AppError::Database(format!("Failed to import ticket: {}", e)))?;
+            let created = created
+                .ok_or_else(|| AppError::Database("Ticket import returned no ID".to_string()))?;
+            ids.insert(ticket.id.clone(), created.to_string());
+        }
+
+        for ticket in &export.tickets {
+            let parent_id = ticket.parent_id.as_ref().and_then(|id| ids.get(id));
+            let linked: Vec<&String> = ticket
+                .linked_tickets
+                .iter()
+                .filter_map(|id| ids.get(id))
+                .collect();
+            if parent_id.is_none() && linked.is_empty() {
+                continue;
+            }
+            self.query(
+                "UPDATE type::thing('tickets', $key) \
+                     SET parent_id = $parent, linked_tickets = $linked",
+            )
+            .bind(("key", ticket_key(&ids[&ticket.id]).to_string()))
+            .bind(("parent", parent_id.cloned()))
+            .bind(("linked", linked.into_iter().cloned().collect::<Vec<_>>()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to link imported ticket: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to link imported ticket: {}", e)))?;
+        }
+
+        let mut relations = 0;
+        for relation in &export.relations {
+            if let (Some(from), Some(to)) = (ids.get(&relation.from), ids.get(&relation.to)) {
+                self.add_ticket_relation(from, to, relation.kind).await?;
+                relations += 1;
+            }
+        }
+
+        let mut time_entries = 0;
+        for entry in export.time_entries {
+            // Entries store the ticket as "tickets:<key>"
+            let ticket_id = entry
+                .ticket_id
+                .as_ref()
+                .and_then(|id| ids.get(id).or_else(|| ids.get(&format!("tickets:{}", id))));
+            let (Some(ticket_id), Some(end_time)) = (ticket_id, entry.end_time) else {
+                continue;
+            };
+            self.add_time_entry(
+                ticket_id,
+                CreateTimeEntryRequest {
+                    start_time: entry.start_time,
+                    end_time: Some(end_time),
+                    duration_minutes: None,
+                    description: Some(entry.description),
+                    billable: Some(entry.billable),
+                    notes: entry.notes,
+                },
+            )
+            .await?;
+            time_entries += 1;
+        }
+
+        tracing::info!(
+            "Imported board '{}': {} tickets, {} relations, {} time entries",
+            board_id,
+            ids.len(),
+            relations,
+            time_entries
+        );
+        Ok(BoardImport {
+            board_id,
+            tickets: ids.len(),
+            relations,
+            time_entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_board_export_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let new_ticket = |title: &str| {
+            serde_json::from_value(serde_json::json!({
+                "title": title, "ticket_type": "task", "metadata": { "board_id": "panel-1" }
+            }))
+            .unwrap()
+        };
+
+        let first = db.create_ticket(new_ticket("First")).await.unwrap();
+        let second = db.create_ticket(new_ticket("Second")).await.unwrap();
+        db.create_ticket(
+            serde_json::from_value(
+                serde_json::json!({ "title": "Elsewhere", "ticket_type": "task" }),
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+        db.add_ticket_relation(&first.id, &second.id, TicketRelationKind::Blocks)
+            .await
+            .unwrap();
+        db.add_comment(
+            &first.id,
+            serde_json::from_value(serde_json::json!({ "author": "Ann", "text": "Looks good" }))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        db.add_time_entry(
+            &second.id,
+            CreateTimeEntryRequest {
+                start_time: "2024-05-01T09:00:00Z".parse().unwrap(),
+                end_time: None,
+                duration_minutes: Some(60),
+                description: None,
+                billable: None,
+                notes: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let export = db.export_board("panel-1").await.unwrap();
+        assert_eq!(export.tickets.len(), 2);
+        assert_eq!(export.relations.len(), 1);
+        assert_eq!(export.time_entries.len(), 1);
+
+        // Round-trips through JSON, and refuses a board that has tickets
+        let export: BoardExport =
+            serde_json::from_value(serde_json::to_value(&export).unwrap()).unwrap();
+        assert!(db.import_board(export.clone(), None).await.is_err());
+
+        let imported = db.import_board(export, Some("panel-2")).await.unwrap();
+        assert_eq!(imported.tickets, 2);
+        assert_eq!(imported.relations, 1);
+        assert_eq!(imported.time_entries, 1);
+
+        let copy = db.export_board("panel-2").await.unwrap();
+        let copied_first = copy.tickets.iter().find(|t| t.title == "First").unwrap();
+        let copied_second = copy.tickets.iter().find(|t| t.title == "Second").unwrap();
+        assert_ne!(copied_first.id, first.id);
+        assert_eq!(copied_first.comments.len(), 1);
+        assert_eq!(copy.relations[0].from, copied_first.id);
+        assert_eq!(copy.relations[0].to, copied_second.id);
+        assert_eq!(copied_second.time_spent, Some(1.0));
+    }
+}
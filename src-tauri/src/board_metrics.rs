@@ -0,0 +1,373 @@
+// Board flow metrics
+//
+// Every status change of a ticket is recorded in `ticket_events` by a table
+// event on `tickets` (see migration 4), so the history also covers imports,
+// bulk moves and edits made outside the move command. From that history the
+// board metrics derive:
+// - lead time: creation to completion
+// - cycle time: first move past the board's start column to completion
+// - throughput: tickets completed per ISO week
+// - aging WIP: how long started, unfinished tickets have been in progress
+// Series are returned ready for charts: one point per ticket or per week.
+
+use crate::boards::{BoardColumn, BoardConfig};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::tickets::{ticket_key, ArchiveFilter, Ticket, TicketFilters};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Weeks covered when no range start is given
+const DEFAULT_RANGE_WEEKS: i64 = 12;
+
+/// Status change of a ticket (`from_status` is None when it was created)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketStatusEvent {
+    pub ticket_id: String,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub at: String,
+}
+
+/// Dates the metrics cover, inclusive; defaults to the last 12 weeks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricsRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Distribution of a duration in days
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DurationStats {
+    pub count: usize,
+    pub average_days: Option<f64>,
+    pub median_days: Option<f64>,
+    /// 85% of the tickets took at most this long
+    pub p85_days: Option<f64>,
+}
+
+/// Ticket completed within the range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedTicket {
+    pub ticket_id: String,
+    pub key: Option<String>,
+    pub title: String,
+    pub completed_at: String,
+    pub lead_time_days: f64,
+    /// None when the ticket was never seen past the start column
+    pub cycle_time_days: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeekThroughput {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    pub completed: usize,
+}
+
+/// Started ticket that isn't done yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgingTicket {
+    pub ticket_id: String,
+    pub key: Option<String>,
+    pub title: String,
+    pub status: String,
+    pub started_at: String,
+    pub age_days: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardMetrics {
+    pub board_id: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub lead_time: DurationStats,
+    pub cycle_time: DurationStats,
+    /// Completed tickets, oldest completion first
+    pub completed: Vec<CompletedTicket>,
+    /// One point per week of the range, including weeks without completions
+    pub throughput: Vec<WeekThroughput>,
+    /// Work in progress, oldest first
+    pub aging_wip: Vec<AgingTicket>,
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|date_time| date_time.with_timezone(&Utc))
+}
+
+fn days_between(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    (end - start).num_seconds().max(0) as f64 / 86_400.0
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Average, median and 85th percentile (nearest rank) of durations in days
+fn duration_stats(mut days: Vec<f64>) -> DurationStats {
+    if days.is_empty() {
+        return DurationStats::default();
+    }
+    days.sort_by(f64::total_cmp);
+    let count = days.len();
+    let median = if count % 2 == 0 {
+        (days[count / 2 - 1] + days[count / 2]) / 2.0
+    } else {
+        days[count / 2]
+    };
+    let p85 = days[((count as f64 * 0.85).ceil() as usize).clamp(1, count) - 1];
+    DurationStats {
+        count,
+        average_days: Some(days.iter().sum::<f64>() / count as f64),
+        median_days: Some(median),
+        p85_days: Some(p85),
+    }
+}
+
+/// Whether a column comes after the board's start column (work has begun)
+fn is_started(board: &BoardConfig, column: Option<&BoardColumn>) -> bool {
+    column.is_some_and(|column| column.order > board.start_column().order)
+}
+
+/// When a ticket first entered a started column
+fn started_at(board: &BoardConfig, events: &[&TicketStatusEvent]) -> Option<DateTime<Utc>> {
+    events
+        .iter()
+        .find(|event| is_started(board, board.column(&event.to_status)))
+        .and_then(|event| parse_time(&event.at))
+}
+
+fn compute_metrics(
+    board: &BoardConfig,
+    tickets: &[Ticket],
+    events: &[TicketStatusEvent],
+    from: NaiveDate,
+    to: NaiveDate,
+    now: DateTime<Utc>,
+) -> BoardMetrics {
+    let mut history: HashMap<&str, Vec<&TicketStatusEvent>> = HashMap::new();
+    for event in events {
+        history
+            .entry(ticket_key(&event.ticket_id))
+            .or_default()
+            .push(event);
+    }
+
+    let mut completed = Vec::new();
+    let mut aging_wip = Vec::new();
+    let mut weeks: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut week = week_start(from);
+    while week <= to {
+        weeks.insert(week, 0);
+        week += Duration::weeks(1);
+    }
+
+    for ticket in tickets {
+        let events = history
+            .get(ticket_key(&ticket.id))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let started = started_at(board, events);
+        let column = board.column(&ticket.status);
+        let done = column.is_some_and(|column| column.is_done);
+
+        if done {
+            let Some(completed_at) = ticket.completed_at.as_deref().and_then(parse_time) else {
+                continue;
+            };
+            let date = completed_at.date_naive();
+            if date < from || date > to {
+                continue;
+            }
+            *weeks.entry(week_start(date)).or_default() += 1;
+            let created_at = parse_time(&ticket.created_at).unwrap_or(completed_at);
+            completed.push(CompletedTicket {
+                ticket_id: ticket.id.clone(),
+                key: ticket.key.clone(),
+                title: ticket.title.clone(),
+                completed_at: completed_at.to_rfc3339(),
+                lead_time_days: days_between(created_at, completed_at),
+                cycle_time_days: started.map(|started| days_between(started, completed_at)),
+            });
+        } else if is_started(board, column) && !ticket.archived {
+            // Tickets from before the history was kept count from their last update
+            let started = started
+                .or_else(|| parse_time(&ticket.updated_at))
+                .unwrap_or(now);
+            aging_wip.push(AgingTicket {
+                ticket_id: ticket.id.clone(),
+                key: ticket.key.clone(),
+                title: ticket.title.clone(),
+                status: ticket.status.clone(),
+                started_at: started.to_rfc3339(),
+                age_days: days_between(started, now),
+            });
+        }
+    }
+
+    completed.sort_by(|a, b| a.completed_at.cmp(&b.completed_at));
+    aging_wip.sort_by(|a, b| b.age_days.total_cmp(&a.age_days));
+    BoardMetrics {
+        board_id: board.board_id.clone(),
+        from,
+        to,
+        lead_time: duration_stats(completed.iter().map(|t| t.lead_time_days).collect()),
+        cycle_time: duration_stats(completed.iter().filter_map(|t| t.cycle_time_days).collect()),
+        completed,
+        throughput: weeks
+            .into_iter()
+            .map(|(week_start, completed)| WeekThroughput {
+                week_start,
+                completed,
+            })
+            .collect(),
+        aging_wip,
+    }
+}
+
+impl Database {
+    /// Status changes of a ticket, oldest first
+    pub async fn get_ticket_history(&self, id: &str) -> Result<Vec<TicketStatusEvent>, AppError> {
+        let mut result = self
+            .query(
+                "SELECT <string> ticket AS ticket_id, from_status, to_status, at \
+                 FROM ticket_events WHERE ticket = type::thing('tickets', $key) ORDER BY at",
+            )
+            .bind(("key", ticket_key(id).to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get ticket history: {}", e)))?;
+        result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to get ticket history: {}", e)))
+    }
+
+    /// Lead time, cycle time, weekly throughput and aging WIP of a board
+    pub async fn get_board_metrics(
+        &self,
+        board_id: &str,
+        range: MetricsRange,
+    ) -> Result<BoardMetrics, AppError> {
+        let board = self.get_board_config(board_id).await?;
+        let now = Utc::now();
+        let to = range.to.unwrap_or(now.date_naive());
+        let from = range
+            .from
+            .unwrap_or(to - Duration::weeks(DEFAULT_RANGE_WEEKS));
+        if to < from {
+            return Err(AppError::Validation(format!(
+                "Metrics range end {} is before its start {}",
+                to, from
+            )));
+        }
+
+        let tickets = self
+            .get_tickets(Some(TicketFilters {
+                board_id: Some(board.board_id.clone()),
+                archived: ArchiveFilter::All,
+                ..Default::default()
+            }))
+            .await?;
+        let ids: Vec<String> = tickets.iter().map(|ticket| ticket.id.clone()).collect();
+        let mut result = self
+            .query(
+                "SELECT <string> ticket AS ticket_id, from_status, to_status, at \
+                 FROM ticket_events WHERE <string> ticket IN $ids ORDER BY at",
+            )
+            .bind(("ids", ids))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get ticket history: {}", e)))?;
+        let events: Vec<TicketStatusEvent> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to get ticket history: {}", e)))?;
+
+        Ok(compute_metrics(&board, &tickets, &events, from, to, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boards::DEFAULT_BOARD;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_duration_stats() {
+        assert_eq!(duration_stats(Vec::new()), DurationStats::default());
+        let stats = duration_stats(vec![4.0, 1.0, 2.0, 3.0]);
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.average_days, Some(2.5));
+        assert_eq!(stats.median_days, Some(2.5));
+        assert_eq!(stats.p85_days, Some(4.0));
+        assert_eq!(duration_stats(vec![7.0]).p85_days, Some(7.0));
+    }
+
+    #[tokio::test]
+    async fn test_board_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let mut ids = Vec::new();
+        for title in ["Finished", "Ongoing", "Waiting"] {
+            let ticket = db
+                .create_ticket(
+                    serde_json::from_value(
+                        serde_json::json!({ "title": title, "ticket_type": "task" }),
+                    )
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+            ids.push(ticket.id);
+        }
+        db.move_ticket(&ids[0], "in-progress").await.unwrap();
+        db.move_ticket(&ids[0], "done").await.unwrap();
+        db.move_ticket(&ids[1], "review").await.unwrap();
+
+        let history = db.get_ticket_history(&ids[0]).await.unwrap();
+        let statuses: Vec<(Option<&str>, &str)> = history
+            .iter()
+            .map(|e| (e.from_status.as_deref(), e.to_status.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (None, "todo"),
+                (Some("todo"), "in-progress"),
+                (Some("in-progress"), "done")
+            ]
+        );
+
+        let metrics = db
+            .get_board_metrics(DEFAULT_BOARD, MetricsRange::default())
+            .await
+            .unwrap();
+        assert_eq!(metrics.completed.len(), 1);
+        assert_eq!(metrics.completed[0].ticket_id, ids[0]);
+        assert!(metrics.completed[0].cycle_time_days.is_some());
+        assert_eq!(metrics.lead_time.count, 1);
+        assert_eq!(metrics.throughput.len(), 13);
+        assert_eq!(metrics.throughput.last().unwrap().completed, 1);
+        assert_eq!(metrics.aging_wip.len(), 1);
+        assert_eq!(metrics.aging_wip[0].ticket_id, ids[1]);
+
+        // Nothing completed in an earlier range
+        let earlier = db
+            .get_board_metrics(
+                DEFAULT_BOARD,
+                MetricsRange {
+                    from: Some("2020-01-01".parse().unwrap()),
+                    to: Some("2020-01-31".parse().unwrap()),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(earlier.completed.is_empty());
+        assert_eq!(earlier.throughput.len(), 5);
+        assert!(earlier.throughput.iter().all(|week| week.completed == 0));
+
+        db.delete_ticket(&ids[0]).await.unwrap();
+        assert!(db.get_ticket_history(&ids[0]).await.unwrap().is_empty());
+    }
+}
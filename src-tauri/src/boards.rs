@@ -0,0 +1,1041 @@
+// Kanban board configuration
+//
+// Each board (a kanban panel, identified by the `board_id` in ticket
+// metadata) has an ordered list of columns stored in the `boards` table. A
+// ticket's status is the ID of the column it sits in. Boards without a stored
+// configuration use the default columns, as do tickets without a board.
+// Columns can carry a work-in-progress limit: moving a ticket into a full
+// column warns, or is refused if the column's limit is strict.
+// Tickets get a sequential key from their board's prefix ("MOD-123"); the
+// numbers come from a counter per prefix in `ticket_sequences`.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::i18n;
+use crate::tickets::{Priority, Ticket, TicketFilters};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use surrealdb::sql::Thing;
+
+/// Board of tickets created without a `board_id`
+pub const DEFAULT_BOARD: &str = "default";
+
+/// Ticket key prefix of boards that don't set their own
+pub const DEFAULT_KEY_PREFIX: &str = "MOD";
+
+// ============================================================================
+// Board Model
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardColumn {
+    /// Status of the tickets in this column
+    pub id: String,
+    pub name: String,
+    pub color: String,
+    /// Position on the board (renumbered from 0 on every save)
+    #[serde(default)]
+    pub order: usize,
+    /// Column new tickets start in
+    #[serde(default)]
+    pub is_start: bool,
+    /// Tickets in this column count as completed
+    #[serde(default)]
+    pub is_done: bool,
+    /// Most tickets the column should hold
+    #[serde(default)]
+    pub wip_limit: Option<usize>,
+    /// Refuse moves past the limit instead of warning
+    #[serde(default)]
+    pub wip_strict: bool,
+}
+
+/// Board as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BoardRecord {
+    pub id: Thing,
+    pub columns: Vec<BoardColumn>,
+    #[serde(default)]
+    pub key_prefix: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub board_id: String,
+    pub columns: Vec<BoardColumn>,
+    /// Prefix of the board's ticket keys
+    #[serde(default = "default_key_prefix")]
+    pub key_prefix: String,
+    /// None while the board uses the default columns
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Changes to a column; unset fields are kept
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateBoardColumnRequest {
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub is_start: Option<bool>,
+    pub is_done: Option<bool>,
+    /// 0 removes the limit
+    pub wip_limit: Option<usize>,
+    pub wip_strict: Option<bool>,
+}
+
+/// A column whose WIP limit a move would exceed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WipLimitExceeded {
+    pub column_id: String,
+    pub limit: usize,
+    /// Tickets in the column if the move goes ahead
+    pub count: usize,
+    /// The limit is strict, so the move was refused
+    pub blocked: bool,
+}
+
+/// Ticket count of a column against its WIP limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnState {
+    #[serde(flatten)]
+    pub column: BoardColumn,
+    pub count: usize,
+    pub over_limit: bool,
+}
+
+/// Columns of a board with their current ticket counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardState {
+    pub board_id: String,
+    pub columns: Vec<ColumnState>,
+    /// Tickets on the board whose status matches no column
+    pub unmapped: usize,
+}
+
+/// What a board's swimlanes group tickets by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwimlaneGrouping {
+    /// One lane per label; tickets with several labels show in each
+    Label,
+    Assignee,
+    Priority,
+}
+
+/// Tickets of one swimlane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Swimlane {
+    /// Label, assignee or priority; None for tickets without one
+    pub key: Option<String>,
+    pub count: usize,
+    /// Ticket count per column ID
+    pub column_counts: HashMap<String, usize>,
+    pub tickets: Vec<Ticket>,
+}
+
+/// A board's tickets grouped into swimlanes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardGrouped {
+    pub board_id: String,
+    pub group_by: SwimlaneGrouping,
+    pub columns: Vec<BoardColumn>,
+    /// Priorities from critical down, others by size; the lane without a
+    /// key comes last
+    pub lanes: Vec<Swimlane>,
+}
+
+/// Priority lanes, most severe first
+const PRIORITY_LANES: [Priority; 4] = [
+    Priority::Critical,
+    Priority::High,
+    Priority::Medium,
+    Priority::Low,
+];
+
+fn priority_key(priority: &Priority) -> Option<String> {
+    serde_json::to_value(priority)
+        .ok()
+        .and_then(|p| p.as_str().map(str::to_string))
+}
+
+/// Lane keys of a ticket
+fn swimlane_keys(ticket: &Ticket, group_by: SwimlaneGrouping) -> Vec<Option<String>> {
+    match group_by {
+        SwimlaneGrouping::Label if !ticket.tags.is_empty() => {
+            ticket.tags.iter().cloned().map(Some).collect()
+        }
+        SwimlaneGrouping::Label => vec![None],
+        SwimlaneGrouping::Assignee => {
+            vec![ticket.assignee.clone().filter(|a| !a.trim().is_empty())]
+        }
+        SwimlaneGrouping::Priority => vec![priority_key(&ticket.priority)],
+    }
+}
+
+/// Group tickets into swimlanes
+fn group_swimlanes(tickets: Vec<Ticket>, group_by: SwimlaneGrouping) -> Vec<Swimlane> {
+    let mut lanes: Vec<Swimlane> = Vec::new();
+    for ticket in tickets {
+        for key in swimlane_keys(&ticket, group_by) {
+            let lane = match lanes.iter().position(|lane| lane.key == key) {
+                Some(index) => &mut lanes[index],
+                None => {
+                    lanes.push(Swimlane {
+                        key,
+                        count: 0,
+                        column_counts: HashMap::new(),
+                        tickets: Vec::new(),
+                    });
+                    lanes.last_mut().unwrap()
+                }
+            };
+            lane.count += 1;
+            *lane.column_counts.entry(ticket.status.clone()).or_default() += 1;
+            lane.tickets.push(ticket.clone());
+        }
+    }
+
+    let priority_rank = |key: &Option<String>| {
+        PRIORITY_LANES
+            .iter()
+            .position(|priority| priority_key(priority) == *key)
+    };
+    lanes.sort_by(|a, b| {
+        let order = match group_by {
+            SwimlaneGrouping::Priority => priority_rank(&a.key).cmp(&priority_rank(&b.key)),
+            _ => b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)),
+        };
+        a.key.is_none().cmp(&b.key.is_none()).then(order)
+    });
+    lanes
+}
+
+impl BoardConfig {
+    /// Column new tickets start in: the first marked as start, else the first
+    pub fn start_column(&self) -> &BoardColumn {
+        self.columns
+            .iter()
+            .find(|column| column.is_start)
+            .unwrap_or(&self.columns[0])
+    }
+
+    pub fn column(&self, id: &str) -> Option<&BoardColumn> {
+        self.columns.iter().find(|column| column.id == id)
+    }
+
+    /// Look up a column a ticket can be moved to
+    pub fn require_column(&self, id: &str) -> Result<&BoardColumn, AppError> {
+        self.column(id).ok_or_else(|| {
+            AppError::Validation(format!(
+                "Board '{}' has no column '{}' (expected one of: {})",
+                self.board_id,
+                id,
+                self.columns
+                    .iter()
+                    .map(|column| column.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+    }
+}
+
+/// Columns of a board without a stored configuration, named in the backend
+/// locale
+pub fn default_columns() -> Vec<BoardColumn> {
+    let column = |id: &str, name: &str, color: &str| BoardColumn {
+        id: id.to_string(),
+        name: name.to_string(),
+        color: color.to_string(),
+        order: 0,
+        is_start: false,
+        is_done: false,
+        wip_limit: None,
+        wip_strict: false,
+    };
+    normalize(vec![
+        column("backlog", i18n::text("board.column.backlog"), "#6c757d"),
+        BoardColumn {
+            is_start: true,
+            ..column("todo", i18n::text("board.column.todo"), "#007bff")
+        },
+        column(
+            "in-progress",
+            i18n::text("board.column.in_progress"),
+            "#ffc107",
+        ),
+        column("review", i18n::text("board.column.review"), "#6f42c1"),
+        BoardColumn {
+            is_done: true,
+            ..column("done", i18n::text("board.column.done"), "#28a745")
+        },
+    ])
+}
+
+fn default_key_prefix() -> String {
+    DEFAULT_KEY_PREFIX.to_string()
+}
+
+/// Check a ticket key prefix: 1-10 letters and digits, starting with a
+/// letter; returned uppercased
+fn validate_key_prefix(prefix: &str) -> Result<String, AppError> {
+    let prefix = prefix.trim().to_uppercase();
+    let valid = (1..=10).contains(&prefix.len())
+        && prefix.starts_with(|c: char| c.is_ascii_alphabetic())
+        && prefix.chars().all(|c| c.is_ascii_alphanumeric());
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Invalid ticket key prefix '{}' (1-10 letters and digits, starting with a letter)",
+            prefix
+        )));
+    }
+    Ok(prefix)
+}
+
+/// Board a ticket belongs to, from its metadata
+pub fn ticket_board_id(metadata: &serde_json::Value) -> &str {
+    metadata
+        .get("board_id")
+        .and_then(|id| id.as_str())
+        .filter(|id| !id.is_empty())
+        .unwrap_or(DEFAULT_BOARD)
+}
+
+/// Renumber column order by position
+fn normalize(mut columns: Vec<BoardColumn>) -> Vec<BoardColumn> {
+    for (order, column) in columns.iter_mut().enumerate() {
+        column.order = order;
+    }
+    columns
+}
+
+/// Check a column list before saving: trimmed, non-empty and unique IDs
+fn validate_columns(columns: Vec<BoardColumn>) -> Result<Vec<BoardColumn>, AppError> {
+    if columns.is_empty() {
+        return Err(AppError::Validation(
+            "A board needs at least one column".to_string(),
+        ));
+    }
+
+    let mut validated: Vec<BoardColumn> = Vec::with_capacity(columns.len());
+    for mut column in columns {
+        column.id = column.id.trim().to_string();
+        column.name = column.name.trim().to_string();
+        if column.id.is_empty() {
+            return Err(AppError::Validation(
+                "Column ID cannot be empty".to_string(),
+            ));
+        }
+        if column.name.is_empty() {
+            column.name = column.id.clone();
+        }
+        column.wip_limit = column.wip_limit.filter(|limit| *limit > 0);
+        if validated.iter().any(|c| c.id == column.id) {
+            return Err(AppError::Validation(format!(
+                "Duplicate column '{}'",
+                column.id
+            )));
+        }
+        validated.push(column);
+    }
+    Ok(normalize(validated))
+}
+
+/// WHERE condition selecting the tickets on a board (binds `$board`)
+pub(crate) fn board_condition(board_id: &str) -> &'static str {
+    if board_id == DEFAULT_BOARD {
+        "(metadata.board_id = NONE OR metadata.board_id = NULL \
+          OR metadata.board_id = '' OR metadata.board_id = $board)"
+    } else {
+        "metadata.board_id = $board"
+    }
+}
+
+// ============================================================================
+// Board Operations
+// ============================================================================
+
+impl Database {
+    /// Board configuration, or the default columns if none is stored
+    pub async fn get_board_config(&self, board_id: &str) -> Result<BoardConfig, AppError> {
+        Ok(match self.board_record(board_id).await? {
+            Some(record) => BoardConfig {
+                board_id: board_id.to_string(),
+                columns: record.columns,
+                key_prefix: record.key_prefix.unwrap_or_else(default_key_prefix),
+                updated_at: Some(record.updated_at),
+            },
+            None => BoardConfig {
+                board_id: board_id.to_string(),
+                columns: default_columns(),
+                key_prefix: default_key_prefix(),
+                updated_at: None,
+            },
+        })
+    }
+
+    /// Boards with a stored configuration (boards on the defaults are left out)
+    pub async fn list_board_configs(&self) -> Result<Vec<BoardConfig>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM boards ORDER BY id ASC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query boards: {}", e)))?;
+        let records: Vec<BoardRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse boards: {}", e)))?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| BoardConfig {
+                board_id: record.id.id.to_raw(),
+                columns: record.columns,
+                key_prefix: record.key_prefix.unwrap_or_else(default_key_prefix),
+                updated_at: Some(record.updated_at),
+            })
+            .collect())
+    }
+
+    async fn board_record(&self, board_id: &str) -> Result<Option<BoardRecord>, AppError> {
+        self.db
+            .select(("boards", board_id))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get board: {}", e)))
+    }
+
+    /// Replace a board's columns
+    pub async fn save_board_columns(
+        &self,
+        board_id: &str,
+        columns: Vec<BoardColumn>,
+    ) -> Result<BoardConfig, AppError> {
+        let columns = validate_columns(columns)?;
+        let key_prefix = self
+            .board_record(board_id)
+            .await?
+            .and_then(|record| record.key_prefix);
+        self.save_board_record(board_id, columns, key_prefix).await
+    }
+
+    async fn save_board_record(
+        &self,
+        board_id: &str,
+        columns: Vec<BoardColumn>,
+        key_prefix: Option<String>,
+    ) -> Result<BoardConfig, AppError> {
+        let record = BoardRecord {
+            id: Thing::from(("boards", board_id)),
+            columns,
+            key_prefix,
+            updated_at: Utc::now(),
+        };
+
+        let saved: Option<BoardRecord> = self
+            .db
+            .upsert(("boards", board_id))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save board: {}", e)))?;
+
+        let saved =
+            saved.ok_or_else(|| AppError::Database("Board save returned no result".to_string()))?;
+        Ok(BoardConfig {
+            board_id: board_id.to_string(),
+            columns: saved.columns,
+            key_prefix: saved.key_prefix.unwrap_or_else(default_key_prefix),
+            updated_at: Some(saved.updated_at),
+        })
+    }
+
+    /// Set the prefix of a board's new ticket keys (existing keys stay)
+    pub async fn set_board_key_prefix(
+        &self,
+        board_id: &str,
+        prefix: &str,
+    ) -> Result<BoardConfig, AppError> {
+        let prefix = validate_key_prefix(prefix)?;
+        let columns = self.get_board_config(board_id).await?.columns;
+        self.save_board_record(board_id, columns, Some(prefix))
+            .await
+    }
+
+    /// Reserve the next `count` ticket keys of a board ("MOD-124", ...)
+    /// Boards sharing a prefix share its numbering, so keys stay unique.
+    pub(crate) async fn reserve_ticket_keys(
+        &self,
+        board_id: &str,
+        count: usize,
+    ) -> Result<Vec<String>, AppError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let prefix = self.get_board_config(board_id).await?.key_prefix;
+
+        let mut result = self
+            .query(
+                "UPSERT type::thing('ticket_sequences', $prefix)
+                 SET last = (last ?? 0) + $count RETURN VALUE last",
+            )
+            .bind(("prefix", prefix.clone()))
+            .bind(("count", count))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to reserve ticket keys: {}", e)))?;
+        let last: Option<usize> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to reserve ticket keys: {}", e)))?;
+        let last = last.ok_or_else(|| {
+            AppError::Database("Ticket key reservation returned no number".to_string())
+        })?;
+
+        Ok((last + 1 - count..=last)
+            .map(|number| format!("{}-{}", prefix, number))
+            .collect())
+    }
+
+    /// Add a column at `position` (default: the end)
+    pub async fn add_board_column(
+        &self,
+        board_id: &str,
+        column: BoardColumn,
+        position: Option<usize>,
+    ) -> Result<BoardConfig, AppError> {
+        let mut columns = self.get_board_config(board_id).await?.columns;
+        let position = position.unwrap_or(columns.len()).min(columns.len());
+        columns.insert(position, column);
+        self.save_board_columns(board_id, columns).await
+    }
+
+    /// Change a column's name, color or flags (its ID is fixed)
+    pub async fn update_board_column(
+        &self,
+        board_id: &str,
+        column_id: &str,
+        req: UpdateBoardColumnRequest,
+    ) -> Result<BoardConfig, AppError> {
+        let mut config = self.get_board_config(board_id).await?;
+        let column = config
+            .columns
+            .iter_mut()
+            .find(|column| column.id == column_id)
+            .ok_or_else(|| AppError::NotFound(format!("Column not found: {}", column_id)))?;
+
+        if let Some(name) = req.name {
+            column.name = name;
+        }
+        if let Some(color) = req.color {
+            column.color = color;
+        }
+        if let Some(is_start) = req.is_start {
+            column.is_start = is_start;
+        }
+        if let Some(is_done) = req.is_done {
+            column.is_done = is_done;
+        }
+        if let Some(wip_limit) = req.wip_limit {
+            column.wip_limit = Some(wip_limit);
+        }
+        if let Some(wip_strict) = req.wip_strict {
+            column.wip_strict = wip_strict;
+        }
+        self.save_board_columns(board_id, config.columns).await
+    }
+
+    /// Put the columns in the given order; every column must be listed once
+    pub async fn reorder_board_columns(
+        &self,
+        board_id: &str,
+        column_ids: &[String],
+    ) -> Result<BoardConfig, AppError> {
+        let mut columns = self.get_board_config(board_id).await?.columns;
+        if column_ids.len() != columns.len() {
+            return Err(AppError::Validation(format!(
+                "Expected {} column IDs, got {}",
+                columns.len(),
+                column_ids.len()
+            )));
+        }
+
+        let mut ordered = Vec::with_capacity(columns.len());
+        for id in column_ids {
+            let index = columns
+                .iter()
+                .position(|column| &column.id == id)
+                .ok_or_else(|| AppError::NotFound(format!("Column not found: {}", id)))?;
+            ordered.push(columns.remove(index));
+        }
+        self.save_board_columns(board_id, ordered).await
+    }
+
+    /// Remove a column. Tickets still in it are moved to `move_tickets_to`;
+    /// without a target the column must be empty.
+    pub async fn delete_board_column(
+        &self,
+        board_id: &str,
+        column_id: &str,
+        move_tickets_to: Option<&str>,
+    ) -> Result<BoardConfig, AppError> {
+        let config = self.get_board_config(board_id).await?;
+        config
+            .column(column_id)
+            .ok_or_else(|| AppError::NotFound(format!("Column not found: {}", column_id)))?;
+        let target = match move_tickets_to {
+            Some(target) if target == column_id => {
+                return Err(AppError::Validation(
+                    "Cannot move tickets into the column being deleted".to_string(),
+                ))
+            }
+            Some(target) => Some(config.require_column(target)?.clone()),
+            None => None,
+        };
+
+        let condition = board_condition(board_id);
+        let mut result = self
+            .query(format!(
+                "SELECT VALUE <string> id FROM tickets WHERE status = $status AND {}",
+                condition
+            ))
+            .bind(("status", column_id.to_string()))
+            .bind(("board", board_id.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get column tickets: {}", e)))?;
+        let ticket_ids: Vec<String> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse column tickets: {}", e)))?;
+
+        if !ticket_ids.is_empty() {
+            let Some(target) = target else {
+                return Err(AppError::Validation(format!(
+                    "Column '{}' still has {} tickets; choose a column to move them to",
+                    column_id,
+                    ticket_ids.len()
+                )));
+            };
+            // Emptying a column ignores the target's WIP limit
+            let completed_at = if target.is_done {
+                "completed_at ?? $now"
+            } else {
+                "NONE"
+            };
+            self.query(format!(
+                "UPDATE tickets SET status = $status, completed_at = {}, updated_at = $now
+                     WHERE <string> id IN $ids",
+                completed_at
+            ))
+            .bind(("status", target.id.clone()))
+            .bind(("now", Utc::now().to_rfc3339()))
+            .bind(("ids", ticket_ids))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to move tickets: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to move tickets: {}", e)))?;
+        }
+
+        let columns = config
+            .columns
+            .into_iter()
+            .filter(|column| column.id != column_id)
+            .collect();
+        self.save_board_columns(board_id, columns).await
+    }
+
+    /// Ticket count per status on a board (archived tickets left out)
+    pub async fn board_status_counts(
+        &self,
+        board_id: &str,
+    ) -> Result<HashMap<String, usize>, AppError> {
+        #[derive(Deserialize)]
+        struct StatusCount {
+            status: String,
+            count: usize,
+        }
+
+        let mut result = self
+            .query(format!(
+                "SELECT status, count() AS count FROM tickets
+                 WHERE archived != true AND {} GROUP BY status",
+                board_condition(board_id)
+            ))
+            .bind(("board", board_id.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count board tickets: {}", e)))?;
+        let counts: Vec<StatusCount> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse board counts: {}", e)))?;
+
+        Ok(counts.into_iter().map(|c| (c.status, c.count)).collect())
+    }
+
+    /// Columns with their ticket counts against WIP limits
+    pub async fn get_board_state(&self, board_id: &str) -> Result<BoardState, AppError> {
+        let config = self.get_board_config(board_id).await?;
+        let mut counts = self.board_status_counts(board_id).await?;
+
+        let columns = config
+            .columns
+            .into_iter()
+            .map(|column| {
+                let count = counts.remove(&column.id).unwrap_or(0);
+                ColumnState {
+                    over_limit: column.wip_limit.is_some_and(|limit| count > limit),
+                    count,
+                    column,
+                }
+            })
+            .collect();
+
+        Ok(BoardState {
+            board_id: board_id.to_string(),
+            columns,
+            unmapped: counts.values().sum(),
+        })
+    }
+
+    /// Active tickets of a board grouped into swimlanes, with per-lane counts
+    pub async fn get_board_grouped(
+        &self,
+        board_id: &str,
+        group_by: SwimlaneGrouping,
+    ) -> Result<BoardGrouped, AppError> {
+        let config = self.get_board_config(board_id).await?;
+        let tickets = self
+            .get_tickets(Some(TicketFilters {
+                board_id: Some(board_id.to_string()),
+                ..Default::default()
+            }))
+            .await?;
+
+        Ok(BoardGrouped {
+            board_id: board_id.to_string(),
+            group_by,
+            columns: config.columns,
+            lanes: group_swimlanes(tickets, group_by),
+        })
+    }
+
+    /// Forget a board's configuration (columns and key prefix) so it uses
+    /// the defaults again
+    pub async fn reset_board_config(&self, board_id: &str) -> Result<BoardConfig, AppError> {
+        let _: Option<BoardRecord> = self
+            .db
+            .delete(("boards", board_id))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete board: {}", e)))?;
+        self.get_board_config(board_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn column(id: &str) -> BoardColumn {
+        BoardColumn {
+            id: id.to_string(),
+            name: String::new(),
+            color: "#000".to_string(),
+            order: 0,
+            is_start: false,
+            is_done: false,
+            wip_limit: None,
+            wip_strict: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_board_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let config = db.get_board_config("panel-1").await.unwrap();
+        assert_eq!(config.columns, default_columns());
+        assert!(config.updated_at.is_none());
+
+        assert!(db
+            .save_board_columns("panel-1", vec![column("a"), column(" a ")])
+            .await
+            .is_err());
+        assert!(db.save_board_columns("panel-1", Vec::new()).await.is_err());
+
+        let config = db
+            .save_board_columns(
+                "panel-1",
+                vec![
+                    BoardColumn {
+                        is_start: true,
+                        ..column("todo")
+                    },
+                    column("shipped"),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(config.columns[1].name, "shipped");
+        assert_eq!(config.columns[1].order, 1);
+
+        let config = db
+            .add_board_column("panel-1", column("doing"), Some(1))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = config.columns.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["todo", "doing", "shipped"]);
+
+        let config = db
+            .update_board_column(
+                "panel-1",
+                "shipped",
+                UpdateBoardColumnRequest {
+                    is_done: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(config.column("shipped").unwrap().is_done);
+
+        let config = db
+            .reorder_board_columns(
+                "panel-1",
+                &[
+                    "shipped".to_string(),
+                    "todo".to_string(),
+                    "doing".to_string(),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(config.columns[0].id, "shipped");
+        assert_eq!(config.columns[0].order, 0);
+        assert!(db
+            .reorder_board_columns("panel-1", &["todo".to_string()])
+            .await
+            .is_err());
+
+        // Moves are validated against the ticket's board
+        let ticket = db
+            .create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": "On board", "ticket_type": "task",
+                    "metadata": { "board_id": "panel-1" }
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ticket.status, "todo");
+        assert!(db.move_ticket(&ticket.id, "review").await.is_err());
+        let moved = db.move_ticket(&ticket.id, "doing").await.unwrap();
+        assert_eq!(moved.ticket.status, "doing");
+        let done = db.move_ticket(&ticket.id, "shipped").await.unwrap();
+        assert!(done.ticket.completed_at.is_some());
+
+        // A non-empty column needs a target for its tickets
+        assert!(db
+            .delete_board_column("panel-1", "shipped", None)
+            .await
+            .is_err());
+        let config = db
+            .delete_board_column("panel-1", "shipped", Some("todo"))
+            .await
+            .unwrap();
+        assert_eq!(config.columns.len(), 2);
+        let tickets = db.get_tickets(None).await.unwrap();
+        assert_eq!(tickets[0].status, "todo");
+        assert!(tickets[0].completed_at.is_none());
+
+        let config = db.reset_board_config("panel-1").await.unwrap();
+        assert_eq!(config.columns, default_columns());
+    }
+
+    #[tokio::test]
+    async fn test_wip_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let mut ids = Vec::new();
+        for title in ["A", "B", "C"] {
+            let ticket = db
+                .create_ticket(
+                    serde_json::from_value(serde_json::json!({
+                        "title": title, "ticket_type": "task"
+                    }))
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+            ids.push(ticket.id);
+        }
+        let limit = |wip_strict: bool| UpdateBoardColumnRequest {
+            wip_limit: Some(1),
+            wip_strict: Some(wip_strict),
+            ..Default::default()
+        };
+        db.update_board_column(DEFAULT_BOARD, "review", limit(false))
+            .await
+            .unwrap();
+
+        let first = db.move_ticket(&ids[0], "review").await.unwrap();
+        assert!(first.moved && first.wip_exceeded.is_none());
+        // Moving within the same column never counts against the limit
+        assert!(db
+            .move_ticket(&ids[0], "review")
+            .await
+            .unwrap()
+            .wip_exceeded
+            .is_none());
+
+        let second = db.move_ticket(&ids[1], "review").await.unwrap();
+        assert!(second.moved);
+        let warning = second.wip_exceeded.unwrap();
+        assert_eq!((warning.limit, warning.count), (1, 2));
+        assert!(!warning.blocked);
+
+        db.update_board_column(DEFAULT_BOARD, "review", limit(true))
+            .await
+            .unwrap();
+        let blocked = db.move_ticket(&ids[2], "review").await.unwrap();
+        assert!(!blocked.moved);
+        assert!(blocked.wip_exceeded.unwrap().blocked);
+        assert_eq!(blocked.ticket.status, "todo");
+
+        let state = db.get_board_state(DEFAULT_BOARD).await.unwrap();
+        let review = state
+            .columns
+            .iter()
+            .find(|c| c.column.id == "review")
+            .unwrap();
+        assert_eq!(review.count, 2);
+        assert!(review.over_limit);
+        let todo = state
+            .columns
+            .iter()
+            .find(|c| c.column.id == "todo")
+            .unwrap();
+        assert_eq!(todo.count, 1);
+        assert!(!todo.over_limit);
+        assert_eq!(state.unmapped, 0);
+
+        // 0 removes the limit
+        let config = db
+            .update_board_column(
+                DEFAULT_BOARD,
+                "review",
+                UpdateBoardColumnRequest {
+                    wip_limit: Some(0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(config.column("review").unwrap().wip_limit, None);
+    }
+
+    #[tokio::test]
+    async fn test_ticket_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let create = |title: &str, board: &str| {
+            serde_json::from_value(serde_json::json!({
+                "title": title, "ticket_type": "task", "metadata": { "board_id": board }
+            }))
+            .unwrap()
+        };
+
+        let first = db.create_ticket(create("First", "")).await.unwrap();
+        let second = db.create_ticket(create("Second", "")).await.unwrap();
+        assert_eq!(first.key.as_deref(), Some("MOD-1"));
+        assert_eq!(second.key.as_deref(), Some("MOD-2"));
+
+        assert!(db.set_board_key_prefix("panel-1", "1AB").await.is_err());
+        assert!(db.set_board_key_prefix("panel-1", "WEB-").await.is_err());
+        let config = db.set_board_key_prefix("panel-1", " web ").await.unwrap();
+        assert_eq!(config.key_prefix, "WEB");
+        assert_eq!(config.columns, default_columns());
+
+        let web = db.create_ticket(create("Web", "panel-1")).await.unwrap();
+        assert_eq!(web.key.as_deref(), Some("WEB-1"));
+        assert_eq!(
+            db.reserve_ticket_keys("panel-1", 2).await.unwrap(),
+            vec!["WEB-2", "WEB-3"]
+        );
+
+        // The prefix survives column changes, and keys are searchable
+        db.add_board_column("panel-1", column("qa"), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_board_config("panel-1").await.unwrap().key_prefix,
+            "WEB"
+        );
+        let found = db
+            .get_tickets(Some(
+                serde_json::from_value(serde_json::json!({ "search": "mod-2" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, second.id);
+    }
+
+    #[tokio::test]
+    async fn test_board_grouped() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        for (title, priority, assignee, tags) in [
+            ("a", "low", "Ada", vec!["ui", "api"]),
+            ("b", "critical", "Ada", vec!["api"]),
+            ("c", "medium", "", vec![]),
+        ] {
+            db.create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": title, "ticket_type": "task", "priority": priority,
+                    "assignee": assignee, "tags": tags
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let lanes = |grouped: BoardGrouped| -> Vec<(Option<String>, usize)> {
+            grouped
+                .lanes
+                .into_iter()
+                .map(|lane| (lane.key, lane.count))
+                .collect()
+        };
+        let by_label = db
+            .get_board_grouped(DEFAULT_BOARD, SwimlaneGrouping::Label)
+            .await
+            .unwrap();
+        assert_eq!(by_label.lanes[0].column_counts["todo"], 2);
+        assert_eq!(
+            lanes(by_label),
+            vec![
+                (Some("api".to_string()), 2),
+                (Some("ui".to_string()), 1),
+                (None, 1)
+            ]
+        );
+        assert_eq!(
+            lanes(
+                db.get_board_grouped(DEFAULT_BOARD, SwimlaneGrouping::Priority)
+                    .await
+                    .unwrap()
+            ),
+            vec![
+                (Some("critical".to_string()), 1),
+                (Some("medium".to_string()), 1),
+                (Some("low".to_string()), 1)
+            ]
+        );
+        assert_eq!(
+            lanes(
+                db.get_board_grouped(DEFAULT_BOARD, SwimlaneGrouping::Assignee)
+                    .await
+                    .unwrap()
+            ),
+            vec![(Some("Ada".to_string()), 2), (None, 1)]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// Crash reports
+//
+// A panic hook writes a report (message, location, backtrace, app version and
+// the last log entries) to `crash_report.json` in the app data directory
+// before the default hook runs. On the next start `get_last_crash_report`
+// hands it to the frontend, which offers a "something went wrong last time"
+// dialog; `dismiss_crash_report` deletes it. Only the latest crash is kept.
+//
+// Panics in background tasks are captured too, even though they don't take
+// the app down.
+
+use crate::error::AppError;
+use crate::logging::{self, LogEntry};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+
+const REPORT_FILE: &str = "crash_report.json";
+
+/// Log entries included in a report
+const REPORT_LOG_ENTRIES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub crashed_at: DateTime<Utc>,
+    pub version: String,
+    pub os: String,
+    pub thread: String,
+    pub message: String,
+    /// file:line:column of the panic
+    pub location: Option<String>,
+    pub backtrace: String,
+    /// Log entries before the panic, oldest first
+    pub recent_logs: Vec<LogEntry>,
+}
+
+fn report_path() -> Result<PathBuf, AppError> {
+    Ok(crate::workspaces::app_root()?.join(REPORT_FILE))
+}
+
+/// Write a crash report on panic, then run the default hook
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = build_report(info);
+        tracing::error!(
+            "Panic in thread '{}' at {}: {}",
+            report.thread,
+            report.location.as_deref().unwrap_or("unknown location"),
+            report.message
+        );
+        match report_path().and_then(|path| write_report(&path, &report)) {
+            Ok(()) => tracing::info!("Crash report written"),
+            Err(e) => tracing::warn!("Failed to write crash report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+fn build_report(info: &PanicHookInfo) -> CrashReport {
+    // Reading the log files can't panic again, errors just leave them out
+    let mut recent_logs =
+        logging::get_recent_logs(None, REPORT_LOG_ENTRIES, None).unwrap_or_default();
+    recent_logs.reverse();
+
+    CrashReport {
+        crashed_at: Utc::now(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("<unnamed>")
+            .to_string(),
+        message: panic_message(info),
+        location: info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        backtrace: Backtrace::force_capture().to_string(),
+        recent_logs,
+    }
+}
+
+fn write_report(path: &Path, report: &CrashReport) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_vec_pretty(report)?)?;
+    Ok(())
+}
+
+fn read_report(path: &Path) -> Result<Option<CrashReport>, AppError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read(path)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+/// The report of the last crash, if it wasn't dismissed
+pub fn last_crash_report() -> Result<Option<CrashReport>, AppError> {
+    read_report(&report_path()?)
+}
+
+/// Delete the last crash report
+pub fn dismiss_crash_report() -> Result<(), AppError> {
+    let path = report_path()?;
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_crash_report_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join(REPORT_FILE);
+        assert_eq!(read_report(&path).unwrap(), None);
+
+        let report = CrashReport {
+            crashed_at: Utc::now(),
+            version: "1.2.3".to_string(),
+            os: "linux x86_64".to_string(),
+            thread: "main".to_string(),
+            message: "index out of bounds".to_string(),
+            location: Some("src/db.rs:10:5".to_string()),
+            backtrace: "0: main".to_string(),
+            recent_logs: vec![LogEntry {
+                timestamp: "2024-05-01T10:00:00Z".to_string(),
+                level: "INFO".to_string(),
+                target: "modulaur::db".to_string(),
+                message: "query".to_string(),
+                fields: Default::default(),
+            }],
+        };
+        write_report(&path, &report).unwrap();
+        assert_eq!(read_report(&path).unwrap(), Some(report));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(read_report(&path).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+// Credential expiry and rotation reminders
+//
+// Tokens expire (GitLab access tokens have an expiry date, company policies
+// ask for rotation every N days), and a fetch failing with 401 is a poor way
+// to find out. A credential can carry an expiry date and/or a maximum age;
+// the age counts from the last time its value changed, so storing a new
+// token restarts it (and drops the expiry date, which belonged to the old
+// token). A background check announces credentials that expire soon or have
+// expired as a `credentials://expiring` event, once per state.
+//
+// The metadata isn't secret, but it belongs with the credentials rather than
+// a workspace database, so it's kept in the credential store next to them
+// (the store can't enumerate, like the profile index).
+
+use crate::credentials::{get_credential, store_credential};
+use crate::error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::{AppHandle, Emitter};
+
+/// Credential key holding the expiry metadata of all credentials
+pub const EXPIRY_INDEX_KEY: &str = "credential_expiry";
+
+/// Credentials expiring within this many days are reported
+pub const DEFAULT_WARNING_DAYS: u32 = 14;
+
+/// How often expiry dates are checked
+const EXPIRY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Event carrying the credentials that just started expiring soon or expired
+pub const EXPIRING_EVENT: &str = "credentials://expiring";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialExpiry {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Rotate the credential after this many days
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// When the value last changed (or the metadata was first set)
+    pub rotated_at: DateTime<Utc>,
+}
+
+impl CredentialExpiry {
+    /// When the credential expires: the expiry date or the end of its
+    /// maximum age, whichever comes first
+    pub fn due_at(&self) -> Option<DateTime<Utc>> {
+        let aged_out = self
+            .max_age_days
+            .map(|days| self.rotated_at + Duration::days(i64::from(days)));
+        match (self.expires_at, aged_out) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// A credential that expires within the warning window (or has expired)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpiringCredential {
+    pub key: String,
+    pub due_at: DateTime<Utc>,
+    pub expired: bool,
+    /// Whole days until the credential expires (negative once expired)
+    pub days_left: i64,
+}
+
+fn load_index() -> Result<BTreeMap<String, CredentialExpiry>, AppError> {
+    match get_credential(EXPIRY_INDEX_KEY.to_string())? {
+        Some(index) => Ok(serde_json::from_str(&index)?),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+fn save_index(index: &BTreeMap<String, CredentialExpiry>) -> Result<(), AppError> {
+    store_credential(EXPIRY_INDEX_KEY.to_string(), serde_json::to_string(index)?)
+}
+
+/// Expiry metadata of every credential that has some, by key
+pub fn list_credential_expiry() -> Result<Vec<CredentialExpiry>, AppError> {
+    Ok(load_index()?.into_values().collect())
+}
+
+/// Set when a credential expires; without a date and a maximum age the
+/// metadata is removed
+pub fn set_credential_expiry(
+    key: &str,
+    expires_at: Option<DateTime<Utc>>,
+    max_age_days: Option<u32>,
+) -> Result<Option<CredentialExpiry>, AppError> {
+    if key == EXPIRY_INDEX_KEY {
+        return Err(AppError::Validation(format!(
+            "{} is reserved for expiry metadata",
+            key
+        )));
+    }
+    if max_age_days == Some(0) {
+        return Err(AppError::Validation(
+            "The maximum age must be at least one day".to_string(),
+        ));
+    }
+
+    let mut index = load_index()?;
+    let expiry = if expires_at.is_none() && max_age_days.is_none() {
+        index.remove(key);
+        None
+    } else {
+        let rotated_at = index
+            .get(key)
+            .map_or_else(Utc::now, |existing| existing.rotated_at);
+        let expiry = CredentialExpiry {
+            key: key.to_string(),
+            expires_at,
+            max_age_days,
+            rotated_at,
+        };
+        index.insert(key.to_string(), expiry.clone());
+        Some(expiry)
+    };
+    save_index(&index)?;
+    Ok(expiry)
+}
+
+/// Credentials expiring within `within_days` or already expired, soonest first
+pub fn get_expiring_credentials(within_days: u32) -> Result<Vec<ExpiringCredential>, AppError> {
+    Ok(expiring_at(&load_index()?, Utc::now(), within_days))
+}
+
+fn expiring_at(
+    index: &BTreeMap<String, CredentialExpiry>,
+    now: DateTime<Utc>,
+    within_days: u32,
+) -> Vec<ExpiringCredential> {
+    let until = now + Duration::days(i64::from(within_days));
+    let mut expiring: Vec<ExpiringCredential> = index
+        .values()
+        .filter_map(|expiry| {
+            let due_at = expiry.due_at()?;
+            (due_at <= until).then(|| ExpiringCredential {
+                key: expiry.key.clone(),
+                due_at,
+                expired: due_at <= now,
+                days_left: (due_at - now).num_days(),
+            })
+        })
+        .collect();
+    expiring.sort_by_key(|credential| credential.due_at);
+    expiring
+}
+
+/// Note that a credential is about to be stored: a changed value is a
+/// rotation, which restarts its maximum age and drops its old expiry date
+pub(crate) fn before_store(key: &str, value: &str) {
+    if key == EXPIRY_INDEX_KEY {
+        return;
+    }
+    let result = (|| {
+        let mut index = load_index()?;
+        let Some(expiry) = index.get_mut(key) else {
+            return Ok(());
+        };
+        let current = get_credential(key.to_string())?;
+        if current.as_deref() == Some(value) {
+            return Ok(());
+        }
+        expiry.rotated_at = Utc::now();
+        expiry.expires_at = None;
+        if expiry.max_age_days.is_none() {
+            index.remove(key);
+        }
+        save_index(&index)
+    })();
+    if let Err(e) = result {
+        tracing::warn!("Failed to update the expiry of credential {}: {}", key, e);
+    }
+}
+
+/// Forget the expiry metadata of a removed credential
+pub(crate) fn after_remove(key: &str) {
+    if key == EXPIRY_INDEX_KEY {
+        return;
+    }
+    let result = load_index().and_then(|mut index| match index.remove(key) {
+        Some(_) => save_index(&index),
+        None => Ok(()),
+    });
+    if let Err(e) = result {
+        tracing::warn!("Failed to remove the expiry of credential {}: {}", key, e);
+    }
+}
+
+/// Credentials not announced in their current state yet; forgets ones that
+/// are no longer expiring so they're announced again if they come back
+fn pending_warnings(
+    notified: &mut HashMap<String, (DateTime<Utc>, bool)>,
+    expiring: Vec<ExpiringCredential>,
+) -> Vec<ExpiringCredential> {
+    notified.retain(|key, _| expiring.iter().any(|c| c.key == *key));
+
+    expiring
+        .into_iter()
+        .filter(|c| {
+            let state = (c.due_at, c.expired);
+            notified.insert(c.key.clone(), state) != Some(state)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// Check credential expiry in the background every hour
+pub fn start(app: AppHandle) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+        let mut notified = HashMap::new();
+
+        loop {
+            ticker.tick().await;
+
+            // The keychain calls block, so keep them off the async workers
+            let expiring =
+                tokio::task::spawn_blocking(|| get_expiring_credentials(DEFAULT_WARNING_DAYS))
+                    .await;
+            let pending = match expiring {
+                Ok(Ok(expiring)) => pending_warnings(&mut notified, expiring),
+                Ok(Err(e)) => {
+                    tracing::warn!("Failed to check credential expiry: {}", e);
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Credential expiry check failed: {}", e);
+                    continue;
+                }
+            };
+
+            if !pending.is_empty() {
+                tracing::info!("{} credentials expiring soon or expired", pending.len());
+                if let Err(e) = app.emit(EXPIRING_EVENT, &pending) {
+                    tracing::warn!("Failed to emit expiring credentials: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::remove_credential;
+
+    #[test]
+    fn test_credential_expiry() {
+        let now = Utc::now();
+        store_credential("expiry-gitlab".to_string(), "glpat-old".to_string()).unwrap();
+        assert!(set_credential_expiry("expiry-gitlab", None, Some(0)).is_err());
+
+        let expiry =
+            set_credential_expiry("expiry-gitlab", Some(now + Duration::days(3)), Some(90))
+                .unwrap()
+                .unwrap();
+        assert_eq!(expiry.due_at(), Some(now + Duration::days(3)));
+        set_credential_expiry("expiry-s3", None, Some(30)).unwrap();
+
+        let expiring = get_expiring_credentials(7).unwrap();
+        let gitlab = expiring.iter().find(|c| c.key == "expiry-gitlab").unwrap();
+        assert!(!gitlab.expired);
+        assert_eq!(gitlab.days_left, 2);
+        assert!(!expiring.iter().any(|c| c.key == "expiry-s3"));
+
+        // Storing the same value again is no rotation
+        store_credential("expiry-gitlab".to_string(), "glpat-old".to_string()).unwrap();
+        assert!(get_expiring_credentials(7)
+            .unwrap()
+            .iter()
+            .any(|c| c.key == "expiry-gitlab"));
+
+        // A new token restarts the maximum age and drops the old expiry date
+        store_credential("expiry-gitlab".to_string(), "glpat-new".to_string()).unwrap();
+        let rotated = list_credential_expiry()
+            .unwrap()
+            .into_iter()
+            .find(|e| e.key == "expiry-gitlab")
+            .unwrap();
+        assert_eq!(rotated.expires_at, None);
+        assert_eq!(rotated.max_age_days, Some(90));
+        assert!(rotated.rotated_at >= now);
+
+        remove_credential("expiry-gitlab".to_string()).unwrap();
+        set_credential_expiry("expiry-s3", None, None).unwrap();
+        assert!(!list_credential_expiry()
+            .unwrap()
+            .iter()
+            .any(|e| e.key.starts_with("expiry-")));
+    }
+
+    #[test]
+    fn test_expiry_warnings() {
+        let now = Utc::now();
+        let mut index = BTreeMap::new();
+        for (key, expires_at, max_age_days, rotated_days_ago) in [
+            ("expired", Some(now - Duration::days(1)), None, 0),
+            ("aged", None, Some(30), 25),
+            ("later", Some(now + Duration::days(60)), None, 0),
+        ] {
+            index.insert(
+                key.to_string(),
+                CredentialExpiry {
+                    key: key.to_string(),
+                    expires_at,
+                    max_age_days,
+                    rotated_at: now - Duration::days(rotated_days_ago),
+                },
+            );
+        }
+
+        let expiring = expiring_at(&index, now, DEFAULT_WARNING_DAYS);
+        let found: Vec<(&str, bool)> = expiring
+            .iter()
+            .map(|c| (c.key.as_str(), c.expired))
+            .collect();
+        assert_eq!(found, vec![("expired", true), ("aged", false)]);
+
+        // Each state is announced once
+        let mut notified = HashMap::new();
+        assert_eq!(pending_warnings(&mut notified, expiring.clone()).len(), 2);
+        assert!(pending_warnings(&mut notified, expiring.clone()).is_empty());
+        let later = expiring_at(&index, now + Duration::days(6), DEFAULT_WARNING_DAYS);
+        let pending = pending_warnings(&mut notified, later);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].key, "aged");
+        assert!(pending[0].expired);
+    }
+}
@@ -0,0 +1,246 @@
+// Encrypted credential bundles
+//
+// Moving to a new machine shouldn't mean re-creating every token by hand.
+// An export gathers the named credentials the app knows about (the keys the
+// data sources use, their `{{credential:...}}` parameter placeholders and the
+// credential profiles) and seals them into one JSON bundle with a password:
+// Argon2id key derivation and AES-256-GCM, like the master-password vault.
+// Importing the bundle on the other machine writes them to its credential
+// store; the data sources themselves travel with the database backup.
+
+use crate::adapters::AuthConfig;
+use crate::credential_profiles::{
+    list_credential_profiles, load_credential_profile, save_credential_profile,
+};
+use crate::credentials::{get_credential, store_credential};
+use crate::data_sources::{placeholder_key, DataSource, DataSourceService};
+use crate::error::AppError;
+use crate::vault::{derive_key, open, seal, MIN_PASSWORD_LENGTH, SALT_LENGTH};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tokio::sync::RwLock;
+
+pub const CREDENTIAL_EXPORT_VERSION: &str = "1.0";
+
+/// Password-sealed credentials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialBundle {
+    pub version: String,
+    pub exported_at: DateTime<Utc>,
+    /// Argon2 salt (hex)
+    pub salt: String,
+    /// Sealed `CredentialPayload` (hex nonce + ciphertext)
+    pub data: String,
+}
+
+/// What a bundle holds once opened
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CredentialPayload {
+    /// Credential key → secret
+    credentials: BTreeMap<String, String>,
+    /// Profile name → auth config
+    profiles: BTreeMap<String, AuthConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialImport {
+    /// Credential keys written
+    pub credentials: Vec<String>,
+    /// Profile names written
+    pub profiles: Vec<String>,
+}
+
+/// Credential keys referenced by a data source's parameters
+fn collect_placeholder_keys(value: &serde_json::Value, keys: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(value) => {
+            if let Some(key) = placeholder_key(value) {
+                keys.push(key.to_string());
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_placeholder_keys(item, keys);
+            }
+        }
+        serde_json::Value::Object(fields) => {
+            for field in fields.values() {
+                collect_placeholder_keys(field, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Credential keys used by data sources (profile references excluded)
+fn credential_keys(sources: &[DataSource]) -> Vec<String> {
+    let mut keys = Vec::new();
+    for source in sources {
+        if let Some(key) = &source.auth_credential_key {
+            if source.auth_type.as_deref() != Some("credentialref") {
+                keys.push(key.clone());
+            }
+        }
+        collect_placeholder_keys(&source.parameters, &mut keys);
+    }
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// Seal the app's named credentials and profiles with a password
+pub async fn export_credentials(
+    data_source_service: &RwLock<DataSourceService>,
+    password: &str,
+) -> Result<CredentialBundle, AppError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::Validation(format!(
+            "The export password needs at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    let sources = data_source_service
+        .read()
+        .await
+        .get_all_data_sources()
+        .await?;
+    let mut payload = CredentialPayload::default();
+    for key in credential_keys(&sources) {
+        // Keys without a stored secret have nothing to move
+        if let Some(value) = get_credential(key.clone())? {
+            payload.credentials.insert(key, value);
+        }
+    }
+    for profile in list_credential_profiles()? {
+        let auth = load_credential_profile(&profile.name)?;
+        payload.profiles.insert(profile.name, auth);
+    }
+
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(password, &salt)?;
+    tracing::info!(
+        "Exported {} credentials and {} credential profiles",
+        payload.credentials.len(),
+        payload.profiles.len()
+    );
+    Ok(CredentialBundle {
+        version: CREDENTIAL_EXPORT_VERSION.to_string(),
+        exported_at: Utc::now(),
+        salt: hex::encode(salt),
+        data: seal(&key, &serde_json::to_vec(&payload)?)?,
+    })
+}
+
+/// Open a bundle and write its credentials and profiles, replacing ones
+/// with the same key or name
+pub fn import_credentials(
+    bundle: &CredentialBundle,
+    password: &str,
+) -> Result<CredentialImport, AppError> {
+    if bundle.version != CREDENTIAL_EXPORT_VERSION {
+        return Err(AppError::Validation(format!(
+            "Unsupported credential bundle version {}",
+            bundle.version
+        )));
+    }
+    let salt = hex::decode(&bundle.salt)
+        .map_err(|e| AppError::Validation(format!("Corrupt credential bundle: {}", e)))?;
+    let key = derive_key(password, &salt)?;
+    let payload: CredentialPayload = open(&key, &bundle.data)
+        .map_err(|_| AppError::Validation("Wrong password for the credential bundle".to_string()))
+        .and_then(|bytes| Ok(serde_json::from_slice(&bytes)?))?;
+
+    let mut imported = CredentialImport::default();
+    for (key, value) in payload.credentials {
+        store_credential(key.clone(), value)?;
+        imported.credentials.push(key);
+    }
+    for (name, auth) in payload.profiles {
+        save_credential_profile(&name, &auth)?;
+        imported.profiles.push(name);
+    }
+    tracing::info!(
+        "Imported {} credentials and {} credential profiles",
+        imported.credentials.len(),
+        imported.profiles.len()
+    );
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credential_profiles::delete_credential_profile;
+    use crate::credentials::remove_credential;
+    use crate::db::Database;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_credential_bundle() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(Mutex::new(
+            Database::new(temp_dir.path().to_path_buf()).await.unwrap(),
+        ));
+        let service = RwLock::new(DataSourceService::new(database));
+
+        let source: DataSource = serde_json::from_value(serde_json::json!({
+            "id": "bundle_gitlab", "name": "GitLab", "adapter_type": "gitlab",
+            "source": "gitlab", "endpoint": "https://gitlab.com",
+            "auth_type": "gitlab", "auth_credential_key": "bundle_gitlab_token",
+            "parameters": { "webhook": { "secret": "{{credential:bundle_gitlab.webhook.secret}}" } },
+            "environment": "both", "enabled": true, "auto_refresh": false, "data_ttl_days": 30,
+            "createdAt": "2024-01-01T00:00:00Z", "updatedAt": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        service
+            .read()
+            .await
+            .save_data_source(&source)
+            .await
+            .unwrap();
+        store_credential("bundle_gitlab_token".to_string(), "glpat-1".to_string()).unwrap();
+        store_credential(
+            "bundle_gitlab.webhook.secret".to_string(),
+            "hook-2".to_string(),
+        )
+        .unwrap();
+        let profile = AuthConfig::Basic {
+            username: "ops".to_string(),
+            password: "hunter22".to_string(),
+        };
+        save_credential_profile("bundle-ops", &profile).unwrap();
+
+        assert!(export_credentials(&service, "short").await.is_err());
+        let bundle = export_credentials(&service, "moving day").await.unwrap();
+        let serialized = serde_json::to_string(&bundle).unwrap();
+        assert!(!serialized.contains("glpat-1") && !serialized.contains("hunter22"));
+
+        // A new machine without the secrets
+        remove_credential("bundle_gitlab_token".to_string()).unwrap();
+        remove_credential("bundle_gitlab.webhook.secret".to_string()).unwrap();
+        delete_credential_profile("bundle-ops").unwrap();
+
+        assert!(import_credentials(&bundle, "wrong password").is_err());
+        let imported = import_credentials(&bundle, "moving day").unwrap();
+        assert_eq!(
+            imported.credentials,
+            vec!["bundle_gitlab.webhook.secret", "bundle_gitlab_token"]
+        );
+        assert!(imported.profiles.contains(&"bundle-ops".to_string()));
+        assert_eq!(
+            get_credential("bundle_gitlab_token".to_string()).unwrap(),
+            Some("glpat-1".to_string())
+        );
+        assert!(matches!(
+            load_credential_profile("bundle-ops").unwrap(),
+            AuthConfig::Basic { password, .. } if password == "hunter22"
+        ));
+        delete_credential_profile("bundle-ops").unwrap();
+    }
+}
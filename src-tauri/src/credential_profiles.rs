@@ -0,0 +1,178 @@
+// Named credential profiles
+//
+// A profile is a complete authentication config (a GitLab token, basic auth,
+// an S3 key pair, ...) stored under a name in the secure credential store.
+// Adapter configs and data sources refer to it with
+// `{ "type": "credentialref", "name": "work-gitlab" }`; the reference is
+// swapped for the stored config right before a fetch or connection test, so
+// the secrets never pass through the frontend again after saving and never
+// end up in data source records, exports or dashboard bundles.
+
+use crate::adapters::{AdapterConfig, AuthConfig};
+use crate::credentials::{get_credential, remove_credential, store_credential};
+use crate::error::AppError;
+use crate::redaction::register_secret;
+use serde::{Deserialize, Serialize};
+
+/// Credential key holding the list of profile names (the store can't enumerate)
+const PROFILE_INDEX_KEY: &str = "credential_profiles";
+
+/// A profile without its secrets, for listings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialProfileSummary {
+    pub name: String,
+    /// The `type` of the stored auth config, e.g. "gitlabtoken"
+    pub auth_type: String,
+}
+
+fn profile_key(name: &str) -> String {
+    format!("profile:{}", name)
+}
+
+fn validate_profile_name(name: &str) -> Result<(), AppError> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid credential profile name {:?}: use letters, digits, '-', '_' and '.'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+fn auth_type(auth: &AuthConfig) -> String {
+    serde_json::to_value(auth)
+        .ok()
+        .and_then(|value| value.get("type")?.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn load_index() -> Result<Vec<String>, AppError> {
+    match get_credential(PROFILE_INDEX_KEY.to_string())? {
+        Some(index) => Ok(serde_json::from_str(&index)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn save_index(names: &[String]) -> Result<(), AppError> {
+    store_credential(PROFILE_INDEX_KEY.to_string(), serde_json::to_string(names)?)
+}
+
+/// The auth config stored under a profile name
+pub fn load_credential_profile(name: &str) -> Result<AuthConfig, AppError> {
+    let stored = get_credential(profile_key(name))?
+        .ok_or_else(|| AppError::NotFound(format!("Credential profile {}", name)))?;
+    Ok(serde_json::from_str(&stored)?)
+}
+
+/// Create or replace a profile
+pub fn save_credential_profile(name: &str, auth: &AuthConfig) -> Result<(), AppError> {
+    validate_profile_name(name)?;
+    if matches!(auth, AuthConfig::CredentialRef { .. }) {
+        return Err(AppError::Validation(
+            "A credential profile can't refer to another profile".to_string(),
+        ));
+    }
+    store_credential(profile_key(name), serde_json::to_string(auth)?)?;
+
+    let mut names = load_index()?;
+    if !names.iter().any(|existing| existing == name) {
+        names.push(name.to_string());
+        names.sort();
+        save_index(&names)?;
+    }
+    Ok(())
+}
+
+/// Profiles by name, with their auth type
+pub fn list_credential_profiles() -> Result<Vec<CredentialProfileSummary>, AppError> {
+    let mut profiles = Vec::new();
+    for name in load_index()? {
+        // Entries removed behind the app's back are skipped
+        match load_credential_profile(&name) {
+            Ok(auth) => profiles.push(CredentialProfileSummary {
+                auth_type: auth_type(&auth),
+                name,
+            }),
+            Err(AppError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(profiles)
+}
+
+pub fn delete_credential_profile(name: &str) -> Result<(), AppError> {
+    remove_credential(profile_key(name))?;
+    let mut names = load_index()?;
+    names.retain(|existing| existing != name);
+    save_index(&names)
+}
+
+/// Swap a credential reference in an adapter config for the profile it names
+pub fn resolve_credential_ref(config: &mut AdapterConfig) -> Result<(), AppError> {
+    if let Some(AuthConfig::CredentialRef { name }) = &config.auth {
+        let auth = load_credential_profile(name).map_err(|e| match e {
+            AppError::NotFound(_) => AppError::Config(format!(
+                "Source '{}' uses the credential profile '{}', which doesn't exist",
+                config.source, name
+            )),
+            e => e,
+        })?;
+        for secret in auth.secrets() {
+            register_secret(secret);
+        }
+        config.auth = Some(auth);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credential_profiles() {
+        let gitlab = AuthConfig::GitLabToken {
+            token: "glpat-secret".to_string(),
+        };
+        save_credential_profile("work-gitlab", &gitlab).unwrap();
+        save_credential_profile("work-gitlab", &gitlab).unwrap();
+        assert!(save_credential_profile("bad name", &gitlab).is_err());
+        assert!(save_credential_profile(
+            "loop",
+            &AuthConfig::CredentialRef {
+                name: "work-gitlab".to_string()
+            }
+        )
+        .is_err());
+
+        let profiles = list_credential_profiles().unwrap();
+        assert!(profiles.contains(&CredentialProfileSummary {
+            name: "work-gitlab".to_string(),
+            auth_type: "gitlabtoken".to_string(),
+        }));
+
+        let mut config = AdapterConfig::new("gitlab", "gitlab", "https://gitlab.com");
+        config.auth =
+            Some(serde_json::from_str(r#"{"type":"credentialref","name":"work-gitlab"}"#).unwrap());
+        resolve_credential_ref(&mut config).unwrap();
+        assert!(matches!(
+            config.auth,
+            Some(AuthConfig::GitLabToken { ref token }) if token == "glpat-secret"
+        ));
+
+        delete_credential_profile("work-gitlab").unwrap();
+        assert!(!list_credential_profiles()
+            .unwrap()
+            .iter()
+            .any(|profile| profile.name == "work-gitlab"));
+        let mut config = AdapterConfig::new("gitlab", "gitlab", "https://gitlab.com");
+        config.auth = Some(AuthConfig::CredentialRef {
+            name: "work-gitlab".to_string(),
+        });
+        assert!(resolve_credential_ref(&mut config).is_err());
+    }
+}
@@ -1,65 +1,284 @@
+// Secure credentials
+//
+// Credentials (API tokens, passwords) live in the OS keychain: Windows
+// Credential Manager, the macOS Keychain or the Secret Service on Linux,
+// under the service name "modulaur" with the credential key as the account.
+// Where no keychain is reachable (e.g. a Linux session without a Secret
+// Service) they fall back to memory for the running session, and
+// `get_credential_storage` reports that, so the frontend keeps its own
+// encrypted copies instead of moving them over.
+//
+// With a master password set (see `vault`), credentials are kept in the
+// encrypted vault instead; ones stored before are moved there when read.
+// Values passing through here are registered with `redaction`, so they are
+// masked in logs and error messages.
+//
+// The frontend's own encrypted copies (kept in localStorage where the backend
+// can't persist credentials) use a random key from `get_local_encryption_key`;
+// copies made under the old environment-derived machine password are
+// re-encrypted when read.
+
+use crate::credential_expiry;
+use crate::error::{ApiError, AppError};
+use crate::redaction::register_secret;
+use crate::vault::{vault_enabled, with_vault};
+use rand::RngCore;
+use serde::Serialize;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Keychain service the credentials are stored under
+const KEYCHAIN_SERVICE: &str = "modulaur";
+
+/// Keychain account of the key encrypting the frontend's local credential copies
+const LOCAL_KEY_ACCOUNT: &str = "__local_encryption_key__";
+
+/// File holding that key where there is no keychain (in the app data dir)
+const LOCAL_KEY_FILE: &str = "local-encryption.key";
+
+static LOCAL_KEY: Mutex<Option<String>> = Mutex::new(None);
 
-/// Simple in-memory credential storage
-/// In production, this could integrate with OS keychain (Windows Credential Manager, etc.)
+/// Session-only credential storage used without a keychain
 static CREDENTIAL_STORE: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
 
-/// Initialize the credential store
-fn ensure_store() {
+/// Where credentials are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialStorage {
+    Keychain,
+    /// Encrypted with the master password
+    Vault,
+    /// Lost when the app exits
+    Memory,
+}
+
+/// Whether the OS keychain can be used (checked once)
+fn keychain_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        // Tests must not touch the developer's keychain
+        if cfg!(test) {
+            return false;
+        }
+        let available = keyring::Entry::new(KEYCHAIN_SERVICE, "__probe__")
+            .and_then(|entry| entry.get_password())
+            .map_or_else(|e| matches!(e, keyring::Error::NoEntry), |_| true);
+        if !available {
+            tracing::warn!("OS keychain unavailable; credentials are kept for this session only");
+        }
+        available
+    })
+}
+
+fn keychain_entry(key: &str) -> Result<keyring::Entry, AppError> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, key)
+        .map_err(|e| AppError::Config(format!("Failed to open keychain entry {}: {}", key, e)))
+}
+
+fn with_memory_store<T>(f: impl FnOnce(&mut HashMap<String, String>) -> T) -> T {
     let mut store = CREDENTIAL_STORE.lock().unwrap();
-    if store.is_none() {
-        *store = Some(HashMap::new());
+    f(store.get_or_insert_with(HashMap::new))
+}
+
+/// Where credentials are stored on this machine
+#[tauri::command]
+pub fn get_credential_storage() -> CredentialStorage {
+    if vault_enabled() {
+        CredentialStorage::Vault
+    } else if keychain_available() {
+        CredentialStorage::Keychain
+    } else {
+        CredentialStorage::Memory
     }
 }
 
 /// Store a credential securely
-/// In production: Would use Windows Credential Manager API
-#[tauri::command]
-pub fn store_secure_credential(key: String, value: String) -> Result<(), String> {
-    ensure_store();
+pub fn store_credential(key: String, value: String) -> Result<(), AppError> {
+    register_secret(&value);
+    credential_expiry::before_store(&key, &value);
+    if vault_enabled() {
+        return with_vault(|vault| vault.store(&key, &value));
+    }
+    store_in_keychain(key, value)
+}
 
-    let mut store = CREDENTIAL_STORE.lock().unwrap();
-    let map = store.as_mut().unwrap();
-    map.insert(key, value);
+/// Retrieve a credential securely
+pub fn get_credential(key: String) -> Result<Option<String>, AppError> {
+    let value = read_credential(key)?;
+    if let Some(value) = &value {
+        register_secret(value);
+    }
+    Ok(value)
+}
 
-    Ok(())
+/// Remove a credential
+pub fn remove_credential(key: String) -> Result<(), AppError> {
+    credential_expiry::after_remove(&key);
+    if vault_enabled() {
+        with_vault(|vault| vault.remove(&key))?;
+    }
+    remove_from_keychain(key)
 }
 
-/// Retrieve a credential securely
-/// In production: Would use Windows Credential Manager API
 #[tauri::command]
-pub fn get_secure_credential(key: String) -> Result<Option<String>, String> {
-    ensure_store();
+pub fn store_secure_credential(key: String, value: String) -> Result<(), ApiError> {
+    store_credential(key, value).map_err(ApiError::from)
+}
 
-    let store = CREDENTIAL_STORE.lock().unwrap();
-    let map = store.as_ref().unwrap();
-    Ok(map.get(&key).cloned())
+#[tauri::command]
+pub fn get_secure_credential(key: String) -> Result<Option<String>, ApiError> {
+    get_credential(key).map_err(ApiError::from)
 }
 
-/// Remove a credential
 #[tauri::command]
-pub fn remove_secure_credential(key: String) -> Result<(), String> {
-    ensure_store();
+pub fn remove_secure_credential(key: String) -> Result<(), ApiError> {
+    remove_credential(key).map_err(ApiError::from)
+}
 
-    let mut store = CREDENTIAL_STORE.lock().unwrap();
-    let map = store.as_mut().unwrap();
-    map.remove(&key);
+fn read_credential(key: String) -> Result<Option<String>, AppError> {
+    if !vault_enabled() {
+        return get_from_keychain(key);
+    }
+    if let Some(value) = with_vault(|vault| vault.get(&key))? {
+        return Ok(Some(value));
+    }
+    // Stored before the master password was set: move it into the vault
+    let Some(value) = get_from_keychain(key.clone())? else {
+        return Ok(None);
+    };
+    with_vault(|vault| vault.store(&key, &value))?;
+    remove_from_keychain(key)?;
+    Ok(Some(value))
+}
+
+fn store_in_keychain(key: String, value: String) -> Result<(), AppError> {
+    if !keychain_available() {
+        with_memory_store(|map| map.insert(key, value));
+        return Ok(());
+    }
+    keychain_entry(&key)?
+        .set_password(&value)
+        .map_err(|e| AppError::Config(format!("Failed to store credential {}: {}", key, e)))
+}
 
-    Ok(())
+fn get_from_keychain(key: String) -> Result<Option<String>, AppError> {
+    if !keychain_available() {
+        return Ok(with_memory_store(|map| map.get(&key).cloned()));
+    }
+    match keychain_entry(&key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::Config(format!(
+            "Failed to read credential {}: {}",
+            key, e
+        ))),
+    }
 }
 
-/// Get machine-specific password for encryption
-/// In production: Could use hardware-based keys or OS key derivation
+fn remove_from_keychain(key: String) -> Result<(), AppError> {
+    if !keychain_available() {
+        with_memory_store(|map| map.remove(&key));
+        return Ok(());
+    }
+    match keychain_entry(&key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(AppError::Config(format!(
+            "Failed to remove credential {}: {}",
+            key, e
+        ))),
+    }
+}
+
+/// Get or create the key the frontend encrypts its local credential copies
+/// with (hex, 256 bits)
+/// The key is random and kept in the OS keychain (on Windows the Credential
+/// Manager, protected by DPAPI), so other users can't derive it. Without a
+/// keychain it is kept in a file only the current user can read.
 #[tauri::command]
-pub fn get_machine_password() -> Result<String, String> {
-    // In production, this would:
-    // 1. Use Windows Credential Manager to get/create a key
-    // 2. Or use DPAPI (Data Protection API)
-    // 3. Or use hardware security module
+pub fn get_local_encryption_key() -> Result<String, ApiError> {
+    let mut cached = LOCAL_KEY.lock().unwrap();
+    if let Some(key) = cached.as_ref() {
+        return Ok(key.clone());
+    }
+    let key = if keychain_available() {
+        local_key_from_keychain()?
+    } else {
+        let path = crate::workspaces::app_root()?.join(LOCAL_KEY_FILE);
+        local_key_from_file(&path)?
+    };
+    register_secret(&key);
+    *cached = Some(key.clone());
+    Ok(key)
+}
+
+fn new_local_key() -> String {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    hex::encode(key)
+}
+
+fn local_key_from_keychain() -> Result<String, AppError> {
+    let entry = keychain_entry(LOCAL_KEY_ACCOUNT)?;
+    match entry.get_password() {
+        Ok(key) => Ok(key),
+        Err(keyring::Error::NoEntry) => {
+            let key = new_local_key();
+            entry.set_password(&key).map_err(|e| {
+                AppError::Config(format!("Failed to store the local encryption key: {}", e))
+            })?;
+            Ok(key)
+        }
+        Err(e) => Err(AppError::Config(format!(
+            "Failed to read the local encryption key: {}",
+            e
+        ))),
+    }
+}
+
+fn local_key_from_file(path: &Path) -> Result<String, AppError> {
+    match std::fs::read_to_string(path) {
+        Ok(key) => return Ok(key.trim().to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(AppError::Config(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    }
 
-    // For now, generate a consistent machine-specific value
+    tracing::warn!(
+        "No OS keychain; keeping the local encryption key in {} (set a master password for stronger protection)",
+        path.display()
+    );
+    let key = new_local_key();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            AppError::Config(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(key.as_bytes()))
+        .map_err(|e| AppError::Config(format!("Failed to write {}: {}", path.display(), e)))?;
+    Ok(key)
+}
+
+/// The password the frontend used to encrypt local credential copies before
+/// `get_local_encryption_key`: a hash of environment variables that any local
+/// process can reproduce. Only for reading (and re-encrypting) those copies.
+#[tauri::command]
+pub fn get_legacy_machine_password() -> Result<String, ApiError> {
+    use std::collections::hash_map::DefaultHasher;
     use std::env;
+    use std::hash::{Hash, Hasher};
 
     let machine_id = format!(
         "{}{}{}",
@@ -68,10 +287,6 @@ pub fn get_machine_password() -> Result<String, String> {
         env::var("USERDOMAIN").unwrap_or_else(|_| "domain".to_string())
     );
 
-    // Hash it to create a password
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-
     let mut hasher = DefaultHasher::new();
     machine_id.hash(&mut hasher);
     let hash = hasher.finish();
@@ -104,12 +319,35 @@ mod tests {
     }
 
     #[test]
-    fn test_machine_password() {
-        let password = get_machine_password().unwrap();
-        assert!(!password.is_empty());
+    fn test_memory_fallback() {
+        // No keychain under test
+        assert_eq!(get_credential_storage(), CredentialStorage::Memory);
+        remove_secure_credential("never_stored".to_string()).unwrap();
+        assert_eq!(
+            get_secure_credential("never_stored".to_string()).unwrap(),
+            None
+        );
+    }
 
-        // Should be consistent
-        let password2 = get_machine_password().unwrap();
-        assert_eq!(password, password2);
+    #[test]
+    fn test_local_encryption_key() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("keys").join(LOCAL_KEY_FILE);
+
+        let key = local_key_from_file(&path).unwrap();
+        assert_eq!(hex::decode(&key).unwrap().len(), 32);
+        assert_eq!(local_key_from_file(&path).unwrap(), key);
+        assert_ne!(new_local_key(), key);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Still readable for migrating old copies
+        let password = get_legacy_machine_password().unwrap();
+        assert!(!password.is_empty());
+        assert_eq!(get_legacy_machine_password().unwrap(), password);
     }
 }
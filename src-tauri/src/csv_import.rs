@@ -0,0 +1,369 @@
+// CSV import
+//
+// Streams a CSV file into staged records using a column → field mapping,
+// complementing the JSON-only `import_database`. Invalid rows are skipped and
+// reported individually; valid rows are upserted in batches.
+
+use crate::db::{Database, RecordMetadata, StagedRecord};
+use crate::error::AppError;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Rows upserted per batch
+const IMPORT_BATCH_SIZE: usize = 500;
+
+/// Row errors reported back at most (the import itself continues)
+const MAX_REPORTED_ERRORS: usize = 1000;
+
+/// How CSV columns map onto record fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvMapping {
+    /// Column name → target: "record_type", "source", "timestamp", "title",
+    /// "status", "description", "tags" or "data.<path>"
+    pub columns: HashMap<String, String>,
+    /// Used when no column is mapped to record_type (or the cell is empty)
+    pub record_type: Option<String>,
+    /// Used when no column is mapped to source (or the cell is empty)
+    pub source: Option<String>,
+    /// Data field whose value makes the record ID deterministic (re-imports update)
+    pub unique_key: Option<String>,
+    pub delimiter: char,
+    /// Separator inside the tags column
+    pub tag_separator: String,
+    /// Store unmapped columns as `data.<column>`
+    pub include_unmapped: bool,
+}
+
+impl Default for CsvMapping {
+    fn default() -> Self {
+        Self {
+            columns: HashMap::new(),
+            record_type: None,
+            source: None,
+            unique_key: None,
+            delimiter: ',',
+            tag_separator: ",".to_string(),
+            include_unmapped: true,
+        }
+    }
+}
+
+/// A row that could not be imported (row numbers are 1-based, excluding the header)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvRowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvImportReport {
+    pub rows_read: usize,
+    pub imported: usize,
+    pub failed: usize,
+    pub errors: Vec<CsvRowError>,
+}
+
+/// Where a column's value goes
+#[derive(Debug, Clone)]
+enum Target {
+    RecordType,
+    Source,
+    Timestamp,
+    Title,
+    Status,
+    Description,
+    Tags,
+    Data(Vec<String>),
+}
+
+impl Target {
+    fn parse(target: &str) -> Result<Self, AppError> {
+        Ok(match target {
+            "record_type" => Target::RecordType,
+            "source" => Target::Source,
+            "timestamp" => Target::Timestamp,
+            "title" => Target::Title,
+            "status" => Target::Status,
+            "description" => Target::Description,
+            "tags" => Target::Tags,
+            _ => match target.strip_prefix("data.") {
+                Some(path) if !path.is_empty() && path.split('.').all(|p| !p.is_empty()) => {
+                    Target::Data(path.split('.').map(String::from).collect())
+                }
+                _ => {
+                    return Err(AppError::Validation(format!(
+                        "Invalid mapping target: {}",
+                        target
+                    )))
+                }
+            },
+        })
+    }
+}
+
+/// Import a CSV file as staged records
+pub async fn import_records_from_csv(
+    db: &Database,
+    path: &Path,
+    mapping: &CsvMapping,
+) -> Result<CsvImportReport, AppError> {
+    if !mapping.delimiter.is_ascii() {
+        return Err(AppError::Validation(
+            "CSV delimiter must be an ASCII character".to_string(),
+        ));
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter as u8)
+        .flexible(true)
+        .from_path(path)
+        .map_err(std::io::Error::from)?;
+    let headers = reader.headers().map_err(std::io::Error::from)?.clone();
+
+    for column in mapping.columns.keys() {
+        if !headers.iter().any(|h| h == column) {
+            return Err(AppError::Validation(format!(
+                "Mapped column '{}' not found in CSV header",
+                column
+            )));
+        }
+    }
+
+    let targets: Vec<Option<Target>> = headers
+        .iter()
+        .map(|header| match mapping.columns.get(header) {
+            Some(target) => Target::parse(target).map(Some),
+            None if mapping.include_unmapped => Ok(Some(Target::Data(vec![header.to_string()]))),
+            None => Ok(None),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut report = CsvImportReport::default();
+    let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+
+    for (index, row) in reader.records().enumerate() {
+        let row_number = index + 1;
+        report.rows_read += 1;
+
+        let result = row
+            .map_err(|e| e.to_string())
+            .and_then(|row| build_record(&row, &targets, mapping));
+        match result {
+            Ok(record) => batch.push(record),
+            Err(message) => {
+                report.failed += 1;
+                if report.errors.len() < MAX_REPORTED_ERRORS {
+                    report.errors.push(CsvRowError {
+                        row: row_number,
+                        message,
+                    });
+                }
+            }
+        }
+
+        if batch.len() >= IMPORT_BATCH_SIZE {
+            report.imported += db
+                .upsert_records_batch(std::mem::take(&mut batch), mapping.unique_key.as_deref())
+                .await?;
+        }
+    }
+
+    if !batch.is_empty() {
+        report.imported += db
+            .upsert_records_batch(batch, mapping.unique_key.as_deref())
+            .await?;
+    }
+
+    tracing::info!(
+        "CSV import from {:?}: {} rows, {} imported, {} failed",
+        path,
+        report.rows_read,
+        report.imported,
+        report.failed
+    );
+    Ok(report)
+}
+
+/// Build and validate a record from one row
+fn build_record(
+    row: &csv::StringRecord,
+    targets: &[Option<Target>],
+    mapping: &CsvMapping,
+) -> Result<StagedRecord, String> {
+    let mut record_type = mapping.record_type.clone();
+    let mut source = mapping.source.clone();
+    let mut timestamp = None;
+    let mut metadata = RecordMetadata {
+        tags: Vec::new(),
+        status: None,
+        title: None,
+        description: None,
+    };
+    let mut data = serde_json::Map::new();
+
+    for (target, cell) in targets.iter().zip(row.iter()) {
+        let Some(target) = target else { continue };
+        let cell = cell.trim();
+        if cell.is_empty() {
+            continue;
+        }
+
+        match target {
+            Target::RecordType => record_type = Some(cell.to_string()),
+            Target::Source => source = Some(cell.to_string()),
+            Target::Timestamp => timestamp = Some(parse_timestamp(cell)?),
+            Target::Title => metadata.title = Some(cell.to_string()),
+            Target::Status => metadata.status = Some(cell.to_string()),
+            Target::Description => metadata.description = Some(cell.to_string()),
+            Target::Tags => metadata.tags.extend(
+                cell.split(mapping.tag_separator.as_str())
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from),
+            ),
+            Target::Data(path) => insert_path(&mut data, path, infer_value(cell)),
+        }
+    }
+
+    let record_type = record_type
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| "Missing record_type".to_string())?;
+    let source = source
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| "Missing source".to_string())?;
+    if let Some(key) = &mapping.unique_key {
+        if !data.contains_key(key) {
+            return Err(format!("Missing unique key field '{}'", key));
+        }
+    }
+
+    Ok(StagedRecord {
+        id: None,
+        record_type,
+        source,
+        timestamp: timestamp.unwrap_or_else(Utc::now),
+        data: serde_json::Value::Object(data),
+        metadata,
+    })
+}
+
+/// Accepts RFC 3339, "YYYY-MM-DD HH:MM:SS" (UTC) and "YYYY-MM-DD"
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    if let Ok(ts) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Ok(ts.and_utc());
+    }
+    if let Some(ts) = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+    {
+        return Ok(ts.and_utc());
+    }
+    Err(format!("Invalid timestamp: {}", value))
+}
+
+/// Numbers and booleans keep their type; everything else stays a string
+fn infer_value(cell: &str) -> serde_json::Value {
+    if let Ok(n) = cell.parse::<i64>() {
+        return n.into();
+    }
+    if let Some(n) = cell
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .and_then(serde_json::Number::from_f64)
+    {
+        return serde_json::Value::Number(n);
+    }
+    match cell {
+        "true" | "TRUE" | "True" => true.into(),
+        "false" | "FALSE" | "False" => false.into(),
+        _ => cell.into(),
+    }
+}
+
+fn insert_path(
+    data: &mut serde_json::Map<String, serde_json::Value>,
+    path: &[String],
+    value: serde_json::Value,
+) {
+    let (last, parents) = path.split_last().expect("data paths are never empty");
+    let mut current = data;
+    for key in parents {
+        let entry = current
+            .entry(key.clone())
+            .or_insert_with(|| serde_json::json!({}));
+        if !entry.is_object() {
+            *entry = serde_json::json!({});
+        }
+        current = entry.as_object_mut().expect("just ensured an object");
+    }
+    current.insert(last.clone(), value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_import_records_from_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("data")).await.unwrap();
+
+        let path = temp_dir.path().join("deploys.csv");
+        std::fs::write(
+            &path,
+            "key,name,env,when,labels,duration\n\
+             a1,Deploy API,prod,2024-03-01 10:00:00,ci;release,12.5\n\
+             a2,Deploy Web,,2024-03-02,ci,7\n\
+             a3,Broken,prod,yesterday,,1\n\
+             a1,Deploy API again,prod,2024-03-03T08:00:00Z,,3\n",
+        )
+        .unwrap();
+
+        let mapping = CsvMapping {
+            columns: HashMap::from([
+                ("name".to_string(), "title".to_string()),
+                ("env".to_string(), "source".to_string()),
+                ("when".to_string(), "timestamp".to_string()),
+                ("labels".to_string(), "tags".to_string()),
+                ("duration".to_string(), "data.stats.duration".to_string()),
+            ]),
+            record_type: Some("deployment".to_string()),
+            unique_key: Some("key".to_string()),
+            tag_separator: ";".to_string(),
+            ..Default::default()
+        };
+
+        let report = import_records_from_csv(&db, &path, &mapping).await.unwrap();
+        assert_eq!(report.rows_read, 4);
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.failed, 2);
+        assert_eq!(report.errors[0].row, 2);
+        assert_eq!(report.errors[0].message, "Missing source");
+        assert!(report.errors[1].message.starts_with("Invalid timestamp"));
+
+        // The second "a1" row updated the first one
+        let records = db.get_records_by_type("deployment").await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].metadata.title.as_deref(),
+            Some("Deploy API again")
+        );
+        assert_eq!(records[0].data["stats"]["duration"], 3);
+
+        let bad_mapping = CsvMapping {
+            columns: HashMap::from([("missing".to_string(), "title".to_string())]),
+            ..Default::default()
+        };
+        assert!(import_records_from_csv(&db, &path, &bad_mapping)
+            .await
+            .is_err());
+    }
+}
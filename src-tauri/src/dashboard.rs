@@ -1,98 +1,480 @@
+// Dashboards
+//
+// Dashboards live in the `dashboards` table, keyed by their ID, like the rest
+// of the workspace data, so exports, backups, live queries and import
+// strategies cover them without special cases. Older versions kept one JSON
+// file per dashboard in the workspace's `dashboards` folder;
+// `migrate_dashboard_files` copies those into the table once and then
+// renames the folder so it isn't read again.
+//
+// Saving a dashboard whose name, panels or refresh interval changed keeps the
+// previous state in `dashboard_versions` (the newest 50 per dashboard), so a
+// bad layout save can be undone with `restore_dashboard_version`.
+
+use crate::db::Database;
 use crate::error::AppError;
-use crate::models::Dashboard;
+use crate::models::{Dashboard, DashboardVariable, Panel};
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
+use surrealdb::sql::Thing;
 use tracing::{error, info};
 
-pub struct DashboardService {
-    storage_path: PathBuf,
+/// Name the dashboard folder gets once its files are in the database
+const MIGRATED_DIR_SUFFIX: &str = "migrated";
+
+/// Prior versions kept per dashboard
+const MAX_DASHBOARD_VERSIONS: u32 = 50;
+
+/// Dashboard as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DashboardRecord {
+    pub id: Thing,
+    pub name: String,
+    #[serde(default)]
+    pub panels: Vec<Panel>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+    #[serde(rename = "refreshInterval", default)]
+    pub refresh_interval: Option<u64>,
+    #[serde(default)]
+    pub variables: Vec<DashboardVariable>,
 }
 
-impl DashboardService {
-    pub fn new() -> Result<Self, AppError> {
-        // Use local app data directory
-        let app_dir = dirs::data_local_dir()
-            .ok_or_else(|| AppError::Config("Cannot determine local data directory".to_string()))?
-            .join("modulaur");
+impl From<DashboardRecord> for Dashboard {
+    fn from(record: DashboardRecord) -> Self {
+        Dashboard {
+            id: record.id.id.to_raw(),
+            name: record.name,
+            panels: record.panels,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+            refresh_interval: record.refresh_interval,
+            variables: record.variables,
+        }
+    }
+}
 
-        let storage_path = app_dir.join("dashboards");
+/// Earlier state of a dashboard, numbered from 1 per dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardVersion {
+    pub dashboard: String,
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub panels: Vec<Panel>,
+    #[serde(rename = "refreshInterval", default)]
+    pub refresh_interval: Option<u64>,
+    /// When this state was replaced (milliseconds, like `updatedAt`)
+    #[serde(rename = "savedAt")]
+    pub saved_at: i64,
+}
 
-        // Create directory if it doesn't exist
-        if !storage_path.exists() {
-            fs::create_dir_all(&storage_path).map_err(AppError::Io)?;
-            info!("Created dashboards directory at {:?}", storage_path);
+/// Variable names must be unique and usable in `${name}` references
+fn validate_variables(variables: &[DashboardVariable]) -> Result<(), AppError> {
+    let mut seen = std::collections::HashSet::new();
+    for variable in variables {
+        let name = variable.name.as_str();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(AppError::Validation(format!(
+                "Invalid dashboard variable name: {:?} (use letters, digits and _)",
+                name
+            )));
+        }
+        if !seen.insert(name) {
+            return Err(AppError::Validation(format!(
+                "Duplicate dashboard variable: {}",
+                name
+            )));
         }
+    }
+    Ok(())
+}
+
+/// Whether saving `next` over `previous` changes what the dashboard shows
+/// (picking other variable values doesn't count as a change of layout)
+fn layout_changed(previous: &Dashboard, next: &Dashboard) -> bool {
+    previous.name != next.name
+        || previous.panels != next.panels
+        || previous.refresh_interval != next.refresh_interval
+}
 
-        Ok(Self { storage_path })
+/// Record key of a dashboard id ("dashboards:abc" or "abc")
+fn dashboard_key(id: &str) -> &str {
+    id.strip_prefix("dashboards:")
+        .unwrap_or(id)
+        .trim_start_matches('⟨')
+        .trim_end_matches('⟩')
+}
+
+impl Database {
+    /// All dashboards, most recently updated first
+    pub async fn get_dashboards(&self) -> Result<Vec<Dashboard>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM dashboards ORDER BY updatedAt DESC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get dashboards: {}", e)))?;
+        let records: Vec<DashboardRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to get dashboards: {}", e)))?;
+        Ok(records.into_iter().map(Dashboard::from).collect())
     }
 
-    pub fn get_all(&self) -> Result<Vec<Dashboard>, AppError> {
-        let mut dashboards = Vec::new();
+    pub async fn get_dashboard(&self, id: &str) -> Result<Dashboard, AppError> {
+        let record: Option<DashboardRecord> = self
+            .db
+            .select(("dashboards", dashboard_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get dashboard: {}", e)))?;
+        record
+            .map(Dashboard::from)
+            .ok_or_else(|| AppError::NotFound(format!("Dashboard not found: {}", id)))
+    }
 
-        if !self.storage_path.exists() {
-            return Ok(dashboards);
+    /// Create or replace a dashboard, keeping the replaced state as a version
+    pub async fn save_dashboard(&self, dashboard: &Dashboard) -> Result<(), AppError> {
+        validate_variables(&dashboard.variables)?;
+        match self.get_dashboard(&dashboard.id).await {
+            Ok(previous) if layout_changed(&previous, dashboard) => {
+                self.add_dashboard_version(&previous).await?;
+            }
+            Ok(_) | Err(AppError::NotFound(_)) => {}
+            Err(e) => return Err(e),
         }
 
-        for entry in fs::read_dir(&self.storage_path).map_err(AppError::Io)? {
-            let entry = entry.map_err(AppError::Io)?;
-            let path = entry.path();
-
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                match fs::read_to_string(&path) {
-                    Ok(content) => match serde_json::from_str::<Dashboard>(&content) {
-                        Ok(dashboard) => dashboards.push(dashboard),
-                        Err(e) => {
-                            error!("Failed to parse dashboard file {:?}: {}", path, e);
-                        }
-                    },
-                    Err(e) => {
-                        error!("Failed to read dashboard file {:?}: {}", path, e);
-                    }
-                }
-            }
+        let mut content = serde_json::to_value(dashboard)?;
+        if let Some(object) = content.as_object_mut() {
+            object.remove("id");
+        }
+        self.query("UPSERT type::thing('dashboards', $key) CONTENT $content RETURN NONE")
+            .bind(("key", dashboard_key(&dashboard.id).to_string()))
+            .bind(("content", content))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save dashboard: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to save dashboard: {}", e)))?;
+
+        info!("Saved dashboard: {}", dashboard.id);
+        Ok(())
+    }
+
+    /// Copy a dashboard under a new name; the copy's panels get new IDs
+    pub async fn duplicate_dashboard(
+        &self,
+        id: &str,
+        new_name: &str,
+    ) -> Result<Dashboard, AppError> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err(AppError::Validation("The copy needs a name".to_string()));
         }
+        let source = self.get_dashboard(id).await?;
 
-        // Sort by updated_at descending
-        dashboards.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        let mut copy = Dashboard::new(new_name.to_string());
+        // Millisecond IDs can collide with a dashboard saved just before
+        copy.id = format!("dashboard_{}", uuid::Uuid::new_v4().simple());
+        copy.refresh_interval = source.refresh_interval;
+        copy.variables = source.variables;
+        copy.panels = source
+            .panels
+            .into_iter()
+            .enumerate()
+            .map(|(index, panel)| Panel {
+                i: format!("panel_{}_{}", copy.created_at, index),
+                ..panel
+            })
+            .collect();
+        self.save_dashboard(&copy).await?;
 
-        Ok(dashboards)
+        info!("Duplicated dashboard {} as {}", id, copy.id);
+        Ok(copy)
     }
 
-    pub fn get(&self, id: &str) -> Result<Dashboard, AppError> {
-        let file_path = self.storage_path.join(format!("{}.json", id));
+    pub async fn delete_dashboard(&self, id: &str) -> Result<(), AppError> {
+        let _: Option<DashboardRecord> = self
+            .db
+            .delete(("dashboards", dashboard_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete dashboard: {}", e)))?;
 
-        if !file_path.exists() {
-            return Err(AppError::Config(format!("Dashboard not found: {}", id)));
-        }
+        info!("Deleted dashboard: {}", id);
+        Ok(())
+    }
 
-        let content = fs::read_to_string(&file_path).map_err(AppError::Io)?;
+    /// Store the current state of a dashboard as its next version, dropping
+    /// versions beyond the newest `MAX_DASHBOARD_VERSIONS`
+    async fn add_dashboard_version(&self, dashboard: &Dashboard) -> Result<(), AppError> {
+        let key = dashboard_key(&dashboard.id).to_string();
+        let last = self
+            .get_dashboard_versions(&key)
+            .await?
+            .first()
+            .map(|v| v.version)
+            .unwrap_or(0);
+        let version = DashboardVersion {
+            dashboard: key.clone(),
+            version: last + 1,
+            name: dashboard.name.clone(),
+            panels: dashboard.panels.clone(),
+            refresh_interval: dashboard.refresh_interval,
+            saved_at: chrono::Utc::now().timestamp_millis(),
+        };
 
-        let dashboard =
-            serde_json::from_str::<Dashboard>(&content).map_err(AppError::Serialization)?;
+        self.query(
+            "CREATE dashboard_versions CONTENT $version RETURN NONE;
+                 DELETE dashboard_versions WHERE dashboard = $key AND version <= $cutoff;",
+        )
+        .bind(("version", version))
+        .bind(("key", key))
+        .bind(("cutoff", (last + 1).saturating_sub(MAX_DASHBOARD_VERSIONS)))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to save dashboard version: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to save dashboard version: {}", e)))?;
+        Ok(())
+    }
 
+    /// Prior versions of a dashboard, newest first
+    pub async fn get_dashboard_versions(
+        &self,
+        id: &str,
+    ) -> Result<Vec<DashboardVersion>, AppError> {
+        let mut result = self
+            .query(
+                "SELECT * OMIT id FROM dashboard_versions WHERE dashboard = $key
+                 ORDER BY version DESC",
+            )
+            .bind(("key", dashboard_key(id).to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get dashboard versions: {}", e)))?;
+        result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to get dashboard versions: {}", e)))
+    }
+
+    /// Bring back a prior version; the state it replaces becomes a version
+    /// itself, so a restore can be undone too
+    pub async fn restore_dashboard_version(
+        &self,
+        id: &str,
+        version: u32,
+    ) -> Result<Dashboard, AppError> {
+        let previous = self
+            .get_dashboard_versions(id)
+            .await?
+            .into_iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Version {} of dashboard {}", version, id))
+            })?;
+
+        let mut dashboard = self.get_dashboard(id).await?;
+        dashboard.name = previous.name;
+        dashboard.panels = previous.panels;
+        dashboard.refresh_interval = previous.refresh_interval;
+        dashboard.updated_at = chrono::Utc::now().timestamp_millis();
+        self.save_dashboard(&dashboard).await?;
+
+        info!("Restored version {} of dashboard {}", version, id);
         Ok(dashboard)
     }
 
-    pub fn save(&self, dashboard: &Dashboard) -> Result<(), AppError> {
-        let file_path = self.storage_path.join(format!("{}.json", dashboard.id));
+    /// Copy dashboard files from `dir` into the database, keeping dashboards
+    /// that already exist there, then rename the folder; returns the number
+    /// of dashboards copied
+    pub async fn migrate_dashboard_files(&self, dir: &Path) -> Result<usize, AppError> {
+        if !dir.is_dir() {
+            return Ok(0);
+        }
+
+        let mut migrated = 0;
+        let mut failed = 0;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let dashboard = match fs::read_to_string(&path)
+                .map_err(AppError::from)
+                .and_then(|content| Ok(serde_json::from_str::<Dashboard>(&content)?))
+            {
+                Ok(dashboard) => dashboard,
+                Err(e) => {
+                    error!("Failed to read dashboard file {:?}: {}", path, e);
+                    failed += 1;
+                    continue;
+                }
+            };
+            if self.get_dashboard(&dashboard.id).await.is_ok() {
+                continue;
+            }
+            self.save_dashboard(&dashboard).await?;
+            migrated += 1;
+        }
+
+        // Unreadable files stay where they are so they can be fixed and retried
+        if failed == 0 {
+            let mut target = dir.as_os_str().to_owned();
+            target.push(format!(".{}", MIGRATED_DIR_SUFFIX));
+            fs::rename(dir, &target)?;
+        }
+        info!(
+            "Migrated {} dashboard files from {:?} into the database",
+            migrated, dir
+        );
+        Ok(migrated)
+    }
+}
 
-        let content = serde_json::to_string_pretty(dashboard).map_err(AppError::Serialization)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
 
-        fs::write(&file_path, content).map_err(AppError::Io)?;
+    #[tokio::test]
+    async fn test_dashboards_in_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("db")).await.unwrap();
 
-        info!("Saved dashboard: {} to {:?}", dashboard.id, file_path);
+        let files = temp_dir.path().join("dashboards");
+        fs::create_dir_all(&files).unwrap();
+        let mut legacy = Dashboard::new("Legacy".to_string());
+        legacy.id = "dashboard_1".to_string();
+        fs::write(
+            files.join("dashboard_1.json"),
+            serde_json::to_string(&legacy).unwrap(),
+        )
+        .unwrap();
 
-        Ok(())
+        assert_eq!(db.migrate_dashboard_files(&files).await.unwrap(), 1);
+        assert!(!files.exists());
+        assert!(temp_dir.path().join("dashboards.migrated").exists());
+        assert_eq!(db.migrate_dashboard_files(&files).await.unwrap(), 0);
+
+        let mut newer = Dashboard::new("Newer".to_string());
+        newer.id = "dashboard_2".to_string();
+        newer.updated_at = legacy.updated_at + 1;
+        db.save_dashboard(&newer).await.unwrap();
+
+        let all = db.get_dashboards().await.unwrap();
+        let names: Vec<&str> = all.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["Newer", "Legacy"]);
+        assert_eq!(
+            db.get_dashboard("dashboard_1").await.unwrap().name,
+            "Legacy"
+        );
+
+        db.delete_dashboard("dashboard_1").await.unwrap();
+        assert!(db.get_dashboard("dashboard_1").await.is_err());
     }
 
-    pub fn delete(&self, id: &str) -> Result<(), AppError> {
-        let file_path = self.storage_path.join(format!("{}.json", id));
+    #[tokio::test]
+    async fn test_duplicate_dashboard() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("db")).await.unwrap();
+
+        let mut original = Dashboard::new("Team".to_string());
+        original.refresh_interval = Some(60);
+        original.variables = vec![DashboardVariable {
+            name: "project".to_string(),
+            label: None,
+            value: serde_json::json!("api"),
+            options: Vec::new(),
+        }];
+        original.panels = serde_json::from_value(serde_json::json!([
+            { "i": "panel_1", "x": 0, "y": 0, "w": 4, "h": 4, "type": "table",
+              "title": "Pipelines", "config": { "dataSource": "gitlab", "pageSize": 10 } },
+            { "i": "panel_2", "x": 4, "y": 0, "w": 4, "h": 4, "type": "text",
+              "title": "Notes", "config": { "content": "Hi" } }
+        ]))
+        .unwrap();
+        db.save_dashboard(&original).await.unwrap();
 
-        if file_path.exists() {
-            fs::remove_file(&file_path).map_err(AppError::Io)?;
-            info!("Deleted dashboard: {}", id);
+        let copy = db
+            .duplicate_dashboard(&original.id, " Team (copy) ")
+            .await
+            .unwrap();
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.name, "Team (copy)");
+        assert_eq!(copy.refresh_interval, Some(60));
+        assert_eq!(copy.variables, original.variables);
+        assert_eq!(copy.panels.len(), 2);
+        for (copied, panel) in copy.panels.iter().zip(&original.panels) {
+            assert_ne!(copied.i, panel.i);
+            assert_eq!(copied.config, panel.config);
         }
+        assert_eq!(db.get_dashboards().await.unwrap().len(), 2);
 
-        Ok(())
+        assert!(db.duplicate_dashboard(&original.id, " ").await.is_err());
+        assert!(db.duplicate_dashboard("missing", "Copy").await.is_err());
+
+        // Variables must be referenceable as `${name}` and unique
+        let mut invalid = copy.clone();
+        invalid.variables[0].name = "my project".to_string();
+        assert!(db.save_dashboard(&invalid).await.is_err());
+        invalid.variables = vec![copy.variables[0].clone(), copy.variables[0].clone()];
+        assert!(db.save_dashboard(&invalid).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_versions() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("db")).await.unwrap();
+
+        let mut dashboard = Dashboard::new("Ops".to_string());
+        db.save_dashboard(&dashboard).await.unwrap();
+        assert!(db
+            .get_dashboard_versions(&dashboard.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Saving without changes keeps no version
+        dashboard.updated_at += 1;
+        db.save_dashboard(&dashboard).await.unwrap();
+        assert!(db
+            .get_dashboard_versions(&dashboard.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        dashboard.panels = serde_json::from_value(serde_json::json!([
+            { "i": "p1", "x": 0, "y": 0, "w": 4, "h": 4, "type": "text",
+              "title": "Notes", "config": {} }
+        ]))
+        .unwrap();
+        db.save_dashboard(&dashboard).await.unwrap();
+        dashboard.panels.clear();
+        dashboard.name = "Broken".to_string();
+        db.save_dashboard(&dashboard).await.unwrap();
+
+        let versions = db.get_dashboard_versions(&dashboard.id).await.unwrap();
+        let numbers: Vec<u32> = versions.iter().map(|v| v.version).collect();
+        assert_eq!(numbers, vec![2, 1]);
+        assert_eq!(versions[0].panels.len(), 1);
+
+        let restored = db
+            .restore_dashboard_version(&dashboard.id, 2)
+            .await
+            .unwrap();
+        assert_eq!(restored.name, "Ops");
+        assert_eq!(restored.panels.len(), 1);
+        // The broken layout can be restored in turn
+        let versions = db.get_dashboard_versions(&dashboard.id).await.unwrap();
+        assert_eq!(versions[0].version, 3);
+        assert_eq!(versions[0].name, "Broken");
+        assert!(db
+            .restore_dashboard_version(&dashboard.id, 9)
+            .await
+            .is_err());
+
+        db.delete_dashboard(&dashboard.id).await.unwrap();
+        assert!(db
+            .get_dashboard_versions(&dashboard.id)
+            .await
+            .unwrap()
+            .is_empty());
     }
 }
@@ -0,0 +1,408 @@
+// Dashboard export and import
+//
+// Bundles one dashboard into a shareable JSON document: its layout, the data
+// sources its panels read and the plugins it needs. Secrets stay in the
+// credential store: data sources reference their auth credential by key, and
+// secret parameters (tokens, passwords, ...) are replaced by
+// `{{credential:<key>}}` placeholders, which are resolved from the credential
+// store when the data source is fetched. The bundle lists every credential
+// reference, so an import can report which plugins and credentials are
+// missing. Imported dashboards get a new ID; data sources keep theirs, and
+// ones that already exist are left as they are.
+
+use crate::credentials::get_credential;
+use crate::dashboard_refresh::panel_sources;
+use crate::data_sources::{
+    credential_placeholder, data_source_key, placeholder_key, DataSource, DataSourceService,
+};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::models::Dashboard;
+use crate::plugins::PluginMetadata;
+use crate::redaction::is_sensitive_field;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use tokio::sync::{Mutex, RwLock};
+
+/// Format version of dashboard bundles
+pub(crate) const DASHBOARD_EXPORT_VERSION: &str = "1.0";
+
+/// A credential a data source of the bundle needs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialReference {
+    pub data_source_id: String,
+    /// Key in the credential store
+    pub key: String,
+    /// Parameter path the placeholder replaced (none for the data source's auth)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardBundle {
+    pub version: String,
+    pub exported_at: String,
+    pub dashboard: Dashboard,
+    #[serde(default)]
+    pub data_sources: Vec<DataSource>,
+    /// Names of the plugins providing the panels and adapters
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    #[serde(default)]
+    pub credentials: Vec<CredentialReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardImport {
+    /// ID of the imported dashboard (none for a dry run)
+    pub dashboard_id: Option<String>,
+    pub data_sources_created: Vec<String>,
+    /// Data sources that already existed and were kept
+    pub data_sources_existing: Vec<String>,
+    pub missing_plugins: Vec<String>,
+    pub missing_credentials: Vec<CredentialReference>,
+}
+
+/// Replace secret parameters with credential placeholders, collecting the references
+fn scrub_parameters(
+    value: &mut Value,
+    path: &str,
+    data_source_id: &str,
+    references: &mut Vec<CredentialReference>,
+) {
+    let child_path = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                let field_path = child_path(name);
+                match field {
+                    Value::String(secret) if is_sensitive_field(name) && !secret.is_empty() => {
+                        // Already a reference (e.g. a re-exported import)
+                        let key = placeholder_key(secret)
+                            .map(String::from)
+                            .unwrap_or_else(|| format!("{}.{}", data_source_id, field_path));
+                        *field = Value::String(credential_placeholder(&key));
+                        references.push(CredentialReference {
+                            data_source_id: data_source_id.to_string(),
+                            key,
+                            parameter: Some(field_path),
+                        });
+                    }
+                    _ => scrub_parameters(field, &field_path, data_source_id, references),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                scrub_parameters(
+                    item,
+                    &child_path(&index.to_string()),
+                    data_source_id,
+                    references,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Bundle a dashboard with the data sources its panels read and the plugins it needs
+fn build_bundle(
+    dashboard: Dashboard,
+    data_sources: &[DataSource],
+    plugins: &[PluginMetadata],
+) -> DashboardBundle {
+    let variables = dashboard.variable_values();
+    let wanted: BTreeSet<String> = dashboard
+        .panels
+        .iter()
+        .flat_map(|panel| panel_sources(panel, &variables))
+        .collect();
+
+    let mut credentials = Vec::new();
+    let data_sources: Vec<DataSource> = data_sources
+        .iter()
+        .filter(|s| {
+            wanted.contains(&s.source)
+                || wanted.contains(&s.id)
+                || wanted.contains(data_source_key(&s.id))
+        })
+        .map(|s| {
+            let mut source = s.clone();
+            source.id = data_source_key(&s.id).to_string();
+            source.last_fetch = None;
+            source.last_fetch_count = None;
+            source.total_records = None;
+            if let Some(key) = &source.auth_credential_key {
+                credentials.push(CredentialReference {
+                    data_source_id: source.id.clone(),
+                    key: key.clone(),
+                    parameter: None,
+                });
+            }
+            scrub_parameters(&mut source.parameters, "", &source.id, &mut credentials);
+            source
+        })
+        .collect();
+
+    let plugins: BTreeSet<String> = plugins
+        .iter()
+        .filter(|plugin| {
+            let provides_adapter = plugin.adapter_type.as_ref().is_some_and(|adapter_type| {
+                data_sources.iter().any(|s| &s.adapter_type == adapter_type)
+            });
+            let provides_panel = plugin.frontend.as_ref().is_some_and(|frontend| {
+                frontend.components.iter().any(|component| {
+                    dashboard
+                        .panels
+                        .iter()
+                        .any(|panel| panel.panel_type == component.name)
+                })
+            });
+            provides_adapter || provides_panel
+        })
+        .map(|plugin| plugin.name.clone())
+        .collect();
+
+    DashboardBundle {
+        version: DASHBOARD_EXPORT_VERSION.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        dashboard,
+        data_sources,
+        plugins: plugins.into_iter().collect(),
+        credentials,
+    }
+}
+
+/// Export a dashboard as a shareable bundle
+pub async fn export_dashboard(
+    database: &Mutex<Database>,
+    data_source_service: &RwLock<DataSourceService>,
+    plugins: &[PluginMetadata],
+    id: &str,
+) -> Result<DashboardBundle, AppError> {
+    let dashboard = database.lock().await.get_dashboard(id).await?;
+    let data_sources = data_source_service
+        .read()
+        .await
+        .get_all_data_sources()
+        .await?;
+    Ok(build_bundle(dashboard, &data_sources, plugins))
+}
+
+/// Import a dashboard bundle as a new dashboard, creating the data sources
+/// that don't exist yet; with `dry_run` only reports what's missing
+pub async fn import_dashboard(
+    database: &Mutex<Database>,
+    data_source_service: &RwLock<DataSourceService>,
+    plugins: &[PluginMetadata],
+    bundle: DashboardBundle,
+    dry_run: bool,
+) -> Result<DashboardImport, AppError> {
+    if bundle.version.split('.').next() != DASHBOARD_EXPORT_VERSION.split('.').next() {
+        return Err(AppError::Validation(format!(
+            "Unsupported dashboard export version {}",
+            bundle.version
+        )));
+    }
+
+    let missing_plugins: Vec<String> = bundle
+        .plugins
+        .iter()
+        .filter(|name| !plugins.iter().any(|plugin| &plugin.name == *name))
+        .cloned()
+        .collect();
+    let missing_credentials: Vec<CredentialReference> = bundle
+        .credentials
+        .iter()
+        .filter(|reference| {
+            get_credential(reference.key.clone())
+                .ok()
+                .flatten()
+                .is_none()
+        })
+        .cloned()
+        .collect();
+
+    let mut data_sources_created = Vec::new();
+    let mut data_sources_existing = Vec::new();
+    for mut source in bundle.data_sources {
+        source.id = data_source_key(&source.id).to_string();
+        let service = data_source_service.read().await;
+        if service.get_data_source(&source.id).await?.is_some() {
+            data_sources_existing.push(source.id);
+            continue;
+        }
+        if !dry_run {
+            // Don't fetch before its credentials have been provided
+            if missing_credentials
+                .iter()
+                .any(|reference| reference.data_source_id == source.id)
+            {
+                source.enabled = false;
+            }
+            service.save_data_source(&source).await?;
+        }
+        data_sources_created.push(source.id);
+    }
+
+    let dashboard_id = if dry_run {
+        None
+    } else {
+        let mut dashboard = Dashboard::new(bundle.dashboard.name);
+        dashboard.panels = bundle.dashboard.panels;
+        dashboard.refresh_interval = bundle.dashboard.refresh_interval;
+        database.lock().await.save_dashboard(&dashboard).await?;
+        Some(dashboard.id)
+    };
+
+    Ok(DashboardImport {
+        dashboard_id,
+        data_sources_created,
+        data_sources_existing,
+        missing_plugins,
+        missing_credentials,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::store_credential;
+    use crate::plugins::{ComponentInfo, FrontendConfig};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn data_source(id: &str, source: &str, adapter_type: &str) -> DataSource {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": source, "adapter_type": adapter_type, "source": source,
+            "endpoint": "https://example.com", "parameters": {},
+            "environment": "both", "enabled": true, "auto_refresh": false,
+            "data_ttl_days": 30, "last_fetch_count": 12,
+            "createdAt": "2024-01-01T00:00:00Z", "updatedAt": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap()
+    }
+
+    fn plugin(name: &str, adapter_type: Option<&str>, panel: Option<&str>) -> PluginMetadata {
+        PluginMetadata {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            author: String::new(),
+            description: String::new(),
+            adapter_type: adapter_type.map(String::from),
+            capabilities: Vec::new(),
+            frontend: panel.map(|panel| FrontendConfig {
+                entry: "index.js".to_string(),
+                components: vec![ComponentInfo {
+                    type_: "panel".to_string(),
+                    name: panel.to_string(),
+                    display_name: panel.to_string(),
+                    description: None,
+                    icon: None,
+                    category: None,
+                    config_schema: None,
+                }],
+                styles: Vec::new(),
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_bundle() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(Mutex::new(
+            Database::new(temp_dir.path().to_path_buf()).await.unwrap(),
+        ));
+        let service = RwLock::new(DataSourceService::new(database.clone()));
+
+        let mut dashboard = Dashboard::new("CI".to_string());
+        dashboard.panels = serde_json::from_value(serde_json::json!([
+            { "i": "p1", "x": 0, "y": 0, "w": 4, "h": 4, "type": "table",
+              "title": "Pipelines", "config": { "dataSource": "gitlab" } },
+            { "i": "p2", "x": 4, "y": 0, "w": 4, "h": 4, "type": "jira-board",
+              "title": "Issues", "config": { "selectedSources": ["jira_issues"] } }
+        ]))
+        .unwrap();
+
+        let mut gitlab = data_source("data_sources:gitlab_ci", "gitlab", "gitlab");
+        gitlab.auth_type = Some("gitlab".to_string());
+        gitlab.auth_credential_key = Some("gitlab_token".to_string());
+        let mut jira = data_source("jira_issues", "jira", "jira");
+        jira.parameters = serde_json::json!({ "project": "OPS", "auth": { "api_key": "k-123" } });
+        let unused = data_source("rss", "rss", "rss");
+
+        let bundle = build_bundle(
+            dashboard,
+            &[gitlab, jira, unused],
+            &[
+                plugin("jira-plugin", Some("jira"), Some("jira-board")),
+                plugin("weather", None, Some("weather")),
+            ],
+        );
+        let ids: Vec<&str> = bundle.data_sources.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["gitlab_ci", "jira_issues"]);
+        assert_eq!(bundle.data_sources[0].last_fetch_count, None);
+        assert_eq!(bundle.plugins, vec!["jira-plugin"]);
+        assert_eq!(
+            bundle.data_sources[1].parameters["auth"]["api_key"],
+            "{{credential:jira_issues.auth.api_key}}"
+        );
+        assert_eq!(bundle.credentials.len(), 2);
+        assert!(!serde_json::to_string(&bundle).unwrap().contains("k-123"));
+
+        // One credential is already stored here, the plugin isn't installed
+        store_credential("gitlab_token".to_string(), "glpat-x".to_string()).unwrap();
+        let preview = import_dashboard(&database, &service, &[], bundle.clone(), true)
+            .await
+            .unwrap();
+        assert!(preview.dashboard_id.is_none());
+        assert_eq!(preview.missing_plugins, vec!["jira-plugin"]);
+        assert_eq!(preview.missing_credentials.len(), 1);
+        assert_eq!(preview.missing_credentials[0].data_source_id, "jira_issues");
+        assert!(service
+            .lock()
+            .await
+            .get_all_data_sources()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let imported = import_dashboard(&database, &service, &[], bundle.clone(), false)
+            .await
+            .unwrap();
+        assert_eq!(imported.data_sources_created.len(), 2);
+        let dashboard_id = imported.dashboard_id.unwrap();
+        assert_eq!(
+            database
+                .lock()
+                .await
+                .get_dashboard(&dashboard_id)
+                .await
+                .unwrap()
+                .panels
+                .len(),
+            2
+        );
+        let jira = service
+            .lock()
+            .await
+            .get_data_source("jira_issues")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!jira.enabled);
+
+        let again = import_dashboard(&database, &service, &[], bundle, true)
+            .await
+            .unwrap();
+        assert_eq!(again.data_sources_existing.len(), 2);
+    }
+}
@@ -0,0 +1,308 @@
+// Dashboard auto-refresh
+//
+// Dashboards can set a refresh interval. A background task checks regularly
+// for dashboards that are due, fetches the data sources their panels read
+// (through the fetch queue, like scheduled polling), re-evaluates the panels'
+// widget queries and pushes the results to the frontend as a
+// `dashboards://refreshed` event, so open dashboards stay current without
+// timers in the webview. Widget queries are evaluated with the dashboard's
+// variables substituted.
+
+use crate::data_sources::DataSourceService;
+use crate::db::drilldown::DrilldownTarget;
+use crate::db::widgets::{
+    apply_variables, resolve_widget_query, Variables, WidgetData, WidgetQuery,
+};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::fetch_queue::{FetchPriority, FetchQueue};
+use crate::fetcher::FetchSummary;
+use crate::models::{Dashboard, Panel};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Mutex, RwLock};
+
+/// How often the task checks for due dashboards
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Shortest refresh interval honored; shorter ones are raised to it
+pub const MIN_REFRESH_SECS: u64 = 15;
+
+/// Event carrying the refreshed panel data of a dashboard
+pub const REFRESH_EVENT: &str = "dashboards://refreshed";
+
+/// Data of one panel after a refresh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelData {
+    pub panel_id: String,
+    /// None for panels without a widget query
+    pub data: Option<WidgetData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// What clicking the panel's widget resolves against (`resolve_drilldown`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drilldown: Option<DrilldownTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardRefresh {
+    pub dashboard_id: String,
+    pub refreshed_at: DateTime<Utc>,
+    /// Fetches of the data sources the panels read
+    pub fetches: Vec<FetchSummary>,
+    pub panels: Vec<PanelData>,
+}
+
+/// Data sources a panel reads, by source identifier or data source ID
+pub(crate) fn panel_sources(panel: &Panel, variables: &Variables) -> Vec<String> {
+    let mut config = panel.config.clone();
+    if let Some(query) = config.get_mut("widgetQuery") {
+        // Unknown variables are reported when the query is evaluated
+        let _ = apply_variables(query, variables);
+    }
+    let mut sources: Vec<String> = config
+        .get("dataSource")
+        .and_then(|v| v.as_str())
+        .into_iter()
+        .map(String::from)
+        .collect();
+    for list in [
+        config.get("selectedSources"),
+        config.pointer("/widgetQuery/filters/sources"),
+    ] {
+        if let Some(list) = list.and_then(|v| v.as_array()) {
+            sources.extend(list.iter().filter_map(|v| v.as_str()).map(String::from));
+        }
+    }
+    sources.retain(|source| !source.is_empty());
+    sources
+}
+
+/// The widget query a panel declares, if any, with the variables substituted
+fn panel_query(panel: &Panel, variables: &Variables) -> Option<Result<WidgetQuery, AppError>> {
+    let query = panel.config.get("widgetQuery")?;
+    Some(resolve_widget_query(query.clone(), variables))
+}
+
+/// Evaluate a panel's widget query against the stored records
+pub(crate) async fn panel_data(
+    database: &Database,
+    panel: &Panel,
+    variables: &Variables,
+) -> PanelData {
+    let (data, error, drilldown) = match panel_query(panel, variables) {
+        None => (None, None, None),
+        Some(Err(e)) => (None, Some(e.to_string()), None),
+        Some(Ok(query)) => {
+            let drilldown = Some(query.drilldown_target());
+            match database.get_widget_data(query).await {
+                Ok(data) => (Some(data), None, drilldown),
+                Err(e) => (None, Some(e.to_string()), drilldown),
+            }
+        }
+    };
+    PanelData {
+        panel_id: panel.i.clone(),
+        data,
+        error,
+        drilldown,
+    }
+}
+
+/// Dashboards with a refresh interval whose last refresh is at least that long ago
+fn due_dashboards(
+    dashboards: Vec<Dashboard>,
+    last_refresh: &HashMap<String, DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Vec<Dashboard> {
+    dashboards
+        .into_iter()
+        .filter(|dashboard| {
+            let Some(interval) = dashboard.refresh_interval.filter(|secs| *secs > 0) else {
+                return false;
+            };
+            let interval = interval.max(MIN_REFRESH_SECS);
+            last_refresh
+                .get(&dashboard.id)
+                .is_none_or(|last| now - *last >= chrono::Duration::seconds(interval as i64))
+        })
+        .collect()
+}
+
+/// Fetch a dashboard's data sources and re-evaluate its panels' widget queries
+pub async fn refresh_dashboard(
+    database: &Mutex<Database>,
+    data_source_service: &RwLock<DataSourceService>,
+    fetch_queue: &FetchQueue,
+    dashboard: &Dashboard,
+) -> DashboardRefresh {
+    let variables = dashboard.variable_values();
+    let wanted: BTreeSet<String> = dashboard
+        .panels
+        .iter()
+        .flat_map(|panel| panel_sources(panel, &variables))
+        .collect();
+    let configs = if wanted.is_empty() {
+        Vec::new()
+    } else {
+        match data_source_service
+            .read()
+            .await
+            .get_enabled_data_sources()
+            .await
+        {
+            Ok(sources) => sources
+                .iter()
+                .filter(|s| wanted.contains(&s.source) || wanted.contains(&s.id))
+                .map(|s| s.to_adapter_config())
+                .collect(),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load data sources for dashboard {}: {}",
+                    dashboard.id,
+                    e
+                );
+                Vec::new()
+            }
+        }
+    };
+    let fetches = futures::future::join_all(
+        configs
+            .into_iter()
+            .map(|config| fetch_queue.run(config, FetchPriority::Scheduled)),
+    )
+    .await;
+
+    let mut panels = Vec::new();
+    for panel in &dashboard.panels {
+        panels.push(panel_data(&*database.lock().await, panel, &variables).await);
+    }
+
+    DashboardRefresh {
+        dashboard_id: dashboard.id.clone(),
+        refreshed_at: Utc::now(),
+        fetches,
+        panels,
+    }
+}
+
+/// Start the background refresh of dashboards with a refresh interval
+pub fn start(
+    app: AppHandle,
+    database: Arc<Mutex<Database>>,
+    data_source_service: Arc<RwLock<DataSourceService>>,
+    fetch_queue: FetchQueue,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(TICK_INTERVAL);
+        let mut last_refresh: HashMap<String, DateTime<Utc>> = HashMap::new();
+
+        loop {
+            ticker.tick().await;
+
+            let dashboards = match database.lock().await.get_dashboards().await {
+                Ok(dashboards) => dashboards,
+                Err(e) => {
+                    tracing::warn!("Failed to load dashboards for refresh: {}", e);
+                    continue;
+                }
+            };
+
+            for dashboard in due_dashboards(dashboards, &last_refresh, Utc::now()) {
+                let refresh =
+                    refresh_dashboard(&database, &data_source_service, &fetch_queue, &dashboard)
+                        .await;
+                last_refresh.insert(dashboard.id.clone(), refresh.refreshed_at);
+                tracing::debug!(
+                    "Refreshed dashboard {} ({} fetches)",
+                    dashboard.id,
+                    refresh.fetches.len()
+                );
+                if let Err(e) = app.emit(REFRESH_EVENT, &refresh) {
+                    tracing::warn!("Failed to emit dashboard refresh: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panel(config: serde_json::Value) -> Panel {
+        serde_json::from_value(serde_json::json!({
+            "i": "p1", "x": 0, "y": 0, "w": 4, "h": 4,
+            "type": "table", "title": "Pipelines", "config": config
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_panel_sources_and_query() {
+        let table = panel(serde_json::json!({
+            "dataSource": "gitlab",
+            "selectedSources": ["rss", ""],
+            "widgetQuery": { "filters": { "sources": ["github"], "limit": 10 } }
+        }));
+        let none = Variables::new();
+        assert_eq!(
+            panel_sources(&table, &none),
+            vec!["gitlab", "rss", "github"]
+        );
+        let query = panel_query(&table, &none).unwrap().unwrap();
+        assert_eq!(query.filters.limit, Some(10));
+
+        let text = panel(serde_json::json!({ "content": "Notes" }));
+        assert!(panel_sources(&text, &none).is_empty());
+        assert!(panel_query(&text, &none).is_none());
+        let broken = panel(serde_json::json!({ "widgetQuery": { "fields": "title" } }));
+        assert!(panel_query(&broken, &none).unwrap().is_err());
+
+        let templated = panel(serde_json::json!({
+            "widgetQuery": { "filters": { "sources": "${sources}", "types": ["${kind}"] } }
+        }));
+        let variables = Variables::from([
+            ("sources".to_string(), serde_json::json!(["gitlab"])),
+            ("kind".to_string(), serde_json::json!("gitlab_job")),
+        ]);
+        assert_eq!(panel_sources(&templated, &variables), vec!["gitlab"]);
+        let query = panel_query(&templated, &variables).unwrap().unwrap();
+        assert_eq!(query.filters.types, vec!["gitlab_job"]);
+        assert!(panel_query(&templated, &none).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_due_dashboards() {
+        let now = Utc::now();
+        let dashboard = |id: &str, interval: Option<u64>| {
+            let mut dashboard = Dashboard::new(id.to_string());
+            dashboard.id = id.to_string();
+            dashboard.refresh_interval = interval;
+            dashboard
+        };
+        let last_refresh = HashMap::from([
+            ("recent".to_string(), now - chrono::Duration::seconds(30)),
+            ("old".to_string(), now - chrono::Duration::seconds(120)),
+            ("fast".to_string(), now - chrono::Duration::seconds(10)),
+        ]);
+
+        let due = due_dashboards(
+            vec![
+                dashboard("manual", None),
+                dashboard("never", Some(60)),
+                dashboard("recent", Some(60)),
+                dashboard("old", Some(60)),
+                // Raised to the minimum interval
+                dashboard("fast", Some(1)),
+            ],
+            &last_refresh,
+            now,
+        );
+        let ids: Vec<&str> = due.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["never", "old"]);
+    }
+}
@@ -0,0 +1,266 @@
+// Dashboard snapshots
+//
+// A snapshot freezes a dashboard as it looked at one moment: the dashboard
+// itself and the data of every panel's widget query, evaluated against the
+// stored records (no fetches are triggered). Snapshots live in the
+// `dashboard_snapshots` table and are self-contained JSON documents, so they
+// can be viewed later ("how it looked on Friday") and exported as is. They
+// are kept when the dashboard is deleted, like a printed report would be.
+
+use crate::dashboard_refresh::{panel_data, PanelData};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::models::Dashboard;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+// ============================================================================
+// Snapshot Model
+// ============================================================================
+
+/// Snapshot as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DashboardSnapshotRecord {
+    pub id: Thing,
+    pub dashboard_id: String,
+    pub label: Option<String>,
+    pub taken_at: DateTime<Utc>,
+    pub dashboard: Dashboard,
+    pub panels: Vec<PanelData>,
+}
+
+/// User-facing snapshot with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardSnapshot {
+    pub id: String,
+    pub dashboard_id: String,
+    pub label: Option<String>,
+    pub taken_at: DateTime<Utc>,
+    /// The dashboard as it was when the snapshot was taken
+    pub dashboard: Dashboard,
+    /// Frozen data of every panel, in the dashboard's panel order
+    pub panels: Vec<PanelData>,
+}
+
+impl From<DashboardSnapshotRecord> for DashboardSnapshot {
+    fn from(record: DashboardSnapshotRecord) -> Self {
+        DashboardSnapshot {
+            id: record.id.id.to_raw(),
+            dashboard_id: record.dashboard_id,
+            label: record.label,
+            taken_at: record.taken_at,
+            dashboard: record.dashboard,
+            panels: record.panels,
+        }
+    }
+}
+
+/// Snapshot without its dashboard and panel data, for listings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DashboardSnapshotSummaryRecord {
+    pub id: Thing,
+    pub dashboard_id: String,
+    pub dashboard_name: String,
+    pub label: Option<String>,
+    pub taken_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardSnapshotSummary {
+    pub id: String,
+    pub dashboard_id: String,
+    pub dashboard_name: String,
+    pub label: Option<String>,
+    pub taken_at: DateTime<Utc>,
+}
+
+impl From<DashboardSnapshotSummaryRecord> for DashboardSnapshotSummary {
+    fn from(record: DashboardSnapshotSummaryRecord) -> Self {
+        DashboardSnapshotSummary {
+            id: record.id.id.to_raw(),
+            dashboard_id: record.dashboard_id,
+            dashboard_name: record.dashboard_name,
+            label: record.label,
+            taken_at: record.taken_at,
+        }
+    }
+}
+
+fn snapshot_key(id: &str) -> &str {
+    id.strip_prefix("dashboard_snapshots:").unwrap_or(id)
+}
+
+// ============================================================================
+// Snapshot Operations
+// ============================================================================
+
+impl Database {
+    /// Freeze the current data of a dashboard's panels into a stored snapshot
+    pub async fn snapshot_dashboard(
+        &self,
+        id: &str,
+        label: Option<String>,
+    ) -> Result<DashboardSnapshot, AppError> {
+        let dashboard = self.get_dashboard(id).await?;
+        let variables = dashboard.variable_values();
+        let mut panels = Vec::new();
+        for panel in &dashboard.panels {
+            panels.push(panel_data(self, panel, &variables).await);
+        }
+
+        let key = uuid::Uuid::new_v4().to_string();
+        let record = DashboardSnapshotRecord {
+            id: Thing::from(("dashboard_snapshots", key.as_str())),
+            dashboard_id: dashboard.id.clone(),
+            label: label
+                .map(|label| label.trim().to_string())
+                .filter(|label| !label.is_empty()),
+            taken_at: Utc::now(),
+            dashboard,
+            panels,
+        };
+
+        let saved: Option<DashboardSnapshotRecord> = self
+            .db
+            .create(("dashboard_snapshots", key.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save dashboard snapshot: {}", e)))?;
+
+        let snapshot: DashboardSnapshot = saved
+            .map(|r| r.into())
+            .ok_or_else(|| AppError::Database("Failed to save dashboard snapshot".to_string()))?;
+        tracing::info!(
+            "Took snapshot {} of dashboard {}",
+            snapshot.id,
+            snapshot.dashboard_id
+        );
+        Ok(snapshot)
+    }
+
+    /// Snapshots, newest first; all dashboards' unless one is given
+    pub async fn list_dashboard_snapshots(
+        &self,
+        dashboard_id: Option<&str>,
+    ) -> Result<Vec<DashboardSnapshotSummary>, AppError> {
+        let mut result = self
+            .query(
+                "SELECT id, dashboard_id, dashboard.name AS dashboard_name, label, taken_at
+                 FROM dashboard_snapshots
+                 WHERE $dashboard = NONE OR dashboard_id = $dashboard
+                 ORDER BY taken_at DESC",
+            )
+            .bind(("dashboard", dashboard_id.map(String::from)))
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to query dashboard snapshots: {}", e))
+            })?;
+
+        let snapshots: Vec<DashboardSnapshotSummaryRecord> = result.take(0).map_err(|e| {
+            AppError::Database(format!("Failed to parse dashboard snapshots: {}", e))
+        })?;
+
+        Ok(snapshots.into_iter().map(|s| s.into()).collect())
+    }
+
+    /// A snapshot with its dashboard and panel data
+    pub async fn get_dashboard_snapshot(&self, id: &str) -> Result<DashboardSnapshot, AppError> {
+        let snapshot: Option<DashboardSnapshotRecord> = self
+            .db
+            .select(("dashboard_snapshots", snapshot_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get dashboard snapshot: {}", e)))?;
+
+        snapshot
+            .map(|s| s.into())
+            .ok_or_else(|| AppError::NotFound(format!("Dashboard snapshot {}", id)))
+    }
+
+    pub async fn delete_dashboard_snapshot(&self, id: &str) -> Result<(), AppError> {
+        let _: Option<DashboardSnapshotRecord> = self
+            .db
+            .delete(("dashboard_snapshots", snapshot_key(id)))
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to delete dashboard snapshot: {}", e))
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::widgets::WidgetData;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_dashboard_snapshots() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("db")).await.unwrap();
+
+        for status in ["success", "failed"] {
+            let mut record = StagedRecord::new(
+                "gitlab_pipeline".to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "id": status }),
+            );
+            record.metadata.status = Some(status.to_string());
+            db.upsert_record(record).await.unwrap();
+        }
+
+        let mut dashboard = Dashboard::new("Weekly".to_string());
+        dashboard.panels = serde_json::from_value(serde_json::json!([
+            { "i": "count", "x": 0, "y": 0, "w": 4, "h": 4, "type": "table", "title": "Pipelines",
+              "config": { "widgetQuery": { "filters": { "types": ["gitlab_pipeline"] } } } },
+            { "i": "notes", "x": 4, "y": 0, "w": 4, "h": 4, "type": "text", "title": "Notes",
+              "config": { "content": "Hi" } }
+        ]))
+        .unwrap();
+        db.save_dashboard(&dashboard).await.unwrap();
+
+        let snapshot = db
+            .snapshot_dashboard(&dashboard.id, Some(" Friday ".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(snapshot.label.as_deref(), Some("Friday"));
+        assert_eq!(snapshot.panels.len(), 2);
+        match &snapshot.panels[0].data {
+            Some(WidgetData::Records { records, .. }) => assert_eq!(records.len(), 2),
+            other => panic!("unexpected panel data: {:?}", other),
+        }
+        assert!(snapshot.panels[1].data.is_none());
+
+        // Later changes don't touch the snapshot
+        dashboard.name = "Renamed".to_string();
+        db.save_dashboard(&dashboard).await.unwrap();
+        db.delete_records_by_source("gitlab").await.unwrap();
+
+        let stored = db.get_dashboard_snapshot(&snapshot.id).await.unwrap();
+        assert_eq!(stored.dashboard.name, "Weekly");
+        match &stored.panels[0].data {
+            Some(WidgetData::Records { total, .. }) => assert_eq!(*total, 2),
+            other => panic!("unexpected panel data: {:?}", other),
+        }
+
+        db.snapshot_dashboard(&dashboard.id, None).await.unwrap();
+        let listed = db
+            .list_dashboard_snapshots(Some(&dashboard.id))
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].dashboard_name, "Renamed");
+        assert_eq!(listed[1].label.as_deref(), Some("Friday"));
+        assert!(db
+            .list_dashboard_snapshots(Some("other"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        db.delete_dashboard_snapshot(&snapshot.id).await.unwrap();
+        assert!(db.get_dashboard_snapshot(&snapshot.id).await.is_err());
+        assert_eq!(db.list_dashboard_snapshots(None).await.unwrap().len(), 1);
+    }
+}
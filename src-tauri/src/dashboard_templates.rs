@@ -0,0 +1,327 @@
+// Dashboard templates
+//
+// Built-in dashboards to start from, shipped with the app like the example
+// prompt packages. Creating a dashboard from a template goes through the
+// dashboard bundle import: the template becomes a bundle with its panels,
+// data source stubs (disabled until their endpoint and credentials are
+// filled in) and required plugins, so the result reports missing plugins and
+// credentials the same way a shared dashboard does.
+
+use crate::dashboard_export::{
+    import_dashboard, CredentialReference, DashboardBundle, DashboardImport,
+    DASHBOARD_EXPORT_VERSION,
+};
+use crate::data_sources::{DataSource, DataSourceService};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::models::{Dashboard, Panel};
+use crate::plugins::PluginMetadata;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{Mutex, RwLock};
+
+/// Data source a template's panels read; created disabled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataSourceStub {
+    pub id: String,
+    pub name: String,
+    pub adapter_type: String,
+    pub source: String,
+    pub endpoint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_credential_key: Option<String>,
+    pub parameters: serde_json::Value,
+}
+
+impl DataSourceStub {
+    fn to_data_source(&self) -> DataSource {
+        let now = Utc::now();
+        DataSource {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            adapter_type: self.adapter_type.clone(),
+            source: self.source.clone(),
+            endpoint: self.endpoint.clone(),
+            auth_type: self.auth_type.clone(),
+            auth_credential_key: self.auth_credential_key.clone(),
+            parameters: self.parameters.clone(),
+            environment: "both".to_string(),
+            enabled: false,
+            auto_refresh: false,
+            refresh_interval: None,
+            data_ttl_days: 30,
+            pipeline: None,
+            unique_key: None,
+            redaction: None,
+            last_fetch: None,
+            last_fetch_count: None,
+            total_records: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub icon: String,
+    pub panels: Vec<Panel>,
+    pub data_sources: Vec<DataSourceStub>,
+    /// Plugins providing the template's panels
+    pub plugins: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<u64>,
+}
+
+impl DashboardTemplate {
+    fn to_bundle(&self) -> DashboardBundle {
+        let mut dashboard = Dashboard::new(self.name.clone());
+        dashboard.panels = self.panels.clone();
+        dashboard.refresh_interval = self.refresh_interval;
+
+        DashboardBundle {
+            version: DASHBOARD_EXPORT_VERSION.to_string(),
+            exported_at: Utc::now().to_rfc3339(),
+            dashboard,
+            data_sources: self
+                .data_sources
+                .iter()
+                .map(DataSourceStub::to_data_source)
+                .collect(),
+            plugins: self.plugins.clone(),
+            credentials: self
+                .data_sources
+                .iter()
+                .filter_map(|stub| {
+                    Some(CredentialReference {
+                        data_source_id: stub.id.clone(),
+                        key: stub.auth_credential_key.clone()?,
+                        parameter: None,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+fn panel(
+    id: &str,
+    (x, y, w, h): (i32, i32, i32, i32),
+    panel_type: &str,
+    title: &str,
+    config: serde_json::Value,
+) -> Panel {
+    Panel {
+        i: id.to_string(),
+        x,
+        y,
+        w,
+        h,
+        panel_type: panel_type.to_string(),
+        title: title.to_string(),
+        config,
+    }
+}
+
+/// GitLab CI overview: pipeline statuses, pipelines per day and the latest pipelines
+fn gitlab_ci_template() -> DashboardTemplate {
+    let pipelines = |extra: serde_json::Value| {
+        let mut config = json!({ "dataSource": "gitlab", "recordType": "gitlab_pipeline" });
+        if let (Some(config), Some(extra)) = (config.as_object_mut(), extra.as_object()) {
+            config.extend(extra.clone());
+        }
+        config
+    };
+
+    DashboardTemplate {
+        id: "gitlab-ci".to_string(),
+        name: "GitLab CI overview".to_string(),
+        description:
+            "Pipeline statuses, pipelines per day and the latest pipelines of a GitLab project"
+                .to_string(),
+        icon: "🦊".to_string(),
+        panels: vec![
+            panel(
+                "status",
+                (0, 0, 4, 6),
+                "chart",
+                "Pipeline status",
+                pipelines(
+                    json!({ "chartType": "doughnut", "groupBy": "status", "dataTransform": "count" }),
+                ),
+            ),
+            panel(
+                "per-day",
+                (4, 0, 8, 6),
+                "chart",
+                "Pipelines per day",
+                pipelines(json!({
+                    "chartType": "bar", "groupBy": "time", "timeBucket": "day", "dataTransform": "count"
+                })),
+            ),
+            panel(
+                "latest",
+                (0, 6, 12, 8),
+                "table",
+                "Latest pipelines",
+                pipelines(json!({
+                    "pageSize": 20,
+                    "widgetQuery": {
+                        "filters": { "types": ["gitlab_pipeline"], "sources": ["gitlab"], "limit": 20 }
+                    }
+                })),
+            ),
+        ],
+        data_sources: vec![DataSourceStub {
+            id: "gitlab".to_string(),
+            name: "GitLab".to_string(),
+            adapter_type: "gitlab".to_string(),
+            source: "gitlab".to_string(),
+            endpoint: "https://gitlab.com".to_string(),
+            auth_type: Some("gitlabtoken".to_string()),
+            auth_credential_key: Some("gitlab_token".to_string()),
+            parameters: json!({ "project_id": "", "fetch_pipelines": true, "fetch_jobs": true }),
+        }],
+        plugins: Vec::new(),
+        refresh_interval: Some(300),
+    }
+}
+
+/// RSS reader: the feed reader panel, which keeps its own feeds
+fn rss_reader_template() -> DashboardTemplate {
+    DashboardTemplate {
+        id: "rss-reader".to_string(),
+        name: "RSS reader".to_string(),
+        description: "Feeds with categories, read/unread status and favorites".to_string(),
+        icon: "📰".to_string(),
+        panels: vec![panel(
+            "feeds",
+            (0, 0, 12, 12),
+            "RSSFeedReaderPanel",
+            "Feeds",
+            json!({ "autoRefreshInterval": 30, "maxArticlesPerFeed": 50, "showImages": true }),
+        )],
+        data_sources: Vec::new(),
+        plugins: vec!["rss-feed-reader".to_string()],
+        refresh_interval: None,
+    }
+}
+
+/// Personal kanban: the ticket board next to notes for the week
+fn personal_kanban_template() -> DashboardTemplate {
+    DashboardTemplate {
+        id: "personal-kanban".to_string(),
+        name: "Personal kanban".to_string(),
+        description: "Your ticket board with a notes panel to plan the week".to_string(),
+        icon: "🎯".to_string(),
+        panels: vec![
+            panel("board", (0, 0, 9, 12), "ticket-kanban", "Board", json!({})),
+            panel(
+                "week",
+                (9, 0, 3, 12),
+                "text",
+                "This week",
+                json!({ "content": "Monday\n\nTuesday\n\nWednesday\n\nThursday\n\nFriday" }),
+            ),
+        ],
+        data_sources: Vec::new(),
+        plugins: Vec::new(),
+        refresh_interval: None,
+    }
+}
+
+/// All built-in dashboard templates
+pub fn dashboard_templates() -> Vec<DashboardTemplate> {
+    vec![
+        gitlab_ci_template(),
+        rss_reader_template(),
+        personal_kanban_template(),
+    ]
+}
+
+/// Create a dashboard (and its data source stubs) from a built-in template
+pub async fn create_dashboard_from_template(
+    database: &Mutex<Database>,
+    data_source_service: &RwLock<DataSourceService>,
+    plugins: &[PluginMetadata],
+    template_id: &str,
+) -> Result<DashboardImport, AppError> {
+    let template = dashboard_templates()
+        .into_iter()
+        .find(|template| template.id == template_id)
+        .ok_or_else(|| AppError::NotFound(format!("Dashboard template {}", template_id)))?;
+    import_dashboard(
+        database,
+        data_source_service,
+        plugins,
+        template.to_bundle(),
+        false,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_dashboard_templates() {
+        let templates = dashboard_templates();
+        let ids: Vec<&str> = templates.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["gitlab-ci", "rss-reader", "personal-kanban"]);
+        // Every panel reads a data source the template creates
+        for template in &templates {
+            for panel in &template.panels {
+                if let Some(source) = panel.config.get("dataSource").and_then(|v| v.as_str()) {
+                    assert!(template.data_sources.iter().any(|s| s.source == source));
+                }
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(Mutex::new(
+            Database::new(temp_dir.path().to_path_buf()).await.unwrap(),
+        ));
+        let service = RwLock::new(DataSourceService::new(database.clone()));
+
+        let created = create_dashboard_from_template(&database, &service, &[], "gitlab-ci")
+            .await
+            .unwrap();
+        assert_eq!(created.data_sources_created, vec!["gitlab"]);
+        let dashboard = database
+            .lock()
+            .await
+            .get_dashboard(&created.dashboard_id.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(dashboard.name, "GitLab CI overview");
+        assert_eq!(dashboard.panels.len(), 3);
+        let gitlab = service
+            .lock()
+            .await
+            .get_data_source("gitlab")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!gitlab.enabled);
+
+        let rss = create_dashboard_from_template(&database, &service, &[], "rss-reader")
+            .await
+            .unwrap();
+        assert_eq!(rss.missing_plugins, vec!["rss-feed-reader"]);
+
+        assert!(
+            create_dashboard_from_template(&database, &service, &[], "missing")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,271 @@
+// Dashboard panel checks
+//
+// Panels of a type that isn't built in come from a plugin's frontend
+// components. When the plugin is removed (or no longer ships the component)
+// such a panel would render empty without explanation, so saving a dashboard
+// checks every plugin panel against the installed plugins and the
+// component's `config_schema` and returns structured warnings. The dashboard
+// is saved either way; the warnings are for the editor to show.
+
+use crate::models::Panel;
+use crate::plugins::{ComponentInfo, ConfigField, PluginMetadata};
+use serde::{Deserialize, Serialize};
+
+/// Panel types shipped with the app (`BUILT_IN_PANELS` in the frontend)
+const BUILT_IN_PANEL_TYPES: &[&str] = &["text", "chart", "table", "kanban", "ticket-kanban"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelWarningKind {
+    /// No installed plugin provides the panel type
+    UnknownType,
+    /// A required config field is missing or empty
+    MissingField,
+    /// A config value doesn't fit its field's type, bounds or options
+    InvalidValue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelWarning {
+    pub panel_id: String,
+    pub panel_title: String,
+    pub panel_type: String,
+    pub kind: PanelWarningKind,
+    /// Config key the warning is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// The installed plugin panel component of a panel type, with its plugin's name
+fn find_component<'a>(
+    plugins: &'a [PluginMetadata],
+    panel_type: &str,
+) -> Option<(&'a str, &'a ComponentInfo)> {
+    plugins.iter().find_map(|plugin| {
+        let component = plugin
+            .frontend
+            .as_ref()?
+            .components
+            .iter()
+            .find(|component| component.type_ == "panel" && component.name == panel_type)?;
+        Some((plugin.name.as_str(), component))
+    })
+}
+
+/// Problem with a config value of a schema field, if any
+fn check_field(
+    field: &ConfigField,
+    value: Option<&serde_json::Value>,
+) -> Option<(PanelWarningKind, String)> {
+    let label = &field.label;
+    let value = match value {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(s)) if s.trim().is_empty() => None,
+        Some(value) => Some(value),
+    };
+    let Some(value) = value else {
+        return field.required.unwrap_or(false).then(|| {
+            (
+                PanelWarningKind::MissingField,
+                format!("{} is required", label),
+            )
+        });
+    };
+
+    let invalid = |message: String| Some((PanelWarningKind::InvalidValue, message));
+    match field.type_.as_str() {
+        "number" => {
+            let Some(number) = value.as_f64() else {
+                return invalid(format!("{} must be a number", label));
+            };
+            if field.min.is_some_and(|min| number < min)
+                || field.max.is_some_and(|max| number > max)
+            {
+                return invalid(format!(
+                    "{} must be between {} and {}",
+                    label,
+                    field.min.map_or("-∞".to_string(), |min| min.to_string()),
+                    field.max.map_or("∞".to_string(), |max| max.to_string())
+                ));
+            }
+        }
+        "checkbox" if !value.is_boolean() => {
+            return invalid(format!("{} must be on or off", label));
+        }
+        "select" => {
+            let options = field.options.as_deref().unwrap_or_default();
+            if !options.is_empty() && !options.iter().any(|option| &option.value == value) {
+                return invalid(format!("{} has no option {}", label, value));
+            }
+        }
+        "text" | "textarea" if !value.is_string() => {
+            return invalid(format!("{} must be text", label));
+        }
+        _ => {}
+    }
+    None
+}
+
+/// Warnings for plugin panels whose plugin is missing or whose config
+/// doesn't match the component's schema
+pub fn check_panels(panels: &[Panel], plugins: &[PluginMetadata]) -> Vec<PanelWarning> {
+    let mut warnings = Vec::new();
+    for panel in panels {
+        if BUILT_IN_PANEL_TYPES.contains(&panel.panel_type.as_str()) {
+            continue;
+        }
+        let warning = |kind, field: Option<&str>, message: String| PanelWarning {
+            panel_id: panel.i.clone(),
+            panel_title: panel.title.clone(),
+            panel_type: panel.panel_type.clone(),
+            kind,
+            field: field.map(String::from),
+            message,
+        };
+
+        let Some((plugin, component)) = find_component(plugins, &panel.panel_type) else {
+            warnings.push(warning(
+                PanelWarningKind::UnknownType,
+                None,
+                format!(
+                    "No installed plugin provides the panel type \"{}\"",
+                    panel.panel_type
+                ),
+            ));
+            continue;
+        };
+
+        for field in component
+            .config_schema
+            .iter()
+            .flat_map(|schema| &schema.fields)
+        {
+            if let Some((kind, message)) = check_field(field, panel.config.get(&field.key)) {
+                warnings.push(warning(
+                    kind,
+                    Some(&field.key),
+                    format!("{} ({} from {})", message, component.display_name, plugin),
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::{ConfigOption, ConfigSchema, FrontendConfig};
+    use serde_json::json;
+
+    fn field(key: &str, type_: &str) -> ConfigField {
+        ConfigField {
+            key: key.to_string(),
+            label: key.to_string(),
+            type_: type_.to_string(),
+            options: None,
+            placeholder: None,
+            required: None,
+            min: None,
+            max: None,
+            rows: None,
+            help: None,
+        }
+    }
+
+    #[test]
+    fn test_check_panels() {
+        let plugin = PluginMetadata {
+            name: "rss-feed-reader".to_string(),
+            version: "1.0.0".to_string(),
+            author: "Modulaur".to_string(),
+            description: String::new(),
+            adapter_type: None,
+            capabilities: Vec::new(),
+            frontend: Some(FrontendConfig {
+                entry: "index.js".to_string(),
+                components: vec![ComponentInfo {
+                    type_: "panel".to_string(),
+                    name: "RSSFeedReaderPanel".to_string(),
+                    display_name: "RSS Feed Reader".to_string(),
+                    description: None,
+                    icon: None,
+                    category: None,
+                    config_schema: Some(ConfigSchema {
+                        fields: vec![
+                            ConfigField {
+                                required: Some(true),
+                                ..field("feedUrl", "text")
+                            },
+                            ConfigField {
+                                min: Some(5.0),
+                                max: Some(120.0),
+                                ..field("autoRefreshInterval", "number")
+                            },
+                            ConfigField {
+                                options: Some(vec![ConfigOption {
+                                    value: json!("list"),
+                                    label: "List".to_string(),
+                                }]),
+                                ..field("layout", "select")
+                            },
+                            field("showImages", "checkbox"),
+                        ],
+                    }),
+                }],
+                styles: Vec::new(),
+            }),
+        };
+        let panel = |id: &str, panel_type: &str, config: serde_json::Value| Panel {
+            i: id.to_string(),
+            x: 0,
+            y: 0,
+            w: 4,
+            h: 4,
+            panel_type: panel_type.to_string(),
+            title: id.to_string(),
+            config,
+        };
+
+        let panels = vec![
+            panel("notes", "text", json!({})),
+            panel(
+                "feeds",
+                "RSSFeedReaderPanel",
+                json!({ "feedUrl": "https://example.com/rss", "autoRefreshInterval": 30 }),
+            ),
+            panel(
+                "broken",
+                "RSSFeedReaderPanel",
+                json!({ "autoRefreshInterval": 1, "layout": "grid", "showImages": "yes" }),
+            ),
+            panel("gone", "weather-panel", json!({})),
+        ];
+
+        let warnings = check_panels(&panels, std::slice::from_ref(&plugin));
+        let found: Vec<(&str, PanelWarningKind, Option<&str>)> = warnings
+            .iter()
+            .map(|w| (w.panel_id.as_str(), w.kind, w.field.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("broken", PanelWarningKind::MissingField, Some("feedUrl")),
+                (
+                    "broken",
+                    PanelWarningKind::InvalidValue,
+                    Some("autoRefreshInterval")
+                ),
+                ("broken", PanelWarningKind::InvalidValue, Some("layout")),
+                ("broken", PanelWarningKind::InvalidValue, Some("showImages")),
+                ("gone", PanelWarningKind::UnknownType, None),
+            ]
+        );
+
+        // Removing the plugin leaves its panels unknown
+        let warnings = check_panels(&panels[..2], &[]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, PanelWarningKind::UnknownType);
+    }
+}
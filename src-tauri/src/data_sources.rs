@@ -1,8 +1,21 @@
 // Data source management service
 // Handles CRUD operations for data source configurations
+//
+// Data sources are persisted in `data_sources` and read from there by the
+// polling job, `fetch_all_enabled` and the local API. Each has a `source`
+// identifier its records are stored under, so no two data sources may share
+// one. Secrets aren't part of the config: `auth_credential_key` references a
+// credential in the secure store (or a credential profile for
+// `credentialref`), and the polling schedule is `auto_refresh` with a
+// `refresh_interval` in seconds.
 
+use crate::adapters::{AdapterConfig, AuthConfig};
+use crate::credentials::{get_credential, store_credential};
 use crate::db::Database;
 use crate::error::AppError;
+use crate::pipeline::Pipeline;
+use crate::redaction::{is_sensitive_field, RedactionRules};
+use crate::vault::vault_enabled;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
@@ -27,6 +40,12 @@ struct DataSourceRecord {
     pub auto_refresh: bool,
     pub refresh_interval: Option<i32>,
     pub data_ttl_days: i32,
+    #[serde(default)]
+    pub pipeline: Option<Pipeline>,
+    #[serde(default)]
+    pub unique_key: Option<String>,
+    #[serde(default)]
+    pub redaction: Option<RedactionRules>,
     pub last_fetch: Option<DateTime<Utc>>,
     pub last_fetch_count: Option<i32>,
     pub total_records: Option<i32>,
@@ -53,6 +72,12 @@ pub struct DataSource {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub refresh_interval: Option<i32>,
     pub data_ttl_days: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<Pipeline>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<RedactionRules>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_fetch: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -81,6 +106,9 @@ impl From<DataSourceRecord> for DataSource {
             auto_refresh: record.auto_refresh,
             refresh_interval: record.refresh_interval,
             data_ttl_days: record.data_ttl_days,
+            pipeline: record.pipeline,
+            unique_key: record.unique_key,
+            redaction: record.redaction,
             last_fetch: record.last_fetch,
             last_fetch_count: record.last_fetch_count,
             total_records: record.total_records,
@@ -90,6 +118,301 @@ impl From<DataSourceRecord> for DataSource {
     }
 }
 
+impl DataSource {
+    /// Build the adapter configuration used to fetch this data source
+    pub fn to_adapter_config(&self) -> AdapterConfig {
+        AdapterConfig {
+            adapter_type: self.adapter_type.clone(),
+            source: self.source.clone(),
+            endpoint: self.endpoint.clone(),
+            auth: self.resolve_auth(),
+            parameters: resolve_credential_placeholders(&self.parameters),
+            polling_interval: self
+                .refresh_interval
+                .filter(|secs| *secs > 0)
+                .map(|secs| secs as u64),
+            enabled: self.enabled,
+            pipeline: self.pipeline.clone(),
+            unique_key: self.unique_key.clone(),
+            redaction: self.redaction.clone(),
+        }
+    }
+
+    /// Resolve token-style auth from the secure credential store
+    fn resolve_auth(&self) -> Option<AuthConfig> {
+        let key = self.auth_credential_key.as_ref()?;
+        // The profile is looked up at fetch time
+        if self.auth_type.as_deref() == Some("credentialref") {
+            return Some(AuthConfig::CredentialRef { name: key.clone() });
+        }
+        let token = get_credential(key.clone()).ok().flatten()?;
+
+        match self.auth_type.as_deref() {
+            Some("bearer") => Some(AuthConfig::Bearer { token }),
+            Some("gitlabtoken") | Some("gitlab") => Some(AuthConfig::GitLabToken { token }),
+            // Stored as "ACCESS_KEY_ID:SECRET_ACCESS_KEY"
+            Some("awssigv4") | Some("s3") => {
+                let (access_key_id, secret_access_key) = token.split_once(':')?;
+                Some(AuthConfig::AwsSigV4 {
+                    access_key_id: access_key_id.to_string(),
+                    secret_access_key: secret_access_key.to_string(),
+                    session_token: None,
+                })
+            }
+            // Username comes from the endpoint (user@host)
+            Some("ssh_password") => Some(AuthConfig::Basic {
+                username: String::new(),
+                password: token,
+            }),
+            Some("ssh_key") => Some(AuthConfig::SshKey {
+                username: String::new(),
+                private_key: token,
+                passphrase: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Parameter value standing in for a secret kept in the credential store
+pub fn credential_placeholder(key: &str) -> String {
+    format!("{{{{credential:{}}}}}", key)
+}
+
+/// Credential key of a placeholder value
+pub fn placeholder_key(value: &str) -> Option<&str> {
+    value.strip_prefix("{{credential:")?.strip_suffix("}}")
+}
+
+/// Parameters with credential placeholders replaced by the stored secrets
+/// (placeholders without a stored credential are left as they are)
+fn resolve_credential_placeholders(parameters: &serde_json::Value) -> serde_json::Value {
+    match parameters {
+        serde_json::Value::String(value) => placeholder_key(value)
+            .and_then(|key| get_credential(key.to_string()).ok().flatten())
+            .map(serde_json::Value::String)
+            .unwrap_or_else(|| parameters.clone()),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(resolve_credential_placeholders).collect())
+        }
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), resolve_credential_placeholders(value)))
+                .collect(),
+        ),
+        _ => parameters.clone(),
+    }
+}
+
+/// Move secret parameters into the credential store, leaving placeholders
+/// keyed like an export's ("<data source id>.<parameter path>")
+fn seal_sensitive_parameters(
+    value: &mut serde_json::Value,
+    path: &str,
+    data_source_id: &str,
+) -> Result<(), AppError> {
+    let serde_json::Value::Object(fields) = value else {
+        return Ok(());
+    };
+    for (name, field) in fields.iter_mut() {
+        let field_path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", path, name)
+        };
+        match field {
+            serde_json::Value::String(secret)
+                if is_sensitive_field(name)
+                    && !secret.is_empty()
+                    && placeholder_key(secret).is_none() =>
+            {
+                let key = format!("{}.{}", data_source_id, field_path);
+                store_credential(key.clone(), secret.clone())?;
+                *field = serde_json::Value::String(credential_placeholder(&key));
+            }
+            _ => seal_sensitive_parameters(field, &field_path, data_source_id)?,
+        }
+    }
+    Ok(())
+}
+
+/// Placeholder the frontend keeps in place of a token it moved to the secure store
+const SECURED_PLACEHOLDER: &str = "***SECURED***";
+
+fn default_parameters() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+fn default_environment() -> String {
+    "both".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_ttl_days() -> i32 {
+    30
+}
+
+/// Data source configuration as created or edited by the user; fetch
+/// statistics and timestamps are kept by the service
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataSourceInput {
+    /// Generated when creating a data source without one
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub adapter_type: String,
+    pub source: String,
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub auth_type: Option<String>,
+    #[serde(default)]
+    pub auth_credential_key: Option<String>,
+    /// Auth as the frontend edits it: a credential profile reference or a
+    /// token, which is moved to the secure store. Takes precedence over
+    /// `auth_type` and `auth_credential_key`.
+    #[serde(default)]
+    pub auth: Option<serde_json::Value>,
+    #[serde(default = "default_parameters")]
+    pub parameters: serde_json::Value,
+    #[serde(default = "default_environment")]
+    pub environment: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub auto_refresh: bool,
+    /// Seconds between scheduled fetches
+    #[serde(default)]
+    pub refresh_interval: Option<i32>,
+    #[serde(default = "default_ttl_days")]
+    pub data_ttl_days: i32,
+    #[serde(default)]
+    pub pipeline: Option<Pipeline>,
+    #[serde(default)]
+    pub unique_key: Option<String>,
+    #[serde(default)]
+    pub redaction: Option<RedactionRules>,
+}
+
+impl DataSourceInput {
+    /// The data source with this config, keeping `existing`'s statistics
+    /// and creation time
+    fn into_data_source(
+        self,
+        id: String,
+        existing: Option<&DataSource>,
+    ) -> Result<DataSource, AppError> {
+        let (auth_type, auth_credential_key) = match &self.auth {
+            Some(auth) => credential_reference(&id, auth)?,
+            None => (self.auth_type, self.auth_credential_key),
+        };
+        let now = Utc::now();
+        Ok(DataSource {
+            id,
+            name: self.name.trim().to_string(),
+            adapter_type: self.adapter_type,
+            source: self.source.trim().to_string(),
+            endpoint: self.endpoint,
+            auth_type,
+            auth_credential_key,
+            parameters: self.parameters,
+            environment: self.environment,
+            enabled: self.enabled,
+            auto_refresh: self.auto_refresh,
+            refresh_interval: self.refresh_interval,
+            data_ttl_days: self.data_ttl_days,
+            pipeline: self.pipeline,
+            unique_key: self.unique_key,
+            redaction: self.redaction,
+            last_fetch: existing.and_then(|s| s.last_fetch),
+            last_fetch_count: existing.and_then(|s| s.last_fetch_count),
+            total_records: existing.and_then(|s| s.total_records),
+            created_at: existing.map(|s| s.created_at).unwrap_or(now),
+            updated_at: now,
+        })
+    }
+}
+
+/// `auth_type` and `auth_credential_key` for the frontend's auth config;
+/// a plain token is moved to the secure store under `ds_<id>_token`
+fn credential_reference(
+    id: &str,
+    auth: &serde_json::Value,
+) -> Result<(Option<String>, Option<String>), AppError> {
+    let text = |field: &str| auth[field].as_str().filter(|s| !s.is_empty());
+    match text("type") {
+        None | Some("none") => Ok((None, None)),
+        Some("credentialref") => {
+            let name = text("name").ok_or_else(|| {
+                AppError::Validation("A credential reference needs a profile name".to_string())
+            })?;
+            Ok((Some("credentialref".to_string()), Some(name.to_string())))
+        }
+        Some(auth_type @ ("bearer" | "gitlabtoken")) => {
+            let key = format!("ds_{}_token", id);
+            match text("token") {
+                Some(SECURED_PLACEHOLDER) => {}
+                Some(token) => store_credential(key.clone(), token.to_string())?,
+                None => {
+                    return Err(AppError::Validation(format!(
+                        "{} auth needs a token",
+                        auth_type
+                    )))
+                }
+            }
+            Ok((Some(auth_type.to_string()), Some(key)))
+        }
+        Some(other) => Err(AppError::Validation(format!(
+            "Auth type '{}' can't be stored with a data source; use a credential profile",
+            other
+        ))),
+    }
+}
+
+/// Check a data source's config before it's saved
+fn validate(source: &DataSource) -> Result<(), AppError> {
+    let required = [
+        ("id", &source.id),
+        ("name", &source.name),
+        ("adapter type", &source.adapter_type),
+        ("source", &source.source),
+    ];
+    if let Some((field, _)) = required.iter().find(|(_, value)| value.trim().is_empty()) {
+        return Err(AppError::Validation(format!(
+            "The data source {} must not be empty",
+            field
+        )));
+    }
+    if source.refresh_interval.is_some_and(|secs| secs <= 0) {
+        return Err(AppError::Validation(
+            "The refresh interval must be at least one second".to_string(),
+        ));
+    }
+    if source.data_ttl_days < 0 {
+        return Err(AppError::Validation(
+            "Days to keep data must not be negative".to_string(),
+        ));
+    }
+    if source.auth_type.is_some() && source.auth_credential_key.is_none() {
+        return Err(AppError::Validation(
+            "An auth type needs a credential reference".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Record key of a data source id ("data_sources:abc" or "abc")
+pub fn data_source_key(id: &str) -> &str {
+    id.strip_prefix("data_sources:")
+        .unwrap_or(id)
+        .trim_start_matches('⟨')
+        .trim_end_matches('⟩')
+}
+
 // ============================================================================
 // Data Source Service
 // ============================================================================
@@ -112,7 +435,6 @@ impl DataSourceService {
 
         let db = self.db.lock().await;
         let mut result = db
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to query data sources: {}", e)))?;
@@ -124,52 +446,135 @@ impl DataSourceService {
         Ok(sources.into_iter().map(|s| s.into()).collect())
     }
 
+    /// Get all enabled data sources that are valid for the current environment
+    pub async fn get_enabled_data_sources(&self) -> Result<Vec<DataSource>, AppError> {
+        let is_production = !cfg!(debug_assertions);
+
+        let sources = self.get_all_data_sources().await?;
+
+        Ok(sources
+            .into_iter()
+            .filter(|s| s.enabled)
+            .filter(|s| match s.environment.as_str() {
+                "dev" => !is_production,
+                "production" => is_production,
+                _ => true,
+            })
+            .collect())
+    }
+
     /// Get a specific data source by ID
     pub async fn get_data_source(&self, id: &str) -> Result<Option<DataSource>, AppError> {
         let db = self.db.lock().await;
         let result: Option<DataSourceRecord> = db
             .db
-            .select(("data_sources", id))
+            .select(("data_sources", data_source_key(id)))
             .await
             .map_err(|e| AppError::Database(format!("Failed to get data source: {}", e)))?;
 
         Ok(result.map(|r| r.into()))
     }
 
+    /// Create a data source from a config, generating its id if it has none
+    pub async fn create_data_source(&self, input: DataSourceInput) -> Result<DataSource, AppError> {
+        let id = input
+            .id
+            .as_deref()
+            .map(data_source_key)
+            .filter(|id| !id.trim().is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("ds_{}", uuid::Uuid::new_v4().simple()));
+        if self.get_data_source(&id).await?.is_some() {
+            return Err(AppError::Validation(format!(
+                "Data source '{}' already exists",
+                id
+            )));
+        }
+
+        self.save_data_source(&input.into_data_source(id.clone(), None)?)
+            .await?;
+        self.saved(&id).await
+    }
+
+    /// Change some fields of a data source's config (as in `DataSourceInput`),
+    /// keeping its fetch statistics
+    pub async fn update_data_source(
+        &self,
+        id: &str,
+        updates: serde_json::Value,
+    ) -> Result<DataSource, AppError> {
+        let existing = self
+            .get_data_source(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Data source {}", id)))?;
+        let serde_json::Value::Object(updates) = updates else {
+            return Err(AppError::Validation(
+                "Data source updates must be an object".to_string(),
+            ));
+        };
+
+        let mut config = serde_json::to_value(&existing)?;
+        for (field, value) in updates {
+            config[field] = value;
+        }
+        let input: DataSourceInput = serde_json::from_value(config)
+            .map_err(|e| AppError::Validation(format!("Invalid data source: {}", e)))?;
+
+        let id = data_source_key(&existing.id).to_string();
+        self.save_data_source(&input.into_data_source(id.clone(), Some(&existing))?)
+            .await?;
+        self.saved(&id).await
+    }
+
+    async fn saved(&self, id: &str) -> Result<DataSource, AppError> {
+        self.get_data_source(id)
+            .await?
+            .ok_or_else(|| AppError::Database(format!("Failed to save data source {}", id)))
+    }
+
     /// Create or update a data source
     pub async fn save_data_source(&self, source: &DataSource) -> Result<(), AppError> {
+        validate(source)?;
         // Validate environment
         self.validate_environment(&source.environment)?;
+        let id = data_source_key(&source.id);
+        self.ensure_unique_source(&source.source, id).await?;
+
+        // With a master password, secrets in the parameters are kept encrypted
+        let mut parameters = source.parameters.clone();
+        if vault_enabled() {
+            seal_sensitive_parameters(&mut parameters, "", id)?;
+        }
 
         let now = Utc::now();
 
         let db = self.db.lock().await;
 
         // Check if exists
-        let exists: Option<DataSourceRecord> = db
-            .db
-            .select(("data_sources", source.id.as_str()))
-            .await
-            .map_err(|e| {
+        let exists: Option<DataSourceRecord> =
+            db.db.select(("data_sources", id)).await.map_err(|e| {
                 AppError::Database(format!("Failed to check data source existence: {}", e))
             })?;
 
         let record = if let Some(existing) = exists {
             // Update existing
             DataSourceRecord {
-                id: Thing::from(("data_sources", source.id.as_str())),
+                id: Thing::from(("data_sources", id)),
                 name: source.name.clone(),
                 adapter_type: source.adapter_type.clone(),
                 source: source.source.clone(),
                 endpoint: source.endpoint.clone(),
                 auth_type: source.auth_type.clone(),
                 auth_credential_key: source.auth_credential_key.clone(),
-                parameters: source.parameters.clone(),
+                parameters: parameters.clone(),
                 environment: source.environment.clone(),
                 enabled: source.enabled,
                 auto_refresh: source.auto_refresh,
                 refresh_interval: source.refresh_interval,
                 data_ttl_days: source.data_ttl_days,
+                pipeline: source.pipeline.clone(),
+                unique_key: source.unique_key.clone(),
+                redaction: source.redaction.clone(),
                 last_fetch: source.last_fetch,
                 last_fetch_count: source.last_fetch_count,
                 total_records: source.total_records,
@@ -179,19 +584,22 @@ impl DataSourceService {
         } else {
             // Create new
             DataSourceRecord {
-                id: Thing::from(("data_sources", source.id.as_str())),
+                id: Thing::from(("data_sources", id)),
                 name: source.name.clone(),
                 adapter_type: source.adapter_type.clone(),
                 source: source.source.clone(),
                 endpoint: source.endpoint.clone(),
                 auth_type: source.auth_type.clone(),
                 auth_credential_key: source.auth_credential_key.clone(),
-                parameters: source.parameters.clone(),
+                parameters: parameters.clone(),
                 environment: source.environment.clone(),
                 enabled: source.enabled,
                 auto_refresh: source.auto_refresh,
                 refresh_interval: source.refresh_interval,
                 data_ttl_days: source.data_ttl_days,
+                pipeline: source.pipeline.clone(),
+                unique_key: source.unique_key.clone(),
+                redaction: source.redaction.clone(),
                 last_fetch: None,
                 last_fetch_count: None,
                 total_records: None,
@@ -202,7 +610,7 @@ impl DataSourceService {
 
         let _: Option<DataSourceRecord> = db
             .db
-            .update(("data_sources", source.id.as_str()))
+            .upsert(("data_sources", id))
             .content(record)
             .await
             .map_err(|e| AppError::Database(format!("Failed to save data source: {}", e)))?;
@@ -211,14 +619,17 @@ impl DataSourceService {
         Ok(())
     }
 
-    /// Delete a data source
+    /// Delete a data source (its fetched records are kept)
     pub async fn delete_data_source(&self, id: &str) -> Result<(), AppError> {
         let db = self.db.lock().await;
-        let _deleted: Option<DataSourceRecord> = db
+        let deleted: Option<DataSourceRecord> = db
             .db
-            .delete(("data_sources", id))
+            .delete(("data_sources", data_source_key(id)))
             .await
             .map_err(|e| AppError::Database(format!("Failed to delete data source: {}", e)))?;
+        if deleted.is_none() {
+            return Err(AppError::NotFound(format!("Data source {}", id)));
+        }
 
         tracing::info!("Deleted data source: {}", id);
         Ok(())
@@ -249,22 +660,50 @@ impl DataSourceService {
         }
     }
 
-    /// Update fetch statistics
-    pub async fn update_fetch_stats(&self, id: &str, record_count: i32) -> Result<(), AppError> {
+    /// Record a successful fetch of the data source with this `source`
+    /// identifier
+    pub async fn update_fetch_stats(
+        &self,
+        source: &str,
+        record_count: usize,
+    ) -> Result<(), AppError> {
         let db = self.db.lock().await;
-        let query = format!(
-            "UPDATE data_sources:{} SET last_fetch = $now, last_fetch_count = {}",
-            id, record_count
-        );
-
-        db.db
-            .query(&query)
+        db
+            .query("UPDATE data_sources SET last_fetch = $now, last_fetch_count = $count WHERE source = $source")
+            .bind(("now", Utc::now()))
+            .bind(("count", record_count as i32))
+            .bind(("source", source.to_string()))
             .await
+            .map_err(|e| AppError::Database(format!("Failed to update fetch stats: {}", e)))?
+            .check()
             .map_err(|e| AppError::Database(format!("Failed to update fetch stats: {}", e)))?;
 
         Ok(())
     }
 
+    /// Fail if another data source already uses this `source` identifier
+    async fn ensure_unique_source(&self, source: &str, id: &str) -> Result<(), AppError> {
+        let db = self.db.lock().await;
+        let mut result = db
+            .query("SELECT VALUE id FROM data_sources WHERE source = $source AND id != $id")
+            .bind(("source", source.to_string()))
+            .bind(("id", Thing::from(("data_sources", id))))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check data source: {}", e)))?;
+        let taken: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to check data source: {}", e)))?;
+
+        match taken.first() {
+            Some(other) => Err(AppError::Validation(format!(
+                "Source '{}' is already used by data source {}",
+                source,
+                other.id.to_raw()
+            ))),
+            None => Ok(()),
+        }
+    }
+
     // Private helper
     fn validate_environment(&self, env: &str) -> Result<(), AppError> {
         match env {
@@ -276,3 +715,74 @@ impl DataSourceService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn input(value: serde_json::Value) -> DataSourceInput {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_data_source_crud() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let service = DataSourceService::new(Arc::new(Mutex::new(db)));
+
+        let created = service
+            .create_data_source(input(json!({
+                "name": "Pipelines",
+                "adapter_type": "gitlab",
+                "source": "gitlab_pipelines",
+                "endpoint": "https://gitlab.example.com",
+                "auth": { "type": "credentialref", "name": "gitlab-work" },
+                "auto_refresh": true,
+                "refresh_interval": 300,
+            })))
+            .await
+            .unwrap();
+        assert!(created.id.starts_with("data_sources:ds_"));
+        assert_eq!(created.auth_type.as_deref(), Some("credentialref"));
+        assert_eq!(created.auth_credential_key.as_deref(), Some("gitlab-work"));
+        assert_eq!(created.environment, "both");
+        assert!(created.enabled);
+
+        // Source identifiers are unique
+        let duplicate = service
+            .create_data_source(input(json!({
+                "name": "Other",
+                "adapter_type": "rest_api",
+                "source": "gitlab_pipelines",
+            })))
+            .await;
+        assert!(matches!(duplicate, Err(AppError::Validation(_))));
+
+        service
+            .update_fetch_stats("gitlab_pipelines", 12)
+            .await
+            .unwrap();
+        let updated = service
+            .update_data_source(&created.id, json!({ "enabled": false, "name": "CI" }))
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "CI");
+        assert!(!updated.enabled);
+        assert_eq!(updated.refresh_interval, Some(300));
+        assert_eq!(updated.last_fetch_count, Some(12));
+        assert_eq!(updated.created_at, created.created_at);
+        assert!(service
+            .update_data_source(&created.id, json!({ "refresh_interval": 0 }))
+            .await
+            .is_err());
+
+        service.delete_data_source(&created.id).await.unwrap();
+        assert!(service.get_all_data_sources().await.unwrap().is_empty());
+        assert!(matches!(
+            service.delete_data_source(&created.id).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
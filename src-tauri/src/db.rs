@@ -1,20 +1,49 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Instant;
 use surrealdb::sql::Thing;
 use surrealdb::Surreal;
 
 // Conditional imports based on feature flags
+// Embedded builds connect through the `any` engine so a workspace can use
+// either the local SurrealKv database or a remote server
 #[cfg(feature = "embedded-db")]
-use surrealdb::engine::local::{Db, SurrealKv};
+use surrealdb::engine::any::{self, Any};
 
 #[cfg(feature = "sidecar-db")]
-use surrealdb::engine::remote::ws::{Client, Ws};
+use surrealdb::engine::remote::ws::{Client, Ws, Wss};
 
-#[cfg(feature = "sidecar-db")]
+use surrealdb::method::Query;
 use surrealdb::opt::auth::Root;
+use surrealdb::opt::IntoQuery;
 
+use crate::audit;
 use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::metrics;
+use crate::undo::{self, UndoKind};
+use crate::workspaces::{RemoteDatabase, Workspace};
+
+/// SurrealDB engine of `Database::db`
+#[cfg(feature = "embedded-db")]
+type Engine = Any;
+
+#[cfg(feature = "sidecar-db")]
+type Engine = Client;
+
+pub mod aggregate;
+pub mod drilldown;
+pub mod integrity;
+pub mod migrations;
+pub mod query;
+pub mod relations;
+pub mod tags;
+pub mod timeseries;
+pub mod widgets;
+
+/// Number of records written per transaction in batch upserts
+const UPSERT_BATCH_SIZE: usize = 500;
 
 /// Generic record stored in SurrealDB
 /// This flexible structure allows adapters to store different types of data
@@ -62,7 +91,7 @@ impl StagedRecord {
 #[cfg(feature = "embedded-db")]
 #[derive(Clone)]
 pub struct Database {
-    pub db: Surreal<Db>,
+    pub db: Surreal<Any>,
 }
 
 #[cfg(feature = "sidecar-db")]
@@ -80,18 +109,23 @@ impl Database {
     /// - Dev mode (debug_assertions): Uses `data_dir/dev/db`
     /// - Prod mode (release): Uses `data_dir/prod/db`
     pub async fn new(data_dir: PathBuf) -> Result<Self, AppError> {
-        tracing::info!("Initializing embedded SurrealDB with SurrealKv");
-
-        // Create data directory if it doesn't exist
-        std::fs::create_dir_all(&data_dir)
-            .map_err(|e| AppError::Database(format!("Failed to create data directory: {}", e)))?;
-
         // Choose environment-specific subdirectory
         let env_subdir = if cfg!(debug_assertions) {
             "dev"
         } else {
             "prod"
         };
+        Self::open_environment(data_dir, env_subdir).await
+    }
+
+    /// Open the database of an environment (`dev` or `prod`) in `data_dir`,
+    /// whichever mode the app runs in
+    pub async fn open_environment(data_dir: PathBuf, env_subdir: &str) -> Result<Self, AppError> {
+        tracing::info!("Initializing embedded SurrealDB with SurrealKv");
+
+        // Create data directory if it doesn't exist
+        std::fs::create_dir_all(&data_dir)
+            .map_err(|e| AppError::Database(format!("Failed to create data directory: {}", e)))?;
 
         let env_data_dir = data_dir.join(env_subdir);
         std::fs::create_dir_all(&env_data_dir).map_err(|e| {
@@ -110,9 +144,11 @@ impl Database {
         tracing::info!("Database path ({}): {}", env_subdir, db_path_str);
 
         // Create embedded database with SurrealKv (pure Rust)
-        let db = Surreal::new::<SurrealKv>(db_path_str).await.map_err(|e| {
-            AppError::Database(format!("Failed to create embedded database: {}", e))
-        })?;
+        let db = any::connect(format!("surrealkv://{}", db_path_str))
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create embedded database: {}", e))
+            })?;
 
         // Use namespace and database
         db.use_ns("modulaur")
@@ -125,7 +161,19 @@ impl Database {
             env_subdir
         );
 
-        Ok(Self { db })
+        let database = Self { db };
+        database.run_migrations().await?;
+        Ok(database)
+    }
+
+    /// Connect to a SurrealDB server over WSS
+    pub async fn connect_remote(remote: &RemoteDatabase, password: &str) -> Result<Self, AppError> {
+        let address = remote.address()?;
+        tracing::info!("Connecting to remote SurrealDB at {}", address);
+        let db = any::connect(format!("wss://{}", address))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to connect to {}: {}", address, e)))?;
+        Self { db }.open_remote(remote, password).await
     }
 
     /// Initialize connection to legacy database (pre-stage-separation)
@@ -152,9 +200,11 @@ impl Database {
         tracing::info!("Legacy database path: {}", db_path_str);
 
         // Create embedded database with SurrealKv (pure Rust)
-        let db = Surreal::new::<SurrealKv>(db_path_str).await.map_err(|e| {
-            AppError::Database(format!("Failed to connect to legacy database: {}", e))
-        })?;
+        let db = any::connect(format!("surrealkv://{}", db_path_str))
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to connect to legacy database: {}", e))
+            })?;
 
         // Use namespace and database
         db.use_ns("modulaur")
@@ -171,12 +221,17 @@ impl Database {
 #[cfg(feature = "sidecar-db")]
 impl Database {
     /// Initialize SurrealDB connection to sidecar process
-    /// Connects via WebSocket to external SurrealDB server for persistence
+    /// Connects via WebSocket to external SurrealDB server for persistence,
+    /// on the port the sidecar was started on
     pub async fn new(_data_dir: PathBuf) -> Result<Self, AppError> {
-        tracing::info!("Connecting to SurrealDB sidecar via WebSocket");
+        let address = format!("127.0.0.1:{}", crate::sidecar::port());
+        tracing::info!(
+            "Connecting to SurrealDB sidecar via WebSocket at {}",
+            address
+        );
 
         // Connect to SurrealDB sidecar (will be started by Tauri)
-        let db = Surreal::new::<Ws>("127.0.0.1:8000").await.map_err(|e| {
+        let db = Surreal::new::<Ws>(address).await.map_err(|e| {
             AppError::Database(format!("Failed to connect to SurrealDB sidecar: {}", e))
         })?;
 
@@ -196,19 +251,72 @@ impl Database {
 
         tracing::info!("Successfully connected to SurrealDB sidecar");
 
-        Ok(Self { db })
+        let database = Self { db };
+        database.run_migrations().await?;
+        Ok(database)
+    }
+
+    /// Connect to a SurrealDB server over WSS instead of the sidecar
+    pub async fn connect_remote(remote: &RemoteDatabase, password: &str) -> Result<Self, AppError> {
+        let address = remote.address()?;
+        tracing::info!("Connecting to remote SurrealDB at {}", address);
+        let db = Surreal::new::<Wss>(address)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to connect to {}: {}", address, e)))?;
+        Self { db }.open_remote(remote, password).await
     }
 }
 
 // Shared methods that work with both embedded and sidecar modes
 impl Database {
+    /// Open the database of a workspace: its remote server if it has one,
+    /// otherwise the local database in its data dir
+    pub async fn open(workspace: &Workspace) -> Result<Self, AppError> {
+        match &workspace.remote {
+            Some(remote) => {
+                let password = crate::credentials::get_credential(workspace.remote_password_key())?
+                    .ok_or_else(|| {
+                        AppError::Config(format!(
+                            "No password stored for the remote database of workspace '{}'",
+                            workspace.name
+                        ))
+                    })?;
+                Self::connect_remote(remote, &password).await
+            }
+            None => Self::new(workspace.data_dir.clone()).await,
+        }
+    }
+
+    /// Sign in to a connected server, select the namespace and database and
+    /// bring the schema up to date
+    async fn open_remote(self, remote: &RemoteDatabase, password: &str) -> Result<Self, AppError> {
+        self.db
+            .signin(Root {
+                username: &remote.username,
+                password,
+            })
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to sign in to SurrealDB: {}", e)))?;
+        self.db
+            .use_ns(&remote.namespace)
+            .use_db(&remote.database)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to use namespace/database: {}", e)))?;
+        tracing::info!(
+            "Connected to remote SurrealDB ({}/{})",
+            remote.namespace,
+            remote.database
+        );
+
+        self.run_migrations().await?;
+        Ok(self)
+    }
+
     /// Create a new record
     pub async fn create_record(&self, record: StagedRecord) -> Result<StagedRecord, AppError> {
         // Create record and let SurrealDB generate the ID
         let created: Option<StagedRecord> = self
-            .db
-            .create("records")
-            .content(record)
+            .create_row("records", record)
             .await
             .map_err(|e| AppError::Database(format!("Failed to create record: {}", e)))?;
 
@@ -222,39 +330,89 @@ impl Database {
     /// Upsert a record (update if exists, create if not)
     /// Uses source + record_type + external_id to determine uniqueness
     pub async fn upsert_record(&self, record: StagedRecord) -> Result<StagedRecord, AppError> {
-        // Extract external ID from the data payload
-        let external_id = record.data.get("id").and_then(|v| v.as_u64()).or_else(|| {
-            record
-                .data
-                .get("id")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse::<u64>().ok())
-        });
-
-        if let Some(ext_id) = external_id {
-            // Create a deterministic record ID: source_type_externalid
-            // e.g., "qcc-gitlab-project_gitlab_pipeline_12345"
-            let record_id = format!(
-                "{}_{}_{}",
-                record.source.replace("-", "_"),
-                record.record_type.replace("-", "_"),
-                ext_id
-            );
+        self.upsert_record_with_key(record, None).await
+    }
 
+    /// Upsert a record using a unique-key expression
+    /// The expression is a comma-separated list of field paths (e.g. "data.url"
+    /// or "data.project_id, data.iid"); without one, `data.id` is used
+    pub async fn upsert_record_with_key(
+        &self,
+        record: StagedRecord,
+        unique_key: Option<&str>,
+    ) -> Result<StagedRecord, AppError> {
+        if let Some(record_id) = deterministic_record_id(&record, unique_key) {
             // Use UPSERT with explicit ID
             let created: Option<StagedRecord> = self
-                .db
-                .upsert(("records", record_id.as_str()))
-                .content(record)  // Owned value, no borrowing issue
+                .upsert_row("records", &record_id, record)
                 .await
                 .map_err(|e| AppError::Database(format!("Failed to upsert record: {}", e)))?;
 
-            created.ok_or_else(|| AppError::Database("Failed to upsert record".to_string()))
+            let created =
+                created.ok_or_else(|| AppError::Database("Failed to upsert record".to_string()))?;
+            announce_upserted(std::slice::from_ref(&created));
+            Ok(created)
         } else {
             // No external ID, fall back to regular create (will create duplicates)
             tracing::warn!("Record has no external ID, using create instead of upsert");
-            self.create_record(record).await
+            let created = self.create_record(record).await?;
+            announce_upserted(std::slice::from_ref(&created));
+            Ok(created)
+        }
+    }
+
+    /// Upsert many records, writing each chunk in a single transaction
+    /// Records without a deterministic ID are created (same as `upsert_record_with_key`)
+    pub async fn upsert_records_batch(
+        &self,
+        records: Vec<StagedRecord>,
+        unique_key: Option<&str>,
+    ) -> Result<usize, AppError> {
+        #[derive(Serialize)]
+        struct BatchItem {
+            key: Option<String>,
+            record: StagedRecord,
+        }
+
+        let upserted = upserted_events(&records);
+        let mut items: Vec<BatchItem> = records
+            .into_iter()
+            .map(|record| BatchItem {
+                key: deterministic_record_id(&record, unique_key),
+                record,
+            })
+            .collect();
+
+        let total = items.len();
+        while !items.is_empty() {
+            let chunk: Vec<BatchItem> = items.drain(..items.len().min(UPSERT_BATCH_SIZE)).collect();
+
+            let started = Instant::now();
+            self.query(
+                "BEGIN TRANSACTION;
+                     FOR $item IN $items {
+                         IF $item.key {
+                             UPSERT type::thing('records', $item.key) CONTENT $item.record;
+                         } ELSE {
+                             CREATE records CONTENT $item.record;
+                         };
+                     };
+                     COMMIT TRANSACTION;",
+            )
+            .bind(("items", chunk))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to upsert records: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to upsert records: {}", e)))?;
+            metrics::observe_since(
+                "db_query_duration_ms",
+                &[("operation", "upsert_records")],
+                started,
+            );
         }
+
+        upserted.into_iter().for_each(events::publish);
+        Ok(total)
     }
 
     /// Get a record by ID
@@ -278,12 +436,18 @@ impl Database {
 
         let query = "SELECT * FROM records WHERE record_type = $type ORDER BY timestamp DESC";
 
-        let mut result = self
-            .db
+        let started = Instant::now();
+        let result = self
             .query(query)
             .bind(("type", record_type.to_string()))
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to query records: {}", e)))?;
+            .await;
+        metrics::observe_since(
+            "db_query_duration_ms",
+            &[("operation", "get_records_by_type")],
+            started,
+        );
+        let mut result =
+            result.map_err(|e| AppError::Database(format!("Failed to query records: {}", e)))?;
 
         let records: Vec<StagedRecord> = result
             .take(0)
@@ -320,7 +484,6 @@ impl Database {
         let query = "SELECT * FROM records WHERE source = $source ORDER BY timestamp DESC";
 
         let mut result = self
-            .db
             .query(query)
             .bind(("source", source.to_string()))
             .await
@@ -341,13 +504,19 @@ impl Database {
     ) -> Result<Vec<StagedRecord>, AppError> {
         let query = "SELECT * FROM records ORDER BY timestamp DESC LIMIT $limit START $offset";
 
-        let mut result = self
-            .db
+        let started = Instant::now();
+        let result = self
             .query(query)
             .bind(("limit", limit))
             .bind(("offset", offset))
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to query records: {}", e)))?;
+            .await;
+        metrics::observe_since(
+            "db_query_duration_ms",
+            &[("operation", "get_all_records")],
+            started,
+        );
+        let mut result =
+            result.map_err(|e| AppError::Database(format!("Failed to query records: {}", e)))?;
 
         let records: Vec<StagedRecord> = result
             .take(0)
@@ -372,7 +541,7 @@ impl Database {
         let id = Self::normalize_record_id(id);
         tracing::info!("🗄️  Database delete_record called for ID: {}", id);
 
-        let deleted: Option<StagedRecord> = self.db.delete(("records", id)).await.map_err(|e| {
+        let deleted: Option<StagedRecord> = self.delete_row("records", id).await.map_err(|e| {
             tracing::error!("🗄️  SurrealDB delete failed for {}: {}", id, e);
             AppError::Database(format!("Failed to delete record: {}", e))
         })?;
@@ -398,22 +567,69 @@ impl Database {
 
         // Use UPDATE with merge to modify an existing record
         let updated: Option<StagedRecord> = self
-            .db
-            .update(("records", id))
-            .merge(record)
+            .merge_row("records", id, record)
             .await
             .map_err(|e| AppError::Database(format!("Failed to update record: {}", e)))?;
 
         updated.ok_or_else(|| AppError::Database(format!("Record not found: {}", id)))
     }
 
+    /// Apply an RFC 7386 JSON merge patch to a record's `data` and/or `metadata`
+    /// `patch` looks like `{"data": {...}, "metadata": {...}}`; `null` removes a field.
+    /// Unlike `update_record` the caller doesn't need to send the whole record.
+    pub async fn patch_record(
+        &self,
+        id: &str,
+        patch: &serde_json::Value,
+    ) -> Result<StagedRecord, AppError> {
+        let id = Self::normalize_record_id(id);
+        let patch = patch
+            .as_object()
+            .ok_or_else(|| AppError::Validation("Patch must be a JSON object".to_string()))?;
+        if let Some(key) = patch.keys().find(|k| *k != "data" && *k != "metadata") {
+            return Err(AppError::Validation(format!(
+                "Cannot patch '{}' (only data and metadata can be patched)",
+                key
+            )));
+        }
+
+        let record = self
+            .get_record(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Record not found: {}", id)))?;
+
+        let mut data = record.data;
+        if let Some(data_patch) = patch.get("data") {
+            json_merge_patch(&mut data, data_patch);
+        }
+
+        let mut metadata = serde_json::to_value(&record.metadata)?;
+        if let Some(metadata_patch) = patch.get("metadata") {
+            json_merge_patch(&mut metadata, metadata_patch);
+        }
+        let metadata: RecordMetadata = serde_json::from_value(metadata)
+            .map_err(|e| AppError::Validation(format!("Invalid metadata: {}", e)))?;
+
+        let mut result = self
+            .query("UPDATE type::thing('records', $id) SET data = $data, metadata = $metadata")
+            .bind(("id", id.to_string()))
+            .bind(("data", data))
+            .bind(("metadata", metadata))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to patch record: {}", e)))?;
+
+        let updated: Option<StagedRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to patch record: {}", e)))?;
+        updated.ok_or_else(|| AppError::NotFound(format!("Record not found: {}", id)))
+    }
+
     /// Delete all records from a specific source
     #[allow(dead_code)] // Will be used in UI for clearing adapter data
     pub async fn delete_records_by_source(&self, source: &str) -> Result<usize, AppError> {
         let query = "DELETE records WHERE source = $source RETURN BEFORE";
 
         let mut result = self
-            .db
             .query(query)
             .bind(("source", source.to_string()))
             .await
@@ -427,26 +643,27 @@ impl Database {
     }
 
     /// M5: Delete all records of a specific type (e.g., "gitlab_pipeline")
+    /// The deleted records are kept in the undo journal
     pub async fn delete_records_by_type(&self, record_type: &str) -> Result<usize, AppError> {
-        let query = "DELETE records WHERE record_type = $type RETURN BEFORE";
+        let statements = format!(
+            "LET $deleted = (DELETE records WHERE record_type = $type RETURN BEFORE);
+             {}
+             RETURN array::len($deleted);",
+            undo::journal_statement(
+                UndoKind::DeleteRecordsByType,
+                "string::concat('Records of type ', $type)",
+                "array::len($deleted)",
+                "{ records: $deleted }",
+            )
+        );
 
         let mut result = self
-            .db
-            .query(query)
-            .bind(("type", record_type.to_string()))
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to delete records by type: {}", e)))?;
-
-        let deleted: Vec<StagedRecord> = result
-            .take(0)
-            .map_err(|e| AppError::Database(format!("Failed to extract deleted records: {}", e)))?;
+            .transaction(&statements, ("type", record_type.to_string()))
+            .await?;
+        let deleted: usize = undo::returned(&mut result).unwrap_or(0);
 
-        tracing::info!(
-            "Deleted {} records of type '{}'",
-            deleted.len(),
-            record_type
-        );
-        Ok(deleted.len())
+        tracing::info!("Deleted {} records of type '{}'", deleted, record_type);
+        Ok(deleted)
     }
 
     /// M5: Delete records by source AND type (e.g., source="qcc-gitlab" AND type="gitlab_job")
@@ -458,7 +675,6 @@ impl Database {
         let query = "DELETE records WHERE source = $source AND record_type = $type RETURN BEFORE";
 
         let mut result = self
-            .db
             .query(query)
             .bind(("source", source.to_string()))
             .bind(("type", record_type.to_string()))
@@ -483,7 +699,6 @@ impl Database {
         let query = "SELECT count() FROM records GROUP ALL";
 
         let mut result = self
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to count records: {}", e)))?;
@@ -507,7 +722,6 @@ impl Database {
             "SELECT * FROM records WHERE metadata.tags CONTAINSANY $tags ORDER BY timestamp DESC";
 
         let mut result = self
-            .db
             .query(query)
             .bind(("tags", tags))
             .await
@@ -521,21 +735,25 @@ impl Database {
     }
 
     /// Clear all records from the database
+    /// The deleted records are kept in the undo journal
     pub async fn clear_all_records(&self) -> Result<usize, AppError> {
-        let query = "DELETE records RETURN BEFORE";
-
-        let mut result = self
-            .db
-            .query(query)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to delete all records: {}", e)))?;
+        let statements = format!(
+            "LET $deleted = (DELETE records RETURN BEFORE);
+             {}
+             RETURN array::len($deleted);",
+            undo::journal_statement(
+                UndoKind::ClearRecords,
+                "'All records'",
+                "array::len($deleted)",
+                "{ records: $deleted }",
+            )
+        );
 
-        let deleted: Vec<StagedRecord> = result
-            .take(0)
-            .map_err(|e| AppError::Database(format!("Failed to extract deleted records: {}", e)))?;
+        let mut result = self.transaction(&statements, serde_json::json!({})).await?;
+        let deleted: usize = undo::returned(&mut result).unwrap_or(0);
 
-        tracing::info!("Cleared {} records from database", deleted.len());
-        Ok(deleted.len())
+        tracing::info!("Cleared {} records from database", deleted);
+        Ok(deleted)
     }
 
     /// Get database statistics
@@ -545,7 +763,6 @@ impl Database {
         // Get count by record type
         let query = "SELECT record_type, count() FROM records GROUP BY record_type";
         let mut result = self
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to get stats: {}", e)))?;
@@ -566,7 +783,6 @@ impl Database {
         // Get count by source
         let query = "SELECT source, count() FROM records GROUP BY source";
         let mut result = self
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to get stats: {}", e)))?;
@@ -584,15 +800,102 @@ impl Database {
             .map(|sc| (sc.source, sc.count))
             .collect();
 
-        // Estimate database size (rough calculation based on record count)
-        // Each record is approximately 500 bytes on average
-        let size_bytes = (total as u64) * 500;
+        // Per type/source breakdown with age range and recent growth
+        let since = Utc::now() - chrono::Duration::days(7);
+        let mut result = self
+            .query(
+                "SELECT record_type, source, count() AS count,
+                     time::min(<datetime> timestamp) AS oldest,
+                     time::max(<datetime> timestamp) AS newest,
+                     count(timestamp >= $since) AS added_last_week
+                 FROM records GROUP BY record_type, source;
+                 SELECT time::format(<datetime> timestamp, '%Y-%m-%d') AS date, count() AS count
+                 FROM records WHERE timestamp >= $since GROUP BY date ORDER BY date;",
+            )
+            .bind(("since", since))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get stats: {}", e)))?;
+
+        let mut groups: Vec<GroupStats> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to get group stats: {}", e)))?;
+        groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+        let daily_growth: Vec<DailyCount> = result
+            .take(1)
+            .map_err(|e| AppError::Database(format!("Failed to get growth stats: {}", e)))?;
+
+        let tables = self.table_stats().await?;
+        // Serialized size is a rough proxy for the on-disk size
+        let size_bytes = tables.iter().map(|t| t.size_bytes).sum();
 
         Ok(DatabaseStats {
             total_records: total,
             size_bytes,
             by_type,
             by_source,
+            oldest_record: groups.iter().filter_map(|g| g.oldest).min(),
+            newest_record: groups.iter().filter_map(|g| g.newest).max(),
+            added_last_week: groups.iter().map(|g| g.added_last_week).sum(),
+            groups,
+            daily_growth,
+            tables,
+        })
+    }
+
+    /// Row count and approximate (serialized) size of every table
+    pub async fn table_stats(&self) -> Result<Vec<TableStats>, AppError> {
+        let mut tables = Vec::new();
+        for table in self.list_tables().await? {
+            tables.push(self.table_size(&table).await?);
+        }
+
+        tables.sort_by_key(|t| std::cmp::Reverse(t.size_bytes));
+        Ok(tables)
+    }
+
+    /// Names of all tables in the database
+    pub async fn list_tables(&self) -> Result<Vec<String>, AppError> {
+        let mut result = self
+            .query("INFO FOR DB")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to list tables: {}", e)))?;
+        let info: Option<serde_json::Value> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to list tables: {}", e)))?;
+
+        Ok(info
+            .as_ref()
+            .and_then(|info| info.get("tables"))
+            .and_then(|tables| tables.as_object())
+            .map(|tables| tables.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    /// Row count and approximate (serialized) size of one table
+    pub async fn table_size(&self, table: &str) -> Result<TableStats, AppError> {
+        let mut result = self
+            .query(
+                "SELECT count() AS rows, math::sum(string::len(<string> $this)) AS size_bytes
+                 FROM type::table($table) GROUP ALL",
+            )
+            .bind(("table", table.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get table stats: {}", e)))?;
+
+        #[derive(Deserialize)]
+        struct Row {
+            rows: usize,
+            size_bytes: u64,
+        }
+
+        let row: Option<Row> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to get table stats: {}", e)))?;
+        let (rows, size_bytes) = row.map(|r| (r.rows, r.size_bytes)).unwrap_or((0, 0));
+        Ok(TableStats {
+            table: table.to_string(),
+            rows,
+            size_bytes,
         })
     }
 
@@ -613,7 +916,6 @@ impl Database {
 
         let (query, mut result) = if let Some(src) = &source_owned {
             let res = self
-                .db
                 .query(
                     "DELETE records WHERE timestamp < $cutoff AND source = $source RETURN BEFORE",
                 )
@@ -624,7 +926,6 @@ impl Database {
             ("with source filter", res)
         } else {
             let res = self
-                .db
                 .query("DELETE records WHERE timestamp < $cutoff RETURN BEFORE")
                 .bind(("cutoff", cutoff))
                 .await
@@ -646,6 +947,282 @@ impl Database {
         Ok(deleted.len())
     }
 
+    /// Count the rows of a table (0 if it doesn't exist yet)
+    pub async fn count_table(&self, table: &str) -> Result<usize, AppError> {
+        let mut result = self
+            .query("SELECT count() AS total FROM type::table($table) GROUP ALL")
+            .bind(("table", table.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to count {}: {}", table, e)))?;
+
+        let total: Option<usize> = result
+            .take((0, "total"))
+            .map_err(|e| AppError::Database(format!("Failed to count {}: {}", table, e)))?;
+        Ok(total.unwrap_or(0))
+    }
+
+    /// Read one chunk of a table in stable (id) order, for streaming exports
+    pub async fn export_table_chunk<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<T>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM type::table($table) ORDER BY id LIMIT $limit START $start")
+            .bind(("table", table.to_string()))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export {}: {}", table, e)))?;
+
+        result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract {}: {}", table, e)))
+    }
+
+    /// Start a query, like `self.db.query`
+    /// Inside `as_actor` the audit actor is bound as `$actor` for the audit
+    /// table events, so writes should go through this rather than `self.db`.
+    pub fn query(&self, query: impl IntoQuery) -> Query<'_, Engine> {
+        let query = self.db.query(query);
+        match audit::current_actor() {
+            Some(actor) => query.bind((audit::ACTOR_PARAM, actor)),
+            None => query,
+        }
+    }
+
+    /// Create a row with a generated ID through `query`
+    pub async fn create_row<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        content: impl Serialize + 'static,
+    ) -> surrealdb::Result<Option<T>> {
+        let mut response = self
+            .query("CREATE type::table($table) CONTENT $content")
+            .bind(("table", table.to_string()))
+            .bind(("content", content))
+            .await?;
+        response.take(0)
+    }
+
+    /// Create a row with the given ID through `query`
+    pub async fn create_row_with_id<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        key: &str,
+        content: impl Serialize + 'static,
+    ) -> surrealdb::Result<Option<T>> {
+        self.write_row("CREATE", table, key, "CONTENT", content)
+            .await
+    }
+
+    /// Replace a row's content through `query`; None if the row doesn't exist
+    pub async fn update_row<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        key: &str,
+        content: impl Serialize + 'static,
+    ) -> surrealdb::Result<Option<T>> {
+        self.write_row("UPDATE", table, key, "CONTENT", content)
+            .await
+    }
+
+    /// Merge fields into a row through `query`; None if the row doesn't exist
+    pub async fn merge_row<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        key: &str,
+        data: impl Serialize + 'static,
+    ) -> surrealdb::Result<Option<T>> {
+        self.write_row("UPDATE", table, key, "MERGE", data).await
+    }
+
+    /// Create or replace a row through `query`
+    pub async fn upsert_row<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        key: &str,
+        content: impl Serialize + 'static,
+    ) -> surrealdb::Result<Option<T>> {
+        self.write_row("UPSERT", table, key, "CONTENT", content)
+            .await
+    }
+
+    /// Delete a row through `query`, returning it as it was
+    pub async fn delete_row<T: serde::de::DeserializeOwned>(
+        &self,
+        table: &str,
+        key: &str,
+    ) -> surrealdb::Result<Option<T>> {
+        let mut response = self
+            .query("DELETE type::thing($table, $key) RETURN BEFORE")
+            .bind(("table", table.to_string()))
+            .bind(("key", key.to_string()))
+            .await?;
+        response.take(0)
+    }
+
+    async fn write_row<T: serde::de::DeserializeOwned>(
+        &self,
+        statement: &str,
+        table: &str,
+        key: &str,
+        clause: &str,
+        data: impl Serialize + 'static,
+    ) -> surrealdb::Result<Option<T>> {
+        let mut response = self
+            .query(format!(
+                "{} type::thing($table, $key) {} $data",
+                statement, clause
+            ))
+            .bind(("table", table.to_string()))
+            .bind(("key", key.to_string()))
+            .bind(("data", data))
+            .await?;
+        response.take(0)
+    }
+
+    /// Run several statements atomically (BEGIN/COMMIT around the batch)
+    /// Either every statement is applied or, if one fails, none are and that
+    /// statement's error is returned. `bindings` is anything `Query::bind`
+    /// accepts, e.g. a `("name", value)` tuple or a JSON object of parameters.
+    pub async fn transaction(
+        &self,
+        statements: &str,
+        bindings: impl Serialize + 'static,
+    ) -> Result<surrealdb::Response, AppError> {
+        let mut response = self
+            .query(format!(
+                "BEGIN TRANSACTION;\n{}\nCOMMIT TRANSACTION;",
+                statements
+            ))
+            .bind(bindings)
+            .await
+            .map_err(|e| AppError::Database(format!("Transaction failed: {}", e)))?;
+
+        let mut errors: Vec<(usize, String)> = response
+            .take_errors()
+            .into_iter()
+            .map(|(index, e)| (index, e.to_string()))
+            .collect();
+        if errors.is_empty() {
+            return Ok(response);
+        }
+
+        // Every other statement reports "not executed due to a failed
+        // transaction"; surface the one that actually failed
+        errors.sort();
+        let (_, error) = errors
+            .iter()
+            .find(|(_, e)| !e.contains("failed transaction"))
+            .unwrap_or(&errors[0]);
+        Err(AppError::Database(format!("Transaction failed: {}", error)))
+    }
+
+    /// Write a row from its exported JSON form, keeping its original ID when present
+    /// If a row with that ID already exists it is replaced when `overwrite` is
+    /// set and left untouched otherwise.
+    pub async fn import_json_row(
+        &self,
+        table: &str,
+        row: &serde_json::Value,
+        overwrite: bool,
+    ) -> Result<RowImport, AppError> {
+        let mut content = row.clone();
+        let key = content
+            .as_object_mut()
+            .and_then(|object| object.remove("id"))
+            .and_then(|id| id.as_str().map(|id| json_row_key(table, id)));
+
+        let statement = match &key {
+            Some(_) => {
+                "LET $existed = record::exists(type::thing($table, $key));
+                 IF $existed AND !$overwrite { RETURN 'skipped'; };
+                 UPSERT type::thing($table, $key) CONTENT $content RETURN NONE;
+                 RETURN IF $existed { 'replaced' } ELSE { 'created' };"
+            }
+            None => "CREATE type::table($table) CONTENT $content RETURN NONE; RETURN 'created';",
+        };
+
+        let mut result = self
+            .transaction(
+                statement,
+                serde_json::json!({
+                    "table": table,
+                    "key": key,
+                    "content": content,
+                    "overwrite": overwrite,
+                }),
+            )
+            .await?;
+
+        // The first RETURN that ran ends the transaction's output
+        let outcome = (0..result.num_statements())
+            .filter_map(|index| result.take::<Option<String>>(index).ok().flatten())
+            .next();
+        match outcome.as_deref() {
+            Some("created") => Ok(RowImport::Created),
+            Some("replaced") => Ok(RowImport::Replaced),
+            Some("skipped") => Ok(RowImport::Skipped),
+            _ => Err(AppError::Database(format!(
+                "Failed to import {} row: unexpected result {:?}",
+                table, outcome
+            ))),
+        }
+    }
+
+    /// Whether the row with the ID of an exported JSON row already exists
+    pub async fn json_row_exists(
+        &self,
+        table: &str,
+        row: &serde_json::Value,
+    ) -> Result<bool, AppError> {
+        let Some(key) = row
+            .get("id")
+            .and_then(|id| id.as_str())
+            .map(|id| json_row_key(table, id))
+        else {
+            return Ok(false);
+        };
+
+        let mut result = self
+            .query("RETURN record::exists(type::thing($table, $key))")
+            .bind(("table", table.to_string()))
+            .bind(("key", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to check {} row: {}", table, e)))?;
+        let exists: Option<bool> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to check {} row: {}", table, e)))?;
+        Ok(exists.unwrap_or(false))
+    }
+
+    /// Read one chunk of a table as plain JSON (record IDs become "table:key" strings)
+    pub async fn export_table_chunk_json(
+        &self,
+        table: &str,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<serde_json::Value>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM type::table($table) ORDER BY id LIMIT $limit START $start")
+            .bind(("table", table.to_string()))
+            .bind(("limit", limit))
+            .bind(("start", start))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export {}: {}", table, e)))?;
+
+        // serde_json can't represent record IDs directly, so convert via SurrealDB's own JSON mapping
+        let rows: surrealdb::Value = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract {}: {}", table, e)))?;
+        match rows.into_inner().into_json() {
+            serde_json::Value::Array(rows) => Ok(rows),
+            _ => Ok(Vec::new()),
+        }
+    }
+
     /// Export all data from the database to JSON
     /// Returns a JSON object containing all tables and their data
     pub async fn export_all_data(&self) -> Result<serde_json::Value, AppError> {
@@ -656,7 +1233,6 @@ impl Database {
         // Export records
         let records_query = "SELECT * FROM records ORDER BY timestamp DESC";
         let mut records_result = self
-            .db
             .query(records_query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to export records: {}", e)))?;
@@ -668,93 +1244,161 @@ impl Database {
         // Export pages (if table exists)
         let pages_query = "SELECT * FROM pages";
         let mut pages_result = self
-            .db
             .query(pages_query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to export pages: {}", e)))?;
 
-        let pages: Vec<serde_json::Value> = pages_result.take(0).unwrap_or_default();
+        let pages = json_rows(pages_result.take(0));
 
         // Export data_sources (if table exists)
         let data_sources_query = "SELECT * FROM data_sources";
         let mut data_sources_result = self
-            .db
             .query(data_sources_query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to export data_sources: {}", e)))?;
 
-        let data_sources: Vec<serde_json::Value> = data_sources_result.take(0).unwrap_or_default();
+        let data_sources = json_rows(data_sources_result.take(0));
 
         // Export settings (if table exists)
         let settings_query = "SELECT * FROM settings";
         let mut settings_result = self
-            .db
             .query(settings_query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to export settings: {}", e)))?;
 
-        let settings: Vec<serde_json::Value> = settings_result.take(0).unwrap_or_default();
+        let settings = json_rows(settings_result.take(0));
 
         // Export plugin_data (if table exists)
         let plugin_data_query = "SELECT * FROM plugin_data";
         let mut plugin_data_result = self
-            .db
             .query(plugin_data_query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to export plugin_data: {}", e)))?;
 
-        let plugin_data: Vec<serde_json::Value> = plugin_data_result.take(0).unwrap_or_default();
+        let plugin_data = json_rows(plugin_data_result.take(0));
 
         // Export tickets (if table exists)
         let tickets_query = "SELECT * FROM tickets";
         let mut tickets_result = self
-            .db
             .query(tickets_query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to export tickets: {}", e)))?;
 
-        let tickets: Vec<serde_json::Value> = tickets_result.take(0).unwrap_or_default();
+        let tickets = json_rows(tickets_result.take(0));
 
-        let export = json!({
-            "version": "1.0",
-            "exported_at": chrono::Utc::now().to_rfc3339(),
-            "data": {
-                "records": records,
-                "pages": pages,
-                "data_sources": data_sources,
-                "settings": settings,
-                "plugin_data": plugin_data,
-                "tickets": tickets,
-                "dashboards": [], // Placeholder - will be filled by main.rs
-            }
-        });
+        // Export attachment metadata (the files stay in the attachments dir)
+        let mut attachments_result = self
+            .query("SELECT * FROM attachments")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export attachments: {}", e)))?;
 
-        tracing::info!("Export complete: {} records, {} pages, {} data_sources, {} settings, {} plugin_data, {} tickets",
-            records.len(), pages.len(), data_sources.len(), settings.len(), plugin_data.len(), tickets.len());
+        let attachments = json_rows(attachments_result.take(0));
 
-        Ok(export)
-    }
+        let mut sprints_result = self
+            .query("SELECT * FROM sprints")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export sprints: {}", e)))?;
 
-    /// Import data from JSON export
-    /// Accepts a JSON object with the same structure as export_all_data()
-    /// merge_strategy: "replace" (clear existing), "merge" (keep both), "skip" (keep existing if conflict)
-    pub async fn import_data(
-        &self,
+        let sprints = json_rows(sprints_result.take(0));
+
+        let mut boards_result = self
+            .query("SELECT * FROM boards")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export boards: {}", e)))?;
+
+        let boards = json_rows(boards_result.take(0));
+
+        let mut ticket_sequences_result = self
+            .query("SELECT * FROM ticket_sequences")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export ticket_sequences: {}", e)))?;
+
+        let ticket_sequences = json_rows(ticket_sequences_result.take(0));
+
+        let mut ticket_events_result = self
+            .query("SELECT * FROM ticket_events")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export ticket_events: {}", e)))?;
+
+        let ticket_events = json_rows(ticket_events_result.take(0));
+
+        let mut dashboards_result = self
+            .query("SELECT * FROM dashboards")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to export dashboards: {}", e)))?;
+
+        let dashboards = json_rows(dashboards_result.take(0));
+
+        let mut dashboard_versions_result = self
+            .query("SELECT * FROM dashboard_versions")
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to export dashboard_versions: {}", e))
+            })?;
+
+        let dashboard_versions = json_rows(dashboard_versions_result.take(0));
+
+        let mut dashboard_snapshots_result = self
+            .query("SELECT * FROM dashboard_snapshots")
+            .await
+            .map_err(|e| {
+                AppError::Database(format!("Failed to export dashboard_snapshots: {}", e))
+            })?;
+
+        let dashboard_snapshots = json_rows(dashboard_snapshots_result.take(0));
+
+        let export = json!({
+            "version": "1.0",
+            "exported_at": chrono::Utc::now().to_rfc3339(),
+            "data": {
+                "records": records,
+                "pages": pages,
+                "data_sources": data_sources,
+                "settings": settings,
+                "plugin_data": plugin_data,
+                "tickets": tickets,
+                "attachments": attachments,
+                "sprints": sprints,
+                "boards": boards,
+                "ticket_sequences": ticket_sequences,
+                "ticket_events": ticket_events,
+                "dashboards": dashboards,
+                "dashboard_versions": dashboard_versions,
+                "dashboard_snapshots": dashboard_snapshots,
+            }
+        });
+
+        tracing::info!("Export complete: {} records, {} pages, {} data_sources, {} settings, {} plugin_data, {} tickets, {} attachments",
+            records.len(), pages.len(), data_sources.len(), settings.len(), plugin_data.len(), tickets.len(), attachments.len());
+
+        Ok(export)
+    }
+
+    /// Import data from JSON export
+    /// Accepts a JSON object with the same structure as export_all_data()
+    /// merge_strategy:
+    /// - "replace": clear existing data, then import everything
+    /// - "merge": imported rows overwrite existing rows with the same ID
+    /// - "skip": existing rows with the same ID are kept
+    ///
+    /// Conflicts are detected by ID: exported rows keep theirs and records
+    /// without one get their deterministic ID (as on fetch).
+    pub async fn import_data(
+        &self,
         import_data: serde_json::Value,
         merge_strategy: &str,
     ) -> Result<ImportStats, AppError> {
         tracing::info!("Starting database import with strategy: {}", merge_strategy);
 
-        let mut stats = ImportStats {
-            records_imported: 0,
-            pages_imported: 0,
-            data_sources_imported: 0,
-            settings_imported: 0,
-            plugin_data_imported: 0,
-            tickets_imported: 0,
-            dashboards_imported: 0,
-            errors: Vec::new(),
-        };
+        if !matches!(merge_strategy, "replace" | "merge" | "skip") {
+            return Err(AppError::Validation(format!(
+                "Unknown merge strategy '{}' (expected replace, merge or skip)",
+                merge_strategy
+            )));
+        }
+        let overwrite = merge_strategy != "skip";
+
+        let mut stats = ImportStats::default();
 
         // Validate import structure
         let data = import_data.get("data").ok_or_else(|| {
@@ -765,86 +1409,61 @@ impl Database {
         if merge_strategy == "replace" {
             tracing::info!("Clearing existing data (replace mode)");
             let _ = self.clear_all_records().await;
-            let _ = self.db.query("DELETE pages").await;
-            let _ = self.db.query("DELETE data_sources").await;
-            let _ = self.db.query("DELETE settings").await;
-            let _ = self.db.query("DELETE plugin_data").await;
-            let _ = self.db.query("DELETE tickets").await;
+            let _ = self.query("DELETE pages").await;
+            let _ = self.query("DELETE data_sources").await;
+            let _ = self.query("DELETE settings").await;
+            let _ = self.query("DELETE plugin_data").await;
+            let _ = self.query("DELETE tickets").await;
+            let _ = self.query("DELETE attachments").await;
+            let _ = self.query("DELETE sprints").await;
+            let _ = self.query("DELETE boards").await;
+            let _ = self.query("DELETE ticket_sequences").await;
+            let _ = self.query("DELETE ticket_events").await;
+            let _ = self.query("DELETE dashboards").await;
+            let _ = self.query("DELETE dashboard_versions").await;
+            let _ = self.query("DELETE dashboard_snapshots").await;
         }
 
         // Import records
         if let Some(records) = data.get("records").and_then(|v| v.as_array()) {
             for record in records {
-                match serde_json::from_value::<StagedRecord>(record.clone()) {
-                    Ok(mut staged_record) => {
-                        // Clear ID to let database assign new one (or use upsert logic)
-                        staged_record.id = None;
-
-                        match self.upsert_record(staged_record).await {
-                            Ok(_) => stats.records_imported += 1,
-                            Err(e) => stats.errors.push(format!("Failed to import record: {}", e)),
-                        }
-                    }
-                    Err(e) => stats.errors.push(format!("Failed to parse record: {}", e)),
-                }
-            }
-        }
-
-        // Import pages
-        if let Some(pages) = data.get("pages").and_then(|v| v.as_array()) {
-            for page in pages {
-                match self.db.create("pages").content(page.clone()).await {
-                    Ok::<Option<serde_json::Value>, _>(_) => stats.pages_imported += 1,
-                    Err(e) => stats.errors.push(format!("Failed to import page: {}", e)),
-                }
-            }
-        }
-
-        // Import data_sources
-        if let Some(data_sources) = data.get("data_sources").and_then(|v| v.as_array()) {
-            for ds in data_sources {
-                match self.db.create("data_sources").content(ds.clone()).await {
-                    Ok::<Option<serde_json::Value>, _>(_) => stats.data_sources_imported += 1,
-                    Err(e) => stats
-                        .errors
-                        .push(format!("Failed to import data_source: {}", e)),
-                }
-            }
-        }
-
-        // Import settings
-        if let Some(settings) = data.get("settings").and_then(|v| v.as_array()) {
-            for setting in settings {
-                match self.db.create("settings").content(setting.clone()).await {
-                    Ok::<Option<serde_json::Value>, _>(_) => stats.settings_imported += 1,
-                    Err(e) => stats
-                        .errors
-                        .push(format!("Failed to import setting: {}", e)),
-                }
+                let result = match serde_json::from_value::<StagedRecord>(record.clone()) {
+                    Ok(record) => self.import_record(record, overwrite).await,
+                    Err(e) => Err(AppError::Validation(format!(
+                        "Failed to parse record: {}",
+                        e
+                    ))),
+                };
+                stats.add("records", result);
             }
         }
 
-        // Import plugin_data
-        if let Some(plugin_data) = data.get("plugin_data").and_then(|v| v.as_array()) {
-            for pd in plugin_data {
-                match self.db.create("plugin_data").content(pd.clone()).await {
-                    Ok::<Option<serde_json::Value>, _>(_) => stats.plugin_data_imported += 1,
-                    Err(e) => stats
-                        .errors
-                        .push(format!("Failed to import plugin_data: {}", e)),
+        // Import the other tables as-is
+        for table in [
+            "pages",
+            "data_sources",
+            "settings",
+            "plugin_data",
+            // Before the tickets, so they don't record a new creation
+            "ticket_events",
+            "tickets",
+            "attachments",
+            "sprints",
+            "boards",
+            "ticket_sequences",
+            "dashboards",
+            "dashboard_versions",
+            "dashboard_snapshots",
+        ] {
+            if let Some(rows) = data.get(table).and_then(|v| v.as_array()) {
+                for row in rows {
+                    let result = self.import_json_row(table, row, overwrite).await;
+                    stats.add(table, result);
                 }
             }
         }
 
-        // Import tickets
-        if let Some(tickets) = data.get("tickets").and_then(|v| v.as_array()) {
-            for ticket in tickets {
-                match self.db.create("tickets").content(ticket.clone()).await {
-                    Ok::<Option<serde_json::Value>, _>(_) => stats.tickets_imported += 1,
-                    Err(e) => stats.errors.push(format!("Failed to import ticket: {}", e)),
-                }
-            }
-        }
+        self.relink_imported_rows(&stats).await?;
 
         tracing::info!("Import complete: {} records, {} pages, {} data_sources, {} settings, {} plugin_data, {} tickets, {} dashboards, {} errors",
             stats.records_imported, stats.pages_imported, stats.data_sources_imported,
@@ -853,9 +1472,74 @@ impl Database {
 
         Ok(stats)
     }
+
+    /// Turn the "table:id" strings of imported link fields back into links
+    pub(crate) async fn relink_imported_rows(&self, stats: &ImportStats) -> Result<(), AppError> {
+        // Exported history rows point at tickets by "table:id" string too
+        if stats
+            .tables
+            .iter()
+            .any(|t| t.table == "ticket_events" && t.imported > 0)
+        {
+            self.query(
+                "UPDATE ticket_events SET ticket = type::record(ticket)
+                     WHERE type::is::string(ticket)",
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to link ticket history: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to link ticket history: {}", e)))?;
+        }
+
+        // Exported attachment owners are "table:id" strings; make them links again
+        if stats.attachments_imported > 0 {
+            self.query(
+                "UPDATE attachments SET owner = type::record(owner)
+                     WHERE type::is::string(owner)",
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to link attachments: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to link attachments: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Import one staged record, keeping (or deriving) its ID for conflict detection
+    async fn import_record(
+        &self,
+        mut record: StagedRecord,
+        overwrite: bool,
+    ) -> Result<RowImport, AppError> {
+        let key = match record.id.take() {
+            Some(id) => Some(id.id.to_raw()),
+            None => deterministic_record_id(&record, None),
+        };
+        let Some(key) = key else {
+            // Nothing to conflict with
+            self.upsert_record(record).await?;
+            return Ok(RowImport::Created);
+        };
+
+        let mut row = serde_json::to_value(&record)?;
+        if let Some(object) = row.as_object_mut() {
+            object.insert("id".to_string(), serde_json::Value::String(key));
+        }
+        self.import_json_row("records", &row, overwrite).await
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// What happened to a single imported row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowImport {
+    Created,
+    /// A row with the same ID existed and was overwritten
+    Replaced,
+    /// A row with the same ID existed and was kept
+    Skipped,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ImportStats {
     pub records_imported: usize,
     pub pages_imported: usize,
@@ -863,16 +1547,238 @@ pub struct ImportStats {
     pub settings_imported: usize,
     pub plugin_data_imported: usize,
     pub tickets_imported: usize,
+    pub attachments_imported: usize,
     pub dashboards_imported: usize,
+    /// Per-table breakdown, in import order
+    pub tables: Vec<TableImportStats>,
     pub errors: Vec<String>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TableImportStats {
+    pub table: String,
+    /// Rows written (new or overwriting an existing row)
+    pub imported: usize,
+    /// Rows not written because a row with the same ID existed
+    pub skipped: usize,
+    /// Rows whose ID already existed (overwritten or skipped)
+    pub conflicts: usize,
+    pub errors: usize,
+}
+
+impl ImportStats {
+    /// Count the outcome of importing one row into `table`
+    pub fn add(&mut self, table: &str, result: Result<RowImport, AppError>) {
+        let index = match self.tables.iter().position(|t| t.table == table) {
+            Some(index) => index,
+            None => {
+                self.tables.push(TableImportStats {
+                    table: table.to_string(),
+                    ..Default::default()
+                });
+                self.tables.len() - 1
+            }
+        };
+        let entry = &mut self.tables[index];
+
+        match result {
+            Ok(RowImport::Created) => entry.imported += 1,
+            Ok(RowImport::Replaced) => {
+                entry.imported += 1;
+                entry.conflicts += 1;
+            }
+            Ok(RowImport::Skipped) => {
+                entry.skipped += 1;
+                entry.conflicts += 1;
+            }
+            Err(e) => {
+                entry.errors += 1;
+                self.errors
+                    .push(format!("Failed to import {} row: {}", table, e));
+                return;
+            }
+        }
+
+        let imported = entry.imported;
+        match table {
+            "records" => self.records_imported = imported,
+            "pages" => self.pages_imported = imported,
+            "data_sources" => self.data_sources_imported = imported,
+            "settings" => self.settings_imported = imported,
+            "plugin_data" => self.plugin_data_imported = imported,
+            "tickets" => self.tickets_imported = imported,
+            "attachments" => self.attachments_imported = imported,
+            "dashboards" => self.dashboards_imported = imported,
+            _ => {}
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DatabaseStats {
     pub total_records: usize,
     pub size_bytes: u64,
     pub by_type: std::collections::HashMap<String, usize>,
     pub by_source: std::collections::HashMap<String, usize>,
+    pub oldest_record: Option<DateTime<Utc>>,
+    pub newest_record: Option<DateTime<Utc>>,
+    /// Records with a timestamp in the last 7 days
+    pub added_last_week: usize,
+    /// Counts per (record_type, source), largest first
+    pub groups: Vec<GroupStats>,
+    /// Records per day over the last 7 days
+    pub daily_growth: Vec<DailyCount>,
+    /// All tables, largest first
+    pub tables: Vec<TableStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupStats {
+    pub record_type: String,
+    pub source: String,
+    pub count: usize,
+    pub oldest: Option<DateTime<Utc>>,
+    pub newest: Option<DateTime<Utc>>,
+    pub added_last_week: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyCount {
+    /// YYYY-MM-DD
+    pub date: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableStats {
+    pub table: String,
+    pub rows: usize,
+    /// Approximate size of the serialized rows
+    pub size_bytes: u64,
+}
+
+/// Apply an RFC 7386 JSON merge patch: objects merge recursively, `null`
+/// removes a key and any other value replaces the target
+pub fn json_merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::json!({});
+    }
+    let target = target.as_object_mut().expect("just ensured an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            json_merge_patch(
+                target.entry(key.clone()).or_insert(serde_json::Value::Null),
+                value,
+            );
+        }
+    }
+}
+
+/// Record key from an exported "table:key" ID string
+fn json_row_key(table: &str, id: &str) -> String {
+    id.strip_prefix(table)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .unwrap_or(id)
+        .trim_start_matches('⟨')
+        .trim_end_matches('⟩')
+        .to_string()
+}
+
+/// Rows of a query result as plain JSON (empty if the query failed)
+fn json_rows(rows: Result<surrealdb::Value, surrealdb::Error>) -> Vec<serde_json::Value> {
+    match rows.map(|rows| rows.into_inner().into_json()) {
+        Ok(serde_json::Value::Array(rows)) => rows,
+        _ => Vec::new(),
+    }
+}
+
+/// One `RecordsUpserted` event per source of the records
+fn upserted_events(records: &[StagedRecord]) -> Vec<AppEvent> {
+    let mut by_source: std::collections::BTreeMap<&str, (Vec<String>, usize)> =
+        std::collections::BTreeMap::new();
+    for record in records {
+        let (record_types, count) = by_source.entry(&record.source).or_default();
+        if !record_types.contains(&record.record_type) {
+            record_types.push(record.record_type.clone());
+        }
+        *count += 1;
+    }
+
+    by_source
+        .into_iter()
+        .map(
+            |(source, (record_types, count))| AppEvent::RecordsUpserted {
+                source: source.to_string(),
+                record_types,
+                count,
+            },
+        )
+        .collect()
+}
+
+fn announce_upserted(records: &[StagedRecord]) {
+    upserted_events(records)
+        .into_iter()
+        .for_each(events::publish);
+}
+
+/// Build a deterministic record ID: source_type_key
+/// e.g., "qcc-gitlab-project_gitlab_pipeline_12345"
+///
+/// Numeric `data.id` values keep their plain form so existing records stay
+/// stable; any other key is hashed so arbitrary strings (URLs, UUIDs,
+/// composite keys) produce a valid and bounded record ID.
+fn deterministic_record_id(record: &StagedRecord, unique_key: Option<&str>) -> Option<String> {
+    let key = match unique_key.map(str::trim).filter(|k| !k.is_empty()) {
+        Some(expression) => {
+            let parts = expression
+                .split(',')
+                .map(|path| {
+                    let path = path.trim();
+                    let path = path.strip_prefix("data.").unwrap_or(path);
+                    match path
+                        .split('.')
+                        .try_fold(&record.data, |current, key| current.get(key))?
+                    {
+                        serde_json::Value::Null => None,
+                        serde_json::Value::String(s) => Some(s.clone()),
+                        other => Some(other.to_string()),
+                    }
+                })
+                .collect::<Option<Vec<String>>>()?;
+            hash_key(&parts.join("\u{1f}"))
+        }
+        None => {
+            let id = record.data.get("id")?;
+            match id
+                .as_u64()
+                .or_else(|| id.as_str().and_then(|s| s.parse::<u64>().ok()))
+            {
+                Some(ext_id) => ext_id.to_string(),
+                None => hash_key(id.as_str().filter(|s| !s.is_empty())?),
+            }
+        }
+    };
+
+    Some(format!(
+        "{}_{}_{}",
+        record.source.replace("-", "_"),
+        record.record_type.replace("-", "_"),
+        key
+    ))
+}
+
+/// Short stable hash of a unique key
+fn hash_key(key: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(key.as_bytes());
+    hex::encode(&digest[..8])
 }
 
 #[cfg(test)]
@@ -914,4 +1820,613 @@ mod tests {
         assert!(fetched.is_some());
         assert_eq!(fetched.unwrap().record_type, "test_type");
     }
+
+    #[tokio::test]
+    async fn test_get_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        for (age_days, source) in [(0, "a"), (1, "a"), (20, "b")] {
+            let mut record = StagedRecord::new(
+                "rest_api".to_string(),
+                source.to_string(),
+                serde_json::json!({ "age": age_days }),
+            );
+            record.timestamp = Utc::now() - chrono::Duration::days(age_days);
+            db.upsert_record(record).await.unwrap();
+        }
+
+        let stats = db.get_stats().await.unwrap();
+        assert_eq!(stats.total_records, 3);
+        assert_eq!(stats.added_last_week, 2);
+        assert_eq!(stats.groups[0].source, "a");
+        assert_eq!(stats.groups[0].count, 2);
+        assert_eq!(stats.daily_growth.iter().map(|d| d.count).sum::<usize>(), 2);
+        assert!(stats.oldest_record.unwrap() < stats.newest_record.unwrap());
+
+        let records_table = stats.tables.iter().find(|t| t.table == "records").unwrap();
+        assert_eq!(records_table.rows, 3);
+        assert!(records_table.size_bytes > 0);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_records_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let records: Vec<StagedRecord> = (0..600)
+            .map(|i| {
+                StagedRecord::new(
+                    "rest_api".to_string(),
+                    "batch".to_string(),
+                    serde_json::json!({ "id": i }),
+                )
+            })
+            .collect();
+
+        let mut events = events::subscribe();
+        assert_eq!(
+            db.upsert_records_batch(records.clone(), None)
+                .await
+                .unwrap(),
+            600
+        );
+        let upserted = AppEvent::RecordsUpserted {
+            source: "rest_api".to_string(),
+            record_types: vec!["batch".to_string()],
+            count: 600,
+        };
+        assert!(std::iter::from_fn(|| events.try_recv().ok()).any(|event| event == upserted));
+
+        // A second run updates the same records instead of duplicating them
+        db.upsert_records_batch(records, None).await.unwrap();
+        assert_eq!(db.count_records().await.unwrap(), 600);
+    }
+
+    #[test]
+    fn test_deterministic_record_id() {
+        let record = StagedRecord::new(
+            "rest_api".to_string(),
+            "my-source".to_string(),
+            serde_json::json!({"id": "42", "url": "https://example.com/a", "project": {"id": 7}}),
+        );
+
+        // Numeric ids keep the plain legacy format
+        assert_eq!(
+            deterministic_record_id(&record, None).as_deref(),
+            Some("my_source_rest_api_42")
+        );
+
+        // Configured keys are hashed and stable
+        let by_url = deterministic_record_id(&record, Some("data.url")).unwrap();
+        assert_eq!(
+            by_url,
+            deterministic_record_id(&record, Some("url")).unwrap()
+        );
+        assert_ne!(
+            by_url,
+            deterministic_record_id(&record, Some("data.url, data.project.id")).unwrap()
+        );
+
+        // Missing key fields fall back to create
+        assert!(deterministic_record_id(&record, Some("data.uuid")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        db.transaction(
+            "CREATE notes:a SET text = $text; CREATE notes:b SET text = $text;",
+            ("text", "hello"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(db.count_table("notes").await.unwrap(), 2);
+
+        // The second statement fails, so the delete before it is undone
+        let error = db
+            .transaction(
+                "DELETE notes:a; CREATE notes:b SET text = 'again';",
+                serde_json::json!({}),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("already exists"));
+        assert_eq!(db.count_table("notes").await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_ticket_cascades() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let new_ticket = |title: &str| {
+            serde_json::from_value(serde_json::json!({ "title": title, "ticket_type": "task" }))
+                .unwrap()
+        };
+
+        let parent = db.create_ticket(new_ticket("Parent")).await.unwrap();
+        let child = db.create_ticket(new_ticket("Child")).await.unwrap();
+        db.query("UPDATE type::record($id) SET parent_id = $parent, linked_tickets = [$parent]")
+            .bind(("id", child.id.clone()))
+            .bind(("parent", parent.id.clone()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        assert_eq!(
+            db.get_tickets(None)
+                .await
+                .unwrap()
+                .iter()
+                .filter(|t| t.parent_id.is_some())
+                .count(),
+            1
+        );
+
+        let source = temp_dir.path().join("notes.txt");
+        std::fs::write(&source, "notes").unwrap();
+        db.add_attachment(
+            &temp_dir.path().join("files"),
+            "tickets",
+            &parent.id,
+            &source,
+        )
+        .await
+        .unwrap();
+
+        db.delete_ticket(&parent.id).await.unwrap();
+
+        let tickets = db.get_tickets(None).await.unwrap();
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].parent_id, None);
+        assert!(tickets[0].linked_tickets.is_empty());
+        assert_eq!(db.count_table("attachments").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_ticket_labels_and_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        for (title, priority, tags) in [
+            ("One", "high", vec!["ui", "bug's"]),
+            ("Two", "low", vec!["ui"]),
+            ("Three", "high", vec![]),
+        ] {
+            db.create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": title, "ticket_type": "task", "priority": priority, "tags": tags
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let labels = db.list_ticket_labels().await.unwrap();
+        assert_eq!((labels[0].tag.as_str(), labels[0].count), ("ui", 2));
+        assert_eq!(labels.len(), 2);
+
+        let filtered = |filters: serde_json::Value| {
+            let db = &db;
+            async move {
+                let mut titles: Vec<String> = db
+                    .get_tickets(Some(serde_json::from_value(filters).unwrap()))
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|t| t.title)
+                    .collect();
+                titles.sort();
+                titles
+            }
+        };
+        assert_eq!(
+            filtered(serde_json::json!({ "priority": "high" })).await,
+            vec!["One", "Three"]
+        );
+        assert_eq!(
+            filtered(serde_json::json!({ "tags": ["ui", "bug's"] })).await,
+            vec!["One"]
+        );
+        assert_eq!(
+            filtered(serde_json::json!({ "priority": "low", "tags": ["ui"] })).await,
+            vec!["Two"]
+        );
+
+        assert_eq!(db.rename_ticket_label("bug's", "ui").await.unwrap(), 1);
+        assert!(db.rename_ticket_label("ui", " ").await.is_err());
+        let labels = db.list_ticket_labels().await.unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_query_tickets() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        for (title, description, priority, due_date) in [
+            ("Login crash", "Crashes on submit", "critical", "2024-03-01"),
+            ("Dark mode", "Theme toggle", "low", "2024-05-01"),
+            ("Export CSV", "Button CRASHES too", "medium", "2024-04-01"),
+            ("Docs", "", "high", "2024-06-01"),
+        ] {
+            db.create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": title, "description": description, "ticket_type": "task",
+                    "priority": priority, "due_date": due_date
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+        let titles = |page: crate::tickets::TicketPage| -> Vec<String> {
+            page.tickets.into_iter().map(|t| t.title).collect()
+        };
+        let query = |value: serde_json::Value| -> crate::tickets::TicketFilters {
+            serde_json::from_value(value).unwrap()
+        };
+
+        let page = db
+            .query_tickets(query(serde_json::json!({
+                "search": "crash", "sort": [{ "field": "title" }]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(titles(page), vec!["Export CSV", "Login crash"]);
+
+        let page = db
+            .query_tickets(query(serde_json::json!({
+                "sort": [{ "field": "priority", "descending": true }],
+                "limit": 2, "offset": 1
+            })))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 4);
+        assert_eq!(titles(page), vec!["Docs", "Export CSV"]);
+
+        let page = db
+            .query_tickets(query(serde_json::json!({
+                "date_range": { "field": "due_date", "from": "2024-04-01", "to": "2024-05-31" },
+                "sort": [{ "field": "due_date" }]
+            })))
+            .await
+            .unwrap();
+        assert_eq!(titles(page), vec!["Export CSV", "Dark mode"]);
+
+        let page = db
+            .query_tickets(query(serde_json::json!({ "search": "x' OR true OR '" })))
+            .await
+            .unwrap();
+        assert_eq!(page.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ticket_people_and_assignees() {
+        use crate::tickets::{TicketPeople, UpdateTicketRequest};
+
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let settings = crate::settings::SettingsService::new(std::sync::Arc::new(
+            tokio::sync::Mutex::new(db.clone()),
+        ));
+
+        let people = TicketPeople::load(&settings).await.unwrap();
+        assert!(people.people.is_empty());
+        assert_eq!(people.author(), "User");
+
+        let saved = TicketPeople {
+            people: vec![" Ada ".to_string(), "Grace".to_string(), "Ada".to_string()],
+            current_user: Some("Linus".to_string()),
+        }
+        .save(&settings)
+        .await
+        .unwrap();
+        assert_eq!(saved.people, vec!["Ada", "Grace", "Linus"]);
+        let people = TicketPeople::load(&settings).await.unwrap();
+        assert_eq!(people.people, saved.people);
+        assert_eq!(people.author(), "Linus");
+
+        let new_ticket = |title: &str, assignee: Option<&str>| {
+            serde_json::from_value(serde_json::json!({
+                "title": title, "ticket_type": "task", "assignee": assignee
+            }))
+            .unwrap()
+        };
+        let ada = db
+            .create_ticket(new_ticket("A", Some("Ada")))
+            .await
+            .unwrap();
+        db.create_ticket(new_ticket("B", None)).await.unwrap();
+        let by_assignee = |assignee: &str| {
+            serde_json::from_value(serde_json::json!({ "assignee": assignee })).unwrap()
+        };
+        assert_eq!(
+            db.get_tickets(Some(by_assignee("Ada")))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            db.get_tickets(Some(by_assignee(""))).await.unwrap().len(),
+            1
+        );
+
+        let unassigned: UpdateTicketRequest =
+            serde_json::from_value(serde_json::json!({ "assignee": "" })).unwrap();
+        let ticket = db.update_ticket(&ada.id, unassigned).await.unwrap();
+        assert_eq!(ticket.assignee, None);
+        assert_eq!(
+            db.get_tickets(Some(by_assignee(""))).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ticket_relations() {
+        use crate::tickets::TicketRelationKind;
+
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let mut ids = Vec::new();
+        for title in ["A", "B", "C"] {
+            let ticket = db
+                .create_ticket(
+                    serde_json::from_value(serde_json::json!({
+                        "title": title, "ticket_type": "task"
+                    }))
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+            ids.push(ticket.id);
+        }
+
+        // A blocks B blocks C; C blocking A would close the loop
+        let a_blocks_b = db
+            .add_ticket_relation(&ids[0], &ids[1], TicketRelationKind::Blocks)
+            .await
+            .unwrap();
+        db.add_ticket_relation(&ids[1], &ids[2], TicketRelationKind::Blocks)
+            .await
+            .unwrap();
+        assert!(db
+            .add_ticket_relation(&ids[2], &ids[0], TicketRelationKind::Blocks)
+            .await
+            .is_err());
+        assert!(db
+            .add_ticket_relation(&ids[0], &ids[0], TicketRelationKind::RelatesTo)
+            .await
+            .is_err());
+
+        // "relates_to" is the same link in either direction
+        let related = db
+            .add_ticket_relation(&ids[0], &ids[2], TicketRelationKind::RelatesTo)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.add_ticket_relation(&ids[2], &ids[0], TicketRelationKind::RelatesTo)
+                .await
+                .unwrap(),
+            related
+        );
+
+        let tickets = db.get_tickets(None).await.unwrap();
+        let relations = |id: &str| {
+            let ticket = tickets.iter().find(|t| t.id == id).unwrap();
+            let mut relations: Vec<(TicketRelationKind, bool, String)> = ticket
+                .relations
+                .iter()
+                .map(|r| (r.kind, r.outgoing, r.ticket_id.clone()))
+                .collect();
+            relations.sort_by_key(|r| r.1);
+            relations
+        };
+        assert_eq!(
+            relations(&ids[1]),
+            vec![
+                (TicketRelationKind::Blocks, false, ids[0].clone()),
+                (TicketRelationKind::Blocks, true, ids[2].clone()),
+            ]
+        );
+        assert_eq!(relations(&ids[2]).len(), 2);
+
+        // Removing a link or a ticket drops its relations
+        db.remove_ticket_relation(&a_blocks_b).await.unwrap();
+        db.add_ticket_relation(&ids[2], &ids[0], TicketRelationKind::Blocks)
+            .await
+            .unwrap();
+        db.delete_ticket(&ids[2]).await.unwrap();
+        let tickets = db.get_tickets(None).await.unwrap();
+        assert!(tickets.iter().all(|t| t.relations.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_archive_tickets() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let mut ids = Vec::new();
+        for title in ["Old done", "Recent done", "Open"] {
+            let ticket = db
+                .create_ticket(
+                    serde_json::from_value(
+                        serde_json::json!({ "title": title, "ticket_type": "task" }),
+                    )
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+            ids.push(ticket.id);
+        }
+        db.move_ticket(&ids[0], "done").await.unwrap();
+        db.move_ticket(&ids[1], "done").await.unwrap();
+        let long_ago = (chrono::Utc::now() - chrono::Duration::days(40)).to_rfc3339();
+        db.query("UPDATE type::record($id) SET completed_at = $at")
+            .bind(("id", ids[0].clone()))
+            .bind(("at", long_ago))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        assert_eq!(db.archive_done_older_than(30).await.unwrap(), 1);
+        assert_eq!(db.archive_done_older_than(30).await.unwrap(), 0);
+        let active = db.get_tickets(None).await.unwrap();
+        assert_eq!(active.len(), 2);
+        assert!(active.iter().all(|t| !t.archived));
+
+        let archived = db.archive_ticket(&ids[2], true).await.unwrap();
+        assert!(archived.archived && archived.archived_at.is_some());
+        let archived = db.get_archived_tickets().await.unwrap();
+        assert_eq!(archived.len(), 2);
+        assert_eq!(db.get_tickets(None).await.unwrap().len(), 1);
+
+        let restored = db.archive_ticket(&ids[2], false).await.unwrap();
+        assert!(!restored.archived && restored.archived_at.is_none());
+        let all = db
+            .get_tickets(Some(
+                serde_json::from_value(serde_json::json!({ "archived": "all" })).unwrap(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_ticket_checklist() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let ticket = db
+            .create_ticket(
+                serde_json::from_value(
+                    serde_json::json!({ "title": "Big", "ticket_type": "task" }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ticket.checklist_progress, None);
+
+        db.add_checklist_item(&ticket.id, "Design").await.unwrap();
+        db.add_checklist_item(&ticket.id, "Build").await.unwrap();
+        let ticket = db.add_checklist_item(&ticket.id, " Ship ").await.unwrap();
+        let texts: Vec<&str> = ticket.checklist.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["Design", "Build", "Ship"]);
+        assert_eq!(ticket.checklist_progress, Some(0));
+        assert!(db.add_checklist_item(&ticket.id, "  ").await.is_err());
+
+        let first = ticket.checklist[0].id.clone();
+        let ticket = db.toggle_checklist_item(&ticket.id, &first).await.unwrap();
+        assert!(ticket.checklist[0].done);
+        assert_eq!(ticket.checklist_progress, Some(33));
+
+        let ticket = db
+            .remove_checklist_item(&ticket.id, &ticket.checklist[2].id)
+            .await
+            .unwrap();
+        assert_eq!(ticket.checklist_progress, Some(50));
+        let ticket = db.toggle_checklist_item(&ticket.id, &first).await.unwrap();
+        assert_eq!(ticket.checklist_progress, Some(0));
+
+        assert!(matches!(
+            db.toggle_checklist_item(&ticket.id, "missing").await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            db.add_checklist_item("tickets:missing", "x").await,
+            Err(AppError::NotFound(_))
+        ));
+        assert_eq!(db.get_tickets(None).await.unwrap()[0].checklist.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_patch_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let mut record = StagedRecord::new(
+            "rest_api".to_string(),
+            "api".to_string(),
+            serde_json::json!({ "id": 1, "status": "open", "owner": { "name": "a", "team": "x" } }),
+        );
+        record.metadata.tags = vec!["keep".to_string()];
+        let record = db.upsert_record(record).await.unwrap();
+        let id = record.id.unwrap().to_string();
+
+        let patched = db
+            .patch_record(
+                &id,
+                &serde_json::json!({
+                    "data": { "status": "closed", "owner": { "team": null }, "note": "done" },
+                    "metadata": { "title": "Patched" }
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            patched.data,
+            serde_json::json!({ "id": 1, "status": "closed", "owner": { "name": "a" }, "note": "done" })
+        );
+        assert_eq!(patched.metadata.title.as_deref(), Some("Patched"));
+        assert_eq!(patched.metadata.tags, vec!["keep".to_string()]);
+
+        assert!(db
+            .patch_record(&id, &serde_json::json!({ "source": "other" }))
+            .await
+            .is_err());
+        assert!(db
+            .patch_record(&id, &serde_json::json!({ "metadata": { "tags": "oops" } }))
+            .await
+            .is_err());
+        assert!(db
+            .patch_record("records:missing", &serde_json::json!({ "data": {} }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_merge_and_skip() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let record = StagedRecord::new(
+            "rest_api".to_string(),
+            "api".to_string(),
+            serde_json::json!({ "id": 1, "status": "exported" }),
+        );
+        let record = db.upsert_record(record).await.unwrap();
+        let export = db.export_all_data().await.unwrap();
+
+        db.patch_record(
+            &record.id.unwrap().to_string(),
+            &serde_json::json!({ "data": { "status": "local" } }),
+        )
+        .await
+        .unwrap();
+        let status = |db: &Database| {
+            let db = db.clone();
+            async move { db.get_records_by_type("rest_api").await.unwrap()[0].data["status"].clone() }
+        };
+
+        let stats = db.import_data(export.clone(), "skip").await.unwrap();
+        let records = &stats.tables[0];
+        assert_eq!(records.table, "records");
+        assert_eq!(
+            (records.imported, records.skipped, records.conflicts),
+            (0, 1, 1)
+        );
+        assert_eq!(status(&db).await, "local");
+
+        let stats = db.import_data(export.clone(), "merge").await.unwrap();
+        assert_eq!(stats.records_imported, 1);
+        assert_eq!(stats.tables[0].conflicts, 1);
+        assert_eq!(db.count_records().await.unwrap(), 1);
+        assert_eq!(status(&db).await, "exported");
+
+        assert!(db.import_data(export, "overwrite").await.is_err());
+    }
 }
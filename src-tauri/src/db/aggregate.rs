@@ -0,0 +1,438 @@
+// Record aggregation
+//
+// Runs GROUP BY queries over records (counts per day, per status, per
+// source, sums of a data field, ...) and shapes the result into chart-ready
+// series, so analytics widgets don't have to download raw records.
+
+use super::query::{validate_field_path, DateRange, RecordQuery};
+use super::Database;
+use crate::error::AppError;
+use chrono::{Duration, Months, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Label used for groups whose field is missing or null
+pub(super) const NONE_LABEL: &str = "(none)";
+
+/// Separator of the group labels of several group fields
+pub(super) const GROUP_SEPARATOR: &str = " / ";
+
+// ============================================================================
+// Aggregation Model
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AggregateQuery {
+    /// Field paths to group by, e.g. "source", "metadata.status" or "data.env"
+    pub group_by: Vec<String>,
+    pub metric: Metric,
+    /// Bucket records by timestamp; buckets become the x axis
+    pub time_bucket: Option<TimeBucket>,
+    /// Record filters (sort and pagination are ignored)
+    pub filters: RecordQuery,
+}
+
+/// What is computed per group
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Metric {
+    #[default]
+    Count,
+    Sum {
+        field: String,
+    },
+    Avg {
+        field: String,
+    },
+    Min {
+        field: String,
+    },
+    Max {
+        field: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeBucket {
+    Hour,
+    Day,
+    /// Weeks start on Monday
+    Week,
+    Month,
+}
+
+/// One line/bar series, aligned with `AggregateResult::labels`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateSeries {
+    pub name: String,
+    pub values: Vec<Option<f64>>,
+}
+
+/// Chart-ready result
+/// With a time bucket the labels are the buckets (oldest first) and there is
+/// one series per group; without one the labels are the groups (largest
+/// first) and there is a single series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateResult {
+    pub labels: Vec<String>,
+    pub series: Vec<AggregateSeries>,
+}
+
+impl Metric {
+    /// Validated field the metric reads, if any
+    pub(super) fn field(&self) -> Result<Option<&str>, AppError> {
+        match self {
+            Metric::Count => Ok(None),
+            Metric::Sum { field }
+            | Metric::Avg { field }
+            | Metric::Min { field }
+            | Metric::Max { field } => validate_field_path(field).map(Some),
+        }
+    }
+
+    fn expression(&self) -> Result<String, AppError> {
+        let field = self.field()?;
+        Ok(match self {
+            Metric::Count => "count()".to_string(),
+            Metric::Sum { .. } => format!("math::sum({})", field.unwrap_or_default()),
+            Metric::Avg { .. } => format!("math::mean({})", field.unwrap_or_default()),
+            Metric::Min { .. } => format!("math::min({})", field.unwrap_or_default()),
+            Metric::Max { .. } => format!("math::max({})", field.unwrap_or_default()),
+        })
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Metric::Count => "count".to_string(),
+            Metric::Sum { field } => format!("sum({})", field),
+            Metric::Avg { field } => format!("avg({})", field),
+            Metric::Min { field } => format!("min({})", field),
+            Metric::Max { field } => format!("max({})", field),
+        }
+    }
+
+    /// Value of a bucket without records (zero for additive metrics)
+    pub(super) fn empty_value(&self) -> Option<f64> {
+        match self {
+            Metric::Count | Metric::Sum { .. } => Some(0.0),
+            _ => None,
+        }
+    }
+}
+
+impl TimeBucket {
+    /// Expression producing the bucket label from the (string) timestamp
+    fn expression(self) -> &'static str {
+        match self {
+            TimeBucket::Hour => {
+                "time::format(time::group(<datetime> timestamp, 'hour'), '%Y-%m-%d %H:00')"
+            }
+            TimeBucket::Day => "time::format(time::group(<datetime> timestamp, 'day'), '%Y-%m-%d')",
+            // time::floor counts weeks from the epoch (a Thursday); shift to Mondays
+            TimeBucket::Week => {
+                "time::format(time::floor(<datetime> timestamp - 4d, 1w) + 4d, '%Y-%m-%d')"
+            }
+            TimeBucket::Month => {
+                "time::format(time::group(<datetime> timestamp, 'month'), '%Y-%m')"
+            }
+        }
+    }
+
+    /// Time range covered by a bucket label
+    pub(super) fn range(self, label: &str) -> Option<DateRange> {
+        let start = match self {
+            TimeBucket::Hour => NaiveDateTime::parse_from_str(label, "%Y-%m-%d %H:%M").ok()?,
+            TimeBucket::Day | TimeBucket::Week => NaiveDate::parse_from_str(label, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)?,
+            TimeBucket::Month => NaiveDate::parse_from_str(&format!("{}-01", label), "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)?,
+        }
+        .and_utc();
+        let end = match self {
+            TimeBucket::Hour => start + Duration::hours(1),
+            TimeBucket::Day => start + Duration::days(1),
+            TimeBucket::Week => start + Duration::weeks(1),
+            TimeBucket::Month => start.checked_add_months(Months::new(1))?,
+        };
+        Some(DateRange {
+            from: Some(start),
+            to: Some(end - Duration::milliseconds(1)),
+        })
+    }
+}
+
+impl AggregateQuery {
+    /// Build the aggregation statement
+    fn compile(&self) -> Result<String, AppError> {
+        let mut projections = Vec::new();
+        let mut groups = Vec::new();
+        for (index, field) in self.group_by.iter().enumerate() {
+            projections.push(format!("{} AS g{}", validate_field_path(field)?, index));
+            groups.push(format!("g{}", index));
+        }
+        if let Some(bucket) = self.time_bucket {
+            projections.push(format!("{} AS bucket", bucket.expression()));
+            groups.push("bucket".to_string());
+        }
+        projections.push(format!("{} AS value", self.metric.expression()?));
+
+        let mut where_clause = self.filters.where_clause()?;
+        if let Some(field) = self.metric.field()? {
+            // Only numeric values can be summed/averaged
+            where_clause.push_str(if where_clause.is_empty() {
+                " WHERE "
+            } else {
+                " AND "
+            });
+            where_clause.push_str(&format!("type::is::number({})", field));
+        }
+
+        let group_clause = if groups.is_empty() {
+            "GROUP ALL".to_string()
+        } else {
+            format!("GROUP BY {}", groups.join(", "))
+        };
+
+        Ok(format!(
+            "SELECT {} FROM records{} {}",
+            projections.join(", "),
+            where_clause,
+            group_clause
+        ))
+    }
+}
+
+/// Label for a group value
+fn group_label(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => NONE_LABEL.to_string(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Shape aggregated rows (`g0..gN`, optional `bucket`, `value`) into series
+fn build_result(query: &AggregateQuery, rows: Vec<serde_json::Value>) -> AggregateResult {
+    let group_key = |row: &serde_json::Value| -> Option<String> {
+        if query.group_by.is_empty() {
+            return None;
+        }
+        Some(
+            (0..query.group_by.len())
+                .map(|index| group_label(row.get(format!("g{}", index))))
+                .collect::<Vec<_>>()
+                .join(GROUP_SEPARATOR),
+        )
+    };
+    let value = |row: &serde_json::Value| row.get("value").and_then(|v| v.as_f64());
+
+    if query.time_bucket.is_none() {
+        let mut points: Vec<(String, Option<f64>)> = rows
+            .iter()
+            .map(|row| {
+                (
+                    group_key(row).unwrap_or_else(|| query.metric.name()),
+                    value(row),
+                )
+            })
+            .collect();
+        points.sort_by(|a, b| {
+            b.1.unwrap_or(f64::MIN)
+                .total_cmp(&a.1.unwrap_or(f64::MIN))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        let (labels, values) = points.into_iter().unzip();
+        return AggregateResult {
+            labels,
+            series: vec![AggregateSeries {
+                name: query.metric.name(),
+                values,
+            }],
+        };
+    }
+
+    let labels: Vec<String> = rows
+        .iter()
+        .filter_map(|row| row.get("bucket").and_then(|b| b.as_str()))
+        .map(String::from)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let positions: HashMap<&str, usize> = labels
+        .iter()
+        .enumerate()
+        .map(|(index, label)| (label.as_str(), index))
+        .collect();
+
+    let mut series: Vec<AggregateSeries> = Vec::new();
+    for row in &rows {
+        let Some(position) = row
+            .get("bucket")
+            .and_then(|b| b.as_str())
+            .and_then(|b| positions.get(b))
+        else {
+            continue;
+        };
+        let name = group_key(row).unwrap_or_else(|| query.metric.name());
+        let index = match series.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => {
+                series.push(AggregateSeries {
+                    name,
+                    values: vec![query.metric.empty_value(); labels.len()],
+                });
+                series.len() - 1
+            }
+        };
+        series[index].values[*position] = value(row);
+    }
+    series.sort_by(|a, b| a.name.cmp(&b.name));
+
+    AggregateResult { labels, series }
+}
+
+// ============================================================================
+// Aggregation Operations
+// ============================================================================
+
+impl Database {
+    /// Aggregate records into chart-ready series
+    pub async fn aggregate_records(
+        &self,
+        query: AggregateQuery,
+    ) -> Result<AggregateResult, AppError> {
+        let statement = query.compile()?;
+
+        let mut result = query
+            .filters
+            .bind_filters(self.query(statement))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to aggregate records: {}", e)))?;
+
+        let rows: Vec<serde_json::Value> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract aggregates: {}", e)))?;
+
+        Ok(build_result(&query, rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use chrono::{TimeZone, Utc};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_bucket_range() {
+        let range = TimeBucket::Month.range("2024-02").unwrap();
+        assert_eq!(
+            range.from,
+            Some(Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            range.to,
+            Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap() - Duration::milliseconds(1))
+        );
+        let range = TimeBucket::Hour.range("2024-03-04 09:00").unwrap();
+        assert_eq!(
+            range.from,
+            Some(Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap())
+        );
+        let range = TimeBucket::Week.range("2024-03-04").unwrap();
+        assert_eq!(
+            range.to,
+            Some(Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap() - Duration::milliseconds(1))
+        );
+        assert!(TimeBucket::Day.range("2024-03").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let runs = [
+            ("gitlab", "success", 10, (2024, 3, 4, 9)),
+            ("gitlab", "failed", 5, (2024, 3, 4, 17)),
+            ("gitlab", "success", 20, (2024, 3, 6, 8)),
+            ("github", "success", 7, (2024, 3, 11, 12)),
+        ];
+        for (i, (source, status, duration, (y, m, d, h))) in runs.iter().enumerate() {
+            let mut record = StagedRecord::new(
+                "pipeline".to_string(),
+                source.to_string(),
+                serde_json::json!({ "id": i, "duration": duration }),
+            );
+            record.metadata.status = Some(status.to_string());
+            record.timestamp = Utc.with_ymd_and_hms(*y, *m, *d, *h, 0, 0).unwrap();
+            db.upsert_record(record).await.unwrap();
+        }
+
+        // Counts per status
+        let by_status = db
+            .aggregate_records(AggregateQuery {
+                group_by: vec!["metadata.status".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(by_status.labels, vec!["success", "failed"]);
+        assert_eq!(by_status.series[0].values, vec![Some(3.0), Some(1.0)]);
+
+        // Counts per day and source
+        let per_day = db
+            .aggregate_records(AggregateQuery {
+                group_by: vec!["source".to_string()],
+                time_bucket: Some(TimeBucket::Day),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            per_day.labels,
+            vec!["2024-03-04", "2024-03-06", "2024-03-11"]
+        );
+        assert_eq!(per_day.series[0].name, "github");
+        assert_eq!(
+            per_day.series[0].values,
+            vec![Some(0.0), Some(0.0), Some(1.0)]
+        );
+        assert_eq!(
+            per_day.series[1].values,
+            vec![Some(2.0), Some(1.0), Some(0.0)]
+        );
+
+        // Summed durations per (Monday-based) week, filtered to gitlab
+        let per_week = db
+            .aggregate_records(AggregateQuery {
+                metric: Metric::Sum {
+                    field: "data.duration".to_string(),
+                },
+                time_bucket: Some(TimeBucket::Week),
+                filters: RecordQuery {
+                    sources: vec!["gitlab".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(per_week.labels, vec!["2024-03-04"]);
+        assert_eq!(per_week.series[0].name, "sum(data.duration)");
+        assert_eq!(per_week.series[0].values, vec![Some(35.0)]);
+
+        assert!(db
+            .aggregate_records(AggregateQuery {
+                group_by: vec!["id; DELETE records".to_string()],
+                ..Default::default()
+            })
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,338 @@
+// Widget drill-down
+//
+// Clicking a summary widget (a counter, a bar, a chart point) lists the
+// records behind it. Evaluated panels carry a drill-down target: the
+// widget's filters plus how its result was grouped and bucketed. The
+// frontend sends the target back with what was clicked (group label, time
+// bucket) and gets the matching records, narrowed by a condition per group
+// field and by the bucket's time range. A widget can also name a dashboard
+// to open instead; the clicked values are then handed over as that
+// dashboard's variables.
+
+use super::aggregate::{TimeBucket, GROUP_SEPARATOR, NONE_LABEL};
+use super::query::{validate_field_path, ConditionOp, DateRange, FieldCondition, RecordQuery};
+use super::widgets::{computed_periods, project, ComputedKind, Variables, WidgetQuery};
+use super::Database;
+use crate::error::AppError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Records returned per drill-down page without a limit
+const DEFAULT_DRILLDOWN_LIMIT: usize = 50;
+
+/// Declared by a widget query: where clicking the widget leads
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DrilldownLink {
+    /// Dashboard to open, with the clicked values as its variables
+    pub dashboard: Option<String>,
+    /// Fields returned per record (all when empty)
+    pub fields: Vec<String>,
+}
+
+/// What a click on a widget resolves against
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DrilldownTarget {
+    /// The records behind the widget
+    pub query: RecordQuery,
+    /// Group fields of the widget's result, matched against the clicked group
+    pub group_by: Vec<String>,
+    /// Bucket size of the widget's time axis
+    pub time_bucket: Option<TimeBucket>,
+    pub dashboard: Option<String>,
+    pub fields: Vec<String>,
+}
+
+/// The clicked element of a widget
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DrilldownContext {
+    /// Group label of the clicked bar, slice or series ("a / b" for two group fields)
+    pub group: Option<String>,
+    /// Time bucket label of the clicked point, e.g. "2024-03-04"
+    pub bucket: Option<String>,
+    /// Variables of the widget's dashboard, handed on to a linked dashboard
+    pub variables: Variables,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Records behind a clicked widget element
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Drilldown {
+    /// The narrowed query, for paging or saving as a view
+    pub query: RecordQuery,
+    pub total: usize,
+    pub records: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dashboard: Option<String>,
+    /// Variables for the linked dashboard
+    #[serde(skip_serializing_if = "Variables::is_empty")]
+    pub variables: Variables,
+}
+
+impl WidgetQuery {
+    /// Drill-down target of the widget's result
+    pub fn drilldown_target(&self) -> DrilldownTarget {
+        let link = self.drilldown.clone().unwrap_or_default();
+        let mut target = DrilldownTarget {
+            query: RecordQuery {
+                limit: None,
+                offset: None,
+                ..self.filters.clone()
+            },
+            dashboard: link.dashboard,
+            fields: if link.fields.is_empty() {
+                self.fields.clone()
+            } else {
+                link.fields
+            },
+            ..Default::default()
+        };
+
+        if let Some(widget) = &self.computed {
+            // The records of the computed period, bucketed like the trend's points
+            if let Ok((current, _)) =
+                computed_periods(widget, self.filters.date_range.as_ref(), Utc::now())
+            {
+                target.query.date_range = current;
+            }
+            if widget.kind == ComputedKind::Trend {
+                target.time_bucket = Some(widget.time_bucket.unwrap_or(TimeBucket::Day));
+            }
+        } else if let Some(aggregation) = &self.aggregate {
+            target.group_by = aggregation.group_by.clone();
+            target.time_bucket = aggregation.time_bucket;
+        }
+        target
+    }
+}
+
+/// Value a group label stands for
+fn group_value(label: &str) -> serde_json::Value {
+    if label == NONE_LABEL {
+        return serde_json::Value::Null;
+    }
+    match serde_json::from_str::<serde_json::Value>(label) {
+        Ok(value @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_))) => value,
+        _ => serde_json::Value::String(label.to_string()),
+    }
+}
+
+/// Later of two range starts / earlier of two range ends
+fn intersect(range: Option<DateRange>, bucket: DateRange) -> DateRange {
+    let Some(range) = range else {
+        return bucket;
+    };
+    DateRange {
+        from: range.from.max(bucket.from),
+        to: match (range.to, bucket.to) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        },
+    }
+}
+
+/// Narrow a target's query to the clicked element; also returns the
+/// variables a linked dashboard receives
+fn narrow(
+    target: &DrilldownTarget,
+    context: &DrilldownContext,
+) -> Result<(RecordQuery, Variables), AppError> {
+    let mut query = target.query.clone();
+    let mut variables = context.variables.clone();
+
+    if let Some(group) = context
+        .group
+        .as_deref()
+        .filter(|_| !target.group_by.is_empty())
+    {
+        let labels: Vec<&str> = if target.group_by.len() == 1 {
+            vec![group]
+        } else {
+            group.split(GROUP_SEPARATOR).collect()
+        };
+        if labels.len() != target.group_by.len() {
+            return Err(AppError::Validation(format!(
+                "Group {:?} doesn't match the widget's {} group fields",
+                group,
+                target.group_by.len()
+            )));
+        }
+        for (field, label) in target.group_by.iter().zip(labels) {
+            validate_field_path(field)?;
+            let value = group_value(label);
+            let name = field.rsplit('.').next().unwrap_or(field);
+            variables.insert(name.to_string(), value.clone());
+            query.conditions.push(FieldCondition {
+                field: field.clone(),
+                op: ConditionOp::Eq,
+                value,
+            });
+        }
+    }
+
+    if let Some(bucket) = &context.bucket {
+        let range = target
+            .time_bucket
+            .and_then(|size| size.range(bucket))
+            .ok_or_else(|| {
+                AppError::Validation(format!("Not a time bucket of this widget: {}", bucket))
+            })?;
+        variables.insert("range".to_string(), serde_json::to_value(&range)?);
+        query.date_range = Some(intersect(query.date_range.take(), range));
+    }
+
+    query.limit = Some(context.limit.unwrap_or(DEFAULT_DRILLDOWN_LIMIT));
+    query.offset = context.offset;
+    Ok((query, variables))
+}
+
+impl Database {
+    /// Records behind the clicked element of a widget
+    pub async fn resolve_drilldown(
+        &self,
+        target: DrilldownTarget,
+        context: DrilldownContext,
+    ) -> Result<Drilldown, AppError> {
+        for field in &target.fields {
+            validate_field_path(field)?;
+        }
+        let (query, variables) = narrow(&target, &context)?;
+
+        let page = self.query_records(query.clone()).await?;
+        let records = page
+            .records
+            .into_iter()
+            .map(|record| Ok(project(serde_json::to_value(record)?, &target.fields)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        Ok(Drilldown {
+            query,
+            total: page.total,
+            records,
+            variables: if target.dashboard.is_some() {
+                variables
+            } else {
+                Variables::new()
+            },
+            dashboard: target.dashboard,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::widgets::WidgetAggregation;
+    use crate::db::StagedRecord;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_resolve_drilldown() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let runs = [
+            ("success", 3, (2024, 3, 4)),
+            ("failed", 3, (2024, 3, 4)),
+            ("failed", 5, (2024, 3, 5)),
+        ];
+        for (i, (status, retries, (y, m, d))) in runs.iter().enumerate() {
+            let mut record = StagedRecord::new(
+                "pipeline".to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "id": i, "retries": retries }),
+            );
+            record.metadata.status = Some(status.to_string());
+            record.timestamp = Utc.with_ymd_and_hms(*y, *m, *d, 12, 0, 0).unwrap();
+            db.upsert_record(record).await.unwrap();
+        }
+
+        let widget = WidgetQuery {
+            filters: RecordQuery {
+                types: vec!["pipeline".to_string()],
+                limit: Some(1),
+                ..Default::default()
+            },
+            aggregate: Some(WidgetAggregation {
+                group_by: vec!["metadata.status".to_string()],
+                time_bucket: Some(TimeBucket::Day),
+                ..Default::default()
+            }),
+            drilldown: Some(DrilldownLink {
+                dashboard: Some("dashboard_runs".to_string()),
+                fields: vec!["data.retries".to_string()],
+            }),
+            ..Default::default()
+        };
+        let target = widget.drilldown_target();
+        assert_eq!(target.query.limit, None);
+        assert_eq!(target.time_bucket, Some(TimeBucket::Day));
+
+        // The failed runs of March 4th
+        let drilldown = db
+            .resolve_drilldown(
+                target.clone(),
+                DrilldownContext {
+                    group: Some("failed".to_string()),
+                    bucket: Some("2024-03-04".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(drilldown.total, 1);
+        assert_eq!(
+            drilldown.records[0]["data"],
+            serde_json::json!({ "retries": 3 })
+        );
+        assert_eq!(drilldown.dashboard.as_deref(), Some("dashboard_runs"));
+        assert_eq!(drilldown.variables["status"], "failed");
+        assert!(drilldown.variables.contains_key("range"));
+
+        // All runs behind the widget
+        let all = db
+            .resolve_drilldown(target.clone(), DrilldownContext::default())
+            .await
+            .unwrap();
+        assert_eq!(all.total, 3);
+
+        // Numeric group labels match numeric fields
+        let by_retries = DrilldownTarget {
+            group_by: vec!["data.retries".to_string()],
+            ..target.clone()
+        };
+        let context = DrilldownContext {
+            group: Some("3".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            db.resolve_drilldown(by_retries, context)
+                .await
+                .unwrap()
+                .total,
+            2
+        );
+
+        let bad_bucket = DrilldownContext {
+            bucket: Some("March".to_string()),
+            ..Default::default()
+        };
+        assert!(db
+            .resolve_drilldown(target.clone(), bad_bucket)
+            .await
+            .is_err());
+        let by_two = DrilldownTarget {
+            group_by: vec!["metadata.status".to_string(), "source".to_string()],
+            ..target
+        };
+        let bad_group = DrilldownContext {
+            group: Some("failed".to_string()),
+            ..Default::default()
+        };
+        assert!(db.resolve_drilldown(by_two, bad_group).await.is_err());
+    }
+}
@@ -0,0 +1,475 @@
+// Database integrity check
+//
+// Verifies that the tables the app relies on exist, that stored references
+// (prompt package IDs, ticket parents/links, attachment owners) resolve and
+// that timestamps parse. Orphaned references can optionally be repaired:
+// dangling children are deleted and dangling ticket references cleared.
+
+use super::Database;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Tables created by the schema migrations
+const REQUIRED_TABLES: &[&str] = &[
+    "schema_meta",
+    "records",
+    "tickets",
+    "fetch_runs",
+    "prompt_templates",
+    "prompt_sections",
+    "prompt_separator_sets",
+    "prompt_data_types",
+    "prompt_tags",
+];
+
+/// Tables whose rows belong to a prompt package via `package_id`
+const PROMPT_CHILD_TABLES: &[&str] = &[
+    "prompt_templates",
+    "prompt_sections",
+    "prompt_separator_sets",
+    "prompt_data_types",
+    "prompt_tags",
+];
+
+/// Rows scanned per query when checking record timestamps
+const SCAN_CHUNK_SIZE: usize = 1000;
+
+/// Issues listed in a report at most (all are counted)
+const MAX_REPORTED_ISSUES: usize = 500;
+
+// ============================================================================
+// Report Model
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    MissingTable,
+    InvalidId,
+    OrphanedReference,
+    InvalidTimestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub kind: IssueKind,
+    pub table: String,
+    /// Row the issue was found in
+    pub id: Option<String>,
+    pub message: String,
+    /// Whether repair mode can fix it
+    pub repairable: bool,
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    /// No issues were found (or all of them were repaired)
+    pub ok: bool,
+    pub total_issues: usize,
+    pub repaired: usize,
+    /// Up to `MAX_REPORTED_ISSUES` issues
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    fn push(&mut self, issue: IntegrityIssue) {
+        self.total_issues += 1;
+        if issue.repaired {
+            self.repaired += 1;
+        }
+        if self.issues.len() < MAX_REPORTED_ISSUES {
+            self.issues.push(issue);
+        }
+    }
+}
+
+/// Key of a reference to `table` ("table:key", "table:⟨key⟩" or a bare key)
+/// None if it points at another table or doesn't parse
+fn reference_key(table: &str, reference: &str) -> Option<String> {
+    if !reference.contains(':') {
+        return (!reference.is_empty()).then(|| reference.to_string());
+    }
+    let thing = surrealdb::sql::thing(reference).ok()?;
+    (thing.tb == table).then(|| thing.id.to_raw())
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageRef {
+    id: String,
+    package_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TicketRefs {
+    id: String,
+    parent_id: Option<String>,
+    linked_tickets: Option<Vec<String>>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimestampRow {
+    id: String,
+    timestamp: Option<serde_json::Value>,
+}
+
+// ============================================================================
+// Integrity Operations
+// ============================================================================
+
+impl Database {
+    /// Check the database for missing tables, dangling references and bad timestamps
+    /// With `repair`, orphaned prompt rows and attachments are deleted and
+    /// dangling ticket parent/link references are cleared.
+    pub async fn check_integrity(&self, repair: bool) -> Result<IntegrityReport, AppError> {
+        let mut report = IntegrityReport {
+            checked_at: Utc::now(),
+            ok: true,
+            total_issues: 0,
+            repaired: 0,
+            issues: Vec::new(),
+        };
+
+        let tables: HashSet<String> = self.list_tables().await?.into_iter().collect();
+        for table in REQUIRED_TABLES {
+            if !tables.contains(*table) {
+                report.push(IntegrityIssue {
+                    kind: IssueKind::MissingTable,
+                    table: table.to_string(),
+                    id: None,
+                    message: format!("Table {} is not defined", table),
+                    repairable: false,
+                    repaired: false,
+                });
+            }
+        }
+
+        self.check_prompt_packages(repair, &mut report).await?;
+        self.check_tickets(repair, &mut report).await?;
+        self.check_attachments(repair, &mut report).await?;
+        self.check_record_timestamps(&mut report).await?;
+
+        report.ok = report.total_issues == report.repaired;
+        tracing::info!(
+            "Integrity check: {} issues, {} repaired",
+            report.total_issues,
+            report.repaired
+        );
+        Ok(report)
+    }
+
+    /// Prompt rows must point at an existing package
+    async fn check_prompt_packages(
+        &self,
+        repair: bool,
+        report: &mut IntegrityReport,
+    ) -> Result<(), AppError> {
+        let packages: HashSet<String> = self
+            .query_rows::<String>("SELECT VALUE <string> meta::id(id) FROM prompt_packages")
+            .await?
+            .into_iter()
+            .collect();
+
+        for table in PROMPT_CHILD_TABLES {
+            let rows: Vec<PackageRef> = self
+                .query_rows(&format!(
+                    "SELECT <string> id AS id, package_id FROM {}",
+                    table
+                ))
+                .await?;
+
+            for row in rows {
+                let package_id = row.package_id.unwrap_or_default();
+                let (kind, message) = match reference_key("prompt_packages", &package_id) {
+                    Some(key) if packages.contains(&key) => continue,
+                    Some(_) => (
+                        IssueKind::OrphanedReference,
+                        format!("Package {} does not exist", package_id),
+                    ),
+                    None => (
+                        IssueKind::InvalidId,
+                        format!("Invalid package ID '{}'", package_id),
+                    ),
+                };
+
+                let repaired = repair && self.delete_by_id(&row.id).await?;
+                report.push(IntegrityIssue {
+                    kind,
+                    table: table.to_string(),
+                    id: Some(row.id),
+                    message,
+                    repairable: true,
+                    repaired,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Ticket parents and links must resolve; created/updated times must parse
+    async fn check_tickets(
+        &self,
+        repair: bool,
+        report: &mut IntegrityReport,
+    ) -> Result<(), AppError> {
+        let rows: Vec<TicketRefs> = self
+            .query_rows(
+                "SELECT <string> id AS id, parent_id, linked_tickets, created_at, updated_at
+                 FROM tickets",
+            )
+            .await?;
+        let tickets: HashSet<String> = rows
+            .iter()
+            .filter_map(|row| reference_key("tickets", &row.id))
+            .collect();
+        let resolves = |reference: &str| {
+            reference_key("tickets", reference).is_some_and(|key| tickets.contains(&key))
+        };
+
+        for row in &rows {
+            if let Some(parent) = row.parent_id.as_deref().filter(|p| !resolves(p)) {
+                let repaired = repair
+                    && self
+                        .run_repair(
+                            "UPDATE type::record($id) SET parent_id = NONE",
+                            &row.id,
+                            serde_json::Value::Null,
+                        )
+                        .await?;
+                report.push(IntegrityIssue {
+                    kind: IssueKind::OrphanedReference,
+                    table: "tickets".to_string(),
+                    id: Some(row.id.clone()),
+                    message: format!("Parent ticket {} does not exist", parent),
+                    repairable: true,
+                    repaired,
+                });
+            }
+
+            let dangling: Vec<&String> = row
+                .linked_tickets
+                .iter()
+                .flatten()
+                .filter(|link| !resolves(link))
+                .collect();
+            if !dangling.is_empty() {
+                let repaired = repair
+                    && self
+                        .run_repair(
+                            "UPDATE type::record($id)
+                             SET linked_tickets = array::complement(linked_tickets, $refs)",
+                            &row.id,
+                            serde_json::json!(dangling),
+                        )
+                        .await?;
+                report.push(IntegrityIssue {
+                    kind: IssueKind::OrphanedReference,
+                    table: "tickets".to_string(),
+                    id: Some(row.id.clone()),
+                    message: format!(
+                        "Linked tickets do not exist: {}",
+                        dangling
+                            .iter()
+                            .map(|s| s.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    repairable: true,
+                    repaired,
+                });
+            }
+
+            for (field, value) in [
+                ("created_at", &row.created_at),
+                ("updated_at", &row.updated_at),
+            ] {
+                let valid = value
+                    .as_deref()
+                    .is_some_and(|v| DateTime::parse_from_rfc3339(v).is_ok());
+                if !valid {
+                    report.push(IntegrityIssue {
+                        kind: IssueKind::InvalidTimestamp,
+                        table: "tickets".to_string(),
+                        id: Some(row.id.clone()),
+                        message: format!("Invalid {}: {:?}", field, value),
+                        repairable: false,
+                        repaired: false,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Attachments must belong to an existing ticket, page or record
+    async fn check_attachments(
+        &self,
+        repair: bool,
+        report: &mut IntegrityReport,
+    ) -> Result<(), AppError> {
+        #[derive(Deserialize)]
+        struct Orphan {
+            id: String,
+            owner: String,
+        }
+
+        let orphans: Vec<Orphan> = self
+            .query_rows(
+                "SELECT <string> id AS id, <string> owner AS owner FROM attachments
+                 WHERE !record::exists(owner)",
+            )
+            .await?;
+        for orphan in orphans {
+            // The row goes; its file stays on disk
+            let repaired = repair && self.delete_by_id(&orphan.id).await?;
+            report.push(IntegrityIssue {
+                kind: IssueKind::OrphanedReference,
+                table: "attachments".to_string(),
+                id: Some(orphan.id),
+                message: format!("Owner {} does not exist", orphan.owner),
+                repairable: true,
+                repaired,
+            });
+        }
+        Ok(())
+    }
+
+    /// Record timestamps must be valid RFC 3339 datetimes
+    async fn check_record_timestamps(&self, report: &mut IntegrityReport) -> Result<(), AppError> {
+        let mut start = 0;
+        loop {
+            let mut result = self
+                .query(
+                    "SELECT <string> id AS id, timestamp FROM records
+                     ORDER BY id LIMIT $limit START $start",
+                )
+                .bind(("limit", SCAN_CHUNK_SIZE))
+                .bind(("start", start))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to scan records: {}", e)))?;
+            let rows: Vec<TimestampRow> = result
+                .take(0)
+                .map_err(|e| AppError::Database(format!("Failed to scan records: {}", e)))?;
+
+            for row in &rows {
+                let valid = row
+                    .timestamp
+                    .as_ref()
+                    .and_then(|t| t.as_str())
+                    .is_some_and(|t| DateTime::parse_from_rfc3339(t).is_ok());
+                if !valid {
+                    report.push(IntegrityIssue {
+                        kind: IssueKind::InvalidTimestamp,
+                        table: "records".to_string(),
+                        id: Some(row.id.clone()),
+                        message: format!("Invalid timestamp: {:?}", row.timestamp),
+                        repairable: false,
+                        repaired: false,
+                    });
+                }
+            }
+
+            if rows.len() < SCAN_CHUNK_SIZE {
+                return Ok(());
+            }
+            start += rows.len();
+        }
+    }
+
+    /// Run a single-statement query and take its rows
+    async fn query_rows<T: serde::de::DeserializeOwned>(
+        &self,
+        statement: &str,
+    ) -> Result<Vec<T>, AppError> {
+        let mut result = self
+            .query(statement)
+            .await
+            .map_err(|e| AppError::Database(format!("Integrity check failed: {}", e)))?;
+        result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Integrity check failed: {}", e)))
+    }
+
+    /// Delete a row by its "table:key" ID
+    async fn delete_by_id(&self, id: &str) -> Result<bool, AppError> {
+        self.run_repair("DELETE type::record($id)", id, serde_json::Value::Null)
+            .await
+    }
+
+    /// Run a repair statement against one row (`$id`, optional `$refs`)
+    async fn run_repair(
+        &self,
+        statement: &str,
+        id: &str,
+        refs: serde_json::Value,
+    ) -> Result<bool, AppError> {
+        self.query(statement)
+            .bind(("id", id.to_string()))
+            .bind(("refs", refs))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to repair {}: {}", id, e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to repair {}: {}", id, e)))?;
+        tracing::info!("Repaired {}", id);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_check_integrity() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        db
+            .query(
+                "CREATE prompt_packages:pkg SET name = 'ok';
+                 CREATE prompt_templates:good SET package_id = 'pkg';
+                 CREATE prompt_templates:orphan SET package_id = 'gone';
+                 CREATE prompt_tags:broken SET package_id = 'records:pkg';
+                 CREATE tickets:parent SET created_at = '2024-01-01T00:00:00Z', updated_at = '2024-01-01T00:00:00Z';
+                 CREATE tickets:child SET parent_id = 'tickets:missing',
+                     linked_tickets = ['tickets:parent', 'tickets:missing'],
+                     created_at = '2024-01-01T00:00:00Z', updated_at = 'yesterday';
+                 CREATE attachments:lost SET owner = tickets:missing;",
+            )
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        db.upsert_record(StagedRecord::new(
+            "rest_api".to_string(),
+            "api".to_string(),
+            serde_json::json!({ "id": 1 }),
+        ))
+        .await
+        .unwrap();
+
+        let report = db.check_integrity(false).await.unwrap();
+        assert!(!report.ok);
+        let count = |kind| report.issues.iter().filter(|i| i.kind == kind).count();
+        assert_eq!(count(IssueKind::OrphanedReference), 4);
+        assert_eq!(count(IssueKind::InvalidId), 1);
+        assert_eq!(count(IssueKind::InvalidTimestamp), 1);
+        assert_eq!(report.repaired, 0);
+
+        let report = db.check_integrity(true).await.unwrap();
+        assert_eq!(report.repaired, 5);
+
+        // Only the unrepairable timestamp is left
+        let report = db.check_integrity(false).await.unwrap();
+        assert_eq!(report.total_issues, 1);
+        assert_eq!(report.issues[0].kind, IssueKind::InvalidTimestamp);
+        assert_eq!(db.count_table("prompt_templates").await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,249 @@
+// Schema migrations
+//
+// Versioned DEFINE TABLE/FIELD/INDEX statements applied at startup. The
+// applied version is tracked in the `schema_meta` table so each migration
+// runs exactly once per database. Append new migrations to the end of the
+// list; never edit one that has already shipped.
+
+use super::Database;
+use crate::boards::DEFAULT_KEY_PREFIX;
+use crate::error::AppError;
+use crate::search;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// A single versioned schema change
+struct Migration {
+    version: u32,
+    name: &'static str,
+    statements: String,
+}
+
+/// All migrations, in order
+fn migrations() -> Vec<Migration> {
+    vec![
+        Migration {
+            version: 1,
+            name: "core indexes",
+            statements: "
+                DEFINE INDEX OVERWRITE records_record_type ON records FIELDS record_type;
+                DEFINE INDEX OVERWRITE records_source ON records FIELDS source;
+                DEFINE INDEX OVERWRITE records_timestamp ON records FIELDS timestamp;
+                DEFINE INDEX OVERWRITE tickets_status ON tickets FIELDS status;
+                DEFINE INDEX OVERWRITE fetch_runs_source ON fetch_runs FIELDS source, started_at;
+                DEFINE INDEX OVERWRITE prompt_templates_package ON prompt_templates FIELDS package_id;
+                DEFINE INDEX OVERWRITE prompt_sections_package ON prompt_sections FIELDS package_id;
+                DEFINE INDEX OVERWRITE prompt_separator_sets_package ON prompt_separator_sets FIELDS package_id;
+                DEFINE INDEX OVERWRITE prompt_data_types_package ON prompt_data_types FIELDS package_id;
+                DEFINE INDEX OVERWRITE prompt_tags_package ON prompt_tags FIELDS package_id;
+            "
+            .to_string(),
+        },
+        Migration {
+            version: 2,
+            name: "record full-text search",
+            statements: search::search_schema(),
+        },
+        Migration {
+            version: 3,
+            name: "ticket keys",
+            statements: format!(
+                "
+                DEFINE INDEX OVERWRITE tickets_key ON tickets FIELDS key;
+                FOR $ticket IN (SELECT id, created_at FROM tickets WHERE key = NONE ORDER BY created_at) {{
+                    LET $number = (UPSERT type::thing('ticket_sequences', '{prefix}')
+                        SET last = (last ?? 0) + 1 RETURN VALUE last)[0];
+                    UPDATE $ticket.id SET key = string::concat('{prefix}-', <string> $number);
+                }};
+            ",
+                prefix = DEFAULT_KEY_PREFIX
+            ),
+        },
+        Migration {
+            version: 4,
+            name: "ticket status history",
+            statements: "
+                DEFINE INDEX OVERWRITE ticket_events_ticket ON ticket_events FIELDS ticket, at;
+                DEFINE EVENT OVERWRITE ticket_status_history ON TABLE tickets
+                    WHEN ($event = 'CREATE' AND count(SELECT VALUE id FROM ticket_events
+                            WHERE ticket IN [$after.id, <string> $after.id]) = 0)
+                        OR ($event = 'UPDATE' AND $before.status != $after.status)
+                    THEN (CREATE ticket_events CONTENT {
+                        ticket: $after.id,
+                        from_status: IF $event = 'UPDATE' { $before.status } ELSE { NONE },
+                        to_status: $after.status,
+                        at: IF $event = 'CREATE' { $after.created_at } ELSE { $after.updated_at },
+                    });
+                DEFINE EVENT OVERWRITE ticket_history_cleanup ON TABLE tickets
+                    WHEN $event = 'DELETE'
+                    THEN (DELETE ticket_events WHERE ticket = $before.id);
+            "
+            .to_string(),
+        },
+        Migration {
+            version: 5,
+            name: "dashboard versions",
+            statements: "
+                DEFINE INDEX OVERWRITE dashboard_versions_dashboard ON dashboard_versions FIELDS dashboard, version;
+                DEFINE EVENT OVERWRITE dashboard_versions_cleanup ON TABLE dashboards
+                    WHEN $event = 'DELETE'
+                    THEN (DELETE dashboard_versions WHERE dashboard = record::id($before.id));
+            "
+            .to_string(),
+        },
+        Migration {
+            version: 6,
+            name: "dashboard snapshots",
+            statements: "
+                DEFINE INDEX OVERWRITE dashboard_snapshots_dashboard ON dashboard_snapshots FIELDS dashboard_id, taken_at;
+            "
+            .to_string(),
+        },
+        Migration {
+            version: 7,
+            name: "feed subscriptions",
+            statements: "
+                DEFINE INDEX OVERWRITE feed_subscriptions_url ON feed_subscriptions FIELDS url;
+                DEFINE INDEX OVERWRITE feed_items_subscription ON feed_items FIELDS subscription, read;
+            "
+            .to_string(),
+        },
+        Migration {
+            version: 8,
+            name: "page blocks",
+            statements: "
+                DEFINE INDEX OVERWRITE page_blocks_page ON page_blocks FIELDS page, position;
+            "
+            .to_string(),
+        },
+        Migration {
+            version: 9,
+            name: "data source identifiers",
+            statements: "
+                DEFINE INDEX OVERWRITE data_sources_source ON data_sources FIELDS source;
+            "
+            .to_string(),
+        },
+        Migration {
+            version: 10,
+            name: "audit log",
+            statements: crate::audit::audit_schema(),
+        },
+    ]
+}
+
+/// Latest schema version known to this build
+pub fn latest_version() -> u32 {
+    migrations().last().map(|m| m.version).unwrap_or(0)
+}
+
+// ============================================================================
+// Schema Version Model
+// ============================================================================
+
+/// Schema version as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SchemaMetaRecord {
+    pub id: Thing,
+    pub version: u32,
+    pub name: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+/// Applied and latest schema version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    pub version: u32,
+    pub latest: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// Migration Operations
+// ============================================================================
+
+impl Database {
+    /// Apply all pending migrations
+    /// Returns the number of migrations applied
+    pub async fn run_migrations(&self) -> Result<usize, AppError> {
+        let current = self.get_schema_version().await?.version;
+        let pending: Vec<Migration> = migrations()
+            .into_iter()
+            .filter(|m| m.version > current)
+            .collect();
+
+        for migration in &pending {
+            tracing::info!(
+                "Applying schema migration {} ({})",
+                migration.version,
+                migration.name
+            );
+
+            self.query(migration.statements.as_str())
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Migration {} failed: {}", migration.version, e))
+                })?
+                .check()
+                .map_err(|e| {
+                    AppError::Database(format!("Migration {} failed: {}", migration.version, e))
+                })?;
+
+            let record = SchemaMetaRecord {
+                id: Thing::from(("schema_meta", "version")),
+                version: migration.version,
+                name: migration.name.to_string(),
+                applied_at: Utc::now(),
+            };
+
+            let _: Option<SchemaMetaRecord> = self
+                .db
+                .upsert(("schema_meta", "version"))
+                .content(record)
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to record schema version: {}", e))
+                })?;
+        }
+
+        Ok(pending.len())
+    }
+
+    /// Get the applied schema version
+    pub async fn get_schema_version(&self) -> Result<SchemaVersion, AppError> {
+        let record: Option<SchemaMetaRecord> = self
+            .db
+            .select(("schema_meta", "version"))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get schema version: {}", e)))?;
+
+        Ok(SchemaVersion {
+            version: record.as_ref().map(|r| r.version).unwrap_or(0),
+            latest: latest_version(),
+            name: record.as_ref().map(|r| r.name.clone()),
+            applied_at: record.map(|r| r.applied_at),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_migrations_run_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let version = db.get_schema_version().await.unwrap();
+        assert_eq!(version.version, latest_version());
+        assert!(version.applied_at.is_some());
+
+        assert_eq!(db.run_migrations().await.unwrap(), 0);
+    }
+}
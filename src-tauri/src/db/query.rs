@@ -0,0 +1,305 @@
+// Record queries
+//
+// Compiles a structured filter (types, sources, tags, status, date range,
+// conditions on record fields, sort and pagination) into a parameterized
+// SurrealQL query so the frontend no longer has to pull whole tables and
+// filter client-side.
+
+use super::{Database, StagedRecord};
+use crate::error::AppError;
+use crate::metrics;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use surrealdb::method::Query;
+use surrealdb::Connection;
+
+/// Default page size
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// Upper bound for a single page
+const MAX_QUERY_LIMIT: usize = 5000;
+
+// ============================================================================
+// Query Model
+// ============================================================================
+
+/// Structured record query; empty lists mean "no filter"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordQuery {
+    pub types: Vec<String>,
+    pub sources: Vec<String>,
+    /// Records carrying any of these tags
+    pub tags: Vec<String>,
+    /// Matches `metadata.status`
+    pub status: Vec<String>,
+    pub date_range: Option<DateRange>,
+    /// Conditions on record fields, all of which must hold
+    pub conditions: Vec<FieldCondition>,
+    /// Sort keys in priority order (defaults to newest first)
+    pub sort: Vec<SortSpec>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DateRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Comparison of a record field with a value, e.g. `data.env = "prod"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldCondition {
+    /// Field path, e.g. "metadata.status" or "data.duration"
+    pub field: String,
+    #[serde(default)]
+    pub op: ConditionOp,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOp {
+    #[default]
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    /// Text or list field contains the value
+    Contains,
+    /// Field equals one of the values of a list
+    In,
+}
+
+impl ConditionOp {
+    fn operator(self) -> &'static str {
+        match self {
+            ConditionOp::Eq => "=",
+            ConditionOp::Ne => "!=",
+            ConditionOp::Gt => ">",
+            ConditionOp::Gte => ">=",
+            ConditionOp::Lt => "<",
+            ConditionOp::Lte => "<=",
+            ConditionOp::Contains => "CONTAINS",
+            ConditionOp::In => "IN",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortSpec {
+    /// Field path, e.g. "timestamp", "metadata.title" or "data.priority"
+    pub field: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// One page of query results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordPage {
+    pub total: usize,
+    pub records: Vec<StagedRecord>,
+}
+
+impl RecordQuery {
+    /// Build the WHERE clause (empty without filters)
+    pub(super) fn where_clause(&self) -> Result<String, AppError> {
+        let mut conditions = Vec::new();
+        if !self.types.is_empty() {
+            conditions.push("record_type IN $types");
+        }
+        if !self.sources.is_empty() {
+            conditions.push("source IN $sources");
+        }
+        if !self.tags.is_empty() {
+            conditions.push("metadata.tags CONTAINSANY $tags");
+        }
+        if !self.status.is_empty() {
+            conditions.push("metadata.status IN $status");
+        }
+        if let Some(range) = &self.date_range {
+            if range.from.is_some() {
+                conditions.push("timestamp >= $from");
+            }
+            if range.to.is_some() {
+                conditions.push("timestamp <= $to");
+            }
+        }
+
+        let mut conditions: Vec<String> = conditions.into_iter().map(String::from).collect();
+        for (index, condition) in self.conditions.iter().enumerate() {
+            conditions.push(format!(
+                "{} {} $c{}",
+                validate_field_path(&condition.field)?,
+                condition.op.operator(),
+                index
+            ));
+        }
+
+        Ok(if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        })
+    }
+
+    /// Bind the parameters used by `where_clause`
+    pub(super) fn bind_filters<'r, C: Connection>(&self, query: Query<'r, C>) -> Query<'r, C> {
+        let range = self.date_range.clone().unwrap_or_default();
+        let mut query = query
+            .bind(("types", self.types.clone()))
+            .bind(("sources", self.sources.clone()))
+            .bind(("tags", self.tags.clone()))
+            .bind(("status", self.status.clone()))
+            .bind(("from", range.from))
+            .bind(("to", range.to));
+        for (index, condition) in self.conditions.iter().enumerate() {
+            query = query.bind((format!("c{}", index), condition.value.clone()));
+        }
+        query
+    }
+
+    /// Check field paths and sort fields without running the query
+    pub fn validate(&self) -> Result<(), AppError> {
+        self.compile().map(|_| ())
+    }
+
+    /// Build the WHERE and ORDER BY clauses
+    fn compile(&self) -> Result<(String, String), AppError> {
+        let where_clause = self.where_clause()?;
+
+        let order = if self.sort.is_empty() {
+            "timestamp DESC".to_string()
+        } else {
+            self.sort
+                .iter()
+                .map(|s| {
+                    validate_field_path(&s.field).map(|field| {
+                        format!("{} {}", field, if s.descending { "DESC" } else { "ASC" })
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .join(", ")
+        };
+
+        Ok((where_clause, order))
+    }
+}
+
+/// Sort and group fields are interpolated into the query, so only plain record paths are allowed
+pub(super) fn validate_field_path(field: &str) -> Result<&str, AppError> {
+    let top_level = field.split('.').next().unwrap_or_default();
+    let valid = matches!(
+        top_level,
+        "record_type" | "source" | "timestamp" | "metadata" | "data"
+    ) && field.split('.').all(|part| {
+        !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+
+    if valid {
+        Ok(field)
+    } else {
+        Err(AppError::Validation(format!("Invalid field: {}", field)))
+    }
+}
+
+// ============================================================================
+// Query Operations
+// ============================================================================
+
+impl Database {
+    /// Run a structured record query
+    pub async fn query_records(&self, query: RecordQuery) -> Result<RecordPage, AppError> {
+        let (where_clause, order) = query.compile()?;
+        let statement = format!(
+            "SELECT * FROM records{where_clause} ORDER BY {order} LIMIT $limit START $offset;
+             SELECT count() AS total FROM records{where_clause} GROUP ALL;"
+        );
+
+        let started = Instant::now();
+        let result = query
+            .bind_filters(self.query(statement))
+            .bind((
+                "limit",
+                query
+                    .limit
+                    .unwrap_or(DEFAULT_QUERY_LIMIT)
+                    .min(MAX_QUERY_LIMIT),
+            ))
+            .bind(("offset", query.offset.unwrap_or(0)))
+            .await;
+        metrics::observe_since(
+            "db_query_duration_ms",
+            &[("operation", "query_records")],
+            started,
+        );
+        let mut result =
+            result.map_err(|e| AppError::Database(format!("Failed to query records: {}", e)))?;
+
+        let records: Vec<StagedRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract records: {}", e)))?;
+        let total: Option<usize> = result
+            .take((1, "total"))
+            .map_err(|e| AppError::Database(format!("Failed to count records: {}", e)))?;
+
+        Ok(RecordPage {
+            total: total.unwrap_or(0),
+            records,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_query() {
+        let query = RecordQuery {
+            types: vec!["ticket".to_string()],
+            status: vec!["open".to_string()],
+            sort: vec![SortSpec {
+                field: "data.priority".to_string(),
+                descending: true,
+            }],
+            ..Default::default()
+        };
+        let (where_clause, order) = query.compile().unwrap();
+        assert_eq!(
+            where_clause,
+            " WHERE record_type IN $types AND metadata.status IN $status"
+        );
+        assert_eq!(order, "data.priority DESC");
+
+        let query = RecordQuery {
+            conditions: vec![FieldCondition {
+                field: "data.duration".to_string(),
+                op: ConditionOp::Gte,
+                value: serde_json::json!(60),
+            }],
+            ..Default::default()
+        };
+        let (where_clause, _) = query.compile().unwrap();
+        assert_eq!(where_clause, " WHERE data.duration >= $c0");
+
+        let (where_clause, order) = RecordQuery::default().compile().unwrap();
+        assert!(where_clause.is_empty());
+        assert_eq!(order, "timestamp DESC");
+
+        for field in ["id; DELETE records", "data.", "other.field", "data.a-b"] {
+            let query = RecordQuery {
+                sort: vec![SortSpec {
+                    field: field.to_string(),
+                    descending: false,
+                }],
+                ..Default::default()
+            };
+            assert!(query.compile().is_err(), "{} should be rejected", field);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// Record relations
+//
+// Links records to each other (and to tickets) with SurrealDB graph edges in
+// the `links` table, e.g. a pipeline record connected to the merge request
+// that triggered it.
+
+use super::Database;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// Tables that can take part in a relation
+const LINKABLE_TABLES: &[&str] = &["records", "tickets"];
+
+/// Edge as stored in database (with Thing IDs)
+#[derive(Debug, Clone, Deserialize)]
+struct LinkRecord {
+    id: Thing,
+    #[serde(rename = "in")]
+    from: Thing,
+    out: Thing,
+    relation: String,
+    created_at: DateTime<Utc>,
+}
+
+/// A relation seen from one of its ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedItem {
+    pub link_id: String,
+    pub relation: String,
+    /// "outgoing" when the queried item is the source of the link
+    pub direction: String,
+    /// ID of the item on the other end ("records:..." or "tickets:...")
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Thing IDs with special characters (or a leading digit) are displayed as ⟨key⟩
+fn unescape_key(key: &str) -> &str {
+    key.trim_start_matches('⟨').trim_end_matches('⟩')
+}
+
+/// Parse "records:<id>", "tickets:<id>" or a bare record id
+fn parse_linkable(id: &str) -> Result<Thing, AppError> {
+    match id.split_once(':') {
+        Some((table, key)) if LINKABLE_TABLES.contains(&table) => {
+            Ok(Thing::from((table, unescape_key(key))))
+        }
+        Some((table, _))
+            if !table.is_empty()
+                && table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+        {
+            Err(AppError::Validation(format!(
+                "Cannot link items of table '{}'",
+                table
+            )))
+        }
+        _ => Ok(Thing::from(("records", id))),
+    }
+}
+
+impl Database {
+    /// Link two items with a named relation (a → b)
+    /// Returns the link id; linking the same pair twice returns the existing link
+    pub async fn link_records(&self, a: &str, b: &str, relation: &str) -> Result<String, AppError> {
+        let relation = relation.trim();
+        if relation.is_empty() {
+            return Err(AppError::Validation(
+                "Relation name cannot be empty".to_string(),
+            ));
+        }
+        let (from, to) = (parse_linkable(a)?, parse_linkable(b)?);
+
+        let mut result = self
+            .query(
+                "BEGIN TRANSACTION;
+                 IF !record::exists($from) OR !record::exists($to) {
+                     THROW 'Both items must exist to be linked';
+                 };
+                 LET $existing = (SELECT VALUE id FROM links WHERE in = $from AND out = $to AND relation = $relation);
+                 LET $link = IF array::len($existing) > 0 {
+                     $existing[0]
+                 } ELSE {
+                     (RELATE $from->links->$to SET relation = $relation, created_at = time::now() RETURN VALUE id)[0]
+                 };
+                 COMMIT TRANSACTION;
+                 RETURN $link;",
+            )
+            .bind(("from", from))
+            .bind(("to", to))
+            .bind(("relation", relation.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to link records: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to link records: {}", e)))?;
+
+        let last = result.num_statements() - 1;
+        let link: Option<Thing> = result
+            .take(last)
+            .map_err(|e| AppError::Database(format!("Failed to link records: {}", e)))?;
+
+        link.map(|l| l.to_string())
+            .ok_or_else(|| AppError::Database("Failed to link records".to_string()))
+    }
+
+    /// Remove a link by its id ("links:<id>" or bare id)
+    pub async fn unlink_records(&self, link_id: &str) -> Result<(), AppError> {
+        let key = unescape_key(link_id.strip_prefix("links:").unwrap_or(link_id));
+        self.query("DELETE $link")
+            .bind(("link", Thing::from(("links", key))))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to unlink records: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to unlink records: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Get all items linked to or from an item, newest links first
+    pub async fn get_related(&self, id: &str) -> Result<Vec<RelatedItem>, AppError> {
+        let thing = parse_linkable(id)?;
+
+        let mut result = self
+            .query("SELECT * FROM links WHERE in = $id OR out = $id ORDER BY created_at DESC")
+            .bind(("id", thing.clone()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query relations: {}", e)))?;
+
+        let links: Vec<LinkRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract relations: {}", e)))?;
+
+        Ok(links
+            .into_iter()
+            .map(|link| {
+                let outgoing = link.from == thing;
+                RelatedItem {
+                    link_id: link.id.to_string(),
+                    relation: link.relation,
+                    direction: if outgoing { "outgoing" } else { "incoming" }.to_string(),
+                    id: if outgoing { link.out } else { link.from }.to_string(),
+                    created_at: link.created_at,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_link_and_get_related() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let mut ids = Vec::new();
+        for (record_type, id) in [("gitlab_pipeline", 1), ("gitlab_mr", 2)] {
+            let record = StagedRecord::new(
+                record_type.to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "id": id }),
+            );
+            let stored = db.upsert_record(record).await.unwrap();
+            ids.push(stored.id.unwrap().to_string());
+        }
+
+        let link = db
+            .link_records(&ids[0], &ids[1], "triggered_by")
+            .await
+            .unwrap();
+        // Linking again is idempotent
+        assert_eq!(
+            db.link_records(&ids[0], &ids[1], "triggered_by")
+                .await
+                .unwrap(),
+            link
+        );
+        assert!(db
+            .link_records(&ids[0], "records:missing", "triggered_by")
+            .await
+            .is_err());
+
+        let related = db.get_related(&ids[1]).await.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].direction, "incoming");
+        assert_eq!(related[0].id, ids[0]);
+
+        db.unlink_records(&link).await.unwrap();
+        assert!(db.get_related(&ids[0]).await.unwrap().is_empty());
+    }
+}
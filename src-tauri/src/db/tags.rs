@@ -0,0 +1,175 @@
+// Tag management
+//
+// Lists the tags used across staged records and edits `metadata.tags` in
+// bulk (add, remove, rename), which adapters otherwise only ever write.
+
+use super::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use surrealdb::sql::Thing;
+
+/// A tag and the number of records carrying it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+impl Database {
+    /// List all tags in use, most used first
+    pub async fn list_tags(&self) -> Result<Vec<TagCount>, AppError> {
+        let mut result = self
+            .query("RETURN array::flatten(SELECT VALUE metadata.tags FROM records)")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to list tags: {}", e)))?;
+
+        let all_tags: Vec<String> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract tags: {}", e)))?;
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for tag in all_tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+
+        let mut tags: Vec<TagCount> = counts
+            .into_iter()
+            .map(|(tag, count)| TagCount { tag, count })
+            .collect();
+        tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+        Ok(tags)
+    }
+
+    /// Add tags to the given records, skipping tags they already have
+    /// Returns the number of records updated
+    pub async fn add_tags(&self, ids: Vec<String>, tags: Vec<String>) -> Result<usize, AppError> {
+        self.update_tags(
+            ids,
+            tags,
+            "metadata.tags = array::union(metadata.tags ?? [], $tags)",
+        )
+        .await
+    }
+
+    /// Remove tags from the given records
+    /// Returns the number of records updated
+    pub async fn remove_tags(
+        &self,
+        ids: Vec<String>,
+        tags: Vec<String>,
+    ) -> Result<usize, AppError> {
+        self.update_tags(
+            ids,
+            tags,
+            "metadata.tags = array::complement(metadata.tags ?? [], $tags)",
+        )
+        .await
+    }
+
+    async fn update_tags(
+        &self,
+        ids: Vec<String>,
+        tags: Vec<String>,
+        assignment: &str,
+    ) -> Result<usize, AppError> {
+        let things: Vec<Thing> = ids
+            .iter()
+            .map(|id| Thing::from(("records", Self::normalize_record_id(id))))
+            .collect();
+
+        let mut result = self
+            .query(format!("UPDATE $ids SET {} RETURN VALUE id", assignment))
+            .bind(("ids", things))
+            .bind(("tags", tags))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update tags: {}", e)))?;
+
+        let updated: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract updated records: {}", e)))?;
+
+        Ok(updated.len())
+    }
+
+    /// Rename a tag on every record carrying it (merging with an existing tag)
+    /// Returns the number of records updated
+    pub async fn rename_tag(&self, from: &str, to: &str) -> Result<usize, AppError> {
+        let to = to.trim();
+        if to.is_empty() {
+            return Err(AppError::Validation(
+                "New tag name cannot be empty".to_string(),
+            ));
+        }
+
+        let mut result = self
+            .query(
+                "UPDATE records
+                 SET metadata.tags = array::union(array::complement(metadata.tags, [$from]), [$to])
+                 WHERE metadata.tags CONTAINS $from
+                 RETURN VALUE id",
+            )
+            .bind(("from", from.to_string()))
+            .bind(("to", to.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to rename tag: {}", e)))?;
+
+        let updated: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract updated records: {}", e)))?;
+
+        tracing::info!(
+            "Renamed tag '{}' to '{}' on {} records",
+            from,
+            to,
+            updated.len()
+        );
+        Ok(updated.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_tag_management() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let mut ids = Vec::new();
+        for (i, tags) in [vec!["ci"], vec!["ci", "prod"], vec![]].iter().enumerate() {
+            let mut record = StagedRecord::new(
+                "rest_api".to_string(),
+                "tags".to_string(),
+                serde_json::json!({ "id": i }),
+            );
+            record.metadata.tags = tags.iter().map(|t| t.to_string()).collect();
+            let stored = db.upsert_record(record).await.unwrap();
+            ids.push(stored.id.unwrap().to_string());
+        }
+
+        let tags = db.list_tags().await.unwrap();
+        assert_eq!(tags[0].tag, "ci");
+        assert_eq!(tags[0].count, 2);
+
+        let added = db
+            .add_tags(ids.clone(), vec!["prod".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(added, 3);
+
+        assert_eq!(db.rename_tag("prod", "ci").await.unwrap(), 3);
+        let tags = db.list_tags().await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].count, 3);
+
+        db.remove_tags(ids[..1].to_vec(), vec!["ci".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(db.list_tags().await.unwrap()[0].count, 2);
+    }
+}
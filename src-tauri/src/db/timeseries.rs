@@ -0,0 +1,284 @@
+// Time series for chart widgets
+//
+// Charts over months of records shouldn't ship every record to the webview.
+// A time series is either bucketed (the metric per hour/day/week/month, as
+// computed by the aggregation) or the raw values of a numeric field, ordered
+// by time. Either way, when there are more points than the chart asks for,
+// they are downsampled with Largest-Triangle-Three-Buckets, which keeps the
+// first and last point and the visually significant peaks and dips.
+
+use super::aggregate::{AggregateQuery, Metric, TimeBucket};
+use super::query::RecordQuery;
+use super::Database;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Points returned without an explicit maximum
+const DEFAULT_MAX_POINTS: usize = 1000;
+
+/// Fewest points worth downsampling to (first, one per bucket, last)
+const MIN_MAX_POINTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeseriesPoint {
+    /// Record timestamp, or the start of the bucket
+    pub time: DateTime<Utc>,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timeseries {
+    pub points: Vec<TimeseriesPoint>,
+    /// Number of points before downsampling
+    pub total_points: usize,
+    pub downsampled: bool,
+}
+
+/// Indexes of the points kept when reducing `points` (x ascending) to
+/// `threshold` with Largest-Triangle-Three-Buckets
+fn lttb(points: &[(f64, f64)], threshold: usize) -> Vec<usize> {
+    if threshold >= points.len() || threshold < MIN_MAX_POINTS {
+        return (0..points.len()).collect();
+    }
+
+    let mut kept = vec![0];
+    // Points between the first and the last, split into threshold - 2 buckets
+    let bucket_size = (points.len() - 2) as f64 / (threshold - 2) as f64;
+    let mut previous = 0;
+    for bucket in 0..threshold - 2 {
+        let start = (bucket as f64 * bucket_size) as usize + 1;
+        let end = (((bucket + 1) as f64 * bucket_size) as usize + 1).min(points.len() - 1);
+
+        // Average of the next bucket (the last point for the last bucket)
+        let next_start = end;
+        let next_end = (((bucket + 2) as f64 * bucket_size) as usize + 1).min(points.len());
+        let next = &points[next_start..next_end.max(next_start + 1)];
+        let (avg_x, avg_y) = next
+            .iter()
+            .fold((0.0, 0.0), |(x, y), point| (x + point.0, y + point.1));
+        let (avg_x, avg_y) = (avg_x / next.len() as f64, avg_y / next.len() as f64);
+
+        let (ax, ay) = points[previous];
+        let chosen = (start..end.max(start + 1))
+            .max_by(|&a, &b| {
+                let area = |index: usize| {
+                    let (x, y) = points[index];
+                    ((ax - avg_x) * (y - ay) - (ax - x) * (avg_y - ay)).abs()
+                };
+                area(a).total_cmp(&area(b))
+            })
+            .unwrap_or(start);
+        kept.push(chosen);
+        previous = chosen;
+    }
+    kept.push(points.len() - 1);
+    kept
+}
+
+/// Reduce points to at most `max_points`; points without a value are
+/// dropped when reducing, since they have no place in a triangle
+fn downsample(points: Vec<TimeseriesPoint>, max_points: usize) -> Timeseries {
+    let total_points = points.len();
+    let max_points = max_points.max(MIN_MAX_POINTS);
+    if total_points <= max_points {
+        return Timeseries {
+            points,
+            total_points,
+            downsampled: false,
+        };
+    }
+
+    let points: Vec<TimeseriesPoint> = points.into_iter().filter(|p| p.value.is_some()).collect();
+    let coordinates: Vec<(f64, f64)> = points
+        .iter()
+        .map(|p| {
+            (
+                p.time.timestamp_millis() as f64,
+                p.value.unwrap_or_default(),
+            )
+        })
+        .collect();
+    let points = lttb(&coordinates, max_points)
+        .into_iter()
+        .map(|index| points[index].clone())
+        .collect();
+    Timeseries {
+        points,
+        total_points,
+        downsampled: true,
+    }
+}
+
+impl Database {
+    /// Metric per time bucket, or the raw values of the metric's field
+    /// without a bucket, downsampled to at most `max_points`
+    pub async fn get_timeseries(
+        &self,
+        filters: RecordQuery,
+        bucket: Option<TimeBucket>,
+        metric: Metric,
+        max_points: Option<usize>,
+    ) -> Result<Timeseries, AppError> {
+        let points = match bucket {
+            Some(bucket) => self.bucketed_points(filters, bucket, metric).await?,
+            None => self.raw_points(filters, &metric).await?,
+        };
+        Ok(downsample(points, max_points.unwrap_or(DEFAULT_MAX_POINTS)))
+    }
+
+    async fn bucketed_points(
+        &self,
+        filters: RecordQuery,
+        bucket: TimeBucket,
+        metric: Metric,
+    ) -> Result<Vec<TimeseriesPoint>, AppError> {
+        let result = self
+            .aggregate_records(AggregateQuery {
+                group_by: Vec::new(),
+                metric,
+                time_bucket: Some(bucket),
+                filters,
+            })
+            .await?;
+        let Some(series) = result.series.into_iter().next() else {
+            return Ok(Vec::new());
+        };
+        Ok(result
+            .labels
+            .iter()
+            .zip(series.values)
+            .filter_map(|(label, value)| {
+                Some(TimeseriesPoint {
+                    time: bucket.range(label)?.from?,
+                    value,
+                })
+            })
+            .collect())
+    }
+
+    async fn raw_points(
+        &self,
+        filters: RecordQuery,
+        metric: &Metric,
+    ) -> Result<Vec<TimeseriesPoint>, AppError> {
+        let Some(field) = metric.field()? else {
+            return Err(AppError::Validation(
+                "Counting records needs a time bucket".to_string(),
+            ));
+        };
+
+        let mut where_clause = filters.where_clause()?;
+        where_clause.push_str(if where_clause.is_empty() {
+            " WHERE "
+        } else {
+            " AND "
+        });
+        where_clause.push_str(&format!("type::is::number({})", field));
+        let statement = format!(
+            "SELECT timestamp, {} AS value FROM records{} ORDER BY timestamp ASC",
+            field, where_clause
+        );
+
+        let mut result = filters
+            .bind_filters(self.query(statement))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query time series: {}", e)))?;
+        let rows: Vec<serde_json::Value> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract time series: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(TimeseriesPoint {
+                    time: serde_json::from_value(row.get("timestamp")?.clone()).ok()?,
+                    value: row.get("value").and_then(|v| v.as_f64()),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use chrono::{Duration, TimeZone};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lttb() {
+        let mut points: Vec<(f64, f64)> = (0..100).map(|x| (x as f64, 1.0)).collect();
+        points[42].1 = 50.0;
+        let kept = lttb(&points, 10);
+        assert_eq!(kept.len(), 10);
+        assert_eq!(kept.first(), Some(&0));
+        assert_eq!(kept.last(), Some(&99));
+        // The spike survives
+        assert!(kept.contains(&42));
+        assert!(kept.windows(2).all(|w| w[0] < w[1]));
+
+        assert_eq!(lttb(&points[..5], 10), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_get_timeseries() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        for hour in 0..72 {
+            let mut record = StagedRecord::new(
+                "pipeline".to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "id": hour, "duration": hour % 24 }),
+            );
+            record.timestamp = start + Duration::hours(hour);
+            db.upsert_record(record).await.unwrap();
+        }
+        let filters = RecordQuery {
+            types: vec!["pipeline".to_string()],
+            ..Default::default()
+        };
+        let duration = Metric::Max {
+            field: "data.duration".to_string(),
+        };
+
+        let raw = db
+            .get_timeseries(filters.clone(), None, duration.clone(), Some(20))
+            .await
+            .unwrap();
+        assert_eq!(raw.total_points, 72);
+        assert!(raw.downsampled);
+        assert_eq!(raw.points.len(), 20);
+        assert_eq!(raw.points[0].time, start);
+        assert_eq!(raw.points[19].time, start + Duration::hours(71));
+
+        let daily = db
+            .get_timeseries(filters.clone(), Some(TimeBucket::Day), duration, None)
+            .await
+            .unwrap();
+        assert!(!daily.downsampled);
+        assert_eq!(daily.points.len(), 3);
+        assert_eq!(daily.points[1].time, start + Duration::days(1));
+        assert_eq!(daily.points[1].value, Some(23.0));
+
+        let counts = db
+            .get_timeseries(
+                filters.clone(),
+                Some(TimeBucket::Hour),
+                Metric::Count,
+                Some(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(counts.total_points, 72);
+        assert_eq!(counts.points.len(), 10);
+
+        assert!(db
+            .get_timeseries(filters, None, Metric::Count, None)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,594 @@
+// Widget data
+//
+// Dashboard widgets declare what they display as a query spec: record
+// filters (types, sources, tags, status, date range, field conditions), an
+// optional aggregation, a limit and the fields they read. The spec is
+// evaluated here, so a widget receives only its rows or its series instead
+// of fetching pages of staged records and filtering them in the webview.
+//
+// Counter, gauge and trend widgets compute a single metric over the
+// filtered records (optionally per time bucket and compared with the
+// previous period of the same length) and get a compact result object.
+//
+// Query specs of dashboard panels can reference dashboard variables as
+// `${name}` (e.g. `"sources": "${project}"`); they are substituted before the
+// spec is parsed, so one dashboard can be pointed at another project,
+// environment or date range without editing its widgets.
+
+use super::aggregate::{AggregateQuery, AggregateResult, Metric, TimeBucket};
+use super::drilldown::DrilldownLink;
+use super::query::{validate_field_path, DateRange, RecordQuery};
+use super::Database;
+use crate::error::AppError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Scale of a gauge without explicit bounds
+const DEFAULT_GAUGE_MAX: f64 = 100.0;
+
+/// Values of dashboard variables by name
+pub type Variables = BTreeMap<String, serde_json::Value>;
+
+/// Text of a variable value inside a longer string
+fn variable_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Substitute `${name}` references in a query spec. A string that is just a
+/// reference takes the variable's value as is (so a list of sources or a date
+/// range object can fill a filter); references inside a longer string are
+/// replaced by the value's text.
+pub fn apply_variables(
+    spec: &mut serde_json::Value,
+    variables: &Variables,
+) -> Result<(), AppError> {
+    let lookup = |name: &str| {
+        variables
+            .get(name)
+            .ok_or_else(|| AppError::Validation(format!("Unknown dashboard variable: {}", name)))
+    };
+
+    match spec {
+        serde_json::Value::String(text) => {
+            if let Some(name) = text
+                .strip_prefix("${")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .filter(|name| !name.contains('}'))
+            {
+                let value = lookup(name)?.clone();
+                *spec = value;
+                return Ok(());
+            }
+            let mut result = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${") {
+                let Some(end) = rest[start..].find('}') else {
+                    break;
+                };
+                result.push_str(&rest[..start]);
+                result.push_str(&variable_text(lookup(&rest[start + 2..start + end])?));
+                rest = &rest[start + end + 1..];
+            }
+            result.push_str(rest);
+            *text = result;
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                apply_variables(item, variables)?;
+            }
+        }
+        serde_json::Value::Object(fields) => {
+            for value in fields.values_mut() {
+                apply_variables(value, variables)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Parse a query spec after substituting the dashboard variables it references
+pub fn resolve_widget_query(
+    mut spec: serde_json::Value,
+    variables: &Variables,
+) -> Result<WidgetQuery, AppError> {
+    apply_variables(&mut spec, variables)?;
+    serde_json::from_value(spec)
+        .map_err(|e| AppError::Validation(format!("Invalid widget query: {}", e)))
+}
+
+/// Query spec of a dashboard widget
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WidgetQuery {
+    /// Record filters, sort and limit
+    pub filters: RecordQuery,
+    /// Aggregate the matching records instead of returning them
+    pub aggregate: Option<WidgetAggregation>,
+    /// Field paths returned per record, e.g. "metadata.title" (all when empty)
+    pub fields: Vec<String>,
+    /// Compute a counter, gauge or trend instead (takes precedence over `aggregate`)
+    pub computed: Option<ComputedWidget>,
+    /// Where clicking the widget leads (its records by default)
+    pub drilldown: Option<DrilldownLink>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WidgetAggregation {
+    pub group_by: Vec<String>,
+    pub metric: Metric,
+    pub time_bucket: Option<TimeBucket>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComputedKind {
+    #[default]
+    Counter,
+    Gauge,
+    Trend,
+}
+
+/// Single metric computed over the widget's records
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComputedWidget {
+    pub kind: ComputedKind,
+    pub metric: Metric,
+    /// Only records of the last this many hours (instead of the filters' date range)
+    pub period_hours: Option<u32>,
+    /// Also compute the metric over the period of the same length just before
+    pub compare_previous: bool,
+    /// Bucket size of a trend's points (defaults to days)
+    pub time_bucket: Option<TimeBucket>,
+    /// Scale of a gauge (defaults to 0..100)
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendPoint {
+    pub label: String,
+    pub value: Option<f64>,
+}
+
+/// Compact result of a counter, gauge or trend widget
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComputedResult {
+    pub value: Option<f64>,
+    /// Value over the previous period, when compared
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<f64>,
+    /// Change relative to the previous value (none when that was 0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_percent: Option<f64>,
+    /// Gauge only: bounds and where the value sits between them (0..1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratio: Option<f64>,
+    /// Trend only: one point per time bucket, oldest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub points: Vec<TrendPoint>,
+}
+
+/// Evaluated widget query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WidgetData {
+    Records {
+        /// Matching records, of which `records` is the first page
+        total: usize,
+        records: Vec<serde_json::Value>,
+    },
+    Series(AggregateResult),
+    Computed(ComputedResult),
+}
+
+/// Copy the `fields` of a record (plus its id) into a new object of the same shape
+pub(super) fn project(record: serde_json::Value, fields: &[String]) -> serde_json::Value {
+    if fields.is_empty() {
+        return record;
+    }
+    let mut projected = serde_json::json!({});
+    if let Some(id) = record.get("id") {
+        projected["id"] = id.clone();
+    }
+    for field in fields {
+        let path: Vec<&str> = field.split('.').collect();
+        let Some(value) = path.iter().try_fold(&record, |value, part| value.get(part)) else {
+            continue;
+        };
+        let mut target = &mut projected;
+        for part in &path[..path.len() - 1] {
+            target = target
+                .as_object_mut()
+                .map(|object| object.entry(*part).or_insert_with(|| serde_json::json!({})))
+                .expect("projected values are objects");
+        }
+        target[path[path.len() - 1]] = value.clone();
+    }
+    projected
+}
+
+/// Date range a computed widget covers and, when compared, the period before it
+pub(super) fn computed_periods(
+    widget: &ComputedWidget,
+    range: Option<&DateRange>,
+    now: DateTime<Utc>,
+) -> Result<(Option<DateRange>, Option<DateRange>), AppError> {
+    let current = match widget.period_hours {
+        Some(hours) => Some(DateRange {
+            from: Some(now - Duration::hours(i64::from(hours))),
+            to: Some(now),
+        }),
+        None => range.cloned(),
+    };
+    if !widget.compare_previous {
+        return Ok((current, None));
+    }
+
+    let Some((from, to)) = current
+        .as_ref()
+        .and_then(|range| Some((range.from?, range.to.unwrap_or(now))))
+    else {
+        return Err(AppError::Validation(
+            "Comparing with the previous period needs a period or a date range with a start"
+                .to_string(),
+        ));
+    };
+    let previous = DateRange {
+        from: Some(from - (to - from)),
+        to: Some(from - Duration::milliseconds(1)),
+    };
+    Ok((current, Some(previous)))
+}
+
+/// Difference to the previous value, absolute and in percent
+fn change(value: Option<f64>, previous: Option<f64>) -> (Option<f64>, Option<f64>) {
+    match (value, previous) {
+        (Some(value), Some(previous)) => (
+            Some(value - previous),
+            (previous != 0.0).then(|| (value - previous) / previous.abs() * 100.0),
+        ),
+        _ => (None, None),
+    }
+}
+
+/// Where a gauge value sits between its bounds, clamped to 0..1
+fn gauge_ratio(value: Option<f64>, min: f64, max: f64) -> Option<f64> {
+    let value = value?;
+    (max > min).then(|| ((value - min) / (max - min)).clamp(0.0, 1.0))
+}
+
+impl Database {
+    /// Evaluate the query spec of a widget
+    pub async fn get_widget_data(&self, query: WidgetQuery) -> Result<WidgetData, AppError> {
+        if let Some(widget) = query.computed {
+            return Ok(WidgetData::Computed(
+                self.compute_widget(widget, query.filters).await?,
+            ));
+        }
+        if let Some(aggregation) = query.aggregate {
+            let result = self
+                .aggregate_records(AggregateQuery {
+                    group_by: aggregation.group_by,
+                    metric: aggregation.metric,
+                    time_bucket: aggregation.time_bucket,
+                    filters: query.filters,
+                })
+                .await?;
+            return Ok(WidgetData::Series(result));
+        }
+
+        for field in &query.fields {
+            validate_field_path(field)?;
+        }
+        let page = self.query_records(query.filters).await?;
+        let records = page
+            .records
+            .into_iter()
+            .map(|record| Ok(project(serde_json::to_value(record)?, &query.fields)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        Ok(WidgetData::Records {
+            total: page.total,
+            records,
+        })
+    }
+
+    /// Counter, gauge or trend over the filtered records
+    async fn compute_widget(
+        &self,
+        widget: ComputedWidget,
+        filters: RecordQuery,
+    ) -> Result<ComputedResult, AppError> {
+        let (current, previous) =
+            computed_periods(&widget, filters.date_range.as_ref(), Utc::now())?;
+        let in_range = |date_range: Option<DateRange>| RecordQuery {
+            date_range,
+            ..filters.clone()
+        };
+
+        let value = self
+            .metric_value(&widget.metric, in_range(current.clone()))
+            .await?;
+        let previous = match previous {
+            Some(range) => {
+                self.metric_value(&widget.metric, in_range(Some(range)))
+                    .await?
+            }
+            None => None,
+        };
+        let (change, change_percent) = change(value, previous);
+        let mut result = ComputedResult {
+            value,
+            previous,
+            change,
+            change_percent,
+            ..Default::default()
+        };
+
+        match widget.kind {
+            ComputedKind::Counter => {}
+            ComputedKind::Gauge => {
+                let min = widget.min.unwrap_or(0.0);
+                let max = widget.max.unwrap_or(DEFAULT_GAUGE_MAX);
+                result.min = Some(min);
+                result.max = Some(max);
+                result.ratio = gauge_ratio(value, min, max);
+            }
+            ComputedKind::Trend => {
+                let series = self
+                    .aggregate_records(AggregateQuery {
+                        group_by: Vec::new(),
+                        metric: widget.metric.clone(),
+                        time_bucket: Some(widget.time_bucket.unwrap_or(TimeBucket::Day)),
+                        filters: in_range(current),
+                    })
+                    .await?;
+                let values = series
+                    .series
+                    .into_iter()
+                    .next()
+                    .map(|series| series.values)
+                    .unwrap_or_default();
+                result.points = series
+                    .labels
+                    .into_iter()
+                    .zip(values)
+                    .map(|(label, value)| TrendPoint { label, value })
+                    .collect();
+            }
+        }
+        Ok(result)
+    }
+
+    /// Metric over all matching records (0 for counts and sums without records)
+    pub(crate) async fn metric_value(
+        &self,
+        metric: &Metric,
+        filters: RecordQuery,
+    ) -> Result<Option<f64>, AppError> {
+        let result = self
+            .aggregate_records(AggregateQuery {
+                group_by: Vec::new(),
+                metric: metric.clone(),
+                time_bucket: None,
+                filters,
+            })
+            .await?;
+        Ok(result
+            .series
+            .first()
+            .and_then(|series| series.values.first().copied().flatten())
+            .or_else(|| metric.empty_value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::query::{ConditionOp, FieldCondition};
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_apply_variables() {
+        let variables = Variables::from([
+            ("project".to_string(), serde_json::json!("api")),
+            (
+                "sources".to_string(),
+                serde_json::json!(["gitlab", "github"]),
+            ),
+            ("limit".to_string(), serde_json::json!(5)),
+            (
+                "range".to_string(),
+                serde_json::json!({ "from": "2024-01-01T00:00:00Z", "to": null }),
+            ),
+        ]);
+
+        let query = resolve_widget_query(
+            serde_json::json!({
+                "filters": {
+                    "sources": "${sources}",
+                    "tags": ["project:${project}", "limit-${limit}"],
+                    "date_range": "${range}",
+                    "limit": "${limit}"
+                }
+            }),
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(query.filters.sources, vec!["gitlab", "github"]);
+        assert_eq!(query.filters.tags, vec!["project:api", "limit-5"]);
+        assert_eq!(query.filters.limit, Some(5));
+        assert!(query.filters.date_range.unwrap().from.is_some());
+
+        // Unknown variables and specs that don't fit after substitution are rejected
+        let mut unknown = serde_json::json!({ "filters": { "types": ["${kind}"] } });
+        assert!(apply_variables(&mut unknown, &variables).is_err());
+        let mismatched = serde_json::json!({ "filters": { "limit": "${project}" } });
+        assert!(resolve_widget_query(mismatched, &variables).is_err());
+        // Text without complete references is left alone
+        let mut plain = serde_json::json!("costs in $ and ${");
+        apply_variables(&mut plain, &variables).unwrap();
+        assert_eq!(plain, "costs in $ and ${");
+    }
+
+    #[test]
+    fn test_computed_helpers() {
+        assert_eq!(change(Some(15.0), Some(10.0)), (Some(5.0), Some(50.0)));
+        assert_eq!(change(Some(3.0), Some(0.0)), (Some(3.0), None));
+        assert_eq!(change(Some(3.0), None), (None, None));
+
+        assert_eq!(gauge_ratio(Some(25.0), 0.0, 100.0), Some(0.25));
+        assert_eq!(gauge_ratio(Some(150.0), 0.0, 100.0), Some(1.0));
+        assert_eq!(gauge_ratio(Some(1.0), 5.0, 5.0), None);
+
+        let now = Utc::now();
+        let widget = ComputedWidget {
+            period_hours: Some(24),
+            compare_previous: true,
+            ..Default::default()
+        };
+        let (current, previous) = computed_periods(&widget, None, now).unwrap();
+        assert_eq!(current.unwrap().from, Some(now - Duration::hours(24)));
+        assert_eq!(previous.unwrap().from, Some(now - Duration::hours(48)));
+    }
+
+    #[tokio::test]
+    async fn test_widget_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        for (i, (env, status)) in [("prod", "failed"), ("prod", "success"), ("dev", "failed")]
+            .iter()
+            .enumerate()
+        {
+            let mut record = StagedRecord::new(
+                "pipeline".to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "id": i, "env": env, "ref": "main" }),
+            );
+            record.metadata.status = Some(status.to_string());
+            db.upsert_record(record).await.unwrap();
+        }
+        let prod = RecordQuery {
+            types: vec!["pipeline".to_string()],
+            conditions: vec![FieldCondition {
+                field: "data.env".to_string(),
+                op: ConditionOp::Eq,
+                value: serde_json::json!("prod"),
+            }],
+            limit: Some(1),
+            ..Default::default()
+        };
+
+        let data = db
+            .get_widget_data(WidgetQuery {
+                filters: prod.clone(),
+                fields: vec!["data.env".to_string(), "metadata.status".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let WidgetData::Records { total, records } = data else {
+            panic!("expected records");
+        };
+        assert_eq!(total, 2);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["data"], serde_json::json!({ "env": "prod" }));
+        assert!(records[0]["metadata"]["status"].is_string());
+        assert!(records[0].get("source").is_none());
+
+        let data = db
+            .get_widget_data(WidgetQuery {
+                filters: prod,
+                aggregate: Some(WidgetAggregation {
+                    group_by: vec!["metadata.status".to_string()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let WidgetData::Series(series) = data else {
+            panic!("expected series");
+        };
+        assert_eq!(series.labels, vec!["failed", "success"]);
+        assert_eq!(series.series[0].values, vec![Some(1.0), Some(1.0)]);
+
+        // Failed pipelines now and in the previous day
+        let failed = RecordQuery {
+            status: vec!["failed".to_string()],
+            ..Default::default()
+        };
+        let data = db
+            .get_widget_data(WidgetQuery {
+                filters: failed.clone(),
+                computed: Some(ComputedWidget {
+                    period_hours: Some(24),
+                    compare_previous: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let WidgetData::Computed(counter) = data else {
+            panic!("expected a computed result");
+        };
+        assert_eq!(counter.value, Some(2.0));
+        assert_eq!(counter.previous, Some(0.0));
+        assert_eq!(counter.change, Some(2.0));
+        assert_eq!(counter.change_percent, None);
+
+        let data = db
+            .get_widget_data(WidgetQuery {
+                filters: failed.clone(),
+                computed: Some(ComputedWidget {
+                    kind: ComputedKind::Trend,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let WidgetData::Computed(trend) = data else {
+            panic!("expected a computed result");
+        };
+        assert_eq!(trend.points.len(), 1);
+        assert_eq!(trend.points[0].value, Some(2.0));
+
+        // Comparing needs a period
+        assert!(db
+            .get_widget_data(WidgetQuery {
+                filters: failed,
+                computed: Some(ComputedWidget {
+                    compare_previous: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .is_err());
+
+        assert!(db
+            .get_widget_data(WidgetQuery {
+                fields: vec!["data.x; DELETE records".to_string()],
+                ..Default::default()
+            })
+            .await
+            .is_err());
+    }
+}
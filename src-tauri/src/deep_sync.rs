@@ -0,0 +1,504 @@
+// GitLab deep sync
+//
+// Host-side orchestrator for the gitlab-adapter plugin. Instead of expecting
+// the plugin to walk every project, pipeline and job in a single call, the
+// host drives the traversal one resource at a time, follows the plugin's
+// continuation tokens, paces requests to stay under GitLab's rate limits and
+// only asks for data updated since the last successful sync.
+
+use crate::adapters::AdapterConfig;
+use crate::audit::Actor;
+use crate::db::{Database, StagedRecord};
+use crate::error::{AppError, HttpFailure};
+use crate::fetcher;
+use crate::plugins::{Plugin, PluginFetchPage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use surrealdb::sql::Thing;
+use tokio::sync::Mutex;
+
+/// Resources that accept an `updated_after` since-cursor
+const INCREMENTAL_RESOURCES: &[&str] = &["pipelines", "merge_requests"];
+
+// ============================================================================
+// Options
+// ============================================================================
+
+/// Deep sync settings, read from `parameters.deep_sync`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeepSyncOptions {
+    /// Per-project resources to sync: "pipelines", "jobs", "merge_requests"
+    pub resources: Vec<String>,
+
+    /// Projects to sync; when empty they are discovered via the "projects" resource
+    pub project_ids: Vec<Value>,
+
+    /// Minimum delay between two plugin calls
+    pub min_request_interval_ms: u64,
+
+    /// Maximum number of pages followed per resource and scope
+    pub max_pages: usize,
+
+    /// Retries for calls that hit the rate limit
+    pub max_retries: u32,
+
+    /// Ignore the since-cursor and fetch everything
+    pub full: bool,
+}
+
+impl Default for DeepSyncOptions {
+    fn default() -> Self {
+        Self {
+            resources: vec![
+                "pipelines".to_string(),
+                "jobs".to_string(),
+                "merge_requests".to_string(),
+            ],
+            project_ids: Vec::new(),
+            min_request_interval_ms: 250,
+            max_pages: 20,
+            max_retries: 3,
+            full: false,
+        }
+    }
+}
+
+/// Whether a source is configured for host-orchestrated deep sync
+pub fn is_enabled(config: &AdapterConfig) -> bool {
+    config.parameters["sync_mode"].as_str() == Some("deep")
+}
+
+fn options(config: &AdapterConfig) -> Result<DeepSyncOptions, AppError> {
+    match config.parameters.get("deep_sync") {
+        Some(value) if !value.is_null() => serde_json::from_value(value.clone())
+            .map_err(|e| AppError::Config(format!("Invalid deep_sync options: {}", e))),
+        _ => Ok(DeepSyncOptions::default()),
+    }
+}
+
+// ============================================================================
+// Orchestration
+// ============================================================================
+
+/// Run a deep sync for a source and store the records
+/// The since-cursor only advances when the whole sync succeeded. Callers
+/// look the plugin up beforehand, so the plugin manager isn't locked while
+/// the sync pages and waits out rate limits.
+pub async fn sync_and_store(
+    plugin: Option<&dyn Plugin>,
+    database: &Mutex<Database>,
+    config: &AdapterConfig,
+) -> Result<usize, AppError> {
+    let plugin = plugin.ok_or_else(|| {
+        AppError::Plugin(format!(
+            "Deep sync requires a plugin for adapter type: {}",
+            config.adapter_type
+        ))
+    })?;
+
+    let options = options(config)?;
+    let since = if options.full {
+        None
+    } else {
+        database
+            .lock()
+            .await
+            .get_sync_cursor(&config.source)
+            .await?
+    };
+
+    let started_at = Utc::now();
+    let records = sync(plugin, config, options, since).await?;
+    let records = fetcher::transform_records(config, records);
+    let stored = fetcher::store_records(
+        database,
+        records,
+        config.unique_key.as_deref(),
+        Actor::plugin(&config.adapter_type),
+    )
+    .await?;
+
+    database
+        .lock()
+        .await
+        .set_sync_cursor(&config.source, started_at)
+        .await?;
+
+    Ok(stored)
+}
+
+/// Walk projects → pipelines → jobs and merge requests through the plugin
+pub async fn sync(
+    plugin: &dyn Plugin,
+    config: &AdapterConfig,
+    options: DeepSyncOptions,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<StagedRecord>, AppError> {
+    let mut run = DeepSyncRun {
+        plugin,
+        config,
+        options,
+        since,
+        last_request: None,
+    };
+
+    tracing::info!(
+        "Deep sync for '{}' (since: {})",
+        config.source,
+        since.map_or("beginning".to_string(), |s| s.to_rfc3339())
+    );
+
+    let mut records = Vec::new();
+
+    let project_ids = if run.options.project_ids.is_empty() {
+        let projects = run.fetch_all("projects", &[]).await?;
+        let ids = record_ids(&projects);
+        records.extend(projects);
+        ids
+    } else {
+        run.options.project_ids.clone()
+    };
+
+    let wants = |resource: &str| run.options.resources.iter().any(|r| r == resource);
+    let (wants_pipelines, wants_jobs, wants_mrs) = (
+        wants("pipelines") || wants("jobs"),
+        wants("jobs"),
+        wants("merge_requests"),
+    );
+
+    for project_id in project_ids {
+        let project_scope = [("project_id", project_id.clone())];
+
+        if wants_pipelines {
+            let pipelines = run.fetch_all("pipelines", &project_scope).await?;
+
+            if wants_jobs {
+                for pipeline_id in record_ids(&pipelines) {
+                    let scope = [
+                        ("project_id", project_id.clone()),
+                        ("pipeline_id", pipeline_id),
+                    ];
+                    records.extend(run.fetch_all("jobs", &scope).await?);
+                }
+            }
+
+            records.extend(pipelines);
+        }
+
+        if wants_mrs {
+            records.extend(run.fetch_all("merge_requests", &project_scope).await?);
+        }
+    }
+
+    tracing::info!(
+        "Deep sync for '{}' collected {} records",
+        config.source,
+        records.len()
+    );
+    Ok(records)
+}
+
+fn record_ids(records: &[StagedRecord]) -> Vec<Value> {
+    records
+        .iter()
+        .map(|r| r.data["id"].clone())
+        .filter(|id| !id.is_null())
+        .collect()
+}
+
+/// State of a single deep sync run
+struct DeepSyncRun<'a> {
+    plugin: &'a dyn Plugin,
+    config: &'a AdapterConfig,
+    options: DeepSyncOptions,
+    since: Option<DateTime<Utc>>,
+    last_request: Option<Instant>,
+}
+
+impl DeepSyncRun<'_> {
+    /// Fetch every page of a resource within a scope, following continuations
+    async fn fetch_all(
+        &mut self,
+        resource: &str,
+        scope: &[(&str, Value)],
+    ) -> Result<Vec<StagedRecord>, AppError> {
+        let mut records = Vec::new();
+        let mut continuation: Option<Value> = None;
+
+        for _ in 0..self.options.max_pages.max(1) {
+            let config = self.page_config(resource, scope, continuation.take());
+            let page = self.call(&config).await?;
+            records.extend(page.records);
+
+            match page.continuation {
+                Some(next) if !next.is_null() => continuation = Some(next),
+                _ => return Ok(records),
+            }
+        }
+
+        tracing::warn!(
+            "Deep sync for '{}' stopped after {} pages of {}",
+            self.config.source,
+            self.options.max_pages,
+            resource
+        );
+        Ok(records)
+    }
+
+    /// Build the config passed to the plugin for one page
+    fn page_config(
+        &self,
+        resource: &str,
+        scope: &[(&str, Value)],
+        continuation: Option<Value>,
+    ) -> AdapterConfig {
+        let mut config = self.config.clone();
+        if !config.parameters.is_object() {
+            config.parameters = serde_json::json!({});
+        }
+
+        let params = &mut config.parameters;
+        params["resource"] = Value::from(resource);
+        for (key, value) in scope {
+            params[*key] = value.clone();
+        }
+        if let Some(since) = self
+            .since
+            .filter(|_| INCREMENTAL_RESOURCES.contains(&resource))
+        {
+            params["updated_after"] = Value::from(since.to_rfc3339());
+        }
+        if let Some(continuation) = continuation {
+            params["continuation"] = continuation;
+        }
+
+        config
+    }
+
+    /// Call the plugin, pacing requests and backing off when rate limited
+    async fn call(&mut self, config: &AdapterConfig) -> Result<PluginFetchPage, AppError> {
+        let interval = Duration::from_millis(self.options.min_request_interval_ms);
+        let mut attempt = 0;
+
+        loop {
+            if let Some(last) = self.last_request {
+                let elapsed = last.elapsed();
+                if elapsed < interval {
+                    tokio::time::sleep(interval - elapsed).await;
+                }
+            }
+            self.last_request = Some(Instant::now());
+
+            match self.plugin.fetch_page(config).await {
+                Ok(page) => return Ok(page),
+                Err(e) if attempt < self.options.max_retries && is_rate_limited(&e) => {
+                    attempt += 1;
+                    let delay = Duration::from_secs(1 << attempt).max(interval);
+                    tracing::warn!(
+                        "Deep sync for '{}' rate limited, retrying in {:?}",
+                        self.config.source,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+fn is_rate_limited(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::Http {
+            failure: HttpFailure::Status(429),
+            ..
+        }
+    )
+}
+
+// ============================================================================
+// Since-Cursors
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncCursorRecord {
+    pub id: Thing,
+    pub source: String,
+    pub synced_at: DateTime<Utc>,
+}
+
+impl Database {
+    /// Start time of the last successful deep sync for a source
+    pub async fn get_sync_cursor(&self, source: &str) -> Result<Option<DateTime<Utc>>, AppError> {
+        let record: Option<SyncCursorRecord> = self
+            .db
+            .select(("sync_cursors", source))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get sync cursor: {}", e)))?;
+
+        Ok(record.map(|r| r.synced_at))
+    }
+
+    /// Advance the since-cursor of a source
+    pub async fn set_sync_cursor(
+        &self,
+        source: &str,
+        synced_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let record = SyncCursorRecord {
+            id: Thing::from(("sync_cursors", source)),
+            source: source.to_string(),
+            synced_at,
+        };
+
+        let _: Option<SyncCursorRecord> = self
+            .db
+            .upsert(("sync_cursors", source))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to set sync cursor: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Forget the since-cursor so the next deep sync fetches everything
+    pub async fn clear_sync_cursor(&self, source: &str) -> Result<(), AppError> {
+        let _: Option<SyncCursorRecord> = self
+            .db
+            .delete(("sync_cursors", source))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to clear sync cursor: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::{PluginContext, PluginMetadata};
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::Mutex as StdMutex;
+
+    /// Fake GitLab plugin that pages projects and records every call
+    struct FakeGitLab {
+        calls: StdMutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl Plugin for FakeGitLab {
+        fn metadata(&self) -> PluginMetadata {
+            PluginMetadata {
+                name: "gitlab-adapter".to_string(),
+                version: "0.0.0".to_string(),
+                author: String::new(),
+                description: String::new(),
+                adapter_type: Some("gitlab".to_string()),
+                capabilities: Vec::new(),
+                frontend: None,
+            }
+        }
+
+        async fn init(&mut self, _context: PluginContext) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        async fn fetch(&self, _config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError> {
+            unreachable!("deep sync uses fetch_page")
+        }
+
+        async fn fetch_page(&self, config: &AdapterConfig) -> Result<PluginFetchPage, AppError> {
+            let params = config.parameters.clone();
+            self.calls.lock().unwrap().push(params.clone());
+
+            let record = |kind: &str, id: i64| {
+                StagedRecord::new(kind.to_string(), "gitlab".to_string(), json!({ "id": id }))
+            };
+
+            Ok(
+                match (params["resource"].as_str(), params.get("continuation")) {
+                    (Some("projects"), None) => PluginFetchPage {
+                        records: vec![record("project", 1)],
+                        continuation: Some(json!({ "page": 2 })),
+                    },
+                    (Some("projects"), Some(_)) => PluginFetchPage {
+                        records: vec![record("project", 2)],
+                        continuation: None,
+                    },
+                    (Some("pipelines"), _) => PluginFetchPage {
+                        records: vec![record(
+                            "pipeline",
+                            params["project_id"].as_i64().unwrap() * 10,
+                        )],
+                        continuation: None,
+                    },
+                    (Some("jobs"), _) => PluginFetchPage {
+                        records: vec![record("job", 100)],
+                        continuation: None,
+                    },
+                    _ => PluginFetchPage::default(),
+                },
+            )
+        }
+
+        async fn test_connection(&self, _config: &AdapterConfig) -> Result<bool, AppError> {
+            Ok(true)
+        }
+
+        async fn shutdown(&self) -> Result<(), AppError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deep_sync_traversal() {
+        let plugin = FakeGitLab {
+            calls: StdMutex::new(Vec::new()),
+        };
+        let config = AdapterConfig::new("gitlab", "gitlab", "https://gitlab.example.com");
+        let options = DeepSyncOptions {
+            resources: vec!["jobs".to_string()],
+            min_request_interval_ms: 0,
+            ..Default::default()
+        };
+        let since = Utc::now();
+
+        let records = sync(&plugin, &config, options, Some(since)).await.unwrap();
+        let types: Vec<&str> = records.iter().map(|r| r.record_type.as_str()).collect();
+        assert_eq!(
+            types,
+            vec!["project", "project", "job", "pipeline", "job", "pipeline"]
+        );
+
+        let calls = plugin.calls.lock().unwrap();
+        // 2 project pages, then pipelines + jobs for each project
+        assert_eq!(calls.len(), 6);
+        assert_eq!(calls[1]["continuation"], json!({ "page": 2 }));
+        assert_eq!(calls[2]["resource"], "pipelines");
+        assert_eq!(calls[2]["updated_after"], since.to_rfc3339());
+        assert_eq!(calls[3]["resource"], "jobs");
+        assert_eq!(calls[3]["pipeline_id"], 10);
+        assert!(calls[3].get("updated_after").is_none());
+    }
+
+    #[test]
+    fn test_rate_limit_detection() {
+        assert!(is_rate_limited(&AppError::http_status(
+            429,
+            "GitLab API returned 429 Too Many Requests"
+        )));
+        assert!(!is_rate_limited(&AppError::http_status(
+            500,
+            "GitLab API returned 500"
+        )));
+        // Only the status counts, not what the message says
+        assert!(!is_rate_limited(&AppError::Plugin(
+            "Rate limit of 429 projects per page exceeded".to_string()
+        )));
+    }
+}
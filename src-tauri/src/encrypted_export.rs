@@ -0,0 +1,273 @@
+// Encrypted export archives
+//
+// A backup kept on a cloud drive should be unreadable without a password. An
+// encrypted archive holds the database export (records, tickets, pages with
+// their blocks, dashboards, ...), the prompt library and a manifest of the
+// attachment files, gzip-compressed and sealed with Argon2id key derivation
+// and AES-256-GCM like credential bundles. Attachment files themselves aren't
+// included; after an import the manifest tells which ones are missing.
+//
+// File layout: `MAGIC`, the Argon2 salt, then the nonce and ciphertext.
+
+use crate::audit::Actor;
+use crate::db::{Database, ImportStats};
+use crate::error::AppError;
+use crate::vault::{derive_key, open_bytes, seal_bytes, MIN_PASSWORD_LENGTH, SALT_LENGTH};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::path::Path;
+use tokio::sync::Mutex;
+
+/// Start of every archive (format version 1)
+const MAGIC: &[u8] = b"MDLRENC1";
+
+/// Tables exported next to the `export_all_data` ones
+const EXTRA_TABLES: &[&str] = &[
+    "page_blocks",
+    "prompt_sections",
+    "prompt_section_revisions",
+    "prompt_templates",
+    "prompt_data_types",
+    "prompt_tags",
+    "prompt_separator_sets",
+    "prompt_presets",
+    "prompt_chains",
+    "prompt_packages",
+];
+
+/// Rows read per query when exporting the extra tables
+const CHUNK_SIZE: usize = 1000;
+
+/// An attachment file the archive's metadata refers to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentManifestEntry {
+    pub id: String,
+    pub file_name: String,
+    /// File name inside the attachments directory
+    pub stored_name: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedExportSummary {
+    pub path: String,
+    pub size_bytes: u64,
+    /// Rows written per table
+    pub tables: Vec<(String, usize)>,
+    pub attachments: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedImport {
+    pub stats: ImportStats,
+    /// Attachments whose file isn't in this workspace's attachments directory
+    pub missing_attachments: Vec<AttachmentManifestEntry>,
+}
+
+fn attachment_manifest(attachments: &[Value]) -> Vec<AttachmentManifestEntry> {
+    attachments
+        .iter()
+        .filter_map(|row| {
+            Some(AttachmentManifestEntry {
+                id: row["id"].as_str()?.to_string(),
+                file_name: row["file_name"].as_str()?.to_string(),
+                stored_name: row["stored_name"].as_str()?.to_string(),
+                size_bytes: row["size_bytes"].as_u64().unwrap_or(0),
+                sha256: row["sha256"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+async fn table_rows(database: &Mutex<Database>, table: &str) -> Result<Vec<Value>, AppError> {
+    let mut rows = Vec::new();
+    loop {
+        // Lock per chunk so other commands can run during long exports
+        let chunk = database
+            .lock()
+            .await
+            .export_table_chunk_json(table, rows.len(), CHUNK_SIZE)
+            .await?;
+        let done = chunk.len() < CHUNK_SIZE;
+        rows.extend(chunk);
+        if done {
+            return Ok(rows);
+        }
+    }
+}
+
+/// Compress and seal a JSON document with a password
+pub fn seal_archive(document: &Value, password: &str) -> Result<Vec<u8>, AppError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, document)?;
+    let compressed = encoder.finish()?;
+
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let key = derive_key(password, &salt)?;
+    Ok([MAGIC, &salt, &seal_bytes(&key, &compressed)?].concat())
+}
+
+/// Open a sealed archive and parse its JSON document
+pub fn open_archive(bytes: &[u8], password: &str) -> Result<Value, AppError> {
+    let sealed = bytes
+        .strip_prefix(MAGIC)
+        .filter(|rest| rest.len() > SALT_LENGTH)
+        .ok_or_else(|| {
+            AppError::Validation("Not a Modulaur encrypted export archive".to_string())
+        })?;
+    let (salt, sealed) = sealed.split_at(SALT_LENGTH);
+    let key = derive_key(password, salt)?;
+    let compressed = open_bytes(&key, sealed)
+        .map_err(|_| AppError::Validation("Wrong password for the export archive".to_string()))?;
+
+    let mut json = Vec::new();
+    GzDecoder::new(compressed.as_slice()).read_to_end(&mut json)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Write the database, prompt library and attachment manifest to an
+/// encrypted archive at `path`
+pub async fn export_database_encrypted(
+    database: &Mutex<Database>,
+    path: &Path,
+    password: &str,
+) -> Result<EncryptedExportSummary, AppError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::Validation(format!(
+            "The export password needs at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    let mut document = database.lock().await.export_all_data().await?;
+    for table in EXTRA_TABLES {
+        let rows = table_rows(database, table).await?;
+        document["data"][*table] = Value::Array(rows);
+    }
+    let manifest = match &document["data"]["attachments"] {
+        Value::Array(rows) => attachment_manifest(rows),
+        _ => Vec::new(),
+    };
+    document["attachments_manifest"] = serde_json::to_value(&manifest)?;
+
+    let tables = match &document["data"] {
+        Value::Object(data) => data
+            .iter()
+            .map(|(table, rows)| (table.clone(), rows.as_array().map_or(0, Vec::len)))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let archive = seal_archive(&document, password)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::File::create(path)?.write_all(&archive)?;
+
+    tracing::info!(
+        "Wrote encrypted export to {:?} ({} bytes)",
+        path,
+        archive.len()
+    );
+    Ok(EncryptedExportSummary {
+        path: path.to_string_lossy().to_string(),
+        size_bytes: archive.len() as u64,
+        tables,
+        attachments: manifest.len(),
+    })
+}
+
+/// Import an encrypted archive with a merge strategy like `import_database`,
+/// reporting attachments whose files aren't in `attachments_dir`
+pub async fn import_database_encrypted(
+    database: &Mutex<Database>,
+    attachments_dir: &Path,
+    path: &Path,
+    password: &str,
+    merge_strategy: &str,
+) -> Result<EncryptedImport, AppError> {
+    let document = open_archive(&std::fs::read(path)?, password)?;
+
+    let db = database.lock().await;
+    let import = async {
+        let mut stats = db.import_data(document.clone(), merge_strategy).await?;
+        let overwrite = merge_strategy != "skip";
+        for table in EXTRA_TABLES {
+            let Some(rows) = document["data"][*table].as_array() else {
+                continue;
+            };
+            if merge_strategy == "replace" {
+                db.query("DELETE type::table($table)")
+                    .bind(("table", table.to_string()))
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to clear {}: {}", table, e)))?;
+            }
+            for row in rows {
+                let result = db.import_json_row(table, row, overwrite).await;
+                stats.add(table, result);
+            }
+        }
+        Ok::<_, AppError>(stats)
+    };
+    let stats = db
+        .as_actor(Actor::command("import_database_encrypted"), import)
+        .await?;
+    drop(db);
+
+    let manifest: Vec<AttachmentManifestEntry> =
+        serde_json::from_value(document["attachments_manifest"].clone()).unwrap_or_default();
+    let missing_attachments: Vec<AttachmentManifestEntry> = manifest
+        .into_iter()
+        .filter(|entry| !attachments_dir.join(&entry.stored_name).exists())
+        .collect();
+
+    tracing::info!(
+        "Imported encrypted export {:?} ({} attachment files missing)",
+        path,
+        missing_attachments.len()
+    );
+    Ok(EncryptedImport {
+        stats,
+        missing_attachments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let document = json!({
+            "version": "1.0",
+            "data": { "records": [{ "source": "gitlab", "data": { "token": "secret-42" } }] }
+        });
+        let archive = seal_archive(&document, "cloud drive").unwrap();
+        assert!(archive.starts_with(MAGIC));
+        assert!(!String::from_utf8_lossy(&archive).contains("secret-42"));
+
+        assert_eq!(open_archive(&archive, "cloud drive").unwrap(), document);
+        assert!(open_archive(&archive, "wrong password").is_err());
+        assert!(open_archive(b"{\"version\":\"1.0\"}", "cloud drive").is_err());
+    }
+
+    #[test]
+    fn test_attachment_manifest() {
+        let manifest = attachment_manifest(&[
+            json!({ "id": "attachments:a1", "file_name": "plan.pdf", "stored_name": "a1.pdf",
+                    "size_bytes": 2048, "sha256": "ab12" }),
+            json!({ "id": "attachments:broken" }),
+        ]);
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].stored_name, "a1.pdf");
+        assert_eq!(manifest[0].size_bytes, 2048);
+    }
+}
@@ -0,0 +1,276 @@
+// Copying data between environments
+//
+// The embedded database keeps a separate store per environment (`dev/` and
+// `prod/` in the workspace data dir), picked by the build mode. Moving a
+// tuned dashboard or a set of tickets from one to the other used to mean a
+// full export and import. `copy_environment_data` copies the chosen kinds of
+// data directly, row by row with their IDs, so rows that exist on both sides
+// are recognised as conflicts and either kept or overwritten.
+
+use crate::db::{Database, ImportStats};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::sync::Mutex;
+
+/// Rows read from the source database per query
+#[cfg(feature = "embedded-db")]
+const COPY_CHUNK_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Dev,
+    Prod,
+}
+
+impl Environment {
+    /// The environment this build runs in
+    pub fn current() -> Self {
+        if cfg!(debug_assertions) {
+            Environment::Dev
+        } else {
+            Environment::Prod
+        }
+    }
+
+    /// Subdirectory of the data dir holding the environment's database
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Environment::Dev => "dev",
+            Environment::Prod => "prod",
+        }
+    }
+}
+
+/// Kinds of data that can be copied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyEntity {
+    Records,
+    /// Tickets with their history, attachments, sprints and boards
+    Tickets,
+    Pages,
+    DataSources,
+    Settings,
+    /// Dashboards with their versions and snapshots
+    Dashboards,
+    PluginData,
+}
+
+impl CopyEntity {
+    /// Tables of the entity, in copy order (history before the tickets, so
+    /// they don't record a new creation)
+    pub fn tables(self) -> &'static [&'static str] {
+        match self {
+            CopyEntity::Records => &["records"],
+            CopyEntity::Tickets => &[
+                "ticket_events",
+                "tickets",
+                "attachments",
+                "sprints",
+                "boards",
+                "ticket_sequences",
+            ],
+            CopyEntity::Pages => &["pages"],
+            CopyEntity::DataSources => &["data_sources"],
+            CopyEntity::Settings => &["settings"],
+            CopyEntity::Dashboards => &["dashboards", "dashboard_versions", "dashboard_snapshots"],
+            CopyEntity::PluginData => &["plugin_data"],
+        }
+    }
+}
+
+/// What to do with rows that exist in both environments
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictMode {
+    /// Keep the target's row
+    #[default]
+    Skip,
+    /// Replace the target's row with the source's
+    Overwrite,
+}
+
+/// Copy `entities` from one environment's database to the other's
+/// `current` is the open database of the environment the app runs in; the
+/// other environment's database is opened for the copy.
+#[cfg(feature = "embedded-db")]
+pub async fn copy_environment_data(
+    current: &Mutex<Database>,
+    data_dir: &Path,
+    from: Environment,
+    to: Environment,
+    entities: &[CopyEntity],
+    on_conflict: ConflictMode,
+) -> Result<ImportStats, AppError> {
+    if from == to {
+        return Err(AppError::Validation(
+            "Source and target environment are the same".to_string(),
+        ));
+    }
+    if entities.is_empty() {
+        return Err(AppError::Validation("Nothing selected to copy".to_string()));
+    }
+    let current_env = Environment::current();
+    if from != current_env && to != current_env {
+        return Err(AppError::Validation(format!(
+            "The app runs in {}; copy from or to it",
+            current_env.dir_name()
+        )));
+    }
+
+    let other_env = if from == current_env { to } else { from };
+    let other = Database::open_environment(data_dir.to_path_buf(), other_env.dir_name())
+        .await
+        .map_err(|e| {
+            AppError::Database(format!(
+                "Failed to open the {} database (is another instance using it?): {}",
+                other_env.dir_name(),
+                e
+            ))
+        })?;
+    let current = current.lock().await;
+    let (source, target) = if from == current_env {
+        (&*current, &other)
+    } else {
+        (&other, &*current)
+    };
+
+    let mut stats = ImportStats::default();
+    for entity in entities {
+        for table in entity.tables() {
+            let mut start = 0;
+            loop {
+                let rows = source
+                    .export_table_chunk_json(table, start, COPY_CHUNK_SIZE)
+                    .await?;
+                for row in &rows {
+                    let result = target
+                        .import_json_row(table, row, on_conflict == ConflictMode::Overwrite)
+                        .await;
+                    stats.add(table, result);
+                }
+                start += rows.len();
+                if rows.len() < COPY_CHUNK_SIZE {
+                    break;
+                }
+            }
+        }
+    }
+    target.relink_imported_rows(&stats).await?;
+
+    tracing::info!(
+        "Copied {:?} from {} to {}: {} tables, {} errors",
+        entities,
+        from.dir_name(),
+        to.dir_name(),
+        stats.tables.len(),
+        stats.errors.len()
+    );
+    Ok(stats)
+}
+
+/// The sidecar database has no per-environment stores on disk
+#[cfg(feature = "sidecar-db")]
+pub async fn copy_environment_data(
+    _current: &Mutex<Database>,
+    _data_dir: &Path,
+    _from: Environment,
+    _to: Environment,
+    _entities: &[CopyEntity],
+    _on_conflict: ConflictMode,
+) -> Result<ImportStats, AppError> {
+    Err(AppError::Config(
+        "Copying between environments is only available with the embedded database".to_string(),
+    ))
+}
+
+#[cfg(all(test, feature = "embedded-db"))]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    fn record(id: i64, status: &str) -> StagedRecord {
+        StagedRecord::new(
+            "pipeline".to_string(),
+            "gitlab".to_string(),
+            serde_json::json!({ "id": id, "status": status }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_copy_environment_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let current = Environment::current();
+        let other = match current {
+            Environment::Dev => Environment::Prod,
+            Environment::Prod => Environment::Dev,
+        };
+
+        {
+            let other_db =
+                Database::open_environment(temp_dir.path().to_path_buf(), other.dir_name())
+                    .await
+                    .unwrap();
+            other_db.upsert_record(record(1, "success")).await.unwrap();
+            other_db.upsert_record(record(2, "failed")).await.unwrap();
+        }
+        let db = Mutex::new(Database::new(temp_dir.path().to_path_buf()).await.unwrap());
+        db.lock()
+            .await
+            .upsert_record(record(2, "running"))
+            .await
+            .unwrap();
+
+        assert!(copy_environment_data(
+            &db,
+            temp_dir.path(),
+            current,
+            current,
+            &[CopyEntity::Records],
+            ConflictMode::Skip
+        )
+        .await
+        .is_err());
+
+        let stats = copy_environment_data(
+            &db,
+            temp_dir.path(),
+            other,
+            current,
+            &[CopyEntity::Records, CopyEntity::Pages],
+            ConflictMode::Skip,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.records_imported, 1);
+        assert_eq!(stats.tables[0].conflicts, 1);
+        assert!(stats.errors.is_empty());
+        assert_eq!(db.lock().await.count_records().await.unwrap(), 2);
+
+        let stats = copy_environment_data(
+            &db,
+            temp_dir.path(),
+            other,
+            current,
+            &[CopyEntity::Records],
+            ConflictMode::Overwrite,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.records_imported, 2);
+        let statuses: Vec<String> = db
+            .lock()
+            .await
+            .export_table_chunk_json("records", 0, 10)
+            .await
+            .unwrap()
+            .iter()
+            .filter_map(|row| Some(row["data"]["status"].as_str()?.to_string()))
+            .collect();
+        assert!(statuses.contains(&"failed".to_string()));
+        assert!(!statuses.contains(&"running".to_string()));
+    }
+}
@@ -1,39 +1,95 @@
+use crate::i18n;
+use crate::redaction::redact_text;
+use serde::Serialize;
 use thiserror::Error;
 
+/// Error of the app's operations
+/// Messages are shown to the user and logged, so credential values, tokens
+/// and auth headers in them are masked. The kind prefix is in the locale of
+/// the `i18n.locale` setting.
 #[derive(Error, Debug)]
 pub enum AppError {
-    #[error("IO error: {0}")]
+    #[error("{}: {}", i18n::text("error.io"), .0)]
     Io(#[from] std::io::Error),
 
-    #[error("Serialization error: {0}")]
+    #[error("{}: {}", i18n::text("error.serialization"), .0)]
     Serialization(#[from] serde_json::Error),
 
-    #[error("Configuration error: {0}")]
+    #[error("{}: {}", i18n::text("error.config"), redact_text(.0))]
     Config(String),
 
-    #[error("Database error: {0}")]
+    #[error("{}: {}", i18n::text("error.database"), redact_text(.0))]
     Database(String),
 
-    #[error("Adapter error: {0}")]
+    #[error("{}: {}", i18n::text("error.adapter"), redact_text(.0))]
     Adapter(String),
 
-    #[error("HTTP error: {0}")]
-    Http(String),
+    #[error("{}: {}", i18n::text("error.http"), redact_text(.message))]
+    Http {
+        failure: HttpFailure,
+        message: String,
+    },
 
-    #[error("Plugin error: {0}")]
+    #[error("{}: {}", i18n::text("error.plugin"), redact_text(.0))]
     Plugin(String),
 
-    #[error("Validation error: {0}")]
+    #[error("{}: {}", i18n::text("error.validation"), redact_text(.0))]
     Validation(String),
 
-    #[error("Not found: {0}")]
+    #[error("{}: {}", i18n::text("error.not_found"), redact_text(.0))]
     NotFound(String),
 
     #[allow(dead_code)] // Reserved for future error cases
-    #[error("Unknown error")]
+    #[error("{}", i18n::text("error.unknown"))]
     Unknown,
 }
 
+/// How an HTTP request failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpFailure {
+    /// Offline mode refused the request
+    Offline,
+    /// No response in time
+    Timeout,
+    /// The server answered with an error status
+    Status(u16),
+    /// Connection failed, or the response couldn't be read
+    Request,
+}
+
+impl AppError {
+    /// Failed request or response read, classified by what reqwest reports
+    pub fn request(error: &reqwest::Error, message: impl Into<String>) -> Self {
+        let failure = if error.is_timeout() {
+            HttpFailure::Timeout
+        } else if let Some(status) = error.status() {
+            HttpFailure::Status(status.as_u16())
+        } else {
+            HttpFailure::Request
+        };
+        AppError::Http {
+            failure,
+            message: message.into(),
+        }
+    }
+
+    /// The server answered with an error status
+    pub fn http_status(status: u16, message: impl Into<String>) -> Self {
+        AppError::Http {
+            failure: HttpFailure::Status(status),
+            message: message.into(),
+        }
+    }
+
+    /// Request failure without a status, e.g. a response missing a field
+    pub fn http(message: impl Into<String>) -> Self {
+        AppError::Http {
+            failure: HttpFailure::Request,
+            message: message.into(),
+        }
+    }
+}
+
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -42,3 +98,169 @@ impl serde::Serialize for AppError {
         serializer.serialize_str(self.to_string().as_ref())
     }
 }
+
+// ============================================================================
+// Command Errors
+// ============================================================================
+
+/// What went wrong in a command, for the frontend to react to without
+/// parsing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Io,
+    Serialization,
+    Config,
+    Database,
+    Adapter,
+    /// A request failed (connection refused, bad status, unreadable response)
+    Network,
+    Timeout,
+    /// Offline mode refused the request
+    Offline,
+    /// The server rejected the credentials (401/403)
+    Unauthorized,
+    Plugin,
+    Validation,
+    NotFound,
+    Internal,
+}
+
+/// Error returned by the Tauri commands
+/// `message` is what `AppError` displays (localized and redacted); `details`
+/// holds what the code alone doesn't say, e.g. the HTTP status.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Prefix the message, keeping the code and details
+    pub fn context(mut self, context: &str) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Code and details of an `AppError::Http`
+fn http_error(failure: HttpFailure) -> (ErrorCode, Option<serde_json::Value>) {
+    match failure {
+        HttpFailure::Offline => (ErrorCode::Offline, None),
+        HttpFailure::Timeout => (ErrorCode::Timeout, None),
+        HttpFailure::Status(status) => {
+            let code = match status {
+                401 | 403 => ErrorCode::Unauthorized,
+                408 | 504 => ErrorCode::Timeout,
+                _ => ErrorCode::Network,
+            };
+            (code, Some(serde_json::json!({ "status": status })))
+        }
+        HttpFailure::Request => (ErrorCode::Network, None),
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(error: AppError) -> Self {
+        let (code, details) = match &error {
+            AppError::Io(_) => (ErrorCode::Io, None),
+            AppError::Serialization(_) => (ErrorCode::Serialization, None),
+            AppError::Config(_) => (ErrorCode::Config, None),
+            AppError::Database(_) => (ErrorCode::Database, None),
+            AppError::Adapter(_) => (ErrorCode::Adapter, None),
+            AppError::Http { failure, .. } => http_error(*failure),
+            AppError::Plugin(_) => (ErrorCode::Plugin, None),
+            AppError::Validation(_) => (ErrorCode::Validation, None),
+            AppError::NotFound(_) => (ErrorCode::NotFound, None),
+            AppError::Unknown => (ErrorCode::Internal, None),
+        };
+        Self {
+            code,
+            message: error.to_string(),
+            details,
+        }
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(error: std::io::Error) -> Self {
+        AppError::from(error).into()
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::from(error).into()
+    }
+}
+
+/// Messages of code paths that don't go through `AppError`
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, redact_text(&message))
+    }
+}
+
+impl From<&str> for ApiError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_codes() {
+        let error = ApiError::from(AppError::NotFound("Plugin 'jira' not found".to_string()));
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert!(error.message.ends_with("Plugin 'jira' not found"));
+
+        let offline = AppError::Http {
+            failure: HttpFailure::Offline,
+            message: "Offline mode is on".to_string(),
+        };
+        assert_eq!(ApiError::from(offline).code, ErrorCode::Offline);
+        let rejected = ApiError::from(AppError::http_status(
+            401,
+            "REST API returned error status: 401 Unauthorized",
+        ));
+        assert_eq!(rejected.code, ErrorCode::Unauthorized);
+        assert_eq!(rejected.details, Some(serde_json::json!({ "status": 401 })));
+        let failed = ApiError::from(AppError::http_status(500, "S3 request failed"));
+        assert_eq!(failed.code, ErrorCode::Network);
+        assert_eq!(failed.details, Some(serde_json::json!({ "status": 500 })));
+        let timed_out = AppError::Http {
+            failure: HttpFailure::Timeout,
+            message: "REST request failed".to_string(),
+        };
+        assert_eq!(ApiError::from(timed_out).code, ErrorCode::Timeout);
+        // A status in the message alone doesn't count
+        let unreachable = ApiError::from(AppError::http("Failed to fetch http://host/api/401"));
+        assert_eq!(unreachable.code, ErrorCode::Network);
+        assert_eq!(unreachable.details, None);
+
+        let value = serde_json::to_value(ApiError::from("Something broke")).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "code": "internal", "message": "Something broke" })
+        );
+    }
+}
@@ -0,0 +1,148 @@
+// Application event bus
+//
+// Services publish what happened to a process-wide bus: records were stored,
+// a ticket moved to another column, a plugin was loaded, unloaded or
+// quarantined, a fetch finished, an alert fired. Background tasks subscribe
+// to it (alert evaluation follows the finished fetches, webhooks forward
+// events), and the bridge forwards every event to the frontend as a Tauri
+// event named after it (`records://upserted`, `tickets://moved`,
+// `plugins://loaded`, `plugins://unloaded`, `plugins://quarantined`,
+// `fetch://finished`, `alerts://fired`), so views update when something
+// changes instead of polling commands. Live queries (`live`) report single
+// row changes; these say what happened.
+//
+// Publishing never blocks or fails: without subscribers an event is dropped,
+// and a subscriber falling more than `EVENT_CAPACITY` events behind misses the
+// oldest ones.
+
+use crate::alerts::FiredAlert;
+use crate::fetcher::FetchSummary;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Events a slow subscriber can fall behind by before missing some
+const EVENT_CAPACITY: usize = 256;
+
+static BUS: OnceLock<broadcast::Sender<AppEvent>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AppEvent {
+    /// Records of a source were stored (by a fetch, an import or a command)
+    RecordsUpserted {
+        source: String,
+        record_types: Vec<String>,
+        count: usize,
+    },
+    TicketMoved {
+        ticket_id: String,
+        board_id: String,
+        from_status: String,
+        to_status: String,
+    },
+    PluginLoaded {
+        name: String,
+        version: String,
+    },
+    PluginUnloaded {
+        name: String,
+    },
+    /// A plugin was set aside because it failed to load
+    PluginQuarantined {
+        name: String,
+        reason: String,
+    },
+    FetchFinished(FetchSummary),
+    AlertFired(FiredAlert),
+}
+
+impl AppEvent {
+    /// Name of the Tauri event the bridge emits
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::RecordsUpserted { .. } => "records://upserted",
+            AppEvent::TicketMoved { .. } => "tickets://moved",
+            AppEvent::PluginLoaded { .. } => "plugins://loaded",
+            AppEvent::PluginUnloaded { .. } => "plugins://unloaded",
+            AppEvent::PluginQuarantined { .. } => "plugins://quarantined",
+            AppEvent::FetchFinished(_) => "fetch://finished",
+            AppEvent::AlertFired(_) => "alerts://fired",
+        }
+    }
+}
+
+fn bus() -> &'static broadcast::Sender<AppEvent> {
+    BUS.get_or_init(|| broadcast::channel(EVENT_CAPACITY).0)
+}
+
+/// Announce an event to the subscribers and the frontend
+pub fn publish(event: AppEvent) {
+    // No subscribers is fine
+    let _ = bus().send(event);
+}
+
+/// Receive every event published from now on
+pub fn subscribe() -> broadcast::Receiver<AppEvent> {
+    bus().subscribe()
+}
+
+/// Forward the bus to the frontend as Tauri events
+pub fn start_bridge(app: AppHandle) {
+    let mut events = subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = app.emit(event.name(), &event) {
+                        tracing::warn!("Failed to emit {}: {}", event.name(), e);
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Event bridge fell behind, {} events not forwarded", missed)
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_bus() {
+        let mut events = subscribe();
+        let moved = AppEvent::TicketMoved {
+            ticket_id: "tickets:bus-1".to_string(),
+            board_id: "default".to_string(),
+            from_status: "todo".to_string(),
+            to_status: "done".to_string(),
+        };
+        publish(moved.clone());
+
+        // Other tests publish to the same bus
+        let received = std::iter::from_fn(|| events.try_recv().ok())
+            .find(|event| *event == moved)
+            .unwrap();
+        assert_eq!(received.name(), "tickets://moved");
+        assert_eq!(
+            serde_json::to_value(&received).unwrap()["kind"],
+            "ticket_moved"
+        );
+
+        let finished = AppEvent::FetchFinished(FetchSummary {
+            source: "gitlab".to_string(),
+            adapter_type: "gitlab".to_string(),
+            records: 3,
+            duration_ms: 120,
+            error: None,
+        });
+        let json = serde_json::to_value(&finished).unwrap();
+        assert_eq!(json["kind"], "fetch_finished");
+        assert_eq!(json["records"], 3);
+    }
+}
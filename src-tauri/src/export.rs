@@ -0,0 +1,307 @@
+// Record export
+//
+// Writes the records matching a `RecordQuery` to CSV, NDJSON or Parquet so
+// staged data can be analyzed in Excel or DuckDB. CSV and Parquet flatten each
+// record into fixed columns plus the selected `data` fields; NDJSON keeps the
+// full record on one line.
+
+use crate::db::query::RecordQuery;
+use crate::db::{Database, StagedRecord};
+use crate::error::AppError;
+use arrow_array::{ArrayRef, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Columns every flattened export starts with
+const BASE_COLUMNS: &[&str] = &[
+    "id",
+    "record_type",
+    "source",
+    "timestamp",
+    "title",
+    "status",
+    "tags",
+];
+
+/// Records fetched per query while exporting
+const EXPORT_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+/// Result of an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub format: ExportFormat,
+    pub records: usize,
+    /// Column names (empty for NDJSON)
+    pub columns: Vec<String>,
+}
+
+/// Export the records matching `query` to `path`
+/// `fields` selects the `data` fields (dotted paths) flattened into columns;
+/// by default all top-level data fields are included
+pub async fn export_records(
+    db: &Database,
+    format: ExportFormat,
+    query: RecordQuery,
+    path: &Path,
+    fields: Option<Vec<String>>,
+) -> Result<ExportSummary, AppError> {
+    let records = collect_records(db, query).await?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let columns = match format {
+        ExportFormat::Ndjson => {
+            write_ndjson(path, &records)?;
+            Vec::new()
+        }
+        ExportFormat::Csv | ExportFormat::Parquet => {
+            let data_fields = fields.unwrap_or_else(|| data_columns(&records));
+            let mut columns: Vec<String> = BASE_COLUMNS.iter().map(|c| c.to_string()).collect();
+            columns.extend(data_fields.iter().map(|f| format!("data.{}", f)));
+
+            let rows: Vec<Vec<Option<String>>> =
+                records.iter().map(|r| flatten(r, &data_fields)).collect();
+
+            if format == ExportFormat::Csv {
+                write_csv(path, &columns, &rows)?;
+            } else {
+                write_parquet(path, &columns, &rows)?;
+            }
+            columns
+        }
+    };
+
+    tracing::info!(
+        "Exported {} records as {:?} to {:?}",
+        records.len(),
+        format,
+        path
+    );
+
+    Ok(ExportSummary {
+        path: path.to_string_lossy().to_string(),
+        format,
+        records: records.len(),
+        columns,
+    })
+}
+
+/// Page through all records matching the query (honoring its limit/offset)
+async fn collect_records(db: &Database, query: RecordQuery) -> Result<Vec<StagedRecord>, AppError> {
+    let mut records = Vec::new();
+    let mut offset = query.offset.unwrap_or(0);
+
+    loop {
+        let wanted = match query.limit {
+            Some(limit) => EXPORT_PAGE_SIZE.min(limit - records.len()),
+            None => EXPORT_PAGE_SIZE,
+        };
+        if wanted == 0 {
+            break;
+        }
+
+        let page = db
+            .query_records(RecordQuery {
+                limit: Some(wanted),
+                offset: Some(offset),
+                ..query.clone()
+            })
+            .await?;
+        let fetched = page.records.len();
+        records.extend(page.records);
+        offset += fetched;
+
+        if fetched < wanted {
+            break;
+        }
+    }
+
+    Ok(records)
+}
+
+/// Top-level data fields across all records, in first-seen order
+fn data_columns(records: &[StagedRecord]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut columns = Vec::new();
+    for record in records {
+        if let Some(object) = record.data.as_object() {
+            for key in object.keys() {
+                if seen.insert(key.clone()) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+    columns
+}
+
+/// Flatten a record into cells (base columns, then the data fields)
+fn flatten(record: &StagedRecord, data_fields: &[String]) -> Vec<Option<String>> {
+    let mut row = vec![
+        record.id.as_ref().map(|id| id.to_string()),
+        Some(record.record_type.clone()),
+        Some(record.source.clone()),
+        Some(record.timestamp.to_rfc3339()),
+        record.metadata.title.clone(),
+        record.metadata.status.clone(),
+        Some(record.metadata.tags.join(",")),
+    ];
+
+    for field in data_fields {
+        let value = field
+            .split('.')
+            .try_fold(&record.data, |value, key| value.get(key));
+        row.push(match value {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::String(s)) => Some(s.clone()),
+            Some(other) => Some(other.to_string()),
+        });
+    }
+
+    row
+}
+
+fn write_ndjson(path: &Path, records: &[StagedRecord]) -> Result<(), AppError> {
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_csv(
+    path: &Path,
+    columns: &[String],
+    rows: &[Vec<Option<String>>],
+) -> Result<(), AppError> {
+    let mut writer = csv::Writer::from_path(path).map_err(std::io::Error::from)?;
+    writer.write_record(columns).map_err(std::io::Error::from)?;
+    for row in rows {
+        writer
+            .write_record(row.iter().map(|cell| cell.as_deref().unwrap_or("")))
+            .map_err(std::io::Error::from)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write all columns as nullable UTF-8 strings
+fn write_parquet(
+    path: &Path,
+    columns: &[String],
+    rows: &[Vec<Option<String>>],
+) -> Result<(), AppError> {
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|name| Field::new(name, DataType::Utf8, true))
+            .collect::<Vec<_>>(),
+    ));
+
+    let arrays: Vec<ArrayRef> = (0..columns.len())
+        .map(|i| {
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row[i].as_deref()),
+            )) as ArrayRef
+        })
+        .collect();
+
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(std::io::Error::other)?;
+
+    let file = std::fs::File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, schema, None).map_err(std::io::Error::other)?;
+    writer.write(&batch).map_err(std::io::Error::other)?;
+    writer.close().map_err(std::io::Error::other)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_export_formats() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("data")).await.unwrap();
+
+        for (i, status) in ["success", "failed", "success"].iter().enumerate() {
+            let record = StagedRecord::new(
+                "gitlab_pipeline".to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "id": i, "status": status, "commit": { "ref": "main" } }),
+            );
+            db.upsert_record(record).await.unwrap();
+        }
+
+        let csv_path = temp_dir.path().join("out/records.csv");
+        let summary = export_records(
+            &db,
+            ExportFormat::Csv,
+            RecordQuery::default(),
+            &csv_path,
+            Some(vec!["status".to_string(), "commit.ref".to_string()]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.records, 3);
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.starts_with(
+            "id,record_type,source,timestamp,title,status,tags,data.status,data.commit.ref\n"
+        ));
+        assert_eq!(
+            csv.lines().filter(|l| l.ends_with(",success,main")).count(),
+            2
+        );
+
+        let ndjson_path = temp_dir.path().join("records.ndjson");
+        let query = RecordQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        export_records(&db, ExportFormat::Ndjson, query, &ndjson_path, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&ndjson_path)
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+
+        let parquet_path = temp_dir.path().join("records.parquet");
+        let summary = export_records(
+            &db,
+            ExportFormat::Parquet,
+            RecordQuery::default(),
+            &parquet_path,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(summary.columns.contains(&"data.commit".to_string()));
+        let reader =
+            SerializedFileReader::new(std::fs::File::open(&parquet_path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+    }
+}
@@ -0,0 +1,673 @@
+// Feed subscriptions
+//
+// Subscribed RSS/Atom feeds live in `feed_subscriptions` and their items in
+// `feed_items`, so the feed reader keeps what was read across restarts and
+// panels. The `feeds` job refreshes every subscription whose refresh interval
+// elapsed. Items are keyed by their subscription and guid: an item fetched
+// again is updated in place, keeps its read state and isn't counted as new.
+//
+// Only the newest `MAX_ITEMS_PER_FEED` items of a feed are kept; unsubscribing
+// deletes a feed's items.
+
+use crate::adapters::rss::{self, Enclosure, Feed};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::network;
+use crate::scheduler::{Job, Schedule};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::Mutex;
+
+/// How often the feeds job looks for due subscriptions
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+pub const DEFAULT_REFRESH_MINUTES: u32 = 30;
+
+/// Items kept per subscription, newest first
+pub const MAX_ITEMS_PER_FEED: usize = 200;
+
+// ============================================================================
+// Feed Model
+// ============================================================================
+
+/// Subscription as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FeedSubscriptionRecord {
+    id: Thing,
+    url: String,
+    title: Option<String>,
+    /// Web page of the feed
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+    refresh_minutes: u32,
+    #[serde(default)]
+    last_fetched: Option<DateTime<Utc>>,
+    #[serde(default)]
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+/// User-facing subscription with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedSubscription {
+    pub id: String,
+    pub url: String,
+    /// The title given when subscribing, else the feed's own
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub category: Option<String>,
+    pub refresh_minutes: u32,
+    pub last_fetched: Option<DateTime<Utc>>,
+    /// Error of the last refresh, None when it succeeded
+    pub last_error: Option<String>,
+    pub unread_count: usize,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FeedSubscription {
+    fn from_record(record: FeedSubscriptionRecord, unread_count: usize) -> Self {
+        FeedSubscription {
+            id: record.id.id.to_raw(),
+            url: record.url,
+            title: record.title,
+            link: record.link,
+            category: record.category,
+            refresh_minutes: record.refresh_minutes,
+            last_fetched: record.last_fetched,
+            last_error: record.last_error,
+            unread_count,
+            created_at: record.created_at,
+        }
+    }
+
+    /// Whether the refresh interval elapsed (never fetched feeds are due)
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.last_fetched.is_none_or(|last| {
+            last + Duration::minutes(i64::from(self.refresh_minutes.max(1))) <= now
+        })
+    }
+}
+
+/// Item as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FeedItemRecord {
+    id: Thing,
+    subscription: String,
+    guid: String,
+    title: String,
+    link: Option<String>,
+    author: Option<String>,
+    summary: Option<String>,
+    content: Option<String>,
+    published: Option<DateTime<Utc>>,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    enclosures: Vec<Enclosure>,
+    read: bool,
+    fetched_at: DateTime<Utc>,
+}
+
+/// User-facing feed item with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItem {
+    pub id: String,
+    pub subscription_id: String,
+    pub guid: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub categories: Vec<String>,
+    pub enclosures: Vec<Enclosure>,
+    pub read: bool,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl From<FeedItemRecord> for FeedItem {
+    fn from(record: FeedItemRecord) -> Self {
+        FeedItem {
+            id: record.id.id.to_raw(),
+            subscription_id: record.subscription,
+            guid: record.guid,
+            title: record.title,
+            link: record.link,
+            author: record.author,
+            summary: record.summary,
+            content: record.content,
+            published: record.published,
+            categories: record.categories,
+            enclosures: record.enclosures,
+            read: record.read,
+            fetched_at: record.fetched_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubscribeFeedRequest {
+    pub url: String,
+    /// Overrides the feed's own title
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub refresh_minutes: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FeedItemFilters {
+    /// Items of these subscriptions, all when empty
+    pub subscription_ids: Vec<String>,
+    pub unread_only: bool,
+    pub limit: Option<usize>,
+}
+
+/// Key of an item: the same guid in the same subscription is the same item
+fn item_key(subscription_id: &str, guid: &str) -> String {
+    let digest = Sha256::digest(format!("{}\n{}", subscription_id, guid));
+    hex::encode(&digest[..16])
+}
+
+fn subscription_key(id: &str) -> &str {
+    id.strip_prefix("feed_subscriptions:").unwrap_or(id)
+}
+
+fn item_id_key(id: &str) -> &str {
+    id.strip_prefix("feed_items:").unwrap_or(id)
+}
+
+/// The newest item first; undated items last
+fn newest_first(a: &Option<DateTime<Utc>>, b: &Option<DateTime<Utc>>) -> std::cmp::Ordering {
+    b.cmp(a)
+}
+
+// ============================================================================
+// Feed Operations
+// ============================================================================
+
+impl Database {
+    /// Subscriptions with their unread counts
+    pub async fn list_feed_subscriptions(&self) -> Result<Vec<FeedSubscription>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM feed_subscriptions ORDER BY created_at ASC")
+            .query(
+                "SELECT subscription, count() AS unread FROM feed_items
+                 WHERE read = false GROUP BY subscription",
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query feeds: {}", e)))?;
+
+        #[derive(Deserialize)]
+        struct Unread {
+            subscription: String,
+            unread: usize,
+        }
+        let records: Vec<FeedSubscriptionRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse feeds: {}", e)))?;
+        let unread: HashMap<String, usize> = result
+            .take::<Vec<Unread>>(1)
+            .map_err(|e| AppError::Database(format!("Failed to count unread items: {}", e)))?
+            .into_iter()
+            .map(|u| (u.subscription, u.unread))
+            .collect();
+
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                let count = unread.get(&record.id.id.to_raw()).copied().unwrap_or(0);
+                FeedSubscription::from_record(record, count)
+            })
+            .collect())
+    }
+
+    pub async fn get_feed_subscription(&self, id: &str) -> Result<FeedSubscription, AppError> {
+        self.list_feed_subscriptions()
+            .await?
+            .into_iter()
+            .find(|s| s.id == subscription_key(id))
+            .ok_or_else(|| AppError::NotFound(format!("Feed subscription {}", id)))
+    }
+
+    /// Subscribe to a feed; subscribing to a URL again returns the existing
+    /// subscription
+    pub async fn subscribe_feed(
+        &self,
+        req: SubscribeFeedRequest,
+    ) -> Result<FeedSubscription, AppError> {
+        let url = url::Url::parse(req.url.trim())
+            .map_err(|e| AppError::Validation(format!("Invalid feed URL: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(AppError::Validation(
+                "The feed URL must start with http:// or https://".to_string(),
+            ));
+        }
+        if req.refresh_minutes == Some(0) {
+            return Err(AppError::Validation(
+                "The refresh interval must be at least one minute".to_string(),
+            ));
+        }
+        if let Some(existing) = self
+            .list_feed_subscriptions()
+            .await?
+            .into_iter()
+            .find(|s| s.url == url.as_str())
+        {
+            return Ok(existing);
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let text = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let record = FeedSubscriptionRecord {
+            id: Thing::from(("feed_subscriptions", id.as_str())),
+            url: url.to_string(),
+            title: text(req.title),
+            link: None,
+            category: text(req.category),
+            refresh_minutes: req.refresh_minutes.unwrap_or(DEFAULT_REFRESH_MINUTES),
+            last_fetched: None,
+            last_error: None,
+            created_at: Utc::now(),
+        };
+        let created: Option<FeedSubscriptionRecord> = self
+            .db
+            .create(("feed_subscriptions", id.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to subscribe to feed: {}", e)))?;
+
+        created
+            .map(|record| FeedSubscription::from_record(record, 0))
+            .ok_or_else(|| AppError::Database("Failed to subscribe to feed".to_string()))
+    }
+
+    /// Remove a subscription and its items
+    pub async fn unsubscribe_feed(&self, id: &str) -> Result<(), AppError> {
+        self.query("DELETE type::thing('feed_subscriptions', $key)")
+            .query("DELETE feed_items WHERE subscription = $key")
+            .bind(("key", subscription_key(id).to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to unsubscribe from feed: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to unsubscribe from feed: {}", e)))?;
+        Ok(())
+    }
+
+    /// Store a fetched feed's items, returning how many are new
+    pub async fn store_feed(&self, id: &str, feed: Feed) -> Result<usize, AppError> {
+        let key = subscription_key(id).to_string();
+        let mut result = self
+            .query("SELECT VALUE id FROM feed_items WHERE subscription = $key")
+            .bind(("key", key.clone()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query feed items: {}", e)))?;
+        let existing: HashSet<String> = result
+            .take::<Vec<Thing>>(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse feed items: {}", e)))?
+            .into_iter()
+            .map(|id| id.id.to_raw())
+            .collect();
+
+        let now = Utc::now();
+        let mut new = 0;
+        for item in feed.items {
+            let item_key = item_key(&key, &item.id);
+            let record = FeedItemRecord {
+                id: Thing::from(("feed_items", item_key.as_str())),
+                subscription: key.clone(),
+                guid: item.id,
+                title: item.title,
+                link: item.link,
+                author: item.author,
+                summary: item.summary,
+                content: item.content,
+                published: item.published,
+                categories: item.categories,
+                enclosures: item.enclosures,
+                read: false,
+                fetched_at: now,
+            };
+            if existing.contains(&item_key) {
+                // Fetched again: refresh the content, keep the read state
+                let mut content = serde_json::to_value(&record)?;
+                if let Some(fields) = content.as_object_mut() {
+                    for field in ["id", "read", "fetched_at"] {
+                        fields.remove(field);
+                    }
+                }
+                let _: Option<FeedItemRecord> = self
+                    .db
+                    .update(("feed_items", item_key.as_str()))
+                    .merge(content)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to store feed item: {}", e)))?;
+            } else {
+                let _: Option<FeedItemRecord> = self
+                    .db
+                    .create(("feed_items", item_key.as_str()))
+                    .content(record)
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to store feed item: {}", e)))?;
+                new += 1;
+            }
+        }
+
+        self.query(
+            "UPDATE type::thing('feed_subscriptions', $key) SET
+                     title = title ?? $title,
+                     link = $link,
+                     last_fetched = $now,
+                     last_error = NONE
+                 RETURN NONE",
+        )
+        .bind(("key", key.clone()))
+        .bind(("title", feed.title))
+        .bind(("link", feed.link))
+        .bind(("now", now))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update feed: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to update feed: {}", e)))?;
+
+        self.prune_feed_items(&key).await?;
+        Ok(new)
+    }
+
+    /// Store why a refresh failed (the feed is retried after its interval)
+    pub async fn record_feed_error(&self, id: &str, error: String) -> Result<(), AppError> {
+        self.query(
+            "UPDATE type::thing('feed_subscriptions', $key) SET
+                     last_fetched = $now,
+                     last_error = $error
+                 RETURN NONE",
+        )
+        .bind(("key", subscription_key(id).to_string()))
+        .bind(("now", Utc::now()))
+        .bind(("error", error))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update feed: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to update feed: {}", e)))?;
+        Ok(())
+    }
+
+    /// Drop the oldest items beyond `MAX_ITEMS_PER_FEED`
+    async fn prune_feed_items(&self, key: &str) -> Result<(), AppError> {
+        let mut items = self
+            .get_feed_items(FeedItemFilters {
+                subscription_ids: vec![key.to_string()],
+                ..Default::default()
+            })
+            .await?;
+        if items.len() <= MAX_ITEMS_PER_FEED {
+            return Ok(());
+        }
+        let stale: Vec<String> = items
+            .split_off(MAX_ITEMS_PER_FEED)
+            .into_iter()
+            .map(|item| Thing::from(("feed_items", item.id.as_str())).to_string())
+            .collect();
+        self.query("DELETE feed_items WHERE <string> id IN $stale")
+            .bind(("stale", stale))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to prune feed items: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to prune feed items: {}", e)))?;
+        Ok(())
+    }
+
+    /// Items, newest first
+    pub async fn get_feed_items(
+        &self,
+        filters: FeedItemFilters,
+    ) -> Result<Vec<FeedItem>, AppError> {
+        let mut conditions = Vec::new();
+        if !filters.subscription_ids.is_empty() {
+            conditions.push("subscription IN $subscriptions");
+        }
+        if filters.unread_only {
+            conditions.push("read = false");
+        }
+        let mut query = "SELECT * FROM feed_items".to_string();
+        if !conditions.is_empty() {
+            query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+
+        let subscriptions: Vec<String> = filters
+            .subscription_ids
+            .iter()
+            .map(|id| subscription_key(id).to_string())
+            .collect();
+        let mut result = self
+            .query(query)
+            .bind(("subscriptions", subscriptions))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query feed items: {}", e)))?;
+        let records: Vec<FeedItemRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse feed items: {}", e)))?;
+
+        let mut items: Vec<FeedItem> = records.into_iter().map(FeedItem::from).collect();
+        items.sort_by(|a, b| newest_first(&a.published, &b.published));
+        if let Some(limit) = filters.limit {
+            items.truncate(limit);
+        }
+        Ok(items)
+    }
+
+    pub async fn mark_feed_item_read(&self, id: &str, read: bool) -> Result<FeedItem, AppError> {
+        let updated: Option<FeedItemRecord> = self
+            .db
+            .update(("feed_items", item_id_key(id)))
+            .merge(serde_json::json!({ "read": read }))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update feed item: {}", e)))?;
+        updated
+            .map(FeedItem::from)
+            .ok_or_else(|| AppError::NotFound(format!("Feed item {}", id)))
+    }
+
+    /// Mark every item of a subscription (all subscriptions when None) read,
+    /// returning how many were unread
+    pub async fn mark_feed_read(&self, id: Option<&str>) -> Result<usize, AppError> {
+        let mut result = self
+            .query(
+                "UPDATE feed_items SET read = true
+                 WHERE read = false AND ($key = NONE OR subscription = $key)
+                 RETURN VALUE id",
+            )
+            .bind(("key", id.map(|id| subscription_key(id).to_string())))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update feed items: {}", e)))?;
+        let marked: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to update feed items: {}", e)))?;
+        Ok(marked.len())
+    }
+}
+
+// ============================================================================
+// Refreshing
+// ============================================================================
+
+/// Fetch a subscription's feed and store its items, returning how many are
+/// new. The database is only locked to store the result, not while fetching.
+pub async fn refresh(
+    database: &Arc<Mutex<Database>>,
+    subscription: &FeedSubscription,
+) -> Result<usize, AppError> {
+    let fetched = match network::ensure_online() {
+        Ok(()) => rss::read_feed(network::client().get(&subscription.url)).await,
+        Err(e) => Err(e),
+    };
+    let db = database.lock().await;
+    match fetched {
+        Ok(feed) => db.store_feed(&subscription.id, feed).await,
+        Err(e) => {
+            db.record_feed_error(&subscription.id, e.to_string())
+                .await?;
+            Err(e)
+        }
+    }
+}
+
+/// Refresh the subscriptions whose interval elapsed
+pub fn job(database: Arc<Mutex<Database>>) -> Job {
+    Job::new(
+        "feeds",
+        "Refresh feed subscriptions whose refresh interval elapsed",
+        Schedule::every(TICK_INTERVAL),
+        move || {
+            let database = database.clone();
+            async move {
+                // Offline, due feeds are refreshed once the network is back
+                if network::ensure_online().is_err() {
+                    return Ok("Offline".to_string());
+                }
+                let now = Utc::now();
+                let due: Vec<FeedSubscription> = database
+                    .lock()
+                    .await
+                    .list_feed_subscriptions()
+                    .await?
+                    .into_iter()
+                    .filter(|s| s.is_due(now))
+                    .collect();
+
+                let mut new = 0;
+                let mut failed = 0;
+                for subscription in &due {
+                    match refresh(&database, subscription).await {
+                        Ok(count) => new += count,
+                        Err(e) => {
+                            tracing::warn!("Failed to refresh feed {}: {}", subscription.url, e);
+                            failed += 1;
+                        }
+                    }
+                }
+                if new > 0 {
+                    tracing::info!("Fetched {} new feed items", new);
+                }
+                Ok(format!(
+                    "Refreshed {} feeds ({} failed), {} new items",
+                    due.len(),
+                    failed,
+                    new
+                ))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn feed(guids: &[&str]) -> Feed {
+        Feed {
+            title: Some("Example News".to_string()),
+            link: Some("https://example.com".to_string()),
+            description: None,
+            items: guids
+                .iter()
+                .enumerate()
+                .map(|(i, guid)| rss::FeedItem {
+                    id: guid.to_string(),
+                    title: format!("Item {}", guid),
+                    link: None,
+                    author: None,
+                    summary: None,
+                    content: None,
+                    published: Some(Utc::now() + Duration::minutes(i as i64)),
+                    categories: vec![],
+                    enclosures: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feed_subscriptions() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let subscribe = |url: &str| SubscribeFeedRequest {
+            url: url.to_string(),
+            title: None,
+            category: Some("news".to_string()),
+            refresh_minutes: None,
+        };
+        assert!(db
+            .subscribe_feed(subscribe("ftp://example.com"))
+            .await
+            .is_err());
+        let subscription = db
+            .subscribe_feed(subscribe("https://example.com/feed.xml"))
+            .await
+            .unwrap();
+        assert!(subscription.is_due(Utc::now()));
+        // Subscribing again doesn't duplicate the feed
+        let again = db
+            .subscribe_feed(subscribe("https://example.com/feed.xml"))
+            .await
+            .unwrap();
+        assert_eq!(again.id, subscription.id);
+
+        assert_eq!(
+            db.store_feed(&subscription.id, feed(&["a", "b"]))
+                .await
+                .unwrap(),
+            2
+        );
+        let items = db.get_feed_items(FeedItemFilters::default()).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].guid, "b");
+        db.mark_feed_item_read(&items[0].id, true).await.unwrap();
+
+        // Refetched items are deduplicated by guid and stay read
+        assert_eq!(
+            db.store_feed(&subscription.id, feed(&["a", "b", "c"]))
+                .await
+                .unwrap(),
+            1
+        );
+        let stored = db.get_feed_subscription(&subscription.id).await.unwrap();
+        assert_eq!(stored.title.as_deref(), Some("Example News"));
+        assert_eq!(stored.unread_count, 2);
+        assert!(!stored.is_due(Utc::now()));
+        let unread = db
+            .get_feed_items(FeedItemFilters {
+                unread_only: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(unread.iter().all(|item| item.guid != "b"));
+
+        assert_eq!(db.mark_feed_read(Some(&subscription.id)).await.unwrap(), 2);
+        assert_eq!(
+            db.list_feed_subscriptions().await.unwrap()[0].unread_count,
+            0
+        );
+
+        db.unsubscribe_feed(&subscription.id).await.unwrap();
+        assert!(db.list_feed_subscriptions().await.unwrap().is_empty());
+        assert!(db
+            .get_feed_items(FeedItemFilters::default())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,444 @@
+// Fetch history module
+//
+// Persists one entry per adapter fetch in the `fetch_runs` table so users can
+// see when each data source was last refreshed and why a refresh failed. Only
+// the latest `MAX_RUNS_PER_SOURCE` runs of a source are kept. The
+// status of a single data source combines its last runs with the records it
+// produced and its next run in the polling scheduler.
+
+use crate::data_sources::DataSource;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::polling::PollingStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// Default number of history entries returned per source
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Failed runs kept in a data source status
+pub const MAX_ERROR_HISTORY: usize = 20;
+
+/// Runs kept per source; older ones are deleted when a new run is recorded
+pub const MAX_RUNS_PER_SOURCE: usize = 500;
+
+// ============================================================================
+// Fetch Run Model
+// ============================================================================
+
+/// Fetch run as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FetchRunRecord {
+    pub id: Thing,
+    pub source: String,
+    pub adapter_type: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub record_count: usize,
+    pub error: Option<String>,
+}
+
+/// Run counts of a source, as aggregated by `get_source_status`
+#[derive(Debug, Deserialize)]
+struct RunCounts {
+    source: String,
+    total_runs: usize,
+    failed_runs: usize,
+}
+
+/// User-facing fetch run with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchRun {
+    pub id: String,
+    pub source: String,
+    pub adapter_type: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub record_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<FetchRunRecord> for FetchRun {
+    fn from(record: FetchRunRecord) -> Self {
+        FetchRun {
+            id: record.id.to_string(),
+            source: record.source,
+            adapter_type: record.adapter_type,
+            started_at: record.started_at,
+            duration_ms: record.duration_ms,
+            record_count: record.record_count,
+            error: record.error,
+        }
+    }
+}
+
+/// Latest fetch state of a single source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceStatus {
+    pub source: String,
+    pub adapter_type: String,
+    pub last_run: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_record_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub total_runs: usize,
+    pub failed_runs: usize,
+}
+
+/// Sync status of a single data source, for troubleshooting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataSourceStatus {
+    pub id: String,
+    pub source: String,
+    pub enabled: bool,
+    pub paused: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<DateTime<Utc>>,
+    /// Records produced by the last successful fetch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_record_count: Option<usize>,
+    /// Records currently stored under the source
+    pub total_records: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+    /// Most recent failed runs, newest first
+    pub errors: Vec<FetchRun>,
+}
+
+// ============================================================================
+// Fetch History Operations
+// ============================================================================
+
+impl Database {
+    /// Record the outcome of a fetch
+    pub async fn record_fetch_run(
+        &self,
+        source: &str,
+        adapter_type: &str,
+        started_at: DateTime<Utc>,
+        duration_ms: u64,
+        record_count: usize,
+        error: Option<String>,
+    ) -> Result<(), AppError> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let record = FetchRunRecord {
+            id: Thing::from(("fetch_runs", id.as_str())),
+            source: source.to_string(),
+            adapter_type: adapter_type.to_string(),
+            started_at,
+            duration_ms,
+            record_count,
+            error,
+        };
+
+        let _: Option<FetchRunRecord> = self
+            .db
+            .create(("fetch_runs", id.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to record fetch run: {}", e)))?;
+
+        self.query(
+            "LET $overflow = (SELECT started_at FROM fetch_runs WHERE source = $source
+                     ORDER BY started_at DESC LIMIT 1 START $keep)[0].started_at;
+                 IF $overflow != NONE {
+                     DELETE fetch_runs WHERE source = $source AND started_at <= $overflow;
+                 };",
+        )
+        .bind(("source", source.to_string()))
+        .bind(("keep", MAX_RUNS_PER_SOURCE))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to prune fetch history: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to prune fetch history: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Get the most recent fetch runs for a source, newest first
+    pub async fn get_fetch_history(
+        &self,
+        source: &str,
+        limit: usize,
+    ) -> Result<Vec<FetchRun>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM fetch_runs WHERE source = $source ORDER BY started_at DESC LIMIT $limit")
+            .bind(("source", source.to_string()))
+            .bind(("limit", limit))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query fetch history: {}", e)))?;
+
+        let runs: Vec<FetchRunRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse fetch history: {}", e)))?;
+
+        Ok(runs.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Get the status of a data source; `polling` is its state in the polling
+    /// scheduler, if the polling job has seen it
+    pub async fn get_data_source_status(
+        &self,
+        data_source: &DataSource,
+        polling: Option<PollingStatus>,
+    ) -> Result<DataSourceStatus, AppError> {
+        let mut result = self
+            .query(
+                "SELECT * FROM fetch_runs WHERE source = $source AND error != NONE AND error != NULL
+                 ORDER BY started_at DESC LIMIT $limit;
+                 SELECT * FROM fetch_runs WHERE source = $source AND (error = NONE OR error = NULL)
+                 ORDER BY started_at DESC LIMIT 1;
+                 SELECT count() AS total FROM records WHERE source = $source GROUP ALL;",
+            )
+            .bind(("source", data_source.source.clone()))
+            .bind(("limit", MAX_ERROR_HISTORY))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query source status: {}", e)))?;
+
+        let errors: Vec<FetchRunRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse fetch runs: {}", e)))?;
+        let successes: Vec<FetchRunRecord> = result
+            .take(1)
+            .map_err(|e| AppError::Database(format!("Failed to parse fetch runs: {}", e)))?;
+        let total_records: Option<usize> = result
+            .take((2, "total"))
+            .map_err(|e| AppError::Database(format!("Failed to count records: {}", e)))?;
+
+        let errors: Vec<FetchRun> = errors.into_iter().map(|r| r.into()).collect();
+        let last_success = successes.into_iter().next();
+        let paused = polling.as_ref().is_some_and(|p| p.paused);
+        let scheduled = data_source.enabled
+            && data_source.auto_refresh
+            && data_source.refresh_interval.is_some_and(|secs| secs > 0);
+        // Sources the polling job hasn't run since startup are due at its next check
+        let next_run = (scheduled && !paused)
+            .then(|| polling.and_then(|p| p.next_run).unwrap_or_else(Utc::now));
+
+        Ok(DataSourceStatus {
+            id: data_source.id.clone(),
+            source: data_source.source.clone(),
+            enabled: data_source.enabled,
+            paused,
+            last_success: last_success.as_ref().map(|run| run.started_at),
+            last_record_count: last_success.map(|run| run.record_count),
+            total_records: total_records.unwrap_or(0),
+            last_error: errors.first().and_then(|run| run.error.clone()),
+            last_error_at: errors.first().map(|run| run.started_at),
+            next_run,
+            errors,
+        })
+    }
+
+    /// Get the latest fetch state of every source that has been fetched
+    pub async fn get_source_status(&self) -> Result<Vec<SourceStatus>, AppError> {
+        let mut result = self
+            .query(
+                "SELECT source, count() AS total_runs,
+                     count(error != NONE AND error != NULL) AS failed_runs
+                 FROM fetch_runs GROUP BY source",
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query fetch runs: {}", e)))?;
+
+        let counts: Vec<RunCounts> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse fetch runs: {}", e)))?;
+
+        let mut statuses = Vec::with_capacity(counts.len());
+        for counts in counts {
+            // Both lookups use the (source, started_at) index
+            let mut result = self
+                .query(
+                    "SELECT * FROM fetch_runs WHERE source = $source
+                     ORDER BY started_at DESC LIMIT 1;
+                     SELECT * FROM fetch_runs WHERE source = $source AND (error = NONE OR error = NULL)
+                     ORDER BY started_at DESC LIMIT 1;",
+                )
+                .bind(("source", counts.source.clone()))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to query fetch runs: {}", e)))?;
+
+            let latest: Option<FetchRunRecord> = result
+                .take(0)
+                .map_err(|e| AppError::Database(format!("Failed to parse fetch runs: {}", e)))?;
+            let last_success: Option<FetchRunRecord> = result
+                .take(1)
+                .map_err(|e| AppError::Database(format!("Failed to parse fetch runs: {}", e)))?;
+            let Some(latest) = latest else {
+                continue;
+            };
+
+            statuses.push(SourceStatus {
+                source: counts.source,
+                adapter_type: latest.adapter_type,
+                last_run: latest.started_at,
+                last_success: last_success.map(|run| run.started_at),
+                last_record_count: latest.record_count,
+                last_error: latest.error,
+                total_runs: counts.total_runs,
+                failed_runs: counts.failed_runs,
+            });
+        }
+
+        statuses.sort_by(|a, b| a.source.cmp(&b.source));
+        Ok(statuses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_source_status_tracks_last_success_and_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let now = Utc::now();
+
+        db.record_fetch_run(
+            "gitlab",
+            "rest_api",
+            now - chrono::Duration::minutes(10),
+            120,
+            5,
+            None,
+        )
+        .await
+        .unwrap();
+        db.record_fetch_run(
+            "gitlab",
+            "rest_api",
+            now,
+            80,
+            0,
+            Some("HTTP 401".to_string()),
+        )
+        .await
+        .unwrap();
+
+        let history = db.get_fetch_history("gitlab", 10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].error.as_deref(), Some("HTTP 401"));
+
+        let statuses = db.get_source_status().await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        let status = &statuses[0];
+        assert_eq!(status.last_error.as_deref(), Some("HTTP 401"));
+        assert_eq!(status.last_record_count, 0);
+        assert_eq!(status.total_runs, 2);
+        assert_eq!(status.failed_runs, 1);
+        assert!(status.last_success.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_data_source_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let now = Utc::now();
+
+        let data_source: DataSource = serde_json::from_value(serde_json::json!({
+            "id": "data_sources:ds_gitlab",
+            "name": "GitLab",
+            "adapter_type": "gitlab",
+            "source": "gitlab",
+            "endpoint": "https://gitlab.example.com",
+            "parameters": {},
+            "environment": "both",
+            "enabled": true,
+            "auto_refresh": true,
+            "refresh_interval": 300,
+            "data_ttl_days": 30,
+            "createdAt": now,
+            "updatedAt": now,
+        }))
+        .unwrap();
+
+        for minutes in [30, 20, 10] {
+            db.record_fetch_run(
+                "gitlab",
+                "gitlab",
+                now - chrono::Duration::minutes(minutes),
+                50,
+                if minutes == 30 { 7 } else { 0 },
+                (minutes != 30).then(|| format!("HTTP 502 ({} min ago)", minutes)),
+            )
+            .await
+            .unwrap();
+        }
+
+        let status = db.get_data_source_status(&data_source, None).await.unwrap();
+        assert_eq!(status.last_record_count, Some(7));
+        assert_eq!(
+            status.last_success,
+            Some(now - chrono::Duration::minutes(30))
+        );
+        assert_eq!(status.last_error.as_deref(), Some("HTTP 502 (10 min ago)"));
+        assert_eq!(status.errors.len(), 2);
+        assert_eq!(status.total_records, 0);
+        assert!(!status.paused);
+        assert!(status.next_run.is_some());
+
+        let paused = PollingStatus {
+            source: "gitlab".to_string(),
+            paused: true,
+            in_flight: false,
+            interval_secs: Some(300),
+            last_run: None,
+            next_run: None,
+            last_error: None,
+        };
+        let status = db
+            .get_data_source_status(&data_source, Some(paused))
+            .await
+            .unwrap();
+        assert!(status.paused);
+        assert!(status.next_run.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_history_is_capped_per_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let now = Utc::now();
+
+        for minutes in (0..MAX_RUNS_PER_SOURCE as i64 + 3).rev() {
+            db.record_fetch_run(
+                "gitlab",
+                "rest_api",
+                now - chrono::Duration::minutes(minutes),
+                10,
+                1,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        db.record_fetch_run("jira", "rest_api", now, 10, 1, None)
+            .await
+            .unwrap();
+
+        let history = db
+            .get_fetch_history("gitlab", MAX_RUNS_PER_SOURCE + 10)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), MAX_RUNS_PER_SOURCE);
+        assert_eq!(history[0].started_at, now);
+
+        let statuses = db.get_source_status().await.unwrap();
+        let runs: Vec<(&str, usize)> = statuses
+            .iter()
+            .map(|s| (s.source.as_str(), s.total_runs))
+            .collect();
+        assert_eq!(runs, vec![("gitlab", MAX_RUNS_PER_SOURCE), ("jira", 1)]);
+    }
+}
@@ -0,0 +1,426 @@
+// Global fetch queue
+//
+// All scheduled and bulk fetches go through a single queue that bounds how
+// many adapters run at the same time. Manual fetches are dispatched before
+// scheduled ones, and queued or running fetches can be cancelled per source.
+// Completed fetches are announced on the event bus (`events`).
+
+use crate::adapters::{AdapterConfig, AdapterRegistry};
+use crate::db::Database;
+use crate::events::{self, AppEvent};
+use crate::fetcher::{self, FetchSummary};
+use crate::plugins::PluginManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::AbortHandle;
+
+/// Fetch priority; higher priorities are dispatched first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FetchPriority {
+    Scheduled,
+    Manual,
+}
+
+/// User-facing view of a queued or running fetch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedFetchInfo {
+    pub source: String,
+    pub adapter_type: String,
+    pub priority: FetchPriority,
+    pub queued_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// Snapshot of the queue for introspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchQueueStatus {
+    pub max_concurrency: usize,
+    pub running: Vec<QueuedFetchInfo>,
+    pub pending: Vec<QueuedFetchInfo>,
+}
+
+struct PendingFetch {
+    seq: u64,
+    config: AdapterConfig,
+    priority: FetchPriority,
+    queued_at: DateTime<Utc>,
+    responder: oneshot::Sender<FetchSummary>,
+}
+
+struct RunningFetch {
+    info: QueuedFetchInfo,
+    abort: AbortHandle,
+    responder: Option<oneshot::Sender<FetchSummary>>,
+}
+
+struct QueueState {
+    max_concurrency: usize,
+    next_seq: u64,
+    pending: Vec<PendingFetch>,
+    running: HashMap<String, RunningFetch>,
+}
+
+/// Shared handle to the fetch queue (cheap to clone)
+#[derive(Clone)]
+pub struct FetchQueue {
+    state: Arc<StdMutex<QueueState>>,
+    plugin_manager: Arc<Mutex<PluginManager>>,
+    adapter_registry: Arc<AdapterRegistry>,
+    database: Arc<Mutex<Database>>,
+}
+
+impl FetchQueue {
+    pub fn new(
+        plugin_manager: Arc<Mutex<PluginManager>>,
+        adapter_registry: Arc<AdapterRegistry>,
+        database: Arc<Mutex<Database>>,
+        max_concurrency: usize,
+    ) -> Self {
+        Self {
+            state: Arc::new(StdMutex::new(QueueState {
+                max_concurrency: max_concurrency.max(1),
+                next_seq: 0,
+                pending: Vec::new(),
+                running: HashMap::new(),
+            })),
+            plugin_manager,
+            adapter_registry,
+            database,
+        }
+    }
+
+    /// Queue a fetch and wait for its summary
+    pub async fn run(&self, config: AdapterConfig, priority: FetchPriority) -> FetchSummary {
+        let source = config.source.clone();
+        let adapter_type = config.adapter_type.clone();
+        let receiver = self.submit(config, priority);
+
+        receiver.await.unwrap_or_else(|_| FetchSummary {
+            source,
+            adapter_type,
+            records: 0,
+            duration_ms: 0,
+            error: Some("Fetch was cancelled".to_string()),
+        })
+    }
+
+    /// Queue a fetch, returning a receiver for its summary
+    pub fn submit(
+        &self,
+        config: AdapterConfig,
+        priority: FetchPriority,
+    ) -> oneshot::Receiver<FetchSummary> {
+        let (responder, receiver) = oneshot::channel();
+
+        {
+            let mut state = self.lock_state();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            tracing::debug!("Queued {:?} fetch for source: {}", priority, config.source);
+            state.pending.push(PendingFetch {
+                seq,
+                config,
+                priority,
+                queued_at: Utc::now(),
+                responder,
+            });
+        }
+
+        self.dispatch();
+        receiver
+    }
+
+    /// Cancel all queued and running fetches for a source
+    /// Returns the number of fetches that were cancelled
+    pub fn cancel(&self, source: &str) -> usize {
+        let mut state = self.lock_state();
+        let mut cancelled = 0;
+
+        let (removed, kept) = std::mem::take(&mut state.pending)
+            .into_iter()
+            .partition::<Vec<_>, _>(|p| p.config.source == source);
+        state.pending = kept;
+        cancelled += removed.len();
+        // Dropping the responders resolves the waiting callers as cancelled
+        drop(removed);
+
+        if let Some(running) = state.running.remove(source) {
+            running.abort.abort();
+            cancelled += 1;
+        }
+
+        if cancelled > 0 {
+            tracing::info!("Cancelled {} fetch(es) for source: {}", cancelled, source);
+        }
+
+        drop(state);
+        self.dispatch();
+        cancelled
+    }
+
+    /// Change the maximum number of concurrent fetches
+    pub fn set_max_concurrency(&self, max_concurrency: usize) {
+        self.lock_state().max_concurrency = max_concurrency.max(1);
+        self.dispatch();
+    }
+
+    /// Snapshot of running and pending fetches, in dispatch order
+    pub fn status(&self) -> FetchQueueStatus {
+        let state = self.lock_state();
+
+        let mut running: Vec<QueuedFetchInfo> =
+            state.running.values().map(|r| r.info.clone()).collect();
+        running.sort_by_key(|r| r.started_at);
+
+        let mut pending: Vec<&PendingFetch> = state.pending.iter().collect();
+        pending.sort_by_key(|p| (std::cmp::Reverse(p.priority), p.seq));
+
+        FetchQueueStatus {
+            max_concurrency: state.max_concurrency,
+            running,
+            pending: pending
+                .into_iter()
+                .map(|p| QueuedFetchInfo {
+                    source: p.config.source.clone(),
+                    adapter_type: p.config.adapter_type.clone(),
+                    priority: p.priority,
+                    queued_at: p.queued_at,
+                    started_at: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start pending fetches while there is capacity
+    fn dispatch(&self) {
+        let mut state = self.lock_state();
+
+        while state.running.len() < state.max_concurrency {
+            // Highest priority first, FIFO within a priority; a source never runs twice at once
+            let next = state
+                .pending
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| !state.running.contains_key(&p.config.source))
+                .max_by_key(|(_, p)| (p.priority, std::cmp::Reverse(p.seq)))
+                .map(|(index, _)| index);
+
+            let Some(index) = next else {
+                break;
+            };
+
+            let pending = state.pending.remove(index);
+            let source = pending.config.source.clone();
+            let info = QueuedFetchInfo {
+                source: source.clone(),
+                adapter_type: pending.config.adapter_type.clone(),
+                priority: pending.priority,
+                queued_at: pending.queued_at,
+                started_at: Some(Utc::now()),
+            };
+
+            let queue = self.clone();
+            let config = pending.config;
+            let task = tokio::spawn(async move {
+                let summary = fetcher::fetch_source(
+                    &queue.plugin_manager,
+                    &queue.adapter_registry,
+                    &queue.database,
+                    config,
+                )
+                .await;
+                queue.complete(summary);
+            });
+
+            state.running.insert(
+                source,
+                RunningFetch {
+                    info,
+                    abort: task.abort_handle(),
+                    responder: Some(pending.responder),
+                },
+            );
+        }
+    }
+
+    /// Hand the summary to the caller, announce it and free the slot
+    fn complete(&self, summary: FetchSummary) {
+        let running = self.lock_state().running.remove(&summary.source);
+
+        events::publish(AppEvent::FetchFinished(summary.clone()));
+
+        if let Some(responder) = running.and_then(|mut r| r.responder.take()) {
+            let _ = responder.send(summary);
+        }
+
+        self.dispatch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::Adapter;
+    use crate::db::StagedRecord;
+    use crate::error::AppError;
+    use async_trait::async_trait;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::sync::Barrier;
+
+    /// Adapter whose fetches wait at a barrier the test controls
+    struct GatedAdapter {
+        gate: Arc<Barrier>,
+    }
+
+    #[async_trait]
+    impl Adapter for GatedAdapter {
+        fn adapter_type(&self) -> &str {
+            "gated"
+        }
+
+        fn name(&self) -> &str {
+            "Gated"
+        }
+
+        async fn fetch(&self, _config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError> {
+            self.gate.wait().await;
+            Ok(Vec::new())
+        }
+
+        async fn test_connection(&self, _config: &AdapterConfig) -> Result<bool, AppError> {
+            Ok(true)
+        }
+
+        fn default_config(&self) -> AdapterConfig {
+            AdapterConfig::new("gated", "gated", "https://example.com")
+        }
+    }
+
+    async fn queue(
+        temp_dir: &TempDir,
+        adapter_registry: AdapterRegistry,
+        max_concurrency: usize,
+    ) -> FetchQueue {
+        let database = Database::new(temp_dir.path().join("data")).await.unwrap();
+        let plugin_manager = PluginManager::new(temp_dir.path().join("plugins"));
+
+        FetchQueue::new(
+            Arc::new(Mutex::new(plugin_manager)),
+            Arc::new(adapter_registry),
+            Arc::new(Mutex::new(database)),
+            max_concurrency,
+        )
+    }
+
+    fn gated_registry(gate: &Arc<Barrier>) -> AdapterRegistry {
+        let mut registry = AdapterRegistry::new();
+        registry.register(Box::new(GatedAdapter { gate: gate.clone() }));
+        registry
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_unknown_adapter() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = queue(&temp_dir, AdapterRegistry::new(), 2).await;
+        let mut events = events::subscribe();
+
+        let summaries = futures::future::join_all(vec![
+            queue.run(
+                AdapterConfig::new("missing_a", "source-a", "https://example.com"),
+                FetchPriority::Manual,
+            ),
+            queue.run(
+                AdapterConfig::new("missing_b", "source-b", "https://example.com"),
+                FetchPriority::Scheduled,
+            ),
+        ])
+        .await;
+
+        assert_eq!(summaries.len(), 2);
+        assert!(summaries
+            .iter()
+            .all(|s| s.records == 0 && s.error.is_some()));
+        assert!(queue.status().running.is_empty());
+
+        // Other tests publish to the same bus
+        let mut finished: Vec<String> = std::iter::from_fn(|| events.try_recv().ok())
+            .filter_map(|event| match event {
+                AppEvent::FetchFinished(summary) => Some(summary.source),
+                _ => None,
+            })
+            .filter(|source| source.starts_with("source-"))
+            .collect();
+        finished.sort();
+        assert_eq!(finished, vec!["source-a", "source-b"]);
+    }
+
+    #[tokio::test]
+    async fn test_pending_order_and_cancel() {
+        let temp_dir = TempDir::new().unwrap();
+        // The first fetch waits until the test passes the gate too
+        let gate = Arc::new(Barrier::new(2));
+        let queue = queue(&temp_dir, gated_registry(&gate), 1).await;
+
+        let first = queue.submit(
+            AdapterConfig::new("gated", "busy", "https://example.com"),
+            FetchPriority::Scheduled,
+        );
+        let _scheduled = queue.submit(
+            AdapterConfig::new("missing", "scheduled", "https://example.com"),
+            FetchPriority::Scheduled,
+        );
+        let manual = queue.submit(
+            AdapterConfig::new("missing", "manual", "https://example.com"),
+            FetchPriority::Manual,
+        );
+
+        let status = queue.status();
+        assert_eq!(status.running.len(), 1);
+        let pending: Vec<&str> = status.pending.iter().map(|p| p.source.as_str()).collect();
+        assert_eq!(pending, vec!["manual", "scheduled"]);
+
+        assert_eq!(queue.cancel("manual"), 1);
+        assert!(manual.await.is_err());
+        assert_eq!(queue.status().pending.len(), 1);
+
+        // A running fetch leaves the plugin manager free for plugin commands
+        assert!(queue.plugin_manager.try_lock().is_ok());
+
+        gate.wait().await;
+        assert!(first.await.unwrap().error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetches_run_concurrently() {
+        let temp_dir = TempDir::new().unwrap();
+        // Both fetches have to be at the gate at the same time to pass it
+        let gate = Arc::new(Barrier::new(2));
+        let queue = queue(&temp_dir, gated_registry(&gate), 2).await;
+
+        let fetches = futures::future::join_all(vec![
+            queue.run(
+                AdapterConfig::new("gated", "parallel-a", "https://example.com"),
+                FetchPriority::Manual,
+            ),
+            queue.run(
+                AdapterConfig::new("gated", "parallel-b", "https://example.com"),
+                FetchPriority::Manual,
+            ),
+        ]);
+        let summaries = tokio::time::timeout(Duration::from_secs(10), fetches)
+            .await
+            .expect("fetches should run at the same time");
+
+        assert!(summaries.iter().all(|s| s.error.is_none()));
+    }
+}
@@ -0,0 +1,214 @@
+// Fetch orchestration
+//
+// Shared logic for running adapter fetches and storing the results in the
+// staging area. Used by the fetch queue so that multi-source refreshes run
+// in the backend instead of being driven one source at a time by the frontend.
+
+use crate::adapters::{AdapterConfig, AdapterRegistry};
+use crate::audit::Actor;
+use crate::credential_profiles::resolve_credential_ref;
+use crate::db::{Database, StagedRecord};
+use crate::deep_sync;
+use crate::error::AppError;
+use crate::metrics;
+use crate::network;
+use crate::plugins::{Plugin, PluginManager};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+/// Default number of sources fetched at the same time
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Result of fetching a single source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FetchSummary {
+    pub source: String,
+    pub adapter_type: String,
+    pub records: usize,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Fetch records for a configuration with the source's plugin, if it has
+/// one, or the built-in adapter
+pub async fn fetch_records(
+    plugin: Option<&dyn Plugin>,
+    adapter_registry: &AdapterRegistry,
+    config: &AdapterConfig,
+) -> Result<Vec<StagedRecord>, AppError> {
+    let records = if let Some(plugin) = plugin {
+        plugin.fetch(config).await?
+    } else if adapter_registry.get(&config.adapter_type).is_some() {
+        adapter_registry.fetch(config).await?
+    } else {
+        return Err(AppError::Adapter(format!(
+            "No plugin or built-in adapter found for adapter type: {}",
+            config.adapter_type
+        )));
+    };
+
+    Ok(transform_records(config, records))
+}
+
+/// Apply the source's transformation pipeline and redaction rules
+pub fn transform_records(config: &AdapterConfig, records: Vec<StagedRecord>) -> Vec<StagedRecord> {
+    let mut records = match &config.pipeline {
+        Some(pipeline) => pipeline.apply(records),
+        None => records,
+    };
+
+    // Redaction runs last so computed fields can't reintroduce secrets
+    if let Some(redaction) = &config.redaction {
+        redaction.apply(&mut records);
+    }
+
+    records
+}
+
+/// Upsert fetched records into the staging area, attributed to the plugin
+/// or adapter that fetched them
+pub async fn store_records(
+    database: &Mutex<Database>,
+    records: Vec<StagedRecord>,
+    unique_key: Option<&str>,
+    actor: Actor,
+) -> Result<usize, AppError> {
+    let db = database.lock().await;
+    db.as_actor(actor, db.upsert_records_batch(records, unique_key))
+        .await
+}
+
+/// Who records fetched with a config are attributed to
+fn fetch_actor(plugin: Option<&dyn Plugin>, config: &AdapterConfig) -> Actor {
+    match plugin {
+        Some(_) => Actor::plugin(&config.adapter_type),
+        None => Actor::adapter(&config.adapter_type, &config.source),
+    }
+}
+
+/// Persist a fetch run in the history, logging instead of failing the fetch
+pub async fn record_run(
+    database: &Mutex<Database>,
+    config: &AdapterConfig,
+    started_at: DateTime<Utc>,
+    duration_ms: u64,
+    record_count: usize,
+    error: Option<String>,
+) {
+    let db = database.lock().await;
+    if let Err(e) = db
+        .record_fetch_run(
+            &config.source,
+            &config.adapter_type,
+            started_at,
+            duration_ms,
+            record_count,
+            error,
+        )
+        .await
+    {
+        tracing::warn!("Failed to record fetch run for '{}': {}", config.source, e);
+    }
+}
+
+/// Fetch and store a single source with its plugin or built-in adapter,
+/// recording metrics and the run in the fetch history
+/// The plugin manager is only locked to look up the plugin, so fetches run
+/// concurrently and don't block plugin commands.
+pub async fn fetch_and_store(
+    plugin_manager: &Mutex<PluginManager>,
+    adapter_registry: &AdapterRegistry,
+    database: &Mutex<Database>,
+    mut config: AdapterConfig,
+) -> Result<usize, AppError> {
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let plugin = plugin_manager
+        .lock()
+        .await
+        .get_plugin_by_adapter_type(&config.adapter_type);
+
+    let result = if let Err(e) = resolve_credential_ref(&mut config) {
+        Err(e)
+    } else if let Err(e) = network::ensure_online() {
+        Err(e)
+    } else if deep_sync::is_enabled(&config) {
+        deep_sync::sync_and_store(plugin.as_deref(), database, &config).await
+    } else {
+        match fetch_records(plugin.as_deref(), adapter_registry, &config).await {
+            Ok(records) => {
+                let actor = fetch_actor(plugin.as_deref(), &config);
+                store_records(database, records, config.unique_key.as_deref(), actor).await
+            }
+            Err(e) => Err(e),
+        }
+    };
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let outcome = if result.is_ok() { "success" } else { "error" };
+    metrics::observe(
+        "fetch_duration_ms",
+        &[
+            ("source", config.source.as_str()),
+            ("adapter_type", config.adapter_type.as_str()),
+        ],
+        duration_ms as f64,
+    );
+    metrics::increment(
+        "fetches_total",
+        &[("source", config.source.as_str()), ("outcome", outcome)],
+        1,
+    );
+    if let Ok(records) = &result {
+        metrics::increment(
+            "records_fetched_total",
+            &[("source", config.source.as_str())],
+            *records as u64,
+        );
+    }
+
+    record_run(
+        database,
+        &config,
+        started_at,
+        duration_ms,
+        result.as_ref().ok().copied().unwrap_or(0),
+        result.as_ref().err().map(|e| e.to_string()),
+    )
+    .await;
+
+    match &result {
+        Ok(records) => tracing::info!(
+            "Fetched {} records from '{}' in {}ms",
+            records,
+            config.source,
+            duration_ms
+        ),
+        Err(e) => tracing::error!("Fetch failed for '{}': {}", config.source, e),
+    }
+    result
+}
+
+/// Fetch and store a single source, returning a summary instead of an error
+pub async fn fetch_source(
+    plugin_manager: &Mutex<PluginManager>,
+    adapter_registry: &AdapterRegistry,
+    database: &Mutex<Database>,
+    config: AdapterConfig,
+) -> FetchSummary {
+    let source = config.source.clone();
+    let adapter_type = config.adapter_type.clone();
+    let started = Instant::now();
+
+    let result = fetch_and_store(plugin_manager, adapter_registry, database, config).await;
+    FetchSummary {
+        source,
+        adapter_type,
+        records: result.as_ref().ok().copied().unwrap_or(0),
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: result.err().map(|e| e.to_string()),
+    }
+}
@@ -0,0 +1,440 @@
+// Global search
+//
+// One query over everything a command palette can jump to: records, tickets,
+// pages, dashboards, prompt sections and packages, and plugins. Records are
+// found through their full-text index (`search`); the rest is small enough to
+// search in memory. Every candidate is scored with the prompt search scoring
+// (every term must match, names rank above body text), so hits of all kinds
+// can be ranked in one list.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::pages::Page;
+use crate::plugins::PluginMetadata;
+use crate::prompt_gen::search::{
+    field, score, search_prompt_content, Field, PromptSearchFilters, PromptSearchKind,
+    PromptSearchMatch,
+};
+use crate::prompt_gen::{PromptPackage, PromptSection};
+use crate::search::SearchFilters;
+use crate::tickets::TicketFilters;
+use serde::{Deserialize, Serialize};
+
+/// Default number of hits returned
+pub const DEFAULT_GLOBAL_SEARCH_LIMIT: usize = 20;
+
+/// Records fetched from the full-text index before ranking
+const RECORD_CANDIDATES: usize = 50;
+
+/// Score of records the index matched (e.g. by word stem) but the scoring
+/// doesn't, so they rank below literal matches
+const STEMMED_MATCH_SCORE: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlobalSearchKind {
+    Record,
+    Ticket,
+    Page,
+    Dashboard,
+    PromptSection,
+    PromptPackage,
+    Plugin,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlobalSearchOptions {
+    /// Only these kinds of results, all when empty
+    pub kinds: Vec<GlobalSearchKind>,
+    pub limit: Option<usize>,
+}
+
+impl GlobalSearchOptions {
+    fn includes(&self, kind: GlobalSearchKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalSearchHit {
+    pub kind: GlobalSearchKind,
+    pub id: String,
+    pub title: String,
+    /// Context shown under the title, e.g. "MOD-12 · in_progress"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    /// Matched text with terms wrapped in `<mark>` tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Frontend route of pages, dashboards and plugins
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    pub score: f64,
+}
+
+/// Lowercased query, split into terms for the scoring
+struct Terms {
+    query: String,
+}
+
+impl Terms {
+    fn new(query: &str) -> Self {
+        Terms {
+            query: query.trim().to_ascii_lowercase(),
+        }
+    }
+
+    fn score(&self, fields: &[Field]) -> Option<(f64, Option<String>)> {
+        let terms: Vec<&str> = self.query.split_whitespace().collect();
+        score(fields, &terms, &self.query).map(|(score, matches)| (score, first_snippet(matches)))
+    }
+}
+
+fn first_snippet(matches: Vec<PromptSearchMatch>) -> Option<String> {
+    matches.into_iter().next().map(|m| m.snippet)
+}
+
+fn subtitle(parts: &[Option<&str>]) -> Option<String> {
+    let parts: Vec<&str> = parts
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|p| !p.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+// ============================================================================
+// Search Operations
+// ============================================================================
+
+impl Database {
+    /// Search every kind of entity, hits of all kinds ranked together
+    pub async fn global_search(
+        &self,
+        query: &str,
+        plugins: &[PluginMetadata],
+        options: GlobalSearchOptions,
+    ) -> Result<Vec<GlobalSearchHit>, AppError> {
+        let terms = Terms::new(query);
+        if terms.query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut hits = Vec::new();
+        if options.includes(GlobalSearchKind::Record) {
+            hits.extend(self.search_record_hits(&terms).await?);
+        }
+        if options.includes(GlobalSearchKind::Ticket) {
+            let tickets = self.get_tickets(Some(TicketFilters::default())).await?;
+            hits.extend(tickets.into_iter().filter_map(|ticket| {
+                let fields = [
+                    field("key", 5.0, ticket.key.clone().unwrap_or_default()),
+                    field("title", 5.0, ticket.title.clone()),
+                    field("tags", 3.0, ticket.tags.join(" ")),
+                    field(
+                        "description",
+                        1.0,
+                        ticket.description.clone().unwrap_or_default(),
+                    ),
+                ];
+                let (score, snippet) = terms.score(&fields)?;
+                Some(GlobalSearchHit {
+                    kind: GlobalSearchKind::Ticket,
+                    subtitle: subtitle(&[ticket.key.as_deref(), Some(ticket.status.as_str())]),
+                    id: ticket.id,
+                    title: ticket.title,
+                    snippet,
+                    route: None,
+                    score,
+                })
+            }));
+        }
+        if options.includes(GlobalSearchKind::Page) {
+            hits.extend(self.search_page_hits(&terms).await?);
+        }
+        if options.includes(GlobalSearchKind::Dashboard) {
+            let dashboards = self.get_dashboards().await?;
+            hits.extend(dashboards.into_iter().filter_map(|dashboard| {
+                let panel_titles: Vec<&str> =
+                    dashboard.panels.iter().map(|p| p.title.as_str()).collect();
+                let fields = [
+                    field("name", 5.0, dashboard.name.clone()),
+                    field("panels", 1.0, panel_titles.join(" … ")),
+                ];
+                let (score, snippet) = terms.score(&fields)?;
+                Some(GlobalSearchHit {
+                    kind: GlobalSearchKind::Dashboard,
+                    subtitle: Some(format!("{} panels", dashboard.panels.len())),
+                    route: Some(format!("/dashboard/{}", dashboard.id)),
+                    id: dashboard.id,
+                    title: dashboard.name,
+                    snippet,
+                    score,
+                })
+            }));
+        }
+        if options.includes(GlobalSearchKind::PromptSection)
+            || options.includes(GlobalSearchKind::PromptPackage)
+        {
+            hits.extend(self.search_prompt_hits(query, &options).await?);
+        }
+        if options.includes(GlobalSearchKind::Plugin) {
+            hits.extend(plugin_hits(plugins, &terms));
+        }
+
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        hits.truncate(options.limit.unwrap_or(DEFAULT_GLOBAL_SEARCH_LIMIT));
+        Ok(hits)
+    }
+
+    async fn search_record_hits(&self, terms: &Terms) -> Result<Vec<GlobalSearchHit>, AppError> {
+        let results = self
+            .search_records(
+                &terms.query,
+                SearchFilters {
+                    limit: Some(RECORD_CANDIDATES),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        Ok(results
+            .hits
+            .into_iter()
+            .map(|hit| {
+                let record = hit.record;
+                let title = record
+                    .metadata
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| record.record_type.clone());
+                let fields = [
+                    field("title", 5.0, title.clone()),
+                    field("tags", 3.0, record.metadata.tags.join(" ")),
+                    field(
+                        "description",
+                        1.0,
+                        record.metadata.description.clone().unwrap_or_default(),
+                    ),
+                ];
+                let (score, snippet) = terms.score(&fields).unwrap_or((STEMMED_MATCH_SCORE, None));
+                GlobalSearchHit {
+                    kind: GlobalSearchKind::Record,
+                    id: record
+                        .id
+                        .as_ref()
+                        .map(|id| id.id.to_raw())
+                        .unwrap_or_default(),
+                    subtitle: subtitle(&[
+                        Some(record.record_type.as_str()),
+                        Some(record.source.as_str()),
+                    ]),
+                    title,
+                    snippet: snippet.or(hit.highlight),
+                    route: None,
+                    score,
+                }
+            })
+            .collect())
+    }
+
+    async fn search_page_hits(&self, terms: &Terms) -> Result<Vec<GlobalSearchHit>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM pages ORDER BY order ASC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query pages: {}", e)))?;
+        let pages: Vec<Page> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract pages: {}", e)))?;
+
+        Ok(pages
+            .into_iter()
+            .filter_map(|page| {
+                let fields = [
+                    field("name", 5.0, page.name.clone()),
+                    field("route", 2.0, page.route.clone()),
+                ];
+                let (score, snippet) = terms.score(&fields)?;
+                Some(GlobalSearchHit {
+                    kind: GlobalSearchKind::Page,
+                    id: page.id.map(|id| id.id.to_raw()).unwrap_or_default(),
+                    title: page.name,
+                    subtitle: Some(page.page_type),
+                    snippet,
+                    route: Some(page.route),
+                    score,
+                })
+            })
+            .collect())
+    }
+
+    async fn search_prompt_hits(
+        &self,
+        query: &str,
+        options: &GlobalSearchOptions,
+    ) -> Result<Vec<GlobalSearchHit>, AppError> {
+        let packages: Vec<PromptPackage> = self
+            .db
+            .select("prompt_packages")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get packages: {}", e)))?;
+        let sections: Vec<PromptSection> = self
+            .db
+            .select("prompt_sections")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get sections: {}", e)))?;
+
+        let kind = match (
+            options.includes(GlobalSearchKind::PromptSection),
+            options.includes(GlobalSearchKind::PromptPackage),
+        ) {
+            (true, false) => Some(PromptSearchKind::Section),
+            (false, true) => Some(PromptSearchKind::Package),
+            _ => None,
+        };
+        let filters = PromptSearchFilters {
+            kind,
+            limit: Some(options.limit.unwrap_or(DEFAULT_GLOBAL_SEARCH_LIMIT)),
+            ..Default::default()
+        };
+
+        Ok(search_prompt_content(&packages, &sections, query, &filters)
+            .into_iter()
+            .map(|hit| GlobalSearchHit {
+                kind: match hit.kind {
+                    PromptSearchKind::Section => GlobalSearchKind::PromptSection,
+                    PromptSearchKind::Package => GlobalSearchKind::PromptPackage,
+                },
+                id: hit.id,
+                title: hit.name,
+                subtitle: subtitle(&[Some(hit.namespace.as_str())]),
+                snippet: first_snippet(hit.matches),
+                route: None,
+                score: hit.score,
+            })
+            .collect())
+    }
+}
+
+fn plugin_hits(plugins: &[PluginMetadata], terms: &Terms) -> Vec<GlobalSearchHit> {
+    plugins
+        .iter()
+        .filter_map(|plugin| {
+            let fields = [
+                field("name", 5.0, plugin.name.clone()),
+                field("description", 2.0, plugin.description.clone()),
+                field("author", 1.0, plugin.author.clone()),
+            ];
+            let (score, snippet) = terms.score(&fields)?;
+            Some(GlobalSearchHit {
+                kind: GlobalSearchKind::Plugin,
+                id: plugin.name.clone(),
+                title: plugin.name.clone(),
+                subtitle: Some(format!("v{}", plugin.version)),
+                snippet,
+                route: Some("/plugin-management".to_string()),
+                score,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_global_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let mut record = StagedRecord::new(
+            "gitlab_pipeline".to_string(),
+            "ci".to_string(),
+            serde_json::json!({ "id": "1" }),
+        );
+        record.metadata.title = Some("Deploy pipeline".to_string());
+        db.upsert_record(record).await.unwrap();
+
+        let fix = db
+            .create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": "Fix deploy script", "ticket_type": "task"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        db.create_ticket(
+            serde_json::from_value(serde_json::json!({
+                "title": "Unrelated", "ticket_type": "task",
+                "description": "Mentions deployments in passing"
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let plugins = vec![PluginMetadata {
+            name: "deploy-tracker".to_string(),
+            version: "1.0.0".to_string(),
+            author: "someone".to_string(),
+            description: "Tracks deployments".to_string(),
+            adapter_type: None,
+            capabilities: vec![],
+            frontend: None,
+        }];
+
+        let hits = db
+            .global_search("deploy", &plugins, GlobalSearchOptions::default())
+            .await
+            .unwrap();
+        let kinds: Vec<GlobalSearchKind> = hits.iter().map(|h| h.kind).collect();
+        assert!(kinds.contains(&GlobalSearchKind::Record));
+        assert!(kinds.contains(&GlobalSearchKind::Ticket));
+        assert!(kinds.contains(&GlobalSearchKind::Plugin));
+        // Title matches rank above a partial match in a description
+        assert_eq!(hits.last().unwrap().title, "Unrelated");
+        let ticket = hits
+            .iter()
+            .find(|h| h.title == "Fix deploy script")
+            .unwrap();
+        assert_eq!(
+            ticket.subtitle,
+            Some(format!("{} · {}", fix.key.unwrap(), fix.status))
+        );
+        assert!(ticket
+            .snippet
+            .as_deref()
+            .unwrap()
+            .contains("<mark>deploy</mark>"));
+
+        let hits = db
+            .global_search(
+                "deploy",
+                &plugins,
+                GlobalSearchOptions {
+                    kinds: vec![GlobalSearchKind::Plugin],
+                    limit: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].route.as_deref(), Some("/plugin-management"));
+
+        assert!(db
+            .global_search("  ", &plugins, GlobalSearchOptions::default())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+// App health
+//
+// One check over the parts the app depends on: database connectivity and
+// latency, the SurrealDB sidecar (when built with it), plugins that failed
+// to load, the job scheduler and the free disk space of the data directory.
+// Every part gets a status; the overall status is the worst of them, so the
+// UI can show a single indicator and the parts behind it.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::plugins::QuarantinedPlugin;
+use crate::scheduler::{JobInfo, JobStatus};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Database round trips slower than this degrade the health
+const SLOW_DATABASE_MS: u64 = 1000;
+
+/// Less free space than this on the data disk degrades the health ...
+const LOW_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// ... and less than this fails it
+const CRITICAL_DISK_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Jobs whose next run is this far in the past are considered stuck
+const OVERDUE_MINUTES: i64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Degraded,
+    Failing,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseHealth {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SidecarHealth {
+    pub status: HealthStatus,
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub port: u16,
+    /// Restarts after the process exited, since the app started
+    pub restarts: u32,
+    /// Why the process last exited or failed to restart, until it runs stably again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginHealth {
+    pub status: HealthStatus,
+    pub loaded: usize,
+    pub quarantined: Vec<QuarantinedPlugin>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchedulerHealth {
+    pub status: HealthStatus,
+    /// Whether the loop running due jobs was started
+    pub running: bool,
+    pub jobs: usize,
+    pub paused: Vec<String>,
+    /// Jobs whose last run failed
+    pub failing: Vec<String>,
+    /// Jobs that should have run a while ago
+    pub overdue: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiskHealth {
+    pub status: HealthStatus,
+    pub path: PathBuf,
+    /// Unknown if no disk holds the path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppHealth {
+    /// Worst status of the parts below
+    pub status: HealthStatus,
+    pub checked_at: DateTime<Utc>,
+    pub database: DatabaseHealth,
+    /// Only present when built with the sidecar database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar: Option<SidecarHealth>,
+    pub plugins: PluginHealth,
+    pub scheduler: SchedulerHealth,
+    pub disk: DiskHealth,
+}
+
+impl AppHealth {
+    pub fn new(
+        database: DatabaseHealth,
+        sidecar: Option<SidecarHealth>,
+        plugins: PluginHealth,
+        scheduler: SchedulerHealth,
+        disk: DiskHealth,
+    ) -> Self {
+        let status = [
+            database.status,
+            sidecar.as_ref().map_or(HealthStatus::Ok, |s| s.status),
+            plugins.status,
+            scheduler.status,
+            disk.status,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(HealthStatus::Ok);
+
+        Self {
+            status,
+            checked_at: Utc::now(),
+            database,
+            sidecar,
+            plugins,
+            scheduler,
+            disk,
+        }
+    }
+}
+
+impl Database {
+    /// Time a trivial query
+    pub async fn ping(&self) -> Result<u64, AppError> {
+        let started = Instant::now();
+        self.query("RETURN 1")
+            .await
+            .and_then(|response| response.check())
+            .map_err(|e| AppError::Database(format!("Database not reachable: {}", e)))?;
+        Ok(started.elapsed().as_millis() as u64)
+    }
+}
+
+pub async fn database_health(db: &Database) -> DatabaseHealth {
+    match db.ping().await {
+        Ok(latency_ms) => DatabaseHealth {
+            status: if latency_ms > SLOW_DATABASE_MS {
+                HealthStatus::Degraded
+            } else {
+                HealthStatus::Ok
+            },
+            latency_ms: Some(latency_ms),
+            error: None,
+        },
+        Err(e) => DatabaseHealth {
+            status: HealthStatus::Failing,
+            latency_ms: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+pub fn sidecar_health(
+    running: bool,
+    pid: Option<u32>,
+    port: u16,
+    restarts: u32,
+    last_error: Option<String>,
+) -> SidecarHealth {
+    SidecarHealth {
+        status: match (running, &last_error) {
+            (false, _) => HealthStatus::Failing,
+            (true, Some(_)) => HealthStatus::Degraded,
+            (true, None) => HealthStatus::Ok,
+        },
+        running,
+        pid,
+        port,
+        restarts,
+        last_error,
+    }
+}
+
+pub fn plugin_health(loaded: usize, quarantined: &[QuarantinedPlugin]) -> PluginHealth {
+    PluginHealth {
+        status: if quarantined.is_empty() {
+            HealthStatus::Ok
+        } else {
+            HealthStatus::Degraded
+        },
+        loaded,
+        quarantined: quarantined.to_vec(),
+    }
+}
+
+pub fn scheduler_health(running: bool, jobs: &[JobInfo], now: DateTime<Utc>) -> SchedulerHealth {
+    let ids = |filter: &dyn Fn(&JobInfo) -> bool| -> Vec<String> {
+        jobs.iter()
+            .filter(|job| filter(job))
+            .map(|job| job.id.clone())
+            .collect()
+    };
+    let paused = ids(&|job| job.paused);
+    let failing = ids(&|job| {
+        job.last_run
+            .as_ref()
+            .is_some_and(|run| run.status == JobStatus::Failed)
+    });
+    let overdue = ids(&|job| {
+        !job.paused
+            && !job.running
+            && job
+                .next_run
+                .is_some_and(|next| next < now - Duration::minutes(OVERDUE_MINUTES))
+    });
+
+    let status = if !running {
+        HealthStatus::Failing
+    } else if !failing.is_empty() || !overdue.is_empty() {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Ok
+    };
+    SchedulerHealth {
+        status,
+        running,
+        jobs: jobs.len(),
+        paused,
+        failing,
+        overdue,
+    }
+}
+
+pub fn disk_health(data_dir: &Path) -> DiskHealth {
+    let space = disk_space(data_dir);
+    let status = match space {
+        Some((available, _)) if available < CRITICAL_DISK_BYTES => HealthStatus::Failing,
+        Some((available, _)) if available < LOW_DISK_BYTES => HealthStatus::Degraded,
+        _ => HealthStatus::Ok,
+    };
+    DiskHealth {
+        status,
+        path: data_dir.to_path_buf(),
+        available_bytes: space.map(|(available, _)| available),
+        total_bytes: space.map(|(_, total)| total),
+    }
+}
+
+/// Available and total bytes of the disk holding `path` (the one with the
+/// longest mount point containing it)
+fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| (disk.available_space(), disk.total_space()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::{JobRun, Schedule};
+
+    fn job(id: &str, paused: bool, next_run: DateTime<Utc>, status: JobStatus) -> JobInfo {
+        JobInfo {
+            id: id.to_string(),
+            description: String::new(),
+            schedule: Schedule::every(std::time::Duration::from_secs(60)),
+            default_schedule: Schedule::every(std::time::Duration::from_secs(60)),
+            paused,
+            running: false,
+            next_run: Some(next_run),
+            last_run: Some(JobRun {
+                started_at: next_run,
+                duration_ms: 1,
+                status,
+                message: String::new(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_scheduler_health() {
+        let now = Utc::now();
+        let jobs = vec![
+            job("retention", false, now, JobStatus::Success),
+            job("backup", true, now - Duration::hours(1), JobStatus::Success),
+            job(
+                "rollups",
+                false,
+                now - Duration::hours(1),
+                JobStatus::Failed,
+            ),
+        ];
+
+        let health = scheduler_health(true, &jobs, now);
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.paused, vec!["backup"]);
+        assert_eq!(health.failing, vec!["rollups"]);
+        assert_eq!(health.overdue, vec!["rollups"]);
+
+        assert_eq!(
+            scheduler_health(false, &[], now).status,
+            HealthStatus::Failing
+        );
+    }
+
+    #[test]
+    fn test_overall_status() {
+        let health = AppHealth::new(
+            DatabaseHealth {
+                status: HealthStatus::Ok,
+                latency_ms: Some(2),
+                error: None,
+            },
+            None,
+            plugin_health(
+                3,
+                &[QuarantinedPlugin {
+                    name: "broken".to_string(),
+                    reason: "missing manifest".to_string(),
+                }],
+            ),
+            scheduler_health(true, &[], Utc::now()),
+            DiskHealth {
+                status: HealthStatus::Ok,
+                path: PathBuf::from("/data"),
+                available_bytes: None,
+                total_bytes: None,
+            },
+        );
+        assert_eq!(health.status, HealthStatus::Degraded);
+
+        let health = AppHealth::new(
+            health.database,
+            Some(sidecar_health(
+                false,
+                None,
+                8000,
+                2,
+                Some("exit status: 1".to_string()),
+            )),
+            plugin_health(3, &[]),
+            health.scheduler,
+            health.disk,
+        );
+        assert_eq!(health.status, HealthStatus::Failing);
+    }
+}
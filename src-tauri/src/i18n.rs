@@ -0,0 +1,263 @@
+// Backend localization
+//
+// Texts the backend hands to the user — error messages, notification titles
+// and bodies, labels of seeded content such as the default board columns and
+// the example prompt packages — are looked up by key in the catalogs below,
+// in the language of the `i18n.locale` setting. Only the language part of the
+// locale matters ("de-AT" uses "de"); keys a catalog lacks fall back to
+// English.
+//
+// Like the network config, the locale is process-wide and re-read whenever
+// the setting changes, so `AppError`'s messages and the notifiers need no
+// access to the settings.
+
+use crate::error::AppError;
+use crate::prompt_gen::locale::language;
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use std::sync::RwLock;
+
+/// Setting holding the locale of backend texts
+pub const LOCALE_SETTING: &str = "i18n.locale";
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Languages with a catalog
+pub const LOCALES: [&str; 2] = ["en", "de"];
+
+/// Language of backend texts; empty until the setting is loaded (English)
+static LANGUAGE: RwLock<String> = RwLock::new(String::new());
+
+const EN: &[(&str, &str)] = &[
+    // Errors
+    ("error.io", "IO error"),
+    ("error.serialization", "Serialization error"),
+    ("error.config", "Configuration error"),
+    ("error.database", "Database error"),
+    ("error.adapter", "Adapter error"),
+    ("error.http", "HTTP error"),
+    ("error.plugin", "Plugin error"),
+    ("error.validation", "Validation error"),
+    ("error.not_found", "Not found"),
+    ("error.unknown", "Unknown error"),
+    // Notifications
+    ("notification.fetch_failed", "Fetching {source} failed"),
+    ("notification.plugin_quarantined", "Plugin {name} was quarantined"),
+    ("notification.ticket_overdue", "Overdue since {when}"),
+    ("notification.ticket_due", "Due {when}"),
+    // Default board columns
+    ("board.column.backlog", "Backlog"),
+    ("board.column.todo", "To Do"),
+    ("board.column.in_progress", "In Progress"),
+    ("board.column.review", "Review"),
+    ("board.column.done", "Done"),
+    // Seeded prompt packages
+    ("seed.examples.name", "Example Prompts"),
+    (
+        "seed.examples.description",
+        "A collection of example prompts demonstrating various features",
+    ),
+    (
+        "seed.examples.created",
+        "Created example package with 13 entry points, 5 fragments, 3 data types, and 10 tags",
+    ),
+    ("seed.text2image.name", "Text2Image Common Library"),
+    (
+        "seed.text2image.description",
+        "Common reusable components for text-to-image prompt generation including subjects, actions, environments, styles, and modifiers",
+    ),
+    (
+        "seed.text2image.created",
+        "Created Text2Image Common Library package with 9 data types, 3 internal fragments, 5 exportable entry points, and 14 tags",
+    ),
+    // Starter workspace
+    ("seed.starter.dashboard", "Getting Started"),
+    ("seed.starter.welcome_title", "Welcome"),
+    (
+        "seed.starter.welcome",
+        "This dashboard was created as a starting point. The table shows the latest posts of the Rust blog, fetched by the RSS data source; the board below holds a few demo tickets. Edit or delete the panels, or add your own data sources.",
+    ),
+    ("seed.starter.source", "Rust Blog (RSS)"),
+    ("seed.starter.feed_title", "Latest Posts"),
+    ("seed.starter.board_title", "Demo Board"),
+    ("seed.starter.ticket.workspace", "Create the starter workspace"),
+    ("seed.starter.ticket.dashboard", "Explore the starter dashboard"),
+    ("seed.starter.ticket.source", "Add a data source of your own"),
+    ("seed.starter.ticket.prompts", "Try the example prompt packages"),
+];
+
+const DE: &[(&str, &str)] = &[
+    // Errors
+    ("error.io", "E/A-Fehler"),
+    ("error.serialization", "Serialisierungsfehler"),
+    ("error.config", "Konfigurationsfehler"),
+    ("error.database", "Datenbankfehler"),
+    ("error.adapter", "Adapterfehler"),
+    ("error.http", "HTTP-Fehler"),
+    ("error.plugin", "Plugin-Fehler"),
+    ("error.validation", "Ungültige Eingabe"),
+    ("error.not_found", "Nicht gefunden"),
+    ("error.unknown", "Unbekannter Fehler"),
+    // Notifications
+    ("notification.fetch_failed", "Abruf von {source} fehlgeschlagen"),
+    (
+        "notification.plugin_quarantined",
+        "Plugin {name} wurde unter Quarantäne gestellt",
+    ),
+    ("notification.ticket_overdue", "Überfällig seit {when}"),
+    ("notification.ticket_due", "Fällig {when}"),
+    // Default board columns
+    ("board.column.backlog", "Backlog"),
+    ("board.column.todo", "Zu erledigen"),
+    ("board.column.in_progress", "In Arbeit"),
+    ("board.column.review", "Review"),
+    ("board.column.done", "Erledigt"),
+    // Seeded prompt packages
+    ("seed.examples.name", "Beispiel-Prompts"),
+    (
+        "seed.examples.description",
+        "Eine Sammlung von Beispiel-Prompts, die verschiedene Funktionen zeigen",
+    ),
+    (
+        "seed.examples.created",
+        "Beispielpaket mit 13 Einstiegspunkten, 5 Fragmenten, 3 Datentypen und 10 Tags erstellt",
+    ),
+    ("seed.text2image.name", "Text2Image-Standardbibliothek"),
+    (
+        "seed.text2image.description",
+        "Wiederverwendbare Bausteine für Text-zu-Bild-Prompts mit Motiven, Handlungen, Umgebungen, Stilen und Modifikatoren",
+    ),
+    (
+        "seed.text2image.created",
+        "Text2Image-Standardbibliothek mit 9 Datentypen, 3 internen Fragmenten, 5 exportierbaren Einstiegspunkten und 14 Tags erstellt",
+    ),
+    // Starter workspace
+    ("seed.starter.dashboard", "Erste Schritte"),
+    ("seed.starter.welcome_title", "Willkommen"),
+    (
+        "seed.starter.welcome",
+        "Dieses Dashboard wurde als Ausgangspunkt erstellt. Die Tabelle zeigt die neuesten Beiträge des Rust-Blogs, abgerufen von der RSS-Datenquelle; das Board darunter enthält einige Demo-Tickets. Bearbeite oder lösche die Panels oder füge eigene Datenquellen hinzu.",
+    ),
+    ("seed.starter.source", "Rust-Blog (RSS)"),
+    ("seed.starter.feed_title", "Neueste Beiträge"),
+    ("seed.starter.board_title", "Demo-Board"),
+    ("seed.starter.ticket.workspace", "Start-Arbeitsbereich anlegen"),
+    ("seed.starter.ticket.dashboard", "Das Start-Dashboard erkunden"),
+    ("seed.starter.ticket.source", "Eine eigene Datenquelle hinzufügen"),
+    ("seed.starter.ticket.prompts", "Die Beispiel-Prompt-Pakete ausprobieren"),
+];
+
+fn catalog(language: &str) -> &'static [(&'static str, &'static str)] {
+    match language {
+        "de" => DE,
+        _ => EN,
+    }
+}
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog
+        .iter()
+        .find(|(entry, _)| *entry == key)
+        .map(|(_, text)| *text)
+}
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![SettingDefinition::new(
+        LOCALE_SETTING,
+        SettingType::String,
+        DEFAULT_LOCALE.into(),
+        "i18n",
+    )
+    .describe(
+        "Language",
+        "Language of messages, notifications and seeded content from the backend",
+    )
+    .options(LOCALES.iter().map(|&locale| locale.into()).collect())]
+}
+
+pub fn is_i18n_setting(key: &str) -> bool {
+    key == LOCALE_SETTING
+}
+
+/// Re-read the locale setting (at startup and after it changes)
+pub async fn reload(settings: &SettingsService) -> Result<(), AppError> {
+    let locale = settings.get_value(LOCALE_SETTING).await?;
+    set_locale(locale.as_str().unwrap_or(DEFAULT_LOCALE));
+    Ok(())
+}
+
+pub fn set_locale(locale: &str) {
+    let mut current = LANGUAGE.write().unwrap_or_else(|e| e.into_inner());
+    *current = language(locale);
+}
+
+/// Language backend texts are currently given in
+pub fn current() -> String {
+    let current = LANGUAGE.read().unwrap_or_else(|e| e.into_inner());
+    if current.is_empty() {
+        DEFAULT_LOCALE.to_string()
+    } else {
+        current.clone()
+    }
+}
+
+/// Text of `key` in `locale`, falling back to English and then to the key
+pub fn text_in(locale: &str, key: &'static str) -> &'static str {
+    lookup(catalog(&language(locale)), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key)
+}
+
+/// Text of `key` in the current locale
+pub fn text(key: &'static str) -> &'static str {
+    text_in(&current(), key)
+}
+
+/// Text of `key` in the current locale with its `{name}` placeholders filled in
+pub fn format(key: &'static str, args: &[(&str, &str)]) -> String {
+    fill(text(key), args)
+}
+
+fn fill(template: &str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(text_in("en", "error.not_found"), "Not found");
+        assert_eq!(text_in("de-AT", "error.not_found"), "Nicht gefunden");
+        // Unknown languages and keys fall back
+        assert_eq!(text_in("fr", "board.column.done"), "Done");
+        assert_eq!(text_in("de", "missing.key"), "missing.key");
+        assert_eq!(
+            fill(
+                text_in("de", "notification.fetch_failed"),
+                &[("source", "gitlab")]
+            ),
+            "Abruf von gitlab fehlgeschlagen"
+        );
+    }
+
+    #[test]
+    fn test_catalogs_match() {
+        for locale in LOCALES {
+            for (key, _) in catalog(locale) {
+                assert!(lookup(EN, key).is_some(), "{} not in English", key);
+            }
+            for (key, _) in EN {
+                assert!(
+                    lookup(catalog(locale), key).is_some(),
+                    "{} missing in {}",
+                    key,
+                    locale
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// Legacy database migration
+//
+// Before dev/prod stage separation the database lived directly in
+// `<data dir>/db`. This copies records, tickets and pages from that layout
+// into the current environment database. Rows keep their IDs and rows that
+// already exist are skipped, so the migration can safely be run again.
+
+use crate::db::Database;
+#[cfg(feature = "embedded-db")]
+use crate::db::RowImport;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::sync::Mutex;
+
+/// Tables copied from the legacy database
+pub const LEGACY_TABLES: &[&str] = &["records", "tickets", "pages"];
+
+/// Rows read from the legacy database per query
+const MIGRATION_CHUNK_SIZE: usize = 500;
+
+/// Row errors reported per table at most
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// Progress of a migration, reported after every chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationProgress {
+    pub table: String,
+    pub processed: usize,
+    pub total: usize,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableMigration {
+    pub table: String,
+    /// Rows in the legacy table
+    pub found: usize,
+    /// Rows copied (or, in a dry run, rows that would be copied)
+    pub migrated: usize,
+    /// Rows already present in the current database
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyMigrationReport {
+    pub legacy_path: String,
+    /// Whether a legacy database was found at all
+    pub found: bool,
+    pub dry_run: bool,
+    pub tables: Vec<TableMigration>,
+}
+
+/// Copy legacy data into `target` (or only count what would be copied when `dry_run`)
+#[cfg(feature = "embedded-db")]
+pub async fn migrate_legacy_database(
+    target: &Mutex<Database>,
+    data_dir: &Path,
+    dry_run: bool,
+    mut on_progress: impl FnMut(&MigrationProgress),
+) -> Result<LegacyMigrationReport, AppError> {
+    let legacy_path = data_dir.join("db");
+    let mut report = LegacyMigrationReport {
+        legacy_path: legacy_path.to_string_lossy().to_string(),
+        found: legacy_path.exists(),
+        dry_run,
+        tables: Vec::new(),
+    };
+    if !report.found {
+        tracing::info!("No legacy database at {:?}", legacy_path);
+        return Ok(report);
+    }
+
+    let legacy = Database::new_legacy(data_dir.to_path_buf()).await?;
+
+    for table in LEGACY_TABLES {
+        let mut migration = TableMigration {
+            table: table.to_string(),
+            found: legacy.count_table(table).await?,
+            ..Default::default()
+        };
+
+        let mut processed = 0;
+        loop {
+            let rows = legacy
+                .export_table_chunk_json(table, processed, MIGRATION_CHUNK_SIZE)
+                .await?;
+
+            let db = target.lock().await;
+            for row in &rows {
+                let result = if dry_run {
+                    db.json_row_exists(table, row).await.map(|exists| !exists)
+                } else {
+                    db.import_json_row(table, row, false)
+                        .await
+                        .map(|outcome| outcome == RowImport::Created)
+                };
+                match result {
+                    Ok(true) => migration.migrated += 1,
+                    Ok(false) => migration.skipped += 1,
+                    Err(e) => {
+                        if migration.errors.len() < MAX_REPORTED_ERRORS {
+                            migration.errors.push(e.to_string());
+                        }
+                    }
+                }
+            }
+            drop(db);
+
+            processed += rows.len();
+            on_progress(&MigrationProgress {
+                table: table.to_string(),
+                processed,
+                total: migration.found.max(processed),
+                dry_run,
+            });
+
+            if rows.len() < MIGRATION_CHUNK_SIZE {
+                break;
+            }
+        }
+
+        tracing::info!(
+            "Legacy migration{} of {}: {} found, {} migrated, {} skipped, {} errors",
+            if dry_run { " (dry run)" } else { "" },
+            table,
+            migration.found,
+            migration.migrated,
+            migration.skipped,
+            migration.errors.len()
+        );
+        report.tables.push(migration);
+    }
+
+    Ok(report)
+}
+
+/// The sidecar database has no legacy on-disk layout
+#[cfg(feature = "sidecar-db")]
+pub async fn migrate_legacy_database(
+    _target: &Mutex<Database>,
+    _data_dir: &Path,
+    _dry_run: bool,
+    _on_progress: impl FnMut(&MigrationProgress),
+) -> Result<LegacyMigrationReport, AppError> {
+    Err(AppError::Config(
+        "Legacy migration is only available with the embedded database".to_string(),
+    ))
+}
+
+#[cfg(all(test, feature = "embedded-db"))]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_migrate_legacy_database() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // `Database::new` puts its files in `<dir>/<env>/db`, which is the
+        // legacy layout as seen from `<dir>/<env>`
+        let legacy_root = temp_dir.path().join("legacy");
+        {
+            let legacy = Database::new(legacy_root.clone()).await.unwrap();
+            for i in 0..3 {
+                let record = StagedRecord::new(
+                    "rest_api".to_string(),
+                    "old".to_string(),
+                    serde_json::json!({ "id": i }),
+                );
+                legacy.upsert_record(record).await.unwrap();
+            }
+            legacy
+                .create_ticket(
+                    serde_json::from_value(serde_json::json!({
+                        "title": "Old ticket",
+                        "ticket_type": "task"
+                    }))
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+        let env = if cfg!(debug_assertions) {
+            "dev"
+        } else {
+            "prod"
+        };
+        let legacy_dir = legacy_root.join(env);
+
+        let target = Mutex::new(
+            Database::new(temp_dir.path().join("current"))
+                .await
+                .unwrap(),
+        );
+
+        let missing = migrate_legacy_database(&target, temp_dir.path(), true, |_| {})
+            .await
+            .unwrap();
+        assert!(!missing.found);
+
+        let mut progress = Vec::new();
+        let preview =
+            migrate_legacy_database(&target, &legacy_dir, true, |p| progress.push(p.clone()))
+                .await
+                .unwrap();
+        assert!(preview.found);
+        assert_eq!(preview.tables[0].found, 3);
+        assert_eq!(preview.tables[0].migrated, 3);
+        assert_eq!(preview.tables[1].migrated, 1);
+        assert_eq!(progress.len(), LEGACY_TABLES.len());
+        assert_eq!(target.lock().await.count_records().await.unwrap(), 0);
+
+        let report = migrate_legacy_database(&target, &legacy_dir, false, |_| {})
+            .await
+            .unwrap();
+        assert!(report.tables.iter().all(|t| t.errors.is_empty()));
+        assert_eq!(report.tables[0].migrated, 3);
+        assert_eq!(target.lock().await.count_records().await.unwrap(), 3);
+        assert_eq!(
+            target.lock().await.get_tickets(None).await.unwrap().len(),
+            1
+        );
+
+        // Running again copies nothing
+        let rerun = migrate_legacy_database(&target, &legacy_dir, false, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(rerun.tables[0].migrated, 0);
+        assert_eq!(rerun.tables[0].skipped, 3);
+        assert_eq!(rerun.tables[1].skipped, 1);
+    }
+}
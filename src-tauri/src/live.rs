@@ -0,0 +1,232 @@
+// Live queries
+//
+// Subscribes to SurrealDB LIVE SELECTs on the records, tickets, pages and
+// dashboards tables and forwards changes to the frontend as Tauri events
+// (`db://<table>/changed`), so dashboards update without polling. Changes are
+// coalesced per table: a bulk write sends one event per `COALESCE_WINDOW`
+// rather than one per row.
+
+use crate::db::Database;
+use crate::error::AppError;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+use surrealdb::sql::Thing;
+use surrealdb::Action;
+use tauri::{AppHandle, Emitter};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Tables whose changes are pushed to the frontend
+pub const LIVE_TABLES: &[&str] = &["records", "tickets", "pages", "dashboards"];
+
+/// How long changes of a table are collected before they are emitted
+const COALESCE_WINDOW: Duration = Duration::from_millis(100);
+
+/// Most changes listed in one event; `count` still counts all of them
+const MAX_CHANGES_PER_EVENT: usize = 100;
+
+/// Event name for changes of a table
+pub fn event_name(table: &str) -> String {
+    format!("db://{}/changed", table)
+}
+
+/// Payload of a `db://<table>/changed` event: the changes of one table within
+/// `COALESCE_WINDOW`. Only identifying fields are sent; listeners re-query what
+/// they display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub table: String,
+    /// Number of changes, including ones left out of `changes`
+    pub count: usize,
+    /// Changed rows, oldest first, each row once with its latest action
+    pub changes: Vec<Change>,
+}
+
+/// One changed row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    /// "create", "update" or "delete"
+    pub action: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Changes of a table waiting to be emitted
+#[derive(Debug, Default)]
+struct Pending {
+    count: usize,
+    changes: Vec<Change>,
+    ids: HashSet<String>,
+}
+
+impl Pending {
+    fn push(&mut self, change: Change) {
+        self.count += 1;
+        if self.ids.contains(&change.id) {
+            if let Some(listed) = self.changes.iter_mut().find(|c| c.id == change.id) {
+                *listed = change;
+            }
+        } else if self.changes.len() < MAX_CHANGES_PER_EVENT {
+            self.ids.insert(change.id.clone());
+            self.changes.push(change);
+        }
+    }
+
+    fn take(&mut self, table: &str) -> Option<ChangeEvent> {
+        if self.count == 0 {
+            return None;
+        }
+        let pending = std::mem::take(self);
+        Some(ChangeEvent {
+            table: table.to_string(),
+            count: pending.count,
+            changes: pending.changes,
+        })
+    }
+}
+
+/// Fields read from a changed row
+#[derive(Debug, Deserialize)]
+struct ChangedRow {
+    id: Thing,
+    #[serde(default)]
+    record_type: Option<String>,
+    #[serde(default)]
+    source: Option<String>,
+}
+
+/// Start one live subscription per table in the background
+/// Aborting the returned tasks ends the subscriptions and kills their live queries
+pub fn start(app: AppHandle, database: Database) -> Vec<JoinHandle<()>> {
+    LIVE_TABLES
+        .iter()
+        .map(|table| {
+            let app = app.clone();
+            let database = database.clone();
+
+            tokio::spawn(async move {
+                if let Err(e) = subscribe(&app, &database, table).await {
+                    tracing::warn!("Live query on '{}' stopped: {}", table, e);
+                }
+            })
+        })
+        .collect()
+}
+
+async fn subscribe(app: &AppHandle, database: &Database, table: &str) -> Result<(), AppError> {
+    let mut stream = database
+        .db
+        .select::<Vec<ChangedRow>>(table)
+        .live()
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to start live query: {}", e)))?;
+
+    tracing::info!("Live query started on '{}'", table);
+
+    let mut pending = Pending::default();
+    let mut deadline: Option<Instant> = None;
+    loop {
+        let next = match deadline {
+            Some(at) => match tokio::time::timeout_at(at, stream.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    emit(app, table, &mut pending);
+                    deadline = None;
+                    continue;
+                }
+            },
+            None => stream.next().await,
+        };
+        let Some(notification) = next else {
+            break;
+        };
+        let notification = match notification {
+            Ok(notification) => notification,
+            Err(e) => {
+                tracing::warn!("Invalid live notification on '{}': {}", table, e);
+                continue;
+            }
+        };
+
+        let action = match notification.action {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+            _ => "unknown",
+        };
+        let row = notification.data;
+        pending.push(Change {
+            action: action.to_string(),
+            id: row.id.to_string(),
+            record_type: row.record_type,
+            source: row.source,
+        });
+        deadline.get_or_insert_with(|| Instant::now() + COALESCE_WINDOW);
+    }
+
+    emit(app, table, &mut pending);
+    Ok(())
+}
+
+fn emit(app: &AppHandle, table: &str, pending: &mut Pending) {
+    if let Some(event) = pending.take(table) {
+        if let Err(e) = app.emit(&event_name(table), &event) {
+            tracing::warn!("Failed to emit change event for '{}': {}", table, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(action: &str, id: &str) -> Change {
+        Change {
+            action: action.to_string(),
+            id: id.to_string(),
+            record_type: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_pending_coalesces_changes() {
+        let mut pending = Pending::default();
+        assert!(pending.take("records").is_none());
+
+        pending.push(change("create", "records:a"));
+        pending.push(change("create", "records:b"));
+        pending.push(change("delete", "records:a"));
+
+        let event = pending.take("records").unwrap();
+        assert_eq!(event.table, "records");
+        assert_eq!(event.count, 3);
+        let listed: Vec<_> = event
+            .changes
+            .iter()
+            .map(|c| (c.action.as_str(), c.id.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![("delete", "records:a"), ("create", "records:b")]
+        );
+        assert!(pending.take("records").is_none());
+    }
+
+    #[test]
+    fn test_pending_caps_listed_changes() {
+        let mut pending = Pending::default();
+        for n in 0..(MAX_CHANGES_PER_EVENT + 50) {
+            pending.push(change("create", &format!("records:{}", n)));
+        }
+
+        let event = pending.take("records").unwrap();
+        assert_eq!(event.count, MAX_CHANGES_PER_EVENT + 50);
+        assert_eq!(event.changes.len(), MAX_CHANGES_PER_EVENT);
+    }
+}
@@ -0,0 +1,468 @@
+// Local HTTP API
+//
+// An optional REST API on 127.0.0.1 for scripts and other apps (Raycast or
+// Alfred scripts, shell tools): query records, create tickets, render prompt
+// sections and trigger fetches. It is off by default (`local_api.enabled`)
+// and every request must carry the API token as `Authorization: Bearer
+// <token>`. The token is random, kept with the secure credentials and can be
+// regenerated from the app.
+//
+// With metrics collection on, `/metrics` serves them in the Prometheus text
+// format (see `metrics`).
+//
+// The server follows the settings: it is started, restarted on another port
+// or stopped whenever one of the "local_api.*" settings changes.
+
+use crate::audit::Actor;
+use crate::credentials;
+use crate::data_sources::DataSourceService;
+use crate::db::query::{RecordPage, RecordQuery};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::fetch_queue::{FetchPriority, FetchQueue};
+use crate::fetcher::FetchSummary;
+use crate::metrics;
+use crate::polling;
+use crate::prompt_gen::record_variables::resolve_record_variables;
+use crate::prompt_gen::renderer::{self, PromptLibrary, RenderedSection};
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use crate::tickets::{CreateTicketRequest, Ticket};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, OnceLock, RwLock};
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Mutex};
+
+pub const ENABLED_SETTING: &str = "local_api.enabled";
+pub const PORT_SETTING: &str = "local_api.port";
+
+pub const DEFAULT_PORT: u16 = 4785;
+
+/// Credential key of the API token
+const TOKEN_CREDENTIAL: &str = "local_api.token";
+
+static CONTEXT: OnceLock<ApiContext> = OnceLock::new();
+static SERVER: Mutex<Option<RunningServer>> = Mutex::const_new(None);
+
+/// Handles the API works with (the same ones the commands use)
+#[derive(Clone)]
+pub struct ApiContext {
+    database: Arc<Mutex<Database>>,
+    data_source_service: Arc<tokio::sync::RwLock<DataSourceService>>,
+    fetch_queue: FetchQueue,
+    token: Arc<RwLock<String>>,
+}
+
+impl ApiContext {
+    pub fn new(
+        database: Arc<Mutex<Database>>,
+        data_source_service: Arc<tokio::sync::RwLock<DataSourceService>>,
+        fetch_queue: FetchQueue,
+    ) -> Self {
+        Self {
+            database,
+            data_source_service,
+            fetch_queue,
+            token: Arc::new(RwLock::new(String::new())),
+        }
+    }
+}
+
+struct RunningServer {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalApiInfo {
+    pub enabled: bool,
+    /// Base URL while the server runs, e.g. "http://127.0.0.1:4785/api/v1"
+    pub base_url: Option<String>,
+    pub token: String,
+}
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new(
+            ENABLED_SETTING,
+            SettingType::Boolean,
+            false.into(),
+            "local_api",
+        )
+        .describe(
+            "Local HTTP API",
+            "Serve a token-protected REST API on 127.0.0.1 for scripts and other apps",
+        ),
+        SettingDefinition::new(
+            PORT_SETTING,
+            SettingType::Number,
+            DEFAULT_PORT.into(),
+            "local_api",
+        )
+        .describe("Local API port", "Port the local HTTP API listens on")
+        .range(Some(1024.0), Some(65535.0)),
+    ]
+}
+
+pub fn is_local_api_setting(key: &str) -> bool {
+    key.starts_with("local_api.")
+}
+
+// ============================================================================
+// Token
+// ============================================================================
+
+fn new_token() -> String {
+    let mut token = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+/// The API token, created on first use
+fn load_token() -> Result<String, AppError> {
+    if let Some(token) = credentials::get_credential(TOKEN_CREDENTIAL.to_string())? {
+        return Ok(token);
+    }
+    let token = new_token();
+    credentials::store_credential(TOKEN_CREDENTIAL.to_string(), token.clone())?;
+    Ok(token)
+}
+
+/// Replace the API token; requests with the old one are rejected from now on
+pub fn regenerate_token() -> Result<String, AppError> {
+    let token = new_token();
+    credentials::store_credential(TOKEN_CREDENTIAL.to_string(), token.clone())?;
+    if let Some(context) = CONTEXT.get() {
+        *context.token.write().unwrap() = token.clone();
+    }
+    Ok(token)
+}
+
+/// Compare without returning early, so timing doesn't reveal the token
+fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+// ============================================================================
+// Server
+// ============================================================================
+
+/// Set the handles the API uses; the server starts with `reload`
+pub fn init(context: ApiContext) {
+    let _ = CONTEXT.set(context);
+}
+
+/// Start, restart or stop the server to match the settings (at startup and
+/// after they change)
+pub async fn reload(settings: &SettingsService) -> Result<(), AppError> {
+    let enabled = settings
+        .get_value(ENABLED_SETTING)
+        .await?
+        .as_bool()
+        .unwrap_or(false);
+    let port = settings
+        .get_value(PORT_SETTING)
+        .await?
+        .as_u64()
+        .and_then(|port| u16::try_from(port).ok())
+        .unwrap_or(DEFAULT_PORT);
+    let Some(context) = CONTEXT.get() else {
+        return Ok(());
+    };
+
+    let mut server = SERVER.lock().await;
+    if enabled && server.as_ref().is_some_and(|s| s.port == port) {
+        return Ok(());
+    }
+    if let Some(running) = server.take() {
+        let _ = running.shutdown.send(());
+        tracing::info!("Local API stopped");
+    }
+    if !enabled {
+        return Ok(());
+    }
+
+    *context.token.write().unwrap() = load_token()?;
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+        .await
+        .map_err(|e| AppError::Config(format!("Local API can't listen on port {}: {}", port, e)))?;
+    *server = Some(RunningServer {
+        port,
+        shutdown: serve(listener, context.clone()),
+    });
+    tracing::info!("Local API listening on http://127.0.0.1:{}", port);
+    Ok(())
+}
+
+/// Whether the API is on, its address and token
+pub async fn info() -> Result<LocalApiInfo, AppError> {
+    let port = SERVER.lock().await.as_ref().map(|s| s.port);
+    Ok(LocalApiInfo {
+        enabled: port.is_some(),
+        base_url: port.map(|port| format!("http://127.0.0.1:{}/api/v1", port)),
+        token: load_token()?,
+    })
+}
+
+/// Serve the API on a listener until the returned sender is used or dropped
+fn serve(listener: TcpListener, context: ApiContext) -> oneshot::Sender<()> {
+    let (shutdown, stopped) = oneshot::channel::<()>();
+    tokio::spawn(async move {
+        let result = axum::serve(listener, router(context))
+            .with_graceful_shutdown(async {
+                let _ = stopped.await;
+            })
+            .await;
+        if let Err(e) = result {
+            tracing::error!("Local API stopped: {}", e);
+        }
+    });
+    shutdown
+}
+
+fn router(context: ApiContext) -> Router {
+    let api = Router::new()
+        .route("/records/query", post(query_records))
+        .route("/tickets", post(create_ticket))
+        .route("/prompts/{section_id}/render", post(render_prompt))
+        .route("/fetch", post(fetch_all))
+        .route("/fetch/{source}", post(fetch_source));
+
+    Router::new()
+        .nest("/api/v1", api)
+        .route("/metrics", get(prometheus_metrics))
+        .layer(middleware::from_fn_with_state(
+            context.clone(),
+            authenticate,
+        ))
+        .with_state(context)
+}
+
+async fn authenticate(State(context): State<ApiContext>, request: Request, next: Next) -> Response {
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    let authorized = {
+        let token = context.token.read().unwrap();
+        !token.is_empty() && token_matches(&token, given)
+    };
+    if !authorized {
+        return HttpError(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid API token".into(),
+        )
+        .into_response();
+    }
+    next.run(request).await
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Error response of a handler, sent as `{ "error": message }`
+struct HttpError(StatusCode, String);
+
+impl From<AppError> for HttpError {
+    fn from(error: AppError) -> Self {
+        let status = match error {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        HttpError(status, error.to_string())
+    }
+}
+
+impl IntoResponse for HttpError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+async fn query_records(
+    State(context): State<ApiContext>,
+    Json(query): Json<RecordQuery>,
+) -> Result<Json<RecordPage>, HttpError> {
+    let db = context.database.lock().await.clone();
+    Ok(Json(db.query_records(query).await?))
+}
+
+async fn create_ticket(
+    State(context): State<ApiContext>,
+    Json(ticket): Json<CreateTicketRequest>,
+) -> Result<(StatusCode, Json<Ticket>), HttpError> {
+    let db = context.database.lock().await;
+    let ticket = db
+        .as_actor(Actor::api("POST /tickets"), db.create_ticket(ticket))
+        .await?;
+    Ok((StatusCode::CREATED, Json(ticket)))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RenderRequest {
+    #[serde(default)]
+    variables: Map<String, Value>,
+    seed: Option<u64>,
+}
+
+/// Render a prompt section (not recorded in the render history)
+async fn render_prompt(
+    State(context): State<ApiContext>,
+    Path(section_id): Path<String>,
+    request: Option<Json<RenderRequest>>,
+) -> Result<Json<RenderedSection>, HttpError> {
+    let RenderRequest {
+        mut variables,
+        seed,
+    } = request.map(|Json(request)| request).unwrap_or_default();
+    let db = context.database.lock().await.clone();
+    let library = PromptLibrary::load(&db).await?;
+    let section = library
+        .find_section(&section_id)
+        .ok_or_else(|| AppError::NotFound(format!("Section {}", section_id)))?;
+    let warnings = resolve_record_variables(&db, section, &mut variables).await;
+    let mut rendered = renderer::render_section(&library, &section_id, variables, seed)?;
+    rendered.warnings.extend(warnings);
+    Ok(Json(rendered))
+}
+
+/// Fetch all enabled data sources
+async fn fetch_all(
+    State(context): State<ApiContext>,
+) -> Result<Json<Vec<FetchSummary>>, HttpError> {
+    let summaries =
+        polling::fetch_enabled(&context.data_source_service, &context.fetch_queue).await?;
+    Ok(Json(summaries))
+}
+
+/// Fetch one data source, by source name or id
+async fn fetch_source(
+    State(context): State<ApiContext>,
+    Path(source): Path<String>,
+) -> Result<Json<FetchSummary>, HttpError> {
+    let data_source = {
+        let service = context.data_source_service.read().await;
+        service
+            .get_all_data_sources()
+            .await?
+            .into_iter()
+            .find(|s| s.source == source || s.id == source)
+            .ok_or_else(|| AppError::NotFound(format!("Data source {}", source)))?
+    };
+    let summary = context
+        .fetch_queue
+        .run(data_source.to_adapter_config(), FetchPriority::Manual)
+        .await;
+    polling::record_fetch(&context.data_source_service, &summary).await;
+    Ok(Json(summary))
+}
+
+async fn prometheus_metrics() -> Result<String, HttpError> {
+    if !metrics::is_enabled() {
+        return Err(HttpError(
+            StatusCode::NOT_FOUND,
+            "Metrics collection is off".into(),
+        ));
+    }
+    Ok(metrics::render_prometheus())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::AdapterRegistry;
+    use crate::plugins::PluginManager;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("abc123", "abc123"));
+        assert!(!token_matches("abc123", "abc124"));
+        assert!(!token_matches("abc123", "abc12"));
+        assert_eq!(new_token().len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_local_api() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(Mutex::new(
+            Database::new(temp_dir.path().join("data")).await.unwrap(),
+        ));
+        let fetch_queue = FetchQueue::new(
+            Arc::new(Mutex::new(PluginManager::new(
+                temp_dir.path().join("plugins"),
+            ))),
+            Arc::new(AdapterRegistry::new()),
+            database.clone(),
+            1,
+        );
+        let context = ApiContext::new(
+            database.clone(),
+            Arc::new(tokio::sync::RwLock::new(DataSourceService::new(
+                database.clone(),
+            ))),
+            fetch_queue,
+        );
+        *context.token.write().unwrap() = "test-token".to_string();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/api/v1", listener.local_addr().unwrap());
+        let _shutdown = serve(listener, context);
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(format!("{}/records/query", base))
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = client
+            .post(format!("{}/tickets", base))
+            .bearer_auth("test-token")
+            .json(&serde_json::json!({ "title": "From a script", "ticket_type": "task" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CREATED);
+        let ticket: Value = response.json().await.unwrap();
+        assert_eq!(ticket["title"], "From a script");
+
+        let response = client
+            .post(format!("{}/records/query", base))
+            .bearer_auth("test-token")
+            .json(&serde_json::json!({ "types": ["nothing"] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let page: Value = response.json().await.unwrap();
+        assert_eq!(page["total"], 0);
+
+        let response = client
+            .post(format!("{}/fetch/unknown-source", base))
+            .bearer_auth("test-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+}
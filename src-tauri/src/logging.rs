@@ -0,0 +1,307 @@
+// Logging
+//
+// Tracing output goes to stderr (readable lines) and to JSON lines in
+// `logs/` under the app data directory, so problems can be diagnosed without
+// a terminal: `get_recent_logs` reads the files back, filtered by level and
+// module. A new file is started every day; at startup the oldest files are
+// deleted beyond `MAX_LOG_FILES` days or `MAX_LOG_BYTES` in total. Secrets are
+// masked in both outputs (see `redaction`).
+
+use crate::error::AppError;
+use crate::redaction::{self, RedactingWriter};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::Level;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Log file names: modulaur.<date>.log
+const LOG_FILE_PREFIX: &str = "modulaur";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Days of log files kept
+const MAX_LOG_FILES: usize = 7;
+
+/// Total size of the log files kept at startup
+const MAX_LOG_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Entries returned by `get_recent_logs` without a limit
+pub const DEFAULT_LIMIT: usize = 200;
+
+static LOG_FILE: OnceLock<RollingFileAppender> = OnceLock::new();
+
+/// A line of the log files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    /// Module path, e.g. "modulaur::fetch_queue"
+    pub target: String,
+    pub message: String,
+    /// Structured fields besides the message
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+}
+
+/// A line as written by the JSON formatter
+#[derive(Deserialize)]
+struct LogLine {
+    timestamp: String,
+    level: String,
+    target: String,
+    #[serde(default)]
+    fields: Map<String, Value>,
+}
+
+/// Directory holding the log files
+pub fn log_dir() -> Result<PathBuf, AppError> {
+    Ok(crate::workspaces::app_root()?.join("logs"))
+}
+
+/// Set up logging to stderr and the log files (stderr only if the log
+/// directory can't be used)
+pub fn init() {
+    let filter = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let file = open_log_file();
+
+    let stderr_layer = tracing_subscriber::fmt::layer()
+        .with_target(true)
+        .with_thread_ids(true)
+        .with_writer(redaction::redacting_stderr);
+    let file_layer = file.as_ref().ok().map(|_| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_thread_ids(true)
+            .with_writer(|| {
+                let file = LOG_FILE.get().expect("log file is opened before logging");
+                RedactingWriter::new(file.make_writer())
+            })
+    });
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::new(filter))
+        .with(stderr_layer)
+        .with(file_layer)
+        .init();
+
+    match file {
+        Ok(dir) => tracing::info!("Logging initialized, writing logs to {}", dir.display()),
+        Err(e) => tracing::warn!("Logging to stderr only: {}", e),
+    }
+}
+
+fn open_log_file() -> Result<PathBuf, AppError> {
+    let dir = log_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    prune_logs(&dir, MAX_LOG_BYTES)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| AppError::Config(format!("Failed to open the log file: {}", e)))?;
+    let _ = LOG_FILE.set(appender);
+    Ok(dir)
+}
+
+/// Log files in the directory, oldest first (the names end in the date)
+fn log_files(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let prefix = format!("{}.", LOG_FILE_PREFIX);
+    let suffix = format!(".{}", LOG_FILE_SUFFIX);
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(&suffix))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Delete the oldest log files until the rest fit in `max_bytes` (the
+/// newest file is always kept)
+fn prune_logs(dir: &Path, max_bytes: u64) -> Result<usize, AppError> {
+    let files = log_files(dir)?;
+    let sizes: Vec<u64> = files
+        .iter()
+        .map(|path| std::fs::metadata(path).map_or(0, |m| m.len()))
+        .collect();
+    let mut total: u64 = sizes.iter().sum();
+
+    let mut removed = 0;
+    for (path, size) in files.iter().zip(&sizes).take(files.len().saturating_sub(1)) {
+        if total <= max_bytes {
+            break;
+        }
+        std::fs::remove_file(path)?;
+        total -= size;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let mut line: LogLine = serde_json::from_str(line).ok()?;
+    let message = match line.fields.remove("message") {
+        Some(Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    Some(LogEntry {
+        timestamp: line.timestamp,
+        level: line.level,
+        target: line.target,
+        message,
+        fields: line.fields,
+    })
+}
+
+/// Whether a target belongs to a module: "db" matches "modulaur::db" and
+/// "modulaur::db::query"
+fn in_module(target: &str, module: &str) -> bool {
+    format!("::{}::", target).contains(&format!("::{}::", module.trim_matches(':')))
+}
+
+/// The most recent log entries, newest first
+/// `level` is the least severe level included ("warn" also returns errors),
+/// `module` a module path such as "db" or "plugins::http".
+pub fn get_recent_logs(
+    level: Option<&str>,
+    limit: usize,
+    module: Option<&str>,
+) -> Result<Vec<LogEntry>, AppError> {
+    let min_level = level
+        .map(|level| {
+            level
+                .parse::<Level>()
+                .map_err(|_| AppError::Validation(format!("Unknown log level: {}", level)))
+        })
+        .transpose()?;
+    let dir = log_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    recent_entries(&dir, min_level, limit, module)
+}
+
+fn recent_entries(
+    dir: &Path,
+    min_level: Option<Level>,
+    limit: usize,
+    module: Option<&str>,
+) -> Result<Vec<LogEntry>, AppError> {
+    let mut entries = Vec::new();
+    for path in log_files(dir)?.iter().rev() {
+        let content = std::fs::read_to_string(path)?;
+        for entry in content.lines().rev().filter_map(parse_line) {
+            if entries.len() >= limit {
+                return Ok(entries);
+            }
+            let level_matches = min_level
+                .is_none_or(|min| entry.level.parse::<Level>().is_ok_and(|level| level <= min));
+            let module_matches = module.is_none_or(|module| in_module(&entry.target, module));
+            if level_matches && module_matches {
+                entries.push(entry);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn line(level: &str, target: &str, message: &str) -> String {
+        serde_json::json!({
+            "timestamp": "2024-05-01T10:00:00.000000Z",
+            "level": level,
+            "fields": { "message": message, "records": 3 },
+            "target": target,
+            "threadId": "ThreadId(1)",
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_recent_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("modulaur.2024-04-30.log"),
+            [
+                line("ERROR", "modulaur::db", "old error"),
+                line("INFO", "modulaur::db", "old info"),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("modulaur.2024-05-01.log"),
+            [
+                line("WARN", "modulaur::plugins::http", "request failed"),
+                "not json".to_string(),
+                line("DEBUG", "modulaur::db::query", "query"),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("other.log"), line("ERROR", "x", "ignored")).unwrap();
+
+        let all = recent_entries(dir, None, DEFAULT_LIMIT, None).unwrap();
+        let messages: Vec<&str> = all.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["query", "request failed", "old info", "old error"]
+        );
+        assert_eq!(all[0].fields["records"], 3);
+        assert!(!all[0].fields.contains_key("message"));
+
+        let warnings = recent_entries(dir, Some(Level::WARN), DEFAULT_LIMIT, None).unwrap();
+        let messages: Vec<&str> = warnings.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["request failed", "old error"]);
+
+        let db = recent_entries(dir, None, 2, Some("db")).unwrap();
+        let messages: Vec<&str> = db.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["query", "old info"]);
+        assert_eq!(
+            recent_entries(dir, None, DEFAULT_LIMIT, Some("plugins::http"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(!in_module("modulaur::dbx", "db"));
+
+        assert!(matches!(
+            get_recent_logs(Some("loud"), DEFAULT_LIMIT, None),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_prune_logs() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        for day in ["2024-04-29", "2024-04-30", "2024-05-01"] {
+            std::fs::write(dir.join(format!("modulaur.{}.log", day)), vec![b'x'; 100]).unwrap();
+        }
+
+        assert_eq!(prune_logs(dir, 250).unwrap(), 1);
+        assert_eq!(prune_logs(dir, 250).unwrap(), 0);
+        // The current file stays however large it is
+        assert_eq!(prune_logs(dir, 10).unwrap(), 1);
+        let left = log_files(dir).unwrap();
+        assert_eq!(left, vec![dir.join("modulaur.2024-05-01.log")]);
+    }
+}
@@ -1,36 +1,101 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod actions;
 mod adapters;
+mod alerts;
+mod app_size;
+mod attachments;
+mod audit;
+mod backup;
+mod board_export;
+mod board_metrics;
+mod boards;
+mod crash;
+mod credential_expiry;
+mod credential_export;
+mod credential_profiles;
 mod credentials;
+mod csv_import;
 mod dashboard;
+mod dashboard_export;
+mod dashboard_refresh;
+mod dashboard_snapshots;
+mod dashboard_templates;
+mod dashboard_validation;
 mod db;
+mod deep_sync;
+mod encrypted_export;
+mod environment_copy;
 mod error;
+mod events;
+mod export;
+mod feeds;
+mod fetch_history;
+mod fetch_queue;
+mod fetcher;
+mod global_search;
+mod health;
+mod i18n;
+mod legacy_migration;
+mod live;
+mod local_api;
+mod logging;
+mod metrics;
 mod models;
+mod network;
+mod notifications;
+mod onboarding;
+mod page_blocks;
+mod page_export;
+mod pipeline;
 mod plugins; // M6: Plugin system
+mod polling;
 mod prompt_gen;
+mod redaction;
+mod reminders;
+mod remote_backup;
+mod retention;
+mod rollups;
+mod scheduler;
+mod search;
+mod share;
+mod sprints;
+mod ticket_import;
 mod tickets; // Ticket/Kanban system
+mod time_entries;
+mod undo;
+mod updates;
+mod vault;
+mod watchers;
+mod webhooks;
 mod window; // Prompt Generator System
-            // Phase 2: New services
+mod workspaces;
+// Phase 2: New services
 mod data_sources;
 mod pages;
 mod plugin_data;
 mod settings;
+mod settings_export;
 
 #[cfg(feature = "sidecar-db")]
 mod sidecar;
 
 use adapters::{AdapterConfig, AdapterRegistry};
 use credentials::{
-    get_machine_password, get_secure_credential, remove_secure_credential, store_secure_credential,
+    get_credential_storage, get_legacy_machine_password, get_local_encryption_key,
+    get_secure_credential, remove_secure_credential, store_secure_credential,
 };
-use dashboard::DashboardService;
 use db::Database;
+use error::{ApiError, AppError, ErrorCode};
 use models::Dashboard;
 use plugins::PluginManager; // M6: Plugin manager
-use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
+use vault::{
+    get_vault_status, lock_credentials, remove_master_password, set_master_password,
+    set_vault_auto_lock, unlock_credentials,
+};
 
 #[cfg(feature = "sidecar-db")]
 use sidecar::SurrealDbSidecar;
@@ -38,51 +103,100 @@ use sidecar::SurrealDbSidecar;
 // Global application state
 #[cfg(feature = "embedded-db")]
 pub struct AppState {
-    pub dashboard_service: Arc<Mutex<DashboardService>>,
     pub plugin_manager: Arc<Mutex<PluginManager>>, // M6: Plugin manager
     pub adapter_registry: Arc<AdapterRegistry>,
+    // Never held across network I/O or sleeps. Read-only commands clone the
+    // handle and drop the guard right away (as does the registry sync, which
+    // downloads between writes); writing commands keep it so their checks and
+    // writes don't interleave with each other. Fetches, deep syncs and exports
+    // lock it per write or chunk; only database imports and restores keep it
+    // for their whole run. The plugin manager is only locked to look up plugin
+    // handles.
     pub database: Arc<Mutex<Database>>,
     // Phase 2: New services (not using page_service - using direct DB access)
-    pub data_source_service: Arc<Mutex<data_sources::DataSourceService>>,
-    pub settings_service: Arc<Mutex<settings::SettingsService>>,
-    pub plugin_data_service: Arc<Mutex<plugin_data::PluginDataService>>,
+    // Read-mostly: commands share read locks; write locks only swap a service on a
+    // workspace switch or change the settings schema
+    pub data_source_service: Arc<RwLock<data_sources::DataSourceService>>,
+    pub settings_service: Arc<RwLock<settings::SettingsService>>,
+    pub plugin_data_service: Arc<RwLock<plugin_data::PluginDataService>>,
+    pub polling_scheduler: Arc<Mutex<polling::PollingScheduler>>,
+    pub fetch_queue: fetch_queue::FetchQueue,
+    pub scheduler: scheduler::JobScheduler,
+    pub workspaces: Arc<Mutex<workspaces::WorkspaceRegistry>>,
+    pub actions: Arc<RwLock<actions::ActionRegistry>>,
+    // Live query subscriptions on the active workspace database
+    pub live_queries: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
 }
 
 #[cfg(feature = "sidecar-db")]
 struct AppState {
-    dashboard_service: Arc<Mutex<DashboardService>>,
     plugin_manager: Arc<Mutex<PluginManager>>, // M6: Plugin manager
     adapter_registry: Arc<AdapterRegistry>,
+    // Never held across network I/O or sleeps. Read-only commands clone the
+    // handle and drop the guard right away (as does the registry sync, which
+    // downloads between writes); writing commands keep it so their checks and
+    // writes don't interleave with each other. Fetches, deep syncs and exports
+    // lock it per write or chunk; only database imports and restores keep it
+    // for their whole run. The plugin manager is only locked to look up plugin
+    // handles.
     database: Arc<Mutex<Database>>,
     _sidecar: Arc<Mutex<SurrealDbSidecar>>, // Keep sidecar alive
     // Phase 2: New services
     page_service: Arc<Mutex<pages::PageService>>,
-    data_source_service: Arc<Mutex<data_sources::DataSourceService>>,
-    settings_service: Arc<Mutex<settings::SettingsService>>,
-    plugin_data_service: Arc<Mutex<plugin_data::PluginDataService>>,
+    // Read-mostly: commands share read locks; write locks only swap a service on a
+    // workspace switch or change the settings schema
+    data_source_service: Arc<RwLock<data_sources::DataSourceService>>,
+    settings_service: Arc<RwLock<settings::SettingsService>>,
+    plugin_data_service: Arc<RwLock<plugin_data::PluginDataService>>,
+    polling_scheduler: Arc<Mutex<polling::PollingScheduler>>,
+    fetch_queue: fetch_queue::FetchQueue,
+    scheduler: scheduler::JobScheduler,
+    workspaces: Arc<Mutex<workspaces::WorkspaceRegistry>>,
+    actions: Arc<RwLock<actions::ActionRegistry>>,
+    // Live query subscriptions on the active workspace database
+    live_queries: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
 }
 
 #[tokio::main]
 async fn main() {
     // Initialize logging
-    init_logging();
+    logging::init();
+    // Crash reports for the "something went wrong last time" dialog
+    crash::install_panic_hook();
+
+    // Resolve the active workspace (separate database and dashboards per workspace)
+    let workspaces = workspaces::WorkspaceRegistry::load(
+        &workspaces::app_root().expect("Failed to get local data directory"),
+    )
+    .expect("Failed to load workspaces");
+    let workspace = workspaces
+        .active()
+        .expect("Failed to resolve active workspace")
+        .clone();
+    tracing::info!("Using workspace '{}'", workspace.name);
+
+    // Credentials vault (master password) is shared by all workspaces
+    if let Err(e) =
+        workspaces::app_root().and_then(|root| vault::init_vault(&root.join("credentials.vault")))
+    {
+        tracing::warn!("Failed to load the credential vault: {}", e);
+    }
 
-    // Initialize dashboard service
-    let dashboard_service =
-        DashboardService::new().expect("Failed to initialize dashboard service");
+    // Network settings from the last run, until the settings database is up
+    network::load_cached();
 
     // Get data directory
-    let data_dir = dirs::data_local_dir()
-        .expect("Failed to get local data directory")
-        .join("modulaur")
-        .join("data");
+    let data_dir = workspace.data_dir.clone();
 
     #[cfg(feature = "sidecar-db")]
     let sidecar = {
         // Start SurrealDB sidecar
         tracing::info!("Starting SurrealDB sidecar...");
-        let sidecar =
-            SurrealDbSidecar::start(data_dir.clone()).expect("Failed to start SurrealDB sidecar");
+        let (binary_path, managed) = sidecar::ensure_binary(&data_dir)
+            .await
+            .expect("Failed to get the SurrealDB binary");
+        let sidecar = SurrealDbSidecar::start(data_dir.clone(), binary_path, managed)
+            .expect("Failed to start SurrealDB sidecar");
 
         // Wait for SurrealDB to be ready
         sidecar
@@ -97,10 +211,18 @@ async fn main() {
     tracing::info!("Using embedded SurrealDB with SurrealKV");
 
     // Initialize database connection
-    let database = Database::new(data_dir)
+    let database = Database::open(&workspace)
         .await
         .expect("Failed to connect to database");
 
+    // Dashboards used to be files in the workspace; move them into the database once
+    if let Err(e) = database
+        .migrate_dashboard_files(&workspace.dashboards_dir)
+        .await
+    {
+        tracing::warn!("Failed to migrate dashboard files: {}", e);
+    }
+
     // M6: Initialize plugin manager
     // In dev mode, use project plugins directory
     // In production, use AppData
@@ -118,33 +240,11 @@ async fn main() {
 
     tracing::info!("Plugin directory: {:?}", plugin_dir);
 
-    // M6 DEBUG: VERY OBVIOUS MESSAGE - Use eprintln! for stderr (unbuffered)
-    eprintln!("\n\n");
-    eprintln!("═══════════════════════════════════════════");
-    eprintln!("🚀 BACKEND STARTED WITH DEBUG CODE! 🚀");
-    eprintln!("═══════════════════════════════════════════");
-    eprintln!("\n");
-
-    // M6 DEBUG: Print to stderr so we can see it (eprintln! is unbuffered)
-    eprintln!("============================================");
-    eprintln!("PLUGIN DISCOVERY DEBUG");
-    eprintln!("============================================");
-    eprintln!("Plugin directory: {:?}", plugin_dir);
-    eprintln!("Directory exists: {}", plugin_dir.exists());
-    if plugin_dir.exists() {
-        eprintln!("Contents:");
-        if let Ok(entries) = std::fs::read_dir(&plugin_dir) {
-            for entry in entries.flatten() {
-                eprintln!("  - {:?}", entry.file_name());
-                let manifest_path = entry.path().join("manifest.json");
-                eprintln!("    manifest.json exists: {}", manifest_path.exists());
-            }
-        }
-    }
-    eprintln!("============================================");
-
     let mut plugin_manager = PluginManager::new(plugin_dir);
 
+    // Subscribed before plugins load, so quarantines at startup are notified
+    let notification_events = events::subscribe();
+
     // Load plugins
     match plugin_manager.load_plugins().await {
         Ok(count) => tracing::info!("Loaded {} plugins", count),
@@ -162,94 +262,428 @@ async fn main() {
     // Note: Pages use direct DB access via Tauri commands (no service layer)
     let data_source_service =
         data_sources::DataSourceService::new(Arc::new(Mutex::new(database.clone())));
-    let settings_service = settings::SettingsService::new(Arc::new(Mutex::new(database.clone())));
+    let mut settings_service =
+        settings::SettingsService::new(Arc::new(Mutex::new(database.clone())));
+    register_plugin_settings(&mut settings_service, &plugin_manager);
+    if let Err(e) = i18n::reload(&settings_service).await {
+        tracing::warn!("Failed to load the locale setting: {}", e);
+    }
+    if let Err(e) = network::reload(&settings_service).await {
+        tracing::warn!("Failed to load network settings: {}", e);
+    }
+    if let Err(e) = metrics::reload(&settings_service).await {
+        tracing::warn!("Failed to load the metrics setting: {}", e);
+    }
     let plugin_data_service =
         plugin_data::PluginDataService::new(Arc::new(Mutex::new(database.clone())));
+    let mut action_registry = actions::ActionRegistry::new();
+    register_plugin_actions(&mut action_registry, &plugin_manager);
+
+    // Shared fetch queue bounding how many adapters run at once
+    let database = Arc::new(Mutex::new(database));
+    let adapter_registry = Arc::new(adapter_registry);
+    let plugin_manager = Arc::new(Mutex::new(plugin_manager));
+    let fetch_queue = fetch_queue::FetchQueue::new(
+        plugin_manager.clone(),
+        adapter_registry.clone(),
+        database.clone(),
+        fetcher::DEFAULT_MAX_CONCURRENCY,
+    );
+    // Recurring background jobs, registered below and started once the app is built
+    let job_scheduler = scheduler::JobScheduler::new(database.clone());
 
     #[cfg(feature = "embedded-db")]
     let app_state = AppState {
-        dashboard_service: Arc::new(Mutex::new(dashboard_service)),
-        database: Arc::new(Mutex::new(database)),
-        adapter_registry: Arc::new(adapter_registry),
-        plugin_manager: Arc::new(Mutex::new(plugin_manager)),
-        data_source_service: Arc::new(Mutex::new(data_source_service)),
-        settings_service: Arc::new(Mutex::new(settings_service)),
-        plugin_data_service: Arc::new(Mutex::new(plugin_data_service)),
+        database,
+        adapter_registry,
+        plugin_manager,
+        data_source_service: Arc::new(RwLock::new(data_source_service)),
+        settings_service: Arc::new(RwLock::new(settings_service)),
+        plugin_data_service: Arc::new(RwLock::new(plugin_data_service)),
+        polling_scheduler: Arc::new(Mutex::new(polling::PollingScheduler::new())),
+        fetch_queue,
+        scheduler: job_scheduler.clone(),
+        workspaces: Arc::new(Mutex::new(workspaces)),
+        actions: Arc::new(RwLock::new(action_registry)),
+        live_queries: Arc::new(Mutex::new(Vec::new())),
     };
 
     #[cfg(feature = "sidecar-db")]
     let app_state = AppState {
-        dashboard_service: Arc::new(Mutex::new(dashboard_service)),
-        database: Arc::new(Mutex::new(database)),
-        adapter_registry: Arc::new(adapter_registry),
+        database,
+        adapter_registry,
         _sidecar: Arc::new(Mutex::new(sidecar)),
-        plugin_manager: Arc::new(Mutex::new(plugin_manager)),
+        plugin_manager,
         page_service: Arc::new(Mutex::new(page_service)),
-        data_source_service: Arc::new(Mutex::new(data_source_service)),
-        settings_service: Arc::new(Mutex::new(settings_service)),
-        plugin_data_service: Arc::new(Mutex::new(plugin_data_service)),
+        data_source_service: Arc::new(RwLock::new(data_source_service)),
+        settings_service: Arc::new(RwLock::new(settings_service)),
+        plugin_data_service: Arc::new(RwLock::new(plugin_data_service)),
+        polling_scheduler: Arc::new(Mutex::new(polling::PollingScheduler::new())),
+        fetch_queue,
+        scheduler: job_scheduler.clone(),
+        workspaces: Arc::new(Mutex::new(workspaces)),
+        actions: Arc::new(RwLock::new(action_registry)),
+        live_queries: Arc::new(Mutex::new(Vec::new())),
     };
 
+    // Local HTTP API for scripts (configured via the "local_api.*" settings)
+    local_api::init(local_api::ApiContext::new(
+        app_state.database.clone(),
+        app_state.data_source_service.clone(),
+        app_state.fetch_queue.clone(),
+    ));
+    if let Err(e) = local_api::reload(&*app_state.settings_service.read().await).await {
+        tracing::warn!("Failed to start the local API: {}", e);
+    }
+
+    // Fetch data sources with a polling interval
+    job_scheduler
+        .register(polling::job(
+            app_state.polling_scheduler.clone(),
+            app_state.data_source_service.clone(),
+            app_state.fetch_queue.clone(),
+        ))
+        .await;
+
+    // Restart the SurrealDB sidecar when it exits
+    #[cfg(feature = "sidecar-db")]
+    job_scheduler
+        .register(sidecar::job(app_state._sidecar.clone()))
+        .await;
+
+    // Apply persisted retention rules
+    job_scheduler
+        .register(retention::job(app_state.database.clone()))
+        .await;
+
+    // Roll up metric records and prune raw points
+    job_scheduler
+        .register(rollups::job(app_state.database.clone()))
+        .await;
+
+    // Prune undo journal entries older than "undo.window_minutes"
+    job_scheduler
+        .register(undo::job(
+            app_state.settings_service.clone(),
+            app_state.database.clone(),
+            app_state.workspaces.clone(),
+        ))
+        .await;
+
+    // Scheduled database backups (configured via the "backup.*" settings)
+    job_scheduler
+        .register(backup::job(
+            app_state.settings_service.clone(),
+            app_state.database.clone(),
+        ))
+        .await;
+
+    // Prune the audit log (limits from the "audit.*" settings)
+    job_scheduler
+        .register(audit::job(
+            app_state.settings_service.clone(),
+            app_state.database.clone(),
+        ))
+        .await;
+
+    // Refresh feed subscriptions
+    job_scheduler
+        .register(feeds::job(app_state.database.clone()))
+        .await;
+
+    // Convert deprecated prompt templates when "prompt_gen.migrate_templates_on_startup" is set
+    prompt_gen::template_migration::start(
+        app_state.settings_service.clone(),
+        app_state.database.clone(),
+    );
+
     #[cfg(feature = "sidecar-db")]
     let sidecar_for_cleanup = app_state._sidecar.clone();
 
+    // Database handle for the live query subscriptions started once the app is built
+    let live_database = app_state.database.lock().await.clone();
+    let live_queries = app_state.live_queries.clone();
+    let reminder_database = app_state.database.clone();
+    let refresh_database = app_state.database.clone();
+    let refresh_data_sources = app_state.data_source_service.clone();
+    let refresh_fetch_queue = app_state.fetch_queue.clone();
+    let alert_database = app_state.database.clone();
+    let webhook_database = app_state.database.clone();
+    let notification_database = app_state.database.clone();
+    let notification_settings = app_state.settings_service.clone();
+    // Dashboards whose windows were open at exit are reopened if they still exist
+    let window_dashboards: Vec<(String, String)> = match live_database.get_dashboards().await {
+        Ok(dashboards) => dashboards
+            .into_iter()
+            .map(|dashboard| (dashboard.id, dashboard.name))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to load dashboards for their windows: {}", e);
+            Vec::new()
+        }
+    };
+
     let app = tauri::Builder::default()
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             check_app_size,
+            get_app_health,
+            get_sidecar_version,
+            list_actions,
+            run_action,
+            is_first_run,
+            check_for_updates,
+            install_update,
+            skip_update_version,
+            seed_starter_workspace,
             get_config,
             get_dashboards,
             get_dashboard,
             save_dashboard,
             delete_dashboard,
+            check_dashboard_panels,
+            duplicate_dashboard,
+            open_dashboard_window,
+            get_dashboard_versions,
+            restore_dashboard_version,
+            refresh_dashboard,
+            snapshot_dashboard,
+            list_dashboard_snapshots,
+            get_dashboard_snapshot,
+            delete_dashboard_snapshot,
+            export_dashboard,
+            import_dashboard,
+            list_dashboard_templates,
+            create_dashboard_from_template,
             // M6: Plugin system
             get_installed_plugins,
             reload_plugins,
             get_plugin_info,
             unload_plugin,
+            uninstall_plugin,
+            get_plugin_storage_usage,
+            insert_plugin_row,
+            query_plugin_rows,
+            delete_plugin_row,
             test_plugin_fetch,
             // M3: Data staging commands
             get_staged_records,
             get_records_by_type,
+            query_records,
+            aggregate_records,
+            get_widget_data,
+            resolve_drilldown,
+            get_timeseries,
+            check_database_integrity,
+            list_tags,
+            add_tags_to_records,
+            remove_tags_from_records,
+            rename_tag,
+            link_records,
+            unlink_records,
+            get_related,
             get_record_count,
             upsert_record,
             update_record,
+            patch_record,
             delete_record,
             // M3: Adapter commands
             list_adapters,
             get_adapter_default_config,
             test_adapter_connection,
             fetch_adapter_data,
+            get_data_sources,
+            get_data_source,
+            save_data_source,
+            update_data_source,
+            delete_data_source,
+            fetch_all_enabled,
+            cancel_fetch,
+            get_fetch_queue,
+            set_fetch_concurrency,
+            pause_polling,
+            resume_polling,
+            get_polling_status,
+            list_jobs,
+            pause_job,
+            resume_job,
+            run_job_now,
+            set_job_schedule,
+            get_fetch_history,
+            get_source_status,
+            get_data_source_status,
+            get_schema_version,
+            get_settings_schema,
+            get_all_settings,
+            get_setting,
+            set_setting,
+            export_settings,
+            import_settings,
+            reset_settings,
+            create_backup,
+            list_backups,
+            restore_backup,
+            list_remote_backups,
+            restore_from_remote,
+            set_remote_backup_password,
+            list_workspaces,
+            create_workspace,
+            switch_workspace,
+            get_database_connection,
+            set_database_connection,
+            add_attachment,
+            get_attachment,
+            list_attachments,
+            delete_attachment,
+            search_records,
+            global_search,
+            format_record_for_share,
+            reset_sync_cursor,
             // M5: Database management
             clear_all_records,
             get_database_stats,
             cleanup_old_records,
+            list_retention_rules,
+            save_retention_rule,
+            delete_retention_rule,
+            preview_retention,
+            apply_retention,
+            list_alert_rules,
+            save_alert_rule,
+            delete_alert_rule,
+            evaluate_alert_rules,
+            list_rollup_rules,
+            save_rollup_rule,
+            delete_rollup_rule,
+            run_rollups,
+            get_notifications,
+            mark_notification_read,
+            get_recent_logs,
+            get_metrics_snapshot,
+            get_last_crash_report,
+            dismiss_crash_report,
+            get_local_api_info,
+            regenerate_local_api_token,
+            list_undoable_operations,
+            undo_last_operation,
+            get_audit_log,
+            list_webhooks,
+            save_webhook,
+            delete_webhook,
+            test_webhook,
             delete_records_by_type,
             delete_records_by_source_and_type,
             // Database export/import
             export_database,
             import_database,
+            export_database_to_file,
+            export_database_encrypted,
+            import_database_encrypted,
+            migrate_legacy_database,
+            copy_environment_data,
+            export_records,
+            import_records_from_csv,
             // M5 Phase 5: Secure credential storage
             store_secure_credential,
             get_secure_credential,
             remove_secure_credential,
-            get_machine_password,
+            get_credential_storage,
+            get_local_encryption_key,
+            get_legacy_machine_password,
+            save_credential_profile,
+            list_credential_profiles,
+            delete_credential_profile,
+            set_credential_expiry,
+            list_credential_expiry,
+            get_expiring_credentials,
+            export_credentials,
+            import_credentials,
+            // Master-password vault
+            get_vault_status,
+            set_master_password,
+            remove_master_password,
+            unlock_credentials,
+            lock_credentials,
+            set_vault_auto_lock,
             // Ticket/Kanban system
             create_ticket,
             update_ticket,
             delete_ticket,
+            archive_ticket,
+            watch_ticket,
+            get_watched_tickets,
+            archive_done_older_than,
+            get_archived_tickets,
             get_tickets,
+            query_tickets,
+            import_tickets,
+            get_ticket_people,
+            save_ticket_people,
             move_ticket,
+            get_board_state,
+            get_board_grouped,
+            get_board_metrics,
+            get_ticket_history,
+            get_board_config,
+            save_board_columns,
+            add_board_column,
+            update_board_column,
+            reorder_board_columns,
+            delete_board_column,
+            reset_board_config,
+            set_board_key_prefix,
+            export_board,
+            import_board,
+            get_due_tickets,
+            list_ticket_labels,
+            rename_ticket_label,
+            add_checklist_item,
+            toggle_checklist_item,
+            remove_checklist_item,
             add_comment,
+            add_ticket_relation,
+            remove_ticket_relation,
+            create_sprint,
+            get_sprints,
+            update_sprint,
+            delete_sprint,
+            set_ticket_sprint,
+            get_sprint_summary,
+            close_sprint,
+            start_ticket_timer,
+            stop_ticket_timer,
+            add_time_entry,
+            delete_time_entry,
+            get_ticket_time_entries,
+            get_running_timer,
+            get_time_summary,
             // RSS Feed Reader
             fetch_rss_feed,
+            list_feed_subscriptions,
+            subscribe_feed,
+            unsubscribe_feed,
+            refresh_feed,
+            get_feed_items,
+            mark_feed_item_read,
+            mark_feed_read,
             // Phase 2 M10: Page management
             pages::get_pages,
+            pages::get_pages_tree,
             pages::create_page,
             pages::update_page,
             pages::delete_page,
             pages::reorder_pages,
+            pages::move_page,
             pages::clear_pages_table,
+            get_page_blocks,
+            get_page_block,
+            insert_page_block,
+            update_page_block,
+            move_page_block,
+            delete_page_block,
+            export_page,
             // ============================================
             // PLUGIN: prompt-generator
             // Backend commands for plugins/featured/prompt-generator/
@@ -260,31 +694,116 @@ async fn main() {
             prompt_gen::commands::update_prompt_package,
             prompt_gen::commands::delete_prompt_package,
             prompt_gen::commands::get_prompt_templates,
+            prompt_gen::commands::migrate_prompt_templates,
             prompt_gen::commands::create_prompt_template,
             prompt_gen::commands::update_prompt_template,
             prompt_gen::commands::delete_prompt_template,
             prompt_gen::commands::get_prompt_sections,
             prompt_gen::commands::create_prompt_section,
             prompt_gen::commands::update_prompt_section,
+            prompt_gen::commands::get_section_revisions,
+            prompt_gen::commands::revert_section,
             prompt_gen::commands::delete_prompt_section,
+            prompt_gen::commands::get_section_references,
+            prompt_gen::commands::get_package_graph,
+            prompt_gen::commands::validate_variables,
+            prompt_gen::commands::render_prompt_section,
+            prompt_gen::commands::render_prompt_matrix,
+            prompt_gen::commands::get_prompt_chains,
+            prompt_gen::commands::create_prompt_chain,
+            prompt_gen::commands::update_prompt_chain,
+            prompt_gen::commands::delete_prompt_chain,
+            prompt_gen::commands::run_prompt_chain,
+            prompt_gen::commands::get_prompt_chain_runs,
+            prompt_gen::commands::get_prompt_presets,
+            prompt_gen::commands::create_prompt_preset,
+            prompt_gen::commands::update_prompt_preset,
+            prompt_gen::commands::delete_prompt_preset,
+            prompt_gen::commands::set_section_favorite,
+            prompt_gen::commands::get_favorite_sections,
+            prompt_gen::commands::get_recent_sections,
+            prompt_gen::commands::get_render_history,
+            prompt_gen::commands::replay_render,
+            prompt_gen::commands::delete_prompt_render_history,
+            prompt_gen::commands::validate_prompt_section,
+            prompt_gen::commands::validate_prompt_package,
+            prompt_gen::commands::run_section_examples,
+            prompt_gen::commands::run_package_examples,
             prompt_gen::commands::get_separator_sets,
+            prompt_gen::commands::get_builtin_separator_sets,
             prompt_gen::commands::create_separator_set,
+            prompt_gen::commands::update_separator_set,
+            prompt_gen::commands::delete_separator_set,
             prompt_gen::commands::get_prompt_data_types,
             prompt_gen::commands::create_prompt_data_type,
+            prompt_gen::commands::update_prompt_data_type,
+            prompt_gen::commands::delete_prompt_data_type,
             prompt_gen::commands::get_prompt_tags,
             prompt_gen::commands::create_prompt_tag,
+            prompt_gen::commands::update_prompt_tag,
+            prompt_gen::commands::delete_prompt_tag,
+            prompt_gen::commands::rename_prompt_tag,
+            prompt_gen::commands::tag_prompt_sections,
             prompt_gen::commands::export_prompt_package,
             prompt_gen::commands::import_prompt_package,
+            prompt_gen::commands::export_prompt_sections,
+            prompt_gen::commands::export_prompt_package_archive,
+            prompt_gen::commands::import_prompt_package_archive,
+            prompt_gen::commands::sync_prompt_registry,
+            prompt_gen::commands::search_prompt_content,
+            prompt_gen::commands::duplicate_prompt_package,
+            prompt_gen::commands::import_templates_from_text,
             prompt_gen::commands::seed_example_packages,
             prompt_gen::commands::seed_text2image_common_package,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
+    // Restore window sizes and positions and reopen dashboard windows
+    window::init(app.handle(), &window_dashboards);
+
+    // Forward the application event bus to the frontend
+    events::start_bridge(app.handle().clone());
+
+    // Notification center and desktop notifications
+    notifications::start(
+        app.handle().clone(),
+        notification_database,
+        notification_settings,
+        notification_events,
+    );
+
+    // Push record, ticket and page changes to the frontend as events
+    *live_queries.lock().await = live::start(app.handle().clone(), live_database);
+
+    // Announce tickets that become due soon or overdue
+    reminders::start(app.handle().clone(), reminder_database);
+
+    // Warn about credentials that expire soon or need rotating
+    credential_expiry::start(app.handle().clone());
+
+    // Refresh dashboards that have a refresh interval and push their panel data
+    dashboard_refresh::start(
+        app.handle().clone(),
+        refresh_database,
+        refresh_data_sources,
+        refresh_fetch_queue,
+    );
+
+    // Evaluate alert rules after fetches and on a schedule; fired alerts become notifications
+    alerts::start(alert_database.clone());
+    job_scheduler.register(alerts::job(alert_database)).await;
+
+    // Post matching events to the configured webhooks
+    webhooks::start(webhook_database);
+
+    job_scheduler.start();
+
     // Register cleanup handler before running
     #[cfg(feature = "sidecar-db")]
     app.run(move |_app_handle, event| {
         if let tauri::RunEvent::Exit = event {
+            window::save();
             tracing::info!("Application exiting, cleaning up SurrealDB sidecar...");
             if let Ok(mut sidecar) = sidecar_for_cleanup.try_lock() {
                 sidecar.stop();
@@ -297,6 +816,7 @@ async fn main() {
     #[cfg(feature = "embedded-db")]
     app.run(|_app_handle, event| {
         if let tauri::RunEvent::Exit = event {
+            window::save();
             tracing::info!("Application exiting (embedded mode)...");
         }
     });
@@ -309,46 +829,157 @@ async fn main() {
 #[tauri::command]
 async fn get_installed_plugins(
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<plugins::PluginMetadata>, String> {
+) -> Result<Vec<plugins::PluginMetadata>, ApiError> {
     let plugin_manager = state.plugin_manager.lock().await;
     Ok(plugin_manager.get_all_plugins())
 }
 
+/// Declare the actions of the loaded plugins, logging invalid ones
+fn register_plugin_actions(registry: &mut actions::ActionRegistry, plugins: &PluginManager) {
+    registry.clear_plugin_actions();
+    for (plugin, definitions) in plugins.action_schemas() {
+        if let Err(e) = registry.register_plugin_actions(&plugin, &definitions) {
+            tracing::warn!("Ignoring actions of plugin {}: {}", plugin, e);
+        }
+    }
+}
+
+/// Declare the settings of the loaded plugins, logging invalid ones
+fn register_plugin_settings(settings: &mut settings::SettingsService, plugins: &PluginManager) {
+    for (plugin, definitions) in plugins.settings_schemas() {
+        if let Err(e) = settings.register_plugin_settings(&plugin, &definitions) {
+            tracing::warn!("Ignoring settings of plugin {}: {}", plugin, e);
+        }
+    }
+}
+
 #[tauri::command]
-async fn reload_plugins(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+async fn reload_plugins(state: tauri::State<'_, AppState>) -> Result<usize, ApiError> {
     let mut plugin_manager = state.plugin_manager.lock().await;
 
     // Shutdown existing plugins
-    plugin_manager
-        .shutdown_all()
-        .await
-        .map_err(|e| e.to_string())?;
+    plugin_manager.shutdown_all().await?;
 
     // Reload plugins
-    plugin_manager
-        .load_plugins()
-        .await
-        .map_err(|e| e.to_string())
+    let count = plugin_manager.load_plugins().await?;
+    register_plugin_settings(&mut *state.settings_service.write().await, &plugin_manager);
+    register_plugin_actions(&mut *state.actions.write().await, &plugin_manager);
+    Ok(count)
 }
 
 #[tauri::command]
 async fn get_plugin_info(
     name: String,
     state: tauri::State<'_, AppState>,
-) -> Result<Option<plugins::PluginMetadata>, String> {
+) -> Result<Option<plugins::PluginMetadata>, ApiError> {
     let plugin_manager = state.plugin_manager.lock().await;
 
     Ok(plugin_manager.get_plugin(&name).map(|p| p.metadata()))
 }
 
 #[tauri::command]
-async fn unload_plugin(name: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn unload_plugin(name: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
     let mut plugin_manager = state.plugin_manager.lock().await;
 
     plugin_manager
         .unload_plugin(&name)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ApiError::from)
+}
+
+/// Uninstall a plugin: unload it, delete its directory and (unless `keep_data`)
+/// remove its namespaced tables and key/value data
+#[tauri::command]
+async fn uninstall_plugin(
+    name: String,
+    keep_data: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    state
+        .plugin_manager
+        .lock()
+        .await
+        .uninstall_plugin(&name)
+        .await?;
+
+    if !keep_data.unwrap_or(false) {
+        state
+            .plugin_data_service
+            .read()
+            .await
+            .purge_plugin(&name)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Storage used by a plugin's tables and key/value data, with its quota
+#[tauri::command]
+async fn get_plugin_storage_usage(
+    plugin_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<plugin_data::PluginStorageUsage, ApiError> {
+    let quota = state.plugin_manager.lock().await.storage_quota(&plugin_id);
+    state
+        .plugin_data_service
+        .read()
+        .await
+        .storage_usage(&plugin_id, quota)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Insert a row into a plugin table (`plugin_<plugin_id>_<table>`)
+#[tauri::command]
+async fn insert_plugin_row(
+    plugin_id: String,
+    table: String,
+    row: serde_json::Value,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value, ApiError> {
+    let quota = state.plugin_manager.lock().await.storage_quota(&plugin_id);
+    state
+        .plugin_data_service
+        .read()
+        .await
+        .insert_plugin_row(&plugin_id, &table, row, quota)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Read rows from a plugin table (default: first 100)
+#[tauri::command]
+async fn query_plugin_rows(
+    plugin_id: String,
+    table: String,
+    start: Option<usize>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, ApiError> {
+    state
+        .plugin_data_service
+        .read()
+        .await
+        .query_plugin_rows(&plugin_id, &table, start.unwrap_or(0), limit.unwrap_or(100))
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Delete a row from a plugin table
+#[tauri::command]
+async fn delete_plugin_row(
+    plugin_id: String,
+    table: String,
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    state
+        .plugin_data_service
+        .read()
+        .await
+        .delete_plugin_row(&plugin_id, &table, &id)
+        .await
+        .map_err(ApiError::from)
 }
 
 /// M6: Test plugin fetch functionality
@@ -356,11 +987,9 @@ async fn unload_plugin(name: String, state: tauri::State<'_, AppState>) -> Resul
 async fn test_plugin_fetch(
     plugin_name: String,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ApiError> {
     tracing::info!("Testing plugin fetch for: {}", plugin_name);
 
-    let plugin_manager = state.plugin_manager.lock().await;
-
     // Create a test adapter config
     let config = adapters::AdapterConfig {
         adapter_type: "example".to_string(),
@@ -370,14 +999,17 @@ async fn test_plugin_fetch(
         parameters: serde_json::json!({}),
         polling_interval: None,
         enabled: true,
+        pipeline: None,
+        unique_key: None,
+        redaction: None,
     };
 
     // Get the plugin and call fetch
-    let plugin = plugin_manager
-        .get_plugin(&plugin_name)
-        .ok_or_else(|| format!("Plugin '{}' not found", plugin_name))?;
+    let plugin = state.plugin_manager.lock().await.get_plugin(&plugin_name);
+    let plugin =
+        plugin.ok_or_else(|| AppError::NotFound(format!("Plugin '{}' not found", plugin_name)))?;
 
-    let records = plugin.fetch(&config).await.map_err(|e| e.to_string())?;
+    let records = plugin.fetch(&config).await?;
 
     Ok(serde_json::json!({
         "plugin": plugin_name,
@@ -386,37 +1018,162 @@ async fn test_plugin_fetch(
     }))
 }
 
-fn init_logging() {
-    let log_level = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
-    tracing_subscriber::fmt()
-        .with_env_filter(log_level)
-        .with_target(true)
-        .with_thread_ids(true)
-        .init();
-    tracing::info!("Logging initialized");
+/// Disk usage of the executable, plugins, workspace databases and logs, with
+/// suggestions for the parts that take up a lot of space
+#[tauri::command]
+async fn check_app_size(state: tauri::State<'_, AppState>) -> Result<app_size::AppSize, ApiError> {
+    let plugin_dir = state.plugin_manager.lock().await.plugin_dir().to_path_buf();
+    let registry = state.workspaces.lock().await.clone();
+    let db = state.database.lock().await.clone();
+    let size = app_size::measure(&db, &registry, &plugin_dir).await?;
+
+    tracing::info!(
+        "App size check: {} ({} suggestions)",
+        app_size::format_bytes(size.total_bytes),
+        size.suggestions.len()
+    );
+    Ok(size)
 }
 
+/// Database, sidecar, plugin, scheduler and disk health with an overall status
 #[tauri::command]
-async fn check_app_size() -> Result<AppSize, String> {
-    // Stub: Will check actual binary size in production
-    let size_mb = 15.5;
-    let is_acceptable = size_mb < 20.0;
+async fn get_app_health(state: tauri::State<'_, AppState>) -> Result<health::AppHealth, ApiError> {
+    let database = health::database_health(&*state.database.lock().await).await;
 
-    tracing::info!(
-        "App size check: {:.1}MB (acceptable: {})",
-        size_mb,
-        is_acceptable
+    #[cfg(feature = "sidecar-db")]
+    let sidecar = Some(state._sidecar.lock().await.health());
+    #[cfg(not(feature = "sidecar-db"))]
+    let sidecar = None;
+
+    let plugins = {
+        let plugin_manager = state.plugin_manager.lock().await;
+        health::plugin_health(
+            plugin_manager.get_all_plugins().len(),
+            plugin_manager.quarantined(),
+        )
+    };
+    let scheduler = health::scheduler_health(
+        state.scheduler.is_started(),
+        &state.scheduler.list().await,
+        chrono::Utc::now(),
     );
+    let data_dir = state.workspaces.lock().await.active()?.data_dir.clone();
+    let disk = health::disk_health(&data_dir);
 
-    Ok(AppSize {
-        size_mb,
-        is_acceptable,
-        threshold_mb: 20.0,
-    })
+    Ok(health::AppHealth::new(
+        database, sidecar, plugins, scheduler, disk,
+    ))
+}
+
+/// Pinned and running version of the SurrealDB sidecar
+#[cfg(feature = "sidecar-db")]
+#[tauri::command]
+async fn get_sidecar_version(
+    state: tauri::State<'_, AppState>,
+) -> Result<sidecar::SidecarVersion, ApiError> {
+    Ok(state._sidecar.lock().await.version().await)
+}
+
+/// Builds with the embedded database have no sidecar
+#[cfg(not(feature = "sidecar-db"))]
+#[tauri::command]
+async fn get_sidecar_version() -> Result<(), ApiError> {
+    Err(AppError::Config("This build uses the embedded database, not a sidecar".to_string()).into())
+}
+
+/// Actions for the command palette, ordered by id
+#[tauri::command]
+async fn list_actions(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<actions::ActionDefinition>, ApiError> {
+    Ok(state.actions.read().await.list())
+}
+
+/// Run an action after checking its arguments
+#[tauri::command]
+async fn run_action(
+    app: tauri::AppHandle,
+    id: String,
+    args: Option<serde_json::Value>,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value, ApiError> {
+    let args = args.unwrap_or(serde_json::Value::Null);
+    let (definition, handler) = state.actions.read().await.get(&id)?;
+    definition.validate_args(&args)?;
+    tracing::info!("Running action {}", id);
+    Ok(handler(app, args).await?)
+}
+
+/// Whether to offer the starter workspace (nothing created or declined yet)
+#[tauri::command]
+async fn is_first_run(state: tauri::State<'_, AppState>) -> Result<bool, ApiError> {
+    let settings = state.settings_service.read().await;
+    let db = state.database.lock().await.clone();
+    let data_sources = state.data_source_service.read().await;
+    Ok(onboarding::is_first_run(&settings, &db, &data_sources).await?)
+}
+
+/// Create a sample dashboard, an RSS data source, a demo kanban board and the
+/// example prompt packages, and start fetching the feed
+#[tauri::command]
+async fn seed_starter_workspace(
+    state: tauri::State<'_, AppState>,
+) -> Result<onboarding::StarterWorkspace, ApiError> {
+    let starter = {
+        let settings = state.settings_service.read().await;
+        let db = state.database.lock().await;
+        let data_sources = state.data_source_service.read().await;
+        onboarding::seed(&settings, &db, &data_sources).await?
+    };
+
+    // The table stays empty until the first scheduled poll otherwise
+    let _ = state.fetch_queue.submit(
+        onboarding::starter_data_source().to_adapter_config(),
+        fetch_queue::FetchPriority::Manual,
+    );
+    Ok(starter)
+}
+
+/// Look for a newer version on the configured update channel
+#[tauri::command]
+async fn check_for_updates(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<updates::UpdateCheck, ApiError> {
+    let config = {
+        let settings = state.settings_service.read().await;
+        updates::UpdateConfig::load(&settings).await?
+    };
+    Ok(updates::check(&app, config).await?)
+}
+
+/// Download and install the newest version of the update channel, then
+/// restart into it
+#[tauri::command]
+async fn install_update(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let config = {
+        let settings = state.settings_service.read().await;
+        updates::UpdateConfig::load(&settings).await?
+    };
+    updates::install(&app, &config).await?;
+    app.restart()
+}
+
+/// Stop offering a version on startup; no version offers every one again
+#[tauri::command]
+async fn skip_update_version(
+    version: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let settings = state.settings_service.read().await;
+    Ok(updates::skip_version(&settings, version).await?)
 }
 
 #[tauri::command]
-async fn get_config() -> Result<serde_json::Value, String> {
+async fn get_config() -> Result<serde_json::Value, ApiError> {
     // Stub: Will load config from file in M2+
     Ok(serde_json::json!({
         "app": {
@@ -431,527 +1188,2700 @@ async fn get_config() -> Result<serde_json::Value, String> {
 }
 
 #[tauri::command]
-async fn get_dashboards(state: tauri::State<'_, AppState>) -> Result<Vec<Dashboard>, String> {
-    let service = state.dashboard_service.lock().await;
-
-    service.get_all().map_err(|e| e.to_string())
+async fn get_dashboards(state: tauri::State<'_, AppState>) -> Result<Vec<Dashboard>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_dashboards().await.map_err(ApiError::from)
 }
 
 #[tauri::command]
-async fn get_dashboard(id: String, state: tauri::State<'_, AppState>) -> Result<Dashboard, String> {
-    let service = state.dashboard_service.lock().await;
-
-    service.get(&id).map_err(|e| e.to_string())
+async fn get_dashboard(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Dashboard, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_dashboard(&id).await.map_err(ApiError::from)
 }
 
 #[tauri::command]
 async fn save_dashboard(
     dashboard: Dashboard,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
-    let service = state.dashboard_service.lock().await;
+) -> Result<Vec<dashboard_validation::PanelWarning>, ApiError> {
+    let plugins = state.plugin_manager.lock().await.get_all_plugins();
+    let db = state.database.lock().await;
+    db.save_dashboard(&dashboard).await?;
+    Ok(dashboard_validation::check_panels(
+        &dashboard.panels,
+        &plugins,
+    ))
+}
 
-    service.save(&dashboard).map_err(|e| e.to_string())
+/// Panels of a dashboard whose plugin is missing or whose config doesn't match the plugin's schema
+#[tauri::command]
+async fn check_dashboard_panels(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<dashboard_validation::PanelWarning>, ApiError> {
+    let plugins = state.plugin_manager.lock().await.get_all_plugins();
+    let db = state.database.lock().await.clone();
+    let dashboard = db.get_dashboard(&id).await?;
+    Ok(dashboard_validation::check_panels(
+        &dashboard.panels,
+        &plugins,
+    ))
 }
 
 #[tauri::command]
-async fn delete_dashboard(id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let service = state.dashboard_service.lock().await;
+async fn delete_dashboard(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.delete_dashboard(&id).await.map_err(ApiError::from)
+}
 
-    service.delete(&id).map_err(|e| e.to_string())
+/// Show a dashboard in a window of its own (focused if already open)
+#[tauri::command]
+async fn open_dashboard_window(
+    app: tauri::AppHandle,
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let dashboard = state.database.lock().await.get_dashboard(&id).await?;
+    window::open_dashboard_window(&app, &dashboard.id, &dashboard.name)?;
+    Ok(())
 }
 
-#[derive(serde::Serialize)]
-struct AppSize {
-    size_mb: f32,
-    is_acceptable: bool,
-    threshold_mb: f32,
+/// Copy a dashboard under a new name as a starting point for another one
+#[tauri::command]
+async fn duplicate_dashboard(
+    id: String,
+    new_name: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Dashboard, ApiError> {
+    let db = state.database.lock().await;
+    db.duplicate_dashboard(&id, &new_name)
+        .await
+        .map_err(ApiError::from)
 }
 
-// ============================================================================
-// M3: Data Staging Commands
-// ============================================================================
+/// Prior versions of a dashboard, newest first
+#[tauri::command]
+async fn get_dashboard_versions(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<dashboard::DashboardVersion>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_dashboard_versions(&id).await.map_err(ApiError::from)
+}
 
+/// Restore a prior version of a dashboard (the current state is kept as a version)
 #[tauri::command]
-async fn get_staged_records(
-    limit: Option<usize>,
-    offset: Option<usize>,
+async fn restore_dashboard_version(
+    id: String,
+    version: u32,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<db::StagedRecord>, String> {
+) -> Result<Dashboard, ApiError> {
     let db = state.database.lock().await;
-
-    db.get_all_records(limit.unwrap_or(100), offset.unwrap_or(0))
+    db.restore_dashboard_version(&id, version)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ApiError::from)
 }
 
+/// Freeze the current data of a dashboard's panels into a stored snapshot
 #[tauri::command]
-async fn get_records_by_type(
-    record_type: String,
+async fn snapshot_dashboard(
+    id: String,
+    label: Option<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<db::StagedRecord>, String> {
+) -> Result<dashboard_snapshots::DashboardSnapshot, ApiError> {
     let db = state.database.lock().await;
-
-    db.get_records_by_type(&record_type)
+    db.snapshot_dashboard(&id, label)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ApiError::from)
 }
 
+/// Snapshots without their data, newest first (of one dashboard if given)
 #[tauri::command]
-async fn get_record_count(state: tauri::State<'_, AppState>) -> Result<usize, String> {
-    let db = state.database.lock().await;
+async fn list_dashboard_snapshots(
+    dashboard_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<dashboard_snapshots::DashboardSnapshotSummary>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_dashboard_snapshots(dashboard_id.as_deref())
+        .await
+        .map_err(ApiError::from)
+}
 
-    db.count_records().await.map_err(|e| e.to_string())
+/// A snapshot with the dashboard and panel data it froze, also its export document
+#[tauri::command]
+async fn get_dashboard_snapshot(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<dashboard_snapshots::DashboardSnapshot, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_dashboard_snapshot(&id).await.map_err(ApiError::from)
 }
 
 #[tauri::command]
-async fn upsert_record(
-    record: db::StagedRecord,
+async fn delete_dashboard_snapshot(
+    id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<db::StagedRecord, String> {
+) -> Result<(), ApiError> {
     let db = state.database.lock().await;
-
-    db.upsert_record(record).await.map_err(|e| e.to_string())
+    db.delete_dashboard_snapshot(&id)
+        .await
+        .map_err(ApiError::from)
 }
 
+/// Fetch a dashboard's data sources and re-evaluate its panels' widget queries now
 #[tauri::command]
-async fn update_record(
+async fn refresh_dashboard(
     id: String,
-    record: db::StagedRecord,
     state: tauri::State<'_, AppState>,
-) -> Result<db::StagedRecord, String> {
-    let db = state.database.lock().await;
+) -> Result<dashboard_refresh::DashboardRefresh, ApiError> {
+    let dashboard = state.database.lock().await.get_dashboard(&id).await?;
+    Ok(dashboard_refresh::refresh_dashboard(
+        &state.database,
+        &state.data_source_service,
+        &state.fetch_queue,
+        &dashboard,
+    )
+    .await)
+}
 
-    db.update_record(&id, record)
+/// A dashboard with the data sources it reads and the plugins it needs, without secrets
+#[tauri::command]
+async fn export_dashboard(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<dashboard_export::DashboardBundle, ApiError> {
+    let plugins = state.plugin_manager.lock().await.get_all_plugins();
+    dashboard_export::export_dashboard(&state.database, &state.data_source_service, &plugins, &id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ApiError::from)
 }
 
+/// Import a dashboard bundle, reporting missing plugins and credentials
+/// (`dry_run` only reports, without saving anything)
 #[tauri::command]
-async fn delete_record(id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    tracing::info!("🗑️  delete_record called with ID: {}", id);
+async fn import_dashboard(
+    bundle: dashboard_export::DashboardBundle,
+    dry_run: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<dashboard_export::DashboardImport, ApiError> {
+    let plugins = state.plugin_manager.lock().await.get_all_plugins();
+    dashboard_export::import_dashboard(
+        &state.database,
+        &state.data_source_service,
+        &plugins,
+        bundle,
+        dry_run.unwrap_or(false),
+    )
+    .await
+    .map_err(ApiError::from)
+}
 
-    let db = state.database.lock().await;
+/// Built-in dashboard templates
+#[tauri::command]
+async fn list_dashboard_templates() -> Result<Vec<dashboard_templates::DashboardTemplate>, ApiError>
+{
+    Ok(dashboard_templates::dashboard_templates())
+}
 
-    match db.delete_record(&id).await {
-        Ok(_) => {
-            tracing::info!("🗑️  Successfully deleted record: {}", id);
-            Ok(())
-        }
-        Err(e) => {
-            tracing::error!("🗑️  Failed to delete record {}: {}", id, e);
-            Err(e.to_string())
-        }
-    }
+/// Create a dashboard from a built-in template, with stubs for the data sources it reads
+#[tauri::command]
+async fn create_dashboard_from_template(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<dashboard_export::DashboardImport, ApiError> {
+    let plugins = state.plugin_manager.lock().await.get_all_plugins();
+    dashboard_templates::create_dashboard_from_template(
+        &state.database,
+        &state.data_source_service,
+        &plugins,
+        &id,
+    )
+    .await
+    .map_err(ApiError::from)
 }
 
 // ============================================================================
-// M3: Adapter Management Commands
+// M3: Data Staging Commands
 // ============================================================================
 
-/// List all available adapter types
 #[tauri::command]
-async fn list_adapters(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
-    Ok(state.adapter_registry.list_types())
+async fn get_staged_records(
+    limit: Option<usize>,
+    offset: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<db::StagedRecord>, ApiError> {
+    let db = state.database.lock().await.clone();
+
+    db.get_all_records(limit.unwrap_or(100), offset.unwrap_or(0))
+        .await
+        .map_err(ApiError::from)
 }
 
-/// Get default configuration for an adapter type
 #[tauri::command]
-async fn get_adapter_default_config(
-    adapter_type: String,
+async fn get_records_by_type(
+    record_type: String,
     state: tauri::State<'_, AppState>,
-) -> Result<AdapterConfig, String> {
-    let adapter = state
-        .adapter_registry
-        .get(&adapter_type)
-        .ok_or_else(|| format!("Unknown adapter type: {}", adapter_type))?;
+) -> Result<Vec<db::StagedRecord>, ApiError> {
+    let db = state.database.lock().await.clone();
 
-    Ok(adapter.default_config())
+    db.get_records_by_type(&record_type)
+        .await
+        .map_err(ApiError::from)
 }
 
-/// Test connection for an adapter configuration
+/// Query records with filters, sorting and pagination
 #[tauri::command]
-async fn test_adapter_connection(
-    config: AdapterConfig,
+async fn query_records(
+    query: db::query::RecordQuery,
     state: tauri::State<'_, AppState>,
-) -> Result<bool, String> {
-    // Phase 3.3: Check if plugin exists first
-    let has_plugin = {
-        let plugin_manager = state.plugin_manager.lock().await;
-        plugin_manager
-            .get_plugin_by_adapter_type(&config.adapter_type)
-            .is_some()
-    };
+) -> Result<db::query::RecordPage, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.query_records(query).await.map_err(ApiError::from)
+}
 
-    if has_plugin {
-        eprintln!("✅ Testing connection with PLUGIN: {}", config.adapter_type);
-        let plugin_manager = state.plugin_manager.lock().await;
-        let plugin = plugin_manager
-            .get_plugin_by_adapter_type(&config.adapter_type)
-            .expect("Plugin should exist");
+/// Check the database for missing tables, dangling references and invalid timestamps
+/// With `repair`, orphaned rows are deleted and dangling ticket references cleared
+#[tauri::command]
+async fn check_database_integrity(
+    repair: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::integrity::IntegrityReport, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.check_integrity(repair.unwrap_or(false))
+        .await
+        .map_err(ApiError::from)
+}
 
-        plugin
-            .test_connection(&config)
-            .await
-            .map_err(|e| e.to_string())
-    } else {
-        eprintln!(
-            "📦 Testing connection with BUILT-IN: {}",
-            config.adapter_type
-        );
+/// Aggregate records (counts/sums per group and time bucket) into chart series
+#[tauri::command]
+async fn aggregate_records(
+    query: db::aggregate::AggregateQuery,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::aggregate::AggregateResult, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.aggregate_records(query).await.map_err(ApiError::from)
+}
+
+/// Evaluate a dashboard widget's query spec (filters, aggregation, limit, fields)
+/// `variables` are the dashboard variable values its `${name}` references take
+#[tauri::command]
+async fn get_widget_data(
+    widget_query: serde_json::Value,
+    variables: Option<db::widgets::Variables>,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::widgets::WidgetData, ApiError> {
+    let widget_query =
+        db::widgets::resolve_widget_query(widget_query, &variables.unwrap_or_default())?;
+    let db = state.database.lock().await.clone();
+    db.get_widget_data(widget_query)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Time series of records for a chart, downsampled to at most `max_points`
+/// (per `bucket` when given, else the raw values of the metric's field)
+#[tauri::command]
+async fn get_timeseries(
+    query: db::query::RecordQuery,
+    bucket: Option<db::aggregate::TimeBucket>,
+    agg: db::aggregate::Metric,
+    max_points: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::timeseries::Timeseries, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_timeseries(query, bucket, agg, max_points)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Records behind the clicked element of a widget (a panel's `drilldown` target)
+#[tauri::command]
+async fn resolve_drilldown(
+    target: db::drilldown::DrilldownTarget,
+    context: Option<db::drilldown::DrilldownContext>,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::drilldown::Drilldown, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.resolve_drilldown(target, context.unwrap_or_default())
+        .await
+        .map_err(ApiError::from)
+}
+
+/// List all tags in use with their record counts
+#[tauri::command]
+async fn list_tags(state: tauri::State<'_, AppState>) -> Result<Vec<db::tags::TagCount>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_tags().await.map_err(ApiError::from)
+}
+
+/// Add tags to many records at once
+#[tauri::command]
+async fn add_tags_to_records(
+    ids: Vec<String>,
+    tags: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    let db = state.database.lock().await;
+    db.add_tags(ids, tags).await.map_err(ApiError::from)
+}
+
+/// Remove tags from many records at once
+#[tauri::command]
+async fn remove_tags_from_records(
+    ids: Vec<String>,
+    tags: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    let db = state.database.lock().await;
+    db.remove_tags(ids, tags).await.map_err(ApiError::from)
+}
+
+/// Rename a tag across all records
+#[tauri::command]
+async fn rename_tag(
+    from: String,
+    to: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    let db = state.database.lock().await;
+    db.rename_tag(&from, &to).await.map_err(ApiError::from)
+}
+
+/// Link two records (or a record and a ticket) with a named relation
+#[tauri::command]
+async fn link_records(
+    a: String,
+    b: String,
+    relation: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, ApiError> {
+    let db = state.database.lock().await;
+    db.link_records(&a, &b, &relation)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Remove a link between two items
+#[tauri::command]
+async fn unlink_records(
+    link_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.unlink_records(&link_id).await.map_err(ApiError::from)
+}
+
+/// Get all items linked to or from a record or ticket
+#[tauri::command]
+async fn get_related(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<db::relations::RelatedItem>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_related(&id).await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn get_record_count(state: tauri::State<'_, AppState>) -> Result<usize, ApiError> {
+    let db = state.database.lock().await.clone();
+
+    db.count_records().await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn upsert_record(
+    record: db::StagedRecord,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::StagedRecord, ApiError> {
+    let db = state.database.lock().await;
+
+    db.as_actor(
+        audit::Actor::command("upsert_record"),
+        db.upsert_record(record),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn update_record(
+    id: String,
+    record: db::StagedRecord,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::StagedRecord, ApiError> {
+    let db = state.database.lock().await;
+
+    db.as_actor(
+        audit::Actor::command("update_record"),
+        db.update_record(&id, record),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Partially update a record with a JSON merge patch on its data/metadata
+/// e.g. `{"data": {"status": "done"}, "metadata": {"tags": ["x"]}}`; `null` removes a field
+#[tauri::command]
+async fn patch_record(
+    id: String,
+    patch: serde_json::Value,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::StagedRecord, ApiError> {
+    let db = state.database.lock().await;
+
+    db.as_actor(
+        audit::Actor::command("patch_record"),
+        db.patch_record(&id, &patch),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn delete_record(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    tracing::info!("🗑️  delete_record called with ID: {}", id);
+
+    let db = state.database.lock().await;
+
+    match db
+        .as_actor(
+            audit::Actor::command("delete_record"),
+            db.delete_record(&id),
+        )
+        .await
+    {
+        Ok(_) => {
+            tracing::info!("🗑️  Successfully deleted record: {}", id);
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!("🗑️  Failed to delete record {}: {}", id, e);
+            Err(e.into())
+        }
+    }
+}
+
+// ============================================================================
+// M3: Adapter Management Commands
+// ============================================================================
+
+/// List all available adapter types
+#[tauri::command]
+async fn list_adapters(state: tauri::State<'_, AppState>) -> Result<Vec<String>, ApiError> {
+    Ok(state.adapter_registry.list_types())
+}
+
+/// Get default configuration for an adapter type
+#[tauri::command]
+async fn get_adapter_default_config(
+    adapter_type: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<AdapterConfig, ApiError> {
+    let adapter = state
+        .adapter_registry
+        .get(&adapter_type)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown adapter type: {}", adapter_type)))?;
+
+    Ok(adapter.default_config())
+}
+
+/// Store an auth config under a name, for `{ type: "credentialref", name }`
+#[tauri::command]
+fn save_credential_profile(name: String, auth: adapters::AuthConfig) -> Result<(), ApiError> {
+    credential_profiles::save_credential_profile(&name, &auth).map_err(ApiError::from)
+}
+
+/// Credential profile names with their auth type (secrets stay in the store)
+#[tauri::command]
+fn list_credential_profiles() -> Result<Vec<credential_profiles::CredentialProfileSummary>, ApiError>
+{
+    credential_profiles::list_credential_profiles().map_err(ApiError::from)
+}
+
+/// Seal the named credentials and credential profiles with a password, for another machine
+#[tauri::command]
+async fn export_credentials(
+    password: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<credential_export::CredentialBundle, ApiError> {
+    credential_export::export_credentials(&state.data_source_service, &password)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Write the credentials of a bundle made by `export_credentials`
+#[tauri::command]
+fn import_credentials(
+    bundle: credential_export::CredentialBundle,
+    password: String,
+) -> Result<credential_export::CredentialImport, ApiError> {
+    credential_export::import_credentials(&bundle, &password).map_err(ApiError::from)
+}
+
+#[tauri::command]
+fn delete_credential_profile(name: String) -> Result<(), ApiError> {
+    credential_profiles::delete_credential_profile(&name).map_err(ApiError::from)
+}
+
+/// Set when a credential expires (a date and/or a maximum age in days); both
+/// empty removes the expiry
+#[tauri::command]
+fn set_credential_expiry(
+    key: String,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    max_age_days: Option<u32>,
+) -> Result<Option<credential_expiry::CredentialExpiry>, ApiError> {
+    credential_expiry::set_credential_expiry(&key, expires_at, max_age_days).map_err(ApiError::from)
+}
+
+#[tauri::command]
+fn list_credential_expiry() -> Result<Vec<credential_expiry::CredentialExpiry>, ApiError> {
+    credential_expiry::list_credential_expiry().map_err(ApiError::from)
+}
+
+/// Credentials expiring within `within_days` (default 14) or already expired
+#[tauri::command]
+fn get_expiring_credentials(
+    within_days: Option<u32>,
+) -> Result<Vec<credential_expiry::ExpiringCredential>, ApiError> {
+    credential_expiry::get_expiring_credentials(
+        within_days.unwrap_or(credential_expiry::DEFAULT_WARNING_DAYS),
+    )
+    .map_err(ApiError::from)
+}
+
+/// Test connection for an adapter configuration
+#[tauri::command]
+async fn test_adapter_connection(
+    mut config: AdapterConfig,
+    state: tauri::State<'_, AppState>,
+) -> Result<bool, ApiError> {
+    credential_profiles::resolve_credential_ref(&mut config)?;
+    network::ensure_online()?;
+
+    // Phase 3.3: Check if plugin exists first
+    let plugin = state
+        .plugin_manager
+        .lock()
+        .await
+        .get_plugin_by_adapter_type(&config.adapter_type);
+
+    if let Some(plugin) = plugin {
+        tracing::debug!("Testing connection with plugin: {}", config.adapter_type);
+        plugin
+            .test_connection(&config)
+            .await
+            .map_err(ApiError::from)
+    } else {
+        tracing::debug!("Testing connection with built-in: {}", config.adapter_type);
 
         state
             .adapter_registry
             .test_connection(&config)
             .await
-            .map_err(|e| e.to_string())
+            .map_err(ApiError::from)
     }
 }
 
-/// Fetch data using an adapter and store in database
+/// Fetch data using an adapter and store in database
+#[tauri::command]
+async fn fetch_adapter_data(
+    config: AdapterConfig,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    tracing::info!("Fetching data with adapter: {}", config.adapter_type);
+
+    fetcher::fetch_and_store(
+        &state.plugin_manager,
+        &state.adapter_registry,
+        &state.database,
+        config,
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// All configured data sources, by name
+#[tauri::command]
+async fn get_data_sources(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<data_sources::DataSource>, ApiError> {
+    let service = state.data_source_service.read().await;
+    Ok(service.get_all_data_sources().await?)
+}
+
+#[tauri::command]
+async fn get_data_source(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<data_sources::DataSource, ApiError> {
+    let service = state.data_source_service.read().await;
+    service
+        .get_data_source(&id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Data source {}", id)).into())
+}
+
+/// Create a data source; its `source` identifier must not be in use yet
+#[tauri::command]
+async fn save_data_source(
+    data_source: data_sources::DataSourceInput,
+    state: tauri::State<'_, AppState>,
+) -> Result<data_sources::DataSource, ApiError> {
+    let service = state.data_source_service.read().await;
+    Ok(service.create_data_source(data_source).await?)
+}
+
+/// Change some fields of a data source
+#[tauri::command]
+async fn update_data_source(
+    id: String,
+    updates: serde_json::Value,
+    state: tauri::State<'_, AppState>,
+) -> Result<data_sources::DataSource, ApiError> {
+    let service = state.data_source_service.read().await;
+    Ok(service.update_data_source(&id, updates).await?)
+}
+
+/// Delete a data source; records it fetched are kept
+#[tauri::command]
+async fn delete_data_source(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let service = state.data_source_service.read().await;
+    Ok(service.delete_data_source(&id).await?)
+}
+
+/// Fetch all enabled data sources through the fetch queue and store their records
+/// Returns a per-source summary (record count, duration, error)
+#[tauri::command]
+async fn fetch_all_enabled(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<fetcher::FetchSummary>, ApiError> {
+    Ok(polling::fetch_enabled(&state.data_source_service, &state.fetch_queue).await?)
+}
+
+/// Cancel queued and running fetches for a source
+/// Returns the number of cancelled fetches
+#[tauri::command]
+async fn cancel_fetch(
+    source: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    Ok(state.fetch_queue.cancel(&source))
+}
+
+/// Get the running and pending fetches of the fetch queue
+#[tauri::command]
+async fn get_fetch_queue(
+    state: tauri::State<'_, AppState>,
+) -> Result<fetch_queue::FetchQueueStatus, ApiError> {
+    Ok(state.fetch_queue.status())
+}
+
+/// Set the maximum number of concurrent fetches
+#[tauri::command]
+async fn set_fetch_concurrency(
+    max_concurrency: usize,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    state.fetch_queue.set_max_concurrency(max_concurrency);
+    Ok(())
+}
+
+/// Pause scheduled polling for a source
+#[tauri::command]
+async fn pause_polling(source: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    state.polling_scheduler.lock().await.pause(&source);
+    Ok(())
+}
+
+/// Resume scheduled polling for a source
+#[tauri::command]
+async fn resume_polling(source: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    state.polling_scheduler.lock().await.resume(&source);
+    Ok(())
+}
+
+/// Get the polling status of all scheduled sources
+#[tauri::command]
+async fn get_polling_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<polling::PollingStatus>, ApiError> {
+    Ok(state.polling_scheduler.lock().await.status())
+}
+
+/// Background jobs with their schedule and last run
+#[tauri::command]
+async fn list_jobs(state: tauri::State<'_, AppState>) -> Result<Vec<scheduler::JobInfo>, ApiError> {
+    Ok(state.scheduler.list().await)
+}
+
+#[tauri::command]
+async fn pause_job(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<scheduler::JobInfo, ApiError> {
+    state.scheduler.pause(&id).await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn resume_job(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<scheduler::JobInfo, ApiError> {
+    state.scheduler.resume(&id).await.map_err(ApiError::from)
+}
+
+/// Run a job right away (also when paused) and return the outcome
+#[tauri::command]
+async fn run_job_now(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<scheduler::JobRun, ApiError> {
+    state.scheduler.run_now(&id).await.map_err(ApiError::from)
+}
+
+/// Change a job's schedule (interval or cron expression); no schedule restores the default
+#[tauri::command]
+async fn set_job_schedule(
+    id: String,
+    schedule: Option<scheduler::Schedule>,
+    state: tauri::State<'_, AppState>,
+) -> Result<scheduler::JobInfo, ApiError> {
+    state
+        .scheduler
+        .set_schedule(&id, schedule)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Get the fetch history of a source, newest first
+#[tauri::command]
+async fn get_fetch_history(
+    source: String,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<fetch_history::FetchRun>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_fetch_history(
+        &source,
+        limit.unwrap_or(fetch_history::DEFAULT_HISTORY_LIMIT),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Get the last refresh time and error of every fetched source
+#[tauri::command]
+async fn get_source_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<fetch_history::SourceStatus>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_source_status().await.map_err(ApiError::from)
+}
+
+/// Get the sync status of a data source with its recent errors
+#[tauri::command]
+async fn get_data_source_status(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<fetch_history::DataSourceStatus, ApiError> {
+    let data_source = state
+        .data_source_service
+        .read()
+        .await
+        .get_data_source(&id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Data source {}", id)))?;
+    let polling = state
+        .polling_scheduler
+        .lock()
+        .await
+        .source_status(&data_source.source);
+
+    let db = state.database.lock().await.clone();
+    db.get_data_source_status(&data_source, polling)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Reset the deep sync since-cursor so the next sync of a source fetches everything
+#[tauri::command]
+async fn reset_sync_cursor(
+    source: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.clear_sync_cursor(&source).await.map_err(ApiError::from)
+}
+
+/// Full-text search over staged records, ranked by relevance
+#[tauri::command]
+async fn search_records(
+    query: String,
+    filters: Option<search::SearchFilters>,
+    state: tauri::State<'_, AppState>,
+) -> Result<search::SearchResults, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.search_records(&query, filters.unwrap_or_default())
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Search records, tickets, pages, dashboards, prompts and plugins at once,
+/// ranked together (for the command palette)
+#[tauri::command]
+async fn global_search(
+    query: String,
+    options: Option<global_search::GlobalSearchOptions>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<global_search::GlobalSearchHit>, ApiError> {
+    let plugins = state.plugin_manager.lock().await.get_all_plugins();
+    let db = state.database.lock().await.clone();
+    db.global_search(&query, &plugins, options.unwrap_or_default())
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Format a record, ticket or rendered prompt as Markdown, plain text or
+/// JSON for the clipboard
+#[tauri::command]
+async fn format_record_for_share(
+    id: String,
+    format: share::ShareFormat,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, ApiError> {
+    let db = state.database.lock().await.clone();
+    share::format_for_share(&db, &id, format)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Get the applied and latest database schema version
+#[tauri::command]
+async fn get_schema_version(
+    state: tauri::State<'_, AppState>,
+) -> Result<db::migrations::SchemaVersion, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_schema_version().await.map_err(ApiError::from)
+}
+
+/// Clear all records from the database
+#[tauri::command]
+async fn clear_all_records(state: tauri::State<'_, AppState>) -> Result<usize, ApiError> {
+    tracing::info!("Clearing all records from database");
+
+    let db = state.database.lock().await;
+    let count = db
+        .as_actor(
+            audit::Actor::command("clear_all_records"),
+            db.clear_all_records(),
+        )
+        .await?;
+
+    tracing::info!("Cleared {} records", count);
+    Ok(count)
+}
+
+/// Get database statistics
+#[tauri::command]
+async fn get_database_stats(
+    state: tauri::State<'_, AppState>,
+) -> Result<db::DatabaseStats, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_stats().await.map_err(ApiError::from)
+}
+
+/// M5 Phase 3: Clean up old records based on TTL
+/// One-off cleanup; recurring cleanup is handled by retention rules
+#[tauri::command]
+async fn cleanup_old_records(
+    ttl_days: i64,
+    source: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value, ApiError> {
+    tracing::info!(
+        "Cleaning up records older than {} days for source: {:?}",
+        ttl_days,
+        source
+    );
+
+    let db = state.database.lock().await;
+    let deleted = db.cleanup_old_records(ttl_days, source.as_deref()).await?;
+
+    tracing::info!("Deleted {} old records", deleted);
+
+    Ok(serde_json::json!({
+        "deleted": deleted
+    }))
+}
+
+/// List persisted retention rules
+#[tauri::command]
+async fn list_retention_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<retention::RetentionRule>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_retention_rules().await.map_err(ApiError::from)
+}
+
+/// Create or update a retention rule (applied automatically every hour)
+#[tauri::command]
+async fn save_retention_rule(
+    rule: retention::SaveRetentionRuleRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<retention::RetentionRule, ApiError> {
+    let db = state.database.lock().await;
+    db.save_retention_rule(rule).await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn delete_retention_rule(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.delete_retention_rule(&id).await.map_err(ApiError::from)
+}
+
+/// Show which records the enabled retention rules would delete
+#[tauri::command]
+async fn preview_retention(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<retention::RetentionOutcome>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.preview_retention().await.map_err(ApiError::from)
+}
+
+/// Apply the enabled retention rules now instead of waiting for the schedule
+#[tauri::command]
+async fn apply_retention(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<retention::RetentionOutcome>, ApiError> {
+    let db = state.database.lock().await;
+    db.as_actor(
+        audit::Actor::command("apply_retention"),
+        db.apply_retention(),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// List alert rules with their last evaluation
+#[tauri::command]
+async fn list_alert_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<alerts::AlertRule>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_alert_rules().await.map_err(ApiError::from)
+}
+
+/// Create or update an alert rule (evaluated after fetches and every five minutes)
+#[tauri::command]
+async fn save_alert_rule(
+    rule: alerts::SaveAlertRuleRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<alerts::AlertRule, ApiError> {
+    let db = state.database.lock().await;
+    db.save_alert_rule(rule).await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn delete_alert_rule(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.delete_alert_rule(&id).await.map_err(ApiError::from)
+}
+
+/// Evaluate the enabled alert rules now, returning the alerts that fired
+#[tauri::command]
+async fn evaluate_alert_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<alerts::FiredAlert>, ApiError> {
+    let db = state.database.lock().await;
+    db.evaluate_alert_rules().await.map_err(ApiError::from)
+}
+
+/// List time-series rollup rules
+#[tauri::command]
+async fn list_rollup_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<rollups::RollupRule>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_rollup_rules().await.map_err(ApiError::from)
+}
+
+/// Create or update a rollup rule (applied automatically every hour)
+#[tauri::command]
+async fn save_rollup_rule(
+    rule: rollups::SaveRollupRuleRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<rollups::RollupRule, ApiError> {
+    let db = state.database.lock().await;
+    db.save_rollup_rule(rule).await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn delete_rollup_rule(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.delete_rollup_rule(&id).await.map_err(ApiError::from)
+}
+
+/// Roll up completed periods now instead of waiting for the schedule
+#[tauri::command]
+async fn run_rollups(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<rollups::RollupOutcome>, ApiError> {
+    let db = state.database.lock().await;
+    db.run_rollups().await.map_err(ApiError::from)
+}
+
+/// Notification center entries, newest first
+#[tauri::command]
+async fn get_notifications(
+    unread_only: Option<bool>,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<notifications::Notification>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_notifications(
+        unread_only.unwrap_or(false),
+        limit.unwrap_or(notifications::DEFAULT_LIMIT),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn mark_notification_read(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<notifications::Notification, ApiError> {
+    let db = state.database.lock().await;
+    db.mark_notification_read(&id).await.map_err(ApiError::from)
+}
+
+/// Recent entries of the log files, newest first
+#[tauri::command]
+async fn get_recent_logs(
+    level: Option<String>,
+    limit: Option<usize>,
+    module: Option<String>,
+) -> Result<Vec<logging::LogEntry>, ApiError> {
+    tokio::task::spawn_blocking(move || {
+        logging::get_recent_logs(
+            level.as_deref(),
+            limit.unwrap_or(logging::DEFAULT_LIMIT),
+            module.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::Internal, e.to_string()))?
+    .map_err(ApiError::from)
+}
+
+/// Collected metrics (empty unless `metrics.enabled` is on)
+#[tauri::command]
+async fn get_metrics_snapshot() -> Result<metrics::MetricsSnapshot, ApiError> {
+    Ok(metrics::snapshot())
+}
+
+/// Report of the last panic, until it's dismissed
+#[tauri::command]
+async fn get_last_crash_report() -> Result<Option<crash::CrashReport>, ApiError> {
+    crash::last_crash_report().map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn dismiss_crash_report() -> Result<(), ApiError> {
+    crash::dismiss_crash_report().map_err(ApiError::from)
+}
+
+/// Whether the local HTTP API runs, its base URL and token
+#[tauri::command]
+async fn get_local_api_info() -> Result<local_api::LocalApiInfo, ApiError> {
+    local_api::info().await.map_err(ApiError::from)
+}
+
+/// Replace the local API token, returning the new one
+#[tauri::command]
+async fn regenerate_local_api_token() -> Result<String, ApiError> {
+    local_api::regenerate_token().map_err(ApiError::from)
+}
+
+/// Deletes that can still be undone, newest first
+#[tauri::command]
+async fn list_undoable_operations(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<undo::UndoableOperation>, ApiError> {
+    let window = undo::window(&*state.settings_service.read().await).await?;
+    let db = state.database.lock().await.clone();
+    db.list_undoable_operations(window)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Restore what the newest undoable delete removed
+#[tauri::command]
+async fn undo_last_operation(
+    state: tauri::State<'_, AppState>,
+) -> Result<undo::UndoableOperation, ApiError> {
+    let window = undo::window(&*state.settings_service.read().await).await?;
+    let db = state.database.lock().await;
+    db.as_actor(
+        audit::Actor::command("undo_last_operation"),
+        db.undo_last_operation(window),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Changes to records, tickets, pages and the prompt library, newest first
+#[tauri::command]
+async fn get_audit_log(
+    filters: Option<audit::AuditFilter>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<audit::AuditEntry>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_audit_log(filters.unwrap_or_default())
+        .await
+        .map_err(ApiError::from)
+}
+
+/// List outbound webhooks with the outcome of their last delivery
+#[tauri::command]
+async fn list_webhooks(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<webhooks::Webhook>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_webhooks().await.map_err(ApiError::from)
+}
+
+/// Create or update a webhook (a None secret keeps the current one)
+#[tauri::command]
+async fn save_webhook(
+    webhook: webhooks::SaveWebhookRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<webhooks::Webhook, ApiError> {
+    let db = state.database.lock().await;
+    db.save_webhook(webhook).await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn delete_webhook(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.delete_webhook(&id).await.map_err(ApiError::from)
+}
+
+/// Send a test notification to a webhook
+#[tauri::command]
+async fn test_webhook(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    webhooks::test_webhook(&state.database, &id)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// M5: Delete records by type (e.g., "gitlab_pipeline")
+#[tauri::command]
+async fn delete_records_by_type(
+    record_type: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value, ApiError> {
+    tracing::info!("Deleting all records of type: {}", record_type);
+
+    let db = state.database.lock().await;
+    let deleted = db
+        .as_actor(
+            audit::Actor::command("delete_records_by_type"),
+            db.delete_records_by_type(&record_type),
+        )
+        .await?;
+
+    Ok(serde_json::json!({
+        "deleted": deleted
+    }))
+}
+
+/// M5: Delete records by source and type
+#[tauri::command]
+async fn delete_records_by_source_and_type(
+    source: String,
+    record_type: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value, ApiError> {
+    tracing::info!(
+        "Deleting records of type '{}' from source '{}'",
+        record_type,
+        source
+    );
+
+    let db = state.database.lock().await;
+    let deleted = db
+        .as_actor(
+            audit::Actor::command("delete_records_by_source_and_type"),
+            db.delete_records_by_source_and_type(&source, &record_type),
+        )
+        .await?;
+
+    Ok(serde_json::json!({
+        "deleted": deleted
+    }))
+}
+
+/// M9: Export all database data to JSON
+/// Can be used to migrate data from dev to prod or vice versa
+#[tauri::command]
+async fn export_database(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, ApiError> {
+    tracing::info!("Exporting database data");
+
+    backup::export_snapshot(&state.database)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// M9: Import database data from JSON
+/// merge_strategy options:
+/// - "replace": Clear existing data first, then import
+/// - "merge": Imported rows overwrite existing rows with the same ID
+/// - "skip": Keep existing rows on conflict
+#[tauri::command]
+async fn import_database(
+    import_data: serde_json::Value,
+    merge_strategy: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::ImportStats, ApiError> {
+    tracing::info!("Importing database data with strategy: {}", merge_strategy);
+
+    backup::import_snapshot(
+        &state.database,
+        import_data,
+        &merge_strategy,
+        audit::Actor::command("import_database"),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Stream the whole database to a file in chunks (for large databases)
+/// Emits `export://progress` events with the table and row counts while running
+#[tauri::command]
+async fn export_database_to_file(
+    path: String,
+    format: Option<backup::SnapshotFormat>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<backup::FileExportSummary, ApiError> {
+    use tauri::Emitter;
+
+    tracing::info!("Streaming database export to {}", path);
+
+    backup::export_snapshot_to_file(
+        &state.database,
+        Path::new(&path),
+        format.unwrap_or_default(),
+        |progress| {
+            if let Err(e) = app.emit("export://progress", progress) {
+                tracing::warn!("Failed to emit export progress: {}", e);
+            }
+        },
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Write the database, prompt library and attachment manifest to a
+/// password-encrypted archive, for backups kept on cloud drives
+#[tauri::command]
+async fn export_database_encrypted(
+    path: String,
+    password: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<encrypted_export::EncryptedExportSummary, ApiError> {
+    tracing::info!("Writing encrypted database export to {}", path);
+
+    encrypted_export::export_database_encrypted(&state.database, Path::new(&path), &password)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Import an archive from `export_database_encrypted`
+/// merge_strategy options are the same as for `import_database`
+#[tauri::command]
+async fn import_database_encrypted(
+    path: String,
+    password: String,
+    merge_strategy: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<encrypted_export::EncryptedImport, ApiError> {
+    tracing::info!("Importing encrypted database export from {}", path);
+
+    let attachments_dir = attachments_dir(&state).await?;
+    encrypted_export::import_database_encrypted(
+        &state.database,
+        &attachments_dir,
+        Path::new(&path),
+        &password,
+        &merge_strategy,
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Copy records, tickets and pages from the legacy (pre-stage-separation)
+/// database in `<data dir>/db` into the current environment database
+/// With `dry_run` nothing is written; the report shows what would be copied.
+/// Emits `legacy-migration://progress` events after every chunk.
+#[tauri::command]
+async fn migrate_legacy_database(
+    dry_run: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<legacy_migration::LegacyMigrationReport, ApiError> {
+    use tauri::Emitter;
+
+    let data_dir = {
+        let registry = state.workspaces.lock().await;
+        registry.active()?.data_dir.clone()
+    };
+    let dry_run = dry_run.unwrap_or(false);
+    tracing::info!(
+        "Migrating legacy database from {:?} (dry run: {})",
+        data_dir,
+        dry_run
+    );
+
+    legacy_migration::migrate_legacy_database(&state.database, &data_dir, dry_run, |progress| {
+        if let Err(e) = app.emit("legacy-migration://progress", progress) {
+            tracing::warn!("Failed to emit migration progress: {}", e);
+        }
+    })
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Copy data between the dev and prod databases of the active workspace
+/// `entities` picks what to copy; rows existing in both are kept unless
+/// `on_conflict` is "overwrite".
+#[tauri::command]
+async fn copy_environment_data(
+    from: environment_copy::Environment,
+    to: environment_copy::Environment,
+    entities: Vec<environment_copy::CopyEntity>,
+    on_conflict: Option<environment_copy::ConflictMode>,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::ImportStats, ApiError> {
+    let data_dir = {
+        let registry = state.workspaces.lock().await;
+        registry.active()?.data_dir.clone()
+    };
+
+    environment_copy::copy_environment_data(
+        &state.database,
+        &data_dir,
+        from,
+        to,
+        &entities,
+        on_conflict.unwrap_or_default(),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Export records matching a query to CSV, NDJSON or Parquet
+/// `fields` selects the data fields flattened into CSV/Parquet columns (default: all top-level fields)
+#[tauri::command]
+async fn export_records(
+    format: export::ExportFormat,
+    query: Option<db::query::RecordQuery>,
+    path: String,
+    fields: Option<Vec<String>>,
+    state: tauri::State<'_, AppState>,
+) -> Result<export::ExportSummary, ApiError> {
+    let db = state.database.lock().await.clone();
+    export::export_records(
+        &db,
+        format,
+        query.unwrap_or_default(),
+        Path::new(&path),
+        fields,
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Import staged records from a CSV file using a column mapping
+/// Invalid rows are skipped and reported with their row number
+#[tauri::command]
+async fn import_records_from_csv(
+    path: String,
+    mapping: csv_import::CsvMapping,
+    state: tauri::State<'_, AppState>,
+) -> Result<csv_import::CsvImportReport, ApiError> {
+    let db = state.database.lock().await;
+    csv_import::import_records_from_csv(&db, Path::new(&path), &mapping)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Declared settings (type, default, constraints), for building settings forms
+#[tauri::command]
+async fn get_settings_schema(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<settings::SettingDefinition>, ApiError> {
+    Ok(state.settings_service.read().await.schema())
+}
+
+/// Every declared setting with its current value (the default when unset)
+#[tauri::command]
+async fn get_all_settings(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<settings::SettingValue>, ApiError> {
+    let settings = state.settings_service.read().await;
+    settings
+        .get_effective_settings()
+        .await
+        .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn get_setting(
+    key: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value, ApiError> {
+    let settings = state.settings_service.read().await;
+    settings.get_value(&key).await.map_err(ApiError::from)
+}
+
+/// Validate and store a setting; null resets it to the default
+#[tauri::command]
+async fn set_setting(
+    key: String,
+    value: serde_json::Value,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    if key == "network.proxy_url" {
+        network::validate_proxy_url(value.as_str().unwrap_or_default())?;
+    }
+    let settings = state.settings_service.read().await;
+    settings.set_value(&key, value).await?;
+    if i18n::is_i18n_setting(&key) {
+        i18n::reload(&settings).await?;
+    }
+    if network::is_network_setting(&key) {
+        network::reload(&settings).await?;
+    }
+    if metrics::is_metrics_setting(&key) {
+        metrics::reload(&settings).await?;
+    }
+    if local_api::is_local_api_setting(&key) {
+        local_api::reload(&settings).await?;
+    }
+    Ok(())
+}
+
+/// Settings, board configs and background job rules as one bundle (no secrets)
+#[tauri::command]
+async fn export_settings(
+    state: tauri::State<'_, AppState>,
+) -> Result<settings_export::SettingsBundle, ApiError> {
+    let settings = state.settings_service.read().await;
+    settings_export::export_settings(&settings, &state.database)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Replace the configuration with a bundle made by `export_settings`
+#[tauri::command]
+async fn import_settings(
+    bundle: settings_export::SettingsBundle,
+    state: tauri::State<'_, AppState>,
+) -> Result<settings_export::SettingsImport, ApiError> {
+    // Exclusive, so no setting is written in the middle of the replacement
+    let settings = state.settings_service.write().await;
+    let imported = settings_export::import_settings(&settings, &state.database, bundle).await?;
+    i18n::reload(&settings).await?;
+    network::reload(&settings).await?;
+    metrics::reload(&settings).await?;
+    local_api::reload(&settings).await?;
+    Ok(imported)
+}
+
+/// Put settings, boards and/or schedules back to the defaults
+#[tauri::command]
+async fn reset_settings(
+    scope: settings_export::SettingsScope,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let settings = state.settings_service.write().await;
+    settings_export::reset_settings(&settings, &state.database, scope).await?;
+    i18n::reload(&settings).await?;
+    network::reload(&settings).await?;
+    metrics::reload(&settings).await?;
+    local_api::reload(&settings).await.map_err(ApiError::from)
+}
+
+/// Write a backup now, using the configured folder and rotation
+#[tauri::command]
+async fn create_backup(state: tauri::State<'_, AppState>) -> Result<backup::BackupInfo, ApiError> {
+    let config = {
+        let settings = state.settings_service.read().await;
+        backup::BackupConfig::load(&settings).await?
+    };
+
+    backup::write_backup(&state.database, &config.folder, config.max_keep)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// List backups in the configured folder, newest first
+#[tauri::command]
+async fn list_backups(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<backup::BackupInfo>, ApiError> {
+    let settings = state.settings_service.read().await;
+    let config = backup::BackupConfig::load(&settings).await?;
+
+    backup::list_backups(&config.folder).map_err(ApiError::from)
+}
+
+/// Restore a backup file, replacing all current data
+#[tauri::command]
+async fn restore_backup(
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::ImportStats, ApiError> {
+    // Keep a copy of the current data in case the restore was a mistake
+    let config = {
+        let settings = state.settings_service.read().await;
+        backup::BackupConfig::load(&settings).await?
+    };
+    backup::write_backup(&state.database, &config.folder, config.max_keep)
+        .await
+        .map_err(|e| ApiError::from(e).context("Failed to write safety backup"))?;
+
+    backup::restore_backup(&state.database, std::path::Path::new(&path))
+        .await
+        .map_err(ApiError::from)
+}
+
+/// The configured remote backup target
+async fn remote_backup_target(state: &AppState) -> Result<remote_backup::RemoteTarget, ApiError> {
+    let settings = state.settings_service.read().await;
+    remote_backup::RemoteTarget::load(&settings)
+        .await?
+        .ok_or_else(|| {
+            AppError::Config("Remote backups are off (backup.remote is \"none\")".to_string())
+                .into()
+        })
+}
+
+/// List backups on the configured WebDAV / S3 target, newest first
+#[tauri::command]
+async fn list_remote_backups(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<remote_backup::RemoteBackup>, ApiError> {
+    let target = remote_backup_target(&state).await?;
+    target.list().await.map_err(ApiError::from)
+}
+
+/// Download a remote backup and restore it, replacing all current data
+#[tauri::command]
+async fn restore_from_remote(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<db::ImportStats, ApiError> {
+    let target = remote_backup_target(&state).await?;
+
+    // Keep a copy of the current data in case the restore was a mistake
+    let config = {
+        let settings = state.settings_service.read().await;
+        backup::BackupConfig::load(&settings).await?
+    };
+    backup::write_backup(&state.database, &config.folder, config.max_keep)
+        .await
+        .map_err(|e| ApiError::from(e).context("Failed to write safety backup"))?;
+
+    remote_backup::restore_from_remote(&state.database, &target, &id)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Set the password remote backups are encrypted with
+#[tauri::command]
+fn set_remote_backup_password(password: String) -> Result<(), ApiError> {
+    remote_backup::set_password(&password).map_err(ApiError::from)
+}
+
+// ============================================================================
+// Workspace Commands
+// ============================================================================
+
+/// List all workspaces and the active one
+#[tauri::command]
+async fn list_workspaces(
+    state: tauri::State<'_, AppState>,
+) -> Result<workspaces::WorkspaceRegistry, ApiError> {
+    Ok(state.workspaces.lock().await.clone())
+}
+
+/// Create a new, empty workspace (does not switch to it)
+#[tauri::command]
+async fn create_workspace(
+    name: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<workspaces::Workspace, ApiError> {
+    state
+        .workspaces
+        .lock()
+        .await
+        .create(&name)
+        .map_err(ApiError::from)
+}
+
+/// Switch to another workspace, reopening the database and services on its data
+/// The frontend should reload its state after this returns
+#[tauri::command]
+async fn switch_workspace(
+    name: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<workspaces::Workspace, ApiError> {
+    let mut registry = state.workspaces.lock().await;
+    let workspace = registry
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("Workspace '{}' not found", name)))?;
+    if registry.active == workspace.name {
+        return Ok(workspace);
+    }
+
+    tracing::info!("Switching to workspace '{}'", workspace.name);
+    let database = Database::open(&workspace).await?;
+    use_database(&workspace, database, app, &state).await;
+
+    registry.set_active(&workspace.name).map_err(ApiError::from)
+}
+
+/// Point the app at a newly opened workspace database
+async fn use_database(
+    workspace: &workspaces::Workspace,
+    database: Database,
+    app: tauri::AppHandle,
+    state: &AppState,
+) {
+    // Stop the old database's subscriptions before its handles are swapped out
+    for task in state.live_queries.lock().await.drain(..) {
+        task.abort();
+    }
+
+    if let Err(e) = database
+        .migrate_dashboard_files(&workspace.dashboards_dir)
+        .await
+    {
+        tracing::warn!("Failed to migrate dashboard files: {}", e);
+    }
+
+    // Swap the shared handles in place so background tasks (polling, fetch queue) follow along
+    *state.database.lock().await = database.clone();
+    *state.data_source_service.write().await =
+        data_sources::DataSourceService::new(Arc::new(Mutex::new(database.clone())));
+    {
+        let mut settings = state.settings_service.write().await;
+        settings.set_database(Arc::new(Mutex::new(database.clone())));
+        if let Err(e) = i18n::reload(&settings).await {
+            tracing::warn!("Failed to load the locale setting: {}", e);
+        }
+        if let Err(e) = network::reload(&settings).await {
+            tracing::warn!("Failed to load network settings: {}", e);
+        }
+        if let Err(e) = metrics::reload(&settings).await {
+            tracing::warn!("Failed to load the metrics setting: {}", e);
+        }
+        if let Err(e) = local_api::reload(&settings).await {
+            tracing::warn!("Failed to start the local API: {}", e);
+        }
+    }
+    *state.plugin_data_service.write().await =
+        plugin_data::PluginDataService::new(Arc::new(Mutex::new(database.clone())));
+    state.scheduler.reload().await;
+
+    *state.live_queries.lock().await = live::start(app, database);
+}
+
+/// How the active workspace's database is reached
+#[derive(serde::Serialize)]
+struct DatabaseConnection {
+    /// "embedded", "sidecar" or "remote"
+    mode: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<workspaces::RemoteDatabase>,
+}
+
+/// Get the database mode of the active workspace
+#[tauri::command]
+async fn get_database_connection(
+    state: tauri::State<'_, AppState>,
+) -> Result<DatabaseConnection, ApiError> {
+    let remote = state.workspaces.lock().await.active()?.remote.clone();
+    let mode = match (&remote, cfg!(feature = "sidecar-db")) {
+        (Some(_), _) => "remote",
+        (None, true) => "sidecar",
+        (None, false) => "embedded",
+    };
+    Ok(DatabaseConnection { mode, remote })
+}
+
+/// Connect the active workspace to a remote SurrealDB server, or back to its
+/// local database with `remote: null`. The connection is checked before it
+/// is saved; `password` can be left out to keep the stored one.
+/// The frontend should reload its state after this returns
+#[tauri::command]
+async fn set_database_connection(
+    remote: Option<workspaces::RemoteDatabase>,
+    password: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<DatabaseConnection, ApiError> {
+    let mut registry = state.workspaces.lock().await;
+    let active = registry.active()?.clone();
+    let password_key = active.remote_password_key();
+
+    let database = match &remote {
+        Some(remote) => {
+            let password = match password.filter(|p| !p.is_empty()) {
+                Some(password) => password,
+                None => credentials::get_credential(password_key.clone())?.ok_or_else(|| {
+                    AppError::Validation("A password is needed for the remote database".to_string())
+                })?,
+            };
+            let database = Database::connect_remote(remote, &password).await?;
+            credentials::store_credential(password_key, password)?;
+            database
+        }
+        None => Database::new(active.data_dir.clone()).await?,
+    };
+    let name = active.name;
+    let workspace = registry.set_remote(&name, remote)?;
+    use_database(&workspace, database, app, &state).await;
+    tracing::info!(
+        "Workspace '{}' now uses {}",
+        name,
+        match &workspace.remote {
+            Some(remote) => format!("remote database {}", remote.url),
+            None => "its local database".to_string(),
+        }
+    );
+    drop(registry);
+
+    get_database_connection(state).await
+}
+
+// ============================================================================
+// Attachment Commands
+// ============================================================================
+
+/// Attachment directory of the active workspace
+async fn attachments_dir(state: &AppState) -> Result<PathBuf, AppError> {
+    let registry = state.workspaces.lock().await;
+    let workspace = registry.active()?;
+    Ok(attachments::storage_dir(&workspace.data_dir))
+}
+
+/// Attach a file to a ticket, page or record (owner_type: "tickets", "pages", "records"),
+/// or with `comment_id` to a comment of a ticket
+#[tauri::command]
+async fn add_attachment(
+    owner_type: String,
+    owner_id: String,
+    path: String,
+    comment_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<attachments::Attachment, ApiError> {
+    let storage_dir = attachments_dir(&state).await?;
+    let db = state.database.lock().await;
+    match comment_id {
+        Some(comment_id) if owner_type == "tickets" => {
+            db.add_comment_attachment(&storage_dir, &owner_id, &comment_id, Path::new(&path))
+                .await
+        }
+        Some(_) => Err(AppError::Validation(
+            "Only ticket comments can have attachments".to_string(),
+        )),
+        None => {
+            db.add_attachment(&storage_dir, &owner_type, &owner_id, Path::new(&path))
+                .await
+        }
+    }
+    .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn get_attachment(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<attachments::Attachment, ApiError> {
+    let storage_dir = attachments_dir(&state).await?;
+    let db = state.database.lock().await.clone();
+    db.get_attachment(&storage_dir, &id)
+        .await
+        .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn list_attachments(
+    owner_type: String,
+    owner_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<attachments::Attachment>, ApiError> {
+    let storage_dir = attachments_dir(&state).await?;
+    let db = state.database.lock().await.clone();
+    db.list_attachments(&storage_dir, &owner_type, &owner_id)
+        .await
+        .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn delete_attachment(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let storage_dir = attachments_dir(&state).await?;
+    let db = state.database.lock().await;
+    db.delete_attachment(&storage_dir, &id)
+        .await
+        .map_err(ApiError::from)
+}
+
+// ============================================================================
+// Ticket System Command Wrappers
+// ============================================================================
+
+#[tauri::command]
+async fn create_ticket(
+    ticket: tickets::CreateTicketRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Ticket, ApiError> {
+    let db = state.database.lock().await;
+    db.as_actor(
+        audit::Actor::command("create_ticket"),
+        db.create_ticket(ticket),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn update_ticket(
+    id: String,
+    updates: tickets::UpdateTicketRequest,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Ticket, ApiError> {
+    let db = state.database.lock().await;
+    let previous = match updates.status {
+        Some(_) => Some(db.get_ticket(&id).await?),
+        None => None,
+    };
+    let ticket = db
+        .as_actor(
+            audit::Actor::command("update_ticket"),
+            db.update_ticket(&id, updates),
+        )
+        .await?;
+
+    if let Some(previous) = previous {
+        let change = watchers::TicketChange::Status {
+            from: previous.status,
+            to: ticket.status.clone(),
+        };
+        watchers::notify(&app, &ticket, change);
+    }
+    Ok(ticket)
+}
+
+/// Watch a ticket for status changes and comments (or stop with `watched: false`)
+#[tauri::command]
+async fn watch_ticket(
+    id: String,
+    watched: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Ticket, ApiError> {
+    let db = state.database.lock().await;
+    db.watch_ticket(&id, watched.unwrap_or(true))
+        .await
+        .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn get_watched_tickets(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<tickets::Ticket>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_watched_tickets().await.map_err(ApiError::from)
+}
+
+/// Archive a ticket (or restore it with `archived: false`)
+#[tauri::command]
+async fn archive_ticket(
+    id: String,
+    archived: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Ticket, ApiError> {
+    let db = state.database.lock().await;
+    db.archive_ticket(&id, archived.unwrap_or(true))
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Archive tickets done for more than `days` days; returns the count
+#[tauri::command]
+async fn archive_done_older_than(
+    days: u32,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    let db = state.database.lock().await;
+    db.archive_done_older_than(days)
+        .await
+        .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn get_archived_tickets(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<tickets::Ticket>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_archived_tickets().await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn delete_ticket(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    // Attachment files stay on disk while the delete can be undone; the undo
+    // journal job removes them
+    let db = state.database.lock().await;
+    db.as_actor(
+        audit::Actor::command("delete_ticket"),
+        db.delete_ticket(&id),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn get_tickets(
+    filters: Option<tickets::TicketFilters>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<tickets::Ticket>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_tickets(filters).await.map_err(ApiError::from)
+}
+
+/// Query tickets with filters, text search, sorting and pagination
+#[tauri::command]
+async fn query_tickets(
+    query: tickets::TicketFilters,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::TicketPage, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.query_tickets(query).await.map_err(ApiError::from)
+}
+
+/// Import tickets from a Trello JSON, Jira CSV or mapped CSV export
+/// Tickets whose external key was imported before are skipped
+#[tauri::command]
+async fn import_tickets(
+    format: ticket_import::TicketImportFormat,
+    payload: String,
+    mapping: Option<ticket_import::TicketImportMapping>,
+    state: tauri::State<'_, AppState>,
+) -> Result<ticket_import::TicketImportReport, ApiError> {
+    let db = state.database.lock().await;
+    ticket_import::import_tickets(&db, format, &payload, &mapping.unwrap_or_default())
+        .await
+        .map_err(ApiError::from)
+}
+
+/// People tickets can be assigned to, and the current user's name
+#[tauri::command]
+async fn get_ticket_people(
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::TicketPeople, ApiError> {
+    let settings = state.settings_service.read().await;
+    tickets::TicketPeople::load(&settings)
+        .await
+        .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn save_ticket_people(
+    people: tickets::TicketPeople,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::TicketPeople, ApiError> {
+    let settings = state.settings_service.read().await;
+    people.save(&settings).await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn list_ticket_labels(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<db::tags::TagCount>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_ticket_labels().await.map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn rename_ticket_label(
+    from: String,
+    to: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    let db = state.database.lock().await;
+    db.rename_ticket_label(&from, &to)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Move a ticket to another column; `moved` is false if a strict WIP limit refused it
+#[tauri::command]
+async fn move_ticket(
+    id: String,
+    new_status: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::TicketMove, ApiError> {
+    let db = state.database.lock().await;
+    let previous = db.get_ticket(&id).await?;
+    let result = db
+        .as_actor(
+            audit::Actor::command("move_ticket"),
+            db.move_ticket(&id, &new_status),
+        )
+        .await?;
+
+    if result.moved {
+        let change = watchers::TicketChange::Status {
+            from: previous.status,
+            to: result.ticket.status.clone(),
+        };
+        watchers::notify(&app, &result.ticket, change);
+    }
+    Ok(result)
+}
+
+/// A board's tickets grouped into swimlanes by label, assignee or priority
+#[tauri::command]
+async fn get_board_grouped(
+    board_id: Option<String>,
+    group_by: boards::SwimlaneGrouping,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardGrouped, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_board_grouped(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        group_by,
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Lead time, cycle time, weekly throughput and aging WIP of a board
+#[tauri::command]
+async fn get_board_metrics(
+    board_id: Option<String>,
+    range: Option<board_metrics::MetricsRange>,
+    state: tauri::State<'_, AppState>,
+) -> Result<board_metrics::BoardMetrics, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_board_metrics(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        range.unwrap_or_default(),
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Status changes of a ticket, oldest first
+#[tauri::command]
+async fn get_ticket_history(
+    id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<board_metrics::TicketStatusEvent>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_ticket_history(&id).await.map_err(ApiError::from)
+}
+
+/// Board columns with their ticket counts against WIP limits
+#[tauri::command]
+async fn get_board_state(
+    board_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardState, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_board_state(board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD))
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Board columns (the default columns without `board_id` or a stored board)
+#[tauri::command]
+async fn get_board_config(
+    board_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_board_config(board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD))
+        .await
+        .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn save_board_columns(
+    board_id: Option<String>,
+    columns: Vec<boards::BoardColumn>,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await;
+    db.save_board_columns(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        columns,
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+#[tauri::command]
+async fn add_board_column(
+    board_id: Option<String>,
+    column: boards::BoardColumn,
+    position: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await;
+    db.add_board_column(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        column,
+        position,
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
 #[tauri::command]
-async fn fetch_adapter_data(
-    config: AdapterConfig,
+async fn update_board_column(
+    board_id: Option<String>,
+    column_id: String,
+    updates: boards::UpdateBoardColumnRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<usize, String> {
-    tracing::info!("Fetching data with adapter: {}", config.adapter_type);
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await;
+    db.update_board_column(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        &column_id,
+        updates,
+    )
+    .await
+    .map_err(ApiError::from)
+}
 
-    // Phase 3.3: Check if plugin exists first
-    let has_plugin = {
-        let plugin_manager = state.plugin_manager.lock().await;
-        plugin_manager
-            .get_plugin_by_adapter_type(&config.adapter_type)
-            .is_some()
-    };
+#[tauri::command]
+async fn reorder_board_columns(
+    board_id: Option<String>,
+    column_ids: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await;
+    db.reorder_board_columns(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        &column_ids,
+    )
+    .await
+    .map_err(ApiError::from)
+}
+
+/// Remove a column, moving its tickets to `move_tickets_to` if it has any
+#[tauri::command]
+async fn delete_board_column(
+    board_id: Option<String>,
+    column_id: String,
+    move_tickets_to: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await;
+    db.delete_board_column(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        &column_id,
+        move_tickets_to.as_deref(),
+    )
+    .await
+    .map_err(ApiError::from)
+}
 
-    let records = if has_plugin {
-        eprintln!("✅ Using PLUGIN for adapter: {}", config.adapter_type);
-        tracing::info!("Using plugin for adapter: {}", config.adapter_type);
+/// Prefix of a board's new ticket keys ("MOD" gives MOD-1, MOD-2, ...)
+#[tauri::command]
+async fn set_board_key_prefix(
+    board_id: Option<String>,
+    prefix: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await;
+    db.set_board_key_prefix(
+        board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD),
+        &prefix,
+    )
+    .await
+    .map_err(ApiError::from)
+}
 
-        let plugin_manager = state.plugin_manager.lock().await;
-        let plugin = plugin_manager
-            .get_plugin_by_adapter_type(&config.adapter_type)
-            .expect("Plugin should exist");
-
-        eprintln!("🔌 Found plugin for adapter type '{}'", config.adapter_type);
-
-        eprintln!("📤 Calling plugin.fetch() with config...");
-        eprintln!("📤 Config adapter_type: {}", config.adapter_type);
-        eprintln!("📤 Config endpoint: {}", config.endpoint);
-        eprintln!("📤 Config source: {}", config.source);
-        eprintln!("📤 Config parameters: {:?}", config.parameters);
-
-        match plugin.fetch(&config).await {
-            Ok(records) => {
-                eprintln!("✅ Plugin fetch succeeded! Got {} records", records.len());
-                records
-            }
-            Err(e) => {
-                eprintln!("❌ Plugin fetch failed: {}", e);
-                tracing::error!("Plugin fetch failed for {}: {}", config.adapter_type, e);
-                return Err(format!("Plugin fetch failed: {}", e));
-            }
-        }
-    } else {
-        eprintln!("❌ No plugin found for adapter: {}", config.adapter_type);
-        tracing::error!("No plugin found for adapter type: {}", config.adapter_type);
-        return Err(format!(
-            "No plugin found for adapter type: {}. Please install the appropriate plugin.",
-            config.adapter_type
-        ));
-    };
+/// Go back to the default columns
+#[tauri::command]
+async fn reset_board_config(
+    board_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<boards::BoardConfig, ApiError> {
+    let db = state.database.lock().await;
+    db.reset_board_config(board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD))
+        .await
+        .map_err(ApiError::from)
+}
 
-    let count = records.len();
-    tracing::info!("Fetched {} records, storing in database", count);
+/// One board with its tickets, relations and tracked time as a JSON document
+#[tauri::command]
+async fn export_board(
+    board_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<board_export::BoardExport, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.export_board(board_id.as_deref().unwrap_or(boards::DEFAULT_BOARD))
+        .await
+        .map_err(ApiError::from)
+}
 
-    // Store all records in database (using upsert to prevent duplicates)
+/// Import an exported board into an empty board (the exported board's ID by default)
+#[tauri::command]
+async fn import_board(
+    document: board_export::BoardExport,
+    board_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<board_export::BoardImport, ApiError> {
     let db = state.database.lock().await;
-    let mut upserted = 0;
-    for record in records {
-        db.upsert_record(record).await.map_err(|e| e.to_string())?;
-        upserted += 1;
-    }
+    db.import_board(document, board_id.as_deref())
+        .await
+        .map_err(ApiError::from)
+}
 
-    tracing::info!(
-        "Upserted {} records successfully (updates existing, creates new)",
-        upserted
-    );
+#[tauri::command]
+async fn add_comment(
+    ticket_id: String,
+    text: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Comment, ApiError> {
+    let people = {
+        let settings = state.settings_service.read().await;
+        tickets::TicketPeople::load(&settings).await?
+    };
+    let db = state.database.lock().await;
+    let req = tickets::CreateCommentRequest {
+        author: people.author(),
+        text,
+    };
+    let comment = db.add_comment(&ticket_id, req).await?;
 
-    Ok(count)
+    let ticket = db.get_ticket(&ticket_id).await?;
+    let change = watchers::TicketChange::Comment {
+        author: comment.author.clone(),
+        text: comment.text.clone(),
+    };
+    watchers::notify(&app, &ticket, change);
+    Ok(comment)
 }
 
-/// Clear all records from the database
 #[tauri::command]
-async fn clear_all_records(state: tauri::State<'_, AppState>) -> Result<usize, String> {
-    tracing::info!("Clearing all records from database");
+async fn get_due_tickets(
+    window_hours: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<reminders::DueTicket>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_due_tickets(window_hours.unwrap_or(reminders::DEFAULT_REMINDER_WINDOW_HOURS))
+        .await
+        .map_err(ApiError::from)
+}
 
+#[tauri::command]
+async fn add_checklist_item(
+    ticket_id: String,
+    text: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Ticket, ApiError> {
     let db = state.database.lock().await;
-    let count = db.clear_all_records().await.map_err(|e| e.to_string())?;
+    db.add_checklist_item(&ticket_id, &text)
+        .await
+        .map_err(ApiError::from)
+}
 
-    tracing::info!("Cleared {} records", count);
-    Ok(count)
+#[tauri::command]
+async fn toggle_checklist_item(
+    ticket_id: String,
+    item_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Ticket, ApiError> {
+    let db = state.database.lock().await;
+    db.toggle_checklist_item(&ticket_id, &item_id)
+        .await
+        .map_err(ApiError::from)
 }
 
-/// Get database statistics
 #[tauri::command]
-async fn get_database_stats(
+async fn remove_checklist_item(
+    ticket_id: String,
+    item_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<db::DatabaseStats, String> {
+) -> Result<tickets::Ticket, ApiError> {
     let db = state.database.lock().await;
-    db.get_stats().await.map_err(|e| e.to_string())
+    db.remove_checklist_item(&ticket_id, &item_id)
+        .await
+        .map_err(ApiError::from)
 }
 
-/// M5 Phase 3: Clean up old records based on TTL
+/// Relate two tickets; "blocks" relations that would form a cycle are refused
 #[tauri::command]
-async fn cleanup_old_records(
-    ttl_days: i64,
-    source: Option<String>,
+async fn add_ticket_relation(
+    from: String,
+    to: String,
+    kind: tickets::TicketRelationKind,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    tracing::info!(
-        "Cleaning up records older than {} days for source: {:?}",
-        ttl_days,
-        source
-    );
+) -> Result<String, ApiError> {
+    let db = state.database.lock().await;
+    db.add_ticket_relation(&from, &to, kind)
+        .await
+        .map_err(ApiError::from)
+}
 
+#[tauri::command]
+async fn remove_ticket_relation(
+    link_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
     let db = state.database.lock().await;
-    let deleted = db
-        .cleanup_old_records(ttl_days, source.as_deref())
+    db.remove_ticket_relation(&link_id)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(ApiError::from)
+}
 
-    tracing::info!("Deleted {} old records", deleted);
+#[tauri::command]
+async fn create_sprint(
+    sprint: sprints::CreateSprintRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<sprints::Sprint, ApiError> {
+    let db = state.database.lock().await;
+    db.create_sprint(sprint).await.map_err(ApiError::from)
+}
 
-    Ok(serde_json::json!({
-        "deleted": deleted
-    }))
+#[tauri::command]
+async fn get_sprints(state: tauri::State<'_, AppState>) -> Result<Vec<sprints::Sprint>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_sprints().await.map_err(ApiError::from)
 }
 
-/// M5: Delete records by type (e.g., "gitlab_pipeline")
 #[tauri::command]
-async fn delete_records_by_type(
-    record_type: String,
+async fn update_sprint(
+    id: String,
+    updates: sprints::UpdateSprintRequest,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    tracing::info!("Deleting all records of type: {}", record_type);
+) -> Result<sprints::Sprint, ApiError> {
+    let db = state.database.lock().await;
+    db.update_sprint(&id, updates).await.map_err(ApiError::from)
+}
 
+/// Delete a sprint; its tickets go back to the backlog
+#[tauri::command]
+async fn delete_sprint(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
     let db = state.database.lock().await;
-    let deleted = db
-        .delete_records_by_type(&record_type)
-        .await
-        .map_err(|e| e.to_string())?;
+    db.delete_sprint(&id).await.map_err(ApiError::from)
+}
 
-    Ok(serde_json::json!({
-        "deleted": deleted
-    }))
+/// Put a ticket into a sprint, or back into the backlog without `sprint_id`
+#[tauri::command]
+async fn set_ticket_sprint(
+    ticket_id: String,
+    sprint_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<tickets::Ticket, ApiError> {
+    let db = state.database.lock().await;
+    db.set_ticket_sprint(&ticket_id, sprint_id.as_deref())
+        .await
+        .map_err(ApiError::from)
 }
 
-/// M5: Delete records by source and type
+/// Ticket counts by status and daily burndown of a sprint
 #[tauri::command]
-async fn delete_records_by_source_and_type(
-    source: String,
-    record_type: String,
+async fn get_sprint_summary(
+    id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    tracing::info!(
-        "Deleting records of type '{}' from source '{}'",
-        record_type,
-        source
-    );
+) -> Result<sprints::SprintSummary, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_sprint_summary(&id).await.map_err(ApiError::from)
+}
 
+/// Close a sprint, carrying unfinished tickets over to another sprint or the backlog
+#[tauri::command]
+async fn close_sprint(
+    id: String,
+    carry_over_to: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<sprints::SprintClose, ApiError> {
     let db = state.database.lock().await;
-    let deleted = db
-        .delete_records_by_source_and_type(&source, &record_type)
+    db.close_sprint(&id, carry_over_to.as_deref())
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(serde_json::json!({
-        "deleted": deleted
-    }))
+        .map_err(ApiError::from)
 }
 
-/// M9: Export all database data to JSON
-/// Can be used to migrate data from dev to prod or vice versa
+/// Start a timer on a ticket; only one timer runs at a time
 #[tauri::command]
-async fn export_database(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
-    tracing::info!("Exporting database data");
+async fn start_ticket_timer(
+    ticket_id: String,
+    description: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<time_entries::TimeEntry, ApiError> {
+    let db = state.database.lock().await;
+    db.start_ticket_timer(&ticket_id, description)
+        .await
+        .map_err(ApiError::from)
+}
 
+#[tauri::command]
+async fn stop_ticket_timer(
+    ticket_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<time_entries::TimeEntry, ApiError> {
     let db = state.database.lock().await;
-    let mut export = db.export_all_data().await.map_err(|e| e.to_string())?;
+    db.stop_ticket_timer(&ticket_id)
+        .await
+        .map_err(ApiError::from)
+}
 
-    // Also export file-based dashboards (legacy format)
-    drop(db); // Release database lock before acquiring dashboard service lock
-    let dashboard_service = state.dashboard_service.lock().await;
-    let dashboards = dashboard_service
-        .get_all()
-        .map_err(|e| format!("Failed to export dashboards: {}", e))?;
+/// Log time on a ticket manually
+#[tauri::command]
+async fn add_time_entry(
+    ticket_id: String,
+    entry: time_entries::CreateTimeEntryRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<time_entries::TimeEntry, ApiError> {
+    let db = state.database.lock().await;
+    db.add_time_entry(&ticket_id, entry)
+        .await
+        .map_err(ApiError::from)
+}
 
-    // Add dashboards to export
-    if let Some(data) = export.get_mut("data") {
-        if let Some(data_obj) = data.as_object_mut() {
-            data_obj.insert(
-                "dashboards".to_string(),
-                serde_json::to_value(&dashboards).unwrap_or(serde_json::json!([])),
-            );
-        }
-    }
+#[tauri::command]
+async fn delete_time_entry(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.delete_time_entry(&id).await.map_err(ApiError::from)
+}
 
-    tracing::info!(
-        "Database export complete (including {} dashboards)",
-        dashboards.len()
-    );
-    Ok(export)
+#[tauri::command]
+async fn get_ticket_time_entries(
+    ticket_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<time_entries::TimeEntry>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_ticket_time_entries(&ticket_id)
+        .await
+        .map_err(ApiError::from)
 }
 
-/// M9: Import database data from JSON
-/// merge_strategy options:
-/// - "replace": Clear existing data first, then import
-/// - "merge": Keep existing data, add imported data (may create duplicates)
-/// - "skip": Keep existing data on conflict
 #[tauri::command]
-async fn import_database(
-    import_data: serde_json::Value,
-    merge_strategy: String,
+async fn get_running_timer(
     state: tauri::State<'_, AppState>,
-) -> Result<db::ImportStats, String> {
-    tracing::info!("Importing database data with strategy: {}", merge_strategy);
+) -> Result<Option<time_entries::TimeEntry>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_running_timer().await.map_err(ApiError::from)
+}
 
-    let db = state.database.lock().await;
-    let mut stats = db
-        .import_data(import_data.clone(), &merge_strategy)
+/// Tracked ticket time per ticket and per day between two dates (inclusive)
+#[tauri::command]
+async fn get_time_summary(
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+    ticket_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<time_entries::TimeSummary, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_time_summary(from, to, ticket_id.as_deref())
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(ApiError::from)
+}
 
-    // Also import file-based dashboards (legacy format)
-    drop(db); // Release database lock before acquiring dashboard service lock
+// ============================================================================
+// RSS Feed Reader Command Wrapper
+// ============================================================================
 
-    if let Some(dashboards) = import_data
-        .get("data")
-        .and_then(|d| d.get("dashboards"))
-        .and_then(|d| d.as_array())
-    {
-        let dashboard_service = state.dashboard_service.lock().await;
-
-        // If replace mode, delete existing dashboards first
-        if merge_strategy == "replace" {
-            // Get all dashboard IDs and delete them
-            if let Ok(existing) = dashboard_service.get_all() {
-                for dash in existing {
-                    let _ = dashboard_service.delete(&dash.id);
-                }
-            }
-        }
+/// Fetch and parse an RSS or Atom feed (for the feed reader plugin, which
+/// can't fetch across origins itself)
+#[tauri::command]
+async fn fetch_rss_feed(url: String) -> Result<adapters::rss::Feed, ApiError> {
+    network::ensure_online()?;
+    tracing::info!("Fetching RSS feed: {}", url);
+    Ok(adapters::rss::read_feed(network::client().get(&url)).await?)
+}
 
-        // Import dashboards
-        for dashboard in dashboards {
-            match serde_json::from_value::<Dashboard>(dashboard.clone()) {
-                Ok(dashboard) => match dashboard_service.save(&dashboard) {
-                    Ok(_) => stats.dashboards_imported += 1,
-                    Err(e) => stats
-                        .errors
-                        .push(format!("Failed to import dashboard: {}", e)),
-                },
-                Err(e) => stats
-                    .errors
-                    .push(format!("Failed to parse dashboard: {}", e)),
-            }
-        }
+/// Feed subscriptions with their unread counts
+#[tauri::command]
+async fn list_feed_subscriptions(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<feeds::FeedSubscription>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.list_feed_subscriptions().await.map_err(ApiError::from)
+}
 
-        tracing::info!("Imported {} dashboards", stats.dashboards_imported);
+/// Subscribe to a feed and fetch it right away; a URL already subscribed to
+/// returns the existing subscription
+#[tauri::command]
+async fn subscribe_feed(
+    subscription: feeds::SubscribeFeedRequest,
+    state: tauri::State<'_, AppState>,
+) -> Result<feeds::FeedSubscription, ApiError> {
+    let subscription = state
+        .database
+        .lock()
+        .await
+        .subscribe_feed(subscription)
+        .await?;
+    if subscription.last_fetched.is_none() {
+        // A feed that can't be fetched yet stays subscribed, with the error
+        if let Err(e) = feeds::refresh(&state.database, &subscription).await {
+            tracing::warn!("Failed to fetch feed {}: {}", subscription.url, e);
+        }
     }
+    let db = state.database.lock().await;
+    Ok(db.get_feed_subscription(&subscription.id).await?)
+}
 
-    tracing::info!("Database import complete");
-    Ok(stats)
+/// Remove a feed subscription and its items
+#[tauri::command]
+async fn unsubscribe_feed(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.unsubscribe_feed(&id).await.map_err(ApiError::from)
 }
 
-// ============================================================================
-// Ticket System Command Wrappers
-// ============================================================================
+/// Fetch a subscribed feed now, returning the number of new items
+#[tauri::command]
+async fn refresh_feed(id: String, state: tauri::State<'_, AppState>) -> Result<usize, ApiError> {
+    let subscription = state
+        .database
+        .lock()
+        .await
+        .get_feed_subscription(&id)
+        .await?;
+    Ok(feeds::refresh(&state.database, &subscription).await?)
+}
 
+/// Items of subscribed feeds, newest first
 #[tauri::command]
-async fn create_ticket(
-    ticket: tickets::CreateTicketRequest,
+async fn get_feed_items(
+    filters: Option<feeds::FeedItemFilters>,
     state: tauri::State<'_, AppState>,
-) -> Result<tickets::Ticket, String> {
-    let db = state.database.lock().await;
-    db.create_ticket(ticket).await.map_err(|e| e.to_string())
+) -> Result<Vec<feeds::FeedItem>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_feed_items(filters.unwrap_or_default())
+        .await
+        .map_err(ApiError::from)
 }
 
+/// Mark a feed item read (or unread again with `read: false`)
 #[tauri::command]
-async fn update_ticket(
+async fn mark_feed_item_read(
     id: String,
-    updates: tickets::UpdateTicketRequest,
+    read: Option<bool>,
     state: tauri::State<'_, AppState>,
-) -> Result<tickets::Ticket, String> {
+) -> Result<feeds::FeedItem, ApiError> {
     let db = state.database.lock().await;
-    db.update_ticket(&id, updates)
+    db.mark_feed_item_read(&id, read.unwrap_or(true))
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ApiError::from)
 }
 
+/// Mark all items of a feed read, or of every feed without a subscription id
 #[tauri::command]
-async fn delete_ticket(id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn mark_feed_read(
+    subscription_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, ApiError> {
     let db = state.database.lock().await;
-    db.delete_ticket(&id).await.map_err(|e| e.to_string())
+    db.mark_feed_read(subscription_id.as_deref())
+        .await
+        .map_err(ApiError::from)
 }
 
+/// Blocks making up a page's content, in order
 #[tauri::command]
-async fn get_tickets(state: tauri::State<'_, AppState>) -> Result<Vec<tickets::Ticket>, String> {
-    let db = state.database.lock().await;
-    db.get_tickets(None).await.map_err(|e| e.to_string())
+async fn get_page_blocks(
+    page_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<page_blocks::PageBlock>, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_page_blocks(&page_id).await.map_err(ApiError::from)
 }
 
 #[tauri::command]
-async fn move_ticket(
+async fn get_page_block(
     id: String,
-    new_status: String,
     state: tauri::State<'_, AppState>,
-) -> Result<tickets::Ticket, String> {
+) -> Result<page_blocks::PageBlock, ApiError> {
+    let db = state.database.lock().await.clone();
+    db.get_page_block(&id).await.map_err(ApiError::from)
+}
+
+/// Add a block to a page at `index` (the end if omitted)
+#[tauri::command]
+async fn insert_page_block(
+    page_id: String,
+    content: page_blocks::BlockContent,
+    index: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<page_blocks::PageBlock, ApiError> {
     let db = state.database.lock().await;
-    db.move_ticket(&id, &new_status)
+    db.insert_page_block(&page_id, content, index)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ApiError::from)
 }
 
 #[tauri::command]
-async fn add_comment(
-    ticket_id: String,
-    text: String,
+async fn update_page_block(
+    id: String,
+    content: page_blocks::BlockContent,
     state: tauri::State<'_, AppState>,
-) -> Result<tickets::Comment, String> {
+) -> Result<page_blocks::PageBlock, ApiError> {
     let db = state.database.lock().await;
-    let req = tickets::CreateCommentRequest {
-        author: "User".to_string(), // TODO: Get from auth context
-        text,
-    };
-    db.add_comment(&ticket_id, req)
+    db.update_page_block(&id, content)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(ApiError::from)
 }
 
-// ============================================================================
-// RSS Feed Reader Command Wrapper
-// ============================================================================
-
+/// Move a block within its page, or to another page with `page_id`
 #[tauri::command]
-async fn fetch_rss_feed(url: String) -> Result<serde_json::Value, String> {
-    use reqwest;
-
-    tracing::info!("Fetching RSS feed: {}", url);
-
-    let response = reqwest::get(&url)
+async fn move_page_block(
+    id: String,
+    index: usize,
+    page_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<page_blocks::PageBlock>, ApiError> {
+    let db = state.database.lock().await;
+    db.move_page_block(&id, index, page_id.as_deref())
         .await
-        .map_err(|e| format!("Failed to fetch RSS feed: {}", e))?;
+        .map_err(ApiError::from)
+}
 
-    let content = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read RSS feed content: {}", e))?;
+#[tauri::command]
+async fn delete_page_block(id: String, state: tauri::State<'_, AppState>) -> Result<(), ApiError> {
+    let db = state.database.lock().await;
+    db.delete_page_block(&id).await.map_err(ApiError::from)
+}
 
-    // Parse RSS/Atom feed (simplified - you might want to use a proper RSS parser crate)
-    // For now, just return the raw XML as a string wrapped in JSON
-    Ok(serde_json::json!({
-        "url": url,
-        "content": content
-    }))
+/// A page's content as a Markdown or standalone HTML document
+#[tauri::command]
+async fn export_page(
+    id: String,
+    format: page_export::PageExportFormat,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, ApiError> {
+    let db = state.database.lock().await.clone();
+    Ok(page_export::export_page(&db, &id, format).await?)
 }
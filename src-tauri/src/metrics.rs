@@ -0,0 +1,383 @@
+// Metrics
+//
+// Counters and latency histograms for the internal performance page: fetch
+// durations, plugin call times, database query latency and stored record
+// counts. Collection is opt-in (the `metrics.enabled` setting, off by
+// default); while it's off recording is a no-op. Values live in memory since
+// the last time metrics were turned on; the local HTTP API (`local_api`) also
+// serves them in the Prometheus text format.
+//
+// Like the network config, the flag is process-wide and re-read whenever the
+// setting changes, so instrumented code needs no access to the settings.
+
+use crate::error::AppError;
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Setting that turns collection on
+pub const ENABLED_SETTING: &str = "metrics.enabled";
+
+/// Prefix of the metric names in the Prometheus export
+const PROMETHEUS_PREFIX: &str = "modulaur_";
+
+/// Upper bounds of the histogram buckets, in milliseconds
+const BUCKETS_MS: [f64; 12] = [
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, 30000.0,
+];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry::new());
+
+/// A metric name with its labels, e.g. fetch_duration_ms{source="gitlab"}
+type MetricKey = (&'static str, Vec<(&'static str, String)>);
+
+struct Registry {
+    since: Option<DateTime<Utc>>,
+    counters: BTreeMap<MetricKey, u64>,
+    histograms: BTreeMap<MetricKey, Histogram>,
+}
+
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            since: None,
+            counters: BTreeMap::new(),
+            histograms: BTreeMap::new(),
+        }
+    }
+}
+
+struct Histogram {
+    count: u64,
+    sum_ms: f64,
+    max_ms: f64,
+    /// Observations per bucket of `BUCKETS_MS` (not cumulative); the last
+    /// entry counts the ones above the largest bound
+    buckets: Vec<u64>,
+}
+
+impl Histogram {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            sum_ms: 0.0,
+            max_ms: 0.0,
+            buckets: vec![0; BUCKETS_MS.len() + 1],
+        }
+    }
+
+    fn observe(&mut self, value_ms: f64) {
+        let bucket = BUCKETS_MS
+            .iter()
+            .position(|bound| value_ms <= *bound)
+            .unwrap_or(BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum_ms += value_ms;
+        self.max_ms = self.max_ms.max(value_ms);
+    }
+
+    /// Estimated value below which a fraction of the observations fall (the
+    /// upper bound of the bucket holding it)
+    fn quantile(&self, q: f64) -> f64 {
+        let target = (self.count as f64 * q).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return BUCKETS_MS.get(bucket).copied().unwrap_or(self.max_ms);
+            }
+        }
+        self.max_ms
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CounterValue {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub value: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramValue {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+    pub count: u64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub enabled: bool,
+    /// When collection was last turned on
+    pub since: Option<DateTime<Utc>>,
+    pub counters: Vec<CounterValue>,
+    pub histograms: Vec<HistogramValue>,
+}
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![SettingDefinition::new(
+        ENABLED_SETTING,
+        SettingType::Boolean,
+        false.into(),
+        "metrics",
+    )
+    .describe(
+        "Collect metrics",
+        "Record fetch, plugin and database timings for the performance page",
+    )]
+}
+
+pub fn is_metrics_setting(key: &str) -> bool {
+    key == ENABLED_SETTING
+}
+
+/// Re-read the metrics setting (at startup and after it changes)
+pub async fn reload(settings: &SettingsService) -> Result<(), AppError> {
+    let enabled = settings.get_value(ENABLED_SETTING).await?;
+    set_enabled(enabled.as_bool().unwrap_or(false));
+    Ok(())
+}
+
+/// Turn collection on or off; turning it on starts from empty values
+pub fn set_enabled(enabled: bool) {
+    let was_enabled = ENABLED.swap(enabled, Ordering::Relaxed);
+    if enabled && !was_enabled {
+        let mut registry = registry();
+        *registry = Registry::new();
+        registry.since = Some(Utc::now());
+        tracing::info!("Metrics collection enabled");
+    } else if !enabled && was_enabled {
+        *registry() = Registry::new();
+        tracing::info!("Metrics collection disabled");
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn key(name: &'static str, labels: &[(&'static str, &str)]) -> MetricKey {
+    let labels = labels
+        .iter()
+        .map(|(label, value)| (*label, value.to_string()))
+        .collect();
+    (name, labels)
+}
+
+/// Add to a counter
+pub fn increment(name: &'static str, labels: &[(&'static str, &str)], by: u64) {
+    if !is_enabled() {
+        return;
+    }
+    *registry().counters.entry(key(name, labels)).or_insert(0) += by;
+}
+
+/// Record a duration in milliseconds
+pub fn observe(name: &'static str, labels: &[(&'static str, &str)], value_ms: f64) {
+    if !is_enabled() {
+        return;
+    }
+    registry()
+        .histograms
+        .entry(key(name, labels))
+        .or_insert_with(Histogram::new)
+        .observe(value_ms);
+}
+
+/// Record the time elapsed since `start`
+pub fn observe_since(name: &'static str, labels: &[(&'static str, &str)], start: Instant) {
+    observe(name, labels, start.elapsed().as_secs_f64() * 1000.0);
+}
+
+fn label_map(labels: &[(&'static str, String)]) -> BTreeMap<String, String> {
+    labels
+        .iter()
+        .map(|(label, value)| (label.to_string(), value.clone()))
+        .collect()
+}
+
+/// Current values, for `get_metrics_snapshot`
+pub fn snapshot() -> MetricsSnapshot {
+    let registry = registry();
+    MetricsSnapshot {
+        enabled: is_enabled(),
+        since: registry.since,
+        counters: registry
+            .counters
+            .iter()
+            .map(|((name, labels), value)| CounterValue {
+                name: name.to_string(),
+                labels: label_map(labels),
+                value: *value,
+            })
+            .collect(),
+        histograms: registry
+            .histograms
+            .iter()
+            .map(|((name, labels), histogram)| HistogramValue {
+                name: name.to_string(),
+                labels: label_map(labels),
+                count: histogram.count,
+                mean_ms: histogram.sum_ms / histogram.count.max(1) as f64,
+                p50_ms: histogram.quantile(0.5),
+                p95_ms: histogram.quantile(0.95),
+                max_ms: histogram.max_ms,
+            })
+            .collect(),
+    }
+}
+
+fn prometheus_labels(labels: &[(&'static str, String)], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(label, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", label, value)
+        })
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+/// Current values in the Prometheus text exposition format (served by the
+/// local HTTP API)
+pub fn render_prometheus() -> String {
+    let registry = registry();
+    let mut out = String::new();
+
+    let mut last_name = "";
+    for ((name, labels), value) in &registry.counters {
+        if *name != last_name {
+            let _ = writeln!(out, "# TYPE {}{} counter", PROMETHEUS_PREFIX, name);
+            last_name = name;
+        }
+        let _ = writeln!(
+            out,
+            "{}{}{} {}",
+            PROMETHEUS_PREFIX,
+            name,
+            prometheus_labels(labels, None),
+            value
+        );
+    }
+
+    let mut last_name = "";
+    for ((name, labels), histogram) in &registry.histograms {
+        if *name != last_name {
+            let _ = writeln!(out, "# TYPE {}{} histogram", PROMETHEUS_PREFIX, name);
+            last_name = name;
+        }
+        let mut cumulative = 0;
+        for (bucket, count) in histogram.buckets.iter().enumerate() {
+            cumulative += count;
+            let le = BUCKETS_MS
+                .get(bucket)
+                .map_or("+Inf".to_string(), |bound| bound.to_string());
+            let _ = writeln!(
+                out,
+                "{}{}_bucket{} {}",
+                PROMETHEUS_PREFIX,
+                name,
+                prometheus_labels(labels, Some(&le)),
+                cumulative
+            );
+        }
+        let labels = prometheus_labels(labels, None);
+        let _ = writeln!(
+            out,
+            "{}{}_sum{} {}",
+            PROMETHEUS_PREFIX, name, labels, histogram.sum_ms
+        );
+        let _ = writeln!(
+            out,
+            "{}{}_count{} {}",
+            PROMETHEUS_PREFIX, name, labels, histogram.count
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        // The registry is process-wide; only this test turns it on
+        set_enabled(false);
+        increment("test_ignored_total", &[], 1);
+
+        set_enabled(true);
+        increment("test_fetches_total", &[("source", "gitlab")], 1);
+        increment("test_fetches_total", &[("source", "gitlab")], 2);
+        increment("test_fetches_total", &[("source", "jira")], 1);
+        for value in [3.0, 8.0, 40.0, 40.0, 60000.0] {
+            observe("test_duration_ms", &[("operation", "query")], value);
+        }
+
+        let snapshot = snapshot();
+        assert!(snapshot.enabled);
+        assert!(snapshot.since.is_some());
+        let gitlab = snapshot
+            .counters
+            .iter()
+            .find(|c| c.name == "test_fetches_total" && c.labels["source"] == "gitlab")
+            .unwrap();
+        assert_eq!(gitlab.value, 3);
+        assert!(!snapshot
+            .counters
+            .iter()
+            .any(|c| c.name == "test_ignored_total"));
+
+        let histogram = snapshot
+            .histograms
+            .iter()
+            .find(|h| h.name == "test_duration_ms")
+            .unwrap();
+        assert_eq!(histogram.count, 5);
+        assert_eq!(histogram.p50_ms, 50.0);
+        assert_eq!(histogram.p95_ms, 60000.0);
+        assert_eq!(histogram.max_ms, 60000.0);
+
+        let text = render_prometheus();
+        assert!(text.contains("# TYPE modulaur_test_fetches_total counter\n"));
+        assert!(text.contains("modulaur_test_fetches_total{source=\"jira\"} 1\n"));
+        assert!(text.contains("modulaur_test_duration_ms_bucket{operation=\"query\",le=\"5\"} 1\n"));
+        assert!(
+            text.contains("modulaur_test_duration_ms_bucket{operation=\"query\",le=\"+Inf\"} 5\n")
+        );
+        assert!(text.contains("modulaur_test_duration_ms_count{operation=\"query\"} 5\n"));
+
+        set_enabled(false);
+        increment("test_fetches_total", &[("source", "gitlab")], 1);
+        assert!(!snapshot()
+            .counters
+            .iter()
+            .any(|c| c.name == "test_fetches_total"));
+    }
+}
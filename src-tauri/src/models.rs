@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Panel {
     pub i: String,
     pub x: i32,
@@ -13,6 +13,21 @@ pub struct Panel {
     pub config: serde_json::Value,
 }
 
+/// Value widget queries of a dashboard can reference as `${name}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardVariable {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Current value: text, a number, a list (e.g. of sources) or a date
+    /// range object (`{ "from": ..., "to": ... }`)
+    #[serde(default)]
+    pub value: serde_json::Value,
+    /// Values offered for selection
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<serde_json::Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dashboard {
     pub id: String,
@@ -22,6 +37,15 @@ pub struct Dashboard {
     pub created_at: i64,
     #[serde(rename = "updatedAt")]
     pub updated_at: i64,
+    /// Seconds between background refreshes (none: no auto-refresh)
+    #[serde(
+        rename = "refreshInterval",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub refresh_interval: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<DashboardVariable>,
 }
 
 impl Dashboard {
@@ -34,6 +58,16 @@ impl Dashboard {
             panels: Vec::new(),
             created_at: now,
             updated_at: now,
+            refresh_interval: None,
+            variables: Vec::new(),
         }
     }
+
+    /// Current values of the dashboard's variables by name
+    pub fn variable_values(&self) -> std::collections::BTreeMap<String, serde_json::Value> {
+        self.variables
+            .iter()
+            .map(|variable| (variable.name.clone(), variable.value.clone()))
+            .collect()
+    }
 }
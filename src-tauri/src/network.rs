@@ -0,0 +1,301 @@
+// Network settings
+//
+// Outbound HTTP (adapters, plugin host calls, RSS feeds, prompt registry
+// sync) goes through clients built here, so the "network.*" settings apply
+// everywhere: a proxy, the request timeout and the User-Agent. The offline
+// toggle (`manualOfflineMode`, also shown by the UI) makes those requests fail
+// right away instead of hanging on a network that isn't there.
+//
+// The settings are read into a process-wide config at startup and again
+// whenever one of them changes, since `HttpClient::new_client` and the plugin
+// host have no access to the settings service. A copy is kept in the app root
+// so the SurrealDB sidecar download, which runs before the settings database
+// is up, follows them too.
+
+use crate::error::{AppError, HttpFailure};
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+pub const DEFAULT_USER_AGENT: &str = concat!("Modulaur/", env!("CARGO_PKG_VERSION"));
+
+/// Setting that turns offline mode on
+pub const OFFLINE_SETTING: &str = "manualOfflineMode";
+
+/// Error message of requests refused in offline mode
+pub const OFFLINE_MESSAGE: &str = "Offline mode is on; outbound requests are disabled";
+
+static NETWORK: RwLock<Option<NetworkConfig>> = RwLock::new(None);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Proxy for all outbound requests, e.g. "http://proxy:3128"
+    pub proxy_url: Option<String>,
+    pub timeout_secs: u64,
+    pub user_agent: String,
+    pub offline: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy_url: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            offline: false,
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// Load the network settings, falling back to defaults
+    pub async fn load(settings: &SettingsService) -> Result<Self, AppError> {
+        let proxy_url = settings.get_value("network.proxy_url").await?;
+        let timeout = settings.get_value("network.timeout_secs").await?;
+        let user_agent = settings.get_value("network.user_agent").await?;
+        let offline = settings.get_value(OFFLINE_SETTING).await?;
+
+        Ok(Self {
+            proxy_url: proxy_url
+                .as_str()
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(String::from),
+            timeout_secs: timeout
+                .as_u64()
+                .filter(|secs| *secs > 0)
+                .unwrap_or(DEFAULT_TIMEOUT_SECS),
+            user_agent: user_agent
+                .as_str()
+                .map(str::trim)
+                .filter(|agent| !agent.is_empty())
+                .unwrap_or(DEFAULT_USER_AGENT)
+                .to_string(),
+            offline: offline.as_bool().unwrap_or(false),
+        })
+    }
+
+    /// Client builder with the proxy, timeout and User-Agent applied
+    pub fn client_builder(&self) -> Result<reqwest::ClientBuilder, AppError> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .user_agent(self.user_agent.clone());
+        if let Some(url) = &self.proxy_url {
+            builder = builder.proxy(proxy(url)?);
+        }
+        Ok(builder)
+    }
+}
+
+fn proxy(url: &str) -> Result<reqwest::Proxy, AppError> {
+    reqwest::Proxy::all(url)
+        .map_err(|e| AppError::Validation(format!("Invalid proxy URL {:?}: {}", url, e)))
+}
+
+/// Check a proxy URL before it is saved (empty means no proxy)
+pub fn validate_proxy_url(url: &str) -> Result<(), AppError> {
+    if url.trim().is_empty() {
+        return Ok(());
+    }
+    proxy(url.trim()).map(|_| ())
+}
+
+/// Network settings as declared in the settings schema
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new(
+            OFFLINE_SETTING,
+            SettingType::Boolean,
+            false.into(),
+            "network",
+        )
+        .describe(
+            "Offline mode",
+            "Behave as if the network were disconnected; outbound requests fail right away",
+        ),
+        SettingDefinition::new(
+            "network.proxy_url",
+            SettingType::String,
+            "".into(),
+            "network",
+        )
+        .describe(
+            "Proxy",
+            "Proxy URL for outbound requests, e.g. http://proxy:3128 (empty: none)",
+        ),
+        SettingDefinition::new(
+            "network.timeout_secs",
+            SettingType::Number,
+            DEFAULT_TIMEOUT_SECS.into(),
+            "network",
+        )
+        .describe(
+            "Request timeout",
+            "Seconds before an outbound request is given up",
+        )
+        .range(Some(1.0), Some(600.0)),
+        SettingDefinition::new(
+            "network.user_agent",
+            SettingType::String,
+            "".into(),
+            "network",
+        )
+        .describe(
+            "User-Agent",
+            "User-Agent header of outbound requests (empty: Modulaur/<version>)",
+        ),
+    ]
+}
+
+/// Whether a setting key affects the network config
+pub fn is_network_setting(key: &str) -> bool {
+    key == OFFLINE_SETTING || key.starts_with("network.")
+}
+
+/// The network config in effect
+pub fn current() -> NetworkConfig {
+    NETWORK.read().unwrap().clone().unwrap_or_default()
+}
+
+/// Make a config the one in effect
+pub fn apply(config: NetworkConfig) {
+    *NETWORK.write().unwrap() = Some(config);
+}
+
+/// Re-read the network settings (at startup and after they change)
+pub async fn reload(settings: &SettingsService) -> Result<(), AppError> {
+    let config = NetworkConfig::load(settings).await?;
+    tracing::info!(
+        "Network settings: proxy {}, timeout {}s, offline {}",
+        config.proxy_url.as_deref().unwrap_or("none"),
+        config.timeout_secs,
+        config.offline
+    );
+    save_cached(&config);
+    apply(config);
+    Ok(())
+}
+
+/// Copy of the settings read at startup, before the database is up
+fn cache_path() -> Result<PathBuf, AppError> {
+    Ok(crate::workspaces::app_root()?.join("network.json"))
+}
+
+/// Apply the settings saved by the last run, if any
+pub fn load_cached() {
+    let Ok(path) = cache_path() else {
+        return;
+    };
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return;
+    };
+    match serde_json::from_str(&text) {
+        Ok(config) => apply(config),
+        Err(e) => tracing::warn!("Ignoring {:?}: {}", path, e),
+    }
+}
+
+fn save_cached(config: &NetworkConfig) {
+    let saved = cache_path().and_then(|path| {
+        let text = serde_json::to_string_pretty(config)?;
+        std::fs::write(path, text)?;
+        Ok(())
+    });
+    if let Err(e) = saved {
+        tracing::warn!("Failed to save the network settings: {}", e);
+    }
+}
+
+/// Error out when offline mode is on
+pub fn ensure_online() -> Result<(), AppError> {
+    if current().offline {
+        return Err(AppError::Http {
+            failure: HttpFailure::Offline,
+            message: OFFLINE_MESSAGE.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// HTTP client following the network settings
+/// A proxy that can't be used (only possible when set behind the app's back)
+/// is left out rather than making every request fail.
+pub fn client() -> reqwest::Client {
+    let config = current();
+    let builder = config.client_builder().unwrap_or_else(|e| {
+        tracing::warn!("Ignoring proxy: {}", e);
+        NetworkConfig {
+            proxy_url: None,
+            ..config
+        }
+        .client_builder()
+        .expect("client without proxy")
+    });
+    builder.build().expect("Failed to create HTTP client")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_network_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let settings = SettingsService::new(Arc::new(Mutex::new(database)));
+
+        assert_eq!(
+            NetworkConfig::load(&settings).await.unwrap(),
+            NetworkConfig::default()
+        );
+
+        settings
+            .set_value("network.proxy_url", json!(" http://proxy:3128 "))
+            .await
+            .unwrap();
+        settings
+            .set_value("network.timeout_secs", json!(5))
+            .await
+            .unwrap();
+        settings
+            .set_value("network.user_agent", json!("ops-dashboard"))
+            .await
+            .unwrap();
+        settings
+            .set_value(OFFLINE_SETTING, json!(true))
+            .await
+            .unwrap();
+        assert!(settings
+            .set_value("network.timeout_secs", json!(0))
+            .await
+            .is_err());
+
+        let config = NetworkConfig::load(&settings).await.unwrap();
+        assert_eq!(
+            config,
+            NetworkConfig {
+                proxy_url: Some("http://proxy:3128".to_string()),
+                timeout_secs: 5,
+                user_agent: "ops-dashboard".to_string(),
+                offline: true,
+            }
+        );
+        assert!(config.client_builder().unwrap().build().is_ok());
+
+        assert!(validate_proxy_url("").is_ok());
+        assert!(validate_proxy_url("http://proxy:3128").is_ok());
+        assert!(validate_proxy_url("not a url").is_err());
+        assert!(is_network_setting("network.proxy_url"));
+        assert!(is_network_setting(OFFLINE_SETTING));
+        assert!(!is_network_setting("theme"));
+    }
+}
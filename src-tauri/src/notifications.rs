@@ -0,0 +1,417 @@
+// Desktop notifications
+//
+// Everything the app tells the user outside of the view they're in goes
+// through `notify`: sources that started failing to fetch, tickets that became
+// due, fired alerts and quarantined plugins. Every notification is kept in the
+// notification center (the `notifications` table, read with
+// `get_notifications`) and announced to the frontend as a `notifications://new`
+// event. It's also shown as an OS notification unless the user turned that
+// off for its category (the `notifications.<category>` settings).
+//
+// Fetch failures and plugin quarantines are picked up from the event bus; a
+// source is reported when it starts failing, not on every failed fetch. Read
+// notifications are removed after `READ_RETENTION_DAYS`.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::events::AppEvent;
+use crate::i18n;
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+use surrealdb::sql::Thing;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::{mpsc, Mutex, RwLock};
+
+/// Event carrying a notification just added to the notification center
+pub const NEW_EVENT: &str = "notifications://new";
+
+/// Read notifications older than this are removed
+const READ_RETENTION_DAYS: i64 = 30;
+
+/// Notifications returned by `get_notifications` without a limit
+pub const DEFAULT_LIMIT: usize = 100;
+
+static NOTIFIER: OnceLock<mpsc::UnboundedSender<PendingNotification>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCategory {
+    FetchFailures,
+    DueTickets,
+    Alerts,
+    PluginQuarantines,
+}
+
+impl NotificationCategory {
+    pub const ALL: [NotificationCategory; 4] = [
+        NotificationCategory::FetchFailures,
+        NotificationCategory::DueTickets,
+        NotificationCategory::Alerts,
+        NotificationCategory::PluginQuarantines,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationCategory::FetchFailures => "fetch_failures",
+            NotificationCategory::DueTickets => "due_tickets",
+            NotificationCategory::Alerts => "alerts",
+            NotificationCategory::PluginQuarantines => "plugin_quarantines",
+        }
+    }
+
+    /// Setting turning the OS notifications of the category on or off
+    pub fn setting_key(self) -> String {
+        format!("notifications.{}", self.as_str())
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            NotificationCategory::FetchFailures => "Fetch failures",
+            NotificationCategory::DueTickets => "Due tickets",
+            NotificationCategory::Alerts => "Alerts",
+            NotificationCategory::PluginQuarantines => "Plugin quarantines",
+        }
+    }
+}
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    NotificationCategory::ALL
+        .into_iter()
+        .map(|category| {
+            SettingDefinition::new(
+                &category.setting_key(),
+                SettingType::Boolean,
+                true.into(),
+                "notifications",
+            )
+            .describe(
+                category.label(),
+                "Show desktop notifications (they're kept in the notification center either way)",
+            )
+        })
+        .collect()
+}
+
+// ============================================================================
+// Notification Center
+// ============================================================================
+
+/// Notification as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NotificationRecord {
+    pub id: Thing,
+    pub category: NotificationCategory,
+    pub title: String,
+    pub body: String,
+    pub read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// User-facing notification with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: String,
+    pub category: NotificationCategory,
+    pub title: String,
+    pub body: String,
+    pub read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<NotificationRecord> for Notification {
+    fn from(record: NotificationRecord) -> Self {
+        Notification {
+            id: record.id.to_string(),
+            category: record.category,
+            title: record.title,
+            body: record.body,
+            read: record.read,
+            created_at: record.created_at,
+        }
+    }
+}
+
+/// Record key of a notification id ("notifications:abc" or "abc")
+fn notification_key(id: &str) -> &str {
+    id.strip_prefix("notifications:")
+        .unwrap_or(id)
+        .trim_start_matches('⟨')
+        .trim_end_matches('⟩')
+}
+
+impl Database {
+    /// Add a notification to the notification center, dropping read
+    /// notifications past the retention
+    pub async fn add_notification(
+        &self,
+        category: NotificationCategory,
+        title: &str,
+        body: &str,
+    ) -> Result<Notification, AppError> {
+        let now = Utc::now();
+        let mut result = self
+            .query("DELETE notifications WHERE read = true AND created_at < $cutoff")
+            .query("CREATE notifications CONTENT $notification")
+            .bind(("cutoff", now - Duration::days(READ_RETENTION_DAYS)))
+            .bind((
+                "notification",
+                serde_json::json!({
+                    "category": category,
+                    "title": title,
+                    "body": body,
+                    "read": false,
+                    "created_at": now,
+                }),
+            ))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to add notification: {}", e)))?;
+
+        let created: Option<NotificationRecord> = result.take(1).map_err(|e| {
+            AppError::Database(format!("Failed to parse created notification: {}", e))
+        })?;
+        created
+            .map(Notification::from)
+            .ok_or_else(|| AppError::Database("Failed to add notification".to_string()))
+    }
+
+    /// Notifications, newest first
+    pub async fn get_notifications(
+        &self,
+        unread_only: bool,
+        limit: usize,
+    ) -> Result<Vec<Notification>, AppError> {
+        let filter = if unread_only {
+            "WHERE read = false"
+        } else {
+            ""
+        };
+        let mut result = self
+            .query(format!(
+                "SELECT * FROM notifications {} ORDER BY created_at DESC LIMIT $limit",
+                filter
+            ))
+            .bind(("limit", limit))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get notifications: {}", e)))?;
+
+        let notifications: Vec<NotificationRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse notifications: {}", e)))?;
+        Ok(notifications.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn mark_notification_read(&self, id: &str) -> Result<Notification, AppError> {
+        let key = notification_key(id);
+        let existing: Option<NotificationRecord> = self
+            .db
+            .select(("notifications", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get notification: {}", e)))?;
+        let mut record = existing
+            .ok_or_else(|| AppError::NotFound(format!("Notification not found: {}", id)))?;
+        record.read = true;
+
+        let updated: Option<NotificationRecord> = self
+            .db
+            .upsert(("notifications", key))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update notification: {}", e)))?;
+        updated
+            .map(Notification::from)
+            .ok_or_else(|| AppError::NotFound(format!("Notification not found: {}", id)))
+    }
+}
+
+// ============================================================================
+// Notifier
+// ============================================================================
+
+#[derive(Debug)]
+struct PendingNotification {
+    category: NotificationCategory,
+    title: String,
+    body: String,
+}
+
+/// Notify the user; dropped until the notifier is started
+pub fn notify(category: NotificationCategory, title: impl Into<String>, body: impl Into<String>) {
+    if let Some(notifier) = NOTIFIER.get() {
+        let _ = notifier.send(PendingNotification {
+            category,
+            title: title.into(),
+            body: body.into(),
+        });
+    }
+}
+
+/// The notification for a bus event, if it warrants one; `failing` holds the
+/// sources whose last fetch failed
+fn notification_for(event: AppEvent, failing: &mut HashSet<String>) -> Option<PendingNotification> {
+    match event {
+        AppEvent::FetchFinished(summary) => match summary.error {
+            Some(error) => failing
+                .insert(summary.source.clone())
+                .then(|| PendingNotification {
+                    category: NotificationCategory::FetchFailures,
+                    title: i18n::format(
+                        "notification.fetch_failed",
+                        &[("source", &summary.source)],
+                    ),
+                    body: error,
+                }),
+            None => {
+                failing.remove(&summary.source);
+                None
+            }
+        },
+        AppEvent::PluginQuarantined { name, reason } => Some(PendingNotification {
+            category: NotificationCategory::PluginQuarantines,
+            title: i18n::format("notification.plugin_quarantined", &[("name", &name)]),
+            body: reason,
+        }),
+        _ => None,
+    }
+}
+
+async fn deliver(
+    app: &AppHandle,
+    database: &Arc<Mutex<Database>>,
+    settings: &Arc<RwLock<SettingsService>>,
+    pending: PendingNotification,
+) -> Result<(), AppError> {
+    let notification = database
+        .lock()
+        .await
+        .add_notification(pending.category, &pending.title, &pending.body)
+        .await?;
+    if let Err(e) = app.emit(NEW_EVENT, &notification) {
+        tracing::warn!("Failed to emit notification: {}", e);
+    }
+
+    let desktop = settings
+        .read()
+        .await
+        .get_value(&pending.category.setting_key())
+        .await?
+        .as_bool()
+        .unwrap_or(true);
+    if desktop {
+        app.notification()
+            .builder()
+            .title(&notification.title)
+            .body(&notification.body)
+            .show()
+            .map_err(|e| AppError::Config(format!("Failed to show notification: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Deliver notifications in the background; `events` is a bus subscription
+/// taken early enough not to miss startup events
+pub fn start(
+    app: AppHandle,
+    database: Arc<Mutex<Database>>,
+    settings: Arc<RwLock<SettingsService>>,
+    mut events: broadcast::Receiver<AppEvent>,
+) {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    if NOTIFIER.set(sender).is_err() {
+        tracing::warn!("Notifier already started");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut failing = HashSet::new();
+
+        loop {
+            let pending = tokio::select! {
+                Some(pending) = receiver.recv() => pending,
+                event = events.recv() => match event {
+                    Ok(event) => match notification_for(event, &mut failing) {
+                        Some(pending) => pending,
+                        None => continue,
+                    },
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            if let Err(e) = deliver(&app, &database, &settings, pending).await {
+                tracing::warn!("Failed to deliver notification: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::FetchSummary;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_notification_center() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let alert = db
+            .add_notification(NotificationCategory::Alerts, "Failed pipelines", "3 > 2")
+            .await
+            .unwrap();
+        db.add_notification(NotificationCategory::DueTickets, "Fix login", "Overdue")
+            .await
+            .unwrap();
+
+        let all = db.get_notifications(false, DEFAULT_LIMIT).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].title, "Fix login");
+        assert!(all.iter().all(|n| !n.read));
+
+        let read = db.mark_notification_read(&alert.id).await.unwrap();
+        assert!(read.read);
+        let unread = db.get_notifications(true, DEFAULT_LIMIT).await.unwrap();
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].category, NotificationCategory::DueTickets);
+        assert_eq!(db.get_notifications(false, 1).await.unwrap().len(), 1);
+
+        assert!(matches!(
+            db.mark_notification_read("notifications:missing").await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_failures_notify_once() {
+        let summary = |error: Option<&str>| {
+            AppEvent::FetchFinished(FetchSummary {
+                source: "gitlab".to_string(),
+                adapter_type: "gitlab".to_string(),
+                records: 0,
+                duration_ms: 10,
+                error: error.map(str::to_string),
+            })
+        };
+        let mut failing = HashSet::new();
+
+        let first = notification_for(summary(Some("401 Unauthorized")), &mut failing).unwrap();
+        assert_eq!(first.category, NotificationCategory::FetchFailures);
+        assert_eq!(first.body, "401 Unauthorized");
+        assert!(notification_for(summary(Some("401 Unauthorized")), &mut failing).is_none());
+
+        // Failing again after a successful fetch is reported again
+        assert!(notification_for(summary(None), &mut failing).is_none());
+        assert!(notification_for(summary(Some("timeout")), &mut failing).is_some());
+
+        assert_eq!(
+            NotificationCategory::PluginQuarantines.setting_key(),
+            "notifications.plugin_quarantines"
+        );
+        assert_eq!(settings_schema().len(), NotificationCategory::ALL.len());
+    }
+}
@@ -0,0 +1,269 @@
+// Onboarding
+//
+// A fresh install opens on empty tables. `seed_starter_workspace` fills the
+// open workspace with a working example in one step: an RSS data source, a
+// demo kanban board with a few tickets, a dashboard showing both, and the
+// example prompt packages. Seeding again replaces the dashboard and the data
+// source instead of duplicating them.
+//
+// The starter workspace is offered on the first run: while the
+// `onboarding.completed` setting is unset and the workspace has neither
+// dashboards nor data sources. Seeding or declining sets the setting.
+
+use crate::data_sources::{DataSource, DataSourceService};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::i18n;
+use crate::models::{Dashboard, Panel};
+use crate::prompt_gen::commands::{seed_examples, seed_text2image_common};
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use crate::tickets::{CreateTicketRequest, Priority, TicketType};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+
+/// Setting marking the starter workspace as created or declined
+pub const COMPLETED_SETTING: &str = "onboarding.completed";
+
+pub const STARTER_DASHBOARD_ID: &str = "dashboard_starter";
+pub const STARTER_SOURCE_ID: &str = "ds_starter_rss";
+
+/// Source name of the records fetched by the starter data source
+pub const STARTER_SOURCE: &str = "starter-rss";
+
+/// Board of the demo tickets (the id of its kanban panel)
+pub const STARTER_BOARD: &str = "starter-board";
+
+const STARTER_FEED: &str = "https://blog.rust-lang.org/feed.xml";
+const STARTER_KEY_PREFIX: &str = "DEMO";
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![SettingDefinition::new(
+        COMPLETED_SETTING,
+        SettingType::Boolean,
+        false.into(),
+        "onboarding",
+    )
+    .describe(
+        "Onboarding completed",
+        "Set once the starter workspace was created or declined; unset it to be offered it again",
+    )]
+}
+
+/// What `seed` created
+#[derive(Debug, Serialize)]
+pub struct StarterWorkspace {
+    pub dashboard_id: String,
+    pub data_source_id: String,
+    pub board_id: String,
+    /// Demo tickets created (none if the board already had tickets)
+    pub tickets: usize,
+    /// Result messages of the prompt package seeds
+    pub prompt_packages: Vec<String>,
+}
+
+/// Whether to offer the starter workspace
+pub async fn is_first_run(
+    settings: &SettingsService,
+    db: &Database,
+    data_sources: &DataSourceService,
+) -> Result<bool, AppError> {
+    if settings
+        .get_value(COMPLETED_SETTING)
+        .await?
+        .as_bool()
+        .unwrap_or(false)
+    {
+        return Ok(false);
+    }
+    Ok(db.get_dashboards().await?.is_empty()
+        && data_sources.get_all_data_sources().await?.is_empty())
+}
+
+/// RSS data source feeding the starter dashboard's table
+pub fn starter_data_source() -> DataSource {
+    let now = Utc::now();
+    DataSource {
+        id: STARTER_SOURCE_ID.to_string(),
+        name: i18n::text("seed.starter.source").to_string(),
+        adapter_type: "rss".to_string(),
+        source: STARTER_SOURCE.to_string(),
+        endpoint: STARTER_FEED.to_string(),
+        auth_type: None,
+        auth_credential_key: None,
+        parameters: json!({ "max_items": 20 }),
+        environment: "both".to_string(),
+        enabled: true,
+        auto_refresh: true,
+        refresh_interval: Some(3600),
+        data_ttl_days: 30,
+        pipeline: None,
+        unique_key: None,
+        redaction: None,
+        last_fetch: None,
+        last_fetch_count: None,
+        total_records: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Dashboard with a welcome note, the feed table and the demo board
+pub fn starter_dashboard() -> Dashboard {
+    let panel = |i: &str, (x, y, w, h), panel_type: &str, title: &str, config| Panel {
+        i: i.to_string(),
+        x,
+        y,
+        w,
+        h,
+        panel_type: panel_type.to_string(),
+        title: title.to_string(),
+        config,
+    };
+    Dashboard {
+        id: STARTER_DASHBOARD_ID.to_string(),
+        panels: vec![
+            panel(
+                "starter-welcome",
+                (0, 0, 4, 6),
+                "text",
+                i18n::text("seed.starter.welcome_title"),
+                json!({ "content": i18n::text("seed.starter.welcome") }),
+            ),
+            panel(
+                "starter-feed",
+                (4, 0, 8, 6),
+                "table",
+                i18n::text("seed.starter.feed_title"),
+                json!({
+                    "dataSource": STARTER_SOURCE,
+                    "recordType": "rss_item",
+                    "pageSize": 10,
+                }),
+            ),
+            panel(
+                STARTER_BOARD,
+                (0, 6, 12, 8),
+                "ticket-kanban",
+                i18n::text("seed.starter.board_title"),
+                json!({}),
+            ),
+        ],
+        ..Dashboard::new(i18n::text("seed.starter.dashboard").to_string())
+    }
+}
+
+/// Demo tickets with the column each is moved to
+fn starter_tickets() -> Vec<(CreateTicketRequest, &'static str)> {
+    let ticket = |title: &'static str, ticket_type, priority| CreateTicketRequest {
+        title: i18n::text(title).to_string(),
+        description: None,
+        ticket_type,
+        priority: Some(priority),
+        assignee: None,
+        tags: Some(vec!["demo".to_string()]),
+        estimate: None,
+        due_date: None,
+        metadata: Some(json!({ "board_id": STARTER_BOARD })),
+    };
+    vec![
+        (
+            ticket(
+                "seed.starter.ticket.workspace",
+                TicketType::Task,
+                Priority::Medium,
+            ),
+            "done",
+        ),
+        (
+            ticket(
+                "seed.starter.ticket.dashboard",
+                TicketType::Task,
+                Priority::High,
+            ),
+            "in-progress",
+        ),
+        (
+            ticket(
+                "seed.starter.ticket.source",
+                TicketType::Feature,
+                Priority::Medium,
+            ),
+            "todo",
+        ),
+        (
+            ticket(
+                "seed.starter.ticket.prompts",
+                TicketType::Story,
+                Priority::Low,
+            ),
+            "backlog",
+        ),
+    ]
+}
+
+/// Create the starter workspace and mark onboarding as completed
+pub async fn seed(
+    settings: &SettingsService,
+    db: &Database,
+    data_sources: &DataSourceService,
+) -> Result<StarterWorkspace, AppError> {
+    let source = starter_data_source();
+    data_sources.save_data_source(&source).await?;
+
+    db.set_board_key_prefix(STARTER_BOARD, STARTER_KEY_PREFIX)
+        .await?;
+    let mut tickets = 0;
+    let existing: usize = db.board_status_counts(STARTER_BOARD).await?.values().sum();
+    if existing == 0 {
+        for (request, status) in starter_tickets() {
+            let ticket = db.create_ticket(request).await?;
+            db.move_ticket(&ticket.id, status).await?;
+            tickets += 1;
+        }
+    }
+
+    let dashboard = starter_dashboard();
+    db.save_dashboard(&dashboard).await?;
+
+    let prompt_packages = vec![seed_examples(db).await?, seed_text2image_common(db).await?];
+
+    settings.set_value(COMPLETED_SETTING, json!(true)).await?;
+    tracing::info!("Created the starter workspace");
+
+    Ok(StarterWorkspace {
+        dashboard_id: dashboard.id,
+        data_source_id: source.id,
+        board_id: STARTER_BOARD.to_string(),
+        tickets,
+        prompt_packages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starter_content() {
+        let dashboard = starter_dashboard();
+        assert_eq!(dashboard.id, STARTER_DASHBOARD_ID);
+        let table = &dashboard.panels[1];
+        assert_eq!(table.config["dataSource"], starter_data_source().source);
+        assert_eq!(table.config["recordType"], "rss_item");
+        // The ticket kanban panel shows the board named by its id
+        assert!(dashboard
+            .panels
+            .iter()
+            .any(|p| p.panel_type == "ticket-kanban" && p.i == STARTER_BOARD));
+
+        let columns: Vec<String> = crate::boards::default_columns()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        for (request, status) in starter_tickets() {
+            assert!(columns.iter().any(|c| c == status));
+            assert_eq!(request.metadata.unwrap()["board_id"], STARTER_BOARD);
+        }
+    }
+}
@@ -0,0 +1,420 @@
+// Page blocks
+//
+// Page content is an ordered list of typed blocks in `page_blocks` rather than
+// one blob in the page config: an editor saves just the block that changed,
+// and other content can link to a block by its id. Each block belongs to a
+// page (by the page's bare key) and has a position among that page's blocks;
+// inserting, moving and deleting renumber the positions so they stay 0..n.
+//
+// A page's blocks are deleted along with the page.
+
+use crate::db::query::RecordQuery;
+use crate::db::Database;
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use surrealdb::sql::Thing;
+
+// ============================================================================
+// Block Model
+// ============================================================================
+
+/// What a block shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlockContent {
+    /// Markdown text
+    Text { text: String },
+
+    /// A dashboard widget, configured like a panel of that type
+    Widget {
+        panel_type: String,
+        #[serde(default)]
+        config: Value,
+    },
+
+    /// Records matching a query
+    RecordQuery {
+        query: RecordQuery,
+        /// How the records are shown, e.g. "table" or "list"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        view: Option<String>,
+    },
+
+    /// A prompt section rendered with fixed variables
+    PromptSection {
+        section_id: String,
+        #[serde(default)]
+        variables: Map<String, Value>,
+        /// Seed for a stable rendering; a new one per render if None
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        seed: Option<u64>,
+    },
+}
+
+impl BlockContent {
+    fn validate(&self) -> Result<(), AppError> {
+        let missing = match self {
+            BlockContent::Text { .. } | BlockContent::RecordQuery { .. } => None,
+            BlockContent::Widget { panel_type, .. } => {
+                panel_type.trim().is_empty().then_some("panel_type")
+            }
+            BlockContent::PromptSection { section_id, .. } => {
+                section_id.trim().is_empty().then_some("section_id")
+            }
+        };
+        match missing {
+            Some(field) => Err(AppError::Validation(format!(
+                "Block {} must not be empty",
+                field
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Block as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PageBlockRecord {
+    id: Thing,
+    page: String,
+    position: usize,
+    content: BlockContent,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// User-facing block with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageBlock {
+    pub id: String,
+    /// Key of the page the block belongs to
+    pub page_id: String,
+    pub position: usize,
+    pub content: BlockContent,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<PageBlockRecord> for PageBlock {
+    fn from(record: PageBlockRecord) -> Self {
+        PageBlock {
+            id: record.id.id.to_raw(),
+            page_id: record.page,
+            position: record.position,
+            content: record.content,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+fn block_key(id: &str) -> &str {
+    id.strip_prefix("page_blocks:").unwrap_or(id)
+}
+
+fn page_key(id: &str) -> &str {
+    id.strip_prefix("pages:").unwrap_or(id)
+}
+
+/// Block keys in order with `key` placed at `index` (the end if None)
+fn insert_at(mut keys: Vec<String>, key: &str, index: Option<usize>) -> Vec<String> {
+    keys.retain(|k| k != key);
+    let index = index.unwrap_or(keys.len()).min(keys.len());
+    keys.insert(index, key.to_string());
+    keys
+}
+
+// ============================================================================
+// Block Operations
+// ============================================================================
+
+impl Database {
+    /// Blocks of a page in order
+    pub async fn get_page_blocks(&self, page_id: &str) -> Result<Vec<PageBlock>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM page_blocks WHERE page = $page ORDER BY position ASC")
+            .bind(("page", page_key(page_id).to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query page blocks: {}", e)))?;
+
+        let records: Vec<PageBlockRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse page blocks: {}", e)))?;
+        Ok(records.into_iter().map(PageBlock::from).collect())
+    }
+
+    pub async fn get_page_block(&self, id: &str) -> Result<PageBlock, AppError> {
+        let record: Option<PageBlockRecord> = self
+            .db
+            .select(("page_blocks", block_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get page block: {}", e)))?;
+
+        record
+            .map(PageBlock::from)
+            .ok_or_else(|| AppError::NotFound(format!("Page block {}", id)))
+    }
+
+    /// Add a block to a page at `index` (the end if None)
+    pub async fn insert_page_block(
+        &self,
+        page_id: &str,
+        content: BlockContent,
+        index: Option<usize>,
+    ) -> Result<PageBlock, AppError> {
+        content.validate()?;
+        let page = page_key(page_id);
+        self.ensure_page_exists(page).await?;
+
+        let key = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let record = PageBlockRecord {
+            id: Thing::from(("page_blocks", key.as_str())),
+            page: page.to_string(),
+            position: 0,
+            content,
+            created_at: now,
+            updated_at: now,
+        };
+        let _: Option<PageBlockRecord> = self
+            .db
+            .create(("page_blocks", key.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create page block: {}", e)))?;
+
+        let keys = self.page_block_keys(page).await?;
+        self.set_block_positions(page, &insert_at(keys, &key, index))
+            .await?;
+        self.get_page_block(&key).await
+    }
+
+    /// Replace a block's content
+    pub async fn update_page_block(
+        &self,
+        id: &str,
+        content: BlockContent,
+    ) -> Result<PageBlock, AppError> {
+        content.validate()?;
+        // SET rather than MERGE so fields of the old block type don't linger
+        let mut result = self
+            .query(
+                "UPDATE type::thing('page_blocks', $key)
+                 SET content = $content, updated_at = $now RETURN AFTER",
+            )
+            .bind(("key", block_key(id).to_string()))
+            .bind(("content", content))
+            .bind(("now", Utc::now()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update page block: {}", e)))?;
+
+        let updated: Vec<PageBlockRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to update page block: {}", e)))?;
+        updated
+            .into_iter()
+            .next()
+            .map(PageBlock::from)
+            .ok_or_else(|| AppError::NotFound(format!("Page block {}", id)))
+    }
+
+    /// Move a block to `index` on its page, or on another page if `page_id`
+    /// is given; returns the blocks of the page it ends up on
+    pub async fn move_page_block(
+        &self,
+        id: &str,
+        index: usize,
+        page_id: Option<&str>,
+    ) -> Result<Vec<PageBlock>, AppError> {
+        let block = self.get_page_block(id).await?;
+        let page = page_id.map(page_key).unwrap_or(&block.page_id).to_string();
+
+        if page != block.page_id {
+            self.ensure_page_exists(&page).await?;
+            self.query(
+                "UPDATE type::thing('page_blocks', $key) SET page = $page, updated_at = $now",
+            )
+            .bind(("key", block.id.clone()))
+            .bind(("page", page.clone()))
+            .bind(("now", Utc::now()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to move page block: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to move page block: {}", e)))?;
+            // Close the gap on the old page
+            let old_keys = self.page_block_keys(&block.page_id).await?;
+            self.set_block_positions(&block.page_id, &old_keys).await?;
+        }
+
+        let keys = self.page_block_keys(&page).await?;
+        self.set_block_positions(&page, &insert_at(keys, &block.id, Some(index)))
+            .await?;
+        self.get_page_blocks(&page).await
+    }
+
+    pub async fn delete_page_block(&self, id: &str) -> Result<(), AppError> {
+        let block = self.get_page_block(id).await?;
+        let _: Option<PageBlockRecord> =
+            self.db
+                .delete(("page_blocks", block.id.as_str()))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to delete page block: {}", e)))?;
+
+        let keys = self.page_block_keys(&block.page_id).await?;
+        self.set_block_positions(&block.page_id, &keys).await
+    }
+
+    /// Delete all blocks of a page
+    pub async fn delete_page_blocks(&self, page_id: &str) -> Result<(), AppError> {
+        self.query("DELETE page_blocks WHERE page = $page")
+            .bind(("page", page_key(page_id).to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete page blocks: {}", e)))?
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to delete page blocks: {}", e)))?;
+        Ok(())
+    }
+
+    async fn ensure_page_exists(&self, page: &str) -> Result<(), AppError> {
+        let mut result = self
+            .query("SELECT VALUE id FROM type::thing('pages', $page)")
+            .bind(("page", page.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query pages: {}", e)))?;
+        let found: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to query pages: {}", e)))?;
+        if found.is_empty() {
+            return Err(AppError::NotFound(format!("Page {}", page)));
+        }
+        Ok(())
+    }
+
+    /// Keys of a page's blocks in their current order
+    async fn page_block_keys(&self, page: &str) -> Result<Vec<String>, AppError> {
+        let mut result = self
+            .query(
+                "SELECT id, position, created_at FROM page_blocks WHERE page = $page
+                 ORDER BY position ASC, created_at ASC",
+            )
+            .bind(("page", page.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query page blocks: {}", e)))?;
+
+        #[derive(Deserialize)]
+        struct Entry {
+            id: Thing,
+        }
+        let entries: Vec<Entry> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse page blocks: {}", e)))?;
+        Ok(entries.into_iter().map(|e| e.id.id.to_raw()).collect())
+    }
+
+    /// Number a page's blocks in the given order
+    async fn set_block_positions(&self, page: &str, keys: &[String]) -> Result<(), AppError> {
+        let order: Vec<Value> = keys
+            .iter()
+            .enumerate()
+            .map(|(position, key)| serde_json::json!({ "key": key, "position": position }))
+            .collect();
+        self.query(
+            "FOR $entry IN $order {
+                    UPDATE type::thing('page_blocks', $entry.key)
+                        SET position = $entry.position WHERE page = $page;
+                }",
+        )
+        .bind(("order", order))
+        .bind(("page", page.to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to order page blocks: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to order page blocks: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn text(text: &str) -> BlockContent {
+        BlockContent::Text {
+            text: text.to_string(),
+        }
+    }
+
+    async fn texts(db: &Database, page: &str) -> Vec<String> {
+        db.get_page_blocks(page)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|block| match block.content {
+                BlockContent::Text { text } => text,
+                other => panic!("unexpected block {:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_page_blocks() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        for page in ["notes", "other"] {
+            db.query("CREATE type::thing('pages', $page) SET name = $page")
+                .bind(("page", page))
+                .await
+                .unwrap()
+                .check()
+                .unwrap();
+        }
+
+        let first = db
+            .insert_page_block("notes", text("a"), None)
+            .await
+            .unwrap();
+        db.insert_page_block("pages:notes", text("c"), None)
+            .await
+            .unwrap();
+        let b = db
+            .insert_page_block("notes", text("b"), Some(1))
+            .await
+            .unwrap();
+        assert_eq!(texts(&db, "notes").await, ["a", "b", "c"]);
+        assert!(db
+            .insert_page_block("missing", text("x"), None)
+            .await
+            .is_err());
+
+        // Changing the block type replaces the content entirely
+        let widget = BlockContent::Widget {
+            panel_type: "chart".to_string(),
+            config: serde_json::json!({ "metric": "duration" }),
+        };
+        let updated = db
+            .update_page_block(&first.id, widget.clone())
+            .await
+            .unwrap();
+        assert_eq!(updated.content, widget);
+        db.update_page_block(&first.id, text("a")).await.unwrap();
+
+        db.move_page_block(&first.id, 5, None).await.unwrap();
+        assert_eq!(texts(&db, "notes").await, ["b", "c", "a"]);
+        let moved = db.move_page_block(&b.id, 0, Some("other")).await.unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(texts(&db, "notes").await, ["c", "a"]);
+
+        db.delete_page_block(&format!("page_blocks:{}", first.id))
+            .await
+            .unwrap();
+        let blocks = db.get_page_blocks("notes").await.unwrap();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].position, 0);
+
+        db.delete_page_blocks("other").await.unwrap();
+        assert!(db.get_page_blocks("other").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+// Page export
+//
+// Turns a page's blocks into a Markdown document or a standalone HTML file so
+// notes kept in Modulaur can be read and kept elsewhere. Text blocks are
+// already Markdown; record query blocks are run and their records inlined as
+// tables, prompt section blocks are rendered, and widgets (which only exist in
+// the app) are listed by type. HTML is the Markdown document converted with
+// pulldown-cmark, wrapped in a page with a small stylesheet.
+
+use crate::db::{Database, StagedRecord};
+use crate::error::AppError;
+use crate::page_blocks::BlockContent;
+use crate::pages::Page;
+use crate::prompt_gen::renderer::{render_section, PromptLibrary};
+use pulldown_cmark::{html, Options, Parser};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Records inlined per record query block
+const MAX_TABLE_ROWS: usize = 500;
+
+/// `data` fields shown next to the date, source and type columns
+const MAX_DATA_COLUMNS: usize = 6;
+
+const STYLESHEET: &str = "
+body { font-family: system-ui, sans-serif; line-height: 1.5; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+table { border-collapse: collapse; margin: 1rem 0; font-size: 0.9rem; }
+th, td { border: 1px solid #ccc; padding: 0.25rem 0.5rem; text-align: left; }
+th { background: #f3f3f3; }
+pre { background: #f6f6f6; padding: 0.75rem; overflow-x: auto; white-space: pre-wrap; }
+blockquote { color: #666; border-left: 3px solid #ccc; margin-left: 0; padding-left: 1rem; }
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageExportFormat {
+    Markdown,
+    Html,
+}
+
+/// Export the page with this id as a Markdown or HTML document
+pub async fn export_page(
+    db: &Database,
+    id: &str,
+    format: PageExportFormat,
+) -> Result<String, AppError> {
+    let key = id.strip_prefix("pages:").unwrap_or(id);
+    let page: Page = db
+        .db
+        .select(("pages", key))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get page: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Page {}", id)))?;
+
+    let blocks = db.get_page_blocks(key).await?;
+    let library = match blocks
+        .iter()
+        .any(|block| matches!(block.content, BlockContent::PromptSection { .. }))
+    {
+        true => PromptLibrary::load(db).await?,
+        false => PromptLibrary::default(),
+    };
+
+    let mut sections = vec![format!("# {}", page.name)];
+    for block in blocks {
+        sections.push(match block.content {
+            BlockContent::Text { text } => text,
+            BlockContent::Widget { panel_type, config } => {
+                match config["title"].as_str().filter(|t| !t.is_empty()) {
+                    Some(title) => format!("> {} ({} widget)", title, panel_type),
+                    None => format!("> {} widget", panel_type),
+                }
+            }
+            BlockContent::RecordQuery { mut query, .. } => {
+                query.limit = Some(query.limit.unwrap_or(MAX_TABLE_ROWS).min(MAX_TABLE_ROWS));
+                let page = db.query_records(query).await?;
+                records_table(&page.records, page.total)
+            }
+            BlockContent::PromptSection {
+                section_id,
+                variables,
+                seed,
+            } => match render_section(&library, &section_id, variables, seed) {
+                Ok(rendered) => format!("```\n{}\n```", rendered.text),
+                Err(e) => format!(
+                    "> Prompt section {} could not be rendered: {}",
+                    section_id, e
+                ),
+            },
+        });
+    }
+    let markdown = sections.join("\n\n") + "\n";
+
+    Ok(match format {
+        PageExportFormat::Markdown => markdown,
+        PageExportFormat::Html => to_html(&page.name, &markdown),
+    })
+}
+
+/// Text with what would break a Markdown table row escaped
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn value_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => cell(s),
+        other => cell(&other.to_string()),
+    }
+}
+
+/// Records as a Markdown table: date, source and type, then the scalar
+/// `data` fields the records have, in order of first appearance
+fn records_table(records: &[StagedRecord], total: usize) -> String {
+    if records.is_empty() {
+        return "_No matching records_".to_string();
+    }
+    let mut fields: Vec<&str> = Vec::new();
+    for record in records {
+        if let Value::Object(data) = &record.data {
+            for (key, value) in data {
+                if !value.is_object() && !value.is_array() && !fields.contains(&key.as_str()) {
+                    fields.push(key);
+                }
+            }
+        }
+    }
+    fields.truncate(MAX_DATA_COLUMNS);
+
+    let mut header = vec!["date", "source", "type"];
+    header.extend(&fields);
+    let mut lines = vec![
+        format!("| {} |", header.join(" | ")),
+        format!("|{}", "---|".repeat(header.len())),
+    ];
+    for record in records {
+        let mut row = vec![
+            record.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+            cell(&record.source),
+            cell(&record.record_type),
+        ];
+        row.extend(fields.iter().map(|field| value_cell(&record.data[*field])));
+        lines.push(format!("| {} |", row.join(" | ")));
+    }
+    if total > records.len() {
+        lines.push(String::new());
+        lines.push(format!("_{} of {} records_", records.len(), total));
+    }
+    lines.join("\n")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A standalone HTML document from Markdown
+fn to_html(title: &str, markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut body = String::new();
+    html::push_html(&mut body, Parser::new_ext(markdown, options));
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        STYLESHEET,
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::RecordMetadata;
+    use serde_json::json;
+
+    #[test]
+    fn test_records_table_to_html() {
+        let record = |data: Value| StagedRecord {
+            id: None,
+            record_type: "pipeline".to_string(),
+            source: "gitlab".to_string(),
+            timestamp: "2024-03-05T10:00:00Z".parse().unwrap(),
+            data,
+            metadata: RecordMetadata {
+                tags: vec![],
+                status: None,
+                title: None,
+                description: None,
+            },
+        };
+        let records = vec![
+            record(json!({ "name": "Deploy | prod", "stages": ["build"] })),
+            record(json!({ "name": "Test", "duration": 42 })),
+        ];
+
+        let table = records_table(&records, 3);
+        assert_eq!(
+            table,
+            "| date | source | type | name | duration |\n\
+             |---|---|---|---|---|\n\
+             | 2024-03-05 10:00 | gitlab | pipeline | Deploy \\| prod |  |\n\
+             | 2024-03-05 10:00 | gitlab | pipeline | Test | 42 |\n\
+             \n\
+             _2 of 3 records_"
+        );
+
+        let html = to_html("Notes <draft>", &format!("# Notes\n\n{}\n", table));
+        assert!(html.contains("<title>Notes &lt;draft&gt;</title>"));
+        assert!(html.contains("<th>duration</th>"));
+        assert!(html.contains("<td>Deploy | prod</td>"));
+    }
+}
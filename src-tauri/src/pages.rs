@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use surrealdb::sql::Thing;
 
+use crate::audit::Actor;
+use crate::db::Database;
+use crate::error::{ApiError, AppError};
 #[cfg(feature = "embedded-db")]
 use crate::AppState;
 
@@ -18,53 +22,198 @@ pub struct Page {
     pub config: Option<serde_json::Value>,
     pub order: i32,
     pub visible: bool,
+    /// Bare key of the parent page; None for top-level pages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl Page {
+    /// Bare key of the page, without the `pages:` prefix
+    pub fn key(&self) -> String {
+        self.id
+            .as_ref()
+            .map(|id| id.id.to_raw())
+            .unwrap_or_default()
+    }
+}
+
+/// A page with its child pages, as returned by `get_pages_tree`
+#[derive(Debug, Serialize, Clone)]
+pub struct PageNode {
+    #[serde(flatten)]
+    pub page: Page,
+    pub children: Vec<PageNode>,
+}
+
+/// What happens to the children of a deleted page
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeletePageMode {
+    /// Delete the child pages (and theirs) as well
+    Cascade,
+    /// Move the child pages up to the deleted page's parent
+    #[default]
+    Orphan,
+}
+
+fn page_key(id: &str) -> &str {
+    id.strip_prefix("pages:").unwrap_or(id)
+}
+
+async fn load_pages(db: &Database) -> Result<Vec<Page>, AppError> {
+    let mut result = db
+        .query("SELECT * FROM pages ORDER BY order ASC")
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query pages: {}", e)))?;
+
+    result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to extract pages: {}", e)))
+}
+
+/// Parent key of every page whose parent exists and isn't part of a cycle;
+/// other pages are treated as top-level pages
+fn effective_parents(pages: &[Page]) -> HashMap<String, String> {
+    let parents: HashMap<String, String> = pages
+        .iter()
+        .filter_map(|page| Some((page.key(), page.parent_id.clone()?)))
+        .collect();
+    let keys: HashSet<String> = pages.iter().map(Page::key).collect();
+
+    parents
+        .iter()
+        .filter(|(key, parent)| {
+            if !keys.contains(*parent) {
+                return false;
+            }
+            // Walk up the ancestors; coming back to the page means it's part
+            // of a cycle (a cycle further up only cuts the walk short)
+            let mut seen = HashSet::new();
+            let mut current = parent.as_str();
+            while current != key.as_str() {
+                match parents.get(current) {
+                    Some(next) if seen.insert(current) => current = next,
+                    _ => return true,
+                }
+            }
+            false
+        })
+        .map(|(key, parent)| (key.clone(), parent.clone()))
+        .collect()
+}
+
+/// Arrange pages into a tree, siblings sorted by order
+pub fn build_tree(pages: Vec<Page>) -> Vec<PageNode> {
+    let parents = effective_parents(&pages);
+    let mut children: HashMap<Option<String>, Vec<Page>> = HashMap::new();
+    for page in pages {
+        children
+            .entry(parents.get(&page.key()).cloned())
+            .or_default()
+            .push(page);
+    }
+
+    fn nodes(
+        parent: Option<String>,
+        children: &mut HashMap<Option<String>, Vec<Page>>,
+    ) -> Vec<PageNode> {
+        let mut pages = children.remove(&parent).unwrap_or_default();
+        pages.sort_by_key(|page| page.order);
+        pages
+            .into_iter()
+            .map(|page| {
+                let key = page.key();
+                PageNode {
+                    page,
+                    children: nodes(Some(key), children),
+                }
+            })
+            .collect()
+    }
+    nodes(None, &mut children)
+}
+
+/// Keys of all pages below this one
+fn descendants(pages: &[Page], key: &str) -> Vec<String> {
+    let parents = effective_parents(pages);
+    let mut found = Vec::new();
+    let mut pending = vec![key.to_string()];
+    while let Some(current) = pending.pop() {
+        for (child, parent) in &parents {
+            if *parent == current {
+                found.push(child.clone());
+                pending.push(child.clone());
+            }
+        }
+    }
+    found
+}
+
+/// Check that `parent` can be the parent of the page `key` (None for a new
+/// page) and return its bare key
+fn check_parent(pages: &[Page], key: Option<&str>, parent: &str) -> Result<String, AppError> {
+    let parent = page_key(parent);
+    if !pages.iter().any(|page| page.key() == parent) {
+        return Err(AppError::NotFound(format!(
+            "Parent page with id '{}' not found",
+            parent
+        )));
+    }
+    if let Some(key) = key {
+        if key == parent || descendants(pages, key).iter().any(|d| d == parent) {
+            return Err(AppError::Validation(
+                "A page can't be moved below itself".to_string(),
+            ));
+        }
+    }
+    Ok(parent.to_string())
+}
+
 // Helper command to clear all pages (for cleanup during development)
 #[tauri::command]
-pub async fn clear_pages_table(state: tauri::State<'_, AppState>) -> Result<String, String> {
+pub async fn clear_pages_table(state: tauri::State<'_, AppState>) -> Result<String, ApiError> {
     tracing::info!("Clearing pages table");
 
     let db = state.database.lock().await;
 
     // Delete all pages
     let _result = db
-        .db
-        .query("DELETE pages")
+        .as_actor(Actor::command("clear_pages_table"), async {
+            db.query("DELETE pages; DELETE page_blocks").await
+        })
         .await
-        .map_err(|e| format!("Failed to clear pages: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Failed to clear pages: {}", e)))?;
 
     Ok("Pages table cleared successfully".to_string())
 }
 
 #[tauri::command]
-pub async fn get_pages(state: tauri::State<'_, AppState>) -> Result<Vec<Page>, String> {
+pub async fn get_pages(state: tauri::State<'_, AppState>) -> Result<Vec<Page>, ApiError> {
     tracing::info!("Getting all pages");
 
     let db = state.database.lock().await;
-    let query = "SELECT * FROM pages ORDER BY order ASC";
-    let mut result = db
-        .db
-        .query(query)
-        .await
-        .map_err(|e| format!("Failed to query pages: {}", e))?;
-
-    let pages: Vec<Page> = result
-        .take(0)
-        .map_err(|e| format!("Failed to extract pages: {}", e))?;
+    let pages = load_pages(&db).await?;
 
     tracing::info!("Found {} pages", pages.len());
 
     Ok(pages)
 }
 
+/// Pages arranged by parent, siblings sorted by order. Pages whose parent is
+/// missing show up at the top level.
+#[tauri::command]
+pub async fn get_pages_tree(state: tauri::State<'_, AppState>) -> Result<Vec<PageNode>, ApiError> {
+    let db = state.database.lock().await;
+    Ok(build_tree(load_pages(&db).await?))
+}
+
 #[tauri::command]
 pub async fn create_page(
     mut page: Page,
     state: tauri::State<'_, AppState>,
-) -> Result<Page, String> {
+) -> Result<Page, ApiError> {
     tracing::info!("Creating page: {}", page.name);
 
     let db = state.database.lock().await;
@@ -72,34 +221,39 @@ pub async fn create_page(
     // Check if route already exists
     let check_query = format!("SELECT * FROM pages WHERE route = '{}'", page.route);
     let mut check_result = db
-        .db
         .query(&check_query)
         .await
-        .map_err(|e| format!("Failed to check route: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Failed to check route: {}", e)))?;
 
     let existing: Vec<Page> = check_result.take(0).unwrap_or_default();
     if !existing.is_empty() {
-        return Err(format!("Page with route '{}' already exists", page.route));
+        return Err(AppError::Validation(format!(
+            "Page with route '{}' already exists",
+            page.route
+        ))
+        .into());
+    }
+
+    if let Some(parent) = &page.parent_id {
+        page.parent_id = Some(check_parent(&load_pages(&db).await?, None, parent)?);
     }
 
     // Create page - let SurrealDB generate the Thing ID
     page.id = None; // Clear any provided ID
     let created: Option<Page> = db
-        .db
-        .create("pages")
-        .content(page)
+        .as_actor(Actor::command("create_page"), db.create_row("pages", page))
         .await
-        .map_err(|e| format!("Failed to create page: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Failed to create page: {}", e)))?;
 
-    created.ok_or_else(|| "Failed to create page".to_string())
+    created.ok_or_else(|| AppError::Database("Failed to create page".to_string()).into())
 }
 
 #[tauri::command]
 pub async fn update_page(
     id: String,
-    updates: serde_json::Value,
+    mut updates: serde_json::Value,
     state: tauri::State<'_, AppState>,
-) -> Result<Page, String> {
+) -> Result<Page, ApiError> {
     tracing::info!("Updating page: {}", id);
 
     let db = state.database.lock().await;
@@ -111,57 +265,226 @@ pub async fn update_page(
             new_route, id
         );
         let mut check_result = db
-            .db
             .query(&check_query)
             .await
-            .map_err(|e| format!("Failed to check route: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to check route: {}", e)))?;
 
         let existing: Vec<Page> = check_result.take(0).unwrap_or_default();
         if !existing.is_empty() {
-            return Err(format!("Page with route '{}' already exists", new_route));
+            return Err(AppError::Validation(format!(
+                "Page with route '{}' already exists",
+                new_route
+            ))
+            .into());
         }
     }
 
+    if let Some(parent) = updates.get("parent_id").and_then(|v| v.as_str()) {
+        let parent = check_parent(&load_pages(&db).await?, Some(page_key(&id)), parent)?;
+        updates["parent_id"] = parent.into();
+    }
+
     let updated: Option<Page> = db
-        .db
-        .update(("pages", id.as_str()))
-        .merge(updates)
+        .as_actor(
+            Actor::command("update_page"),
+            db.merge_row("pages", &id, updates),
+        )
         .await
-        .map_err(|e| format!("Failed to update page: {}", e))?;
+        .map_err(|e| AppError::Database(format!("Failed to update page: {}", e)))?;
 
-    updated.ok_or_else(|| format!("Page with id '{}' not found", id))
+    updated.ok_or_else(|| AppError::NotFound(format!("Page with id '{}' not found", id)).into())
 }
 
+/// Delete a page and its blocks; its child pages move up a level unless
+/// `mode` is cascade
 #[tauri::command]
-pub async fn delete_page(id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
-    tracing::info!("Deleting page: {}", id);
+pub async fn delete_page(
+    id: String,
+    mode: Option<DeletePageMode>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let mode = mode.unwrap_or_default();
+    tracing::info!("Deleting page: {} ({:?})", id, mode);
 
     let db = state.database.lock().await;
-    let _: Option<Page> = db
-        .db
-        .delete(("pages", id.as_str()))
-        .await
-        .map_err(|e| format!("Failed to delete page: {}", e))?;
+    let key = page_key(&id);
+    let pages = load_pages(&db).await?;
+    let parent = pages
+        .iter()
+        .find(|page| page.key() == key)
+        .and_then(|page| page.parent_id.clone());
+
+    let delete = async {
+        match mode {
+            DeletePageMode::Cascade => {
+                for child in descendants(&pages, key) {
+                    let _: Option<Page> = db
+                        .delete_row("pages", &child)
+                        .await
+                        .map_err(|e| AppError::Database(format!("Failed to delete page: {}", e)))?;
+                    db.delete_page_blocks(&child).await?;
+                }
+            }
+            DeletePageMode::Orphan => {
+                for child in pages
+                    .iter()
+                    .filter(|page| page.parent_id.as_deref() == Some(key))
+                {
+                    let _: Option<Page> = db
+                        .merge_row(
+                            "pages",
+                            &child.key(),
+                            serde_json::json!({ "parent_id": parent }),
+                        )
+                        .await
+                        .map_err(|e| AppError::Database(format!("Failed to move page: {}", e)))?;
+                }
+            }
+        }
+
+        let _: Option<Page> = db
+            .delete_row("pages", key)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete page: {}", e)))?;
+        db.delete_page_blocks(key).await?;
+        Ok::<_, AppError>(())
+    };
+    db.as_actor(Actor::command("delete_page"), delete).await?;
 
     Ok(())
 }
 
+/// Move a page below another page (None for the top level), at `index`
+/// among its new siblings (the end if None), and return the new tree
+#[tauri::command]
+pub async fn move_page(
+    id: String,
+    parent_id: Option<String>,
+    index: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<PageNode>, ApiError> {
+    tracing::info!("Moving page {} below {:?}", id, parent_id);
+
+    let db = state.database.lock().await;
+    let key = page_key(&id);
+    let pages = load_pages(&db).await?;
+    if !pages.iter().any(|page| page.key() == key) {
+        return Err(AppError::NotFound(format!("Page with id '{}' not found", id)).into());
+    }
+    let parent = parent_id
+        .map(|parent| check_parent(&pages, Some(key), &parent))
+        .transpose()?;
+
+    // Renumber the new siblings with the page inserted among them
+    let parents = effective_parents(&pages);
+    let mut siblings: Vec<&Page> = pages
+        .iter()
+        .filter(|page| page.key() != key && parents.get(&page.key()) == parent.as_ref())
+        .collect();
+    siblings.sort_by_key(|page| page.order);
+    let mut keys: Vec<String> = siblings.iter().map(|page| page.key()).collect();
+    keys.insert(index.unwrap_or(keys.len()).min(keys.len()), key.to_string());
+
+    let renumber = async {
+        for (order, sibling) in keys.iter().enumerate() {
+            let mut changes = serde_json::json!({ "order": order as i32 });
+            if sibling == key {
+                changes["parent_id"] = serde_json::json!(parent);
+            }
+            let _: Option<Page> = db
+                .merge_row("pages", sibling, changes)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to move page: {}", e)))?;
+        }
+        Ok::<_, AppError>(())
+    };
+    db.as_actor(Actor::command("move_page"), renumber).await?;
+
+    Ok(build_tree(load_pages(&db).await?))
+}
+
 #[tauri::command]
 pub async fn reorder_pages(
     page_ids: Vec<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     tracing::info!("Reordering {} pages", page_ids.len());
 
     let db = state.database.lock().await;
-    for (index, page_id) in page_ids.iter().enumerate() {
-        let _: Option<Page> = db
-            .db
-            .update(("pages", page_id.as_str()))
-            .merge(serde_json::json!({ "order": index as i32 }))
-            .await
-            .map_err(|e| format!("Failed to update page order: {}", e))?;
-    }
+    let reorder = async {
+        for (index, page_id) in page_ids.iter().enumerate() {
+            let _: Option<Page> = db
+                .merge_row(
+                    "pages",
+                    page_id,
+                    serde_json::json!({ "order": index as i32 }),
+                )
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update page order: {}", e)))?;
+        }
+        Ok::<_, AppError>(())
+    };
+    db.as_actor(Actor::command("reorder_pages"), reorder)
+        .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(key: &str, parent: Option<&str>, order: i32) -> Page {
+        Page {
+            id: Some(Thing::from(("pages", key))),
+            name: key.to_string(),
+            route: key.to_string(),
+            page_type: "dashboard".to_string(),
+            icon: None,
+            config: None,
+            order,
+            visible: true,
+            parent_id: parent.map(str::to_string),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn keys(nodes: &[PageNode]) -> Vec<String> {
+        nodes.iter().map(|node| node.page.key()).collect()
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let pages = vec![
+            page("notes", None, 1),
+            page("home", None, 0),
+            page("ideas", Some("notes"), 1),
+            page("todo", Some("notes"), 0),
+            page("later", Some("ideas"), 0),
+            // Missing parent and a cycle end up at the top level
+            page("lost", Some("gone"), 2),
+            page("a", Some("b"), 3),
+            page("b", Some("a"), 4),
+            page("c", Some("a"), 0),
+        ];
+
+        let tree = build_tree(pages.clone());
+        assert_eq!(keys(&tree), ["home", "notes", "lost", "a", "b"]);
+        assert_eq!(keys(&tree[1].children), ["todo", "ideas"]);
+        assert_eq!(keys(&tree[1].children[1].children), ["later"]);
+        assert_eq!(keys(&tree[3].children), ["c"]);
+
+        let mut below_notes = descendants(&pages, "notes");
+        below_notes.sort();
+        assert_eq!(below_notes, ["ideas", "later", "todo"]);
+
+        assert!(check_parent(&pages, Some("notes"), "pages:later").is_err());
+        assert!(check_parent(&pages, Some("notes"), "notes").is_err());
+        assert!(check_parent(&pages, Some("notes"), "missing").is_err());
+        assert_eq!(
+            check_parent(&pages, Some("later"), "pages:home").unwrap(),
+            "home"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+// Record transformation pipeline
+//
+// Optional per-source post-processing applied between the adapter fetch and
+// the upsert into the staging area. A pipeline is an ordered list of steps
+// (filters, field renames, computed fields, tag assignment) so users can drop
+// noise and enrich records without modifying plugins.
+//
+// Field paths are dot-separated and resolve into the record's `data` payload
+// (e.g. "pipeline.status" or "author.name").
+
+use crate::db::StagedRecord;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// ============================================================================
+// Pipeline Configuration
+// ============================================================================
+
+/// Ordered list of transformation steps
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Pipeline {
+    #[serde(default)]
+    pub steps: Vec<PipelineStep>,
+}
+
+/// A single transformation step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PipelineStep {
+    /// Keep only records matching the condition (or drop them when `exclude` is set)
+    Filter {
+        #[serde(flatten)]
+        condition: Condition,
+        #[serde(default)]
+        exclude: bool,
+    },
+
+    /// Move a field to a new path
+    Rename { from: String, to: String },
+
+    /// Set a field from a template, e.g. "{project.name} #{iid}"
+    Compute { field: String, template: String },
+
+    /// Add tags, optionally only to records matching a condition
+    Tag {
+        tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        when: Option<Condition>,
+    },
+}
+
+/// Comparison against a field of the record data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Condition {
+    pub field: String,
+    pub op: FilterOp,
+    #[serde(default)]
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Contains,
+    Exists,
+    Missing,
+    Gt,
+    Lt,
+    In,
+}
+
+// ============================================================================
+// Pipeline Execution
+// ============================================================================
+
+impl Pipeline {
+    /// Run all steps over the fetched records
+    pub fn apply(&self, records: Vec<StagedRecord>) -> Vec<StagedRecord> {
+        let before = records.len();
+
+        let records: Vec<StagedRecord> = records
+            .into_iter()
+            .filter_map(|record| self.apply_record(record))
+            .collect();
+
+        if records.len() != before {
+            tracing::info!(
+                "Pipeline dropped {} of {} records",
+                before - records.len(),
+                before
+            );
+        }
+
+        records
+    }
+
+    fn apply_record(&self, mut record: StagedRecord) -> Option<StagedRecord> {
+        for step in &self.steps {
+            match step {
+                PipelineStep::Filter { condition, exclude } => {
+                    if condition.matches(&record.data) == *exclude {
+                        return None;
+                    }
+                }
+                PipelineStep::Rename { from, to } => {
+                    if let Some(value) = remove_path(&mut record.data, from) {
+                        set_path(&mut record.data, to, value);
+                    }
+                }
+                PipelineStep::Compute { field, template } => {
+                    let value = render_template(template, &record.data);
+                    set_path(&mut record.data, field, Value::String(value));
+                }
+                PipelineStep::Tag { tags, when } => {
+                    if when.as_ref().is_none_or(|c| c.matches(&record.data)) {
+                        for tag in tags {
+                            if !record.metadata.tags.contains(tag) {
+                                record.metadata.tags.push(tag.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(record)
+    }
+}
+
+impl Condition {
+    /// Evaluate the condition against record data
+    pub fn matches(&self, data: &Value) -> bool {
+        let actual = get_path(data, &self.field);
+
+        match self.op {
+            FilterOp::Exists => actual.is_some_and(|v| !v.is_null()),
+            FilterOp::Missing => actual.is_none_or(|v| v.is_null()),
+            FilterOp::Eq => actual.is_some_and(|v| values_equal(v, &self.value)),
+            FilterOp::Ne => !actual.is_some_and(|v| values_equal(v, &self.value)),
+            FilterOp::Contains => match actual {
+                Some(Value::String(s)) => {
+                    self.value.as_str().is_some_and(|needle| s.contains(needle))
+                }
+                Some(Value::Array(items)) => items.iter().any(|i| values_equal(i, &self.value)),
+                _ => false,
+            },
+            FilterOp::Gt | FilterOp::Lt => {
+                match (actual.and_then(as_number), as_number(&self.value)) {
+                    (Some(a), Some(b)) if self.op == FilterOp::Gt => a > b,
+                    (Some(a), Some(b)) => a < b,
+                    _ => false,
+                }
+            }
+            FilterOp::In => match (&self.value, actual) {
+                (Value::Array(options), Some(v)) => options.iter().any(|o| values_equal(v, o)),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Compare values, treating numbers and numeric strings as equal when they match
+fn values_equal(a: &Value, b: &Value) -> bool {
+    if a == b {
+        return true;
+    }
+    match (as_number(a), as_number(b)) {
+        (Some(x), Some(y)) => x == y,
+        _ => false,
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn get_path<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(data, |current, key| current.get(key))
+}
+
+fn remove_path(data: &mut Value, path: &str) -> Option<Value> {
+    let (parent, key) = match path.rsplit_once('.') {
+        Some((parent, key)) => (get_path_mut(data, parent)?, key),
+        None => (data, path),
+    };
+    parent.as_object_mut()?.remove(key)
+}
+
+fn get_path_mut<'a>(data: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split('.')
+        .try_fold(data, |current, key| current.get_mut(key))
+}
+
+/// Set a value at a path, creating intermediate objects as needed
+fn set_path(data: &mut Value, path: &str, value: Value) {
+    let mut current = data;
+    let mut keys = path.split('.').peekable();
+
+    while let Some(key) = keys.next() {
+        if !current.is_object() {
+            *current = Value::Object(serde_json::Map::new());
+        }
+        let map = current.as_object_mut().expect("value is an object");
+
+        if keys.peek().is_none() {
+            map.insert(key.to_string(), value);
+            return;
+        }
+        current = map
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(serde_json::Map::new()));
+    }
+}
+
+/// Replace `{path}` placeholders with values from the record data
+fn render_template(template: &str, data: &Value) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        match after.find('}') {
+            Some(end) => {
+                match get_path(data, after[..end].trim()) {
+                    Some(Value::String(s)) => output.push_str(s),
+                    Some(Value::Null) | None => {}
+                    Some(other) => output.push_str(&other.to_string()),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(data: Value) -> StagedRecord {
+        StagedRecord::new("rest_api".to_string(), "test".to_string(), data)
+    }
+
+    #[test]
+    fn test_filter_rename_compute_and_tag() {
+        let pipeline: Pipeline = serde_json::from_value(json!({
+            "steps": [
+                { "type": "filter", "field": "status", "op": "eq", "value": "skipped", "exclude": true },
+                { "type": "rename", "from": "ref", "to": "branch.name" },
+                { "type": "compute", "field": "label", "template": "{branch.name} #{id}" },
+                { "type": "tag", "tags": ["failing"], "when": { "field": "status", "op": "eq", "value": "failed" } }
+            ]
+        }))
+        .unwrap();
+
+        let records = pipeline.apply(vec![
+            record(json!({ "id": 1, "status": "failed", "ref": "main" })),
+            record(json!({ "id": 2, "status": "skipped", "ref": "main" })),
+            record(json!({ "id": 3, "status": "success", "ref": "dev" })),
+        ]);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].data["branch"]["name"], "main");
+        assert!(records[0].data.get("ref").is_none());
+        assert_eq!(records[0].data["label"], "main #1");
+        assert_eq!(records[0].metadata.tags, vec!["failing".to_string()]);
+        assert!(records[1].metadata.tags.is_empty());
+    }
+
+    #[test]
+    fn test_condition_operators() {
+        let data = json!({ "count": "12", "labels": ["bug", "ui"], "owner": null });
+
+        let cond = |field: &str, op: FilterOp, value: Value| Condition {
+            field: field.to_string(),
+            op,
+            value,
+        };
+
+        assert!(cond("count", FilterOp::Gt, json!(10)).matches(&data));
+        assert!(cond("count", FilterOp::Eq, json!(12)).matches(&data));
+        assert!(cond("labels", FilterOp::Contains, json!("bug")).matches(&data));
+        assert!(cond("owner", FilterOp::Missing, Value::Null).matches(&data));
+        assert!(cond("nested.field", FilterOp::Missing, Value::Null).matches(&data));
+        assert!(cond("count", FilterOp::In, json!(["11", "12"])).matches(&data));
+        assert!(!cond("labels", FilterOp::Exists, Value::Null).matches(&json!({})));
+    }
+}
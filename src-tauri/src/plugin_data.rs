@@ -1,12 +1,20 @@
 // Plugin data management service
 // Handles CRUD operations for plugin-specific data storage
+//
+// Besides the shared `plugin_data` key/value table, every plugin can keep
+// rows in its own namespaced tables (`plugin_<name>_<table>`). All of a
+// plugin's data counts towards its storage quota and is removed when the
+// plugin is uninstalled.
 
-use crate::db::Database;
+use crate::db::{Database, TableStats};
 use crate::error::AppError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
 
+/// Storage quota of plugins whose manifest doesn't set one
+pub const DEFAULT_PLUGIN_QUOTA_BYTES: u64 = 50 * 1024 * 1024;
+
 // ============================================================================
 // Plugin Data Models
 // ============================================================================
@@ -59,6 +67,53 @@ impl From<PluginDataRecord> for PluginData {
     }
 }
 
+/// Storage used by a plugin
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginStorageUsage {
+    pub plugin_id: String,
+    /// Namespaced `plugin_<name>_*` tables
+    pub tables: Vec<TableStats>,
+    /// Approximate size of the plugin's rows in the shared `plugin_data` table
+    pub key_value_bytes: u64,
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+}
+
+/// Prefix of a plugin's namespaced tables ("prompt-generator" → "plugin_prompt_generator_")
+pub fn plugin_table_prefix(plugin_id: &str) -> String {
+    let name: String = plugin_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("plugin_{}_", name)
+}
+
+/// Full name of a plugin's table; table names are limited to `[a-z0-9_]`
+pub fn plugin_table_name(plugin_id: &str, table: &str) -> Result<String, AppError> {
+    if plugin_id.is_empty() {
+        return Err(AppError::Validation(
+            "Plugin ID cannot be empty".to_string(),
+        ));
+    }
+    if table.is_empty()
+        || !table
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid plugin table name '{}' (use lowercase letters, digits and '_')",
+            table
+        )));
+    }
+    Ok(format!("{}{}", plugin_table_prefix(plugin_id), table))
+}
+
 // ============================================================================
 // Plugin Data Service
 // ============================================================================
@@ -96,7 +151,6 @@ impl PluginDataService {
 
         let db = self.db.lock().await;
         let mut result = db
-            .db
             .query(&query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to query plugin data: {}", e)))?;
@@ -116,9 +170,12 @@ impl PluginDataService {
         key: &str,
         value: &str,
         data_type: &str,
+        quota_bytes: u64,
     ) -> Result<(), AppError> {
         // Validate type
         self.validate_data_type(data_type)?;
+        self.ensure_quota(plugin_id, value.len() as u64, quota_bytes)
+            .await?;
 
         // Validate scope
         let scope = if panel_id.is_some() {
@@ -150,8 +207,7 @@ impl PluginDataService {
                 )
             };
 
-            db.db
-                .query(&query)
+            db.query(&query)
                 .await
                 .map_err(|e| AppError::Database(format!("Failed to update plugin data: {}", e)))?;
         } else {
@@ -219,8 +275,7 @@ impl PluginDataService {
             }
         };
 
-        db.db
-            .query(&query)
+        db.query(&query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to delete plugin data: {}", e)))?;
 
@@ -242,7 +297,6 @@ impl PluginDataService {
 
         let db = self.db.lock().await;
         let mut result = db
-            .db
             .query(&query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to query plugin data: {}", e)))?;
@@ -254,6 +308,172 @@ impl PluginDataService {
         Ok(data.into_iter().map(|d| d.into()).collect())
     }
 
+    /// Insert a row into one of the plugin's tables and return it (with its ID)
+    pub async fn insert_plugin_row(
+        &self,
+        plugin_id: &str,
+        table: &str,
+        row: serde_json::Value,
+        quota_bytes: u64,
+    ) -> Result<serde_json::Value, AppError> {
+        let table = plugin_table_name(plugin_id, table)?;
+        if !row.is_object() {
+            return Err(AppError::Validation(
+                "Plugin rows must be JSON objects".to_string(),
+            ));
+        }
+        self.ensure_quota(plugin_id, row.to_string().len() as u64, quota_bytes)
+            .await?;
+
+        let db = self.db.lock().await;
+        let mut result = db
+            .query("CREATE type::table($table) CONTENT $row")
+            .bind(("table", table.clone()))
+            .bind(("row", row))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to insert into {}: {}", table, e)))?;
+        let created: surrealdb::Value = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to insert into {}: {}", table, e)))?;
+
+        match created.into_inner().into_json() {
+            serde_json::Value::Array(mut rows) if !rows.is_empty() => Ok(rows.remove(0)),
+            _ => Err(AppError::Database(format!(
+                "Failed to insert into {}",
+                table
+            ))),
+        }
+    }
+
+    /// Read rows from one of the plugin's tables
+    pub async fn query_plugin_rows(
+        &self,
+        plugin_id: &str,
+        table: &str,
+        start: usize,
+        limit: usize,
+    ) -> Result<Vec<serde_json::Value>, AppError> {
+        let table = plugin_table_name(plugin_id, table)?;
+        let db = self.db.lock().await;
+        db.export_table_chunk_json(&table, start, limit).await
+    }
+
+    /// Delete a row ("plugin_x_table:id" or bare id) from one of the plugin's tables
+    pub async fn delete_plugin_row(
+        &self,
+        plugin_id: &str,
+        table: &str,
+        id: &str,
+    ) -> Result<(), AppError> {
+        let table = plugin_table_name(plugin_id, table)?;
+        let key = id
+            .strip_prefix(&format!("{}:", table))
+            .unwrap_or(id)
+            .trim_start_matches('⟨')
+            .trim_end_matches('⟩');
+
+        let db = self.db.lock().await;
+        db.query("DELETE type::thing($table, $key)")
+            .bind(("table", table.clone()))
+            .bind(("key", key.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete from {}: {}", table, e)))?;
+        Ok(())
+    }
+
+    /// Storage used by a plugin's tables and key/value entries
+    pub async fn storage_usage(
+        &self,
+        plugin_id: &str,
+        quota_bytes: u64,
+    ) -> Result<PluginStorageUsage, AppError> {
+        let prefix = plugin_table_prefix(plugin_id);
+        let db = self.db.lock().await;
+
+        let mut tables = Vec::new();
+        for table in db.list_tables().await? {
+            if table.starts_with(&prefix) {
+                tables.push(db.table_size(&table).await?);
+            }
+        }
+        tables.sort_by(|a, b| a.table.cmp(&b.table));
+
+        let mut result = db
+            .query(
+                "SELECT math::sum(string::len(<string> $this)) AS size_bytes
+                 FROM plugin_data WHERE plugin_id = $plugin_id GROUP ALL",
+            )
+            .bind(("plugin_id", plugin_id.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get plugin storage: {}", e)))?;
+        let key_value_bytes: Option<u64> = result
+            .take((0, "size_bytes"))
+            .map_err(|e| AppError::Database(format!("Failed to get plugin storage: {}", e)))?;
+        let key_value_bytes = key_value_bytes.unwrap_or(0);
+
+        Ok(PluginStorageUsage {
+            plugin_id: plugin_id.to_string(),
+            used_bytes: key_value_bytes + tables.iter().map(|t| t.size_bytes).sum::<u64>(),
+            tables,
+            key_value_bytes,
+            quota_bytes,
+        })
+    }
+
+    /// Remove all of a plugin's tables and key/value entries (on uninstall)
+    pub async fn purge_plugin(&self, plugin_id: &str) -> Result<usize, AppError> {
+        let prefix = plugin_table_prefix(plugin_id);
+        let db = self.db.lock().await;
+
+        let tables: Vec<String> = db
+            .list_tables()
+            .await?
+            .into_iter()
+            .filter(|t| t.starts_with(&prefix))
+            .collect();
+        for table in &tables {
+            // REMOVE TABLE takes no parameters; only plain names are interpolated
+            if !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(AppError::Validation(format!(
+                    "Refusing to remove table with unexpected name: {}",
+                    table
+                )));
+            }
+            db.query(format!("REMOVE TABLE {}", table))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to remove {}: {}", table, e)))?;
+        }
+
+        db.query("DELETE plugin_data WHERE plugin_id = $plugin_id")
+            .bind(("plugin_id", plugin_id.to_string()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete plugin data: {}", e)))?;
+
+        tracing::info!(
+            "Purged data of plugin {} ({} tables)",
+            plugin_id,
+            tables.len()
+        );
+        Ok(tables.len())
+    }
+
+    /// Fail if writing `extra_bytes` would take the plugin over its quota
+    async fn ensure_quota(
+        &self,
+        plugin_id: &str,
+        extra_bytes: u64,
+        quota_bytes: u64,
+    ) -> Result<(), AppError> {
+        let usage = self.storage_usage(plugin_id, quota_bytes).await?;
+        if usage.used_bytes + extra_bytes > quota_bytes {
+            return Err(AppError::Validation(format!(
+                "Plugin {} storage quota exceeded ({} of {} bytes used)",
+                plugin_id, usage.used_bytes, quota_bytes
+            )));
+        }
+        Ok(())
+    }
+
     // Private helper
     fn validate_data_type(&self, data_type: &str) -> Result<(), AppError> {
         match data_type {
@@ -265,3 +485,87 @@ impl PluginDataService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_plugin_tables_quota_and_purge() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let service = PluginDataService::new(Arc::new(Mutex::new(db)));
+
+        assert_eq!(
+            plugin_table_name("prompt-generator", "history").unwrap(),
+            "plugin_prompt_generator_history"
+        );
+        assert!(plugin_table_name("prompt-generator", "x; REMOVE TABLE records").is_err());
+
+        let row = service
+            .insert_plugin_row(
+                "prompt-generator",
+                "history",
+                serde_json::json!({ "prompt": "hello" }),
+                DEFAULT_PLUGIN_QUOTA_BYTES,
+            )
+            .await
+            .unwrap();
+        assert!(row["id"]
+            .as_str()
+            .unwrap()
+            .starts_with("plugin_prompt_generator_history:"));
+        service
+            .save_plugin_data("prompt-generator", None, "theme", "dark", "string", 1024)
+            .await
+            .unwrap();
+        service
+            .insert_plugin_row("other", "items", serde_json::json!({ "n": 1 }), 1024)
+            .await
+            .unwrap();
+
+        let usage = service
+            .storage_usage("prompt-generator", 1024)
+            .await
+            .unwrap();
+        assert_eq!(usage.tables.len(), 1);
+        assert_eq!(usage.tables[0].rows, 1);
+        assert!(usage.key_value_bytes > 0);
+
+        // Writes beyond the quota are rejected
+        let big = serde_json::json!({ "prompt": "x".repeat(2048) });
+        assert!(service
+            .insert_plugin_row("prompt-generator", "history", big, 1024)
+            .await
+            .is_err());
+
+        let id = row["id"].as_str().unwrap();
+        service
+            .delete_plugin_row("prompt-generator", "history", id)
+            .await
+            .unwrap();
+        assert!(service
+            .query_plugin_rows("prompt-generator", "history", 0, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(service.purge_plugin("prompt-generator").await.unwrap(), 1);
+        let usage = service
+            .storage_usage("prompt-generator", 1024)
+            .await
+            .unwrap();
+        assert!(usage.tables.is_empty());
+        assert_eq!(usage.used_bytes, 0);
+        // Other plugins are untouched
+        assert_eq!(
+            service
+                .query_plugin_rows("other", "items", 0, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -89,7 +89,7 @@ pub fn add_http_to_linker(linker: &mut Linker<WasiP1Ctx>) -> Result<(), anyhow::
             ) {
                 Ok(response_json) => response_json,
                 Err(e) => {
-                    eprintln!("HTTP request failed: {}", e);
+                    tracing::warn!("Plugin HTTP request failed: {}", e);
                     return -1;
                 }
             };
@@ -172,7 +172,7 @@ pub fn add_http_to_linker(linker: &mut Linker<WasiP1Ctx>) -> Result<(), anyhow::
             let result = match make_http_request_sync(&url, "GET", None, None) {
                 Ok(response_json) => response_json,
                 Err(e) => {
-                    eprintln!("HTTP GET failed: {}", e);
+                    tracing::warn!("Plugin HTTP GET failed: {}", e);
                     return -1;
                 }
             };
@@ -266,7 +266,8 @@ fn make_http_request_sync(
 
         // Use the handle to spawn the async work
         handle.block_on(async {
-            let client = reqwest::Client::new();
+            crate::network::ensure_online()?;
+            let client = crate::network::client();
 
             let mut request = match method.to_uppercase().as_str() {
                 "GET" => client.get(url),
@@ -9,8 +9,11 @@ mod http;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 use wasmtime::*;
 use wasmtime_wasi::preview1::{self, WasiP1Ctx};
 use wasmtime_wasi::WasiCtxBuilder;
@@ -18,6 +21,8 @@ use wasmtime_wasi::WasiCtxBuilder;
 use crate::adapters::AdapterConfig;
 use crate::db::StagedRecord;
 use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::metrics;
 
 // ============================================================================
 // Plugin Metadata
@@ -59,6 +64,19 @@ pub struct PluginManifest {
 
     #[serde(default)]
     pub tags: Vec<String>,
+
+    /// Storage quota for the plugin's data in MB (defaults to `DEFAULT_PLUGIN_QUOTA_BYTES`)
+    #[serde(default)]
+    pub storage_quota_mb: Option<u64>,
+
+    /// Settings the plugin declares, registered as `plugins.<name>.<key>`
+    #[serde(default)]
+    pub settings: Vec<crate::settings::SettingDefinition>,
+
+    /// Command palette actions the plugin declares, registered as
+    /// `plugins.<name>.<id>` and run by its backend
+    #[serde(default)]
+    pub actions: Vec<crate::actions::ActionDefinition>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,12 +155,36 @@ pub struct PluginContext {
 impl PluginContext {
     pub fn new(config_dir: PathBuf) -> Self {
         Self {
-            http_client: reqwest::Client::new(),
+            http_client: crate::network::client(),
             config_dir,
         }
     }
 }
 
+/// One page of a plugin fetch
+///
+/// Plugins may return either a plain record array or
+/// `{ "records": [...], "continuation": ... }`. A non-null continuation means
+/// more data is available; the host passes it back as
+/// `parameters.continuation` on the next call.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginFetchPage {
+    pub records: Vec<StagedRecord>,
+    #[serde(default)]
+    pub continuation: Option<serde_json::Value>,
+}
+
+/// Error a plugin returns instead of a fetch result
+///
+/// `{ "error": "...", "status": 429 }`; `status` is the HTTP status of the
+/// request that failed, if the failure was one.
+#[derive(Debug, Deserialize)]
+struct PluginFetchError {
+    error: String,
+    #[serde(default)]
+    status: Option<u16>,
+}
+
 // ============================================================================
 // Plugin Trait
 // ============================================================================
@@ -159,11 +201,28 @@ pub trait Plugin: Send + Sync {
     /// Fetch data (for adapter plugins)
     async fn fetch(&self, config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError>;
 
+    /// Fetch a single page using the continuation protocol
+    async fn fetch_page(&self, config: &AdapterConfig) -> Result<PluginFetchPage, AppError> {
+        Ok(PluginFetchPage {
+            records: self.fetch(config).await?,
+            continuation: None,
+        })
+    }
+
     /// Test connection (for adapter plugins)
     async fn test_connection(&self, config: &AdapterConfig) -> Result<bool, AppError>;
 
+    /// Run one of the actions the plugin declares in its manifest
+    async fn run_action(&self, action: &str, _args: &Value) -> Result<Value, AppError> {
+        Err(AppError::Plugin(format!(
+            "Plugin {} cannot run action {}",
+            self.metadata().name,
+            action
+        )))
+    }
+
     /// Shutdown the plugin
-    async fn shutdown(&mut self) -> Result<(), AppError>;
+    async fn shutdown(&self) -> Result<(), AppError>;
 }
 
 // ============================================================================
@@ -201,6 +260,24 @@ impl WasmPlugin {
         &self,
         function_name: &str,
         params: Vec<u8>,
+    ) -> Result<Vec<u8>, AppError> {
+        let started = Instant::now();
+        let result = self.run_function(function_name, params).await;
+        metrics::observe_since(
+            "plugin_call_duration_ms",
+            &[
+                ("plugin", self.metadata.name.as_str()),
+                ("function", function_name),
+            ],
+            started,
+        );
+        result
+    }
+
+    async fn run_function(
+        &self,
+        function_name: &str,
+        params: Vec<u8>,
     ) -> Result<Vec<u8>, AppError> {
         tracing::debug!(
             "Calling WASM function: {} with {} bytes",
@@ -333,6 +410,10 @@ impl Plugin for WasmPlugin {
     }
 
     async fn fetch(&self, config: &AdapterConfig) -> Result<Vec<StagedRecord>, AppError> {
+        Ok(self.fetch_page(config).await?.records)
+    }
+
+    async fn fetch_page(&self, config: &AdapterConfig) -> Result<PluginFetchPage, AppError> {
         tracing::info!("Fetching data using plugin: {}", self.metadata.name);
 
         // Serialize config to JSON for passing to WASM
@@ -342,12 +423,26 @@ impl Plugin for WasmPlugin {
         // Call the WASM fetch function (wasm_bindgen exports as "plugin_fetch")
         let result = self.call_function("plugin_fetch", config_json).await?;
 
-        // Deserialize the result
-        let records: Vec<StagedRecord> = serde_json::from_slice(&result)
-            .map_err(|e| AppError::Plugin(format!("Failed to deserialize plugin result: {}", e)))?;
+        if let Ok(failed) = serde_json::from_slice::<PluginFetchError>(&result) {
+            return Err(match failed.status {
+                Some(status) => AppError::http_status(status, failed.error),
+                None => AppError::Plugin(failed.error),
+            });
+        }
+
+        // Deserialize the result (plain array or continuation page)
+        let page = match serde_json::from_slice::<Vec<StagedRecord>>(&result) {
+            Ok(records) => PluginFetchPage {
+                records,
+                continuation: None,
+            },
+            Err(_) => serde_json::from_slice::<PluginFetchPage>(&result).map_err(|e| {
+                AppError::Plugin(format!("Failed to deserialize plugin result: {}", e))
+            })?,
+        };
 
-        tracing::info!("Plugin returned {} records", records.len());
-        Ok(records)
+        tracing::info!("Plugin returned {} records", page.records.len());
+        Ok(page)
     }
 
     async fn test_connection(&self, config: &AdapterConfig) -> Result<bool, AppError> {
@@ -366,7 +461,22 @@ impl Plugin for WasmPlugin {
         Ok(!result.is_empty())
     }
 
-    async fn shutdown(&mut self) -> Result<(), AppError> {
+    async fn run_action(&self, action: &str, args: &Value) -> Result<Value, AppError> {
+        tracing::info!("Running action {} of plugin {}", action, self.metadata.name);
+
+        let request = serde_json::to_vec(&serde_json::json!({ "action": action, "args": args }))
+            .map_err(|e| AppError::Plugin(format!("Failed to serialize action: {}", e)))?;
+
+        // wasm_bindgen exports as "plugin_run_action"; an empty result is no value
+        let result = self.call_function("plugin_run_action", request).await?;
+        if result.is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_slice(&result)
+            .map_err(|e| AppError::Plugin(format!("Failed to deserialize action result: {}", e)))
+    }
+
+    async fn shutdown(&self) -> Result<(), AppError> {
         tracing::info!("Shutting down plugin: {}", self.metadata.name);
         Ok(())
     }
@@ -376,10 +486,23 @@ impl Plugin for WasmPlugin {
 // Plugin Manager
 // ============================================================================
 
+/// A plugin directory that failed to load
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedPlugin {
+    /// Name of the plugin's directory
+    pub name: String,
+    pub reason: String,
+}
+
 /// Manages all loaded plugins
+///
+/// Lookups hand out shared plugin handles, so callers can release the
+/// manager's lock before awaiting a fetch or action.
 pub struct PluginManager {
-    plugins: HashMap<String, Box<dyn Plugin>>, // Backend plugins (WASM)
+    plugins: HashMap<String, Arc<dyn Plugin>>, // Backend plugins (WASM)
     manifests: HashMap<String, PluginManifest>, // All plugin manifests (including frontend-only)
+    plugin_paths: HashMap<String, PathBuf>,    // Directory each manifest was loaded from
+    quarantined: Vec<QuarantinedPlugin>,       // Plugins that failed to load in the last scan
     plugin_dir: PathBuf,
 }
 
@@ -389,18 +512,27 @@ impl PluginManager {
         Self {
             plugins: HashMap::new(),
             manifests: HashMap::new(),
+            plugin_paths: HashMap::new(),
+            quarantined: Vec::new(),
             plugin_dir,
         }
     }
 
+    /// Plugins set aside because they failed to load in the last scan
+    pub fn quarantined(&self) -> &[QuarantinedPlugin] {
+        &self.quarantined
+    }
+
+    /// Directory plugins are loaded from
+    pub fn plugin_dir(&self) -> &Path {
+        &self.plugin_dir
+    }
+
     /// Scan plugin directory and load all plugins
     pub async fn load_plugins(&mut self) -> Result<usize, AppError> {
-        eprintln!("🔍 PluginManager::load_plugins() called");
-        eprintln!("   Current plugins in HashMap: {}", self.plugins.len());
         tracing::info!("Scanning for plugins in: {:?}", self.plugin_dir);
 
         if !self.plugin_dir.exists() {
-            eprintln!("⚠️  Plugin directory does not exist: {:?}", self.plugin_dir);
             tracing::warn!(
                 "Plugin directory does not exist, creating: {:?}",
                 self.plugin_dir
@@ -415,6 +547,7 @@ impl PluginManager {
             .map_err(|e| AppError::Plugin(format!("Failed to read plugin directory: {}", e)))?;
 
         let mut count = 0;
+        self.quarantined.clear();
 
         for entry in entries {
             let entry =
@@ -422,25 +555,28 @@ impl PluginManager {
             let path = entry.path();
 
             if path.is_dir() {
-                eprintln!("Attempting to load plugin from: {:?}", path);
+                tracing::debug!("Loading plugin from: {:?}", path);
                 match self.load_plugin(&path).await {
                     Ok(_) => {
                         count += 1;
-                        eprintln!("✅ Successfully loaded plugin from: {:?}", path);
                         tracing::info!("Successfully loaded plugin from: {:?}", path);
                     }
                     Err(e) => {
-                        eprintln!("❌ Failed to load plugin {:?}: {}", path, e);
                         tracing::warn!("Failed to load plugin {:?}: {}", path, e);
+                        let quarantined = QuarantinedPlugin {
+                            name: entry.file_name().to_string_lossy().into_owned(),
+                            reason: e.to_string(),
+                        };
+                        events::publish(AppEvent::PluginQuarantined {
+                            name: quarantined.name.clone(),
+                            reason: quarantined.reason.clone(),
+                        });
+                        self.quarantined.push(quarantined);
                     }
                 }
             }
         }
 
-        eprintln!(
-            "📊 load_plugins() complete: {} plugins in HashMap",
-            self.plugins.len()
-        );
         tracing::info!("Loaded {} plugins", count);
         Ok(count)
     }
@@ -467,6 +603,8 @@ impl PluginManager {
         // 2. Store manifest (for all plugins, including frontend-only)
         self.manifests
             .insert(manifest.name.clone(), manifest.clone());
+        self.plugin_paths
+            .insert(manifest.name.clone(), path.to_path_buf());
 
         // 3. Validate permissions
         self.validate_permissions(&manifest)?;
@@ -500,7 +638,7 @@ impl PluginManager {
                 // Load the WASM plugin
                 let plugin = WasmPlugin::load(&wasm_path, metadata)?;
 
-                self.plugins.insert(manifest.name.clone(), Box::new(plugin));
+                self.plugins.insert(manifest.name.clone(), Arc::new(plugin));
             } else {
                 return Err(AppError::Plugin(format!(
                     "Unsupported backend type: {}",
@@ -511,16 +649,16 @@ impl PluginManager {
             // Frontend-only plugin (no backend)
             // For now, we just track it in metadata without loading a WASM module
             // The frontend will handle loading the Vue components
-            eprintln!(
-                "✅ Frontend-only plugin: {} (no backend to load)",
-                manifest.name
-            );
             tracing::info!("Frontend-only plugin registered: {}", manifest.name);
 
             // Note: We don't add it to self.plugins because it has no backend implementation
             // The frontend will query the manifest directly via get_installed_plugins
         }
 
+        events::publish(AppEvent::PluginLoaded {
+            name: manifest.name,
+            version: manifest.version,
+        });
         Ok(())
     }
 
@@ -537,15 +675,32 @@ impl PluginManager {
     }
 
     /// Get a plugin by name
-    pub fn get_plugin(&self, name: &str) -> Option<&dyn Plugin> {
-        self.plugins.get(name).map(|p| p.as_ref())
+    pub fn get_plugin(&self, name: &str) -> Option<Arc<dyn Plugin>> {
+        self.plugins.get(name).cloned()
+    }
+
+    /// Settings declared by each plugin's manifest
+    pub fn settings_schemas(&self) -> Vec<(String, Vec<crate::settings::SettingDefinition>)> {
+        self.manifests
+            .values()
+            .filter(|manifest| !manifest.settings.is_empty())
+            .map(|manifest| (manifest.name.clone(), manifest.settings.clone()))
+            .collect()
+    }
+
+    /// Actions declared by each plugin's manifest
+    pub fn action_schemas(&self) -> Vec<(String, Vec<crate::actions::ActionDefinition>)> {
+        self.manifests
+            .values()
+            .filter(|manifest| !manifest.actions.is_empty())
+            .map(|manifest| (manifest.name.clone(), manifest.actions.clone()))
+            .collect()
     }
 
     /// Get all loaded plugins
     pub fn get_all_plugins(&self) -> Vec<PluginMetadata> {
         // Return metadata from ALL manifests (including frontend-only plugins)
-        let plugins: Vec<PluginMetadata> = self
-            .manifests
+        self.manifests
             .values()
             .map(|manifest| {
                 // Check if there's a loaded backend plugin for additional info
@@ -579,56 +734,74 @@ impl PluginManager {
                     frontend: manifest.frontend.clone(),
                 }
             })
-            .collect();
-
-        eprintln!(
-            "📋 get_all_plugins() called: returning {} plugins",
-            plugins.len()
-        );
-        for plugin in &plugins {
-            eprintln!(
-                "   - {} (frontend: {})",
-                plugin.name,
-                plugin.frontend.is_some()
-            );
-        }
-        plugins
+            .collect()
     }
 
     /// Get a plugin by adapter type (for Phase 3.3 plugin-first lookup)
-    pub fn get_plugin_by_adapter_type(&self, adapter_type: &str) -> Option<&dyn Plugin> {
+    pub fn get_plugin_by_adapter_type(&self, adapter_type: &str) -> Option<Arc<dyn Plugin>> {
         // Check all loaded backend plugins for matching adapter type
         for (name, plugin) in &self.plugins {
             let metadata = plugin.metadata();
             if metadata.adapter_type.as_deref() == Some(adapter_type) {
-                eprintln!(
-                    "🔌 Found plugin '{}' for adapter type '{}'",
-                    name, adapter_type
-                );
                 tracing::info!("Found plugin {} for adapter type {}", name, adapter_type);
-                return Some(plugin.as_ref());
+                return Some(plugin.clone());
             }
         }
 
-        eprintln!("❌ No plugin found for adapter type '{}'", adapter_type);
         tracing::debug!("No plugin found for adapter type {}", adapter_type);
         None
     }
 
     /// Unload a plugin
     pub async fn unload_plugin(&mut self, name: &str) -> Result<(), AppError> {
-        if let Some(mut plugin) = self.plugins.remove(name) {
+        // Fetches still holding the plugin's handle finish with it
+        if let Some(plugin) = self.plugins.remove(name) {
             plugin.shutdown().await?;
             tracing::info!("Unloaded plugin: {}", name);
+            events::publish(AppEvent::PluginUnloaded {
+                name: name.to_string(),
+            });
         }
         Ok(())
     }
 
+    /// Unload a plugin and delete its directory
+    /// The plugin's stored data is cleaned up separately (`PluginDataService::purge_plugin`).
+    pub async fn uninstall_plugin(&mut self, name: &str) -> Result<(), AppError> {
+        let path = self
+            .plugin_paths
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Plugin {} is not installed", name)))?;
+
+        self.unload_plugin(name).await?;
+        std::fs::remove_dir_all(&path).map_err(|e| {
+            AppError::Plugin(format!(
+                "Failed to remove plugin directory {:?}: {}",
+                path, e
+            ))
+        })?;
+
+        self.manifests.remove(name);
+        self.plugin_paths.remove(name);
+        tracing::info!("Uninstalled plugin: {}", name);
+        Ok(())
+    }
+
+    /// Storage quota of a plugin in bytes
+    pub fn storage_quota(&self, name: &str) -> u64 {
+        self.manifests
+            .get(name)
+            .and_then(|m| m.storage_quota_mb)
+            .map(|mb| mb * 1024 * 1024)
+            .unwrap_or(crate::plugin_data::DEFAULT_PLUGIN_QUOTA_BYTES)
+    }
+
     /// Shutdown all plugins
     pub async fn shutdown_all(&mut self) -> Result<(), AppError> {
         tracing::info!("Shutting down all plugins");
 
-        for (name, mut plugin) in self.plugins.drain() {
+        for (name, plugin) in self.plugins.drain() {
             if let Err(e) = plugin.shutdown().await {
                 tracing::error!("Error shutting down plugin {}: {}", name, e);
             }
@@ -0,0 +1,305 @@
+// Background polling scheduler
+//
+// Periodically fetches enabled data sources according to their polling
+// interval (checked by the `polling` job). Sources can be paused/resumed
+// individually, and a source is skipped while a previous fetch for it is
+// still in flight.
+
+use crate::adapters::AdapterConfig;
+use crate::data_sources::DataSourceService;
+use crate::error::AppError;
+use crate::fetch_queue::{FetchPriority, FetchQueue};
+use crate::fetcher;
+use crate::scheduler::{Job, Schedule};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+
+/// How often the polling job checks for due sources
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Scheduling state for a single source
+#[derive(Debug, Clone, Default)]
+struct SourceSchedule {
+    paused: bool,
+    in_flight: bool,
+    interval_secs: Option<u64>,
+    last_run: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+/// User-facing polling status for a source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollingStatus {
+    pub source: String,
+    pub paused: bool,
+    pub in_flight: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Tracks polling state for all sources
+#[derive(Default)]
+pub struct PollingScheduler {
+    sources: HashMap<String, SourceSchedule>,
+}
+
+impl PollingScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pause scheduled fetches for a source
+    pub fn pause(&mut self, source: &str) {
+        self.sources.entry(source.to_string()).or_default().paused = true;
+        tracing::info!("Paused polling for source: {}", source);
+    }
+
+    /// Resume scheduled fetches for a source
+    pub fn resume(&mut self, source: &str) {
+        self.sources.entry(source.to_string()).or_default().paused = false;
+        tracing::info!("Resumed polling for source: {}", source);
+    }
+
+    /// Get the polling status of all known sources
+    pub fn status(&self) -> Vec<PollingStatus> {
+        let mut statuses: Vec<PollingStatus> = self
+            .sources
+            .iter()
+            .map(|(source, schedule)| polling_status(source, schedule))
+            .collect();
+
+        statuses.sort_by(|a, b| a.source.cmp(&b.source));
+        statuses
+    }
+
+    /// Get the polling status of a source, if it is known
+    pub fn source_status(&self, source: &str) -> Option<PollingStatus> {
+        self.sources
+            .get(source)
+            .map(|schedule| polling_status(source, schedule))
+    }
+
+    /// Select the configs that are due at `now` and mark them as in flight
+    fn take_due(&mut self, configs: Vec<AdapterConfig>, now: DateTime<Utc>) -> Vec<AdapterConfig> {
+        let mut due = Vec::new();
+
+        for config in configs {
+            let Some(interval) = config.polling_interval.filter(|secs| *secs > 0) else {
+                continue;
+            };
+
+            let schedule = self.sources.entry(config.source.clone()).or_default();
+            schedule.interval_secs = Some(interval);
+
+            if schedule.paused || schedule.in_flight {
+                continue;
+            }
+
+            let is_due = match schedule.last_run {
+                None => true,
+                Some(last) => now - last >= chrono::Duration::seconds(interval as i64),
+            };
+
+            if is_due {
+                schedule.in_flight = true;
+                due.push(config);
+            }
+        }
+
+        due
+    }
+
+    /// Record the outcome of a scheduled fetch
+    fn finish(&mut self, summary: &fetcher::FetchSummary) {
+        let schedule = self.sources.entry(summary.source.clone()).or_default();
+        schedule.in_flight = false;
+        schedule.last_run = Some(Utc::now());
+        schedule.last_error = summary.error.clone();
+    }
+}
+
+fn polling_status(source: &str, schedule: &SourceSchedule) -> PollingStatus {
+    PollingStatus {
+        source: source.to_string(),
+        paused: schedule.paused,
+        in_flight: schedule.in_flight,
+        interval_secs: schedule.interval_secs,
+        last_run: schedule.last_run,
+        next_run: match (schedule.last_run, schedule.interval_secs) {
+            (Some(last), Some(secs)) => Some(last + chrono::Duration::seconds(secs as i64)),
+            _ => None,
+        },
+        last_error: schedule.last_error.clone(),
+    }
+}
+
+/// Keep the outcome of a successful fetch with its data source
+pub async fn record_fetch(
+    data_source_service: &RwLock<DataSourceService>,
+    summary: &fetcher::FetchSummary,
+) {
+    if summary.error.is_some() {
+        return;
+    }
+    let service = data_source_service.read().await;
+    if let Err(e) = service
+        .update_fetch_stats(&summary.source, summary.records)
+        .await
+    {
+        tracing::warn!("Failed to record fetch of {}: {}", summary.source, e);
+    }
+}
+
+/// Fetch all enabled data sources through the fetch queue and store their records
+/// Returns a per-source summary (record count, duration, error)
+pub async fn fetch_enabled(
+    data_source_service: &RwLock<DataSourceService>,
+    fetch_queue: &FetchQueue,
+) -> Result<Vec<fetcher::FetchSummary>, AppError> {
+    let sources = {
+        let service = data_source_service.read().await;
+        service.get_enabled_data_sources().await?
+    };
+
+    tracing::info!("Fetching {} enabled data sources", sources.len());
+
+    let summaries = futures::future::join_all(
+        sources
+            .iter()
+            .map(|s| fetch_queue.run(s.to_adapter_config(), FetchPriority::Manual)),
+    )
+    .await;
+    for summary in &summaries {
+        record_fetch(data_source_service, summary).await;
+    }
+
+    let failed = summaries.iter().filter(|s| s.error.is_some()).count();
+    tracing::info!(
+        "Refreshed {} data sources ({} failed)",
+        summaries.len(),
+        failed
+    );
+
+    Ok(summaries)
+}
+
+/// Job starting the fetches of due sources, checking every 15 seconds by default
+pub fn job(
+    scheduler: Arc<Mutex<PollingScheduler>>,
+    data_source_service: Arc<RwLock<DataSourceService>>,
+    fetch_queue: FetchQueue,
+) -> Job {
+    Job::new(
+        "polling",
+        "Fetch data sources whose polling interval elapsed",
+        Schedule::every(TICK_INTERVAL),
+        move || {
+            let scheduler = scheduler.clone();
+            let data_source_service = data_source_service.clone();
+            let fetch_queue = fetch_queue.clone();
+            async move {
+                let sources = data_source_service
+                    .read()
+                    .await
+                    .get_enabled_data_sources()
+                    .await?;
+
+                let configs = sources
+                    .iter()
+                    .filter(|s| s.auto_refresh)
+                    .map(|s| s.to_adapter_config())
+                    .collect();
+
+                let due = scheduler.lock().await.take_due(configs, Utc::now());
+                let started = due.len();
+
+                // Fetches finish in the background; their outcome shows in the polling status
+                for config in due {
+                    tracing::info!("Scheduled fetch for source: {}", config.source);
+
+                    let scheduler = scheduler.clone();
+                    let fetch_queue = fetch_queue.clone();
+                    let data_source_service = data_source_service.clone();
+
+                    tokio::spawn(async move {
+                        let summary = fetch_queue.run(config, FetchPriority::Scheduled).await;
+                        scheduler.lock().await.finish(&summary);
+                        record_fetch(&data_source_service, &summary).await;
+                    });
+                }
+                Ok(format!("Started {} fetches", started))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(source: &str, interval: Option<u64>) -> AdapterConfig {
+        let mut config = AdapterConfig::new("rest_api", source, "https://example.com");
+        config.polling_interval = interval;
+        config
+    }
+
+    #[test]
+    fn test_take_due_skips_paused_and_in_flight() {
+        let mut scheduler = PollingScheduler::new();
+        let now = Utc::now();
+
+        scheduler.pause("paused");
+        let due = scheduler.take_due(
+            vec![
+                config("a", Some(60)),
+                config("paused", Some(60)),
+                config("manual", None),
+            ],
+            now,
+        );
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].source, "a");
+
+        // "a" is still in flight, so it must not be scheduled again
+        let due = scheduler.take_due(vec![config("a", Some(60))], now);
+        assert!(due.is_empty());
+    }
+
+    #[test]
+    fn test_take_due_honors_interval() {
+        let mut scheduler = PollingScheduler::new();
+        let now = Utc::now();
+
+        scheduler.take_due(vec![config("a", Some(60))], now);
+        scheduler.finish(&fetcher::FetchSummary {
+            source: "a".to_string(),
+            adapter_type: "rest_api".to_string(),
+            records: 1,
+            duration_ms: 10,
+            error: None,
+        });
+
+        let last_run = scheduler.sources["a"].last_run.unwrap();
+        let due = scheduler.take_due(
+            vec![config("a", Some(60))],
+            last_run + chrono::Duration::seconds(30),
+        );
+        assert!(due.is_empty());
+
+        let due = scheduler.take_due(
+            vec![config("a", Some(60))],
+            last_run + chrono::Duration::seconds(61),
+        );
+        assert_eq!(due.len(), 1);
+    }
+}
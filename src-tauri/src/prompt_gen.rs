@@ -17,6 +17,32 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::Thing;
 
+pub mod archive;
+pub mod chains;
+pub mod duplicate;
+pub mod examples;
+pub mod formats;
+pub mod graph;
+pub mod history;
+pub mod locale;
+pub mod matrix;
+pub mod namespaces;
+pub mod package_import;
+pub mod partial_export;
+pub mod presets;
+pub mod record_variables;
+pub mod references;
+pub mod registry;
+pub mod renderer;
+pub mod revisions;
+pub mod search;
+pub mod tags;
+pub mod template_import;
+pub mod template_migration;
+pub mod usage;
+pub mod validation;
+pub mod variables;
+
 // ============================================
 // DATA STRUCTURES
 // ============================================
@@ -52,6 +78,9 @@ pub struct PromptTemplate {
     pub variables: Vec<serde_json::Value>,
     pub tags: Vec<String>,
     pub examples: Vec<serde_json::Value>,
+    /// Key of the section this template was migrated to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub migrated_to: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -81,10 +110,28 @@ pub struct PromptSection {
     /// Example renderings (only used when is_entry_point=true)
     #[serde(default)]
     pub examples: Vec<serde_json::Value>,
+    /// Locale for plural rules and list conjunctions, e.g. "de" (only used when is_entry_point=true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Negative prompt, rendered alongside `content` (text2image workflows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_content: Option<serde_json::Value>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl PromptSection {
+    /// The section's content trees with their field names: `content`, then
+    /// `negative_content` when set
+    pub fn content_fields(&self) -> impl Iterator<Item = (&'static str, &serde_json::Value)> {
+        std::iter::once(("content", &self.content)).chain(
+            self.negative_content
+                .iter()
+                .map(|negative| ("negative_content", negative)),
+        )
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SeparatorSet {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -128,7 +175,7 @@ pub struct PromptTag {
     pub updated_at: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PackageExport {
     pub format_version: String,
     pub exported_at: String,
@@ -160,18 +207,22 @@ fn extract_id(thing: &Option<Thing>) -> Option<String> {
 
 pub mod commands {
     use super::*;
+    use crate::audit::Actor;
+    use crate::db::Database;
+    use crate::error::{ApiError, AppError};
+    use crate::i18n;
     use crate::AppState;
 
     #[tauri::command]
     pub async fn get_prompt_packages(
         state: tauri::State<'_, AppState>,
-    ) -> Result<Vec<PromptPackage>, String> {
-        let db = state.database.lock().await;
+    ) -> Result<Vec<PromptPackage>, ApiError> {
+        let db = state.database.lock().await.clone();
         let packages: Vec<PromptPackage> = db
             .db
             .select("prompt_packages")
             .await
-            .map_err(|e| format!("Failed to get packages: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to get packages: {}", e)))?;
         Ok(packages)
     }
 
@@ -179,13 +230,13 @@ pub mod commands {
     pub async fn get_prompt_package(
         id: String,
         state: tauri::State<'_, AppState>,
-    ) -> Result<Option<PromptPackage>, String> {
-        let db = state.database.lock().await;
+    ) -> Result<Option<PromptPackage>, ApiError> {
+        let db = state.database.lock().await.clone();
         let package: Option<PromptPackage> = db
             .db
             .select(("prompt_packages", &id))
             .await
-            .map_err(|e| format!("Failed to get package: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to get package: {}", e)))?;
         Ok(package)
     }
 
@@ -193,21 +244,23 @@ pub mod commands {
     pub async fn create_prompt_package(
         mut package: PromptPackage,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptPackage, String> {
+    ) -> Result<PromptPackage, ApiError> {
         let db = state.database.lock().await;
+        namespaces::check_namespaces(&db, &package, None).await?;
         let timestamp = get_timestamp();
         package.created_at = timestamp.clone();
         package.updated_at = timestamp;
         package.id = None;
 
         let created: Option<PromptPackage> = db
-            .db
-            .create("prompt_packages")
-            .content(package)
+            .as_actor(
+                Actor::command("create_prompt_package"),
+                db.create_row("prompt_packages", package),
+            )
             .await
-            .map_err(|e| format!("Failed to create package: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create package: {}", e)))?;
 
-        created.ok_or_else(|| "Failed to create package".to_string())
+        created.ok_or_else(|| AppError::Database("Failed to create package".to_string()).into())
     }
 
     #[tauri::command]
@@ -215,84 +268,51 @@ pub mod commands {
         id: String,
         mut package: PromptPackage,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptPackage, String> {
+    ) -> Result<PromptPackage, ApiError> {
         let db = state.database.lock().await;
+        namespaces::check_namespaces(&db, &package, Some(&id)).await?;
         package.updated_at = get_timestamp();
 
         let result: Option<PromptPackage> = db
-            .db
-            .update(("prompt_packages", &id))
-            .content(package)
+            .as_actor(
+                Actor::command("update_prompt_package"),
+                db.update_row("prompt_packages", &id, package),
+            )
             .await
-            .map_err(|e| format!("Failed to update package: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to update package: {}", e)))?;
 
-        result.ok_or_else(|| "Package not found".to_string())
+        result.ok_or_else(|| AppError::NotFound("Package not found".to_string()).into())
     }
 
     #[tauri::command]
     pub async fn delete_prompt_package(
         id: String,
         state: tauri::State<'_, AppState>,
-    ) -> Result<(), String> {
+    ) -> Result<(), ApiError> {
         let db = state.database.lock().await;
 
-        // Cascade delete all related data
-        // Delete sections
-        let _: Vec<PromptSection> = db
-            .db
-            .query("DELETE FROM prompt_sections WHERE package_id = $pkg_id")
-            .bind(("pkg_id", id.clone()))
-            .await
-            .map_err(|e| format!("Failed to delete sections: {}", e))?
-            .take(0)
-            .unwrap_or_default();
-
-        // Delete templates
-        let _: Vec<PromptTemplate> = db
-            .db
-            .query("DELETE FROM prompt_templates WHERE package_id = $pkg_id")
-            .bind(("pkg_id", id.clone()))
-            .await
-            .map_err(|e| format!("Failed to delete templates: {}", e))?
-            .take(0)
-            .unwrap_or_default();
-
-        // Delete separator sets
-        let _: Vec<SeparatorSet> = db
-            .db
-            .query("DELETE FROM prompt_separator_sets WHERE package_id = $pkg_id")
-            .bind(("pkg_id", id.clone()))
-            .await
-            .map_err(|e| format!("Failed to delete separator sets: {}", e))?
-            .take(0)
-            .unwrap_or_default();
-
-        // Delete data types
-        let _: Vec<PromptDataType> = db
-            .db
-            .query("DELETE FROM prompt_data_types WHERE package_id = $pkg_id")
-            .bind(("pkg_id", id.clone()))
-            .await
-            .map_err(|e| format!("Failed to delete data types: {}", e))?
-            .take(0)
-            .unwrap_or_default();
-
-        // Delete tags
-        let _: Vec<PromptTag> = db
-            .db
-            .query("DELETE FROM prompt_tags WHERE package_id = $pkg_id")
-            .bind(("pkg_id", id.clone()))
-            .await
-            .map_err(|e| format!("Failed to delete tags: {}", e))?
-            .take(0)
-            .unwrap_or_default();
-
-        // Finally delete the package itself
-        let _: Option<PromptPackage> = db
-            .db
-            .delete(("prompt_packages", &id))
-            .await
-            .map_err(|e| format!("Failed to delete package: {}", e))?;
+        // Keep everything in the undo journal, then cascade delete all related
+        // data and the package itself, atomically
+        let statements = format!(
+            "{}
+             DELETE prompt_sections WHERE package_id = $pkg_id;
+             DELETE prompt_templates WHERE package_id = $pkg_id;
+             DELETE prompt_separator_sets WHERE package_id = $pkg_id;
+             DELETE prompt_data_types WHERE package_id = $pkg_id;
+             DELETE prompt_tags WHERE package_id = $pkg_id;
+             DELETE prompt_section_revisions WHERE package_id = $pkg_id;
+             DELETE prompt_presets WHERE package_id = $pkg_id;
+             DELETE prompt_chains WHERE package_id = $pkg_id;
+             DELETE prompt_chain_runs WHERE package_id = $pkg_id;
+             DELETE type::thing('prompt_packages', $pkg_id);",
+            crate::undo::journal_prompt_package_statement()
+        );
+        db.as_actor(
+            Actor::command("delete_prompt_package"),
+            db.transaction(&statements, ("pkg_id", id)),
+        )
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete package: {}", e)))?;
         Ok(())
     }
 
@@ -300,34 +320,51 @@ pub mod commands {
     pub async fn get_prompt_templates(
         package_id: Option<String>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<Vec<PromptTemplate>, String> {
-        let db = state.database.lock().await;
+    ) -> Result<Vec<PromptTemplate>, ApiError> {
+        let db = state.database.lock().await.clone();
 
+        // Migrated templates live on as sections
         let templates: Vec<PromptTemplate> = if let Some(pkg_id) = package_id {
             let mut result = db
-                .db
-                .query("SELECT * FROM prompt_templates WHERE package_id = $package_id")
+                .query(
+                    "SELECT * FROM prompt_templates
+                     WHERE package_id = $package_id AND migrated_to = NONE",
+                )
                 .bind(("package_id", pkg_id))
                 .await
-                .map_err(|e| format!("Failed to query templates: {}", e))?;
+                .map_err(|e| AppError::Database(format!("Failed to query templates: {}", e)))?;
             result
                 .take(0)
-                .map_err(|e| format!("Failed to extract templates: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to extract templates: {}", e)))?
         } else {
-            db.db
-                .select("prompt_templates")
+            let mut result = db
+                .query("SELECT * FROM prompt_templates WHERE migrated_to = NONE")
                 .await
-                .map_err(|e| format!("Failed to get templates: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to get templates: {}", e)))?;
+            result
+                .take(0)
+                .map_err(|e| AppError::Database(format!("Failed to extract templates: {}", e)))?
         };
 
         Ok(templates)
     }
 
+    /// Convert remaining deprecated templates into entry-point sections
+    #[tauri::command]
+    pub async fn migrate_prompt_templates(
+        state: tauri::State<'_, AppState>,
+    ) -> Result<template_migration::TemplateMigration, ApiError> {
+        let db = state.database.lock().await;
+        template_migration::migrate_templates(&db)
+            .await
+            .map_err(ApiError::from)
+    }
+
     #[tauri::command]
     pub async fn create_prompt_template(
         mut template: PromptTemplate,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptTemplate, String> {
+    ) -> Result<PromptTemplate, ApiError> {
         let db = state.database.lock().await;
         let timestamp = get_timestamp();
         template.created_at = timestamp.clone();
@@ -335,13 +372,14 @@ pub mod commands {
         template.id = None;
 
         let created: Option<PromptTemplate> = db
-            .db
-            .create("prompt_templates")
-            .content(template)
+            .as_actor(
+                Actor::command("create_prompt_template"),
+                db.create_row("prompt_templates", template),
+            )
             .await
-            .map_err(|e| format!("Failed to create template: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create template: {}", e)))?;
 
-        created.ok_or_else(|| "Failed to create template".to_string())
+        created.ok_or_else(|| AppError::Database("Failed to create template".to_string()).into())
     }
 
     #[tauri::command]
@@ -349,31 +387,34 @@ pub mod commands {
         id: String,
         mut template: PromptTemplate,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptTemplate, String> {
+    ) -> Result<PromptTemplate, ApiError> {
         let db = state.database.lock().await;
         template.updated_at = get_timestamp();
 
         let result: Option<PromptTemplate> = db
-            .db
-            .update(("prompt_templates", &id))
-            .content(template)
+            .as_actor(
+                Actor::command("update_prompt_template"),
+                db.update_row("prompt_templates", &id, template),
+            )
             .await
-            .map_err(|e| format!("Failed to update template: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to update template: {}", e)))?;
 
-        result.ok_or_else(|| "Template not found".to_string())
+        result.ok_or_else(|| AppError::NotFound("Template not found".to_string()).into())
     }
 
     #[tauri::command]
     pub async fn delete_prompt_template(
         id: String,
         state: tauri::State<'_, AppState>,
-    ) -> Result<(), String> {
+    ) -> Result<(), ApiError> {
         let db = state.database.lock().await;
         let _: Option<PromptTemplate> = db
-            .db
-            .delete(("prompt_templates", &id))
+            .as_actor(
+                Actor::command("delete_prompt_template"),
+                db.delete_row("prompt_templates", &id),
+            )
             .await
-            .map_err(|e| format!("Failed to delete template: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to delete template: {}", e)))?;
         Ok(())
     }
 
@@ -381,24 +422,23 @@ pub mod commands {
     pub async fn get_prompt_sections(
         package_id: Option<String>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<Vec<PromptSection>, String> {
-        let db = state.database.lock().await;
+    ) -> Result<Vec<PromptSection>, ApiError> {
+        let db = state.database.lock().await.clone();
 
         let sections: Vec<PromptSection> = if let Some(pkg_id) = package_id {
             let mut result = db
-                .db
                 .query("SELECT * FROM prompt_sections WHERE package_id = $package_id")
                 .bind(("package_id", pkg_id))
                 .await
-                .map_err(|e| format!("Failed to query sections: {}", e))?;
+                .map_err(|e| AppError::Database(format!("Failed to query sections: {}", e)))?;
             result
                 .take(0)
-                .map_err(|e| format!("Failed to extract sections: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to extract sections: {}", e)))?
         } else {
             db.db
                 .select("prompt_sections")
                 .await
-                .map_err(|e| format!("Failed to get sections: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to get sections: {}", e)))?
         };
 
         Ok(sections)
@@ -408,7 +448,7 @@ pub mod commands {
     pub async fn create_prompt_section(
         mut section: PromptSection,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptSection, String> {
+    ) -> Result<PromptSection, ApiError> {
         let db = state.database.lock().await;
         let timestamp = get_timestamp();
         section.created_at = timestamp.clone();
@@ -416,80 +456,538 @@ pub mod commands {
         section.id = None;
 
         let created: Option<PromptSection> = db
-            .db
-            .create("prompt_sections")
-            .content(section)
+            .as_actor(
+                Actor::command("create_prompt_section"),
+                db.create_row("prompt_sections", section),
+            )
             .await
-            .map_err(|e| format!("Failed to create section: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create section: {}", e)))?;
 
-        created.ok_or_else(|| "Failed to create section".to_string())
+        created.ok_or_else(|| AppError::Database("Failed to create section".to_string()).into())
     }
 
     #[tauri::command]
     pub async fn update_prompt_section(
         id: String,
-        mut section: PromptSection,
+        section: PromptSection,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptSection, String> {
+    ) -> Result<PromptSection, ApiError> {
         let db = state.database.lock().await;
-        section.updated_at = get_timestamp();
+        db.as_actor(
+            Actor::command("update_prompt_section"),
+            revisions::save_section(&db, &id, section),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
 
-        let result: Option<PromptSection> = db
-            .db
-            .update(("prompt_sections", &id))
-            .content(section)
+    /// Earlier states of a section, newest first
+    #[tauri::command]
+    pub async fn get_section_revisions(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<Vec<revisions::SectionRevision>, ApiError> {
+        let db = state.database.lock().await.clone();
+        revisions::list_revisions(&db, &id)
             .await
-            .map_err(|e| format!("Failed to update section: {}", e))?;
+            .map_err(ApiError::from)
+    }
 
-        result.ok_or_else(|| "Section not found".to_string())
+    /// Restore a section to an earlier revision (the current state is kept as a new one)
+    #[tauri::command]
+    pub async fn revert_section(
+        id: String,
+        revision: u32,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<PromptSection, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("revert_section"),
+            revisions::revert_section(&db, &id, revision),
+        )
+        .await
+        .map_err(ApiError::from)
     }
 
+    /// Delete a section; refused while other sections reference it unless `force` is set
     #[tauri::command]
     pub async fn delete_prompt_section(
         id: String,
+        force: Option<bool>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<(), String> {
+    ) -> Result<(), ApiError> {
         let db = state.database.lock().await;
-        let _: Option<PromptSection> = db
-            .db
-            .delete(("prompt_sections", &id))
+        if !force.unwrap_or(false) {
+            let library = renderer::PromptLibrary::load(&db).await?;
+            let references = references::section_references(&library, &id);
+            if references.is_referenced() {
+                let names: Vec<String> = references
+                    .referenced_by
+                    .iter()
+                    .map(|r| format!("{}:{}", r.namespace, r.name))
+                    .collect();
+                return Err(AppError::Validation(format!(
+                    "Section is still referenced by {} section(s): {} ({} entry point(s) affected). Delete with force to remove it anyway.",
+                    names.len(),
+                    names.join(", "),
+                    references.affected_entry_points.len()
+                ))
+                .into());
+            }
+        }
+        let delete = async {
+            let _: Option<PromptSection> = db
+                .delete_row("prompt_sections", &id)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to delete section: {}", e)))?;
+            revisions::delete_revisions(&db, &id).await?;
+            usage::delete_usage(&db, &id).await?;
+            presets::delete_section_presets(&db, &id).await
+        };
+        db.as_actor(Actor::command("delete_prompt_section"), delete)
             .await
-            .map_err(|e| format!("Failed to delete section: {}", e))?;
-        Ok(())
+            .map_err(ApiError::from)
     }
 
+    /// Sections, data types and separator sets of a package with the references between them
     #[tauri::command]
-    pub async fn get_separator_sets(
+    pub async fn get_package_graph(
+        package_id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<graph::PackageGraph, ApiError> {
+        let db = state.database.lock().await.clone();
+        let library = renderer::PromptLibrary::load(&db).await?;
+        Ok(graph::package_graph(&library, &package_id))
+    }
+
+    /// Check variable values against a section's definitions and data types
+    #[tauri::command]
+    pub async fn validate_variables(
+        section_id: String,
+        values: serde_json::Map<String, serde_json::Value>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<variables::VariableValidation, ApiError> {
+        let db = state.database.lock().await.clone();
+        let library = renderer::PromptLibrary::load(&db).await?;
+        let section = library
+            .find_section(&section_id)
+            .ok_or_else(|| AppError::NotFound(format!("Section not found: {}", section_id)))?;
+        let errors = variables::validate_variables(&library, section, &values);
+        Ok(variables::VariableValidation {
+            valid: errors.is_empty(),
+            errors,
+        })
+    }
+
+    /// Sections that reference a section, directly and transitively
+    #[tauri::command]
+    pub async fn get_section_references(
+        section_id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<references::SectionReferences, ApiError> {
+        let db = state.database.lock().await.clone();
+        let library = renderer::PromptLibrary::load(&db).await?;
+        if library.find_section(&section_id).is_none() {
+            return Err(AppError::NotFound(format!("Section not found: {}", section_id)).into());
+        }
+        Ok(references::section_references(&library, &section_id))
+    }
+
+    /// Render a section server-side (same AST semantics as the frontend engine)
+    /// The render is recorded in the history with its seed unless `save_history` is false.
+    #[tauri::command]
+    pub async fn render_prompt_section(
+        section_id: String,
+        variables: Option<serde_json::Map<String, serde_json::Value>>,
+        seed: Option<u64>,
+        save_history: Option<bool>,
+        preset_id: Option<String>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<renderer::RenderedSection, ApiError> {
+        let db = state.database.lock().await.clone();
+        let library = renderer::PromptLibrary::load(&db).await?;
+        let mut variables = variables.unwrap_or_default();
+        if let Some(preset_id) = preset_id {
+            let preset = presets::get_preset(&db, &preset_id).await?;
+            let section_key = library
+                .find_section(&section_id)
+                .and_then(|s| extract_id(&s.id));
+            if section_key.as_deref() != Some(preset.section_id.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "Preset '{}' belongs to another section",
+                    preset.name
+                ))
+                .into());
+            }
+            variables = presets::apply_preset(&preset, variables);
+        }
+        // Live values for variables bound to record queries
+        let record_warnings = match library.find_section(&section_id) {
+            Some(section) => {
+                record_variables::resolve_record_variables(&db, section, &mut variables).await
+            }
+            None => Vec::new(),
+        };
+        let mut rendered = renderer::render_section(&library, &section_id, variables, seed)?;
+        rendered.warnings.extend(record_warnings);
+
+        if save_history.unwrap_or(true) {
+            let entry = history::record_render(&db, &rendered).await?;
+            rendered.history_id = Some(entry.id);
+        }
+        usage::record_use(&db, &rendered.section_id).await?;
+        Ok(rendered)
+    }
+
+    /// Render every combination of the axes' values
+    #[tauri::command]
+    pub async fn render_prompt_matrix(
+        section_id: String,
+        axes: Vec<matrix::MatrixAxis>,
+        variables: Option<serde_json::Map<String, serde_json::Value>>,
+        seed: Option<u64>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<matrix::PromptMatrix, ApiError> {
+        let db = state.database.lock().await.clone();
+        let library = renderer::PromptLibrary::load(&db).await?;
+        matrix::render_matrix(
+            &library,
+            &section_id,
+            &axes,
+            variables.unwrap_or_default(),
+            seed,
+        )
+        .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn get_prompt_chains(
         package_id: Option<String>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<Vec<SeparatorSet>, String> {
+    ) -> Result<Vec<chains::PromptChain>, ApiError> {
+        let db = state.database.lock().await.clone();
+        chains::list_chains(&db, package_id)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn create_prompt_chain(
+        chain: chains::PromptChain,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<chains::PromptChain, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("create_prompt_chain"),
+            chains::create_chain(&db, chain),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn update_prompt_chain(
+        id: String,
+        chain: chains::PromptChain,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<chains::PromptChain, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("update_prompt_chain"),
+            chains::update_chain(&db, &id, chain),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn delete_prompt_chain(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<(), ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("delete_prompt_chain"),
+            chains::delete_chain(&db, &id),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    /// Run a chain's steps in order; `responses` holds LLM responses by step id
+    #[tauri::command]
+    pub async fn run_prompt_chain(
+        id: String,
+        variables: Option<serde_json::Map<String, serde_json::Value>>,
+        responses: Option<std::collections::HashMap<String, String>>,
+        seed: Option<u64>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<chains::ChainRun, ApiError> {
         let db = state.database.lock().await;
+        chains::run_chain(
+            &db,
+            &id,
+            variables.unwrap_or_default(),
+            responses.unwrap_or_default(),
+            seed,
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn get_prompt_chain_runs(
+        chain_id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<Vec<chains::ChainRun>, ApiError> {
+        let db = state.database.lock().await.clone();
+        chains::list_runs(&db, &chain_id)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    /// Variable presets saved for an entry point
+    #[tauri::command]
+    pub async fn get_prompt_presets(
+        section_id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<Vec<presets::PromptPreset>, ApiError> {
+        let db = state.database.lock().await.clone();
+        presets::list_presets(&db, &section_id)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn create_prompt_preset(
+        preset: presets::PromptPreset,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<presets::PromptPreset, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("create_prompt_preset"),
+            presets::create_preset(&db, preset),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn update_prompt_preset(
+        id: String,
+        preset: presets::PromptPreset,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<presets::PromptPreset, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("update_prompt_preset"),
+            presets::update_preset(&db, &id, preset),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn delete_prompt_preset(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<(), ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("delete_prompt_preset"),
+            presets::delete_preset(&db, &id),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    /// Star or unstar an entry point
+    #[tauri::command]
+    pub async fn set_section_favorite(
+        section_id: String,
+        favorite: bool,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<(), ApiError> {
+        let db = state.database.lock().await;
+        usage::set_favorite(&db, &section_id, favorite)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn get_favorite_sections(
+        state: tauri::State<'_, AppState>,
+    ) -> Result<Vec<usage::SectionUsage>, ApiError> {
+        let db = state.database.lock().await.clone();
+        usage::favorite_sections(&db).await.map_err(ApiError::from)
+    }
+
+    /// Sections rendered most recently, newest first
+    #[tauri::command]
+    pub async fn get_recent_sections(
+        limit: Option<usize>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<Vec<usage::SectionUsage>, ApiError> {
+        let db = state.database.lock().await.clone();
+        usage::recent_sections(&db, limit.unwrap_or(usage::DEFAULT_RECENT_LIMIT))
+            .await
+            .map_err(ApiError::from)
+    }
+
+    /// Recorded renders, newest first, optionally of one section
+    #[tauri::command]
+    pub async fn get_render_history(
+        section_id: Option<String>,
+        limit: Option<usize>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<Vec<history::RenderHistoryEntry>, ApiError> {
+        let db = state.database.lock().await.clone();
+        history::list_render_history(&db, section_id, limit.unwrap_or(50))
+            .await
+            .map_err(ApiError::from)
+    }
+
+    /// Reproduce a recorded render from its variables and seed
+    #[tauri::command]
+    pub async fn replay_render(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<renderer::RenderedSection, ApiError> {
+        let db = state.database.lock().await;
+        let entry = history::get_render_history_entry(&db, &id).await?;
+        let library = renderer::PromptLibrary::load(&db).await?;
+        history::replay(&library, &entry).map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn delete_prompt_render_history(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<(), ApiError> {
+        let db = state.database.lock().await;
+        history::delete_render_history_entry(&db, &id)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    /// Check one section for broken references and undeclared variables
+    #[tauri::command]
+    pub async fn validate_prompt_section(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<validation::ValidationReport, ApiError> {
+        let library = {
+            let db = state.database.lock().await.clone();
+            renderer::PromptLibrary::load(&db).await?
+        };
+        let section = library
+            .find_section(&id)
+            .ok_or_else(|| AppError::NotFound("Section not found".to_string()))?;
+        Ok(validation::validate_sections(&library, [section]))
+    }
+
+    /// Library for checks over one package's sections (errors if the package doesn't exist)
+    async fn load_package_library(
+        db: &crate::db::Database,
+        package_key: &str,
+    ) -> Result<renderer::PromptLibrary, AppError> {
+        let package: Option<PromptPackage> =
+            db.db
+                .select(("prompt_packages", package_key))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to get package: {}", e)))?;
+        if package.is_none() {
+            return Err(AppError::NotFound("Package not found".to_string()));
+        }
+        renderer::PromptLibrary::load(db).await
+    }
+
+    /// Check every section of a package
+    #[tauri::command]
+    pub async fn validate_prompt_package(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<validation::ValidationReport, ApiError> {
+        let key = id.strip_prefix("prompt_packages:").unwrap_or(&id);
+        let library = load_package_library(&*state.database.lock().await, key).await?;
+        Ok(validation::validate_sections(
+            &library,
+            library.sections.iter().filter(|s| s.package_id == key),
+        ))
+    }
+
+    /// Render a section's examples and compare them with their expected output
+    #[tauri::command]
+    pub async fn run_section_examples(
+        section_id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<examples::ExampleRunReport, ApiError> {
+        let library = {
+            let db = state.database.lock().await.clone();
+            renderer::PromptLibrary::load(&db).await?
+        };
+        let section = library
+            .find_section(&section_id)
+            .ok_or_else(|| AppError::NotFound("Section not found".to_string()))?;
+        Ok(examples::run_examples(&library, [section]))
+    }
+
+    /// Run the examples of every section in a package
+    #[tauri::command]
+    pub async fn run_package_examples(
+        package_id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<examples::ExampleRunReport, ApiError> {
+        let key = package_id
+            .strip_prefix("prompt_packages:")
+            .unwrap_or(&package_id);
+        let library = load_package_library(&*state.database.lock().await, key).await?;
+        Ok(examples::run_examples(
+            &library,
+            library.sections.iter().filter(|s| s.package_id == key),
+        ))
+    }
+
+    #[tauri::command]
+    pub async fn get_separator_sets(
+        package_id: Option<String>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<Vec<SeparatorSet>, ApiError> {
+        let db = state.database.lock().await.clone();
 
         let sets: Vec<SeparatorSet> = if let Some(pkg_id) = package_id {
             let mut result = db
-                .db
                 .query("SELECT * FROM prompt_separator_sets WHERE package_id = $package_id")
                 .bind(("package_id", pkg_id))
                 .await
-                .map_err(|e| format!("Failed to query separator sets: {}", e))?;
-            result
-                .take(0)
-                .map_err(|e| format!("Failed to extract separator sets: {}", e))?
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to query separator sets: {}", e))
+                })?;
+            result.take(0).map_err(|e| {
+                AppError::Database(format!("Failed to extract separator sets: {}", e))
+            })?
         } else {
             db.db
                 .select("prompt_separator_sets")
                 .await
-                .map_err(|e| format!("Failed to get separator sets: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to get separator sets: {}", e)))?
         };
 
         Ok(sets)
     }
 
+    /// Separator sets every package can reference without defining them
+    #[tauri::command]
+    pub async fn get_builtin_separator_sets() -> Result<Vec<SeparatorSet>, ApiError> {
+        Ok(renderer::builtin_separator_sets())
+    }
+
     #[tauri::command]
     pub async fn create_separator_set(
         mut separator_set: SeparatorSet,
         state: tauri::State<'_, AppState>,
-    ) -> Result<SeparatorSet, String> {
+    ) -> Result<SeparatorSet, ApiError> {
+        renderer::check_separator_rules(&separator_set.rules)?;
         let db = state.database.lock().await;
         let timestamp = get_timestamp();
         separator_set.created_at = timestamp.clone();
@@ -497,37 +995,90 @@ pub mod commands {
         separator_set.id = None;
 
         let created: Option<SeparatorSet> = db
-            .db
-            .create("prompt_separator_sets")
-            .content(separator_set)
+            .as_actor(
+                Actor::command("create_separator_set"),
+                db.create_row("prompt_separator_sets", separator_set),
+            )
             .await
-            .map_err(|e| format!("Failed to create separator set: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create separator set: {}", e)))?;
 
-        created.ok_or_else(|| "Failed to create separator set".to_string())
+        created
+            .ok_or_else(|| AppError::Database("Failed to create separator set".to_string()).into())
+    }
+
+    #[tauri::command]
+    pub async fn update_separator_set(
+        id: String,
+        mut separator_set: SeparatorSet,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<SeparatorSet, ApiError> {
+        if renderer::is_builtin_separator_set(&id) {
+            return Err(AppError::Validation(format!(
+                "Built-in separator set '{}' cannot be changed",
+                id
+            ))
+            .into());
+        }
+        renderer::check_separator_rules(&separator_set.rules)?;
+        let db = state.database.lock().await;
+        separator_set.updated_at = get_timestamp();
+        separator_set.id = None;
+
+        let result: Option<SeparatorSet> = db
+            .as_actor(
+                Actor::command("update_separator_set"),
+                db.update_row("prompt_separator_sets", &id, separator_set),
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update separator set: {}", e)))?;
+
+        result.ok_or_else(|| AppError::NotFound("Separator set not found".to_string()).into())
+    }
+
+    #[tauri::command]
+    pub async fn delete_separator_set(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<(), ApiError> {
+        if renderer::is_builtin_separator_set(&id) {
+            return Err(AppError::Validation(format!(
+                "Built-in separator set '{}' cannot be deleted",
+                id
+            ))
+            .into());
+        }
+        let db = state.database.lock().await;
+        let _: Option<SeparatorSet> = db
+            .as_actor(
+                Actor::command("delete_separator_set"),
+                db.delete_row("prompt_separator_sets", &id),
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete separator set: {}", e)))?;
+        Ok(())
     }
 
     #[tauri::command]
     pub async fn get_prompt_data_types(
         package_id: Option<String>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<Vec<PromptDataType>, String> {
-        let db = state.database.lock().await;
+    ) -> Result<Vec<PromptDataType>, ApiError> {
+        let db = state.database.lock().await.clone();
 
         let types: Vec<PromptDataType> = if let Some(pkg_id) = package_id {
             let mut result = db
-                .db
                 .query("SELECT * FROM prompt_data_types WHERE package_id = $package_id")
                 .bind(("package_id", pkg_id))
                 .await
-                .map_err(|e| format!("Failed to query data types: {}", e))?;
+                .map_err(|e| AppError::Database(format!("Failed to query data types: {}", e)))?;
             result
                 .take(0)
-                .map_err(|e| format!("Failed to extract data types: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to extract data types: {}", e)))?
         } else {
             db.db
                 .select("prompt_data_types")
                 .await
-                .map_err(|e| format!("Failed to get data types: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to get data types: {}", e)))?
         };
 
         Ok(types)
@@ -537,7 +1088,7 @@ pub mod commands {
     pub async fn create_prompt_data_type(
         mut data_type: PromptDataType,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptDataType, String> {
+    ) -> Result<PromptDataType, ApiError> {
         let db = state.database.lock().await;
         let timestamp = get_timestamp();
         data_type.created_at = timestamp.clone();
@@ -545,37 +1096,116 @@ pub mod commands {
         data_type.id = None;
 
         let created: Option<PromptDataType> = db
-            .db
-            .create("prompt_data_types")
-            .content(data_type)
+            .as_actor(
+                Actor::command("create_prompt_data_type"),
+                db.create_row("prompt_data_types", data_type),
+            )
             .await
-            .map_err(|e| format!("Failed to create data type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create data type: {}", e)))?;
 
-        created.ok_or_else(|| "Failed to create data type".to_string())
+        created.ok_or_else(|| AppError::Database("Failed to create data type".to_string()).into())
+    }
+
+    /// Update a data type; a rename must not orphan the sections using it
+    #[tauri::command]
+    pub async fn update_prompt_data_type(
+        id: String,
+        mut data_type: PromptDataType,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<PromptDataType, ApiError> {
+        let db = state.database.lock().await;
+        let key = id.strip_prefix("prompt_data_types:").unwrap_or(&id);
+        let mut library = renderer::PromptLibrary::load(&db).await?;
+        let users = references::data_type_references(&library, key);
+
+        // Resolve the current references against the updated data type
+        let mut updated = data_type.clone();
+        updated.id = Some(Thing::from(("prompt_data_types", key)));
+        library
+            .data_types
+            .retain(|t| extract_id(&t.id).as_deref() != Some(key));
+        library.data_types.push(updated);
+        let broken: Vec<String> = users
+            .iter()
+            .filter(|r| {
+                library
+                    .resolve_data_type(&r.reference)
+                    .and_then(|t| extract_id(&t.id))
+                    .as_deref()
+                    != Some(key)
+            })
+            .map(|r| format!("{} ({})", r.name, r.reference))
+            .collect();
+        if !broken.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Renaming the data type would break references in: {}",
+                broken.join(", ")
+            ))
+            .into());
+        }
+
+        data_type.updated_at = get_timestamp();
+        data_type.id = None;
+        let result: Option<PromptDataType> = db
+            .as_actor(
+                Actor::command("update_prompt_data_type"),
+                db.update_row("prompt_data_types", key, data_type),
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update data type: {}", e)))?;
+
+        result.ok_or_else(|| AppError::NotFound("Data type not found".to_string()).into())
+    }
+
+    /// Delete a data type no section uses
+    #[tauri::command]
+    pub async fn delete_prompt_data_type(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<(), ApiError> {
+        let db = state.database.lock().await;
+        let key = id.strip_prefix("prompt_data_types:").unwrap_or(&id);
+        let library = renderer::PromptLibrary::load(&db).await?;
+        let users = references::data_type_references(&library, key);
+        if !users.is_empty() {
+            let mut names: Vec<&str> = users.iter().map(|r| r.name.as_str()).collect();
+            names.dedup();
+            return Err(
+                AppError::Validation(format!("Data type is used by {}", names.join(", "))).into(),
+            );
+        }
+
+        let _: Option<PromptDataType> = db
+            .as_actor(
+                Actor::command("delete_prompt_data_type"),
+                db.delete_row("prompt_data_types", key),
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete data type: {}", e)))?;
+        Ok(())
     }
 
     #[tauri::command]
     pub async fn get_prompt_tags(
         package_id: Option<String>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<Vec<PromptTag>, String> {
-        let db = state.database.lock().await;
+    ) -> Result<Vec<PromptTag>, ApiError> {
+        let db = state.database.lock().await.clone();
 
         let tags: Vec<PromptTag> = if let Some(pkg_id) = package_id {
             let mut result = db
-                .db
                 .query("SELECT * FROM prompt_tags WHERE package_id = $package_id")
                 .bind(("package_id", pkg_id))
                 .await
-                .map_err(|e| format!("Failed to query tags: {}", e))?;
+                .map_err(|e| AppError::Database(format!("Failed to query tags: {}", e)))?;
             result
                 .take(0)
-                .map_err(|e| format!("Failed to extract tags: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to extract tags: {}", e)))?
         } else {
             db.db
                 .select("prompt_tags")
                 .await
-                .map_err(|e| format!("Failed to get tags: {}", e))?
+                .map_err(|e| AppError::Database(format!("Failed to get tags: {}", e)))?
         };
 
         Ok(tags)
@@ -585,7 +1215,7 @@ pub mod commands {
     pub async fn create_prompt_tag(
         mut tag: PromptTag,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PromptTag, String> {
+    ) -> Result<PromptTag, ApiError> {
         let db = state.database.lock().await;
         let timestamp = get_timestamp();
         tag.created_at = timestamp.clone();
@@ -593,67 +1223,196 @@ pub mod commands {
         tag.id = None;
 
         let created: Option<PromptTag> = db
-            .db
-            .create("prompt_tags")
-            .content(tag)
+            .as_actor(
+                Actor::command("create_prompt_tag"),
+                db.create_row("prompt_tags", tag),
+            )
             .await
-            .map_err(|e| format!("Failed to create tag: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create tag: {}", e)))?;
+
+        created.ok_or_else(|| AppError::Database("Failed to create tag".to_string()).into())
+    }
+
+    /// Update a tag; renaming it also renames it on sections and child tags
+    #[tauri::command]
+    pub async fn update_prompt_tag(
+        id: String,
+        tag: PromptTag,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<PromptTag, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("update_prompt_tag"),
+            tags::update_tag(&db, &id, tag),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    /// Delete a tag and remove it from the package's sections
+    #[tauri::command]
+    pub async fn delete_prompt_tag(
+        id: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<tags::TagChanges, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("delete_prompt_tag"),
+            tags::delete_tag(&db, &id),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    #[tauri::command]
+    pub async fn rename_prompt_tag(
+        package_id: String,
+        old: String,
+        new: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<tags::TagChanges, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("rename_prompt_tag"),
+            tags::rename_tag(&db, &package_id, &old, &new),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
+
+    /// Add or remove a tag on several sections at once
+    #[tauri::command]
+    pub async fn tag_prompt_sections(
+        section_ids: Vec<String>,
+        tag: String,
+        action: tags::TagAction,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<tags::TagChanges, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("tag_prompt_sections"),
+            tags::tag_sections(&db, &section_ids, &tag, action),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
 
-        created.ok_or_else(|| "Failed to create tag".to_string())
+    /// Result of `export_prompt_package`: the importable JSON structure, or a
+    /// text document for the other formats
+    #[derive(Debug, Serialize)]
+    #[serde(untagged)]
+    pub enum PackageExportOutput {
+        Json(Box<PackageExport>),
+        Text(formats::TextExport),
     }
 
     #[tauri::command]
     pub async fn export_prompt_package(
         package_id: String,
+        format: Option<formats::ExportFormat>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<PackageExport, String> {
+    ) -> Result<PackageExportOutput, ApiError> {
+        let db = state.database.lock().await.clone();
+        let export = load_package_export(&db, &package_id).await?;
+
+        match format {
+            None | Some(formats::ExportFormat::Json) => {
+                Ok(PackageExportOutput::Json(Box::new(export)))
+            }
+            Some(format) => formats::export_text(&export, format)
+                .map(PackageExportOutput::Text)
+                .map_err(ApiError::from),
+        }
+    }
+
+    /// Export selected sections with the fragments, separator sets, data types
+    /// and tags they depend on
+    #[tauri::command]
+    pub async fn export_prompt_sections(
+        ids: Vec<String>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<PackageExport, ApiError> {
+        let db = state.database.lock().await.clone();
+        partial_export::export_sections(&db, &ids)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    /// Write a package as a single `.promptpkg` archive with a checksummed manifest
+    #[tauri::command]
+    pub async fn export_prompt_package_archive(
+        package_id: String,
+        path: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<archive::ArchiveManifest, ApiError> {
+        let db = state.database.lock().await.clone();
+        let export = load_package_export(&db, &package_id).await?;
+        archive::write_archive_file(&export, std::path::Path::new(&path)).map_err(ApiError::from)
+    }
+
+    /// Verify a `.promptpkg` archive and import the package it contains
+    #[tauri::command]
+    pub async fn import_prompt_package_archive(
+        path: String,
+        mode: Option<package_import::ImportMode>,
+        new_namespace: Option<String>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<package_import::ImportSummary, ApiError> {
+        let (_, export) = archive::read_archive_file(std::path::Path::new(&path))?;
         let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("import_prompt_package_archive"),
+            package_import::import_package(&db, export, mode.unwrap_or_default(), new_namespace),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
 
+    async fn load_package_export(
+        db: &crate::db::Database,
+        package_id: &str,
+    ) -> Result<PackageExport, AppError> {
+        let package_id = package_id.to_string();
         let package: PromptPackage = db
             .db
             .select(("prompt_packages", &package_id))
             .await
-            .map_err(|e| format!("Failed to get package: {}", e))?
-            .ok_or("Package not found")?;
+            .map_err(|e| AppError::Database(format!("Failed to get package: {}", e)))?
+            .ok_or_else(|| AppError::NotFound("Package not found".to_string()))?;
 
         let mut result = db
-            .db
-            .query("SELECT * FROM prompt_templates WHERE package_id = $id")
+            .query("SELECT * FROM prompt_templates WHERE package_id = $id AND migrated_to = NONE")
             .bind(("id", package_id.clone()))
             .await
-            .map_err(|e| format!("Failed to get templates: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to get templates: {}", e)))?;
         let templates: Vec<PromptTemplate> = result.take(0).unwrap_or_default();
 
         let mut result = db
-            .db
             .query("SELECT * FROM prompt_sections WHERE package_id = $id")
             .bind(("id", package_id.clone()))
             .await
-            .map_err(|e| format!("Failed to get sections: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to get sections: {}", e)))?;
         let sections: Vec<PromptSection> = result.take(0).unwrap_or_default();
 
         let mut result = db
-            .db
             .query("SELECT * FROM prompt_separator_sets WHERE package_id = $id")
             .bind(("id", package_id.clone()))
             .await
-            .map_err(|e| format!("Failed to get separator sets: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to get separator sets: {}", e)))?;
         let separator_sets: Vec<SeparatorSet> = result.take(0).unwrap_or_default();
 
         let mut result = db
-            .db
             .query("SELECT * FROM prompt_data_types WHERE package_id = $id")
             .bind(("id", package_id.clone()))
             .await
-            .map_err(|e| format!("Failed to get data types: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to get data types: {}", e)))?;
         let data_types: Vec<PromptDataType> = result.take(0).unwrap_or_default();
 
         let mut result = db
-            .db
             .query("SELECT * FROM prompt_tags WHERE package_id = $id")
             .bind(("id", package_id.clone()))
             .await
-            .map_err(|e| format!("Failed to get tags: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to get tags: {}", e)))?;
         let tags: Vec<PromptTag> = result.take(0).unwrap_or_default();
 
         Ok(PackageExport {
@@ -669,99 +1428,91 @@ pub mod commands {
     }
 
     #[tauri::command]
-    pub async fn import_prompt_package(
-        export_data: PackageExport,
+    pub async fn search_prompt_content(
+        query: String,
+        filters: Option<search::PromptSearchFilters>,
         state: tauri::State<'_, AppState>,
-    ) -> Result<String, String> {
-        let db = state.database.lock().await;
-        let timestamp = get_timestamp();
-
-        let mut package = export_data.package;
-        package.created_at = timestamp.clone();
-        package.updated_at = timestamp.clone();
-        package.id = None;
-
-        let created_package: Option<PromptPackage> = db
+    ) -> Result<Vec<search::PromptSearchHit>, ApiError> {
+        let db = state.database.lock().await.clone();
+        let packages: Vec<PromptPackage> = db
             .db
-            .create("prompt_packages")
-            .content(package)
+            .select("prompt_packages")
             .await
-            .map_err(|e| format!("Failed to import package: {}", e))?;
-
-        let pkg = created_package.ok_or("Failed to import package")?;
-        let package_id = extract_id(&pkg.id).ok_or("Failed to get created package ID")?;
-
-        for mut template in export_data.templates {
-            template.id = None;
-            template.package_id = package_id.clone();
-            template.created_at = timestamp.clone();
-            template.updated_at = timestamp.clone();
-
-            let _: Option<PromptTemplate> = db
-                .db
-                .create("prompt_templates")
-                .content(template)
-                .await
-                .map_err(|e| format!("Failed to import template: {}", e))?;
-        }
-
-        for mut section in export_data.sections {
-            section.id = None;
-            section.package_id = package_id.clone();
-            section.created_at = timestamp.clone();
-            section.updated_at = timestamp.clone();
-
-            let _: Option<PromptSection> =
-                db.db
-                    .create("prompt_sections")
-                    .content(section)
-                    .await
-                    .map_err(|e| format!("Failed to import section: {}", e))?;
-        }
-
-        for mut set in export_data.separator_sets {
-            set.id = None;
-            set.package_id = package_id.clone();
-            set.created_at = timestamp.clone();
-            set.updated_at = timestamp.clone();
-
-            let _: Option<SeparatorSet> = db
-                .db
-                .create("prompt_separator_sets")
-                .content(set)
-                .await
-                .map_err(|e| format!("Failed to import separator set: {}", e))?;
-        }
-
-        for mut dt in export_data.data_types {
-            dt.id = None;
-            dt.package_id = package_id.clone();
-            dt.created_at = timestamp.clone();
-            dt.updated_at = timestamp.clone();
+            .map_err(|e| AppError::Database(format!("Failed to get packages: {}", e)))?;
+        let sections: Vec<PromptSection> = db
+            .db
+            .select("prompt_sections")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get sections: {}", e)))?;
+        Ok(search::search_prompt_content(
+            &packages,
+            &sections,
+            &query,
+            &filters.unwrap_or_default(),
+        ))
+    }
 
-            let _: Option<PromptDataType> = db
-                .db
-                .create("prompt_data_types")
-                .content(dt)
-                .await
-                .map_err(|e| format!("Failed to import data type: {}", e))?;
-        }
+    /// Fork a package (with all its records) under a new namespace
+    #[tauri::command]
+    pub async fn duplicate_prompt_package(
+        id: String,
+        new_namespace: String,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<PromptPackage, ApiError> {
+        let db = state.database.lock().await;
+        let key = id.strip_prefix("prompt_packages:").unwrap_or(&id);
+        let export = load_package_export(&db, key).await?;
+        db.as_actor(
+            Actor::command("duplicate_prompt_package"),
+            duplicate::duplicate_package(&db, export, &new_namespace),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
 
-        for mut tag in export_data.tags {
-            tag.id = None;
-            tag.package_id = package_id.clone();
-            tag.created_at = timestamp.clone();
-            tag.updated_at = timestamp.clone();
+    /// Parse Jinja/Handlebars text into draft sections (not saved)
+    #[tauri::command]
+    pub async fn import_templates_from_text(
+        format: formats::TemplateDialect,
+        content: String,
+    ) -> Result<template_import::TemplateImport, ApiError> {
+        template_import::import_templates(format, &content).map_err(ApiError::from)
+    }
 
-            let _: Option<PromptTag> = db
-                .db
-                .create("prompt_tags")
-                .content(tag)
-                .await
-                .map_err(|e| format!("Failed to import tag: {}", e))?;
-        }
+    /// Import a package export; re-importing an installed namespace upgrades it in place
+    #[tauri::command]
+    pub async fn import_prompt_package(
+        export_data: PackageExport,
+        mode: Option<package_import::ImportMode>,
+        new_namespace: Option<String>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<package_import::ImportSummary, ApiError> {
+        let db = state.database.lock().await;
+        db.as_actor(
+            Actor::command("import_prompt_package"),
+            package_import::import_package(
+                &db,
+                export_data,
+                mode.unwrap_or_default(),
+                new_namespace,
+            ),
+        )
+        .await
+        .map_err(ApiError::from)
+    }
 
-        Ok(package_id)
+    /// List the packages of a remote registry index with their local status,
+    /// installing or updating the namespaces given in `install` first
+    #[tauri::command]
+    pub async fn sync_prompt_registry(
+        url: String,
+        install: Option<Vec<String>>,
+        state: tauri::State<'_, AppState>,
+    ) -> Result<registry::RegistrySync, ApiError> {
+        let db = state.database.lock().await.clone();
+        registry::sync_registry(&db, &url, &install.unwrap_or_default())
+            .await
+            .map_err(ApiError::from)
     }
 
     /// Seed the database with example packages for demonstration
@@ -769,18 +1520,22 @@ pub mod commands {
     #[tauri::command]
     pub async fn seed_example_packages(
         state: tauri::State<'_, AppState>,
-    ) -> Result<String, String> {
+    ) -> Result<String, ApiError> {
         let db = state.database.lock().await;
+        seed_examples(&db).await.map_err(ApiError::from)
+    }
+
+    /// Create the example packages (replacing existing ones)
+    pub async fn seed_examples(db: &Database) -> Result<String, AppError> {
         let timestamp = get_timestamp();
 
         // Check if examples already exist and delete them
         let existing: Vec<PromptPackage> = db
-            .db
             .query("SELECT * FROM prompt_packages WHERE namespace = 'examples'")
             .await
-            .map_err(|e| format!("Failed to check existing: {}", e))?
+            .map_err(|e| AppError::Database(format!("Failed to check existing: {}", e)))?
             .take(0)
-            .map_err(|e| format!("Failed to extract: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to extract: {}", e)))?;
 
         if !existing.is_empty() {
             // Delete all related data for existing example packages
@@ -794,51 +1549,54 @@ pub mod commands {
 
                     // Delete sections
                     let _: Vec<PromptSection> = db
-                        .db
                         .query("DELETE FROM prompt_sections WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete sections: {}", e))?
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete sections: {}", e))
+                        })?
                         .take(0)
                         .unwrap_or_default();
 
                     // Delete templates
                     let _: Vec<PromptTemplate> = db
-                        .db
                         .query("DELETE FROM prompt_templates WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete templates: {}", e))?
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete templates: {}", e))
+                        })?
                         .take(0)
                         .unwrap_or_default();
 
                     // Delete separator sets
                     let _: Vec<SeparatorSet> = db
-                        .db
                         .query("DELETE FROM prompt_separator_sets WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete separator sets: {}", e))?
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete separator sets: {}", e))
+                        })?
                         .take(0)
                         .unwrap_or_default();
 
                     // Delete data types
                     let _: Vec<PromptDataType> = db
-                        .db
                         .query("DELETE FROM prompt_data_types WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete data types: {}", e))?
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete data types: {}", e))
+                        })?
                         .take(0)
                         .unwrap_or_default();
 
                     // Delete tags
                     let _: Vec<PromptTag> = db
-                        .db
                         .query("DELETE FROM prompt_tags WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete tags: {}", e))?
+                        .map_err(|e| AppError::Database(format!("Failed to delete tags: {}", e)))?
                         .take(0)
                         .unwrap_or_default();
 
@@ -847,7 +1605,9 @@ pub mod commands {
                         .db
                         .delete(("prompt_packages", pkg_id.as_str()))
                         .await
-                        .map_err(|e| format!("Failed to delete package: {}", e))?;
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete package: {}", e))
+                        })?;
                 }
             }
         }
@@ -857,10 +1617,9 @@ pub mod commands {
             id: None,
             namespace: "examples".to_string(),
             additional_namespaces: vec!["examples-internal".to_string()],
-            name: "Example Prompts".to_string(),
+            name: i18n::text("seed.examples.name").to_string(),
             version: "1.0.0".to_string(),
-            description: "A collection of example prompts demonstrating various features"
-                .to_string(),
+            description: i18n::text("seed.examples.description").to_string(),
             author: "System".to_string(),
             dependencies: vec![],
             exports: vec![
@@ -877,10 +1636,12 @@ pub mod commands {
             .create("prompt_packages")
             .content(package)
             .await
-            .map_err(|e| format!("Failed to create package: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create package: {}", e)))?;
 
-        let pkg = created_package.ok_or("Failed to create package")?;
-        let package_id = extract_id(&pkg.id).ok_or("Failed to get package ID")?;
+        let pkg = created_package
+            .ok_or_else(|| AppError::Database("Failed to create package".to_string()))?;
+        let package_id = extract_id(&pkg.id)
+            .ok_or_else(|| AppError::Database("Failed to get package ID".to_string()))?;
 
         // ============================================
         // SIMPLE ENTRY POINT: Greeting
@@ -942,6 +1703,8 @@ pub mod commands {
                     "expected_output": "Hello, Alice, Bob, and Charlie! Welcome to our conference."
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -951,7 +1714,7 @@ pub mod commands {
             .create("prompt_sections")
             .content(greeting_section)
             .await
-            .map_err(|e| format!("Failed to create greeting section: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create greeting section: {}", e)))?;
 
         // ============================================
         // MEDIUM ENTRY POINT: Character Description
@@ -1058,6 +1821,8 @@ pub mod commands {
                     "expected_output": "Create a detailed character description for Aria, a blacksmith. They should have the following traits: brave, curious, and stubborn. The setting is fantasy."
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1067,7 +1832,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(character_section)
             .await
-            .map_err(|e| format!("Failed to create character section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create character section: {}", e))
+            })?;
 
         // ============================================
         // FRAGMENT: Review Guidelines (reusable)
@@ -1094,6 +1861,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1103,7 +1872,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(guidelines_fragment)
             .await
-            .map_err(|e| format!("Failed to create guidelines fragment: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create guidelines fragment: {}", e))
+            })?;
 
         // ============================================
         // COMPLEX ENTRY POINT: Code Review
@@ -1227,6 +1998,8 @@ pub mod commands {
                     "expected_output": "Please review the following Rust code, focusing on performance and security.\n\nContext: This is a hot path in our authentication system\n\nReview Guidelines:\n• Check for clear variable naming\n• Verify error handling is comprehensive\n• Look for potential performance issues\n• Ensure code follows project conventions\n\nReview depth: Deep-Dive\n\nPlease pay special attention to:\n• Memory allocation patterns\n• Error handling edge cases"
                 })
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1236,7 +2009,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(code_review_section)
             .await
-            .map_err(|e| format!("Failed to create code review section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create code review section: {}", e))
+            })?;
 
         // ============================================
         // LONG ENTRY POINT: AI Agent System Prompt
@@ -1427,6 +2202,8 @@ pub mod commands {
                     "expected_output": "You are a technical writer with expertise in documentation, API design, and developer experience.\n\nYou can:\n• Write clear technical documentation\n• Create API reference guides\n• Review and improve existing docs\n\nImportant constraints:\n1. Keep explanations concise\n2. Use code examples when helpful\n3. Avoid jargon without explanation\n\nCommunication style: Professional but friendly.\n\nExample interactions:\n1. User: How do I document a REST API? → Explain OpenAPI/Swagger, provide examples\n2. User: This paragraph is confusing → Rewrite for clarity, explain changes\n\nAdditional instructions:\nWhen reviewing documentation, always suggest at least one improvement even if the content is good."
                 })
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1436,7 +2213,7 @@ pub mod commands {
             .create("prompt_sections")
             .content(agent_section)
             .await
-            .map_err(|e| format!("Failed to create agent section: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create agent section: {}", e)))?;
 
         // ============================================
         // PLURALIZATION EXAMPLE: Task Summary
@@ -1522,6 +2299,8 @@ pub mod commands {
                     "expected_output": "You have 3 tasks to complete: Review PR, Update docs, and Deploy to staging. Let's get started!"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1531,7 +2310,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(task_summary_section)
             .await
-            .map_err(|e| format!("Failed to create task summary section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create task summary section: {}", e))
+            })?;
 
         // ============================================
         // ARTICLE SELECTION EXAMPLE: Item Description
@@ -1625,6 +2406,8 @@ pub mod commands {
                     "expected_output": "You found an hour glass! It's an epic item."
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1634,7 +2417,7 @@ pub mod commands {
             .create("prompt_sections")
             .content(article_section)
             .await
-            .map_err(|e| format!("Failed to create article section: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create article section: {}", e)))?;
 
         // ============================================
         // SWITCH EXAMPLE: Greeting by Time of Day
@@ -1733,6 +2516,8 @@ pub mod commands {
                     "expected_output": "Good evening, Bob! Wrapping up for the day?"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1742,7 +2527,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(greeting_switch_section)
             .await
-            .map_err(|e| format!("Failed to create greeting switch section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create greeting switch section: {}", e))
+            })?;
 
         // ============================================
         // FRAGMENT: Error Message Builder (reusable)
@@ -1776,6 +2563,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1785,7 +2574,7 @@ pub mod commands {
             .create("prompt_sections")
             .content(error_fragment)
             .await
-            .map_err(|e| format!("Failed to create error fragment: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create error fragment: {}", e)))?;
 
         // ============================================
         // COMPLEX: Notification with Nested Sections
@@ -1914,6 +2703,8 @@ pub mod commands {
                     "expected_output": "📬 Notification Summary for Bob\n\nMessages: 2 new messages from Alice and Charlie\n\nAlerts:\n⚠️ Warning: Disk space low\n❌ Error: Build failed\n\nStatus: 🔴 Multiple items need attention"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -1923,7 +2714,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(notification_section)
             .await
-            .map_err(|e| format!("Failed to create notification section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create notification section: {}", e))
+            })?;
 
         // ============================================
         // DATA TYPE EXAMPLE: Create custom data types
@@ -1949,7 +2742,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(severity_type)
             .await
-            .map_err(|e| format!("Failed to create severity type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create severity type: {}", e)))?;
 
         let item_rarity_type = PromptDataType {
             id: None,
@@ -1974,7 +2767,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(item_rarity_type)
             .await
-            .map_err(|e| format!("Failed to create item rarity type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create item rarity type: {}", e)))?;
 
         // ============================================
         // DATA TYPE: Writing Styles
@@ -2000,7 +2793,9 @@ pub mod commands {
             .create("prompt_data_types")
             .content(writing_style_type)
             .await
-            .map_err(|e| format!("Failed to create writing style type: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create writing style type: {}", e))
+            })?;
 
         // ============================================
         // FRAGMENT: Random Adjective Pool
@@ -2021,6 +2816,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2030,7 +2827,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(adjective_fragment)
             .await
-            .map_err(|e| format!("Failed to create adjective fragment: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create adjective fragment: {}", e))
+            })?;
 
         // ============================================
         // FRAGMENT: Random Location
@@ -2058,6 +2857,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2067,7 +2868,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(location_fragment)
             .await
-            .map_err(|e| format!("Failed to create location fragment: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create location fragment: {}", e))
+            })?;
 
         // ============================================
         // FRAGMENT: Random Character Trait
@@ -2095,6 +2898,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2104,7 +2909,7 @@ pub mod commands {
             .create("prompt_sections")
             .content(trait_fragment)
             .await
-            .map_err(|e| format!("Failed to create trait fragment: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create trait fragment: {}", e)))?;
 
         // ============================================
         // ENTRY POINT: Random Story Prompt Generator
@@ -2160,6 +2965,8 @@ pub mod commands {
                     "expected_output": "Write a story about a mysterious hero who discovers a forbidden artifact in a dense forest shrouded in mist."
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2169,7 +2976,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(story_prompt_section)
             .await
-            .map_err(|e| format!("Failed to create story prompt section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create story prompt section: {}", e))
+            })?;
 
         // ============================================
         // ENTRY POINT: Random Character Generator
@@ -2248,6 +3057,8 @@ pub mod commands {
                 "variables": {},
                 "expected_output": "Create a character named Seraphina who is wise beyond their years and gifted with magic. They carry an ancient staff."
             })],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2257,7 +3068,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(character_gen_section)
             .await
-            .map_err(|e| format!("Failed to create character gen section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create character gen section: {}", e))
+            })?;
 
         // ============================================
         // ENTRY POINT: Random Quest Generator
@@ -2327,6 +3140,8 @@ pub mod commands {
                 "variables": {},
                 "expected_output": "🎯 Quest: The Lost Artifact\n\n📍 Location: a towering castle on a cliff\n\n📋 Objectives:\n• Defeat the guardian\n• Solve the ancient riddle\n• Retrieve the artifact\n\n🏆 Reward: 500 gold coins"
             })],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2336,7 +3151,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(quest_gen_section)
             .await
-            .map_err(|e| format!("Failed to create quest gen section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create quest gen section: {}", e))
+            })?;
 
         // ============================================
         // ENTRY POINT: Random Writing Prompt with Style
@@ -2397,6 +3214,8 @@ pub mod commands {
                 "variables": {},
                 "expected_output": "Write in a poetic style about a secret that refuses to stay buried.\n\nInclude these elements: a ticking clock, an unexpected ally, and a moral dilemma."
             })],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2406,7 +3225,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(writing_prompt_section)
             .await
-            .map_err(|e| format!("Failed to create writing prompt section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create writing prompt section: {}", e))
+            })?;
 
         // ============================================
         // ENTRY POINT: Shuffle-Based Itinerary
@@ -2478,6 +3299,8 @@ pub mod commands {
                 },
                 "expected_output": "Today's Adventure Plan:\n\n1. Explore the park\n2. Try the local café\n3. Visit the museum\n4. Walk by the river\n\n✨ Special surprise: A hidden gem awaits!"
             })],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2487,7 +3310,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(itinerary_section)
             .await
-            .map_err(|e| format!("Failed to create itinerary section: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create itinerary section: {}", e))
+            })?;
 
         // ============================================
         // TAGS for categorization
@@ -2523,30 +3348,31 @@ pub mod commands {
                 .create("prompt_tags")
                 .content(tag)
                 .await
-                .map_err(|e| format!("Failed to create tag: {}", e))?;
+                .map_err(|e| AppError::Database(format!("Failed to create tag: {}", e)))?;
         }
 
-        Ok(
-            "Created example package with 13 entry points, 5 fragments, 3 data types, and 10 tags"
-                .to_string(),
-        )
+        Ok(i18n::text("seed.examples.created").to_string())
     }
 
     #[tauri::command]
     pub async fn seed_text2image_common_package(
         state: tauri::State<'_, AppState>,
-    ) -> Result<String, String> {
+    ) -> Result<String, ApiError> {
         let db = state.database.lock().await;
+        seed_text2image_common(&db).await.map_err(ApiError::from)
+    }
+
+    /// Create the Text2Image common library package (replacing an existing one)
+    pub async fn seed_text2image_common(db: &Database) -> Result<String, AppError> {
         let timestamp = get_timestamp();
 
         // Check if text2image-common already exists and delete it
         let existing: Vec<PromptPackage> = db
-            .db
             .query("SELECT * FROM prompt_packages WHERE namespace = 'text2image-common'")
             .await
-            .map_err(|e| format!("Failed to check existing: {}", e))?
+            .map_err(|e| AppError::Database(format!("Failed to check existing: {}", e)))?
             .take(0)
-            .map_err(|e| format!("Failed to extract: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to extract: {}", e)))?;
 
         if !existing.is_empty() {
             // Delete all related data for existing text2image-common packages
@@ -2560,41 +3386,43 @@ pub mod commands {
 
                     // Delete sections
                     let _: Vec<PromptSection> = db
-                        .db
                         .query("DELETE FROM prompt_sections WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete sections: {}", e))?
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete sections: {}", e))
+                        })?
                         .take(0)
                         .unwrap_or_default();
 
                     // Delete data types
                     let _: Vec<PromptDataType> = db
-                        .db
                         .query("DELETE FROM prompt_data_types WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete data types: {}", e))?
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete data types: {}", e))
+                        })?
                         .take(0)
                         .unwrap_or_default();
 
                     // Delete tags
                     let _: Vec<PromptTag> = db
-                        .db
                         .query("DELETE FROM prompt_tags WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete tags: {}", e))?
+                        .map_err(|e| AppError::Database(format!("Failed to delete tags: {}", e)))?
                         .take(0)
                         .unwrap_or_default();
 
                     // Delete separator sets
                     let _: Vec<SeparatorSet> = db
-                        .db
                         .query("DELETE FROM prompt_separator_sets WHERE package_id = $pkg_id")
                         .bind(("pkg_id", pkg_id.clone()))
                         .await
-                        .map_err(|e| format!("Failed to delete separator sets: {}", e))?
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete separator sets: {}", e))
+                        })?
                         .take(0)
                         .unwrap_or_default();
 
@@ -2603,7 +3431,9 @@ pub mod commands {
                         .db
                         .delete(("prompt_packages", pkg_id.as_str()))
                         .await
-                        .map_err(|e| format!("Failed to delete package: {}", e))?;
+                        .map_err(|e| {
+                            AppError::Database(format!("Failed to delete package: {}", e))
+                        })?;
                 }
             }
         }
@@ -2613,9 +3443,9 @@ pub mod commands {
             id: None,
             namespace: "text2image-common".to_string(),
             additional_namespaces: vec!["t2i-internal".to_string()],
-            name: "Text2Image Common Library".to_string(),
+            name: i18n::text("seed.text2image.name").to_string(),
             version: "1.0.0".to_string(),
-            description: "Common reusable components for text-to-image prompt generation including subjects, actions, environments, styles, and modifiers".to_string(),
+            description: i18n::text("seed.text2image.description").to_string(),
             author: "System".to_string(),
             dependencies: vec![],
             exports: vec![
@@ -2623,7 +3453,7 @@ pub mod commands {
                 "scene-description".to_string(),
                 "style-modifiers".to_string(),
                 "lighting-atmosphere".to_string(),
-                "camera-settings".to_string()
+                "camera-settings".to_string(),
             ],
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
@@ -2634,10 +3464,12 @@ pub mod commands {
             .create("prompt_packages")
             .content(package)
             .await
-            .map_err(|e| format!("Failed to create package: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create package: {}", e)))?;
 
-        let pkg = created_package.ok_or("Failed to create package")?;
-        let package_id = extract_id(&pkg.id).ok_or("Failed to get package ID")?;
+        let pkg = created_package
+            .ok_or_else(|| AppError::Database("Failed to create package".to_string()))?;
+        let package_id = extract_id(&pkg.id)
+            .ok_or_else(|| AppError::Database("Failed to get package ID".to_string()))?;
 
         // ============================================
         // DATA TYPES
@@ -2672,7 +3504,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(hero_type)
             .await
-            .map_err(|e| format!("Failed to create hero type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create hero type: {}", e)))?;
 
         // Action Types
         let action_type = PromptDataType {
@@ -2707,7 +3539,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(action_type)
             .await
-            .map_err(|e| format!("Failed to create action type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create action type: {}", e)))?;
 
         // Environment Types
         let environment_type = PromptDataType {
@@ -2743,7 +3575,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(environment_type)
             .await
-            .map_err(|e| format!("Failed to create environment type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create environment type: {}", e)))?;
 
         // Art Style Types
         let art_style_type = PromptDataType {
@@ -2781,7 +3613,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(art_style_type)
             .await
-            .map_err(|e| format!("Failed to create art style type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create art style type: {}", e)))?;
 
         // Lighting Types
         let lighting_type = PromptDataType {
@@ -2816,7 +3648,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(lighting_type)
             .await
-            .map_err(|e| format!("Failed to create lighting type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create lighting type: {}", e)))?;
 
         // Camera Angle Types
         let camera_angle_type = PromptDataType {
@@ -2849,7 +3681,9 @@ pub mod commands {
             .create("prompt_data_types")
             .content(camera_angle_type)
             .await
-            .map_err(|e| format!("Failed to create camera angle type: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create camera angle type: {}", e))
+            })?;
 
         // Quality Modifiers
         let quality_type = PromptDataType {
@@ -2877,7 +3711,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(quality_type)
             .await
-            .map_err(|e| format!("Failed to create quality type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create quality type: {}", e)))?;
 
         // Color Palette Types
         let color_palette_type = PromptDataType {
@@ -2910,7 +3744,9 @@ pub mod commands {
             .create("prompt_data_types")
             .content(color_palette_type)
             .await
-            .map_err(|e| format!("Failed to create color palette type: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create color palette type: {}", e))
+            })?;
 
         // Mood Types
         let mood_type = PromptDataType {
@@ -2945,7 +3781,7 @@ pub mod commands {
             .create("prompt_data_types")
             .content(mood_type)
             .await
-            .map_err(|e| format!("Failed to create mood type: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create mood type: {}", e)))?;
 
         // ============================================
         // FRAGMENTS (Reusable Sections)
@@ -2968,6 +3804,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -2976,7 +3814,7 @@ pub mod commands {
             .create("prompt_sections")
             .content(hero_fragment)
             .await
-            .map_err(|e| format!("Failed to create hero fragment: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create hero fragment: {}", e)))?;
 
         // Random Action
         let action_fragment = PromptSection {
@@ -2995,6 +3833,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -3003,7 +3843,7 @@ pub mod commands {
             .create("prompt_sections")
             .content(action_fragment)
             .await
-            .map_err(|e| format!("Failed to create action fragment: {}", e))?;
+            .map_err(|e| AppError::Database(format!("Failed to create action fragment: {}", e)))?;
 
         // Random Environment
         let environment_fragment = PromptSection {
@@ -3022,6 +3862,8 @@ pub mod commands {
             variables: vec![],
             tags: vec![],
             examples: vec![],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -3030,7 +3872,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(environment_fragment)
             .await
-            .map_err(|e| format!("Failed to create environment fragment: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create environment fragment: {}", e))
+            })?;
 
         // ============================================
         // ENTRY POINTS (Exportable Templates)
@@ -3106,6 +3950,8 @@ pub mod commands {
                     "expected_output": "cyborg, glowing red eyes, metallic armor, and lightning effects"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -3114,7 +3960,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(hero_description_entry)
             .await
-            .map_err(|e| format!("Failed to create hero description entry: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create hero description entry: {}", e))
+            })?;
 
         // Scene Description Entry Point
         let scene_description_entry = PromptSection {
@@ -3207,6 +4055,8 @@ pub mod commands {
                     "expected_output": "mage casting spell in ancient ruins, with glowing crystals, floating runes, and magical tome"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -3215,7 +4065,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(scene_description_entry)
             .await
-            .map_err(|e| format!("Failed to create scene description entry: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create scene description entry: {}", e))
+            })?;
 
         // Style Modifiers Entry Point
         let style_modifiers_entry = PromptSection {
@@ -3305,6 +4157,8 @@ pub mod commands {
                     "expected_output": "anime, 8k, highly detailed, and masterpiece, vibrant colors"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -3313,7 +4167,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(style_modifiers_entry)
             .await
-            .map_err(|e| format!("Failed to create style modifiers entry: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create style modifiers entry: {}", e))
+            })?;
 
         // Lighting and Atmosphere Entry Point
         let lighting_atmosphere_entry = PromptSection {
@@ -3404,6 +4260,8 @@ pub mod commands {
                     "expected_output": "volumetric lighting, epic mood, god rays, dust particles, and lens flare"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -3412,7 +4270,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(lighting_atmosphere_entry)
             .await
-            .map_err(|e| format!("Failed to create lighting atmosphere entry: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create lighting atmosphere entry: {}", e))
+            })?;
 
         // Camera Settings Entry Point
         let camera_settings_entry = PromptSection {
@@ -3502,6 +4362,8 @@ pub mod commands {
                     "expected_output": "low angle, 85mm lens, shallow depth of field with bokeh"
                 }),
             ],
+            locale: None,
+            negative_content: None,
             created_at: timestamp.clone(),
             updated_at: timestamp.clone(),
         };
@@ -3510,7 +4372,9 @@ pub mod commands {
             .create("prompt_sections")
             .content(camera_settings_entry)
             .await
-            .map_err(|e| format!("Failed to create camera settings entry: {}", e))?;
+            .map_err(|e| {
+                AppError::Database(format!("Failed to create camera settings entry: {}", e))
+            })?;
 
         // ============================================
         // TAGS for categorization
@@ -3550,9 +4414,38 @@ pub mod commands {
                 .create("prompt_tags")
                 .content(tag)
                 .await
-                .map_err(|e| format!("Failed to create tag: {}", e))?;
+                .map_err(|e| AppError::Database(format!("Failed to create tag: {}", e)))?;
         }
 
-        Ok("Created Text2Image Common Library package with 9 data types, 3 internal fragments, 5 exportable entry points, and 14 tags".to_string())
+        Ok(i18n::text("seed.text2image.created").to_string())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::PromptSection;
+    use surrealdb::sql::Thing;
+
+    /// Exportable entry point `prompt_sections:<key>` in package "pkg" and
+    /// namespace "demo"; tests override the fields they care about
+    pub(crate) fn section(key: &str, content: serde_json::Value) -> PromptSection {
+        PromptSection {
+            id: Some(Thing::from(("prompt_sections", key))),
+            package_id: "pkg".to_string(),
+            namespace: "demo".to_string(),
+            name: key.to_string(),
+            description: String::new(),
+            content,
+            is_entry_point: true,
+            exportable: true,
+            required_variables: Vec::new(),
+            variables: Vec::new(),
+            tags: Vec::new(),
+            examples: Vec::new(),
+            locale: None,
+            negative_content: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
     }
 }
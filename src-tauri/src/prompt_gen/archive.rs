@@ -0,0 +1,184 @@
+// Prompt package archives
+//
+// A `.promptpkg` file is a gzip-compressed tar holding `manifest.json` and
+// `package.json` (the regular `PackageExport`). The manifest records the
+// archive format version and the SHA-256 of `package.json`, so a package
+// passed around by chat or email is verified before it is imported.
+
+use super::{get_timestamp, PackageExport};
+use crate::error::AppError;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Archive layout version written to the manifest
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const PACKAGE_FILE: &str = "package.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub namespace: String,
+    pub name: String,
+    pub version: String,
+    pub created_at: String,
+    /// Hex SHA-256 of `package.json`
+    pub sha256: String,
+}
+
+fn append_file<W: Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    content: &[u8],
+) -> Result<(), AppError> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, content)?;
+    Ok(())
+}
+
+/// Write a package export as an archive
+pub fn write_archive<W: Write>(
+    export: &PackageExport,
+    writer: W,
+) -> Result<ArchiveManifest, AppError> {
+    let package = serde_json::to_vec_pretty(export)?;
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        namespace: export.package.namespace.clone(),
+        name: export.package.name.clone(),
+        version: export.package.version.clone(),
+        created_at: get_timestamp(),
+        sha256: hex::encode(Sha256::digest(&package)),
+    };
+
+    let mut builder = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+    append_file(
+        &mut builder,
+        MANIFEST_FILE,
+        &serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    append_file(&mut builder, PACKAGE_FILE, &package)?;
+    builder.into_inner()?.finish()?.flush()?;
+    Ok(manifest)
+}
+
+/// Read and verify an archive
+pub fn read_archive<R: Read>(reader: R) -> Result<(ArchiveManifest, PackageExport), AppError> {
+    let invalid =
+        |message: String| AppError::Validation(format!("Invalid package archive: {}", message));
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    let (mut manifest, mut package) = (None, None);
+    for entry in archive.entries().map_err(|e| invalid(e.to_string()))? {
+        let mut entry = entry.map_err(|e| invalid(e.to_string()))?;
+        let name = entry
+            .path()
+            .map_err(|e| invalid(e.to_string()))?
+            .to_string_lossy()
+            .to_string();
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .map_err(|e| invalid(e.to_string()))?;
+        match name.as_str() {
+            MANIFEST_FILE => manifest = Some(content),
+            PACKAGE_FILE => package = Some(content),
+            _ => {}
+        }
+    }
+
+    let manifest: ArchiveManifest = serde_json::from_slice(
+        &manifest.ok_or_else(|| invalid(format!("{} is missing", MANIFEST_FILE)))?,
+    )
+    .map_err(|e| invalid(format!("{}: {}", MANIFEST_FILE, e)))?;
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(invalid(format!(
+            "format version {} is newer than supported ({})",
+            manifest.format_version, ARCHIVE_FORMAT_VERSION
+        )));
+    }
+    let package = package.ok_or_else(|| invalid(format!("{} is missing", PACKAGE_FILE)))?;
+    let sha256 = hex::encode(Sha256::digest(&package));
+    if sha256 != manifest.sha256 {
+        return Err(invalid(format!(
+            "checksum mismatch (expected {}, got {})",
+            manifest.sha256, sha256
+        )));
+    }
+    let export: PackageExport = serde_json::from_slice(&package)
+        .map_err(|e| invalid(format!("{}: {}", PACKAGE_FILE, e)))?;
+    Ok((manifest, export))
+}
+
+pub fn write_archive_file(
+    export: &PackageExport,
+    path: &Path,
+) -> Result<ArchiveManifest, AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_archive(export, std::fs::File::create(path)?)
+}
+
+pub fn read_archive_file(path: &Path) -> Result<(ArchiveManifest, PackageExport), AppError> {
+    read_archive(std::fs::File::open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_package_archive() {
+        let export: PackageExport = serde_json::from_value(json!({
+            "format_version": "1.0.0", "exported_at": "",
+            "package": {
+                "namespace": "demo", "name": "Demo", "version": "1.2.0", "description": "",
+                "author": "", "dependencies": [], "exports": [], "created_at": "", "updated_at": ""
+            },
+            "sections": [{
+                "package_id": "pkg", "namespace": "demo", "name": "intro", "description": "",
+                "content": { "type": "text", "value": "Hello" }, "is_entry_point": true,
+                "exportable": true, "required_variables": [], "created_at": "", "updated_at": ""
+            }],
+            "separator_sets": [], "data_types": [], "tags": []
+        }))
+        .unwrap();
+
+        let mut bytes = Vec::new();
+        let manifest = write_archive(&export, &mut bytes).unwrap();
+        assert_eq!(manifest.namespace, "demo");
+        assert_eq!(manifest.version, "1.2.0");
+        let (read, imported) = read_archive(bytes.as_slice()).unwrap();
+        assert_eq!(read, manifest);
+        assert_eq!(imported.sections[0].content["value"], "Hello");
+
+        // A package.json that doesn't match the manifest is rejected
+        let mut tampered = Vec::new();
+        {
+            let mut builder =
+                tar::Builder::new(GzEncoder::new(&mut tampered, Compression::default()));
+            append_file(
+                &mut builder,
+                MANIFEST_FILE,
+                &serde_json::to_vec(&manifest).unwrap(),
+            )
+            .unwrap();
+            append_file(&mut builder, PACKAGE_FILE, b"{}").unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+        let err = read_archive(tampered.as_slice()).unwrap_err().to_string();
+        assert!(err.contains("checksum mismatch"), "{}", err);
+        assert!(read_archive(&b"not an archive"[..]).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+// Prompt chains
+//
+// A chain is an ordered list of steps, each rendering an entry point. Step
+// inputs feed an earlier step's rendered output, or the LLM response to it,
+// into a variable of a later step. The app doesn't call models itself:
+// responses are passed in by the caller, and a run stops at the first step
+// needing a response it hasn't been given (`waiting_for`), to be run again
+// once it is available. Runs are stored in `prompt_chain_runs`.
+
+use super::get_timestamp;
+use super::renderer::{render_section, PromptLibrary};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use surrealdb::sql::Thing;
+
+/// Runs kept per chain (older ones are dropped when a run is stored)
+const MAX_RUNS_PER_CHAIN: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainInputSource {
+    /// The earlier step's rendered prompt
+    #[default]
+    Output,
+    /// The LLM response to the earlier step's prompt
+    Response,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainStepInput {
+    /// Variable of this step to set
+    pub variable: String,
+    /// Id of an earlier step
+    pub from_step: String,
+    #[serde(default)]
+    pub source: ChainInputSource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainStep {
+    /// Unique within the chain, e.g. "outline"
+    pub id: String,
+    /// Entry point rendered by this step
+    pub section_id: String,
+    /// Fixed variable values of this step
+    #[serde(default)]
+    pub variables: Map<String, Value>,
+    #[serde(default)]
+    pub inputs: Vec<ChainStepInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptChain {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+    pub package_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub steps: Vec<ChainStep>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainStepResult {
+    pub step_id: String,
+    pub section_id: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    pub seed: u64,
+    pub variables: Map<String, Value>,
+    pub warnings: Vec<String>,
+    /// LLM response supplied for this step
+    #[serde(default)]
+    pub response: Option<String>,
+}
+
+/// Run as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChainRunRecord {
+    id: Thing,
+    chain_id: String,
+    package_id: String,
+    steps: Vec<ChainStepResult>,
+    waiting_for: Option<String>,
+    ran_at: String,
+}
+
+/// User-facing run with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainRun {
+    pub id: String,
+    pub chain_id: String,
+    /// Results of the steps that ran, in order
+    pub steps: Vec<ChainStepResult>,
+    /// Step whose LLM response is needed before the chain can continue
+    pub waiting_for: Option<String>,
+    pub ran_at: String,
+}
+
+impl From<ChainRunRecord> for ChainRun {
+    fn from(record: ChainRunRecord) -> Self {
+        ChainRun {
+            id: record.id.id.to_raw(),
+            chain_id: record.chain_id,
+            steps: record.steps,
+            waiting_for: record.waiting_for,
+            ran_at: record.ran_at,
+        }
+    }
+}
+
+fn chain_key(id: &str) -> &str {
+    id.strip_prefix("prompt_chains:").unwrap_or(id)
+}
+
+/// Check step ids, inputs and entry points of a chain
+pub fn check_chain(library: &PromptLibrary, chain: &PromptChain) -> Result<(), AppError> {
+    let invalid = |message: String| Err(AppError::Validation(message));
+    if chain.name.trim().is_empty() {
+        return invalid("Chain name cannot be empty".to_string());
+    }
+    if chain.steps.is_empty() {
+        return invalid("A chain needs at least one step".to_string());
+    }
+    let mut earlier = HashSet::new();
+    for step in &chain.steps {
+        if step.id.trim().is_empty() {
+            return invalid("Step ids cannot be empty".to_string());
+        }
+        if earlier.contains(step.id.as_str()) {
+            return invalid(format!("Duplicate step id '{}'", step.id));
+        }
+        if library.find_section(&step.section_id).is_none() {
+            return invalid(format!(
+                "Step '{}': section {} not found",
+                step.id, step.section_id
+            ));
+        }
+        for input in &step.inputs {
+            if !earlier.contains(input.from_step.as_str()) {
+                return invalid(format!(
+                    "Step '{}': input '{}' must come from an earlier step, not '{}'",
+                    step.id, input.variable, input.from_step
+                ));
+            }
+        }
+        earlier.insert(step.id.as_str());
+    }
+    Ok(())
+}
+
+/// Run the steps in order until one needs a response that wasn't supplied
+///
+/// `variables` apply to every step, below the step's own values and inputs.
+/// With a seed, step `n` renders with `seed + n`.
+pub fn run_steps(
+    library: &PromptLibrary,
+    chain: &PromptChain,
+    variables: &Map<String, Value>,
+    responses: &HashMap<String, String>,
+    seed: Option<u64>,
+) -> Result<(Vec<ChainStepResult>, Option<String>), AppError> {
+    let mut results: Vec<ChainStepResult> = Vec::new();
+    for (index, step) in chain.steps.iter().enumerate() {
+        let mut values = variables.clone();
+        values.extend(step.variables.clone());
+        for input in &step.inputs {
+            let earlier = results
+                .iter()
+                .find(|r| r.step_id == input.from_step)
+                .ok_or_else(|| {
+                    AppError::Validation(format!(
+                        "Step '{}': unknown input step '{}'",
+                        step.id, input.from_step
+                    ))
+                })?;
+            let value = match input.source {
+                ChainInputSource::Output => earlier.text.clone(),
+                ChainInputSource::Response => match &earlier.response {
+                    Some(response) => response.clone(),
+                    None => {
+                        let waiting_for = earlier.step_id.clone();
+                        return Ok((results, Some(waiting_for)));
+                    }
+                },
+            };
+            values.insert(input.variable.clone(), Value::String(value));
+        }
+
+        let rendered = render_section(
+            library,
+            &step.section_id,
+            values,
+            seed.map(|s| s.wrapping_add(index as u64)),
+        )?;
+        results.push(ChainStepResult {
+            step_id: step.id.clone(),
+            section_id: rendered.section_id,
+            text: rendered.text,
+            negative_prompt: rendered.negative_prompt,
+            seed: rendered.seed,
+            variables: rendered.variables,
+            warnings: rendered.warnings,
+            response: responses.get(&step.id).cloned(),
+        });
+    }
+    Ok((results, None))
+}
+
+pub async fn list_chains(
+    db: &Database,
+    package_id: Option<String>,
+) -> Result<Vec<PromptChain>, AppError> {
+    let filter = if package_id.is_some() {
+        " WHERE package_id = $package_id"
+    } else {
+        ""
+    };
+    let mut result = db
+        .query(format!(
+            "SELECT * FROM prompt_chains{} ORDER BY name",
+            filter
+        ))
+        .bind(("package_id", package_id))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query chains: {}", e)))?;
+    result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse chains: {}", e)))
+}
+
+pub async fn get_chain(db: &Database, id: &str) -> Result<PromptChain, AppError> {
+    let chain: Option<PromptChain> = db
+        .db
+        .select(("prompt_chains", chain_key(id)))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get chain: {}", e)))?;
+    chain.ok_or_else(|| AppError::NotFound(format!("Chain {}", id)))
+}
+
+pub async fn create_chain(db: &Database, mut chain: PromptChain) -> Result<PromptChain, AppError> {
+    check_chain(&PromptLibrary::load(db).await?, &chain)?;
+    let timestamp = get_timestamp();
+    chain.id = None;
+    chain.created_at = timestamp.clone();
+    chain.updated_at = timestamp;
+
+    let created: Option<PromptChain> = db
+        .create_row("prompt_chains", chain)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create chain: {}", e)))?;
+    created.ok_or_else(|| AppError::Database("Failed to create chain".to_string()))
+}
+
+pub async fn update_chain(
+    db: &Database,
+    id: &str,
+    mut chain: PromptChain,
+) -> Result<PromptChain, AppError> {
+    let current = get_chain(db, id).await?;
+    check_chain(&PromptLibrary::load(db).await?, &chain)?;
+    chain.id = None;
+    chain.created_at = current.created_at;
+    chain.updated_at = get_timestamp();
+
+    let updated: Option<PromptChain> =
+        db.update_row("prompt_chains", chain_key(id), chain)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update chain: {}", e)))?;
+    updated.ok_or_else(|| AppError::NotFound(format!("Chain {}", id)))
+}
+
+/// Delete a chain and its runs
+pub async fn delete_chain(db: &Database, id: &str) -> Result<(), AppError> {
+    let key = chain_key(id).to_string();
+    db.transaction(
+        "DELETE prompt_chain_runs WHERE chain_id = $chain_id;
+         DELETE type::thing('prompt_chains', $chain_id);",
+        ("chain_id", key),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Run a chain and store the run
+pub async fn run_chain(
+    db: &Database,
+    id: &str,
+    variables: Map<String, Value>,
+    responses: HashMap<String, String>,
+    seed: Option<u64>,
+) -> Result<ChainRun, AppError> {
+    let chain = get_chain(db, id).await?;
+    let library = PromptLibrary::load(db).await?;
+    let (steps, waiting_for) = run_steps(&library, &chain, &variables, &responses, seed)?;
+
+    let key = uuid::Uuid::new_v4().to_string();
+    let record = ChainRunRecord {
+        id: Thing::from(("prompt_chain_runs", key.as_str())),
+        chain_id: chain_key(id).to_string(),
+        package_id: chain.package_id,
+        steps,
+        waiting_for,
+        ran_at: get_timestamp(),
+    };
+    let saved: Option<ChainRunRecord> = db
+        .create_row_with_id("prompt_chain_runs", key.as_str(), record)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to store chain run: {}", e)))?;
+    let saved = saved.ok_or_else(|| AppError::Database("Failed to store chain run".to_string()))?;
+
+    // Keep the runs of each chain bounded
+    db.query(
+        "LET $old = (SELECT VALUE id FROM (SELECT id, ran_at FROM prompt_chain_runs
+                 WHERE chain_id = $chain_id ORDER BY ran_at DESC START $keep));
+             DELETE $old;",
+    )
+    .bind(("chain_id", saved.chain_id.clone()))
+    .bind(("keep", MAX_RUNS_PER_CHAIN))
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to trim chain runs: {}", e)))?
+    .check()
+    .map_err(|e| AppError::Database(format!("Failed to trim chain runs: {}", e)))?;
+
+    Ok(saved.into())
+}
+
+/// Stored runs of a chain, newest first
+pub async fn list_runs(db: &Database, chain_id: &str) -> Result<Vec<ChainRun>, AppError> {
+    let mut result = db
+        .query("SELECT * FROM prompt_chain_runs WHERE chain_id = $chain_id ORDER BY ran_at DESC")
+        .bind(("chain_id", chain_key(chain_id).to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query chain runs: {}", e)))?;
+    let records: Vec<ChainRunRecord> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse chain runs: {}", e)))?;
+    Ok(records.into_iter().map(ChainRun::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::test_support::section;
+    use serde_json::json;
+
+    fn var(id: &str) -> Value {
+        json!({ "type": "variable", "variable_id": id })
+    }
+
+    #[test]
+    fn test_run_chain_steps() {
+        let library = PromptLibrary {
+            sections: vec![
+                section(
+                    "outline",
+                    json!({ "type": "composite", "parts": [
+                        { "type": "text", "value": "Outline a story about " }, var("topic")
+                    ]}),
+                ),
+                section(
+                    "draft",
+                    json!({ "type": "composite", "parts": [
+                        { "type": "text", "value": "Write it in a " }, var("tone"),
+                        { "type": "text", "value": " tone: " }, var("outline")
+                    ]}),
+                ),
+                section(
+                    "review",
+                    json!({ "type": "composite", "parts": [
+                        { "type": "text", "value": "Review: " }, var("draft"),
+                        { "type": "text", "value": " (asked: " }, var("asked"),
+                        { "type": "text", "value": ")" }
+                    ]}),
+                ),
+            ],
+            ..Default::default()
+        };
+        let chain: PromptChain = serde_json::from_value(json!({
+            "package_id": "pkg", "name": "Story",
+            "steps": [
+                { "id": "outline", "section_id": "outline" },
+                { "id": "draft", "section_id": "draft", "variables": { "tone": "dry" },
+                  "inputs": [{ "variable": "outline", "from_step": "outline", "source": "response" }] },
+                { "id": "review", "section_id": "review",
+                  "inputs": [
+                      { "variable": "draft", "from_step": "draft", "source": "response" },
+                      { "variable": "asked", "from_step": "draft" }
+                  ] }
+            ]
+        }))
+        .unwrap();
+        check_chain(&library, &chain).unwrap();
+        let variables = json!({ "topic": "a lighthouse", "tone": "warm" })
+            .as_object()
+            .unwrap()
+            .clone();
+
+        // Without responses the chain stops after the first step
+        let (steps, waiting_for) =
+            run_steps(&library, &chain, &variables, &HashMap::new(), Some(1)).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].text, "Outline a story about a lighthouse");
+        assert_eq!(waiting_for.as_deref(), Some("outline"));
+
+        let responses = HashMap::from([
+            ("outline".to_string(), "1. Storm 2. Rescue".to_string()),
+            ("draft".to_string(), "It was a dark night.".to_string()),
+        ]);
+        let (steps, waiting_for) =
+            run_steps(&library, &chain, &variables, &responses, Some(1)).unwrap();
+        assert_eq!(waiting_for, None);
+        assert_eq!(steps[1].text, "Write it in a dry tone: 1. Storm 2. Rescue");
+        assert_eq!(
+            steps[2].text,
+            format!("Review: It was a dark night. (asked: {})", steps[1].text)
+        );
+        assert_eq!(steps[1].seed, 2);
+
+        let mut broken = chain.clone();
+        broken.steps[1].inputs[0].from_step = "review".to_string();
+        assert!(check_chain(&library, &broken).is_err());
+        broken.steps[1].id = "outline".to_string();
+        assert!(check_chain(&library, &broken).is_err());
+        let mut missing = chain;
+        missing.steps[0].section_id = "nope".to_string();
+        assert!(check_chain(&library, &missing).is_err());
+    }
+}
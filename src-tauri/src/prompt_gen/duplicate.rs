@@ -0,0 +1,281 @@
+// Package duplication
+//
+// Forks a package under a new namespace: every section, separator set, data
+// type and tag is copied with a fresh ID, and references inside section
+// content that point at the source package's namespaces or record IDs are
+// rewritten to the copies, so the fork never reaches back into the original.
+
+use super::namespaces::check_namespaces;
+use super::{
+    extract_id, get_timestamp, PackageExport, PromptDataType, PromptPackage, PromptSection,
+    PromptTag, SeparatorSet,
+};
+use crate::db::Database;
+use crate::error::AppError;
+use serde_json::Value;
+use std::collections::HashMap;
+use surrealdb::sql::Thing;
+
+/// Content fields that reference other package records
+const REFERENCE_FIELDS: [&str; 3] = ["section_id", "separator_set_id", "data_type_id"];
+
+/// Rewrites namespaces and record IDs of the source package
+struct Rewriter {
+    namespaces: HashMap<String, String>,
+    ids: HashMap<String, String>,
+}
+
+impl Rewriter {
+    fn namespace(&self, namespace: &str) -> String {
+        self.namespaces
+            .get(namespace)
+            .cloned()
+            .unwrap_or_else(|| namespace.to_string())
+    }
+
+    /// "namespace:key" or a bare key
+    fn reference(&self, reference: &str) -> String {
+        match reference.split_once(':') {
+            Some((ns, key)) if self.namespaces.contains_key(ns) => format!(
+                "{}:{}",
+                self.namespace(ns),
+                self.ids.get(key).map_or(key, String::as_str)
+            ),
+            Some(_) => reference.to_string(),
+            None => self
+                .ids
+                .get(reference)
+                .cloned()
+                .unwrap_or_else(|| reference.to_string()),
+        }
+    }
+
+    fn content(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (key, field) in fields.iter_mut() {
+                    match field {
+                        Value::String(reference) if REFERENCE_FIELDS.contains(&key.as_str()) => {
+                            *reference = self.reference(reference);
+                        }
+                        _ => self.content(field),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.content(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Copy `export` (a loaded package) into `new_namespace` and return the new package
+pub async fn duplicate_package(
+    db: &Database,
+    export: PackageExport,
+    new_namespace: &str,
+) -> Result<PromptPackage, AppError> {
+    let new_namespace = new_namespace.trim();
+
+    // Additional namespaces keep their suffix: "examples-internal" -> "<new>-internal"
+    let source = &export.package;
+    let mut namespaces = HashMap::from([(source.namespace.clone(), new_namespace.to_string())]);
+    for additional in &source.additional_namespaces {
+        let renamed = match additional.strip_prefix(&source.namespace) {
+            Some(suffix) => format!("{}{}", new_namespace, suffix),
+            None => format!("{}-{}", new_namespace, additional),
+        };
+        namespaces.insert(additional.clone(), renamed);
+    }
+
+    // Fresh record keys, known up front so ID references can be rewritten
+    let mut ids = HashMap::new();
+    let mut fresh_key = |id: &Option<Thing>| {
+        let key = uuid::Uuid::new_v4().to_string();
+        if let Some(old) = extract_id(id) {
+            ids.insert(old, key.clone());
+        }
+        key
+    };
+    let section_keys: Vec<String> = export.sections.iter().map(|s| fresh_key(&s.id)).collect();
+    let set_keys: Vec<String> = export
+        .separator_sets
+        .iter()
+        .map(|s| fresh_key(&s.id))
+        .collect();
+    let type_keys: Vec<String> = export.data_types.iter().map(|t| fresh_key(&t.id)).collect();
+    let rewriter = Rewriter { namespaces, ids };
+
+    let timestamp = get_timestamp();
+    let package_key = uuid::Uuid::new_v4().to_string();
+    let mut package = export.package.clone();
+    package.id = None;
+    package.namespace = new_namespace.to_string();
+    package.additional_namespaces = source
+        .additional_namespaces
+        .iter()
+        .map(|ns| rewriter.namespace(ns))
+        .collect();
+    package.name = format!("{} (copy)", source.name);
+    package.exports = source
+        .exports
+        .iter()
+        .map(|e| rewriter.reference(e))
+        .collect();
+    package.created_at = timestamp.clone();
+    package.updated_at = timestamp.clone();
+    check_namespaces(db, &package, None).await?;
+
+    let created: Option<PromptPackage> = db
+        .create_row_with_id("prompt_packages", package_key.as_str(), package)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create package: {}", e)))?;
+    let created =
+        created.ok_or_else(|| AppError::Database("Failed to create package".to_string()))?;
+
+    for (mut section, key) in export.sections.into_iter().zip(section_keys) {
+        section.id = None;
+        section.package_id = package_key.clone();
+        section.namespace = rewriter.namespace(&section.namespace);
+        rewriter.content(&mut section.content);
+        if let Some(negative) = &mut section.negative_content {
+            rewriter.content(negative);
+        }
+        section.created_at = timestamp.clone();
+        section.updated_at = timestamp.clone();
+        let _: Option<PromptSection> = db
+            .create_row_with_id("prompt_sections", key.as_str(), section)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to copy section: {}", e)))?;
+    }
+
+    for (mut set, key) in export.separator_sets.into_iter().zip(set_keys) {
+        set.id = None;
+        set.package_id = package_key.clone();
+        set.namespace = rewriter.namespace(&set.namespace);
+        set.created_at = timestamp.clone();
+        set.updated_at = timestamp.clone();
+        let _: Option<SeparatorSet> = db
+            .create_row_with_id("prompt_separator_sets", key.as_str(), set)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to copy separator set: {}", e)))?;
+    }
+
+    for (mut data_type, key) in export.data_types.into_iter().zip(type_keys) {
+        data_type.id = None;
+        data_type.package_id = package_key.clone();
+        data_type.namespace = rewriter.namespace(&data_type.namespace);
+        data_type.created_at = timestamp.clone();
+        data_type.updated_at = timestamp.clone();
+        let _: Option<PromptDataType> = db
+            .create_row_with_id("prompt_data_types", key.as_str(), data_type)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to copy data type: {}", e)))?;
+    }
+
+    for mut tag in export.tags {
+        tag.id = None;
+        tag.package_id = package_key.clone();
+        tag.namespace = rewriter.namespace(&tag.namespace);
+        tag.created_at = timestamp.clone();
+        tag.updated_at = timestamp.clone();
+        let _: Option<PromptTag> = db
+            .create_row("prompt_tags", tag)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to copy tag: {}", e)))?;
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_duplicate_package() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let export: PackageExport = serde_json::from_value(json!({
+            "format_version": "1.0.0",
+            "exported_at": "",
+            "package": {
+                "namespace": "seed", "additional_namespaces": ["seed-internal"],
+                "name": "Seed", "version": "1.0.0", "description": "", "author": "",
+                "dependencies": ["other"], "exports": ["seed:greeting"],
+                "created_at": "", "updated_at": ""
+            },
+            "sections": [{
+                "id": { "tb": "prompt_sections", "id": { "String": "s1" } },
+                "package_id": "pkg", "namespace": "seed", "name": "greeting",
+                "description": "",
+                "content": { "type": "composite", "parts": [
+                    { "type": "section-ref", "section_id": "seed-internal:name" },
+                    { "type": "section-ref", "section_id": "s2" },
+                    { "type": "section-ref", "section_id": "other:thing" },
+                    { "type": "list", "variable_id": "xs", "separator_set_id": "seed:dashes" }
+                ]},
+                "is_entry_point": true, "exportable": true, "required_variables": [],
+                "created_at": "", "updated_at": ""
+            }, {
+                "id": { "tb": "prompt_sections", "id": { "String": "s2" } },
+                "package_id": "pkg", "namespace": "seed-internal", "name": "name",
+                "description": "", "content": { "type": "text", "value": "Ada" },
+                "is_entry_point": false, "exportable": true, "required_variables": [],
+                "created_at": "", "updated_at": ""
+            }],
+            "separator_sets": [{
+                "package_id": "pkg", "namespace": "seed", "name": "dashes",
+                "description": "", "rules": {}, "created_at": "", "updated_at": ""
+            }],
+            "data_types": [],
+            "tags": [{
+                "package_id": "pkg", "namespace": "seed", "name": "demo",
+                "description": "", "color": null, "parent": null,
+                "created_at": "", "updated_at": ""
+            }]
+        }))
+        .unwrap();
+
+        let package = duplicate_package(&db, export, "fork").await.unwrap();
+        assert_eq!(package.namespace, "fork");
+        assert_eq!(package.additional_namespaces, vec!["fork-internal"]);
+        assert_eq!(package.exports, vec!["fork:greeting"]);
+        assert_eq!(package.dependencies, vec!["other"]);
+        let package_key = extract_id(&package.id).unwrap();
+
+        let sections: Vec<PromptSection> = db.db.select("prompt_sections").await.unwrap();
+        assert_eq!(sections.len(), 2);
+        assert!(sections.iter().all(|s| s.package_id == package_key));
+        let name = sections.iter().find(|s| s.name == "name").unwrap();
+        assert_eq!(name.namespace, "fork-internal");
+        assert_ne!(extract_id(&name.id).as_deref(), Some("s2"));
+
+        let greeting = sections.iter().find(|s| s.name == "greeting").unwrap();
+        let parts = &greeting.content["parts"];
+        assert_eq!(parts[0]["section_id"], "fork-internal:name");
+        assert_eq!(parts[1]["section_id"], json!(extract_id(&name.id).unwrap()));
+        assert_eq!(parts[2]["section_id"], "other:thing");
+        assert_eq!(parts[3]["separator_set_id"], "fork:dashes");
+
+        let tags: Vec<PromptTag> = db.db.select("prompt_tags").await.unwrap();
+        assert_eq!(tags[0].namespace, "fork");
+
+        let export = PackageExport {
+            format_version: "1.0.0".to_string(),
+            exported_at: String::new(),
+            package: package.clone(),
+            templates: Vec::new(),
+            sections: Vec::new(),
+            separator_sets: Vec::new(),
+            data_types: Vec::new(),
+            tags: Vec::new(),
+        };
+        assert!(matches!(
+            duplicate_package(&db, export, "fork-internal").await,
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+// Section examples as tests
+//
+// A section's `examples` pair variable values with an `expected_output`.
+// Running them renders each example through the Rust renderer and compares
+// the result, giving package authors regression tests for their prompt logic.
+// Examples may carry a `seed`; without one seed 0 is used so random content
+// renders the same way on every run.
+
+use super::renderer::{render_section, PromptLibrary};
+use super::{extract_id, PromptSection};
+use serde::{Deserialize, Serialize};
+
+/// An entry of `PromptSection::examples`
+#[derive(Debug, Clone, Deserialize)]
+struct SectionExample {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    variables: serde_json::Map<String, serde_json::Value>,
+    expected_output: Option<String>,
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Equal,
+    /// Expected but not rendered
+    Missing,
+    /// Rendered but not expected
+    Unexpected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExampleResult {
+    pub section_id: String,
+    pub example: String,
+    pub passed: bool,
+    pub expected: String,
+    pub actual: String,
+    /// Line diff, empty when the example passed
+    pub diff: Vec<DiffLine>,
+    /// Why the example could not be run (invalid example or section)
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExampleRunReport {
+    pub passed: usize,
+    pub failed: usize,
+    /// Examples without an expected output
+    pub skipped: usize,
+    pub results: Vec<ExampleResult>,
+}
+
+/// Render every example of the given sections and compare the output
+pub fn run_examples<'a>(
+    library: &PromptLibrary,
+    sections: impl IntoIterator<Item = &'a PromptSection>,
+) -> ExampleRunReport {
+    let mut report = ExampleRunReport::default();
+
+    for section in sections {
+        let section_id = extract_id(&section.id).unwrap_or_else(|| section.name.clone());
+        for (index, raw) in section.examples.iter().enumerate() {
+            let example: SectionExample = match serde_json::from_value(raw.clone()) {
+                Ok(example) => example,
+                Err(e) => {
+                    report.failed += 1;
+                    report.results.push(ExampleResult {
+                        section_id: section_id.clone(),
+                        example: format!("#{}", index + 1),
+                        passed: false,
+                        expected: String::new(),
+                        actual: String::new(),
+                        diff: Vec::new(),
+                        error: Some(format!("Invalid example: {}", e)),
+                        warnings: Vec::new(),
+                    });
+                    continue;
+                }
+            };
+            let Some(expected) = example.expected_output else {
+                report.skipped += 1;
+                continue;
+            };
+            let name = if example.name.is_empty() {
+                format!("#{}", index + 1)
+            } else {
+                example.name
+            };
+
+            let result = match render_section(
+                library,
+                &section_id,
+                example.variables,
+                Some(example.seed.unwrap_or(0)),
+            ) {
+                Ok(rendered) => {
+                    let expected = expected.replace("\r\n", "\n");
+                    let passed = rendered.text == expected;
+                    ExampleResult {
+                        section_id: section_id.clone(),
+                        example: name,
+                        passed,
+                        diff: if passed {
+                            Vec::new()
+                        } else {
+                            diff_lines(&expected, &rendered.text)
+                        },
+                        expected,
+                        actual: rendered.text,
+                        error: None,
+                        warnings: rendered.warnings,
+                    }
+                }
+                Err(e) => ExampleResult {
+                    section_id: section_id.clone(),
+                    example: name,
+                    passed: false,
+                    expected,
+                    actual: String::new(),
+                    diff: Vec::new(),
+                    error: Some(e.to_string()),
+                    warnings: Vec::new(),
+                },
+            };
+
+            if result.passed {
+                report.passed += 1;
+            } else {
+                report.failed += 1;
+            }
+            report.results.push(result);
+        }
+    }
+
+    report
+}
+
+/// Line diff of two texts (longest common subsequence)
+fn diff_lines(expected: &str, actual: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = expected.split('\n').collect();
+    let b: Vec<&str> = actual.split('\n').collect();
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            diff.push(line(DiffKind::Equal, a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(line(DiffKind::Missing, a[i]));
+            i += 1;
+        } else {
+            diff.push(line(DiffKind::Unexpected, b[j]));
+            j += 1;
+        }
+    }
+    diff.extend(a[i..].iter().map(|text| line(DiffKind::Missing, text)));
+    diff.extend(b[j..].iter().map(|text| line(DiffKind::Unexpected, text)));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_run_examples() {
+        let section: PromptSection = serde_json::from_value(json!({
+            "package_id": "pkg",
+            "namespace": "demo",
+            "name": "greeting",
+            "description": "",
+            "content": { "type": "composite", "parts": [
+                { "type": "text", "value": "Hello " },
+                { "type": "variable", "variable_id": "name" },
+                { "type": "text", "value": "\nBye" }
+            ]},
+            "is_entry_point": true,
+            "exportable": true,
+            "required_variables": ["name"],
+            "examples": [
+                { "name": "ada", "variables": { "name": "Ada" }, "expected_output": "Hello Ada\nBye" },
+                { "name": "bob", "variables": { "name": "Bob" }, "expected_output": "Hello Rob\nBye" },
+                { "name": "draft", "variables": { "name": "Cy" } },
+                "not an example"
+            ],
+            "created_at": "",
+            "updated_at": ""
+        }))
+        .unwrap();
+        let library = PromptLibrary {
+            sections: vec![section],
+            ..Default::default()
+        };
+
+        let report = run_examples(&library, &library.sections);
+        assert_eq!(report.passed, 1);
+        assert_eq!(report.failed, 2);
+        assert_eq!(report.skipped, 1);
+
+        let bob = &report.results[1];
+        assert!(!bob.passed);
+        assert_eq!(bob.actual, "Hello Bob\nBye");
+        let kinds: Vec<DiffKind> = bob.diff.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![DiffKind::Missing, DiffKind::Unexpected, DiffKind::Equal]
+        );
+        assert_eq!(bob.diff[0].text, "Hello Rob");
+
+        assert!(report.results[2]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid example"));
+    }
+}
@@ -0,0 +1,764 @@
+// Prompt package export formats
+//
+// Besides the JSON `PackageExport` used for import, packages can be exported
+// as Markdown documentation, as YAML, or as Handlebars/Jinja templates so
+// they can be used outside Modulaur. The template conversion is best-effort:
+// natural-language helpers (articles, plurals) map onto helpers/filters the
+// target environment has to provide, and random content keeps only a
+// comment plus its first option where the dialect has no equivalent.
+
+use super::renderer::{Condition, PromptContent, TextCase};
+use super::{extract_id, PackageExport, PromptSection};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Yaml,
+    Handlebars,
+    Jinja,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Handlebars => "hbs",
+            ExportFormat::Jinja => "j2",
+        }
+    }
+}
+
+/// Export rendered as a text document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextExport {
+    pub format: ExportFormat,
+    /// Suggested file name, e.g. "creative-writing.md"
+    pub file_name: String,
+    pub content: String,
+}
+
+/// Render a package export in a text format (JSON included)
+pub fn export_text(export: &PackageExport, format: ExportFormat) -> Result<TextExport, AppError> {
+    let content = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(export)?,
+        ExportFormat::Markdown => to_markdown(export),
+        ExportFormat::Yaml => serde_yaml::to_string(export)
+            .map_err(|e| AppError::Validation(format!("Failed to write YAML: {}", e)))?,
+        ExportFormat::Handlebars => to_templates(export, TemplateDialect::Handlebars),
+        ExportFormat::Jinja => to_templates(export, TemplateDialect::Jinja),
+    };
+
+    Ok(TextExport {
+        format,
+        file_name: format!("{}.{}", export.package.namespace, format.extension()),
+        content,
+    })
+}
+
+fn parse_content(section: &PromptSection) -> Option<PromptContent> {
+    serde_json::from_value(section.content.clone()).ok()
+}
+
+// ============================================
+// MARKDOWN
+// ============================================
+
+fn to_markdown(export: &PackageExport) -> String {
+    let package = &export.package;
+    let mut out = format!("# {}\n\n", package.name);
+    out.push_str(&format!(
+        "- **Namespace:** `{}`\n- **Version:** {}\n",
+        package.namespace, package.version
+    ));
+    if !package.author.is_empty() {
+        out.push_str(&format!("- **Author:** {}\n", package.author));
+    }
+    if !package.dependencies.is_empty() {
+        out.push_str(&format!(
+            "- **Dependencies:** {}\n",
+            package
+                .dependencies
+                .iter()
+                .map(|d| format!("`{}`", d))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if !package.description.is_empty() {
+        out.push_str(&format!("\n{}\n", package.description));
+    }
+
+    let (entry_points, fragments): (Vec<&PromptSection>, Vec<&PromptSection>) =
+        export.sections.iter().partition(|s| s.is_entry_point);
+    for (title, sections) in [("Entry points", entry_points), ("Sections", fragments)] {
+        if sections.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n## {}\n", title));
+        for section in sections {
+            markdown_section(&mut out, section);
+        }
+    }
+
+    if !export.separator_sets.is_empty() {
+        out.push_str("\n## Separator sets\n\n");
+        for set in &export.separator_sets {
+            out.push_str(&format!("- `{}` — {}\n", set.name, set.description));
+        }
+    }
+    if !export.data_types.is_empty() {
+        out.push_str("\n## Data types\n\n");
+        for data_type in &export.data_types {
+            out.push_str(&format!(
+                "- `{}` ({}) — {}\n",
+                data_type.name, data_type.base_type, data_type.description
+            ));
+        }
+    }
+    if !export.tags.is_empty() {
+        out.push_str("\n## Tags\n\n");
+        for tag in &export.tags {
+            out.push_str(&format!("- `{}` — {}\n", tag.name, tag.description));
+        }
+    }
+
+    out
+}
+
+fn markdown_section(out: &mut String, section: &PromptSection) {
+    out.push_str(&format!("\n### {}\n\n", section.name));
+    if !section.description.is_empty() {
+        out.push_str(&format!("{}\n\n", section.description));
+    }
+    if !section.tags.is_empty() {
+        out.push_str(&format!("Tags: {}\n\n", section.tags.join(", ")));
+    }
+
+    if !section.variables.is_empty() {
+        out.push_str("| Variable | Type | Required | Default | Description |\n");
+        out.push_str("|---|---|---|---|---|\n");
+        for variable in &section.variables {
+            let field = |key: &str| match variable.get(key) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+            };
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                field("id"),
+                field("type"),
+                if variable.get("required").and_then(|r| r.as_bool()) == Some(true) {
+                    "yes"
+                } else {
+                    "no"
+                },
+                field("default_value"),
+                field("description").replace('\n', " ")
+            ));
+        }
+        out.push('\n');
+    } else if !section.required_variables.is_empty() {
+        out.push_str(&format!(
+            "Requires: {}\n\n",
+            section
+                .required_variables
+                .iter()
+                .map(|v| format!("`{}`", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if let Some(content) = parse_content(section) {
+        out.push_str("```handlebars\n");
+        out.push_str(&TemplateDialect::Handlebars.convert(&content));
+        out.push_str("\n```\n");
+    }
+
+    for example in &section.examples {
+        let name = example
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or("Example");
+        out.push_str(&format!("\n**{}**\n\n", name));
+        if let Some(variables) = example.get("variables") {
+            out.push_str(&format!("Variables: `{}`\n\n", variables));
+        }
+        if let Some(expected) = example.get("expected_output").and_then(|e| e.as_str()) {
+            out.push_str(&format!("```text\n{}\n```\n", expected));
+        }
+    }
+}
+
+// ============================================
+// TEMPLATE DIALECTS
+// ============================================
+
+/// Template languages packages can be converted to and imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateDialect {
+    Handlebars,
+    Jinja,
+}
+
+/// Identifier usable as a partial/macro name
+fn template_name(section: &PromptSection) -> String {
+    section
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn to_templates(export: &PackageExport, dialect: TemplateDialect) -> String {
+    let mut out = match dialect {
+        TemplateDialect::Handlebars => format!(
+            "{{{{!-- {} ({}) v{}\n  Sections are inline partials. Expects helpers: eq, ne, gt, lt,\n  upper, lower, title, sentence, article, join, plural. --}}}}\n",
+            export.package.name, export.package.namespace, export.package.version
+        ),
+        TemplateDialect::Jinja => format!(
+            "{{# {} ({}) v{}\n   Sections are macros. Expects an `article` filter. #}}\n",
+            export.package.name, export.package.namespace, export.package.version
+        ),
+    };
+
+    for section in &export.sections {
+        let Some(content) = parse_content(section) else {
+            continue;
+        };
+        let name = template_name(section);
+        let body = dialect.convert(&content);
+        out.push('\n');
+        if !section.description.is_empty() {
+            out.push_str(&dialect.comment(&section.description));
+            out.push('\n');
+        }
+        match dialect {
+            TemplateDialect::Handlebars => out.push_str(&format!(
+                "{{{{#*inline \"{}\"}}}}{}{{{{/inline}}}}\n",
+                name, body
+            )),
+            TemplateDialect::Jinja => out.push_str(&format!(
+                "{{% macro {}() %}}{}{{% endmacro %}}\n",
+                name, body
+            )),
+        }
+    }
+
+    // Entry points render their partial/macro
+    for section in export.sections.iter().filter(|s| s.is_entry_point) {
+        let id = extract_id(&section.id).unwrap_or_default();
+        out.push('\n');
+        out.push_str(&dialect.comment(&format!("Entry point {} ({})", section.name, id)));
+        out.push('\n');
+        match dialect {
+            TemplateDialect::Handlebars => {
+                out.push_str(&format!("{{{{> {}}}}}\n", template_name(section)))
+            }
+            TemplateDialect::Jinja => {
+                out.push_str(&format!("{{{{ {}() }}}}\n", template_name(section)))
+            }
+        }
+    }
+
+    out
+}
+
+impl TemplateDialect {
+    fn comment(self, text: &str) -> String {
+        match self {
+            TemplateDialect::Handlebars => {
+                format!("{{{{!-- {} --}}}}", text.replace("--}}", "- -}}"))
+            }
+            TemplateDialect::Jinja => format!("{{# {} #}}", text.replace("#}", "# }")),
+        }
+    }
+
+    fn output(self, expression: &str) -> String {
+        match self {
+            TemplateDialect::Handlebars => format!("{{{{{}}}}}", expression),
+            TemplateDialect::Jinja => format!("{{{{ {} }}}}", expression),
+        }
+    }
+
+    fn text(self, value: &str) -> String {
+        match self {
+            TemplateDialect::Handlebars => value.replace("{{", "\\{{"),
+            TemplateDialect::Jinja => value
+                .replace("{{", "{{ '{{' }}")
+                .replace("{%", "{{ '{%' }}")
+                .replace("{#", "{{ '{#' }}"),
+        }
+    }
+
+    fn literal(self, value: &serde_json::Value) -> String {
+        match (self, value) {
+            (TemplateDialect::Jinja, serde_json::Value::Bool(b)) => {
+                if *b { "true" } else { "false" }.to_string()
+            }
+            (TemplateDialect::Jinja, serde_json::Value::Null) => "none".to_string(),
+            _ => value.to_string(),
+        }
+    }
+
+    fn if_block(self, condition: &str, then: &str, otherwise: Option<&str>) -> String {
+        match (self, otherwise) {
+            (TemplateDialect::Handlebars, Some(otherwise)) => {
+                format!(
+                    "{{{{#if {}}}}}{}{{{{else}}}}{}{{{{/if}}}}",
+                    condition, then, otherwise
+                )
+            }
+            (TemplateDialect::Handlebars, None) => {
+                format!("{{{{#if {}}}}}{}{{{{/if}}}}", condition, then)
+            }
+            (TemplateDialect::Jinja, Some(otherwise)) => {
+                format!(
+                    "{{% if {} %}}{}{{% else %}}{}{{% endif %}}",
+                    condition, then, otherwise
+                )
+            }
+            (TemplateDialect::Jinja, None) => {
+                format!("{{% if {} %}}{}{{% endif %}}", condition, then)
+            }
+        }
+    }
+
+    /// Binary comparison in the dialect's syntax
+    fn compare(self, op: &str, left: &str, right: &str) -> String {
+        match self {
+            TemplateDialect::Handlebars => format!("({} {} {})", op, left, right),
+            TemplateDialect::Jinja => {
+                let symbol = match op {
+                    "eq" => "==",
+                    "ne" => "!=",
+                    "gt" => ">",
+                    _ => "<",
+                };
+                format!("{} {} {}", left, symbol, right)
+            }
+        }
+    }
+
+    fn condition(self, condition: &Condition) -> String {
+        let nested = |conditions: &[Condition], joiner: &str| -> String {
+            let parts: Vec<String> = conditions.iter().map(|c| self.condition(c)).collect();
+            match self {
+                TemplateDialect::Handlebars => format!("({} {})", joiner, parts.join(" ")),
+                TemplateDialect::Jinja => format!("({})", parts.join(&format!(" {} ", joiner))),
+            }
+        };
+        if let Some(all) = &condition.and {
+            return nested(all, "and");
+        }
+        if let Some(any) = &condition.or {
+            return nested(any, "or");
+        }
+        if let Some(not) = &condition.not {
+            return match self {
+                TemplateDialect::Handlebars => format!("(not {})", self.condition(not)),
+                TemplateDialect::Jinja => format!("not ({})", self.condition(not)),
+            };
+        }
+
+        let subject = match (&condition.variable, &condition.context_key) {
+            (Some(variable), _) => match &condition.path {
+                Some(path) if !path.is_empty() => format!("{}.{}", variable, path),
+                _ => variable.clone(),
+            },
+            (None, Some(key)) => key.clone(),
+            (None, None) => "undefined".to_string(),
+        };
+        let value = self.literal(&condition.value);
+        match condition.operator.as_str() {
+            "equals" => self.compare("eq", &subject, &value),
+            "not_equals" => self.compare("ne", &subject, &value),
+            "greater_than" => self.compare("gt", &subject, &value),
+            "less_than" => self.compare("lt", &subject, &value),
+            "not_exists" | "is_empty" => match self {
+                TemplateDialect::Handlebars => format!("(not {})", subject),
+                TemplateDialect::Jinja => format!("not {}", subject),
+            },
+            "contains" => match self {
+                TemplateDialect::Handlebars => format!("(includes {} {})", subject, value),
+                TemplateDialect::Jinja => format!("{} in {}", value, subject),
+            },
+            "starts_with" | "ends_with" | "matches" => match self {
+                TemplateDialect::Handlebars => {
+                    format!("({} {} {})", condition.operator, subject, value)
+                }
+                TemplateDialect::Jinja => {
+                    format!("{} is {}({})", subject, condition.operator, value)
+                }
+            },
+            // exists, has_items
+            _ => subject,
+        }
+    }
+
+    fn with_case(self, expression: &str, case: Option<TextCase>) -> String {
+        let Some(case) = case else {
+            return self.output(expression);
+        };
+        let name = match (self, case) {
+            (_, TextCase::Upper) => "upper",
+            (_, TextCase::Lower) => "lower",
+            (_, TextCase::Title) => "title",
+            (TemplateDialect::Handlebars, TextCase::Sentence) => "sentence",
+            (TemplateDialect::Jinja, TextCase::Sentence) => "capitalize",
+        };
+        match self {
+            TemplateDialect::Handlebars => format!("{{{{{} {}}}}}", name, expression),
+            TemplateDialect::Jinja => format!("{{{{ {} | {} }}}}", expression, name),
+        }
+    }
+
+    /// Loop over an array variable, joining items with ", "
+    fn each(self, variable: &str, item: Option<&PromptContent>) -> String {
+        match self {
+            TemplateDialect::Handlebars => {
+                let body = item.map_or_else(|| "{{this}}".to_string(), |t| self.convert(t));
+                format!(
+                    "{{{{#each {}}}}}{{{{#unless @first}}}}, {{{{/unless}}}}{}{{{{/each}}}}",
+                    variable, body
+                )
+            }
+            TemplateDialect::Jinja => match item {
+                Some(template) => format!(
+                    "{{% for item in {} %}}{}{{% if not loop.last %}}, {{% endif %}}{{% endfor %}}",
+                    variable,
+                    self.convert(template)
+                ),
+                None => format!("{{{{ {} | join(\", \") }}}}", variable),
+            },
+        }
+    }
+
+    fn convert(self, content: &PromptContent) -> String {
+        match content {
+            PromptContent::Text { value } => self.text(value),
+            PromptContent::Variable {
+                variable_id,
+                path,
+                format,
+            } => {
+                let expression = match path {
+                    Some(path) if !path.is_empty() => format!("{}.{}", variable_id, path),
+                    _ => variable_id.clone(),
+                };
+                let format = format.clone().unwrap_or_default();
+                format!(
+                    "{}{}{}",
+                    self.text(format.prefix.as_deref().unwrap_or_default()),
+                    self.with_case(&expression, format.case),
+                    self.text(format.suffix.as_deref().unwrap_or_default())
+                )
+            }
+            PromptContent::SectionRef { section_id } => {
+                let name: String = section_id
+                    .rsplit(':')
+                    .next()
+                    .unwrap_or(section_id)
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                    .collect();
+                match self {
+                    TemplateDialect::Handlebars => format!("{{{{> {}}}}}", name),
+                    TemplateDialect::Jinja => format!("{{{{ {}() }}}}", name),
+                }
+            }
+            PromptContent::Composite { parts } => parts.iter().map(|p| self.convert(p)).collect(),
+            PromptContent::Conditional {
+                condition,
+                then_content,
+                else_content,
+            } => self.if_block(
+                &self.condition(condition),
+                &self.convert(then_content),
+                else_content.as_deref().map(|e| self.convert(e)).as_deref(),
+            ),
+            PromptContent::List {
+                variable_id,
+                item_template,
+                ..
+            }
+            | PromptContent::Shuffle {
+                variable_id,
+                item_template,
+                ..
+            } => self.each(variable_id, item_template.as_deref()),
+            PromptContent::Context {
+                context_key,
+                fallback,
+            } => match fallback {
+                Some(fallback) => self.if_block(
+                    context_key,
+                    &self.output(context_key),
+                    Some(&self.convert(fallback)),
+                ),
+                None => self.output(context_key),
+            },
+            PromptContent::Plural {
+                count_variable,
+                one,
+                other,
+                ..
+            } => {
+                let count = self.output(count_variable);
+                self.if_block(
+                    &self.compare("eq", count_variable, "1"),
+                    &self.text(one).replace("{count}", &count),
+                    Some(&self.text(other).replace("{count}", &count)),
+                )
+            }
+            PromptContent::Article {
+                word_variable,
+                word_content,
+                ..
+            } => {
+                let word = match (word_variable, word_content) {
+                    (Some(variable), _) => variable.clone(),
+                    (None, Some(_)) => "word".to_string(),
+                    (None, None) => return String::new(),
+                };
+                match self {
+                    TemplateDialect::Handlebars => format!("{{{{article {}}}}}", word),
+                    TemplateDialect::Jinja => format!("{{{{ {} | article }}}}", word),
+                }
+            }
+            PromptContent::Possessive {
+                word_variable,
+                word_content,
+            } => {
+                let word = match (word_variable, word_content) {
+                    (Some(variable), _) => variable.clone(),
+                    (None, Some(_)) => "word".to_string(),
+                    (None, None) => return String::new(),
+                };
+                match self {
+                    TemplateDialect::Handlebars => format!("{{{{possessive {}}}}}", word),
+                    TemplateDialect::Jinja => format!("{{{{ {} | possessive }}}}", word),
+                }
+            }
+            PromptContent::Verb {
+                verb,
+                count_variable,
+                singular,
+            } => {
+                let singular = singular
+                    .clone()
+                    .unwrap_or_else(|| super::renderer::third_person_singular(verb));
+                let plural = if verb.trim() == "be" {
+                    "are"
+                } else {
+                    verb.trim()
+                };
+                self.if_block(
+                    &self.compare("eq", count_variable, "1"),
+                    &self.text(&singular),
+                    Some(&self.text(plural)),
+                )
+            }
+            PromptContent::CountSwitch {
+                count_variable,
+                cases,
+                default_content,
+            } => {
+                let branches: Vec<(String, &PromptContent)> = cases
+                    .iter()
+                    .map(|case| {
+                        let condition = match case.count.as_str() {
+                            Some("zero") => self.compare("eq", count_variable, "0"),
+                            Some("one") => self.compare("eq", count_variable, "1"),
+                            Some(_) => self.compare("gt", count_variable, "1"),
+                            None => self.compare("eq", count_variable, &case.count.to_string()),
+                        };
+                        (condition, &case.content)
+                    })
+                    .collect();
+                self.chain(&branches, default_content.as_deref())
+            }
+            PromptContent::Switch {
+                variable_id,
+                cases,
+                default_content,
+            } => {
+                let branches: Vec<(String, &PromptContent)> = cases
+                    .iter()
+                    .map(|case| {
+                        (
+                            self.compare("eq", variable_id, &self.literal(&case.value)),
+                            &case.content,
+                        )
+                    })
+                    .collect();
+                self.chain(&branches, default_content.as_deref())
+            }
+            PromptContent::PickOne { candidates, .. }
+            | PromptContent::PickMany { candidates, .. } => {
+                self.random_choice("pick", candidates.iter())
+            }
+            PromptContent::WeightedPick { options } => {
+                self.random_choice("weighted-pick", options.iter().map(|o| &o.content))
+            }
+            PromptContent::RandomValue {
+                pool,
+                pool_variable,
+                ..
+            } => match (self, pool, pool_variable) {
+                (TemplateDialect::Jinja, Some(pool), _) => format!(
+                    "{{{{ {} | random }}}}",
+                    serde_json::to_string(pool).unwrap_or_default()
+                ),
+                (TemplateDialect::Jinja, None, Some(variable)) => {
+                    format!("{{{{ {} | random }}}}", variable)
+                }
+                (TemplateDialect::Handlebars, Some(pool), _) => format!(
+                    "{}{}",
+                    self.comment("random value"),
+                    pool.first().map(|v| self.text(v)).unwrap_or_default()
+                ),
+                (TemplateDialect::Handlebars, None, Some(variable)) => {
+                    format!("{}{{{{{}.[0]}}}}", self.comment("random value"), variable)
+                }
+                _ => self.comment("random value from data type"),
+            },
+            PromptContent::Unknown => self.comment("unsupported content"),
+        }
+    }
+
+    /// if / else-if chain
+    fn chain(
+        self,
+        branches: &[(String, &PromptContent)],
+        default: Option<&PromptContent>,
+    ) -> String {
+        let default = default.map(|d| self.convert(d));
+        match self {
+            TemplateDialect::Jinja => {
+                let mut out = String::new();
+                for (i, (condition, content)) in branches.iter().enumerate() {
+                    let keyword = if i == 0 { "if" } else { "elif" };
+                    out.push_str(&format!(
+                        "{{% {} {} %}}{}",
+                        keyword,
+                        condition,
+                        self.convert(content)
+                    ));
+                }
+                if branches.is_empty() {
+                    return default.unwrap_or_default();
+                }
+                if let Some(default) = default {
+                    out.push_str(&format!("{{% else %}}{}", default));
+                }
+                out.push_str("{% endif %}");
+                out
+            }
+            TemplateDialect::Handlebars => branches
+                .iter()
+                .rev()
+                .fold(default, |otherwise, (condition, content)| {
+                    Some(self.if_block(condition, &self.convert(content), otherwise.as_deref()))
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Random selection: Jinja picks among text options, Handlebars keeps the first
+    fn random_choice<'c>(
+        self,
+        kind: &str,
+        options: impl Iterator<Item = &'c PromptContent>,
+    ) -> String {
+        let options: Vec<&PromptContent> = options.collect();
+        let texts: Option<Vec<&str>> = options
+            .iter()
+            .map(|o| match o {
+                PromptContent::Text { value } => Some(value.as_str()),
+                _ => None,
+            })
+            .collect();
+        match (self, texts) {
+            (TemplateDialect::Jinja, Some(texts)) if !texts.is_empty() => format!(
+                "{{{{ {} | random }}}}",
+                serde_json::to_string(&texts).unwrap_or_default()
+            ),
+            _ => format!(
+                "{}{}",
+                self.comment(&format!("{}: first of {} options", kind, options.len())),
+                options.first().map(|o| self.convert(o)).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_export_formats() {
+        let export: PackageExport = serde_json::from_value(json!({
+            "format_version": "1.0.0",
+            "exported_at": "",
+            "package": {
+                "namespace": "demo", "name": "Demo", "version": "1.0.0",
+                "description": "Demo package", "author": "me",
+                "dependencies": [], "exports": [], "created_at": "", "updated_at": ""
+            },
+            "sections": [{
+                "package_id": "pkg", "namespace": "demo", "name": "greeting",
+                "description": "Says hello",
+                "content": { "type": "composite", "parts": [
+                    { "type": "text", "value": "Hello " },
+                    { "type": "variable", "variable_id": "name", "format": { "case": "upper" } },
+                    { "type": "conditional",
+                      "condition": { "variable": "mood", "operator": "equals", "value": "happy" },
+                      "then_content": { "type": "text", "value": "!" },
+                      "else_content": { "type": "text", "value": "." } },
+                    { "type": "pick-one", "candidates": [
+                        { "type": "text", "value": " Hi" }, { "type": "text", "value": " Yo" } ] }
+                ]},
+                "is_entry_point": true, "exportable": true,
+                "required_variables": ["name"],
+                "variables": [{ "id": "name", "type": "string", "required": true }],
+                "examples": [{ "name": "Basic", "variables": { "name": "Ada" }, "expected_output": "Hello ADA." }],
+                "created_at": "", "updated_at": ""
+            }],
+            "separator_sets": [], "data_types": [], "tags": []
+        }))
+        .unwrap();
+
+        let markdown = export_text(&export, ExportFormat::Markdown).unwrap();
+        assert_eq!(markdown.file_name, "demo.md");
+        assert!(markdown.content.starts_with("# Demo\n"));
+        assert!(markdown.content.contains("## Entry points"));
+        assert!(markdown.content.contains("| `name` | string | yes |  |  |"));
+        assert!(markdown.content.contains("```text\nHello ADA.\n```"));
+
+        let yaml = export_text(&export, ExportFormat::Yaml).unwrap();
+        let parsed: PackageExport = serde_yaml::from_str(&yaml.content).unwrap();
+        assert_eq!(parsed.sections[0].name, "greeting");
+
+        let handlebars = export_text(&export, ExportFormat::Handlebars).unwrap();
+        assert!(handlebars.content.contains(
+            "{{#*inline \"greeting\"}}Hello {{upper name}}{{#if (eq mood \"happy\")}}!{{else}}.{{/if}}{{!-- pick: first of 2 options --}} Hi{{/inline}}"
+        ));
+        assert!(handlebars.content.contains("{{> greeting}}"));
+
+        let jinja = export_text(&export, ExportFormat::Jinja).unwrap();
+        assert!(jinja.content.contains(
+            "{% macro greeting() %}Hello {{ name | upper }}{% if mood == \"happy\" %}!{% else %}.{% endif %}{{ [\" Hi\",\" Yo\"] | random }}{% endmacro %}"
+        ));
+        assert!(jinja.content.contains("{{ greeting() }}"));
+    }
+}
@@ -0,0 +1,376 @@
+// Package dependency graph
+//
+// Nodes are a package's sections, data types and separator sets, plus the
+// records of other packages (and built-in separator sets) they point to.
+// Edges follow `section-ref`s, `separator_set_id`s and `data_type_id`s,
+// resolved the way the renderer resolves them. Fragments nothing references
+// are flagged as orphaned.
+
+use super::renderer::{builtin_separator_sets, PromptContent, PromptLibrary, BUILTIN_NAMESPACE};
+use super::validation::walk;
+use super::{extract_id, PromptSection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphNodeKind {
+    Section,
+    DataType,
+    SeparatorSet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphEdgeKind {
+    SectionRef,
+    SeparatorSet,
+    DataType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// "<kind>:<record key>", unique within the graph
+    pub id: String,
+    pub kind: GraphNodeKind,
+    pub name: String,
+    pub namespace: String,
+    pub is_entry_point: bool,
+    /// Belongs to another package or is built in
+    pub external: bool,
+    /// A fragment of this package that nothing references
+    pub orphaned: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: GraphEdgeKind,
+    /// Where the reference sits ("content.parts[1]", "variables.style")
+    pub path: String,
+}
+
+/// A reference that doesn't resolve to any record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedReference {
+    pub from: String,
+    pub kind: GraphEdgeKind,
+    pub reference: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageGraph {
+    pub package_id: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub unresolved: Vec<UnresolvedReference>,
+}
+
+fn node_id(kind: GraphNodeKind, key: &str) -> String {
+    let prefix = match kind {
+        GraphNodeKind::Section => "section",
+        GraphNodeKind::DataType => "data_type",
+        GraphNodeKind::SeparatorSet => "separator_set",
+    };
+    format!("{}:{}", prefix, key)
+}
+
+/// (edge kind, reference, path) for every reference a section makes
+pub(super) fn references_of(section: &PromptSection) -> Vec<(GraphEdgeKind, String, String)> {
+    let mut found = Vec::new();
+    for (field, value) in section.content_fields() {
+        let Ok(content) = serde_json::from_value::<PromptContent>(value.clone()) else {
+            continue;
+        };
+        walk(&content, field.to_string(), false, &mut |node, path, _| {
+            let path = path.to_string();
+            match node {
+                PromptContent::SectionRef { section_id } => {
+                    found.push((GraphEdgeKind::SectionRef, section_id.clone(), path))
+                }
+                PromptContent::List {
+                    separator_set_id, ..
+                } => found.push((GraphEdgeKind::SeparatorSet, separator_set_id.clone(), path)),
+                PromptContent::PickMany {
+                    separator_set_id: Some(separator_set_id),
+                    ..
+                }
+                | PromptContent::Shuffle {
+                    separator_set_id: Some(separator_set_id),
+                    ..
+                } => found.push((GraphEdgeKind::SeparatorSet, separator_set_id.clone(), path)),
+                PromptContent::RandomValue {
+                    data_type_id: Some(data_type_id),
+                    ..
+                } => found.push((GraphEdgeKind::DataType, data_type_id.clone(), path)),
+                _ => {}
+            }
+        });
+    }
+    for definition in &section.variables {
+        if let (Some(id), Some(data_type_id)) = (
+            definition.get("id").and_then(|v| v.as_str()),
+            definition.get("data_type_id").and_then(|v| v.as_str()),
+        ) {
+            found.push((
+                GraphEdgeKind::DataType,
+                data_type_id.to_string(),
+                format!("variables.{}", id),
+            ));
+        }
+    }
+    found
+}
+
+/// The dependency graph of one package
+pub fn package_graph(library: &PromptLibrary, package_id: &str) -> PackageGraph {
+    let mut nodes: BTreeMap<String, GraphNode> = BTreeMap::new();
+    let mut add = |kind, key: String, name: &str, namespace: &str, entry, external| {
+        let id = node_id(kind, &key);
+        nodes.entry(id.clone()).or_insert_with(|| GraphNode {
+            id: id.clone(),
+            kind,
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            is_entry_point: entry,
+            external,
+            orphaned: false,
+        });
+        id
+    };
+
+    let own = |owner: &str| owner == package_id;
+    for section in library.sections.iter().filter(|s| own(&s.package_id)) {
+        let key = extract_id(&section.id).unwrap_or_else(|| section.name.clone());
+        add(
+            GraphNodeKind::Section,
+            key,
+            &section.name,
+            &section.namespace,
+            section.is_entry_point,
+            false,
+        );
+    }
+    for data_type in library.data_types.iter().filter(|t| own(&t.package_id)) {
+        let key = extract_id(&data_type.id).unwrap_or_else(|| data_type.name.clone());
+        add(
+            GraphNodeKind::DataType,
+            key,
+            &data_type.name,
+            &data_type.namespace,
+            false,
+            false,
+        );
+    }
+    for set in library.separator_sets.iter().filter(|s| own(&s.package_id)) {
+        let key = extract_id(&set.id).unwrap_or_else(|| set.name.clone());
+        add(
+            GraphNodeKind::SeparatorSet,
+            key,
+            &set.name,
+            &set.namespace,
+            false,
+            false,
+        );
+    }
+
+    let builtins = builtin_separator_sets();
+    let mut edges = Vec::new();
+    let mut unresolved = Vec::new();
+    for section in library.sections.iter().filter(|s| own(&s.package_id)) {
+        let from = node_id(
+            GraphNodeKind::Section,
+            &extract_id(&section.id).unwrap_or_else(|| section.name.clone()),
+        );
+        for (kind, reference, path) in references_of(section) {
+            let target = match kind {
+                GraphEdgeKind::SectionRef => library
+                    .resolve_section(&reference, Some(&section.namespace))
+                    .map(|s| {
+                        let key = extract_id(&s.id).unwrap_or_else(|| s.name.clone());
+                        let external = !own(&s.package_id);
+                        add(
+                            GraphNodeKind::Section,
+                            key,
+                            &s.name,
+                            &s.namespace,
+                            s.is_entry_point,
+                            external,
+                        )
+                    }),
+                GraphEdgeKind::SeparatorSet => {
+                    match builtins.iter().find(|b| b.name == reference) {
+                        Some(builtin) => Some(add(
+                            GraphNodeKind::SeparatorSet,
+                            builtin.name.clone(),
+                            &builtin.name,
+                            BUILTIN_NAMESPACE,
+                            false,
+                            true,
+                        )),
+                        None => library.resolve_separator_set(&reference).map(|s| {
+                            let key = extract_id(&s.id).unwrap_or_else(|| s.name.clone());
+                            let external = !own(&s.package_id);
+                            add(
+                                GraphNodeKind::SeparatorSet,
+                                key,
+                                &s.name,
+                                &s.namespace,
+                                false,
+                                external,
+                            )
+                        }),
+                    }
+                }
+                GraphEdgeKind::DataType => library.resolve_data_type(&reference).map(|t| {
+                    let key = extract_id(&t.id).unwrap_or_else(|| t.name.clone());
+                    let external = !own(&t.package_id);
+                    add(
+                        GraphNodeKind::DataType,
+                        key,
+                        &t.name,
+                        &t.namespace,
+                        false,
+                        external,
+                    )
+                }),
+            };
+            match target {
+                Some(to) => edges.push(GraphEdge {
+                    from: from.clone(),
+                    to,
+                    kind,
+                    path,
+                }),
+                None => unresolved.push(UnresolvedReference {
+                    from: from.clone(),
+                    kind,
+                    reference,
+                    path,
+                }),
+            }
+        }
+    }
+
+    // Fragments may also be used by other packages
+    let mut referenced: HashSet<String> = edges.iter().map(|e| e.to.clone()).collect();
+    for section in library.sections.iter().filter(|s| !own(&s.package_id)) {
+        for (kind, reference, _) in references_of(section) {
+            if kind != GraphEdgeKind::SectionRef {
+                continue;
+            }
+            if let Some(target) = library.resolve_section(&reference, Some(&section.namespace)) {
+                let key = extract_id(&target.id).unwrap_or_else(|| target.name.clone());
+                referenced.insert(node_id(GraphNodeKind::Section, &key));
+            }
+        }
+    }
+    for node in nodes.values_mut() {
+        node.orphaned = node.kind == GraphNodeKind::Section
+            && !node.external
+            && !node.is_entry_point
+            && !referenced.contains(&node.id);
+    }
+
+    PackageGraph {
+        package_id: package_id.to_string(),
+        nodes: nodes.into_values().collect(),
+        edges,
+        unresolved,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::{test_support, PromptDataType};
+    use serde_json::{json, Value};
+
+    fn section(key: &str, package_id: &str, entry: bool, content: Value) -> PromptSection {
+        PromptSection {
+            package_id: package_id.to_string(),
+            namespace: package_id.to_string(),
+            is_entry_point: entry,
+            variables: vec![json!({ "id": "style", "data_type_id": "Style" })],
+            ..test_support::section(key, content)
+        }
+    }
+
+    #[test]
+    fn test_package_graph() {
+        let data_type: PromptDataType = serde_json::from_value(json!({
+            "package_id": "common", "namespace": "common", "name": "Style", "description": "",
+            "base_type": "string", "examples": [], "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let library = PromptLibrary {
+            sections: vec![
+                section(
+                    "main",
+                    "pkg",
+                    true,
+                    json!({ "type": "composite", "parts": [
+                        { "type": "section-ref", "section_id": "helper" },
+                        { "type": "section-ref", "section_id": "common:shared" },
+                        { "type": "list", "variable_id": "xs", "separator_set_id": "bullet-list" },
+                        { "type": "section-ref", "section_id": "missing" }
+                    ]}),
+                ),
+                section(
+                    "helper",
+                    "pkg",
+                    false,
+                    json!({ "type": "text", "value": "h" }),
+                ),
+                section(
+                    "unused",
+                    "pkg",
+                    false,
+                    json!({ "type": "text", "value": "u" }),
+                ),
+                section(
+                    "shared",
+                    "common",
+                    false,
+                    json!({ "type": "text", "value": "s" }),
+                ),
+            ],
+            data_types: vec![data_type],
+            ..Default::default()
+        };
+
+        let graph = package_graph(&library, "pkg");
+        let nodes: Vec<(&str, bool, bool)> = graph
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n.external, n.orphaned))
+            .collect();
+        assert_eq!(
+            nodes,
+            vec![
+                ("data_type:Style", true, false),
+                ("section:helper", false, false),
+                ("section:main", false, false),
+                ("section:shared", true, false),
+                ("section:unused", false, true),
+                ("separator_set:bullet-list", true, false),
+            ]
+        );
+        let edges: Vec<(&str, &str, &str)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.path.as_str()))
+            .collect();
+        assert!(edges.contains(&("section:main", "section:helper", "content.parts[0]")));
+        assert!(edges.contains(&(
+            "section:main",
+            "separator_set:bullet-list",
+            "content.parts[2]"
+        )));
+        assert!(edges.contains(&("section:unused", "data_type:Style", "variables.style")));
+        assert_eq!(graph.unresolved.len(), 1);
+        assert_eq!(graph.unresolved[0].reference, "missing");
+    }
+}
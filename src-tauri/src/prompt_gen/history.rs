@@ -0,0 +1,231 @@
+// Prompt render history
+//
+// Server-side renders are recorded in `prompt_renders` together with
+// the variable values and the RNG seed they used, so a good random prompt can
+// be reproduced exactly later by replaying the entry.
+
+use super::get_timestamp;
+use super::renderer::{render_section, PromptLibrary, RenderedSection};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// Entries kept per section (older ones are dropped when recording)
+const MAX_HISTORY_PER_SECTION: usize = 200;
+
+/// History entry as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RenderHistoryRecord {
+    pub id: Thing,
+    pub section_id: String,
+    pub variables: serde_json::Map<String, serde_json::Value>,
+    pub seed: u64,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    pub rendered_at: String,
+}
+
+/// User-facing history entry with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderHistoryEntry {
+    pub id: String,
+    pub section_id: String,
+    pub variables: serde_json::Map<String, serde_json::Value>,
+    pub seed: u64,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    pub rendered_at: String,
+}
+
+impl From<RenderHistoryRecord> for RenderHistoryEntry {
+    fn from(record: RenderHistoryRecord) -> Self {
+        RenderHistoryEntry {
+            id: record.id.id.to_raw(),
+            section_id: record.section_id,
+            variables: record.variables,
+            seed: record.seed,
+            text: record.text,
+            negative_prompt: record.negative_prompt,
+            rendered_at: record.rendered_at,
+        }
+    }
+}
+
+fn history_key(id: &str) -> &str {
+    id.strip_prefix("prompt_renders:").unwrap_or(id)
+}
+
+/// Record a render and return the stored entry
+pub async fn record_render(
+    db: &Database,
+    rendered: &RenderedSection,
+) -> Result<RenderHistoryEntry, AppError> {
+    let key = uuid::Uuid::new_v4().to_string();
+    let record = RenderHistoryRecord {
+        id: Thing::from(("prompt_renders", key.as_str())),
+        section_id: rendered.section_id.clone(),
+        variables: rendered.variables.clone(),
+        seed: rendered.seed,
+        text: rendered.text.clone(),
+        negative_prompt: rendered.negative_prompt.clone(),
+        rendered_at: get_timestamp(),
+    };
+
+    let saved: Option<RenderHistoryRecord> = db
+        .db
+        .create(("prompt_renders", key.as_str()))
+        .content(record)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to record render: {}", e)))?;
+    let saved = saved.ok_or_else(|| AppError::Database("Failed to record render".to_string()))?;
+
+    // Keep the history of each section bounded
+    db.query(
+        "LET $old = (SELECT VALUE id FROM (SELECT id, rendered_at FROM prompt_renders
+                 WHERE section_id = $section_id ORDER BY rendered_at DESC START $keep));
+             DELETE $old;",
+    )
+    .bind(("section_id", rendered.section_id.clone()))
+    .bind(("keep", MAX_HISTORY_PER_SECTION))
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to trim render history: {}", e)))?
+    .check()
+    .map_err(|e| AppError::Database(format!("Failed to trim render history: {}", e)))?;
+
+    Ok(saved.into())
+}
+
+/// Recorded renders, newest first
+pub async fn list_render_history(
+    db: &Database,
+    section_id: Option<String>,
+    limit: usize,
+) -> Result<Vec<RenderHistoryEntry>, AppError> {
+    let filter = if section_id.is_some() {
+        " WHERE section_id = $section_id"
+    } else {
+        ""
+    };
+    let mut result = db
+        .query(format!(
+            "SELECT * FROM prompt_renders{} ORDER BY rendered_at DESC LIMIT $limit",
+            filter
+        ))
+        .bind(("section_id", section_id))
+        .bind(("limit", limit))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query render history: {}", e)))?;
+
+    let records: Vec<RenderHistoryRecord> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse render history: {}", e)))?;
+    Ok(records.into_iter().map(|r| r.into()).collect())
+}
+
+pub async fn get_render_history_entry(
+    db: &Database,
+    id: &str,
+) -> Result<RenderHistoryEntry, AppError> {
+    let record: Option<RenderHistoryRecord> = db
+        .db
+        .select(("prompt_renders", history_key(id)))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get render history: {}", e)))?;
+    record
+        .map(|r| r.into())
+        .ok_or_else(|| AppError::NotFound(format!("Render history entry {}", id)))
+}
+
+pub async fn delete_render_history_entry(db: &Database, id: &str) -> Result<(), AppError> {
+    let _: Option<RenderHistoryRecord> = db
+        .db
+        .delete(("prompt_renders", history_key(id)))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete render history: {}", e)))?;
+    Ok(())
+}
+
+/// Render an entry again with its recorded variables and seed
+/// The output matches as long as the sections involved haven't changed.
+pub fn replay(
+    library: &PromptLibrary,
+    entry: &RenderHistoryEntry,
+) -> Result<RenderedSection, AppError> {
+    let mut rendered = render_section(
+        library,
+        &entry.section_id,
+        entry.variables.clone(),
+        Some(entry.seed),
+    )?;
+    rendered.history_id = Some(entry.id.clone());
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::PromptSection;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_render_history_replay() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let section: PromptSection = serde_json::from_value(json!({
+            "package_id": "pkg",
+            "namespace": "demo",
+            "name": "scene",
+            "description": "",
+            "content": { "type": "composite", "parts": [
+                { "type": "pick-one", "candidates": [
+                    { "type": "text", "value": "a castle" },
+                    { "type": "text", "value": "a forest" },
+                    { "type": "text", "value": "a harbor" }
+                ]},
+                { "type": "text", "value": " at " },
+                { "type": "random-value", "pool": ["dawn", "noon", "dusk", "night"] },
+                { "type": "text", "value": ", " },
+                { "type": "variable", "variable_id": "style" }
+            ]},
+            "is_entry_point": true,
+            "exportable": true,
+            "required_variables": [],
+            "variables": [{ "id": "style", "default_value": "oil painting" }],
+            "created_at": "",
+            "updated_at": ""
+        }))
+        .unwrap();
+        let _: Option<PromptSection> = db
+            .db
+            .create(("prompt_sections", "scene"))
+            .content(section)
+            .await
+            .unwrap();
+        let library = PromptLibrary::load(&db).await.unwrap();
+
+        let rendered =
+            render_section(&library, "scene", serde_json::Map::new(), Some(123_456)).unwrap();
+        let entry = record_render(&db, &rendered).await.unwrap();
+        assert_eq!(entry.seed, 123_456);
+        assert_eq!(entry.variables["style"], "oil painting");
+
+        let history = list_render_history(&db, Some("scene".to_string()), 10)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].id, entry.id);
+
+        let loaded = get_render_history_entry(&db, &entry.id).await.unwrap();
+        let replayed = replay(&library, &loaded).unwrap();
+        assert_eq!(replayed.text, rendered.text);
+        assert_eq!(replayed.history_id.as_deref(), Some(entry.id.as_str()));
+
+        delete_render_history_entry(&db, &entry.id).await.unwrap();
+        assert!(list_render_history(&db, None, 10).await.unwrap().is_empty());
+    }
+}
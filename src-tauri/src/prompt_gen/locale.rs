@@ -0,0 +1,158 @@
+// Locale rules for rendering
+//
+// CLDR cardinal plural categories and list conjunctions for the languages
+// prompt packages are commonly written in. Only the language part of a
+// locale tag matters ("de-AT" behaves like "de"); unknown languages follow
+// the English rules.
+
+/// CLDR plural category of a count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Conjunction joining the last two list items
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conjunction {
+    And,
+    Or,
+}
+
+/// Language part of a locale tag, lowercased
+pub fn language(locale: &str) -> String {
+    locale
+        .trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Plural category of `n` in `locale` (cardinal rules)
+pub fn plural_category(locale: &str, n: f64) -> PluralCategory {
+    use PluralCategory::*;
+
+    let n = n.abs();
+    // Fractions are "other" in every rule implemented here except cs/sk
+    let integer = n.fract() == 0.0;
+    let i = n.trunc() as u64;
+    let (mod10, mod100) = (i % 10, i % 100);
+
+    match language(locale).as_str() {
+        "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" => Other,
+        "fr" | "pt" if i <= 1 => One,
+        "fr" | "pt" => Other,
+        "ru" | "uk" | "be" if !integer => Other,
+        "ru" | "uk" | "be" if mod10 == 1 && mod100 != 11 => One,
+        "ru" | "uk" | "be" if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) => Few,
+        "ru" | "uk" | "be" => Many,
+        "pl" if !integer => Other,
+        "pl" if i == 1 => One,
+        "pl" if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) => Few,
+        "pl" => Many,
+        "cs" | "sk" if !integer => Many,
+        "cs" | "sk" if i == 1 => One,
+        "cs" | "sk" if (2..=4).contains(&i) => Few,
+        "cs" | "sk" => Other,
+        "ar" if !integer => Other,
+        "ar" => match i {
+            0 => Zero,
+            1 => One,
+            2 => Two,
+            _ if (3..=10).contains(&mod100) => Few,
+            _ if (11..=99).contains(&mod100) => Many,
+            _ => Other,
+        },
+        "he" if integer && i == 1 => One,
+        "he" if integer && i == 2 => Two,
+        "he" => Other,
+        _ if integer && i == 1 => One,
+        _ => Other,
+    }
+}
+
+/// The word for "and"/"or" in `locale`
+pub fn conjunction(locale: &str, kind: Conjunction) -> &'static str {
+    let (and, or) = match language(locale).as_str() {
+        "de" => ("und", "oder"),
+        "fr" => ("et", "ou"),
+        "es" => ("y", "o"),
+        "it" => ("e", "o"),
+        "pt" => ("e", "ou"),
+        "nl" => ("en", "of"),
+        "sv" => ("och", "eller"),
+        "da" | "no" | "nb" | "nn" => ("og", "eller"),
+        "pl" => ("i", "lub"),
+        "cs" | "sk" => ("a", "nebo"),
+        "ru" => ("и", "или"),
+        "uk" => ("і", "або"),
+        _ => ("and", "or"),
+    };
+    match kind {
+        Conjunction::And => and,
+        Conjunction::Or => or,
+    }
+}
+
+/// Whether lists use a comma before the final conjunction (Oxford comma)
+pub fn uses_serial_comma(locale: &str) -> bool {
+    language(locale) == "en"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PluralCategory::*;
+
+    #[test]
+    fn test_locale_rules() {
+        let categories = |locale: &str| -> Vec<PluralCategory> {
+            [0.0, 1.0, 2.0, 5.0, 11.0, 22.0, 1.5]
+                .iter()
+                .map(|n| plural_category(locale, *n))
+                .collect()
+        };
+        assert_eq!(
+            categories("en-US"),
+            vec![Other, One, Other, Other, Other, Other, Other]
+        );
+        assert_eq!(categories("de"), categories("en"));
+        assert_eq!(
+            categories("fr"),
+            vec![One, One, Other, Other, Other, Other, One]
+        );
+        assert_eq!(
+            categories("ru"),
+            vec![Many, One, Few, Many, Many, Few, Other]
+        );
+        assert_eq!(
+            categories("ar"),
+            vec![Zero, One, Two, Few, Many, Many, Other]
+        );
+        assert_eq!(categories("ja")[1], Other);
+
+        assert_eq!(conjunction("de_DE", Conjunction::And), "und");
+        assert_eq!(conjunction("fr", Conjunction::Or), "ou");
+        assert_eq!(conjunction("xx", Conjunction::And), "and");
+        assert!(uses_serial_comma("en-GB"));
+        assert!(!uses_serial_comma("de"));
+    }
+}
@@ -0,0 +1,255 @@
+// Variation matrix
+//
+// Renders a section once for every combination of the values of selected
+// variables (the cartesian product of the axes), e.g. style × lighting ×
+// mood. An axis takes its values from the request, else from the variable's
+// enum values (its own or its data type's), else from the items of an array
+// value passed for it. All combinations share one seed, so only the axes
+// change between rows.
+
+use super::renderer::{display_value, render_section, PromptLibrary};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Most combinations a single matrix may render
+pub const MAX_MATRIX_COMBINATIONS: usize = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrixAxis {
+    pub variable: String,
+    /// Values to try; defaults to the variable's enum or array values
+    #[serde(default)]
+    pub values: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrixRow {
+    /// "style=anime, lighting=soft"
+    pub label: String,
+    /// Axis values of this row, in axis order
+    pub values: Vec<Value>,
+    pub text: String,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMatrix {
+    pub section_id: String,
+    pub seed: u64,
+    /// Axis variable names, the columns of the table
+    pub axes: Vec<String>,
+    pub rows: Vec<MatrixRow>,
+}
+
+/// Values an axis varies over
+fn axis_values(
+    library: &PromptLibrary,
+    definitions: &[Value],
+    axis: &MatrixAxis,
+    variables: &Map<String, Value>,
+) -> Vec<Value> {
+    if let Some(values) = axis.values.as_ref().filter(|v| !v.is_empty()) {
+        return values.clone();
+    }
+    let definition = definitions
+        .iter()
+        .find(|d| d.get("id").and_then(|id| id.as_str()) == Some(axis.variable.as_str()));
+    let enum_values = definition.and_then(|d| {
+        d.get("enum_values").cloned().or_else(|| {
+            d.get("data_type_id")
+                .and_then(|id| id.as_str())
+                .and_then(|id| library.resolve_data_type(id))
+                .and_then(|t| t.validation.as_ref())
+                .and_then(|v| v.get("enum_values"))
+                .cloned()
+        })
+    });
+    match enum_values {
+        Some(Value::Array(values)) if !values.is_empty() => values,
+        _ => match variables.get(&axis.variable) {
+            Some(Value::Array(items)) => items.clone(),
+            _ => Vec::new(),
+        },
+    }
+}
+
+/// Render every combination of the axes' values
+pub fn render_matrix(
+    library: &PromptLibrary,
+    section_id: &str,
+    axes: &[MatrixAxis],
+    variables: Map<String, Value>,
+    seed: Option<u64>,
+) -> Result<PromptMatrix, AppError> {
+    let section = library
+        .find_section(section_id)
+        .ok_or_else(|| AppError::NotFound(format!("Section {}", section_id)))?;
+    if axes.is_empty() {
+        return Err(AppError::Validation(
+            "A matrix needs at least one axis".to_string(),
+        ));
+    }
+
+    let mut columns: Vec<Vec<Value>> = Vec::new();
+    for (i, axis) in axes.iter().enumerate() {
+        if axes[..i].iter().any(|a| a.variable == axis.variable) {
+            return Err(AppError::Validation(format!(
+                "Variable '{}' is used by more than one axis",
+                axis.variable
+            )));
+        }
+        let values = axis_values(library, &section.variables, axis, &variables);
+        if values.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Variable '{}' has no values to vary",
+                axis.variable
+            )));
+        }
+        columns.push(values);
+    }
+    let combinations = columns
+        .iter()
+        .try_fold(1usize, |total, values| total.checked_mul(values.len()))
+        .filter(|total| *total <= MAX_MATRIX_COMBINATIONS)
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "The matrix has more than {} combinations; remove values or axes",
+                MAX_MATRIX_COMBINATIONS
+            ))
+        })?;
+
+    let seed = seed.unwrap_or_else(|| u64::from(rand::random::<u32>()));
+    let mut matrix = PromptMatrix {
+        section_id: section_id.to_string(),
+        seed,
+        axes: axes.iter().map(|a| a.variable.clone()).collect(),
+        rows: Vec::with_capacity(combinations),
+    };
+    for index in 0..combinations {
+        // The first axis varies slowest
+        let mut rest = index;
+        let mut picks = vec![Value::Null; columns.len()];
+        for (column, values) in columns.iter().enumerate().rev() {
+            picks[column] = values[rest % values.len()].clone();
+            rest /= values.len();
+        }
+
+        let mut values = variables.clone();
+        for (axis, value) in axes.iter().zip(&picks) {
+            values.insert(axis.variable.clone(), value.clone());
+        }
+        let rendered = render_section(library, section_id, values, Some(seed))?;
+        matrix.seed = rendered.seed;
+        matrix.rows.push(MatrixRow {
+            label: axes
+                .iter()
+                .zip(&picks)
+                .map(|(axis, value)| format!("{}={}", axis.variable, display_value(value)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            values: picks,
+            text: rendered.text,
+            warnings: rendered.warnings,
+        });
+    }
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::{PromptDataType, PromptSection};
+    use serde_json::json;
+
+    #[test]
+    fn test_render_matrix() {
+        let section: PromptSection = serde_json::from_value(json!({
+            "id": { "tb": "prompt_sections", "id": { "String": "scene" } },
+            "package_id": "pkg", "namespace": "demo", "name": "scene", "description": "",
+            "content": { "type": "composite", "parts": [
+                { "type": "variable", "variable_id": "style" },
+                { "type": "text", "value": " photo, " },
+                { "type": "variable", "variable_id": "light" },
+                { "type": "text", "value": " light, " },
+                { "type": "variable", "variable_id": "mood" }
+            ]},
+            "is_entry_point": true, "exportable": true, "required_variables": [],
+            "variables": [
+                { "id": "style", "type": "enum", "enum_values": ["anime", "noir"] },
+                { "id": "light", "data_type_id": "Lighting" },
+                { "id": "mood", "type": "string" }
+            ],
+            "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let lighting: PromptDataType = serde_json::from_value(json!({
+            "package_id": "pkg", "namespace": "demo", "name": "Lighting", "description": "",
+            "base_type": "enum", "validation": { "enum_values": ["soft", "hard", "rim"] },
+            "examples": [], "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let library = PromptLibrary {
+            sections: vec![section],
+            data_types: vec![lighting],
+            ..Default::default()
+        };
+        let axis = |variable: &str| MatrixAxis {
+            variable: variable.to_string(),
+            values: None,
+        };
+        let mut variables = Map::new();
+        variables.insert("mood".to_string(), json!(["calm", "tense"]));
+
+        let matrix = render_matrix(
+            &library,
+            "scene",
+            &[axis("style"), axis("light")],
+            variables.clone(),
+            Some(7),
+        )
+        .unwrap();
+        assert_eq!(matrix.axes, vec!["style", "light"]);
+        assert_eq!(matrix.rows.len(), 6);
+        assert_eq!(matrix.rows[0].label, "style=anime, light=soft");
+        assert_eq!(matrix.rows[5].values, vec![json!("noir"), json!("rim")]);
+        assert!(matrix.rows[4].text.starts_with("noir photo, hard light"));
+
+        // Array values and explicit values work as axes too
+        let matrix = render_matrix(
+            &library,
+            "scene",
+            &[
+                axis("mood"),
+                MatrixAxis {
+                    variable: "style".to_string(),
+                    values: Some(vec![json!("pixel art")]),
+                },
+            ],
+            variables.clone(),
+            Some(7),
+        )
+        .unwrap();
+        let texts: Vec<&str> = matrix.rows.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts.len(), 2);
+        assert!(texts[1].starts_with("pixel art photo") && texts[1].ends_with("tense"));
+
+        assert!(render_matrix(&library, "scene", &[], variables.clone(), None).is_err());
+        assert!(render_matrix(
+            &library,
+            "scene",
+            &[axis("style"), axis("style")],
+            variables.clone(),
+            None
+        )
+        .is_err());
+        let many = MatrixAxis {
+            variable: "mood".to_string(),
+            values: Some((0..MAX_MATRIX_COMBINATIONS).map(|i| json!(i)).collect()),
+        };
+        let err = render_matrix(&library, "scene", &[axis("style"), many], variables, None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("combinations"), "{}", err);
+    }
+}
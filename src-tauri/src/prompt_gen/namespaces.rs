@@ -0,0 +1,174 @@
+// Package namespaces
+//
+// Every namespace (primary or additional) belongs to exactly one package.
+// Namespaces of the built-in packages are reserved: re-seeding deletes and
+// recreates everything in them, so user packages must not live there.
+
+use super::{extract_id, PromptPackage};
+use crate::db::Database;
+use crate::error::AppError;
+
+/// Namespaces owned by the seeded example and common packages
+pub const RESERVED_NAMESPACES: &[&str] = &[
+    "examples",
+    "examples-internal",
+    "text2image-common",
+    "t2i-internal",
+];
+
+pub fn is_reserved(namespace: &str) -> bool {
+    RESERVED_NAMESPACES.contains(&namespace)
+}
+
+/// All namespaces of a package, primary first
+fn namespaces_of(package: &PromptPackage) -> Vec<String> {
+    std::iter::once(&package.namespace)
+        .chain(&package.additional_namespaces)
+        .cloned()
+        .collect()
+}
+
+/// Validate a user package's namespaces: well-formed, not reserved and not
+/// used by another package (`package_id` is the package itself when updating)
+pub async fn check_namespaces(
+    db: &Database,
+    package: &PromptPackage,
+    package_id: Option<&str>,
+) -> Result<(), AppError> {
+    let namespaces = namespaces_of(package);
+    for (i, namespace) in namespaces.iter().enumerate() {
+        if namespace.trim().is_empty() || namespace.contains(':') || namespace.contains(' ') {
+            return Err(AppError::Validation(format!(
+                "Invalid namespace '{}'",
+                namespace
+            )));
+        }
+        if namespaces[..i].contains(namespace) {
+            return Err(AppError::Validation(format!(
+                "Namespace '{}' is listed twice",
+                namespace
+            )));
+        }
+    }
+
+    let mut result = db
+        .query(
+            "SELECT * FROM prompt_packages
+             WHERE namespace IN $namespaces OR additional_namespaces CONTAINSANY $namespaces",
+        )
+        .bind(("namespaces", namespaces.clone()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to check namespaces: {}", e)))?;
+    let packages: Vec<PromptPackage> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to check namespaces: {}", e)))?;
+
+    // A package may keep the (reserved) namespaces it already has
+    let (own, others): (Vec<PromptPackage>, Vec<PromptPackage>) = packages
+        .into_iter()
+        .partition(|p| package_id.is_some() && extract_id(&p.id).as_deref() == package_id);
+    let own: Vec<String> = own.iter().flat_map(namespaces_of).collect();
+    if let Some(namespace) = namespaces
+        .iter()
+        .find(|ns| is_reserved(ns) && !own.contains(ns))
+    {
+        return Err(AppError::Validation(format!(
+            "Namespace '{}' is reserved for built-in packages",
+            namespace
+        )));
+    }
+
+    for other in others {
+        let taken = namespaces_of(&other);
+        if let Some(namespace) = namespaces.iter().find(|ns| taken.contains(ns)) {
+            let suggestion = suggest_namespace(db, namespace).await?;
+            return Err(AppError::Validation(format!(
+                "Namespace '{}' is already used by package '{}'; try '{}'",
+                namespace, other.name, suggestion
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// First free "<base>-<n>" namespace
+pub async fn suggest_namespace(db: &Database, base: &str) -> Result<String, AppError> {
+    let mut result = db
+        .query("SELECT VALUE array::concat([namespace], additional_namespaces ?? []) FROM prompt_packages")
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query namespaces: {}", e)))?;
+    let taken: Vec<Vec<String>> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to query namespaces: {}", e)))?;
+    let taken: Vec<String> = taken.into_iter().flatten().collect();
+
+    Ok((2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken.contains(candidate) && !is_reserved(candidate))
+        .unwrap_or_else(|| base.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn package(namespace: &str, additional: &[&str]) -> PromptPackage {
+        serde_json::from_value(json!({
+            "namespace": namespace, "additional_namespaces": additional,
+            "name": namespace, "version": "1.0.0", "description": "", "author": "",
+            "dependencies": [], "exports": [], "created_at": "", "updated_at": ""
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_namespaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let mine = package("mine", &["mine-internal"]);
+        check_namespaces(&db, &mine, None).await.unwrap();
+        let _: Option<PromptPackage> = db
+            .db
+            .create(("prompt_packages", "p1"))
+            .content(mine.clone())
+            .await
+            .unwrap();
+
+        // Updating itself is fine, anyone else using its namespaces is not
+        check_namespaces(&db, &mine, Some("p1")).await.unwrap();
+        let _: Option<PromptPackage> = db
+            .db
+            .create(("prompt_packages", "seeded"))
+            .content(package("examples", &[]))
+            .await
+            .unwrap();
+        check_namespaces(&db, &package("examples", &[]), Some("seeded"))
+            .await
+            .unwrap();
+        let err = check_namespaces(&db, &package("other", &["mine-internal"]), None)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("already used by package 'mine'"), "{}", err);
+        let err = check_namespaces(&db, &package("mine", &[]), None)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("try 'mine-2'"), "{}", err);
+
+        for invalid in [
+            package("examples", &[]),
+            package("ok", &["t2i-internal"]),
+            package("a:b", &[]),
+            package("dup", &["dup"]),
+        ] {
+            assert!(matches!(
+                check_namespaces(&db, &invalid, None).await,
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+}
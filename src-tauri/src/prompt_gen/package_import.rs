@@ -0,0 +1,395 @@
+// Upgrade-aware package import
+//
+// Importing a package whose namespace already exists updates that package in
+// place instead of creating a duplicate: records are matched by namespace and
+// name, changed ones are updated, new ones added. Records that only exist
+// locally are kept and reported, never deleted. "Copy" mode imports the
+// package as a fork under a free namespace instead.
+
+use super::duplicate::duplicate_package;
+use super::namespaces::check_namespaces;
+use super::{
+    extract_id, get_timestamp, PackageExport, PromptDataType, PromptPackage, PromptSection,
+    PromptTag, PromptTemplate, SeparatorSet,
+};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Update the package with the same namespace, or create it
+    #[default]
+    Merge,
+    /// Always create a new package (under a new namespace if taken)
+    Copy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Created,
+    Updated,
+    Copied,
+}
+
+/// Record names by outcome, as "namespace:name"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordChanges {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub unchanged: Vec<String>,
+    /// In the installed package but not in the import (kept)
+    pub local_only: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub package_id: String,
+    pub namespace: String,
+    pub action: ImportAction,
+    pub previous_version: Option<String>,
+    pub version: String,
+    pub sections: RecordChanges,
+    pub separator_sets: RecordChanges,
+    pub data_types: RecordChanges,
+    pub tags: RecordChanges,
+    pub templates: RecordChanges,
+}
+
+/// Package records matched by namespace and name
+trait PackageRecord: Clone + Serialize + DeserializeOwned + Send + Sync + 'static {
+    const TABLE: &'static str;
+
+    fn key(&self) -> String;
+    fn id(&self) -> &Option<Thing>;
+    fn created_at(&self) -> &str;
+    /// Replace the bookkeeping fields
+    fn set_meta(&mut self, id: Option<Thing>, package_id: &str, created_at: &str, updated_at: &str);
+}
+
+macro_rules! package_record {
+    ($type:ty, $table:literal) => {
+        impl PackageRecord for $type {
+            const TABLE: &'static str = $table;
+
+            fn key(&self) -> String {
+                format!("{}:{}", self.namespace, self.name)
+            }
+
+            fn id(&self) -> &Option<Thing> {
+                &self.id
+            }
+
+            fn created_at(&self) -> &str {
+                &self.created_at
+            }
+
+            fn set_meta(
+                &mut self,
+                id: Option<Thing>,
+                package_id: &str,
+                created_at: &str,
+                updated_at: &str,
+            ) {
+                self.id = id;
+                self.package_id = package_id.to_string();
+                self.created_at = created_at.to_string();
+                self.updated_at = updated_at.to_string();
+            }
+        }
+    };
+}
+
+package_record!(PromptSection, "prompt_sections");
+package_record!(SeparatorSet, "prompt_separator_sets");
+package_record!(PromptDataType, "prompt_data_types");
+package_record!(PromptTag, "prompt_tags");
+package_record!(PromptTemplate, "prompt_templates");
+
+/// Whether two records differ in anything but bookkeeping fields
+fn same_content<T: PackageRecord>(a: &T, b: &T) -> bool {
+    let normalize = |record: &T| {
+        let mut record = record.clone();
+        record.set_meta(None, "", "", "");
+        serde_json::to_value(record).ok()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Apply incoming records to a package: add new ones, update changed ones
+async fn merge_records<T: PackageRecord>(
+    db: &Database,
+    package_id: &str,
+    incoming: Vec<T>,
+) -> Result<RecordChanges, AppError> {
+    let mut result = db
+        .query(format!("SELECT * FROM {} WHERE package_id = $id", T::TABLE))
+        .bind(("id", package_id.to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query {}: {}", T::TABLE, e)))?;
+    let mut existing: Vec<T> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse {}: {}", T::TABLE, e)))?;
+
+    let timestamp = get_timestamp();
+    let mut changes = RecordChanges::default();
+    for mut record in incoming {
+        let key = record.key();
+        let Some(index) = existing.iter().position(|e| e.key() == key) else {
+            record.set_meta(None, package_id, &timestamp, &timestamp);
+            let _: Option<T> = db
+                .create_row(T::TABLE, record)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to import {}: {}", key, e)))?;
+            changes.added.push(key);
+            continue;
+        };
+
+        let current = existing.remove(index);
+        if same_content(&record, &current) {
+            changes.unchanged.push(key);
+            continue;
+        }
+        let record_key = extract_id(current.id())
+            .ok_or_else(|| AppError::Database(format!("Record {} has no ID", key)))?;
+        record.set_meta(None, package_id, current.created_at(), &timestamp);
+        let _: Option<T> = db
+            .update_row(T::TABLE, record_key.as_str(), record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update {}: {}", key, e)))?;
+        changes.updated.push(key);
+    }
+    changes.local_only = existing.iter().map(|e| e.key()).collect();
+    Ok(changes)
+}
+
+/// Import a package export, merging into an installed package of the same namespace
+pub async fn import_package(
+    db: &Database,
+    export: PackageExport,
+    mode: ImportMode,
+    new_namespace: Option<String>,
+) -> Result<ImportSummary, AppError> {
+    let mut result = db
+        .query("SELECT * FROM prompt_packages WHERE namespace = $namespace")
+        .bind(("namespace", export.package.namespace.clone()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query packages: {}", e)))?;
+    let installed: Vec<PromptPackage> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse packages: {}", e)))?;
+
+    if mode == ImportMode::Copy && !installed.is_empty() {
+        return import_copy(db, export, new_namespace).await;
+    }
+
+    let timestamp = get_timestamp();
+    let mut package = export.package;
+    package.updated_at = timestamp.clone();
+    let (package_id, action, previous_version) = match installed.into_iter().next() {
+        Some(current) => {
+            let package_id = extract_id(&current.id)
+                .ok_or_else(|| AppError::Database("Package has no ID".to_string()))?;
+            package.id = None;
+            package.created_at = current.created_at;
+            check_namespaces(db, &package, Some(&package_id)).await?;
+            let _: Option<PromptPackage> = db
+                .update_row("prompt_packages", package_id.as_str(), package.clone())
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update package: {}", e)))?;
+            (package_id, ImportAction::Updated, Some(current.version))
+        }
+        None => {
+            package.id = None;
+            package.created_at = timestamp;
+            check_namespaces(db, &package, None).await?;
+            let created: Option<PromptPackage> = db
+                .create_row("prompt_packages", package.clone())
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to import package: {}", e)))?;
+            let package_id = created
+                .and_then(|p| extract_id(&p.id))
+                .ok_or_else(|| AppError::Database("Failed to import package".to_string()))?;
+            (package_id, ImportAction::Created, None)
+        }
+    };
+
+    Ok(ImportSummary {
+        sections: merge_records(db, &package_id, export.sections).await?,
+        separator_sets: merge_records(db, &package_id, export.separator_sets).await?,
+        data_types: merge_records(db, &package_id, export.data_types).await?,
+        tags: merge_records(db, &package_id, export.tags).await?,
+        templates: merge_records(db, &package_id, export.templates).await?,
+        package_id,
+        namespace: package.namespace,
+        action,
+        previous_version,
+        version: package.version,
+    })
+}
+
+/// Import as a fork under `new_namespace`, or the first free "<namespace>-copy[-n]"
+async fn import_copy(
+    db: &Database,
+    export: PackageExport,
+    new_namespace: Option<String>,
+) -> Result<ImportSummary, AppError> {
+    let namespace = match new_namespace.filter(|ns| !ns.trim().is_empty()) {
+        Some(namespace) => namespace,
+        None => {
+            let mut result = db
+                .query("SELECT VALUE namespace FROM prompt_packages")
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to query packages: {}", e)))?;
+            let taken: Vec<String> = result
+                .take(0)
+                .map_err(|e| AppError::Database(format!("Failed to parse packages: {}", e)))?;
+            let base = format!("{}-copy", export.package.namespace);
+            (1..)
+                .map(|n| {
+                    if n == 1 {
+                        base.clone()
+                    } else {
+                        format!("{}-{}", base, n)
+                    }
+                })
+                .find(|candidate| !taken.contains(candidate))
+                .unwrap_or(base)
+        }
+    };
+
+    let added = |keys: Vec<String>| RecordChanges {
+        added: keys,
+        ..Default::default()
+    };
+    let sections: Vec<String> = export.sections.iter().map(|s| s.name.clone()).collect();
+    let separator_sets: Vec<String> = export
+        .separator_sets
+        .iter()
+        .map(|s| s.name.clone())
+        .collect();
+    let data_types: Vec<String> = export.data_types.iter().map(|t| t.name.clone()).collect();
+    let tags: Vec<String> = export.tags.iter().map(|t| t.name.clone()).collect();
+
+    let package = duplicate_package(db, export, &namespace).await?;
+    let qualify = |names: Vec<String>| {
+        names
+            .into_iter()
+            .map(|name| format!("{}:{}", package.namespace, name))
+            .collect()
+    };
+    Ok(ImportSummary {
+        package_id: extract_id(&package.id).unwrap_or_default(),
+        namespace: package.namespace.clone(),
+        action: ImportAction::Copied,
+        previous_version: None,
+        version: package.version.clone(),
+        sections: added(qualify(sections)),
+        separator_sets: added(qualify(separator_sets)),
+        data_types: added(qualify(data_types)),
+        tags: added(qualify(tags)),
+        templates: RecordChanges::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::test_support;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn export(version: &str, sections: serde_json::Value) -> PackageExport {
+        serde_json::from_value(json!({
+            "format_version": "1.0.0",
+            "exported_at": "",
+            "package": {
+                "namespace": "seed", "name": "Seed", "version": version,
+                "description": "", "author": "", "dependencies": [], "exports": [],
+                "created_at": "", "updated_at": ""
+            },
+            "sections": sections,
+            "separator_sets": [],
+            "data_types": [],
+            "tags": [{
+                "package_id": "x", "namespace": "seed", "name": "demo", "description": "",
+                "color": null, "parent": null, "created_at": "", "updated_at": ""
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn section(name: &str, text: &str) -> serde_json::Value {
+        serde_json::to_value(PromptSection {
+            id: None,
+            package_id: "x".to_string(),
+            namespace: "seed".to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            ..test_support::section(name, json!({ "type": "text", "value": text }))
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_package_merges() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let first = export("1.0.0", json!([section("a", "A"), section("b", "B")]));
+        let summary = import_package(&db, first, ImportMode::Merge, None)
+            .await
+            .unwrap();
+        assert_eq!(summary.action, ImportAction::Created);
+        assert_eq!(summary.sections.added, vec!["seed:a", "seed:b"]);
+        let package_id = summary.package_id;
+
+        // Local-only section survives the upgrade
+        let mut local: PromptSection = serde_json::from_value(section("local", "L")).unwrap();
+        local.package_id = package_id.clone();
+        let _: Option<PromptSection> = db
+            .db
+            .create("prompt_sections")
+            .content(local)
+            .await
+            .unwrap();
+
+        let second = export(
+            "1.1.0",
+            json!([section("a", "A"), section("b", "B2"), section("c", "C")]),
+        );
+        let summary = import_package(&db, second, ImportMode::Merge, None)
+            .await
+            .unwrap();
+        assert_eq!(summary.action, ImportAction::Updated);
+        assert_eq!(summary.package_id, package_id);
+        assert_eq!(summary.previous_version.as_deref(), Some("1.0.0"));
+        assert_eq!(summary.sections.unchanged, vec!["seed:a"]);
+        assert_eq!(summary.sections.updated, vec!["seed:b"]);
+        assert_eq!(summary.sections.added, vec!["seed:c"]);
+        assert_eq!(summary.sections.local_only, vec!["seed:local"]);
+        assert_eq!(summary.tags.unchanged, vec!["seed:demo"]);
+
+        let packages: Vec<PromptPackage> = db.db.select("prompt_packages").await.unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].version, "1.1.0");
+        let sections: Vec<PromptSection> = db.db.select("prompt_sections").await.unwrap();
+        assert_eq!(sections.len(), 4);
+        let b = sections.iter().find(|s| s.name == "b").unwrap();
+        assert_eq!(b.content["value"], "B2");
+
+        let third = export("1.1.0", json!([section("a", "A")]));
+        let summary = import_package(&db, third, ImportMode::Copy, None)
+            .await
+            .unwrap();
+        assert_eq!(summary.action, ImportAction::Copied);
+        assert_eq!(summary.namespace, "seed-copy");
+        assert_eq!(summary.sections.added, vec!["seed-copy:a"]);
+        let packages: Vec<PromptPackage> = db.db.select("prompt_packages").await.unwrap();
+        assert_eq!(packages.len(), 2);
+    }
+}
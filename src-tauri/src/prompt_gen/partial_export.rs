@@ -0,0 +1,279 @@
+// Selective export
+//
+// Exports chosen sections of a package together with everything they need
+// from the same package: referenced fragments (transitively), separator sets,
+// data types, and the tags they carry. References into other packages stay
+// references, as in a whole-package export, and the package's dependency
+// list is kept.
+
+use super::graph::{references_of, GraphEdgeKind};
+use super::renderer::{builtin_separator_sets, PromptLibrary};
+use super::{
+    extract_id, get_timestamp, PackageExport, PromptDataType, PromptPackage, PromptSection,
+    PromptTag, SeparatorSet,
+};
+use crate::db::Database;
+use crate::error::AppError;
+use std::collections::{HashSet, VecDeque};
+
+/// Records of one package needed by the selected sections
+#[derive(Debug, Default)]
+struct Selection {
+    package_id: String,
+    sections: Vec<PromptSection>,
+    separator_sets: Vec<SeparatorSet>,
+    data_types: Vec<PromptDataType>,
+}
+
+fn key_of(id: &Option<surrealdb::sql::Thing>, name: &str) -> String {
+    extract_id(id).unwrap_or_else(|| name.to_string())
+}
+
+/// The selected sections plus their same-package dependencies, in library order
+fn select(library: &PromptLibrary, section_ids: &[String]) -> Result<Selection, AppError> {
+    let mut package_id: Option<String> = None;
+    let mut queue = VecDeque::new();
+    for id in section_ids {
+        let section = library
+            .find_section(id)
+            .ok_or_else(|| AppError::NotFound(format!("Section {}", id)))?;
+        match &package_id {
+            Some(package_id) if *package_id != section.package_id => {
+                return Err(AppError::Validation(
+                    "Selected sections must belong to the same package".to_string(),
+                ))
+            }
+            _ => package_id = Some(section.package_id.clone()),
+        }
+        queue.push_back(section);
+    }
+    let package_id =
+        package_id.ok_or_else(|| AppError::Validation("No sections selected".to_string()))?;
+
+    let builtins: HashSet<String> = builtin_separator_sets()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    let (mut sections, mut sets, mut types) = (HashSet::new(), HashSet::new(), HashSet::new());
+    while let Some(section) = queue.pop_front() {
+        if !sections.insert(key_of(&section.id, &section.name)) {
+            continue;
+        }
+        for (kind, reference, _) in references_of(section) {
+            match kind {
+                GraphEdgeKind::SectionRef => {
+                    if let Some(target) =
+                        library.resolve_section(&reference, Some(&section.namespace))
+                    {
+                        if target.package_id == package_id {
+                            queue.push_back(target);
+                        }
+                    }
+                }
+                GraphEdgeKind::SeparatorSet if !builtins.contains(&reference) => {
+                    if let Some(set) = library.resolve_separator_set(&reference) {
+                        if set.package_id == package_id {
+                            sets.insert(key_of(&set.id, &set.name));
+                        }
+                    }
+                }
+                GraphEdgeKind::SeparatorSet => {}
+                GraphEdgeKind::DataType => {
+                    if let Some(data_type) = library.resolve_data_type(&reference) {
+                        if data_type.package_id == package_id {
+                            types.insert(key_of(&data_type.id, &data_type.name));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Selection {
+        sections: library
+            .sections
+            .iter()
+            .filter(|s| sections.contains(&key_of(&s.id, &s.name)))
+            .cloned()
+            .collect(),
+        separator_sets: library
+            .separator_sets
+            .iter()
+            .filter(|s| sets.contains(&key_of(&s.id, &s.name)))
+            .cloned()
+            .collect(),
+        data_types: library
+            .data_types
+            .iter()
+            .filter(|t| types.contains(&key_of(&t.id, &t.name)))
+            .cloned()
+            .collect(),
+        package_id,
+    })
+}
+
+/// Tags used by the sections, with their ancestors
+fn used_tags(tags: Vec<PromptTag>, sections: &[PromptSection]) -> Vec<PromptTag> {
+    let mut needed: HashSet<&str> = sections
+        .iter()
+        .flat_map(|s| s.tags.iter().map(String::as_str))
+        .collect();
+    let mut pending: Vec<&str> = needed.iter().copied().collect();
+    while let Some(name) = pending.pop() {
+        let parent = tags
+            .iter()
+            .find(|t| t.name == name)
+            .and_then(|t| t.parent.as_deref());
+        if let Some(parent) = parent {
+            if needed.insert(parent) {
+                pending.push(parent);
+            }
+        }
+    }
+    let needed: HashSet<String> = needed.into_iter().map(str::to_string).collect();
+    tags.into_iter()
+        .filter(|t| needed.contains(&t.name))
+        .collect()
+}
+
+/// Export the selected sections and their dependencies as a partial package
+pub async fn export_sections(
+    db: &Database,
+    section_ids: &[String],
+) -> Result<PackageExport, AppError> {
+    let library = PromptLibrary::load(db).await?;
+    let selection = select(&library, section_ids)?;
+
+    let package: Option<PromptPackage> = db
+        .db
+        .select(("prompt_packages", selection.package_id.as_str()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get package: {}", e)))?;
+    let mut package =
+        package.ok_or_else(|| AppError::NotFound(format!("Package {}", selection.package_id)))?;
+    let exported: HashSet<String> = selection
+        .sections
+        .iter()
+        .flat_map(|s| [s.name.clone(), key_of(&s.id, &s.name)])
+        .collect();
+    package.exports.retain(|name| exported.contains(name));
+
+    let mut result = db
+        .query("SELECT * FROM prompt_tags WHERE package_id = $id")
+        .bind(("id", selection.package_id.clone()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get tags: {}", e)))?;
+    let tags: Vec<PromptTag> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse tags: {}", e)))?;
+
+    Ok(PackageExport {
+        format_version: "1.0.0".to_string(),
+        exported_at: get_timestamp(),
+        package,
+        templates: Vec::new(),
+        tags: used_tags(tags, &selection.sections),
+        sections: selection.sections,
+        separator_sets: selection.separator_sets,
+        data_types: selection.data_types,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::test_support;
+    use serde_json::{json, Value};
+
+    fn section(key: &str, package_id: &str, content: Value, tags: &[&str]) -> PromptSection {
+        PromptSection {
+            package_id: package_id.to_string(),
+            namespace: package_id.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            variables: vec![json!({ "id": "mood", "data_type_id": "Mood" })],
+            ..test_support::section(key, content)
+        }
+    }
+
+    fn named<T: serde::de::DeserializeOwned>(name: &str, package_id: &str, extra: Value) -> T {
+        let mut value = json!({
+            "package_id": package_id, "namespace": package_id, "name": name,
+            "description": "", "created_at": "", "updated_at": ""
+        });
+        value
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_selective_export() {
+        let text = |value: &str| json!({ "type": "text", "value": value });
+        let library = PromptLibrary {
+            sections: vec![
+                section(
+                    "scene",
+                    "pkg",
+                    json!({ "type": "composite", "parts": [
+                        { "type": "section-ref", "section_id": "subject" },
+                        { "type": "section-ref", "section_id": "common:quality" },
+                        { "type": "list", "variable_id": "xs", "separator_set_id": "Pipes" },
+                        { "type": "list", "variable_id": "ys", "separator_set_id": "bullet-list" }
+                    ]}),
+                    &["art"],
+                ),
+                section(
+                    "subject",
+                    "pkg",
+                    json!({ "type": "section-ref", "section_id": "detail" }),
+                    &[],
+                ),
+                section("detail", "pkg", text("d"), &[]),
+                section("unrelated", "pkg", text("u"), &["misc"]),
+                section("quality", "common", text("q"), &[]),
+            ],
+            separator_sets: vec![
+                named("Pipes", "pkg", json!({ "rules": {} })),
+                named("Unused", "pkg", json!({ "rules": {} })),
+            ],
+            data_types: vec![named(
+                "Mood",
+                "pkg",
+                json!({ "base_type": "string", "examples": [] }),
+            )],
+        };
+
+        let selection = select(&library, &["prompt_sections:scene".to_string()]).unwrap();
+        assert_eq!(selection.package_id, "pkg");
+        let names: Vec<&str> = selection.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["scene", "subject", "detail"]);
+        assert_eq!(selection.separator_sets.len(), 1);
+        assert_eq!(selection.separator_sets[0].name, "Pipes");
+        assert_eq!(selection.data_types.len(), 1);
+
+        let tags: Vec<PromptTag> = vec![
+            named("art", "pkg", json!({ "color": null, "parent": "media" })),
+            named("media", "pkg", json!({ "color": null, "parent": null })),
+            named("misc", "pkg", json!({ "color": null, "parent": null })),
+        ];
+        let kept: Vec<String> = used_tags(tags, &selection.sections)
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(kept, vec!["art", "media"]);
+
+        assert!(matches!(
+            select(&library, &["scene".to_string(), "quality".to_string()]),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            select(&library, &[]),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            select(&library, &["missing".to_string()]),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
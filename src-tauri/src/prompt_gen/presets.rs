@@ -0,0 +1,244 @@
+// Variable presets
+//
+// Named sets of variable values saved for an entry point, so standard
+// generations don't need their variables typed in again. Rendering with a
+// preset starts from its values; variables passed explicitly still win.
+
+use super::{get_timestamp, PromptSection};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use surrealdb::sql::Thing;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPreset {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Thing>,
+    /// Entry point the preset belongs to
+    pub section_id: String,
+    /// Package of that entry point (filled in on save)
+    #[serde(default)]
+    pub package_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub variables: Map<String, Value>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+fn preset_key(id: &str) -> &str {
+    id.strip_prefix("prompt_presets:").unwrap_or(id)
+}
+
+fn section_key(id: &str) -> &str {
+    id.strip_prefix("prompt_sections:").unwrap_or(id)
+}
+
+/// Presets of an entry point, by name
+pub async fn list_presets(db: &Database, section_id: &str) -> Result<Vec<PromptPreset>, AppError> {
+    let mut result = db
+        .query("SELECT * FROM prompt_presets WHERE section_id = $section_id ORDER BY name")
+        .bind(("section_id", section_key(section_id).to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query presets: {}", e)))?;
+    result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse presets: {}", e)))
+}
+
+pub async fn get_preset(db: &Database, id: &str) -> Result<PromptPreset, AppError> {
+    let preset: Option<PromptPreset> = db
+        .db
+        .select(("prompt_presets", preset_key(id)))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get preset: {}", e)))?;
+    preset.ok_or_else(|| AppError::NotFound(format!("Preset {}", id)))
+}
+
+/// Check the name and entry point of a preset about to be saved
+async fn prepare(
+    db: &Database,
+    preset: &mut PromptPreset,
+    id: Option<&str>,
+) -> Result<(), AppError> {
+    preset.name = preset.name.trim().to_string();
+    if preset.name.is_empty() {
+        return Err(AppError::Validation(
+            "Preset name cannot be empty".to_string(),
+        ));
+    }
+    preset.section_id = section_key(&preset.section_id).to_string();
+    let section: Option<PromptSection> = db
+        .db
+        .select(("prompt_sections", preset.section_id.as_str()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get section: {}", e)))?;
+    let section =
+        section.ok_or_else(|| AppError::NotFound(format!("Section {}", preset.section_id)))?;
+    preset.package_id = section.package_id;
+
+    let taken = list_presets(db, &preset.section_id)
+        .await?
+        .into_iter()
+        .any(|p| {
+            p.name == preset.name
+                && id.is_none_or(|id| p.id.as_ref().map(|t| t.id.to_raw()).as_deref() != Some(id))
+        });
+    if taken {
+        return Err(AppError::Validation(format!(
+            "A preset named '{}' already exists for this section",
+            preset.name
+        )));
+    }
+    Ok(())
+}
+
+pub async fn create_preset(
+    db: &Database,
+    mut preset: PromptPreset,
+) -> Result<PromptPreset, AppError> {
+    prepare(db, &mut preset, None).await?;
+    let timestamp = get_timestamp();
+    preset.id = None;
+    preset.created_at = timestamp.clone();
+    preset.updated_at = timestamp;
+
+    let key = uuid::Uuid::new_v4().to_string();
+    let created: Option<PromptPreset> = db
+        .create_row_with_id("prompt_presets", key.as_str(), preset)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to create preset: {}", e)))?;
+    created.ok_or_else(|| AppError::Database("Failed to create preset".to_string()))
+}
+
+pub async fn update_preset(
+    db: &Database,
+    id: &str,
+    mut preset: PromptPreset,
+) -> Result<PromptPreset, AppError> {
+    let id = preset_key(id);
+    let current = get_preset(db, id).await?;
+    prepare(db, &mut preset, Some(id)).await?;
+    preset.id = None;
+    preset.created_at = current.created_at;
+    preset.updated_at = get_timestamp();
+
+    let updated: Option<PromptPreset> = db
+        .update_row("prompt_presets", id, preset)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update preset: {}", e)))?;
+    updated.ok_or_else(|| AppError::NotFound(format!("Preset {}", id)))
+}
+
+pub async fn delete_preset(db: &Database, id: &str) -> Result<(), AppError> {
+    let _: Option<PromptPreset> = db
+        .delete_row("prompt_presets", preset_key(id))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete preset: {}", e)))?;
+    Ok(())
+}
+
+/// Drop the presets of a deleted section
+pub async fn delete_section_presets(db: &Database, section_id: &str) -> Result<(), AppError> {
+    db.query("DELETE prompt_presets WHERE section_id = $section_id")
+        .bind(("section_id", section_key(section_id).to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete presets: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to delete presets: {}", e)))?;
+    Ok(())
+}
+
+/// Preset values overlaid with the explicitly passed variables
+pub fn apply_preset(preset: &PromptPreset, variables: Map<String, Value>) -> Map<String, Value> {
+    let mut merged = preset.variables.clone();
+    merged.extend(variables);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn preset(name: &str, variables: Value) -> PromptPreset {
+        serde_json::from_value(json!({
+            "section_id": "prompt_sections:portrait", "name": name, "variables": variables
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_variable_presets() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let section: PromptSection = serde_json::from_value(json!({
+            "package_id": "pkg", "namespace": "demo", "name": "portrait", "description": "",
+            "content": { "type": "text", "value": "" }, "is_entry_point": true,
+            "exportable": true, "required_variables": [], "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let _: Option<PromptSection> = db
+            .db
+            .create(("prompt_sections", "portrait"))
+            .content(section)
+            .await
+            .unwrap();
+
+        let studio = create_preset(
+            &db,
+            preset(" Studio ", json!({ "light": "soft", "lens": "85mm" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(studio.name, "Studio");
+        assert_eq!(studio.package_id, "pkg");
+        create_preset(&db, preset("Outdoor", json!({ "light": "sun" })))
+            .await
+            .unwrap();
+        assert!(matches!(
+            create_preset(&db, preset("Studio", json!({}))).await,
+            Err(AppError::Validation(_))
+        ));
+        let mut orphan = preset("X", json!({}));
+        orphan.section_id = "missing".to_string();
+        assert!(matches!(
+            create_preset(&db, orphan).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        let names: Vec<String> = list_presets(&db, "portrait")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["Outdoor", "Studio"]);
+
+        // Renaming to its own name is fine; explicit variables override the preset
+        let id = studio.id.as_ref().unwrap().id.to_raw();
+        let updated = update_preset(
+            &db,
+            &id,
+            preset("Studio", json!({ "light": "hard", "lens": "85mm" })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.created_at, studio.created_at);
+        let values = json!({ "light": "rim" }).as_object().unwrap().clone();
+        let merged = apply_preset(&get_preset(&db, &id).await.unwrap(), values);
+        assert_eq!(
+            Value::Object(merged),
+            json!({ "light": "rim", "lens": "85mm" })
+        );
+
+        delete_preset(&db, &id).await.unwrap();
+        delete_section_presets(&db, "portrait").await.unwrap();
+        assert!(list_presets(&db, "portrait").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+// Record-bound variables
+//
+// A variable definition may carry a `record_query`: a regular record query
+// (types, sources, tags, status, sort) plus the field to read, e.g.
+//
+//   { "id": "pipeline", "record_query": { "types": ["gitlab_pipeline"],
+//     "field": "data.status" } }
+//
+// Before rendering, such variables are filled from the staging database with
+// the newest matching record's field, or with the list of fields of all
+// matches for `"mode": "list"`. Values passed explicitly win, and a query
+// that finds nothing leaves the variable to its default.
+
+use super::PromptSection;
+use crate::db::query::RecordQuery;
+use crate::db::Database;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Records read for list bindings without a limit
+const DEFAULT_LIST_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BindingMode {
+    /// The field of the first (by default newest) match
+    #[default]
+    Latest,
+    /// The field of every match, as an array
+    List,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordBinding {
+    #[serde(flatten)]
+    pub query: RecordQuery,
+    /// Path into the record, e.g. "data.status" or "metadata.title";
+    /// without one the record's `data` is used
+    pub field: Option<String>,
+    pub mode: BindingMode,
+    /// Only records from the last N hours ("today's events")
+    pub within_hours: Option<u32>,
+}
+
+fn lookup<'a>(record: &'a Value, field: &str) -> Option<&'a Value> {
+    field
+        .split('.')
+        .try_fold(record, |current, key| current.get(key))
+}
+
+/// Fill the section's record-bound variables missing from `variables`;
+/// returns warnings for bindings that couldn't be resolved
+pub async fn resolve_record_variables(
+    db: &Database,
+    section: &PromptSection,
+    variables: &mut Map<String, Value>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for definition in &section.variables {
+        let (Some(id), Some(binding)) = (
+            definition.get("id").and_then(|id| id.as_str()),
+            definition.get("record_query"),
+        ) else {
+            continue;
+        };
+        if variables.get(id).is_some_and(|v| !v.is_null()) {
+            continue;
+        }
+        let binding: RecordBinding = match serde_json::from_value(binding.clone()) {
+            Ok(binding) => binding,
+            Err(e) => {
+                warnings.push(format!("Variable '{}': invalid record query: {}", id, e));
+                continue;
+            }
+        };
+
+        let mut query = binding.query;
+        query.limit = match binding.mode {
+            BindingMode::Latest => Some(1),
+            BindingMode::List => query.limit.or(Some(DEFAULT_LIST_LIMIT)),
+        };
+        if let Some(hours) = binding.within_hours {
+            let range = query.date_range.get_or_insert_with(Default::default);
+            range.from = Some(chrono::Utc::now() - chrono::Duration::hours(i64::from(hours)));
+        }
+        let records = match db.query_records(query).await {
+            Ok(page) => page.records,
+            Err(e) => {
+                warnings.push(format!("Variable '{}': {}", id, e));
+                continue;
+            }
+        };
+
+        let field = binding.field.as_deref().unwrap_or("data");
+        let values: Vec<Value> = records
+            .iter()
+            .filter_map(|record| serde_json::to_value(record).ok())
+            .filter_map(|record| lookup(&record, field).cloned())
+            .filter(|value| !value.is_null())
+            .collect();
+        let value = match binding.mode {
+            BindingMode::Latest => values.into_iter().next(),
+            BindingMode::List => Some(Value::Array(values)),
+        };
+        if let Some(value) = value {
+            variables.insert(id.to_string(), value);
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_record_variables() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        for (hours_ago, status, title) in [(3, "failed", "Build 1"), (1, "success", "Build 2")] {
+            let mut record = StagedRecord::new(
+                "pipeline".to_string(),
+                "gitlab".to_string(),
+                json!({ "status": status }),
+            );
+            record.timestamp = chrono::Utc::now() - chrono::Duration::hours(hours_ago);
+            record.metadata.title = Some(title.to_string());
+            db.create_record(record).await.unwrap();
+        }
+
+        let section: PromptSection = serde_json::from_value(json!({
+            "package_id": "pkg", "namespace": "demo", "name": "standup", "description": "",
+            "content": { "type": "text", "value": "" }, "is_entry_point": true,
+            "exportable": true, "required_variables": [], "created_at": "", "updated_at": "",
+            "variables": [
+                { "id": "status", "record_query": { "types": ["pipeline"], "field": "data.status" } },
+                { "id": "titles", "record_query": {
+                    "types": ["pipeline"], "field": "metadata.title", "mode": "list",
+                    "sort": [{ "field": "timestamp" }] } },
+                { "id": "recent", "record_query": {
+                    "types": ["pipeline"], "field": "data.status", "mode": "list",
+                    "within_hours": 2 } },
+                { "id": "none", "record_query": { "types": ["calendar_event"] } },
+                { "id": "given", "record_query": { "types": ["pipeline"] } },
+                { "id": "broken", "record_query": { "types": ["pipeline"], "sort": [{ "field": "x;" }] } }
+            ]
+        }))
+        .unwrap();
+
+        let mut variables = Map::new();
+        variables.insert("given".to_string(), json!("explicit"));
+        let warnings = resolve_record_variables(&db, &section, &mut variables).await;
+
+        assert_eq!(variables["status"], "success");
+        assert_eq!(variables["titles"], json!(["Build 1", "Build 2"]));
+        assert_eq!(variables["recent"], json!(["success"]));
+        assert!(!variables.contains_key("none"));
+        assert_eq!(variables["given"], "explicit");
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].starts_with("Variable 'broken'"),
+            "{:?}",
+            warnings
+        );
+    }
+}
@@ -0,0 +1,267 @@
+// Section reference analysis
+//
+// Builds the reverse `section-ref` graph of the library to answer "what uses
+// this section?": the sections referencing it directly, those reaching it
+// through other fragments, and the entry points that would break if it went
+// away. Data types get the same question answered for `data_type_id`s.
+// References resolve the same way the renderer resolves them.
+
+use super::graph::{references_of, GraphEdgeKind};
+use super::renderer::{PromptContent, PromptLibrary};
+use super::validation::walk;
+use super::{extract_id, PromptSection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionReference {
+    pub section_id: String,
+    pub name: String,
+    pub namespace: String,
+    pub is_entry_point: bool,
+    /// 1 for direct references, 2 when referencing a direct referrer, ...
+    pub depth: usize,
+    /// The section this one references on the way to the analyzed section
+    pub via: String,
+    /// Location of the reference in this section's content
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SectionReferences {
+    pub section_id: String,
+    /// Direct and transitive referrers, nearest first
+    pub referenced_by: Vec<SectionReference>,
+    /// Entry points rendering the section, directly or transitively
+    pub affected_entry_points: Vec<String>,
+}
+
+impl SectionReferences {
+    pub fn is_referenced(&self) -> bool {
+        !self.referenced_by.is_empty()
+    }
+}
+
+/// A section using a data type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataTypeReference {
+    pub section_id: String,
+    pub name: String,
+    pub namespace: String,
+    /// The `data_type_id` as written
+    pub reference: String,
+    /// "variables.style" or a content path
+    pub path: String,
+}
+
+/// Sections whose `data_type_id`s resolve to the data type with record key `key`
+pub fn data_type_references(library: &PromptLibrary, key: &str) -> Vec<DataTypeReference> {
+    let key = key.strip_prefix("prompt_data_types:").unwrap_or(key);
+    let mut found = Vec::new();
+    for section in &library.sections {
+        for (kind, reference, path) in references_of(section) {
+            if kind != GraphEdgeKind::DataType {
+                continue;
+            }
+            let resolved = library.resolve_data_type(&reference);
+            if resolved.and_then(|t| extract_id(&t.id)).as_deref() == Some(key) {
+                found.push(DataTypeReference {
+                    section_id: section_key(section),
+                    name: section.name.clone(),
+                    namespace: section.namespace.clone(),
+                    reference,
+                    path,
+                });
+            }
+        }
+    }
+    found
+}
+
+fn section_key(section: &PromptSection) -> String {
+    extract_id(&section.id).unwrap_or_else(|| section.name.clone())
+}
+
+/// Who references `section_id`, directly and transitively
+pub fn section_references(library: &PromptLibrary, section_id: &str) -> SectionReferences {
+    let Some(target) = library.find_section(section_id) else {
+        return SectionReferences {
+            section_id: section_id.to_string(),
+            ..Default::default()
+        };
+    };
+    let target_key = section_key(target);
+
+    // referenced section -> [(referrer, path)]
+    let mut referrers: HashMap<String, Vec<(&PromptSection, String)>> = HashMap::new();
+    for section in &library.sections {
+        let mut found = Vec::new();
+        for (field, value) in section.content_fields() {
+            let Ok(content) = serde_json::from_value::<PromptContent>(value.clone()) else {
+                continue;
+            };
+            walk(&content, field.to_string(), false, &mut |node, path, _| {
+                if let PromptContent::SectionRef { section_id } = node {
+                    found.push((section_id.clone(), path.to_string()));
+                }
+            });
+        }
+        for (reference, path) in found {
+            if let Some(referenced) = library.resolve_section(&reference, Some(&section.namespace))
+            {
+                referrers
+                    .entry(section_key(referenced))
+                    .or_default()
+                    .push((section, path));
+            }
+        }
+    }
+
+    let mut result = SectionReferences {
+        section_id: target_key.clone(),
+        ..Default::default()
+    };
+    let mut seen = HashSet::from([target_key.clone()]);
+    let mut queue = VecDeque::from([(target_key, 1)]);
+    while let Some((key, depth)) = queue.pop_front() {
+        for (referrer, path) in referrers.get(&key).into_iter().flatten() {
+            let referrer_key = section_key(referrer);
+            if !seen.insert(referrer_key.clone()) {
+                continue;
+            }
+            if referrer.is_entry_point {
+                result.affected_entry_points.push(referrer_key.clone());
+            }
+            result.referenced_by.push(SectionReference {
+                section_id: referrer_key.clone(),
+                name: referrer.name.clone(),
+                namespace: referrer.namespace.clone(),
+                is_entry_point: referrer.is_entry_point,
+                depth,
+                via: key.clone(),
+                path: path.clone(),
+            });
+            queue.push_back((referrer_key, depth + 1));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::{test_support, PromptDataType};
+    use serde_json::{json, Value};
+
+    fn section(key: &str, namespace: &str, entry: bool, content: Value) -> PromptSection {
+        PromptSection {
+            namespace: namespace.to_string(),
+            is_entry_point: entry,
+            ..test_support::section(key, content)
+        }
+    }
+
+    #[test]
+    fn test_section_references() {
+        let library = PromptLibrary {
+            sections: vec![
+                section(
+                    "leaf",
+                    "demo",
+                    false,
+                    json!({ "type": "text", "value": "x" }),
+                ),
+                section(
+                    "middle",
+                    "demo",
+                    false,
+                    json!({ "type": "composite", "parts": [
+                        { "type": "text", "value": "a" },
+                        { "type": "section-ref", "section_id": "leaf" }
+                    ]}),
+                ),
+                section(
+                    "top",
+                    "demo",
+                    true,
+                    json!({ "type": "section-ref", "section_id": "demo:middle" }),
+                ),
+                section(
+                    "other",
+                    "demo",
+                    true,
+                    json!({ "type": "conditional",
+                        "condition": { "variable": "x", "operator": "exists" },
+                        "then_content": { "type": "section-ref", "section_id": "leaf" } }),
+                ),
+                section(
+                    "unrelated",
+                    "demo",
+                    true,
+                    json!({ "type": "text", "value": "y" }),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let refs = section_references(&library, "prompt_sections:leaf");
+        assert!(refs.is_referenced());
+        let found: Vec<(&str, usize, &str)> = refs
+            .referenced_by
+            .iter()
+            .map(|r| (r.section_id.as_str(), r.depth, r.via.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("middle", 1, "leaf"),
+                ("other", 1, "leaf"),
+                ("top", 2, "middle")
+            ]
+        );
+        assert_eq!(refs.referenced_by[0].path, "content.parts[1]");
+        assert_eq!(refs.referenced_by[1].path, "content.then");
+        assert_eq!(refs.affected_entry_points, vec!["other", "top"]);
+
+        assert!(!section_references(&library, "top").is_referenced());
+        assert!(!section_references(&library, "missing").is_referenced());
+    }
+
+    #[test]
+    fn test_data_type_references() {
+        let data_type = |key: &str, namespace: &str, name: &str| -> PromptDataType {
+            serde_json::from_value(json!({
+                "id": { "tb": "prompt_data_types", "id": { "String": key } },
+                "package_id": "pkg", "namespace": namespace, "name": name, "description": "",
+                "base_type": "enum", "examples": [], "created_at": "", "updated_at": ""
+            }))
+            .unwrap()
+        };
+        let mut user = section(
+            "user",
+            "demo",
+            true,
+            json!({ "type": "random-value", "data_type_id": "demo:Mood" }),
+        );
+        user.variables = vec![json!({ "id": "light", "data_type_id": "light-key" })];
+        let library = PromptLibrary {
+            sections: vec![user],
+            data_types: vec![
+                data_type("mood-key", "demo", "Mood"),
+                data_type("light-key", "demo", "Lighting"),
+                data_type("unused-key", "demo", "Unused"),
+            ],
+            ..Default::default()
+        };
+
+        let mood = data_type_references(&library, "prompt_data_types:mood-key");
+        assert_eq!(mood.len(), 1);
+        assert_eq!(
+            (mood[0].reference.as_str(), mood[0].path.as_str()),
+            ("demo:Mood", "content")
+        );
+        let light = data_type_references(&library, "light-key");
+        assert_eq!(light[0].path, "variables.light");
+        assert!(data_type_references(&library, "unused-key").is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+// Prompt package registry
+//
+// A registry is a JSON index listing shared packages; each entry points to a
+// package in the regular `PackageExport` format (relative URLs resolve against
+// the index) or embeds it. Syncing lists every entry with the locally
+// installed version and installs or updates the selected ones through the
+// normal merge import.
+
+use super::package_import::{import_package, ImportMode, ImportSummary};
+use super::{PackageExport, PromptPackage};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub namespace: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    /// Location of the package export, absolute or relative to the index
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The package export itself, for small registries
+    #[serde(default, skip_serializing)]
+    pub export: Option<Box<PackageExport>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryIndex {
+    #[serde(default)]
+    pub name: String,
+    pub packages: Vec<RegistryEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistryStatus {
+    NotInstalled,
+    UpToDate,
+    UpdateAvailable,
+    /// The installed version is newer than the registry's
+    LocalNewer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryPackage {
+    #[serde(flatten)]
+    pub entry: RegistryEntry,
+    pub installed_version: Option<String>,
+    pub installed_package_id: Option<String>,
+    pub status: RegistryStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrySync {
+    pub url: String,
+    pub name: String,
+    pub packages: Vec<RegistryPackage>,
+    /// Packages installed or updated during this sync
+    pub installed: Vec<ImportSummary>,
+}
+
+/// Compare dotted version numbers ("1.10.0" > "1.9.2"); a pre-release or
+/// build suffix is ignored, other non-numeric parts compare as text
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<String> {
+        let core = v.trim().trim_start_matches('v');
+        let core = core.split(['-', '+']).next().unwrap_or_default();
+        core.split('.').map(str::to_string).collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).map_or("0", String::as_str);
+        let y = b.get(i).map_or("0", String::as_str);
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Parse an index, resolving relative package URLs against `index_url`
+pub fn parse_index(index_url: &str, text: &str) -> Result<RegistryIndex, AppError> {
+    let mut index: RegistryIndex = serde_json::from_str(text)
+        .map_err(|e| AppError::Validation(format!("Invalid registry index: {}", e)))?;
+    let base = url::Url::parse(index_url)
+        .map_err(|e| AppError::Validation(format!("Invalid registry URL: {}", e)))?;
+    for entry in &mut index.packages {
+        if entry.url.is_none() && entry.export.is_none() {
+            return Err(AppError::Validation(format!(
+                "Registry entry '{}' has neither a url nor an export",
+                entry.namespace
+            )));
+        }
+        if let Some(url) = &entry.url {
+            let resolved = base
+                .join(url)
+                .map_err(|e| AppError::Validation(format!("Invalid package URL {}: {}", url, e)))?;
+            entry.url = Some(resolved.to_string());
+        }
+    }
+    Ok(index)
+}
+
+/// Registry entries with their installation status
+pub fn package_statuses(
+    index: &RegistryIndex,
+    installed: &[PromptPackage],
+) -> Vec<RegistryPackage> {
+    index
+        .packages
+        .iter()
+        .map(|entry| {
+            let local = installed.iter().find(|p| p.namespace == entry.namespace);
+            let status = match local {
+                None => RegistryStatus::NotInstalled,
+                Some(p) => match compare_versions(&p.version, &entry.version) {
+                    Ordering::Less => RegistryStatus::UpdateAvailable,
+                    Ordering::Equal => RegistryStatus::UpToDate,
+                    Ordering::Greater => RegistryStatus::LocalNewer,
+                },
+            };
+            RegistryPackage {
+                entry: entry.clone(),
+                installed_version: local.map(|p| p.version.clone()),
+                installed_package_id: local.and_then(|p| super::extract_id(&p.id)),
+                status,
+            }
+        })
+        .collect()
+}
+
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String, AppError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::request(&e, format!("Failed to fetch {}: {}", url, e)))?
+        .error_for_status()
+        .map_err(|e| AppError::request(&e, format!("Failed to fetch {}: {}", url, e)))?;
+    response
+        .text()
+        .await
+        .map_err(|e| AppError::request(&e, format!("Failed to read {}: {}", url, e)))
+}
+
+async fn fetch_export(
+    client: &reqwest::Client,
+    entry: &RegistryEntry,
+) -> Result<PackageExport, AppError> {
+    let export = match (&entry.export, &entry.url) {
+        (Some(export), _) => (**export).clone(),
+        (None, Some(url)) => serde_json::from_str(&fetch_text(client, url).await?)
+            .map_err(|e| AppError::Validation(format!("Invalid package at {}: {}", url, e)))?,
+        (None, None) => unreachable!("checked by parse_index"),
+    };
+    if export.package.namespace != entry.namespace {
+        return Err(AppError::Validation(format!(
+            "Registry entry '{}' points to package namespace '{}'",
+            entry.namespace, export.package.namespace
+        )));
+    }
+    Ok(export)
+}
+
+async fn installed_packages(db: &Database) -> Result<Vec<PromptPackage>, AppError> {
+    db.db
+        .select("prompt_packages")
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get packages: {}", e)))
+}
+
+/// Fetch a registry index, install or update the packages whose namespaces
+/// are in `install`, and list every entry with its (new) local status
+pub async fn sync_registry(
+    db: &Database,
+    url: &str,
+    install: &[String],
+) -> Result<RegistrySync, AppError> {
+    crate::network::ensure_online()?;
+    let client = crate::network::client();
+    let index = parse_index(url, &fetch_text(&client, url).await?)?;
+    if let Some(missing) = install
+        .iter()
+        .find(|ns| !index.packages.iter().any(|e| &e.namespace == *ns))
+    {
+        return Err(AppError::NotFound(format!(
+            "Package '{}' in registry {}",
+            missing, url
+        )));
+    }
+
+    let mut installed = Vec::new();
+    for entry in index
+        .packages
+        .iter()
+        .filter(|e| install.contains(&e.namespace))
+    {
+        let export = fetch_export(&client, entry).await?;
+        installed.push(import_package(db, export, ImportMode::Merge, None).await?);
+    }
+
+    let packages = package_statuses(&index, &installed_packages(db).await?);
+    Ok(RegistrySync {
+        url: url.to_string(),
+        name: index.name,
+        packages,
+        installed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_registry_index() {
+        assert_eq!(compare_versions("1.10.0", "1.9.2"), Ordering::Greater);
+        assert_eq!(compare_versions("v2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0-beta", "1.0.1"), Ordering::Less);
+
+        let text = json!({
+            "name": "Community",
+            "packages": [
+                { "namespace": "poetry", "name": "Poetry", "version": "1.2.0", "url": "packages/poetry.json" },
+                { "namespace": "code", "name": "Code", "version": "2.0.0", "url": "https://cdn.example.com/code.json" },
+                { "namespace": "story", "name": "Story", "version": "0.1.0", "url": "story.json" }
+            ]
+        })
+        .to_string();
+        let index = parse_index("https://example.com/registry/index.json", &text).unwrap();
+        assert_eq!(
+            index.packages[0].url.as_deref(),
+            Some("https://example.com/registry/packages/poetry.json")
+        );
+        assert_eq!(
+            index.packages[1].url.as_deref(),
+            Some("https://cdn.example.com/code.json")
+        );
+
+        let installed: Vec<PromptPackage> = [("poetry", "1.1.9"), ("code", "2.0.0")]
+            .iter()
+            .map(|(ns, version)| {
+                serde_json::from_value(json!({
+                    "namespace": ns, "name": ns, "version": version, "description": "",
+                    "author": "", "dependencies": [], "exports": [],
+                    "created_at": "", "updated_at": ""
+                }))
+                .unwrap()
+            })
+            .collect();
+        let listed = package_statuses(&index, &installed);
+        let statuses: Vec<(&str, RegistryStatus, Option<&str>)> = listed
+            .iter()
+            .map(|p| {
+                (
+                    p.entry.namespace.as_str(),
+                    p.status,
+                    p.installed_version.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("poetry", RegistryStatus::UpdateAvailable, Some("1.1.9")),
+                ("code", RegistryStatus::UpToDate, Some("2.0.0")),
+                ("story", RegistryStatus::NotInstalled, None),
+            ]
+        );
+
+        let invalid = json!({ "packages": [{ "namespace": "x", "name": "X", "version": "1" }] });
+        assert!(parse_index("https://example.com/", &invalid.to_string()).is_err());
+    }
+}
@@ -0,0 +1,1624 @@
+// Prompt rendering
+//
+// Server-side interpreter for the section content AST, following the
+// frontend render engine (services/promptRenderEngine.ts) so exports, tests
+// and automation can produce prompt text without the UI. Random choices draw
+// from one seeded generator per render; the seed is returned so a render can
+// be repeated exactly.
+
+use super::locale::{self, Conjunction, PluralCategory};
+use super::{extract_id, PromptDataType, PromptSection, SeparatorSet};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Nested section references followed at most (guards against cycles)
+const MAX_SECTION_DEPTH: usize = 32;
+
+/// Separator set used by pick-many and shuffle when none is given
+const DEFAULT_SEPARATOR_SET: &str = "oxford-comma";
+
+/// Namespace reported for the built-in separator sets
+pub const BUILTIN_NAMESPACE: &str = "builtin";
+
+/// Built-in separator set IDs with their descriptions
+const BUILTIN_SEPARATOR_SETS: &[(&str, &str)] = &[
+    ("oxford-comma", "a, b, and c"),
+    ("simple-comma", "a, b, c"),
+    ("or-list", "a, b, or c"),
+    ("and-list-no-oxford", "a, b and c"),
+    ("bullet-list", "One bulleted line per item"),
+    ("numbered-list", "One numbered line per item"),
+    ("newline", "One line per item"),
+    ("space", "a b c"),
+];
+
+// ============================================
+// CONTENT MODEL
+// ============================================
+
+/// One node of a section's content tree
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum PromptContent {
+    Text {
+        value: String,
+    },
+    Variable {
+        variable_id: String,
+        path: Option<String>,
+        format: Option<FormatOptions>,
+    },
+    SectionRef {
+        section_id: String,
+    },
+    Composite {
+        #[serde(default)]
+        parts: Vec<PromptContent>,
+    },
+    Conditional {
+        condition: Condition,
+        then_content: Box<PromptContent>,
+        else_content: Option<Box<PromptContent>>,
+    },
+    List {
+        variable_id: String,
+        separator_set_id: String,
+        item_template: Option<Box<PromptContent>>,
+    },
+    Context {
+        context_key: String,
+        fallback: Option<Box<PromptContent>>,
+    },
+    Plural {
+        count_variable: String,
+        zero: Option<String>,
+        one: String,
+        two: Option<String>,
+        few: Option<String>,
+        many: Option<String>,
+        other: String,
+    },
+    Article {
+        word_variable: Option<String>,
+        word_content: Option<Box<PromptContent>>,
+        #[serde(default)]
+        style: ArticleStyle,
+        #[serde(default)]
+        capitalize: bool,
+    },
+    /// Possessive form of a word ("Alice's", "James'")
+    Possessive {
+        word_variable: Option<String>,
+        word_content: Option<Box<PromptContent>>,
+    },
+    /// Verb agreeing with a subject count ("runs" for one, "run" otherwise)
+    Verb {
+        /// Base form ("run", "be", "look up")
+        verb: String,
+        count_variable: String,
+        /// Explicit singular form where the rules get it wrong
+        singular: Option<String>,
+    },
+    CountSwitch {
+        count_variable: String,
+        #[serde(default)]
+        cases: Vec<CountCase>,
+        default_content: Option<Box<PromptContent>>,
+    },
+    Switch {
+        variable_id: String,
+        #[serde(default)]
+        cases: Vec<SwitchCase>,
+        default_content: Option<Box<PromptContent>>,
+    },
+    PickOne {
+        #[serde(default)]
+        candidates: Vec<PromptContent>,
+        weights: Option<Vec<f64>>,
+    },
+    PickMany {
+        #[serde(default)]
+        candidates: Vec<PromptContent>,
+        count: PickCount,
+        separator_set_id: Option<String>,
+        #[serde(default)]
+        allow_duplicates: bool,
+    },
+    RandomValue {
+        pool: Option<Vec<String>>,
+        pool_variable: Option<String>,
+        data_type_id: Option<String>,
+        format: Option<FormatOptions>,
+    },
+    WeightedPick {
+        #[serde(default)]
+        options: Vec<WeightedOption>,
+    },
+    Shuffle {
+        variable_id: String,
+        count: Option<usize>,
+        separator_set_id: Option<String>,
+        item_template: Option<Box<PromptContent>>,
+    },
+    /// Content types this renderer doesn't know (rendered as nothing)
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArticleStyle {
+    #[default]
+    Indefinite,
+    Definite,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CountCase {
+    /// A number, or "zero" / "one" / "other"
+    pub count: Value,
+    pub content: PromptContent,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SwitchCase {
+    pub value: Value,
+    pub content: PromptContent,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeightedOption {
+    pub weight: f64,
+    pub content: PromptContent,
+}
+
+/// How many candidates pick-many selects
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+pub enum PickCount {
+    Exact(usize),
+    Range { min: usize, max: usize },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Condition {
+    pub variable: Option<String>,
+    pub path: Option<String>,
+    pub context_key: Option<String>,
+    pub operator: String,
+    pub value: Value,
+    pub and: Option<Vec<Condition>>,
+    pub or: Option<Vec<Condition>>,
+    pub not: Option<Box<Condition>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextCase {
+    Upper,
+    Lower,
+    Title,
+    Sentence,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Replacement {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    pub case: Option<TextCase>,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    /// Max length in characters, including the ellipsis
+    pub truncate: Option<usize>,
+    /// Output when the value is missing
+    pub placeholder: Option<String>,
+    pub trim: bool,
+    pub replace: Vec<Replacement>,
+}
+
+// ============================================
+// SEPARATOR RULES
+// ============================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SingleItemRule {
+    template: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TwoItemRule {
+    separator: String,
+    template: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManyItemRule {
+    item_separator: String,
+    last_separator: String,
+    template: String,
+    #[serde(default)]
+    numbered: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SeparatorRules {
+    single: SingleItemRule,
+    two: TwoItemRule,
+    many: ManyItemRule,
+}
+
+impl SeparatorRules {
+    fn new(single: &str, two: (&str, &str), many: (&str, &str, &str), numbered: bool) -> Self {
+        SeparatorRules {
+            single: SingleItemRule {
+                template: single.to_string(),
+            },
+            two: TwoItemRule {
+                separator: two.0.to_string(),
+                template: two.1.to_string(),
+            },
+            many: ManyItemRule {
+                item_separator: many.0.to_string(),
+                last_separator: many.1.to_string(),
+                template: many.2.to_string(),
+                numbered,
+            },
+        }
+    }
+
+    /// Separator sets available without a package
+    fn builtin(id: &str) -> Option<Self> {
+        const PAIR: &str = "{first}{separator}{second}";
+        const MANY: &str = "{items}{last_separator}{last}";
+        Some(match id {
+            "oxford-comma" => Self::new("{item}", (" and ", PAIR), (", ", ", and ", MANY), false),
+            "simple-comma" => Self::new("{item}", (", ", PAIR), (", ", ", ", MANY), false),
+            "or-list" => Self::new("{item}", (" or ", PAIR), (", ", ", or ", MANY), false),
+            "and-list-no-oxford" => {
+                Self::new("{item}", (" and ", PAIR), (", ", " and ", MANY), false)
+            }
+            "bullet-list" => Self::new(
+                "• {item}",
+                ("\n", "• {first}\n• {second}"),
+                ("\n• ", "\n• ", "• {items}{last_separator}{last}"),
+                false,
+            ),
+            "numbered-list" => Self::new(
+                "1. {item}",
+                ("\n", "1. {first}\n2. {second}"),
+                ("\n", "\n", "{numbered}"),
+                true,
+            ),
+            "newline" => Self::new("{item}", ("\n", PAIR), ("\n", "\n", MANY), false),
+            "space" => Self::new("{item}", (" ", PAIR), (" ", " ", MANY), false),
+            _ => return None,
+        })
+    }
+
+    /// Conjunction lists ("oxford-comma", "and-list-no-oxford", "or-list") in
+    /// a non-English locale's words, without the serial comma
+    fn localized(id: &str, locale: &str) -> Option<Self> {
+        if locale::uses_serial_comma(locale) {
+            return None;
+        }
+        let kind = match id {
+            "oxford-comma" | "and-list-no-oxford" => Conjunction::And,
+            "or-list" => Conjunction::Or,
+            _ => return None,
+        };
+        let conjunction = format!(" {} ", locale::conjunction(locale, kind));
+        Some(Self::new(
+            "{item}",
+            (&conjunction, "{first}{separator}{second}"),
+            (", ", &conjunction, "{items}{last_separator}{last}"),
+            false,
+        ))
+    }
+
+    fn join(&self, items: &[String]) -> String {
+        match items {
+            [] => String::new(),
+            [item] => self.single.template.replacen("{item}", item, 1),
+            [first, second] => self
+                .two
+                .template
+                .replacen("{first}", first, 1)
+                .replacen("{separator}", &self.two.separator, 1)
+                .replacen("{second}", second, 1),
+            [rest @ .., last] => {
+                if self.many.numbered {
+                    return items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| format!("{}. {}", i + 1, item))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                }
+                self.many
+                    .template
+                    .replacen("{items}", &rest.join(&self.many.item_separator), 1)
+                    .replacen("{last_separator}", &self.many.last_separator, 1)
+                    .replacen("{last}", last, 1)
+            }
+        }
+    }
+}
+
+/// Whether `id` names a built-in separator set
+pub fn is_builtin_separator_set(id: &str) -> bool {
+    SeparatorRules::builtin(id).is_some()
+}
+
+/// The built-in separator sets as records, resolvable from any package
+pub fn builtin_separator_sets() -> Vec<SeparatorSet> {
+    BUILTIN_SEPARATOR_SETS
+        .iter()
+        .filter_map(|(name, description)| {
+            let rules = SeparatorRules::builtin(name)?;
+            Some(SeparatorSet {
+                id: Some(surrealdb::sql::Thing::from((
+                    "prompt_separator_sets",
+                    *name,
+                ))),
+                package_id: String::new(),
+                namespace: BUILTIN_NAMESPACE.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                rules: serde_json::to_value(rules).ok()?,
+                created_at: String::new(),
+                updated_at: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Check that a separator set's rules have the single/two/many shape
+pub fn check_separator_rules(rules: &Value) -> Result<(), AppError> {
+    serde_json::from_value::<SeparatorRules>(rules.clone())
+        .map(|_| ())
+        .map_err(|e| AppError::Validation(format!("Invalid separator rules: {}", e)))
+}
+
+// ============================================
+// LIBRARY
+// ============================================
+
+/// Sections, separator sets and data types that references resolve against
+#[derive(Debug, Clone, Default)]
+pub struct PromptLibrary {
+    pub sections: Vec<PromptSection>,
+    pub separator_sets: Vec<SeparatorSet>,
+    pub data_types: Vec<PromptDataType>,
+}
+
+/// Whether an entity is referenced by `key` (its record key or its name)
+fn is_referenced(id: &Option<surrealdb::sql::Thing>, name: &str, key: &str) -> bool {
+    name == key || extract_id(id).as_deref() == Some(key)
+}
+
+impl PromptLibrary {
+    /// Load every package's sections, separator sets and data types
+    pub async fn load(db: &Database) -> Result<Self, AppError> {
+        let sections: Vec<PromptSection> = db
+            .db
+            .select("prompt_sections")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get sections: {}", e)))?;
+        let separator_sets: Vec<SeparatorSet> = db
+            .db
+            .select("prompt_separator_sets")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get separator sets: {}", e)))?;
+        let data_types: Vec<PromptDataType> = db
+            .db
+            .select("prompt_data_types")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get data types: {}", e)))?;
+
+        Ok(PromptLibrary {
+            sections,
+            separator_sets,
+            data_types,
+        })
+    }
+
+    /// Look up a section by record ID ("prompt_sections:key" or "key"), then as a reference
+    pub fn find_section(&self, section_id: &str) -> Option<&PromptSection> {
+        let key = section_id
+            .strip_prefix("prompt_sections:")
+            .unwrap_or(section_id);
+        self.sections
+            .iter()
+            .find(|s| extract_id(&s.id).as_deref() == Some(key))
+            .or_else(|| self.resolve_section(key, None))
+    }
+
+    /// Resolve "namespace:section", or a section of `namespace`, or any exportable section
+    pub(super) fn resolve_section(
+        &self,
+        reference: &str,
+        namespace: Option<&str>,
+    ) -> Option<&PromptSection> {
+        if let Some((ns, key)) = reference.split_once(':') {
+            return self
+                .sections
+                .iter()
+                .find(|s| s.namespace == ns && is_referenced(&s.id, &s.name, key));
+        }
+        namespace
+            .and_then(|ns| {
+                self.sections
+                    .iter()
+                    .find(|s| s.namespace == ns && is_referenced(&s.id, &s.name, reference))
+            })
+            .or_else(|| {
+                self.sections
+                    .iter()
+                    .find(|s| s.exportable && is_referenced(&s.id, &s.name, reference))
+            })
+    }
+
+    pub(super) fn resolve_separator_set(&self, reference: &str) -> Option<&SeparatorSet> {
+        match reference.split_once(':') {
+            Some((ns, key)) => self
+                .separator_sets
+                .iter()
+                .find(|s| s.namespace == ns && is_referenced(&s.id, &s.name, key)),
+            None => self
+                .separator_sets
+                .iter()
+                .find(|s| is_referenced(&s.id, &s.name, reference)),
+        }
+    }
+
+    /// Whether a separator set ID refers to a built-in or a valid package set
+    pub(super) fn has_separator_set(&self, reference: &str) -> bool {
+        self.separator_rules(reference, None).is_some()
+    }
+
+    fn separator_rules(&self, reference: &str, locale: Option<&str>) -> Option<SeparatorRules> {
+        locale
+            .and_then(|locale| SeparatorRules::localized(reference, locale))
+            .or_else(|| SeparatorRules::builtin(reference))
+            .or_else(|| {
+                self.resolve_separator_set(reference)
+                    .and_then(|set| serde_json::from_value(set.rules.clone()).ok())
+            })
+    }
+
+    pub(super) fn resolve_data_type(&self, reference: &str) -> Option<&PromptDataType> {
+        match reference.split_once(':') {
+            Some((ns, key)) => self
+                .data_types
+                .iter()
+                .find(|t| t.namespace == ns && is_referenced(&t.id, &t.name, key)),
+            None => self
+                .data_types
+                .iter()
+                .find(|t| is_referenced(&t.id, &t.name, reference)),
+        }
+    }
+}
+
+// ============================================
+// RENDERING
+// ============================================
+
+/// Result of rendering one section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedSection {
+    pub section_id: String,
+    pub text: String,
+    /// Rendered `negative_content`, for sections that have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_prompt: Option<String>,
+    /// Seed of the random choices; pass it again to repeat this output
+    pub seed: u64,
+    /// Variable values the section was rendered with, defaults included
+    pub variables: Map<String, Value>,
+    pub variables_used: Vec<String>,
+    pub sections_used: Vec<String>,
+    /// Problems that didn't stop rendering (missing sections, unknown types, ...)
+    pub warnings: Vec<String>,
+    /// Render history entry, when the render was recorded
+    #[serde(default)]
+    pub history_id: Option<String>,
+}
+
+/// Generator state is kept to 31 bits
+const SEED_MASK: u64 = 0x7fff_ffff;
+
+/// Linear congruential generator, same constants as the frontend engine
+struct SeededRandom(u64);
+
+impl SeededRandom {
+    /// Next value in [0, 1]
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(1103515245).wrapping_add(12345) & SEED_MASK;
+        self.0 as f64 / SEED_MASK as f64
+    }
+
+    /// Random index below `len` (which must not be zero)
+    fn index(&mut self, len: usize) -> usize {
+        ((self.next() * len as f64) as usize).min(len - 1)
+    }
+
+    /// Index picked with the given (non-negative) weights
+    fn weighted(&mut self, weights: &[f64]) -> usize {
+        let total: f64 = weights.iter().sum();
+        let mut remaining = self.next() * total;
+        for (index, weight) in weights.iter().enumerate() {
+            remaining -= weight;
+            if remaining <= 0.0 {
+                return index;
+            }
+        }
+        weights.len() - 1
+    }
+}
+
+struct Renderer<'a> {
+    library: &'a PromptLibrary,
+    /// Namespace of the entry section, searched first for section references
+    namespace: String,
+    /// Locale of the entry section (plural categories, list conjunctions)
+    locale: Option<String>,
+    system: Map<String, Value>,
+    random: SeededRandom,
+    variables_used: BTreeSet<String>,
+    sections_used: BTreeSet<String>,
+    warnings: Vec<String>,
+    depth: usize,
+}
+
+/// Render a section with the given variable values
+///
+/// Variables missing from `variables` fall back to the section's
+/// `default_value`s. Without a seed a random one is chosen.
+pub fn render_section(
+    library: &PromptLibrary,
+    section_id: &str,
+    mut variables: Map<String, Value>,
+    seed: Option<u64>,
+) -> Result<RenderedSection, AppError> {
+    let section = library
+        .find_section(section_id)
+        .ok_or_else(|| AppError::NotFound(format!("Section {}", section_id)))?;
+
+    let content: PromptContent = serde_json::from_value(section.content.clone()).map_err(|e| {
+        AppError::Validation(format!(
+            "Invalid content in section {}: {}",
+            section.name, e
+        ))
+    })?;
+    let negative_content: Option<PromptContent> = section
+        .negative_content
+        .clone()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| {
+            AppError::Validation(format!(
+                "Invalid negative content in section {}: {}",
+                section.name, e
+            ))
+        })?;
+
+    for definition in &section.variables {
+        if let (Some(id), Some(default)) = (
+            definition.get("id").and_then(|id| id.as_str()),
+            definition.get("default_value").filter(|v| !v.is_null()),
+        ) {
+            variables
+                .entry(id.to_string())
+                .or_insert_with(|| default.clone());
+        }
+    }
+
+    let warnings = super::variables::validate_variables(library, section, &variables)
+        .into_iter()
+        .map(|e| format!("Variable '{}': {}", e.variable, e.message))
+        .collect();
+
+    // Only the low 31 bits influence the generator
+    let seed = seed.unwrap_or_else(|| u64::from(rand::random::<u32>())) & SEED_MASK;
+    let mut system = Map::new();
+    system.insert(
+        "timestamp".to_string(),
+        chrono::Utc::now().to_rfc3339().into(),
+    );
+    system.insert("random_seed".to_string(), seed.into());
+
+    let mut renderer = Renderer {
+        library,
+        namespace: section.namespace.clone(),
+        locale: section
+            .locale
+            .as_deref()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string),
+        system,
+        random: SeededRandom(seed),
+        variables_used: BTreeSet::new(),
+        sections_used: BTreeSet::new(),
+        warnings,
+        depth: 0,
+    };
+    let text = renderer.render(&content, &variables);
+    let negative_prompt = negative_content.map(|negative| renderer.render(&negative, &variables));
+
+    Ok(RenderedSection {
+        section_id: extract_id(&section.id).unwrap_or_else(|| section_id.to_string()),
+        text,
+        negative_prompt,
+        seed,
+        variables_used: renderer.variables_used.into_iter().collect(),
+        sections_used: renderer.sections_used.into_iter().collect(),
+        warnings: renderer.warnings,
+        variables,
+        history_id: None,
+    })
+}
+
+impl Renderer<'_> {
+    fn render(&mut self, content: &PromptContent, vars: &Map<String, Value>) -> String {
+        match content {
+            PromptContent::Text { value } => value.clone(),
+            PromptContent::Variable {
+                variable_id,
+                path,
+                format,
+            } => {
+                self.variables_used.insert(variable_id.clone());
+                let value = vars
+                    .get(variable_id)
+                    .and_then(|v| lookup_path(v, path.as_deref()))
+                    .filter(|v| !v.is_null());
+                match (value, format) {
+                    (None, Some(format)) => format.placeholder.clone().unwrap_or_default(),
+                    (None, None) => String::new(),
+                    (Some(value), Some(format)) => apply_format(&display_value(value), format),
+                    (Some(value), None) => display_value(value),
+                }
+            }
+            PromptContent::SectionRef { section_id } => self.render_section_ref(section_id, vars),
+            PromptContent::Composite { parts } => {
+                parts.iter().map(|part| self.render(part, vars)).collect()
+            }
+            PromptContent::Conditional {
+                condition,
+                then_content,
+                else_content,
+            } => {
+                if self.evaluate(condition, vars) {
+                    self.render(then_content, vars)
+                } else if let Some(else_content) = else_content {
+                    self.render(else_content, vars)
+                } else {
+                    String::new()
+                }
+            }
+            PromptContent::List {
+                variable_id,
+                separator_set_id,
+                item_template,
+            } => {
+                self.variables_used.insert(variable_id.clone());
+                let items = match vars.get(variable_id) {
+                    Some(Value::Array(items)) if !items.is_empty() => items,
+                    _ => return String::new(),
+                };
+                let rendered = self.render_items(items, item_template.as_deref(), vars);
+                self.join(&rendered, separator_set_id)
+            }
+            PromptContent::Context {
+                context_key,
+                fallback,
+            } => {
+                let (category, key) = context_key
+                    .split_once('.')
+                    .unwrap_or((context_key.as_str(), ""));
+                let value = match category {
+                    "system" => self.system.get(key).cloned(),
+                    "variables" => vars.get(key).cloned(),
+                    _ => None,
+                };
+                match (value.filter(|v| !v.is_null()), fallback) {
+                    (Some(value), _) => display_value(&value),
+                    (None, Some(fallback)) => self.render(fallback, vars),
+                    (None, None) => String::new(),
+                }
+            }
+            PromptContent::Plural {
+                count_variable,
+                zero,
+                one,
+                two,
+                few,
+                many,
+                other,
+            } => {
+                self.variables_used.insert(count_variable.clone());
+                let count = count_of(vars.get(count_variable));
+                if let Some(locale) = &self.locale {
+                    // An explicit zero form wins over the locale's category
+                    let form = match locale::plural_category(locale, count) {
+                        _ if count == 0.0 && zero.is_some() => zero.as_deref(),
+                        PluralCategory::Zero => zero.as_deref(),
+                        PluralCategory::One => Some(one.as_str()),
+                        PluralCategory::Two => two.as_deref(),
+                        PluralCategory::Few => few.as_deref(),
+                        PluralCategory::Many => many.as_deref(),
+                        PluralCategory::Other => None,
+                    };
+                    return form
+                        .unwrap_or(other)
+                        .replace("{count}", &format_number(count));
+                }
+                let template = match count {
+                    c if c == 0.0 && zero.is_some() => zero.as_deref(),
+                    1.0 => Some(one.as_str()),
+                    c if c == 2.0 && two.is_some() => two.as_deref(),
+                    c if (3.0..=4.0).contains(&c) && few.is_some() => few.as_deref(),
+                    c if c >= 5.0 && many.is_some() => many.as_deref(),
+                    _ => None,
+                }
+                .unwrap_or(other);
+                template.replace("{count}", &format_number(count))
+            }
+            PromptContent::Article {
+                word_variable,
+                word_content,
+                style,
+                capitalize,
+            } => {
+                let word = if let Some(variable) = word_variable {
+                    self.variables_used.insert(variable.clone());
+                    vars.get(variable).map(display_value).unwrap_or_default()
+                } else if let Some(word_content) = word_content {
+                    self.render(word_content, vars)
+                } else {
+                    return String::new();
+                };
+                article_for(&word, *style, *capitalize)
+            }
+            PromptContent::Possessive {
+                word_variable,
+                word_content,
+            } => {
+                let word = if let Some(variable) = word_variable {
+                    self.variables_used.insert(variable.clone());
+                    vars.get(variable).map(display_value).unwrap_or_default()
+                } else if let Some(word_content) = word_content {
+                    self.render(word_content, vars)
+                } else {
+                    return String::new();
+                };
+                possessive_of(&word)
+            }
+            PromptContent::Verb {
+                verb,
+                count_variable,
+                singular,
+            } => {
+                self.variables_used.insert(count_variable.clone());
+                match (count_of(vars.get(count_variable)) == 1.0, singular) {
+                    (true, Some(singular)) => singular.clone(),
+                    (true, None) => third_person_singular(verb),
+                    (false, _) if verb.trim() == "be" => "are".to_string(),
+                    (false, _) => verb.trim().to_string(),
+                }
+            }
+            PromptContent::CountSwitch {
+                count_variable,
+                cases,
+                default_content,
+            } => {
+                self.variables_used.insert(count_variable.clone());
+                let count = count_of(vars.get(count_variable));
+                let category = self
+                    .locale
+                    .as_deref()
+                    .map(|locale| locale::plural_category(locale, count));
+                let matched = cases.iter().find(|case| match &case.count {
+                    Value::Number(n) => n.as_f64() == Some(count),
+                    Value::String(s) if s == "zero" && count == 0.0 => true,
+                    Value::String(s) if category.is_some() => {
+                        category.map(PluralCategory::name) == Some(s.as_str())
+                    }
+                    Value::String(s) => match s.as_str() {
+                        "zero" => count == 0.0,
+                        "one" => count == 1.0,
+                        "other" => count > 1.0,
+                        _ => false,
+                    },
+                    _ => false,
+                });
+                match matched
+                    .map(|case| &case.content)
+                    .or(default_content.as_deref())
+                {
+                    Some(content) => self.render(content, vars),
+                    None => String::new(),
+                }
+            }
+            PromptContent::Switch {
+                variable_id,
+                cases,
+                default_content,
+            } => {
+                self.variables_used.insert(variable_id.clone());
+                let value = vars.get(variable_id).unwrap_or(&Value::Null);
+                let matched = cases.iter().find(|case| values_equal(&case.value, value));
+                match matched
+                    .map(|case| &case.content)
+                    .or(default_content.as_deref())
+                {
+                    Some(content) => self.render(content, vars),
+                    None => String::new(),
+                }
+            }
+            PromptContent::PickOne {
+                candidates,
+                weights,
+            } => {
+                if candidates.is_empty() {
+                    return String::new();
+                }
+                let index = match weights {
+                    Some(weights) if weights.len() == candidates.len() => {
+                        self.random.weighted(weights)
+                    }
+                    _ => self.random.index(candidates.len()),
+                };
+                self.render(&candidates[index], vars)
+            }
+            PromptContent::PickMany {
+                candidates,
+                count,
+                separator_set_id,
+                allow_duplicates,
+            } => {
+                if candidates.is_empty() {
+                    return String::new();
+                }
+                let count = match *count {
+                    PickCount::Exact(count) => count,
+                    PickCount::Range { min, max } => {
+                        min + self.random.index(max.saturating_sub(min) + 1)
+                    }
+                };
+                let mut available: Vec<usize> = (0..candidates.len()).collect();
+                let mut picked = Vec::new();
+                while picked.len() < count && !available.is_empty() {
+                    let index = self.random.index(available.len());
+                    picked.push(available[index]);
+                    if !allow_duplicates {
+                        available.remove(index);
+                    }
+                }
+                let rendered: Vec<String> = picked
+                    .into_iter()
+                    .map(|index| self.render(&candidates[index], vars))
+                    .collect();
+                self.join(
+                    &rendered,
+                    separator_set_id.as_deref().unwrap_or(DEFAULT_SEPARATOR_SET),
+                )
+            }
+            PromptContent::RandomValue {
+                pool,
+                pool_variable,
+                data_type_id,
+                format,
+            } => {
+                let pool: Vec<String> = if let Some(pool) = pool {
+                    pool.clone()
+                } else if let Some(variable) = pool_variable {
+                    self.variables_used.insert(variable.clone());
+                    match vars.get(variable) {
+                        Some(Value::Array(items)) => items.iter().map(display_value).collect(),
+                        _ => Vec::new(),
+                    }
+                } else if let Some(data_type_id) = data_type_id {
+                    self.library
+                        .resolve_data_type(data_type_id)
+                        .and_then(|t| t.validation.as_ref())
+                        .and_then(|v| v.get("enum_values"))
+                        .and_then(|v| v.as_array())
+                        .map(|values| values.iter().map(display_value).collect())
+                        .unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+                if pool.is_empty() {
+                    return String::new();
+                }
+                let value = &pool[self.random.index(pool.len())];
+                match format {
+                    Some(format) => apply_format(value, format),
+                    None => value.clone(),
+                }
+            }
+            PromptContent::WeightedPick { options } => {
+                if options.is_empty() {
+                    return String::new();
+                }
+                let weights: Vec<f64> = options.iter().map(|o| o.weight).collect();
+                let index = self.random.weighted(&weights);
+                self.render(&options[index].content, vars)
+            }
+            PromptContent::Shuffle {
+                variable_id,
+                count,
+                separator_set_id,
+                item_template,
+            } => {
+                self.variables_used.insert(variable_id.clone());
+                let mut items = match vars.get(variable_id) {
+                    Some(Value::Array(items)) if !items.is_empty() => items.clone(),
+                    _ => return String::new(),
+                };
+                // Fisher-Yates
+                for i in (1..items.len()).rev() {
+                    let j = self.random.index(i + 1);
+                    items.swap(i, j);
+                }
+                if let Some(count) = count.filter(|c| *c > 0) {
+                    items.truncate(count);
+                }
+                let rendered = self.render_items(&items, item_template.as_deref(), vars);
+                self.join(
+                    &rendered,
+                    separator_set_id.as_deref().unwrap_or(DEFAULT_SEPARATOR_SET),
+                )
+            }
+            PromptContent::Unknown => {
+                self.warnings.push("Unknown content type".to_string());
+                String::new()
+            }
+        }
+    }
+
+    fn render_section_ref(&mut self, section_id: &str, vars: &Map<String, Value>) -> String {
+        let library = self.library;
+        let Some(section) = library.resolve_section(section_id, Some(&self.namespace)) else {
+            self.warnings
+                .push(format!("Section not found: {}", section_id));
+            return String::new();
+        };
+        if self.depth >= MAX_SECTION_DEPTH {
+            self.warnings.push(format!(
+                "Section nesting too deep at {} (circular reference?)",
+                section_id
+            ));
+            return String::new();
+        }
+        let content: PromptContent = match serde_json::from_value(section.content.clone()) {
+            Ok(content) => content,
+            Err(e) => {
+                self.warnings
+                    .push(format!("Invalid content in section {}: {}", section_id, e));
+                return String::new();
+            }
+        };
+
+        self.sections_used.insert(section_id.to_string());
+        self.depth += 1;
+        let text = self.render(&content, vars);
+        self.depth -= 1;
+        text
+    }
+
+    /// Render array items, with `item`, `index` and the item's own fields in scope
+    fn render_items(
+        &mut self,
+        items: &[Value],
+        template: Option<&PromptContent>,
+        vars: &Map<String, Value>,
+    ) -> Vec<String> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| match template {
+                Some(template) => {
+                    let mut scope = vars.clone();
+                    scope.insert("item".to_string(), item.clone());
+                    scope.insert("index".to_string(), index.into());
+                    if let Value::Object(fields) = item {
+                        scope.extend(fields.clone());
+                    }
+                    self.render(template, &scope)
+                }
+                None => display_value(item),
+            })
+            .collect()
+    }
+
+    fn join(&mut self, items: &[String], separator_set_id: &str) -> String {
+        match self
+            .library
+            .separator_rules(separator_set_id, self.locale.as_deref())
+        {
+            Some(rules) => rules.join(items),
+            None => {
+                self.warnings
+                    .push(format!("Separator set not found: {}", separator_set_id));
+                items.join(", ")
+            }
+        }
+    }
+
+    fn evaluate(&mut self, condition: &Condition, vars: &Map<String, Value>) -> bool {
+        if let Some(all) = &condition.and {
+            return all.iter().all(|c| self.evaluate(c, vars));
+        }
+        if let Some(any) = &condition.or {
+            return any.iter().any(|c| self.evaluate(c, vars));
+        }
+        if let Some(not) = &condition.not {
+            return !self.evaluate(not, vars);
+        }
+
+        let value = if let Some(variable) = &condition.variable {
+            vars.get(variable)
+                .and_then(|v| lookup_path(v, condition.path.as_deref()))
+                .cloned()
+        } else if let Some(key) = &condition.context_key {
+            match key.split_once('.') {
+                Some(("system", key)) => self.system.get(key).cloned(),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let value = value.unwrap_or(Value::Null);
+        let expected = &condition.value;
+        let is_blank = value.is_null() || value.as_str() == Some("");
+
+        match condition.operator.as_str() {
+            "exists" => !is_blank,
+            "not_exists" => is_blank,
+            "equals" => values_equal(&value, expected),
+            "not_equals" => !values_equal(&value, expected),
+            "contains" => match &value {
+                Value::Array(items) => items.iter().any(|item| values_equal(item, expected)),
+                Value::String(s) => s.contains(&display_value(expected)),
+                _ => false,
+            },
+            "starts_with" => value
+                .as_str()
+                .zip(expected.as_str())
+                .is_some_and(|(s, prefix)| s.starts_with(prefix)),
+            "ends_with" => value
+                .as_str()
+                .zip(expected.as_str())
+                .is_some_and(|(s, suffix)| s.ends_with(suffix)),
+            "greater_than" => value
+                .as_f64()
+                .zip(expected.as_f64())
+                .is_some_and(|(a, b)| a > b),
+            "less_than" => value
+                .as_f64()
+                .zip(expected.as_f64())
+                .is_some_and(|(a, b)| a < b),
+            "has_items" => value.as_array().is_some_and(|items| !items.is_empty()),
+            "is_empty" => match &value {
+                Value::Array(items) => items.is_empty(),
+                Value::String(s) => s.is_empty(),
+                other => !is_truthy(other),
+            },
+            "matches" => match (value.as_str(), expected.as_str()) {
+                (Some(s), Some(pattern)) if !pattern.is_empty() => {
+                    match regex::Regex::new(pattern) {
+                        Ok(re) => re.is_match(s),
+                        Err(e) => {
+                            self.warnings
+                                .push(format!("Invalid pattern {}: {}", pattern, e));
+                            false
+                        }
+                    }
+                }
+                _ => false,
+            },
+            other => {
+                self.warnings
+                    .push(format!("Unknown condition operator: {}", other));
+                false
+            }
+        }
+    }
+}
+
+// ============================================
+// VALUE HELPERS
+// ============================================
+
+/// Follow a dotted path into an object value
+fn lookup_path<'v>(value: &'v Value, path: Option<&str>) -> Option<&'v Value> {
+    match path.filter(|p| !p.is_empty()) {
+        Some(path) => path
+            .split('.')
+            .try_fold(value, |current, key| current.get(key)),
+        None => Some(value),
+    }
+}
+
+/// Text form of a value (strings unquoted, arrays comma-joined like JS)
+pub(super) fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(display_value)
+            .collect::<Vec<_>>()
+            .join(","),
+        other => other.to_string(),
+    }
+}
+
+/// Equality with numbers compared by value (1 == 1.0)
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
+
+/// Count of an array variable, or the value of a numeric one
+fn count_of(value: Option<&Value>) -> f64 {
+    match value {
+        Some(Value::Array(items)) => items.len() as f64,
+        Some(Value::Number(n)) => n.as_f64().unwrap_or(0.0),
+        _ => 0.0,
+    }
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+fn apply_format(value: &str, format: &FormatOptions) -> String {
+    let mut result = if format.trim {
+        value.trim().to_string()
+    } else {
+        value.to_string()
+    };
+
+    if let Some(case) = format.case {
+        result = match case {
+            TextCase::Upper => result.to_uppercase(),
+            TextCase::Lower => result.to_lowercase(),
+            TextCase::Title => {
+                let mut title = String::with_capacity(result.len());
+                let mut word_start = true;
+                for c in result.chars() {
+                    if c.is_whitespace() {
+                        word_start = true;
+                        title.push(c);
+                    } else if word_start {
+                        word_start = false;
+                        title.extend(c.to_uppercase());
+                    } else {
+                        title.extend(c.to_lowercase());
+                    }
+                }
+                title
+            }
+            TextCase::Sentence => {
+                let mut chars = result.chars();
+                match chars.next() {
+                    Some(first) => first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect(),
+                    None => result,
+                }
+            }
+        };
+    }
+
+    for replacement in &format.replace {
+        if !replacement.from.is_empty() {
+            result = result.replace(&replacement.from, &replacement.to);
+        }
+    }
+
+    if let Some(max) = format.truncate.filter(|max| *max > 0) {
+        if result.chars().count() > max {
+            result = result
+                .chars()
+                .take(max.saturating_sub(3))
+                .collect::<String>()
+                + "...";
+        }
+    }
+
+    if let Some(prefix) = &format.prefix {
+        result.insert_str(0, prefix);
+    }
+    if let Some(suffix) = &format.suffix {
+        result.push_str(suffix);
+    }
+    result
+}
+
+/// Words that take "an" despite not starting with a vowel letter
+const AN_WORDS: &[&str] = &[
+    "hour", "hours", "honest", "honor", "honour", "heir", "heirloom", "herb", "herbs", "html",
+    "http", "sql", "mri", "fbi", "nba", "nfl", "mba",
+];
+
+/// Words that take "a" despite starting with a vowel letter
+const A_WORDS: &[&str] = &[
+    "one",
+    "once",
+    "university",
+    "unicorn",
+    "uniform",
+    "union",
+    "unique",
+    "unit",
+    "united",
+    "universe",
+    "universal",
+    "use",
+    "used",
+    "useful",
+    "user",
+    "usual",
+    "usually",
+    "utility",
+    "utensil",
+    "euphoria",
+    "european",
+    "eucalyptus",
+    "ewe",
+];
+
+/// "a"/"an"/"the" for the word that follows
+fn article_for(word: &str, style: ArticleStyle, capitalize: bool) -> String {
+    let word = word.trim().to_lowercase();
+    let Some(first_word) = word.split_whitespace().next() else {
+        return String::new();
+    };
+
+    let article = match style {
+        ArticleStyle::Definite => "the",
+        ArticleStyle::Indefinite if AN_WORDS.contains(&first_word) => "an",
+        ArticleStyle::Indefinite if A_WORDS.contains(&first_word) => "a",
+        ArticleStyle::Indefinite if first_word.starts_with(['a', 'e', 'i', 'o', 'u']) => "an",
+        ArticleStyle::Indefinite => "a",
+    };
+
+    if capitalize {
+        let mut chars = article.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        article.to_string()
+    }
+}
+
+/// "Alice" -> "Alice's", "James" -> "James'"
+fn possessive_of(word: &str) -> String {
+    let word = word.trim();
+    if word.is_empty() {
+        String::new()
+    } else if word.ends_with(['s', 'S']) {
+        format!("{}'", word)
+    } else {
+        format!("{}'s", word)
+    }
+}
+
+/// Third-person singular present of a base-form verb ("run" -> "runs");
+/// only the first word of phrasal verbs is conjugated ("look up" -> "looks up")
+pub(super) fn third_person_singular(verb: &str) -> String {
+    let verb = verb.trim();
+    let (head, rest) = match verb.split_once(' ') {
+        Some((head, rest)) => (head, format!(" {}", rest)),
+        None => (verb, String::new()),
+    };
+    let lower = head.to_lowercase();
+    let conjugated = match lower.as_str() {
+        "" => return String::new(),
+        "be" => "is".to_string(),
+        "have" => "has".to_string(),
+        _ if ["s", "sh", "ch", "x", "z", "o"]
+            .iter()
+            .any(|ending| lower.ends_with(ending)) =>
+        {
+            format!("{}es", head)
+        }
+        _ if lower.ends_with('y')
+            && !lower[..lower.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']) =>
+        {
+            format!("{}ies", &head[..head.len() - 1])
+        }
+        _ => format!("{}s", head),
+    };
+    format!("{}{}", conjugated, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::test_support;
+    use serde_json::json;
+
+    fn section(key: &str, content: Value, exportable: bool) -> PromptSection {
+        PromptSection {
+            exportable,
+            variables: vec![json!({ "id": "role", "default_value": "engineer" })],
+            ..test_support::section(key, content)
+        }
+    }
+
+    #[test]
+    fn test_render_section() {
+        let greeting = json!({
+            "type": "composite",
+            "parts": [
+                { "type": "text", "value": "Hello " },
+                { "type": "variable", "variable_id": "name", "format": { "case": "title" } },
+                { "type": "text", "value": ", you are " },
+                { "type": "article", "word_variable": "role", "style": "indefinite" },
+                { "type": "text", "value": " " },
+                { "type": "variable", "variable_id": "role" },
+                { "type": "text", "value": ". " },
+                { "type": "plural", "count_variable": "items", "one": "one item", "other": "{count} items" },
+                { "type": "text", "value": ": " },
+                { "type": "list", "variable_id": "items", "separator_set_id": "oxford-comma" },
+                { "type": "conditional",
+                  "condition": { "variable": "vip", "operator": "exists" },
+                  "then_content": { "type": "text", "value": " (VIP)" } },
+                { "type": "text", "value": " " },
+                { "type": "section-ref", "section_id": "signoff" },
+                { "type": "section-ref", "section_id": "missing" }
+            ]
+        });
+        let styles = json!({
+            "type": "pick-many",
+            "candidates": [
+                { "type": "text", "value": "bold" },
+                { "type": "text", "value": "calm" },
+                { "type": "text", "value": "dark" },
+                { "type": "text", "value": "warm" }
+            ],
+            "count": { "min": 2, "max": 2 }
+        });
+        let library = PromptLibrary {
+            sections: vec![
+                section("greeting", greeting, false),
+                section("signoff", json!({ "type": "text", "value": "Bye." }), false),
+                section("styles", styles, true),
+            ],
+            ..Default::default()
+        };
+
+        let variables = json!({ "name": "ada lovelace", "items": ["a", "b", "c"] });
+        let rendered = render_section(
+            &library,
+            "greeting",
+            variables.as_object().unwrap().clone(),
+            Some(7),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered.text,
+            "Hello Ada Lovelace, you are an engineer. 3 items: a, b, and c Bye."
+        );
+        assert_eq!(rendered.variables_used, vec!["items", "name", "role"]);
+        assert_eq!(rendered.sections_used, vec!["signoff"]);
+        assert_eq!(rendered.warnings, vec!["Section not found: missing"]);
+
+        // Same seed, same picks; two distinct candidates are picked
+        let first = render_section(&library, "demo:styles", Map::new(), Some(42)).unwrap();
+        let again =
+            render_section(&library, "prompt_sections:styles", Map::new(), Some(42)).unwrap();
+        assert_eq!(first.text, again.text);
+        let picked: Vec<&str> = first.text.split(" and ").collect();
+        assert_eq!(picked.len(), 2);
+        assert_ne!(picked[0], picked[1]);
+
+        assert!(matches!(
+            render_section(&library, "nope", Map::new(), None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_builtin_separator_sets() {
+        let sets = builtin_separator_sets();
+        assert_eq!(sets.len(), BUILTIN_SEPARATOR_SETS.len());
+        let items: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        for set in &sets {
+            check_separator_rules(&set.rules).unwrap();
+            let rules: SeparatorRules = serde_json::from_value(set.rules.clone()).unwrap();
+            let builtin = SeparatorRules::builtin(&set.name).unwrap();
+            assert_eq!(rules.join(&items), builtin.join(&items));
+        }
+        let numbered = sets.iter().find(|s| s.name == "numbered-list").unwrap();
+        assert_eq!(numbered.rules["many"]["numbered"], Value::Bool(true));
+        assert!(is_builtin_separator_set("bullet-list"));
+        assert!(!is_builtin_separator_set("demo:bullet-list"));
+        assert!(check_separator_rules(&serde_json::json!({ "single": {} })).is_err());
+    }
+
+    #[test]
+    fn test_possessive_and_verb() {
+        let library = PromptLibrary {
+            sections: vec![serde_json::from_value(json!({
+                "package_id": "pkg", "namespace": "demo", "name": "owners", "description": "",
+                "content": { "type": "composite", "parts": [
+                    { "type": "possessive", "word_variable": "owner" },
+                    { "type": "text", "value": " cats " },
+                    { "type": "verb", "verb": "chase", "count_variable": "cats" },
+                    { "type": "text", "value": " mice and " },
+                    { "type": "verb", "verb": "be", "count_variable": "cats" },
+                    { "type": "text", "value": " happy." }
+                ]},
+                "is_entry_point": true, "exportable": true, "required_variables": [],
+                "created_at": "", "updated_at": ""
+            }))
+            .unwrap()],
+            ..Default::default()
+        };
+        let render = |vars: Value| {
+            render_section(&library, "owners", vars.as_object().unwrap().clone(), None)
+                .unwrap()
+                .text
+        };
+        assert_eq!(
+            render(json!({ "owner": "James", "cats": ["Tom"] })),
+            "James' cats chases mice and is happy."
+        );
+        assert_eq!(
+            render(json!({ "owner": "Alice", "cats": 3 })),
+            "Alice's cats chase mice and are happy."
+        );
+
+        let singular: Vec<String> = ["run", "go", "watch", "fly", "play", "have", "look up"]
+            .iter()
+            .map(|v| third_person_singular(v))
+            .collect();
+        assert_eq!(
+            singular,
+            vec!["runs", "goes", "watches", "flies", "plays", "has", "looks up"]
+        );
+        assert_eq!(possessive_of(" "), "");
+    }
+
+    #[test]
+    fn test_locale_rendering() {
+        let entry = |name: &str, locale: &str, content: Value| -> PromptSection {
+            serde_json::from_value(json!({
+                "package_id": "pkg", "namespace": "demo", "name": name, "description": "",
+                "content": content, "is_entry_point": true, "exportable": true,
+                "required_variables": [], "locale": locale, "created_at": "", "updated_at": ""
+            }))
+            .unwrap()
+        };
+        let files = json!({ "type": "plural", "count_variable": "n",
+            "one": "{count} файл", "few": "{count} файла", "many": "{count} файлов", "other": "{count} файла" });
+        let library = PromptLibrary {
+            sections: vec![
+                entry(
+                    "guests",
+                    "de-DE",
+                    json!({ "type": "composite", "parts": [
+                        { "type": "list", "variable_id": "names", "separator_set_id": "oxford-comma" },
+                        { "type": "text", "value": " oder " },
+                        { "type": "list", "variable_id": "names", "separator_set_id": "simple-comma" }
+                    ]}),
+                ),
+                entry("files", "ru", files.clone()),
+                entry("files-en", "", files),
+                entry(
+                    "switch",
+                    "ru",
+                    json!({ "type": "count-switch", "count_variable": "n", "cases": [
+                        { "count": "zero", "content": { "type": "text", "value": "нет" } },
+                        { "count": "few", "content": { "type": "text", "value": "несколько" } }
+                    ], "default_content": { "type": "text", "value": "много" } }),
+                ),
+            ],
+            ..Default::default()
+        };
+        let render = |name: &str, vars: Value| {
+            render_section(&library, name, vars.as_object().unwrap().clone(), None)
+                .unwrap()
+                .text
+        };
+
+        assert_eq!(
+            render("guests", json!({ "names": ["Anna", "Ben", "Carla"] })),
+            "Anna, Ben und Carla oder Anna, Ben, Carla"
+        );
+        let counts = |name: &str| -> Vec<String> {
+            [1, 3, 5, 21]
+                .iter()
+                .map(|n| render(name, json!({ "n": n })))
+                .collect()
+        };
+        assert_eq!(
+            counts("files"),
+            vec!["1 файл", "3 файла", "5 файлов", "21 файл"]
+        );
+        // Without a locale the count ranges apply as before
+        assert_eq!(counts("files-en")[3], "21 файлов");
+        let switch: Vec<String> = [0, 2, 7]
+            .iter()
+            .map(|n| render("switch", json!({ "n": n })))
+            .collect();
+        assert_eq!(switch, vec!["нет", "несколько", "много"]);
+    }
+
+    #[test]
+    fn test_negative_prompt() {
+        let mut portrait = section(
+            "portrait",
+            json!({ "type": "composite", "parts": [
+                { "type": "text", "value": "portrait of an " },
+                { "type": "variable", "variable_id": "role" }
+            ]}),
+            true,
+        );
+        portrait.negative_content = Some(json!({ "type": "composite", "parts": [
+            { "type": "text", "value": "blurry, " },
+            { "type": "pick-one", "candidates": [
+                { "type": "text", "value": "extra fingers" },
+                { "type": "text", "value": "watermark" }
+            ]}
+        ]}));
+        let library = PromptLibrary {
+            sections: vec![
+                portrait,
+                section("plain", json!({ "type": "text", "value": "x" }), true),
+            ],
+            ..Default::default()
+        };
+
+        let rendered = render_section(&library, "portrait", Map::new(), Some(3)).unwrap();
+        assert_eq!(rendered.text, "portrait of an engineer");
+        let negative = rendered.negative_prompt.unwrap();
+        assert!(negative.starts_with("blurry, "), "{}", negative);
+        let again = render_section(&library, "portrait", Map::new(), Some(3)).unwrap();
+        assert_eq!(again.negative_prompt.as_deref(), Some(negative.as_str()));
+
+        let plain = render_section(&library, "plain", Map::new(), None).unwrap();
+        assert_eq!(plain.negative_prompt, None);
+        assert!(!serde_json::to_value(&plain)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .contains_key("negative_prompt"));
+    }
+}
@@ -0,0 +1,246 @@
+// Section revisions
+//
+// Updating a section first stores its previous state in
+// `prompt_section_revisions`, numbered per section, so a composite that used
+// to work can be looked at again or restored. Reverting is itself an update:
+// the state being replaced becomes the newest revision.
+
+use super::{get_timestamp, PromptSection};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// Revisions kept per section (older ones are dropped when recording)
+const MAX_REVISIONS_PER_SECTION: usize = 50;
+
+/// Revision as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RevisionRecord {
+    pub id: Thing,
+    pub section_id: String,
+    pub package_id: String,
+    pub revision: u32,
+    pub section: PromptSection,
+    pub saved_at: String,
+}
+
+/// User-facing revision with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionRevision {
+    pub id: String,
+    pub section_id: String,
+    /// 1 for the oldest kept state, increasing with every update
+    pub revision: u32,
+    /// The section as it was before being replaced
+    pub section: PromptSection,
+    pub saved_at: String,
+}
+
+impl From<RevisionRecord> for SectionRevision {
+    fn from(record: RevisionRecord) -> Self {
+        SectionRevision {
+            id: record.id.id.to_raw(),
+            section_id: record.section_id,
+            revision: record.revision,
+            section: record.section,
+            saved_at: record.saved_at,
+        }
+    }
+}
+
+fn section_key(id: &str) -> &str {
+    id.strip_prefix("prompt_sections:").unwrap_or(id)
+}
+
+/// Whether two states of a section differ in more than ID and timestamps
+fn changed(old: &PromptSection, new: &PromptSection) -> bool {
+    let strip = |section: &PromptSection| {
+        let mut value = serde_json::to_value(section).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            for key in ["id", "created_at", "updated_at"] {
+                fields.remove(key);
+            }
+        }
+        value
+    };
+    strip(old) != strip(new)
+}
+
+async fn record_revision(
+    db: &Database,
+    section_id: &str,
+    previous: &PromptSection,
+) -> Result<(), AppError> {
+    let mut result = db
+        .query(
+            "SELECT VALUE revision FROM prompt_section_revisions
+             WHERE section_id = $section_id ORDER BY revision DESC LIMIT 1",
+        )
+        .bind(("section_id", section_id.to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query revisions: {}", e)))?;
+    let latest: Vec<u32> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to query revisions: {}", e)))?;
+
+    let key = uuid::Uuid::new_v4().to_string();
+    let record = RevisionRecord {
+        id: Thing::from(("prompt_section_revisions", key.as_str())),
+        section_id: section_id.to_string(),
+        package_id: previous.package_id.clone(),
+        revision: latest.first().map_or(1, |r| r + 1),
+        section: PromptSection {
+            id: None,
+            ..previous.clone()
+        },
+        saved_at: get_timestamp(),
+    };
+    let _: Option<RevisionRecord> = db
+        .create_row_with_id("prompt_section_revisions", key.as_str(), record)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to record revision: {}", e)))?;
+
+    // Keep the revisions of each section bounded
+    db.query(
+        "LET $old = (SELECT VALUE id FROM (SELECT id, revision FROM prompt_section_revisions
+                 WHERE section_id = $section_id ORDER BY revision DESC START $keep));
+             DELETE $old;",
+    )
+    .bind(("section_id", section_id.to_string()))
+    .bind(("keep", MAX_REVISIONS_PER_SECTION))
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to trim revisions: {}", e)))?
+    .check()
+    .map_err(|e| AppError::Database(format!("Failed to trim revisions: {}", e)))?;
+    Ok(())
+}
+
+/// Update a section, keeping its previous state as a revision when it changed
+pub async fn save_section(
+    db: &Database,
+    id: &str,
+    mut section: PromptSection,
+) -> Result<PromptSection, AppError> {
+    let id = section_key(id);
+    let current: Option<PromptSection> = db
+        .db
+        .select(("prompt_sections", id))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get section: {}", e)))?;
+    let current = current.ok_or_else(|| AppError::NotFound(format!("Section {}", id)))?;
+    if changed(&current, &section) {
+        record_revision(db, id, &current).await?;
+    }
+
+    section.id = None;
+    section.updated_at = get_timestamp();
+    let updated: Option<PromptSection> = db
+        .update_row("prompt_sections", id, section)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update section: {}", e)))?;
+    updated.ok_or_else(|| AppError::NotFound(format!("Section {}", id)))
+}
+
+/// Stored revisions of a section, newest first
+pub async fn list_revisions(
+    db: &Database,
+    section_id: &str,
+) -> Result<Vec<SectionRevision>, AppError> {
+    let mut result = db
+        .query(
+            "SELECT * FROM prompt_section_revisions
+             WHERE section_id = $section_id ORDER BY revision DESC",
+        )
+        .bind(("section_id", section_key(section_id).to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query revisions: {}", e)))?;
+    let records: Vec<RevisionRecord> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse revisions: {}", e)))?;
+    Ok(records.into_iter().map(|r| r.into()).collect())
+}
+
+/// Restore a section to the state stored as `revision`
+pub async fn revert_section(
+    db: &Database,
+    section_id: &str,
+    revision: u32,
+) -> Result<PromptSection, AppError> {
+    let stored = list_revisions(db, section_id)
+        .await?
+        .into_iter()
+        .find(|r| r.revision == revision)
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Revision {} of section {}", revision, section_id))
+        })?;
+    save_section(db, section_id, stored.section).await
+}
+
+/// Drop the revisions of a deleted section
+pub async fn delete_revisions(db: &Database, section_id: &str) -> Result<(), AppError> {
+    db.query("DELETE prompt_section_revisions WHERE section_id = $section_id")
+        .bind(("section_id", section_key(section_id).to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete revisions: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to delete revisions: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::test_support;
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+
+    fn section(content: Value) -> PromptSection {
+        PromptSection {
+            id: None,
+            ..test_support::section("intro", content)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_section_revisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let first = json!({ "type": "text", "value": "v1" });
+        let second = json!({ "type": "text", "value": "v2" });
+        let _: Option<PromptSection> = db
+            .db
+            .create(("prompt_sections", "intro"))
+            .content(section(first.clone()))
+            .await
+            .unwrap();
+
+        save_section(&db, "intro", section(second.clone()))
+            .await
+            .unwrap();
+        // Saving without changes keeps no revision
+        save_section(&db, "prompt_sections:intro", section(second.clone()))
+            .await
+            .unwrap();
+        let revisions = list_revisions(&db, "intro").await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].revision, 1);
+        assert_eq!(revisions[0].section.content, first);
+
+        let reverted = revert_section(&db, "intro", 1).await.unwrap();
+        assert_eq!(reverted.content, first);
+        let revisions = list_revisions(&db, "intro").await.unwrap();
+        let found: Vec<(u32, &Value)> = revisions
+            .iter()
+            .map(|r| (r.revision, &r.section.content))
+            .collect();
+        assert_eq!(found, vec![(2, &second), (1, &first)]);
+
+        assert!(matches!(
+            revert_section(&db, "intro", 9).await,
+            Err(AppError::NotFound(_))
+        ));
+        delete_revisions(&db, "intro").await.unwrap();
+        assert!(list_revisions(&db, "intro").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+// Prompt content search
+//
+// Finds sections and packages by name, description, tags and the literal text
+// inside section content (text nodes, plural forms, random pools), plus the
+// IDs content refers to, so "bullet-list" finds sections using that separator
+// set. Libraries are small enough to search in memory: every query term must
+// match somewhere, and matches in names and tags rank above body text. The
+// scoring is shared with `global_search`.
+
+use super::{extract_id, PromptPackage, PromptSection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Default number of hits returned
+pub const DEFAULT_PROMPT_SEARCH_LIMIT: usize = 50;
+
+/// Content keys holding literal prompt text
+const TEXT_KEYS: &[&str] = &[
+    "value",
+    "zero",
+    "one",
+    "two",
+    "few",
+    "many",
+    "other",
+    "pool",
+    "prefix",
+    "suffix",
+    "placeholder",
+    "verb",
+    "singular",
+];
+
+/// Content keys referring to other records or variables
+const REFERENCE_KEYS: &[&str] = &[
+    "section_id",
+    "separator_set_id",
+    "data_type_id",
+    "variable_id",
+    "count_variable",
+    "word_variable",
+    "pool_variable",
+];
+
+/// Characters of context on each side of a match in snippets
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptSearchKind {
+    Section,
+    Package,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptSearchFilters {
+    /// Only this kind of result
+    pub kind: Option<PromptSearchKind>,
+    pub package_id: Option<String>,
+    pub namespace: Option<String>,
+    /// Sections must carry all of these tags
+    pub tags: Vec<String>,
+    pub entry_points_only: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptSearchMatch {
+    /// "name", "description", "tags", "content", "references", ...
+    pub field: String,
+    /// Matched text with terms wrapped in `<mark>` tags
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptSearchHit {
+    pub kind: PromptSearchKind,
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    /// Owning package (packages: their own ID)
+    pub package_id: String,
+    pub score: f64,
+    pub matches: Vec<PromptSearchMatch>,
+}
+
+/// Searchable field of a result candidate
+pub(crate) struct Field {
+    name: &'static str,
+    weight: f64,
+    text: String,
+}
+
+/// Search sections and packages; hits are ordered by score
+pub fn search_prompt_content(
+    packages: &[PromptPackage],
+    sections: &[PromptSection],
+    query: &str,
+    filters: &PromptSearchFilters,
+) -> Vec<PromptSearchHit> {
+    let query = query.trim().to_ascii_lowercase();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits = Vec::new();
+    if filters.kind != Some(PromptSearchKind::Package) {
+        for section in sections {
+            if filters
+                .package_id
+                .as_ref()
+                .is_some_and(|id| *id != section.package_id)
+                || filters
+                    .namespace
+                    .as_ref()
+                    .is_some_and(|ns| *ns != section.namespace)
+                || !filters.tags.iter().all(|t| section.tags.contains(t))
+                || (filters.entry_points_only && !section.is_entry_point)
+            {
+                continue;
+            }
+
+            let (mut text, mut references) = (Vec::new(), Vec::new());
+            for (_, content) in section.content_fields() {
+                collect_content(content, None, &mut text, &mut references);
+            }
+            let fields = [
+                field("name", 5.0, section.name.clone()),
+                field("tags", 3.0, section.tags.join(" ")),
+                field("description", 2.0, section.description.clone()),
+                field("content", 1.0, text.join(" … ")),
+                field("references", 1.0, references.join(" ")),
+            ];
+            if let Some((score, matches)) = score(&fields, &terms, &query) {
+                hits.push(PromptSearchHit {
+                    kind: PromptSearchKind::Section,
+                    id: extract_id(&section.id).unwrap_or_default(),
+                    name: section.name.clone(),
+                    namespace: section.namespace.clone(),
+                    package_id: section.package_id.clone(),
+                    score,
+                    matches,
+                });
+            }
+        }
+    }
+
+    let package_filters_apply = filters.tags.is_empty() && !filters.entry_points_only;
+    if filters.kind != Some(PromptSearchKind::Section) && package_filters_apply {
+        for package in packages {
+            let id = extract_id(&package.id).unwrap_or_default();
+            if filters.package_id.as_ref().is_some_and(|p| *p != id)
+                || filters
+                    .namespace
+                    .as_ref()
+                    .is_some_and(|ns| *ns != package.namespace)
+            {
+                continue;
+            }
+            let fields = [
+                field("name", 5.0, package.name.clone()),
+                field("namespace", 3.0, package.namespace.clone()),
+                field("description", 2.0, package.description.clone()),
+                field("author", 1.0, package.author.clone()),
+            ];
+            if let Some((score, matches)) = score(&fields, &terms, &query) {
+                hits.push(PromptSearchHit {
+                    kind: PromptSearchKind::Package,
+                    package_id: id.clone(),
+                    id,
+                    name: package.name.clone(),
+                    namespace: package.namespace.clone(),
+                    score,
+                    matches,
+                });
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    hits.truncate(filters.limit.unwrap_or(DEFAULT_PROMPT_SEARCH_LIMIT));
+    hits
+}
+
+pub(crate) fn field(name: &'static str, weight: f64, text: String) -> Field {
+    Field { name, weight, text }
+}
+
+/// Literal text and references found in a content AST
+fn collect_content(
+    value: &Value,
+    key: Option<&str>,
+    text: &mut Vec<String>,
+    references: &mut Vec<String>,
+) {
+    match value {
+        Value::String(s) if s.trim().is_empty() => {}
+        Value::String(s) => match key {
+            Some(k) if TEXT_KEYS.contains(&k) => text.push(s.clone()),
+            Some(k) if REFERENCE_KEYS.contains(&k) => references.push(s.clone()),
+            _ => {}
+        },
+        Value::Array(items) => {
+            for item in items {
+                collect_content(item, key, text, references);
+            }
+        }
+        Value::Object(fields) => {
+            for (k, v) in fields {
+                collect_content(v, Some(k), text, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Score a candidate; `None` unless every term matches some field
+pub(crate) fn score(
+    fields: &[Field],
+    terms: &[&str],
+    query: &str,
+) -> Option<(f64, Vec<PromptSearchMatch>)> {
+    let lowered: Vec<String> = fields.iter().map(|f| f.text.to_ascii_lowercase()).collect();
+    let mut total = 0.0;
+    for term in terms {
+        let best = fields
+            .iter()
+            .zip(&lowered)
+            .filter(|(_, text)| text.contains(term))
+            .map(|(f, text)| {
+                // Whole-word matches count fully, partial ones half
+                let whole = text
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .any(|word| word == *term);
+                if whole {
+                    f.weight
+                } else {
+                    f.weight / 2.0
+                }
+            })
+            .fold(None, |best: Option<f64>, w| {
+                Some(best.map_or(w, |b| b.max(w)))
+            })?;
+        total += best;
+    }
+
+    let mut matches = Vec::new();
+    for (f, text) in fields.iter().zip(&lowered) {
+        if terms.iter().any(|t| text.contains(t)) {
+            // Phrase bonus for multi-word queries
+            if terms.len() > 1 && text.contains(query) {
+                total += f.weight;
+            }
+            matches.push(PromptSearchMatch {
+                field: f.name.to_string(),
+                snippet: snippet(&f.text, text, terms),
+            });
+        }
+    }
+    Some((total, matches))
+}
+
+/// Text around the first match with all terms highlighted
+fn snippet(text: &str, lowered: &str, terms: &[&str]) -> String {
+    let first = terms
+        .iter()
+        .filter_map(|t| lowered.find(t))
+        .min()
+        .unwrap_or(0);
+    let mut start = first.saturating_sub(SNIPPET_CONTEXT);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (first + SNIPPET_CONTEXT * 2).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+
+    // Highlight ranges within the window, merged where they overlap
+    let mut ranges: Vec<(usize, usize)> = terms
+        .iter()
+        .flat_map(|t| {
+            lowered[start..end]
+                .match_indices(t)
+                .map(move |(i, m)| (start + i, start + i + m.len()))
+        })
+        .collect();
+    ranges.sort_unstable();
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    let mut pos = start;
+    for (from, to) in ranges {
+        if to <= pos {
+            continue;
+        }
+        let from = from.max(pos);
+        out.push_str(&text[pos..from]);
+        out.push_str("<mark>");
+        out.push_str(&text[from..to]);
+        out.push_str("</mark>");
+        pos = to;
+    }
+    out.push_str(&text[pos..end]);
+    if end < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_search_prompt_content() {
+        let section = |name: &str, tags: Vec<&str>, content: Value| -> PromptSection {
+            serde_json::from_value(json!({
+                "package_id": "pkg", "namespace": "demo", "name": name,
+                "description": "", "content": content,
+                "is_entry_point": true, "exportable": true, "required_variables": [],
+                "tags": tags, "created_at": "", "updated_at": ""
+            }))
+            .unwrap()
+        };
+        let sections = vec![
+            section(
+                "code-review",
+                vec!["review"],
+                json!({ "type": "composite", "parts": [
+                    { "type": "text", "value": "Follow these review guidelines:\n" },
+                    { "type": "list", "variable_id": "rules", "separator_set_id": "bullet-list" }
+                ]}),
+            ),
+            section(
+                "guidelines",
+                vec![],
+                json!({ "type": "text", "value": "Be kind." }),
+            ),
+            section(
+                "story",
+                vec!["fiction"],
+                json!({ "type": "plural", "count_variable": "n", "one": "a dragon", "other": "{count} dragons" }),
+            ),
+        ];
+        let packages: Vec<PromptPackage> = vec![serde_json::from_value(json!({
+            "namespace": "demo", "name": "Review helpers", "version": "1.0.0",
+            "description": "Guidelines for code review", "author": "",
+            "dependencies": [], "exports": [], "created_at": "", "updated_at": ""
+        }))
+        .unwrap()];
+
+        let filters = PromptSearchFilters::default();
+        let hits = search_prompt_content(&packages, &sections, "bullet-list guidelines", &filters);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].name, "code-review");
+        let content = hits[0]
+            .matches
+            .iter()
+            .find(|m| m.field == "content")
+            .unwrap();
+        assert_eq!(
+            content.snippet,
+            "Follow these review <mark>guidelines</mark>:\n"
+        );
+
+        let hits = search_prompt_content(&packages, &sections, "Guidelines", &filters);
+        let names: Vec<&str> = hits.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["guidelines", "Review helpers", "code-review"]);
+
+        let hits = search_prompt_content(&packages, &sections, "dragons", &filters);
+        assert_eq!(hits[0].name, "story");
+
+        let filters = PromptSearchFilters {
+            tags: vec!["review".to_string()],
+            ..Default::default()
+        };
+        let hits = search_prompt_content(&packages, &sections, "guidelines", &filters);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, PromptSearchKind::Section);
+        assert!(search_prompt_content(&packages, &sections, "  ", &filters).is_empty());
+    }
+}
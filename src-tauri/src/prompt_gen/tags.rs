@@ -0,0 +1,325 @@
+// Prompt tag maintenance
+//
+// Sections carry tags by name, so renaming or deleting a tag has to rewrite
+// the `tags` of every section in the package (and the `parent` of child
+// tags). Bulk add/remove applies one tag to a selection of sections.
+
+use super::{extract_id, get_timestamp, PromptSection, PromptTag};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagAction {
+    Add,
+    Remove,
+}
+
+/// Sections and tags touched by a tag operation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagChanges {
+    pub sections_updated: usize,
+    pub tags_updated: usize,
+}
+
+/// Replace `old` by `new` (or drop it when `new` is `None`), keeping order
+/// and avoiding duplicates; returns whether anything changed
+fn replace_tag(tags: &mut Vec<String>, old: &str, new: Option<&str>) -> bool {
+    let Some(index) = tags.iter().position(|t| t == old) else {
+        return false;
+    };
+    tags.retain(|t| t != old);
+    if let Some(new) = new.filter(|new| !tags.iter().any(|t| t == new)) {
+        tags.insert(index, new.to_string());
+    }
+    true
+}
+
+fn section_key(id: &str) -> &str {
+    id.strip_prefix("prompt_sections:").unwrap_or(id)
+}
+
+fn tag_key(id: &str) -> &str {
+    id.strip_prefix("prompt_tags:").unwrap_or(id)
+}
+
+async fn query_records<T: serde::de::DeserializeOwned>(
+    db: &Database,
+    sql: &str,
+    package_id: &str,
+) -> Result<Vec<T>, AppError> {
+    let mut result = db
+        .query(sql)
+        .bind(("package_id", package_id.to_string()))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query tags: {}", e)))?;
+    result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to query tags: {}", e)))
+}
+
+async fn set_section_tags(db: &Database, key: &str, tags: &[String]) -> Result<(), AppError> {
+    let _: Option<PromptSection> = db
+        .merge_row(
+            "prompt_sections",
+            key,
+            json!({ "tags": tags, "updated_at": get_timestamp() }),
+        )
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update section tags: {}", e)))?;
+    Ok(())
+}
+
+/// Rename (or with `new` = `None`, remove) a tag across a package's sections
+/// and child tags
+async fn cascade(
+    db: &Database,
+    package_id: &str,
+    old: &str,
+    new: Option<&str>,
+    new_parent: Option<&str>,
+) -> Result<TagChanges, AppError> {
+    let mut changes = TagChanges::default();
+    let sections: Vec<PromptSection> = query_records(
+        db,
+        "SELECT * FROM prompt_sections WHERE package_id = $package_id",
+        package_id,
+    )
+    .await?;
+    for mut section in sections {
+        if replace_tag(&mut section.tags, old, new) {
+            let key = extract_id(&section.id).unwrap_or_default();
+            set_section_tags(db, &key, &section.tags).await?;
+            changes.sections_updated += 1;
+        }
+    }
+
+    let tags: Vec<PromptTag> = query_records(
+        db,
+        "SELECT * FROM prompt_tags WHERE package_id = $package_id",
+        package_id,
+    )
+    .await?;
+    for tag in tags.iter().filter(|t| t.parent.as_deref() == Some(old)) {
+        let key = extract_id(&tag.id).unwrap_or_default();
+        let _: Option<PromptTag> = db
+            .merge_row(
+                "prompt_tags",
+                key.as_str(),
+                json!({ "parent": new.or(new_parent), "updated_at": get_timestamp() }),
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update tag: {}", e)))?;
+        changes.tags_updated += 1;
+    }
+    Ok(changes)
+}
+
+/// Rename a tag in a package: its definition (if any), the sections using it
+/// and the tags nested under it
+pub async fn rename_tag(
+    db: &Database,
+    package_id: &str,
+    old: &str,
+    new: &str,
+) -> Result<TagChanges, AppError> {
+    let new = new.trim();
+    if new.is_empty() {
+        return Err(AppError::Validation("Tag name cannot be empty".to_string()));
+    }
+    let tags: Vec<PromptTag> = query_records(
+        db,
+        "SELECT * FROM prompt_tags WHERE package_id = $package_id",
+        package_id,
+    )
+    .await?;
+    if new != old && tags.iter().any(|t| t.name == new) {
+        return Err(AppError::Validation(format!(
+            "Tag '{}' already exists in this package",
+            new
+        )));
+    }
+
+    let mut changes = cascade(db, package_id, old, Some(new), None).await?;
+    if let Some(tag) = tags.iter().find(|t| t.name == old) {
+        let key = extract_id(&tag.id).unwrap_or_default();
+        let _: Option<PromptTag> = db
+            .merge_row(
+                "prompt_tags",
+                key.as_str(),
+                json!({ "name": new, "updated_at": get_timestamp() }),
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to rename tag: {}", e)))?;
+        changes.tags_updated += 1;
+    }
+    Ok(changes)
+}
+
+/// Update a tag definition; a new name is cascaded like `rename_tag`
+pub async fn update_tag(
+    db: &Database,
+    id: &str,
+    mut tag: PromptTag,
+) -> Result<PromptTag, AppError> {
+    let id = tag_key(id);
+    let current: Option<PromptTag> = db
+        .db
+        .select(("prompt_tags", id))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to get tag: {}", e)))?;
+    let current = current.ok_or_else(|| AppError::NotFound(format!("Tag {}", id)))?;
+    if current.name != tag.name {
+        rename_tag(db, &current.package_id, &current.name, &tag.name).await?;
+    }
+
+    tag.id = None;
+    tag.created_at = current.created_at;
+    tag.updated_at = get_timestamp();
+    let updated: Option<PromptTag> = db
+        .update_row("prompt_tags", id, tag)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update tag: {}", e)))?;
+    updated.ok_or_else(|| AppError::NotFound(format!("Tag {}", id)))
+}
+
+/// Delete a tag definition, removing it from sections; child tags move up
+/// to the deleted tag's parent
+pub async fn delete_tag(db: &Database, id: &str) -> Result<TagChanges, AppError> {
+    let id = tag_key(id);
+    let tag: Option<PromptTag> = db
+        .delete_row("prompt_tags", id)
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete tag: {}", e)))?;
+    let tag = tag.ok_or_else(|| AppError::NotFound(format!("Tag {}", id)))?;
+    cascade(db, &tag.package_id, &tag.name, None, tag.parent.as_deref()).await
+}
+
+/// Add or remove one tag on the given sections
+pub async fn tag_sections(
+    db: &Database,
+    section_ids: &[String],
+    tag: &str,
+    action: TagAction,
+) -> Result<TagChanges, AppError> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err(AppError::Validation("Tag name cannot be empty".to_string()));
+    }
+    let mut changes = TagChanges::default();
+    for id in section_ids {
+        let key = section_key(id);
+        let section: Option<PromptSection> = db
+            .db
+            .select(("prompt_sections", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get section: {}", e)))?;
+        let mut section = section.ok_or_else(|| AppError::NotFound(format!("Section {}", id)))?;
+        let changed = match action {
+            TagAction::Add if !section.tags.iter().any(|t| t == tag) => {
+                section.tags.push(tag.to_string());
+                true
+            }
+            TagAction::Add => false,
+            TagAction::Remove => replace_tag(&mut section.tags, tag, None),
+        };
+        if changed {
+            set_section_tags(db, key, &section.tags).await?;
+            changes.sections_updated += 1;
+        }
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn create_section(db: &Database, key: &str, package_id: &str, tags: &[&str]) {
+        let section: PromptSection = serde_json::from_value(json!({
+            "package_id": package_id, "namespace": "demo", "name": key, "description": "",
+            "content": { "type": "text", "value": key }, "is_entry_point": true,
+            "exportable": true, "required_variables": [], "tags": tags,
+            "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let _: Option<PromptSection> = db
+            .db
+            .create(("prompt_sections", key))
+            .content(section)
+            .await
+            .unwrap();
+    }
+
+    async fn tags_of(db: &Database, key: &str) -> Vec<String> {
+        let section: Option<PromptSection> = db.db.select(("prompt_sections", key)).await.unwrap();
+        section.unwrap().tags
+    }
+
+    async fn create_tag(db: &Database, key: &str, name: &str, parent: Option<&str>) {
+        let tag: PromptTag = serde_json::from_value(json!({
+            "package_id": "pkg", "namespace": "demo", "name": name, "description": "",
+            "color": null, "parent": parent, "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let _: Option<PromptTag> = db
+            .db
+            .create(("prompt_tags", key))
+            .content(tag)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tag_operations() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        create_section(&db, "a", "pkg", &["draft", "style", "fantasy"]).await;
+        create_section(&db, "b", "pkg", &["style", "painting"]).await;
+        create_section(&db, "c", "other", &["style"]).await;
+        create_tag(&db, "t1", "style", None).await;
+        create_tag(&db, "t2", "painting", Some("style")).await;
+        create_tag(&db, "t3", "art", None).await;
+
+        // Renames cascade within the package only, keeping tag order
+        let changes = rename_tag(&db, "pkg", "style", "art-style").await.unwrap();
+        assert_eq!(changes.sections_updated, 2);
+        assert_eq!(changes.tags_updated, 2);
+        assert_eq!(
+            tags_of(&db, "a").await,
+            vec!["draft", "art-style", "fantasy"]
+        );
+        assert_eq!(tags_of(&db, "c").await, vec!["style"]);
+        let child: Option<PromptTag> = db.db.select(("prompt_tags", "t2")).await.unwrap();
+        assert_eq!(child.unwrap().parent.as_deref(), Some("art-style"));
+        assert!(matches!(
+            rename_tag(&db, "pkg", "art-style", "art").await,
+            Err(AppError::Validation(_))
+        ));
+
+        let ids = vec!["a".to_string(), "prompt_sections:b".to_string()];
+        let changes = tag_sections(&db, &ids, "fantasy", TagAction::Add)
+            .await
+            .unwrap();
+        assert_eq!(changes.sections_updated, 1);
+        assert_eq!(
+            tags_of(&db, "b").await,
+            vec!["art-style", "painting", "fantasy"]
+        );
+        let changes = tag_sections(&db, &ids, "draft", TagAction::Remove)
+            .await
+            .unwrap();
+        assert_eq!(changes.sections_updated, 1);
+        assert_eq!(tags_of(&db, "a").await, vec!["art-style", "fantasy"]);
+
+        // Deleting a tag strips it from sections and re-parents its children
+        let changes = delete_tag(&db, "t1").await.unwrap();
+        assert_eq!(changes.sections_updated, 2);
+        assert_eq!(tags_of(&db, "a").await, vec!["fantasy"]);
+        let child: Option<PromptTag> = db.db.select(("prompt_tags", "t2")).await.unwrap();
+        assert_eq!(child.unwrap().parent, None);
+    }
+}
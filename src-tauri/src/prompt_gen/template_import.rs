@@ -0,0 +1,1005 @@
+// Template import
+//
+// Converts simple Jinja2 / Handlebars templates into section content so an
+// existing prompt library can be migrated. Supported: text, variable output
+// with case filters/helpers, if/elif/else/unless, for/each loops (imported as
+// `list` content), includes/partials (`section-ref`) and macros/inline
+// partials, which become sections of their own. Anything else is skipped and
+// reported as a warning; the result is a set of draft sections to review.
+
+use super::formats::TemplateDialect;
+use super::{get_timestamp, PromptSection};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Parsed templates, not yet assigned to a package
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateImport {
+    /// The top-level template first (named "main"), then one section per macro/partial
+    pub sections: Vec<PromptSection>,
+    pub warnings: Vec<String>,
+}
+
+/// Parse template text into draft sections
+pub fn import_templates(
+    dialect: TemplateDialect,
+    content: &str,
+) -> Result<TemplateImport, AppError> {
+    let mut parser = Parser {
+        dialect,
+        tokens: tokenize(dialect, content)?,
+        pos: 0,
+        loop_vars: Vec::new(),
+        loop_control: false,
+        variables: BTreeMap::new(),
+        partials: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let (nodes, _) = parser.parse_until(&[])?;
+    let main = composite(nodes);
+    let mut sections = Vec::new();
+    let is_blank = matches!(&main, Value::Object(o)
+        if o["type"] == "text" && o["value"].as_str().is_some_and(|v| v.trim().is_empty()));
+    if !is_blank {
+        sections.push(draft_section(
+            dialect,
+            "main",
+            main,
+            &parser.variables,
+            true,
+        ));
+    }
+    for (name, content, variables) in &parser.partials {
+        sections.push(draft_section(
+            dialect,
+            name,
+            content.clone(),
+            variables,
+            false,
+        ));
+    }
+
+    Ok(TemplateImport {
+        sections,
+        warnings: parser.warnings,
+    })
+}
+
+fn draft_section(
+    dialect: TemplateDialect,
+    name: &str,
+    content: Value,
+    variables: &BTreeMap<String, &'static str>,
+    is_entry_point: bool,
+) -> PromptSection {
+    let timestamp = get_timestamp();
+    PromptSection {
+        id: None,
+        package_id: String::new(),
+        namespace: String::new(),
+        name: name.to_string(),
+        description: format!(
+            "Imported from {}",
+            match dialect {
+                TemplateDialect::Handlebars => "Handlebars",
+                TemplateDialect::Jinja => "Jinja",
+            }
+        ),
+        content,
+        is_entry_point,
+        exportable: true,
+        required_variables: variables.keys().cloned().collect(),
+        variables: variables
+            .iter()
+            .map(|(id, kind)| json!({ "id": id, "name": id, "type": kind, "required": true }))
+            .collect(),
+        tags: Vec::new(),
+        examples: Vec::new(),
+        locale: None,
+        negative_content: None,
+        created_at: timestamp.clone(),
+        updated_at: timestamp,
+    }
+}
+
+// ============================================
+// TOKENIZER
+// ============================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    /// `{{ expression }}`
+    Output(String),
+    /// `{% statement %}`, `{{#block}}`, `{{/block}}`, `{{else}}`, `{{> partial}}`
+    Tag(String),
+}
+
+fn tokenize(dialect: TemplateDialect, input: &str) -> Result<Vec<Token>, AppError> {
+    let delimiters: &[(&str, &str)] = match dialect {
+        TemplateDialect::Handlebars => &[("{{!--", "--}}"), ("{{{", "}}}"), ("{{", "}}")],
+        TemplateDialect::Jinja => &[("{{", "}}"), ("{%", "%}"), ("{#", "#}")],
+    };
+    let trim_marker = match dialect {
+        TemplateDialect::Handlebars => '~',
+        TemplateDialect::Jinja => '-',
+    };
+
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = input;
+    loop {
+        let found = rest.match_indices('{').find_map(|(i, _)| {
+            delimiters
+                .iter()
+                .find(|(open, _)| rest[i..].starts_with(open))
+                .map(|(open, close)| (i, *open, *close))
+        });
+        let Some((start, open, close)) = found else {
+            text.push_str(rest);
+            break;
+        };
+
+        // Handlebars escape: \{{ is literal
+        if dialect == TemplateDialect::Handlebars && rest[..start].ends_with('\\') {
+            text.push_str(&rest[..start - 1]);
+            text.push_str(open);
+            rest = &rest[start + open.len()..];
+            continue;
+        }
+
+        text.push_str(&rest[..start]);
+        let after_open = &rest[start + open.len()..];
+        let end = after_open.find(close).ok_or_else(|| {
+            let line = input[..input.len() - rest.len() + start]
+                .lines()
+                .count()
+                .max(1);
+            AppError::Validation(format!("Unclosed `{}` on line {}", open, line))
+        })?;
+        let raw = &after_open[..end];
+        rest = &after_open[end + close.len()..];
+
+        if raw.starts_with(trim_marker) {
+            text.truncate(text.trim_end().len());
+        }
+        if raw.ends_with(trim_marker) {
+            rest = rest.trim_start();
+        }
+        let inner = raw
+            .trim_start_matches([trim_marker, '+'])
+            .trim_end_matches(trim_marker)
+            .trim();
+
+        let token = match (dialect, open) {
+            (TemplateDialect::Handlebars, "{{!--") | (TemplateDialect::Jinja, "{#") => None,
+            (TemplateDialect::Handlebars, "{{") if inner.starts_with('!') => None,
+            (TemplateDialect::Handlebars, "{{")
+                if inner.starts_with(['#', '/', '>', '^'])
+                    || inner == "else"
+                    || inner.starts_with("else ") =>
+            {
+                Some(Token::Tag(inner.to_string()))
+            }
+            (TemplateDialect::Jinja, "{%") => Some(Token::Tag(inner.to_string())),
+            _ => Some(Token::Output(inner.to_string())),
+        };
+        if let Some(token) = token {
+            if !text.is_empty() {
+                tokens.push(Token::Text(std::mem::take(&mut text)));
+            }
+            tokens.push(token);
+        }
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+/// Normalized tag keyword and arguments, e.g. `{{#each items}}` -> ("for", "items")
+fn split_tag(dialect: TemplateDialect, tag: &str) -> (String, String) {
+    if let Some(name) = tag.strip_prefix('>') {
+        return ("include".to_string(), name.trim().to_string());
+    }
+    let (keyword, rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+    let rest = rest.trim().to_string();
+    let keyword = match dialect {
+        TemplateDialect::Handlebars => match keyword {
+            "#if" => "if",
+            "#unless" => "unless",
+            "#each" => "for",
+            "/if" | "/unless" => "endif",
+            "/each" => "endfor",
+            "#*inline" => "macro",
+            "/inline" => "endmacro",
+            "else" => {
+                if let Some(condition) = rest.strip_prefix("if ") {
+                    return ("elif".to_string(), condition.trim().to_string());
+                }
+                "else"
+            }
+            other => other,
+        },
+        TemplateDialect::Jinja => keyword,
+    };
+    (keyword.to_string(), rest)
+}
+
+// ============================================
+// PARSER
+// ============================================
+
+struct Parser {
+    dialect: TemplateDialect,
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Jinja loop variable names in scope (mapped to the list's `item`)
+    loop_vars: Vec<Option<String>>,
+    /// Set when a loop body contained separator logic (loop.last, @first, ...)
+    loop_control: bool,
+    /// Variables of the section being parsed and their type
+    variables: BTreeMap<String, &'static str>,
+    partials: Vec<(String, Value, BTreeMap<String, &'static str>)>,
+    warnings: Vec<String>,
+}
+
+impl Parser {
+    /// Parse until one of the `ends` tags (or the end of input when `ends` is empty)
+    fn parse_until(&mut self, ends: &[&str]) -> Result<(Vec<Value>, String), AppError> {
+        let mut nodes = Vec::new();
+        while let Some(token) = self.tokens.get(self.pos).cloned() {
+            self.pos += 1;
+            match token {
+                Token::Text(value) => nodes.push(json!({ "type": "text", "value": value })),
+                Token::Output(expression) => nodes.push(self.output(&expression)),
+                Token::Tag(tag) => {
+                    let (keyword, rest) = split_tag(self.dialect, &tag);
+                    if ends.contains(&keyword.as_str()) {
+                        return Ok((nodes, keyword));
+                    }
+                    if let Some(node) = self.tag(&tag, &keyword, &rest)? {
+                        nodes.push(node);
+                    }
+                }
+            }
+        }
+        if ends.is_empty() {
+            Ok((nodes, String::new()))
+        } else {
+            Err(AppError::Validation(format!(
+                "Template ended before `{}`",
+                ends.last().copied().unwrap_or_default()
+            )))
+        }
+    }
+
+    fn tag(&mut self, tag: &str, keyword: &str, rest: &str) -> Result<Option<Value>, AppError> {
+        match keyword {
+            "if" | "unless" => {
+                let condition = self.condition(rest);
+                let negate = keyword == "unless";
+                let node =
+                    self.if_chain(condition.map(|c| if negate { json!({ "not": c }) } else { c }))?;
+                Ok(node)
+            }
+            "for" => self.for_loop(rest).map(Some),
+            "macro" => {
+                self.macro_block(rest)?;
+                Ok(None)
+            }
+            "include" => {
+                let name = rest
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .trim_matches(['"', '\'']);
+                let name = name.rsplit('/').next().unwrap_or(name);
+                let name = match self.dialect {
+                    TemplateDialect::Jinja => name.split('.').next().unwrap_or(name),
+                    TemplateDialect::Handlebars => name,
+                };
+                Ok(Some(json!({ "type": "section-ref", "section_id": name })))
+            }
+            _ => {
+                self.warn(format!("Unsupported tag `{}` skipped", tag));
+                Ok(None)
+            }
+        }
+    }
+
+    /// if / elif / else ... endif; `None` conditions are loop separator logic and dropped
+    fn if_chain(&mut self, condition: Option<Value>) -> Result<Option<Value>, AppError> {
+        let (then_nodes, end) = self.parse_until(&["elif", "else", "endif"])?;
+        let else_content = match end.as_str() {
+            "elif" => {
+                let (_, rest) = split_tag(self.dialect, &self.previous_tag());
+                let condition = self.condition(&rest);
+                self.if_chain(condition)?
+            }
+            "else" => Some(composite(self.parse_until(&["endif"])?.0)),
+            _ => None,
+        };
+
+        let Some(condition) = condition else {
+            self.loop_control = true;
+            return Ok(None);
+        };
+        let mut node = json!({
+            "type": "conditional",
+            "condition": condition,
+            "then_content": composite(then_nodes),
+        });
+        if let Some(else_content) = else_content {
+            node["else_content"] = else_content;
+        }
+        Ok(Some(node))
+    }
+
+    fn previous_tag(&self) -> String {
+        match self.tokens.get(self.pos - 1) {
+            Some(Token::Tag(tag)) => tag.clone(),
+            _ => String::new(),
+        }
+    }
+
+    fn for_loop(&mut self, header: &str) -> Result<Value, AppError> {
+        let (loop_var, source) = match self.dialect {
+            // `items` or `items as |item index|`
+            TemplateDialect::Handlebars => match header.split_once(" as ") {
+                Some((source, alias)) => (
+                    alias
+                        .trim_matches(|c: char| c == '|' || c.is_whitespace())
+                        .split_whitespace()
+                        .next()
+                        .map(String::from),
+                    source.trim(),
+                ),
+                None => (None, header),
+            },
+            // `item in items`
+            TemplateDialect::Jinja => match header.split_once(" in ") {
+                Some((var, source)) => {
+                    if var.contains(',') {
+                        self.warn(format!("Loop `for {}` imported as a single item", header));
+                    }
+                    (
+                        Some(var.trim().to_string()),
+                        source.split(" if ").next().unwrap_or(source).trim(),
+                    )
+                }
+                None => {
+                    return Err(AppError::Validation(format!(
+                        "Invalid loop `for {}`",
+                        header
+                    )))
+                }
+            },
+        };
+        let source = self.resolve_path(source).join(".");
+        if source.contains('.') {
+            self.warn(format!(
+                "Loop over nested path `{}` expects a variable with that name",
+                source
+            ));
+        }
+        if !self.in_loop() {
+            self.variables.insert(source.clone(), "array");
+        }
+
+        let outer_control = std::mem::replace(&mut self.loop_control, false);
+        self.loop_vars.push(loop_var);
+        let (mut body, end) = self.parse_until(&["else", "endfor"])?;
+        let empty = if end == "else" {
+            Some(composite(self.parse_until(&["endfor"])?.0))
+        } else {
+            None
+        };
+        self.loop_vars.pop();
+        let had_control = std::mem::replace(&mut self.loop_control, outer_control);
+
+        // Items are joined by a separator set; derive it from the body
+        let separator = if had_control {
+            self.warn(format!(
+                "Separator logic in loop over `{}` replaced by separator set \"simple-comma\"",
+                source
+            ));
+            "simple-comma"
+        } else if strip_trailing(&mut body, '\n') {
+            "newline"
+        } else if strip_trailing(&mut body, ' ') {
+            "space"
+        } else {
+            "simple-comma"
+        };
+
+        let mut list = json!({
+            "type": "list",
+            "variable_id": source,
+            "separator_set_id": separator,
+        });
+        let is_plain_item = matches!(body.as_slice(), [node]
+            if node["type"] == "variable" && node["variable_id"] == "item"
+                && node.get("path").is_none() && node.get("format").is_none());
+        if !is_plain_item {
+            list["item_template"] = composite(body);
+        }
+
+        Ok(match empty {
+            Some(empty) => json!({
+                "type": "conditional",
+                "condition": { "variable": source, "operator": "has_items" },
+                "then_content": list,
+                "else_content": empty,
+            }),
+            None => list,
+        })
+    }
+
+    fn macro_block(&mut self, header: &str) -> Result<(), AppError> {
+        let name = match self.dialect {
+            TemplateDialect::Handlebars => header.trim_matches(['"', '\'']).to_string(),
+            TemplateDialect::Jinja => {
+                let (name, args) = header.split_once('(').unwrap_or((header, ")"));
+                if !args.trim_end_matches(')').trim().is_empty() {
+                    self.warn(format!(
+                        "Macro `{}` arguments are read from the section's variables",
+                        name.trim()
+                    ));
+                }
+                name.trim().to_string()
+            }
+        };
+
+        let outer_variables = std::mem::take(&mut self.variables);
+        let outer_loops = std::mem::take(&mut self.loop_vars);
+        let (body, _) = self.parse_until(&["endmacro"])?;
+        let variables = std::mem::replace(&mut self.variables, outer_variables);
+        self.loop_vars = outer_loops;
+        self.partials.push((name, composite(body), variables));
+        Ok(())
+    }
+
+    fn in_loop(&self) -> bool {
+        !self.loop_vars.is_empty()
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+
+    // ----- expressions -----
+
+    /// Path segments with loop variables mapped to `item` / `index`
+    fn resolve_path(&self, path: &str) -> Vec<String> {
+        let path = path
+            .trim()
+            .trim_start_matches("../")
+            .trim_start_matches("@root.");
+        let mut segments: Vec<String> = path
+            .split('.')
+            .filter(|s| !s.is_empty() && !s.starts_with('['))
+            .map(String::from)
+            .collect();
+        let Some(root) = segments.first().cloned() else {
+            return segments;
+        };
+        let is_loop_var = self.loop_vars.iter().flatten().any(|var| *var == root);
+        match (self.dialect, root.as_str()) {
+            // Partials are usually rendered per item, so `this` is the item everywhere
+            (TemplateDialect::Handlebars, "this") => segments[0] = "item".to_string(),
+            (TemplateDialect::Handlebars, "@index") | (TemplateDialect::Jinja, "loop")
+                if self.in_loop() =>
+            {
+                segments = vec!["index".to_string()];
+            }
+            _ if is_loop_var => segments[0] = "item".to_string(),
+            _ => {}
+        }
+        segments
+    }
+
+    /// Variable (or `system.*` context) reference node
+    fn reference(&mut self, path: &str) -> Value {
+        let segments = self.resolve_path(path);
+        let Some((root, rest)) = segments.split_first() else {
+            return json!({ "type": "text", "value": "" });
+        };
+        if root == "system" {
+            return json!({ "type": "context", "context_key": segments.join(".") });
+        }
+        self.track(root);
+        let mut node = json!({ "type": "variable", "variable_id": root });
+        if !rest.is_empty() {
+            node["path"] = json!(rest.join("."));
+        }
+        node
+    }
+
+    /// Record a section variable (loop-scoped names excluded)
+    fn track(&mut self, root: &str) {
+        let loop_scoped = match self.dialect {
+            // Inside {{#each}} bare names may be item fields
+            TemplateDialect::Handlebars => self.in_loop() || root == "item",
+            TemplateDialect::Jinja => self.in_loop() && (root == "item" || root == "index"),
+        };
+        if !loop_scoped {
+            self.variables.entry(root.to_string()).or_insert("string");
+        }
+    }
+
+    fn output(&mut self, expression: &str) -> Value {
+        match self.dialect {
+            TemplateDialect::Jinja => self.jinja_output(expression),
+            TemplateDialect::Handlebars => self.handlebars_output(expression),
+        }
+    }
+
+    fn jinja_output(&mut self, expression: &str) -> Value {
+        let mut parts = split_top_level(expression, "|").into_iter();
+        let base = parts.next().unwrap_or_default();
+        let filters: Vec<String> = parts.collect();
+
+        if let Some(literal) = parse_literal(&base) {
+            if let (Value::Array(options), true) = (&literal, filters.iter().any(|f| f == "random"))
+            {
+                let candidates: Vec<Value> = options
+                    .iter()
+                    .map(|o| json!({ "type": "text", "value": display(o) }))
+                    .collect();
+                return json!({ "type": "pick-one", "candidates": candidates });
+            }
+            return json!({ "type": "text", "value": display(&literal) });
+        }
+        if let Some(name) = base.strip_suffix("()") {
+            return json!({ "type": "section-ref", "section_id": name.trim() });
+        }
+
+        let mut node = self.reference(&base);
+        for filter in &filters {
+            let (name, args) = filter.split_once('(').unwrap_or((filter, ""));
+            let arg = parse_literal(args.trim_end_matches(')'));
+            match name.trim() {
+                "upper" | "lower" | "title" => {
+                    node = with_format(node, "case", json!(name.trim()));
+                }
+                "capitalize" => node = with_format(node, "case", json!("sentence")),
+                "trim" => node = with_format(node, "trim", json!(true)),
+                "default" | "d" => match arg {
+                    Some(Value::String(fallback)) => {
+                        node = with_format(node, "placeholder", json!(fallback))
+                    }
+                    _ => self.warn(format!("Filter `{}` skipped", filter)),
+                },
+                "article" => node = article(node),
+                "random" => node = random_value(node),
+                "join" => node = join(node, arg.as_ref().and_then(|a| a.as_str())),
+                _ => self.warn(format!("Filter `{}` skipped", filter)),
+            }
+        }
+        node
+    }
+
+    fn handlebars_output(&mut self, expression: &str) -> Value {
+        let args = split_top_level(expression, " ");
+        match args.as_slice() {
+            [single] => match parse_literal(single) {
+                Some(literal) => json!({ "type": "text", "value": display(&literal) }),
+                None => self.reference(single),
+            },
+            [helper, value, rest @ ..] => {
+                let node = self.reference(value);
+                match helper.as_str() {
+                    "upper" | "lower" | "title" | "sentence" => {
+                        with_format(node, "case", json!(helper))
+                    }
+                    "article" => article(node),
+                    "join" => join(
+                        node,
+                        rest.first()
+                            .and_then(|s| parse_literal(s))
+                            .as_ref()
+                            .and_then(|s| s.as_str()),
+                    ),
+                    _ => {
+                        self.warn(format!("Helper `{}` skipped", helper));
+                        node
+                    }
+                }
+            }
+            [] => json!({ "type": "text", "value": "" }),
+        }
+    }
+
+    /// Condition JSON; `None` for loop separator logic (loop.last, @first, ...)
+    fn condition(&mut self, expression: &str) -> Option<Value> {
+        let expression = strip_parens(expression.trim());
+        match self.dialect {
+            TemplateDialect::Jinja => self.jinja_condition(expression),
+            TemplateDialect::Handlebars => self.handlebars_condition(expression),
+        }
+    }
+
+    fn jinja_condition(&mut self, expression: &str) -> Option<Value> {
+        for (joiner, key) in [(" or ", "or"), (" and ", "and")] {
+            let parts = split_top_level(expression, joiner);
+            if parts.len() > 1 {
+                let conditions: Option<Vec<Value>> =
+                    parts.iter().map(|p| self.condition(p)).collect();
+                return Some(json!({ (key): conditions? }));
+            }
+        }
+        if let Some(inner) = expression.strip_prefix("not ") {
+            return Some(json!({ "not": self.condition(inner)? }));
+        }
+
+        for (op, operator) in [
+            (" is not none", "exists"),
+            (" is not defined", "not_exists"),
+            (" is defined", "exists"),
+            (" is none", "not_exists"),
+        ] {
+            if let Some(subject) = expression.strip_suffix(op) {
+                return self.compare(subject, operator, Value::Null);
+            }
+        }
+        for (op, operator) in [
+            ("==", "equals"),
+            ("!=", "not_equals"),
+            (">=", ">="),
+            ("<=", "<="),
+            (">", "greater_than"),
+            ("<", "less_than"),
+            (" not in ", "not in"),
+            (" in ", "in"),
+        ] {
+            let parts = split_top_level(expression, op);
+            if let [left, right] = parts.as_slice() {
+                return self.comparison(left, operator, right);
+            }
+        }
+        self.compare(expression, "exists", Value::Null)
+    }
+
+    fn handlebars_condition(&mut self, expression: &str) -> Option<Value> {
+        let args = split_top_level(expression, " ");
+        match args.as_slice() {
+            [single] if single.starts_with('(') => self.condition(single),
+            [single] => self.compare(single, "exists", Value::Null),
+            [helper, rest @ ..] => match (helper.as_str(), rest) {
+                ("not", [inner]) => Some(json!({ "not": self.condition(inner)? })),
+                ("and" | "or", _) => {
+                    let conditions: Option<Vec<Value>> =
+                        rest.iter().map(|p| self.condition(p)).collect();
+                    Some(json!({ (helper.as_str()): conditions? }))
+                }
+                (op, [left, right]) => {
+                    let operator = match op {
+                        "eq" => "equals",
+                        "ne" => "not_equals",
+                        "gt" => "greater_than",
+                        "lt" => "less_than",
+                        "gte" => ">=",
+                        "lte" => "<=",
+                        "includes" | "contains" => "contains",
+                        other => other,
+                    };
+                    self.comparison(left, operator, right)
+                }
+                _ => {
+                    self.warn(format!("Condition `{}` imported as `exists`", expression));
+                    self.compare(rest.last()?, "exists", Value::Null)
+                }
+            },
+            [] => None,
+        }
+    }
+
+    /// `left <op> right` where right is usually a literal
+    fn comparison(&mut self, left: &str, operator: &str, right: &str) -> Option<Value> {
+        let value = parse_literal(right);
+        match (operator, value) {
+            (">=" | "<=", Some(value)) => {
+                let strict = if operator == ">=" {
+                    "greater_than"
+                } else {
+                    "less_than"
+                };
+                Some(json!({ "or": [
+                    self.compare(left, strict, value.clone())?,
+                    self.compare(left, "equals", value)?,
+                ]}))
+            }
+            // `"x" in items` checks the collection
+            ("in" | "not in", Some(Value::Array(options))) => {
+                let options: Option<Vec<Value>> = options
+                    .into_iter()
+                    .map(|o| self.compare(left, "equals", o))
+                    .collect();
+                let any = json!({ "or": options? });
+                Some(if operator == "in" {
+                    any
+                } else {
+                    json!({ "not": any })
+                })
+            }
+            ("in" | "not in", _) => {
+                let needle = parse_literal(left).unwrap_or_else(|| json!(left));
+                let contains = self.compare(right, "contains", needle)?;
+                Some(if operator == "in" {
+                    contains
+                } else {
+                    json!({ "not": contains })
+                })
+            }
+            (_, Some(value)) => self.compare(left, operator, value),
+            (_, None) => {
+                self.warn(format!(
+                    "Comparison of `{}` with `{}` needs a literal value",
+                    left, right
+                ));
+                self.compare(left, operator, Value::Null)
+            }
+        }
+    }
+
+    fn compare(&mut self, subject: &str, operator: &str, value: Value) -> Option<Value> {
+        let subject = subject.trim();
+        if (subject.starts_with('@') && subject != "@index")
+            || (subject.starts_with("loop.") && subject != "loop.index")
+        {
+            return None;
+        }
+        let segments = self.resolve_path(subject);
+        let (root, rest) = segments.split_first()?;
+        let mut condition = if root == "system" {
+            json!({ "context_key": segments.join(".") })
+        } else {
+            self.track(root);
+            let mut condition = json!({ "variable": root });
+            if !rest.is_empty() {
+                condition["path"] = json!(rest.join("."));
+            }
+            condition
+        };
+        condition["operator"] = json!(operator);
+        if !value.is_null() {
+            condition["value"] = value;
+        }
+        Some(condition)
+    }
+}
+
+// ============================================
+// HELPERS
+// ============================================
+
+/// Merge adjacent text nodes and collapse single-node composites
+fn composite(nodes: Vec<Value>) -> Value {
+    let mut parts: Vec<Value> = Vec::new();
+    for node in nodes {
+        if let (Some(last), Some(text)) = (parts.last_mut(), node_text(&node)) {
+            if let Some(previous) = node_text(last) {
+                *last = json!({ "type": "text", "value": format!("{}{}", previous, text) });
+                continue;
+            }
+        }
+        parts.push(node);
+    }
+    match parts.len() {
+        0 => json!({ "type": "text", "value": "" }),
+        1 => parts.remove(0),
+        _ => json!({ "type": "composite", "parts": parts }),
+    }
+}
+
+fn node_text(node: &Value) -> Option<String> {
+    (node["type"] == "text").then(|| node["value"].as_str().unwrap_or_default().to_string())
+}
+
+/// Remove a trailing character from the last text node, if present
+fn strip_trailing(nodes: &mut Vec<Value>, c: char) -> bool {
+    let Some(text) = nodes.last().and_then(node_text) else {
+        return false;
+    };
+    let Some(stripped) = text.strip_suffix(c) else {
+        return false;
+    };
+    if stripped.is_empty() {
+        nodes.pop();
+    } else if let Some(last) = nodes.last_mut() {
+        last["value"] = json!(stripped);
+    }
+    true
+}
+
+fn with_format(mut node: Value, key: &str, value: Value) -> Value {
+    if node["type"] == "variable" {
+        if node.get("format").is_none() {
+            node["format"] = json!({});
+        }
+        node["format"][key] = value;
+    }
+    node
+}
+
+fn article(node: Value) -> Value {
+    match node["variable_id"].as_str() {
+        Some(variable) if node.get("path").is_none() => {
+            json!({ "type": "article", "word_variable": variable })
+        }
+        _ => json!({ "type": "article", "word_content": node }),
+    }
+}
+
+fn random_value(node: Value) -> Value {
+    match node["variable_id"].as_str() {
+        Some(variable) => json!({ "type": "random-value", "pool_variable": variable }),
+        None => node,
+    }
+}
+
+fn join(node: Value, separator: Option<&str>) -> Value {
+    let Some(variable) = node["variable_id"].as_str() else {
+        return node;
+    };
+    let separator_set = match separator {
+        Some("\n") => "newline",
+        Some(" ") | Some("") => "space",
+        Some(" and ") => "and-list-no-oxford",
+        Some(" or ") => "or-list",
+        _ => "simple-comma",
+    };
+    json!({ "type": "list", "variable_id": variable, "separator_set_id": separator_set })
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// String, number, boolean, none or list literal
+fn parse_literal(s: &str) -> Option<Value> {
+    let s = s.trim();
+    if s.len() >= 2
+        && ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')))
+    {
+        let inner = &s[1..s.len() - 1];
+        return Some(Value::String(
+            inner
+                .replace("\\\"", "\"")
+                .replace("\\'", "'")
+                .replace("\\n", "\n"),
+        ));
+    }
+    match s {
+        "true" | "True" => return Some(json!(true)),
+        "false" | "False" => return Some(json!(false)),
+        "none" | "None" | "null" => return Some(Value::Null),
+        _ => {}
+    }
+    if let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return split_top_level(inner, ",")
+            .iter()
+            .filter(|item| !item.trim().is_empty())
+            .map(|item| parse_literal(item))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array);
+    }
+    s.parse::<f64>()
+        .ok()
+        .filter(|_| !s.is_empty())
+        .and_then(|_| serde_json::from_str(s).ok())
+}
+
+/// Drop one pair of enclosing parentheses
+fn strip_parens(s: &str) -> &str {
+    match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(inner) if split_top_level(s, " ").len() == 1 => inner.trim(),
+        _ => s,
+    }
+}
+
+/// Split on `separator` outside quotes, parentheses and brackets
+fn split_top_level(s: &str, separator: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    let mut i = 0;
+    let bytes = s.as_bytes();
+    while i < s.len() {
+        let c = s[i..].chars().next().unwrap_or_default();
+        match quote {
+            Some(q) if c == q && bytes.get(i.wrapping_sub(1)) != Some(&b'\\') => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                _ if depth == 0 && s[i..].starts_with(separator) => {
+                    parts.push(s[start..i].trim().to_string());
+                    i += separator.len();
+                    start = i;
+                    continue;
+                }
+                _ => {}
+            },
+        }
+        i += c.len_utf8();
+    }
+    parts.push(s[start..].trim().to_string());
+    if separator.trim().is_empty() {
+        parts.retain(|p| !p.is_empty());
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::renderer::{render_section, PromptLibrary};
+
+    fn render(sections: Vec<PromptSection>, variables: Value) -> String {
+        let library = PromptLibrary {
+            sections,
+            ..Default::default()
+        };
+        let Value::Object(variables) = variables else {
+            unreachable!()
+        };
+        render_section(&library, "main", variables, Some(0))
+            .unwrap()
+            .text
+    }
+
+    #[test]
+    fn test_import_templates() {
+        let jinja = r#"{# greeting #}
+{%- macro signoff() %}Bye, {{ user.name }}{% endmacro -%}
+Hello {{ user.name | upper }}!
+{% if mood == "happy" and system.time_of_day %}Nice {{ system.time_of_day }}!{% elif mood != "sad" %}Hi.{% else %}Cheer up.{% endif %}
+Pets: {% for pet in pets %}{{ pet.kind }}{% if not loop.last %}, {% endif %}{% endfor %}
+{{ signoff() }}{{ '{{' }}{% set x = 1 %}"#;
+        let import = import_templates(TemplateDialect::Jinja, jinja).unwrap();
+        let names: Vec<&str> = import.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["main", "signoff"]);
+        assert_eq!(
+            import.sections[0].required_variables,
+            vec!["mood", "pets", "user"]
+        );
+        assert_eq!(import.sections[1].required_variables, vec!["user"]);
+        assert!(import.warnings.iter().any(|w| w.contains("set x = 1")));
+
+        let text = render(
+            import.sections,
+            json!({
+                "user": { "name": "Ada" },
+                "mood": "ok",
+                "pets": [{ "kind": "cat" }, { "kind": "dog" }, { "kind": "owl" }]
+            }),
+        );
+        assert_eq!(text, "Hello ADA!\nHi.\nPets: cat, dog, owl\nBye, Ada{{");
+
+        let handlebars = "{{#*inline \"item\"}}- {{upper this}}{{/inline}}\
+            {{#if (eq count 1)}}One{{else if (gt count 1)}}Many{{else}}None{{/if}}: \
+            {{#each tags}}{{> item}}\n{{/each}}\\{{done}} {{#unless extra}}plain{{/unless}}";
+        let import = import_templates(TemplateDialect::Handlebars, handlebars).unwrap();
+        assert_eq!(import.sections[0].variables[2]["type"], "array");
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+
+        let text = render(import.sections, json!({ "count": 2, "tags": ["a", "b"] }));
+        assert_eq!(text, "Many: - A\n- B{{done}} plain");
+
+        assert!(import_templates(TemplateDialect::Jinja, "{% if x %}open").is_err());
+        assert!(import_templates(TemplateDialect::Handlebars, "{{name").is_err());
+    }
+}
@@ -0,0 +1,353 @@
+// PromptTemplate migration
+//
+// Converts the remaining rows of the deprecated `prompt_templates` table into
+// entry-point sections. A section keeps its template's record key when that
+// key is free, so references by key or name keep resolving; when it isn't,
+// the section gets a new key and `section-ref`s to the old one are rewritten.
+// Migrated templates are marked with `migrated_to` instead of being deleted,
+// which makes the migration safe to run again. Set
+// `prompt_gen.migrate_templates_on_startup` to "true" to run it at startup.
+
+use super::renderer::PromptLibrary;
+use super::{extract_id, get_timestamp, PromptSection, PromptTemplate};
+use crate::db::Database;
+use crate::error::AppError;
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+/// Setting that runs the migration when the app starts
+pub const MIGRATE_ON_STARTUP_SETTING: &str = "prompt_gen.migrate_templates_on_startup";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigratedTemplate {
+    pub template_id: String,
+    pub section_id: String,
+    pub namespace: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedTemplate {
+    pub template_id: String,
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateMigration {
+    pub migrated: Vec<MigratedTemplate>,
+    pub skipped: Vec<SkippedTemplate>,
+    /// Sections whose references were rewritten to a migrated template's new key
+    pub sections_updated: usize,
+}
+
+/// The entry-point section a template becomes
+fn to_section(template: &PromptTemplate) -> PromptSection {
+    let required_variables = template
+        .variables
+        .iter()
+        .filter(|v| v.get("required").and_then(|r| r.as_bool()) == Some(true))
+        .filter_map(|v| v.get("id").and_then(|id| id.as_str()).map(str::to_string))
+        .collect();
+    PromptSection {
+        id: None,
+        package_id: template.package_id.clone(),
+        namespace: template.namespace.clone(),
+        name: template.name.clone(),
+        description: template.description.clone(),
+        content: template.content.clone(),
+        is_entry_point: true,
+        exportable: true,
+        required_variables,
+        variables: template.variables.clone(),
+        tags: template.tags.clone(),
+        examples: template.examples.clone(),
+        locale: None,
+        negative_content: None,
+        created_at: template.created_at.clone(),
+        updated_at: get_timestamp(),
+    }
+}
+
+/// Point `section_id`s at renamed keys: bare keys within `namespace`,
+/// "namespace:key" anywhere. Returns whether anything changed.
+fn rewrite_references(
+    value: &mut Value,
+    section_namespace: &str,
+    renamed: &HashMap<(String, String), String>,
+) -> bool {
+    match value {
+        Value::Object(fields) => {
+            let mut changed = false;
+            for (key, field) in fields.iter_mut() {
+                match field {
+                    Value::String(reference) if key == "section_id" => {
+                        let rewritten = match reference.split_once(':') {
+                            Some((ns, old)) => renamed
+                                .get(&(ns.to_string(), old.to_string()))
+                                .map(|new| format!("{}:{}", ns, new)),
+                            None => renamed
+                                .get(&(section_namespace.to_string(), reference.clone()))
+                                .cloned(),
+                        };
+                        if let Some(rewritten) = rewritten {
+                            *reference = rewritten;
+                            changed = true;
+                        }
+                    }
+                    _ => changed |= rewrite_references(field, section_namespace, renamed),
+                }
+            }
+            changed
+        }
+        Value::Array(items) => items.iter_mut().fold(false, |changed, item| {
+            rewrite_references(item, section_namespace, renamed) | changed
+        }),
+        _ => false,
+    }
+}
+
+/// Convert every template not migrated yet
+pub async fn migrate_templates(db: &Database) -> Result<TemplateMigration, AppError> {
+    let mut result = db
+        .query("SELECT * FROM prompt_templates WHERE migrated_to = NONE")
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query templates: {}", e)))?;
+    let templates: Vec<PromptTemplate> = result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse templates: {}", e)))?;
+    let mut library = PromptLibrary::load(db).await?;
+
+    let mut migration = TemplateMigration::default();
+    let mut renamed: HashMap<(String, String), String> = HashMap::new();
+    for template in templates {
+        let template_key = extract_id(&template.id).unwrap_or_default();
+        if library
+            .sections
+            .iter()
+            .any(|s| s.namespace == template.namespace && s.name == template.name)
+        {
+            migration.skipped.push(SkippedTemplate {
+                template_id: template_key,
+                name: template.name.clone(),
+                reason: format!(
+                    "A section named '{}' already exists in namespace '{}'",
+                    template.name, template.namespace
+                ),
+            });
+            continue;
+        }
+
+        let section_key = if library.find_section(&template_key).is_none() {
+            template_key.clone()
+        } else {
+            uuid::Uuid::new_v4().to_string()
+        };
+        let mut section = to_section(&template);
+        db.transaction(
+            "CREATE type::thing('prompt_sections', $section_key) CONTENT $section;
+             UPDATE type::thing('prompt_templates', $template_key)
+                 SET migrated_to = $section_key, migrated_at = $now;",
+            json!({
+                "section_key": section_key,
+                "section": section,
+                "template_key": template_key,
+                "now": get_timestamp(),
+            }),
+        )
+        .await?;
+
+        if section_key != template_key {
+            renamed.insert(
+                (template.namespace.clone(), template_key.clone()),
+                section_key.clone(),
+            );
+        }
+        section.id = Some(surrealdb::sql::Thing::from((
+            "prompt_sections",
+            section_key.as_str(),
+        )));
+        library.sections.push(section);
+        migration.migrated.push(MigratedTemplate {
+            template_id: template_key,
+            section_id: section_key,
+            namespace: template.namespace,
+            name: template.name,
+        });
+    }
+
+    if !renamed.is_empty() {
+        for section in &library.sections {
+            let (mut content, mut negative) =
+                (section.content.clone(), section.negative_content.clone());
+            let mut changed = rewrite_references(&mut content, &section.namespace, &renamed);
+            if let Some(negative) = &mut negative {
+                changed |= rewrite_references(negative, &section.namespace, &renamed);
+            }
+            let Some(key) = extract_id(&section.id).filter(|_| changed) else {
+                continue;
+            };
+            let mut changes = json!({ "content": content, "updated_at": get_timestamp() });
+            if let Some(negative) = negative {
+                changes["negative_content"] = negative;
+            }
+            let _: Option<PromptSection> = db
+                .db
+                .update(("prompt_sections", key.as_str()))
+                .merge(changes)
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to update section: {}", e)))?;
+            migration.sections_updated += 1;
+        }
+    }
+    Ok(migration)
+}
+
+/// The prompt template migration setting
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![SettingDefinition::new(
+        MIGRATE_ON_STARTUP_SETTING,
+        SettingType::Boolean,
+        false.into(),
+        "prompt_gen",
+    )
+    .describe(
+        "Migrate templates on startup",
+        "Convert deprecated prompt templates when the app starts",
+    )]
+}
+
+/// Run the migration in the background when the startup setting is enabled
+pub fn start(settings_service: Arc<RwLock<SettingsService>>, database: Arc<Mutex<Database>>) {
+    tokio::spawn(async move {
+        let enabled = settings_service
+            .read()
+            .await
+            .get_setting(MIGRATE_ON_STARTUP_SETTING)
+            .await;
+        match enabled {
+            Ok(Some(value)) if value == "true" => {}
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!("Failed to read {}: {}", MIGRATE_ON_STARTUP_SETTING, e);
+                return;
+            }
+        }
+
+        let db = database.lock().await;
+        match migrate_templates(&db).await {
+            Ok(migration) => tracing::info!(
+                "Migrated {} prompt templates ({} skipped)",
+                migration.migrated.len(),
+                migration.skipped.len()
+            ),
+            Err(e) => tracing::warn!("Prompt template migration failed: {}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_migrate_templates() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let template = |name: &str| -> PromptTemplate {
+            serde_json::from_value(json!({
+                "package_id": "pkg", "namespace": "demo", "name": name, "description": "",
+                "content": { "type": "text", "value": name },
+                "variables": [{ "id": "subject", "required": true }],
+                "tags": ["old"], "examples": [], "created_at": "then", "updated_at": ""
+            }))
+            .unwrap()
+        };
+        let section = |name: &str, content: Value| -> PromptSection {
+            serde_json::from_value(json!({
+                "package_id": "pkg", "namespace": "demo", "name": name, "description": "",
+                "content": content, "is_entry_point": false, "exportable": true,
+                "required_variables": [], "created_at": "", "updated_at": ""
+            }))
+            .unwrap()
+        };
+        for (key, name) in [("poster", "poster"), ("taken", "banner"), ("dup", "intro")] {
+            let _: Option<PromptTemplate> = db
+                .db
+                .create(("prompt_templates", key))
+                .content(template(name))
+                .await
+                .unwrap();
+        }
+        // "taken" is already a section key; "intro" already a section name
+        let wrapper = json!({ "type": "composite", "parts": [
+            { "type": "section-ref", "section_id": "taken" },
+            { "type": "section-ref", "section_id": "demo:taken" },
+            { "type": "section-ref", "section_id": "poster" }
+        ]});
+        for (key, name, content) in [
+            ("taken", "other", json!({ "type": "text", "value": "" })),
+            ("intro", "intro", json!({ "type": "text", "value": "" })),
+            ("wrapper", "wrapper", wrapper),
+        ] {
+            let _: Option<PromptSection> = db
+                .db
+                .create(("prompt_sections", key))
+                .content(section(name, content))
+                .await
+                .unwrap();
+        }
+
+        let migration = migrate_templates(&db).await.unwrap();
+        let mut migrated: Vec<(&str, bool)> = migration
+            .migrated
+            .iter()
+            .map(|m| (m.name.as_str(), m.section_id == m.template_id))
+            .collect();
+        migrated.sort();
+        assert_eq!(migrated, vec![("banner", false), ("poster", true)]);
+        assert_eq!(migration.skipped.len(), 1);
+        assert_eq!(migration.skipped[0].template_id, "dup");
+        assert_eq!(migration.sections_updated, 1);
+
+        let poster: PromptSection = db
+            .db
+            .select(("prompt_sections", "poster"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(poster.is_entry_point);
+        assert_eq!(poster.required_variables, vec!["subject"]);
+        assert_eq!(poster.created_at, "then");
+
+        let banner_key = &migration
+            .migrated
+            .iter()
+            .find(|m| m.name == "banner")
+            .unwrap()
+            .section_id;
+        let wrapper: PromptSection = db
+            .db
+            .select(("prompt_sections", "wrapper"))
+            .await
+            .unwrap()
+            .unwrap();
+        let parts = &wrapper.content["parts"];
+        assert_eq!(parts[0]["section_id"], json!(banner_key));
+        assert_eq!(
+            parts[1]["section_id"],
+            json!(format!("demo:{}", banner_key))
+        );
+        assert_eq!(parts[2]["section_id"], "poster");
+
+        // Marked rows are left alone on the next run
+        let again = migrate_templates(&db).await.unwrap();
+        assert!(again.migrated.is_empty());
+        assert_eq!(again.skipped.len(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+// Entry point favorites and recent use
+//
+// Per-section usage lives in `prompt_section_usage` (keyed like the section)
+// rather than on the section itself, so starring or rendering a section
+// neither creates a revision nor ends up in package exports.
+
+use super::{get_timestamp, PromptSection};
+use crate::db::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+
+/// Default number of recently used sections returned
+pub const DEFAULT_RECENT_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct UsageRecord {
+    section_id: String,
+    favorite: bool,
+    last_used_at: Option<String>,
+    use_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionUsage {
+    pub section: PromptSection,
+    pub favorite: bool,
+    pub last_used_at: Option<String>,
+    pub use_count: u64,
+}
+
+fn section_key(id: &str) -> &str {
+    id.strip_prefix("prompt_sections:").unwrap_or(id)
+}
+
+/// Bump a section's last-used timestamp and use count
+pub async fn record_use(db: &Database, section_id: &str) -> Result<(), AppError> {
+    db.query(
+        "UPSERT type::thing('prompt_section_usage', $section_id) SET
+                 section_id = $section_id,
+                 favorite = favorite ?? false,
+                 last_used_at = $now,
+                 use_count = (use_count ?? 0) + 1",
+    )
+    .bind(("section_id", section_key(section_id).to_string()))
+    .bind(("now", get_timestamp()))
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to record section use: {}", e)))?
+    .check()
+    .map_err(|e| AppError::Database(format!("Failed to record section use: {}", e)))?;
+    Ok(())
+}
+
+pub async fn set_favorite(db: &Database, section_id: &str, favorite: bool) -> Result<(), AppError> {
+    db.query(
+        "UPSERT type::thing('prompt_section_usage', $section_id) SET
+                 section_id = $section_id,
+                 favorite = $favorite,
+                 use_count = use_count ?? 0",
+    )
+    .bind(("section_id", section_key(section_id).to_string()))
+    .bind(("favorite", favorite))
+    .await
+    .map_err(|e| AppError::Database(format!("Failed to update favorite: {}", e)))?
+    .check()
+    .map_err(|e| AppError::Database(format!("Failed to update favorite: {}", e)))?;
+    Ok(())
+}
+
+pub async fn delete_usage(db: &Database, section_id: &str) -> Result<(), AppError> {
+    let _: Option<UsageRecord> = db
+        .db
+        .delete(("prompt_section_usage", section_key(section_id)))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to delete section usage: {}", e)))?;
+    Ok(())
+}
+
+/// Attach the sections to usage records, skipping sections deleted since
+async fn with_sections(
+    db: &Database,
+    records: Vec<UsageRecord>,
+) -> Result<Vec<SectionUsage>, AppError> {
+    let mut result = Vec::new();
+    for record in records {
+        let section: Option<PromptSection> = db
+            .db
+            .select(("prompt_sections", record.section_id.as_str()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get section: {}", e)))?;
+        if let Some(section) = section {
+            result.push(SectionUsage {
+                section,
+                favorite: record.favorite,
+                last_used_at: record.last_used_at,
+                use_count: record.use_count,
+            });
+        }
+    }
+    Ok(result)
+}
+
+async fn query_usage(
+    db: &Database,
+    sql: &str,
+    limit: Option<usize>,
+) -> Result<Vec<UsageRecord>, AppError> {
+    let mut result = db
+        .query(sql)
+        .bind(("limit", limit))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to query section usage: {}", e)))?;
+    result
+        .take(0)
+        .map_err(|e| AppError::Database(format!("Failed to parse section usage: {}", e)))
+}
+
+/// Favorite sections, by name
+pub async fn favorite_sections(db: &Database) -> Result<Vec<SectionUsage>, AppError> {
+    let records = query_usage(
+        db,
+        "SELECT * FROM prompt_section_usage WHERE favorite = true",
+        None,
+    )
+    .await?;
+    let mut favorites = with_sections(db, records).await?;
+    favorites.sort_by(|a, b| a.section.name.cmp(&b.section.name));
+    Ok(favorites)
+}
+
+/// Most recently used sections, newest first
+pub async fn recent_sections(db: &Database, limit: usize) -> Result<Vec<SectionUsage>, AppError> {
+    let records = query_usage(
+        db,
+        "SELECT * FROM prompt_section_usage WHERE last_used_at != NONE
+         ORDER BY last_used_at DESC LIMIT $limit",
+        Some(limit),
+    )
+    .await?;
+    with_sections(db, records).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_section_usage() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        for key in ["alpha", "beta", "gamma"] {
+            let section: PromptSection = serde_json::from_value(json!({
+                "package_id": "pkg", "namespace": "demo", "name": key, "description": "",
+                "content": { "type": "text", "value": key }, "is_entry_point": true,
+                "exportable": true, "required_variables": [], "created_at": "", "updated_at": ""
+            }))
+            .unwrap();
+            let _: Option<PromptSection> = db
+                .db
+                .create(("prompt_sections", key))
+                .content(section)
+                .await
+                .unwrap();
+        }
+
+        record_use(&db, "alpha").await.unwrap();
+        record_use(&db, "beta").await.unwrap();
+        record_use(&db, "prompt_sections:alpha").await.unwrap();
+        set_favorite(&db, "gamma", true).await.unwrap();
+        set_favorite(&db, "beta", true).await.unwrap();
+
+        let recent = recent_sections(&db, 10).await.unwrap();
+        let found: Vec<(&str, u64, bool)> = recent
+            .iter()
+            .map(|u| (u.section.name.as_str(), u.use_count, u.favorite))
+            .collect();
+        assert_eq!(found, vec![("alpha", 2, false), ("beta", 1, true)]);
+        assert_eq!(recent_sections(&db, 1).await.unwrap().len(), 1);
+
+        let names = |usage: Vec<SectionUsage>| -> Vec<String> {
+            usage.into_iter().map(|u| u.section.name).collect()
+        };
+        assert_eq!(
+            names(favorite_sections(&db).await.unwrap()),
+            vec!["beta", "gamma"]
+        );
+
+        // Unstarring keeps the use count; deleted sections drop out
+        set_favorite(&db, "beta", false).await.unwrap();
+        let _: Option<PromptSection> = db.db.delete(("prompt_sections", "gamma")).await.unwrap();
+        assert!(favorite_sections(&db).await.unwrap().is_empty());
+        assert_eq!(recent_sections(&db, 10).await.unwrap()[1].use_count, 1);
+        delete_usage(&db, "alpha").await.unwrap();
+        assert_eq!(names(recent_sections(&db, 10).await.unwrap()), vec!["beta"]);
+    }
+}
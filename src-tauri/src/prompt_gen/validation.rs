@@ -0,0 +1,453 @@
+// Prompt validation
+//
+// Static checks over section content, so broken references show up as
+// diagnostics in the editor instead of as silently empty output at render
+// time: unresolved section references, cyclic references, unknown separator
+// sets and variables the section doesn't declare.
+
+use super::renderer::{Condition, PromptContent, PromptLibrary};
+use super::{extract_id, PromptSection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem found in a section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Machine-readable code, e.g. "SECTION_NOT_FOUND"
+    pub code: String,
+    pub message: String,
+    pub section_id: String,
+    /// Location in the content tree, e.g. "root.parts[2].then"
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// No errors (warnings are allowed)
+    pub valid: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Validate sections against the library their references resolve in
+pub fn validate_sections<'a>(
+    library: &PromptLibrary,
+    sections: impl IntoIterator<Item = &'a PromptSection>,
+) -> ValidationReport {
+    let diagnostics: Vec<Diagnostic> = sections
+        .into_iter()
+        .flat_map(|section| validate_section(library, section))
+        .collect();
+
+    ValidationReport {
+        valid: diagnostics.iter().all(|d| d.severity != Severity::Error),
+        diagnostics,
+    }
+}
+
+fn section_key(section: &PromptSection) -> String {
+    extract_id(&section.id).unwrap_or_else(|| section.name.clone())
+}
+
+fn parse_content(section: &PromptSection) -> Result<PromptContent, serde_json::Error> {
+    serde_json::from_value(section.content.clone())
+}
+
+fn validate_section(library: &PromptLibrary, section: &PromptSection) -> Vec<Diagnostic> {
+    let section_id = section_key(section);
+    let mut diagnostics = Vec::new();
+    let mut report = |severity, code: &str, path: &str, message: String| {
+        diagnostics.push(Diagnostic {
+            severity,
+            code: code.to_string(),
+            message,
+            section_id: section_id.clone(),
+            path: path.to_string(),
+        })
+    };
+
+    let declared: HashSet<&str> = section
+        .required_variables
+        .iter()
+        .map(String::as_str)
+        .chain(
+            section
+                .variables
+                .iter()
+                .filter_map(|v| v.get("id").and_then(|id| id.as_str())),
+        )
+        .collect();
+
+    // The negative prompt is checked like the main content, under its own root
+    for (field, value) in section.content_fields() {
+        let root = if field == "content" { "root" } else { field };
+        let content: PromptContent = match serde_json::from_value(value.clone()) {
+            Ok(content) => content,
+            Err(e) => {
+                report(
+                    Severity::Error,
+                    "INVALID_CONTENT",
+                    root,
+                    format!("Invalid {}: {}", field.replace('_', " "), e),
+                );
+                continue;
+            }
+        };
+
+        walk(
+            &content,
+            root.to_string(),
+            false,
+            &mut |node, path, in_item| {
+                match node {
+                    PromptContent::SectionRef { section_id }
+                        if library
+                            .resolve_section(section_id, Some(&section.namespace))
+                            .is_none() =>
+                    {
+                        report(
+                            Severity::Error,
+                            "SECTION_NOT_FOUND",
+                            path,
+                            format!("Section not found: {}", section_id),
+                        )
+                    }
+                    PromptContent::List {
+                        separator_set_id, ..
+                    } => check_separator(library, Some(separator_set_id), path, &mut report),
+                    PromptContent::PickMany {
+                        separator_set_id, ..
+                    }
+                    | PromptContent::Shuffle {
+                        separator_set_id, ..
+                    } => check_separator(library, separator_set_id.as_ref(), path, &mut report),
+                    PromptContent::Unknown => report(
+                        Severity::Warning,
+                        "UNKNOWN_CONTENT_TYPE",
+                        path,
+                        "Unknown content type (rendered as nothing)".to_string(),
+                    ),
+                    _ => {}
+                }
+
+                // Item templates also see `item`, `index` and the item's own fields,
+                // which can't be known statically
+                if !in_item {
+                    for variable in variables_of(node) {
+                        if !declared.contains(variable) {
+                            report(
+                                Severity::Warning,
+                                "UNDECLARED_VARIABLE",
+                                path,
+                                format!("Variable '{}' is not declared by this section", variable),
+                            );
+                        }
+                    }
+                }
+            },
+        );
+
+        let mut stack = vec![section_key(section)];
+        let mut explored = HashSet::new();
+        if let Some(cycle) = find_cycle(
+            library,
+            &content,
+            &section.namespace,
+            &mut stack,
+            &mut explored,
+        ) {
+            report(
+                Severity::Error,
+                "CYCLIC_REFERENCE",
+                root,
+                format!("Section references itself: {}", cycle.join(" → ")),
+            );
+        }
+    }
+
+    diagnostics
+}
+
+fn check_separator(
+    library: &PromptLibrary,
+    separator_set_id: Option<&String>,
+    path: &str,
+    report: &mut impl FnMut(Severity, &str, &str, String),
+) {
+    if let Some(id) = separator_set_id {
+        if !library.has_separator_set(id) {
+            report(
+                Severity::Error,
+                "SEPARATOR_NOT_FOUND",
+                path,
+                format!("Separator set not found: {}", id),
+            );
+        }
+    }
+}
+
+/// Follow section references depth-first; returns the reference chain when
+/// it leads back to the section at the bottom of `stack`
+fn find_cycle(
+    library: &PromptLibrary,
+    content: &PromptContent,
+    namespace: &str,
+    stack: &mut Vec<String>,
+    explored: &mut HashSet<String>,
+) -> Option<Vec<String>> {
+    let mut references = Vec::new();
+    walk(content, String::new(), false, &mut |node, _, _| {
+        if let PromptContent::SectionRef { section_id } = node {
+            references.push(section_id.clone());
+        }
+    });
+
+    for reference in references {
+        let Some(target) = library.resolve_section(&reference, Some(namespace)) else {
+            continue;
+        };
+        let key = section_key(target);
+        if key == stack[0] {
+            let mut cycle: Vec<String> = stack.clone();
+            cycle.push(key);
+            return Some(cycle);
+        }
+        if stack.contains(&key) || !explored.insert(key.clone()) {
+            continue;
+        }
+        let Ok(content) = parse_content(target) else {
+            continue;
+        };
+        stack.push(key);
+        if let Some(cycle) = find_cycle(library, &content, namespace, stack, explored) {
+            return Some(cycle);
+        }
+        stack.pop();
+    }
+    None
+}
+
+/// Visit every node with its path and whether it is inside an item template
+pub(super) fn walk<'c>(
+    content: &'c PromptContent,
+    path: String,
+    in_item: bool,
+    visit: &mut impl FnMut(&'c PromptContent, &str, bool),
+) {
+    visit(content, &path, in_item);
+
+    let mut child = |suffix: String, node: &'c PromptContent, item_scope: bool| {
+        walk(
+            node,
+            format!("{}{}", path, suffix),
+            in_item || item_scope,
+            visit,
+        )
+    };
+    match content {
+        PromptContent::Composite { parts } => {
+            for (i, part) in parts.iter().enumerate() {
+                child(format!(".parts[{}]", i), part, false);
+            }
+        }
+        PromptContent::Conditional {
+            then_content,
+            else_content,
+            ..
+        } => {
+            child(".then".to_string(), then_content, false);
+            if let Some(else_content) = else_content {
+                child(".else".to_string(), else_content, false);
+            }
+        }
+        PromptContent::List {
+            item_template: Some(template),
+            ..
+        }
+        | PromptContent::Shuffle {
+            item_template: Some(template),
+            ..
+        } => child(".item_template".to_string(), template, true),
+        PromptContent::Context {
+            fallback: Some(fallback),
+            ..
+        } => child(".fallback".to_string(), fallback, false),
+        PromptContent::Article {
+            word_content: Some(word),
+            ..
+        }
+        | PromptContent::Possessive {
+            word_content: Some(word),
+            ..
+        } => child(".word_content".to_string(), word, false),
+        PromptContent::CountSwitch {
+            cases,
+            default_content,
+            ..
+        } => {
+            for (i, case) in cases.iter().enumerate() {
+                child(format!(".cases[{}]", i), &case.content, false);
+            }
+            if let Some(default) = default_content {
+                child(".default".to_string(), default, false);
+            }
+        }
+        PromptContent::Switch {
+            cases,
+            default_content,
+            ..
+        } => {
+            for (i, case) in cases.iter().enumerate() {
+                child(format!(".cases[{}]", i), &case.content, false);
+            }
+            if let Some(default) = default_content {
+                child(".default".to_string(), default, false);
+            }
+        }
+        PromptContent::PickOne { candidates, .. } | PromptContent::PickMany { candidates, .. } => {
+            for (i, candidate) in candidates.iter().enumerate() {
+                child(format!(".candidates[{}]", i), candidate, false);
+            }
+        }
+        PromptContent::WeightedPick { options } => {
+            for (i, option) in options.iter().enumerate() {
+                child(format!(".options[{}]", i), &option.content, false);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Variables a node reads directly
+fn variables_of(content: &PromptContent) -> Vec<&str> {
+    let mut variables = Vec::new();
+    match content {
+        PromptContent::Variable { variable_id, .. }
+        | PromptContent::List { variable_id, .. }
+        | PromptContent::Switch { variable_id, .. }
+        | PromptContent::Shuffle { variable_id, .. } => variables.push(variable_id.as_str()),
+        PromptContent::Plural { count_variable, .. }
+        | PromptContent::CountSwitch { count_variable, .. }
+        | PromptContent::Verb { count_variable, .. } => variables.push(count_variable.as_str()),
+        PromptContent::Article {
+            word_variable: Some(variable),
+            ..
+        }
+        | PromptContent::Possessive {
+            word_variable: Some(variable),
+            ..
+        }
+        | PromptContent::RandomValue {
+            pool: None,
+            pool_variable: Some(variable),
+            ..
+        } => variables.push(variable.as_str()),
+        PromptContent::Conditional { condition, .. } => {
+            condition_variables(condition, &mut variables)
+        }
+        _ => {}
+    }
+    variables
+}
+
+fn condition_variables<'c>(condition: &'c Condition, out: &mut Vec<&'c str>) {
+    if let Some(variable) = &condition.variable {
+        out.push(variable);
+    }
+    for nested in condition
+        .and
+        .iter()
+        .flatten()
+        .chain(condition.or.iter().flatten())
+        .chain(condition.not.as_deref())
+    {
+        condition_variables(nested, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_gen::test_support;
+    use serde_json::{json, Value};
+
+    fn section(key: &str, content: Value, required: &[&str]) -> PromptSection {
+        PromptSection {
+            is_entry_point: false,
+            exportable: false,
+            required_variables: required.iter().map(|v| v.to_string()).collect(),
+            ..test_support::section(key, content)
+        }
+    }
+
+    fn codes(report: &ValidationReport) -> Vec<&str> {
+        report.diagnostics.iter().map(|d| d.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_validate_sections() {
+        let library = PromptLibrary {
+            sections: vec![
+                section(
+                    "intro",
+                    json!({ "type": "composite", "parts": [
+                        { "type": "variable", "variable_id": "name" },
+                        { "type": "variable", "variable_id": "mood" },
+                        { "type": "list", "variable_id": "items", "separator_set_id": "nope",
+                          "item_template": { "type": "variable", "variable_id": "label" } },
+                        { "type": "section-ref", "section_id": "missing" },
+                        { "type": "section-ref", "section_id": "outro" }
+                    ]}),
+                    &["name", "items"],
+                ),
+                section(
+                    "outro",
+                    json!({ "type": "section-ref", "section_id": "loop" }),
+                    &[],
+                ),
+                section(
+                    "loop",
+                    json!({ "type": "section-ref", "section_id": "outro" }),
+                    &[],
+                ),
+                section(
+                    "clean",
+                    json!({ "type": "list", "variable_id": "items", "separator_set_id": "or-list" }),
+                    &["items"],
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let intro = validate_sections(&library, &library.sections[..1]);
+        assert!(!intro.valid);
+        assert_eq!(
+            codes(&intro),
+            vec![
+                "UNDECLARED_VARIABLE",
+                "SEPARATOR_NOT_FOUND",
+                "SECTION_NOT_FOUND"
+            ]
+        );
+        assert_eq!(intro.diagnostics[0].path, "root.parts[1]");
+        assert_eq!(intro.diagnostics[2].path, "root.parts[3]");
+
+        let cyclic = validate_sections(&library, &library.sections[1..3]);
+        assert_eq!(codes(&cyclic), vec!["CYCLIC_REFERENCE", "CYCLIC_REFERENCE"]);
+        assert_eq!(
+            cyclic.diagnostics[0].message,
+            "Section references itself: outro → loop → outro"
+        );
+
+        let clean = validate_sections(&library, &library.sections[3..]);
+        assert!(clean.valid);
+        assert!(clean.diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+// Variable validation
+//
+// Checks supplied variable values against a section's variable definitions:
+// required values, the declared type, enum values, and — when a definition
+// names a `data_type_id` such as "examples:WritingStyle" — the data type's
+// base type and validation rules (pattern, min/max, enum_values).
+
+use super::renderer::PromptLibrary;
+use super::{PromptDataType, PromptSection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableError {
+    pub variable: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariableValidation {
+    pub valid: bool,
+    pub errors: Vec<VariableError>,
+}
+
+/// Rules shared by variable definitions and data types
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Rules {
+    pattern: Option<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+    enum_values: Option<Vec<String>>,
+}
+
+/// Validate `values` (defaults applied) against the section's definitions
+pub fn validate_variables(
+    library: &PromptLibrary,
+    section: &PromptSection,
+    values: &Map<String, Value>,
+) -> Vec<VariableError> {
+    let mut errors = Vec::new();
+    for definition in &section.variables {
+        let Some(id) = definition.get("id").and_then(|id| id.as_str()) else {
+            continue;
+        };
+        let mut error = |message: String| {
+            errors.push(VariableError {
+                variable: id.to_string(),
+                message,
+            })
+        };
+
+        let value = values
+            .get(id)
+            .or_else(|| definition.get("default_value"))
+            .filter(|v| !v.is_null());
+        let Some(value) = value else {
+            if definition.get("required").and_then(|r| r.as_bool()) == Some(true) {
+                error("A value is required".to_string());
+            }
+            continue;
+        };
+
+        let declared = definition.get("type").and_then(|t| t.as_str());
+        if let Some(kind) = declared.filter(|k| *k != "custom") {
+            let rules = Rules {
+                enum_values: definition
+                    .get("enum_values")
+                    .and_then(|v| serde_json::from_value(v.clone()).ok()),
+                min: definition.get("min_items").and_then(|v| v.as_f64()),
+                max: definition.get("max_items").and_then(|v| v.as_f64()),
+                ..Default::default()
+            };
+            for message in check_value(value, kind, &rules) {
+                error(message);
+            }
+        }
+
+        if let Some(data_type_id) = definition.get("data_type_id").and_then(|d| d.as_str()) {
+            match library.resolve_data_type(data_type_id) {
+                Some(data_type) => {
+                    for message in check_data_type(value, data_type) {
+                        error(message);
+                    }
+                }
+                None => error(format!("Unknown data type '{}'", data_type_id)),
+            }
+        }
+    }
+    errors
+}
+
+fn check_data_type(value: &Value, data_type: &PromptDataType) -> Vec<String> {
+    let rules: Rules = data_type
+        .validation
+        .clone()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    check_value(value, &data_type.base_type, &rules)
+        .into_iter()
+        .map(|message| format!("{} ({})", message, data_type.name))
+        .collect()
+}
+
+/// Type and rule violations of one value
+fn check_value(value: &Value, kind: &str, rules: &Rules) -> Vec<String> {
+    let type_ok = match kind {
+        "string" | "enum" => value.is_string(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    };
+    if !type_ok {
+        return vec![format!("Expected {}, got {}", kind, type_name(value))];
+    }
+
+    let mut errors = Vec::new();
+    if let (Some(allowed), Some(s)) = (&rules.enum_values, value.as_str()) {
+        if !allowed.iter().any(|a| a == s) {
+            errors.push(format!("'{}' is not one of: {}", s, allowed.join(", ")));
+        }
+    }
+    if let (Some(pattern), Some(s)) = (&rules.pattern, value.as_str()) {
+        match regex::Regex::new(pattern) {
+            Ok(re) if !re.is_match(s) => {
+                errors.push(format!("'{}' does not match pattern {}", s, pattern))
+            }
+            Ok(_) => {}
+            Err(e) => errors.push(format!("Invalid pattern {}: {}", pattern, e)),
+        }
+    }
+
+    // min/max bound numbers, or the length of strings and arrays
+    let (measure, unit) = match value {
+        Value::Number(n) => (n.as_f64(), ""),
+        Value::String(s) => (Some(s.chars().count() as f64), " characters"),
+        Value::Array(items) => (Some(items.len() as f64), " items"),
+        _ => (None, ""),
+    };
+    if let Some(measure) = measure {
+        if let Some(min) = rules.min.filter(|min| measure < *min) {
+            errors.push(format!("Must be at least {}{}", min, unit));
+        }
+        if let Some(max) = rules.max.filter(|max| measure > *max) {
+            errors.push(format!("Must be at most {}{}", max, unit));
+        }
+    }
+    errors
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_variables() {
+        let data_type: PromptDataType = serde_json::from_value(json!({
+            "package_id": "pkg", "namespace": "examples", "name": "WritingStyle",
+            "description": "", "base_type": "enum",
+            "validation": { "enum_values": ["formal", "casual"] },
+            "examples": [], "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let code: PromptDataType = serde_json::from_value(json!({
+            "package_id": "pkg", "namespace": "examples", "name": "Code",
+            "description": "", "base_type": "string",
+            "validation": { "pattern": "^[A-Z]{3}$" },
+            "examples": [], "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let section: PromptSection = serde_json::from_value(json!({
+            "package_id": "pkg", "namespace": "examples", "name": "letter", "description": "",
+            "content": { "type": "text", "value": "" },
+            "is_entry_point": true, "exportable": true, "required_variables": [],
+            "variables": [
+                { "id": "style", "type": "custom", "data_type_id": "examples:WritingStyle", "required": true },
+                { "id": "code", "type": "custom", "data_type_id": "Code" },
+                { "id": "count", "type": "number", "required": true },
+                { "id": "tags", "type": "array", "max_items": 2 },
+                { "id": "tone", "type": "string", "default_value": "warm" },
+                { "id": "mood", "type": "custom", "data_type_id": "examples:Missing" }
+            ],
+            "created_at": "", "updated_at": ""
+        }))
+        .unwrap();
+        let library = PromptLibrary {
+            sections: vec![section.clone()],
+            data_types: vec![data_type, code],
+            ..Default::default()
+        };
+
+        let values = json!({ "style": "formal", "code": "ABC", "count": 3, "tags": ["a"] });
+        let errors = validate_variables(&library, &section, values.as_object().unwrap());
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let values =
+            json!({ "style": "sloppy", "code": "abc", "tags": ["a", "b", "c"], "mood": "x" });
+        let errors = validate_variables(&library, &section, values.as_object().unwrap());
+        let found: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|e| (e.variable.as_str(), e.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "style",
+                    "'sloppy' is not one of: formal, casual (WritingStyle)"
+                ),
+                ("code", "'abc' does not match pattern ^[A-Z]{3}$ (Code)"),
+                ("count", "A value is required"),
+                ("tags", "Must be at most 2 items"),
+                ("mood", "Unknown data type 'examples:Missing'"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+// Ticket due date reminders
+//
+// Lists open tickets that are overdue or due within a time window, and checks
+// them in the background every 15 minutes. Newly due and newly overdue tickets
+// are sent to the frontend as a `tickets://due` event, and the user is
+// notified (see `notifications`). Each ticket is announced once per state
+// (due soon, overdue) until its due date changes.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::i18n;
+use crate::notifications::{notify, NotificationCategory};
+use crate::tickets::{Ticket, DONE_STATUS};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+/// How often due dates are checked
+const REMINDER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Tickets due within this many hours count as "due soon"
+pub const DEFAULT_REMINDER_WINDOW_HOURS: u32 = 24;
+
+/// Event carrying the tickets that just became due soon or overdue
+pub const DUE_EVENT: &str = "tickets://due";
+
+/// An open ticket with a due date inside the window (or in the past)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DueTicket {
+    pub ticket: Ticket,
+    pub due_at: DateTime<Utc>,
+    pub overdue: bool,
+}
+
+impl DueTicket {
+    /// "Overdue since …" or "Due …", in local time and the backend locale
+    fn describe(&self) -> String {
+        let when = self
+            .due_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        if self.overdue {
+            i18n::format("notification.ticket_overdue", &[("when", &when)])
+        } else {
+            i18n::format("notification.ticket_due", &[("when", &when)])
+        }
+    }
+}
+
+/// Parse a ticket due date: RFC 3339, a datetime-local value
+/// ("2024-05-01T14:30", UTC) or a plain date (due at the end of that day)
+pub fn parse_due_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(date_time.with_timezone(&Utc));
+    }
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M") {
+        return Some(date_time.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .map(|date_time| date_time.and_utc())
+}
+
+impl Database {
+    /// Open tickets that are overdue or due within `window_hours`, soonest first
+    pub async fn get_due_tickets(&self, window_hours: u32) -> Result<Vec<DueTicket>, AppError> {
+        let now = Utc::now();
+        let until = now + Duration::hours(i64::from(window_hours));
+
+        let mut due: Vec<DueTicket> = self
+            .get_tickets(None)
+            .await?
+            .into_iter()
+            .filter(|ticket| ticket.status != DONE_STATUS)
+            .filter_map(|ticket| {
+                let due_at = parse_due_date(ticket.due_date.as_deref()?)?;
+                (due_at <= until).then_some(DueTicket {
+                    overdue: due_at < now,
+                    ticket,
+                    due_at,
+                })
+            })
+            .collect();
+        due.sort_by_key(|d| d.due_at);
+
+        Ok(due)
+    }
+}
+
+/// Tickets not announced in their current state yet; forgets tickets that
+/// are no longer due so they're announced again if they come back
+fn pending_reminders(
+    notified: &mut HashMap<String, (DateTime<Utc>, bool)>,
+    due: Vec<DueTicket>,
+) -> Vec<DueTicket> {
+    notified.retain(|id, _| due.iter().any(|d| d.ticket.id == *id));
+
+    due.into_iter()
+        .filter(|d| {
+            let state = (d.due_at, d.overdue);
+            notified.insert(d.ticket.id.clone(), state) != Some(state)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// Check due dates in the background every 15 minutes
+pub fn start(app: AppHandle, database: Arc<Mutex<Database>>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(REMINDER_INTERVAL);
+        let mut notified = HashMap::new();
+
+        loop {
+            ticker.tick().await;
+
+            let due = {
+                let db = database.lock().await;
+                db.get_due_tickets(DEFAULT_REMINDER_WINDOW_HOURS).await
+            };
+            let pending = match due {
+                Ok(due) => pending_reminders(&mut notified, due),
+                Err(e) => {
+                    tracing::warn!("Failed to check ticket due dates: {}", e);
+                    continue;
+                }
+            };
+
+            if !pending.is_empty() {
+                tracing::info!("{} tickets due soon or overdue", pending.len());
+                if let Err(e) = app.emit(DUE_EVENT, &pending) {
+                    tracing::warn!("Failed to emit due tickets: {}", e);
+                }
+                for due in &pending {
+                    notify(
+                        NotificationCategory::DueTickets,
+                        &due.ticket.title,
+                        due.describe(),
+                    );
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_due_tickets() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let now = Utc::now();
+        let tomorrow_date = (now + Duration::days(1)).format("%Y-%m-%d").to_string();
+        for (title, due_date) in [
+            ("Overdue", Some((now - Duration::hours(2)).to_rfc3339())),
+            ("Soon", Some((now + Duration::hours(3)).to_rfc3339())),
+            ("Tomorrow", Some(tomorrow_date)),
+            ("Later", Some((now + Duration::days(10)).to_rfc3339())),
+            ("Unparseable", Some("someday".to_string())),
+            ("Undated", None),
+        ] {
+            db.create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": title, "ticket_type": "task", "due_date": due_date
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+        let finished = db
+            .create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": "Finished", "ticket_type": "task",
+                    "due_date": (now - Duration::hours(1)).to_rfc3339()
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        db.move_ticket(&finished.id, DONE_STATUS).await.unwrap();
+
+        let due = db.get_due_tickets(24).await.unwrap();
+        let titles: Vec<(&str, bool)> = due
+            .iter()
+            .map(|d| (d.ticket.title.as_str(), d.overdue))
+            .collect();
+        assert_eq!(titles, vec![("Overdue", true), ("Soon", false)]);
+        assert_eq!(db.get_due_tickets(48).await.unwrap().len(), 3);
+
+        // Each state is announced once
+        let mut notified = HashMap::new();
+        assert_eq!(pending_reminders(&mut notified, due.clone()).len(), 2);
+        assert!(pending_reminders(&mut notified, due.clone()).is_empty());
+        let mut now_overdue = due.clone();
+        now_overdue[1].overdue = true;
+        let pending = pending_reminders(&mut notified, now_overdue);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].ticket.title, "Soon");
+        pending_reminders(&mut notified, Vec::new());
+        assert_eq!(pending_reminders(&mut notified, due).len(), 2);
+    }
+}
@@ -0,0 +1,542 @@
+// Remote backup targets
+//
+// After each scheduled backup, an encrypted copy can be uploaded to a WebDAV
+// folder (Nextcloud, ownCloud, a NAS, ...) or an S3-compatible bucket. The
+// copy is sealed with the remote backup password like encrypted exports, so
+// the storage provider never sees the data. Authentication comes from a
+// credential profile: basic auth for WebDAV, an AWS key pair for S3. The
+// newest `backup.max_keep` copies are kept on the remote as well.
+
+use crate::adapters::{s3, AdapterConfig, AuthConfig, HttpClient};
+use crate::audit::Actor;
+use crate::backup::{import_snapshot, BackupInfo, FILE_PREFIX};
+use crate::credential_profiles::load_credential_profile;
+use crate::credentials::{get_credential, store_credential};
+use crate::db::{Database, ImportStats};
+use crate::encrypted_export::{open_archive, seal_archive};
+use crate::error::AppError;
+use crate::redaction::register_secret;
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use crate::vault::MIN_PASSWORD_LENGTH;
+use chrono::{DateTime, Utc};
+use quick_xml::events::Event;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+/// Remote copies are the local backup file name plus this suffix
+const REMOTE_SUFFIX: &str = ".enc";
+
+/// Credential key of the password remote backups are sealed with
+const PASSWORD_KEY: &str = "backup_remote_password";
+
+const KINDS: &[&str] = &["none", "webdav", "s3"];
+
+/// Properties requested when listing a WebDAV folder
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<propfind xmlns="DAV:"><prop><getcontentlength/><getlastmodified/></prop></propfind>"#;
+
+/// The `backup.remote*` settings
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new(
+            "backup.remote",
+            SettingType::String,
+            "none".into(),
+            "backup",
+        )
+        .describe(
+            "Remote backups",
+            "Also upload encrypted backups to WebDAV or S3",
+        )
+        .options(KINDS.iter().map(|&kind| kind.into()).collect()),
+        SettingDefinition::new(
+            "backup.remote_url",
+            SettingType::String,
+            "".into(),
+            "backup",
+        )
+        .describe(
+            "Remote URL",
+            "WebDAV folder URL, or the S3 endpoint (e.g. https://s3.amazonaws.com)",
+        ),
+        SettingDefinition::new(
+            "backup.remote_bucket",
+            SettingType::String,
+            "".into(),
+            "backup",
+        )
+        .describe("S3 bucket", "Bucket the backups are uploaded to"),
+        SettingDefinition::new(
+            "backup.remote_prefix",
+            SettingType::String,
+            "".into(),
+            "backup",
+        )
+        .describe("S3 key prefix", "Prepended to backup names, e.g. modulaur/"),
+        SettingDefinition::new(
+            "backup.remote_region",
+            SettingType::String,
+            "us-east-1".into(),
+            "backup",
+        )
+        .describe("S3 region", "Region used for request signing"),
+        SettingDefinition::new(
+            "backup.remote_profile",
+            SettingType::String,
+            "".into(),
+            "backup",
+        )
+        .describe(
+            "Remote credentials",
+            "Credential profile: basic auth for WebDAV, an AWS key pair for S3",
+        ),
+    ]
+}
+
+/// Where remote backups go
+#[derive(Debug, Clone)]
+pub enum RemoteTarget {
+    WebDav {
+        /// Folder URL without a trailing slash
+        url: String,
+        auth: Option<AuthConfig>,
+    },
+    /// An S3 adapter config naming the endpoint, bucket, prefix and region
+    S3(AdapterConfig),
+}
+
+/// A backup copy on the remote target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteBackup {
+    /// File name on the remote, passed to `restore_from_remote`
+    pub id: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl RemoteTarget {
+    /// Load the configured target, or None when remote backups are off
+    pub async fn load(settings: &SettingsService) -> Result<Option<Self>, AppError> {
+        let setting = |key: &'static str| async move {
+            settings
+                .get_setting(key)
+                .await
+                .map(|value| value.unwrap_or_default().trim().to_string())
+        };
+
+        let kind = setting("backup.remote").await?;
+        if kind.is_empty() || kind == "none" {
+            return Ok(None);
+        }
+
+        let url = setting("backup.remote_url").await?;
+        if url.is_empty() {
+            return Err(AppError::Config(
+                "Remote backups need a URL (backup.remote_url)".to_string(),
+            ));
+        }
+
+        let profile = setting("backup.remote_profile").await?;
+        let auth = if profile.is_empty() {
+            None
+        } else {
+            let auth = load_credential_profile(&profile).map_err(|e| match e {
+                AppError::NotFound(_) => AppError::Config(format!(
+                    "Remote backups use the credential profile '{}', which doesn't exist",
+                    profile
+                )),
+                e => e,
+            })?;
+            for secret in auth.secrets() {
+                register_secret(secret);
+            }
+            Some(auth)
+        };
+
+        match kind.as_str() {
+            "webdav" => Ok(Some(RemoteTarget::WebDav {
+                url: url.trim_end_matches('/').to_string(),
+                auth,
+            })),
+            "s3" => {
+                let mut config = AdapterConfig::new("s3", "remote-backup", &url);
+                config.parameters = serde_json::json!({
+                    "bucket": setting("backup.remote_bucket").await?,
+                    "prefix": setting("backup.remote_prefix").await?,
+                    "region": setting("backup.remote_region").await?,
+                });
+                config.auth = auth;
+                Ok(Some(RemoteTarget::S3(config)))
+            }
+            other => Err(AppError::Config(format!(
+                "Unknown remote backup type '{}' (expected one of {:?})",
+                other, KINDS
+            ))),
+        }
+    }
+
+    /// "webdav" or "s3", for messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RemoteTarget::WebDav { .. } => "webdav",
+            RemoteTarget::S3(_) => "s3",
+        }
+    }
+
+    /// List remote backups, newest first
+    pub async fn list(&self) -> Result<Vec<RemoteBackup>, AppError> {
+        let mut backups = match self {
+            RemoteTarget::WebDav { url, auth } => webdav_list(url, auth).await?,
+            RemoteTarget::S3(config) => {
+                let prefix = config.parameters["prefix"].as_str().unwrap_or("");
+                s3::list_bucket(config)
+                    .await?
+                    .into_iter()
+                    .map(|object| RemoteBackup {
+                        id: object
+                            .key
+                            .strip_prefix(prefix)
+                            .unwrap_or(&object.key)
+                            .to_string(),
+                        size_bytes: object.size,
+                        modified_at: object
+                            .last_modified
+                            .as_deref()
+                            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                            .map(|d| d.with_timezone(&Utc)),
+                    })
+                    .collect()
+            }
+        };
+
+        backups.retain(|backup| is_backup_id(&backup.id));
+        // Timestamped names sort chronologically
+        backups.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(backups)
+    }
+
+    async fn upload(&self, id: &str, body: Vec<u8>) -> Result<(), AppError> {
+        match self {
+            RemoteTarget::WebDav { url, auth } => webdav_upload(url, auth, id, body).await,
+            RemoteTarget::S3(config) => s3::put_object(config, &s3_key(config, id), body).await,
+        }
+    }
+
+    async fn download(&self, id: &str) -> Result<Vec<u8>, AppError> {
+        match self {
+            RemoteTarget::WebDav { url, auth } => {
+                let response = webdav_request(url, auth, reqwest::Method::GET, Some(id))
+                    .send()
+                    .await
+                    .map_err(|e| AppError::request(&e, format!("WebDAV request failed: {}", e)))?;
+                let response = check_status(response, "GET").await?;
+                let bytes = response.bytes().await.map_err(|e| {
+                    AppError::request(&e, format!("Failed to download remote backup: {}", e))
+                })?;
+                Ok(bytes.to_vec())
+            }
+            RemoteTarget::S3(config) => s3::get_object(config, &s3_key(config, id)).await,
+        }
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), AppError> {
+        match self {
+            RemoteTarget::WebDav { url, auth } => {
+                let response = webdav_request(url, auth, reqwest::Method::DELETE, Some(id))
+                    .send()
+                    .await
+                    .map_err(|e| AppError::request(&e, format!("WebDAV request failed: {}", e)))?;
+                check_status(response, "DELETE").await?;
+                Ok(())
+            }
+            RemoteTarget::S3(config) => s3::delete_object(config, &s3_key(config, id)).await,
+        }
+    }
+}
+
+fn is_backup_id(id: &str) -> bool {
+    id.starts_with(FILE_PREFIX) && id.ends_with(REMOTE_SUFFIX) && !id.contains('/')
+}
+
+fn s3_key(config: &AdapterConfig, id: &str) -> String {
+    format!(
+        "{}{}",
+        config.parameters["prefix"].as_str().unwrap_or(""),
+        id
+    )
+}
+
+// ============================================================================
+// Password
+// ============================================================================
+
+/// Store the password remote backups are sealed with
+pub fn set_password(password: &str) -> Result<(), AppError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::Validation(format!(
+            "The remote backup password needs at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+    store_credential(PASSWORD_KEY.to_string(), password.to_string())
+}
+
+fn password() -> Result<String, AppError> {
+    get_credential(PASSWORD_KEY.to_string())?.ok_or_else(|| {
+        AppError::Config("Set a remote backup password before using remote backups".to_string())
+    })
+}
+
+// ============================================================================
+// Upload / Restore
+// ============================================================================
+
+/// Seal a local backup file, upload it and delete all but the newest
+/// `max_keep` remote backups
+pub async fn upload_backup(
+    target: &RemoteTarget,
+    backup: &BackupInfo,
+    max_keep: usize,
+) -> Result<RemoteBackup, AppError> {
+    let password = password()?;
+    let snapshot: serde_json::Value = serde_json::from_slice(&std::fs::read(&backup.path)?)?;
+    let sealed = seal_archive(&snapshot, &password)?;
+    let size_bytes = sealed.len() as u64;
+
+    let id = format!("{}{}", backup.file_name, REMOTE_SUFFIX);
+    target.upload(&id, sealed).await?;
+    tracing::info!("Uploaded backup {} to {}", id, target.kind());
+
+    for old in target.list().await?.into_iter().skip(max_keep) {
+        tracing::info!("Removing old remote backup: {}", old.id);
+        target.delete(&old.id).await?;
+    }
+
+    Ok(RemoteBackup {
+        id,
+        size_bytes,
+        modified_at: Some(Utc::now()),
+    })
+}
+
+/// Download a remote backup and restore it, replacing the current data
+pub async fn restore_from_remote(
+    database: &Mutex<Database>,
+    target: &RemoteTarget,
+    id: &str,
+) -> Result<ImportStats, AppError> {
+    if !is_backup_id(id) {
+        return Err(AppError::Validation(format!(
+            "Not a remote backup name: {}",
+            id
+        )));
+    }
+
+    let sealed = target.download(id).await?;
+    let snapshot = open_archive(&sealed, &password()?)?;
+    if snapshot.get("data").is_none() {
+        return Err(AppError::Validation(format!(
+            "Remote backup {} contains no data",
+            id
+        )));
+    }
+
+    tracing::info!("Restoring remote backup {} from {}", id, target.kind());
+    import_snapshot(
+        database,
+        snapshot,
+        "replace",
+        Actor::command("restore_from_remote"),
+    )
+    .await
+}
+
+// ============================================================================
+// WebDAV
+// ============================================================================
+
+fn webdav_request(
+    url: &str,
+    auth: &Option<AuthConfig>,
+    method: reqwest::Method,
+    file_name: Option<&str>,
+) -> reqwest::RequestBuilder {
+    let url = match file_name {
+        Some(name) => format!("{}/{}", url, name),
+        None => format!("{}/", url),
+    };
+    HttpClient::add_auth(HttpClient::new_client().request(method, url), auth)
+}
+
+async fn check_status(
+    response: reqwest::Response,
+    method: &str,
+) -> Result<reqwest::Response, AppError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(AppError::http_status(
+        status.as_u16(),
+        format!(
+            "WebDAV {} request failed with status {}: {}",
+            method,
+            status,
+            body.chars().take(200).collect::<String>()
+        ),
+    ))
+}
+
+fn webdav_method(name: &str) -> reqwest::Method {
+    reqwest::Method::from_bytes(name.as_bytes()).expect("WebDAV method names are valid tokens")
+}
+
+async fn webdav_upload(
+    url: &str,
+    auth: &Option<AuthConfig>,
+    id: &str,
+    body: Vec<u8>,
+) -> Result<(), AppError> {
+    let put = || webdav_request(url, auth, reqwest::Method::PUT, Some(id)).body(body.clone());
+    let send_error =
+        |e: reqwest::Error| AppError::request(&e, format!("WebDAV request failed: {}", e));
+
+    let response = put().send().await.map_err(send_error)?;
+    // A missing folder is reported as 409 Conflict (404 on some servers)
+    let response = if matches!(response.status().as_u16(), 404 | 409) {
+        let created = webdav_request(url, auth, webdav_method("MKCOL"), None)
+            .send()
+            .await
+            .map_err(send_error)?;
+        check_status(created, "MKCOL").await?;
+        put().send().await.map_err(send_error)?
+    } else {
+        response
+    };
+    check_status(response, "PUT").await?;
+    Ok(())
+}
+
+async fn webdav_list(url: &str, auth: &Option<AuthConfig>) -> Result<Vec<RemoteBackup>, AppError> {
+    let response = webdav_request(url, auth, webdav_method("PROPFIND"), None)
+        .header("Depth", "1")
+        .header("Content-Type", "application/xml")
+        .body(PROPFIND_BODY)
+        .send()
+        .await
+        .map_err(|e| AppError::request(&e, format!("WebDAV request failed: {}", e)))?;
+    // Nothing was uploaded yet
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    let body = check_status(response, "PROPFIND")
+        .await?
+        .text()
+        .await
+        .map_err(|e| AppError::request(&e, format!("Failed to read WebDAV listing: {}", e)))?;
+
+    parse_multistatus(&body)
+}
+
+/// Parse a PROPFIND multistatus response into the files it lists
+fn parse_multistatus(xml: &str) -> Result<Vec<RemoteBackup>, AppError> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut files = Vec::new();
+    let mut element: Option<Vec<u8>> = None;
+    let mut href = String::new();
+    let mut size_bytes = 0;
+    let mut modified_at = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| AppError::Adapter(format!("Failed to parse WebDAV listing: {}", e)))?;
+        match event {
+            Event::Start(start) => {
+                let name = start.local_name().as_ref().to_vec();
+                if name == b"response" {
+                    href.clear();
+                    size_bytes = 0;
+                    modified_at = None;
+                }
+                element = Some(name);
+            }
+            Event::Text(text) => {
+                let text = text.decode().map_err(|e| {
+                    AppError::Adapter(format!("Failed to parse WebDAV listing: {}", e))
+                })?;
+                match element.as_deref() {
+                    Some(b"href") => href.push_str(text.trim()),
+                    Some(b"getcontentlength") => size_bytes = text.trim().parse().unwrap_or(0),
+                    Some(b"getlastmodified") => {
+                        modified_at = DateTime::parse_from_rfc2822(text.trim())
+                            .ok()
+                            .map(|d| d.with_timezone(&Utc))
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(end) => {
+                if end.local_name().as_ref() == b"response" {
+                    // The last path segment is the file name; the folder itself ends in '/'
+                    if let Some(name) = href.rsplit('/').next().filter(|n| !n.is_empty()) {
+                        files.push(RemoteBackup {
+                            id: name.to_string(),
+                            size_bytes,
+                            modified_at,
+                        });
+                    }
+                }
+                element = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multistatus() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+            <d:multistatus xmlns:d="DAV:">
+                <d:response>
+                    <d:href>/remote.php/dav/files/me/backups/</d:href>
+                    <d:propstat><d:prop><d:getlastmodified>Mon, 01 Jan 2024 12:00:00 GMT</d:getlastmodified></d:prop></d:propstat>
+                </d:response>
+                <d:response>
+                    <d:href>/remote.php/dav/files/me/backups/modulaur-backup-20240101-120000.json.enc</d:href>
+                    <d:propstat>
+                        <d:prop>
+                            <d:getcontentlength>4096</d:getcontentlength>
+                            <d:getlastmodified>Mon, 01 Jan 2024 12:00:05 GMT</d:getlastmodified>
+                        </d:prop>
+                        <d:status>HTTP/1.1 200 OK</d:status>
+                    </d:propstat>
+                </d:response>
+            </d:multistatus>"#;
+
+        let files = parse_multistatus(xml).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].id, "modulaur-backup-20240101-120000.json.enc");
+        assert_eq!(files[0].size_bytes, 4096);
+        assert_eq!(
+            files[0].modified_at.unwrap().to_rfc3339(),
+            "2024-01-01T12:00:05+00:00"
+        );
+    }
+
+    #[test]
+    fn test_is_backup_id() {
+        assert!(is_backup_id("modulaur-backup-20240101-120000.json.enc"));
+        assert!(!is_backup_id("modulaur-backup-20240101-120000.json"));
+        assert!(!is_backup_id("modulaur-backup-x/../secrets.json.enc"));
+        assert!(!is_backup_id("notes.txt"));
+    }
+}
@@ -0,0 +1,416 @@
+// Retention policies
+//
+// Persisted rules in the `retention_rules` table that limit how many staged
+// records are kept per record_type and/or source (by age and/or count). Rules
+// are applied hourly by the `retention` job; `preview_retention` shows what the
+// next run would delete.
+
+use crate::audit::Actor;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::scheduler::{Job, Schedule};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::Mutex;
+
+/// How often retention rules are applied
+const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Record IDs included in a preview per rule
+const PREVIEW_SAMPLE_SIZE: usize = 20;
+
+// ============================================================================
+// Retention Rule Model
+// ============================================================================
+
+/// Retention rule as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RetentionRuleRecord {
+    pub id: Thing,
+    pub record_type: Option<String>,
+    pub source: Option<String>,
+    pub max_age_days: Option<i64>,
+    pub max_count: Option<usize>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// User-facing retention rule with String ID
+/// Rules without record_type/source apply to all records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionRule {
+    pub id: String,
+    pub record_type: Option<String>,
+    pub source: Option<String>,
+    /// Delete records older than this many days
+    pub max_age_days: Option<i64>,
+    /// Keep only the newest N records
+    pub max_count: Option<usize>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<RetentionRuleRecord> for RetentionRule {
+    fn from(record: RetentionRuleRecord) -> Self {
+        RetentionRule {
+            id: record.id.id.to_raw(),
+            record_type: record.record_type,
+            source: record.source,
+            max_age_days: record.max_age_days,
+            max_count: record.max_count,
+            enabled: record.enabled,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+/// Create (no id) or update (with id) a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveRetentionRuleRequest {
+    pub id: Option<String>,
+    pub record_type: Option<String>,
+    pub source: Option<String>,
+    pub max_age_days: Option<i64>,
+    pub max_count: Option<usize>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Records a rule deletes (or would delete)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionOutcome {
+    pub rule_id: String,
+    pub record_type: Option<String>,
+    pub source: Option<String>,
+    pub count: usize,
+    /// Up to 20 affected record IDs (previews only)
+    pub sample_ids: Vec<String>,
+}
+
+// ============================================================================
+// Retention Operations
+// ============================================================================
+
+impl Database {
+    pub async fn list_retention_rules(&self) -> Result<Vec<RetentionRule>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM retention_rules ORDER BY created_at ASC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query retention rules: {}", e)))?;
+
+        let rules: Vec<RetentionRuleRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse retention rules: {}", e)))?;
+
+        Ok(rules.into_iter().map(|r| r.into()).collect())
+    }
+
+    pub async fn save_retention_rule(
+        &self,
+        req: SaveRetentionRuleRequest,
+    ) -> Result<RetentionRule, AppError> {
+        if req.max_age_days.is_none() && req.max_count.is_none() {
+            return Err(AppError::Validation(
+                "A retention rule needs max_age_days and/or max_count".to_string(),
+            ));
+        }
+        if req.max_age_days.is_some_and(|days| days <= 0) {
+            return Err(AppError::Validation(
+                "max_age_days must be positive".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let (id, created_at) = match &req.id {
+            Some(id) => {
+                let key = id
+                    .strip_prefix("retention_rules:")
+                    .unwrap_or(id)
+                    .to_string();
+                let existing: Option<RetentionRuleRecord> = self
+                    .db
+                    .select(("retention_rules", key.as_str()))
+                    .await
+                    .map_err(|e| {
+                        AppError::Database(format!("Failed to get retention rule: {}", e))
+                    })?;
+                let existing =
+                    existing.ok_or_else(|| AppError::NotFound(format!("Retention rule {}", id)))?;
+                (key, existing.created_at)
+            }
+            None => (uuid::Uuid::new_v4().to_string(), now),
+        };
+
+        let record = RetentionRuleRecord {
+            id: Thing::from(("retention_rules", id.as_str())),
+            record_type: req.record_type.filter(|t| !t.is_empty()),
+            source: req.source.filter(|s| !s.is_empty()),
+            max_age_days: req.max_age_days,
+            max_count: req.max_count,
+            enabled: req.enabled,
+            created_at,
+            updated_at: now,
+        };
+
+        let saved: Option<RetentionRuleRecord> = self
+            .db
+            .upsert(("retention_rules", id.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save retention rule: {}", e)))?;
+
+        saved
+            .map(|r| r.into())
+            .ok_or_else(|| AppError::Database("Failed to save retention rule".to_string()))
+    }
+
+    pub async fn delete_retention_rule(&self, id: &str) -> Result<(), AppError> {
+        let key = id.strip_prefix("retention_rules:").unwrap_or(id);
+        let _: Option<RetentionRuleRecord> = self
+            .db
+            .delete(("retention_rules", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete retention rule: {}", e)))?;
+        Ok(())
+    }
+
+    /// Show what applying the enabled rules would delete
+    pub async fn preview_retention(&self) -> Result<Vec<RetentionOutcome>, AppError> {
+        self.evaluate_retention(true).await
+    }
+
+    /// Apply all enabled rules
+    pub async fn apply_retention(&self) -> Result<Vec<RetentionOutcome>, AppError> {
+        self.evaluate_retention(false).await
+    }
+
+    async fn evaluate_retention(&self, dry_run: bool) -> Result<Vec<RetentionOutcome>, AppError> {
+        let mut outcomes = Vec::new();
+
+        for rule in self.list_retention_rules().await? {
+            if !rule.enabled {
+                continue;
+            }
+
+            let expired = self.expired_record_ids(&rule).await?;
+            if !dry_run && !expired.is_empty() {
+                self.query("DELETE $ids")
+                    .bind(("ids", expired.clone()))
+                    .await
+                    .map_err(|e| {
+                        AppError::Database(format!("Failed to apply retention rule: {}", e))
+                    })?
+                    .check()
+                    .map_err(|e| {
+                        AppError::Database(format!("Failed to apply retention rule: {}", e))
+                    })?;
+                tracing::info!(
+                    "Retention rule {} deleted {} records",
+                    rule.id,
+                    expired.len()
+                );
+            }
+
+            outcomes.push(RetentionOutcome {
+                rule_id: rule.id,
+                record_type: rule.record_type,
+                source: rule.source,
+                count: expired.len(),
+                sample_ids: if dry_run {
+                    expired
+                        .iter()
+                        .take(PREVIEW_SAMPLE_SIZE)
+                        .map(|id| id.to_string())
+                        .collect()
+                } else {
+                    Vec::new()
+                },
+            });
+        }
+
+        Ok(outcomes)
+    }
+
+    /// IDs of records matched by a rule: older than max age or beyond the newest max_count
+    async fn expired_record_ids(&self, rule: &RetentionRule) -> Result<Vec<Thing>, AppError> {
+        let mut conditions = Vec::new();
+        if rule.record_type.is_some() {
+            conditions.push("record_type = $record_type");
+        }
+        if rule.source.is_some() {
+            conditions.push("source = $source");
+        }
+        let filter = if conditions.is_empty() {
+            "true".to_string()
+        } else {
+            conditions.join(" AND ")
+        };
+
+        let mut statements = Vec::new();
+        if rule.max_age_days.is_some() {
+            statements.push(format!(
+                "SELECT VALUE id FROM records WHERE {} AND timestamp < $cutoff;",
+                filter
+            ));
+        }
+        if rule.max_count.is_some() {
+            statements.push(format!(
+                "SELECT VALUE id FROM (SELECT id, timestamp FROM records WHERE {} ORDER BY timestamp DESC START $max_count);",
+                filter
+            ));
+        }
+
+        let cutoff = rule
+            .max_age_days
+            .map(|days| Utc::now() - Duration::days(days));
+        let mut result = self
+            .query(statements.join("\n"))
+            .bind(("record_type", rule.record_type.clone()))
+            .bind(("source", rule.source.clone()))
+            .bind(("cutoff", cutoff))
+            .bind(("max_count", rule.max_count.unwrap_or(0)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to evaluate retention rule: {}", e)))?;
+
+        let mut ids = Vec::new();
+        for index in 0..statements.len() {
+            let matched: Vec<Thing> = result.take(index).map_err(|e| {
+                AppError::Database(format!("Failed to evaluate retention rule: {}", e))
+            })?;
+            ids.extend(matched);
+        }
+
+        // A record can match both limits
+        ids.sort_by_key(|id| id.to_string());
+        ids.dedup();
+        Ok(ids)
+    }
+}
+
+// ============================================================================
+// Scheduled Job
+// ============================================================================
+
+/// Job applying the retention rules, hourly by default
+pub fn job(database: Arc<Mutex<Database>>) -> Job {
+    Job::new(
+        "retention",
+        "Apply retention rules",
+        Schedule::every(RETENTION_INTERVAL),
+        move || {
+            let database = database.clone();
+            async move {
+                let db = database.lock().await;
+                let outcomes = db
+                    .as_actor(Actor::job("retention"), db.apply_retention())
+                    .await?;
+                drop(db);
+                let deleted: usize = outcomes.iter().map(|o| o.count).sum();
+                if deleted > 0 {
+                    tracing::info!("Retention removed {} records", deleted);
+                }
+                Ok(format!("Removed {} records", deleted))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_retention_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        // Five daily builds, one log record that no rule touches
+        for age_days in 0..5 {
+            let mut record = StagedRecord::new(
+                "gitlab_pipeline".to_string(),
+                "gitlab".to_string(),
+                serde_json::json!({ "age": age_days }),
+            );
+            record.timestamp = Utc::now() - Duration::days(age_days);
+            db.upsert_record(record).await.unwrap();
+        }
+        let mut log = StagedRecord::new(
+            "log".to_string(),
+            "gitlab".to_string(),
+            serde_json::json!({}),
+        );
+        log.timestamp = Utc::now() - Duration::days(30);
+        db.upsert_record(log).await.unwrap();
+
+        assert!(db
+            .save_retention_rule(SaveRetentionRuleRequest {
+                id: None,
+                record_type: None,
+                source: None,
+                max_age_days: None,
+                max_count: None,
+                enabled: true,
+            })
+            .await
+            .is_err());
+
+        // Age drops the 4-day-old build, count drops everything but the newest 3
+        let rule = db
+            .save_retention_rule(SaveRetentionRuleRequest {
+                id: None,
+                record_type: Some("gitlab_pipeline".to_string()),
+                source: None,
+                max_age_days: Some(4),
+                max_count: Some(3),
+                enabled: true,
+            })
+            .await
+            .unwrap();
+
+        let preview = db.preview_retention().await.unwrap();
+        assert_eq!(preview.len(), 1);
+        assert_eq!(preview[0].rule_id, rule.id);
+        assert_eq!(preview[0].count, 2);
+        assert_eq!(preview[0].sample_ids.len(), 2);
+        assert_eq!(
+            db.get_records_by_type("gitlab_pipeline")
+                .await
+                .unwrap()
+                .len(),
+            5
+        );
+
+        let applied = db.apply_retention().await.unwrap();
+        assert_eq!(applied[0].count, 2);
+        let remaining = db.get_records_by_type("gitlab_pipeline").await.unwrap();
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(db.get_records_by_type("log").await.unwrap().len(), 1);
+
+        // Disabled rules are skipped
+        db.save_retention_rule(SaveRetentionRuleRequest {
+            id: Some(rule.id.clone()),
+            record_type: Some("gitlab_pipeline".to_string()),
+            source: None,
+            max_age_days: None,
+            max_count: Some(1),
+            enabled: false,
+        })
+        .await
+        .unwrap();
+        assert!(db.preview_retention().await.unwrap().is_empty());
+
+        db.delete_retention_rule(&rule.id).await.unwrap();
+        assert!(db.list_retention_rules().await.unwrap().is_empty());
+    }
+}
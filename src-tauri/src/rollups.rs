@@ -0,0 +1,629 @@
+// Time-series rollups
+//
+// Persisted rules in the `rollup_rules` table that summarise high-frequency
+// metric records (Prometheus samples, weather readings, ...) into hourly or
+// daily rows with count/min/max/avg/sum per numeric data field. Summary rows
+// are ordinary records of type `<record_type>_hourly` / `<record_type>_daily`,
+// so charts can query them like any other record. Once a period is rolled up,
+// raw points older than the rule's retention window are pruned.
+//
+// Rules run hourly as the `rollups` job. Each rule only rolls up completed
+// periods and remembers how far it got, so every period is summarised once;
+// raw points that arrive for an already rolled-up period are not included.
+
+use crate::audit::Actor;
+use crate::db::{Database, StagedRecord};
+use crate::error::AppError;
+use crate::scheduler::{Job, Schedule};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::Mutex;
+
+/// How often rollup rules are applied
+const ROLLUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Raw records read per query while rolling up
+const ROLLUP_CHUNK_SIZE: usize = 1000;
+
+// ============================================================================
+// Rollup Rule Model
+// ============================================================================
+
+/// Period a summary row covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RollupResolution {
+    Hour,
+    Day,
+}
+
+impl RollupResolution {
+    fn duration(self) -> Duration {
+        match self {
+            RollupResolution::Hour => Duration::hours(1),
+            RollupResolution::Day => Duration::days(1),
+        }
+    }
+
+    /// Start of the period containing `timestamp`
+    fn floor(self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        timestamp
+            .duration_trunc(self.duration())
+            .unwrap_or(timestamp)
+    }
+
+    /// Record type of the summary rows
+    fn summary_type(self, record_type: &str) -> String {
+        match self {
+            RollupResolution::Hour => format!("{}_hourly", record_type),
+            RollupResolution::Day => format!("{}_daily", record_type),
+        }
+    }
+}
+
+/// Rollup rule as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RollupRuleRecord {
+    pub id: Thing,
+    pub record_type: String,
+    pub source: Option<String>,
+    pub fields: Vec<String>,
+    pub resolution: RollupResolution,
+    pub raw_retention_days: Option<i64>,
+    pub enabled: bool,
+    pub rolled_up_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// User-facing rollup rule with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollupRule {
+    pub id: String,
+    pub record_type: String,
+    /// Limit the rule to one source (all sources otherwise)
+    pub source: Option<String>,
+    /// Numeric data fields to summarise, e.g. "temperature" or "main.humidity"
+    pub fields: Vec<String>,
+    pub resolution: RollupResolution,
+    /// Delete rolled-up raw points older than this many days (keep them otherwise)
+    pub raw_retention_days: Option<i64>,
+    pub enabled: bool,
+    /// End of the last period that was rolled up
+    pub rolled_up_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<RollupRuleRecord> for RollupRule {
+    fn from(record: RollupRuleRecord) -> Self {
+        RollupRule {
+            id: record.id.id.to_raw(),
+            record_type: record.record_type,
+            source: record.source,
+            fields: record.fields,
+            resolution: record.resolution,
+            raw_retention_days: record.raw_retention_days,
+            enabled: record.enabled,
+            rolled_up_until: record.rolled_up_until,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+/// Create (no id) or update (with id) a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveRollupRuleRequest {
+    pub id: Option<String>,
+    pub record_type: String,
+    pub source: Option<String>,
+    pub fields: Vec<String>,
+    pub resolution: RollupResolution,
+    pub raw_retention_days: Option<i64>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// What one rule did during a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollupOutcome {
+    pub rule_id: String,
+    pub record_type: String,
+    pub source: Option<String>,
+    /// Summary rows written
+    pub buckets: usize,
+    /// Raw points deleted
+    pub pruned: usize,
+    pub rolled_up_until: Option<DateTime<Utc>>,
+}
+
+/// Raw point as read for rolling up
+#[derive(Debug, Deserialize)]
+struct RawPoint {
+    source: String,
+    timestamp: DateTime<Utc>,
+    data: serde_json::Value,
+}
+
+/// Running statistics of one field within one period
+#[derive(Debug, Clone, Copy)]
+struct FieldStats {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl FieldStats {
+    fn add(stats: &mut Option<FieldStats>, value: f64) {
+        match stats {
+            Some(s) => {
+                s.count += 1;
+                s.sum += value;
+                s.min = s.min.min(value);
+                s.max = s.max.max(value);
+            }
+            None => {
+                *stats = Some(FieldStats {
+                    count: 1,
+                    sum: value,
+                    min: value,
+                    max: value,
+                })
+            }
+        }
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "count": self.count,
+            "sum": self.sum,
+            "min": self.min,
+            "max": self.max,
+            "avg": self.sum / self.count as f64,
+        })
+    }
+}
+
+/// One period of one source
+#[derive(Debug)]
+struct Bucket {
+    count: usize,
+    fields: Vec<Option<FieldStats>>,
+}
+
+fn validate_fields(fields: &[String]) -> Result<(), AppError> {
+    if fields.is_empty() {
+        return Err(AppError::Validation(
+            "A rollup rule needs at least one field".to_string(),
+        ));
+    }
+    for field in fields {
+        let valid = field.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        if !valid {
+            return Err(AppError::Validation(format!("Invalid field: {}", field)));
+        }
+    }
+    Ok(())
+}
+
+/// Numeric value at a dotted data path
+fn field_value(data: &serde_json::Value, path: &str) -> Option<f64> {
+    path.split('.')
+        .try_fold(data, |current, key| current.get(key))?
+        .as_f64()
+}
+
+// ============================================================================
+// Rollup Operations
+// ============================================================================
+
+impl Database {
+    pub async fn list_rollup_rules(&self) -> Result<Vec<RollupRule>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM rollup_rules ORDER BY created_at ASC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query rollup rules: {}", e)))?;
+
+        let rules: Vec<RollupRuleRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse rollup rules: {}", e)))?;
+
+        Ok(rules.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Create or update a rule
+    /// Changing the record type, source or resolution starts the rollup over.
+    pub async fn save_rollup_rule(
+        &self,
+        req: SaveRollupRuleRequest,
+    ) -> Result<RollupRule, AppError> {
+        if req.record_type.trim().is_empty() {
+            return Err(AppError::Validation(
+                "A rollup rule needs a record_type".to_string(),
+            ));
+        }
+        validate_fields(&req.fields)?;
+        if req.raw_retention_days.is_some_and(|days| days <= 0) {
+            return Err(AppError::Validation(
+                "raw_retention_days must be positive".to_string(),
+            ));
+        }
+
+        let source = req.source.filter(|s| !s.is_empty());
+        let now = Utc::now();
+        let (id, created_at, rolled_up_until) = match &req.id {
+            Some(id) => {
+                let key = id.strip_prefix("rollup_rules:").unwrap_or(id).to_string();
+                let existing: Option<RollupRuleRecord> = self
+                    .db
+                    .select(("rollup_rules", key.as_str()))
+                    .await
+                    .map_err(|e| AppError::Database(format!("Failed to get rollup rule: {}", e)))?;
+                let existing =
+                    existing.ok_or_else(|| AppError::NotFound(format!("Rollup rule {}", id)))?;
+                let same_series = existing.record_type == req.record_type
+                    && existing.source == source
+                    && existing.resolution == req.resolution;
+                (
+                    key,
+                    existing.created_at,
+                    existing.rolled_up_until.filter(|_| same_series),
+                )
+            }
+            None => (uuid::Uuid::new_v4().to_string(), now, None),
+        };
+
+        let record = RollupRuleRecord {
+            id: Thing::from(("rollup_rules", id.as_str())),
+            record_type: req.record_type,
+            source,
+            fields: req.fields,
+            resolution: req.resolution,
+            raw_retention_days: req.raw_retention_days,
+            enabled: req.enabled,
+            rolled_up_until,
+            created_at,
+            updated_at: now,
+        };
+
+        let saved: Option<RollupRuleRecord> = self
+            .db
+            .upsert(("rollup_rules", id.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save rollup rule: {}", e)))?;
+
+        saved
+            .map(|r| r.into())
+            .ok_or_else(|| AppError::Database("Failed to save rollup rule".to_string()))
+    }
+
+    /// Delete a rule (summary rows already written are kept)
+    pub async fn delete_rollup_rule(&self, id: &str) -> Result<(), AppError> {
+        let key = id.strip_prefix("rollup_rules:").unwrap_or(id);
+        let _: Option<RollupRuleRecord> = self
+            .db
+            .delete(("rollup_rules", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete rollup rule: {}", e)))?;
+        Ok(())
+    }
+
+    /// Roll up completed periods and prune raw points for all enabled rules
+    pub async fn run_rollups(&self) -> Result<Vec<RollupOutcome>, AppError> {
+        self.run_rollups_at(Utc::now()).await
+    }
+
+    async fn run_rollups_at(&self, now: DateTime<Utc>) -> Result<Vec<RollupOutcome>, AppError> {
+        let mut rules: Vec<RollupRule> = self
+            .list_rollup_rules()
+            .await?
+            .into_iter()
+            .filter(|r| r.enabled)
+            .collect();
+
+        let mut outcomes = Vec::new();
+        for rule in rules.iter_mut() {
+            let until = rule.resolution.floor(now);
+            let buckets = self.roll_up(rule, until).await?;
+            if rule.rolled_up_until.is_none_or(|done| done < until) {
+                self.query("UPDATE type::thing('rollup_rules', $id) SET rolled_up_until = $until")
+                    .bind(("id", rule.id.clone()))
+                    .bind(("until", until))
+                    .await
+                    .map_err(|e| {
+                        AppError::Database(format!("Failed to update rollup rule: {}", e))
+                    })?
+                    .check()
+                    .map_err(|e| {
+                        AppError::Database(format!("Failed to update rollup rule: {}", e))
+                    })?;
+                rule.rolled_up_until = Some(until);
+            }
+
+            outcomes.push(RollupOutcome {
+                rule_id: rule.id.clone(),
+                record_type: rule.record_type.clone(),
+                source: rule.source.clone(),
+                buckets,
+                pruned: 0,
+                rolled_up_until: rule.rolled_up_until,
+            });
+        }
+
+        // Prune after all rules ran, so a raw point is only deleted once every
+        // rule covering it has rolled it up
+        for (rule, outcome) in rules.iter().zip(outcomes.iter_mut()) {
+            let Some(days) = rule.raw_retention_days else {
+                continue;
+            };
+            let covered_until = rules
+                .iter()
+                .filter(|other| {
+                    other.record_type == rule.record_type
+                        && (other.source.is_none()
+                            || rule.source.is_none()
+                            || other.source == rule.source)
+                })
+                .filter_map(|other| other.rolled_up_until)
+                .min();
+            let Some(covered_until) = covered_until else {
+                continue;
+            };
+            let cutoff = covered_until.min(now - Duration::days(days));
+            outcome.pruned = self.prune_raw_points(rule, cutoff).await?;
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Write summary rows for the periods between the rule's progress and `until`
+    async fn roll_up(&self, rule: &RollupRule, until: DateTime<Utc>) -> Result<usize, AppError> {
+        let mut conditions = vec!["record_type = $record_type", "timestamp < $until"];
+        if rule.source.is_some() {
+            conditions.push("source = $source");
+        }
+        if rule.rolled_up_until.is_some() {
+            conditions.push("timestamp >= $from");
+        }
+        let statement = format!(
+            "SELECT source, timestamp, data FROM records WHERE {} ORDER BY timestamp ASC LIMIT $limit START $start",
+            conditions.join(" AND ")
+        );
+
+        let mut buckets: BTreeMap<(String, DateTime<Utc>), Bucket> = BTreeMap::new();
+        let mut start = 0;
+        loop {
+            let mut result = self
+                .query(statement.as_str())
+                .bind(("record_type", rule.record_type.clone()))
+                .bind(("source", rule.source.clone()))
+                .bind(("from", rule.rolled_up_until))
+                .bind(("until", until))
+                .bind(("limit", ROLLUP_CHUNK_SIZE))
+                .bind(("start", start))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to read raw points: {}", e)))?;
+            let points: Vec<RawPoint> = result
+                .take(0)
+                .map_err(|e| AppError::Database(format!("Failed to parse raw points: {}", e)))?;
+
+            for point in &points {
+                let bucket = buckets
+                    .entry((point.source.clone(), rule.resolution.floor(point.timestamp)))
+                    .or_insert_with(|| Bucket {
+                        count: 0,
+                        fields: vec![None; rule.fields.len()],
+                    });
+                bucket.count += 1;
+                for (field, stats) in rule.fields.iter().zip(bucket.fields.iter_mut()) {
+                    if let Some(value) = field_value(&point.data, field) {
+                        FieldStats::add(stats, value);
+                    }
+                }
+            }
+
+            start += points.len();
+            if points.len() < ROLLUP_CHUNK_SIZE {
+                break;
+            }
+        }
+
+        let summary_type = rule.resolution.summary_type(&rule.record_type);
+        let summaries: Vec<StagedRecord> = buckets
+            .into_iter()
+            .map(|((source, bucket_start), bucket)| {
+                let mut data = serde_json::Map::new();
+                data.insert("bucket".to_string(), bucket_start.to_rfc3339().into());
+                data.insert(
+                    "bucket_end".to_string(),
+                    (bucket_start + rule.resolution.duration())
+                        .to_rfc3339()
+                        .into(),
+                );
+                data.insert("count".to_string(), bucket.count.into());
+                for (field, stats) in rule.fields.iter().zip(bucket.fields) {
+                    if let Some(stats) = stats {
+                        data.insert(field.clone(), stats.to_json());
+                    }
+                }
+
+                let mut record = StagedRecord::new(
+                    summary_type.clone(),
+                    source,
+                    serde_json::Value::Object(data),
+                );
+                record.timestamp = bucket_start;
+                record
+            })
+            .collect();
+
+        if summaries.is_empty() {
+            return Ok(0);
+        }
+        let written = self.upsert_records_batch(summaries, Some("bucket")).await?;
+        tracing::info!(
+            "Rollup rule {} wrote {} {} rows",
+            rule.id,
+            written,
+            summary_type
+        );
+        Ok(written)
+    }
+
+    /// Delete the rule's raw points older than `cutoff`
+    async fn prune_raw_points(
+        &self,
+        rule: &RollupRule,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, AppError> {
+        let mut filter = "record_type = $record_type AND timestamp < $cutoff".to_string();
+        if rule.source.is_some() {
+            filter.push_str(" AND source = $source");
+        }
+
+        let mut result = self
+            .query(format!(
+                "SELECT VALUE id FROM records WHERE {filter}; DELETE records WHERE {filter};"
+            ))
+            .bind(("record_type", rule.record_type.clone()))
+            .bind(("source", rule.source.clone()))
+            .bind(("cutoff", cutoff))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to prune raw points: {}", e)))?;
+        let pruned: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to prune raw points: {}", e)))?;
+        result
+            .check()
+            .map_err(|e| AppError::Database(format!("Failed to prune raw points: {}", e)))?;
+
+        if !pruned.is_empty() {
+            tracing::info!("Rollup rule {} pruned {} raw points", rule.id, pruned.len());
+        }
+        Ok(pruned.len())
+    }
+}
+
+// ============================================================================
+// Scheduled Job
+// ============================================================================
+
+/// Job applying the rollup rules, hourly by default
+pub fn job(database: Arc<Mutex<Database>>) -> Job {
+    Job::new(
+        "rollups",
+        "Roll up metric records and prune raw points",
+        Schedule::every(ROLLUP_INTERVAL),
+        move || {
+            let database = database.clone();
+            async move {
+                let db = database.lock().await;
+                let outcomes = db.as_actor(Actor::job("rollups"), db.run_rollups()).await?;
+                drop(db);
+                let buckets: usize = outcomes.iter().map(|o| o.buckets).sum();
+                let pruned: usize = outcomes.iter().map(|o| o.pruned).sum();
+                Ok(format!(
+                    "Wrote {} summary rows, pruned {} raw points",
+                    buckets, pruned
+                ))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_rollup_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        // Readings every 20 minutes from 10:00 to 12:40
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap();
+        for i in 0..9 {
+            let mut record = StagedRecord::new(
+                "weather".to_string(),
+                "station".to_string(),
+                serde_json::json!({ "id": i, "main": { "temp": 10 + i } }),
+            );
+            record.timestamp = start + Duration::minutes(20 * i);
+            db.upsert_record(record).await.unwrap();
+        }
+
+        assert!(db
+            .save_rollup_rule(SaveRollupRuleRequest {
+                id: None,
+                record_type: "weather".to_string(),
+                source: None,
+                fields: vec!["main.temp; DELETE records".to_string()],
+                resolution: RollupResolution::Hour,
+                raw_retention_days: None,
+                enabled: true,
+            })
+            .await
+            .is_err());
+
+        let rule = db
+            .save_rollup_rule(SaveRollupRuleRequest {
+                id: None,
+                record_type: "weather".to_string(),
+                source: None,
+                fields: vec!["main.temp".to_string()],
+                resolution: RollupResolution::Hour,
+                raw_retention_days: Some(1),
+                enabled: true,
+            })
+            .await
+            .unwrap();
+
+        // At 12:30 only the 10:00 and 11:00 hours are complete; nothing is
+        // older than a day yet
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+        let outcomes = db.run_rollups_at(now).await.unwrap();
+        assert_eq!(outcomes[0].rule_id, rule.id);
+        assert_eq!(outcomes[0].buckets, 2);
+        assert_eq!(outcomes[0].pruned, 0);
+
+        let mut hourly = db.get_records_by_type("weather_hourly").await.unwrap();
+        hourly.sort_by_key(|r| r.timestamp);
+        assert_eq!(hourly.len(), 2);
+        assert_eq!(hourly[0].timestamp, start);
+        assert_eq!(hourly[0].data["count"], 3);
+        assert_eq!(hourly[0].data["main.temp"]["min"], 10.0);
+        assert_eq!(hourly[0].data["main.temp"]["max"], 12.0);
+        assert_eq!(hourly[1].data["main.temp"]["avg"], 14.0);
+
+        // Two days later the 12:00 hour is rolled up and all raw points pruned
+        let later = now + Duration::days(2);
+        let outcomes = db.run_rollups_at(later).await.unwrap();
+        assert_eq!(outcomes[0].buckets, 1);
+        assert_eq!(outcomes[0].pruned, 9);
+        assert!(db.get_records_by_type("weather").await.unwrap().is_empty());
+        assert_eq!(
+            db.get_records_by_type("weather_hourly")
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+
+        db.delete_rollup_rule(&rule.id).await.unwrap();
+        assert!(db.list_rollup_rules().await.unwrap().is_empty());
+    }
+}
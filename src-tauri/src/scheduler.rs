@@ -0,0 +1,578 @@
+// Background job scheduler
+//
+// Recurring background work runs as named jobs: data source polling,
+// retention cleanup, metric rollups, backups and alert evaluation. Each job
+// has a schedule, an interval or a cron expression, which can be changed from
+// its default; jobs can be paused and run on demand, and remember how their
+// last run went. Schedule changes, pause state and the last run are persisted
+// in the `scheduled_jobs` table, so they survive restarts.
+//
+// Cron expressions have five fields (minute, hour, day of month, month, day
+// of week with 0 or 7 for Sunday), or an alias like `@daily`, and follow the
+// local time. A cron job whose time passed while the app was closed runs once
+// at startup.
+
+use crate::db::Database;
+use crate::error::AppError;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::Mutex;
+
+/// How often the scheduler looks for due jobs
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<String, AppError>> + Send>>;
+type JobFn = Arc<dyn Fn() -> JobFuture + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Schedule {
+    /// Every `secs` seconds, counted from the start of the last run
+    Interval {
+        secs: u64,
+    },
+    Cron {
+        expression: String,
+    },
+}
+
+impl Schedule {
+    pub fn every(interval: std::time::Duration) -> Self {
+        Schedule::Interval {
+            secs: interval.as_secs(),
+        }
+    }
+
+    fn parse_cron(expression: &str) -> Result<croner::Cron, AppError> {
+        croner::Cron::new(expression).parse().map_err(|e| {
+            AppError::Validation(format!("Invalid cron expression {:?}: {}", expression, e))
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        match self {
+            Schedule::Interval { secs: 0 } => Err(AppError::Validation(
+                "The interval must be at least one second".to_string(),
+            )),
+            Schedule::Interval { .. } => Ok(()),
+            Schedule::Cron { expression } => Self::parse_cron(expression).map(|_| ()),
+        }
+    }
+
+    /// When a job that last started at `last_run` is due next; interval jobs
+    /// that never ran are due right away
+    pub fn next_run(
+        &self,
+        last_run: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Interval { secs } => {
+                Some(last_run.map_or(now, |last| last + chrono::Duration::seconds(*secs as i64)))
+            }
+            Schedule::Cron { expression } => {
+                let after = last_run.unwrap_or(now).with_timezone(&Local);
+                Self::parse_cron(expression)
+                    .ok()?
+                    .find_next_occurrence(&after, false)
+                    .ok()
+                    .map(|next| next.with_timezone(&Utc))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Success,
+    Failed,
+}
+
+/// Outcome of a job run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRun {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub status: JobStatus,
+    /// What the job did, or why it failed
+    pub message: String,
+}
+
+/// A recurring job as registered by the module doing the work
+pub struct Job {
+    id: &'static str,
+    description: &'static str,
+    schedule: Schedule,
+    run: JobFn,
+}
+
+impl Job {
+    /// `run` returns a short summary of what it did
+    pub fn new<F, Fut>(
+        id: &'static str,
+        description: &'static str,
+        schedule: Schedule,
+        run: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, AppError>> + Send + 'static,
+    {
+        Self {
+            id,
+            description,
+            schedule,
+            run: Arc::new(move || Box::pin(run())),
+        }
+    }
+}
+
+/// User-facing job status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub description: String,
+    pub schedule: Schedule,
+    pub default_schedule: Schedule,
+    pub paused: bool,
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<JobRun>,
+}
+
+/// Persisted job state as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JobStateRecord {
+    pub id: Thing,
+    /// Schedule replacing the job's default
+    pub schedule: Option<Schedule>,
+    pub paused: bool,
+    pub last_run: Option<JobRun>,
+    pub updated_at: DateTime<Utc>,
+}
+
+struct JobEntry {
+    description: &'static str,
+    default_schedule: Schedule,
+    schedule: Schedule,
+    paused: bool,
+    running: bool,
+    next_run: Option<DateTime<Utc>>,
+    last_run: Option<JobRun>,
+    run: JobFn,
+}
+
+impl JobEntry {
+    fn info(&self, id: &str) -> JobInfo {
+        JobInfo {
+            id: id.to_string(),
+            description: self.description.to_string(),
+            schedule: self.schedule.clone(),
+            default_schedule: self.default_schedule.clone(),
+            paused: self.paused,
+            running: self.running,
+            next_run: self.next_run,
+            last_run: self.last_run.clone(),
+        }
+    }
+
+    fn state_record(&self, id: &str) -> JobStateRecord {
+        JobStateRecord {
+            id: Thing::from(("scheduled_jobs", id)),
+            schedule: (self.schedule != self.default_schedule).then(|| self.schedule.clone()),
+            paused: self.paused,
+            last_run: self.last_run.clone(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Take over persisted state
+    fn restore(&mut self, record: Option<JobStateRecord>, now: DateTime<Utc>) {
+        let (schedule, paused, last_run) = match record {
+            Some(record) => (record.schedule, record.paused, record.last_run),
+            None => (None, false, None),
+        };
+        self.schedule = schedule
+            .filter(|schedule| schedule.validate().is_ok())
+            .unwrap_or_else(|| self.default_schedule.clone());
+        self.paused = paused;
+        self.last_run = last_run;
+        self.reschedule(now);
+    }
+
+    fn reschedule(&mut self, now: DateTime<Utc>) {
+        let last_started = self.last_run.as_ref().map(|run| run.started_at);
+        self.next_run = self.schedule.next_run(last_started, now);
+    }
+}
+
+// ============================================================================
+// Job State Operations
+// ============================================================================
+
+impl Database {
+    async fn load_job_state(&self, id: &str) -> Result<Option<JobStateRecord>, AppError> {
+        self.db
+            .select(("scheduled_jobs", id))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get job state: {}", e)))
+    }
+
+    async fn save_job_state(&self, record: JobStateRecord) -> Result<(), AppError> {
+        let key = record.id.id.to_raw();
+        let _: Option<JobStateRecord> = self
+            .db
+            .upsert(("scheduled_jobs", key.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save job state: {}", e)))?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// Runs the registered jobs on their schedules
+#[derive(Clone)]
+pub struct JobScheduler {
+    jobs: Arc<Mutex<BTreeMap<&'static str, JobEntry>>>,
+    database: Arc<Mutex<Database>>,
+    /// Set once the loop running due jobs was spawned
+    started: Arc<AtomicBool>,
+}
+
+impl JobScheduler {
+    pub fn new(database: Arc<Mutex<Database>>) -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(BTreeMap::new())),
+            database,
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    async fn load_state(&self, id: &str) -> Option<JobStateRecord> {
+        match self.database.lock().await.load_job_state(id).await {
+            Ok(record) => record,
+            Err(e) => {
+                tracing::warn!("Failed to load the state of job {}: {}", id, e);
+                None
+            }
+        }
+    }
+
+    async fn save_state(&self, record: JobStateRecord) {
+        let id = record.id.id.to_raw();
+        if let Err(e) = self.database.lock().await.save_job_state(record).await {
+            tracing::warn!("Failed to save the state of job {}: {}", id, e);
+        }
+    }
+
+    /// Add a job, taking over its persisted schedule, pause state and last run
+    pub async fn register(&self, job: Job) {
+        let record = self.load_state(job.id).await;
+        let mut entry = JobEntry {
+            description: job.description,
+            default_schedule: job.schedule.clone(),
+            schedule: job.schedule,
+            paused: false,
+            running: false,
+            next_run: None,
+            last_run: None,
+            run: job.run,
+        };
+        entry.restore(record, Utc::now());
+        self.jobs.lock().await.insert(job.id, entry);
+    }
+
+    /// Re-read the persisted job state (after the workspace database changed)
+    pub async fn reload(&self) {
+        let ids: Vec<&'static str> = self.jobs.lock().await.keys().copied().collect();
+        for id in ids {
+            let record = self.load_state(id).await;
+            if let Some(entry) = self.jobs.lock().await.get_mut(id) {
+                entry.restore(record, Utc::now());
+            }
+        }
+    }
+
+    pub async fn list(&self) -> Vec<JobInfo> {
+        self.jobs
+            .lock()
+            .await
+            .iter()
+            .map(|(id, entry)| entry.info(id))
+            .collect()
+    }
+
+    /// Change a job and persist its state
+    async fn update(
+        &self,
+        id: &str,
+        change: impl FnOnce(&mut JobEntry),
+    ) -> Result<JobInfo, AppError> {
+        let (info, record) = {
+            let mut jobs = self.jobs.lock().await;
+            let entry = jobs
+                .get_mut(id)
+                .ok_or_else(|| AppError::NotFound(format!("Job {}", id)))?;
+            change(entry);
+            (entry.info(id), entry.state_record(id))
+        };
+        self.save_state(record).await;
+        Ok(info)
+    }
+
+    pub async fn pause(&self, id: &str) -> Result<JobInfo, AppError> {
+        let info = self.update(id, |entry| entry.paused = true).await?;
+        tracing::info!("Paused job {}", id);
+        Ok(info)
+    }
+
+    pub async fn resume(&self, id: &str) -> Result<JobInfo, AppError> {
+        let info = self.update(id, |entry| entry.paused = false).await?;
+        tracing::info!("Resumed job {}", id);
+        Ok(info)
+    }
+
+    /// Replace a job's schedule; `None` goes back to the default
+    pub async fn set_schedule(
+        &self,
+        id: &str,
+        schedule: Option<Schedule>,
+    ) -> Result<JobInfo, AppError> {
+        if let Some(schedule) = &schedule {
+            schedule.validate()?;
+        }
+        self.update(id, |entry| {
+            entry.schedule = schedule.unwrap_or_else(|| entry.default_schedule.clone());
+            entry.reschedule(Utc::now());
+        })
+        .await
+    }
+
+    /// Run a job right away (also when paused) and wait for the outcome
+    pub async fn run_now(&self, id: &str) -> Result<JobRun, AppError> {
+        let run = {
+            let mut jobs = self.jobs.lock().await;
+            let entry = jobs
+                .get_mut(id)
+                .ok_or_else(|| AppError::NotFound(format!("Job {}", id)))?;
+            if entry.running {
+                return Err(AppError::Validation(format!(
+                    "Job {} is already running",
+                    id
+                )));
+            }
+            entry.running = true;
+            entry.run.clone()
+        };
+        Ok(self.execute(id, run).await)
+    }
+
+    /// Jobs due at `now`, marked as running
+    async fn take_due(&self, now: DateTime<Utc>) -> Vec<(&'static str, JobFn)> {
+        let mut jobs = self.jobs.lock().await;
+        jobs.iter_mut()
+            .filter(|(_, entry)| {
+                !entry.paused && !entry.running && entry.next_run.is_some_and(|next| next <= now)
+            })
+            .map(|(id, entry)| {
+                entry.running = true;
+                (*id, entry.run.clone())
+            })
+            .collect()
+    }
+
+    async fn execute(&self, id: &str, run: JobFn) -> JobRun {
+        let started_at = Utc::now();
+        let started = std::time::Instant::now();
+        let result = run().await;
+        let job_run = JobRun {
+            started_at,
+            duration_ms: started.elapsed().as_millis() as u64,
+            status: if result.is_ok() {
+                JobStatus::Success
+            } else {
+                JobStatus::Failed
+            },
+            message: match result {
+                Ok(summary) => summary,
+                Err(e) => e.to_string(),
+            },
+        };
+        if job_run.status == JobStatus::Failed {
+            tracing::warn!("Job {} failed: {}", id, job_run.message);
+        }
+
+        let record = {
+            let mut jobs = self.jobs.lock().await;
+            let Some(entry) = jobs.get_mut(id) else {
+                return job_run;
+            };
+            entry.running = false;
+            entry.last_run = Some(job_run.clone());
+            entry.reschedule(Utc::now());
+            entry.state_record(id)
+        };
+        self.save_state(record).await;
+        job_run
+    }
+
+    /// Whether the loop running due jobs was started
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Relaxed)
+    }
+
+    /// Spawn the loop running due jobs
+    pub fn start(&self) {
+        self.started.store(true, Ordering::Relaxed);
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            tracing::info!("Job scheduler started");
+            let mut ticker = tokio::time::interval(TICK_INTERVAL);
+
+            loop {
+                ticker.tick().await;
+
+                for (id, run) in scheduler.take_due(Utc::now()).await {
+                    let scheduler = scheduler.clone();
+                    tokio::spawn(async move {
+                        scheduler.execute(id, run).await;
+                    });
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Timelike};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_schedule_next_run() {
+        let now = Utc::now();
+        let hourly = Schedule::every(std::time::Duration::from_secs(3600));
+        assert_eq!(hourly.next_run(None, now), Some(now));
+        assert_eq!(
+            hourly.next_run(Some(now), now),
+            Some(now + Duration::hours(1))
+        );
+        assert!(Schedule::Interval { secs: 0 }.validate().is_err());
+
+        let nightly = Schedule::Cron {
+            expression: "30 3 * * *".to_string(),
+        };
+        assert!(nightly.validate().is_ok());
+        let last = Local
+            .with_ymd_and_hms(2024, 5, 1, 3, 30, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        let next = nightly.next_run(Some(last), now).unwrap();
+        assert_eq!(next - last, Duration::days(1));
+        let next = nightly.next_run(None, now).unwrap().with_timezone(&Local);
+        assert!(next > now && (next.hour(), next.minute()) == (3, 30));
+
+        assert!(Schedule::Cron {
+            expression: "61 * * * *".to_string()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_job_scheduler() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(Mutex::new(
+            Database::new(temp_dir.path().to_path_buf()).await.unwrap(),
+        ));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let job = |runs: Arc<AtomicUsize>| {
+            Job::new(
+                "cleanup",
+                "Clean up",
+                Schedule::every(std::time::Duration::from_secs(60)),
+                move || {
+                    let runs = runs.clone();
+                    async move {
+                        match runs.fetch_add(1, Ordering::SeqCst) {
+                            0 => Ok("Removed 3 records".to_string()),
+                            _ => Err(AppError::Database("disk full".to_string())),
+                        }
+                    }
+                },
+            )
+        };
+
+        let scheduler = JobScheduler::new(database.clone());
+        scheduler.register(job(runs.clone())).await;
+        assert!(scheduler.run_now("missing").await.is_err());
+
+        // Due right away, then not again within the interval
+        let due = scheduler.take_due(Utc::now()).await;
+        assert_eq!(due.len(), 1);
+        assert!(scheduler.take_due(Utc::now()).await.is_empty());
+        let (id, run) = due.into_iter().next().unwrap();
+        let job_run = scheduler.execute(id, run).await;
+        assert_eq!(job_run.status, JobStatus::Success);
+        assert_eq!(job_run.message, "Removed 3 records");
+        assert!(scheduler.take_due(Utc::now()).await.is_empty());
+        assert_eq!(
+            scheduler.list().await[0].next_run,
+            Some(job_run.started_at + Duration::seconds(60))
+        );
+
+        let cron = Schedule::Cron {
+            expression: "0 4 * * 1-5".to_string(),
+        };
+        assert!(scheduler
+            .set_schedule(
+                "cleanup",
+                Some(Schedule::Cron {
+                    expression: "bad".to_string()
+                })
+            )
+            .await
+            .is_err());
+        scheduler
+            .set_schedule("cleanup", Some(cron.clone()))
+            .await
+            .unwrap();
+        scheduler.pause("cleanup").await.unwrap();
+        assert_eq!(
+            scheduler.run_now("cleanup").await.unwrap().status,
+            JobStatus::Failed
+        );
+
+        // A restart keeps the schedule, pause state and last run
+        let restarted = JobScheduler::new(database);
+        restarted.register(job(runs.clone())).await;
+        let info = &restarted.list().await[0];
+        assert_eq!(info.schedule, cron);
+        assert!(info.paused);
+        assert_eq!(info.last_run.as_ref().unwrap().message, "disk full");
+        assert!(restarted
+            .take_due(Utc::now() + Duration::days(7))
+            .await
+            .is_empty());
+
+        restarted.resume("cleanup").await.unwrap();
+        let info = restarted.set_schedule("cleanup", None).await.unwrap();
+        assert_eq!(info.schedule, info.default_schedule);
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,232 @@
+// Full-text search over staged records
+//
+// A `search_text` field on the records table is computed by SurrealDB on every
+// write from the title, description, tags and a few common data fields, and
+// indexed with a BM25 full-text index. Searches return ranked, highlighted
+// and paginated results.
+
+use crate::db::{Database, RecordMetadata, StagedRecord};
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+/// Default page size for search results
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Data fields included in the search text besides title, description and tags
+const SEARCHABLE_DATA_FIELDS: &[&str] = &[
+    "name",
+    "title",
+    "summary",
+    "description",
+    "text",
+    "body",
+    "message",
+    "content",
+];
+
+// ============================================================================
+// Search Models
+// ============================================================================
+
+/// Optional filters narrowing a search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+    pub record_type: Option<String>,
+    pub source: Option<String>,
+    /// Records must carry all of these tags
+    pub tags: Vec<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Search hit as returned by the database (with Thing ID)
+#[derive(Debug, Clone, Deserialize)]
+struct SearchHitRecord {
+    id: Thing,
+    record_type: String,
+    source: String,
+    timestamp: DateTime<Utc>,
+    data: serde_json::Value,
+    metadata: RecordMetadata,
+    score: Option<f64>,
+    highlight: Option<String>,
+}
+
+/// A ranked search hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub record: StagedRecord,
+    pub score: f64,
+    /// Matched text with terms wrapped in `<mark>` tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+}
+
+impl From<SearchHitRecord> for SearchHit {
+    fn from(hit: SearchHitRecord) -> Self {
+        SearchHit {
+            record: StagedRecord {
+                id: Some(hit.id),
+                record_type: hit.record_type,
+                source: hit.source,
+                timestamp: hit.timestamp,
+                data: hit.data,
+                metadata: hit.metadata,
+            },
+            score: hit.score.unwrap_or(0.0),
+            highlight: hit.highlight,
+        }
+    }
+}
+
+/// One page of search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+}
+
+/// Search field, analyzer and index definitions (applied as a schema migration)
+/// Existing records are touched once so their search text gets computed
+pub fn search_schema() -> String {
+    let data_fields: Vec<String> = SEARCHABLE_DATA_FIELDS
+        .iter()
+        .map(|f| format!("data.{}", f))
+        .collect();
+
+    format!(
+        "DEFINE ANALYZER OVERWRITE record_search TOKENIZERS class FILTERS lowercase, ascii, snowball(english);
+         DEFINE FIELD OVERWRITE search_text ON records VALUE array::join(array::filter(
+             [metadata.title, metadata.description, array::join(metadata.tags ?? [], ' '), {}],
+             |$v| type::is::string($v)
+         ), ' ');
+         DEFINE INDEX OVERWRITE record_search ON records FIELDS search_text SEARCH ANALYZER record_search BM25 HIGHLIGHTS;
+         UPDATE records;",
+        data_fields.join(", ")
+    )
+}
+
+// ============================================================================
+// Search Operations
+// ============================================================================
+
+impl Database {
+    /// Ranked full-text search over staged records
+    pub async fn search_records(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+    ) -> Result<SearchResults, AppError> {
+        let mut conditions = vec!["search_text @1@ $query".to_string()];
+        if filters.record_type.is_some() {
+            conditions.push("record_type = $record_type".to_string());
+        }
+        if filters.source.is_some() {
+            conditions.push("source = $source".to_string());
+        }
+        if !filters.tags.is_empty() {
+            conditions.push("metadata.tags CONTAINSALL $tags".to_string());
+        }
+        if filters.from.is_some() {
+            conditions.push("timestamp >= $from".to_string());
+        }
+        if filters.to.is_some() {
+            conditions.push("timestamp <= $to".to_string());
+        }
+        let where_clause = conditions.join(" AND ");
+
+        let statement = format!(
+            "SELECT *, search::score(1) AS score, search::highlight('<mark>', '</mark>', 1) AS highlight
+             FROM records WHERE {where_clause}
+             ORDER BY score DESC LIMIT $limit START $offset;
+             SELECT count() AS total FROM records WHERE {where_clause} GROUP ALL;"
+        );
+
+        let mut result = self
+            .query(statement)
+            .bind(("query", query.to_string()))
+            .bind(("record_type", filters.record_type))
+            .bind(("source", filters.source))
+            .bind(("tags", filters.tags))
+            .bind(("from", filters.from))
+            .bind(("to", filters.to))
+            .bind(("limit", filters.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)))
+            .bind(("offset", filters.offset.unwrap_or(0)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to search records: {}", e)))?;
+
+        let hits: Vec<SearchHitRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract search results: {}", e)))?;
+        let total: Option<usize> = result
+            .take((1, "total"))
+            .map_err(|e| AppError::Database(format!("Failed to count search results: {}", e)))?;
+
+        Ok(SearchResults {
+            total: total.unwrap_or(0),
+            hits: hits.into_iter().map(SearchHit::from).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_search_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let mut failed = StagedRecord::new(
+            "gitlab_pipeline".to_string(),
+            "ci".to_string(),
+            serde_json::json!({ "id": "1", "message": "Deploy to production failed" }),
+        );
+        failed.metadata.title = Some("Pipeline #1".to_string());
+        failed.metadata.tags = vec!["deploy".to_string()];
+
+        let mut passed = StagedRecord::new(
+            "gitlab_pipeline".to_string(),
+            "ci".to_string(),
+            serde_json::json!({ "id": "2", "message": "Unit tests passed", "count": 3 }),
+        );
+        passed.metadata.title = Some("Deploy preview".to_string());
+
+        db.upsert_record(failed).await.unwrap();
+        db.upsert_record(passed).await.unwrap();
+
+        let results = db
+            .search_records("deploy", SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(results.total, 2);
+
+        let results = db
+            .search_records(
+                "deploy",
+                SearchFilters {
+                    tags: vec!["deploy".to_string()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.total, 1);
+        assert_eq!(results.hits[0].record.data["id"], "1");
+        assert!(results.hits[0].highlight.is_some());
+
+        let results = db
+            .search_records("kubernetes", SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(results.total, 0);
+        assert!(results.hits.is_empty());
+    }
+}
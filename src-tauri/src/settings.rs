@@ -1,13 +1,23 @@
 // Settings management service
 // Handles CRUD operations for application settings
+//
+// Every setting is declared in a typed schema (key, type, default,
+// constraints). Core modules contribute their settings through
+// `core_settings`, plugins through the `settings` of their manifest (under
+// `plugins.<name>.`). Writes are validated against the schema, reads of unset
+// keys return the default, and `get_settings_schema` lets the UI build forms.
 
 use crate::db::Database;
 use crate::error::AppError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use surrealdb::sql::Thing;
 
+/// Owner of the settings registered by the app itself
+pub const CORE_OWNER: &str = "core";
+
 // ============================================================================
 // Settings Models
 // ============================================================================
@@ -52,6 +62,219 @@ impl From<SettingRecord> for Setting {
     }
 }
 
+// ============================================================================
+// Settings Schema
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingType {
+    String,
+    Number,
+    Boolean,
+    Json,
+}
+
+impl SettingType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SettingType::String => "string",
+            SettingType::Number => "number",
+            SettingType::Boolean => "boolean",
+            SettingType::Json => "json",
+        }
+    }
+
+    fn from_name(name: &str) -> Result<Self, AppError> {
+        match name {
+            "string" => Ok(SettingType::String),
+            "number" => Ok(SettingType::Number),
+            "boolean" => Ok(SettingType::Boolean),
+            "json" => Ok(SettingType::Json),
+            _ => Err(AppError::Config(format!(
+                "Invalid setting type: {}. Must be 'string', 'number', 'boolean', or 'json'",
+                name
+            ))),
+        }
+    }
+
+    /// Value of a stored setting string
+    fn parse(self, raw: &str) -> Option<Value> {
+        match self {
+            SettingType::String => Some(Value::String(raw.to_string())),
+            SettingType::Number => {
+                let raw = raw.trim();
+                match raw.parse::<i64>() {
+                    Ok(n) => Some(Value::from(n)),
+                    Err(_) => raw
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(Value::Number),
+                }
+            }
+            SettingType::Boolean => match raw.trim() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            SettingType::Json => serde_json::from_str(raw).ok(),
+        }
+    }
+
+    /// Whether a value is of this type
+    pub fn matches(self, value: &Value) -> bool {
+        match self {
+            SettingType::String => value.is_string(),
+            SettingType::Number => value.is_number(),
+            SettingType::Boolean => value.is_boolean(),
+            SettingType::Json => true,
+        }
+    }
+}
+
+/// String a setting value is stored as (strings as they are, the rest as JSON)
+fn stored_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// A declared setting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingDefinition {
+    pub key: String,
+    #[serde(rename = "type")]
+    pub setting_type: SettingType,
+    /// Value of the setting while unset (null: no value)
+    #[serde(default)]
+    pub default: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Bounds of a number setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Allowed values (any when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<Value>,
+    /// "core" or the plugin that declared the setting
+    #[serde(default)]
+    pub owner: String,
+}
+
+impl SettingDefinition {
+    pub fn new(key: &str, setting_type: SettingType, default: Value, category: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            setting_type,
+            default,
+            label: None,
+            description: None,
+            category: Some(category.to_string()),
+            min: None,
+            max: None,
+            options: Vec::new(),
+            owner: CORE_OWNER.to_string(),
+        }
+    }
+
+    pub fn describe(mut self, label: &str, description: &str) -> Self {
+        self.label = Some(label.to_string());
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn range(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn options(mut self, options: Vec<Value>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Check a value against the type and constraints (null always unsets)
+    pub fn validate(&self, value: &Value) -> Result<(), AppError> {
+        if value.is_null() {
+            return Ok(());
+        }
+        if !self.setting_type.matches(value) {
+            return Err(AppError::Validation(format!(
+                "Setting {} must be a {}, got {}",
+                self.key,
+                self.setting_type.as_str(),
+                value
+            )));
+        }
+        if let Some(number) = value.as_f64() {
+            if self.min.is_some_and(|min| number < min) || self.max.is_some_and(|max| number > max)
+            {
+                return Err(AppError::Validation(format!(
+                    "Setting {} must be between {} and {}",
+                    self.key,
+                    self.min.map_or("-∞".to_string(), |min| min.to_string()),
+                    self.max.map_or("∞".to_string(), |max| max.to_string())
+                )));
+            }
+        }
+        if !self.options.is_empty() && !self.options.contains(value) {
+            return Err(AppError::Validation(format!(
+                "Setting {} has no option {}",
+                self.key, value
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// App-wide UI settings kept by the settings store
+fn ui_settings() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new("showDebugInfo", SettingType::Boolean, false.into(), "ui")
+            .describe("Debug info", "Show debug information in the UI"),
+        SettingDefinition::new("theme", SettingType::String, "light".into(), "ui")
+            .describe("Theme", "Color theme")
+            .options(vec!["light".into(), "dark".into()]),
+    ]
+}
+
+/// Settings declared by the app's own modules
+pub fn core_settings() -> Vec<SettingDefinition> {
+    let mut settings = ui_settings();
+    settings.extend(crate::i18n::settings_schema());
+    settings.extend(crate::network::settings_schema());
+    settings.extend(crate::metrics::settings_schema());
+    settings.extend(crate::local_api::settings_schema());
+    settings.extend(crate::backup::settings_schema());
+    settings.extend(crate::remote_backup::settings_schema());
+    settings.extend(crate::audit::settings_schema());
+    settings.extend(crate::notifications::settings_schema());
+    settings.extend(crate::tickets::settings_schema());
+    settings.extend(crate::undo::settings_schema());
+    settings.extend(crate::onboarding::settings_schema());
+    settings.extend(crate::updates::settings_schema());
+    settings.extend(crate::prompt_gen::template_migration::settings_schema());
+    settings
+}
+
+/// Current value of a setting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingValue {
+    pub key: String,
+    pub value: Value,
+    /// Whether the value is the schema default (nothing stored)
+    pub is_default: bool,
+}
+
 // ============================================================================
 // Settings Service
 // ============================================================================
@@ -61,11 +284,114 @@ use tokio::sync::Mutex;
 
 pub struct SettingsService {
     db: Arc<Mutex<Database>>,
+    schema: BTreeMap<String, SettingDefinition>,
 }
 
 impl SettingsService {
     pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+        let mut service = Self {
+            db,
+            schema: BTreeMap::new(),
+        };
+        for definition in core_settings() {
+            if let Err(e) = service.register(definition) {
+                tracing::error!("Invalid core setting: {}", e);
+            }
+        }
+        service
+    }
+
+    /// Point the service at another database, keeping the schema (workspace switch)
+    pub fn set_database(&mut self, db: Arc<Mutex<Database>>) {
+        self.db = db;
+    }
+
+    /// Declare a setting; its default must pass its own constraints
+    pub fn register(&mut self, definition: SettingDefinition) -> Result<(), AppError> {
+        definition.validate(&definition.default)?;
+        if let Some(existing) = self.schema.get(&definition.key) {
+            if existing.owner != definition.owner {
+                return Err(AppError::Validation(format!(
+                    "Setting {} is already declared by {}",
+                    definition.key, existing.owner
+                )));
+            }
+        }
+        self.schema.insert(definition.key.clone(), definition);
+        Ok(())
+    }
+
+    /// Declare a plugin's settings under `plugins.<plugin>.`, replacing the
+    /// ones it declared before
+    pub fn register_plugin_settings(
+        &mut self,
+        plugin: &str,
+        definitions: &[SettingDefinition],
+    ) -> Result<(), AppError> {
+        self.schema
+            .retain(|_, definition| definition.owner != plugin);
+        for definition in definitions {
+            self.register(SettingDefinition {
+                key: format!("plugins.{}.{}", plugin, definition.key),
+                category: definition
+                    .category
+                    .clone()
+                    .or_else(|| Some(format!("plugins.{}", plugin))),
+                owner: plugin.to_string(),
+                ..definition.clone()
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Declared settings, ordered by key
+    pub fn schema(&self) -> Vec<SettingDefinition> {
+        self.schema.values().cloned().collect()
+    }
+
+    fn definition(&self, key: &str) -> Result<&SettingDefinition, AppError> {
+        self.schema
+            .get(key)
+            .ok_or_else(|| AppError::Validation(format!("Unknown setting: {}", key)))
+    }
+
+    /// Current value of a setting, the default when unset
+    pub async fn get_value(&self, key: &str) -> Result<Value, AppError> {
+        let definition = self.definition(key)?;
+        Ok(self
+            .stored(key)
+            .await?
+            .and_then(|raw| definition.setting_type.parse(&raw))
+            .unwrap_or_else(|| definition.default.clone()))
+    }
+
+    /// Validate and store a setting; null removes it (back to the default)
+    pub async fn set_value(&self, key: &str, value: Value) -> Result<(), AppError> {
+        let definition = self.definition(key)?;
+        definition.validate(&value)?;
+        if value.is_null() {
+            return self.delete_setting(key).await;
+        }
+        self.write(key, &stored_value(&value), definition).await
+    }
+
+    /// Every declared setting with its current value
+    pub async fn get_effective_settings(&self) -> Result<Vec<SettingValue>, AppError> {
+        let stored = self.get_all_settings().await?;
+        Ok(self
+            .schema
+            .values()
+            .map(|definition| {
+                let value = stored
+                    .get(&definition.key)
+                    .and_then(|raw| definition.setting_type.parse(raw));
+                SettingValue {
+                    key: definition.key.clone(),
+                    is_default: value.is_none(),
+                    value: value.unwrap_or_else(|| definition.default.clone()),
+                }
+            })
+            .collect())
     }
 
     /// Get all settings as a HashMap
@@ -74,7 +400,6 @@ impl SettingsService {
 
         let db = self.db.lock().await;
         let mut result = db
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to query settings: {}", e)))?;
@@ -91,8 +416,19 @@ impl SettingsService {
         Ok(map)
     }
 
-    /// Get a specific setting by key
+    /// Get a specific setting by key, as stored (the default when unset)
     pub async fn get_setting(&self, key: &str) -> Result<Option<String>, AppError> {
+        if let Some(value) = self.stored(key).await? {
+            return Ok(Some(value));
+        }
+        Ok(self
+            .schema
+            .get(key)
+            .filter(|definition| !definition.default.is_null())
+            .map(|definition| stored_value(&definition.default)))
+    }
+
+    async fn stored(&self, key: &str) -> Result<Option<String>, AppError> {
         let db = self.db.lock().await;
         let result: Option<SettingRecord> = db
             .db
@@ -103,7 +439,7 @@ impl SettingsService {
         Ok(result.map(|r| r.value))
     }
 
-    /// Save a setting
+    /// Save a setting given as its stored string, validated against the schema
     pub async fn save_setting(
         &self,
         key: &str,
@@ -111,9 +447,39 @@ impl SettingsService {
         setting_type: &str,
         category: Option<String>,
     ) -> Result<(), AppError> {
-        // Validate type
-        self.validate_setting_type(setting_type)?;
+        let setting_type = SettingType::from_name(setting_type)?;
+        let definition = self.definition(key)?;
+        if setting_type != definition.setting_type {
+            return Err(AppError::Validation(format!(
+                "Setting {} is a {}, not a {}",
+                key,
+                definition.setting_type.as_str(),
+                setting_type.as_str()
+            )));
+        }
+        let parsed = setting_type.parse(value).ok_or_else(|| {
+            AppError::Validation(format!(
+                "Setting {} must be a {}, got {:?}",
+                key,
+                setting_type.as_str(),
+                value
+            ))
+        })?;
+        definition.validate(&parsed)?;
+
+        let definition = SettingDefinition {
+            category: category.or_else(|| definition.category.clone()),
+            ..definition.clone()
+        };
+        self.write(key, value, &definition).await
+    }
 
+    async fn write(
+        &self,
+        key: &str,
+        value: &str,
+        definition: &SettingDefinition,
+    ) -> Result<(), AppError> {
         let now = Utc::now();
 
         let db = self.db.lock().await;
@@ -122,15 +488,15 @@ impl SettingsService {
             id: Thing::from(("settings", key)),
             key: key.to_string(),
             value: value.to_string(),
-            setting_type: setting_type.to_string(),
-            category,
-            description: None,
+            setting_type: definition.setting_type.as_str().to_string(),
+            category: definition.category.clone(),
+            description: definition.description.clone(),
             updated_at: now,
         };
 
         let _: Option<SettingRecord> = db
             .db
-            .update(("settings", key))
+            .upsert(("settings", key))
             .content(record)
             .await
             .map_err(|e| AppError::Database(format!("Failed to save setting: {}", e)))?;
@@ -157,7 +523,7 @@ impl SettingsService {
         let query = format!("SELECT * FROM settings WHERE category = '{}'", category);
 
         let db = self.db.lock().await;
-        let mut result = db.db.query(&query).await.map_err(|e| {
+        let mut result = db.query(&query).await.map_err(|e| {
             AppError::Database(format!("Failed to query settings by category: {}", e))
         })?;
 
@@ -167,15 +533,105 @@ impl SettingsService {
 
         Ok(settings.into_iter().map(|s| s.into()).collect())
     }
+}
 
-    // Private helper
-    fn validate_setting_type(&self, setting_type: &str) -> Result<(), AppError> {
-        match setting_type {
-            "string" | "number" | "boolean" | "json" => Ok(()),
-            _ => Err(AppError::Config(format!(
-                "Invalid setting type: {}. Must be 'string', 'number', 'boolean', or 'json'",
-                setting_type
-            ))),
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_settings_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let mut settings = SettingsService::new(Arc::new(Mutex::new(database)));
+
+        // Unset keys read as their defaults
+        assert_eq!(
+            settings.get_value("backup.max_keep").await.unwrap(),
+            json!(7)
+        );
+        assert_eq!(
+            settings
+                .get_setting("backup.enabled")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("false")
+        );
+        assert_eq!(
+            settings.get_setting("tickets.current_user").await.unwrap(),
+            None
+        );
+
+        settings
+            .set_value("backup.interval_hours", json!(6))
+            .await
+            .unwrap();
+        assert_eq!(
+            settings
+                .get_setting("backup.interval_hours")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("6")
+        );
+        settings
+            .save_setting("theme", "dark", "string", None)
+            .await
+            .unwrap();
+        assert_eq!(settings.get_value("theme").await.unwrap(), json!("dark"));
+
+        // Writes are checked against type, bounds, options and the schema
+        assert!(settings
+            .set_value("backup.interval_hours", json!(0))
+            .await
+            .is_err());
+        assert!(settings
+            .set_value("backup.enabled", json!("yes"))
+            .await
+            .is_err());
+        assert!(settings.set_value("theme", json!("sepia")).await.is_err());
+        assert!(settings
+            .save_setting("backup.max_keep", "many", "number", None)
+            .await
+            .is_err());
+        assert!(settings
+            .set_value("no.such.setting", json!(1))
+            .await
+            .is_err());
+
+        // Null resets to the default
+        settings.set_value("theme", Value::Null).await.unwrap();
+        assert_eq!(settings.get_value("theme").await.unwrap(), json!("light"));
+
+        // Plugin settings live under their plugin's prefix
+        let plugin_setting: SettingDefinition = serde_json::from_value(json!({
+            "key": "refresh_minutes", "type": "number", "default": 30, "min": 5
+        }))
+        .unwrap();
+        settings
+            .register_plugin_settings("rss-feed-reader", &[plugin_setting.clone()])
+            .unwrap();
+        let key = "plugins.rss-feed-reader.refresh_minutes";
+        assert_eq!(settings.get_value(key).await.unwrap(), json!(30));
+        assert!(settings.set_value(key, json!(1)).await.is_err());
+        let invalid_default = SettingDefinition {
+            default: json!(1),
+            ..plugin_setting
+        };
+        assert!(settings
+            .register_plugin_settings("other", &[invalid_default])
+            .is_err());
+
+        let effective = settings.get_effective_settings().await.unwrap();
+        let interval = effective
+            .iter()
+            .find(|s| s.key == "backup.interval_hours")
+            .unwrap();
+        assert_eq!(interval.value, json!(6));
+        assert!(!interval.is_default);
+        assert!(effective.iter().any(|s| s.key == key && s.is_default));
     }
 }
@@ -0,0 +1,376 @@
+// Settings bundles
+//
+// Setting up a second machine shouldn't mean clicking through every option
+// again. A settings bundle holds the app's configuration: the settings that
+// differ from their defaults, the board configs (columns, WIP limits, key
+// prefixes) and the schedules of the background jobs (retention and rollup
+// rules). Secrets stay out: credentials live in their own store (see
+// `credential_export`) and settings with a secret-looking name are skipped.
+//
+// Importing replaces the configuration with the bundle's, so the machine ends
+// up configured like the one the bundle came from. `reset_settings` puts a
+// part of the configuration back to the defaults.
+
+use crate::boards::BoardConfig;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::redaction::is_sensitive_field;
+use crate::retention::SaveRetentionRuleRequest;
+use crate::rollups::SaveRollupRuleRequest;
+use crate::settings::SettingsService;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tokio::sync::Mutex;
+
+pub const SETTINGS_EXPORT_VERSION: &str = "1.0";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: String,
+    pub exported_at: DateTime<Utc>,
+    /// Setting key → value, for settings that differ from their default
+    #[serde(default)]
+    pub settings: BTreeMap<String, Value>,
+    /// Boards with their own configuration
+    #[serde(default)]
+    pub boards: Vec<BoardConfig>,
+    #[serde(default)]
+    pub retention_rules: Vec<SaveRetentionRuleRequest>,
+    #[serde(default)]
+    pub rollup_rules: Vec<SaveRollupRuleRequest>,
+}
+
+/// Part of the configuration to reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsScope {
+    All,
+    /// App and plugin settings
+    Settings,
+    /// Board columns and key prefixes
+    Boards,
+    /// Retention and rollup rules
+    Schedules,
+}
+
+impl SettingsScope {
+    fn includes(self, scope: SettingsScope) -> bool {
+        self == SettingsScope::All || self == scope
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsImport {
+    pub settings: usize,
+    pub boards: usize,
+    pub retention_rules: usize,
+    pub rollup_rules: usize,
+    /// Entries that were left out, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// Whether a setting key names a secret (by its last segment)
+fn is_secret_setting(key: &str) -> bool {
+    key.rsplit('.').next().is_some_and(is_sensitive_field)
+}
+
+/// Gather the app's configuration into a bundle
+pub async fn export_settings(
+    settings: &SettingsService,
+    database: &Mutex<Database>,
+) -> Result<SettingsBundle, AppError> {
+    let values = settings
+        .get_effective_settings()
+        .await?
+        .into_iter()
+        .filter(|setting| !setting.is_default && !is_secret_setting(&setting.key))
+        .map(|setting| (setting.key, setting.value))
+        .collect();
+
+    let db = database.lock().await;
+    let boards = db.list_board_configs().await?;
+    let retention_rules = db
+        .list_retention_rules()
+        .await?
+        .into_iter()
+        .map(|rule| SaveRetentionRuleRequest {
+            id: None,
+            record_type: rule.record_type,
+            source: rule.source,
+            max_age_days: rule.max_age_days,
+            max_count: rule.max_count,
+            enabled: rule.enabled,
+        })
+        .collect();
+    let rollup_rules = db
+        .list_rollup_rules()
+        .await?
+        .into_iter()
+        .map(|rule| SaveRollupRuleRequest {
+            id: None,
+            record_type: rule.record_type,
+            source: rule.source,
+            fields: rule.fields,
+            resolution: rule.resolution,
+            raw_retention_days: rule.raw_retention_days,
+            enabled: rule.enabled,
+        })
+        .collect();
+
+    Ok(SettingsBundle {
+        version: SETTINGS_EXPORT_VERSION.to_string(),
+        exported_at: Utc::now(),
+        settings: values,
+        boards,
+        retention_rules,
+        rollup_rules,
+    })
+}
+
+/// Replace the configuration with a bundle's
+/// Settings the bundle doesn't list go back to their defaults; entries that
+/// don't fit this machine (unknown settings, invalid values) are skipped and
+/// reported instead of failing the import.
+pub async fn import_settings(
+    settings: &SettingsService,
+    database: &Mutex<Database>,
+    bundle: SettingsBundle,
+) -> Result<SettingsImport, AppError> {
+    if bundle.version != SETTINGS_EXPORT_VERSION {
+        return Err(AppError::Validation(format!(
+            "Unsupported settings bundle version {}",
+            bundle.version
+        )));
+    }
+
+    reset_settings(settings, database, SettingsScope::All).await?;
+    let mut imported = SettingsImport::default();
+    for (key, value) in bundle.settings {
+        if is_secret_setting(&key) {
+            imported
+                .skipped
+                .push(format!("{}: secrets aren't imported", key));
+            continue;
+        }
+        match settings.set_value(&key, value).await {
+            Ok(()) => imported.settings += 1,
+            Err(AppError::Validation(message)) => {
+                imported.skipped.push(format!("{}: {}", key, message))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    // Lock per entry so commands aren't held up for the whole bundle
+    for board in bundle.boards {
+        let db = database.lock().await;
+        let saved = match db.save_board_columns(&board.board_id, board.columns).await {
+            Ok(_) => {
+                db.set_board_key_prefix(&board.board_id, &board.key_prefix)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        drop(db);
+        match saved {
+            Ok(_) => imported.boards += 1,
+            Err(AppError::Validation(message)) => imported
+                .skipped
+                .push(format!("board {}: {}", board.board_id, message)),
+            Err(e) => return Err(e),
+        }
+    }
+    for rule in bundle.retention_rules {
+        match database
+            .lock()
+            .await
+            .save_retention_rule(SaveRetentionRuleRequest { id: None, ..rule })
+            .await
+        {
+            Ok(_) => imported.retention_rules += 1,
+            Err(AppError::Validation(message)) => imported
+                .skipped
+                .push(format!("retention rule: {}", message)),
+            Err(e) => return Err(e),
+        }
+    }
+    for rule in bundle.rollup_rules {
+        match database
+            .lock()
+            .await
+            .save_rollup_rule(SaveRollupRuleRequest { id: None, ..rule })
+            .await
+        {
+            Ok(_) => imported.rollup_rules += 1,
+            Err(AppError::Validation(message)) => {
+                imported.skipped.push(format!("rollup rule: {}", message))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    tracing::info!(
+        "Imported {} settings, {} boards, {} retention and {} rollup rules ({} skipped)",
+        imported.settings,
+        imported.boards,
+        imported.retention_rules,
+        imported.rollup_rules,
+        imported.skipped.len()
+    );
+    Ok(imported)
+}
+
+/// Put part of the configuration back to the defaults
+pub async fn reset_settings(
+    settings: &SettingsService,
+    database: &Mutex<Database>,
+    scope: SettingsScope,
+) -> Result<(), AppError> {
+    if scope.includes(SettingsScope::Settings) {
+        for key in settings.get_all_settings().await?.into_keys() {
+            settings.delete_setting(&key).await?;
+        }
+    }
+
+    let db = database.lock().await;
+    if scope.includes(SettingsScope::Boards) {
+        for board in db.list_board_configs().await? {
+            db.reset_board_config(&board.board_id).await?;
+        }
+    }
+    if scope.includes(SettingsScope::Schedules) {
+        for rule in db.list_retention_rules().await? {
+            db.delete_retention_rule(&rule.id).await?;
+        }
+        for rule in db.list_rollup_rules().await? {
+            db.delete_rollup_rule(&rule.id).await?;
+        }
+    }
+    tracing::info!("Reset {:?} configuration to the defaults", scope);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boards::default_columns;
+    use crate::rollups::RollupResolution;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    async fn machine() -> (TempDir, SettingsService, Arc<Mutex<Database>>) {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(Mutex::new(
+            Database::new(temp_dir.path().to_path_buf()).await.unwrap(),
+        ));
+        let settings = SettingsService::new(database.clone());
+        (temp_dir, settings, database)
+    }
+
+    #[tokio::test]
+    async fn test_settings_bundle() {
+        let (_first_dir, settings, database) = machine().await;
+        settings.set_value("theme", json!("dark")).await.unwrap();
+        settings
+            .set_value("backup.max_keep", json!(3))
+            .await
+            .unwrap();
+        {
+            let db = database.lock().await;
+            db.set_board_key_prefix("panel-1", "OPS").await.unwrap();
+            db.save_retention_rule(SaveRetentionRuleRequest {
+                id: None,
+                record_type: Some("pipeline".to_string()),
+                source: None,
+                max_age_days: Some(30),
+                max_count: None,
+                enabled: true,
+            })
+            .await
+            .unwrap();
+            db.save_rollup_rule(SaveRollupRuleRequest {
+                id: None,
+                record_type: "weather".to_string(),
+                source: None,
+                fields: vec!["temperature".to_string()],
+                resolution: RollupResolution::Hour,
+                raw_retention_days: None,
+                enabled: true,
+            })
+            .await
+            .unwrap();
+        }
+
+        let mut bundle = export_settings(&settings, &database).await.unwrap();
+        assert_eq!(bundle.settings.len(), 2);
+        assert_eq!(bundle.boards.len(), 1);
+        bundle
+            .settings
+            .insert("plugins.gone.interval".to_string(), json!(5));
+
+        // A second machine with some configuration of its own
+        let (_second_dir, other, other_db) = machine().await;
+        other.set_value("showDebugInfo", json!(true)).await.unwrap();
+        let imported = import_settings(&other, &other_db, bundle.clone())
+            .await
+            .unwrap();
+        assert_eq!(imported.settings, 2);
+        assert_eq!(imported.boards, 1);
+        assert_eq!(imported.retention_rules, 1);
+        assert_eq!(imported.rollup_rules, 1);
+        assert_eq!(imported.skipped.len(), 1);
+        assert_eq!(other.get_value("theme").await.unwrap(), json!("dark"));
+        assert_eq!(
+            other.get_value("showDebugInfo").await.unwrap(),
+            json!(false)
+        );
+        {
+            let db = other_db.lock().await;
+            assert_eq!(
+                db.get_board_config("panel-1").await.unwrap().key_prefix,
+                "OPS"
+            );
+        }
+
+        // Importing again doesn't duplicate the rules
+        import_settings(&other, &other_db, bundle).await.unwrap();
+        assert_eq!(
+            other_db
+                .lock()
+                .await
+                .list_retention_rules()
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        reset_settings(&other, &other_db, SettingsScope::Boards)
+            .await
+            .unwrap();
+        {
+            let db = other_db.lock().await;
+            assert_eq!(
+                db.get_board_config("panel-1").await.unwrap().columns,
+                default_columns()
+            );
+            assert_eq!(db.list_rollup_rules().await.unwrap().len(), 1);
+        }
+        assert_eq!(other.get_value("theme").await.unwrap(), json!("dark"));
+
+        reset_settings(&other, &other_db, SettingsScope::All)
+            .await
+            .unwrap();
+        assert_eq!(other.get_value("theme").await.unwrap(), json!("light"));
+        assert!(other_db
+            .lock()
+            .await
+            .list_retention_rules()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
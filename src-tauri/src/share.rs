@@ -0,0 +1,277 @@
+// Share formatting
+//
+// "Copy as…" for anything with an id: a staged record, a ticket or a rendered
+// prompt from the render history is turned into Markdown, plain text or JSON
+// for the clipboard. Formatting lives here so every widget copies the same
+// text instead of assembling its own.
+//
+// The kind of item is taken from the id's table prefix: `tickets:` and
+// `prompt_renders:` ids resolve to tickets and rendered prompts, any
+// other id (with or without `records:`) to a record.
+
+use crate::db::{Database, StagedRecord};
+use crate::error::AppError;
+use crate::prompt_gen::history::{get_render_history_entry, RenderHistoryEntry};
+use crate::tickets::Ticket;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareFormat {
+    Markdown,
+    Text,
+    Json,
+}
+
+/// Record data fields tried, in order, for a title and a link
+const TITLE_FIELDS: [&str; 3] = ["title", "name", "summary"];
+const LINK_FIELDS: [&str; 4] = ["web_url", "html_url", "url", "link"];
+
+/// Format the record, ticket or rendered prompt with this id
+pub async fn format_for_share(
+    db: &Database,
+    id: &str,
+    format: ShareFormat,
+) -> Result<String, AppError> {
+    if id.starts_with("tickets:") {
+        let ticket = db.get_ticket(id).await?;
+        return format_ticket(&ticket, format);
+    }
+    if id.starts_with("prompt_renders:") {
+        let entry = get_render_history_entry(db, id).await?;
+        return format_render(&entry, format);
+    }
+    let key = id.strip_prefix("records:").unwrap_or(id);
+    let record = db
+        .get_record(key)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Record {}", id)))?;
+    format_record(&record, format)
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, AppError> {
+    serde_json::to_string_pretty(value).map_err(AppError::from)
+}
+
+/// A scalar JSON value as text, None for nulls, arrays and objects
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn first_field(data: &Value, fields: &[&str]) -> Option<String> {
+    fields.iter().find_map(|field| scalar(&data[*field]))
+}
+
+pub fn format_record(record: &StagedRecord, format: ShareFormat) -> Result<String, AppError> {
+    if format == ShareFormat::Json {
+        return to_json(record);
+    }
+    let markdown = format == ShareFormat::Markdown;
+    let id = record
+        .id
+        .as_ref()
+        .map(|id| id.id.to_raw())
+        .unwrap_or_default();
+    let title = record
+        .metadata
+        .title
+        .clone()
+        .or_else(|| first_field(&record.data, &TITLE_FIELDS))
+        .unwrap_or_else(|| format!("{} {}", record.record_type, id));
+    let link = first_field(&record.data, &LINK_FIELDS);
+
+    let mut lines = vec![match (&link, markdown) {
+        (Some(link), true) => format!("**[{}]({})**", title, link),
+        (_, true) => format!("**{}**", title),
+        (_, false) => title.clone(),
+    }];
+    let mut context = vec![record.record_type.clone(), record.source.clone()];
+    context.extend(record.metadata.status.clone());
+    context.push(record.timestamp.format("%Y-%m-%d %H:%M").to_string());
+    lines.push(context.join(" · "));
+    if !markdown {
+        lines.extend(link);
+    }
+    if let Some(description) = record
+        .metadata
+        .description
+        .as_ref()
+        .filter(|d| !d.is_empty())
+    {
+        lines.push(String::new());
+        lines.push(description.clone());
+    }
+
+    // Remaining scalar fields (nested values are left to the JSON format)
+    if let Value::Object(data) = &record.data {
+        let fields: Vec<String> = data
+            .iter()
+            .filter(|(key, _)| {
+                !TITLE_FIELDS.contains(&key.as_str()) && !LINK_FIELDS.contains(&key.as_str())
+            })
+            .filter_map(|(key, value)| {
+                let value = scalar(value)?;
+                Some(match markdown {
+                    true => format!("- **{}:** {}", key, value),
+                    false => format!("{}: {}", key, value),
+                })
+            })
+            .collect();
+        if !fields.is_empty() {
+            lines.push(String::new());
+            lines.extend(fields);
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+pub fn format_ticket(ticket: &Ticket, format: ShareFormat) -> Result<String, AppError> {
+    if format == ShareFormat::Json {
+        return to_json(ticket);
+    }
+    let markdown = format == ShareFormat::Markdown;
+    let heading = match &ticket.key {
+        Some(key) => format!("{}: {}", key, ticket.title),
+        None => ticket.title.clone(),
+    };
+    let mut lines = vec![match markdown {
+        true => format!("## {}", heading),
+        false => heading,
+    }];
+
+    let mut context = vec![
+        to_label(&ticket.ticket_type),
+        ticket.status.clone(),
+        to_label(&ticket.priority),
+    ];
+    context.extend(ticket.assignee.as_ref().map(|a| format!("@{}", a)));
+    context.extend(ticket.due_date.as_ref().map(|d| format!("due {}", d)));
+    lines.push(context.join(" · "));
+    if !ticket.tags.is_empty() {
+        let tags: Vec<String> = ticket.tags.iter().map(|t| format!("#{}", t)).collect();
+        lines.push(tags.join(" "));
+    }
+
+    if let Some(description) = ticket.description.as_ref().filter(|d| !d.is_empty()) {
+        lines.push(String::new());
+        lines.push(description.clone());
+    }
+    if !ticket.checklist.is_empty() {
+        lines.push(String::new());
+        lines.extend(ticket.checklist.iter().map(|item| {
+            let done = match (markdown, item.done) {
+                (true, true) => "- [x]",
+                (true, false) => "- [ ]",
+                (false, true) => "[x]",
+                (false, false) => "[ ]",
+            };
+            format!("{} {}", done, item.text)
+        }));
+    }
+    Ok(lines.join("\n"))
+}
+
+pub fn format_render(entry: &RenderHistoryEntry, format: ShareFormat) -> Result<String, AppError> {
+    match format {
+        ShareFormat::Json => to_json(entry),
+        // Plain text is the prompt itself, ready to paste into a generator
+        ShareFormat::Text => Ok(match &entry.negative_prompt {
+            Some(negative) => format!("{}\n\nNegative prompt: {}", entry.text, negative),
+            None => entry.text.clone(),
+        }),
+        ShareFormat::Markdown => {
+            let mut lines = vec!["```".to_string(), entry.text.clone(), "```".to_string()];
+            if let Some(negative) = &entry.negative_prompt {
+                lines.push(String::new());
+                lines.push("Negative prompt:".to_string());
+                lines.extend(["```".to_string(), negative.clone(), "```".to_string()]);
+            }
+            lines.push(String::new());
+            lines.push(format!(
+                "_{} · seed {} · {}_",
+                entry.section_id, entry.seed, entry.rendered_at
+            ));
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+/// Serialized name of a unit enum variant, e.g. "feature" for TicketType::Feature
+fn to_label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .as_ref()
+        .and_then(scalar)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::RecordMetadata;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_record() {
+        let record = StagedRecord {
+            id: None,
+            record_type: "pipeline".to_string(),
+            source: "gitlab".to_string(),
+            timestamp: "2024-03-05T10:00:00Z".parse().unwrap(),
+            data: json!({
+                "name": "Deploy",
+                "web_url": "https://gitlab.example.com/p/1",
+                "duration": 42,
+                "stages": ["build"],
+            }),
+            metadata: RecordMetadata {
+                tags: vec![],
+                status: Some("failed".to_string()),
+                title: None,
+                description: None,
+            },
+        };
+
+        let markdown = format_record(&record, ShareFormat::Markdown).unwrap();
+        assert_eq!(
+            markdown,
+            "**[Deploy](https://gitlab.example.com/p/1)**\n\
+             pipeline · gitlab · failed · 2024-03-05 10:00\n\
+             \n\
+             - **duration:** 42"
+        );
+        let text = format_record(&record, ShareFormat::Text).unwrap();
+        assert!(text.starts_with("Deploy\n"));
+        assert!(text.contains("https://gitlab.example.com/p/1"));
+
+        let json: Value =
+            serde_json::from_str(&format_record(&record, ShareFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["data"]["duration"], 42);
+    }
+
+    #[test]
+    fn test_format_render() {
+        let entry = RenderHistoryEntry {
+            id: "abc".to_string(),
+            section_id: "portrait".to_string(),
+            variables: Default::default(),
+            seed: 7,
+            text: "a cat".to_string(),
+            negative_prompt: Some("blurry".to_string()),
+            rendered_at: "2024-03-05T10:00:00Z".to_string(),
+        };
+        assert_eq!(
+            format_render(&entry, ShareFormat::Text).unwrap(),
+            "a cat\n\nNegative prompt: blurry"
+        );
+        let markdown = format_render(&entry, ShareFormat::Markdown).unwrap();
+        assert!(markdown.starts_with("```\na cat\n```"));
+        assert!(markdown.ends_with("_portrait · seed 7 · 2024-03-05T10:00:00Z_"));
+    }
+}
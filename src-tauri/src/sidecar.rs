@@ -1,16 +1,285 @@
-use std::path::PathBuf;
+// SurrealDB sidecar process
+//
+// Sidecar builds run SurrealDB as a separate process. A binary in
+// sidecar-binaries/ (development) or bundled with the app is used when there
+// is one; otherwise the pinned SurrealDB release is downloaded into the data
+// dir on first start through the `network` settings, and checked against the
+// SHA-256 checksums pinned below rather than ones fetched alongside it.
+//
+// SurrealDB listens on port 8000, or on a free port when something else holds
+// 8000; `Database::new` connects to whichever port was picked. The `sidecar`
+// job restarts the process when it exits, backing off after repeated crashes,
+// and the app health check shows its state.
+
+use crate::error::AppError;
+use crate::health::{self, SidecarHealth};
+use crate::network;
+use crate::scheduler::{Job, Schedule};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 use tokio::time::sleep;
 
+/// SurrealDB release downloaded when no binary is bundled
+pub const SURREALDB_VERSION: &str = "2.1.3";
+
+/// SHA-256 of each platform's release asset for `SURREALDB_VERSION`, copied
+/// from the release's `.txt` checksum files whenever the version changes.
+/// A platform without a checksum here is not downloaded.
+const SURREALDB_SHA256: &[(&str, &str)] = &[
+    ("linux-amd64", ""),
+    ("linux-arm64", ""),
+    ("darwin-amd64", ""),
+    ("darwin-arm64", ""),
+    ("windows-amd64", ""),
+];
+
+const RELEASES_URL: &str = "https://github.com/surrealdb/surrealdb/releases/download";
+
+/// Port SurrealDB listens on unless it is taken
+pub const DEFAULT_PORT: u16 = 8000;
+
+/// How often the `sidecar` job checks the process
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest wait before restarting a crashed process
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A process running this long after a restart is no longer crashing
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// Port of the running sidecar, read by `Database::new`
+static PORT: AtomicU16 = AtomicU16::new(DEFAULT_PORT);
+
+/// Port the sidecar listens on
+pub fn port() -> u16 {
+    PORT.load(Ordering::Relaxed)
+}
+
+/// Sidecar binary version, for `get_sidecar_version`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SidecarVersion {
+    /// Version downloaded when no binary is bundled
+    pub pinned: String,
+    /// Version reported by the running server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running: Option<String>,
+    pub binary_path: String,
+    /// Whether the binary was downloaded into the data dir
+    pub managed: bool,
+}
+
 pub struct SurrealDbSidecar {
     process: Option<Child>,
     data_path: PathBuf,
+    binary_path: PathBuf,
+    managed: bool,
+    port: u16,
+    started_at: Instant,
+    /// Restarts since the app started
+    restarts: u32,
+    /// Exits since the process last ran stably, for the restart backoff
+    crashes: u32,
+    next_restart: Option<Instant>,
+    last_error: Option<String>,
+}
+
+/// `preferred` if it is free on localhost, otherwise a free port picked by the OS
+fn pick_port(preferred: u16) -> Result<u16, String> {
+    if TcpListener::bind(("127.0.0.1", preferred)).is_ok() {
+        return Ok(preferred);
+    }
+    let port = TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("Failed to find a free port for SurrealDB: {}", e))?;
+    tracing::warn!(
+        "Port {} is in use, starting SurrealDB on port {}",
+        preferred,
+        port
+    );
+    Ok(port)
+}
+
+/// Wait before the restart after `crashes` exits in a row: 1s, 2s, 4s, ... up to a minute
+fn restart_backoff(crashes: u32) -> Duration {
+    let secs = 1u64 << crashes.saturating_sub(1).min(6);
+    Duration::from_secs(secs).min(MAX_RESTART_BACKOFF)
+}
+
+/// Release asset name suffix for this platform
+fn release_platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux-amd64"),
+        ("linux", "aarch64") => Some("linux-arm64"),
+        ("macos", "x86_64") => Some("darwin-amd64"),
+        ("macos", "aarch64") => Some("darwin-arm64"),
+        ("windows", "x86_64") => Some("windows-amd64"),
+        _ => None,
+    }
+}
+
+/// Path of the downloaded binary for the pinned version
+fn managed_binary_path(data_path: &Path) -> PathBuf {
+    let name = format!(
+        "surreal-v{}{}",
+        SURREALDB_VERSION,
+        std::env::consts::EXE_SUFFIX
+    );
+    data_path.join("bin").join(name)
+}
+
+/// Binary shipped with the app (or placed in sidecar-binaries/ during development)
+fn bundled_binary_path() -> PathBuf {
+    let name = format!("surreal{}", std::env::consts::EXE_SUFFIX);
+    if cfg!(debug_assertions) {
+        PathBuf::from("sidecar-binaries").join(name)
+    } else {
+        PathBuf::from(name)
+    }
+}
+
+/// Pinned SHA-256 of the release asset for a platform
+fn pinned_checksum(platform: &str) -> Option<&'static str> {
+    SURREALDB_SHA256
+        .iter()
+        .find(|(name, _)| *name == platform)
+        .map(|(_, hash)| *hash)
+        .filter(|hash| !hash.is_empty())
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    Ok(bytes.to_vec())
+}
+
+/// Download the pinned SurrealDB release into the data dir, verifying its checksum
+async fn download_binary(target: &Path) -> Result<(), String> {
+    let platform = release_platform().ok_or_else(|| {
+        format!(
+            "No SurrealDB release for {}-{}; install SurrealDB into sidecar-binaries/",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+    })?;
+    let asset = format!("surreal-v{}.{}", SURREALDB_VERSION, platform);
+    let archive = if cfg!(target_os = "windows") {
+        format!("{}.exe", asset)
+    } else {
+        format!("{}.tgz", asset)
+    };
+    let expected = pinned_checksum(platform).ok_or_else(|| {
+        format!(
+            "No pinned checksum for SurrealDB v{} ({}); install SurrealDB into sidecar-binaries/",
+            SURREALDB_VERSION, platform
+        )
+    })?;
+    let base = format!("{}/v{}", RELEASES_URL, SURREALDB_VERSION);
+
+    network::ensure_online().map_err(|e| e.to_string())?;
+    tracing::info!(
+        "Downloading SurrealDB v{} ({})...",
+        SURREALDB_VERSION,
+        platform
+    );
+    let client = network::client();
+    let bytes = download(&client, &format!("{}/{}", base, archive)).await?;
+
+    let actual = hex::encode(Sha256::digest(&bytes));
+    if actual != expected {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            archive, expected, actual
+        ));
+    }
+
+    let binary = if cfg!(target_os = "windows") {
+        bytes
+    } else {
+        extract_binary(&bytes)?
+    };
+
+    let dir = target.parent().ok_or("Invalid sidecar binary path")?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    // Written next to the target first, so an interrupted download isn't picked up
+    let partial = target.with_extension("part");
+    std::fs::write(&partial, binary)
+        .map_err(|e| format!("Failed to write {:?}: {}", partial, e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make {:?} executable: {}", partial, e))?;
+    }
+    std::fs::rename(&partial, target)
+        .map_err(|e| format!("Failed to move {:?} into place: {}", partial, e))?;
+
+    tracing::info!(
+        "Downloaded SurrealDB v{} to {:?}",
+        SURREALDB_VERSION,
+        target
+    );
+    Ok(())
+}
+
+/// The `surreal` binary from a release tarball
+fn extract_binary(archive: &[u8]) -> Result<Vec<u8>, String> {
+    let mut tarball = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    let entries = tarball
+        .entries()
+        .map_err(|e| format!("Failed to read SurrealDB archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read SurrealDB archive: {}", e))?;
+        let is_binary = entry
+            .path()
+            .map(|path| path.file_name() == Some("surreal".as_ref()))
+            .unwrap_or(false);
+        if is_binary {
+            let mut binary = Vec::new();
+            entry
+                .read_to_end(&mut binary)
+                .map_err(|e| format!("Failed to extract SurrealDB: {}", e))?;
+            return Ok(binary);
+        }
+    }
+    Err("SurrealDB archive has no surreal binary".to_string())
+}
+
+/// Find the SurrealDB binary, downloading the pinned version when none is
+/// bundled; returns its path and whether it was downloaded into the data dir
+pub async fn ensure_binary(data_path: &Path) -> Result<(PathBuf, bool), String> {
+    let bundled = bundled_binary_path();
+    if bundled.exists() {
+        return Ok((bundled, false));
+    }
+
+    let managed = managed_binary_path(data_path);
+    if !managed.exists() {
+        download_binary(&managed).await?;
+    }
+    Ok((managed, true))
 }
 
 impl SurrealDbSidecar {
-    /// Start SurrealDB sidecar process
-    pub fn start(data_path: PathBuf) -> Result<Self, String> {
+    /// Start SurrealDB sidecar process with the binary from `ensure_binary`,
+    /// on port 8000 or a free port if that one is taken
+    pub fn start(data_path: PathBuf, binary_path: PathBuf, managed: bool) -> Result<Self, String> {
         tracing::info!("Starting SurrealDB sidecar process...");
         tracing::info!("Data path: {:?}", data_path);
 
@@ -20,8 +289,31 @@ impl SurrealDbSidecar {
                 .map_err(|e| format!("Failed to create data directory: {}", e))?;
         }
 
+        tracing::info!("SurrealDB binary path: {:?}", binary_path);
+
+        let port = pick_port(DEFAULT_PORT)?;
+        PORT.store(port, Ordering::Relaxed);
+
+        let mut sidecar = Self {
+            process: None,
+            data_path,
+            binary_path,
+            managed,
+            port,
+            started_at: Instant::now(),
+            restarts: 0,
+            crashes: 0,
+            next_restart: None,
+            last_error: None,
+        };
+        sidecar.spawn()?;
+        Ok(sidecar)
+    }
+
+    /// Start the SurrealDB process on the sidecar's port
+    fn spawn(&mut self) -> Result<(), String> {
         // Check for and clean up stale lock file
-        let db_path = data_path.join("db");
+        let db_path = self.data_path.join("db");
         let lock_file = db_path.join("LOCK");
         if lock_file.exists() {
             tracing::warn!("Found existing LOCK file, attempting to clean up...");
@@ -33,43 +325,29 @@ impl SurrealDbSidecar {
             }
         }
 
-        // Get the path to the SurrealDB binary
-        // In development: look for binary in sidecar-binaries/
-        // In production: Tauri will bundle it automatically
-        let surreal_path = if cfg!(debug_assertions) {
-            // Development: use local binary
-            PathBuf::from("sidecar-binaries/surreal.exe")
-        } else {
-            // Production: use bundled binary
-            // Tauri will handle the path resolution
-            PathBuf::from("surreal.exe")
-        };
-
-        tracing::info!("SurrealDB binary path: {:?}", surreal_path);
-
-        // Start SurrealDB process
-        // surreal start --bind 127.0.0.1:8000 --user root --pass root file://data/db
-        let db_file_path = data_path.join("db");
-        let process = Command::new(&surreal_path)
+        // surreal start --bind 127.0.0.1:<port> --user root --pass root file://data/db
+        let process = Command::new(&self.binary_path)
             .args(&[
                 "start",
                 "--bind",
-                "127.0.0.1:8000",
+                &format!("127.0.0.1:{}", self.port),
                 "--user",
                 "root",
                 "--pass",
                 "root",
-                &format!("file://{}", db_file_path.display()),
+                &format!("file://{}", db_path.display()),
             ])
             .spawn()
             .map_err(|e| format!("Failed to start SurrealDB: {}", e))?;
 
-        tracing::info!("SurrealDB sidecar started (PID: {})", process.id());
-
-        Ok(Self {
-            process: Some(process),
-            data_path,
-        })
+        tracing::info!(
+            "SurrealDB sidecar started (PID: {}, port {})",
+            process.id(),
+            self.port
+        );
+        self.process = Some(process);
+        self.started_at = Instant::now();
+        Ok(())
     }
 
     /// Wait for SurrealDB to be ready
@@ -77,7 +355,7 @@ impl SurrealDbSidecar {
         tracing::info!("Waiting for SurrealDB to be ready...");
 
         let client = reqwest::Client::new();
-        let health_url = "http://127.0.0.1:8000/health";
+        let health_url = format!("http://127.0.0.1:{}/health", self.port);
 
         let start = std::time::Instant::now();
         loop {
@@ -85,7 +363,7 @@ impl SurrealDbSidecar {
                 return Err("SurrealDB failed to start within timeout".to_string());
             }
 
-            match client.get(health_url).send().await {
+            match client.get(&health_url).send().await {
                 Ok(response) if response.status().is_success() => {
                     tracing::info!("✅ SurrealDB is ready!");
                     return Ok(());
@@ -98,6 +376,106 @@ impl SurrealDbSidecar {
         }
     }
 
+    /// Whether the sidecar process is still alive
+    pub fn is_running(&mut self) -> bool {
+        match self.process.as_mut() {
+            Some(process) => matches!(process.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+
+    /// Process id of the running sidecar
+    pub fn pid(&self) -> Option<u32> {
+        self.process.as_ref().map(|process| process.id())
+    }
+
+    /// Status for the app health check
+    pub fn health(&mut self) -> SidecarHealth {
+        let running = self.is_running();
+        health::sidecar_health(
+            running,
+            self.pid(),
+            self.port,
+            self.restarts,
+            self.last_error.clone(),
+        )
+    }
+
+    /// Restart the process if it exited, waiting longer after each crash in
+    /// a row. It keeps its port, which the database connection is bound to.
+    pub async fn supervise(&mut self) -> Result<String, String> {
+        // Stopped on purpose
+        let Some(process) = self.process.as_mut() else {
+            return Ok("Stopped".to_string());
+        };
+
+        if let Ok(None) = process.try_wait() {
+            if self.crashes > 0 && self.started_at.elapsed() >= STABLE_AFTER {
+                self.crashes = 0;
+                self.last_error = None;
+            }
+            return Ok("Running".to_string());
+        }
+
+        let next_restart = match self.next_restart {
+            Some(at) => at,
+            None => {
+                let exit = match process.try_wait() {
+                    Ok(Some(status)) => format!("SurrealDB exited ({})", status),
+                    _ => "SurrealDB is not running".to_string(),
+                };
+                self.crashes += 1;
+                let delay = restart_backoff(self.crashes);
+                tracing::warn!("{}; restarting in {}s", exit, delay.as_secs());
+                self.last_error = Some(exit);
+                *self.next_restart.insert(Instant::now() + delay)
+            }
+        };
+        if Instant::now() < next_restart {
+            return Ok("Waiting to restart".to_string());
+        }
+
+        self.next_restart = None;
+        let restarted = match self.spawn() {
+            Ok(()) => self.wait_for_ready(30).await,
+            Err(e) => Err(e),
+        };
+        match restarted {
+            Ok(()) => {
+                self.restarts += 1;
+                Ok(format!("Restarted (restart {})", self.restarts))
+            }
+            Err(e) => {
+                // Don't leave a half-started process holding the port
+                if let Some(process) = self.process.as_mut() {
+                    let _ = process.kill();
+                    let _ = process.wait();
+                }
+                self.crashes += 1;
+                let delay = restart_backoff(self.crashes);
+                tracing::error!("Failed to restart SurrealDB: {}", e);
+                self.last_error = Some(e.clone());
+                self.next_restart = Some(Instant::now() + delay);
+                Err(e)
+            }
+        }
+    }
+
+    /// Pinned and running SurrealDB version
+    pub async fn version(&self) -> SidecarVersion {
+        // The server answers with e.g. "surrealdb-2.1.3"
+        let running = match reqwest::get(&format!("http://127.0.0.1:{}/version", self.port)).await {
+            Ok(response) if response.status().is_success() => response.text().await.ok(),
+            _ => None,
+        };
+        SidecarVersion {
+            pinned: SURREALDB_VERSION.to_string(),
+            running: running.map(|v| v.trim().trim_start_matches("surrealdb-").to_string()),
+            binary_path: self.binary_path.display().to_string(),
+            managed: self.managed,
+        }
+    }
+
     /// Stop the SurrealDB sidecar
     pub fn stop(&mut self) {
         if let Some(mut process) = self.process.take() {
@@ -137,6 +515,26 @@ impl SurrealDbSidecar {
     }
 }
 
+/// Job restarting the sidecar when its process exited, checking every 5 seconds
+pub fn job(sidecar: Arc<Mutex<SurrealDbSidecar>>) -> Job {
+    Job::new(
+        "sidecar",
+        "Restart the SurrealDB sidecar when it exits",
+        Schedule::every(SUPERVISE_INTERVAL),
+        move || {
+            let sidecar = sidecar.clone();
+            async move {
+                sidecar
+                    .lock()
+                    .await
+                    .supervise()
+                    .await
+                    .map_err(AppError::Database)
+            }
+        },
+    )
+}
+
 impl Drop for SurrealDbSidecar {
     fn drop(&mut self) {
         tracing::info!("SurrealDbSidecar being dropped, ensuring cleanup...");
@@ -150,3 +548,44 @@ impl Drop for SurrealDbSidecar {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_checksums() {
+        for platform in [
+            "linux-amd64",
+            "linux-arm64",
+            "darwin-amd64",
+            "darwin-arm64",
+            "windows-amd64",
+        ] {
+            let hash = pinned_checksum(platform)
+                .unwrap_or_else(|| panic!("no pinned checksum for {}", platform));
+            assert_eq!(hash.len(), 64, "{}", platform);
+            assert!(
+                hash.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')),
+                "{}",
+                platform
+            );
+        }
+        assert_eq!(pinned_checksum("freebsd-amd64"), None);
+    }
+
+    #[test]
+    fn test_restart_backoff() {
+        let secs: Vec<u64> = (1..=8).map(|n| restart_backoff(n).as_secs()).collect();
+        assert_eq!(secs, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+    }
+
+    #[test]
+    fn test_pick_port_falls_back_when_taken() {
+        let taken = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let picked = pick_port(port).unwrap();
+        assert_ne!(picked, port);
+        assert_ne!(picked, 0);
+    }
+}
@@ -0,0 +1,607 @@
+// Sprints module
+//
+// Sprints (or milestones) group tickets over a date range with an optional
+// goal. A ticket belongs to at most one sprint through its `sprint_id`. The
+// sprint summary counts tickets by status and computes a burndown of the
+// remaining estimate points per day. Closing a sprint moves its unfinished
+// tickets to another open sprint (or back to the backlog) and remembers them,
+// so the closed sprint's burndown still shows them as unfinished.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::tickets::{ticket_key, ArchiveFilter, Ticket, TicketFilters, TicketRecord, DONE_STATUS};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use surrealdb::sql::Thing;
+
+// ============================================================================
+// Sprint Model
+// ============================================================================
+
+/// Sprint as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SprintRecord {
+    pub id: Thing,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub goal: Option<String>,
+    #[serde(default)]
+    pub closed_at: Option<DateTime<Utc>>,
+    /// IDs of unfinished tickets moved out when the sprint closed
+    #[serde(default)]
+    pub carried_over: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// User-facing sprint with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sprint {
+    pub id: String,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub goal: Option<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub carried_over: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<SprintRecord> for Sprint {
+    fn from(record: SprintRecord) -> Self {
+        Sprint {
+            id: record.id.to_string(),
+            name: record.name,
+            start_date: record.start_date,
+            end_date: record.end_date,
+            goal: record.goal,
+            closed_at: record.closed_at,
+            carried_over: record.carried_over,
+            created_at: record.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSprintRequest {
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub goal: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSprintRequest {
+    pub name: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// An empty goal clears it
+    pub goal: Option<String>,
+}
+
+/// Remaining work at the end of one sprint day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurndownPoint {
+    pub date: NaiveDate,
+    pub remaining_points: f64,
+    pub remaining_tickets: usize,
+    /// Straight line from the total points on the first day to 0 on the last
+    pub ideal_points: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintSummary {
+    pub sprint: Sprint,
+    pub total_tickets: usize,
+    /// Sum of ticket estimates (unestimated tickets count as 0)
+    pub total_points: f64,
+    pub completed_points: f64,
+    /// Tickets currently in the sprint, by status
+    pub by_status: BTreeMap<String, usize>,
+    /// Unfinished tickets moved out when the sprint closed
+    pub carried_over: usize,
+    /// One point per day from the start to the end (or today, or the close)
+    pub burndown: Vec<BurndownPoint>,
+}
+
+/// Result of closing a sprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintClose {
+    pub sprint: Sprint,
+    /// Unfinished tickets moved to the next sprint or the backlog
+    pub carried_over: usize,
+}
+
+/// Record key of a sprint id ("sprints:abc", "sprints:⟨a-b⟩" or "abc")
+fn sprint_key(id: &str) -> &str {
+    id.strip_prefix("sprints:")
+        .unwrap_or(id)
+        .trim_start_matches('⟨')
+        .trim_end_matches('⟩')
+}
+
+fn validate_dates(start: NaiveDate, end: NaiveDate) -> Result<(), AppError> {
+    if end < start {
+        return Err(AppError::Validation(format!(
+            "Sprint end date {} is before its start date {}",
+            end, start
+        )));
+    }
+    Ok(())
+}
+
+/// Estimate points of a ticket (unestimated tickets count as 0)
+fn points(ticket: &Ticket) -> f64 {
+    ticket.estimate.unwrap_or(0.0)
+}
+
+/// Day a ticket was completed, if it was
+fn completed_on(ticket: &Ticket) -> Option<NaiveDate> {
+    let completed_at = ticket.completed_at.as_deref()?;
+    DateTime::parse_from_rfc3339(completed_at)
+        .ok()
+        .map(|date_time| date_time.with_timezone(&Utc).date_naive())
+}
+
+/// Remaining points and tickets per day from the sprint start through `last`
+fn burndown(
+    tickets: &[Ticket],
+    start: NaiveDate,
+    end: NaiveDate,
+    last: NaiveDate,
+) -> Vec<BurndownPoint> {
+    let total: f64 = tickets.iter().map(points).sum();
+    let sprint_days = (end - start).num_days().max(1) as f64;
+
+    let mut burndown = Vec::new();
+    let mut date = start;
+    while date <= last {
+        let remaining: Vec<&Ticket> = tickets
+            .iter()
+            .filter(|ticket| completed_on(ticket).is_none_or(|done| done > date))
+            .collect();
+        let elapsed = (date - start).num_days() as f64;
+        burndown.push(BurndownPoint {
+            date,
+            remaining_points: remaining.iter().copied().map(points).sum(),
+            remaining_tickets: remaining.len(),
+            ideal_points: (total * (1.0 - elapsed / sprint_days)).max(0.0),
+        });
+        date += Duration::days(1);
+    }
+    burndown
+}
+
+// ============================================================================
+// Sprint Operations
+// ============================================================================
+
+impl Database {
+    pub async fn create_sprint(&self, req: CreateSprintRequest) -> Result<Sprint, AppError> {
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "Sprint name cannot be empty".to_string(),
+            ));
+        }
+        validate_dates(req.start_date, req.end_date)?;
+
+        let mut result = self
+            .query("CREATE sprints CONTENT $sprint")
+            .bind((
+                "sprint",
+                serde_json::json!({
+                    "name": name,
+                    "start_date": req.start_date,
+                    "end_date": req.end_date,
+                    "goal": req.goal.filter(|goal| !goal.trim().is_empty()),
+                    "carried_over": Vec::<String>::new(),
+                    "created_at": Utc::now(),
+                }),
+            ))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to create sprint: {}", e)))?;
+
+        let created: Option<SprintRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse created sprint: {}", e)))?;
+
+        created
+            .map(|record| record.into())
+            .ok_or_else(|| AppError::Database("Sprint creation returned no result".to_string()))
+    }
+
+    /// All sprints, latest start first
+    pub async fn get_sprints(&self) -> Result<Vec<Sprint>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM sprints ORDER BY start_date DESC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get sprints: {}", e)))?;
+
+        let sprints: Vec<SprintRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse sprints: {}", e)))?;
+
+        Ok(sprints.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn sprint_record(&self, id: &str) -> Result<SprintRecord, AppError> {
+        let record: Option<SprintRecord> = self
+            .db
+            .select(("sprints", sprint_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get sprint: {}", e)))?;
+        record.ok_or_else(|| AppError::NotFound(format!("Sprint not found: {}", id)))
+    }
+
+    pub async fn get_sprint(&self, id: &str) -> Result<Sprint, AppError> {
+        Ok(self.sprint_record(id).await?.into())
+    }
+
+    pub async fn update_sprint(
+        &self,
+        id: &str,
+        req: UpdateSprintRequest,
+    ) -> Result<Sprint, AppError> {
+        let mut record = self.sprint_record(id).await?;
+
+        if let Some(name) = req.name {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(AppError::Validation(
+                    "Sprint name cannot be empty".to_string(),
+                ));
+            }
+            record.name = name.to_string();
+        }
+        if let Some(start_date) = req.start_date {
+            record.start_date = start_date;
+        }
+        if let Some(end_date) = req.end_date {
+            record.end_date = end_date;
+        }
+        if let Some(goal) = req.goal {
+            record.goal = Some(goal).filter(|goal| !goal.trim().is_empty());
+        }
+        validate_dates(record.start_date, record.end_date)?;
+
+        let updated: Option<SprintRecord> = self
+            .db
+            .update(("sprints", sprint_key(id)))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update sprint: {}", e)))?;
+
+        updated
+            .map(|record| record.into())
+            .ok_or_else(|| AppError::NotFound(format!("Sprint not found: {}", id)))
+    }
+
+    /// Delete a sprint; its tickets go back to the backlog
+    pub async fn delete_sprint(&self, id: &str) -> Result<(), AppError> {
+        let key = sprint_key(id);
+        self.transaction(
+            "LET $sprint = type::thing('sprints', $key);
+             UPDATE tickets SET sprint_id = NONE WHERE sprint_id = <string> $sprint;
+             DELETE $sprint;",
+            serde_json::json!({ "key": key }),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Put a ticket into an open sprint, or back into the backlog with `None`
+    pub async fn set_ticket_sprint(
+        &self,
+        ticket_id: &str,
+        sprint_id: Option<&str>,
+    ) -> Result<Ticket, AppError> {
+        let sprint_id = match sprint_id.filter(|id| !id.is_empty()) {
+            Some(sprint_id) => {
+                let sprint = self.sprint_record(sprint_id).await?;
+                if sprint.closed_at.is_some() {
+                    return Err(AppError::Validation(format!(
+                        "Sprint '{}' is closed",
+                        sprint.name
+                    )));
+                }
+                Some(sprint.id.to_string())
+            }
+            None => None,
+        };
+
+        let mut result = self
+            .query(
+                "UPDATE type::thing('tickets', $key)
+                 SET sprint_id = $sprint_id, updated_at = $now",
+            )
+            .bind(("key", ticket_key(ticket_id).to_string()))
+            .bind(("sprint_id", sprint_id))
+            .bind(("now", Utc::now().to_rfc3339()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to set ticket sprint: {}", e)))?;
+
+        let updated: Option<TicketRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse updated ticket: {}", e)))?;
+        updated
+            .map(|record| record.into())
+            .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", ticket_id)))
+    }
+
+    /// Status counts and burndown of a sprint
+    pub async fn get_sprint_summary(&self, id: &str) -> Result<SprintSummary, AppError> {
+        let sprint: Sprint = self.sprint_record(id).await?.into();
+
+        let tickets = self
+            .get_tickets(Some(TicketFilters {
+                sprint_id: Some(sprint.id.clone()),
+                archived: ArchiveFilter::All,
+                ..Default::default()
+            }))
+            .await?;
+        let mut by_status = BTreeMap::new();
+        for ticket in &tickets {
+            *by_status.entry(ticket.status.clone()).or_default() += 1;
+        }
+
+        let mut carried_over: Vec<TicketRecord> = Vec::new();
+        if !sprint.carried_over.is_empty() {
+            let mut result = self
+                .query("SELECT * FROM tickets WHERE <string> id IN $ids")
+                .bind(("ids", sprint.carried_over.clone()))
+                .await
+                .map_err(|e| {
+                    AppError::Database(format!("Failed to get carried over tickets: {}", e))
+                })?;
+            carried_over = result.take(0).map_err(|e| {
+                AppError::Database(format!("Failed to parse carried over tickets: {}", e))
+            })?;
+        }
+        let carried_over: Vec<Ticket> = carried_over.into_iter().map(|r| r.into()).collect();
+
+        let today = match sprint.closed_at {
+            Some(closed_at) => closed_at.date_naive(),
+            None => Utc::now().date_naive(),
+        };
+        let last = sprint.end_date.min(today);
+        let all: Vec<Ticket> = tickets.iter().chain(&carried_over).cloned().collect();
+        let burndown = burndown(&all, sprint.start_date, sprint.end_date, last);
+
+        Ok(SprintSummary {
+            total_tickets: tickets.len(),
+            total_points: tickets.iter().map(points).sum(),
+            completed_points: tickets
+                .iter()
+                .filter(|t| t.status == DONE_STATUS)
+                .map(points)
+                .sum(),
+            by_status,
+            carried_over: carried_over.len(),
+            burndown,
+            sprint,
+        })
+    }
+
+    /// Close a sprint, moving its unfinished tickets to `carry_over_to` (an
+    /// open sprint) or back to the backlog
+    pub async fn close_sprint(
+        &self,
+        id: &str,
+        carry_over_to: Option<&str>,
+    ) -> Result<SprintClose, AppError> {
+        let sprint = self.sprint_record(id).await?;
+        if sprint.closed_at.is_some() {
+            return Err(AppError::Validation(format!(
+                "Sprint '{}' is already closed",
+                sprint.name
+            )));
+        }
+
+        let target = match carry_over_to.filter(|target| !target.is_empty()) {
+            Some(target) => {
+                let target = self.sprint_record(target).await?;
+                if target.id == sprint.id {
+                    return Err(AppError::Validation(
+                        "Cannot carry tickets over into the sprint being closed".to_string(),
+                    ));
+                }
+                if target.closed_at.is_some() {
+                    return Err(AppError::Validation(format!(
+                        "Sprint '{}' is closed",
+                        target.name
+                    )));
+                }
+                Some(target.id.to_string())
+            }
+            None => None,
+        };
+
+        let mut result = self
+            .query(
+                "SELECT VALUE <string> id FROM tickets
+                 WHERE sprint_id = $sprint_id AND status != $done",
+            )
+            .bind(("sprint_id", sprint.id.to_string()))
+            .bind(("done", DONE_STATUS))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get sprint tickets: {}", e)))?;
+        let moved: Vec<String> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse sprint tickets: {}", e)))?;
+
+        let now = Utc::now();
+        self.transaction(
+            "UPDATE tickets SET sprint_id = $target, updated_at = $now
+                 WHERE <string> id IN $moved;
+             UPDATE type::thing('sprints', $key)
+                 SET closed_at = $closed_at, carried_over = $moved;",
+            serde_json::json!({
+                "key": sprint_key(id),
+                "target": target,
+                "moved": moved,
+                "now": now.to_rfc3339(),
+                "closed_at": now,
+            }),
+        )
+        .await?;
+
+        tracing::info!(
+            "Closed sprint '{}', carried over {} tickets",
+            sprint.name,
+            moved.len()
+        );
+        Ok(SprintClose {
+            sprint: self.get_sprint(id).await?,
+            carried_over: moved.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_burndown() {
+        let ticket = |estimate: f64, completed_at: Option<&str>| -> Ticket {
+            serde_json::from_value(serde_json::json!({
+                "id": "tickets:x", "source": "native", "source_id": null, "title": "x",
+                "description": null, "ticket_type": "task", "status": "todo",
+                "priority": "medium", "tags": [], "assignee": null, "reporter": null,
+                "estimate": estimate, "time_spent": null, "due_date": null,
+                "created_at": "", "updated_at": "", "parent_id": null,
+                "linked_tickets": [], "comments": [], "checklist": [],
+                "checklist_progress": null, "sprint_id": null,
+                "completed_at": completed_at, "metadata": {}
+            }))
+            .unwrap()
+        };
+        let tickets = vec![
+            ticket(3.0, Some("2024-05-02T10:00:00Z")),
+            ticket(5.0, None),
+            ticket(2.0, Some("2024-05-03T18:00:00Z")),
+        ];
+
+        let points = burndown(
+            &tickets,
+            date("2024-05-01"),
+            date("2024-05-05"),
+            date("2024-05-03"),
+        );
+        let remaining: Vec<(f64, usize, f64)> = points
+            .iter()
+            .map(|p| (p.remaining_points, p.remaining_tickets, p.ideal_points))
+            .collect();
+        assert_eq!(
+            remaining,
+            vec![(10.0, 3, 10.0), (7.0, 2, 7.5), (5.0, 1, 5.0)]
+        );
+
+        // Not started yet
+        assert!(burndown(
+            &tickets,
+            date("2024-05-01"),
+            date("2024-05-05"),
+            date("2024-04-30")
+        )
+        .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sprint_lifecycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let today = Utc::now().date_naive();
+        let new_sprint = |name: &str| CreateSprintRequest {
+            name: name.to_string(),
+            start_date: today - Duration::days(2),
+            end_date: today + Duration::days(5),
+            goal: Some("Ship it".to_string()),
+        };
+
+        assert!(db
+            .create_sprint(CreateSprintRequest {
+                end_date: today - Duration::days(5),
+                ..new_sprint("Backwards")
+            })
+            .await
+            .is_err());
+        let first = db.create_sprint(new_sprint("Sprint 1")).await.unwrap();
+        let second = db.create_sprint(new_sprint("Sprint 2")).await.unwrap();
+        assert_eq!(db.get_sprints().await.unwrap().len(), 2);
+
+        let mut tickets = Vec::new();
+        for (title, estimate) in [("A", 3.0), ("B", 5.0), ("C", 2.0)] {
+            let ticket = db
+                .create_ticket(
+                    serde_json::from_value(serde_json::json!({
+                        "title": title, "ticket_type": "task", "estimate": estimate
+                    }))
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+            let ticket = db
+                .set_ticket_sprint(&ticket.id, Some(first.id.as_str()))
+                .await
+                .unwrap();
+            assert_eq!(ticket.sprint_id.as_deref(), Some(first.id.as_str()));
+            tickets.push(ticket);
+        }
+        let done = db.move_ticket(&tickets[0].id, DONE_STATUS).await.unwrap();
+        assert!(done.ticket.completed_at.is_some());
+        db.move_ticket(&tickets[1].id, "in-progress").await.unwrap();
+
+        let summary = db.get_sprint_summary(&first.id).await.unwrap();
+        assert_eq!(summary.total_tickets, 3);
+        assert_eq!(summary.total_points, 10.0);
+        assert_eq!(summary.completed_points, 3.0);
+        assert_eq!(summary.by_status.get("todo"), Some(&1));
+        assert_eq!(summary.by_status.get("in-progress"), Some(&1));
+        assert_eq!(summary.burndown.len(), 3);
+        assert_eq!(summary.burndown[0].remaining_points, 10.0);
+        assert_eq!(summary.burndown[2].remaining_points, 7.0);
+
+        let closed = db
+            .close_sprint(&first.id, Some(second.id.as_str()))
+            .await
+            .unwrap();
+        assert_eq!(closed.carried_over, 2);
+        assert!(closed.sprint.closed_at.is_some());
+        assert!(db.close_sprint(&first.id, None).await.is_err());
+        assert!(db
+            .set_ticket_sprint(&tickets[2].id, Some(first.id.as_str()))
+            .await
+            .is_err());
+
+        let summary = db.get_sprint_summary(&first.id).await.unwrap();
+        assert_eq!(summary.total_tickets, 1);
+        assert_eq!(summary.carried_over, 2);
+        assert_eq!(summary.burndown.last().unwrap().remaining_points, 7.0);
+        assert_eq!(
+            db.get_sprint_summary(&second.id)
+                .await
+                .unwrap()
+                .total_tickets,
+            2
+        );
+
+        db.delete_sprint(&second.id).await.unwrap();
+        let backlog = db
+            .get_tickets(Some(TicketFilters {
+                sprint_id: Some(String::new()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(backlog.len(), 2);
+    }
+}
@@ -0,0 +1,592 @@
+// Ticket import
+//
+// Brings tickets over from other trackers: a Trello board export (JSON), a
+// Jira issue export (CSV) or any CSV with a column mapping. Source statuses
+// (Trello list names, Jira statuses) are mapped onto the target board's
+// columns. The external key (Trello short link, Jira issue key, or the mapped
+// key column) is stored as the ticket's `source_id`, and tickets whose key
+// already exists are skipped, so importing the same export twice is harmless.
+// Bad rows are skipped and reported like in the record CSV import.
+
+use crate::boards::{BoardConfig, DEFAULT_BOARD};
+use crate::csv_import::CsvRowError;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::reminders::parse_due_date;
+use crate::tickets::{Priority, TicketType, DONE_STATUS};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Row errors reported back at most (the import itself continues)
+const MAX_REPORTED_ERRORS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketImportFormat {
+    /// Trello board export ("Export as JSON")
+    Trello,
+    /// Jira issue search export ("Export CSV (all fields)")
+    JiraCsv,
+    /// Any CSV; needs a column mapping with a title column
+    Csv,
+}
+
+impl TicketImportFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Trello => "trello",
+            Self::JiraCsv => "jira_csv",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// How the export maps onto tickets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketImportMapping {
+    /// CSV column → ticket field: "key", "title", "description", "status",
+    /// "type", "priority", "assignee", "reporter", "tags", "estimate" or
+    /// "due_date". For Jira this extends the built-in mapping.
+    pub columns: HashMap<String, String>,
+    /// Source status → board column id (case-insensitive). Unmapped statuses
+    /// go to the column with the same id or name, else the start column.
+    pub statuses: HashMap<String, String>,
+    /// Board to import into (the default board without it)
+    pub board_id: Option<String>,
+    /// CSV delimiter (comma without it)
+    pub delimiter: Option<char>,
+    /// Separator inside the tags column (comma without it)
+    pub tag_separator: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TicketImportReport {
+    pub rows_read: usize,
+    pub imported: usize,
+    /// Tickets whose external key was already imported
+    pub duplicates: usize,
+    /// Archived Trello cards, which are not imported
+    pub skipped: usize,
+    pub failed: usize,
+    pub errors: Vec<CsvRowError>,
+}
+
+/// A ticket read from an export, before it is mapped onto the board
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ImportedTicket {
+    key: Option<String>,
+    title: String,
+    description: Option<String>,
+    status: Option<String>,
+    ticket_type: Option<String>,
+    priority: Option<String>,
+    assignee: Option<String>,
+    reporter: Option<String>,
+    tags: Vec<String>,
+    estimate: Option<f64>,
+    due_date: Option<String>,
+}
+
+/// Ticket field a CSV column maps onto
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Key,
+    Title,
+    Description,
+    Status,
+    Type,
+    Priority,
+    Assignee,
+    Reporter,
+    Tags,
+    Estimate,
+    DueDate,
+}
+
+impl Field {
+    fn parse(field: &str) -> Result<Self, AppError> {
+        Ok(match field {
+            "key" => Field::Key,
+            "title" => Field::Title,
+            "description" => Field::Description,
+            "status" => Field::Status,
+            "type" => Field::Type,
+            "priority" => Field::Priority,
+            "assignee" => Field::Assignee,
+            "reporter" => Field::Reporter,
+            "tags" => Field::Tags,
+            "estimate" => Field::Estimate,
+            "due_date" => Field::DueDate,
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "Invalid ticket field: {}",
+                    field
+                )))
+            }
+        })
+    }
+}
+
+/// Columns of a Jira CSV export; Jira repeats "Labels" once per label
+const JIRA_COLUMNS: [(&str, &str); 12] = [
+    ("Issue key", "key"),
+    ("Summary", "title"),
+    ("Description", "description"),
+    ("Status", "status"),
+    ("Issue Type", "type"),
+    ("Priority", "priority"),
+    ("Assignee", "assignee"),
+    ("Reporter", "reporter"),
+    ("Labels", "tags"),
+    ("Due Date", "due_date"),
+    ("Story Points", "estimate"),
+    ("Custom field (Story Points)", "estimate"),
+];
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+#[derive(Deserialize)]
+struct TrelloBoard {
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+}
+
+#[derive(Deserialize)]
+struct TrelloList {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    short_link: Option<String>,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+    due: Option<String>,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Deserialize)]
+struct TrelloLabel {
+    #[serde(default)]
+    name: String,
+    color: Option<String>,
+}
+
+/// Open cards of a Trello export, their list name as status, and the number
+/// of archived cards
+fn parse_trello(payload: &str) -> Result<(Vec<ImportedTicket>, usize), AppError> {
+    let board: TrelloBoard = serde_json::from_str(payload)
+        .map_err(|e| AppError::Validation(format!("Invalid Trello export: {}", e)))?;
+    let lists: HashMap<&str, &str> = board
+        .lists
+        .iter()
+        .map(|list| (list.id.as_str(), list.name.as_str()))
+        .collect();
+
+    let archived = board.cards.iter().filter(|card| card.closed).count();
+    let tickets = board
+        .cards
+        .into_iter()
+        .filter(|card| !card.closed)
+        .map(|card| ImportedTicket {
+            status: lists
+                .get(card.id_list.as_str())
+                .map(|name| name.to_string()),
+            key: Some(card.short_link.unwrap_or(card.id)),
+            title: card.name,
+            description: Some(card.desc).filter(|d| !d.is_empty()),
+            // Unnamed Trello labels only have a color
+            tags: card
+                .labels
+                .into_iter()
+                .filter_map(|label| Some(label.name).filter(|n| !n.is_empty()).or(label.color))
+                .collect(),
+            due_date: card.due,
+            ..Default::default()
+        })
+        .collect();
+    Ok((tickets, archived))
+}
+
+/// Rows of a CSV export as tickets, or the error of each bad row
+fn parse_csv(
+    payload: &str,
+    columns: &HashMap<String, String>,
+    mapping: &TicketImportMapping,
+) -> Result<Vec<Result<ImportedTicket, String>>, AppError> {
+    let delimiter = mapping.delimiter.unwrap_or(',');
+    if !delimiter.is_ascii() {
+        return Err(AppError::Validation(
+            "CSV delimiter must be an ASCII character".to_string(),
+        ));
+    }
+    let tag_separator = mapping.tag_separator.as_deref().unwrap_or(",");
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .flexible(true)
+        .from_reader(payload.as_bytes());
+    let headers = reader.headers().map_err(std::io::Error::from)?.clone();
+
+    let fields: Vec<Option<Field>> = headers
+        .iter()
+        .map(|header| {
+            columns
+                .get(header.trim())
+                .map(|field| Field::parse(field))
+                .transpose()
+        })
+        .collect::<Result<_, _>>()?;
+    if !fields.iter().any(|f| matches!(f, Some(Field::Title))) {
+        return Err(AppError::Validation(
+            "No CSV column is mapped to the ticket title".to_string(),
+        ));
+    }
+
+    Ok(reader
+        .records()
+        .map(|row| {
+            let row = row.map_err(|e| e.to_string())?;
+            let mut ticket = ImportedTicket::default();
+            for (field, cell) in fields.iter().zip(row.iter()) {
+                let Some(field) = field else { continue };
+                let cell = cell.trim();
+                if cell.is_empty() {
+                    continue;
+                }
+                let value = Some(cell.to_string());
+                match field {
+                    Field::Key => ticket.key = value,
+                    Field::Title => ticket.title = cell.to_string(),
+                    Field::Description => ticket.description = value,
+                    Field::Status => ticket.status = value,
+                    Field::Type => ticket.ticket_type = value,
+                    Field::Priority => ticket.priority = value,
+                    Field::Assignee => ticket.assignee = value,
+                    Field::Reporter => ticket.reporter = value,
+                    Field::Tags => ticket.tags.extend(
+                        cell.split(tag_separator)
+                            .map(str::trim)
+                            .filter(|t| !t.is_empty())
+                            .map(String::from),
+                    ),
+                    Field::Estimate => {
+                        ticket.estimate = Some(
+                            cell.parse()
+                                .map_err(|_| format!("Invalid estimate: {}", cell))?,
+                        )
+                    }
+                    Field::DueDate => ticket.due_date = value,
+                }
+            }
+            if ticket.title.is_empty() {
+                return Err("Missing title".to_string());
+            }
+            Ok(ticket)
+        })
+        .collect())
+}
+
+// ============================================================================
+// Mapping
+// ============================================================================
+
+fn ticket_type(value: Option<&str>) -> TicketType {
+    match value.map(str::to_lowercase).as_deref() {
+        Some("bug" | "defect") => TicketType::Bug,
+        Some("story" | "user story") => TicketType::Story,
+        Some("epic") => TicketType::Epic,
+        Some("feature" | "new feature" | "improvement") => TicketType::Feature,
+        _ => TicketType::Task,
+    }
+}
+
+/// Jira priority names map onto the nearest of the four levels
+fn priority(value: Option<&str>) -> Priority {
+    match value.map(str::to_lowercase).as_deref() {
+        Some("critical" | "blocker" | "highest" | "urgent") => Priority::Critical,
+        Some("high" | "major") => Priority::High,
+        Some("low" | "lowest" | "minor" | "trivial") => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+/// Due dates as the ticket editor stores them (RFC 3339 or a plain date);
+/// Jira writes "01/May/24 9:30 AM"
+fn due_date(value: &str) -> Result<String, String> {
+    if let Some(due) = parse_due_date(value) {
+        return Ok(if value.len() == 10 {
+            value.to_string()
+        } else {
+            due.to_rfc3339()
+        });
+    }
+    for format in ["%Y-%m-%d %H:%M", "%d/%b/%y %I:%M %p", "%d/%b/%Y %I:%M %p"] {
+        if let Ok(due) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(due.and_utc().to_rfc3339());
+        }
+    }
+    for format in ["%d/%b/%y", "%d/%b/%Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return Ok(date.format("%Y-%m-%d").to_string());
+        }
+    }
+    Err(format!("Invalid due date: {}", value))
+}
+
+/// Board column of a source status
+fn column_for(board: &BoardConfig, statuses: &HashMap<String, String>, status: &str) -> String {
+    let status = status.trim().to_lowercase();
+    let mapped = statuses
+        .iter()
+        .find(|(from, _)| from.trim().to_lowercase() == status)
+        .map(|(_, to)| to.to_lowercase());
+    let wanted = mapped.as_deref().unwrap_or(&status);
+    board
+        .columns
+        .iter()
+        .find(|c| c.id.to_lowercase() == wanted || c.name.to_lowercase() == wanted)
+        .unwrap_or_else(|| board.start_column())
+        .id
+        .clone()
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+/// Import tickets from a Trello, Jira or CSV export into a board
+/// WIP limits are not enforced, like when a column's tickets are moved in bulk.
+pub async fn import_tickets(
+    db: &Database,
+    format: TicketImportFormat,
+    payload: &str,
+    mapping: &TicketImportMapping,
+) -> Result<TicketImportReport, AppError> {
+    let board_id = mapping.board_id.as_deref().unwrap_or(DEFAULT_BOARD);
+    let board = db.get_board_config(board_id).await?;
+    for column in mapping.statuses.values() {
+        board.require_column(column)?;
+    }
+
+    let mut report = TicketImportReport::default();
+    let rows = match format {
+        TicketImportFormat::Trello => {
+            let (tickets, archived) = parse_trello(payload)?;
+            report.skipped = archived;
+            tickets.into_iter().map(Ok).collect()
+        }
+        TicketImportFormat::JiraCsv => {
+            let mut columns: HashMap<String, String> = JIRA_COLUMNS
+                .iter()
+                .map(|(column, field)| (column.to_string(), field.to_string()))
+                .collect();
+            columns.extend(mapping.columns.clone());
+            parse_csv(payload, &columns, mapping)?
+        }
+        TicketImportFormat::Csv => parse_csv(payload, &mapping.columns, mapping)?,
+    };
+
+    let mut known_keys: HashSet<String> = {
+        let mut result = db
+            .query(
+                "SELECT VALUE source_id FROM tickets WHERE source_id != NONE AND source_id != NULL",
+            )
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query ticket keys: {}", e)))?;
+        let keys: Vec<String> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse ticket keys: {}", e)))?;
+        keys.into_iter().collect()
+    };
+
+    let source = match format {
+        TicketImportFormat::JiraCsv => "jira",
+        _ => "native",
+    };
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut tickets = Vec::new();
+
+    for (index, row) in rows.into_iter().enumerate() {
+        report.rows_read += 1;
+        let ticket = row.and_then(|ticket| {
+            let due = ticket.due_date.as_deref().map(due_date).transpose()?;
+            Ok((ticket, due))
+        });
+        let (ticket, due) = match ticket {
+            Ok(ticket) => ticket,
+            Err(message) => {
+                report.failed += 1;
+                if report.errors.len() < MAX_REPORTED_ERRORS {
+                    report.errors.push(CsvRowError {
+                        row: index + 1,
+                        message,
+                    });
+                }
+                continue;
+            }
+        };
+
+        if let Some(key) = &ticket.key {
+            if !known_keys.insert(key.clone()) {
+                report.duplicates += 1;
+                continue;
+            }
+        }
+
+        let status = match &ticket.status {
+            Some(status) => column_for(&board, &mapping.statuses, status),
+            None => board.start_column().id.clone(),
+        };
+        let done = board.column(&status).is_some_and(|c| c.is_done) || status == DONE_STATUS;
+        let mut metadata = serde_json::json!({ "imported_from": format.as_str() });
+        if board_id != DEFAULT_BOARD {
+            metadata["board_id"] = board_id.into();
+        }
+
+        tickets.push(serde_json::json!({
+            "source": source,
+            "source_id": ticket.key,
+            "title": ticket.title,
+            "description": ticket.description,
+            "ticket_type": ticket_type(ticket.ticket_type.as_deref()),
+            "status": status,
+            "priority": priority(ticket.priority.as_deref()),
+            "tags": ticket.tags,
+            "assignee": ticket.assignee,
+            "reporter": ticket.reporter,
+            "estimate": ticket.estimate,
+            "time_spent": serde_json::Value::Null,
+            "due_date": due,
+            "created_at": &now,
+            "updated_at": &now,
+            "parent_id": serde_json::Value::Null,
+            "linked_tickets": [],
+            "comments": [],
+            "checklist": [],
+            "sprint_id": serde_json::Value::Null,
+            "completed_at": if done { Some(&now) } else { None },
+            "metadata": metadata,
+        }));
+    }
+
+    if !tickets.is_empty() {
+        let keys = db.reserve_ticket_keys(board_id, tickets.len()).await?;
+        for (ticket, key) in tickets.iter_mut().zip(keys) {
+            ticket["key"] = key.into();
+        }
+        report.imported = tickets.len();
+        db.transaction(
+            "INSERT INTO tickets $tickets;",
+            serde_json::json!({ "tickets": tickets }),
+        )
+        .await?;
+    }
+
+    tracing::info!(
+        "Ticket import ({}): {} rows, {} imported, {} duplicates, {} failed",
+        format.as_str(),
+        report.rows_read,
+        report.imported,
+        report.duplicates,
+        report.failed
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_trello() {
+        let payload = serde_json::json!({
+            "lists": [{ "id": "l1", "name": "Doing" }],
+            "cards": [
+                {
+                    "id": "c1", "name": "Card", "desc": "", "idList": "l1", "shortLink": "abc",
+                    "labels": [{ "name": "", "color": "red" }, { "name": "ui", "color": "blue" }],
+                    "due": "2024-05-01T12:00:00.000Z"
+                },
+                { "id": "c2", "name": "Old", "idList": "l1", "closed": true }
+            ]
+        });
+        let (tickets, archived) = parse_trello(&payload.to_string()).unwrap();
+        assert_eq!(archived, 1);
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].key.as_deref(), Some("abc"));
+        assert_eq!(tickets[0].status.as_deref(), Some("Doing"));
+        assert_eq!(tickets[0].tags, vec!["red", "ui"]);
+        assert_eq!(tickets[0].description, None);
+    }
+
+    #[test]
+    fn test_due_date() {
+        assert_eq!(due_date("2024-05-01").unwrap(), "2024-05-01");
+        assert_eq!(
+            due_date("01/May/24 9:30 AM").unwrap(),
+            "2024-05-01T09:30:00+00:00"
+        );
+        assert!(due_date("soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_jira_csv() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let payload = "Summary,Issue key,Status,Issue Type,Priority,Labels,Labels,Story Points\n\
+            Login fails,MOD-1,In Progress,Bug,Blocker,auth,web,3\n\
+            Add export,MOD-2,Done,New Feature,Minor,,,\n\
+            ,MOD-3,To Do,Task,,,,\n\
+            Tidy up,MOD-4,Waiting,Task,,,,x\n";
+        let mapping = TicketImportMapping::default();
+        let report = import_tickets(&db, TicketImportFormat::JiraCsv, payload, &mapping)
+            .await
+            .unwrap();
+        assert_eq!(report.rows_read, 4);
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.failed, 2);
+        assert_eq!(report.errors[0].row, 3);
+
+        let mut tickets = db.get_tickets(None).await.unwrap();
+        tickets.sort_by(|a, b| a.source_id.cmp(&b.source_id));
+        assert_eq!(tickets[0].status, "in-progress");
+        assert!(matches!(tickets[0].priority, Priority::Critical));
+        assert!(matches!(tickets[0].ticket_type, TicketType::Bug));
+        assert_eq!(tickets[0].tags, vec!["auth", "web"]);
+        assert_eq!(tickets[0].estimate, Some(3.0));
+        assert_eq!(tickets[1].status, "done");
+        assert!(tickets[1].completed_at.is_some());
+
+        // Re-importing skips known keys; statuses can be mapped explicitly
+        let mapping = TicketImportMapping {
+            statuses: HashMap::from([("Waiting".to_string(), "review".to_string())]),
+            ..Default::default()
+        };
+        let payload = "Summary,Issue key,Status\nLogin fails,MOD-1,To Do\nTidy up,MOD-4,Waiting\n";
+        let report = import_tickets(&db, TicketImportFormat::JiraCsv, payload, &mapping)
+            .await
+            .unwrap();
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.imported, 1);
+        let tickets = db.get_tickets(None).await.unwrap();
+        let tidy = tickets.iter().find(|t| t.title == "Tidy up").unwrap();
+        assert_eq!(tidy.status, "review");
+    }
+}
@@ -3,19 +3,30 @@
 // Provides CRUD operations for tickets (native and external)
 // Stores tickets in SurrealDB with a generic model
 
+use crate::boards::{board_condition, ticket_board_id, WipLimitExceeded};
+use crate::db::tags::TagCount;
 use crate::db::Database;
 use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use crate::undo::{self, UndoKind};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use surrealdb::sql::Thing;
 
+/// Ticket status of finished work
+pub const DONE_STATUS: &str = "done";
+
 // ============================================================================
 // Ticket Model
 // ============================================================================
 
 /// Internal ticket structure that matches SurrealDB's response format (with Thing ID)
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct TicketRecord {
+pub(crate) struct TicketRecord {
     pub id: Thing,
+    #[serde(default)]
+    pub key: Option<String>,
     pub source: TicketSource,
     pub source_id: Option<String>,
     pub title: String,
@@ -34,6 +45,18 @@ struct TicketRecord {
     pub parent_id: Option<String>,
     pub linked_tickets: Vec<String>,
     pub comments: Vec<Comment>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default)]
+    pub sprint_id: Option<String>,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    #[serde(default)]
+    pub watched: bool,
     pub metadata: serde_json::Value,
 }
 
@@ -41,6 +64,9 @@ struct TicketRecord {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ticket {
     pub id: String,
+    /// Human-readable sequential key ("MOD-123")
+    #[serde(default)]
+    pub key: Option<String>,
     pub source: TicketSource,
     pub source_id: Option<String>,
 
@@ -66,6 +92,29 @@ pub struct Ticket {
     pub linked_tickets: Vec<String>,
 
     pub comments: Vec<Comment>,
+
+    pub checklist: Vec<ChecklistItem>,
+    /// Share of checklist items done (0-100), None without a checklist
+    pub checklist_progress: Option<u8>,
+
+    pub sprint_id: Option<String>,
+    /// When the ticket last moved to done (cleared when it's reopened)
+    pub completed_at: Option<String>,
+
+    /// Archived tickets are left out of ticket queries unless asked for
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub archived_at: Option<String>,
+
+    /// Status changes and comments on watched tickets are notified
+    #[serde(default)]
+    pub watched: bool,
+
+    /// Typed links to other tickets (filled in by ticket queries)
+    #[serde(default)]
+    pub relations: Vec<TicketRelation>,
+
     pub metadata: serde_json::Value,
 }
 
@@ -73,6 +122,7 @@ impl From<TicketRecord> for Ticket {
     fn from(record: TicketRecord) -> Self {
         Ticket {
             id: record.id.to_string(),
+            key: record.key,
             source: record.source,
             source_id: record.source_id,
             title: record.title,
@@ -91,6 +141,14 @@ impl From<TicketRecord> for Ticket {
             parent_id: record.parent_id,
             linked_tickets: record.linked_tickets,
             comments: record.comments,
+            checklist_progress: checklist_progress(&record.checklist),
+            checklist: record.checklist,
+            sprint_id: record.sprint_id,
+            completed_at: record.completed_at,
+            archived: record.archived,
+            archived_at: record.archived_at,
+            watched: record.watched,
+            relations: Vec::new(),
             metadata: record.metadata,
         }
     }
@@ -132,6 +190,214 @@ pub struct Comment {
     pub created_at: String,
 }
 
+/// One step of a ticket's checklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub id: String,
+    pub text: String,
+    pub done: bool,
+}
+
+/// Kind of link between two tickets (stored as the `links` relation name)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketRelationKind {
+    /// The source ticket must be finished before the target
+    Blocks,
+    RelatesTo,
+    /// The source ticket duplicates the target
+    Duplicates,
+}
+
+impl TicketRelationKind {
+    const ALL: [TicketRelationKind; 3] = [Self::Blocks, Self::RelatesTo, Self::Duplicates];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Blocks => "blocks",
+            Self::RelatesTo => "relates_to",
+            Self::Duplicates => "duplicates",
+        }
+    }
+
+    fn parse(relation: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == relation)
+    }
+}
+
+/// A relation seen from one of its tickets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketRelation {
+    pub link_id: String,
+    pub kind: TicketRelationKind,
+    /// True when this ticket is the source (it blocks / duplicates the other)
+    pub outgoing: bool,
+    pub ticket_id: String,
+}
+
+/// Relation edge between two tickets, as read from `links`
+#[derive(Debug, Deserialize)]
+struct TicketLinkRow {
+    id: Thing,
+    #[serde(rename = "in")]
+    from: Thing,
+    out: Thing,
+    relation: String,
+}
+
+/// Whether `from` can reach `to` by following "blocks" edges
+fn blocks_path(edges: &[(String, String)], from: &str, to: &str) -> bool {
+    let mut seen = HashSet::new();
+    let mut pending = vec![from];
+    while let Some(current) = pending.pop() {
+        if current == to {
+            return true;
+        }
+        if seen.insert(current) {
+            pending.extend(
+                edges
+                    .iter()
+                    .filter(|(source, _)| source == current)
+                    .map(|(_, target)| target.as_str()),
+            );
+        }
+    }
+    false
+}
+
+/// Percentage of done items, rounded down
+fn checklist_progress(items: &[ChecklistItem]) -> Option<u8> {
+    if items.is_empty() {
+        return None;
+    }
+    let done = items.iter().filter(|item| item.done).count();
+    Some((done * 100 / items.len()) as u8)
+}
+
+/// SET clause keeping `completed_at` in step with a new status
+fn completion_update(done: bool, now: &str) -> String {
+    if done {
+        format!("completed_at = completed_at ?? '{}'", now)
+    } else {
+        "completed_at = NONE".to_string()
+    }
+}
+
+/// Record key of a ticket id ("tickets:abc", "tickets:⟨a-b⟩" or "abc")
+pub(crate) fn ticket_key(id: &str) -> &str {
+    id.strip_prefix("tickets:")
+        .unwrap_or(id)
+        .trim_start_matches('⟨')
+        .trim_end_matches('⟩')
+}
+
+// ============================================================================
+// People
+// ============================================================================
+
+/// Setting holding the people tickets can be assigned to (JSON array of names)
+pub const PEOPLE_SETTING: &str = "tickets.people";
+
+/// Setting holding the name comments are written as
+pub const CURRENT_USER_SETTING: &str = "tickets.current_user";
+
+/// Comment author when no current user is configured
+const DEFAULT_AUTHOR: &str = "User";
+
+/// The `tickets.*` settings
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new(
+            PEOPLE_SETTING,
+            SettingType::Json,
+            serde_json::json!([]),
+            "tickets",
+        )
+        .describe("People", "Names tickets can be assigned to"),
+        SettingDefinition::new(
+            CURRENT_USER_SETTING,
+            SettingType::String,
+            serde_json::Value::Null,
+            "tickets",
+        )
+        .describe("Current user", "Name comments are written as"),
+    ]
+}
+
+/// The local people list and the current user's identity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TicketPeople {
+    pub people: Vec<String>,
+    pub current_user: Option<String>,
+}
+
+impl TicketPeople {
+    /// Load the people settings (empty when unset)
+    pub async fn load(settings: &SettingsService) -> Result<Self, AppError> {
+        let people = match settings.get_setting(PEOPLE_SETTING).await? {
+            Some(value) => serde_json::from_str(&value)?,
+            None => Vec::new(),
+        };
+        let current_user = settings
+            .get_setting(CURRENT_USER_SETTING)
+            .await?
+            .filter(|name| !name.trim().is_empty());
+        Ok(Self {
+            people,
+            current_user,
+        })
+    }
+
+    /// Save trimmed, de-duplicated names; the current user joins the list
+    pub async fn save(mut self, settings: &SettingsService) -> Result<Self, AppError> {
+        let current_user = self
+            .current_user
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string);
+        let mut people: Vec<String> = Vec::new();
+        for name in self.people.iter().chain(&current_user) {
+            let name = name.trim();
+            if !name.is_empty() && !people.iter().any(|p| p == name) {
+                people.push(name.to_string());
+            }
+        }
+        self.people = people;
+        self.current_user = current_user;
+
+        settings
+            .save_setting(
+                PEOPLE_SETTING,
+                &serde_json::to_string(&self.people)?,
+                "json",
+                Some("tickets".to_string()),
+            )
+            .await?;
+        match &self.current_user {
+            Some(name) => {
+                settings
+                    .save_setting(
+                        CURRENT_USER_SETTING,
+                        name,
+                        "string",
+                        Some("tickets".to_string()),
+                    )
+                    .await?
+            }
+            None => settings.delete_setting(CURRENT_USER_SETTING).await?,
+        }
+        Ok(self)
+    }
+
+    /// Name new comments are written as
+    pub fn author(&self) -> String {
+        self.current_user
+            .clone()
+            .unwrap_or_else(|| DEFAULT_AUTHOR.to_string())
+    }
+}
+
 // ============================================================================
 // Request/Response Types
 // ============================================================================
@@ -162,14 +428,251 @@ pub struct UpdateTicketRequest {
     pub due_date: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Ticket query; unset fields mean "no filter"
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
 pub struct TicketFilters {
     pub source: Option<TicketSource>,
     pub ticket_type: Option<TicketType>,
     pub status: Option<String>,
     pub priority: Option<Priority>,
     pub assignee: Option<String>,
+    /// Sprint ID; empty matches tickets outside any sprint
+    pub sprint_id: Option<String>,
+    /// Board (kanban panel) ID; "default" also matches tickets without one
+    pub board_id: Option<String>,
+    pub archived: ArchiveFilter,
+    /// Watched (true) or unwatched (false) tickets only
+    pub watched: Option<bool>,
+    /// Tickets carrying all of these labels
     pub tags: Option<Vec<String>>,
+    /// Case-insensitive text matched against key, title and description
+    pub search: Option<String>,
+    pub date_range: Option<TicketDateRange>,
+    /// Sort keys in priority order (defaults to newest first)
+    pub sort: Vec<TicketSort>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Which tickets a query returns by archive state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFilter {
+    /// Tickets that are not archived
+    #[default]
+    Active,
+    Archived,
+    All,
+}
+
+/// Selected fields when sorting by priority, which sorts by severity
+const PRIORITY_RANK_FIELDS: &str = "*, (IF priority = 'critical' { 3 } \
+    ELSE IF priority = 'high' { 2 } ELSE IF priority = 'medium' { 1 } ELSE { 0 }) AS priority_rank";
+
+/// Date fields tickets can be filtered and sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketDateField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    DueDate,
+}
+
+/// Inclusive range over a date field (RFC 3339 or plain dates)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TicketDateRange {
+    #[serde(default)]
+    pub field: TicketDateField,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketSortField {
+    CreatedAt,
+    UpdatedAt,
+    DueDate,
+    Title,
+    Status,
+    /// Low < medium < high < critical
+    Priority,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketSort {
+    pub field: TicketSortField,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// One page of ticket query results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketPage {
+    pub total: usize,
+    pub tickets: Vec<Ticket>,
+}
+
+impl TicketDateField {
+    fn column(self) -> &'static str {
+        match self {
+            TicketDateField::CreatedAt => "created_at",
+            TicketDateField::UpdatedAt => "updated_at",
+            TicketDateField::DueDate => "due_date",
+        }
+    }
+}
+
+impl TicketFilters {
+    /// Build the WHERE clause (empty without filters) and its parameters
+    fn where_clause(&self) -> (String, serde_json::Map<String, serde_json::Value>) {
+        let mut conditions = Vec::new();
+        let mut bindings = serde_json::Map::new();
+        let mut filter = |name: &str, condition: String, value: serde_json::Value| {
+            conditions.push(condition);
+            bindings.insert(name.to_string(), value);
+        };
+
+        if let Some(source) = &self.source {
+            filter(
+                "source",
+                "source = $source".into(),
+                serde_json::json!(source),
+            );
+        }
+        if let Some(ticket_type) = &self.ticket_type {
+            filter(
+                "ticket_type",
+                "ticket_type = $ticket_type".into(),
+                serde_json::json!(ticket_type),
+            );
+        }
+        if let Some(status) = &self.status {
+            filter(
+                "status",
+                "status = $status".into(),
+                serde_json::json!(status),
+            );
+        }
+        if let Some(priority) = &self.priority {
+            filter(
+                "priority",
+                "priority = $priority".into(),
+                serde_json::json!(priority),
+            );
+        }
+        // An empty assignee matches unassigned tickets
+        if let Some(assignee) = &self.assignee {
+            let condition = if assignee.is_empty() {
+                "(assignee = NONE OR assignee = NULL OR assignee = $assignee)"
+            } else {
+                "assignee = $assignee"
+            };
+            filter("assignee", condition.into(), serde_json::json!(assignee));
+        }
+        if let Some(sprint_id) = &self.sprint_id {
+            let condition = if sprint_id.is_empty() {
+                "(sprint_id = NONE OR sprint_id = NULL OR sprint_id = $sprint_id)"
+            } else {
+                "sprint_id = $sprint_id"
+            };
+            filter("sprint_id", condition.into(), serde_json::json!(sprint_id));
+        }
+        if let Some(board_id) = &self.board_id {
+            filter(
+                "board",
+                board_condition(board_id).into(),
+                serde_json::json!(board_id),
+            );
+        }
+        if let Some(tags) = self.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            filter(
+                "tags",
+                "tags CONTAINSALL $tags".into(),
+                serde_json::json!(tags),
+            );
+        }
+        if let Some(search) = self
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        {
+            filter(
+                "search",
+                "(string::lowercase(title) CONTAINS $search \
+                  OR string::lowercase(description ?? '') CONTAINS $search \
+                  OR string::lowercase(key ?? '') CONTAINS $search)"
+                    .into(),
+                serde_json::json!(search.to_lowercase()),
+            );
+        }
+        if let Some(range) = &self.date_range {
+            let column = range.field.column();
+            if let Some(from) = &range.from {
+                filter(
+                    "from",
+                    format!("{} >= $from", column),
+                    serde_json::json!(from),
+                );
+            }
+            if let Some(to) = &range.to {
+                filter("to", format!("{} <= $to", column), serde_json::json!(to));
+            }
+        }
+
+        match self.watched {
+            Some(true) => conditions.push("watched = true".to_string()),
+            Some(false) => conditions.push("watched != true".to_string()),
+            None => {}
+        }
+        match self.archived {
+            ArchiveFilter::Active => conditions.push("archived != true".to_string()),
+            ArchiveFilter::Archived => conditions.push("archived = true".to_string()),
+            ArchiveFilter::All => {}
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+        (where_clause, bindings)
+    }
+
+    /// Build the ORDER BY clause
+    fn order(&self) -> String {
+        if self.sort.is_empty() {
+            return "created_at DESC".to_string();
+        }
+        self.sort
+            .iter()
+            .map(|sort| {
+                let field = match sort.field {
+                    TicketSortField::CreatedAt => "created_at",
+                    TicketSortField::UpdatedAt => "updated_at",
+                    TicketSortField::DueDate => "due_date",
+                    TicketSortField::Title => "title",
+                    TicketSortField::Status => "status",
+                    TicketSortField::Priority => "priority_rank",
+                };
+                format!("{} {}", field, if sort.descending { "DESC" } else { "ASC" })
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Outcome of moving a ticket between columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketMove {
+    /// The ticket after the move (unchanged if it was blocked)
+    pub ticket: Ticket,
+    pub moved: bool,
+    /// Set when the target column is over its WIP limit with this ticket
+    pub wip_exceeded: Option<WipLimitExceeded>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -183,18 +686,29 @@ pub struct CreateCommentRequest {
 // ============================================================================
 
 impl Database {
-    /// Create a new native ticket
+    /// Create a new native ticket in its board's start column, with the
+    /// board's next ticket key
     pub async fn create_ticket(&self, req: CreateTicketRequest) -> Result<Ticket, AppError> {
         let now = chrono::Utc::now().to_rfc3339();
+        let metadata = req.metadata.unwrap_or_else(|| serde_json::json!({}));
+        let board_id = ticket_board_id(&metadata);
+        let status = self
+            .get_board_config(board_id)
+            .await?
+            .start_column()
+            .id
+            .clone();
+        let key = self.reserve_ticket_keys(board_id, 1).await?.pop();
 
         // Create ticket data without id - SurrealDB will generate it
         let ticket_data = serde_json::json!({
+            "key": key,
             "source": "native",
             "source_id": serde_json::Value::Null,
             "title": req.title,
             "description": req.description,
             "ticket_type": req.ticket_type,
-            "status": "todo",
+            "status": status,
             "priority": req.priority.unwrap_or(Priority::Medium),
             "tags": req.tags.unwrap_or_default(),
             "assignee": req.assignee,
@@ -207,13 +721,17 @@ impl Database {
             "parent_id": serde_json::Value::Null,
             "linked_tickets": Vec::<String>::new(),
             "comments": Vec::<Comment>::new(),
-            "metadata": req.metadata.unwrap_or_else(|| serde_json::json!({}))
+            "checklist": Vec::<ChecklistItem>::new(),
+            "sprint_id": serde_json::Value::Null,
+            "completed_at": serde_json::Value::Null,
+            "archived": false,
+            "watched": false,
+            "metadata": metadata
         });
 
         // Store in database - use simple CREATE, SurrealDB will generate ID
         let query = format!("CREATE tickets CONTENT {}", ticket_data);
         let mut result = self
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to create ticket: {}", e)))?;
@@ -249,13 +767,19 @@ impl Database {
             ));
         }
         if let Some(status) = req.status {
+            updates.push(completion_update(status == DONE_STATUS, &now));
             updates.push(format!("status = '{}'", status.replace("'", "''")));
         }
         if let Some(priority) = req.priority {
             updates.push(format!("priority = '{:?}'", priority).to_lowercase());
         }
         if let Some(assignee) = req.assignee {
-            updates.push(format!("assignee = '{}'", assignee.replace("'", "''")));
+            // An empty assignee unassigns the ticket
+            if assignee.trim().is_empty() {
+                updates.push("assignee = NONE".to_string());
+            } else {
+                updates.push(format!("assignee = '{}'", assignee.replace("'", "''")));
+            }
         }
         if let Some(tags) = req.tags {
             let tags_json = serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string());
@@ -279,7 +803,6 @@ impl Database {
 
         let query = format!("UPDATE {} SET {}", id_owned, updates.join(", "));
         let mut result = self
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to update ticket: {}", e)))?;
@@ -293,83 +816,265 @@ impl Database {
             .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id_owned)))
     }
 
-    /// Delete a ticket
+    /// Delete a ticket together with its attachment rows and relations, and drop references
+    /// to it from other tickets (parent and links) in one transaction
+    /// Everything removed or changed is kept in the undo journal. Attachment
+    /// files are left on disk until the journal entry is pruned.
     pub async fn delete_ticket(&self, id: &str) -> Result<(), AppError> {
-        let id_owned = id.to_string();
-        let query = format!("DELETE {}", id_owned);
-        self.db
-            .query(query)
-            .await
-            .map_err(|e| AppError::Database(format!("Failed to delete ticket: {}", e)))?;
+        let key = ticket_key(id);
+        let refs = [id.to_string(), Thing::from(("tickets", key)).to_string()];
+
+        let statements = format!(
+            "LET $ticket = type::thing('tickets', $key);
+             LET $row = (SELECT * FROM $ticket);
+             LET $children = (SELECT id, parent_id FROM tickets WHERE parent_id IN $refs);
+             LET $linking = (SELECT id, array::intersect(linked_tickets, $refs) AS refs
+                 FROM tickets WHERE linked_tickets CONTAINSANY $refs);
+             LET $attachments = (DELETE attachments WHERE owner = $ticket RETURN BEFORE);
+             LET $links = (DELETE links WHERE in = $ticket OR out = $ticket RETURN BEFORE);
+             {}
+             UPDATE tickets SET parent_id = NONE WHERE parent_id IN $refs;
+             UPDATE tickets SET linked_tickets = array::complement(linked_tickets, $refs)
+                 WHERE linked_tickets CONTAINSANY $refs;
+             DELETE $ticket;",
+            undo::journal_statement(
+                UndoKind::DeleteTicket,
+                "string::concat('Ticket ', $row[0].title)",
+                "array::len($row)",
+                "{ tickets: $row, attachments: $attachments, links: $links,
+                   children: $children, linking: $linking }",
+            )
+        );
+        self.transaction(&statements, serde_json::json!({ "key": key, "refs": refs }))
+            .await?;
 
         Ok(())
     }
 
+    /// Get one ticket with its relations
+    pub async fn get_ticket(&self, id: &str) -> Result<Ticket, AppError> {
+        let record: Option<TicketRecord> = self
+            .db
+            .select(("tickets", ticket_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get ticket: {}", e)))?;
+        let record =
+            record.ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id)))?;
+        let mut tickets = vec![Ticket::from(record)];
+        self.attach_ticket_relations(&mut tickets).await?;
+        Ok(tickets.remove(0))
+    }
+
+    /// Watch a ticket, or stop watching it with `watched: false`
+    pub async fn watch_ticket(&self, id: &str, watched: bool) -> Result<Ticket, AppError> {
+        let mut result = self
+            .query("UPDATE type::thing('tickets', $key) SET watched = $watched")
+            .bind(("key", ticket_key(id).to_string()))
+            .bind(("watched", watched))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to watch ticket: {}", e)))?;
+
+        let updated: Option<TicketRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse watched ticket: {}", e)))?;
+        updated
+            .map(Ticket::from)
+            .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id)))
+    }
+
+    /// Watched tickets that aren't archived, newest first
+    pub async fn get_watched_tickets(&self) -> Result<Vec<Ticket>, AppError> {
+        self.get_tickets(Some(TicketFilters {
+            watched: Some(true),
+            ..Default::default()
+        }))
+        .await
+    }
+
+    /// Archive a ticket, or bring it back with `archived: false`
+    pub async fn archive_ticket(&self, id: &str, archived: bool) -> Result<Ticket, AppError> {
+        let mut result = self
+            .query(
+                "UPDATE type::thing('tickets', $key) \
+                 SET archived = $archived, archived_at = IF $archived { $now } ELSE { NONE }",
+            )
+            .bind(("key", ticket_key(id).to_string()))
+            .bind(("archived", archived))
+            .bind(("now", chrono::Utc::now().to_rfc3339()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to archive ticket: {}", e)))?;
+
+        let updated: Option<TicketRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse archived ticket: {}", e)))?;
+        updated
+            .map(Ticket::from)
+            .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id)))
+    }
+
+    /// Archive tickets that have been done for more than `days` days;
+    /// returns how many were archived
+    pub async fn archive_done_older_than(&self, days: u32) -> Result<usize, AppError> {
+        let now = chrono::Utc::now();
+        let cutoff = now - chrono::Duration::days(i64::from(days));
+
+        // Tickets finished before completion was tracked fall back to their
+        // last update
+        let mut result = self
+            .query(
+                "UPDATE tickets SET archived = true, archived_at = $now
+                 WHERE archived != true AND (
+                     (completed_at != NONE AND completed_at != NULL AND completed_at < $cutoff)
+                     OR ((completed_at = NONE OR completed_at = NULL)
+                         AND status = $done AND updated_at < $cutoff)
+                 )
+                 RETURN VALUE id",
+            )
+            .bind(("now", now.to_rfc3339()))
+            .bind(("cutoff", cutoff.to_rfc3339()))
+            .bind(("done", DONE_STATUS))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to archive tickets: {}", e)))?;
+
+        let archived: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to archive tickets: {}", e)))?;
+        tracing::info!(
+            "Archived {} tickets done for more than {} days",
+            archived.len(),
+            days
+        );
+        Ok(archived.len())
+    }
+
+    /// Archived tickets, most recently archived first
+    pub async fn get_archived_tickets(&self) -> Result<Vec<Ticket>, AppError> {
+        let mut tickets = self
+            .get_tickets(Some(TicketFilters {
+                archived: ArchiveFilter::Archived,
+                ..Default::default()
+            }))
+            .await?;
+        tickets.sort_by(|a, b| b.archived_at.cmp(&a.archived_at));
+        Ok(tickets)
+    }
+
     /// Get tickets with optional filters
     pub async fn get_tickets(
         &self,
         filters: Option<TicketFilters>,
     ) -> Result<Vec<Ticket>, AppError> {
-        let mut query = "SELECT * FROM tickets".to_string();
-        let mut conditions = Vec::new();
+        Ok(self
+            .query_tickets(filters.unwrap_or_default())
+            .await?
+            .tickets)
+    }
 
-        if let Some(f) = filters {
-            if let Some(source) = f.source {
-                conditions.push(format!("source = '{:?}'", source).to_lowercase());
-            }
-            if let Some(ticket_type) = f.ticket_type {
-                conditions.push(format!("ticket_type = '{:?}'", ticket_type).to_lowercase());
-            }
-            if let Some(status) = f.status {
-                conditions.push(format!("status = '{}'", status));
-            }
-            if let Some(priority) = f.priority {
-                conditions.push(format!("priority = '{:?}'", priority).to_lowercase());
-            }
-            if let Some(assignee) = f.assignee {
-                conditions.push(format!("assignee = '{}'", assignee));
-            }
-            if let Some(tags) = f.tags {
-                for tag in tags {
-                    conditions.push(format!("'{}' IN tags", tag));
-                }
-            }
+    /// Run a ticket query, returning one page and the total match count
+    pub async fn query_tickets(&self, filters: TicketFilters) -> Result<TicketPage, AppError> {
+        let (where_clause, bindings) = filters.where_clause();
+        let order = filters.order();
+        let mut page = String::new();
+        if let Some(limit) = filters.limit {
+            page.push_str(&format!(" LIMIT {}", limit));
         }
-
-        if !conditions.is_empty() {
-            query.push_str(" WHERE ");
-            query.push_str(&conditions.join(" AND "));
+        if let Some(offset) = filters.offset {
+            page.push_str(&format!(" START {}", offset));
         }
-
-        query.push_str(" ORDER BY created_at DESC");
+        let fields = if filters
+            .sort
+            .iter()
+            .any(|s| s.field == TicketSortField::Priority)
+        {
+            PRIORITY_RANK_FIELDS
+        } else {
+            "*"
+        };
+        let statement = format!(
+            "SELECT {fields} FROM tickets{where_clause} ORDER BY {order}{page};
+             SELECT count() AS total FROM tickets{where_clause} GROUP ALL;"
+        );
 
         let mut result = self
-            .db
-            .query(query)
+            .query(statement)
+            .bind(bindings)
             .await
             .map_err(|e| AppError::Database(format!("Failed to query tickets: {}", e)))?;
 
         let tickets: Vec<TicketRecord> = result
             .take(0)
             .map_err(|e| AppError::Database(format!("Failed to parse tickets: {}", e)))?;
+        let total: Option<usize> = result
+            .take((1, "total"))
+            .map_err(|e| AppError::Database(format!("Failed to count tickets: {}", e)))?;
 
-        Ok(tickets.into_iter().map(|r| r.into()).collect())
+        let mut tickets: Vec<Ticket> = tickets.into_iter().map(|r| r.into()).collect();
+        self.attach_ticket_relations(&mut tickets).await?;
+
+        Ok(TicketPage {
+            total: total.unwrap_or(0),
+            tickets,
+        })
     }
 
-    /// Move ticket to different status
-    pub async fn move_ticket(&self, id: &str, new_status: &str) -> Result<Ticket, AppError> {
+    /// Move ticket to another column of its board. A move past the column's
+    /// WIP limit goes ahead with a warning, or is refused if the limit is strict.
+    pub async fn move_ticket(&self, id: &str, new_status: &str) -> Result<TicketMove, AppError> {
         let now = chrono::Utc::now().to_rfc3339();
         let id_owned = id.to_string();
         let status_owned = new_status.to_string();
 
+        let record: Option<TicketRecord> = self
+            .db
+            .select(("tickets", ticket_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get ticket: {}", e)))?;
+        let record =
+            record.ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id)))?;
+        let board_id = ticket_board_id(&record.metadata);
+        let board = self.get_board_config(board_id).await?;
+        let column = board.require_column(new_status)?;
+
+        let wip_exceeded = match column.wip_limit {
+            Some(limit) if record.status != column.id => {
+                let count = self
+                    .board_status_counts(board_id)
+                    .await?
+                    .get(&column.id)
+                    .copied()
+                    .unwrap_or(0)
+                    + 1;
+                (count > limit).then(|| WipLimitExceeded {
+                    column_id: column.id.clone(),
+                    limit,
+                    count,
+                    blocked: column.wip_strict,
+                })
+            }
+            _ => None,
+        };
+        if let Some(exceeded) = wip_exceeded.as_ref().filter(|e| e.blocked) {
+            tracing::info!(
+                "Refused moving {} to '{}': WIP limit {} reached",
+                id,
+                exceeded.column_id,
+                exceeded.limit
+            );
+            return Ok(TicketMove {
+                ticket: record.into(),
+                moved: false,
+                wip_exceeded,
+            });
+        }
+
         let query = format!(
-            "UPDATE {} SET status = '{}', updated_at = '{}'",
-            id_owned,
+            "UPDATE {} SET status = '{}', {}, updated_at = '{}'",
+            record.id,
             status_owned.replace("'", "''"),
+            completion_update(column.is_done, &now),
             now
         );
         let mut result = self
-            .db
             .query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to move ticket: {}", e)))?;
@@ -378,9 +1083,22 @@ impl Database {
             .take(0)
             .map_err(|e| AppError::Database(format!("Failed to parse moved ticket: {}", e)))?;
 
-        updated
+        let ticket: Ticket = updated
             .map(|record| record.into())
-            .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id_owned)))
+            .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id_owned)))?;
+        if record.status != status_owned {
+            events::publish(AppEvent::TicketMoved {
+                ticket_id: ticket.id.clone(),
+                board_id: board_id.to_string(),
+                from_status: record.status,
+                to_status: status_owned,
+            });
+        }
+        Ok(TicketMove {
+            ticket,
+            moved: true,
+            wip_exceeded,
+        })
     }
 
     /// Add comment to ticket
@@ -406,11 +1124,298 @@ impl Database {
             "UPDATE {} SET comments += {}, updated_at = '{}'",
             ticket_id_owned, comment_json, now
         );
-        self.db
-            .query(query)
+        self.query(query)
             .await
             .map_err(|e| AppError::Database(format!("Failed to add comment: {}", e)))?;
 
         Ok(comment)
     }
+
+    /// Whether the ticket has a comment with this ID
+    pub async fn ticket_has_comment(
+        &self,
+        ticket_id: &str,
+        comment_id: &str,
+    ) -> Result<bool, AppError> {
+        let record: Option<TicketRecord> = self
+            .db
+            .select(("tickets", ticket_key(ticket_id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get ticket: {}", e)))?;
+        let record =
+            record.ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", ticket_id)))?;
+        Ok(record.comments.iter().any(|c| c.id == comment_id))
+    }
+
+    // ------------------------------------------------------------------------
+    // Relations
+    // ------------------------------------------------------------------------
+
+    /// Relate two tickets (from → to). "blocks" links may not form a cycle.
+    /// Returns the link id; relating the same pair twice returns the existing
+    /// link, and "relates_to" counts in either direction.
+    pub async fn add_ticket_relation(
+        &self,
+        from: &str,
+        to: &str,
+        kind: TicketRelationKind,
+    ) -> Result<String, AppError> {
+        let from = Thing::from(("tickets", ticket_key(from)));
+        let to = Thing::from(("tickets", ticket_key(to)));
+        if from == to {
+            return Err(AppError::Validation(
+                "A ticket cannot be related to itself".to_string(),
+            ));
+        }
+
+        let links = self.ticket_links(&[from.clone(), to.clone()]).await?;
+        let existing = links.iter().find(|link| {
+            link.relation == kind.as_str()
+                && ((link.from == from && link.out == to)
+                    || (kind == TicketRelationKind::RelatesTo
+                        && link.from == to
+                        && link.out == from))
+        });
+        if let Some(link) = existing {
+            return Ok(link.id.to_string());
+        }
+
+        if kind == TicketRelationKind::Blocks {
+            let mut result = self
+                .query(
+                    "SELECT VALUE [<string> in, <string> out] FROM links
+                     WHERE relation = $relation",
+                )
+                .bind(("relation", kind.as_str()))
+                .await
+                .map_err(|e| AppError::Database(format!("Failed to query blockers: {}", e)))?;
+            let edges: Vec<(String, String)> = result
+                .take(0)
+                .map_err(|e| AppError::Database(format!("Failed to parse blockers: {}", e)))?;
+            if blocks_path(&edges, &to.to_string(), &from.to_string()) {
+                return Err(AppError::Validation(format!(
+                    "{} already blocks {} (directly or through other tickets)",
+                    to, from
+                )));
+            }
+        }
+
+        self.link_records(&from.to_string(), &to.to_string(), kind.as_str())
+            .await
+    }
+
+    /// Remove a ticket relation by its link id
+    pub async fn remove_ticket_relation(&self, link_id: &str) -> Result<(), AppError> {
+        self.unlink_records(link_id).await
+    }
+
+    /// Relation edges touching any of the tickets
+    async fn ticket_links(&self, tickets: &[Thing]) -> Result<Vec<TicketLinkRow>, AppError> {
+        let relations: Vec<&str> = TicketRelationKind::ALL
+            .iter()
+            .map(|kind| kind.as_str())
+            .collect();
+        let mut result = self
+            .query(
+                "SELECT id, in, out, relation FROM links
+                 WHERE relation IN $relations AND (in IN $tickets OR out IN $tickets)",
+            )
+            .bind(("relations", relations))
+            .bind(("tickets", tickets.to_vec()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query ticket relations: {}", e)))?;
+        result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse ticket relations: {}", e)))
+    }
+
+    /// Fill in the relations of each ticket
+    async fn attach_ticket_relations(&self, tickets: &mut [Ticket]) -> Result<(), AppError> {
+        if tickets.is_empty() {
+            return Ok(());
+        }
+        let things: Vec<Thing> = tickets
+            .iter()
+            .map(|ticket| Thing::from(("tickets", ticket_key(&ticket.id))))
+            .collect();
+        let links = self.ticket_links(&things).await?;
+
+        for (ticket, thing) in tickets.iter_mut().zip(&things) {
+            ticket.relations = links
+                .iter()
+                .filter_map(|link| {
+                    let kind = TicketRelationKind::parse(&link.relation)?;
+                    let outgoing = link.from == *thing;
+                    if !outgoing && link.out != *thing {
+                        return None;
+                    }
+                    Some(TicketRelation {
+                        link_id: link.id.to_string(),
+                        kind,
+                        outgoing,
+                        ticket_id: if outgoing { &link.out } else { &link.from }.to_string(),
+                    })
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Labels
+    // ------------------------------------------------------------------------
+
+    /// List the labels (ticket tags) in use, most used first
+    pub async fn list_ticket_labels(&self) -> Result<Vec<TagCount>, AppError> {
+        let mut result = self
+            .query("RETURN array::flatten(SELECT VALUE tags FROM tickets)")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to list labels: {}", e)))?;
+
+        let all_labels: Vec<String> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract labels: {}", e)))?;
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for label in all_labels {
+            *counts.entry(label).or_default() += 1;
+        }
+
+        let mut labels: Vec<TagCount> = counts
+            .into_iter()
+            .map(|(tag, count)| TagCount { tag, count })
+            .collect();
+        labels.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+        Ok(labels)
+    }
+
+    /// Rename a label on every ticket carrying it (merging with an existing label)
+    /// Returns the number of tickets updated
+    pub async fn rename_ticket_label(&self, from: &str, to: &str) -> Result<usize, AppError> {
+        let to = to.trim();
+        if to.is_empty() {
+            return Err(AppError::Validation(
+                "New label name cannot be empty".to_string(),
+            ));
+        }
+
+        let mut result = self
+            .query(
+                "UPDATE tickets
+                 SET tags = array::union(array::complement(tags, [$from]), [$to]),
+                     updated_at = $now
+                 WHERE tags CONTAINS $from
+                 RETURN VALUE id",
+            )
+            .bind(("from", from.to_string()))
+            .bind(("to", to.to_string()))
+            .bind(("now", chrono::Utc::now().to_rfc3339()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to rename label: {}", e)))?;
+
+        let updated: Vec<Thing> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to extract updated tickets: {}", e)))?;
+
+        tracing::info!(
+            "Renamed label '{}' to '{}' on {} tickets",
+            from,
+            to,
+            updated.len()
+        );
+        Ok(updated.len())
+    }
+
+    // ------------------------------------------------------------------------
+    // Checklist
+    // ------------------------------------------------------------------------
+
+    async fn ticket_checklist(&self, id: &str) -> Result<Vec<ChecklistItem>, AppError> {
+        let record: Option<TicketRecord> = self
+            .db
+            .select(("tickets", ticket_key(id)))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get ticket: {}", e)))?;
+        record
+            .map(|record| record.checklist)
+            .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id)))
+    }
+
+    async fn set_ticket_checklist(
+        &self,
+        id: &str,
+        checklist: Vec<ChecklistItem>,
+    ) -> Result<Ticket, AppError> {
+        let mut result = self
+            .query(
+                "UPDATE type::thing('tickets', $key)
+                 SET checklist = $checklist, updated_at = $now",
+            )
+            .bind(("key", ticket_key(id).to_string()))
+            .bind(("checklist", checklist))
+            .bind(("now", chrono::Utc::now().to_rfc3339()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to update checklist: {}", e)))?;
+        let updated: Option<TicketRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse updated ticket: {}", e)))?;
+        updated
+            .map(|record| record.into())
+            .ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", id)))
+    }
+
+    /// Append an item to a ticket's checklist
+    pub async fn add_checklist_item(
+        &self,
+        ticket_id: &str,
+        text: &str,
+    ) -> Result<Ticket, AppError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(AppError::Validation(
+                "Checklist item text cannot be empty".to_string(),
+            ));
+        }
+        let mut checklist = self.ticket_checklist(ticket_id).await?;
+        checklist.push(ChecklistItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            text: text.to_string(),
+            done: false,
+        });
+        self.set_ticket_checklist(ticket_id, checklist).await
+    }
+
+    /// Flip a checklist item between done and open
+    pub async fn toggle_checklist_item(
+        &self,
+        ticket_id: &str,
+        item_id: &str,
+    ) -> Result<Ticket, AppError> {
+        let mut checklist = self.ticket_checklist(ticket_id).await?;
+        let item = checklist
+            .iter_mut()
+            .find(|item| item.id == item_id)
+            .ok_or_else(|| AppError::NotFound(format!("Checklist item not found: {}", item_id)))?;
+        item.done = !item.done;
+        self.set_ticket_checklist(ticket_id, checklist).await
+    }
+
+    /// Remove an item from a ticket's checklist
+    pub async fn remove_checklist_item(
+        &self,
+        ticket_id: &str,
+        item_id: &str,
+    ) -> Result<Ticket, AppError> {
+        let mut checklist = self.ticket_checklist(ticket_id).await?;
+        let count = checklist.len();
+        checklist.retain(|item| item.id != item_id);
+        if checklist.len() == count {
+            return Err(AppError::NotFound(format!(
+                "Checklist item not found: {}",
+                item_id
+            )));
+        }
+        self.set_ticket_checklist(ticket_id, checklist).await
+    }
 }
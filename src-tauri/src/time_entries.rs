@@ -0,0 +1,503 @@
+// Ticket time tracking
+//
+// Time spent on tickets is stored as `time_entry` records in the `records`
+// table, in the same shape the time-tracker plugin writes, so timers started
+// from a ticket show up in the plugin and vice versa. The record timestamp is
+// the start time; `data.end_time` and `data.duration_seconds` stay null while
+// a timer runs. Only one timer runs at a time. A ticket's `time_spent` is kept
+// as the total hours of its finished entries.
+
+use crate::db::{Database, StagedRecord};
+use crate::error::AppError;
+use crate::tickets::UpdateTicketRequest;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Record type of time entries
+pub const TIME_ENTRY_TYPE: &str = "time_entry";
+
+/// Record source shared with the time-tracker plugin
+const TIME_ENTRY_SOURCE: &str = "time-tracker";
+
+/// Project of entries tracked from a ticket
+const TICKET_PROJECT: &str = "Tickets";
+
+// ============================================================================
+// Time Entry Model
+// ============================================================================
+
+/// `data` of a time entry record
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct TimeEntryData {
+    ticket_id: Option<String>,
+    project: String,
+    description: String,
+    end_time: Option<DateTime<Utc>>,
+    duration_seconds: Option<i64>,
+    tags: Vec<String>,
+    billable: bool,
+    hourly_rate: Option<f64>,
+    notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub id: String,
+    pub ticket_id: Option<String>,
+    pub project: String,
+    pub description: String,
+    pub start_time: DateTime<Utc>,
+    /// None while the timer runs
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration_seconds: Option<i64>,
+    pub billable: bool,
+    pub notes: Option<String>,
+}
+
+impl TimeEntry {
+    fn from_record(record: StagedRecord) -> Option<Self> {
+        let data: TimeEntryData = serde_json::from_value(record.data).ok()?;
+        Some(TimeEntry {
+            id: record.id?.to_string(),
+            ticket_id: data.ticket_id,
+            project: data.project,
+            description: data.description,
+            start_time: record.timestamp,
+            end_time: data.end_time,
+            duration_seconds: data.duration_seconds,
+            billable: data.billable,
+            notes: data.notes,
+        })
+    }
+}
+
+/// Manually logged time on a ticket; give an end time or a duration
+#[derive(Debug, Deserialize)]
+pub struct CreateTimeEntryRequest {
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration_minutes: Option<u32>,
+    pub description: Option<String>,
+    pub billable: Option<bool>,
+    pub notes: Option<String>,
+}
+
+/// Finished time of one ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketTime {
+    pub ticket_id: String,
+    pub total_seconds: i64,
+    pub entries: usize,
+}
+
+/// Finished time of one day, by ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayTime {
+    pub date: NaiveDate,
+    pub total_seconds: i64,
+    pub by_ticket: BTreeMap<String, i64>,
+}
+
+/// Time tracked on tickets over a date range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSummary {
+    pub total_seconds: i64,
+    /// Most time first
+    pub by_ticket: Vec<TicketTime>,
+    /// Days with tracked time, oldest first
+    pub by_day: Vec<DayTime>,
+    /// Timer still running, if any (not counted in the totals)
+    pub running: Option<TimeEntry>,
+}
+
+/// Ticket reference forms a time entry may carry ("tickets:abc" and "abc")
+fn ticket_refs(ticket_id: &str) -> [String; 2] {
+    let key = ticket_id.strip_prefix("tickets:").unwrap_or(ticket_id);
+    [format!("tickets:{}", key), key.to_string()]
+}
+
+/// Totals per ticket and per day (by start date) of finished entries
+fn summarize(entries: &[TimeEntry]) -> TimeSummary {
+    let mut by_ticket: BTreeMap<String, TicketTime> = BTreeMap::new();
+    let mut by_day: BTreeMap<NaiveDate, DayTime> = BTreeMap::new();
+    let mut total_seconds = 0;
+
+    for entry in entries {
+        let (Some(seconds), Some(ticket_id)) = (entry.duration_seconds, &entry.ticket_id) else {
+            continue;
+        };
+        let ticket_id = ticket_refs(ticket_id)[0].clone();
+        total_seconds += seconds;
+
+        let ticket = by_ticket
+            .entry(ticket_id.clone())
+            .or_insert_with(|| TicketTime {
+                ticket_id: ticket_id.clone(),
+                total_seconds: 0,
+                entries: 0,
+            });
+        ticket.total_seconds += seconds;
+        ticket.entries += 1;
+
+        let date = entry.start_time.date_naive();
+        let day = by_day.entry(date).or_insert_with(|| DayTime {
+            date,
+            total_seconds: 0,
+            by_ticket: BTreeMap::new(),
+        });
+        day.total_seconds += seconds;
+        *day.by_ticket.entry(ticket_id).or_default() += seconds;
+    }
+
+    let mut by_ticket: Vec<TicketTime> = by_ticket.into_values().collect();
+    by_ticket.sort_by(|a, b| b.total_seconds.cmp(&a.total_seconds));
+
+    TimeSummary {
+        total_seconds,
+        by_ticket,
+        by_day: by_day.into_values().collect(),
+        running: entries.iter().find(|e| e.end_time.is_none()).cloned(),
+    }
+}
+
+// ============================================================================
+// Time Entry Operations
+// ============================================================================
+
+impl Database {
+    /// Time entries of a ticket, newest first
+    pub async fn get_ticket_time_entries(
+        &self,
+        ticket_id: &str,
+    ) -> Result<Vec<TimeEntry>, AppError> {
+        self.query_time_entries("data.ticket_id IN $tickets", ticket_refs(ticket_id))
+            .await
+    }
+
+    async fn query_time_entries(
+        &self,
+        condition: &str,
+        tickets: [String; 2],
+    ) -> Result<Vec<TimeEntry>, AppError> {
+        let mut result = self
+            .query(format!(
+                "SELECT * FROM records WHERE record_type = $type AND {} ORDER BY timestamp DESC",
+                condition
+            ))
+            .bind(("type", TIME_ENTRY_TYPE))
+            .bind(("tickets", tickets))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query time entries: {}", e)))?;
+
+        let records: Vec<StagedRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse time entries: {}", e)))?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(TimeEntry::from_record)
+            .collect())
+    }
+
+    /// The running timer, if any (on any ticket or project)
+    pub async fn get_running_timer(&self) -> Result<Option<TimeEntry>, AppError> {
+        let entries = self
+            .query_time_entries(
+                "(data.end_time = NONE OR data.end_time = NULL)",
+                Default::default(),
+            )
+            .await?;
+        Ok(entries.into_iter().next())
+    }
+
+    /// Start a timer on a ticket; fails while another timer runs
+    pub async fn start_ticket_timer(
+        &self,
+        ticket_id: &str,
+        description: Option<String>,
+    ) -> Result<TimeEntry, AppError> {
+        if let Some(running) = self.get_running_timer().await? {
+            return Err(AppError::Validation(format!(
+                "A timer is already running ({}); stop it first",
+                running.description
+            )));
+        }
+        self.create_time_entry(ticket_id, Utc::now(), None, description, true, None)
+            .await
+    }
+
+    /// Stop the ticket's running timer
+    pub async fn stop_ticket_timer(&self, ticket_id: &str) -> Result<TimeEntry, AppError> {
+        let running = self
+            .get_ticket_time_entries(ticket_id)
+            .await?
+            .into_iter()
+            .find(|entry| entry.end_time.is_none())
+            .ok_or_else(|| {
+                AppError::NotFound(format!("No running timer on ticket {}", ticket_id))
+            })?;
+
+        let end_time = Utc::now();
+        let duration = (end_time - running.start_time).num_seconds().max(0);
+        let record = self
+            .patch_record(
+                &running.id,
+                &serde_json::json!({
+                    "data": { "end_time": end_time, "duration_seconds": duration },
+                    "metadata": { "status": "completed" },
+                }),
+            )
+            .await?;
+        self.sync_ticket_time_spent(ticket_id).await?;
+
+        TimeEntry::from_record(record)
+            .ok_or_else(|| AppError::Database("Failed to parse stopped time entry".to_string()))
+    }
+
+    /// Log time on a ticket after the fact
+    pub async fn add_time_entry(
+        &self,
+        ticket_id: &str,
+        req: CreateTimeEntryRequest,
+    ) -> Result<TimeEntry, AppError> {
+        let end_time = match (req.end_time, req.duration_minutes) {
+            (Some(end_time), _) => end_time,
+            (None, Some(minutes)) => req.start_time + chrono::Duration::minutes(minutes.into()),
+            (None, None) => {
+                return Err(AppError::Validation(
+                    "A time entry needs an end time or a duration".to_string(),
+                ))
+            }
+        };
+        if end_time <= req.start_time {
+            return Err(AppError::Validation(
+                "A time entry must end after it starts".to_string(),
+            ));
+        }
+
+        let entry = self
+            .create_time_entry(
+                ticket_id,
+                req.start_time,
+                Some(end_time),
+                req.description,
+                req.billable.unwrap_or(true),
+                req.notes,
+            )
+            .await?;
+        self.sync_ticket_time_spent(ticket_id).await?;
+        Ok(entry)
+    }
+
+    /// Delete a time entry and update its ticket's time spent
+    pub async fn delete_time_entry(&self, id: &str) -> Result<(), AppError> {
+        let ticket_id = self
+            .get_record(id.strip_prefix("records:").unwrap_or(id))
+            .await?
+            .filter(|record| record.record_type == TIME_ENTRY_TYPE)
+            .ok_or_else(|| AppError::NotFound(format!("Time entry not found: {}", id)))?
+            .data
+            .get("ticket_id")
+            .and_then(|t| t.as_str())
+            .map(str::to_string);
+
+        self.delete_record(id).await?;
+        if let Some(ticket_id) = ticket_id {
+            self.sync_ticket_time_spent(&ticket_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Ticket time per ticket and per day for entries started in `from..=to`
+    /// (all time without bounds), optionally for one ticket
+    pub async fn get_time_summary(
+        &self,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        ticket_id: Option<&str>,
+    ) -> Result<TimeSummary, AppError> {
+        let mut entries = match ticket_id {
+            Some(ticket_id) => self.get_ticket_time_entries(ticket_id).await?,
+            None => {
+                self.query_time_entries(
+                    "data.ticket_id != NONE AND data.ticket_id != NULL",
+                    Default::default(),
+                )
+                .await?
+            }
+        };
+        entries.retain(|entry| {
+            let date = entry.start_time.date_naive();
+            from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+        });
+        Ok(summarize(&entries))
+    }
+
+    async fn create_time_entry(
+        &self,
+        ticket_id: &str,
+        start_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+        description: Option<String>,
+        billable: bool,
+        notes: Option<String>,
+    ) -> Result<TimeEntry, AppError> {
+        let [ticket_ref, key] = ticket_refs(ticket_id);
+        let ticket: Option<serde_json::Value> = self
+            .db
+            .select(("tickets", key.as_str()))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get ticket: {}", e)))?;
+        let ticket =
+            ticket.ok_or_else(|| AppError::NotFound(format!("Ticket not found: {}", ticket_id)))?;
+        let title = ticket["title"].as_str().unwrap_or_default();
+
+        let description = description
+            .filter(|d| !d.trim().is_empty())
+            .unwrap_or_else(|| title.to_string());
+        let data = TimeEntryData {
+            ticket_id: Some(ticket_ref.clone()),
+            project: TICKET_PROJECT.to_string(),
+            description: description.clone(),
+            end_time,
+            duration_seconds: end_time.map(|end| (end - start_time).num_seconds()),
+            billable,
+            notes,
+            ..Default::default()
+        };
+        let mut record = StagedRecord::new(
+            TIME_ENTRY_TYPE.to_string(),
+            TIME_ENTRY_SOURCE.to_string(),
+            serde_json::to_value(data)?,
+        );
+        record.timestamp = start_time;
+        let status = if end_time.is_some() {
+            "completed"
+        } else {
+            "active"
+        };
+        record.metadata.status = Some(status.to_string());
+        record.metadata.title = Some(format!("{}: {}", TICKET_PROJECT, description));
+        record.metadata.description = Some(format!("Ticket: {}", ticket_ref));
+
+        let created = self.create_record(record).await?;
+        TimeEntry::from_record(created)
+            .ok_or_else(|| AppError::Database("Failed to parse created time entry".to_string()))
+    }
+
+    /// Set a ticket's `time_spent` to the hours of its finished entries
+    async fn sync_ticket_time_spent(&self, ticket_id: &str) -> Result<(), AppError> {
+        let seconds: i64 = self
+            .get_ticket_time_entries(ticket_id)
+            .await?
+            .iter()
+            .filter_map(|entry| entry.duration_seconds)
+            .sum();
+        let hours = (seconds as f64 / 36.0).round() / 100.0;
+
+        let [ticket_ref, _] = ticket_refs(ticket_id);
+        self.update_ticket(
+            &ticket_ref,
+            UpdateTicketRequest {
+                title: None,
+                description: None,
+                status: None,
+                priority: None,
+                assignee: None,
+                tags: None,
+                estimate: None,
+                time_spent: Some(hours),
+                due_date: None,
+            },
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_ticket_time_tracking() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let ticket = db
+            .create_ticket(
+                serde_json::from_value(serde_json::json!({
+                    "title": "Fix login", "ticket_type": "bug"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let running = db.start_ticket_timer(&ticket.id, None).await.unwrap();
+        assert_eq!(running.description, "Fix login");
+        assert!(running.end_time.is_none());
+        assert!(db.start_ticket_timer(&ticket.id, None).await.is_err());
+        let summary = db.get_time_summary(None, None, None).await.unwrap();
+        assert_eq!(summary.running.unwrap().id, running.id);
+
+        let stopped = db.stop_ticket_timer(&ticket.id).await.unwrap();
+        assert!(stopped.end_time.is_some());
+        assert!(db.stop_ticket_timer(&ticket.id).await.is_err());
+
+        let day = |value: &str| -> DateTime<Utc> { value.parse().unwrap() };
+        let entry = |start: &str, minutes: u32| CreateTimeEntryRequest {
+            start_time: day(start),
+            end_time: None,
+            duration_minutes: Some(minutes),
+            description: Some("Debugging".to_string()),
+            billable: None,
+            notes: None,
+        };
+        db.add_time_entry(&ticket.id, entry("2024-05-01T09:00:00Z", 90))
+            .await
+            .unwrap();
+        let second = db
+            .add_time_entry(&ticket.id, entry("2024-05-02T09:00:00Z", 30))
+            .await
+            .unwrap();
+        assert!(db
+            .add_time_entry(
+                &ticket.id,
+                CreateTimeEntryRequest {
+                    end_time: Some(day("2024-05-01T08:00:00Z")),
+                    ..entry("2024-05-01T09:00:00Z", 0)
+                }
+            )
+            .await
+            .is_err());
+
+        let tickets = db.get_tickets(None).await.unwrap();
+        let time_spent = tickets[0].time_spent.unwrap();
+        assert!((2.0..2.1).contains(&time_spent));
+
+        let summary = db
+            .get_time_summary(
+                Some("2024-05-01".parse().unwrap()),
+                Some("2024-05-02".parse().unwrap()),
+                Some(&ticket.id),
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary.total_seconds, 2 * 3600);
+        assert_eq!(summary.by_ticket.len(), 1);
+        assert_eq!(summary.by_ticket[0].entries, 2);
+        let days: Vec<i64> = summary.by_day.iter().map(|d| d.total_seconds).collect();
+        assert_eq!(days, vec![90 * 60, 30 * 60]);
+
+        db.delete_time_entry(&second.id).await.unwrap();
+        let tickets = db.get_tickets(None).await.unwrap();
+        assert!(tickets[0].time_spent.unwrap() < 1.6);
+        assert_eq!(
+            db.get_ticket_time_entries(&ticket.id).await.unwrap().len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,467 @@
+// Undo journal
+//
+// Destructive operations (clearing all records, deleting the records of a
+// type, deleting a ticket or a prompt package) copy what they delete into the
+// `undo_journal` table, in the same transaction as the delete. Entries keep
+// the rows as stored (record IDs, datetimes and relations intact), so
+// `undo_last_operation` puts them back as they were; rows that exist again
+// in the meantime (e.g. records fetched since) are left alone.
+//
+// Entries are kept for `undo.window_minutes` (30 by default) and pruned by
+// the `undo` job. Attachment files of a deleted ticket stay on disk until its
+// entry is pruned, so an undone delete gets its files back too.
+
+use crate::db::Database;
+use crate::error::AppError;
+use crate::scheduler::{Job, Schedule};
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use crate::workspaces::WorkspaceRegistry;
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::{Mutex, RwLock};
+
+pub const WINDOW_SETTING: &str = "undo.window_minutes";
+
+const DEFAULT_WINDOW_MINUTES: u64 = 30;
+
+/// How often expired entries are pruned
+const PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Tables a deleted prompt package is spread over
+const PROMPT_PACKAGE_TABLES: &[&str] = &[
+    "prompt_packages",
+    "prompt_sections",
+    "prompt_templates",
+    "prompt_separator_sets",
+    "prompt_data_types",
+    "prompt_tags",
+    "prompt_section_revisions",
+    "prompt_presets",
+    "prompt_chains",
+    "prompt_chain_runs",
+];
+
+/// Operations that can be undone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoKind {
+    ClearRecords,
+    DeleteRecordsByType,
+    DeleteTicket,
+    DeletePromptPackage,
+}
+
+impl UndoKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            UndoKind::ClearRecords => "clear_records",
+            UndoKind::DeleteRecordsByType => "delete_records_by_type",
+            UndoKind::DeleteTicket => "delete_ticket",
+            UndoKind::DeletePromptPackage => "delete_prompt_package",
+        }
+    }
+
+    /// Tables of the payload, restored in this order
+    fn tables(self) -> &'static [&'static str] {
+        match self {
+            UndoKind::ClearRecords | UndoKind::DeleteRecordsByType => &["records"],
+            UndoKind::DeleteTicket => &["tickets", "attachments"],
+            UndoKind::DeletePromptPackage => PROMPT_PACKAGE_TABLES,
+        }
+    }
+
+    /// Statements restoring what the delete changed besides removing rows
+    fn restore_references(self) -> &'static str {
+        match self {
+            UndoKind::DeleteTicket => {
+                "LET $links = $entry.payload.links;
+                 IF array::len($links) > 0 { INSERT RELATION INTO links $links; };
+                 FOR $child IN $entry.payload.children {
+                     UPDATE $child.id SET parent_id = $child.parent_id;
+                 };
+                 FOR $linking IN $entry.payload.linking {
+                     UPDATE $linking.id SET linked_tickets = array::union(linked_tickets, $linking.refs);
+                 };"
+            }
+            _ => "",
+        }
+    }
+}
+
+/// Statement journaling a delete, to run in the delete's transaction
+/// `description` and `items` are SurrealQL expressions, `payload` an object
+/// of table names and their deleted rows. Nothing is journaled when no items
+/// were deleted.
+pub(crate) fn journal_statement(
+    kind: UndoKind,
+    description: &str,
+    items: &str,
+    payload: &str,
+) -> String {
+    format!(
+        "IF ({items}) > 0 {{
+             CREATE undo_journal CONTENT {{
+                 kind: '{kind}',
+                 description: {description},
+                 items: ({items}),
+                 payload: {payload},
+                 created_at: time::now()
+             }} RETURN NONE;
+         }};",
+        kind = kind.as_str()
+    )
+}
+
+/// Statement journaling the prompt package `$pkg_id` before it's deleted
+pub(crate) fn journal_prompt_package_statement() -> String {
+    let payload: Vec<String> = PROMPT_PACKAGE_TABLES
+        .iter()
+        .map(|table| match *table {
+            "prompt_packages" => {
+                format!("{table}: (SELECT * FROM type::thing('{table}', $pkg_id))")
+            }
+            _ => format!("{table}: (SELECT * FROM {table} WHERE package_id = $pkg_id)"),
+        })
+        .collect();
+    format!(
+        "LET $package = (SELECT * FROM type::thing('prompt_packages', $pkg_id))[0];
+         {}",
+        journal_statement(
+            UndoKind::DeletePromptPackage,
+            "string::concat('Package ', $package.name)",
+            "count($package)",
+            &format!("{{ {} }}", payload.join(", ")),
+        )
+    )
+}
+
+/// Value of the last statement of a transaction that returned one
+pub(crate) fn returned<T: DeserializeOwned>(response: &mut surrealdb::Response) -> Option<T> {
+    (0..response.num_statements())
+        .rev()
+        .find_map(|index| response.take::<Option<T>>(index).ok().flatten())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct UndoEntryRecord {
+    id: Thing,
+    kind: UndoKind,
+    description: String,
+    items: usize,
+    created_at: DateTime<Utc>,
+}
+
+/// A journaled operation that can still be undone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoableOperation {
+    pub id: String,
+    pub kind: UndoKind,
+    /// E.g. "Ticket Fix login" or "Records of type gitlab_pipeline"
+    pub description: String,
+    /// Records, tickets or packages deleted
+    pub items: usize,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UndoEntryRecord {
+    fn into_operation(self, window: Duration) -> UndoableOperation {
+        UndoableOperation {
+            id: self.id.id.to_raw(),
+            kind: self.kind,
+            description: self.description,
+            items: self.items,
+            created_at: self.created_at,
+            expires_at: self.created_at + window,
+        }
+    }
+}
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![SettingDefinition::new(
+        WINDOW_SETTING,
+        SettingType::Number,
+        DEFAULT_WINDOW_MINUTES.into(),
+        "undo",
+    )
+    .describe(
+        "Undo window",
+        "Minutes deleted records, tickets and prompt packages can be restored",
+    )
+    .range(Some(1.0), Some(1440.0))]
+}
+
+/// How long operations can be undone
+pub async fn window(settings: &SettingsService) -> Result<Duration, AppError> {
+    let minutes = settings
+        .get_value(WINDOW_SETTING)
+        .await?
+        .as_u64()
+        .unwrap_or(DEFAULT_WINDOW_MINUTES);
+    Ok(Duration::minutes(minutes as i64))
+}
+
+// ============================================================================
+// Journal Operations
+// ============================================================================
+
+impl Database {
+    /// Operations that can still be undone, newest first
+    pub async fn list_undoable_operations(
+        &self,
+        window: Duration,
+    ) -> Result<Vec<UndoableOperation>, AppError> {
+        let mut result = self
+            .query(
+                "SELECT id, kind, description, items, created_at FROM undo_journal
+                 WHERE created_at >= $cutoff ORDER BY created_at DESC",
+            )
+            .bind(("cutoff", Utc::now() - window))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query the undo journal: {}", e)))?;
+
+        let entries: Vec<UndoEntryRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse the undo journal: {}", e)))?;
+
+        Ok(entries
+            .into_iter()
+            .map(|e| e.into_operation(window))
+            .collect())
+    }
+
+    /// Restore what the newest operation deleted and drop it from the journal
+    pub async fn undo_last_operation(
+        &self,
+        window: Duration,
+    ) -> Result<UndoableOperation, AppError> {
+        let operation = self
+            .list_undoable_operations(window)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound("Nothing to undo".to_string()))?;
+
+        let mut statements =
+            "LET $entry = (SELECT * FROM ONLY type::thing('undo_journal', $key));\n".to_string();
+        for table in operation.kind.tables() {
+            statements.push_str(&format!(
+                "LET $rows = $entry.payload.{table} ?? [];
+                 IF array::len($rows) > 0 {{ INSERT IGNORE INTO {table} $rows; }};\n"
+            ));
+        }
+        statements.push_str(operation.kind.restore_references());
+        statements.push_str("\nDELETE type::thing('undo_journal', $key);");
+
+        self.transaction(&statements, ("key", operation.id.clone()))
+            .await?;
+
+        tracing::info!(
+            "Undid {} ({} items)",
+            operation.description,
+            operation.items
+        );
+        Ok(operation)
+    }
+
+    /// Delete expired entries, returning the attachment files of their tickets
+    async fn prune_undo_journal(&self, window: Duration) -> Result<Vec<String>, AppError> {
+        let mut result = self
+            .query(
+                "LET $expired = (SELECT kind, payload.attachments.stored_name AS files
+                     FROM undo_journal WHERE created_at < $cutoff);
+                 DELETE undo_journal WHERE created_at < $cutoff;
+                 RETURN array::flatten($expired[WHERE kind = 'delete_ticket'].files);",
+            )
+            .bind(("cutoff", Utc::now() - window))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to prune the undo journal: {}", e)))?;
+
+        let files: Option<Vec<String>> = result
+            .take(2)
+            .map_err(|e| AppError::Database(format!("Failed to prune the undo journal: {}", e)))?;
+        Ok(files.unwrap_or_default())
+    }
+}
+
+/// Delete expired entries and the attachment files only they still refer to
+pub async fn prune(
+    database: &Database,
+    window: Duration,
+    attachments_dir: &Path,
+) -> Result<usize, AppError> {
+    let files = database.prune_undo_journal(window).await?;
+    for file in &files {
+        if let Err(e) = std::fs::remove_file(attachments_dir.join(file)) {
+            tracing::warn!("Failed to remove attachment file {}: {}", file, e);
+        }
+    }
+    Ok(files.len())
+}
+
+/// Job pruning expired undo entries every ten minutes
+pub fn job(
+    settings_service: Arc<RwLock<SettingsService>>,
+    database: Arc<Mutex<Database>>,
+    workspaces: Arc<Mutex<WorkspaceRegistry>>,
+) -> Job {
+    Job::new(
+        "undo",
+        "Prune expired undo journal entries",
+        Schedule::every(PRUNE_INTERVAL),
+        move || {
+            let settings_service = settings_service.clone();
+            let database = database.clone();
+            let workspaces = workspaces.clone();
+            async move {
+                let window = window(&*settings_service.read().await).await?;
+                let attachments_dir =
+                    crate::attachments::storage_dir(&workspaces.lock().await.active()?.data_dir);
+                let files = prune(&*database.lock().await, window, &attachments_dir).await?;
+                Ok(format!("{} attachment files removed", files))
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StagedRecord;
+    use tempfile::TempDir;
+
+    fn record(record_type: &str, id: u32) -> StagedRecord {
+        StagedRecord::new(
+            record_type.to_string(),
+            "ci".to_string(),
+            serde_json::json!({ "id": id }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_undo_record_deletes() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let window = Duration::minutes(30);
+
+        db.upsert_record(record("gitlab_pipeline", 1))
+            .await
+            .unwrap();
+        db.upsert_record(record("gitlab_pipeline", 2))
+            .await
+            .unwrap();
+        db.upsert_record(record("gitlab_job", 1)).await.unwrap();
+        assert!(db.undo_last_operation(window).await.is_err());
+
+        // Deleting nothing isn't journaled
+        assert_eq!(db.delete_records_by_type("unknown").await.unwrap(), 0);
+        assert!(db
+            .list_undoable_operations(window)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            db.delete_records_by_type("gitlab_pipeline").await.unwrap(),
+            2
+        );
+        assert_eq!(db.clear_all_records().await.unwrap(), 1);
+        let operations = db.list_undoable_operations(window).await.unwrap();
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0].kind, UndoKind::ClearRecords);
+        assert_eq!(operations[1].items, 2);
+
+        // A record fetched again since isn't overwritten
+        let mut refetched = record("gitlab_job", 1);
+        refetched.metadata.title = Some("Refetched".to_string());
+        db.upsert_record(refetched).await.unwrap();
+
+        let undone = db.undo_last_operation(window).await.unwrap();
+        assert_eq!(undone.kind, UndoKind::ClearRecords);
+        let jobs = db.get_records_by_type("gitlab_job").await.unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].metadata.title.as_deref(), Some("Refetched"));
+
+        let undone = db.undo_last_operation(window).await.unwrap();
+        assert_eq!(undone.description, "Records of type gitlab_pipeline");
+        let pipelines = db.get_records_by_type("gitlab_pipeline").await.unwrap();
+        assert_eq!(pipelines.len(), 2);
+        assert!(pipelines.iter().all(|r| r.id.is_some()));
+        assert!(db
+            .list_undoable_operations(window)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Expired entries can't be undone and are pruned
+        db.delete_records_by_type("gitlab_pipeline").await.unwrap();
+        assert!(db
+            .list_undoable_operations(Duration::zero())
+            .await
+            .unwrap()
+            .is_empty());
+        prune(&db, Duration::zero(), temp_dir.path()).await.unwrap();
+        assert!(db
+            .list_undoable_operations(window)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_undo_ticket_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let window = Duration::minutes(30);
+
+        let ticket = |title: &str| {
+            serde_json::from_value(serde_json::json!({ "title": title, "ticket_type": "task" }))
+                .unwrap()
+        };
+        let parent = db.create_ticket(ticket("Parent")).await.unwrap();
+        let child = db.create_ticket(ticket("Child")).await.unwrap();
+        db.query("UPDATE type::record($id) SET parent_id = $parent, linked_tickets = [$parent]")
+            .bind(("id", child.id.clone()))
+            .bind(("parent", parent.id.clone()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let source = temp_dir.path().join("notes.txt");
+        std::fs::write(&source, "notes").unwrap();
+        let files = temp_dir.path().join("files");
+        db.add_attachment(&files, "tickets", &parent.id, &source)
+            .await
+            .unwrap();
+
+        db.delete_ticket(&parent.id).await.unwrap();
+        assert!(db.get_ticket(&parent.id).await.is_err());
+        assert_eq!(db.get_ticket(&child.id).await.unwrap().parent_id, None);
+        let operations = db.list_undoable_operations(window).await.unwrap();
+        assert_eq!(operations[0].kind, UndoKind::DeleteTicket);
+        assert_eq!(operations[0].description, "Ticket Parent");
+
+        db.undo_last_operation(window).await.unwrap();
+        let restored = db.get_ticket(&parent.id).await.unwrap();
+        assert_eq!(restored.title, "Parent");
+        assert_eq!(restored.key, parent.key);
+        let child = db.get_ticket(&child.id).await.unwrap();
+        assert_eq!(child.parent_id.as_deref(), Some(parent.id.as_str()));
+        assert_eq!(child.linked_tickets, vec![parent.id.clone()]);
+        let attachments = db
+            .list_attachments(&files, "tickets", &parent.id)
+            .await
+            .unwrap();
+        assert_eq!(attachments.len(), 1);
+        assert!(Path::new(&attachments[0].path).exists());
+
+        // Pruning the entry of a deleted ticket removes its files
+        db.delete_ticket(&parent.id).await.unwrap();
+        assert_eq!(prune(&db, Duration::zero(), &files).await.unwrap(), 1);
+        assert!(!Path::new(&attachments[0].path).exists());
+    }
+}
@@ -0,0 +1,237 @@
+// Updates
+//
+// Update checks go through Tauri's updater (tauri-plugin-updater): it fetches
+// the update manifest, compares versions, picks the build for this platform
+// and verifies its signature before installing. The manifest URL is
+// `ENDPOINT` with `{{channel}}` replaced by the selected channel, so the beta
+// channel is just a second manifest next to the stable one.
+//
+// The trust root ships with the build: signatures are verified with the
+// public key in tauri.conf.json (`plugins.updater.pubkey`), and neither the
+// key nor the manifest URL is a setting, so an imported settings bundle can't
+// point the updater at someone else's releases. `plugins.updater.endpoints`
+// stays empty since the URL depends on the channel. Release builds fill in
+// the key; builds without one (local and dev builds) refuse to check or
+// install rather than offer updates they couldn't verify.
+//
+// A version the user chose to skip is kept in `updates.skipped_version`.
+// Checks still report it, marked as skipped, so the startup check can stay
+// quiet about it while a manual check offers it anyway.
+
+use crate::error::AppError;
+use crate::network;
+use crate::settings::{SettingDefinition, SettingType, SettingsService};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+use url::Url;
+
+pub const CHANNEL_SETTING: &str = "updates.channel";
+pub const CHECK_ON_STARTUP_SETTING: &str = "updates.check_on_startup";
+pub const SKIPPED_VERSION_SETTING: &str = "updates.skipped_version";
+
+pub const CHANNELS: [&str; 2] = ["stable", "beta"];
+
+/// Update manifest of a channel
+pub const ENDPOINT: &str =
+    "https://github.com/signatur3-git/modulaur/releases/download/updates/{{channel}}.json";
+
+pub fn settings_schema() -> Vec<SettingDefinition> {
+    vec![
+        SettingDefinition::new(
+            CHANNEL_SETTING,
+            SettingType::String,
+            "stable".into(),
+            "updates",
+        )
+        .describe(
+            "Update channel",
+            "Beta gets new versions before stable does",
+        )
+        .options(CHANNELS.iter().map(|&c| c.into()).collect()),
+        SettingDefinition::new(
+            CHECK_ON_STARTUP_SETTING,
+            SettingType::Boolean,
+            true.into(),
+            "updates",
+        )
+        .describe(
+            "Check for updates on startup",
+            "Show when a new version is out",
+        ),
+        SettingDefinition::new(
+            SKIPPED_VERSION_SETTING,
+            SettingType::String,
+            "".into(),
+            "updates",
+        )
+        .describe(
+            "Skipped version",
+            "Version not offered on startup (clear it to be offered it again)",
+        ),
+    ]
+}
+
+/// The update settings
+#[derive(Debug, Clone)]
+pub struct UpdateConfig {
+    pub channel: String,
+    pub skipped_version: Option<String>,
+}
+
+impl UpdateConfig {
+    pub async fn load(settings: &SettingsService) -> Result<Self, AppError> {
+        let text = |value: serde_json::Value| {
+            value
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        Ok(Self {
+            channel: text(settings.get_value(CHANNEL_SETTING).await?)
+                .unwrap_or_else(|| CHANNELS[0].to_string()),
+            skipped_version: text(settings.get_value(SKIPPED_VERSION_SETTING).await?),
+        })
+    }
+
+    /// Manifest URL of the channel
+    pub fn endpoint_url(&self) -> Result<Url, AppError> {
+        let endpoint = ENDPOINT.replace("{{channel}}", &self.channel);
+        Url::parse(&endpoint)
+            .map_err(|e| AppError::Config(format!("Invalid update endpoint {}: {}", endpoint, e)))
+    }
+}
+
+/// A newer version on the update channel
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    /// Release notes from the manifest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pub_date: Option<String>,
+    /// Whether the user chose to skip this version
+    pub skipped: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateCheck {
+    pub current_version: String,
+    pub channel: String,
+    /// None when this is the latest version of the channel
+    pub update: Option<AvailableUpdate>,
+}
+
+fn updater_error(e: tauri_plugin_updater::Error) -> AppError {
+    AppError::http(format!("Update check failed: {}", e))
+}
+
+/// Whether the build ships the public key update signatures are verified with
+fn has_signing_key(app: &AppHandle) -> bool {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater["pubkey"].as_str())
+        .is_some_and(|key| !key.trim().is_empty())
+}
+
+/// Tauri's updater set up for the channel and the network settings; the
+/// signing key comes from tauri.conf.json, without it there is no updater
+fn updater(app: &AppHandle, config: &UpdateConfig) -> Result<Updater, AppError> {
+    if !has_signing_key(app) {
+        return Err(AppError::Config(
+            "Updates aren't set up for this build: it has no update signing key".to_string(),
+        ));
+    }
+    network::ensure_online()?;
+    let network = network::current();
+    let mut builder = app
+        .updater_builder()
+        .endpoints(vec![config.endpoint_url()?])
+        .map_err(updater_error)?
+        .timeout(Duration::from_secs(network.timeout_secs));
+    if let Some(proxy) = network
+        .proxy_url
+        .as_deref()
+        .and_then(|p| Url::parse(p).ok())
+    {
+        builder = builder.proxy(proxy);
+    }
+    builder.build().map_err(updater_error)
+}
+
+fn available(update: &Update, config: &UpdateConfig) -> AvailableUpdate {
+    AvailableUpdate {
+        version: update.version.clone(),
+        notes: update.body.clone(),
+        pub_date: update.raw_json["pub_date"].as_str().map(str::to_string),
+        skipped: config.skipped_version.as_deref() == Some(update.version.as_str()),
+    }
+}
+
+/// Look for a newer version on the update channel
+pub async fn check(app: &AppHandle, config: UpdateConfig) -> Result<UpdateCheck, AppError> {
+    let update = updater(app, &config)?
+        .check()
+        .await
+        .map_err(updater_error)?;
+    if let Some(update) = &update {
+        tracing::info!(
+            "Update {} available on the {} channel",
+            update.version,
+            config.channel
+        );
+    }
+    Ok(UpdateCheck {
+        current_version: app.package_info().version.to_string(),
+        update: update.as_ref().map(|u| available(u, &config)),
+        channel: config.channel,
+    })
+}
+
+/// Download and install the newest version of the channel; the app has to be
+/// restarted afterwards
+pub async fn install(app: &AppHandle, config: &UpdateConfig) -> Result<String, AppError> {
+    let update = updater(app, config)?
+        .check()
+        .await
+        .map_err(updater_error)?
+        .ok_or_else(|| AppError::NotFound("No update available".to_string()))?;
+
+    tracing::info!("Installing update {}", update.version);
+    update
+        .download_and_install(|_, _| {}, || tracing::info!("Update downloaded"))
+        .await
+        .map_err(|e| AppError::http(format!("Update install failed: {}", e)))?;
+    Ok(update.version)
+}
+
+/// Stop offering a version on startup (None offers every version again)
+pub async fn skip_version(
+    settings: &SettingsService,
+    version: Option<String>,
+) -> Result<(), AppError> {
+    let value = version.map(serde_json::Value::from).unwrap_or_default();
+    settings.set_value(SKIPPED_VERSION_SETTING, value).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_url() {
+        let config = UpdateConfig {
+            channel: "beta".to_string(),
+            skipped_version: None,
+        };
+        assert_eq!(
+            config.endpoint_url().unwrap().as_str(),
+            "https://github.com/signatur3-git/modulaur/releases/download/updates/beta.json"
+        );
+    }
+}
@@ -0,0 +1,448 @@
+// Master-password credential vault
+//
+// For machines where the OS keychain can't be relied on, an optional master
+// password keeps credentials in an encrypted file next to the workspaces
+// (`credentials.vault`). The key is derived from the password with Argon2id
+// and every value is sealed with AES-256-GCM under its own nonce; the file
+// also holds an encrypted check value to tell a wrong password apart.
+//
+// While a master password is set, all secure credentials go through the
+// vault, and credentials stored before that are moved into it when first
+// read. The derived key is only held in memory while unlocked and is dropped
+// on `lock_credentials` or after the auto-lock timeout without use.
+
+use crate::error::{ApiError, AppError};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const VAULT_VERSION: u32 = 1;
+
+/// Plaintext of the check value
+const VAULT_CHECK: &[u8] = b"modulaur-vault";
+
+const DEFAULT_AUTO_LOCK_MINUTES: u64 = 15;
+
+pub(crate) const MIN_PASSWORD_LENGTH: usize = 8;
+
+pub(crate) const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+
+/// The vault of this app instance, set up at startup
+static VAULT: Mutex<Option<Vault>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    /// Argon2 salt (hex)
+    salt: String,
+    /// VAULT_CHECK sealed with the key (hex nonce + ciphertext)
+    check: String,
+    /// Minutes without use before the key is dropped (0 = never)
+    auto_lock_minutes: u64,
+    /// Credential key → sealed value
+    entries: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VaultStatus {
+    /// Whether a master password is set
+    pub enabled: bool,
+    pub locked: bool,
+    pub auto_lock_minutes: u64,
+}
+
+pub(crate) fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], AppError> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::Config(format!("Failed to derive vault key: {}", e)))?;
+    Ok(key)
+}
+
+pub(crate) fn seal(key: &[u8; 32], plaintext: &[u8]) -> Result<String, AppError> {
+    seal_bytes(key, plaintext).map(hex::encode)
+}
+
+pub(crate) fn open(key: &[u8; 32], sealed: &str) -> Result<Vec<u8>, AppError> {
+    let bytes =
+        hex::decode(sealed).map_err(|e| AppError::Config(format!("Corrupt vault entry: {}", e)))?;
+    open_bytes(key, &bytes)
+}
+
+/// AES-256-GCM with a random nonce, returned as nonce + ciphertext
+pub(crate) fn seal_bytes(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>, AppError> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(key.into())
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| AppError::Config("Failed to encrypt credential".to_string()))?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+pub(crate) fn open_bytes(key: &[u8; 32], sealed: &[u8]) -> Result<Vec<u8>, AppError> {
+    if sealed.len() < NONCE_LENGTH {
+        return Err(AppError::Config("Corrupt vault entry".to_string()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    Aes256Gcm::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::Config("Failed to decrypt credential".to_string()))
+}
+
+/// Key for a password, if it is the vault's password
+fn verify(file: &VaultFile, password: &str) -> Result<[u8; 32], AppError> {
+    let salt = hex::decode(&file.salt)
+        .map_err(|e| AppError::Config(format!("Corrupt vault salt: {}", e)))?;
+    let key = derive_key(password, &salt)?;
+    match open(&key, &file.check) {
+        Ok(check) if check == VAULT_CHECK => Ok(key),
+        _ => Err(AppError::Validation("Wrong master password".to_string())),
+    }
+}
+
+/// Credential vault backed by a file
+pub struct Vault {
+    path: PathBuf,
+    /// None until a master password is set
+    file: Option<VaultFile>,
+    /// Derived key while unlocked
+    key: Option<[u8; 32]>,
+    last_used: Instant,
+}
+
+impl Vault {
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let file = if path.exists() {
+            Some(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            key: None,
+            last_used: Instant::now(),
+        })
+    }
+
+    fn save(&self) -> Result<(), AppError> {
+        match &self.file {
+            Some(file) => {
+                if let Some(parent) = self.path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&self.path, serde_json::to_string_pretty(file)?)?;
+            }
+            None if self.path.exists() => std::fs::remove_file(&self.path)?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Drop the key after the auto-lock timeout without use
+    fn expire(&mut self) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        if self.key.is_some()
+            && file.auto_lock_minutes > 0
+            && self.last_used.elapsed() > Duration::from_secs(file.auto_lock_minutes * 60)
+        {
+            tracing::info!(
+                "Credential vault locked after {} idle minutes",
+                file.auto_lock_minutes
+            );
+            self.key = None;
+        }
+    }
+
+    fn unlocked(&mut self) -> Result<(&mut VaultFile, [u8; 32]), AppError> {
+        self.expire();
+        let key = self.key.ok_or_else(|| {
+            AppError::Config(
+                "Credentials are locked; unlock them with the master password".to_string(),
+            )
+        })?;
+        self.last_used = Instant::now();
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| AppError::Config("No master password is set".to_string()))?;
+        Ok((file, key))
+    }
+
+    pub fn status(&mut self) -> VaultStatus {
+        self.expire();
+        VaultStatus {
+            enabled: self.file.is_some(),
+            locked: self.file.is_some() && self.key.is_none(),
+            auto_lock_minutes: self
+                .file
+                .as_ref()
+                .map_or(DEFAULT_AUTO_LOCK_MINUTES, |file| file.auto_lock_minutes),
+        }
+    }
+
+    /// Set or change the master password, re-sealing the stored entries;
+    /// changing it needs the current password
+    pub fn set_password(&mut self, password: &str, current: Option<&str>) -> Result<(), AppError> {
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AppError::Validation(format!(
+                "The master password needs at least {} characters",
+                MIN_PASSWORD_LENGTH
+            )));
+        }
+        let (entries, auto_lock_minutes) = match &self.file {
+            Some(file) => {
+                let current = current.ok_or_else(|| {
+                    AppError::Validation("The current master password is required".to_string())
+                })?;
+                let key = verify(file, current)?;
+                let entries = file
+                    .entries
+                    .iter()
+                    .map(|(name, sealed)| Ok((name.clone(), open(&key, sealed)?)))
+                    .collect::<Result<Vec<_>, AppError>>()?;
+                (entries, file.auto_lock_minutes)
+            }
+            None => (Vec::new(), DEFAULT_AUTO_LOCK_MINUTES),
+        };
+
+        let mut salt = [0u8; SALT_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let key = derive_key(password, &salt)?;
+        self.file = Some(VaultFile {
+            version: VAULT_VERSION,
+            salt: hex::encode(salt),
+            check: seal(&key, VAULT_CHECK)?,
+            auto_lock_minutes,
+            entries: entries
+                .into_iter()
+                .map(|(name, value)| Ok((name, seal(&key, &value)?)))
+                .collect::<Result<_, AppError>>()?,
+        });
+        self.key = Some(key);
+        self.last_used = Instant::now();
+        self.save()
+    }
+
+    /// Remove the master password, returning the decrypted entries so they
+    /// can go back to the regular credential store
+    pub fn remove_password(&mut self, password: &str) -> Result<Vec<(String, String)>, AppError> {
+        let Some(file) = &self.file else {
+            return Ok(Vec::new());
+        };
+        let key = verify(file, password)?;
+        let entries = file
+            .entries
+            .iter()
+            .map(|(name, sealed)| {
+                let value = String::from_utf8(open(&key, sealed)?)
+                    .map_err(|_| AppError::Config("Corrupt vault entry".to_string()))?;
+                Ok((name.clone(), value))
+            })
+            .collect::<Result<Vec<_>, AppError>>()?;
+        self.file = None;
+        self.key = None;
+        self.save()?;
+        Ok(entries)
+    }
+
+    pub fn unlock(&mut self, password: &str) -> Result<(), AppError> {
+        let file = self
+            .file
+            .as_ref()
+            .ok_or_else(|| AppError::Config("No master password is set".to_string()))?;
+        self.key = Some(verify(file, password)?);
+        self.last_used = Instant::now();
+        Ok(())
+    }
+
+    pub fn lock(&mut self) {
+        self.key = None;
+    }
+
+    pub fn set_auto_lock(&mut self, minutes: u64) -> Result<(), AppError> {
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| AppError::Config("No master password is set".to_string()))?;
+        file.auto_lock_minutes = minutes;
+        self.save()
+    }
+
+    pub fn store(&mut self, name: &str, value: &str) -> Result<(), AppError> {
+        let (file, key) = self.unlocked()?;
+        file.entries
+            .insert(name.to_string(), seal(&key, value.as_bytes())?);
+        self.save()
+    }
+
+    pub fn get(&mut self, name: &str) -> Result<Option<String>, AppError> {
+        let (file, key) = self.unlocked()?;
+        let Some(sealed) = file.entries.get(name) else {
+            return Ok(None);
+        };
+        let value = String::from_utf8(open(&key, sealed)?)
+            .map_err(|_| AppError::Config("Corrupt vault entry".to_string()))?;
+        Ok(Some(value))
+    }
+
+    /// Removing needs no key, so it works while locked
+    pub fn remove(&mut self, name: &str) -> Result<(), AppError> {
+        let removed = self
+            .file
+            .as_mut()
+            .is_some_and(|file| file.entries.remove(name).is_some());
+        if removed {
+            self.save()?;
+        }
+        Ok(())
+    }
+}
+
+/// Load the app's vault file at startup
+pub fn init_vault(path: &Path) -> Result<(), AppError> {
+    *VAULT.lock().unwrap() = Some(Vault::load(path)?);
+    Ok(())
+}
+
+/// Run with the app's vault; errors when it wasn't set up
+pub fn with_vault<T>(f: impl FnOnce(&mut Vault) -> Result<T, AppError>) -> Result<T, AppError> {
+    let mut vault = VAULT.lock().unwrap();
+    let vault = vault
+        .as_mut()
+        .ok_or_else(|| AppError::Config("Credential vault is not available".to_string()))?;
+    f(vault)
+}
+
+/// Whether credentials go through the vault
+pub fn vault_enabled() -> bool {
+    VAULT
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(Vault::is_enabled)
+}
+
+#[tauri::command]
+pub fn get_vault_status() -> Result<VaultStatus, ApiError> {
+    with_vault(|vault| Ok(vault.status())).map_err(ApiError::from)
+}
+
+/// Set the master password, or change it with the current one
+#[tauri::command]
+pub fn set_master_password(
+    password: String,
+    current_password: Option<String>,
+) -> Result<(), ApiError> {
+    with_vault(|vault| vault.set_password(&password, current_password.as_deref()))
+        .map_err(ApiError::from)
+}
+
+/// Remove the master password, moving the credentials back to the regular store
+#[tauri::command]
+pub fn remove_master_password(password: String) -> Result<(), ApiError> {
+    let entries = with_vault(|vault| vault.remove_password(&password))?;
+    for (key, value) in entries {
+        crate::credentials::store_credential(key, value)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unlock_credentials(password: String) -> Result<(), ApiError> {
+    with_vault(|vault| vault.unlock(&password)).map_err(ApiError::from)
+}
+
+#[tauri::command]
+pub fn lock_credentials() -> Result<(), ApiError> {
+    with_vault(|vault| {
+        vault.lock();
+        Ok(())
+    })
+    .map_err(ApiError::from)
+}
+
+/// Minutes without use before the vault locks itself (0 = never)
+#[tauri::command]
+pub fn set_vault_auto_lock(minutes: u64) -> Result<(), ApiError> {
+    with_vault(|vault| vault.set_auto_lock(minutes)).map_err(ApiError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_vault() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("credentials.vault");
+        let mut vault = Vault::load(&path).unwrap();
+        assert!(!vault.status().enabled);
+        assert!(vault.store("token", "secret").is_err());
+        assert!(vault.set_password("short", None).is_err());
+
+        vault.set_password("correct horse", None).unwrap();
+        vault.store("token", "glpat-secret").unwrap();
+        assert_eq!(vault.get("token").unwrap().as_deref(), Some("glpat-secret"));
+        assert_eq!(vault.get("missing").unwrap(), None);
+        // Nothing readable at rest
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("glpat-secret"));
+
+        vault.lock();
+        assert!(vault.status().locked);
+        assert!(vault.get("token").is_err());
+        assert!(vault.unlock("wrong password").is_err());
+
+        // A fresh instance reads the file and needs the password
+        let mut reloaded = Vault::load(&path).unwrap();
+        assert!(reloaded.status().locked);
+        reloaded.unlock("correct horse").unwrap();
+        assert_eq!(
+            reloaded.get("token").unwrap().as_deref(),
+            Some("glpat-secret")
+        );
+
+        // Auto-lock after idling
+        reloaded.set_auto_lock(1).unwrap();
+        if let Some(idle) = Instant::now().checked_sub(Duration::from_secs(120)) {
+            reloaded.last_used = idle;
+            assert!(reloaded.status().locked);
+            reloaded.unlock("correct horse").unwrap();
+        }
+
+        // Changing the password re-seals the entries
+        assert!(reloaded.set_password("new password", None).is_err());
+        reloaded
+            .set_password("new password", Some("correct horse"))
+            .unwrap();
+        reloaded.lock();
+        assert!(reloaded.unlock("correct horse").is_err());
+        reloaded.unlock("new password").unwrap();
+        assert_eq!(
+            reloaded.get("token").unwrap().as_deref(),
+            Some("glpat-secret")
+        );
+
+        let entries = reloaded.remove_password("new password").unwrap();
+        assert_eq!(
+            entries,
+            vec![("token".to_string(), "glpat-secret".to_string())]
+        );
+        assert!(!path.exists());
+        assert!(!reloaded.status().enabled);
+    }
+}
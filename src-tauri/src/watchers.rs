@@ -0,0 +1,108 @@
+// Ticket watchers
+//
+// Tickets can be marked as watched. When a watched ticket changes status or
+// gets a comment, the change is sent to the frontend as a `tickets://watched`
+// event, which shows it as a desktop notification like due date reminders.
+
+use crate::tickets::Ticket;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+/// Event carrying a change to a watched ticket
+pub const WATCH_EVENT: &str = "tickets://watched";
+
+/// What happened to a watched ticket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TicketChange {
+    Status { from: String, to: String },
+    Comment { author: String, text: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedTicketChange {
+    pub ticket_id: String,
+    pub key: Option<String>,
+    pub title: String,
+    #[serde(flatten)]
+    pub change: TicketChange,
+}
+
+/// The event for a change, if the ticket is watched and something changed
+fn watched_change(ticket: &Ticket, change: TicketChange) -> Option<WatchedTicketChange> {
+    if !ticket.watched {
+        return None;
+    }
+    if let TicketChange::Status { from, to } = &change {
+        if from == to {
+            return None;
+        }
+    }
+    Some(WatchedTicketChange {
+        ticket_id: ticket.id.clone(),
+        key: ticket.key.clone(),
+        title: ticket.title.clone(),
+        change,
+    })
+}
+
+/// Tell the frontend about a change to a ticket if it's watched
+pub fn notify(app: &AppHandle, ticket: &Ticket, change: TicketChange) {
+    let Some(event) = watched_change(ticket, change) else {
+        return;
+    };
+    if let Err(e) = app.emit(WATCH_EVENT, &event) {
+        tracing::warn!("Failed to emit watched ticket change: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_watched_tickets() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+        let mut ids = Vec::new();
+        for title in ["Watched", "Other"] {
+            let ticket = db
+                .create_ticket(
+                    serde_json::from_value(
+                        serde_json::json!({ "title": title, "ticket_type": "task" }),
+                    )
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+            ids.push(ticket.id);
+        }
+
+        let status = |from: &str, to: &str| TicketChange::Status {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let other = db.get_ticket(&ids[1]).await.unwrap();
+        assert!(watched_change(&other, status("todo", "done")).is_none());
+
+        let watched = db.watch_ticket(&ids[0], true).await.unwrap();
+        assert!(watched.watched);
+        assert!(watched_change(&watched, status("todo", "todo")).is_none());
+        let event = watched_change(&watched, status("todo", "done")).unwrap();
+        assert_eq!(event.key, watched.key);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap()["kind"],
+            serde_json::json!("status")
+        );
+
+        let listed = db.get_watched_tickets().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, ids[0]);
+
+        db.watch_ticket(&ids[0], false).await.unwrap();
+        assert!(db.get_watched_tickets().await.unwrap().is_empty());
+        assert!(db.watch_ticket("tickets:missing", true).await.is_err());
+    }
+}
@@ -0,0 +1,668 @@
+// Outbound webhooks
+//
+// Persisted webhooks in the `webhooks` table: a URL, the events they're
+// fired for and a body format (plain JSON, Slack, Discord or ntfy), so fetch
+// failures, fired alerts or tickets moved to "done" reach a chat or a phone
+// without a plugin. `start` subscribes to the event bus and posts every
+// matching event; an empty event list matches all events. A secret is kept
+// with the credentials (`webhook.<id>`), never in the database: when set,
+// requests carry `X-Modulaur-Signature: sha256=<hex HMAC of the body>`.
+//
+// Deliveries go through the network settings and aren't retried; the outcome
+// of the last one is stored on the webhook.
+
+use crate::credentials;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::network;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use surrealdb::sql::Thing;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+
+/// Header carrying the event kind
+const EVENT_HEADER: &str = "X-Modulaur-Event";
+
+/// Header carrying the body signature when the webhook has a secret
+const SIGNATURE_HEADER: &str = "X-Modulaur-Signature";
+
+// ============================================================================
+// Webhook Model
+// ============================================================================
+
+/// Events a webhook can be fired for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    FetchFinished,
+    FetchFailed,
+    AlertFired,
+    TicketMoved,
+    RecordsUpserted,
+    PluginLoaded,
+    PluginUnloaded,
+    PluginQuarantined,
+}
+
+impl WebhookEvent {
+    /// The webhook event of a bus event (failed fetches are `FetchFailed`)
+    pub fn of(event: &AppEvent) -> Self {
+        match event {
+            AppEvent::FetchFinished(summary) if summary.error.is_some() => {
+                WebhookEvent::FetchFailed
+            }
+            AppEvent::FetchFinished(_) => WebhookEvent::FetchFinished,
+            AppEvent::AlertFired(_) => WebhookEvent::AlertFired,
+            AppEvent::TicketMoved { .. } => WebhookEvent::TicketMoved,
+            AppEvent::RecordsUpserted { .. } => WebhookEvent::RecordsUpserted,
+            AppEvent::PluginLoaded { .. } => WebhookEvent::PluginLoaded,
+            AppEvent::PluginUnloaded { .. } => WebhookEvent::PluginUnloaded,
+            AppEvent::PluginQuarantined { .. } => WebhookEvent::PluginQuarantined,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::FetchFinished => "fetch_finished",
+            WebhookEvent::FetchFailed => "fetch_failed",
+            WebhookEvent::AlertFired => "alert_fired",
+            WebhookEvent::TicketMoved => "ticket_moved",
+            WebhookEvent::RecordsUpserted => "records_upserted",
+            WebhookEvent::PluginLoaded => "plugin_loaded",
+            WebhookEvent::PluginUnloaded => "plugin_unloaded",
+            WebhookEvent::PluginQuarantined => "plugin_quarantined",
+        }
+    }
+}
+
+/// Body format of the requests
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// `{event, title, text, timestamp, data}` with the event as `data`
+    #[default]
+    Json,
+    /// Slack incoming webhook (`{"text": ...}`)
+    Slack,
+    /// Discord webhook (`{"content": ...}`)
+    Discord,
+    /// ntfy topic URL: the text as body, the title as `Title` header
+    Ntfy,
+}
+
+/// Webhook as stored in database (with Thing ID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WebhookRecord {
+    pub id: Thing,
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub ticket_statuses: Vec<String>,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    pub enabled: bool,
+    #[serde(default)]
+    pub has_secret: bool,
+    #[serde(default)]
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// User-facing webhook with String ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// Events the webhook is fired for, all when empty
+    pub events: Vec<WebhookEvent>,
+    /// Only tickets moved to one of these statuses (e.g. "done"), all when empty
+    pub ticket_statuses: Vec<String>,
+    pub format: WebhookFormat,
+    pub enabled: bool,
+    pub has_secret: bool,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    /// Error of the last delivery, None when it succeeded
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<WebhookRecord> for Webhook {
+    fn from(record: WebhookRecord) -> Self {
+        Webhook {
+            id: record.id.id.to_raw(),
+            name: record.name,
+            url: record.url,
+            events: record.events,
+            ticket_statuses: record.ticket_statuses,
+            format: record.format,
+            enabled: record.enabled,
+            has_secret: record.has_secret,
+            last_delivery_at: record.last_delivery_at,
+            last_error: record.last_error,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+impl Webhook {
+    /// Whether the webhook is fired for an event
+    pub fn matches(&self, event: &AppEvent) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if !self.events.is_empty() && !self.events.contains(&WebhookEvent::of(event)) {
+            return false;
+        }
+        match event {
+            AppEvent::TicketMoved { to_status, .. } => {
+                self.ticket_statuses.is_empty()
+                    || self
+                        .ticket_statuses
+                        .iter()
+                        .any(|status| status.eq_ignore_ascii_case(to_status))
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Create (no id) or update (with id) a webhook
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveWebhookRequest {
+    pub id: Option<String>,
+    pub name: String,
+    pub url: String,
+    /// New secret; None keeps the current one, an empty string removes it
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub ticket_statuses: Vec<String>,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn secret_key(id: &str) -> String {
+    format!("webhook.{}", id)
+}
+
+// ============================================================================
+// Webhook Operations
+// ============================================================================
+
+impl Database {
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>, AppError> {
+        let mut result = self
+            .query("SELECT * FROM webhooks ORDER BY created_at ASC")
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to query webhooks: {}", e)))?;
+
+        let webhooks: Vec<WebhookRecord> = result
+            .take(0)
+            .map_err(|e| AppError::Database(format!("Failed to parse webhooks: {}", e)))?;
+
+        Ok(webhooks.into_iter().map(|w| w.into()).collect())
+    }
+
+    pub async fn get_webhook(&self, id: &str) -> Result<Webhook, AppError> {
+        let key = id.strip_prefix("webhooks:").unwrap_or(id);
+        let webhook: Option<WebhookRecord> = self
+            .db
+            .select(("webhooks", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to get webhook: {}", e)))?;
+        webhook
+            .map(|w| w.into())
+            .ok_or_else(|| AppError::NotFound(format!("Webhook {}", id)))
+    }
+
+    /// Create or update a webhook, storing its secret with the credentials
+    pub async fn save_webhook(&self, req: SaveWebhookRequest) -> Result<Webhook, AppError> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::Validation("A webhook needs a name".to_string()));
+        }
+        let url = url::Url::parse(req.url.trim())
+            .map_err(|e| AppError::Validation(format!("Invalid webhook URL: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(AppError::Validation(
+                "The webhook URL must start with http:// or https://".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let (id, created_at, had_secret) = match &req.id {
+            Some(id) => {
+                let existing = self.get_webhook(id).await?;
+                (existing.id, existing.created_at, existing.has_secret)
+            }
+            None => (uuid::Uuid::new_v4().to_string(), now, false),
+        };
+
+        match req.secret.as_deref() {
+            None => {}
+            Some("") => {
+                if had_secret {
+                    credentials::remove_credential(secret_key(&id))?;
+                }
+            }
+            Some(secret) => credentials::store_credential(secret_key(&id), secret.to_string())?,
+        }
+
+        let record = WebhookRecord {
+            id: Thing::from(("webhooks", id.as_str())),
+            name: req.name.trim().to_string(),
+            url: url.to_string(),
+            events: req.events,
+            ticket_statuses: req
+                .ticket_statuses
+                .into_iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            format: req.format,
+            enabled: req.enabled,
+            has_secret: req.secret.map_or(had_secret, |s| !s.is_empty()),
+            last_delivery_at: None,
+            last_error: None,
+            created_at,
+            updated_at: now,
+        };
+
+        let saved: Option<WebhookRecord> = self
+            .db
+            .upsert(("webhooks", id.as_str()))
+            .content(record)
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to save webhook: {}", e)))?;
+
+        saved
+            .map(|w| w.into())
+            .ok_or_else(|| AppError::Database("Failed to save webhook".to_string()))
+    }
+
+    pub async fn delete_webhook(&self, id: &str) -> Result<(), AppError> {
+        let key = id.strip_prefix("webhooks:").unwrap_or(id);
+        let deleted: Option<WebhookRecord> = self
+            .db
+            .delete(("webhooks", key))
+            .await
+            .map_err(|e| AppError::Database(format!("Failed to delete webhook: {}", e)))?;
+        if deleted.is_some_and(|w| w.has_secret) {
+            credentials::remove_credential(secret_key(key))?;
+        }
+        Ok(())
+    }
+
+    /// Store the outcome of a delivery
+    async fn record_webhook_delivery(
+        &self,
+        id: &str,
+        error: Option<String>,
+    ) -> Result<(), AppError> {
+        self.query(
+            "UPDATE type::thing('webhooks', $key) SET
+                     last_delivery_at = $now,
+                     last_error = $error
+                 RETURN NONE",
+        )
+        .bind(("key", id.to_string()))
+        .bind(("now", Utc::now()))
+        .bind(("error", error))
+        .await
+        .map_err(|e| AppError::Database(format!("Failed to update webhook: {}", e)))?
+        .check()
+        .map_err(|e| AppError::Database(format!("Failed to update webhook: {}", e)))?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+/// What a request says, independent of the body format
+#[derive(Debug, Clone, PartialEq)]
+struct Payload {
+    event: &'static str,
+    title: String,
+    text: String,
+    data: serde_json::Value,
+}
+
+impl Payload {
+    fn from_event(event: &AppEvent) -> Result<Self, AppError> {
+        let (title, text) = match event {
+            AppEvent::FetchFinished(summary) => match &summary.error {
+                Some(error) => (
+                    "Fetch failed".to_string(),
+                    format!("{}: {}", summary.source, error),
+                ),
+                None => (
+                    "Fetch finished".to_string(),
+                    format!(
+                        "{}: {} records in {} ms",
+                        summary.source, summary.records, summary.duration_ms
+                    ),
+                ),
+            },
+            AppEvent::AlertFired(alert) => (alert.rule_name.clone(), alert.message.clone()),
+            AppEvent::TicketMoved {
+                ticket_id,
+                from_status,
+                to_status,
+                ..
+            } => (
+                "Ticket moved".to_string(),
+                format!(
+                    "Ticket {} moved from {} to {}",
+                    ticket_id, from_status, to_status
+                ),
+            ),
+            AppEvent::RecordsUpserted { source, count, .. } => (
+                "Records stored".to_string(),
+                format!("{} records stored from {}", count, source),
+            ),
+            AppEvent::PluginLoaded { name, version } => (
+                "Plugin loaded".to_string(),
+                format!("{} {} loaded", name, version),
+            ),
+            AppEvent::PluginUnloaded { name } => {
+                ("Plugin unloaded".to_string(), format!("{} unloaded", name))
+            }
+            AppEvent::PluginQuarantined { name, reason } => (
+                "Plugin quarantined".to_string(),
+                format!("{}: {}", name, reason),
+            ),
+        };
+        Ok(Payload {
+            event: WebhookEvent::of(event).as_str(),
+            title,
+            text,
+            data: serde_json::to_value(event)?,
+        })
+    }
+
+    /// Sent by `test_webhook`
+    fn test() -> Self {
+        Payload {
+            event: "test",
+            title: "Modulaur".to_string(),
+            text: "Test notification from Modulaur".to_string(),
+            data: serde_json::Value::Null,
+        }
+    }
+
+    /// Content type and body of a request in a format
+    fn body(&self, format: WebhookFormat) -> Result<(&'static str, Vec<u8>), AppError> {
+        let message = format!("{}: {}", self.title, self.text);
+        let json = match format {
+            WebhookFormat::Json => serde_json::json!({
+                "event": self.event,
+                "title": self.title,
+                "text": self.text,
+                "timestamp": Utc::now(),
+                "data": self.data,
+            }),
+            WebhookFormat::Slack => serde_json::json!({ "text": message }),
+            WebhookFormat::Discord => serde_json::json!({ "content": message }),
+            WebhookFormat::Ntfy => return Ok(("text/plain", self.text.clone().into_bytes())),
+        };
+        Ok(("application/json", serde_json::to_vec(&json)?))
+    }
+}
+
+/// `sha256=<hex HMAC-SHA256 of the body>`
+fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn send(webhook: &Webhook, payload: &Payload) -> Result<(), AppError> {
+    network::ensure_online()?;
+
+    let (content_type, body) = payload.body(webhook.format)?;
+    let mut request = network::client()
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .header(EVENT_HEADER, payload.event);
+    if webhook.format == WebhookFormat::Ntfy {
+        request = request.header("Title", payload.title.as_str());
+    }
+    if webhook.has_secret {
+        let secret = credentials::get_credential(secret_key(&webhook.id))?.ok_or_else(|| {
+            AppError::Config(format!("Secret of webhook {} is missing", webhook.name))
+        })?;
+        request = request.header(SIGNATURE_HEADER, signature(&secret, &body));
+    }
+
+    request
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| AppError::request(&e, format!("Webhook {} failed: {}", webhook.name, e)))?;
+    Ok(())
+}
+
+/// Send a payload and store the outcome on the webhook
+async fn deliver(
+    database: &Mutex<Database>,
+    webhook: &Webhook,
+    payload: &Payload,
+) -> Result<(), AppError> {
+    let result = send(webhook, payload).await;
+    let error = result.as_ref().err().map(|e| e.to_string());
+    database
+        .lock()
+        .await
+        .record_webhook_delivery(&webhook.id, error)
+        .await?;
+    result
+}
+
+/// Send a test notification to a webhook
+pub async fn test_webhook(database: &Mutex<Database>, id: &str) -> Result<(), AppError> {
+    let webhook = database.lock().await.get_webhook(id).await?;
+    deliver(database, &webhook, &Payload::test()).await
+}
+
+/// Fire the matching webhooks for every event on the bus
+pub fn start(database: Arc<Mutex<Database>>) {
+    let mut events = events::subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Webhooks fell behind, {} events not delivered", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            let webhooks = match database.lock().await.list_webhooks().await {
+                Ok(webhooks) => webhooks,
+                Err(e) => {
+                    tracing::warn!("Failed to list webhooks: {}", e);
+                    continue;
+                }
+            };
+            let webhooks: Vec<Webhook> =
+                webhooks.into_iter().filter(|w| w.matches(&event)).collect();
+            if webhooks.is_empty() {
+                continue;
+            }
+
+            let payload = match Payload::from_event(&event) {
+                Ok(payload) => Arc::new(payload),
+                Err(e) => {
+                    tracing::warn!("Failed to build webhook payload: {}", e);
+                    continue;
+                }
+            };
+            for webhook in webhooks {
+                let database = database.clone();
+                let payload = payload.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = deliver(&database, &webhook, &payload).await {
+                        tracing::warn!("{}", e);
+                    }
+                });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::FetchSummary;
+    use tempfile::TempDir;
+
+    fn fetch(error: Option<&str>) -> AppEvent {
+        AppEvent::FetchFinished(FetchSummary {
+            source: "gitlab".to_string(),
+            adapter_type: "gitlab".to_string(),
+            records: 3,
+            duration_ms: 120,
+            error: error.map(str::to_string),
+        })
+    }
+
+    fn moved(to_status: &str) -> AppEvent {
+        AppEvent::TicketMoved {
+            ticket_id: "tickets:1".to_string(),
+            board_id: "default".to_string(),
+            from_status: "review".to_string(),
+            to_status: to_status.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().to_path_buf()).await.unwrap();
+
+        let invalid = |url: &str| SaveWebhookRequest {
+            id: None,
+            name: "Chat".to_string(),
+            url: url.to_string(),
+            secret: None,
+            events: vec![],
+            ticket_statuses: vec![],
+            format: WebhookFormat::Slack,
+            enabled: true,
+        };
+        assert!(db.save_webhook(invalid("not a url")).await.is_err());
+        assert!(db.save_webhook(invalid("ftp://example.com")).await.is_err());
+
+        let webhook = db
+            .save_webhook(SaveWebhookRequest {
+                id: None,
+                name: " Done tickets ".to_string(),
+                url: "https://hooks.example.com/services/1".to_string(),
+                secret: None,
+                events: vec![WebhookEvent::TicketMoved, WebhookEvent::FetchFailed],
+                ticket_statuses: vec!["done".to_string(), " ".to_string()],
+                format: WebhookFormat::Slack,
+                enabled: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(webhook.name, "Done tickets");
+        assert_eq!(webhook.ticket_statuses, vec!["done"]);
+        assert!(!webhook.has_secret);
+
+        assert!(webhook.matches(&moved("done")));
+        assert!(webhook.matches(&moved("Done")));
+        assert!(!webhook.matches(&moved("review")));
+        assert!(webhook.matches(&fetch(Some("timeout"))));
+        assert!(!webhook.matches(&fetch(None)));
+
+        let updated = db
+            .save_webhook(SaveWebhookRequest {
+                id: Some(format!("webhooks:{}", webhook.id)),
+                events: vec![],
+                ticket_statuses: vec![],
+                enabled: false,
+                ..invalid("https://hooks.example.com/services/2")
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated.id, webhook.id);
+        assert_eq!(updated.created_at, webhook.created_at);
+        assert!(!updated.matches(&fetch(None)));
+        assert!(Webhook {
+            enabled: true,
+            ..updated
+        }
+        .matches(&fetch(None)));
+
+        db.record_webhook_delivery(&webhook.id, Some("HTTP 500".to_string()))
+            .await
+            .unwrap();
+        let listed = db.list_webhooks().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].last_error.as_deref(), Some("HTTP 500"));
+        assert!(listed[0].last_delivery_at.is_some());
+
+        db.delete_webhook(&webhook.id).await.unwrap();
+        assert!(db.list_webhooks().await.unwrap().is_empty());
+        assert!(db.get_webhook(&webhook.id).await.is_err());
+    }
+
+    #[test]
+    fn test_webhook_payloads() {
+        let payload = Payload::from_event(&fetch(Some("timeout"))).unwrap();
+        assert_eq!(payload.event, "fetch_failed");
+        assert_eq!(payload.text, "gitlab: timeout");
+
+        let (content_type, body) = payload.body(WebhookFormat::Json).unwrap();
+        assert_eq!(content_type, "application/json");
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["event"], "fetch_failed");
+        assert_eq!(json["data"]["kind"], "fetch_finished");
+        assert_eq!(json["data"]["error"], "timeout");
+
+        let (_, body) = payload.body(WebhookFormat::Slack).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["text"], "Fetch failed: gitlab: timeout");
+
+        let (_, body) = payload.body(WebhookFormat::Discord).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["content"], "Fetch failed: gitlab: timeout");
+
+        let (content_type, body) = payload.body(WebhookFormat::Ntfy).unwrap();
+        assert_eq!(content_type, "text/plain");
+        assert_eq!(body, b"gitlab: timeout");
+
+        // Well-known HMAC-SHA256 example
+        assert_eq!(
+            signature("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+}
@@ -1,4 +1,255 @@
-use tauri::WebviewWindow;
+// Window state
+//
+// Size, position and maximized state of every window are kept per window
+// label in `window-state.json` next to the data (like the workspace registry,
+// it is needed before a database is open). States are restored when a window
+// is created and saved when it is closed and when the app exits.
+//
+// A dashboard can be opened in a window of its own, e.g. to keep it on a
+// second monitor. Dashboard windows still open when the app exits are
+// reopened at the next start.
+
+use crate::error::AppError;
+use crate::workspaces::app_root;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
+
+const STATE_FILE: &str = "window-state.json";
+
+/// Label of the window declared in tauri.conf.json
+pub const MAIN_WINDOW: &str = "main";
+
+/// Label prefix of dashboard windows, followed by the dashboard id
+const DASHBOARD_PREFIX: &str = "dashboard-";
+
+/// Part of a restored window that must be on a connected monitor
+const VISIBLE_MARGIN: i32 = 50;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    /// Inner size in physical pixels; 0 until the window was resized or moved
+    pub width: u32,
+    pub height: u32,
+    /// Outer position in physical pixels
+    pub x: i32,
+    pub y: i32,
+    #[serde(default)]
+    pub maximized: bool,
+    /// Dashboard shown by a dashboard window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dashboard_id: Option<String>,
+    /// Whether the window was open when the app exited
+    #[serde(default)]
+    pub open: bool,
+}
+
+/// Window states by label; loaded by `init`
+static STATES: Mutex<BTreeMap<String, WindowState>> = Mutex::new(BTreeMap::new());
+
+fn states() -> MutexGuard<'static, BTreeMap<String, WindowState>> {
+    STATES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn state_path() -> Result<PathBuf, AppError> {
+    Ok(app_root()?.join(STATE_FILE))
+}
+
+pub fn load_states(path: &Path) -> Result<BTreeMap<String, WindowState>, AppError> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_states(path: &Path, states: &BTreeMap<String, WindowState>) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(states)?)?;
+    Ok(())
+}
+
+/// Write the current window states to disk
+pub fn save() {
+    let states = states().clone();
+    if let Err(e) = state_path().and_then(|path| save_states(&path, &states)) {
+        tracing::warn!("Failed to save window state: {}", e);
+    }
+}
+
+/// Load the saved states, restore the main window and reopen the dashboard
+/// windows that were open at exit (if their dashboard still exists)
+pub fn init(app: &AppHandle, dashboards: &[(String, String)]) {
+    match state_path().and_then(|path| load_states(&path)) {
+        Ok(loaded) => *states() = loaded,
+        Err(e) => tracing::warn!("Failed to load window state: {}", e),
+    }
+
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        track(&window);
+    }
+
+    let reopen: Vec<String> = states()
+        .values()
+        .filter(|state| state.open)
+        .filter_map(|state| state.dashboard_id.clone())
+        .collect();
+    for id in reopen {
+        match dashboards
+            .iter()
+            .find(|(dashboard_id, _)| *dashboard_id == id)
+        {
+            Some((_, name)) => {
+                if let Err(e) = open_dashboard_window(app, &id, name) {
+                    tracing::warn!("Failed to reopen the window of dashboard {}: {}", id, e);
+                }
+            }
+            None => {
+                states().remove(&dashboard_label(&id));
+            }
+        }
+    }
+}
+
+/// Window label of a dashboard window
+pub fn dashboard_label(dashboard_id: &str) -> String {
+    format!("{}{}", DASHBOARD_PREFIX, dashboard_id)
+}
+
+/// Open a dashboard in a window of its own, or focus its window if it is open
+pub fn open_dashboard_window(
+    app: &AppHandle,
+    dashboard_id: &str,
+    name: &str,
+) -> Result<WebviewWindow, AppError> {
+    // Ids end up in the window label and the URL, which allow only these
+    if dashboard_id.is_empty()
+        || !dashboard_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(AppError::Validation(format!(
+            "Dashboard id '{}' cannot be opened in a window",
+            dashboard_id
+        )));
+    }
+
+    let label = dashboard_label(dashboard_id);
+    let to_app_error = |e: tauri::Error| AppError::Unknown(e.to_string());
+    if let Some(window) = app.get_webview_window(&label) {
+        window.unminimize().map_err(to_app_error)?;
+        window.set_focus().map_err(to_app_error)?;
+        return Ok(window);
+    }
+
+    let window = tauri::WebviewWindowBuilder::new(
+        app,
+        &label,
+        tauri::WebviewUrl::App(
+            format!("index.html#/dashboard/{}?window=dashboard", dashboard_id).into(),
+        ),
+    )
+    .title(format!("Modulaur - {}", name))
+    .inner_size(1200.0, 800.0)
+    .min_inner_size(400.0, 300.0)
+    .resizable(true)
+    .build()
+    .map_err(to_app_error)?;
+
+    {
+        let mut states = states();
+        let state = states.entry(label).or_default();
+        state.dashboard_id = Some(dashboard_id.to_string());
+        state.open = true;
+    }
+    track(&window);
+    save();
+    Ok(window)
+}
+
+/// Restore the saved state of a window and keep it up to date
+pub fn track(window: &WebviewWindow) {
+    let saved = states().get(window.label()).cloned();
+    if let Some(state) = saved {
+        if let Err(e) = restore(window, &state) {
+            tracing::warn!("Failed to restore window '{}': {}", window.label(), e);
+        }
+    }
+
+    let tracked = window.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => record(&tracked),
+        WindowEvent::CloseRequested { .. } => {
+            record(&tracked);
+            // Closed by the user rather than by exiting, so not reopened
+            if let Some(state) = states().get_mut(tracked.label()) {
+                state.open = false;
+            }
+            save();
+        }
+        _ => {}
+    });
+}
+
+fn restore(window: &WebviewWindow, state: &WindowState) -> tauri::Result<()> {
+    if state.width > 0 && state.height > 0 {
+        window.set_size(PhysicalSize::new(state.width, state.height))?;
+        // Positions on monitors that are no longer connected are dropped
+        let on_screen = window.available_monitors()?.iter().any(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            contains(
+                (position.x, position.y, size.width, size.height),
+                state.x.saturating_add(VISIBLE_MARGIN),
+                state.y.saturating_add(VISIBLE_MARGIN),
+            )
+        });
+        if on_screen {
+            window.set_position(PhysicalPosition::new(state.x, state.y))?;
+        }
+    }
+    if state.maximized {
+        window.maximize()?;
+    }
+    Ok(())
+}
+
+/// Store the current size, position and maximized state of a window
+fn record(window: &WebviewWindow) {
+    // Minimized windows report a zero size; keep the last real one
+    if window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let geometry = (window.inner_size(), window.outer_position());
+
+    let mut states = states();
+    let state = states.entry(window.label().to_string()).or_default();
+    state.maximized = maximized;
+    // Maximized windows keep the size and position they are restored to
+    if maximized {
+        return;
+    }
+    if let (Ok(size), Ok(position)) = geometry {
+        state.width = size.width;
+        state.height = size.height;
+        state.x = position.x;
+        state.y = position.y;
+    }
+}
+
+/// Whether the point lies within the area (x, y, width, height)
+fn contains(area: (i32, i32, u32, u32), x: i32, y: i32) -> bool {
+    let (left, top, width, height) = area;
+    x >= left
+        && y >= top
+        && i64::from(x) < i64::from(left) + i64::from(width)
+        && i64::from(y) < i64::from(top) + i64::from(height)
+}
 
 /// Initialize main application window
 #[allow(dead_code)] // Will be used for multi-window support later
@@ -14,3 +265,37 @@ pub fn create_main_window(app_handle: &tauri::AppHandle) -> Result<WebviewWindow
     .resizable(true)
     .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_window_states() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(STATE_FILE);
+        assert!(load_states(&path).unwrap().is_empty());
+
+        let mut states = BTreeMap::new();
+        states.insert(
+            dashboard_label("dashboard_1"),
+            WindowState {
+                width: 800,
+                height: 600,
+                x: 1920,
+                y: 0,
+                maximized: false,
+                dashboard_id: Some("dashboard_1".to_string()),
+                open: true,
+            },
+        );
+        save_states(&path, &states).unwrap();
+        assert_eq!(load_states(&path).unwrap(), states);
+
+        // Second monitor to the left of the primary one
+        assert!(contains((-1920, 0, 1920, 1080), -1000, 50));
+        assert!(!contains((0, 0, 1920, 1080), -1000, 50));
+        assert!(!contains((0, 0, 1920, 1080), 1920, 50));
+    }
+}
@@ -0,0 +1,282 @@
+// Workspaces
+//
+// A workspace is a named profile with its own database (which also holds its
+// dashboards), so work and personal data can be kept apart. The registry lives
+// in `workspaces.json` next to the data (it cannot be stored in the database
+// it selects). The "default" workspace uses the original data locations.
+// A workspace can instead use a shared SurrealDB server (`remote`), so several
+// installs work on the same data; its password is kept in the credential
+// store, not in the registry.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the workspace using the pre-workspace data directories
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+const REGISTRY_FILE: &str = "workspaces.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    /// Directory passed to `Database::new`
+    pub data_dir: PathBuf,
+    /// Folder of dashboard files from before dashboards moved into the database
+    pub dashboards_dir: PathBuf,
+    pub created_at: DateTime<Utc>,
+    /// SurrealDB server used instead of the local database
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteDatabase>,
+}
+
+impl Workspace {
+    /// Credential store key of the remote database password
+    pub fn remote_password_key(&self) -> String {
+        format!("remote_db_{}", slugify(&self.name))
+    }
+}
+
+/// Connection to an external SurrealDB server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteDatabase {
+    /// `wss://host[:port]`
+    pub url: String,
+    pub namespace: String,
+    pub database: String,
+    pub username: String,
+}
+
+impl RemoteDatabase {
+    /// Check the connection settings, returning the server address without the scheme
+    pub fn address(&self) -> Result<&str, AppError> {
+        let address = self
+            .url
+            .trim()
+            .strip_prefix("wss://")
+            .map(|address| address.trim_end_matches('/'))
+            .filter(|address| !address.is_empty())
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Remote database URL must start with wss://, got '{}'",
+                    self.url
+                ))
+            })?;
+        for (field, value) in [
+            ("namespace", &self.namespace),
+            ("database", &self.database),
+            ("username", &self.username),
+        ] {
+            if value.trim().is_empty() {
+                return Err(AppError::Validation(format!(
+                    "Remote database {} must not be empty",
+                    field
+                )));
+            }
+        }
+        Ok(address)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceRegistry {
+    /// Name of the workspace opened at startup
+    pub active: String,
+    pub workspaces: Vec<Workspace>,
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+/// Application data root (`<local data>/modulaur`)
+pub fn app_root() -> Result<PathBuf, AppError> {
+    Ok(dirs::data_local_dir()
+        .ok_or_else(|| AppError::Config("Cannot determine local data directory".to_string()))?
+        .join("modulaur"))
+}
+
+/// Directory name for a workspace: lowercase alphanumerics separated by '-'
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+impl WorkspaceRegistry {
+    /// Load the registry from `root`, creating the default workspace if missing
+    pub fn load(root: &Path) -> Result<Self, AppError> {
+        let path = root.join(REGISTRY_FILE);
+        if !path.exists() {
+            return Ok(Self {
+                active: DEFAULT_WORKSPACE.to_string(),
+                workspaces: vec![Workspace {
+                    name: DEFAULT_WORKSPACE.to_string(),
+                    data_dir: root.join("data"),
+                    dashboards_dir: root.join("dashboards"),
+                    created_at: Utc::now(),
+                    remote: None,
+                }],
+                root: root.to_path_buf(),
+            });
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let mut registry: Self = serde_json::from_str(&content)?;
+        registry.root = root.to_path_buf();
+
+        if registry.get(&registry.active).is_none() {
+            tracing::warn!(
+                "Active workspace '{}' not found, falling back to '{}'",
+                registry.active,
+                DEFAULT_WORKSPACE
+            );
+            registry.active = DEFAULT_WORKSPACE.to_string();
+        }
+        Ok(registry)
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        std::fs::create_dir_all(&self.root)?;
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(self.root.join(REGISTRY_FILE), content)?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.name == name)
+    }
+
+    /// The currently active workspace
+    pub fn active(&self) -> Result<&Workspace, AppError> {
+        self.get(&self.active)
+            .ok_or_else(|| AppError::NotFound(format!("Workspace '{}'", self.active)))
+    }
+
+    /// Register a new workspace with its own directories
+    pub fn create(&mut self, name: &str) -> Result<Workspace, AppError> {
+        let name = name.trim();
+        let slug = slugify(name);
+        if slug.is_empty() {
+            return Err(AppError::Validation(
+                "Workspace name must contain letters or digits".to_string(),
+            ));
+        }
+        if self
+            .workspaces
+            .iter()
+            .any(|w| w.name.eq_ignore_ascii_case(name) || slugify(&w.name) == slug)
+        {
+            return Err(AppError::Validation(format!(
+                "Workspace '{}' already exists",
+                name
+            )));
+        }
+
+        let dir = self.root.join("workspaces").join(&slug);
+        let workspace = Workspace {
+            name: name.to_string(),
+            data_dir: dir.join("data"),
+            dashboards_dir: dir.join("dashboards"),
+            created_at: Utc::now(),
+            remote: None,
+        };
+        std::fs::create_dir_all(&workspace.data_dir)?;
+
+        self.workspaces.push(workspace.clone());
+        self.save()?;
+
+        tracing::info!("Created workspace '{}' at {:?}", name, dir);
+        Ok(workspace)
+    }
+
+    /// Point a workspace at a remote database, or back at its local one with `None`
+    pub fn set_remote(
+        &mut self,
+        name: &str,
+        remote: Option<RemoteDatabase>,
+    ) -> Result<Workspace, AppError> {
+        if let Some(remote) = &remote {
+            remote.address()?;
+        }
+        let workspace = self
+            .workspaces
+            .iter_mut()
+            .find(|w| w.name == name)
+            .ok_or_else(|| AppError::NotFound(format!("Workspace '{}'", name)))?;
+        workspace.remote = remote;
+        let workspace = workspace.clone();
+        self.save()?;
+        Ok(workspace)
+    }
+
+    /// Mark a workspace as active and persist the choice
+    pub fn set_active(&mut self, name: &str) -> Result<Workspace, AppError> {
+        let workspace = self
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Workspace '{}'", name)))?;
+        self.active = workspace.name.clone();
+        self.save()?;
+        Ok(workspace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_workspace_registry() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut registry = WorkspaceRegistry::load(temp_dir.path()).unwrap();
+        assert_eq!(
+            registry.active().unwrap().data_dir,
+            temp_dir.path().join("data")
+        );
+
+        let work = registry.create("Work Stuff").unwrap();
+        assert!(work.data_dir.ends_with("workspaces/work-stuff/data"));
+        assert!(registry.create("work stuff").is_err());
+        assert!(registry.create("  ").is_err());
+        assert!(registry.set_active("Personal").is_err());
+
+        registry.set_active("Work Stuff").unwrap();
+        let reloaded = WorkspaceRegistry::load(temp_dir.path()).unwrap();
+        assert_eq!(reloaded.active, "Work Stuff");
+        assert_eq!(reloaded.workspaces.len(), 2);
+    }
+
+    #[test]
+    fn test_remote_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut registry = WorkspaceRegistry::load(temp_dir.path()).unwrap();
+        let remote = RemoteDatabase {
+            url: "wss://db.example.com:8000/".to_string(),
+            namespace: "team".to_string(),
+            database: "modulaur".to_string(),
+            username: "modulaur".to_string(),
+        };
+        assert_eq!(remote.address().unwrap(), "db.example.com:8000");
+
+        let insecure = RemoteDatabase {
+            url: "ws://db.example.com".to_string(),
+            ..remote.clone()
+        };
+        assert!(registry
+            .set_remote(DEFAULT_WORKSPACE, Some(insecure))
+            .is_err());
+
+        registry
+            .set_remote(DEFAULT_WORKSPACE, Some(remote.clone()))
+            .unwrap();
+        let reloaded = WorkspaceRegistry::load(temp_dir.path()).unwrap();
+        assert_eq!(reloaded.active().unwrap().remote, Some(remote));
+        assert_eq!(
+            reloaded.active().unwrap().remote_password_key(),
+            "remote_db_default"
+        );
+    }
+}
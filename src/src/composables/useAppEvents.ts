@@ -0,0 +1,95 @@
+import { onMounted, onUnmounted } from 'vue'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { useDataSourceStore } from '../stores/dataSourceStore'
+import { usePluginStore } from '../stores/pluginStore'
+import { useTicketStore } from '../stores/ticketStore'
+
+export interface RecordsUpsertedEvent {
+  kind: 'records_upserted'
+  source: string
+  record_types: string[]
+  count: number
+}
+
+export interface TicketMovedEvent {
+  kind: 'ticket_moved'
+  ticket_id: string
+  board_id: string
+  from_status: string
+  to_status: string
+}
+
+export interface PluginLoadedEvent {
+  kind: 'plugin_loaded'
+  name: string
+  version: string
+}
+
+export interface PluginUnloadedEvent {
+  kind: 'plugin_unloaded'
+  name: string
+}
+
+export interface FetchFinishedEvent {
+  kind: 'fetch_finished'
+  source: string
+  adapter_type: string
+  records: number
+  duration_ms: number
+  error?: string
+}
+
+// Record counts are recomputed once a burst of upserts has settled
+const TOTALS_REFRESH_DELAY_MS = 1000
+
+/**
+ * App Events Composable
+ * Keeps the stores in sync with the backend's event bus (`records://upserted`,
+ * `tickets://moved`, `plugins://loaded`, `plugins://unloaded`,
+ * `fetch://finished`) instead of polling commands for changes
+ */
+export function useAppEvents() {
+  const dataSourceStore = useDataSourceStore()
+  const pluginStore = usePluginStore()
+  const ticketStore = useTicketStore()
+  const unlisteners: UnlistenFn[] = []
+  let totalsTimer: number | undefined
+
+  function refreshTotalsSoon() {
+    window.clearTimeout(totalsTimer)
+    totalsTimer = window.setTimeout(() => {
+      dataSourceStore.refreshTotalRecords()
+    }, TOTALS_REFRESH_DELAY_MS)
+  }
+
+  onMounted(async () => {
+    unlisteners.push(
+      await listen<RecordsUpsertedEvent>('records://upserted', event => {
+        if (dataSourceStore.dataSources.some(ds => ds.source === event.payload.source)) {
+          refreshTotalsSoon()
+        }
+      }),
+      // The backend records the fetch time and count on the data source
+      await listen<FetchFinishedEvent>('fetch://finished', () => {
+        dataSourceStore.loadDataSources()
+      }),
+      await listen<TicketMovedEvent>('tickets://moved', event => {
+        const ticket = ticketStore.tickets.get(event.payload.ticket_id)
+        if (ticket && ticket.status !== event.payload.to_status) {
+          ticketStore.tickets.set(ticket.id, { ...ticket, status: event.payload.to_status })
+        }
+      }),
+      await listen<PluginLoadedEvent>('plugins://loaded', () => {
+        pluginStore.loadPlugins()
+      }),
+      await listen<PluginUnloadedEvent>('plugins://unloaded', () => {
+        pluginStore.loadPlugins()
+      })
+    )
+  })
+
+  onUnmounted(() => {
+    window.clearTimeout(totalsTimer)
+    unlisteners.forEach(unlisten => unlisten())
+  })
+}
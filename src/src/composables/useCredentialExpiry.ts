@@ -0,0 +1,55 @@
+import { onMounted, onUnmounted } from 'vue'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import type { ExpiringCredential } from './useSecureStorage'
+import { useToast } from './useToast'
+
+/**
+ * Credential Expiry Composable
+ * Shows the backend's `credentials://expiring` events (credentials that
+ * just started expiring soon, or expired) as desktop notifications, falling
+ * back to toasts
+ */
+export function useCredentialExpiry() {
+  const toast = useToast()
+  let unlisten: UnlistenFn | null = null
+
+  async function canNotify(): Promise<boolean> {
+    if (typeof Notification === 'undefined') return false
+    if (Notification.permission === 'default') {
+      await Notification.requestPermission()
+    }
+    return Notification.permission === 'granted'
+  }
+
+  function describe(credential: ExpiringCredential): string {
+    if (credential.expired) {
+      return `Credential "${credential.key}" has expired`
+    }
+    if (credential.days_left < 1) {
+      return `Credential "${credential.key}" expires today`
+    }
+    const days = credential.days_left === 1 ? '1 day' : `${credential.days_left} days`
+    return `Credential "${credential.key}" expires in ${days}`
+  }
+
+  async function announce(credential: ExpiringCredential) {
+    const message = describe(credential)
+    if (await canNotify()) {
+      new Notification('Rotate credential', { body: message })
+    } else {
+      toast.warning(message)
+    }
+  }
+
+  onMounted(async () => {
+    unlisten = await listen<ExpiringCredential[]>('credentials://expiring', event => {
+      for (const credential of event.payload) {
+        announce(credential)
+      }
+    })
+  })
+
+  onUnmounted(() => {
+    unlisten?.()
+  })
+}
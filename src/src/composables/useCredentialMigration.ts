@@ -12,7 +12,7 @@ import type { AuthConfig } from '../stores/dataSourceStore'
  */
 export async function testEncryption(): Promise<boolean> {
   try {
-    const { storeCredential, getCredential } = useSecureStorage()
+    const { storeCredential, getCredential, removeCredential } = useSecureStorage()
 
     // Test encrypt/decrypt round trip
     const testValue = 'test-' + Date.now()
@@ -20,7 +20,7 @@ export async function testEncryption(): Promise<boolean> {
     const retrieved = await getCredential('__test__')
 
     // Cleanup
-    localStorage.removeItem('secure___test__')
+    await removeCredential('__test__')
 
     return retrieved === testValue
   } catch (e) {
@@ -0,0 +1,23 @@
+import { onMounted, onUnmounted } from 'vue'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { useDashboardStore, type DashboardRefresh } from '../stores/dashboardStore'
+
+/**
+ * Dashboard Refresh Composable
+ * Applies the backend's `dashboards://refreshed` events (panel data of
+ * dashboards with a refresh interval) to the dashboard store
+ */
+export function useDashboardRefresh() {
+  const dashboardStore = useDashboardStore()
+  let unlisten: UnlistenFn | null = null
+
+  onMounted(async () => {
+    unlisten = await listen<DashboardRefresh>('dashboards://refreshed', event => {
+      dashboardStore.applyRefresh(event.payload)
+    })
+  })
+
+  onUnmounted(() => {
+    unlisten?.()
+  })
+}
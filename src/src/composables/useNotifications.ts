@@ -0,0 +1,29 @@
+import { onMounted, onUnmounted } from 'vue'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { useNotificationStore, type AppNotification } from '../stores/notificationStore'
+
+/**
+ * Notifications Composable
+ * Fills the notification center from the backend, which also shows desktop
+ * notifications (fetch failures, due tickets, alerts, plugin quarantines)
+ * as the per-category settings allow
+ */
+export function useNotifications() {
+  const notificationStore = useNotificationStore()
+  let unlisten: UnlistenFn | null = null
+
+  onMounted(async () => {
+    unlisten = await listen<AppNotification>('notifications://new', event => {
+      notificationStore.receive(event.payload)
+    })
+    try {
+      await notificationStore.loadNotifications()
+    } catch (e) {
+      console.warn('Failed to load notifications:', e)
+    }
+  })
+
+  onUnmounted(() => {
+    unlisten?.()
+  })
+}
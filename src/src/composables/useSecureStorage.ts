@@ -3,16 +3,50 @@ import { invoke } from '@tauri-apps/api/core'
 
 /**
  * Composable for secure credential storage
- * Encrypts sensitive data before storing in localStorage
- * Uses browser's SubtleCrypto API for encryption
+ * Stores credentials in the OS keychain through the backend; where there is
+ * no keychain (browser mode, or a session-only backend store) they are
+ * encrypted with the browser's SubtleCrypto API and kept in localStorage,
+ * under a random key the backend keeps in the OS keychain (or a user-only
+ * file). Copies encrypted under the old machine password are re-encrypted.
  */
 
+export interface VaultStatus {
+  enabled: boolean
+  locked: boolean
+  auto_lock_minutes: number
+}
+
+export interface CredentialBundle {
+  version: string
+  exported_at: string
+  salt: string
+  data: string
+}
+
+export interface CredentialExpiry {
+  key: string
+  expires_at?: string
+  max_age_days?: number
+  rotated_at: string
+}
+
+export interface ExpiringCredential {
+  key: string
+  due_at: string
+  expired: boolean
+  days_left: number
+}
+
 interface EncryptedCredential {
   encrypted: string
   iv: string
   salt: string
+  /** LOCAL_KEY_VERSION when encrypted with the backend's local key */
+  version?: number
 }
 
+const LOCAL_KEY_VERSION = 2
+
 export function useSecureStorage() {
   const isSupported = ref(typeof window !== 'undefined' && window.crypto && window.crypto.subtle)
 
@@ -48,20 +82,36 @@ export function useSecureStorage() {
   }
 
   /**
-   * Get or create machine-specific password
-   * In production, this could use Windows Credential Manager via Tauri
+   * The backend's random local encryption key (OS keychain-bound); null in
+   * browser mode
    */
-  async function getMachinePassword(): Promise<string> {
-    // Try to get from Tauri secure storage (if available)
+  let localKey: Promise<CryptoKey | null> | null = null
+
+  function getLocalKey(): Promise<CryptoKey | null> {
+    localKey ??= invoke<string>('get_local_encryption_key')
+      .then(hex => {
+        const bytes = new Uint8Array(hex.match(/../g)!.map(byte => parseInt(byte, 16)))
+        return window.crypto.subtle.importKey('raw', bytes, 'AES-GCM', false, [
+          'encrypt',
+          'decrypt',
+        ])
+      })
+      .catch(() => null)
+    return localKey
+  }
+
+  /**
+   * Password of the old scheme: the backend's legacy machine password, or a
+   * browser fingerprint in browser mode (which has nothing better)
+   */
+  async function getFallbackPassword(): Promise<string> {
     try {
-      const stored = await invoke<string>('get_machine_password')
+      const stored = await invoke<string>('get_legacy_machine_password')
       if (stored) return stored
     } catch {
-      // Fallback: use browser fingerprint
-      console.warn('Tauri secure storage not available, using fallback')
+      // Browser mode: use the fingerprint
     }
 
-    // Fallback: Generate based on machine characteristics
     // This is not perfect security, but better than plaintext
     const fingerprint = [
       navigator.userAgent,
@@ -92,17 +142,23 @@ export function useSecureStorage() {
       }
     }
 
-    // Generate salt and IV
-    const salt = window.crypto.getRandomValues(new Uint8Array(16))
     const iv = window.crypto.getRandomValues(new Uint8Array(12))
+    const data = new TextEncoder().encode(value)
 
-    // Get machine password and derive key
-    const password = await getMachinePassword()
-    const key = await deriveKey(password, salt)
+    const local = await getLocalKey()
+    if (local) {
+      const encrypted = await window.crypto.subtle.encrypt({ name: 'AES-GCM', iv: iv }, local, data)
+      return {
+        encrypted: btoa(String.fromCharCode(...new Uint8Array(encrypted))),
+        iv: btoa(String.fromCharCode(...iv)),
+        salt: '',
+        version: LOCAL_KEY_VERSION,
+      }
+    }
 
-    // Encrypt the value
-    const encoder = new TextEncoder()
-    const data = encoder.encode(value)
+    // Browser mode: derive the key from the fallback password
+    const salt = window.crypto.getRandomValues(new Uint8Array(16))
+    const key = await deriveKey(await getFallbackPassword(), salt)
     const encrypted = await window.crypto.subtle.encrypt({ name: 'AES-GCM', iv: iv }, key, data)
 
     // Convert to base64 for storage
@@ -117,7 +173,7 @@ export function useSecureStorage() {
    * Decrypt a value
    */
   async function decrypt(credential: EncryptedCredential): Promise<string> {
-    if (!isSupported.value || !credential.iv || !credential.salt) {
+    if (!isSupported.value || !credential.iv) {
       // Was stored without encryption (fallback)
       try {
         return atob(credential.encrypted)
@@ -131,11 +187,16 @@ export function useSecureStorage() {
       // Convert from base64
       const encrypted = Uint8Array.from(atob(credential.encrypted), c => c.charCodeAt(0))
       const iv = Uint8Array.from(atob(credential.iv), c => c.charCodeAt(0))
-      const salt = Uint8Array.from(atob(credential.salt), c => c.charCodeAt(0))
 
-      // Get machine password and derive key
-      const password = await getMachinePassword()
-      const key = await deriveKey(password, salt)
+      let key: CryptoKey
+      if (credential.version === LOCAL_KEY_VERSION) {
+        const local = await getLocalKey()
+        if (!local) throw new Error('Local encryption key unavailable')
+        key = local
+      } else {
+        const salt = Uint8Array.from(atob(credential.salt), c => c.charCodeAt(0))
+        key = await deriveKey(await getFallbackPassword(), salt)
+      }
 
       // Decrypt
       const decrypted = await window.crypto.subtle.decrypt(
@@ -154,34 +215,190 @@ export function useSecureStorage() {
   }
 
   /**
-   * Store a credential securely
+   * Whether the backend keeps credentials across restarts (OS keychain or
+   * master-password vault)
    */
-  async function storeCredential(key: string, value: string): Promise<void> {
-    const encrypted = await encrypt(value)
-    localStorage.setItem(`secure_${key}`, JSON.stringify(encrypted))
+  async function hasKeychain(): Promise<boolean> {
+    try {
+      const storage = await invoke<string>('get_credential_storage')
+      return storage === 'keychain' || storage === 'vault'
+    } catch {
+      return false
+    }
   }
 
   /**
-   * Retrieve a credential securely
+   * Read a credential kept encrypted in localStorage
    */
-  async function getCredential(key: string): Promise<string | null> {
+  async function getLocalCredential(key: string): Promise<string | null> {
     const stored = localStorage.getItem(`secure_${key}`)
     if (!stored) return null
 
     try {
       const credential = JSON.parse(stored) as EncryptedCredential
-      return await decrypt(credential)
+      const value = await decrypt(credential)
+      // Encrypted under the old scheme: move it to the local key
+      if (value && credential.version !== LOCAL_KEY_VERSION && (await getLocalKey())) {
+        localStorage.setItem(`secure_${key}`, JSON.stringify(await encrypt(value)))
+      }
+      return value
     } catch (e) {
       console.error('Failed to retrieve credential:', e)
       return null
     }
   }
 
+  /**
+   * Store a credential securely
+   */
+  async function storeCredential(key: string, value: string): Promise<void> {
+    if (await hasKeychain()) {
+      await invoke('store_secure_credential', { key, value })
+      localStorage.removeItem(`secure_${key}`)
+      return
+    }
+    const encrypted = await encrypt(value)
+    localStorage.setItem(`secure_${key}`, JSON.stringify(encrypted))
+  }
+
+  /**
+   * Retrieve a credential securely
+   */
+  async function getCredential(key: string): Promise<string | null> {
+    if (await hasKeychain()) {
+      const value = await invoke<string | null>('get_secure_credential', { key })
+      if (value !== null) return value
+      // Not moved to the keychain yet
+      const local = await getLocalCredential(key)
+      if (local !== null) await storeCredential(key, local)
+      return local
+    }
+    return await getLocalCredential(key)
+  }
+
   /**
    * Remove a credential
    */
-  function removeCredential(key: string): void {
+  async function removeCredential(key: string): Promise<void> {
     localStorage.removeItem(`secure_${key}`)
+    if (await hasKeychain()) {
+      await invoke('remove_secure_credential', { key })
+    }
+  }
+
+  /**
+   * Keys of the credentials kept encrypted in localStorage
+   */
+  function localCredentialKeys(): string[] {
+    const keys: string[] = []
+    for (let i = 0; i < localStorage.length; i++) {
+      const key = localStorage.key(i)
+      if (key?.startsWith('secure_')) keys.push(key.slice('secure_'.length))
+    }
+    return keys
+  }
+
+  /**
+   * Move the credentials encrypted in localStorage into the OS keychain
+   * Returns the number of credentials moved (none without a keychain)
+   */
+  async function migrateToKeychain(): Promise<number> {
+    if (!(await hasKeychain())) return 0
+
+    let migrated = 0
+    for (const key of localCredentialKeys()) {
+      const value = await getLocalCredential(key)
+      if (value === null) continue
+      try {
+        await storeCredential(key, value)
+        migrated++
+      } catch (e) {
+        // Keep the local copy so nothing is lost
+        console.error(`Failed to move credential ${key} to the keychain:`, e)
+      }
+    }
+    return migrated
+  }
+
+  /**
+   * Re-encrypt the localStorage copies made under the old machine password
+   * with the local encryption key
+   * Returns the number of credentials re-encrypted
+   */
+  async function migrateLocalEncryption(): Promise<number> {
+    if (!(await getLocalKey())) return 0
+
+    let migrated = 0
+    for (const key of localCredentialKeys()) {
+      // Reading re-encrypts old copies
+      const before = localStorage.getItem(`secure_${key}`)
+      await getLocalCredential(key)
+      if (localStorage.getItem(`secure_${key}`) !== before) migrated++
+    }
+    return migrated
+  }
+
+  /**
+   * Master password: status, set/change/remove, lock and unlock
+   */
+  async function getVaultStatus(): Promise<VaultStatus> {
+    return await invoke<VaultStatus>('get_vault_status')
+  }
+
+  async function setMasterPassword(password: string, currentPassword?: string): Promise<void> {
+    await invoke('set_master_password', { password, currentPassword: currentPassword ?? null })
+  }
+
+  async function removeMasterPassword(password: string): Promise<void> {
+    await invoke('remove_master_password', { password })
+  }
+
+  async function unlockCredentials(password: string): Promise<void> {
+    await invoke('unlock_credentials', { password })
+  }
+
+  async function lockCredentials(): Promise<void> {
+    await invoke('lock_credentials')
+  }
+
+  async function setVaultAutoLock(minutes: number): Promise<void> {
+    await invoke('set_vault_auto_lock', { minutes })
+  }
+
+  /**
+   * Password-sealed bundle of the named credentials and profiles, for another machine
+   */
+  async function exportCredentials(password: string): Promise<CredentialBundle> {
+    return await invoke<CredentialBundle>('export_credentials', { password })
+  }
+
+  async function importCredentials(
+    bundle: CredentialBundle,
+    password: string
+  ): Promise<{ credentials: string[]; profiles: string[] }> {
+    return await invoke('import_credentials', { bundle, password })
+  }
+
+  /**
+   * Expiry date and/or maximum age of a credential; storing a new value
+   * restarts the age. Passing neither removes the expiry.
+   */
+  async function setCredentialExpiry(
+    key: string,
+    expiresAt: string | null,
+    maxAgeDays: number | null
+  ): Promise<CredentialExpiry | null> {
+    return await invoke('set_credential_expiry', { key, expiresAt, maxAgeDays })
+  }
+
+  async function listCredentialExpiry(): Promise<CredentialExpiry[]> {
+    return await invoke<CredentialExpiry[]>('list_credential_expiry')
+  }
+
+  async function getExpiringCredentials(withinDays?: number): Promise<ExpiringCredential[]> {
+    return await invoke<ExpiringCredential[]>('get_expiring_credentials', {
+      withinDays: withinDays ?? null,
+    })
   }
 
   /**
@@ -222,5 +439,18 @@ export function useSecureStorage() {
     getCredential,
     removeCredential,
     migrateCredentials,
+    migrateToKeychain,
+    migrateLocalEncryption,
+    getVaultStatus,
+    setMasterPassword,
+    removeMasterPassword,
+    unlockCredentials,
+    lockCredentials,
+    setVaultAutoLock,
+    exportCredentials,
+    importCredentials,
+    setCredentialExpiry,
+    listCredentialExpiry,
+    getExpiringCredentials,
   }
 }
@@ -0,0 +1,47 @@
+import { onMounted, onUnmounted } from 'vue'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import type { WatchedTicketChange } from '../stores/ticketStore'
+import { useToast } from './useToast'
+
+/**
+ * Ticket Watchers Composable
+ * Shows the backend's `tickets://watched` changes (status moves and comments
+ * on watched tickets) as desktop notifications, falling back to toasts
+ */
+export function useTicketWatchers() {
+  const toast = useToast()
+  let unlisten: UnlistenFn | null = null
+
+  function describe(change: WatchedTicketChange): string {
+    return change.kind === 'status'
+      ? `Moved from ${change.from} to ${change.to}`
+      : `${change.author}: ${change.text}`
+  }
+
+  async function canNotify(): Promise<boolean> {
+    if (typeof Notification === 'undefined') return false
+    if (Notification.permission === 'default') {
+      await Notification.requestPermission()
+    }
+    return Notification.permission === 'granted'
+  }
+
+  async function announce(change: WatchedTicketChange) {
+    const title = change.key ? `${change.key} ${change.title}` : change.title
+    if (await canNotify()) {
+      new Notification(title, { body: describe(change) })
+    } else {
+      toast.info(`${title}: ${describe(change)}`)
+    }
+  }
+
+  onMounted(async () => {
+    unlisten = await listen<WatchedTicketChange>('tickets://watched', event => {
+      announce(event.payload)
+    })
+  })
+
+  onUnmounted(() => {
+    unlisten?.()
+  })
+}
@@ -112,6 +112,7 @@ export const BUILT_IN_PANELS: BuiltInPanelType[] = [
             { value: 'rest_api', label: 'REST API' },
             { value: 'gitlab_pipeline', label: 'GitLab Pipeline' },
             { value: 'gitlab_job', label: 'GitLab Job' },
+            { value: 'rss_item', label: 'RSS Item' },
           ],
         },
         {
@@ -145,6 +146,7 @@ export const BUILT_IN_PANELS: BuiltInPanelType[] = [
             { value: 'rest_api', label: 'REST API' },
             { value: 'gitlab_pipeline', label: 'GitLab Pipeline' },
             { value: 'gitlab_job', label: 'GitLab Job' },
+            { value: 'rss_item', label: 'RSS Item' },
           ],
         },
         {
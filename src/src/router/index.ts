@@ -47,6 +47,11 @@ const router = createRouter({
       name: 'database-management',
       component: () => import('@/components/DatabaseManager.vue'),
     },
+    {
+      path: '/performance',
+      name: 'performance',
+      component: () => import('@/views/PerformancePage.vue'),
+    },
     {
       path: '/page/:id',
       name: 'page',
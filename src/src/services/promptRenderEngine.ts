@@ -15,6 +15,7 @@ import type {
   PromptSection,
 } from '@/types/promptTypes'
 import { usePromptStore } from '@/stores/promptStore'
+import { errorMessage } from '@/tauri'
 
 // Built-in separator rules
 const BUILTIN_SEPARATORS: Record<string, SeparatorRules> = {
@@ -119,16 +120,20 @@ export async function renderPrompt(request: RenderRequest): Promise<RenderResult
         }
 
         const text = renderContent(entryPoint.content, context)
+        const negativeContent =
+          'negative_content' in entryPoint ? entryPoint.negative_content : undefined
+        const negativePrompt = negativeContent ? renderContent(negativeContent, context) : undefined
 
         outputs.push({
           text,
+          negative_prompt: negativePrompt,
           variable_values: request.context.variables,
           warnings: context.errors.length > 0 ? context.errors.map(e => e.message) : undefined,
         })
       } catch (e) {
         errors.push({
           code: 'RENDER_ERROR',
-          message: e instanceof Error ? e.message : String(e),
+          message: errorMessage(e),
           location: `output ${i + 1}`,
         })
       }
@@ -153,7 +158,7 @@ export async function renderPrompt(request: RenderRequest): Promise<RenderResult
       errors: [
         {
           code: 'FATAL_ERROR',
-          message: e instanceof Error ? e.message : String(e),
+          message: errorMessage(e),
         },
       ],
       metadata: {
@@ -210,6 +215,12 @@ function renderContent(content: PromptContent, context: RenderContext): string {
     case 'article':
       return renderArticle(content, context)
 
+    case 'possessive':
+      return renderPossessive(content, context)
+
+    case 'verb':
+      return renderVerb(content, context)
+
     case 'count-switch':
       return renderCountSwitch(content, context)
 
@@ -770,6 +781,80 @@ function renderArticle(
   return article
 }
 
+/**
+ * Render possessive form (Alice's, James')
+ */
+function renderPossessive(
+  content: {
+    type: 'possessive'
+    word_variable?: string
+    word_content?: PromptContent
+  },
+  context: RenderContext
+): string {
+  let word: string
+  if (content.word_variable) {
+    context.variablesUsed.add(content.word_variable)
+    word = String(context.variables[content.word_variable] || '')
+  } else if (content.word_content) {
+    word = renderContent(content.word_content, context)
+  } else {
+    return ''
+  }
+
+  word = word.trim()
+  if (!word) return ''
+  return /s$/i.test(word) ? `${word}'` : `${word}'s`
+}
+
+/**
+ * Third-person singular of a base-form verb (run → runs, look up → looks up)
+ */
+function thirdPersonSingular(verb: string): string {
+  const [head, ...rest] = verb.trim().split(' ')
+  const lower = head.toLowerCase()
+  let conjugated: string
+
+  if (!head) {
+    return ''
+  } else if (lower === 'be') {
+    conjugated = 'is'
+  } else if (lower === 'have') {
+    conjugated = 'has'
+  } else if (/(s|sh|ch|x|z|o)$/.test(lower)) {
+    conjugated = `${head}es`
+  } else if (/[^aeiou]y$/.test(lower)) {
+    conjugated = `${head.slice(0, -1)}ies`
+  } else {
+    conjugated = `${head}s`
+  }
+
+  return [conjugated, ...rest].join(' ')
+}
+
+/**
+ * Render a verb agreeing with the subject count
+ */
+function renderVerb(
+  content: {
+    type: 'verb'
+    verb: string
+    count_variable: string
+    singular?: string
+  },
+  context: RenderContext
+): string {
+  context.variablesUsed.add(content.count_variable)
+
+  const value = context.variables[content.count_variable]
+  const count = Array.isArray(value) ? value.length : typeof value === 'number' ? value : 0
+
+  if (count === 1) {
+    return content.singular ?? thirdPersonSingular(content.verb)
+  }
+  return content.verb.trim() === 'be' ? 'are' : content.verb.trim()
+}
+
 /**
  * Render count-based switch
  */
@@ -0,0 +1,91 @@
+import { defineStore } from 'pinia'
+import { ref } from 'vue'
+import type { AggregateMetric, RecordQuery } from './dashboardStore'
+
+// Helper to check if Tauri is available
+const isTauri = () => {
+  return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
+}
+
+// Safe invoke wrapper
+const safeInvoke = async (command: string, args?: any) => {
+  if (!isTauri()) {
+    throw new Error('Not in Tauri mode')
+  }
+  const { invoke } = await import('@tauri-apps/api/core')
+  return invoke(command, args)
+}
+
+export type AlertComparison = 'gt' | 'gte' | 'lt' | 'lte' | 'eq' | 'ne'
+
+// Fires when `metric` over the records matching `query` meets the threshold
+export interface AlertRule {
+  id: string
+  name: string
+  query: RecordQuery
+  metric: AggregateMetric
+  comparison: AlertComparison
+  threshold: number
+  enabled: boolean
+  triggered: boolean
+  last_value: number | null
+  last_evaluated_at: string | null
+  last_fired_at: string | null
+  created_at: string
+  updated_at: string
+}
+
+export interface SaveAlertRuleRequest {
+  id?: string
+  name: string
+  query?: RecordQuery
+  metric?: AggregateMetric
+  comparison?: AlertComparison
+  threshold: number
+  enabled?: boolean
+}
+
+// Pushed by the backend as `alerts://fired` (one event per alert); also stored as records of type 'alert'
+export interface FiredAlert {
+  rule_id: string
+  rule_name: string
+  value: number
+  comparison: AlertComparison
+  threshold: number
+  message: string
+  fired_at: string
+}
+
+export const useAlertStore = defineStore('alerts', () => {
+  const rules = ref<AlertRule[]>([])
+
+  async function loadRules() {
+    rules.value = (await safeInvoke('list_alert_rules')) as AlertRule[]
+    return rules.value
+  }
+
+  async function saveRule(rule: SaveAlertRuleRequest) {
+    const saved = (await safeInvoke('save_alert_rule', { rule })) as AlertRule
+    await loadRules()
+    return saved
+  }
+
+  async function deleteRule(id: string) {
+    await safeInvoke('delete_alert_rule', { id })
+    rules.value = rules.value.filter(r => r.id !== id)
+  }
+
+  async function evaluateRules() {
+    const fired = (await safeInvoke('evaluate_alert_rules')) as FiredAlert[]
+    await loadRules()
+    return fired
+  }
+
+  return {
+    rules,
+    loadRules,
+    saveRule,
+    deleteRule,
+    evaluateRules,
+  }
+})
@@ -1,5 +1,5 @@
 import { defineStore } from 'pinia'
-import { ref } from 'vue'
+import { computed, ref } from 'vue'
 
 // Helper to check if Tauri is available
 const isTauri = () => {
@@ -38,21 +38,240 @@ export interface Panel {
     recordType?: string
     pageSize?: number
     query?: string
+    widgetQuery?: WidgetQuery
   }
 }
 
+// Condition on a record field, e.g. { field: 'data.env', op: 'eq', value: 'prod' }
+export interface FieldCondition {
+  field: string
+  op?: 'eq' | 'ne' | 'gt' | 'gte' | 'lt' | 'lte' | 'contains' | 'in'
+  value: unknown
+}
+
+export interface RecordQuery {
+  types?: string[]
+  sources?: string[]
+  tags?: string[]
+  status?: string[]
+  date_range?: { from?: string; to?: string }
+  conditions?: FieldCondition[]
+  sort?: Array<{ field: string; descending?: boolean }>
+  limit?: number
+  offset?: number
+}
+
+export type AggregateMetric =
+  | { op: 'count' }
+  | { op: 'sum' | 'avg' | 'min' | 'max'; field: string }
+
+// Query spec a widget declares; evaluated by the backend
+export interface WidgetQuery {
+  filters?: RecordQuery
+  aggregate?: {
+    group_by?: string[]
+    metric?: AggregateMetric
+    time_bucket?: 'hour' | 'day' | 'week' | 'month'
+  }
+  // Field paths returned per record (all when empty)
+  fields?: string[]
+  // Counter, gauge or trend; takes precedence over `aggregate`
+  computed?: ComputedWidget
+  // Where clicking the widget leads (its records by default)
+  drilldown?: { dashboard?: string; fields?: string[] }
+}
+
+// What a click on a panel's widget resolves against, from the panel data
+export interface DrilldownTarget {
+  query: RecordQuery
+  group_by: string[]
+  time_bucket?: 'hour' | 'day' | 'week' | 'month'
+  dashboard?: string
+  fields: string[]
+}
+
+// The clicked element: a group label ("a / b" for two group fields) and/or time bucket
+export interface DrilldownContext {
+  group?: string
+  bucket?: string
+  variables?: Record<string, any>
+  limit?: number
+  offset?: number
+}
+
+export interface Drilldown {
+  query: RecordQuery
+  total: number
+  records: any[]
+  // Set when the widget links to a dashboard, with the variables to open it with
+  dashboard?: string
+  variables?: Record<string, any>
+}
+
+export interface ComputedWidget {
+  kind?: 'counter' | 'gauge' | 'trend'
+  metric?: AggregateMetric
+  // Last N hours instead of the filters' date range
+  period_hours?: number
+  compare_previous?: boolean
+  // Trend point size (default: day)
+  time_bucket?: 'hour' | 'day' | 'week' | 'month'
+  // Gauge scale (default: 0..100)
+  min?: number
+  max?: number
+}
+
+export interface ComputedResult {
+  value: number | null
+  previous?: number
+  change?: number
+  change_percent?: number
+  min?: number
+  max?: number
+  // Gauge position between min and max, 0..1
+  ratio?: number
+  points?: Array<{ label: string; value: number | null }>
+}
+
+export interface AggregateResult {
+  labels: string[]
+  series: Array<{ name: string; values: Array<number | null> }>
+}
+
+export type WidgetData =
+  | { kind: 'records'; total: number; records: any[] }
+  | ({ kind: 'series' } & AggregateResult)
+  | ({ kind: 'computed' } & ComputedResult)
+
+// Chart points from `get_timeseries`, downsampled server-side
+export interface Timeseries {
+  points: Array<{ time: string; value: number | null }>
+  // Points before downsampling
+  total_points: number
+  downsampled: boolean
+}
+
+// Plugin panel whose plugin is missing or whose config doesn't match its schema
+export interface PanelWarning {
+  panel_id: string
+  panel_title: string
+  panel_type: string
+  kind: 'unknown_type' | 'missing_field' | 'invalid_value'
+  field?: string
+  message: string
+}
+
+// Pushed by the backend as `dashboards://refreshed` after an auto-refresh
+export interface DashboardRefresh {
+  dashboard_id: string
+  refreshed_at: string
+  fetches: Array<{ source: string; records: number; error?: string }>
+  panels: Array<{
+    panel_id: string
+    data: WidgetData | null
+    error?: string
+    drilldown?: DrilldownTarget
+  }>
+}
+
+// Reference to a secret a bundled data source needs (never the secret itself)
+export interface CredentialReference {
+  data_source_id: string
+  key: string
+  parameter?: string
+}
+
+// Shareable dashboard from `export_dashboard`
+export interface DashboardBundle {
+  version: string
+  exported_at: string
+  dashboard: Dashboard
+  data_sources: any[]
+  plugins: string[]
+  credentials: CredentialReference[]
+}
+
+export interface DashboardImport {
+  dashboard_id: string | null
+  data_sources_created: string[]
+  data_sources_existing: string[]
+  missing_plugins: string[]
+  missing_credentials: CredentialReference[]
+}
+
+// Earlier state of a dashboard, kept on every save that changed it
+export interface DashboardVersion {
+  dashboard: string
+  version: number
+  name: string
+  panels: Panel[]
+  refreshInterval?: number
+  savedAt: number
+}
+
+// Dashboard frozen with its panel data by `snapshot_dashboard`
+export interface DashboardSnapshotSummary {
+  id: string
+  dashboard_id: string
+  dashboard_name: string
+  label?: string
+  taken_at: string
+}
+
+export interface DashboardSnapshot {
+  id: string
+  dashboard_id: string
+  label?: string
+  taken_at: string
+  dashboard: Dashboard
+  panels: Array<{
+    panel_id: string
+    data: WidgetData | null
+    error?: string
+    drilldown?: DrilldownTarget
+  }>
+}
+
+// Built-in dashboard from `list_dashboard_templates`
+export interface DashboardTemplate {
+  id: string
+  name: string
+  description: string
+  icon: string
+  panels: Panel[]
+  data_sources: Array<{ id: string; name: string; adapter_type: string; source: string }>
+  plugins: string[]
+  refresh_interval?: number
+}
+
+// Referenced as `${name}` in widget queries; substituted by the backend
+export interface DashboardVariable {
+  name: string
+  label?: string
+  value: any
+  options?: any[]
+}
+
 export interface Dashboard {
   id: string
   name: string
   panels: Panel[]
   createdAt: number
   updatedAt: number
+  // Seconds between backend refreshes (unset: no auto-refresh)
+  refreshInterval?: number
+  variables?: DashboardVariable[]
 }
 
 export const useDashboardStore = defineStore('dashboard', () => {
   const currentDashboard = ref<Dashboard | null>(null)
   const dashboards = ref<Dashboard[]>([])
   const isEditing = ref(false)
+  // Warnings about plugin panels from the last save or check
+  const panelWarnings = ref<PanelWarning[]>([])
+  // Latest backend-evaluated data of the current dashboard's panels, by panel id
+  const panelData = ref<Record<string, WidgetData>>({})
+  const lastRefresh = ref<string | null>(null)
 
   async function loadDashboards() {
     try {
@@ -90,7 +309,9 @@ export const useDashboardStore = defineStore('dashboard', () => {
     currentDashboard.value.updatedAt = Date.now()
 
     try {
-      await safeInvoke('save_dashboard', { dashboard: currentDashboard.value })
+      panelWarnings.value = ((await safeInvoke('save_dashboard', {
+        dashboard: currentDashboard.value,
+      })) ?? []) as PanelWarning[]
       const index = dashboards.value.findIndex(d => d.id === currentDashboard.value!.id)
       if (index >= 0) {
         dashboards.value[index] = { ...currentDashboard.value }
@@ -189,6 +410,21 @@ export const useDashboardStore = defineStore('dashboard', () => {
     }
   }
 
+  async function checkPanels(id: string) {
+    panelWarnings.value = (await safeInvoke('check_dashboard_panels', { id })) as PanelWarning[]
+    return panelWarnings.value
+  }
+
+  async function duplicateDashboard(id: string, newName: string) {
+    const dashboard = (await safeInvoke('duplicate_dashboard', { id, newName })) as Dashboard
+    dashboards.value.push(dashboard)
+    return dashboard
+  }
+
+  async function openInWindow(id: string) {
+    await safeInvoke('open_dashboard_window', { id })
+  }
+
   async function renameDashboard(newName: string) {
     if (!currentDashboard.value || !newName.trim()) return
 
@@ -215,6 +451,114 @@ export const useDashboardStore = defineStore('dashboard', () => {
     }
   }
 
+  function applyRefresh(refresh: DashboardRefresh) {
+    if (refresh.dashboard_id !== currentDashboard.value?.id) return
+    const data: Record<string, WidgetData> = { ...panelData.value }
+    for (const panel of refresh.panels) {
+      if (panel.data) data[panel.panel_id] = panel.data
+    }
+    panelData.value = data
+    lastRefresh.value = refresh.refreshed_at
+  }
+
+  async function refreshDashboard(id: string) {
+    const refresh = (await safeInvoke('refresh_dashboard', { id })) as DashboardRefresh
+    applyRefresh(refresh)
+    return refresh
+  }
+
+  async function setRefreshInterval(seconds: number | undefined) {
+    if (!currentDashboard.value) return
+    currentDashboard.value.refreshInterval = seconds && seconds > 0 ? seconds : undefined
+    await saveDashboard()
+  }
+
+  async function loadVersions(id: string): Promise<DashboardVersion[]> {
+    return (await safeInvoke('get_dashboard_versions', { id })) as DashboardVersion[]
+  }
+
+  async function restoreVersion(id: string, version: number) {
+    const dashboard = (await safeInvoke('restore_dashboard_version', { id, version })) as Dashboard
+    if (currentDashboard.value?.id === id) currentDashboard.value = dashboard
+    dashboards.value = dashboards.value.map(d => (d.id === id ? dashboard : d))
+    return dashboard
+  }
+
+  async function takeSnapshot(id: string, label?: string): Promise<DashboardSnapshot> {
+    return (await safeInvoke('snapshot_dashboard', { id, label })) as DashboardSnapshot
+  }
+
+  async function loadSnapshots(dashboardId?: string): Promise<DashboardSnapshotSummary[]> {
+    return (await safeInvoke('list_dashboard_snapshots', { dashboardId })) as DashboardSnapshotSummary[]
+  }
+
+  // The full snapshot is also its export document
+  async function loadSnapshot(id: string): Promise<DashboardSnapshot> {
+    return (await safeInvoke('get_dashboard_snapshot', { id })) as DashboardSnapshot
+  }
+
+  async function deleteSnapshot(id: string) {
+    await safeInvoke('delete_dashboard_snapshot', { id })
+  }
+
+  async function exportDashboard(id: string): Promise<DashboardBundle> {
+    return (await safeInvoke('export_dashboard', { id })) as DashboardBundle
+  }
+
+  // With dryRun only reports missing plugins and credentials
+  async function importDashboard(bundle: DashboardBundle, dryRun = false) {
+    const report = (await safeInvoke('import_dashboard', { bundle, dryRun })) as DashboardImport
+    if (!dryRun) await loadDashboards()
+    return report
+  }
+
+  async function listTemplates(): Promise<DashboardTemplate[]> {
+    return (await safeInvoke('list_dashboard_templates')) as DashboardTemplate[]
+  }
+
+  // Also creates (disabled) stubs for the data sources the template reads
+  async function createFromTemplate(id: string) {
+    const report = (await safeInvoke('create_dashboard_from_template', { id })) as DashboardImport
+    await loadDashboards()
+    return report
+  }
+
+  // Current variable values of the open dashboard, by name
+  const variableValues = computed(() =>
+    Object.fromEntries((currentDashboard.value?.variables ?? []).map(v => [v.name, v.value]))
+  )
+
+  async function loadWidgetData(widgetQuery: WidgetQuery): Promise<WidgetData> {
+    return (await safeInvoke('get_widget_data', {
+      widgetQuery,
+      variables: variableValues.value,
+    })) as WidgetData
+  }
+
+  // Without a bucket: the raw values of the metric's field, ordered by time
+  async function loadTimeseries(
+    query: RecordQuery,
+    agg: AggregateMetric,
+    bucket?: 'hour' | 'day' | 'week' | 'month',
+    maxPoints?: number
+  ): Promise<Timeseries> {
+    return (await safeInvoke('get_timeseries', { query, bucket, agg, maxPoints })) as Timeseries
+  }
+
+  async function drilldown(target: DrilldownTarget, context: DrilldownContext = {}) {
+    return (await safeInvoke('resolve_drilldown', {
+      target,
+      context: { variables: variableValues.value, ...context },
+    })) as Drilldown
+  }
+
+  async function setVariable(name: string, value: any) {
+    const variable = currentDashboard.value?.variables?.find(v => v.name === name)
+    if (!variable) return
+    variable.value = value
+    await saveDashboard()
+  }
+
   return {
     currentDashboard,
     dashboards,
@@ -229,5 +573,29 @@ export const useDashboardStore = defineStore('dashboard', () => {
     updateLayout,
     deleteDashboard,
     renameDashboard,
+    loadWidgetData,
+    variableValues,
+    setVariable,
+    drilldown,
+    loadTimeseries,
+    loadVersions,
+    restoreVersion,
+    duplicateDashboard,
+    openInWindow,
+    panelWarnings,
+    checkPanels,
+    takeSnapshot,
+    loadSnapshots,
+    loadSnapshot,
+    deleteSnapshot,
+    exportDashboard,
+    importDashboard,
+    listTemplates,
+    createFromTemplate,
+    panelData,
+    lastRefresh,
+    applyRefresh,
+    refreshDashboard,
+    setRefreshInterval,
   }
 })
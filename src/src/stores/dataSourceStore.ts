@@ -38,6 +38,7 @@ export interface AuthConfig {
     | 'oauth2authorizationcode'
     | 'apikey'
     | 'gitlabtoken'
+    | 'credentialref'
   token?: string
   username?: string
   password?: string
@@ -47,6 +48,14 @@ export interface AuthConfig {
   token_url?: string
   api_key?: string
   api_key_header?: string
+  /** Credential profile name, for 'credentialref' */
+  name?: string
+}
+
+/** A named credential profile, listed without its secrets */
+export interface CredentialProfile {
+  name: string
+  auth_type: string
 }
 
 export interface DataSourceConfig {
@@ -72,7 +81,7 @@ export interface DataSourceConfig {
   environment?: 'dev' | 'production' | 'both' // M9: Environment validation
   enabled: boolean
   auto_refresh?: boolean
-  refresh_interval?: number // minutes
+  refresh_interval?: number // seconds between scheduled fetches
   data_ttl_days?: number // Days to keep data (default: 30)
   last_fetch?: string
   last_fetch_count?: number // Records fetched in last operation
@@ -81,6 +90,27 @@ export interface DataSourceConfig {
   updated_at: string
 }
 
+/** Data source as persisted by the backend */
+interface StoredDataSource extends Omit<DataSourceConfig, 'auth' | 'created_at' | 'updated_at'> {
+  auth_type?: string
+  auth_credential_key?: string
+  createdAt: string
+  updatedAt: string
+}
+
+// The backend keeps a credential reference instead of the auth config;
+// tokens live in the secure store, as restoreAuthConfig expects
+function fromStored(stored: StoredDataSource): DataSourceConfig {
+  const { auth_type, auth_credential_key, createdAt, updatedAt, ...config } = stored
+  let auth: AuthConfig | null = null
+  if (auth_type === 'credentialref') {
+    auth = { type: 'credentialref', name: auth_credential_key }
+  } else if (auth_type === 'bearer' || auth_type === 'gitlabtoken') {
+    auth = { type: auth_type, token: '***SECURED***' }
+  }
+  return { ...config, auth, created_at: createdAt, updated_at: updatedAt }
+}
+
 export interface FetchHistory {
   id: string
   data_source_id: string
@@ -90,6 +120,33 @@ export interface FetchHistory {
   error?: string
 }
 
+/** A fetch run as kept in the backend fetch history */
+export interface FetchRun {
+  id: string
+  source: string
+  adapter_type: string
+  started_at: string
+  duration_ms: number
+  record_count: number
+  error?: string
+}
+
+/** Sync status of a data source, from the backend */
+export interface DataSourceStatus {
+  id: string
+  source: string
+  enabled: boolean
+  paused: boolean
+  last_success?: string
+  last_record_count?: number
+  total_records: number
+  last_error?: string
+  last_error_at?: string
+  next_run?: string
+  /** Most recent failed runs, newest first */
+  errors: FetchRun[]
+}
+
 export const useDataSourceStore = defineStore('dataSource', () => {
   const dataSources = ref<DataSourceConfig[]>([])
   const fetchHistory = ref<FetchHistory[]>([])
@@ -139,12 +196,22 @@ export const useDataSourceStore = defineStore('dataSource', () => {
         return dataSources.value
       }
 
-      // In Tauri mode, could load from file (future enhancement)
-      // For now, still use localStorage
-      const stored = localStorage.getItem('data-sources')
-      if (stored) {
-        dataSources.value = JSON.parse(stored)
+      // Tauri mode: data sources are persisted by the backend. Sources kept
+      // in localStorage by earlier versions are moved there once.
+      const legacy = localStorage.getItem('data-sources')
+      if (legacy) {
+        for (const source of JSON.parse(legacy) as DataSourceConfig[]) {
+          try {
+            await safeInvoke('save_data_source', { dataSource: source })
+          } catch (err) {
+            console.warn(`Failed to move data source ${source.name} to the database:`, err)
+          }
+        }
+        localStorage.removeItem('data-sources')
       }
+
+      const stored = (await safeInvoke('get_data_sources')) as StoredDataSource[]
+      dataSources.value = stored.map(fromStored)
       return dataSources.value
     } catch (err: any) {
       error.value = `Failed to load data sources: ${err.message}`
@@ -176,10 +243,12 @@ export const useDataSourceStore = defineStore('dataSource', () => {
         return newSource
       }
 
-      // M9: Tauri mode - save to database
-      await safeInvoke('save_data_source', { dataSource: newSource })
-      dataSources.value.push(newSource)
-      return newSource
+      // M9: Tauri mode - save to database (fails if the source name is taken)
+      const saved = fromStored(
+        (await safeInvoke('save_data_source', { dataSource: newSource })) as StoredDataSource
+      )
+      dataSources.value.push(saved)
+      return saved
     } catch (err: any) {
       error.value = `Failed to create data source: ${err.message}`
       console.error(error.value)
@@ -472,6 +541,14 @@ export const useDataSourceStore = defineStore('dataSource', () => {
     return fetchHistory.value.filter(h => h.data_source_id === dataSourceId)
   }
 
+  // Get sync status and recent errors of a data source (Tauri mode only)
+  async function getDataSourceStatus(id: string): Promise<DataSourceStatus | null> {
+    if (!isTauri()) {
+      return null
+    }
+    return (await safeInvoke('get_data_source_status', { id })) as DataSourceStatus
+  }
+
   // Get available adapter types
   async function getAvailableAdapters(): Promise<string[]> {
     if (!isTauri()) {
@@ -533,6 +610,20 @@ export const useDataSourceStore = defineStore('dataSource', () => {
     }
   }
 
+  // Named credential profiles, resolved by the backend at fetch time
+  async function listCredentialProfiles(): Promise<CredentialProfile[]> {
+    if (!isTauri()) return []
+    return (await safeInvoke('list_credential_profiles')) as CredentialProfile[]
+  }
+
+  async function saveCredentialProfile(name: string, auth: AuthConfig): Promise<void> {
+    await safeInvoke('save_credential_profile', { name, auth })
+  }
+
+  async function deleteCredentialProfile(name: string): Promise<void> {
+    await safeInvoke('delete_credential_profile', { name })
+  }
+
   // NEW: Get all enabled data sources for multi-source selection
   function getEnabledDataSources(): DataSourceConfig[] {
     return dataSources.value.filter(ds => ds.enabled)
@@ -619,8 +710,12 @@ export const useDataSourceStore = defineStore('dataSource', () => {
     testConnection,
     fetchData,
     getDataSourceHistory,
+    getDataSourceStatus,
     getAvailableAdapters,
     getAdapterDefaultConfig,
+    listCredentialProfiles,
+    saveCredentialProfile,
+    deleteCredentialProfile,
     getEnabledDataSources,
     getRecordsBySource,
     refreshTotalRecords,
@@ -9,6 +9,7 @@
  * - LocalStorage Export/Import: Plugin panel data stored in browser
  * - Database Statistics: View counts and sizes
  * - Cleanup Operations: Clear records, cleanup old data
+ * - Undo: Restore the last deleted records, ticket or prompt package
  *
  * Environment Databases:
  * - Dev mode: Uses data/dev/db
@@ -18,6 +19,7 @@
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
+import { errorMessage } from '@/tauri'
 
 export interface DatabaseStats {
   total_records: number
@@ -33,10 +35,31 @@ export interface ImportStats {
   settings_imported: number
   plugin_data_imported: number
   tickets_imported: number
+  attachments_imported: number
   dashboards_imported: number
   errors: string[]
 }
 
+export interface UndoableOperation {
+  id: string
+  kind: 'clear_records' | 'delete_records_by_type' | 'delete_ticket' | 'delete_prompt_package'
+  description: string
+  items: number
+  created_at: string
+  expires_at: string
+}
+
+export type Environment = 'dev' | 'prod'
+
+export type CopyEntity =
+  | 'records'
+  | 'tickets'
+  | 'pages'
+  | 'data_sources'
+  | 'settings'
+  | 'dashboards'
+  | 'plugin_data'
+
 export interface DatabaseExport {
   version: string
   exported_at: string
@@ -68,7 +91,7 @@ export const useDatabaseStore = defineStore('database', () => {
       stats.value = result
       return result
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       throw e
     } finally {
       isLoading.value = false
@@ -95,7 +118,7 @@ export const useDatabaseStore = defineStore('database', () => {
       })
       return result
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       throw e
     } finally {
       isLoading.value = false
@@ -120,7 +143,38 @@ export const useDatabaseStore = defineStore('database', () => {
       console.log('Database imported:', result)
       return result
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
+      throw e
+    } finally {
+      isLoading.value = false
+    }
+  }
+
+  /**
+   * Copy data between the dev and prod databases
+   * Rows existing in both are kept unless onConflict is 'overwrite'
+   */
+  async function copyEnvironmentData(
+    from: Environment,
+    to: Environment,
+    entities: CopyEntity[],
+    onConflict: 'skip' | 'overwrite' = 'skip'
+  ): Promise<ImportStats> {
+    isLoading.value = true
+    error.value = null
+
+    try {
+      const result = await invoke<ImportStats>('copy_environment_data', {
+        from,
+        to,
+        entities,
+        onConflict,
+      })
+      console.log(`Copied ${entities.join(', ')} from ${from} to ${to}:`, result)
+      await getStats()
+      return result
+    } catch (e) {
+      error.value = errorMessage(e)
       throw e
     } finally {
       isLoading.value = false
@@ -163,7 +217,33 @@ export const useDatabaseStore = defineStore('database', () => {
 
       return count
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
+      throw e
+    } finally {
+      isLoading.value = false
+    }
+  }
+
+  /**
+   * Deletes that can still be undone, newest first
+   */
+  async function listUndoableOperations(): Promise<UndoableOperation[]> {
+    return invoke<UndoableOperation[]>('list_undoable_operations')
+  }
+
+  /**
+   * Restore what the newest undoable delete removed
+   */
+  async function undoLastOperation(): Promise<UndoableOperation> {
+    isLoading.value = true
+    error.value = null
+
+    try {
+      const operation = await invoke<UndoableOperation>('undo_last_operation')
+      await getStats()
+      return operation
+    } catch (e) {
+      error.value = errorMessage(e)
       throw e
     } finally {
       isLoading.value = false
@@ -189,7 +269,7 @@ export const useDatabaseStore = defineStore('database', () => {
 
       return result.deleted
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       throw e
     } finally {
       isLoading.value = false
@@ -353,9 +433,12 @@ export const useDatabaseStore = defineStore('database', () => {
     getStats,
     exportDatabase,
     importDatabase,
+    copyEnvironmentData,
     downloadExport,
     clearAllRecords,
     cleanupOldRecords,
+    listUndoableOperations,
+    undoLastOperation,
     // LocalStorage panel data functions
     exportLocalStoragePanelData,
     importLocalStoragePanelData,
@@ -0,0 +1,83 @@
+import { defineStore } from 'pinia'
+import { ref } from 'vue'
+
+// Helper to check if Tauri is available
+const isTauri = () => {
+  return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
+}
+
+// Safe invoke wrapper
+const safeInvoke = async (command: string, args?: any) => {
+  if (!isTauri()) {
+    throw new Error('Not in Tauri mode')
+  }
+  const { invoke } = await import('@tauri-apps/api/core')
+  return invoke(command, args)
+}
+
+// An interval in seconds, or a five-field cron expression in local time
+export type JobSchedule =
+  | { type: 'interval'; secs: number }
+  | { type: 'cron'; expression: string }
+
+export interface JobRun {
+  started_at: string
+  duration_ms: number
+  status: 'success' | 'failed'
+  // What the job did, or why it failed
+  message: string
+}
+
+// Recurring background job (polling, retention, rollups, backup, alerts)
+export interface Job {
+  id: string
+  description: string
+  schedule: JobSchedule
+  default_schedule: JobSchedule
+  paused: boolean
+  running: boolean
+  next_run?: string
+  last_run?: JobRun
+}
+
+export const useJobStore = defineStore('jobs', () => {
+  const jobs = ref<Job[]>([])
+
+  function replace(job: Job) {
+    jobs.value = jobs.value.map(j => (j.id === job.id ? job : j))
+    return job
+  }
+
+  async function loadJobs() {
+    jobs.value = (await safeInvoke('list_jobs')) as Job[]
+    return jobs.value
+  }
+
+  async function pauseJob(id: string) {
+    return replace((await safeInvoke('pause_job', { id })) as Job)
+  }
+
+  async function resumeJob(id: string) {
+    return replace((await safeInvoke('resume_job', { id })) as Job)
+  }
+
+  async function runJobNow(id: string) {
+    const run = (await safeInvoke('run_job_now', { id })) as JobRun
+    await loadJobs()
+    return run
+  }
+
+  // No schedule restores the job's default
+  async function setJobSchedule(id: string, schedule: JobSchedule | null) {
+    return replace((await safeInvoke('set_job_schedule', { id, schedule })) as Job)
+  }
+
+  return {
+    jobs,
+    loadJobs,
+    pauseJob,
+    resumeJob,
+    runJobNow,
+    setJobSchedule,
+  }
+})
@@ -0,0 +1,68 @@
+import { defineStore } from 'pinia'
+import { computed, ref } from 'vue'
+
+// Helper to check if Tauri is available
+const isTauri = () => {
+  return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
+}
+
+// Safe invoke wrapper
+const safeInvoke = async (command: string, args?: any) => {
+  if (!isTauri()) {
+    throw new Error('Not in Tauri mode')
+  }
+  const { invoke } = await import('@tauri-apps/api/core')
+  return invoke(command, args)
+}
+
+// Desktop notifications can be turned off per category (`notifications.<category>` settings)
+export type NotificationCategory =
+  | 'fetch_failures'
+  | 'due_tickets'
+  | 'alerts'
+  | 'plugin_quarantines'
+
+export interface AppNotification {
+  id: string
+  category: NotificationCategory
+  title: string
+  body: string
+  read: boolean
+  created_at: string
+}
+
+export const useNotificationStore = defineStore('notifications', () => {
+  const notifications = ref<AppNotification[]>([])
+
+  const unreadCount = computed(() => notifications.value.filter(n => !n.read).length)
+
+  async function loadNotifications(unreadOnly = false, limit?: number) {
+    notifications.value = (await safeInvoke('get_notifications', {
+      unreadOnly,
+      limit,
+    })) as AppNotification[]
+    return notifications.value
+  }
+
+  // Add a notification pushed by the backend (`notifications://new`)
+  function receive(notification: AppNotification) {
+    notifications.value = [
+      notification,
+      ...notifications.value.filter(n => n.id !== notification.id),
+    ]
+  }
+
+  async function markRead(id: string) {
+    const updated = (await safeInvoke('mark_notification_read', { id })) as AppNotification
+    notifications.value = notifications.value.map(n => (n.id === id ? updated : n))
+    return updated
+  }
+
+  return {
+    notifications,
+    unreadCount,
+    loadNotifications,
+    receive,
+    markRead,
+  }
+})
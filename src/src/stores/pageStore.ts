@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
+import { errorMessage } from '@/tauri'
 
 // SurrealDB Thing type
 interface Thing {
@@ -17,6 +18,31 @@ export interface Page {
   config?: any
   order: number
   visible: boolean
+  parent_id?: string // Key of the parent page, unset for top-level pages
+  created_at: string
+  updated_at: string
+}
+
+export interface PageNode extends Page {
+  children: PageNode[]
+}
+
+export type DeletePageMode = 'orphan' | 'cascade'
+
+export type PageExportFormat = 'markdown' | 'html'
+
+// Page content, stored block by block
+export type BlockContent =
+  | { type: 'text'; text: string }
+  | { type: 'widget'; panel_type: string; config?: Record<string, any> }
+  | { type: 'record_query'; query: Record<string, any>; view?: string }
+  | { type: 'prompt_section'; section_id: string; variables?: Record<string, any>; seed?: number }
+
+export interface PageBlock {
+  id: string
+  page_id: string
+  position: number
+  content: BlockContent
   created_at: string
   updated_at: string
 }
@@ -65,6 +91,8 @@ function getPageId(page: Page): string {
 export const usePageStore = defineStore('pages', () => {
   // State
   const pages = ref<Page[]>([])
+  const tree = ref<PageNode[]>([])
+  const blocks = ref<Record<string, PageBlock[]>>({}) // By page key
   const currentPageId = ref<string | null>(null)
   const loading = ref(false)
   const error = ref<string | null>(null)
@@ -86,7 +114,7 @@ export const usePageStore = defineStore('pages', () => {
       pages.value = result
       return result
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       console.error('Failed to load pages:', e)
       // Return empty array on error for graceful degradation
       return []
@@ -95,6 +123,17 @@ export const usePageStore = defineStore('pages', () => {
     }
   }
 
+  async function loadPageTree() {
+    try {
+      tree.value = await invoke<PageNode[]>('get_pages_tree')
+      return tree.value
+    } catch (e) {
+      error.value = errorMessage(e)
+      console.error('Failed to load page tree:', e)
+      return []
+    }
+  }
+
   async function createPage(pageData: Partial<Page>): Promise<Page> {
     loading.value = true
     error.value = null
@@ -111,7 +150,7 @@ export const usePageStore = defineStore('pages', () => {
       pages.value.push(newPage)
       return newPage
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       throw e
     } finally {
       loading.value = false
@@ -138,22 +177,23 @@ export const usePageStore = defineStore('pages', () => {
 
       return updated
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       throw e
     } finally {
       loading.value = false
     }
   }
 
-  async function deletePage(id: string): Promise<void> {
+  async function deletePage(id: string, mode: DeletePageMode = 'orphan'): Promise<void> {
     loading.value = true
     error.value = null
 
     try {
-      await invoke('delete_page', { id })
-      pages.value = pages.value.filter(p => getPageId(p) !== id)
+      await invoke('delete_page', { id, mode })
+      // Children were moved or deleted as well
+      await loadPages()
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       throw e
     } finally {
       loading.value = false
@@ -175,16 +215,79 @@ export const usePageStore = defineStore('pages', () => {
         }
       })
     } catch (e) {
-      error.value = e instanceof Error ? e.message : String(e)
+      error.value = errorMessage(e)
       throw e
     } finally {
       loading.value = false
     }
   }
 
+  async function movePage(id: string, parentId: string | null, index?: number): Promise<void> {
+    error.value = null
+
+    try {
+      tree.value = await invoke<PageNode[]>('move_page', { id, parentId, index })
+      await loadPages()
+    } catch (e) {
+      error.value = errorMessage(e)
+      throw e
+    }
+  }
+
+  // Blocks are saved one at a time, so editing a block doesn't rewrite the page
+  async function loadBlocks(pageId: string): Promise<PageBlock[]> {
+    const result = await invoke<PageBlock[]>('get_page_blocks', { pageId })
+    blocks.value[pageId] = result
+    return result
+  }
+
+  async function insertBlock(
+    pageId: string,
+    content: BlockContent,
+    index?: number
+  ): Promise<PageBlock> {
+    const block = await invoke<PageBlock>('insert_page_block', { pageId, content, index })
+    await loadBlocks(pageId)
+    return block
+  }
+
+  async function updateBlock(id: string, content: BlockContent): Promise<PageBlock> {
+    const updated = await invoke<PageBlock>('update_page_block', { id, content })
+    const list = blocks.value[updated.page_id]
+    const index = list?.findIndex(b => b.id === updated.id) ?? -1
+    if (list && index !== -1) {
+      list[index] = updated
+    }
+    return updated
+  }
+
+  async function moveBlock(block: PageBlock, index: number, pageId?: string): Promise<void> {
+    const target = pageId ?? block.page_id
+    blocks.value[target] = await invoke<PageBlock[]>('move_page_block', {
+      id: block.id,
+      index,
+      pageId,
+    })
+    if (target !== block.page_id) {
+      await loadBlocks(block.page_id)
+    }
+  }
+
+  async function deleteBlock(block: PageBlock): Promise<void> {
+    await invoke('delete_page_block', { id: block.id })
+    await loadBlocks(block.page_id)
+  }
+
+  // Markdown or standalone HTML, with queried records inlined as tables
+  async function exportPage(id: string, format: PageExportFormat): Promise<string> {
+    return invoke<string>('export_page', { id, format })
+  }
+
   return {
     // State
     pages,
+    tree,
+    blocks,
     currentPageId,
     loading,
     error,
@@ -195,9 +298,17 @@ export const usePageStore = defineStore('pages', () => {
 
     // Actions
     loadPages,
+    loadPageTree,
     createPage,
     updatePage,
     deletePage,
     reorderPages,
+    movePage,
+    loadBlocks,
+    insertBlock,
+    updateBlock,
+    moveBlock,
+    deleteBlock,
+    exportPage,
   }
 })
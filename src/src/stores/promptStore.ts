@@ -13,6 +13,7 @@ import type {
   PromptTag,
   PackageExport,
 } from '@/types/promptTypes'
+import { errorMessage } from '@/tauri'
 
 export const usePromptStore = defineStore('prompt', () => {
   // ============================================
@@ -114,7 +115,7 @@ export const usePromptStore = defineStore('prompt', () => {
     try {
       packages.value = await invoke('get_prompt_packages')
     } catch (e) {
-      error.value = `Failed to load packages: ${e}`
+      error.value = `Failed to load packages: ${errorMessage(e)}`
       console.error('Failed to load packages:', e)
     } finally {
       loading.value = false
@@ -131,7 +132,7 @@ export const usePromptStore = defineStore('prompt', () => {
       packages.value.push(created)
       return created
     } catch (e) {
-      error.value = `Failed to create package: ${e}`
+      error.value = `Failed to create package: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -147,7 +148,7 @@ export const usePromptStore = defineStore('prompt', () => {
       if (index >= 0) packages.value[index] = updated
       return updated
     } catch (e) {
-      error.value = `Failed to update package: ${e}`
+      error.value = `Failed to update package: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -166,7 +167,7 @@ export const usePromptStore = defineStore('prompt', () => {
         selectedPackageId.value = null
       }
     } catch (e) {
-      error.value = `Failed to delete package: ${e}`
+      error.value = `Failed to delete package: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -183,7 +184,7 @@ export const usePromptStore = defineStore('prompt', () => {
     try {
       templates.value = await invoke('get_prompt_templates', { packageId })
     } catch (e) {
-      error.value = `Failed to load templates: ${e}`
+      error.value = `Failed to load templates: ${errorMessage(e)}`
       console.error('Failed to load templates:', e)
     } finally {
       loading.value = false
@@ -202,7 +203,7 @@ export const usePromptStore = defineStore('prompt', () => {
       templates.value.push(created)
       return created
     } catch (e) {
-      error.value = `Failed to create template: ${e}`
+      error.value = `Failed to create template: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -218,7 +219,7 @@ export const usePromptStore = defineStore('prompt', () => {
       if (index >= 0) templates.value[index] = updated
       return updated
     } catch (e) {
-      error.value = `Failed to update template: ${e}`
+      error.value = `Failed to update template: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -232,7 +233,7 @@ export const usePromptStore = defineStore('prompt', () => {
       await invoke('delete_prompt_template', { id })
       templates.value = templates.value.filter(t => getIdString(t.id) !== id)
     } catch (e) {
-      error.value = `Failed to delete template: ${e}`
+      error.value = `Failed to delete template: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -249,7 +250,7 @@ export const usePromptStore = defineStore('prompt', () => {
     try {
       sections.value = await invoke('get_prompt_sections', { packageId })
     } catch (e) {
-      error.value = `Failed to load sections: ${e}`
+      error.value = `Failed to load sections: ${errorMessage(e)}`
       console.error('Failed to load sections:', e)
     } finally {
       loading.value = false
@@ -266,7 +267,7 @@ export const usePromptStore = defineStore('prompt', () => {
       sections.value.push(created)
       return created
     } catch (e) {
-      error.value = `Failed to create section: ${e}`
+      error.value = `Failed to create section: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -283,7 +284,7 @@ export const usePromptStore = defineStore('prompt', () => {
     try {
       separatorSets.value = await invoke('get_separator_sets', { packageId })
     } catch (e) {
-      error.value = `Failed to load separator sets: ${e}`
+      error.value = `Failed to load separator sets: ${errorMessage(e)}`
       console.error('Failed to load separator sets:', e)
     } finally {
       loading.value = false
@@ -300,7 +301,37 @@ export const usePromptStore = defineStore('prompt', () => {
       separatorSets.value.push(created)
       return created
     } catch (e) {
-      error.value = `Failed to create separator set: ${e}`
+      error.value = `Failed to create separator set: ${errorMessage(e)}`
+      throw e
+    } finally {
+      loading.value = false
+    }
+  }
+
+  async function updateSeparatorSet(id: string, set: SeparatorSet) {
+    loading.value = true
+    error.value = null
+    try {
+      const updated = await invoke<SeparatorSet>('update_separator_set', { id, separatorSet: set })
+      const index = separatorSets.value.findIndex(s => getIdString(s.id) === id)
+      if (index >= 0) separatorSets.value[index] = updated
+      return updated
+    } catch (e) {
+      error.value = `Failed to update separator set: ${errorMessage(e)}`
+      throw e
+    } finally {
+      loading.value = false
+    }
+  }
+
+  async function deleteSeparatorSet(id: string) {
+    loading.value = true
+    error.value = null
+    try {
+      await invoke('delete_separator_set', { id })
+      separatorSets.value = separatorSets.value.filter(s => getIdString(s.id) !== id)
+    } catch (e) {
+      error.value = `Failed to delete separator set: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -317,7 +348,7 @@ export const usePromptStore = defineStore('prompt', () => {
     try {
       dataTypes.value = await invoke('get_prompt_data_types', { packageId })
     } catch (e) {
-      error.value = `Failed to load data types: ${e}`
+      error.value = `Failed to load data types: ${errorMessage(e)}`
       console.error('Failed to load data types:', e)
     } finally {
       loading.value = false
@@ -334,7 +365,37 @@ export const usePromptStore = defineStore('prompt', () => {
       dataTypes.value.push(created)
       return created
     } catch (e) {
-      error.value = `Failed to create data type: ${e}`
+      error.value = `Failed to create data type: ${errorMessage(e)}`
+      throw e
+    } finally {
+      loading.value = false
+    }
+  }
+
+  async function updateDataType(id: string, dt: PromptDataType) {
+    loading.value = true
+    error.value = null
+    try {
+      const updated = await invoke<PromptDataType>('update_prompt_data_type', { id, dataType: dt })
+      const index = dataTypes.value.findIndex(t => getIdString(t.id) === id)
+      if (index >= 0) dataTypes.value[index] = updated
+      return updated
+    } catch (e) {
+      error.value = `Failed to update data type: ${errorMessage(e)}`
+      throw e
+    } finally {
+      loading.value = false
+    }
+  }
+
+  async function deleteDataType(id: string) {
+    loading.value = true
+    error.value = null
+    try {
+      await invoke('delete_prompt_data_type', { id })
+      dataTypes.value = dataTypes.value.filter(t => getIdString(t.id) !== id)
+    } catch (e) {
+      error.value = `Failed to delete data type: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -351,7 +412,7 @@ export const usePromptStore = defineStore('prompt', () => {
     try {
       tags.value = await invoke('get_prompt_tags', { packageId })
     } catch (e) {
-      error.value = `Failed to load tags: ${e}`
+      error.value = `Failed to load tags: ${errorMessage(e)}`
       console.error('Failed to load tags:', e)
     } finally {
       loading.value = false
@@ -368,7 +429,7 @@ export const usePromptStore = defineStore('prompt', () => {
       tags.value.push(created)
       return created
     } catch (e) {
-      error.value = `Failed to create tag: ${e}`
+      error.value = `Failed to create tag: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -385,7 +446,7 @@ export const usePromptStore = defineStore('prompt', () => {
     try {
       return await invoke<PackageExport>('export_prompt_package', { packageId })
     } catch (e) {
-      error.value = `Failed to export package: ${e}`
+      error.value = `Failed to export package: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -396,7 +457,9 @@ export const usePromptStore = defineStore('prompt', () => {
     loading.value = true
     error.value = null
     try {
-      const packageId = await invoke<string>('import_prompt_package', { exportData })
+      const summary = await invoke<{ package_id: string }>('import_prompt_package', {
+        exportData,
+      })
       // Reload all data
       await loadPackages()
       await loadTemplates()
@@ -404,9 +467,9 @@ export const usePromptStore = defineStore('prompt', () => {
       await loadSeparatorSets()
       await loadDataTypes()
       await loadTags()
-      return packageId
+      return summary.package_id
     } catch (e) {
-      error.value = `Failed to import package: ${e}`
+      error.value = `Failed to import package: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -422,7 +485,7 @@ export const usePromptStore = defineStore('prompt', () => {
       await loadAll()
       return result
     } catch (e) {
-      error.value = `Failed to seed examples: ${e}`
+      error.value = `Failed to seed examples: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -438,7 +501,7 @@ export const usePromptStore = defineStore('prompt', () => {
       await loadAll()
       return result
     } catch (e) {
-      error.value = `Failed to seed text2image common package: ${e}`
+      error.value = `Failed to seed text2image common package: ${errorMessage(e)}`
       throw e
     } finally {
       loading.value = false
@@ -462,7 +525,7 @@ export const usePromptStore = defineStore('prompt', () => {
         loadTags(),
       ])
     } catch (e) {
-      error.value = `Failed to load data: ${e}`
+      error.value = `Failed to load data: ${errorMessage(e)}`
       console.error('Failed to load prompt data:', e)
     } finally {
       loading.value = false
@@ -605,10 +668,14 @@ export const usePromptStore = defineStore('prompt', () => {
     // Separator set actions
     loadSeparatorSets,
     createSeparatorSet,
+    updateSeparatorSet,
+    deleteSeparatorSet,
 
     // Data type actions
     loadDataTypes,
     createDataType,
+    updateDataType,
+    deleteDataType,
 
     // Tag actions
     loadTags,
@@ -1,5 +1,6 @@
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
+import { errorMessage } from '@/tauri'
 
 /**
  * Settings Store - Manages app-wide settings
@@ -9,8 +10,44 @@ import { ref } from 'vue'
  * STORAGE STRATEGY:
  * - Tauri mode (dev OR prod): Always uses SurrealDB database
  * - Pure browser mode (no Tauri): Falls back to localStorage for frontend-only development
+ *
+ * Settings are typed: the backend validates writes against its schema
+ * (`get_settings_schema`) and reports defaults for unset keys
  */
 
+export interface SettingDefinition {
+  key: string
+  type: 'string' | 'number' | 'boolean' | 'json'
+  default: unknown
+  label?: string
+  description?: string
+  category?: string
+  min?: number
+  max?: number
+  options?: unknown[]
+  owner: string
+}
+
+/** App configuration without secrets, as made by `export_settings` */
+export interface SettingsBundle {
+  version: string
+  exported_at: string
+  settings: Record<string, unknown>
+  boards: unknown[]
+  retention_rules: unknown[]
+  rollup_rules: unknown[]
+}
+
+export interface SettingsImport {
+  settings: number
+  boards: number
+  retention_rules: number
+  rollup_rules: number
+  skipped: string[]
+}
+
+export type SettingsScope = 'all' | 'settings' | 'boards' | 'schedules'
+
 // Helper to check if Tauri is available (browser-only vs Tauri app, NOT dev vs prod)
 const isTauri = () => {
   return typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window
@@ -33,6 +70,8 @@ export const useSettingsStore = defineStore('settings', () => {
   const showDebugInfo = ref(false)
   const theme = ref<'light' | 'dark'>('light')
 
+  const schema = ref<SettingDefinition[]>([])
+
   const loading = ref(false)
   const error = ref<string | null>(null)
 
@@ -75,32 +114,28 @@ export const useSettingsStore = defineStore('settings', () => {
         return
       }
 
-      // M9: Tauri mode - load from database
+      // M9: Tauri mode - load from database (typed values, defaults included)
       const settings = (await safeInvoke('get_all_settings')) as Array<{
         key: string
-        value: string
+        value: any
+        is_default: boolean
       }>
 
       settings.forEach(setting => {
-        try {
-          const value = JSON.parse(setting.value)
-          switch (setting.key) {
-            case 'manualOfflineMode':
-              manualOfflineMode.value = value
-              break
-            case 'showDebugInfo':
-              showDebugInfo.value = value
-              break
-            case 'theme':
-              theme.value = value
-              break
-          }
-        } catch (e) {
-          console.warn(`Failed to parse setting ${setting.key}:`, e)
+        switch (setting.key) {
+          case 'manualOfflineMode':
+            manualOfflineMode.value = setting.value
+            break
+          case 'showDebugInfo':
+            showDebugInfo.value = setting.value
+            break
+          case 'theme':
+            theme.value = setting.value
+            break
         }
       })
     } catch (err: any) {
-      const errorMsg = err instanceof Error ? err.message : String(err)
+      const errorMsg = errorMessage(err)
       error.value = `Failed to load settings: ${errorMsg}`
       console.error('Failed to load settings:', err)
 
@@ -139,18 +174,9 @@ export const useSettingsStore = defineStore('settings', () => {
       }
 
       // M9: Tauri mode - save to database
-      await safeInvoke('set_setting', {
-        key: 'manualOfflineMode',
-        value: JSON.stringify(manualOfflineMode.value),
-      })
-      await safeInvoke('set_setting', {
-        key: 'showDebugInfo',
-        value: JSON.stringify(showDebugInfo.value),
-      })
-      await safeInvoke('set_setting', {
-        key: 'theme',
-        value: JSON.stringify(theme.value),
-      })
+      await safeInvoke('set_setting', { key: 'manualOfflineMode', value: manualOfflineMode.value })
+      await safeInvoke('set_setting', { key: 'showDebugInfo', value: showDebugInfo.value })
+      await safeInvoke('set_setting', { key: 'theme', value: theme.value })
     } catch (err: any) {
       error.value = `Failed to save settings: ${err.message}`
       console.error(error.value)
@@ -167,6 +193,38 @@ export const useSettingsStore = defineStore('settings', () => {
     }
   }
 
+  /**
+   * Load the declared settings (core and plugins) for generated settings forms
+   */
+  async function loadSchema() {
+    if (!isTauri()) return
+    schema.value = (await safeInvoke('get_settings_schema')) as SettingDefinition[]
+  }
+
+  /**
+   * Export settings, board configs and background job rules (no secrets)
+   */
+  async function exportSettings(): Promise<SettingsBundle> {
+    return (await safeInvoke('export_settings')) as SettingsBundle
+  }
+
+  /**
+   * Replace the configuration with an exported bundle, then reload it
+   */
+  async function importSettings(bundle: SettingsBundle): Promise<SettingsImport> {
+    const result = (await safeInvoke('import_settings', { bundle })) as SettingsImport
+    await loadSettings()
+    return result
+  }
+
+  /**
+   * Put part of the configuration back to the defaults
+   */
+  async function resetSettings(scope: SettingsScope = 'all') {
+    await safeInvoke('reset_settings', { scope })
+    await loadSettings()
+  }
+
   // Auto-save when settings change
   async function autoSave() {
     await saveSettings()
@@ -176,12 +234,17 @@ export const useSettingsStore = defineStore('settings', () => {
     manualOfflineMode,
     showDebugInfo,
     theme,
+    schema,
     loading,
     error,
     toggleManualOffline,
     setManualOffline,
     loadSettings,
     saveSettings,
+    loadSchema,
+    exportSettings,
+    importSettings,
+    resetSettings,
     autoSave,
   }
 })
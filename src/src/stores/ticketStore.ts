@@ -3,6 +3,7 @@
 
 import { defineStore } from 'pinia'
 import { invoke } from '@tauri-apps/api/core'
+import { errorMessage } from '@/tauri'
 
 // ============================================================================
 // Types
@@ -14,6 +15,7 @@ export type Priority = 'low' | 'medium' | 'high' | 'critical'
 
 export interface Ticket {
   id: string
+  key?: string // Sequential key, e.g. "MOD-123"
   source: TicketSource
   source_id?: string
 
@@ -39,9 +41,38 @@ export interface Ticket {
   linked_tickets: string[]
 
   comments: Comment[]
+
+  checklist: ChecklistItem[]
+  checklist_progress?: number // 0-100, absent without a checklist
+
+  sprint_id?: string
+  completed_at?: string // Set while the ticket is done
+
+  archived: boolean // Left out of ticket queries unless asked for
+  archived_at?: string
+
+  watched: boolean // Status changes and comments are notified
+
+  relations: TicketRelation[]
+
   metadata: Record<string, any>
 }
 
+export type TicketRelationKind = 'blocks' | 'relates_to' | 'duplicates'
+
+export interface TicketRelation {
+  link_id: string
+  kind: TicketRelationKind
+  outgoing: boolean // This ticket blocks / duplicates the other one
+  ticket_id: string
+}
+
+export interface ChecklistItem {
+  id: string
+  text: string
+  done: boolean
+}
+
 export interface Comment {
   id: string
   author: string
@@ -79,7 +110,171 @@ export interface TicketFilters {
   status?: string
   priority?: Priority
   assignee?: string
-  tags?: string[]
+  sprint_id?: string // Empty matches tickets outside any sprint
+  board_id?: string // "default" also matches tickets without a board
+  archived?: 'active' | 'archived' | 'all' // Defaults to active
+  watched?: boolean
+  tags?: string[] // Tickets carrying all of these labels
+}
+
+export type TicketSortField = 'created_at' | 'updated_at' | 'due_date' | 'title' | 'status' | 'priority'
+
+// Server-side ticket query (filters plus search, date range, sort and paging)
+export interface TicketQuery extends TicketFilters {
+  search?: string
+  date_range?: {
+    field?: 'created_at' | 'updated_at' | 'due_date'
+    from?: string
+    to?: string
+  }
+  sort?: { field: TicketSortField; descending?: boolean }[]
+  limit?: number
+  offset?: number
+}
+
+export interface TicketPage {
+  total: number
+  tickets: Ticket[]
+}
+
+export interface Attachment {
+  id: string
+  owner_type: string
+  owner_id: string
+  comment_id?: string // Set for files attached to a comment
+  file_name: string
+  content_type: string
+  size_bytes: number
+  sha256: string
+  created_at: string
+  path: string
+}
+
+// Payload of the `tickets://watched` event
+export type WatchedTicketChange = {
+  ticket_id: string
+  key?: string
+  title: string
+} & (
+  | { kind: 'status'; from: string; to: string }
+  | { kind: 'comment'; author: string; text: string }
+)
+
+export interface DueTicket {
+  ticket: Ticket
+  due_at: string
+  overdue: boolean
+}
+
+// Local people list for assignees and the name comments are written as
+export interface TicketPeople {
+  people: string[]
+  current_user?: string
+}
+
+export interface Sprint {
+  id: string
+  name: string
+  start_date: string // YYYY-MM-DD
+  end_date: string
+  goal?: string
+  closed_at?: string
+  carried_over: string[] // Unfinished ticket IDs moved out on close
+  created_at: string
+}
+
+export interface CreateSprintData {
+  name: string
+  start_date: string
+  end_date: string
+  goal?: string
+}
+
+export interface BurndownPoint {
+  date: string
+  remaining_points: number
+  remaining_tickets: number
+  ideal_points: number
+}
+
+export interface SprintSummary {
+  sprint: Sprint
+  total_tickets: number
+  total_points: number
+  completed_points: number
+  by_status: Record<string, number>
+  carried_over: number
+  burndown: BurndownPoint[]
+}
+
+export interface TimeEntry {
+  id: string
+  ticket_id?: string
+  project: string
+  description: string
+  start_time: string
+  end_time?: string
+  duration_seconds?: number
+  billable: boolean
+  notes?: string
+}
+
+export interface CreateTimeEntryData {
+  start_time: string
+  end_time?: string
+  duration_minutes?: number
+  description?: string
+  billable?: boolean
+  notes?: string
+}
+
+export interface TimeSummary {
+  total_seconds: number
+  by_ticket: { ticket_id: string; total_seconds: number; entries: number }[]
+  by_day: { date: string; total_seconds: number; by_ticket: Record<string, number> }[]
+  running?: TimeEntry
+}
+
+export interface BoardExport {
+  version: string
+  exported_at: string
+  board: BoardConfig
+  tickets: Ticket[]
+  relations: { from: string; to: string; kind: TicketRelationKind }[]
+  time_entries: TimeEntry[]
+}
+
+export interface BoardImport {
+  board_id: string
+  tickets: number
+  relations: number
+  time_entries: number
+}
+
+export type TicketImportFormat = 'trello' | 'jira_csv' | 'csv'
+
+export interface TicketImportMapping {
+  // CSV column -> ticket field (key, title, description, status, type, priority, ...)
+  columns?: Record<string, string>
+  // Source status -> board column id
+  statuses?: Record<string, string>
+  board_id?: string
+  delimiter?: string
+  tag_separator?: string
+}
+
+export interface TicketImportReport {
+  rows_read: number
+  imported: number
+  duplicates: number
+  skipped: number
+  failed: number
+  errors: { row: number; message: string }[]
+}
+
+export interface LabelCount {
+  tag: string
+  count: number
 }
 
 export interface KanbanColumn {
@@ -89,6 +284,97 @@ export interface KanbanColumn {
   color: string
   is_start?: boolean
   is_done?: boolean
+  wip_limit?: number
+  wip_strict?: boolean // Refuse moves past the limit instead of warning
+}
+
+export interface WipLimitExceeded {
+  column_id: string
+  limit: number
+  count: number // Tickets in the column if the move goes ahead
+  blocked: boolean
+}
+
+export interface TicketMove {
+  ticket: Ticket
+  moved: boolean
+  wip_exceeded?: WipLimitExceeded
+}
+
+export interface BoardState {
+  board_id: string
+  columns: (KanbanColumn & { count: number; over_limit: boolean })[]
+  unmapped: number // Tickets whose status matches no column
+}
+
+export type SwimlaneGrouping = 'label' | 'assignee' | 'priority'
+
+export interface Swimlane {
+  key?: string // Absent for tickets without a label/assignee
+  count: number
+  column_counts: Record<string, number>
+  tickets: Ticket[]
+}
+
+export interface BoardGrouped {
+  board_id: string
+  group_by: SwimlaneGrouping
+  columns: KanbanColumn[]
+  lanes: Swimlane[]
+}
+
+// Status change of a ticket (from_status is null when it was created)
+export interface TicketStatusEvent {
+  ticket_id: string
+  from_status: string | null
+  to_status: string
+  at: string
+}
+
+// Dates (YYYY-MM-DD, inclusive) the board metrics cover; default last 12 weeks
+export interface MetricsRange {
+  from?: string
+  to?: string
+}
+
+export interface DurationStats {
+  count: number
+  average_days: number | null
+  median_days: number | null
+  p85_days: number | null
+}
+
+export interface BoardMetrics {
+  board_id: string
+  from: string
+  to: string
+  lead_time: DurationStats
+  cycle_time: DurationStats
+  completed: Array<{
+    ticket_id: string
+    key: string | null
+    title: string
+    completed_at: string
+    lead_time_days: number
+    cycle_time_days: number | null
+  }>
+  throughput: Array<{ week_start: string; completed: number }>
+  aging_wip: Array<{
+    ticket_id: string
+    key: string | null
+    title: string
+    status: string
+    started_at: string
+    age_days: number
+  }>
+}
+
+// Stored column configuration of one board
+export interface BoardConfig {
+  board_id: string
+  columns: KanbanColumn[]
+  key_prefix: string // Prefix of new ticket keys, e.g. "MOD"
+  updated_at?: string // Absent while the board uses the default columns
 }
 
 // ============================================================================
@@ -98,7 +384,9 @@ export interface KanbanColumn {
 interface TicketState {
   tickets: Map<string, Ticket>
   columns: KanbanColumn[]
+  boardColumns: Record<string, KanbanColumn[]> // Loaded board configurations
   filters: TicketFilters
+  people: TicketPeople
   selectedTicket: Ticket | null
   loading: boolean
   error: string | null
@@ -114,13 +402,20 @@ export const useTicketStore = defineStore('tickets', {
       { id: 'review', name: 'Review', order: 3, color: '#6f42c1' },
       { id: 'done', name: 'Done', order: 4, color: '#28a745', is_done: true },
     ],
+    boardColumns: {},
     filters: {},
+    people: { people: [] },
     selectedTicket: null,
     loading: false,
     error: null,
   }),
 
   getters: {
+    // Columns of a board (the default columns until its configuration is loaded)
+    columnsFor(): (boardId?: string) => KanbanColumn[] {
+      return boardId => (boardId && this.boardColumns[boardId]) || this.columns
+    },
+
     // Get all tickets as array
     allTickets(): Ticket[] {
       return Array.from(this.tickets.values())
@@ -201,9 +496,9 @@ export const useTicketStore = defineStore('tickets', {
       return counts
     },
 
-    // Get all unique assignees
+    // Get all unique assignees (configured people plus those on tickets)
     allAssignees(): string[] {
-      const assignees = new Set<string>()
+      const assignees = new Set<string>(this.people.people)
       this.tickets.forEach(ticket => {
         if (ticket.assignee) {
           assignees.add(ticket.assignee)
@@ -238,7 +533,7 @@ export const useTicketStore = defineStore('tickets', {
         console.log('✅ Ticket created:', ticket)
         return ticket
       } catch (err) {
-        const error = err instanceof Error ? err.message : String(err)
+        const error = errorMessage(err)
         console.error('❌ Failed to create ticket:', error)
         this.error = error
         throw err
@@ -267,7 +562,7 @@ export const useTicketStore = defineStore('tickets', {
 
         console.log(`✅ Loaded ${tickets.length} tickets`)
       } catch (err) {
-        const error = err instanceof Error ? err.message : String(err)
+        const error = errorMessage(err)
         console.error('❌ Failed to load tickets:', error)
         this.error = error
         throw err
@@ -276,6 +571,11 @@ export const useTicketStore = defineStore('tickets', {
       }
     },
 
+    // Query one page of tickets without replacing the loaded board
+    async queryTickets(query: TicketQuery): Promise<TicketPage> {
+      return invoke<TicketPage>('query_tickets', { query })
+    },
+
     // ========================================================================
     // Update
     // ========================================================================
@@ -297,7 +597,7 @@ export const useTicketStore = defineStore('tickets', {
         console.log('✅ Ticket updated:', ticket)
         return ticket
       } catch (err) {
-        const error = err instanceof Error ? err.message : String(err)
+        const error = errorMessage(err)
         console.error('❌ Failed to update ticket:', error)
         this.error = error
         throw err
@@ -326,7 +626,7 @@ export const useTicketStore = defineStore('tickets', {
 
         console.log('✅ Ticket deleted:', id)
       } catch (err) {
-        const error = err instanceof Error ? err.message : String(err)
+        const error = errorMessage(err)
         console.error('❌ Failed to delete ticket:', error)
         this.error = error
         throw err
@@ -339,16 +639,24 @@ export const useTicketStore = defineStore('tickets', {
     // Move (Change Status)
     // ========================================================================
 
-    async moveTicket(id: string, newStatus: string): Promise<Ticket> {
+    // Throws when a strict WIP limit refuses the move; check wip_exceeded for warnings
+    async moveTicket(id: string, newStatus: string): Promise<TicketMove> {
       console.log('🔄 Moving ticket:', id, '→', newStatus)
       this.loading = true
       this.error = null
 
       try {
-        const ticket = await invoke<Ticket>('move_ticket', {
+        const result = await invoke<TicketMove>('move_ticket', {
           id,
           newStatus,
         })
+        const exceeded = result.wip_exceeded
+        if (!result.moved && exceeded) {
+          throw new Error(
+            `WIP limit reached in ${exceeded.column_id} (${exceeded.count - 1}/${exceeded.limit})`
+          )
+        }
+        const ticket = result.ticket
         this.tickets.set(ticket.id, ticket)
 
         // Update selected ticket if it's the one being moved
@@ -357,9 +665,9 @@ export const useTicketStore = defineStore('tickets', {
         }
 
         console.log('✅ Ticket moved:', ticket)
-        return ticket
+        return result
       } catch (err) {
-        const error = err instanceof Error ? err.message : String(err)
+        const error = errorMessage(err)
         console.error('❌ Failed to move ticket:', error)
         this.error = error
         throw err
@@ -372,7 +680,8 @@ export const useTicketStore = defineStore('tickets', {
     // Comments
     // ========================================================================
 
-    async addComment(ticketId: string, author: string, text: string): Promise<Comment> {
+    // The author is the configured current user
+    async addComment(ticketId: string, text: string): Promise<Comment> {
       console.log('💬 Adding comment to ticket:', ticketId)
       this.loading = true
       this.error = null
@@ -380,7 +689,7 @@ export const useTicketStore = defineStore('tickets', {
       try {
         const comment = await invoke<Comment>('add_comment', {
           ticketId,
-          comment: { author, text },
+          text,
         })
 
         // Update local ticket with new comment
@@ -398,7 +707,7 @@ export const useTicketStore = defineStore('tickets', {
         console.log('✅ Comment added:', comment)
         return comment
       } catch (err) {
-        const error = err instanceof Error ? err.message : String(err)
+        const error = errorMessage(err)
         console.error('❌ Failed to add comment:', error)
         this.error = error
         throw err
@@ -407,6 +716,372 @@ export const useTicketStore = defineStore('tickets', {
       }
     },
 
+    // Payload is the export file's content; known external keys are skipped
+    async importTickets(
+      format: TicketImportFormat,
+      payload: string,
+      mapping?: TicketImportMapping
+    ): Promise<TicketImportReport> {
+      const report = await invoke<TicketImportReport>('import_tickets', {
+        format,
+        payload,
+        mapping,
+      })
+      if (report.imported > 0) {
+        await this.loadTickets(this.filters)
+      }
+      return report
+    },
+
+    async watchTicket(id: string, watched = true): Promise<Ticket> {
+      const ticket = await invoke<Ticket>('watch_ticket', { id, watched })
+      const loaded = this.tickets.get(id)
+      if (loaded) {
+        loaded.watched = ticket.watched
+      }
+      return ticket
+    },
+
+    async loadWatchedTickets(): Promise<Ticket[]> {
+      return invoke<Ticket[]>('get_watched_tickets')
+    },
+
+    // Archived tickets drop out of the loaded list; restoring reloads it
+    async archiveTicket(id: string, archived = true): Promise<Ticket> {
+      const ticket = await invoke<Ticket>('archive_ticket', { id, archived })
+      if (archived) {
+        this.tickets.delete(id)
+      } else {
+        await this.loadTickets(this.filters)
+      }
+      return ticket
+    },
+
+    async archiveDoneOlderThan(days: number): Promise<number> {
+      const count = await invoke<number>('archive_done_older_than', { days })
+      if (count > 0) {
+        await this.loadTickets(this.filters)
+      }
+      return count
+    },
+
+    async loadArchivedTickets(): Promise<Ticket[]> {
+      return invoke<Ticket[]>('get_archived_tickets')
+    },
+
+    // ========================================================================
+    // Relations
+    // ========================================================================
+
+    // "blocks" relations that would form a cycle are refused
+    async addRelation(from: string, to: string, kind: TicketRelationKind): Promise<string> {
+      const linkId = await invoke<string>('add_ticket_relation', { from, to, kind })
+      await this.loadTickets(this.filters)
+      return linkId
+    },
+
+    async removeRelation(linkId: string): Promise<void> {
+      await invoke('remove_ticket_relation', { linkId })
+      this.tickets.forEach(ticket => {
+        ticket.relations = ticket.relations.filter(r => r.link_id !== linkId)
+      })
+    },
+
+    // ========================================================================
+    // Attachments
+    // ========================================================================
+
+    // Files of a ticket, including those attached to its comments
+    async loadAttachments(ticketId: string): Promise<Attachment[]> {
+      return invoke<Attachment[]>('list_attachments', {
+        ownerType: 'tickets',
+        ownerId: ticketId,
+      })
+    },
+
+    // Copy a file into the workspace and attach it to the ticket or one of its comments
+    async addAttachment(ticketId: string, path: string, commentId?: string): Promise<Attachment> {
+      return invoke<Attachment>('add_attachment', {
+        ownerType: 'tickets',
+        ownerId: ticketId,
+        path,
+        commentId,
+      })
+    },
+
+    async removeAttachment(id: string): Promise<void> {
+      await invoke('delete_attachment', { id })
+    },
+
+    // ========================================================================
+    // People
+    // ========================================================================
+
+    async loadPeople(): Promise<TicketPeople> {
+      this.people = await invoke<TicketPeople>('get_ticket_people')
+      return this.people
+    },
+
+    // Names are trimmed and de-duplicated; the current user joins the list
+    async savePeople(people: TicketPeople): Promise<TicketPeople> {
+      this.people = await invoke<TicketPeople>('save_ticket_people', { people })
+      return this.people
+    },
+
+    // ========================================================================
+    // Board Columns
+    // ========================================================================
+
+    // Columns with their ticket counts against WIP limits
+    async loadBoardState(boardId?: string): Promise<BoardState> {
+      return invoke<BoardState>('get_board_state', { boardId })
+    },
+
+    // Grouped server-side; a ticket with several labels is in each label's lane
+    async loadBoardGrouped(groupBy: SwimlaneGrouping, boardId?: string): Promise<BoardGrouped> {
+      return invoke<BoardGrouped>('get_board_grouped', { boardId, groupBy })
+    },
+
+    async loadBoardMetrics(range?: MetricsRange, boardId?: string): Promise<BoardMetrics> {
+      return invoke<BoardMetrics>('get_board_metrics', { boardId, range })
+    },
+
+    async loadTicketHistory(id: string): Promise<TicketStatusEvent[]> {
+      return invoke<TicketStatusEvent[]>('get_ticket_history', { id })
+    },
+
+    async loadBoardColumns(boardId?: string): Promise<KanbanColumn[]> {
+      return this.applyBoardConfig(invoke<BoardConfig>('get_board_config', { boardId }))
+    },
+
+    async saveBoardColumns(boardId: string | undefined, columns: KanbanColumn[]) {
+      return this.applyBoardConfig(invoke<BoardConfig>('save_board_columns', { boardId, columns }))
+    },
+
+    async addBoardColumn(boardId: string | undefined, column: KanbanColumn, position?: number) {
+      return this.applyBoardConfig(
+        invoke<BoardConfig>('add_board_column', { boardId, column, position })
+      )
+    },
+
+    async updateBoardColumn(
+      boardId: string | undefined,
+      columnId: string,
+      updates: Partial<Omit<KanbanColumn, 'id' | 'order'>>
+    ) {
+      return this.applyBoardConfig(
+        invoke<BoardConfig>('update_board_column', { boardId, columnId, updates })
+      )
+    },
+
+    async reorderBoardColumns(boardId: string | undefined, columnIds: string[]) {
+      return this.applyBoardConfig(
+        invoke<BoardConfig>('reorder_board_columns', { boardId, columnIds })
+      )
+    },
+
+    // A column that still has tickets needs a column to move them to
+    async deleteBoardColumn(boardId: string | undefined, columnId: string, moveTicketsTo?: string) {
+      const columns = await this.applyBoardConfig(
+        invoke<BoardConfig>('delete_board_column', { boardId, columnId, moveTicketsTo })
+      )
+      if (moveTicketsTo) {
+        await this.loadTickets()
+      }
+      return columns
+    },
+
+    async resetBoardColumns(boardId?: string): Promise<KanbanColumn[]> {
+      return this.applyBoardConfig(invoke<BoardConfig>('reset_board_config', { boardId }))
+    },
+
+    // New tickets on the board get keys with this prefix; existing keys stay
+    async setBoardKeyPrefix(prefix: string, boardId?: string): Promise<BoardConfig> {
+      return invoke<BoardConfig>('set_board_key_prefix', { boardId, prefix })
+    },
+
+    // Board document (columns, tickets, relations, time entries) for sharing
+    async exportBoard(boardId?: string): Promise<BoardExport> {
+      return invoke<BoardExport>('export_board', { boardId })
+    },
+
+    // Target board must be empty; defaults to the exported board's ID
+    async importBoard(document: BoardExport, boardId?: string): Promise<BoardImport> {
+      const result = await invoke<BoardImport>('import_board', { document, boardId })
+      await this.loadBoardColumns(result.board_id)
+      await this.loadTickets(this.filters)
+      return result
+    },
+
+    async applyBoardConfig(request: Promise<BoardConfig>): Promise<KanbanColumn[]> {
+      try {
+        const config = await request
+        this.boardColumns[config.board_id] = config.columns
+        return config.columns
+      } catch (err) {
+        const error = errorMessage(err)
+        console.error('❌ Failed to update board columns:', error)
+        this.error = error
+        throw err
+      }
+    },
+
+    // ========================================================================
+    // Sprints
+    // ========================================================================
+
+    async loadSprints(): Promise<Sprint[]> {
+      return invoke<Sprint[]>('get_sprints')
+    },
+
+    async createSprint(sprint: CreateSprintData): Promise<Sprint> {
+      return invoke<Sprint>('create_sprint', { sprint })
+    },
+
+    async updateSprint(id: string, updates: Partial<CreateSprintData>): Promise<Sprint> {
+      return invoke<Sprint>('update_sprint', { id, updates })
+    },
+
+    // Its tickets go back to the backlog
+    async deleteSprint(id: string): Promise<void> {
+      await invoke('delete_sprint', { id })
+      this.tickets.forEach(ticket => {
+        if (ticket.sprint_id === id) {
+          ticket.sprint_id = undefined
+        }
+      })
+    },
+
+    // Without a sprint ID the ticket goes back to the backlog
+    async setTicketSprint(ticketId: string, sprintId?: string): Promise<Ticket> {
+      const ticket = await invoke<Ticket>('set_ticket_sprint', { ticketId, sprintId })
+      this.tickets.set(ticket.id, ticket)
+      if (this.selectedTicket?.id === ticket.id) {
+        this.selectedTicket = ticket
+      }
+      return ticket
+    },
+
+    async loadSprintSummary(id: string): Promise<SprintSummary> {
+      return invoke<SprintSummary>('get_sprint_summary', { id })
+    },
+
+    // Unfinished tickets move to carryOverTo, or the backlog without it
+    async closeSprint(
+      id: string,
+      carryOverTo?: string
+    ): Promise<{ sprint: Sprint; carried_over: number }> {
+      const result = await invoke<{ sprint: Sprint; carried_over: number }>('close_sprint', {
+        id,
+        carryOverTo,
+      })
+      if (result.carried_over > 0) {
+        await this.loadTickets()
+      }
+      return result
+    },
+
+    // ========================================================================
+    // Time Tracking
+    // ========================================================================
+
+    // Fails while another timer runs (also one started from the time tracker)
+    async startTimer(ticketId: string, description?: string): Promise<TimeEntry> {
+      return invoke<TimeEntry>('start_ticket_timer', { ticketId, description })
+    },
+
+    // Stopping or logging time updates the ticket's time_spent, so reload
+    async stopTimer(ticketId: string): Promise<TimeEntry> {
+      const entry = await invoke<TimeEntry>('stop_ticket_timer', { ticketId })
+      await this.loadTickets(this.filters)
+      return entry
+    },
+
+    async addTimeEntry(ticketId: string, entry: CreateTimeEntryData): Promise<TimeEntry> {
+      const created = await invoke<TimeEntry>('add_time_entry', { ticketId, entry })
+      await this.loadTickets(this.filters)
+      return created
+    },
+
+    async deleteTimeEntry(id: string): Promise<void> {
+      await invoke('delete_time_entry', { id })
+      await this.loadTickets(this.filters)
+    },
+
+    async loadTimeEntries(ticketId: string): Promise<TimeEntry[]> {
+      return invoke<TimeEntry[]>('get_ticket_time_entries', { ticketId })
+    },
+
+    async loadRunningTimer(): Promise<TimeEntry | null> {
+      return invoke<TimeEntry | null>('get_running_timer')
+    },
+
+    // Dates are YYYY-MM-DD, both inclusive
+    async loadTimeSummary(from?: string, to?: string, ticketId?: string): Promise<TimeSummary> {
+      return invoke<TimeSummary>('get_time_summary', { from, to, ticketId })
+    },
+
+    // ========================================================================
+    // Due Dates
+    // ========================================================================
+
+    // Open tickets overdue or due within the window (default 24h), soonest first
+    async loadDueTickets(windowHours?: number): Promise<DueTicket[]> {
+      return invoke<DueTicket[]>('get_due_tickets', { windowHours })
+    },
+
+    // ========================================================================
+    // Labels
+    // ========================================================================
+
+    async loadLabels(): Promise<LabelCount[]> {
+      return invoke<LabelCount[]>('list_ticket_labels')
+    },
+
+    // Rename a label on all tickets, then reload them to pick up the change
+    async renameLabel(from: string, to: string): Promise<number> {
+      const updated = await invoke<number>('rename_ticket_label', { from, to })
+      if (updated > 0) {
+        await this.loadTickets()
+      }
+      return updated
+    },
+
+    // ========================================================================
+    // Checklist
+    // ========================================================================
+
+    async addChecklistItem(ticketId: string, text: string): Promise<Ticket> {
+      return this.updateChecklist('add_checklist_item', { ticketId, text })
+    },
+
+    async toggleChecklistItem(ticketId: string, itemId: string): Promise<Ticket> {
+      return this.updateChecklist('toggle_checklist_item', { ticketId, itemId })
+    },
+
+    async removeChecklistItem(ticketId: string, itemId: string): Promise<Ticket> {
+      return this.updateChecklist('remove_checklist_item', { ticketId, itemId })
+    },
+
+    async updateChecklist(command: string, args: Record<string, string>): Promise<Ticket> {
+      this.error = null
+
+      try {
+        const ticket = await invoke<Ticket>(command, args)
+        this.tickets.set(ticket.id, ticket)
+
+        if (this.selectedTicket?.id === ticket.id) {
+          this.selectedTicket = ticket
+        }
+        return ticket
+      } catch (err) {
+        const error = errorMessage(err)
+        console.error('❌ Failed to update checklist:', error)
+        this.error = error
+        throw err
+      }
+    },
+
     // ========================================================================
     // UI State Management
     // ========================================================================
@@ -28,3 +28,59 @@ export const checkTauriContext = () => {
   console.log('=========================')
   return isTauri()
 }
+
+// Error codes of failed commands (see `ErrorCode` in src-tauri/src/error.rs)
+export type ErrorCode =
+  | 'io'
+  | 'serialization'
+  | 'config'
+  | 'database'
+  | 'adapter'
+  | 'network'
+  | 'timeout'
+  | 'offline'
+  | 'unauthorized'
+  | 'plugin'
+  | 'validation'
+  | 'not_found'
+  | 'internal'
+
+// What a command rejects with
+export interface ApiError {
+  code: ErrorCode
+  message: string
+  details?: Record<string, unknown>
+}
+
+export const isApiError = (error: unknown): error is ApiError => {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as ApiError).code === 'string' &&
+    typeof (error as ApiError).message === 'string'
+  )
+}
+
+// Message of anything a command (or other code) threw
+export const errorMessage = (error: unknown): string => {
+  if (error instanceof Error || isApiError(error)) {
+    return error.message
+  }
+  return String(error)
+}
+
+// Whether this is a dashboard's own window (see `open_dashboard_window`),
+// which shows just the dashboard without navigation or background jobs
+export const isDashboardWindow = (): boolean => {
+  const query = window.location.hash.split('?')[1] ?? ''
+  return new URLSearchParams(query).get('window') === 'dashboard'
+}
+
+export type ShareFormat = 'markdown' | 'text' | 'json'
+
+// Copy a record, ticket or rendered prompt to the clipboard, formatted by
+// `format_record_for_share`
+export const copyForShare = async (id: string, format: ShareFormat = 'markdown') => {
+  const text = await invoke<string>('format_record_for_share', { id, format })
+  await navigator.clipboard.writeText(text)
+}
@@ -0,0 +1,21 @@
+// Diagnostics types (log entries and crash reports from the backend)
+
+export interface LogEntry {
+  timestamp: string
+  level: string
+  target: string
+  message: string
+  fields?: Record<string, unknown>
+}
+
+export interface CrashReport {
+  crashed_at: string
+  version: string
+  os: string
+  thread: string
+  message: string
+  location?: string
+  backtrace: string
+  // Oldest first
+  recent_logs: LogEntry[]
+}
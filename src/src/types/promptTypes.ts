@@ -67,6 +67,12 @@ export interface PromptSection {
   // Example renderings for documentation
   examples?: PromptExample[]
 
+  // Locale for plural categories and list conjunctions (e.g. "de", "fr-CA")
+  locale?: string
+
+  // Negative prompt, rendered alongside content (text2image workflows)
+  negative_content?: PromptContent
+
   created_at: string
   updated_at: string
 }
@@ -157,6 +163,8 @@ export type PromptContent =
   | ContextContent
   | PluralContent
   | ArticleContent
+  | PossessiveContent
+  | VerbContent
   | CountSwitchContent
   | SwitchContent
   // Random selection types
@@ -235,6 +243,25 @@ export interface ArticleContent {
   capitalize?: boolean // Whether to capitalize (A vs a)
 }
 
+/**
+ * Possessive form of a word or name (Alice → Alice's, James → James')
+ */
+export interface PossessiveContent {
+  type: 'possessive'
+  word_variable?: string // Variable containing the word
+  word_content?: PromptContent // Or content that produces the word
+}
+
+/**
+ * Verb agreeing with a subject count (run → runs for a single subject)
+ */
+export interface VerbContent {
+  type: 'verb'
+  verb: string // Base form: "run", "be", "look up"
+  count_variable: string // Subject count (or array to count)
+  singular?: string // Explicit singular form when the rules get it wrong
+}
+
 /**
  * Switch based on count (for more complex count-based logic)
  */
@@ -342,6 +369,26 @@ export interface VariableDefinition {
 
   // For object types
   properties?: Record<string, VariableDefinition>
+
+  // Fill from the staging database at render time
+  record_query?: RecordBinding
+}
+
+/**
+ * Record query a variable is bound to (filters as in record queries)
+ */
+export interface RecordBinding {
+  types?: string[]
+  sources?: string[]
+  tags?: string[]
+  status?: string[]
+  sort?: { field: string; descending?: boolean }[]
+  limit?: number
+  // Path into the record, e.g. "data.status" (default: the record's data)
+  field?: string
+  // 'latest' (default) reads one record, 'list' an array of all matches
+  mode?: 'latest' | 'list'
+  within_hours?: number
 }
 
 export type VariableType = 'string' | 'number' | 'boolean' | 'array' | 'object' | 'enum' | 'custom' // References a PromptDataType
@@ -527,6 +574,7 @@ export interface RenderResult {
 
 export interface RenderedPrompt {
   text: string
+  negative_prompt?: string
   variable_values: Record<string, any>
   warnings?: string[]
 }